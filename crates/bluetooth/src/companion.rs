@@ -0,0 +1,605 @@
+//! Companion app GATT server — serves [`crate::gatt`] from the player's
+//! state.
+//!
+//! [`CompanionServer`] holds the last known track, transport status and
+//! battery level, answers reads and writes from the phone, and turns state
+//! changes into notifications for the characteristics the phone subscribed
+//! to.  It does no I/O: the BLE task feeds it ATT requests and sends
+//! whatever [`CompanionServer::poll_notification`] returns.
+//!
+//! # Keeping in sync with playback
+//!
+//! The playback crate is a separate vertical slice, so the server takes
+//! its own [`PlayerEvent`] rather than `playback::events::PlaybackEvent`;
+//! the firmware maps one onto the other as it drains its bus subscriber.
+//! Track metadata comes separately through
+//! [`set_metadata`](CompanionServer::set_metadata) once the library lookup
+//! finishes, and is ignored if the track has changed since.
+//!
+//! The bus drops the oldest events when a subscriber falls behind.  After
+//! lag the firmware calls [`resync`](CompanionServer::resync) with a
+//! [`PlayerSnapshot`] of the engine, which replaces whatever the missed
+//! events would have changed and re-notifies it.
+//!
+//! # Notification pacing
+//!
+//! Position ticks arrive several times a second; the phone interpolates
+//! between updates, so [`Status`](Characteristic::Status) is only
+//! re-notified for position once it has moved by
+//! [`POSITION_NOTIFY_INTERVAL_MS`].  State, track, EQ and volume changes
+//! are notified at once.  Several changes between two polls coalesce into
+//! one notification per characteristic.
+//!
+//! Writes never change the server's state directly.  A
+//! [`TransportCommand`] goes to the playback task, and the events it causes
+//! come back through [`apply`](CompanionServer::apply), so the phone only
+//! ever sees what the engine actually did.
+
+use heapless::String;
+
+use crate::gatt::{
+    self, Characteristic, GattError, PlayerStatus, QueuePage, QueuePageRequest, TrackInfo,
+    TransportCommand, TransportState, Value, DEFAULT_ATT_MTU, MAX_ATT_MTU, MAX_TEXT_LEN,
+    MAX_VALUE_LEN, PROTOCOL_VERSION,
+};
+use crate::transfer::{TransferMessage, TransferResponse};
+
+/// Smallest position change that triggers a
+/// [`Status`](Characteristic::Status) notification on its own.
+pub const POSITION_NOTIFY_INTERVAL_MS: u32 = 1_000;
+
+/// Order in which pending notifications are sent: status first, as it is
+/// small and the phone's transport buttons depend on it.
+const NOTIFY_ORDER: [Characteristic; 5] = [
+    Characteristic::Status,
+    Characteristic::Track,
+    Characteristic::Queue,
+    Characteristic::Transfer,
+    Characteristic::BatteryLevel,
+];
+
+/// Playback events the server follows — the subset of the firmware's
+/// playback bus the phone can see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlayerEvent {
+    /// A new track began decoding.
+    TrackStarted {
+        /// Library track ID.
+        track_id: u32,
+        /// Duration in milliseconds (0 when unknown).
+        duration_ms: u32,
+    },
+    /// The engine changed between stopped, playing and paused.
+    StateChanged(TransportState),
+    /// Periodic position update while playing.
+    PositionTick {
+        /// Position within the current track, in milliseconds.
+        position_ms: u32,
+    },
+    /// The active EQ preset changed.
+    EqChanged {
+        /// Preset index (0 = flat).
+        preset: u8,
+    },
+}
+
+/// The engine's current state, for [`CompanionServer::resync`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerSnapshot {
+    /// Current library track ID, `None` when nothing is loaded.
+    pub track_id: Option<u32>,
+    /// Duration of the current track in milliseconds.
+    pub duration_ms: u32,
+    /// Transport state.
+    pub state: TransportState,
+    /// Position in the current track, in milliseconds.
+    pub position_ms: u32,
+    /// Active EQ preset.
+    pub eq_preset: u8,
+}
+
+/// A decoded write the firmware must act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Request {
+    /// Forward to the playback task.
+    Transport(TransportCommand),
+    /// Answer with [`CompanionServer::answer_queue`].
+    QueuePage(QueuePageRequest),
+    /// A well-formed transfer message: pass the written value to the
+    /// [`TransferReceiver`](crate::transfer::TransferReceiver) and its
+    /// response to [`CompanionServer::notify_transfer`].
+    Transfer,
+}
+
+/// A notification to send on the current connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    /// Characteristic whose value changed.
+    pub characteristic: Characteristic,
+    /// New value, no longer than the connection's notification payload.
+    pub value: Value,
+}
+
+/// GATT server state for the companion service.  See the module docs.
+#[derive(Debug, Clone)]
+pub struct CompanionServer {
+    /// ATT MTU of the current connection, `None` while disconnected.
+    mtu: Option<u16>,
+    /// [`Characteristic::mask`] bits with notifications enabled.
+    subscribed: u8,
+    /// [`Characteristic::mask`] bits changed since they were last notified.
+    dirty: u8,
+    /// `None` while nothing is loaded.
+    track: Option<TrackInfo>,
+    status: PlayerStatus,
+    /// Position last sent in a status notification.
+    notified_position_ms: u32,
+    battery: u8,
+    /// Answer to the last queue request, until it is notified.
+    queue_page: Option<QueuePage>,
+    /// Last transfer response, until it is notified.
+    transfer_response: Option<TransferResponse>,
+}
+
+impl Default for CompanionServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompanionServer {
+    /// Create a disconnected server with nothing playing.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            mtu: None,
+            subscribed: 0,
+            dirty: 0,
+            track: None,
+            status: PlayerStatus {
+                state: TransportState::Stopped,
+                position_ms: 0,
+                volume: 0,
+                eq_preset: 0,
+            },
+            notified_position_ms: 0,
+            battery: 0,
+            queue_page: None,
+            transfer_response: None,
+        }
+    }
+
+    // ── Connection ─────────────────────────────────────────────────────────
+
+    /// A phone connected.  Subscriptions start disabled, as the player does
+    /// not bond with companion phones and so keeps no CCCD state.
+    pub fn connected(&mut self) {
+        self.mtu = Some(DEFAULT_ATT_MTU);
+        self.subscribed = 0;
+        self.dirty = 0;
+        self.queue_page = None;
+        self.transfer_response = None;
+    }
+
+    /// The phone completed an ATT MTU exchange; the result is clamped to
+    /// [`DEFAULT_ATT_MTU`]..=[`MAX_ATT_MTU`].
+    pub fn set_mtu(&mut self, mtu: u16) {
+        if self.mtu.is_some() {
+            self.mtu = Some(mtu.clamp(DEFAULT_ATT_MTU, MAX_ATT_MTU));
+        }
+    }
+
+    /// The link dropped.  Player state is kept for the next connection.
+    pub fn disconnected(&mut self) {
+        self.mtu = None;
+        self.subscribed = 0;
+        self.dirty = 0;
+        self.queue_page = None;
+    }
+
+    /// Whether a phone is connected.
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.mtu.is_some()
+    }
+
+    /// ATT MTU of the current connection.
+    #[must_use]
+    pub fn mtu(&self) -> Option<u16> {
+        self.mtu
+    }
+
+    /// The phone wrote a characteristic's CCCD.  Enabling notifications
+    /// sends the current value straight away, so the phone needs no read.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::NotifyNotSupported`] for a characteristic without
+    /// notifications.
+    pub fn subscribe(
+        &mut self,
+        characteristic: Characteristic,
+        enabled: bool,
+    ) -> Result<(), GattError> {
+        if !characteristic.can_notify() {
+            return Err(GattError::NotifyNotSupported);
+        }
+        let bit = characteristic.mask();
+        if enabled {
+            self.subscribed |= bit;
+            // Queue and transfer values are answers, not state to catch up on.
+            if !matches!(
+                characteristic,
+                Characteristic::Queue | Characteristic::Transfer
+            ) {
+                self.dirty |= bit;
+            }
+        } else {
+            self.subscribed &= !bit;
+            self.dirty &= !bit;
+        }
+        Ok(())
+    }
+
+    // ── Player state ───────────────────────────────────────────────────────
+
+    /// Follow one playback event.
+    pub fn apply(&mut self, event: PlayerEvent) {
+        match event {
+            PlayerEvent::TrackStarted {
+                track_id,
+                duration_ms,
+            } => self.start_track(track_id, duration_ms),
+            PlayerEvent::StateChanged(state) => {
+                if self.status.state != state {
+                    self.status.state = state;
+                    self.mark_status();
+                }
+            }
+            PlayerEvent::PositionTick { position_ms } => {
+                self.status.position_ms = position_ms;
+                if position_ms.abs_diff(self.notified_position_ms) >= POSITION_NOTIFY_INTERVAL_MS {
+                    self.mark_status();
+                }
+            }
+            PlayerEvent::EqChanged { preset } => {
+                if self.status.eq_preset != preset {
+                    self.status.eq_preset = preset;
+                    self.mark_status();
+                }
+            }
+        }
+    }
+
+    /// Replace the state after the event bus dropped events, and notify
+    /// the track and status again.  The metadata of a changed track has to
+    /// be looked up again with [`set_metadata`](Self::set_metadata).
+    pub fn resync(&mut self, snapshot: PlayerSnapshot) {
+        match snapshot.track_id {
+            Some(id) if self.track_id() != Some(id) => self.start_track(id, snapshot.duration_ms),
+            Some(_) => {}
+            None => self.track = None,
+        }
+        self.status.state = snapshot.state;
+        self.status.position_ms = snapshot.position_ms;
+        self.status.eq_preset = snapshot.eq_preset;
+        self.dirty |= Characteristic::Track.mask();
+        self.mark_status();
+    }
+
+    /// Title and artist of `track_id`, once the library has them.  Ignored
+    /// when another track has started since the lookup began.  Longer text
+    /// is cut to [`MAX_TEXT_LEN`] bytes.
+    pub fn set_metadata(&mut self, track_id: u32, title: &str, artist: &str) {
+        let Some(track) = self.track.as_mut().filter(|t| t.track_id == track_id) else {
+            return;
+        };
+        track.title = text(title);
+        track.artist = text(artist);
+        self.dirty |= Characteristic::Track.mask();
+    }
+
+    /// Output volume, 0–100.
+    pub fn set_volume(&mut self, volume: u8) {
+        let volume = volume.min(100);
+        if self.status.volume != volume {
+            self.status.volume = volume;
+            self.mark_status();
+        }
+    }
+
+    /// Battery charge in percent.
+    pub fn set_battery(&mut self, percent: u8) {
+        let percent = percent.min(100);
+        if self.battery != percent {
+            self.battery = percent;
+            self.dirty |= Characteristic::BatteryLevel.mask();
+        }
+    }
+
+    /// ID of the track the server believes is current.
+    #[must_use]
+    pub fn track_id(&self) -> Option<u32> {
+        self.track.as_ref().map(|t| t.track_id)
+    }
+
+    /// Status as the phone sees it on its next read.
+    #[must_use]
+    pub fn status(&self) -> PlayerStatus {
+        self.status
+    }
+
+    // ── ATT requests ───────────────────────────────────────────────────────
+
+    /// Value of a Read Request.  Reads are never shortened; long values
+    /// are fetched with Read Blob by the BLE stack.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::ReadNotPermitted`] for write-only characteristics.
+    pub fn read(&self, characteristic: Characteristic) -> Result<Value, GattError> {
+        match characteristic {
+            Characteristic::Version => {
+                let mut value = Value::new();
+                value
+                    .push(PROTOCOL_VERSION)
+                    .map_err(|_| GattError::InvalidLength)?;
+                Ok(value)
+            }
+            Characteristic::Track => Ok(self.track_value(MAX_VALUE_LEN)),
+            Characteristic::Status => Ok(self.status.encode()),
+            Characteristic::BatteryLevel => Ok(gatt::encode_battery(self.battery)),
+            Characteristic::Control | Characteristic::Queue | Characteristic::Transfer => {
+                Err(GattError::ReadNotPermitted)
+            }
+        }
+    }
+
+    /// Decode a Write Request.  Nothing is applied: the caller acts on the
+    /// returned [`Request`].
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::WriteNotPermitted`] for read-only characteristics, or
+    /// the decode error of a malformed value.
+    pub fn write(
+        &self,
+        characteristic: Characteristic,
+        value: &[u8],
+    ) -> Result<Request, GattError> {
+        match characteristic {
+            Characteristic::Control => TransportCommand::decode(value).map(Request::Transport),
+            Characteristic::Queue => QueuePageRequest::decode(value).map(Request::QueuePage),
+            Characteristic::Transfer => TransferMessage::decode(value).map(|_| Request::Transfer),
+            _ => Err(GattError::WriteNotPermitted),
+        }
+    }
+
+    /// Answer a [`Request::QueuePage`] with the requested slice of `queue`
+    /// (track IDs in play order, `current` the index playing now).  The
+    /// page is cut to fit one notification and sent on the next poll.
+    pub fn answer_queue(&mut self, queue: &[u32], current: Option<u16>, request: QueuePageRequest) {
+        let max_len = gatt::notify_len(self.mtu.unwrap_or(DEFAULT_ATT_MTU));
+        self.queue_page = Some(QueuePage::from_queue(queue, current, request, max_len));
+        self.dirty |= Characteristic::Queue.mask();
+    }
+
+    /// Queue a transfer response for the phone, sent on the next poll.
+    pub fn notify_transfer(&mut self, response: TransferResponse) {
+        self.transfer_response = Some(response);
+        self.dirty |= Characteristic::Transfer.mask();
+    }
+
+    /// Next notification to send, or `None` when nothing subscribed has
+    /// changed or no phone is connected.
+    pub fn poll_notification(&mut self) -> Option<Notification> {
+        let max_len = gatt::notify_len(self.mtu?);
+        let pending = self.dirty & self.subscribed;
+        let characteristic = NOTIFY_ORDER.into_iter().find(|c| pending & c.mask() != 0)?;
+        self.dirty &= !characteristic.mask();
+        let value = match characteristic {
+            Characteristic::Status => {
+                self.notified_position_ms = self.status.position_ms;
+                self.status.encode()
+            }
+            Characteristic::Track => self.track_value(max_len),
+            Characteristic::Queue => self.queue_page.take()?.encode(),
+            Characteristic::Transfer => self.transfer_response.take()?.encode(),
+            _ => gatt::encode_battery(self.battery),
+        };
+        Some(Notification {
+            characteristic,
+            value,
+        })
+    }
+
+    fn start_track(&mut self, track_id: u32, duration_ms: u32) {
+        self.track = Some(TrackInfo {
+            track_id,
+            duration_ms,
+            ..TrackInfo::default()
+        });
+        self.status.position_ms = 0;
+        self.dirty |= Characteristic::Track.mask();
+        self.mark_status();
+    }
+
+    fn mark_status(&mut self) {
+        self.dirty |= Characteristic::Status.mask();
+    }
+
+    /// Track value, or an all-zero [`TrackInfo`] while nothing is loaded.
+    fn track_value(&self, max_len: usize) -> Value {
+        match &self.track {
+            Some(track) => track.encode(max_len),
+            None => TrackInfo::default().encode(max_len),
+        }
+    }
+}
+
+/// `value` cut to [`MAX_TEXT_LEN`] bytes at a character boundary.
+fn text(value: &str) -> String<MAX_TEXT_LEN> {
+    let mut out = String::new();
+    let _ = out.push_str(gatt::truncate(value, MAX_TEXT_LEN));
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn connected() -> CompanionServer {
+        let mut server = CompanionServer::new();
+        server.connected();
+        for c in [
+            Characteristic::Track,
+            Characteristic::Status,
+            Characteristic::Queue,
+            Characteristic::BatteryLevel,
+        ] {
+            server.subscribe(c, true).unwrap();
+        }
+        while server.poll_notification().is_some() {}
+        server
+    }
+
+    fn drain(server: &mut CompanionServer) -> Vec<Characteristic> {
+        core::iter::from_fn(|| server.poll_notification())
+            .map(|n| n.characteristic)
+            .collect()
+    }
+
+    #[test]
+    fn test_subscribe_sends_current_value() {
+        let mut server = CompanionServer::new();
+        server.connected();
+        server.subscribe(Characteristic::Status, true).unwrap();
+        assert_eq!(drain(&mut server), [Characteristic::Status]);
+        assert_eq!(
+            server.subscribe(Characteristic::Control, true),
+            Err(GattError::NotifyNotSupported)
+        );
+    }
+
+    #[test]
+    fn test_no_notifications_while_disconnected() {
+        let mut server = CompanionServer::new();
+        server.apply(PlayerEvent::StateChanged(TransportState::Playing));
+        assert_eq!(server.poll_notification(), None);
+        assert_eq!(server.status().state, TransportState::Playing);
+    }
+
+    #[test]
+    fn test_changes_coalesce_per_characteristic() {
+        let mut server = connected();
+        server.apply(PlayerEvent::TrackStarted {
+            track_id: 3,
+            duration_ms: 1_000,
+        });
+        server.apply(PlayerEvent::StateChanged(TransportState::Playing));
+        server.set_volume(30);
+        assert_eq!(
+            drain(&mut server),
+            [Characteristic::Status, Characteristic::Track]
+        );
+    }
+
+    #[test]
+    fn test_position_ticks_are_rate_limited() {
+        let mut server = connected();
+        server.apply(PlayerEvent::PositionTick { position_ms: 250 });
+        server.apply(PlayerEvent::PositionTick { position_ms: 500 });
+        assert!(drain(&mut server).is_empty());
+        server.apply(PlayerEvent::PositionTick { position_ms: 1_000 });
+        assert_eq!(drain(&mut server), [Characteristic::Status]);
+        // A backwards seek counts as movement too.
+        server.apply(PlayerEvent::PositionTick { position_ms: 0 });
+        assert_eq!(drain(&mut server), [Characteristic::Status]);
+    }
+
+    #[test]
+    fn test_stale_metadata_is_ignored() {
+        let mut server = connected();
+        server.apply(PlayerEvent::TrackStarted {
+            track_id: 1,
+            duration_ms: 0,
+        });
+        server.apply(PlayerEvent::TrackStarted {
+            track_id: 2,
+            duration_ms: 0,
+        });
+        drain(&mut server);
+        server.set_metadata(1, "Old", "Artist");
+        assert!(drain(&mut server).is_empty());
+        server.set_metadata(2, "New", "Artist");
+        let track = TrackInfo::decode(&server.read(Characteristic::Track).unwrap()).unwrap();
+        assert_eq!(track.title, "New");
+    }
+
+    #[test]
+    fn test_resync_replaces_missed_state() {
+        let mut server = connected();
+        server.apply(PlayerEvent::TrackStarted {
+            track_id: 1,
+            duration_ms: 0,
+        });
+        server.set_metadata(1, "Kept", "");
+        drain(&mut server);
+        server.resync(PlayerSnapshot {
+            track_id: Some(1),
+            duration_ms: 0,
+            state: TransportState::Paused,
+            position_ms: 42_000,
+            eq_preset: 1,
+        });
+        assert_eq!(
+            drain(&mut server),
+            [Characteristic::Status, Characteristic::Track]
+        );
+        let track = TrackInfo::decode(&server.read(Characteristic::Track).unwrap()).unwrap();
+        assert_eq!(track.title, "Kept");
+        assert_eq!(server.status().position_ms, 42_000);
+    }
+
+    #[test]
+    fn test_writes_are_decoded_not_applied() {
+        let server = connected();
+        assert_eq!(
+            server.write(Characteristic::Control, &[0x08, 55]),
+            Ok(Request::Transport(TransportCommand::SetVolume(55)))
+        );
+        assert_eq!(server.status().volume, 0);
+        assert_eq!(
+            server.write(Characteristic::Track, &[0]),
+            Err(GattError::WriteNotPermitted)
+        );
+        assert_eq!(
+            server.read(Characteristic::Control),
+            Err(GattError::ReadNotPermitted)
+        );
+    }
+
+    #[test]
+    fn test_transfer_writes_and_responses() {
+        let mut server = connected();
+        assert_eq!(
+            server.write(Characteristic::Transfer, &[0x03]),
+            Ok(Request::Transfer)
+        );
+        assert_eq!(
+            server.write(Characteristic::Transfer, &[0x7F]),
+            Err(GattError::UnknownOpcode(0x7F))
+        );
+
+        let response = TransferResponse::Ack { next_offset: 1024 };
+        server.notify_transfer(response);
+        assert!(server.poll_notification().is_none());
+        server.subscribe(Characteristic::Transfer, true).unwrap();
+        server.notify_transfer(response);
+        let notification = server.poll_notification().unwrap();
+        assert_eq!(notification.characteristic, Characteristic::Transfer);
+        assert_eq!(TransferResponse::decode(&notification.value), Ok(response));
+        assert!(server.poll_notification().is_none());
+    }
+}
@@ -0,0 +1,609 @@
+//! Host-side simulation of the companion app talking to the player.
+//!
+//! Three pieces stand in for the parts of a real session that only exist
+//! on hardware or on a phone:
+//!
+//! - [`SimPlayer`] — a playback engine with a queue and a small library.
+//!   It executes [`TransportCommand`]s and answers with the
+//!   [`PlayerEvent`]s the real engine would publish.
+//! - [`CompanionApp`] — the phone: it decodes every notification and read
+//!   into the state its UI would show, and caches queue pages.
+//! - [`CompanionSession`] — one connection between them through a
+//!   [`CompanionServer`], the way the firmware wires it: writes become
+//!   commands, commands become events, events become notifications.  File
+//!   transfers land in a [`TransferReceiver`] on a RAM store, and every
+//!   verified file is kept in [`CompanionSession::files`].
+//!
+//! Like [`crate::sim`], it is deterministic: nothing happens until a test
+//! sends a command or advances time with [`CompanionSession::advance`].
+//!
+//! # Example
+//! ```
+//! use bluetooth::companion_sim::{CompanionSession, SimPlayer};
+//! use bluetooth::gatt::{TransportCommand, TransportState, MAX_ATT_MTU};
+//!
+//! let player = SimPlayer::new()
+//!     .with_track(1, "Mysterons", "Portishead", 306_000)
+//!     .with_track(2, "Sour Times", "Portishead", 254_000);
+//! let mut session = CompanionSession::connect(player, MAX_ATT_MTU).unwrap();
+//!
+//! session.send(TransportCommand::Play).unwrap();
+//! assert_eq!(session.app.status.unwrap().state, TransportState::Playing);
+//! assert_eq!(session.app.track.as_ref().unwrap().title, "Mysterons");
+//! ```
+
+use std::string::String;
+use std::vec::Vec;
+
+use embassy_futures::block_on;
+
+use crate::companion::{CompanionServer, PlayerEvent, PlayerSnapshot, Request};
+use crate::gatt::{
+    self, Characteristic, GattError, PlayerStatus, QueuePage, QueuePageRequest, TrackInfo,
+    TransportCommand, TransportState,
+};
+use crate::transfer::{
+    max_chunk_len, MemoryTransferStore, TransferError, TransferKind, TransferMessage,
+    TransferReceiver, TransferResponse, TransferStatus,
+};
+
+/// Interval between position ticks while playing, as the engine publishes
+/// them.
+pub const TICK_MS: u32 = 250;
+
+/// Restart the current track instead of going back when Previous arrives
+/// later than this into it.
+const RESTART_THRESHOLD_MS: u32 = 3_000;
+
+/// Largest file the simulated player accepts.
+pub const SIM_TRANSFER_CAP: usize = 16 * 1024;
+
+/// The simulated player's transfer receiver.
+pub type SimReceiver = TransferReceiver<MemoryTransferStore<SIM_TRANSFER_CAP>>;
+
+/// A library entry of the [`SimPlayer`].
+#[derive(Debug, Clone)]
+struct SimTrack {
+    id: u32,
+    title: String,
+    artist: String,
+    duration_ms: u32,
+}
+
+/// Minimal playback engine: a queue of tracks, transport state, position,
+/// volume and EQ.  See the module docs.
+#[derive(Debug, Clone)]
+pub struct SimPlayer {
+    tracks: Vec<SimTrack>,
+    current: Option<u16>,
+    state: TransportState,
+    position_ms: u32,
+    /// Output volume, 0–100.
+    pub volume: u8,
+    eq_preset: u8,
+}
+
+impl Default for SimPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimPlayer {
+    /// An empty, stopped player at volume 50.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            tracks: Vec::new(),
+            current: None,
+            state: TransportState::Stopped,
+            position_ms: 0,
+            volume: 50,
+            eq_preset: 0,
+        }
+    }
+
+    /// Append a track to the library and the queue.
+    #[must_use]
+    pub fn with_track(mut self, id: u32, title: &str, artist: &str, duration_ms: u32) -> Self {
+        self.tracks.push(SimTrack {
+            id,
+            title: title.into(),
+            artist: artist.into(),
+            duration_ms,
+        });
+        self
+    }
+
+    /// Track IDs in play order.
+    #[must_use]
+    pub fn queue(&self) -> Vec<u32> {
+        self.tracks.iter().map(|t| t.id).collect()
+    }
+
+    /// Queue index of the current track.
+    #[must_use]
+    pub fn current_index(&self) -> Option<u16> {
+        self.current
+    }
+
+    /// Title and artist of `track_id`, as the library lookup returns them.
+    #[must_use]
+    pub fn metadata(&self, track_id: u32) -> Option<(&str, &str)> {
+        self.tracks
+            .iter()
+            .find(|t| t.id == track_id)
+            .map(|t| (t.title.as_str(), t.artist.as_str()))
+    }
+
+    /// The engine's state, for [`CompanionServer::resync`].
+    #[must_use]
+    pub fn snapshot(&self) -> PlayerSnapshot {
+        let track = self.current_track();
+        PlayerSnapshot {
+            track_id: track.map(|t| t.id),
+            duration_ms: track.map_or(0, |t| t.duration_ms),
+            state: self.state,
+            position_ms: self.position_ms,
+            eq_preset: self.eq_preset,
+        }
+    }
+
+    /// Set the EQ preset.
+    pub fn set_eq(&mut self, preset: u8) -> Vec<PlayerEvent> {
+        if self.eq_preset == preset {
+            return Vec::new();
+        }
+        self.eq_preset = preset;
+        vec![PlayerEvent::EqChanged { preset }]
+    }
+
+    /// Carry out `command` and return the events it publishes.
+    pub fn execute(&mut self, command: TransportCommand) -> Vec<PlayerEvent> {
+        let mut events = Vec::new();
+        match command {
+            TransportCommand::Play => self.play(&mut events),
+            TransportCommand::Pause => self.set_state(TransportState::Paused, &mut events),
+            TransportCommand::TogglePlayPause => {
+                if self.state == TransportState::Playing {
+                    self.set_state(TransportState::Paused, &mut events);
+                } else {
+                    self.play(&mut events);
+                }
+            }
+            TransportCommand::Stop => {
+                self.position_ms = 0;
+                self.set_state(TransportState::Stopped, &mut events);
+            }
+            TransportCommand::Next => {
+                let next = self.current.map_or(0, |i| i.saturating_add(1));
+                if usize::from(next) < self.tracks.len() {
+                    self.load(next, &mut events);
+                } else {
+                    self.position_ms = 0;
+                    self.set_state(TransportState::Stopped, &mut events);
+                }
+            }
+            TransportCommand::Previous => {
+                let index = self.current.unwrap_or(0);
+                if self.position_ms > RESTART_THRESHOLD_MS || index == 0 {
+                    self.seek(0, &mut events);
+                } else {
+                    self.load(index.saturating_sub(1), &mut events);
+                }
+            }
+            TransportCommand::Seek(ms) => self.seek(ms, &mut events),
+            TransportCommand::SetVolume(volume) => self.volume = volume.min(100),
+            TransportCommand::PlayIndex(index) => {
+                if usize::from(index) < self.tracks.len() {
+                    self.load(index, &mut events);
+                }
+            }
+        }
+        events
+    }
+
+    /// Let `ms` of playback pass, ticking every [`TICK_MS`] and moving to
+    /// the next track at the end of the current one.
+    pub fn advance(&mut self, ms: u32) -> Vec<PlayerEvent> {
+        let mut events = Vec::new();
+        let mut left = ms;
+        while left >= TICK_MS && self.state == TransportState::Playing {
+            left = left.saturating_sub(TICK_MS);
+            self.position_ms = self.position_ms.saturating_add(TICK_MS);
+            let duration = self.current_track().map_or(0, |t| t.duration_ms);
+            if self.position_ms >= duration {
+                events.extend(self.execute(TransportCommand::Next));
+            } else {
+                events.push(PlayerEvent::PositionTick {
+                    position_ms: self.position_ms,
+                });
+            }
+        }
+        events
+    }
+
+    fn current_track(&self) -> Option<&SimTrack> {
+        self.tracks.get(usize::from(self.current?))
+    }
+
+    fn play(&mut self, events: &mut Vec<PlayerEvent>) {
+        match self.current {
+            Some(_) => self.set_state(TransportState::Playing, events),
+            None if !self.tracks.is_empty() => self.load(0, events),
+            None => {}
+        }
+    }
+
+    fn load(&mut self, index: u16, events: &mut Vec<PlayerEvent>) {
+        self.current = Some(index);
+        self.position_ms = 0;
+        if let Some(track) = self.current_track() {
+            events.push(PlayerEvent::TrackStarted {
+                track_id: track.id,
+                duration_ms: track.duration_ms,
+            });
+        }
+        self.set_state(TransportState::Playing, events);
+    }
+
+    fn seek(&mut self, ms: u32, events: &mut Vec<PlayerEvent>) {
+        let duration = self.current_track().map_or(0, |t| t.duration_ms);
+        self.position_ms = ms.min(duration);
+        events.push(PlayerEvent::PositionTick {
+            position_ms: self.position_ms,
+        });
+    }
+
+    fn set_state(&mut self, state: TransportState, events: &mut Vec<PlayerEvent>) {
+        if self.state != state {
+            self.state = state;
+            events.push(PlayerEvent::StateChanged(state));
+        }
+    }
+}
+
+/// The phone: what its UI shows, built only from reads and notifications.
+#[derive(Debug, Clone, Default)]
+pub struct CompanionApp {
+    /// Protocol version read at connect.
+    pub version: Option<u8>,
+    /// Last track value.
+    pub track: Option<TrackInfo>,
+    /// Last status value.
+    pub status: Option<PlayerStatus>,
+    /// Last battery level.
+    pub battery: Option<u8>,
+    /// Queue as far as it has been browsed; `None` entries are not loaded.
+    pub queue: Vec<Option<u32>>,
+    /// Queue index of the current track, from the last page.
+    pub current: Option<u16>,
+    /// Every notification received, in order.
+    pub received: Vec<Characteristic>,
+    /// Largest notification payload seen, in bytes.
+    pub largest_notification: usize,
+    /// Every transfer response received, in order.
+    pub transfer: Vec<TransferResponse>,
+}
+
+impl CompanionApp {
+    /// Decode a notification or read result for `characteristic`.
+    ///
+    /// # Errors
+    ///
+    /// The decode error of a malformed value.
+    pub fn accept(
+        &mut self,
+        characteristic: Characteristic,
+        value: &[u8],
+    ) -> Result<(), GattError> {
+        match characteristic {
+            Characteristic::Version => self.version = value.first().copied(),
+            Characteristic::Track => self.track = Some(TrackInfo::decode(value)?),
+            Characteristic::Status => self.status = Some(PlayerStatus::decode(value)?),
+            Characteristic::BatteryLevel => self.battery = Some(gatt::decode_battery(value)?),
+            Characteristic::Queue => self.accept_page(&QueuePage::decode(value)?),
+            Characteristic::Transfer => self.transfer.push(TransferResponse::decode(value)?),
+            Characteristic::Control => return Err(GattError::ReadNotPermitted),
+        }
+        Ok(())
+    }
+
+    /// Whether every queue entry has been browsed.
+    #[must_use]
+    pub fn queue_complete(&self) -> bool {
+        self.queue.iter().all(Option::is_some)
+    }
+
+    fn accept_page(&mut self, page: &QueuePage) {
+        self.queue.resize(usize::from(page.total), None);
+        let start = usize::from(page.offset);
+        for (slot, &id) in self.queue.iter_mut().skip(start).zip(&page.tracks) {
+            *slot = Some(id);
+        }
+        self.current = page.current;
+    }
+}
+
+/// One connection between a [`CompanionApp`] and a [`SimPlayer`] through a
+/// [`CompanionServer`].  See the module docs.
+#[derive(Debug, Clone)]
+pub struct CompanionSession {
+    /// The player's GATT server.
+    pub server: CompanionServer,
+    /// The playback engine.
+    pub player: SimPlayer,
+    /// The phone.
+    pub app: CompanionApp,
+    /// The player's transfer receiver; it outlives connections.
+    pub transfer: SimReceiver,
+    /// Every file received and verified, in order.  The simulated player
+    /// applies them all.
+    pub files: Vec<Vec<u8>>,
+}
+
+impl CompanionSession {
+    /// Connect a fresh app to `player`: exchange the MTU, read the version
+    /// and subscribe to every notifiable characteristic.
+    ///
+    /// # Errors
+    ///
+    /// A decode error on the initial values.
+    pub fn connect(player: SimPlayer, mtu: u16) -> Result<Self, GattError> {
+        let transfer = block_on(TransferReceiver::open(
+            MemoryTransferStore::new(),
+            u32::try_from(SIM_TRANSFER_CAP).unwrap_or(u32::MAX),
+        ))
+        .map_err(|_| GattError::InvalidLength)?;
+        let mut session = Self {
+            server: CompanionServer::new(),
+            player,
+            app: CompanionApp::default(),
+            transfer,
+            files: Vec::new(),
+        };
+        session.reconnect(mtu)?;
+        Ok(session)
+    }
+
+    /// Drop the link and connect again, as the phone does after walking out
+    /// of range.  The app keeps its cached state.
+    ///
+    /// # Errors
+    ///
+    /// A decode error on the initial values.
+    pub fn reconnect(&mut self, mtu: u16) -> Result<(), GattError> {
+        self.server.disconnected();
+        self.server.connected();
+        self.server.set_mtu(mtu);
+        self.server.set_volume(self.player.volume);
+        let version = self.server.read(Characteristic::Version)?;
+        self.app.accept(Characteristic::Version, &version)?;
+        for characteristic in Characteristic::ALL {
+            if characteristic.can_notify() {
+                self.server.subscribe(characteristic, true)?;
+            }
+        }
+        self.pump()?;
+        Ok(())
+    }
+
+    /// Deliver every pending notification to the app.  Returns how many
+    /// were sent.
+    ///
+    /// # Errors
+    ///
+    /// A decode error, or [`GattError::InvalidLength`] for a notification
+    /// longer than the MTU allows.
+    pub fn pump(&mut self) -> Result<usize, GattError> {
+        let max_len = gatt::notify_len(self.server.mtu().unwrap_or(gatt::DEFAULT_ATT_MTU));
+        let mut sent = 0_usize;
+        while let Some(notification) = self.server.poll_notification() {
+            if notification.value.len() > max_len {
+                return Err(GattError::InvalidLength);
+            }
+            self.app.received.push(notification.characteristic);
+            self.app.largest_notification =
+                self.app.largest_notification.max(notification.value.len());
+            self.app
+                .accept(notification.characteristic, &notification.value)?;
+            sent = sent.saturating_add(1);
+        }
+        Ok(sent)
+    }
+
+    /// Write `command` to the control characteristic and deliver the
+    /// notifications it causes.
+    ///
+    /// # Errors
+    ///
+    /// As [`write`](Self::write).
+    pub fn send(&mut self, command: TransportCommand) -> Result<(), GattError> {
+        self.write(Characteristic::Control, &command.encode())
+    }
+
+    /// Write raw bytes to `characteristic`, act on the request as the
+    /// firmware does, and deliver the resulting notifications.
+    ///
+    /// # Errors
+    ///
+    /// The ATT error the server answers the write with.  A transfer write
+    /// the store cannot hold is answered with
+    /// [`GattError::InvalidLength`].
+    pub fn write(&mut self, characteristic: Characteristic, value: &[u8]) -> Result<(), GattError> {
+        match self.server.write(characteristic, value)? {
+            Request::Transport(command) => {
+                let events = self.player.execute(command);
+                self.deliver(events);
+                self.server.set_volume(self.player.volume);
+            }
+            Request::QueuePage(request) => {
+                let queue = self.player.queue();
+                self.server
+                    .answer_queue(&queue, self.player.current_index(), request);
+            }
+            Request::Transfer => self.receive(value)?,
+        }
+        self.pump()?;
+        Ok(())
+    }
+
+    /// Upload `data` as transfer `id`, resuming wherever the player's
+    /// receiver stands, in chunks as large as the MTU allows.  Returns the
+    /// status of the final [`TransferResponse::Done`].
+    ///
+    /// # Errors
+    ///
+    /// As [`write`](Self::write), or [`GattError::InvalidLength`] when the
+    /// player stops answering as the protocol requires.
+    pub fn upload(&mut self, id: u32, data: &[u8]) -> Result<TransferStatus, GattError> {
+        self.upload_until(id, data, data.len())?;
+        self.write(Characteristic::Transfer, &TransferMessage::Commit.encode())?;
+        match self.app.transfer.last() {
+            Some(&TransferResponse::Done { id: done, status }) if done == id => Ok(status),
+            _ => Err(GattError::InvalidLength),
+        }
+    }
+
+    /// Begin uploading `data` as transfer `id` but stop once `limit` bytes
+    /// have been sent, as a phone does when the link drops mid-transfer.
+    /// Returns the offset the player resumed from.
+    ///
+    /// # Errors
+    ///
+    /// As [`upload`](Self::upload).
+    pub fn upload_until(&mut self, id: u32, data: &[u8], limit: usize) -> Result<u32, GattError> {
+        let begin = TransferMessage::Begin {
+            kind: TransferKind::LibraryDelta,
+            id,
+            len: u32::try_from(data.len()).map_err(|_| GattError::InvalidLength)?,
+            crc32: crc32fast::hash(data),
+        };
+        self.write(Characteristic::Transfer, &begin.encode())?;
+        let Some(&TransferResponse::Ready { next_offset, .. }) = self.app.transfer.last() else {
+            return Err(GattError::InvalidLength);
+        };
+        let chunk_len = max_chunk_len(self.server.mtu().unwrap_or(gatt::DEFAULT_ATT_MTU));
+        let end = limit.min(data.len());
+        let mut offset = usize::try_from(next_offset).map_err(|_| GattError::InvalidLength)?;
+        while offset < end {
+            let chunk = data
+                .get(offset..end.min(offset.saturating_add(chunk_len)))
+                .ok_or(GattError::InvalidLength)?;
+            let message = TransferMessage::Chunk {
+                offset: u32::try_from(offset).map_err(|_| GattError::InvalidLength)?,
+                data: chunk,
+            };
+            self.write(Characteristic::Transfer, &message.encode())?;
+            offset = offset.saturating_add(chunk.len());
+        }
+        Ok(next_offset)
+    }
+
+    /// Request `count` queue entries from `offset`; the app caches the page.
+    ///
+    /// # Errors
+    ///
+    /// As [`write`](Self::write).
+    pub fn browse(&mut self, offset: u16, count: u8) -> Result<(), GattError> {
+        self.write(
+            Characteristic::Queue,
+            &QueuePageRequest { offset, count }.encode(),
+        )
+    }
+
+    /// Page through the whole queue, `count` entries at a time, until the
+    /// app has every entry.  Returns the number of requests it took.
+    ///
+    /// # Errors
+    ///
+    /// As [`write`](Self::write), or [`GattError::InvalidLength`] if a page
+    /// comes back empty before the queue is complete.
+    pub fn browse_all(&mut self, count: u8) -> Result<usize, GattError> {
+        let mut requests = 0_usize;
+        self.browse(0, count)?;
+        requests = requests.saturating_add(1);
+        while let Some(missing) = self.app.queue.iter().position(Option::is_none) {
+            let offset = u16::try_from(missing).map_err(|_| GattError::InvalidLength)?;
+            self.browse(offset, count)?;
+            requests = requests.saturating_add(1);
+            if self.app.queue.get(missing).copied().flatten().is_none() {
+                return Err(GattError::InvalidLength);
+            }
+        }
+        Ok(requests)
+    }
+
+    /// Let `ms` of playback pass one [`TICK_MS`] at a time, delivering the
+    /// notifications after each tick as the BLE task would.  Returns how
+    /// many were sent.
+    ///
+    /// # Errors
+    ///
+    /// As [`pump`](Self::pump).
+    pub fn advance(&mut self, ms: u32) -> Result<usize, GattError> {
+        let mut sent = 0_usize;
+        let mut left = ms;
+        while left >= TICK_MS {
+            left = left.saturating_sub(TICK_MS);
+            let events = self.player.advance(TICK_MS);
+            self.deliver(events);
+            sent = sent.saturating_add(self.pump()?);
+        }
+        Ok(sent)
+    }
+
+    /// Feed playback events to the server, followed by the library lookup
+    /// of every new track — what the firmware's BLE task does as it drains
+    /// the bus.
+    pub fn deliver(&mut self, events: impl IntoIterator<Item = PlayerEvent>) {
+        for event in events {
+            self.server.apply(event);
+            if let PlayerEvent::TrackStarted { track_id, .. } = event {
+                self.lookup(track_id);
+            }
+        }
+    }
+
+    /// Recover after the bus dropped events, as the firmware does when its
+    /// subscriber reports lag.
+    ///
+    /// # Errors
+    ///
+    /// As [`pump`](Self::pump).
+    pub fn resync(&mut self) -> Result<usize, GattError> {
+        let snapshot = self.player.snapshot();
+        self.server.resync(snapshot);
+        if let Some(track_id) = snapshot.track_id {
+            self.lookup(track_id);
+        }
+        self.pump()
+    }
+
+    /// Hand a transfer write to the receiver, notify its response, and
+    /// take delivery of a file once it is verified.
+    fn receive(&mut self, value: &[u8]) -> Result<(), GattError> {
+        block_on(self.transfer.write(value)).map_err(|e| match e {
+            TransferError::Gatt(e) => e,
+            TransferError::Store(_) => GattError::InvalidLength,
+        })?;
+        if self.transfer.completed().is_some() {
+            let mut buf = vec![0; SIM_TRANSFER_CAP];
+            let file = block_on(self.transfer.read_completed(&mut buf))
+                .map_err(|_| GattError::InvalidLength)?;
+            self.files.push(file.to_vec());
+            block_on(self.transfer.finish(TransferStatus::Applied))
+                .map_err(|_| GattError::InvalidLength)?;
+        }
+        if let Some(response) = self.transfer.take_response() {
+            self.server.notify_transfer(response);
+        }
+        Ok(())
+    }
+
+    fn lookup(&mut self, track_id: u32) {
+        if let Some((title, artist)) = self.player.metadata(track_id) {
+            self.server.set_metadata(track_id, title, artist);
+        }
+    }
+}
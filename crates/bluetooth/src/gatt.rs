@@ -0,0 +1,867 @@
+//! Companion app protocol — the GATT service a phone app uses to control
+//! the player.
+//!
+//! This module is the protocol specification: the service and
+//! characteristic UUIDs, what each characteristic does, and the byte
+//! layout of every value.  It has no state; [`crate::companion`] is the
+//! server that serves it and [`crate::companion_sim`] plays the phone.
+//!
+//! # Service
+//!
+//! One primary service, [`SERVICE_UUID`], plus the standard Battery Service
+//! ([`BATTERY_SERVICE_UUID`]) so the phone's own battery widget shows the
+//! player's charge.
+//!
+//! | Characteristic              | Properties    | Value                    |
+//! |-----------------------------|---------------|--------------------------|
+//! | [`Version`]                 | read          | `[PROTOCOL_VERSION]`     |
+//! | [`Track`]                   | read, notify  | [`TrackInfo`]            |
+//! | [`Status`]                  | read, notify  | [`PlayerStatus`]         |
+//! | [`Control`]                 | write         | [`TransportCommand`]     |
+//! | [`Queue`]                   | write, notify | [`QueuePageRequest`] in, [`QueuePage`] out |
+//! | [`Transfer`]                | write, notify | [`crate::transfer`] messages |
+//! | [`BatteryLevel`] (0x2A19)   | read, notify  | percent, `u8`            |
+//!
+//! [`Version`]: Characteristic::Version
+//! [`Track`]: Characteristic::Track
+//! [`Status`]: Characteristic::Status
+//! [`Control`]: Characteristic::Control
+//! [`Queue`]: Characteristic::Queue
+//! [`Transfer`]: Characteristic::Transfer
+//! [`BatteryLevel`]: Characteristic::BatteryLevel
+//!
+//! # Wire format
+//!
+//! All integers are little-endian, as everywhere else in ATT.  Strings are
+//! UTF-8 with a one-byte length prefix.  Values never exceed
+//! [`MAX_VALUE_LEN`]; notifications are cut to the negotiated ATT MTU
+//! ([`notify_len`]) by shortening the track strings or the queue page, never
+//! by truncating a field, so every notification decodes.  A client that
+//! wants the full title reads [`Track`] (ATT Read Blob handles values
+//! longer than the MTU).
+//!
+//! Queue browsing is request/response over one characteristic: the phone
+//! writes a [`QueuePageRequest`] and the player answers with a
+//! [`QueuePage`] notification.  Track IDs are library IDs
+//! (`library.idx` keys); the phone resolves them through the track
+//! notifications it has seen or leaves them as numbers.
+//!
+//! File uploads (library deltas) use [`Transfer`] with the chunked,
+//! resumable protocol specified in [`crate::transfer`].
+//!
+//! Writes that do not decode are answered with the ATT error in
+//! [`GattError::att_code`]; nothing is partially applied.
+
+use heapless::{String, Vec};
+
+/// Version of this wire format, readable from [`Characteristic::Version`].
+/// Bumped on any incompatible layout change.
+pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Companion service UUID (`5e0a0001-7c3b-4d2e-9a51-3f6c0d1e8b24`).
+pub const SERVICE_UUID: u128 = 0x5e0a_0001_7c3b_4d2e_9a51_3f6c_0d1e_8b24;
+
+/// Standard Battery Service (Bluetooth SIG assigned number).
+pub const BATTERY_SERVICE_UUID: u16 = 0x180F;
+
+/// ATT MTU every connection starts with, before an MTU exchange.
+pub const DEFAULT_ATT_MTU: u16 = 23;
+
+/// Largest ATT MTU the player negotiates (LE Data Length Extension).
+pub const MAX_ATT_MTU: u16 = 247;
+
+/// Longest title or artist carried in a [`TrackInfo`], in bytes.
+pub const MAX_TEXT_LEN: usize = 64;
+
+/// Most track IDs in one [`QueuePage`].
+pub const MAX_PAGE_TRACKS: usize = 32;
+
+/// Longest value of any characteristic.
+pub const MAX_VALUE_LEN: usize = 160;
+
+/// Bytes of a notification payload at `mtu` (the ATT header takes 3).
+#[must_use]
+pub fn notify_len(mtu: u16) -> usize {
+    usize::from(mtu.saturating_sub(3)).min(MAX_VALUE_LEN)
+}
+
+/// An encoded characteristic value.
+pub type Value = Vec<u8, MAX_VALUE_LEN>;
+
+/// A 16-bit SIG-assigned or 128-bit vendor UUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Uuid {
+    /// Bluetooth SIG assigned number.
+    Short(u16),
+    /// Vendor-specific 128-bit UUID.
+    Long(u128),
+}
+
+/// GATT characteristic property bits (Core Spec Vol 3, Part G, 3.3.1.1).
+pub mod properties {
+    /// The value may be read.
+    pub const READ: u8 = 0x02;
+    /// The value may be written (with response).
+    pub const WRITE: u8 = 0x08;
+    /// The value may be notified.
+    pub const NOTIFY: u8 = 0x10;
+}
+
+/// ATT error codes (Core Spec Vol 3, Part F, 3.4.1.1) used by the service.
+pub mod att {
+    /// The attribute cannot be read.
+    pub const READ_NOT_PERMITTED: u8 = 0x02;
+    /// The attribute cannot be written.
+    pub const WRITE_NOT_PERMITTED: u8 = 0x03;
+    /// The request is not supported (e.g. notifications on a write-only
+    /// characteristic).
+    pub const REQUEST_NOT_SUPPORTED: u8 = 0x06;
+    /// The value has the wrong length for its layout.
+    pub const INVALID_ATTRIBUTE_VALUE_LENGTH: u8 = 0x0D;
+    /// A field is outside its allowed range.
+    pub const VALUE_NOT_ALLOWED: u8 = 0x13;
+    /// Application error: the control opcode is not defined.
+    pub const UNKNOWN_COMMAND: u8 = 0x80;
+}
+
+/// Characteristics of the companion service, plus the battery level.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Characteristic {
+    /// Protocol version.
+    Version,
+    /// Current track: ID, duration, title and artist.
+    Track,
+    /// Transport state, position, volume and EQ preset.
+    Status,
+    /// Transport commands from the phone.
+    Control,
+    /// Queue browsing: page requests in, pages out.
+    Queue,
+    /// File uploads from the phone, see [`crate::transfer`].
+    Transfer,
+    /// Battery Level (0x2A19) in the Battery Service.
+    BatteryLevel,
+}
+
+impl Characteristic {
+    /// Every characteristic, in handle order.
+    pub const ALL: [Self; 7] = [
+        Self::Version,
+        Self::Track,
+        Self::Status,
+        Self::Control,
+        Self::Queue,
+        Self::Transfer,
+        Self::BatteryLevel,
+    ];
+
+    /// UUID of the characteristic declaration.
+    #[must_use]
+    pub const fn uuid(self) -> Uuid {
+        match self {
+            Self::Version => Uuid::Long(0x5e0a_0002_7c3b_4d2e_9a51_3f6c_0d1e_8b24),
+            Self::Track => Uuid::Long(0x5e0a_0003_7c3b_4d2e_9a51_3f6c_0d1e_8b24),
+            Self::Status => Uuid::Long(0x5e0a_0004_7c3b_4d2e_9a51_3f6c_0d1e_8b24),
+            Self::Control => Uuid::Long(0x5e0a_0005_7c3b_4d2e_9a51_3f6c_0d1e_8b24),
+            Self::Queue => Uuid::Long(0x5e0a_0006_7c3b_4d2e_9a51_3f6c_0d1e_8b24),
+            Self::Transfer => Uuid::Long(0x5e0a_0007_7c3b_4d2e_9a51_3f6c_0d1e_8b24),
+            Self::BatteryLevel => Uuid::Short(0x2A19),
+        }
+    }
+
+    /// UUID of the service the characteristic belongs to.
+    #[must_use]
+    pub const fn service(self) -> Uuid {
+        match self {
+            Self::BatteryLevel => Uuid::Short(BATTERY_SERVICE_UUID),
+            _ => Uuid::Long(SERVICE_UUID),
+        }
+    }
+
+    /// [`properties`] bits.
+    #[must_use]
+    pub const fn properties(self) -> u8 {
+        match self {
+            Self::Version => properties::READ,
+            Self::Track | Self::Status | Self::BatteryLevel => {
+                properties::READ | properties::NOTIFY
+            }
+            Self::Control => properties::WRITE,
+            Self::Queue | Self::Transfer => properties::WRITE | properties::NOTIFY,
+        }
+    }
+
+    /// Whether the value may be read.
+    #[must_use]
+    pub const fn is_readable(self) -> bool {
+        self.properties() & properties::READ != 0
+    }
+
+    /// Whether the value may be written.
+    #[must_use]
+    pub const fn is_writable(self) -> bool {
+        self.properties() & properties::WRITE != 0
+    }
+
+    /// Whether a client may subscribe to notifications.
+    #[must_use]
+    pub const fn can_notify(self) -> bool {
+        self.properties() & properties::NOTIFY != 0
+    }
+
+    /// One bit per characteristic, for subscription and dirty masks.
+    #[must_use]
+    pub const fn mask(self) -> u8 {
+        match self {
+            Self::Version => 0x01,
+            Self::Track => 0x02,
+            Self::Status => 0x04,
+            Self::Control => 0x08,
+            Self::Queue => 0x10,
+            Self::BatteryLevel => 0x20,
+            Self::Transfer => 0x40,
+        }
+    }
+}
+
+/// Errors decoding a value or serving a request, each with its ATT code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GattError {
+    /// The value is shorter or longer than its layout.
+    InvalidLength,
+    /// A field is out of range: an unknown transport state, volume above
+    /// 100, or text that is not UTF-8.
+    ValueNotAllowed,
+    /// A [`Characteristic::Control`] write with an undefined opcode.
+    UnknownOpcode(u8),
+    /// The characteristic is not readable.
+    ReadNotPermitted,
+    /// The characteristic is not writable.
+    WriteNotPermitted,
+    /// Notifications were requested on a characteristic without them.
+    NotifyNotSupported,
+}
+
+impl GattError {
+    /// ATT error code sent back to the client.
+    #[must_use]
+    pub const fn att_code(self) -> u8 {
+        match self {
+            Self::InvalidLength => att::INVALID_ATTRIBUTE_VALUE_LENGTH,
+            Self::ValueNotAllowed => att::VALUE_NOT_ALLOWED,
+            Self::UnknownOpcode(_) => att::UNKNOWN_COMMAND,
+            Self::ReadNotPermitted => att::READ_NOT_PERMITTED,
+            Self::WriteNotPermitted => att::WRITE_NOT_PERMITTED,
+            Self::NotifyNotSupported => att::REQUEST_NOT_SUPPORTED,
+        }
+    }
+}
+
+/// Transport state as carried in [`PlayerStatus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TransportState {
+    /// Nothing loaded, or stopped.
+    Stopped = 0,
+    /// Playing.
+    Playing = 1,
+    /// Paused; the position is kept.
+    Paused = 2,
+}
+
+impl TransportState {
+    /// Decode a wire value.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::ValueNotAllowed`] for anything but 0, 1 and 2.
+    pub const fn from_u8(value: u8) -> Result<Self, GattError> {
+        match value {
+            0 => Ok(Self::Stopped),
+            1 => Ok(Self::Playing),
+            2 => Ok(Self::Paused),
+            _ => Err(GattError::ValueNotAllowed),
+        }
+    }
+}
+
+/// [`Characteristic::Track`] value.
+///
+/// Layout: `[track_id u32][duration_ms u32][title_len u8][title]
+/// [artist_len u8][artist]`, 10 bytes plus the text.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TrackInfo {
+    /// Library track ID.
+    pub track_id: u32,
+    /// Duration in milliseconds, 0 when unknown.
+    pub duration_ms: u32,
+    /// Title, empty until the library lookup finishes.
+    pub title: String<MAX_TEXT_LEN>,
+    /// Artist, empty until the library lookup finishes.
+    pub artist: String<MAX_TEXT_LEN>,
+}
+
+/// Bytes of a [`TrackInfo`] without its text.
+const TRACK_FIXED_LEN: usize = 10;
+
+impl TrackInfo {
+    /// Encode into at most `max_len` bytes, shortening the artist first and
+    /// then the title (at character boundaries) to fit.
+    #[must_use]
+    pub fn encode(&self, max_len: usize) -> Value {
+        let mut out = Value::new();
+        push(&mut out, &self.track_id.to_le_bytes());
+        push(&mut out, &self.duration_ms.to_le_bytes());
+        let budget = max_len.min(MAX_VALUE_LEN).saturating_sub(TRACK_FIXED_LEN);
+        let title = truncate(&self.title, budget);
+        let artist = truncate(&self.artist, budget.saturating_sub(title.len()));
+        push_text(&mut out, title);
+        push_text(&mut out, artist);
+        out
+    }
+
+    /// Decode a value produced by [`encode`](Self::encode).
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::InvalidLength`] for a truncated or over-long value,
+    /// [`GattError::ValueNotAllowed`] for text that is not UTF-8.
+    pub fn decode(bytes: &[u8]) -> Result<Self, GattError> {
+        let mut r = Reader::new(bytes);
+        let info = Self {
+            track_id: r.u32()?,
+            duration_ms: r.u32()?,
+            title: r.text()?,
+            artist: r.text()?,
+        };
+        r.finish()?;
+        Ok(info)
+    }
+}
+
+/// [`Characteristic::Status`] value.
+///
+/// Layout: `[state u8][position_ms u32][volume u8][eq_preset u8]`, 7 bytes,
+/// so it fits a notification at the default MTU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerStatus {
+    /// Transport state.
+    pub state: TransportState,
+    /// Position in the current track, in milliseconds.
+    pub position_ms: u32,
+    /// Volume, 0–100.
+    pub volume: u8,
+    /// EQ preset index (0 = flat).
+    pub eq_preset: u8,
+}
+
+impl PlayerStatus {
+    /// Encode the 7-byte value.
+    #[must_use]
+    pub fn encode(&self) -> Value {
+        let mut out = Value::new();
+        push(&mut out, &[self.state as u8]);
+        push(&mut out, &self.position_ms.to_le_bytes());
+        push(&mut out, &[self.volume, self.eq_preset]);
+        out
+    }
+
+    /// Decode the 7-byte value.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::InvalidLength`] or, for an unknown state or a volume
+    /// above 100, [`GattError::ValueNotAllowed`].
+    pub fn decode(bytes: &[u8]) -> Result<Self, GattError> {
+        let mut r = Reader::new(bytes);
+        let status = Self {
+            state: TransportState::from_u8(r.u8()?)?,
+            position_ms: r.u32()?,
+            volume: volume(r.u8()?)?,
+            eq_preset: r.u8()?,
+        };
+        r.finish()?;
+        Ok(status)
+    }
+}
+
+/// [`Characteristic::Control`] write: one opcode byte and its argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportCommand {
+    /// `0x01` — start or resume playback.
+    Play,
+    /// `0x02` — pause.
+    Pause,
+    /// `0x03` — play if paused or stopped, pause if playing.
+    TogglePlayPause,
+    /// `0x04` — stop.
+    Stop,
+    /// `0x05` — next track in the queue.
+    Next,
+    /// `0x06` — previous track (or restart the current one).
+    Previous,
+    /// `0x07` + `u32` — seek to a position in milliseconds.
+    Seek(u32),
+    /// `0x08` + `u8` — set the volume, 0–100.
+    SetVolume(u8),
+    /// `0x09` + `u16` — jump to a queue index (from a [`QueuePage`]).
+    PlayIndex(u16),
+}
+
+impl TransportCommand {
+    /// Opcode byte.
+    #[must_use]
+    pub const fn opcode(self) -> u8 {
+        match self {
+            Self::Play => 0x01,
+            Self::Pause => 0x02,
+            Self::TogglePlayPause => 0x03,
+            Self::Stop => 0x04,
+            Self::Next => 0x05,
+            Self::Previous => 0x06,
+            Self::Seek(_) => 0x07,
+            Self::SetVolume(_) => 0x08,
+            Self::PlayIndex(_) => 0x09,
+        }
+    }
+
+    /// Encode as the phone writes it.
+    #[must_use]
+    pub fn encode(self) -> Value {
+        let mut out = Value::new();
+        push(&mut out, &[self.opcode()]);
+        match self {
+            Self::Seek(ms) => push(&mut out, &ms.to_le_bytes()),
+            Self::SetVolume(volume) => push(&mut out, &[volume]),
+            Self::PlayIndex(index) => push(&mut out, &index.to_le_bytes()),
+            _ => {}
+        }
+        out
+    }
+
+    /// Decode a control write.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::UnknownOpcode`], [`GattError::InvalidLength`] when the
+    /// argument is missing or followed by extra bytes, or
+    /// [`GattError::ValueNotAllowed`] for a volume above 100.
+    pub fn decode(bytes: &[u8]) -> Result<Self, GattError> {
+        let mut r = Reader::new(bytes);
+        let command = match r.u8()? {
+            0x01 => Self::Play,
+            0x02 => Self::Pause,
+            0x03 => Self::TogglePlayPause,
+            0x04 => Self::Stop,
+            0x05 => Self::Next,
+            0x06 => Self::Previous,
+            0x07 => Self::Seek(r.u32()?),
+            0x08 => Self::SetVolume(volume(r.u8()?)?),
+            0x09 => Self::PlayIndex(r.u16()?),
+            other => return Err(GattError::UnknownOpcode(other)),
+        };
+        r.finish()?;
+        Ok(command)
+    }
+}
+
+/// [`Characteristic::Queue`] write: which slice of the queue to send.
+///
+/// Layout: `[offset u16][count u8]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuePageRequest {
+    /// Index of the first entry.
+    pub offset: u16,
+    /// Entries wanted; the page may hold fewer (end of queue, MTU).
+    pub count: u8,
+}
+
+impl QueuePageRequest {
+    /// Encode as the phone writes it.
+    #[must_use]
+    pub fn encode(self) -> Value {
+        let mut out = Value::new();
+        push(&mut out, &self.offset.to_le_bytes());
+        push(&mut out, &[self.count]);
+        out
+    }
+
+    /// Decode a queue write.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::InvalidLength`] unless the value is exactly 3 bytes.
+    pub fn decode(bytes: &[u8]) -> Result<Self, GattError> {
+        let mut r = Reader::new(bytes);
+        let request = Self {
+            offset: r.u16()?,
+            count: r.u8()?,
+        };
+        r.finish()?;
+        Ok(request)
+    }
+}
+
+/// [`Characteristic::Queue`] notification answering a [`QueuePageRequest`].
+///
+/// Layout: `[total u16][offset u16][current u16][track_id u32]...`, with
+/// `current` = `0xFFFF` when nothing is selected.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QueuePage {
+    /// Entries in the whole queue.
+    pub total: u16,
+    /// Queue index of `tracks[0]`.
+    pub offset: u16,
+    /// Queue index of the current track.
+    pub current: Option<u16>,
+    /// Track IDs from `offset` on.
+    pub tracks: Vec<u32, MAX_PAGE_TRACKS>,
+}
+
+/// Bytes of a [`QueuePage`] without its track IDs.
+const PAGE_FIXED_LEN: usize = 6;
+
+/// Wire value of [`QueuePage::current`] when nothing is selected.
+const NO_CURRENT: u16 = 0xFFFF;
+
+impl QueuePage {
+    /// The page of `queue` that `request` asks for, cut to what fits in
+    /// `max_len` bytes.
+    #[must_use]
+    pub fn from_queue(
+        queue: &[u32],
+        current: Option<u16>,
+        request: QueuePageRequest,
+        max_len: usize,
+    ) -> Self {
+        let room = max_len.min(MAX_VALUE_LEN).saturating_sub(PAGE_FIXED_LEN) / 4;
+        let count = usize::from(request.count).min(room).min(MAX_PAGE_TRACKS);
+        let tracks = queue
+            .iter()
+            .skip(usize::from(request.offset))
+            .take(count)
+            .copied()
+            .collect();
+        Self {
+            total: u16::try_from(queue.len()).unwrap_or(u16::MAX),
+            offset: request.offset,
+            current,
+            tracks,
+        }
+    }
+
+    /// Encode the notification.
+    #[must_use]
+    pub fn encode(&self) -> Value {
+        let mut out = Value::new();
+        push(&mut out, &self.total.to_le_bytes());
+        push(&mut out, &self.offset.to_le_bytes());
+        push(&mut out, &self.current.unwrap_or(NO_CURRENT).to_le_bytes());
+        for track in &self.tracks {
+            push(&mut out, &track.to_le_bytes());
+        }
+        out
+    }
+
+    /// Decode a notification.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::InvalidLength`] when the header is short, the IDs are
+    /// not whole `u32`s, or there are more than [`MAX_PAGE_TRACKS`].
+    pub fn decode(bytes: &[u8]) -> Result<Self, GattError> {
+        let mut r = Reader::new(bytes);
+        let total = r.u16()?;
+        let offset = r.u16()?;
+        let current = Some(r.u16()?).filter(|&c| c != NO_CURRENT);
+        let mut tracks = Vec::new();
+        while !r.is_empty() {
+            tracks
+                .push(r.u32()?)
+                .map_err(|_| GattError::InvalidLength)?;
+        }
+        Ok(Self {
+            total,
+            offset,
+            current,
+            tracks,
+        })
+    }
+}
+
+/// Encode a [`Characteristic::BatteryLevel`] value.
+#[must_use]
+pub fn encode_battery(percent: u8) -> Value {
+    let mut out = Value::new();
+    push(&mut out, &[percent.min(100)]);
+    out
+}
+
+/// Decode a [`Characteristic::BatteryLevel`] value.
+///
+/// # Errors
+///
+/// [`GattError::InvalidLength`] unless the value is one byte, or
+/// [`GattError::ValueNotAllowed`] above 100 %.
+pub fn decode_battery(bytes: &[u8]) -> Result<u8, GattError> {
+    let mut r = Reader::new(bytes);
+    let percent = volume(r.u8()?)?;
+    r.finish()?;
+    Ok(percent)
+}
+
+/// Validate a 0–100 percentage.
+fn volume(value: u8) -> Result<u8, GattError> {
+    if value <= 100 {
+        Ok(value)
+    } else {
+        Err(GattError::ValueNotAllowed)
+    }
+}
+
+/// Append `bytes`; callers stay within [`MAX_VALUE_LEN`] by construction,
+/// so an overflow would only drop bytes, never panic.
+pub(crate) fn push(out: &mut Value, bytes: &[u8]) {
+    let _ = out.extend_from_slice(bytes);
+}
+
+/// Append a length-prefixed string (at most [`MAX_TEXT_LEN`] bytes).
+fn push_text(out: &mut Value, text: &str) {
+    let text = truncate(text, MAX_TEXT_LEN);
+    push(out, &[u8::try_from(text.len()).unwrap_or(0)]);
+    push(out, text.as_bytes());
+}
+
+/// The longest prefix of `text` of at most `max` bytes that ends on a
+/// character boundary.
+pub(crate) fn truncate(text: &str, max: usize) -> &str {
+    let mut end = max.min(text.len());
+    while !text.is_char_boundary(end) {
+        end = end.saturating_sub(1);
+    }
+    text.get(..end).unwrap_or("")
+}
+
+/// Little-endian cursor over a received value.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], GattError> {
+        let (head, rest) = self.take_slice(N)?;
+        self.bytes = rest;
+        head.try_into().map_err(|_| GattError::InvalidLength)
+    }
+
+    fn take_slice(&self, len: usize) -> Result<(&'a [u8], &'a [u8]), GattError> {
+        if self.bytes.len() < len {
+            return Err(GattError::InvalidLength);
+        }
+        Ok(self.bytes.split_at(len))
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, GattError> {
+        self.take::<1>().map(|[b]| b)
+    }
+
+    fn u16(&mut self) -> Result<u16, GattError> {
+        self.take().map(u16::from_le_bytes)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, GattError> {
+        self.take().map(u32::from_le_bytes)
+    }
+
+    fn text<const N: usize>(&mut self) -> Result<String<N>, GattError> {
+        let len = usize::from(self.u8()?);
+        let (text, rest) = self.take_slice(len)?;
+        self.bytes = rest;
+        let text = core::str::from_utf8(text).map_err(|_| GattError::ValueNotAllowed)?;
+        let mut out = String::new();
+        out.push_str(text).map_err(|_| GattError::InvalidLength)?;
+        Ok(out)
+    }
+
+    /// Everything not read yet.
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        core::mem::take(&mut self.bytes)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub(crate) fn finish(&self) -> Result<(), GattError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(GattError::InvalidLength)
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::arithmetic_side_effects)]
+mod tests {
+    use super::*;
+
+    fn track(title: &str, artist: &str) -> TrackInfo {
+        let mut info = TrackInfo {
+            track_id: 7,
+            duration_ms: 215_000,
+            ..TrackInfo::default()
+        };
+        info.title.push_str(title).unwrap();
+        info.artist.push_str(artist).unwrap();
+        info
+    }
+
+    #[test]
+    fn test_uuids_are_distinct() {
+        for (i, a) in Characteristic::ALL.iter().enumerate() {
+            for b in Characteristic::ALL.iter().skip(i + 1) {
+                assert_ne!(a.uuid(), b.uuid());
+                assert_ne!(a.mask(), b.mask());
+            }
+            assert_ne!(a.uuid(), Uuid::Long(SERVICE_UUID));
+        }
+    }
+
+    #[test]
+    fn test_properties() {
+        assert!(Characteristic::Track.can_notify());
+        assert!(!Characteristic::Control.is_readable());
+        assert!(Characteristic::Control.is_writable());
+        assert!(!Characteristic::Version.can_notify());
+        assert_eq!(
+            Characteristic::BatteryLevel.service(),
+            Uuid::Short(BATTERY_SERVICE_UUID)
+        );
+    }
+
+    #[test]
+    fn test_track_round_trip() {
+        let info = track("Glory Box", "Portishead");
+        let value = info.encode(MAX_VALUE_LEN);
+        assert_eq!(value.len(), TRACK_FIXED_LEN + 9 + 10);
+        assert_eq!(TrackInfo::decode(&value), Ok(info));
+    }
+
+    #[test]
+    fn test_track_text_shortened_to_mtu_at_char_boundary() {
+        let info = track("Sonata Noël", "Satie");
+        let value = info.encode(notify_len(DEFAULT_ATT_MTU));
+        assert!(value.len() <= 20);
+        let decoded = TrackInfo::decode(&value).unwrap();
+        // 10 fixed bytes leave 10 for text; the 10th would split the ë, so
+        // the artist gets the one byte left over.
+        assert_eq!(decoded.title, "Sonata No");
+        assert_eq!(decoded.artist, "S");
+        assert_eq!(decoded.track_id, 7);
+    }
+
+    #[test]
+    fn test_status_round_trip_fits_default_mtu() {
+        let status = PlayerStatus {
+            state: TransportState::Paused,
+            position_ms: 61_000,
+            volume: 40,
+            eq_preset: 2,
+        };
+        let value = status.encode();
+        assert!(value.len() <= notify_len(DEFAULT_ATT_MTU));
+        assert_eq!(PlayerStatus::decode(&value), Ok(status));
+        assert_eq!(
+            PlayerStatus::decode(&[3, 0, 0, 0, 0, 0, 0]),
+            Err(GattError::ValueNotAllowed)
+        );
+    }
+
+    #[test]
+    fn test_command_round_trip() {
+        let commands = [
+            TransportCommand::Play,
+            TransportCommand::Pause,
+            TransportCommand::TogglePlayPause,
+            TransportCommand::Stop,
+            TransportCommand::Next,
+            TransportCommand::Previous,
+            TransportCommand::Seek(90_000),
+            TransportCommand::SetVolume(100),
+            TransportCommand::PlayIndex(3),
+        ];
+        for command in commands {
+            assert_eq!(TransportCommand::decode(&command.encode()), Ok(command));
+        }
+    }
+
+    #[test]
+    fn test_command_rejects_malformed_writes() {
+        assert_eq!(TransportCommand::decode(&[]), Err(GattError::InvalidLength));
+        assert_eq!(
+            TransportCommand::decode(&[0x42]),
+            Err(GattError::UnknownOpcode(0x42))
+        );
+        assert_eq!(
+            TransportCommand::decode(&[0x07, 1, 2]),
+            Err(GattError::InvalidLength)
+        );
+        assert_eq!(
+            TransportCommand::decode(&[0x01, 0]),
+            Err(GattError::InvalidLength)
+        );
+        assert_eq!(
+            TransportCommand::decode(&[0x08, 101]),
+            Err(GattError::ValueNotAllowed)
+        );
+        assert_eq!(
+            GattError::UnknownOpcode(0x42).att_code(),
+            att::UNKNOWN_COMMAND
+        );
+    }
+
+    #[test]
+    fn test_queue_page_fits_mtu() {
+        let queue: [u32; 10] = [10, 11, 12, 13, 14, 15, 16, 17, 18, 19];
+        let request = QueuePageRequest {
+            offset: 2,
+            count: 8,
+        };
+        assert_eq!(QueuePageRequest::decode(&request.encode()), Ok(request));
+
+        // 20 bytes: 6 header + 3 IDs.
+        let page = QueuePage::from_queue(&queue, Some(4), request, notify_len(DEFAULT_ATT_MTU));
+        assert_eq!(page.tracks.as_slice(), &[12, 13, 14]);
+        assert_eq!(page.total, 10);
+        let value = page.encode();
+        assert!(value.len() <= 20);
+        assert_eq!(QueuePage::decode(&value), Ok(page));
+
+        let page = QueuePage::from_queue(&queue, None, request, notify_len(MAX_ATT_MTU));
+        assert_eq!(page.tracks.len(), 8);
+        assert_eq!(QueuePage::decode(&page.encode()).unwrap().current, None);
+    }
+
+    #[test]
+    fn test_queue_page_past_the_end_is_empty() {
+        let request = QueuePageRequest {
+            offset: 5,
+            count: 4,
+        };
+        let page = QueuePage::from_queue(&[1, 2], Some(0), request, MAX_VALUE_LEN);
+        assert!(page.tracks.is_empty());
+        assert_eq!(page.total, 2);
+    }
+
+    #[test]
+    fn test_battery() {
+        assert_eq!(decode_battery(&encode_battery(87)), Ok(87));
+        assert_eq!(decode_battery(&encode_battery(150)), Ok(100));
+        assert_eq!(decode_battery(&[]), Err(GattError::InvalidLength));
+    }
+}
@@ -11,7 +11,7 @@
 #[cfg(test)]
 #[allow(clippy::expect_used)] // Tests use expect() for readable assertions
 mod tests {
-    use super::{HciCommand, HciError, HciEvent, HciEventCode, HciPacket};
+    use super::{status, HciCommand, HciError, HciEvent, HciEventCode, HciPacket};
 
     // ---- Command tests -------------------------------------------------------
 
@@ -66,6 +66,80 @@ mod tests {
         let result = HciPacket::parse(&raw);
         assert_eq!(result, Err(HciError::UnknownPacketType(0xFF)));
     }
+
+    #[test]
+    fn test_hci_event_parse_connection_complete() {
+        // [type, code=0x03, len=11, status, handle=0x0041 (flags in top bits),
+        //  bd_addr, link_type=ACL, encryption=off]
+        let raw = [
+            0x04_u8, 0x03, 0x0B, 0x00, 0x41, 0x20, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x01, 0x00,
+        ];
+        let event = HciPacket::parse(&raw).expect("should parse successfully");
+        assert_eq!(
+            event,
+            HciEvent::ConnectionComplete {
+                status: status::SUCCESS,
+                handle: 0x0041,
+                address: [0x01, 0x02, 0x03, 0x04, 0x05, 0x06],
+            }
+        );
+        assert_eq!(
+            HciPacket::parse(raw.split_at(13).0),
+            Err(HciError::PacketTooShort)
+        );
+    }
+
+    #[test]
+    fn test_hci_event_parse_disconnection_complete() {
+        let raw = [0x04_u8, 0x05, 0x04, 0x00, 0x41, 0x00, 0x08];
+        let event = HciPacket::parse(&raw).expect("should parse successfully");
+        assert_eq!(
+            event,
+            HciEvent::DisconnectionComplete {
+                status: status::SUCCESS,
+                handle: 0x0041,
+                reason: status::CONNECTION_TIMEOUT,
+            }
+        );
+    }
+
+    // ---- Command parsing tests -----------------------------------------------
+
+    #[test]
+    fn test_hci_command_round_trip() {
+        let commands = [
+            HciCommand::Reset,
+            HciCommand::Inquiry { length: 0x08 },
+            HciCommand::CreateConnection {
+                address: [0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF],
+            },
+            HciCommand::Disconnect {
+                handle: 0x0041,
+                reason: status::REMOTE_USER_TERMINATED,
+            },
+            HciCommand::AuthenticationRequested { handle: 0x0041 },
+            HciCommand::LeSetAdvertisingEnable(true),
+        ];
+        for cmd in commands {
+            let pkt = HciPacket::from_command(cmd).expect("fixed-size params always fit");
+            assert_eq!(HciPacket::parse_command(&pkt), Ok(cmd));
+        }
+    }
+
+    #[test]
+    fn test_hci_parse_command_rejects_truncated_and_unknown() {
+        // Disconnect declares 3 param bytes but carries 2.
+        let truncated = [0x01_u8, 0x06, 0x04, 0x03, 0x41, 0x00];
+        assert_eq!(
+            HciPacket::parse_command(&truncated),
+            Err(HciError::PacketTooShort)
+        );
+        let unknown = [0x01_u8, 0x34, 0x12, 0x00];
+        assert_eq!(
+            HciPacket::parse_command(&unknown),
+            Err(HciError::UnknownOpcode(0x1234))
+        );
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -77,14 +151,48 @@ mod tests {
 pub enum HciCommand {
     /// HCI_Reset — OGF=0x03 (Controller & Baseband), OCF=0x0003.
     Reset,
+    /// HCI_Inquiry — OGF=0x01 (Link Control), OCF=0x0001.
+    ///
+    /// Uses the General Inquiry Access Code and an unlimited response count.
+    Inquiry {
+        /// Inquiry length in 1.28 s units (0x01–0x30).
+        length: u8,
+    },
+    /// HCI_Create_Connection — OGF=0x01, OCF=0x0005.
+    CreateConnection {
+        /// BD_ADDR of the peer, little-endian as on the wire.
+        address: [u8; 6],
+    },
+    /// HCI_Disconnect — OGF=0x01, OCF=0x0006.
+    Disconnect {
+        /// Connection handle from the Connection Complete event.
+        handle: u16,
+        /// Reason code sent to the peer (e.g. [`status::REMOTE_USER_TERMINATED`]).
+        reason: u8,
+    },
+    /// HCI_Authentication_Requested — OGF=0x01, OCF=0x0011.
+    AuthenticationRequested {
+        /// Connection handle to pair/authenticate.
+        handle: u16,
+    },
+    /// HCI_LE_Set_Advertising_Enable — OGF=0x08 (LE), OCF=0x000A.
+    LeSetAdvertisingEnable(bool),
 }
 
+/// GIAC LAP (0x9E8B33), little-endian.
+const GENERAL_INQUIRY_LAP: [u8; 3] = [0x33, 0x8B, 0x9E];
+
 impl HciCommand {
     /// Return the 16-bit opcode: `(OGF << 10) | OCF`.
     #[must_use]
     pub const fn opcode(self) -> u16 {
         match self {
-            HciCommand::Reset => 0x0C03, // OGF=3, OCF=0x003
+            HciCommand::Reset => 0x0C03,                          // OGF=3, OCF=0x003
+            HciCommand::Inquiry { .. } => 0x0401,                 // OGF=1, OCF=0x001
+            HciCommand::CreateConnection { .. } => 0x0405,        // OGF=1, OCF=0x005
+            HciCommand::Disconnect { .. } => 0x0406,              // OGF=1, OCF=0x006
+            HciCommand::AuthenticationRequested { .. } => 0x0411, // OGF=1, OCF=0x011
+            HciCommand::LeSetAdvertisingEnable(_) => 0x200A,      // OGF=8, OCF=0x00A
         }
     }
 
@@ -93,16 +201,91 @@ impl HciCommand {
     pub fn params(self) -> heapless::Vec<u8, 64> {
         match self {
             HciCommand::Reset => heapless::Vec::new(),
+            HciCommand::Inquiry { length } => {
+                let [l0, l1, l2] = GENERAL_INQUIRY_LAP;
+                fixed_params([l0, l1, l2, length, 0x00])
+            }
+            HciCommand::CreateConnection { address } => {
+                let [a0, a1, a2, a3, a4, a5] = address;
+                // Packet types DM1/DH1/DM3/DH3/DM5/DH5 (0xCC18), page scan
+                // repetition mode R1, no clock offset, role switch allowed.
+                fixed_params([
+                    a0, a1, a2, a3, a4, a5, 0x18, 0xCC, 0x01, 0x00, 0x00, 0x00, 0x01,
+                ])
+            }
+            HciCommand::Disconnect { handle, reason } => {
+                let [h0, h1] = handle.to_le_bytes();
+                fixed_params([h0, h1, reason])
+            }
+            HciCommand::AuthenticationRequested { handle } => fixed_params(handle.to_le_bytes()),
+            HciCommand::LeSetAdvertisingEnable(enable) => fixed_params([u8::from(enable)]),
         }
     }
+
+    /// Whether the controller answers with Command Status (and a later
+    /// completion event) rather than Command Complete.
+    #[must_use]
+    pub const fn is_deferred(self) -> bool {
+        matches!(
+            self,
+            HciCommand::Inquiry { .. }
+                | HciCommand::CreateConnection { .. }
+                | HciCommand::Disconnect { .. }
+                | HciCommand::AuthenticationRequested { .. }
+        )
+    }
+}
+
+/// Copy a fixed-size parameter block into the HCI parameter buffer.
+fn fixed_params<const N: usize>(bytes: [u8; N]) -> heapless::Vec<u8, 64> {
+    // Every caller passes at most 13 bytes, so this cannot overflow.
+    heapless::Vec::from_slice(&bytes).unwrap_or_default()
+}
+
+/// HCI status and reason codes (Core Spec Vol 1, Part F).
+pub mod status {
+    /// Success.
+    pub const SUCCESS: u8 = 0x00;
+    /// Unknown HCI Command.
+    pub const UNKNOWN_HCI_COMMAND: u8 = 0x01;
+    /// Unknown Connection Identifier.
+    pub const UNKNOWN_CONNECTION_ID: u8 = 0x02;
+    /// Page Timeout — the peer did not answer a connection request.
+    pub const PAGE_TIMEOUT: u8 = 0x04;
+    /// Authentication Failure (e.g. wrong link key or PIN).
+    pub const AUTHENTICATION_FAILURE: u8 = 0x05;
+    /// Connection Timeout — supervision timeout expired.
+    pub const CONNECTION_TIMEOUT: u8 = 0x08;
+    /// ACL Connection Already Exists.
+    pub const CONNECTION_ALREADY_EXISTS: u8 = 0x0B;
+    /// Command Disallowed.
+    pub const COMMAND_DISALLOWED: u8 = 0x0C;
+    /// Remote User Terminated Connection.
+    pub const REMOTE_USER_TERMINATED: u8 = 0x13;
+    /// Connection Terminated By Local Host.
+    pub const TERMINATED_BY_LOCAL_HOST: u8 = 0x16;
+    /// Pairing Not Allowed.
+    pub const PAIRING_NOT_ALLOWED: u8 = 0x18;
 }
 
 /// HCI event codes (controller → host).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum HciEventCode {
+    /// InquiryComplete event (0x01): the inquiry has ended.
+    InquiryComplete = 0x01,
+    /// InquiryResult event (0x02): a discoverable device answered.
+    InquiryResult = 0x02,
+    /// ConnectionComplete event (0x03): a link was established (or failed).
+    ConnectionComplete = 0x03,
+    /// DisconnectionComplete event (0x05): a link was closed.
+    DisconnectionComplete = 0x05,
+    /// AuthenticationComplete event (0x06): pairing finished.
+    AuthenticationComplete = 0x06,
     /// CommandComplete event (0x0E): reports the result of a command.
     CommandComplete = 0x0E,
+    /// CommandStatus event (0x0F): a deferred command was accepted or refused.
+    CommandStatus = 0x0F,
 }
 
 /// Decoded HCI events.
@@ -113,6 +296,50 @@ pub enum HciEvent {
         /// Return status; `0x00` means success.
         status: u8,
     },
+    /// A deferred command was accepted (`status == 0`) or refused.
+    CommandStatus {
+        /// [`status`] code.
+        status: u8,
+        /// Opcode of the command this answers.
+        opcode: u16,
+    },
+    /// A device answered an inquiry (first response of the event only).
+    InquiryResult {
+        /// Peer BD_ADDR, little-endian.
+        address: [u8; 6],
+        /// 24-bit Class of Device.
+        class_of_device: u32,
+    },
+    /// The inquiry has ended.
+    InquiryComplete {
+        /// [`status`] code.
+        status: u8,
+    },
+    /// A link was established (`status == 0`) or the attempt failed.
+    ConnectionComplete {
+        /// [`status`] code, e.g. [`status::PAGE_TIMEOUT`].
+        status: u8,
+        /// 12-bit connection handle.
+        handle: u16,
+        /// Peer BD_ADDR, little-endian.
+        address: [u8; 6],
+    },
+    /// A link was closed.
+    DisconnectionComplete {
+        /// [`status`] code of the disconnection itself.
+        status: u8,
+        /// Handle of the closed link.
+        handle: u16,
+        /// Why the link closed, e.g. [`status::CONNECTION_TIMEOUT`].
+        reason: u8,
+    },
+    /// Pairing/authentication on a link finished.
+    AuthenticationComplete {
+        /// [`status`] code, e.g. [`status::AUTHENTICATION_FAILURE`].
+        status: u8,
+        /// Handle of the link.
+        handle: u16,
+    },
 }
 
 /// Errors that can occur when parsing or serializing an HCI packet.
@@ -128,6 +355,8 @@ pub enum HciError {
     /// LE Audio commands can have up to 251 bytes of params — these must
     /// be rejected explicitly rather than silently truncated.
     CommandTooLong,
+    /// A command packet carries an opcode this crate does not know.
+    UnknownOpcode(u16),
 }
 
 /// Zero-size marker struct that owns the HCI framing logic.
@@ -230,14 +459,121 @@ impl HciPacket {
         }
     }
 
+    /// Parse an H4-framed command packet (the controller side of
+    /// [`from_command`](Self::from_command)).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HciError::PacketTooShort`] when the header or the declared
+    /// parameters are truncated, [`HciError::UnknownPacketType`] when the
+    /// packet is not a command, or [`HciError::UnknownOpcode`] for commands
+    /// outside [`HciCommand`].
+    #[allow(clippy::indexing_slicing)] // Safety: len checks guard all indexing
+    pub fn parse_command(bytes: &[u8]) -> Result<HciCommand, HciError> {
+        if bytes.len() < 4 {
+            return Err(HciError::PacketTooShort);
+        }
+        if bytes[0] != 0x01 {
+            return Err(HciError::UnknownPacketType(bytes[0]));
+        }
+        let opcode = u16::from_le_bytes([bytes[1], bytes[2]]);
+        let params = bytes
+            .get(4..)
+            .and_then(|rest| rest.get(..usize::from(bytes[3])))
+            .ok_or(HciError::PacketTooShort)?;
+        let handle = || {
+            params
+                .get(..2)
+                .map(|h| u16::from_le_bytes([h[0], h[1]]) & 0x0FFF)
+                .ok_or(HciError::PacketTooShort)
+        };
+
+        match opcode {
+            0x0C03 => Ok(HciCommand::Reset),
+            0x0401 => params
+                .get(3)
+                .map(|&length| HciCommand::Inquiry { length })
+                .ok_or(HciError::PacketTooShort),
+            0x0405 => params
+                .get(..6)
+                .map(|a| HciCommand::CreateConnection {
+                    address: [a[0], a[1], a[2], a[3], a[4], a[5]],
+                })
+                .ok_or(HciError::PacketTooShort),
+            0x0406 => Ok(HciCommand::Disconnect {
+                handle: handle()?,
+                reason: *params.get(2).ok_or(HciError::PacketTooShort)?,
+            }),
+            0x0411 => Ok(HciCommand::AuthenticationRequested { handle: handle()? }),
+            0x200A => params
+                .first()
+                .map(|&enable| HciCommand::LeSetAdvertisingEnable(enable != 0))
+                .ok_or(HciError::PacketTooShort),
+            other => Err(HciError::UnknownOpcode(other)),
+        }
+    }
+
     /// Parse the payload of an H4 event packet (packet-type byte already consumed).
-    #[allow(clippy::indexing_slicing)] // Safety: is_empty + len < 6 guards all indexing
+    ///
+    /// Layout: `[event_code, param_len, ...params]`.
+    #[allow(clippy::indexing_slicing)] // Safety: is_empty + per-event len guards all indexing
     fn parse_event(bytes: &[u8]) -> Result<HciEvent, HciError> {
         if bytes.is_empty() {
             return Err(HciError::PacketTooShort);
         }
 
+        let need = |len: usize| {
+            if bytes.len() < len {
+                Err(HciError::PacketTooShort)
+            } else {
+                Ok(())
+            }
+        };
+        // Connection handles occupy the low 12 bits; the top 4 are flags.
+        let handle = |lo: u8, hi: u8| u16::from_le_bytes([lo, hi]) & 0x0FFF;
+
         match bytes[0] {
+            0x01 => {
+                // InquiryComplete: [0x01, len, status]
+                need(3)?;
+                Ok(HciEvent::InquiryComplete { status: bytes[2] })
+            }
+            0x02 => {
+                // InquiryResult: [0x02, len, num_responses, bd_addr(6),
+                //   page_scan_rep_mode, reserved(2), class_of_device(3), clock_offset(2)]
+                need(17)?;
+                Ok(HciEvent::InquiryResult {
+                    address: [bytes[3], bytes[4], bytes[5], bytes[6], bytes[7], bytes[8]],
+                    class_of_device: u32::from_le_bytes([bytes[12], bytes[13], bytes[14], 0]),
+                })
+            }
+            0x03 => {
+                // ConnectionComplete: [0x03, len, status, handle(2), bd_addr(6),
+                //   link_type, encryption_enabled]
+                need(13)?;
+                Ok(HciEvent::ConnectionComplete {
+                    status: bytes[2],
+                    handle: handle(bytes[3], bytes[4]),
+                    address: [bytes[5], bytes[6], bytes[7], bytes[8], bytes[9], bytes[10]],
+                })
+            }
+            0x05 => {
+                // DisconnectionComplete: [0x05, len, status, handle(2), reason]
+                need(6)?;
+                Ok(HciEvent::DisconnectionComplete {
+                    status: bytes[2],
+                    handle: handle(bytes[3], bytes[4]),
+                    reason: bytes[5],
+                })
+            }
+            0x06 => {
+                // AuthenticationComplete: [0x06, len, status, handle(2)]
+                need(5)?;
+                Ok(HciEvent::AuthenticationComplete {
+                    status: bytes[2],
+                    handle: handle(bytes[3], bytes[4]),
+                })
+            }
             0x0E => {
                 // CommandComplete:
                 //   [event_code=0x0E, param_len, num_hci_cmds, opcode_lo, opcode_hi, status]
@@ -247,12 +583,18 @@ impl HciPacket {
                 // bytes[3] = opcode_lo
                 // bytes[4] = opcode_hi
                 // bytes[5] = status
-                if bytes.len() < 6 {
-                    return Err(HciError::PacketTooShort);
-                }
+                need(6)?;
                 let status = bytes[5];
                 Ok(HciEvent::CommandComplete { status })
             }
+            0x0F => {
+                // CommandStatus: [0x0F, len, status, num_hci_cmds, opcode_lo, opcode_hi]
+                need(6)?;
+                Ok(HciEvent::CommandStatus {
+                    status: bytes[2],
+                    opcode: u16::from_le_bytes([bytes[4], bytes[5]]),
+                })
+            }
             _ => Err(HciError::PacketTooShort),
         }
     }
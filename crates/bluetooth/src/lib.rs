@@ -1,10 +1,24 @@
 //! Bluetooth audio/control — STM32WB55 HCI interface, BLE Audio (LE Audio, LC3).
 //!
-//! This crate is `no_std` by default; it only uses `core` + `heapless`.
+//! This crate is `no_std` by default; it only uses `core`, `heapless`,
+//! `crc32fast` and the `platform` traits.
+//! The `std` feature adds `sim`, a virtual HCI controller for host tests, and
+//! `companion_sim`, a simulated companion app session.
+//!
+//! `gatt` specifies the companion app protocol and `companion` serves it;
+//! `transfer` receives files from the app over it.
 
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 // TODO: Add rustdoc to all public items (tracked as tech debt)
 #![allow(missing_docs)]
 
+pub mod companion;
+pub mod gatt;
 pub mod hci;
 pub mod state;
+pub mod transfer;
+
+#[cfg(feature = "std")]
+pub mod companion_sim;
+#[cfg(feature = "std")]
+pub mod sim;
@@ -0,0 +1,552 @@
+//! Virtual HCI controller for host-side tests.
+//!
+//! [`VirtualController`] stands in for the STM32WB55 wireless co-processor.
+//! It accepts H4 command packets, answers with H4 event packets derived from a
+//! scripted set of [`SimPeer`]s, and implements
+//! [`platform::BluetoothAdapter`], so the host logic above the HCI transport
+//! (connection tracking, pairing, reconnects) runs unchanged on a desktop.
+//!
+//! The simulation is deterministic: events are queued synchronously in
+//! command order, and time only moves when a test calls
+//! [`VirtualController::advance`].
+//!
+//! What it covers:
+//!
+//! - **Inquiry** — every discoverable peer answers with an Inquiry Result,
+//!   followed by Inquiry Complete.
+//! - **Connections** — Create Connection to a known peer succeeds; an unknown
+//!   address fails with [`status::PAGE_TIMEOUT`].  While advertising, a peer
+//!   can connect to us via [`VirtualController::peer_connects`].
+//! - **Pairing** — Authentication Requested completes according to the
+//!   peer's [`Pairing`] behaviour.
+//! - **Link loss** — a peer can drop the link after a delay
+//!   ([`SimPeer::drop_after_ms`]), or a test drops it at once with
+//!   [`VirtualController::drop_link`].
+//! - **A2DP sink acceptance** — [`VirtualController::open_a2dp_stream`]
+//!   stands in for AVDTP stream setup on an authenticated link; only peers
+//!   that are A2DP sinks accept it.
+//!
+//! One link at a time, as the player only ever talks to a single headset.
+//! Every link is reported with the BR/EDR Connection Complete event, LE
+//! connections included.
+//!
+//! # Example
+//! ```
+//! use bluetooth::hci::{HciEvent, HciPacket};
+//! use bluetooth::sim::{SimPeer, VirtualController};
+//! use platform::BluetoothAdapter;
+//!
+//! let headset = [0x11, 0x22, 0x33, 0x44, 0x55, 0x66];
+//! let mut ctrl = VirtualController::new();
+//! ctrl.add_peer(SimPeer::headphones(headset));
+//!
+//! embassy_futures::block_on(async {
+//!     ctrl.init().await.unwrap();
+//!     ctrl.start_advertising("Soul Listener").await.unwrap();
+//! });
+//! ctrl.peer_connects(headset).unwrap();
+//! assert!(ctrl.is_connected());
+//!
+//! let last = std::iter::from_fn(|| ctrl.poll_event()).last().unwrap();
+//! assert!(matches!(
+//!     HciPacket::parse(&last),
+//!     Ok(HciEvent::ConnectionComplete { address, .. }) if address == headset
+//! ));
+//! ```
+
+use std::collections::VecDeque;
+use std::string::String;
+use std::vec::Vec;
+
+use platform::BluetoothAdapter;
+
+use crate::hci::{status, HciCommand, HciError, HciEventCode, HciPacket};
+
+/// Longest local name that fits in legacy advertising data
+/// (31 bytes minus the 2-byte AD structure header).
+pub const MAX_ADVERTISED_NAME_LEN: usize = 29;
+
+/// Class of Device of stereo headphones: Audio/Video major class, headphones
+/// minor class, rendering and audio service bits.
+pub const COD_HEADPHONES: u32 = 0x24_0418;
+
+/// First connection handle handed out after a reset.
+const FIRST_HANDLE: u16 = 0x0040;
+
+/// How a peer answers a pairing request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pairing {
+    /// Pairing succeeds and the link is authenticated.
+    Accept,
+    /// The peer refuses to pair ([`status::PAIRING_NOT_ALLOWED`]).
+    Reject,
+    /// The stored link keys do not match ([`status::AUTHENTICATION_FAILURE`]).
+    WrongKey,
+}
+
+/// A scripted remote device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimPeer {
+    /// BD_ADDR, little-endian as on the wire.
+    pub address: [u8; 6],
+    /// 24-bit Class of Device reported in Inquiry Result.
+    pub class_of_device: u32,
+    /// Whether the peer answers inquiries.
+    pub discoverable: bool,
+    /// Outcome of Authentication Requested.
+    pub pairing: Pairing,
+    /// Whether the peer accepts an A2DP stream.
+    pub a2dp_sink: bool,
+    /// Drop the link (supervision timeout) this long after it comes up.
+    pub drop_after_ms: Option<u64>,
+}
+
+impl SimPeer {
+    /// Discoverable headphones that pair and accept an A2DP stream.
+    pub fn headphones(address: [u8; 6]) -> Self {
+        Self {
+            address,
+            class_of_device: COD_HEADPHONES,
+            discoverable: true,
+            pairing: Pairing::Accept,
+            a2dp_sink: true,
+            drop_after_ms: None,
+        }
+    }
+
+    /// Set how the peer answers pairing.
+    #[must_use]
+    pub fn with_pairing(mut self, pairing: Pairing) -> Self {
+        self.pairing = pairing;
+        self
+    }
+
+    /// Set whether the peer accepts an A2DP stream.
+    #[must_use]
+    pub fn with_a2dp_sink(mut self, a2dp_sink: bool) -> Self {
+        self.a2dp_sink = a2dp_sink;
+        self
+    }
+
+    /// Drop every link to this peer `ms` after it is established.
+    #[must_use]
+    pub fn dropping_after(mut self, ms: u64) -> Self {
+        self.drop_after_ms = Some(ms);
+        self
+    }
+
+    /// Stop the peer from answering inquiries (it stays connectable).
+    #[must_use]
+    pub fn hidden(mut self) -> Self {
+        self.discoverable = false;
+        self
+    }
+}
+
+/// Errors returned by [`VirtualController`].
+///
+/// Failures a real controller reports over HCI (page timeout, pairing
+/// refused, …) arrive as event status codes instead.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SimError {
+    /// The command packet could not be parsed.
+    Hci(HciError),
+    /// [`BluetoothAdapter::init`] has not been called.
+    NotInitialised,
+    /// The advertised name exceeds [`MAX_ADVERTISED_NAME_LEN`].
+    NameTooLong,
+    /// A peer tried to connect while we were not advertising.
+    NotAdvertising,
+    /// No scripted peer has this address.
+    UnknownPeer,
+    /// A link is already up.
+    LinkBusy,
+    /// No link has this handle.
+    NoSuchConnection,
+    /// A2DP needs an authenticated link.
+    NotAuthenticated,
+    /// The peer is not an A2DP sink.
+    A2dpRejected,
+}
+
+impl From<HciError> for SimError {
+    fn from(e: HciError) -> Self {
+        SimError::Hci(e)
+    }
+}
+
+/// The single ACL link.
+#[derive(Debug, Clone, Copy)]
+struct Link {
+    handle: u16,
+    address: [u8; 6],
+    authenticated: bool,
+    streaming: bool,
+    drop_at_ms: Option<u64>,
+}
+
+/// Deterministic stand-in for the STM32WB55 HCI controller.
+#[derive(Debug)]
+pub struct VirtualController {
+    peers: Vec<SimPeer>,
+    events: VecDeque<Vec<u8>>,
+    initialised: bool,
+    advertising: bool,
+    local_name: String,
+    link: Option<Link>,
+    next_handle: u16,
+    now_ms: u64,
+}
+
+impl VirtualController {
+    /// A controller with no peers, waiting for HCI_Reset.
+    pub fn new() -> Self {
+        Self {
+            peers: Vec::new(),
+            events: VecDeque::new(),
+            initialised: false,
+            advertising: false,
+            local_name: String::new(),
+            link: None,
+            next_handle: FIRST_HANDLE,
+            now_ms: 0,
+        }
+    }
+
+    /// Add a remote device to the simulated air.
+    pub fn add_peer(&mut self, peer: SimPeer) {
+        self.peers.push(peer);
+    }
+
+    /// Hand an H4 command packet to the controller.
+    ///
+    /// The answer (Command Complete or Command Status, plus any follow-up
+    /// events) is queued for [`poll_event`](Self::poll_event).  Commands with
+    /// an unknown opcode are answered with [`status::UNKNOWN_HCI_COMMAND`],
+    /// as a real controller would.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SimError::Hci`] for a malformed packet.
+    pub fn send_command(&mut self, packet: &[u8]) -> Result<(), SimError> {
+        match HciPacket::parse_command(packet) {
+            Ok(cmd) => {
+                self.execute(cmd);
+                Ok(())
+            }
+            Err(HciError::UnknownOpcode(opcode)) => {
+                self.command_complete(opcode, status::UNKNOWN_HCI_COMMAND);
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Next H4 event packet, oldest first.
+    pub fn poll_event(&mut self) -> Option<Vec<u8>> {
+        self.events.pop_front()
+    }
+
+    /// Number of events waiting to be polled.
+    pub fn pending_events(&self) -> usize {
+        self.events.len()
+    }
+
+    /// A scripted peer connects to us while we advertise.
+    ///
+    /// Advertising stops, as it does on the real controller once a
+    /// connection is made.  Returns the new connection handle.
+    ///
+    /// # Errors
+    ///
+    /// [`SimError::NotAdvertising`], [`SimError::LinkBusy`] or
+    /// [`SimError::UnknownPeer`].
+    pub fn peer_connects(&mut self, address: [u8; 6]) -> Result<u16, SimError> {
+        if !self.advertising {
+            return Err(SimError::NotAdvertising);
+        }
+        if self.link.is_some() {
+            return Err(SimError::LinkBusy);
+        }
+        if self.peer(address).is_none() {
+            return Err(SimError::UnknownPeer);
+        }
+        self.advertising = false;
+        Ok(self.open_link(address))
+    }
+
+    /// Drop the current link with `reason` (e.g.
+    /// [`status::CONNECTION_TIMEOUT`] for a walk out of range).
+    ///
+    /// Returns `false` if there was no link.
+    pub fn drop_link(&mut self, reason: u8) -> bool {
+        let had_link = self.link.is_some();
+        self.close_link(reason);
+        had_link
+    }
+
+    /// Move simulated time forward, firing any scheduled link drops.
+    pub fn advance(&mut self, ms: u64) {
+        self.now_ms = self.now_ms.saturating_add(ms);
+        if self
+            .link
+            .and_then(|link| link.drop_at_ms)
+            .is_some_and(|at| at <= self.now_ms)
+        {
+            self.close_link(status::CONNECTION_TIMEOUT);
+        }
+    }
+
+    /// Set up an A2DP stream to the peer on link `handle`.
+    ///
+    /// # Errors
+    ///
+    /// [`SimError::NoSuchConnection`], [`SimError::NotAuthenticated`] or
+    /// [`SimError::A2dpRejected`] when the peer is not a sink.
+    pub fn open_a2dp_stream(&mut self, handle: u16) -> Result<(), SimError> {
+        let link = self
+            .link
+            .filter(|link| link.handle == handle)
+            .ok_or(SimError::NoSuchConnection)?;
+        if !link.authenticated {
+            return Err(SimError::NotAuthenticated);
+        }
+        if !self.peer(link.address).is_some_and(|peer| peer.a2dp_sink) {
+            return Err(SimError::A2dpRejected);
+        }
+        if let Some(link) = &mut self.link {
+            link.streaming = true;
+        }
+        Ok(())
+    }
+
+    /// Whether LE advertising is enabled.
+    pub fn is_advertising(&self) -> bool {
+        self.advertising
+    }
+
+    /// Name passed to the last [`BluetoothAdapter::start_advertising`].
+    pub fn local_name(&self) -> &str {
+        &self.local_name
+    }
+
+    /// Handle of the current link.
+    pub fn connection_handle(&self) -> Option<u16> {
+        self.link.map(|link| link.handle)
+    }
+
+    /// Whether the current link completed pairing.
+    pub fn is_authenticated(&self) -> bool {
+        self.link.is_some_and(|link| link.authenticated)
+    }
+
+    /// Whether an A2DP stream is open on the current link.
+    pub fn is_streaming(&self) -> bool {
+        self.link.is_some_and(|link| link.streaming)
+    }
+
+    /// Simulated time since creation, in milliseconds.
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+
+    fn command(&mut self, cmd: HciCommand) -> Result<(), SimError> {
+        let packet = HciPacket::from_command(cmd)?;
+        self.send_command(&packet)
+    }
+
+    fn execute(&mut self, cmd: HciCommand) {
+        if !self.initialised && cmd != HciCommand::Reset {
+            self.reply(cmd, status::COMMAND_DISALLOWED);
+            return;
+        }
+
+        match cmd {
+            HciCommand::Reset => {
+                // A reset forgets links and queued events but not the air.
+                let peers = core::mem::take(&mut self.peers);
+                *self = Self {
+                    peers,
+                    initialised: true,
+                    now_ms: self.now_ms,
+                    ..Self::new()
+                };
+                self.reply(cmd, status::SUCCESS);
+            }
+            HciCommand::Inquiry { .. } => {
+                self.reply(cmd, status::SUCCESS);
+                let results: Vec<_> = self
+                    .peers
+                    .iter()
+                    .filter(|peer| peer.discoverable)
+                    .map(inquiry_result)
+                    .collect();
+                self.events.extend(results);
+                self.push_event(HciEventCode::InquiryComplete, &[status::SUCCESS]);
+            }
+            HciCommand::CreateConnection { address } => {
+                if self.link.is_some() {
+                    self.reply(cmd, status::CONNECTION_ALREADY_EXISTS);
+                    return;
+                }
+                self.reply(cmd, status::SUCCESS);
+                if self.peer(address).is_some() {
+                    self.open_link(address);
+                } else {
+                    self.connection_complete(status::PAGE_TIMEOUT, 0, address);
+                }
+            }
+            HciCommand::Disconnect { handle, .. } => {
+                if self.connection_handle() != Some(handle) {
+                    self.reply(cmd, status::UNKNOWN_CONNECTION_ID);
+                    return;
+                }
+                self.reply(cmd, status::SUCCESS);
+                self.close_link(status::TERMINATED_BY_LOCAL_HOST);
+            }
+            HciCommand::AuthenticationRequested { handle } => {
+                let Some(link) = self.link.filter(|link| link.handle == handle) else {
+                    self.reply(cmd, status::UNKNOWN_CONNECTION_ID);
+                    return;
+                };
+                self.reply(cmd, status::SUCCESS);
+                let outcome = match self.peer(link.address).map(|peer| peer.pairing) {
+                    Some(Pairing::Accept) => status::SUCCESS,
+                    Some(Pairing::Reject) => status::PAIRING_NOT_ALLOWED,
+                    Some(Pairing::WrongKey) | None => status::AUTHENTICATION_FAILURE,
+                };
+                if let Some(link) = &mut self.link {
+                    link.authenticated = outcome == status::SUCCESS;
+                }
+                let [h0, h1] = handle.to_le_bytes();
+                self.push_event(HciEventCode::AuthenticationComplete, &[outcome, h0, h1]);
+            }
+            HciCommand::LeSetAdvertisingEnable(enable) => {
+                self.advertising = enable;
+                self.reply(cmd, status::SUCCESS);
+            }
+        }
+    }
+
+    fn peer(&self, address: [u8; 6]) -> Option<&SimPeer> {
+        self.peers.iter().find(|peer| peer.address == address)
+    }
+
+    fn open_link(&mut self, address: [u8; 6]) -> u16 {
+        let handle = self.next_handle;
+        self.next_handle = handle.wrapping_add(1) & 0x0FFF;
+        let drop_at_ms = self
+            .peer(address)
+            .and_then(|peer| peer.drop_after_ms)
+            .map(|after| self.now_ms.saturating_add(after));
+        self.link = Some(Link {
+            handle,
+            address,
+            authenticated: false,
+            streaming: false,
+            drop_at_ms,
+        });
+        self.connection_complete(status::SUCCESS, handle, address);
+        handle
+    }
+
+    fn close_link(&mut self, reason: u8) {
+        if let Some(link) = self.link.take() {
+            let [h0, h1] = link.handle.to_le_bytes();
+            self.push_event(
+                HciEventCode::DisconnectionComplete,
+                &[status::SUCCESS, h0, h1, reason],
+            );
+        }
+    }
+
+    fn connection_complete(&mut self, status: u8, handle: u16, address: [u8; 6]) {
+        let [h0, h1] = handle.to_le_bytes();
+        let [a0, a1, a2, a3, a4, a5] = address;
+        // Link type ACL (0x01), encryption off.
+        self.push_event(
+            HciEventCode::ConnectionComplete,
+            &[status, h0, h1, a0, a1, a2, a3, a4, a5, 0x01, 0x00],
+        );
+    }
+
+    /// Command Complete or Command Status, whichever `cmd` is answered with.
+    fn reply(&mut self, cmd: HciCommand, status: u8) {
+        if cmd.is_deferred() {
+            let [lo, hi] = cmd.opcode().to_le_bytes();
+            self.push_event(HciEventCode::CommandStatus, &[status, 1, lo, hi]);
+        } else {
+            self.command_complete(cmd.opcode(), status);
+        }
+    }
+
+    fn command_complete(&mut self, opcode: u16, status: u8) {
+        let [lo, hi] = opcode.to_le_bytes();
+        self.push_event(HciEventCode::CommandComplete, &[1, lo, hi, status]);
+    }
+
+    fn push_event(&mut self, code: HciEventCode, params: &[u8]) {
+        self.events.push_back(event_packet(code, params));
+    }
+}
+
+impl Default for VirtualController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BluetoothAdapter for VirtualController {
+    type Error = SimError;
+
+    async fn init(&mut self) -> Result<(), SimError> {
+        self.command(HciCommand::Reset)
+    }
+
+    async fn start_advertising(&mut self, name: &str) -> Result<(), SimError> {
+        if !self.initialised {
+            return Err(SimError::NotInitialised);
+        }
+        if name.len() > MAX_ADVERTISED_NAME_LEN {
+            return Err(SimError::NameTooLong);
+        }
+        self.local_name = name.into();
+        self.command(HciCommand::LeSetAdvertisingEnable(true))
+    }
+
+    async fn stop_advertising(&mut self) -> Result<(), SimError> {
+        if !self.initialised {
+            return Err(SimError::NotInitialised);
+        }
+        self.command(HciCommand::LeSetAdvertisingEnable(false))
+    }
+
+    fn is_connected(&self) -> bool {
+        self.link.is_some()
+    }
+}
+
+/// H4 event packet: `[0x04, code, param_len, ...params]`.
+fn event_packet(code: HciEventCode, params: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(params.len().saturating_add(3));
+    // Simulator events carry at most 15 parameter bytes.
+    packet.extend_from_slice(&[
+        0x04,
+        code as u8,
+        u8::try_from(params.len()).unwrap_or(u8::MAX),
+    ]);
+    packet.extend_from_slice(params);
+    packet
+}
+
+/// Single-response Inquiry Result for `peer`.
+fn inquiry_result(peer: &SimPeer) -> Vec<u8> {
+    let [a0, a1, a2, a3, a4, a5] = peer.address;
+    let [c0, c1, c2, _] = peer.class_of_device.to_le_bytes();
+    // num_responses, bd_addr, page scan repetition mode R1, reserved (2),
+    // class of device, clock offset.
+    event_packet(
+        HciEventCode::InquiryResult,
+        &[1, a0, a1, a2, a3, a4, a5, 0x01, 0, 0, c0, c1, c2, 0, 0],
+    )
+}
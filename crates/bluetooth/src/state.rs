@@ -1,5 +1,7 @@
 //! Bluetooth connection state tracker.
 
+use crate::hci::{status, HciEvent};
+
 /// Tracks whether a BLE peer is currently connected and, if so, its address.
 pub struct BluetoothState {
     connected: bool,
@@ -27,6 +29,25 @@ impl BluetoothState {
         self.peer_address = None;
     }
 
+    /// Update the state from a controller event.
+    ///
+    /// Only successful Connection Complete and Disconnection Complete events
+    /// change the state; failed attempts and unrelated events are ignored.
+    pub fn handle_event(&mut self, event: &HciEvent) {
+        match *event {
+            HciEvent::ConnectionComplete {
+                status: status::SUCCESS,
+                address,
+                ..
+            } => self.on_connected(address),
+            HciEvent::DisconnectionComplete {
+                status: status::SUCCESS,
+                ..
+            } => self.on_disconnected(),
+            _ => {}
+        }
+    }
+
     /// Returns `true` if a peer is currently connected.
     #[must_use]
     pub fn connected(&self) -> bool {
@@ -49,6 +70,7 @@ impl Default for BluetoothState {
 #[cfg(test)]
 mod tests {
     use super::BluetoothState;
+    use crate::hci::{status, HciEvent};
 
     #[test]
     fn test_bt_starts_disconnected() {
@@ -84,4 +106,28 @@ mod tests {
         let state = BluetoothState::new();
         assert_eq!(state.peer_address(), None);
     }
+
+    #[test]
+    fn test_bt_handle_event_ignores_failed_connection() {
+        let mut state = BluetoothState::new();
+        state.handle_event(&HciEvent::ConnectionComplete {
+            status: status::PAGE_TIMEOUT,
+            handle: 0,
+            address: [0x01; 6],
+        });
+        assert!(!state.connected());
+
+        state.handle_event(&HciEvent::ConnectionComplete {
+            status: status::SUCCESS,
+            handle: 0x0040,
+            address: [0x01; 6],
+        });
+        assert_eq!(state.peer_address(), Some([0x01; 6]));
+        state.handle_event(&HciEvent::DisconnectionComplete {
+            status: status::SUCCESS,
+            handle: 0x0040,
+            reason: status::CONNECTION_TIMEOUT,
+        });
+        assert!(!state.connected());
+    }
 }
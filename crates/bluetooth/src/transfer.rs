@@ -0,0 +1,1052 @@
+//! BLE file transfer — chunked, resumable, CRC-checked uploads from the
+//! companion app.
+//!
+//! The companion app uses this to send small files (library deltas: new
+//! playlists, corrected tags) without the SD card leaving the player.  All
+//! messages go over [`Characteristic::Transfer`](crate::gatt::Characteristic::Transfer):
+//! the phone writes [`TransferMessage`]s, the player answers with
+//! [`TransferResponse`] notifications.
+//!
+//! # Protocol
+//!
+//! ```text
+//! phone                                        player
+//!   Begin{kind, id, len, crc32}        ──►
+//!                                      ◄──  Ready{id, next_offset}
+//!   Chunk{offset, data} × n            ──►
+//!                                      ◄──  Ack{next_offset}  every ACK_INTERVAL bytes
+//!   Commit                             ──►
+//!                                      ◄──  Done{id, status}
+//! ```
+//!
+//! - **Chunked** — a chunk carries as much as one write holds at the
+//!   connection's MTU ([`max_chunk_len`]).  The phone sends chunks as Write
+//!   Commands and waits for an [`Ack`](TransferResponse::Ack) every
+//!   [`ACK_INTERVAL`] bytes, so it never runs more than one interval ahead.
+//! - **Resumable** — chunks land in a staging file and the receiver's
+//!   [`TransferState`] is saved with every ack.  A `Begin` with the same
+//!   kind, ID, length and CRC as the transfer in progress (after a dropped
+//!   link or a reboot) answers with the offset to continue from instead of
+//!   starting over.  A chunk at the wrong offset is answered with
+//!   [`Nak`](TransferResponse::Nak) carrying the expected one.
+//! - **CRC-checked** — the receiver keeps a running CRC32 of the received
+//!   bytes; `Commit` succeeds only if the length and CRC match the ones
+//!   announced in `Begin`.  A mismatch discards the transfer.
+//!
+//! After a successful commit the file waits in the store until the firmware
+//! has handed it to its consumer ([`TransferKind`] says which) and reported
+//! the outcome with [`TransferReceiver::finish`], which sends
+//! [`Done`](TransferResponse::Done) to the phone.
+//!
+//! # Wire format
+//!
+//! Integers are little-endian.  Writes start with an opcode:
+//!
+//! | Opcode | Message  | Payload                                           |
+//! |--------|----------|---------------------------------------------------|
+//! | `0x01` | Begin    | `[kind u8][id u32][len u32][crc32 u32]`           |
+//! | `0x02` | Chunk    | `[offset u32][data …]`                            |
+//! | `0x03` | Commit   | —                                                 |
+//! | `0x04` | Abort    | —                                                 |
+//!
+//! Notifications start with a response code:
+//!
+//! | Code   | Response | Payload                                           |
+//! |--------|----------|---------------------------------------------------|
+//! | `0x81` | Ready    | `[id u32][next_offset u32]`                       |
+//! | `0x82` | Ack      | `[next_offset u32]`                               |
+//! | `0x83` | Nak      | `[expected_offset u32]`                           |
+//! | `0x84` | Done     | `[id u32][status u8]` ([`TransferStatus`])        |
+
+use crate::gatt::{self, GattError, Reader, Value};
+
+/// Largest file the protocol carries.
+pub const MAX_TRANSFER_LEN: u32 = 64 * 1024;
+
+/// Bytes between two [`TransferResponse::Ack`]s (and state saves).
+pub const ACK_INTERVAL: u32 = 1024;
+
+/// Bytes of a chunk write before its data: opcode and offset.
+pub const CHUNK_HEADER_LEN: usize = 5;
+
+/// Bytes of an encoded [`TransferState`].
+pub const STATE_SIZE: usize = 25;
+
+/// Most data one chunk carries at `mtu`.
+#[must_use]
+pub fn max_chunk_len(mtu: u16) -> usize {
+    gatt::notify_len(mtu).saturating_sub(CHUNK_HEADER_LEN)
+}
+
+/// What a transferred file is, and so who consumes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TransferKind {
+    /// A library delta (`library::delta`).
+    LibraryDelta = 1,
+}
+
+impl TransferKind {
+    /// Decode a wire value.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::ValueNotAllowed`] for an unknown kind.
+    pub const fn from_u8(value: u8) -> Result<Self, GattError> {
+        match value {
+            1 => Ok(Self::LibraryDelta),
+            _ => Err(GattError::ValueNotAllowed),
+        }
+    }
+}
+
+/// Outcome reported in [`TransferResponse::Done`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TransferStatus {
+    /// Received, verified and applied.
+    Applied = 0,
+    /// The CRC of the received bytes does not match `Begin`; discarded.
+    CrcMismatch = 1,
+    /// `Commit` arrived before all bytes; the transfer can be resumed.
+    Incomplete = 2,
+    /// The file arrived intact but its consumer refused it (malformed, or
+    /// made for another library); discarded.
+    Rejected = 3,
+    /// The player could not store or apply the file; discarded.
+    StorageError = 4,
+}
+
+impl TransferStatus {
+    /// Decode a wire value.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::ValueNotAllowed`] for an unknown status.
+    pub const fn from_u8(value: u8) -> Result<Self, GattError> {
+        match value {
+            0 => Ok(Self::Applied),
+            1 => Ok(Self::CrcMismatch),
+            2 => Ok(Self::Incomplete),
+            3 => Ok(Self::Rejected),
+            4 => Ok(Self::StorageError),
+            _ => Err(GattError::ValueNotAllowed),
+        }
+    }
+}
+
+/// A write to the transfer characteristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferMessage<'a> {
+    /// Start or resume a transfer.
+    Begin {
+        /// What the file is.
+        kind: TransferKind,
+        /// Phone-chosen ID; a resume must reuse it.
+        id: u32,
+        /// Total length in bytes.
+        len: u32,
+        /// CRC32 (IEEE) of the whole file.
+        crc32: u32,
+    },
+    /// File bytes from `offset` on.
+    Chunk {
+        /// Offset of `data[0]` in the file.
+        offset: u32,
+        /// The bytes.
+        data: &'a [u8],
+    },
+    /// All bytes sent; verify and hand over the file.
+    Commit,
+    /// Drop the transfer in progress.
+    Abort,
+}
+
+impl<'a> TransferMessage<'a> {
+    /// Encode as the phone writes it.  A chunk's data is cut to
+    /// [`gatt::MAX_VALUE_LEN`].
+    #[must_use]
+    pub fn encode(&self) -> Value {
+        let mut out = Value::new();
+        match *self {
+            Self::Begin {
+                kind,
+                id,
+                len,
+                crc32,
+            } => {
+                gatt::push(&mut out, &[0x01, kind as u8]);
+                gatt::push(&mut out, &id.to_le_bytes());
+                gatt::push(&mut out, &len.to_le_bytes());
+                gatt::push(&mut out, &crc32.to_le_bytes());
+            }
+            Self::Chunk { offset, data } => {
+                gatt::push(&mut out, &[0x02]);
+                gatt::push(&mut out, &offset.to_le_bytes());
+                let room = gatt::MAX_VALUE_LEN.saturating_sub(CHUNK_HEADER_LEN);
+                gatt::push(&mut out, data.get(..room).unwrap_or(data));
+            }
+            Self::Commit => gatt::push(&mut out, &[0x03]),
+            Self::Abort => gatt::push(&mut out, &[0x04]),
+        }
+        out
+    }
+
+    /// Decode a write.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::UnknownOpcode`], [`GattError::InvalidLength`], or
+    /// [`GattError::ValueNotAllowed`] for an unknown kind.
+    pub fn decode(bytes: &'a [u8]) -> Result<Self, GattError> {
+        let mut r = Reader::new(bytes);
+        let message = match r.u8()? {
+            0x01 => Self::Begin {
+                kind: TransferKind::from_u8(r.u8()?)?,
+                id: r.u32()?,
+                len: r.u32()?,
+                crc32: r.u32()?,
+            },
+            0x02 => {
+                let offset = r.u32()?;
+                let data = r.rest();
+                if data.is_empty() {
+                    return Err(GattError::InvalidLength);
+                }
+                Self::Chunk { offset, data }
+            }
+            0x03 => Self::Commit,
+            0x04 => Self::Abort,
+            other => return Err(GattError::UnknownOpcode(other)),
+        };
+        r.finish()?;
+        Ok(message)
+    }
+}
+
+/// A notification on the transfer characteristic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferResponse {
+    /// Answer to `Begin`: send from `next_offset` (0 for a new transfer).
+    Ready {
+        /// The transfer's ID.
+        id: u32,
+        /// First byte the player does not have.
+        next_offset: u32,
+    },
+    /// Everything before `next_offset` is stored.
+    Ack {
+        /// First byte the player does not have.
+        next_offset: u32,
+    },
+    /// A chunk arrived at the wrong offset; continue from `expected`.
+    Nak {
+        /// First byte the player does not have.
+        expected: u32,
+    },
+    /// The transfer ended.
+    Done {
+        /// The transfer's ID.
+        id: u32,
+        /// How it ended.
+        status: TransferStatus,
+    },
+}
+
+impl TransferResponse {
+    /// Encode the notification.
+    #[must_use]
+    pub fn encode(&self) -> Value {
+        let mut out = Value::new();
+        match *self {
+            Self::Ready { id, next_offset } => {
+                gatt::push(&mut out, &[0x81]);
+                gatt::push(&mut out, &id.to_le_bytes());
+                gatt::push(&mut out, &next_offset.to_le_bytes());
+            }
+            Self::Ack { next_offset } => {
+                gatt::push(&mut out, &[0x82]);
+                gatt::push(&mut out, &next_offset.to_le_bytes());
+            }
+            Self::Nak { expected } => {
+                gatt::push(&mut out, &[0x83]);
+                gatt::push(&mut out, &expected.to_le_bytes());
+            }
+            Self::Done { id, status } => {
+                gatt::push(&mut out, &[0x84]);
+                gatt::push(&mut out, &id.to_le_bytes());
+                gatt::push(&mut out, &[status as u8]);
+            }
+        }
+        out
+    }
+
+    /// Decode a notification.
+    ///
+    /// # Errors
+    ///
+    /// [`GattError::UnknownOpcode`], [`GattError::InvalidLength`], or
+    /// [`GattError::ValueNotAllowed`] for an unknown status.
+    pub fn decode(bytes: &[u8]) -> Result<Self, GattError> {
+        let mut r = Reader::new(bytes);
+        let response = match r.u8()? {
+            0x81 => Self::Ready {
+                id: r.u32()?,
+                next_offset: r.u32()?,
+            },
+            0x82 => Self::Ack {
+                next_offset: r.u32()?,
+            },
+            0x83 => Self::Nak { expected: r.u32()? },
+            0x84 => Self::Done {
+                id: r.u32()?,
+                status: TransferStatus::from_u8(r.u8()?)?,
+            },
+            other => return Err(GattError::UnknownOpcode(other)),
+        };
+        r.finish()?;
+        Ok(response)
+    }
+}
+
+/// Progress of the transfer in progress, saved so it survives a reboot.
+///
+/// Layout ([`STATE_SIZE`] bytes): `[kind u8][id u32][len u32][crc32 u32]
+/// [received u32][running_crc u32][state_crc u32]`, the last field being
+/// the CRC32 of the 21 bytes before it so a torn save is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferState {
+    /// What the file is.
+    pub kind: TransferKind,
+    /// Phone-chosen transfer ID.
+    pub id: u32,
+    /// Announced length.
+    pub len: u32,
+    /// Announced CRC32.
+    pub crc32: u32,
+    /// Bytes stored so far.
+    pub received: u32,
+    /// CRC32 of the bytes stored so far.
+    pub running_crc: u32,
+}
+
+impl TransferState {
+    /// Encode for the store.
+    #[must_use]
+    pub fn encode(&self) -> [u8; STATE_SIZE] {
+        let mut out = Value::new();
+        gatt::push(&mut out, &[self.kind as u8]);
+        for field in [
+            self.id,
+            self.len,
+            self.crc32,
+            self.received,
+            self.running_crc,
+        ] {
+            gatt::push(&mut out, &field.to_le_bytes());
+        }
+        let crc = crc32fast::hash(&out);
+        gatt::push(&mut out, &crc.to_le_bytes());
+        let mut bytes = [0u8; STATE_SIZE];
+        for (dst, src) in bytes.iter_mut().zip(out.iter()) {
+            *dst = *src;
+        }
+        bytes
+    }
+
+    /// Decode a saved state; `None` when it is torn or corrupt.
+    #[must_use]
+    pub fn decode(bytes: &[u8; STATE_SIZE]) -> Option<Self> {
+        let (body, crc) = bytes.split_at(STATE_SIZE.saturating_sub(4));
+        let mut r = Reader::new(crc);
+        if r.u32().ok()? != crc32fast::hash(body) {
+            return None;
+        }
+        let mut r = Reader::new(body);
+        let state = Self {
+            kind: TransferKind::from_u8(r.u8().ok()?).ok()?,
+            id: r.u32().ok()?,
+            len: r.u32().ok()?,
+            crc32: r.u32().ok()?,
+            received: r.u32().ok()?,
+            running_crc: r.u32().ok()?,
+        };
+        (state.received <= state.len).then_some(state)
+    }
+
+    /// Whether every byte has arrived.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.received == self.len
+    }
+}
+
+/// A file that passed its length and CRC check, waiting for
+/// [`TransferReceiver::finish`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Completed {
+    /// What the file is.
+    pub kind: TransferKind,
+    /// Phone-chosen transfer ID.
+    pub id: u32,
+    /// Length in bytes.
+    pub len: u32,
+}
+
+/// Where a transfer's bytes and state are kept.
+///
+/// The hardware implementation maps onto two files on the SD card
+/// ([`transfer_part_path`](platform::soul_library::transfer_part_path) and
+/// [`transfer_state_path`](platform::soul_library::transfer_state_path));
+/// the emulator and tests use [`MemoryTransferStore`].
+///
+/// # Durability contract
+///
+/// `write_at` must have made the data durable before it returns: the
+/// receiver saves a state claiming those bytes right after.
+#[allow(async_fn_in_trait)]
+pub trait TransferStore {
+    /// Storage error type.
+    type Error: core::fmt::Debug;
+
+    /// Read the saved state, `None` when there is none.
+    async fn load_state(&mut self) -> Result<Option<[u8; STATE_SIZE]>, Self::Error>;
+
+    /// Replace the saved state; `None` deletes it.
+    async fn save_state(&mut self, state: Option<&[u8; STATE_SIZE]>) -> Result<(), Self::Error>;
+
+    /// Cut the staged file to `len` bytes (0 to discard it).
+    async fn truncate(&mut self, len: u32) -> Result<(), Self::Error>;
+
+    /// Write `data` at `offset` of the staged file.  `offset` is never
+    /// past its end.
+    async fn write_at(&mut self, offset: u32, data: &[u8]) -> Result<(), Self::Error>;
+
+    /// Read staged bytes from `offset` into `buf`.  Returns the number of
+    /// bytes read (0 at the end).
+    async fn read_at(&mut self, offset: u32, buf: &mut [u8]) -> Result<usize, Self::Error>;
+}
+
+/// Errors returned by [`TransferReceiver::write`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferError<E> {
+    /// The write is malformed or not allowed now; answer it with
+    /// [`GattError::att_code`].
+    Gatt(GattError),
+    /// The store failed.  The transfer in progress is unchanged, and the
+    /// phone can resume it.
+    Store(E),
+}
+
+impl<E> From<GattError> for TransferError<E> {
+    fn from(e: GattError) -> Self {
+        Self::Gatt(e)
+    }
+}
+
+/// Player side of the transfer protocol.  See the module docs.
+#[derive(Debug, Clone)]
+pub struct TransferReceiver<S: TransferStore> {
+    store: S,
+    max_len: u32,
+    active: Option<TransferState>,
+    /// Bytes received since the last ack.
+    unacked: u32,
+    completed: Option<Completed>,
+    response: Option<TransferResponse>,
+}
+
+impl<S: TransferStore> TransferReceiver<S> {
+    /// Open the receiver on `store`, picking up a transfer interrupted by a
+    /// reboot.  Files longer than `max_len` (at most
+    /// [`MAX_TRANSFER_LEN`]) are refused.
+    ///
+    /// # Errors
+    ///
+    /// The store's error if the state cannot be read or the staged file
+    /// cannot be cut back to the saved length.
+    pub async fn open(mut store: S, max_len: u32) -> Result<Self, S::Error> {
+        let active = match store.load_state().await? {
+            Some(bytes) => TransferState::decode(&bytes),
+            None => None,
+        };
+        // Bytes past the last saved state were never acknowledged.
+        store
+            .truncate(active.map_or(0, |state| state.received))
+            .await?;
+        Ok(Self {
+            store,
+            max_len: max_len.min(MAX_TRANSFER_LEN),
+            active,
+            unacked: 0,
+            completed: None,
+            response: None,
+        })
+    }
+
+    /// The transfer in progress.
+    #[must_use]
+    pub fn active(&self) -> Option<TransferState> {
+        self.active
+    }
+
+    /// The verified file waiting for [`finish`](Self::finish).
+    #[must_use]
+    pub fn completed(&self) -> Option<Completed> {
+        self.completed
+    }
+
+    /// The notification to send, if the last write produced one.
+    pub fn take_response(&mut self) -> Option<TransferResponse> {
+        self.response.take()
+    }
+
+    /// The backing store.
+    pub fn store_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+
+    /// Handle a write to the transfer characteristic.
+    ///
+    /// # Errors
+    ///
+    /// [`TransferError::Gatt`] for a malformed write, a `Begin` that is too
+    /// long or arrives while a verified file is still waiting, or a chunk
+    /// or commit without a transfer; [`TransferError::Store`] when storage
+    /// fails.
+    pub async fn write(&mut self, value: &[u8]) -> Result<(), TransferError<S::Error>> {
+        match TransferMessage::decode(value)? {
+            TransferMessage::Begin {
+                kind,
+                id,
+                len,
+                crc32,
+            } => self.begin(kind, id, len, crc32).await,
+            TransferMessage::Chunk { offset, data } => self.chunk(offset, data).await,
+            TransferMessage::Commit => self.commit().await,
+            TransferMessage::Abort => {
+                self.discard().await.map_err(TransferError::Store)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Read the verified file into `buf`, returning the filled prefix.
+    ///
+    /// # Errors
+    ///
+    /// [`TransferError::Gatt`] with [`GattError::InvalidLength`] when
+    /// nothing is waiting, `buf` is too small, or the staged file is
+    /// shorter than verified; the store's error when reading fails.
+    pub async fn read_completed<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+    ) -> Result<&'b [u8], TransferError<S::Error>> {
+        let len = self
+            .completed
+            .map(|c| usize::try_from(c.len).unwrap_or(usize::MAX))
+            .ok_or(GattError::InvalidLength)?;
+        let file = buf.get_mut(..len).ok_or(GattError::InvalidLength)?;
+        let mut filled = 0_usize;
+        while let Some(rest) = file.get_mut(filled..).filter(|r| !r.is_empty()) {
+            let offset = u32::try_from(filled).map_err(|_| GattError::InvalidLength)?;
+            let n = self
+                .store
+                .read_at(offset, rest)
+                .await
+                .map_err(TransferError::Store)?;
+            if n == 0 {
+                return Err(GattError::InvalidLength.into());
+            }
+            filled = filled.saturating_add(n);
+        }
+        Ok(file)
+    }
+
+    /// Report what became of the verified file and discard it.  Queues
+    /// [`TransferResponse::Done`] with `status` for the phone.
+    ///
+    /// # Errors
+    ///
+    /// The store's error if the staged file or state cannot be removed.
+    pub async fn finish(&mut self, status: TransferStatus) -> Result<(), S::Error> {
+        let Some(completed) = self.completed.take() else {
+            return Ok(());
+        };
+        self.response = Some(TransferResponse::Done {
+            id: completed.id,
+            status,
+        });
+        self.store.truncate(0).await?;
+        self.store.save_state(None).await
+    }
+
+    async fn begin(
+        &mut self,
+        kind: TransferKind,
+        id: u32,
+        len: u32,
+        crc32: u32,
+    ) -> Result<(), TransferError<S::Error>> {
+        if self.completed.is_some() || len == 0 || len > self.max_len {
+            return Err(GattError::ValueNotAllowed.into());
+        }
+        let resumable = self.active.filter(|state| {
+            state.kind == kind && state.id == id && state.len == len && state.crc32 == crc32
+        });
+        let state = match resumable {
+            Some(state) => state,
+            None => {
+                let state = TransferState {
+                    kind,
+                    id,
+                    len,
+                    crc32,
+                    received: 0,
+                    running_crc: 0,
+                };
+                self.store.truncate(0).await.map_err(TransferError::Store)?;
+                self.store
+                    .save_state(Some(&state.encode()))
+                    .await
+                    .map_err(TransferError::Store)?;
+                self.active = Some(state);
+                state
+            }
+        };
+        self.unacked = 0;
+        self.response = Some(TransferResponse::Ready {
+            id,
+            next_offset: state.received,
+        });
+        Ok(())
+    }
+
+    async fn chunk(&mut self, offset: u32, data: &[u8]) -> Result<(), TransferError<S::Error>> {
+        let mut state = self.active.ok_or(GattError::ValueNotAllowed)?;
+        if offset != state.received {
+            self.response = Some(TransferResponse::Nak {
+                expected: state.received,
+            });
+            return Ok(());
+        }
+        let data_len = u32::try_from(data.len()).map_err(|_| GattError::InvalidLength)?;
+        let end = offset
+            .checked_add(data_len)
+            .filter(|&end| end <= state.len)
+            .ok_or(GattError::InvalidLength)?;
+        self.store
+            .write_at(offset, data)
+            .await
+            .map_err(TransferError::Store)?;
+        let mut hasher = crc32fast::Hasher::new_with_initial(state.running_crc);
+        hasher.update(data);
+        state.running_crc = hasher.finalize();
+        state.received = end;
+        self.active = Some(state);
+
+        self.unacked = self.unacked.saturating_add(data_len);
+        if self.unacked >= ACK_INTERVAL || state.is_complete() {
+            self.store
+                .save_state(Some(&state.encode()))
+                .await
+                .map_err(TransferError::Store)?;
+            self.unacked = 0;
+            self.response = Some(TransferResponse::Ack { next_offset: end });
+        }
+        Ok(())
+    }
+
+    async fn commit(&mut self) -> Result<(), TransferError<S::Error>> {
+        let state = self.active.ok_or(GattError::ValueNotAllowed)?;
+        if !state.is_complete() {
+            self.response = Some(TransferResponse::Done {
+                id: state.id,
+                status: TransferStatus::Incomplete,
+            });
+            return Ok(());
+        }
+        if state.running_crc != state.crc32 {
+            self.discard().await.map_err(TransferError::Store)?;
+            self.response = Some(TransferResponse::Done {
+                id: state.id,
+                status: TransferStatus::CrcMismatch,
+            });
+            return Ok(());
+        }
+        self.active = None;
+        self.completed = Some(Completed {
+            kind: state.kind,
+            id: state.id,
+            len: state.len,
+        });
+        Ok(())
+    }
+
+    async fn discard(&mut self) -> Result<(), S::Error> {
+        self.active = None;
+        self.unacked = 0;
+        self.store.truncate(0).await?;
+        self.store.save_state(None).await
+    }
+}
+
+/// Error returned by [`MemoryTransferStore`] when its capacity is exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryTransferFull;
+
+/// RAM-backed [`TransferStore`] for the emulator and host tests, holding up
+/// to `CAP` staged bytes.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTransferStore<const CAP: usize> {
+    data: heapless::Vec<u8, CAP>,
+    state: Option<[u8; STATE_SIZE]>,
+}
+
+impl<const CAP: usize> MemoryTransferStore<CAP> {
+    /// Create an empty store.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            data: heapless::Vec::new(),
+            state: None,
+        }
+    }
+
+    /// The staged bytes.
+    #[must_use]
+    pub fn staged(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Whether a state is saved.
+    #[must_use]
+    pub fn has_state(&self) -> bool {
+        self.state.is_some()
+    }
+
+    /// Append bytes without saving a state, as a write that landed just
+    /// before power was lost.
+    pub fn append_unacknowledged(&mut self, bytes: &[u8]) {
+        let _ = self.data.extend_from_slice(bytes);
+    }
+}
+
+impl<const CAP: usize> TransferStore for MemoryTransferStore<CAP> {
+    type Error = MemoryTransferFull;
+
+    async fn load_state(&mut self) -> Result<Option<[u8; STATE_SIZE]>, Self::Error> {
+        Ok(self.state)
+    }
+
+    async fn save_state(&mut self, state: Option<&[u8; STATE_SIZE]>) -> Result<(), Self::Error> {
+        self.state = state.copied();
+        Ok(())
+    }
+
+    async fn truncate(&mut self, len: u32) -> Result<(), Self::Error> {
+        self.data
+            .truncate(usize::try_from(len).unwrap_or(usize::MAX));
+        Ok(())
+    }
+
+    async fn write_at(&mut self, offset: u32, data: &[u8]) -> Result<(), Self::Error> {
+        self.data
+            .truncate(usize::try_from(offset).unwrap_or(usize::MAX));
+        self.data
+            .extend_from_slice(data)
+            .map_err(|_| MemoryTransferFull)
+    }
+
+    async fn read_at(&mut self, offset: u32, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let start = usize::try_from(offset).unwrap_or(usize::MAX);
+        let rest = self.data.get(start..).unwrap_or_default();
+        let n = rest.len().min(buf.len());
+        for (dst, src) in buf.iter_mut().zip(rest.iter().take(n)) {
+            *dst = *src;
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation
+)]
+mod tests {
+    use super::*;
+    use embassy_futures::block_on;
+
+    type Receiver = TransferReceiver<MemoryTransferStore<4096>>;
+
+    fn file(len: usize) -> std::vec::Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn receiver() -> Receiver {
+        block_on(TransferReceiver::open(MemoryTransferStore::new(), 4096)).unwrap()
+    }
+
+    fn send(rx: &mut Receiver, message: TransferMessage<'_>) -> Option<TransferResponse> {
+        block_on(rx.write(&message.encode())).unwrap();
+        rx.take_response()
+    }
+
+    fn begin(data: &[u8]) -> TransferMessage<'static> {
+        TransferMessage::Begin {
+            kind: TransferKind::LibraryDelta,
+            id: 7,
+            len: u32::try_from(data.len()).unwrap(),
+            crc32: crc32fast::hash(data),
+        }
+    }
+
+    /// Send `data` from `from` in chunks of `chunk` bytes.
+    fn send_from(rx: &mut Receiver, data: &[u8], from: usize, chunk: usize) {
+        for (i, part) in data.get(from..).unwrap().chunks(chunk).enumerate() {
+            let offset = u32::try_from(from + i * chunk).unwrap();
+            send(rx, TransferMessage::Chunk { offset, data: part });
+        }
+    }
+
+    #[test]
+    fn test_messages_round_trip() {
+        let data = [1, 2, 3];
+        for message in [
+            begin(&data),
+            TransferMessage::Chunk {
+                offset: 40,
+                data: &data,
+            },
+            TransferMessage::Commit,
+            TransferMessage::Abort,
+        ] {
+            assert_eq!(TransferMessage::decode(&message.encode()), Ok(message));
+        }
+        for response in [
+            TransferResponse::Ready {
+                id: 1,
+                next_offset: 2,
+            },
+            TransferResponse::Ack { next_offset: 3 },
+            TransferResponse::Nak { expected: 4 },
+            TransferResponse::Done {
+                id: 5,
+                status: TransferStatus::Rejected,
+            },
+        ] {
+            assert_eq!(TransferResponse::decode(&response.encode()), Ok(response));
+        }
+        assert_eq!(
+            TransferMessage::decode(&[0x02, 0, 0, 0, 0]),
+            Err(GattError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_state_round_trip_and_torn_save() {
+        let state = TransferState {
+            kind: TransferKind::LibraryDelta,
+            id: 9,
+            len: 100,
+            crc32: 0xDEAD_BEEF,
+            received: 40,
+            running_crc: 0x1234_5678,
+        };
+        let mut bytes = state.encode();
+        assert_eq!(TransferState::decode(&bytes), Some(state));
+        bytes[3] ^= 0xFF;
+        assert_eq!(TransferState::decode(&bytes), None);
+    }
+
+    #[test]
+    fn test_transfer_commits_after_crc_check() {
+        let data = file(3000);
+        let mut rx = receiver();
+        assert_eq!(
+            send(&mut rx, begin(&data)),
+            Some(TransferResponse::Ready {
+                id: 7,
+                next_offset: 0
+            })
+        );
+        send_from(&mut rx, &data, 0, 100);
+        assert_eq!(send(&mut rx, TransferMessage::Commit), None);
+        assert_eq!(
+            rx.completed(),
+            Some(Completed {
+                kind: TransferKind::LibraryDelta,
+                id: 7,
+                len: 3000
+            })
+        );
+        let mut buf = vec![0u8; 4096];
+        assert_eq!(block_on(rx.read_completed(&mut buf)).unwrap(), &data[..]);
+
+        block_on(rx.finish(TransferStatus::Applied)).unwrap();
+        assert_eq!(
+            rx.take_response(),
+            Some(TransferResponse::Done {
+                id: 7,
+                status: TransferStatus::Applied
+            })
+        );
+        assert!(rx.store_mut().staged().is_empty());
+        assert!(!rx.store_mut().has_state());
+    }
+
+    #[test]
+    fn test_acks_every_interval() {
+        let data = file(2500);
+        let mut rx = receiver();
+        send(&mut rx, begin(&data));
+        let acks: std::vec::Vec<_> = data
+            .chunks(100)
+            .enumerate()
+            .filter_map(|(i, part)| {
+                let offset = u32::try_from(i * 100).unwrap();
+                send(&mut rx, TransferMessage::Chunk { offset, data: part })
+            })
+            .collect();
+        assert_eq!(
+            acks,
+            [
+                TransferResponse::Ack { next_offset: 1100 },
+                TransferResponse::Ack { next_offset: 2200 },
+                TransferResponse::Ack { next_offset: 2500 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_out_of_order_chunk_is_nakked() {
+        let data = file(300);
+        let mut rx = receiver();
+        send(&mut rx, begin(&data));
+        send_from(&mut rx, data.get(..100).unwrap(), 0, 100);
+        let skipped = TransferMessage::Chunk {
+            offset: 200,
+            data: data.get(200..).unwrap(),
+        };
+        assert_eq!(
+            send(&mut rx, skipped),
+            Some(TransferResponse::Nak { expected: 100 })
+        );
+        assert_eq!(rx.active().unwrap().received, 100);
+    }
+
+    #[test]
+    fn test_crc_mismatch_discards() {
+        let data = file(200);
+        let mut corrupt = data.clone();
+        corrupt[150] ^= 1;
+        let mut rx = receiver();
+        send(&mut rx, begin(&data));
+        send_from(&mut rx, &corrupt, 0, 64);
+        assert_eq!(
+            send(&mut rx, TransferMessage::Commit),
+            Some(TransferResponse::Done {
+                id: 7,
+                status: TransferStatus::CrcMismatch
+            })
+        );
+        assert_eq!(rx.active(), None);
+        assert_eq!(rx.completed(), None);
+    }
+
+    #[test]
+    fn test_resume_after_reboot_drops_unacknowledged_bytes() {
+        let data = file(2500);
+        let mut rx = receiver();
+        send(&mut rx, begin(&data));
+        send_from(&mut rx, data.get(..1500).unwrap(), 0, 100);
+        // Acked at 1100; 400 more bytes landed but were never acknowledged.
+        let store = core::mem::take(rx.store_mut());
+        let mut rx: Receiver = block_on(TransferReceiver::open(store, 4096)).unwrap();
+        assert_eq!(rx.store_mut().staged().len(), 1100);
+
+        assert_eq!(
+            send(&mut rx, begin(&data)),
+            Some(TransferResponse::Ready {
+                id: 7,
+                next_offset: 1100
+            })
+        );
+        send_from(&mut rx, &data, 1100, 100);
+        send(&mut rx, TransferMessage::Commit);
+        assert!(rx.completed().is_some());
+    }
+
+    #[test]
+    fn test_new_begin_replaces_other_transfer() {
+        let data = file(500);
+        let mut rx = receiver();
+        send(&mut rx, begin(&data));
+        send_from(&mut rx, data.get(..200).unwrap(), 0, 100);
+        let other = TransferMessage::Begin {
+            kind: TransferKind::LibraryDelta,
+            id: 8,
+            len: 10,
+            crc32: 0,
+        };
+        assert_eq!(
+            send(&mut rx, other),
+            Some(TransferResponse::Ready {
+                id: 8,
+                next_offset: 0
+            })
+        );
+        assert!(rx.store_mut().staged().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_invalid_requests() {
+        let mut rx = receiver();
+        let too_long = TransferMessage::Begin {
+            kind: TransferKind::LibraryDelta,
+            id: 1,
+            len: 4097,
+            crc32: 0,
+        };
+        assert_eq!(
+            block_on(rx.write(&too_long.encode())),
+            Err(TransferError::Gatt(GattError::ValueNotAllowed))
+        );
+        assert_eq!(
+            block_on(rx.write(&TransferMessage::Commit.encode())),
+            Err(TransferError::Gatt(GattError::ValueNotAllowed))
+        );
+        assert_eq!(
+            block_on(rx.write(&[0x01, 9, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0])),
+            Err(TransferError::Gatt(GattError::ValueNotAllowed))
+        );
+
+        let data = file(10);
+        send(&mut rx, begin(&data));
+        let overflow = TransferMessage::Chunk {
+            offset: 0,
+            data: &[0; 11],
+        };
+        assert_eq!(
+            block_on(rx.write(&overflow.encode())),
+            Err(TransferError::Gatt(GattError::InvalidLength))
+        );
+    }
+
+    #[test]
+    fn test_chunk_fits_mtu() {
+        assert_eq!(max_chunk_len(gatt::DEFAULT_ATT_MTU), 15);
+        let data = [0u8; 15];
+        let chunk = TransferMessage::Chunk {
+            offset: 0,
+            data: &data,
+        };
+        assert_eq!(
+            chunk.encode().len(),
+            gatt::notify_len(gatt::DEFAULT_ATT_MTU)
+        );
+    }
+}
@@ -0,0 +1,279 @@
+//! Drive the companion GATT service end to end against a simulated phone.
+//!
+//! Run with: cargo test -p bluetooth --features std --test companion_sim
+#![cfg(feature = "std")]
+// Integration test file: expect/unwrap/panic are intentional test mechanisms.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects
+)]
+
+use bluetooth::companion::POSITION_NOTIFY_INTERVAL_MS;
+use bluetooth::companion_sim::{CompanionSession, SimPlayer};
+use bluetooth::gatt::{
+    att, notify_len, Characteristic, GattError, TransportCommand, TransportState, DEFAULT_ATT_MTU,
+    MAX_ATT_MTU, PROTOCOL_VERSION,
+};
+
+const LONG_TITLE: &str = "Roads (Live at Roseland NYC, with the Orchestra)";
+
+fn album() -> SimPlayer {
+    let mut player = SimPlayer::new();
+    let titles = [
+        "Mysterons",
+        "Sour Times",
+        "Strangers",
+        "It Could Be Sweet",
+        "Wandering Star",
+        "It's a Fire",
+        "Numb",
+        "Roads",
+        "Pedestal",
+        "Biscuit",
+        "Glory Box",
+    ];
+    for (id, title) in (1..).zip(titles) {
+        player = player.with_track(id, title, "Portishead", 240_000);
+    }
+    player
+}
+
+fn session(mtu: u16) -> CompanionSession {
+    CompanionSession::connect(album(), mtu).unwrap()
+}
+
+#[test]
+fn connect_reads_version_and_initial_state() {
+    let s = session(MAX_ATT_MTU);
+    assert_eq!(s.app.version, Some(PROTOCOL_VERSION));
+    let status = s.app.status.unwrap();
+    assert_eq!(status.state, TransportState::Stopped);
+    assert_eq!(status.volume, 50);
+    assert_eq!(s.app.track.as_ref().unwrap().track_id, 0);
+    assert_eq!(s.app.battery, Some(0));
+}
+
+#[test]
+fn play_pause_round_trip() {
+    let mut s = session(DEFAULT_ATT_MTU);
+    s.send(TransportCommand::Play).unwrap();
+    assert_eq!(s.app.status.unwrap().state, TransportState::Playing);
+    let track = s.app.track.clone().unwrap();
+    assert_eq!((track.track_id, track.title.as_str()), (1, "Mysterons"));
+
+    s.send(TransportCommand::TogglePlayPause).unwrap();
+    assert_eq!(s.app.status.unwrap().state, TransportState::Paused);
+    s.send(TransportCommand::TogglePlayPause).unwrap();
+    assert_eq!(s.app.status.unwrap().state, TransportState::Playing);
+    s.send(TransportCommand::Stop).unwrap();
+    assert_eq!(s.app.status.unwrap().state, TransportState::Stopped);
+}
+
+#[test]
+fn next_and_previous_follow_the_queue() {
+    let mut s = session(MAX_ATT_MTU);
+    s.send(TransportCommand::Play).unwrap();
+    s.send(TransportCommand::Next).unwrap();
+    s.send(TransportCommand::Next).unwrap();
+    assert_eq!(s.app.track.as_ref().unwrap().title, "Strangers");
+
+    // Early in a track, Previous goes back; later it restarts the track.
+    s.send(TransportCommand::Previous).unwrap();
+    assert_eq!(s.app.track.as_ref().unwrap().title, "Sour Times");
+    s.advance(10_000).unwrap();
+    s.send(TransportCommand::Previous).unwrap();
+    assert_eq!(s.app.track.as_ref().unwrap().title, "Sour Times");
+    assert_eq!(s.app.status.unwrap().position_ms, 0);
+}
+
+#[test]
+fn seek_and_volume_are_reflected_in_status() {
+    let mut s = session(DEFAULT_ATT_MTU);
+    s.send(TransportCommand::Play).unwrap();
+    s.send(TransportCommand::Seek(90_000)).unwrap();
+    assert_eq!(s.app.status.unwrap().position_ms, 90_000);
+    s.send(TransportCommand::SetVolume(80)).unwrap();
+    assert_eq!(s.app.status.unwrap().volume, 80);
+    assert_eq!(s.player.volume, 80);
+}
+
+#[test]
+fn position_notifications_are_rate_limited() {
+    let mut s = session(DEFAULT_ATT_MTU);
+    s.send(TransportCommand::Play).unwrap();
+    s.app.received.clear();
+
+    // 20 engine ticks in five seconds, but only one notification a second.
+    let sent = s.advance(5_000).unwrap();
+    assert_eq!(
+        sent,
+        usize::try_from(5_000 / POSITION_NOTIFY_INTERVAL_MS).unwrap()
+    );
+    assert!(s.app.received.iter().all(|&c| c == Characteristic::Status));
+    assert_eq!(s.app.status.unwrap().position_ms, 5_000);
+}
+
+#[test]
+fn end_of_track_advances_and_notifies_new_track() {
+    let mut s = session(MAX_ATT_MTU);
+    s.send(TransportCommand::Play).unwrap();
+    s.advance(240_000).unwrap();
+    let track = s.app.track.as_ref().unwrap();
+    assert_eq!(track.title, "Sour Times");
+    assert_eq!(s.app.status.unwrap().position_ms, 0);
+}
+
+#[test]
+fn notifications_fit_the_negotiated_mtu() {
+    for mtu in [DEFAULT_ATT_MTU, 64, MAX_ATT_MTU] {
+        let player = SimPlayer::new().with_track(9, LONG_TITLE, "Portishead", 300_000);
+        let mut s = CompanionSession::connect(player, mtu).unwrap();
+        s.send(TransportCommand::Play).unwrap();
+        assert!(s.app.largest_notification <= notify_len(mtu));
+
+        let title = s.app.track.as_ref().unwrap().title.clone();
+        assert!(LONG_TITLE.starts_with(title.as_str()));
+        if mtu == MAX_ATT_MTU {
+            assert_eq!(title, LONG_TITLE);
+        }
+        // A read always returns the full value.
+        let read = s.server.read(Characteristic::Track).unwrap();
+        s.app.accept(Characteristic::Track, &read).unwrap();
+        assert_eq!(s.app.track.as_ref().unwrap().title, LONG_TITLE);
+    }
+}
+
+#[test]
+fn browse_whole_queue_in_pages() {
+    let mut s = session(DEFAULT_ATT_MTU);
+    s.send(TransportCommand::Play).unwrap();
+
+    // Three IDs per 20-byte notification: 11 tracks take four pages.
+    let requests = s.browse_all(16).unwrap();
+    assert_eq!(requests, 4);
+    assert!(s.app.queue_complete());
+    let queue: Vec<u32> = s.app.queue.iter().map(|id| id.unwrap()).collect();
+    assert_eq!(queue, s.player.queue());
+    assert_eq!(s.app.current, Some(0));
+
+    // With a large MTU, one request fetches everything.
+    let mut s = session(MAX_ATT_MTU);
+    assert_eq!(s.browse_all(16).unwrap(), 1);
+    assert_eq!(s.app.current, None);
+}
+
+#[test]
+fn play_index_from_browsed_queue() {
+    let mut s = session(MAX_ATT_MTU);
+    s.browse_all(32).unwrap();
+    let index = s.app.queue.iter().position(|&id| id == Some(11)).unwrap();
+    s.send(TransportCommand::PlayIndex(u16::try_from(index).unwrap()))
+        .unwrap();
+    assert_eq!(s.app.track.as_ref().unwrap().title, "Glory Box");
+
+    s.browse(0, 1).unwrap();
+    assert_eq!(s.app.current, Some(10));
+}
+
+#[test]
+fn malformed_writes_are_rejected_without_effect() {
+    let mut s = session(DEFAULT_ATT_MTU);
+    let before = s.app.received.len();
+
+    let cases: [(Characteristic, &[u8], u8); 6] = [
+        (Characteristic::Control, &[0x42], att::UNKNOWN_COMMAND),
+        (
+            Characteristic::Control,
+            &[],
+            att::INVALID_ATTRIBUTE_VALUE_LENGTH,
+        ),
+        (
+            Characteristic::Control,
+            &[0x07, 1],
+            att::INVALID_ATTRIBUTE_VALUE_LENGTH,
+        ),
+        (
+            Characteristic::Control,
+            &[0x08, 200],
+            att::VALUE_NOT_ALLOWED,
+        ),
+        (
+            Characteristic::Queue,
+            &[0, 0],
+            att::INVALID_ATTRIBUTE_VALUE_LENGTH,
+        ),
+        (Characteristic::Status, &[0; 7], att::WRITE_NOT_PERMITTED),
+    ];
+    for (characteristic, value, code) in cases {
+        let err = s.write(characteristic, value).unwrap_err();
+        assert_eq!(err.att_code(), code, "{characteristic:?} {value:?}");
+    }
+    assert_eq!(s.app.received.len(), before);
+    assert_eq!(s.app.status.unwrap().state, TransportState::Stopped);
+    assert_eq!(s.player.volume, 50);
+}
+
+#[test]
+fn resync_recovers_from_dropped_events() {
+    let mut s = session(MAX_ATT_MTU);
+    s.send(TransportCommand::Play).unwrap();
+
+    // The BLE task falls behind: the engine moves on, the events are lost.
+    s.player.execute(TransportCommand::Next);
+    s.player.execute(TransportCommand::Pause);
+    s.player.set_eq(3);
+    assert_eq!(s.app.track.as_ref().unwrap().title, "Mysterons");
+
+    s.resync().unwrap();
+    let status = s.app.status.unwrap();
+    assert_eq!(status.state, TransportState::Paused);
+    assert_eq!(status.eq_preset, 3);
+    assert_eq!(s.app.track.as_ref().unwrap().title, "Sour Times");
+}
+
+#[test]
+fn eq_change_is_notified() {
+    let mut s = session(DEFAULT_ATT_MTU);
+    let events = s.player.set_eq(2);
+    s.deliver(events);
+    s.pump().unwrap();
+    assert_eq!(s.app.status.unwrap().eq_preset, 2);
+}
+
+#[test]
+fn battery_level_is_notified_on_change() {
+    let mut s = session(DEFAULT_ATT_MTU);
+    s.server.set_battery(76);
+    assert_eq!(s.pump().unwrap(), 1);
+    assert_eq!(s.app.battery, Some(76));
+    s.server.set_battery(76);
+    assert_eq!(s.pump().unwrap(), 0);
+}
+
+#[test]
+fn reconnect_resubscribes_and_catches_up() {
+    let mut s = session(DEFAULT_ATT_MTU);
+    s.send(TransportCommand::Play).unwrap();
+
+    s.server.disconnected();
+    let events = s.player.execute(TransportCommand::Next);
+    s.deliver(events);
+    assert!(s.server.poll_notification().is_none());
+    assert_eq!(s.app.track.as_ref().unwrap().title, "Mysterons");
+
+    s.reconnect(MAX_ATT_MTU).unwrap();
+    assert_eq!(s.app.track.as_ref().unwrap().title, "Sour Times");
+    assert_eq!(s.app.status.unwrap().state, TransportState::Playing);
+}
+
+#[test]
+fn control_is_write_only() {
+    let s = session(DEFAULT_ATT_MTU);
+    assert_eq!(
+        s.server.read(Characteristic::Control),
+        Err(GattError::ReadNotPermitted)
+    );
+}
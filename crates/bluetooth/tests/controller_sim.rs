@@ -0,0 +1,308 @@
+//! Drive the host-side state tracking against the virtual HCI controller.
+//!
+//! Run with: cargo test -p bluetooth --features std --test controller_sim
+#![cfg(feature = "std")]
+// Integration test file: expect/unwrap/panic are intentional test mechanisms.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects
+)]
+
+use bluetooth::hci::{status, HciCommand, HciEvent, HciPacket};
+use bluetooth::sim::{Pairing, SimError, SimPeer, VirtualController, COD_HEADPHONES};
+use bluetooth::state::BluetoothState;
+use embassy_futures::block_on;
+use platform::BluetoothAdapter;
+
+const HEADPHONES: [u8; 6] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+const SPEAKER: [u8; 6] = [0x10, 0x20, 0x30, 0x40, 0x50, 0x60];
+const NOBODY: [u8; 6] = [0xEE; 6];
+
+/// Host side of the transport: parse every queued event and feed the state.
+struct Host {
+    ctrl: VirtualController,
+    state: BluetoothState,
+}
+
+impl Host {
+    fn new(peers: impl IntoIterator<Item = SimPeer>) -> Self {
+        let mut ctrl = VirtualController::new();
+        for peer in peers {
+            ctrl.add_peer(peer);
+        }
+        block_on(ctrl.init()).unwrap();
+        let mut host = Self {
+            ctrl,
+            state: BluetoothState::new(),
+        };
+        assert_eq!(
+            host.events(),
+            [HciEvent::CommandComplete {
+                status: status::SUCCESS
+            }]
+        );
+        host
+    }
+
+    fn send(&mut self, cmd: HciCommand) -> Vec<HciEvent> {
+        let packet = HciPacket::from_command(cmd).unwrap();
+        self.ctrl.send_command(&packet).unwrap();
+        self.events()
+    }
+
+    fn events(&mut self) -> Vec<HciEvent> {
+        let mut events = Vec::new();
+        while let Some(packet) = self.ctrl.poll_event() {
+            let event = HciPacket::parse(&packet).unwrap();
+            self.state.handle_event(&event);
+            events.push(event);
+        }
+        events
+    }
+
+    fn connect(&mut self, address: [u8; 6]) -> u16 {
+        let events = self.send(HciCommand::CreateConnection { address });
+        match events.as_slice() {
+            [HciEvent::CommandStatus { status: 0, .. }, HciEvent::ConnectionComplete {
+                status: 0, handle, ..
+            }] => *handle,
+            other => panic!("unexpected events {other:?}"),
+        }
+    }
+}
+
+fn accepted(opcode: u16) -> HciEvent {
+    HciEvent::CommandStatus {
+        status: status::SUCCESS,
+        opcode,
+    }
+}
+
+#[test]
+fn test_commands_before_reset_are_disallowed() {
+    let mut ctrl = VirtualController::new();
+    assert_eq!(
+        block_on(ctrl.start_advertising("Soul")),
+        Err(SimError::NotInitialised)
+    );
+    let packet = HciPacket::from_command(HciCommand::Inquiry { length: 4 }).unwrap();
+    ctrl.send_command(&packet).unwrap();
+    assert_eq!(
+        HciPacket::parse(&ctrl.poll_event().unwrap()),
+        Ok(HciEvent::CommandStatus {
+            status: status::COMMAND_DISALLOWED,
+            opcode: 0x0401
+        })
+    );
+}
+
+#[test]
+fn test_inquiry_reports_discoverable_peers_only() {
+    let mut host = Host::new([
+        SimPeer::headphones(HEADPHONES),
+        SimPeer::headphones(SPEAKER).hidden(),
+    ]);
+    let events = host.send(HciCommand::Inquiry { length: 8 });
+    assert_eq!(
+        events,
+        [
+            accepted(0x0401),
+            HciEvent::InquiryResult {
+                address: HEADPHONES,
+                class_of_device: COD_HEADPHONES
+            },
+            HciEvent::InquiryComplete {
+                status: status::SUCCESS
+            },
+        ]
+    );
+    // Hidden peers still accept connections from a known address.
+    host.connect(SPEAKER);
+    assert_eq!(host.state.peer_address(), Some(SPEAKER));
+}
+
+#[test]
+fn test_connect_pair_and_stream() {
+    let mut host = Host::new([SimPeer::headphones(HEADPHONES)]);
+    let handle = host.connect(HEADPHONES);
+    assert!(host.ctrl.is_connected() && host.state.connected());
+
+    assert_eq!(
+        host.ctrl.open_a2dp_stream(handle),
+        Err(SimError::NotAuthenticated)
+    );
+    assert_eq!(
+        host.send(HciCommand::AuthenticationRequested { handle }),
+        [
+            accepted(0x0411),
+            HciEvent::AuthenticationComplete {
+                status: status::SUCCESS,
+                handle
+            },
+        ]
+    );
+    host.ctrl.open_a2dp_stream(handle).unwrap();
+    assert!(host.ctrl.is_streaming());
+
+    let events = host.send(HciCommand::Disconnect {
+        handle,
+        reason: status::REMOTE_USER_TERMINATED,
+    });
+    assert_eq!(
+        events[1],
+        HciEvent::DisconnectionComplete {
+            status: status::SUCCESS,
+            handle,
+            reason: status::TERMINATED_BY_LOCAL_HOST
+        }
+    );
+    assert!(!host.state.connected() && !host.ctrl.is_streaming());
+}
+
+#[test]
+fn test_pairing_failures_leave_link_unauthenticated() {
+    for (pairing, expected) in [
+        (Pairing::Reject, status::PAIRING_NOT_ALLOWED),
+        (Pairing::WrongKey, status::AUTHENTICATION_FAILURE),
+    ] {
+        let mut host = Host::new([SimPeer::headphones(HEADPHONES).with_pairing(pairing)]);
+        let handle = host.connect(HEADPHONES);
+        let events = host.send(HciCommand::AuthenticationRequested { handle });
+        assert_eq!(
+            events[1],
+            HciEvent::AuthenticationComplete {
+                status: expected,
+                handle
+            }
+        );
+        assert!(!host.ctrl.is_authenticated());
+        assert!(host.state.connected(), "failed pairing keeps the link up");
+    }
+}
+
+#[test]
+fn test_a2dp_rejected_by_non_sink() {
+    let mut host = Host::new([SimPeer::headphones(SPEAKER).with_a2dp_sink(false)]);
+    let handle = host.connect(SPEAKER);
+    host.send(HciCommand::AuthenticationRequested { handle });
+    assert_eq!(
+        host.ctrl.open_a2dp_stream(handle),
+        Err(SimError::A2dpRejected)
+    );
+    assert_eq!(
+        host.ctrl.open_a2dp_stream(handle + 1),
+        Err(SimError::NoSuchConnection)
+    );
+}
+
+#[test]
+fn test_page_timeout_and_busy_link() {
+    let mut host = Host::new([
+        SimPeer::headphones(HEADPHONES),
+        SimPeer::headphones(SPEAKER),
+    ]);
+    let events = host.send(HciCommand::CreateConnection { address: NOBODY });
+    assert_eq!(
+        events[1],
+        HciEvent::ConnectionComplete {
+            status: status::PAGE_TIMEOUT,
+            handle: 0,
+            address: NOBODY
+        }
+    );
+    assert!(!host.state.connected());
+
+    host.connect(HEADPHONES);
+    let events = host.send(HciCommand::CreateConnection { address: SPEAKER });
+    assert_eq!(
+        events,
+        [HciEvent::CommandStatus {
+            status: status::CONNECTION_ALREADY_EXISTS,
+            opcode: 0x0405
+        }]
+    );
+    assert_eq!(host.state.peer_address(), Some(HEADPHONES));
+}
+
+#[test]
+fn test_scheduled_link_drop_fires_on_time() {
+    let mut host = Host::new([SimPeer::headphones(HEADPHONES).dropping_after(5_000)]);
+    let handle = host.connect(HEADPHONES);
+
+    host.ctrl.advance(4_999);
+    assert!(host.events().is_empty());
+    assert!(host.state.connected());
+
+    host.ctrl.advance(1);
+    assert_eq!(
+        host.events(),
+        [HciEvent::DisconnectionComplete {
+            status: status::SUCCESS,
+            handle,
+            reason: status::CONNECTION_TIMEOUT
+        }]
+    );
+    assert!(!host.state.connected() && !host.ctrl.is_connected());
+
+    // Reconnecting gets a fresh handle and a fresh drop timer.
+    let second = host.connect(HEADPHONES);
+    assert_ne!(second, handle);
+    host.ctrl.advance(4_000);
+    assert!(host.state.connected());
+}
+
+#[test]
+fn test_incoming_connection_while_advertising() {
+    let mut host = Host::new([SimPeer::headphones(HEADPHONES)]);
+    assert_eq!(
+        host.ctrl.peer_connects(HEADPHONES),
+        Err(SimError::NotAdvertising)
+    );
+    assert_eq!(
+        block_on(
+            host.ctrl
+                .start_advertising("a name far too long to advertise")
+        ),
+        Err(SimError::NameTooLong)
+    );
+
+    block_on(host.ctrl.start_advertising("Soul Listener")).unwrap();
+    assert!(host.ctrl.is_advertising());
+    assert_eq!(host.ctrl.local_name(), "Soul Listener");
+    assert_eq!(host.ctrl.peer_connects(NOBODY), Err(SimError::UnknownPeer));
+
+    let handle = host.ctrl.peer_connects(HEADPHONES).unwrap();
+    assert!(!host.ctrl.is_advertising(), "connecting stops advertising");
+    host.events();
+    assert_eq!(host.state.peer_address(), Some(HEADPHONES));
+
+    assert!(host.ctrl.drop_link(status::REMOTE_USER_TERMINATED));
+    assert_eq!(
+        host.events(),
+        [HciEvent::DisconnectionComplete {
+            status: status::SUCCESS,
+            handle,
+            reason: status::REMOTE_USER_TERMINATED
+        }]
+    );
+    assert!(!host.ctrl.drop_link(status::REMOTE_USER_TERMINATED));
+}
+
+#[test]
+fn test_unknown_opcode_gets_status_not_error() {
+    let mut host = Host::new([]);
+    host.ctrl.send_command(&[0x01, 0x34, 0x12, 0x00]).unwrap();
+    assert_eq!(
+        host.events(),
+        [HciEvent::CommandComplete {
+            status: status::UNKNOWN_HCI_COMMAND
+        }]
+    );
+    assert!(matches!(
+        host.ctrl.send_command(&[0x01, 0x06, 0x04, 0x03]),
+        Err(SimError::Hci(_))
+    ));
+}
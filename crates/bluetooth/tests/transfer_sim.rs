@@ -0,0 +1,186 @@
+//! Upload files from the simulated companion app over the transfer
+//! characteristic.
+//!
+//! Run with: cargo test -p bluetooth --features std --test transfer_sim
+#![cfg(feature = "std")]
+// Integration test file: expect/unwrap/panic are intentional test mechanisms.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation
+)]
+
+use bluetooth::companion_sim::{CompanionSession, SimPlayer};
+use bluetooth::gatt::{att, Characteristic, DEFAULT_ATT_MTU, MAX_ATT_MTU};
+use bluetooth::transfer::{
+    max_chunk_len, TransferKind, TransferMessage, TransferResponse, TransferStatus, ACK_INTERVAL,
+};
+
+fn session(mtu: u16) -> CompanionSession {
+    let player = SimPlayer::new().with_track(1, "Teardrop", "Massive Attack", 330_000);
+    CompanionSession::connect(player, mtu).unwrap()
+}
+
+fn delta(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 % 256) as u8).collect()
+}
+
+#[test]
+fn upload_is_verified_and_delivered() {
+    for mtu in [DEFAULT_ATT_MTU, MAX_ATT_MTU] {
+        let mut s = session(mtu);
+        let data = delta(5_000);
+        assert_eq!(s.upload(1, &data).unwrap(), TransferStatus::Applied);
+        assert_eq!(s.files, [data]);
+        assert!(s.transfer.active().is_none());
+        assert!(s.app.largest_notification <= bluetooth::gatt::notify_len(mtu));
+    }
+}
+
+#[test]
+fn acks_pace_the_upload() {
+    let mut s = session(MAX_ATT_MTU);
+    let data = delta(4_500);
+    s.upload(2, &data).unwrap();
+    let acks: Vec<u32> = s
+        .app
+        .transfer
+        .iter()
+        .filter_map(|r| match *r {
+            TransferResponse::Ack { next_offset } => Some(next_offset),
+            _ => None,
+        })
+        .collect();
+    // Never more than one interval and a chunk in flight.
+    let chunk = max_chunk_len(MAX_ATT_MTU) as u32;
+    assert!(acks[0] >= ACK_INTERVAL);
+    assert!(acks.windows(2).all(|w| w[1] - w[0] < ACK_INTERVAL + chunk));
+    assert_eq!(acks.last(), Some(&4_500));
+}
+
+#[test]
+fn dropped_link_resumes_where_it_stopped() {
+    let mut s = session(DEFAULT_ATT_MTU);
+    let data = delta(3_000);
+    assert_eq!(s.upload_until(3, &data, 2_500).unwrap(), 0);
+
+    s.server.disconnected();
+    s.reconnect(MAX_ATT_MTU).unwrap();
+    let resumed = s.upload_until(3, &data, data.len()).unwrap();
+    assert_eq!(resumed, 2_500);
+    s.write(Characteristic::Transfer, &TransferMessage::Commit.encode())
+        .unwrap();
+    assert_eq!(
+        s.app.transfer.last(),
+        Some(&TransferResponse::Done {
+            id: 3,
+            status: TransferStatus::Applied
+        })
+    );
+    assert_eq!(s.files, [data]);
+}
+
+#[test]
+fn different_file_restarts_from_zero() {
+    let mut s = session(MAX_ATT_MTU);
+    s.upload_until(4, &delta(3_000), 2_000).unwrap();
+    let other = delta(1_000);
+    assert_eq!(s.upload_until(5, &other, other.len()).unwrap(), 0);
+    assert_eq!(s.upload(5, &other).unwrap(), TransferStatus::Applied);
+    assert_eq!(s.files, [other]);
+}
+
+#[test]
+fn corrupted_upload_is_discarded() {
+    let mut s = session(MAX_ATT_MTU);
+    let data = delta(600);
+    let begin = TransferMessage::Begin {
+        kind: TransferKind::LibraryDelta,
+        id: 6,
+        len: 600,
+        crc32: crc32fast::hash(&data) ^ 1,
+    };
+    s.write(Characteristic::Transfer, &begin.encode()).unwrap();
+    for (i, chunk) in data.chunks(150).enumerate() {
+        let message = TransferMessage::Chunk {
+            offset: (i * 150) as u32,
+            data: chunk,
+        };
+        s.write(Characteristic::Transfer, &message.encode())
+            .unwrap();
+    }
+    s.write(Characteristic::Transfer, &TransferMessage::Commit.encode())
+        .unwrap();
+    assert_eq!(
+        s.app.transfer.last(),
+        Some(&TransferResponse::Done {
+            id: 6,
+            status: TransferStatus::CrcMismatch
+        })
+    );
+    assert!(s.files.is_empty());
+    assert!(s.transfer.active().is_none());
+}
+
+#[test]
+fn early_commit_keeps_the_transfer_resumable() {
+    let mut s = session(MAX_ATT_MTU);
+    let data = delta(2_000);
+    s.upload_until(7, &data, 1_500).unwrap();
+    s.write(Characteristic::Transfer, &TransferMessage::Commit.encode())
+        .unwrap();
+    assert_eq!(
+        s.app.transfer.last(),
+        Some(&TransferResponse::Done {
+            id: 7,
+            status: TransferStatus::Incomplete
+        })
+    );
+    assert_eq!(s.upload(7, &data).unwrap(), TransferStatus::Applied);
+    assert_eq!(s.files, [data]);
+}
+
+#[test]
+fn chunk_at_wrong_offset_is_nakked() {
+    let mut s = session(MAX_ATT_MTU);
+    let data = delta(400);
+    s.upload_until(8, &data, 100).unwrap();
+    let ahead = TransferMessage::Chunk {
+        offset: 300,
+        data: &data[300..],
+    };
+    s.write(Characteristic::Transfer, &ahead.encode()).unwrap();
+    assert_eq!(
+        s.app.transfer.last(),
+        Some(&TransferResponse::Nak { expected: 100 })
+    );
+}
+
+#[test]
+fn invalid_transfer_writes_are_rejected() {
+    let mut s = session(MAX_ATT_MTU);
+    let cases: [(&[u8], u8); 4] = [
+        (&[0x03], att::VALUE_NOT_ALLOWED),
+        (&[0x02, 0, 0, 0, 0, 1], att::VALUE_NOT_ALLOWED),
+        (&[0x01, 1, 0, 0], att::INVALID_ATTRIBUTE_VALUE_LENGTH),
+        (&[0x09], att::UNKNOWN_COMMAND),
+    ];
+    for (value, code) in cases {
+        let err = s.write(Characteristic::Transfer, value).unwrap_err();
+        assert_eq!(err.att_code(), code, "{value:?}");
+    }
+    let too_big = TransferMessage::Begin {
+        kind: TransferKind::LibraryDelta,
+        id: 9,
+        len: 1 << 20,
+        crc32: 0,
+    };
+    let err = s
+        .write(Characteristic::Transfer, &too_big.encode())
+        .unwrap_err();
+    assert_eq!(err.att_code(), att::VALUE_NOT_ALLOWED);
+    assert!(s.app.transfer.is_empty());
+}
@@ -0,0 +1,216 @@
+//! Panel-adaptive rendering
+//!
+//! Components draw in [`Gray4`] (16 levels), but panels show fewer: the
+//! 1bpp drivers only black and white, 2bpp panels four greys.  Left to the
+//! driver, a mid-grey is thresholded to solid black or white and a 16-step
+//! gradient collapses into bands.
+//!
+//! [`AdaptiveDisplay`] sits between the components and the display and maps
+//! every pixel to the panel's [`PanelDepth`] with a 4×4 ordered (Bayer)
+//! dither: on a black-and-white panel a mid-grey becomes a checkerboard of
+//! the right density, on a four-level panel a gradient dithers between the
+//! two nearest greys.  Levels the panel can show exactly are never dithered,
+//! so pure black-on-white UI is pixel-identical on every panel.
+//!
+//! ```
+//! use eink_components::adaptive::{AdaptiveDisplay, PanelDepth};
+//! use eink_components::prelude::*;
+//! use embedded_graphics::{mock_display::MockDisplay, pixelcolor::Gray4, prelude::*};
+//!
+//! let mut display: MockDisplay<Gray4> = MockDisplay::new();
+//! display.set_allow_overdraw(true);
+//! // From `platform::DisplayInfo::grayscale_levels` on the target.
+//! let depth = PanelDepth::from_grayscale_levels(2);
+//! let mut target = AdaptiveDisplay::new(&mut display, depth);
+//! ProgressBar::new(40, 6).progress(0.5).render(&mut target, Point::zero()).unwrap();
+//! ```
+
+use embedded_graphics::{pixelcolor::Gray4, prelude::*, primitives::Rectangle};
+
+/// 4×4 Bayer threshold matrix, values 0..16.
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Grey levels a panel can show.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PanelDepth {
+    /// Black and white only (1bpp).
+    BlackWhite,
+    /// Four greys (2bpp).
+    #[default]
+    FourLevel,
+    /// All sixteen [`Gray4`] levels.
+    Full,
+}
+
+impl PanelDepth {
+    /// Depth for a panel showing `levels` greys (e.g.
+    /// `DisplayInfo::grayscale_levels`); rounds down to a supported depth.
+    pub const fn from_grayscale_levels(levels: u8) -> Self {
+        match levels {
+            0..=3 => Self::BlackWhite,
+            4..=15 => Self::FourLevel,
+            _ => Self::Full,
+        }
+    }
+
+    /// Distance between adjacent displayable [`Gray4`] levels.
+    const fn step(self) -> u8 {
+        match self {
+            Self::BlackWhite => 15,
+            Self::FourLevel => 5,
+            Self::Full => 1,
+        }
+    }
+
+    /// Map `color` at `point` to a level this panel can show.
+    // SAFETY: luma is 0..=15 and the step divides 15, so `lower + step`
+    // never exceeds 15; the remainder times 16 fits in u8.
+    #[allow(clippy::arithmetic_side_effects, clippy::indexing_slicing)]
+    pub fn quantize(self, color: Gray4, point: Point) -> Gray4 {
+        let step = self.step();
+        let luma = color.luma();
+        let lower = luma - luma % step;
+        let remainder = luma % step;
+        if remainder == 0 {
+            return color;
+        }
+        // `& 3` keeps both indices in 0..4, also for negative coordinates.
+        let threshold = BAYER_4X4[(point.y & 3) as usize][(point.x & 3) as usize];
+        if remainder * 16 > threshold * step {
+            Gray4::new(lower + step)
+        } else {
+            Gray4::new(lower)
+        }
+    }
+}
+
+/// [`DrawTarget`] wrapper that dithers every pixel to a [`PanelDepth`].
+///
+/// Wrap the display once per frame and render components into the wrapper;
+/// the components themselves need no changes.
+pub struct AdaptiveDisplay<'a, D> {
+    display: &'a mut D,
+    depth: PanelDepth,
+}
+
+impl<'a, D> AdaptiveDisplay<'a, D>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    /// Render into `display` as a panel of `depth`.
+    pub fn new(display: &'a mut D, depth: PanelDepth) -> Self {
+        Self { display, depth }
+    }
+
+    /// Depth pixels are mapped to.
+    pub fn depth(&self) -> PanelDepth {
+        self.depth
+    }
+}
+
+impl<D> OriginDimensions for AdaptiveDisplay<'_, D>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
+
+impl<D> DrawTarget for AdaptiveDisplay<'_, D>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    type Color = Gray4;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let depth = self.depth;
+        self.display.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(p, c)| Pixel(p, depth.quantize(c, p))),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        // Displayable levels keep the driver's fast solid fill.
+        if color.luma().is_multiple_of(self.depth.step()) {
+            self.display.fill_solid(area, color)
+        } else {
+            self.draw_iter(area.points().map(|p| Pixel(p, color)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::arithmetic_side_effects)]
+    use super::*;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::primitives::PrimitiveStyle;
+
+    fn fill(depth: PanelDepth, color: Gray4) -> MockDisplay<Gray4> {
+        let mut display = MockDisplay::new();
+        let mut target = AdaptiveDisplay::new(&mut display, depth);
+        Rectangle::new(Point::zero(), Size::new(8, 8))
+            .into_styled(PrimitiveStyle::with_fill(color))
+            .draw(&mut target)
+            .unwrap();
+        display
+    }
+
+    fn count(display: &MockDisplay<Gray4>, luma: u8) -> usize {
+        Rectangle::new(Point::zero(), Size::new(8, 8))
+            .points()
+            .filter(|&p| display.get_pixel(p).map(|c| c.luma()) == Some(luma))
+            .count()
+    }
+
+    #[test]
+    fn levels_round_down_to_supported_depths() {
+        assert_eq!(PanelDepth::from_grayscale_levels(2), PanelDepth::BlackWhite);
+        assert_eq!(PanelDepth::from_grayscale_levels(4), PanelDepth::FourLevel);
+        assert_eq!(PanelDepth::from_grayscale_levels(6), PanelDepth::FourLevel);
+        assert_eq!(PanelDepth::from_grayscale_levels(16), PanelDepth::Full);
+    }
+
+    #[test]
+    fn black_and_white_pass_through_on_every_depth() {
+        for depth in [
+            PanelDepth::BlackWhite,
+            PanelDepth::FourLevel,
+            PanelDepth::Full,
+        ] {
+            assert_eq!(count(&fill(depth, Gray4::BLACK), 0), 64);
+            assert_eq!(count(&fill(depth, Gray4::WHITE), 15), 64);
+        }
+    }
+
+    #[test]
+    fn mid_grey_dithers_to_half_coverage_on_one_bit_panels() {
+        // 0x8 is just over half way: 8/15 of 16 thresholds → 9 of 16 white.
+        let display = fill(PanelDepth::BlackWhite, Gray4::new(0x8));
+        assert_eq!(count(&display, 15), 36);
+        assert_eq!(count(&display, 0), 28);
+    }
+
+    #[test]
+    fn four_level_panels_keep_their_greys_and_dither_between_them() {
+        let exact = fill(PanelDepth::FourLevel, Gray4::new(0xA));
+        assert_eq!(count(&exact, 0xA), 64);
+
+        // 0x7 lies 2/5 of the way from 0x5 to 0xA.
+        let between = fill(PanelDepth::FourLevel, Gray4::new(0x7));
+        assert_eq!(count(&between, 0xA) + count(&between, 0x5), 64);
+        assert!(count(&between, 0x5) > count(&between, 0xA));
+    }
+
+    #[test]
+    fn full_depth_is_untouched() {
+        assert_eq!(count(&fill(PanelDepth::Full, Gray4::new(0x7)), 0x7), 64);
+    }
+}
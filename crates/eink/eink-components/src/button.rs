@@ -1,5 +1,8 @@
 //! Button component
 
+use core::hash::Hasher;
+
+use crate::render_cache::{Cacheable, StateHash, StateHasher};
 use eink_system::prelude::*;
 use embedded_graphics::{
     mono_font::{ascii::FONT_10X20, MonoTextStyle},
@@ -178,6 +181,43 @@ impl Button {
     }
 }
 
+impl Layout for Button {
+    fn layout(&self, constraints: Constraints) -> LayoutResult {
+        LayoutResult::leaf(constraints.constrain(self.calculate_size()))
+    }
+}
+
+impl StateHash for ButtonStyle {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_gray(self.background);
+        hasher.write_gray(self.foreground);
+        hasher.write_opt_gray(self.border);
+        hasher.write_edges(self.padding);
+        hasher.write_u32(self.corner_radius);
+    }
+}
+
+impl StateHash for Button {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_text(self.label);
+        self.style.hash_state(hasher);
+        hasher.write_u32(self.min_width.unwrap_or(0));
+    }
+}
+
+impl Cacheable for Button {
+    fn cached_size(&self) -> Size {
+        self.calculate_size()
+    }
+
+    fn draw_cached<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        self.render(display, position)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,227 @@
+//! Checkbox component with optional label
+
+use core::hash::Hasher;
+
+use crate::form::{self, FocusState, FormStyle};
+use crate::label::TextSize;
+use crate::render_cache::{Cacheable, StateHash, StateHasher};
+use eink_system::layout::{Constraints, Layout, LayoutResult};
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+
+/// Side of the check box in pixels.
+pub const CHECKBOX_SIZE: u32 = 14;
+/// Gap between the box and its label.
+const LABEL_GAP: u32 = 6;
+
+/// Checkbox with an optional text label to its right
+///
+/// The tick is drawn with 2 px strokes so it survives 1-bit rendering
+/// without anti-aliasing.
+pub struct Checkbox {
+    checked: bool,
+    label: Option<&'static str>,
+    state: FocusState,
+    style: FormStyle,
+    #[cfg(feature = "std")]
+    pub test_id: Option<String>,
+}
+
+impl Checkbox {
+    /// Create a checkbox, checked or not
+    pub fn new(checked: bool) -> Self {
+        Self {
+            checked,
+            label: None,
+            state: FocusState::Normal,
+            style: FormStyle::one_bit(),
+            #[cfg(feature = "std")]
+            test_id: None,
+        }
+    }
+
+    /// Set label text
+    pub fn label(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Set style
+    pub fn style(mut self, style: FormStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set focus state
+    pub fn focus(mut self, state: FocusState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Set the test ID for this component (used by eink-testing query_by_test_id).
+    #[cfg(feature = "std")]
+    pub fn test_id(mut self, id: impl Into<String>) -> Self {
+        self.test_id = Some(id.into());
+        self
+    }
+
+    /// Get the test ID for this component.
+    #[cfg(feature = "std")]
+    pub fn get_test_id(&self) -> Option<&str> {
+        self.test_id.as_deref()
+    }
+
+    /// Whether the box is checked
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    /// Current focus state
+    pub fn focus_state(&self) -> FocusState {
+        self.state
+    }
+
+    /// Flip the check (ignored while disabled). Returns the new value.
+    pub fn toggle(&mut self) -> bool {
+        if self.state != FocusState::Disabled {
+            self.checked = !self.checked;
+        }
+        self.checked
+    }
+
+    /// Control size without padding
+    // SAFETY: label length and glyph width are small UI values; overflow is not possible.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn content_size(&self) -> Size {
+        let small = TextSize::Small;
+        match self.label {
+            Some(text) => Size::new(
+                CHECKBOX_SIZE + LABEL_GAP + text.len() as u32 * small.char_width(),
+                CHECKBOX_SIZE.max(small.line_height()),
+            ),
+            None => Size::new(CHECKBOX_SIZE, CHECKBOX_SIZE),
+        }
+    }
+
+    /// Size including padding
+    pub fn size(&self) -> Size {
+        form::padded_size(self.content_size(), &self.style)
+    }
+
+    /// Get checkbox bounding box
+    pub fn bounds(&self, position: Point) -> Rectangle {
+        Rectangle::new(position, self.size())
+    }
+
+    /// Render checkbox to display
+    // SAFETY: geometry is derived from the fixed 14 px box and label length.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn render<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        let bounds = self.bounds(position);
+        form::draw_background(display, bounds, &self.style)?;
+
+        let origin = form::content_origin(position, &self.style);
+        let content_h = self.content_size().height;
+        let box_origin = origin + Point::new(0, ((content_h - CHECKBOX_SIZE) / 2) as i32);
+        let fg = self.style.foreground;
+
+        Rectangle::new(box_origin, Size::new(CHECKBOX_SIZE, CHECKBOX_SIZE))
+            .into_styled(PrimitiveStyle::with_stroke(fg, 2))
+            .draw(display)?;
+
+        if self.checked {
+            let tick = PrimitiveStyle::with_stroke(fg, 2);
+            let a = box_origin + Point::new(3, 7);
+            let b = box_origin + Point::new(6, 10);
+            let c = box_origin + Point::new(11, 3);
+            Line::new(a, b).into_styled(tick).draw(display)?;
+            Line::new(b, c).into_styled(tick).draw(display)?;
+        }
+
+        if let Some(text) = self.label {
+            let text_x = origin.x + (CHECKBOX_SIZE + LABEL_GAP) as i32;
+            let text_y = origin.y + (content_h / 2) as i32;
+            Text::with_baseline(
+                text,
+                Point::new(text_x, text_y),
+                MonoTextStyle::new(&FONT_6X10, fg),
+                Baseline::Middle,
+            )
+            .draw(display)?;
+        }
+
+        form::draw_state(display, bounds, self.state, &self.style)
+    }
+}
+
+impl Layout for Checkbox {
+    fn layout(&self, constraints: Constraints) -> LayoutResult {
+        LayoutResult::leaf(constraints.constrain(self.size()))
+    }
+}
+
+impl StateHash for Checkbox {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u8(u8::from(self.checked));
+        hasher.write_text(self.label.unwrap_or(""));
+        hasher.write_u8(self.state as u8);
+        self.style.hash_state(hasher);
+    }
+}
+
+impl Cacheable for Checkbox {
+    fn cached_size(&self) -> Size {
+        self.size()
+    }
+
+    fn draw_cached<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        self.render(display, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::arithmetic_side_effects)]
+    use super::*;
+
+    #[test]
+    fn test_checkbox_toggles() {
+        let mut checkbox = Checkbox::new(false);
+        assert!(checkbox.toggle());
+        assert!(checkbox.is_checked());
+        assert!(!checkbox.toggle());
+    }
+
+    #[test]
+    fn test_disabled_checkbox_ignores_input() {
+        let mut checkbox = Checkbox::new(false).focus(FocusState::Disabled);
+        assert!(!checkbox.toggle());
+    }
+
+    #[test]
+    fn test_label_widens_checkbox() {
+        let bare = Checkbox::new(false);
+        let labelled = Checkbox::new(false).label("Gapless");
+        // 7 chars × 6 px + 6 px gap
+        assert_eq!(labelled.size().width, bare.size().width + 48);
+        assert_eq!(labelled.size().height, bare.size().height);
+    }
+
+    #[test]
+    fn test_layout_respects_constraints() {
+        let checkbox = Checkbox::new(true).label("Shuffle");
+        let result = checkbox.layout(Constraints::loose(Size::new(40, 100)));
+        assert_eq!(result.size.width, 40);
+    }
+}
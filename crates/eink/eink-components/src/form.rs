@@ -0,0 +1,172 @@
+//! Shared styling and focus handling for form components
+//!
+//! [`Toggle`](crate::toggle::Toggle), [`Slider`](crate::slider::Slider) and
+//! [`Checkbox`](crate::checkbox::Checkbox) are driven by buttons or the
+//! rotary encoder, so exactly one of them has focus at a time.  Focus is
+//! drawn as a solid ring inside the component's padding rather than by a
+//! grey fill: it survives 1-bit rendering (DU/A2 refreshes), and because the
+//! ring sits in space the layout already reserved, moving focus never
+//! resizes anything — only the two affected components need a partial
+//! refresh.
+
+use crate::render_cache::{StateHash, StateHasher};
+use eink_system::prelude::Edges;
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, StrokeAlignment},
+};
+
+/// Width of the focus ring in pixels.
+pub const FOCUS_RING_WIDTH: u32 = 2;
+
+/// Interaction state of a form component.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum FocusState {
+    /// Not focused.
+    #[default]
+    Normal,
+    /// Has input focus — drawn with a focus ring.
+    Focused,
+    /// Cannot be changed — drawn with a 50% checkerboard over it.
+    Disabled,
+}
+
+/// Colours and spacing shared by form components.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FormStyle {
+    pub foreground: Gray4,
+    pub background: Gray4,
+    /// Space around the control; must be at least [`FOCUS_RING_WIDTH`] + 1
+    /// on every side to fit the focus ring.
+    pub padding: Edges,
+}
+
+impl FormStyle {
+    /// Pure black on white — renders identically in 1-bit and Gray4 modes.
+    pub fn one_bit() -> Self {
+        Self {
+            foreground: Gray4::BLACK,
+            background: Gray4::WHITE,
+            padding: Edges::all(FOCUS_RING_WIDTH + 1),
+        }
+    }
+
+    /// White on black, for controls on dark headers.
+    pub fn inverted() -> Self {
+        Self {
+            foreground: Gray4::WHITE,
+            background: Gray4::BLACK,
+            ..Self::one_bit()
+        }
+    }
+}
+
+impl Default for FormStyle {
+    fn default() -> Self {
+        Self::one_bit()
+    }
+}
+
+/// Outer size of a control of `content` size with `style` padding.
+// SAFETY: widget sizes and paddings are small UI values; overflow is not possible.
+#[allow(clippy::arithmetic_side_effects)]
+pub(crate) fn padded_size(content: Size, style: &FormStyle) -> Size {
+    Size::new(
+        content.width + style.padding.horizontal(),
+        content.height + style.padding.vertical(),
+    )
+}
+
+/// Top-left corner of the control inside its padded bounds.
+// SAFETY: padding values are small UI values; overflow is not possible.
+#[allow(clippy::arithmetic_side_effects)]
+pub(crate) fn content_origin(position: Point, style: &FormStyle) -> Point {
+    position + Point::new(style.padding.left as i32, style.padding.top as i32)
+}
+
+/// Fill `bounds` with the style background.
+///
+/// Components call this before drawing the control and [`draw_state`]
+/// after, so the disabled checkerboard covers the control.
+pub(crate) fn draw_background<D>(
+    display: &mut D,
+    bounds: Rectangle,
+    style: &FormStyle,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    bounds
+        .into_styled(PrimitiveStyle::with_fill(style.background))
+        .draw(display)
+}
+
+/// Overlay the focus ring or disabled mask for `state` on `bounds`.
+// SAFETY: coordinates are bounded by the widget rectangle.
+#[allow(clippy::arithmetic_side_effects)]
+pub(crate) fn draw_state<D>(
+    display: &mut D,
+    bounds: Rectangle,
+    state: FocusState,
+    style: &FormStyle,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    match state {
+        FocusState::Normal => Ok(()),
+        FocusState::Focused => bounds
+            .into_styled(
+                PrimitiveStyleBuilder::new()
+                    .stroke_color(style.foreground)
+                    .stroke_width(FOCUS_RING_WIDTH)
+                    .stroke_alignment(StrokeAlignment::Inside)
+                    .build(),
+            )
+            .draw(display),
+        FocusState::Disabled => {
+            let origin = bounds.top_left;
+            let pixels = bounds
+                .points()
+                .filter(move |p| (p.x - origin.x + p.y - origin.y) % 2 == 0)
+                .map(|p| Pixel(p, style.background));
+            display.draw_iter(pixels)
+        }
+    }
+}
+
+impl StateHash for FormStyle {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_gray(self.foreground);
+        hasher.write_gray(self.background);
+        hasher.write_edges(self.padding);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::arithmetic_side_effects)]
+    use super::*;
+
+    #[test]
+    fn default_padding_fits_focus_ring() {
+        let style = FormStyle::default();
+        assert!(style.padding.top > FOCUS_RING_WIDTH);
+        assert!(style.padding.left > FOCUS_RING_WIDTH);
+        assert_eq!(
+            padded_size(Size::new(10, 10), &style),
+            Size::new(
+                10 + 2 * (FOCUS_RING_WIDTH + 1),
+                10 + 2 * (FOCUS_RING_WIDTH + 1)
+            )
+        );
+    }
+
+    #[test]
+    fn inverted_swaps_colours() {
+        let style = FormStyle::inverted();
+        assert_eq!(style.foreground, Gray4::WHITE);
+        assert_eq!(style.background, Gray4::BLACK);
+    }
+}
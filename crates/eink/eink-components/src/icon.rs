@@ -1,5 +1,9 @@
 //! Simple icon component
 
+use core::hash::Hasher;
+
+use crate::render_cache::{Cacheable, StateHash, StateHasher};
+use eink_system::layout::{Constraints, Layout, LayoutResult};
 use embedded_graphics::{
     pixelcolor::Gray4,
     prelude::*,
@@ -249,6 +253,33 @@ impl Icon {
     }
 }
 
+impl Layout for Icon {
+    fn layout(&self, constraints: Constraints) -> LayoutResult {
+        LayoutResult::leaf(constraints.constrain(self.dimensions()))
+    }
+}
+
+impl StateHash for Icon {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u8(self.icon_type as u8);
+        hasher.write_u32(self.size);
+        hasher.write_gray(self.color);
+    }
+}
+
+impl Cacheable for Icon {
+    fn cached_size(&self) -> Size {
+        self.dimensions()
+    }
+
+    fn draw_cached<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        self.render(display, position)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
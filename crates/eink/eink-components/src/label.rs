@@ -1,5 +1,8 @@
 //! Label component for displaying text
 
+use core::hash::Hasher;
+
+use crate::render_cache::{Cacheable, StateHash, StateHasher};
 use embedded_graphics::{
     mono_font::{ascii::FONT_10X20, ascii::FONT_6X10, MonoTextStyle},
     pixelcolor::Gray4,
@@ -126,6 +129,27 @@ impl LabelBuilder {
     }
 }
 
+impl StateHash for Label {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_text(self.text);
+        hasher.write_gray(self.color);
+        hasher.write_u8(self.size as u8);
+    }
+}
+
+impl Cacheable for Label {
+    fn cached_size(&self) -> Size {
+        self.dimensions()
+    }
+
+    fn draw_cached<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        self.render(display, position)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -8,6 +8,19 @@
 //! - `Label` - Static text display
 //! - `ProgressBar` - Visual progress indicator
 //! - `Icon` - Simple icon representation
+//! - `TextMarquee` - Scrolling text for titles wider than the screen
+//! - `StatusBar` - Battery, Bluetooth, playback, clock and volume row with
+//!   per-segment dirty tracking for partial refreshes
+//! - `Toggle`, `Slider`, `Checkbox` - Form controls for settings screens
+//!   (1-bit-friendly focus states, see [`form`])
+//!
+//! [`render_cache::RenderCache`] skips redrawing components whose state hash
+//! has not changed and reports the regions it did redraw, so the frame diff
+//! only has to compare those.
+//!
+//! Render through [`adaptive::AdaptiveDisplay`] to dither the components'
+//! greys down to what the panel can show (1bpp or 4-level), so the same
+//! screens run on every panel variant.
 //!
 //! # Example
 //!
@@ -30,14 +43,30 @@
     clippy::cast_sign_loss,
 )]
 
+pub mod adaptive;
 pub mod button;
+pub mod checkbox;
+pub mod form;
 pub mod icon;
 pub mod label;
+pub mod marquee;
 pub mod progress_bar;
+pub mod render_cache;
+pub mod slider;
+pub mod status_bar;
+pub mod toggle;
 
 pub mod prelude {
+    pub use crate::adaptive::{AdaptiveDisplay, PanelDepth};
     pub use crate::button::*;
+    pub use crate::checkbox::*;
+    pub use crate::form::{FocusState, FormStyle};
     pub use crate::icon::*;
     pub use crate::label::*;
+    pub use crate::marquee::*;
     pub use crate::progress_bar::*;
+    pub use crate::render_cache::{CacheOutcome, Cacheable, RenderCache, StateHash, StateHasher};
+    pub use crate::slider::*;
+    pub use crate::status_bar::*;
+    pub use crate::toggle::*;
 }
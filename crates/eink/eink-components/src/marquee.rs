@@ -0,0 +1,404 @@
+//! Marquee component for text wider than its box
+//!
+//! Track titles regularly exceed the width of the now-playing screen.
+//! [`TextMarquee`] shows the start of the text, dwells, scrolls until the end
+//! is visible, dwells again and jumps back.  The caller drives it with
+//! [`TextMarquee::advance`] and redraws (one partial refresh of
+//! [`TextMarquee::bounds`]) only when that returns `true`.
+//!
+//! Two scroll modes are available:
+//!
+//! - [`ScrollMode::Stepped`] (default) moves in large, whole-character jumps
+//!   on a slow interval — each jump is one partial refresh, which is what an
+//!   e-ink panel can sustain without ghosting or burning power.
+//! - [`ScrollMode::Smooth`] moves a pixel at a time on a frame interval, for
+//!   the emulator or LCD-class displays.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_components::prelude::*;
+//!
+//! let mut title = TextMarquee::new("A Very Long Track Title (Extended Remix)", 200)
+//!     .start_dwell_ms(3000);
+//!
+//! // In the UI loop, with the time since the last call:
+//! if title.advance(100) {
+//!     // redraw `title` and partially refresh title.bounds(position)
+//! }
+//! ```
+
+use crate::label::TextSize;
+use eink_system::layout::{Constraints, Layout, LayoutResult};
+use embedded_graphics::{
+    mono_font::{
+        ascii::{FONT_10X20, FONT_6X10},
+        MonoTextStyle,
+    },
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+
+/// How the marquee moves between dwells
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ScrollMode {
+    /// Whole-character jumps of about ¾ of the box width every 1.2 s —
+    /// one partial refresh per jump.
+    #[default]
+    Stepped,
+    /// 1 px every 40 ms.
+    Smooth,
+}
+
+/// Where the marquee is in its scroll cycle
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Phase {
+    StartDwell,
+    Scrolling,
+    EndDwell,
+}
+
+/// Single-line text that scrolls when it does not fit its width
+pub struct TextMarquee {
+    text: &'static str,
+    width: u32,
+    size: TextSize,
+    color: Gray4,
+    background: Gray4,
+    mode: ScrollMode,
+    /// Overrides for the mode's step / interval defaults.
+    step: Option<u32>,
+    interval_ms: Option<u32>,
+    start_dwell_ms: u32,
+    end_dwell_ms: u32,
+    phase: Phase,
+    /// Time accumulated in the current phase.
+    timer_ms: u32,
+    offset: u32,
+    #[cfg(feature = "std")]
+    pub test_id: Option<String>,
+}
+
+impl TextMarquee {
+    /// Create a marquee showing `text` in a box `width` pixels wide
+    pub fn new(text: &'static str, width: u32) -> Self {
+        Self {
+            text,
+            width,
+            size: TextSize::Normal,
+            color: Gray4::BLACK,
+            background: Gray4::WHITE,
+            mode: ScrollMode::Stepped,
+            step: None,
+            interval_ms: None,
+            start_dwell_ms: 2000,
+            end_dwell_ms: 1500,
+            phase: Phase::StartDwell,
+            timer_ms: 0,
+            offset: 0,
+            #[cfg(feature = "std")]
+            test_id: None,
+        }
+    }
+
+    /// Set scroll mode
+    pub fn mode(mut self, mode: ScrollMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set scroll distance per step in pixels (rounded down to whole
+    /// characters, minimum one, in [`ScrollMode::Stepped`])
+    pub fn step(mut self, pixels: u32) -> Self {
+        self.step = Some(pixels.max(1));
+        self
+    }
+
+    /// Set time between scroll steps
+    pub fn interval_ms(mut self, ms: u32) -> Self {
+        self.interval_ms = Some(ms.max(1));
+        self
+    }
+
+    /// Set how long the start of the text is shown before scrolling
+    pub fn start_dwell_ms(mut self, ms: u32) -> Self {
+        self.start_dwell_ms = ms;
+        self
+    }
+
+    /// Set how long the end of the text is shown before jumping back
+    pub fn end_dwell_ms(mut self, ms: u32) -> Self {
+        self.end_dwell_ms = ms;
+        self
+    }
+
+    /// Set text size
+    pub fn size(mut self, size: TextSize) -> Self {
+        self.size = size;
+        self.reset();
+        self
+    }
+
+    /// Set text color
+    pub fn color(mut self, color: Gray4) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set background color
+    pub fn background(mut self, color: Gray4) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Set the test ID for this component (used by eink-testing query_by_test_id).
+    #[cfg(feature = "std")]
+    pub fn test_id(mut self, id: impl Into<String>) -> Self {
+        self.test_id = Some(id.into());
+        self
+    }
+
+    /// Get the test ID for this component.
+    #[cfg(feature = "std")]
+    pub fn get_test_id(&self) -> Option<&str> {
+        self.test_id.as_deref()
+    }
+
+    /// Current text
+    pub fn text(&self) -> &'static str {
+        self.text
+    }
+
+    /// Replace the text (new track) and restart the cycle.
+    /// Returns `true` if the text changed.
+    pub fn set_text(&mut self, text: &'static str) -> bool {
+        if text == self.text {
+            return false;
+        }
+        self.text = text;
+        self.reset();
+        true
+    }
+
+    /// Return to the start of the text and restart the start dwell
+    pub fn reset(&mut self) {
+        self.phase = Phase::StartDwell;
+        self.timer_ms = 0;
+        self.offset = 0;
+    }
+
+    /// Current horizontal scroll offset in pixels
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Width of the full text in pixels
+    // SAFETY: title length and glyph width are small values; the product fits in u32.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn text_width(&self) -> u32 {
+        self.text.chars().count() as u32 * self.size.char_width()
+    }
+
+    /// Whether the text is wider than the box
+    pub fn needs_scroll(&self) -> bool {
+        self.text_width() > self.width
+    }
+
+    /// Offset at which the end of the text is visible
+    pub fn max_offset(&self) -> u32 {
+        self.text_width().saturating_sub(self.width)
+    }
+
+    /// Effective scroll distance per step
+    // SAFETY: char_width is non-zero; division and product stay within width.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn step_px(&self) -> u32 {
+        match self.mode {
+            ScrollMode::Smooth => self.step.unwrap_or(1),
+            ScrollMode::Stepped => {
+                let char_w = self.size.char_width();
+                let step = self.step.unwrap_or(self.width * 3 / 4);
+                (step / char_w).max(1) * char_w
+            }
+        }
+    }
+
+    /// Effective time between steps
+    pub fn step_interval_ms(&self) -> u32 {
+        self.interval_ms.unwrap_or(match self.mode {
+            ScrollMode::Stepped => 1200,
+            ScrollMode::Smooth => 40,
+        })
+    }
+
+    /// Advance the animation by `elapsed_ms`
+    ///
+    /// Returns `true` when the offset changed and the marquee must be
+    /// redrawn.  If several steps fall due in one call they are coalesced
+    /// into a single jump, so a late caller still issues one refresh.
+    // SAFETY: timers are compared before subtraction; offset is clamped to max_offset.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn advance(&mut self, elapsed_ms: u32) -> bool {
+        if !self.needs_scroll() {
+            return false;
+        }
+        let before = self.offset;
+        let (step, interval, max) = (self.step_px(), self.step_interval_ms(), self.max_offset());
+        self.timer_ms = self.timer_ms.saturating_add(elapsed_ms);
+
+        loop {
+            let due = match self.phase {
+                Phase::StartDwell => self.start_dwell_ms,
+                Phase::Scrolling => interval,
+                Phase::EndDwell => self.end_dwell_ms,
+            };
+            if self.timer_ms < due {
+                break;
+            }
+            self.timer_ms -= due;
+            self.phase = match self.phase {
+                Phase::StartDwell => Phase::Scrolling,
+                Phase::Scrolling => {
+                    self.offset = self.offset.saturating_add(step).min(max);
+                    if self.offset == max {
+                        Phase::EndDwell
+                    } else {
+                        Phase::Scrolling
+                    }
+                }
+                Phase::EndDwell => {
+                    self.offset = 0;
+                    Phase::StartDwell
+                }
+            };
+        }
+        self.offset != before
+    }
+
+    /// Get dimensions
+    pub fn dimensions(&self) -> Size {
+        Size::new(self.width, self.size.line_height())
+    }
+
+    /// Get marquee bounding box — the region to refresh after a step
+    pub fn bounds(&self, position: Point) -> Rectangle {
+        Rectangle::new(position, self.dimensions())
+    }
+
+    /// Render the visible window of the text
+    pub fn render<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        let bounds = self.bounds(position);
+        bounds
+            .into_styled(PrimitiveStyle::with_fill(self.background))
+            .draw(display)?;
+
+        let font = match self.size {
+            TextSize::Small => &FONT_6X10,
+            TextSize::Normal => &FONT_10X20,
+        };
+        let origin = Point::new(position.x.saturating_sub(self.offset as i32), position.y);
+        Text::with_baseline(
+            self.text,
+            origin,
+            MonoTextStyle::new(font, self.color),
+            Baseline::Top,
+        )
+        .draw(&mut display.clipped(&bounds))?;
+        Ok(())
+    }
+}
+
+impl Layout for TextMarquee {
+    fn layout(&self, constraints: Constraints) -> LayoutResult {
+        LayoutResult::leaf(constraints.constrain(self.dimensions()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const TITLE: &str = "Twenty characters!!!"; // 20 × 10 px = 200 px
+
+    #[test]
+    fn test_short_text_never_scrolls() {
+        let mut marquee = TextMarquee::new("Short", 100);
+        assert!(!marquee.needs_scroll());
+        assert!(!marquee.advance(60_000));
+        assert_eq!(marquee.offset(), 0);
+    }
+
+    #[test]
+    fn test_stepped_cycle() {
+        let mut marquee = TextMarquee::new(TITLE, 100)
+            .step(45)
+            .interval_ms(1000)
+            .start_dwell_ms(2000)
+            .end_dwell_ms(500);
+        // 45 px snaps down to four 10 px characters.
+        assert_eq!(marquee.step_px(), 40);
+        assert_eq!(marquee.max_offset(), 100);
+
+        assert!(!marquee.advance(1999));
+        assert!(!marquee.advance(1)); // dwell over, first step not due yet
+        assert!(marquee.advance(1000));
+        assert_eq!(marquee.offset(), 40);
+        assert!(marquee.advance(1000));
+        assert_eq!(marquee.offset(), 80);
+        assert!(marquee.advance(1000));
+        assert_eq!(marquee.offset(), 100); // last jump lands on the end
+        assert!(!marquee.advance(499));
+        assert!(marquee.advance(1));
+        assert_eq!(marquee.offset(), 0);
+    }
+
+    #[test]
+    fn test_late_caller_gets_one_coalesced_jump() {
+        let mut marquee = TextMarquee::new(TITLE, 100)
+            .step(10)
+            .interval_ms(100)
+            .start_dwell_ms(0);
+        assert!(marquee.advance(350));
+        assert_eq!(marquee.offset(), 30);
+    }
+
+    #[test]
+    fn test_smooth_mode_moves_per_pixel() {
+        let mut marquee = TextMarquee::new(TITLE, 100)
+            .mode(ScrollMode::Smooth)
+            .start_dwell_ms(0);
+        assert_eq!(marquee.step_px(), 1);
+        assert!(marquee.advance(40));
+        assert_eq!(marquee.offset(), 1);
+    }
+
+    #[test]
+    fn test_set_text_restarts() {
+        let mut marquee = TextMarquee::new(TITLE, 100).start_dwell_ms(0);
+        marquee.advance(1200);
+        assert!(marquee.offset() > 0);
+        assert!(!marquee.set_text(TITLE));
+        assert!(marquee.set_text("Another long title for the box"));
+        assert_eq!(marquee.offset(), 0);
+    }
+
+    #[test]
+    fn test_render_is_clipped_to_bounds() {
+        use embedded_graphics::mock_display::MockDisplay;
+
+        let mut marquee = TextMarquee::new(TITLE, 50)
+            .size(TextSize::Small)
+            .start_dwell_ms(0);
+        marquee.advance(1200);
+        let mut display: MockDisplay<Gray4> = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        marquee.render(&mut display, Point::new(4, 4)).unwrap();
+        assert_eq!(display.affected_area(), marquee.bounds(Point::new(4, 4)));
+    }
+}
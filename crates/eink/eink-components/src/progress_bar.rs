@@ -1,5 +1,9 @@
 //! Progress bar component
 
+use core::hash::Hasher;
+
+use crate::render_cache::{Cacheable, StateHash, StateHasher};
+use eink_system::layout::{Constraints, Layout, LayoutResult};
 use embedded_graphics::{
     pixelcolor::Gray4,
     prelude::*,
@@ -106,6 +110,36 @@ impl ProgressBar {
     }
 }
 
+impl Layout for ProgressBar {
+    fn layout(&self, constraints: Constraints) -> LayoutResult {
+        LayoutResult::leaf(constraints.constrain(self.size()))
+    }
+}
+
+impl StateHash for ProgressBar {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u32(self.width);
+        hasher.write_u32(self.height);
+        hasher.write_u32(self.progress.to_bits());
+        hasher.write_gray(self.background);
+        hasher.write_gray(self.foreground);
+        hasher.write_opt_gray(self.border);
+    }
+}
+
+impl Cacheable for ProgressBar {
+    fn cached_size(&self) -> Size {
+        self.size()
+    }
+
+    fn draw_cached<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        self.render(display, position)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
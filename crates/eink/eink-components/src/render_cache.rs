@@ -0,0 +1,463 @@
+//! Component render cache keyed by state hash
+//!
+//! Most of a screen does not change between frames: a list row, a button,
+//! a label.  Re-drawing them costs CPU, and on a panel driven through a
+//! frame diff it costs a compare per tile too.  [`RenderCache`] remembers,
+//! per component key, the [`StateHash`] and bounds it last drew; a
+//! component whose hash and bounds are unchanged is skipped outright and
+//! its previous pixels stay in the framebuffer.
+//!
+//! Skipping only works when the target keeps the previous frame — the
+//! device's `DoubleBuffer` back buffer does, a freshly cleared buffer does
+//! not — so a screen that uses the cache must not clear the whole frame
+//! first.  Instead the cache clears each component's bounds to its
+//! background before redrawing it, and clears the old bounds of a
+//! component that moved.
+//!
+//! Every region the cache touched is collected in
+//! [`redrawn`](RenderCache::redrawn).  Hand those to
+//! `DoubleBuffer::diff_within` so only tiles under redrawn components are
+//! compared, or use them directly as partial refresh windows.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_components::prelude::*;
+//! use embedded_graphics::{pixelcolor::Gray4, prelude::*};
+//!
+//! # fn demo<D: DrawTarget<Color = Gray4>>(display: &mut D) -> Result<(), D::Error> {
+//! let mut cache = RenderCache::<8>::new(Gray4::WHITE);
+//! let title = Label::new("Albums");
+//! cache.render("title", &title, display, Point::new(8, 8))?; // drawn
+//! cache.render("title", &title, display, Point::new(8, 8))?; // skipped
+//! for _region in cache.redrawn() {
+//!     // partial refresh / diff only here
+//! }
+//! cache.clear_redrawn();
+//! # Ok(())
+//! # }
+//! ```
+
+use core::hash::Hasher;
+
+use eink_system::render::envelope;
+use eink_system::style::Edges;
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+/// 64-bit FNV-1a hasher for component state.
+///
+/// Deterministic across builds and targets, unlike the std hasher, so a
+/// hash can be compared with one computed on an earlier frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateHasher(u64);
+
+impl StateHasher {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub const fn new() -> Self {
+        Self(Self::OFFSET)
+    }
+
+    /// Hash a grey level.
+    pub fn write_gray(&mut self, color: Gray4) {
+        self.write_u8(color.luma());
+    }
+
+    /// Hash an optional grey level.
+    pub fn write_opt_gray(&mut self, color: Option<Gray4>) {
+        match color {
+            Some(color) => {
+                self.write_u8(1);
+                self.write_gray(color);
+            }
+            None => self.write_u8(0),
+        }
+    }
+
+    /// Hash edge insets.
+    pub fn write_edges(&mut self, edges: Edges) {
+        for side in [edges.top, edges.right, edges.bottom, edges.left] {
+            self.write_u32(side);
+        }
+    }
+
+    /// Hash a string, length first so `("ab", "c")` and `("a", "bc")`
+    /// differ.
+    pub fn write_text(&mut self, text: &str) {
+        self.write_usize(text.len());
+        self.write(text.as_bytes());
+    }
+}
+
+impl Default for StateHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for StateHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(Self::PRIME);
+        }
+    }
+}
+
+/// Everything that affects how a component looks.
+///
+/// Two values with equal hashes must draw the same pixels at the same
+/// position.  Test ids and other metadata stay out of the hash.
+pub trait StateHash {
+    fn hash_state(&self, hasher: &mut StateHasher);
+
+    fn state_hash(&self) -> u64 {
+        let mut hasher = StateHasher::new();
+        self.hash_state(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// A component the cache can draw: its size and the shared
+/// `render(display, position)` signature.
+pub trait Cacheable: StateHash {
+    /// Size of the drawn area.
+    fn cached_size(&self) -> Size;
+
+    fn draw_cached<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>;
+}
+
+/// What [`RenderCache::render`] did.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CacheOutcome {
+    /// Same state and bounds as last time; nothing was drawn.
+    Skipped,
+    /// The component was (re)drawn.
+    Drawn,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct CacheEntry {
+    key: &'static str,
+    hash: u64,
+    bounds: Rectangle,
+}
+
+/// Last drawn state of up to `N` components.
+///
+/// Keys are component test ids (or any `'static` name unique per screen).
+/// Components past `N` are always drawn.
+#[derive(Debug, Clone)]
+pub struct RenderCache<const N: usize> {
+    entries: heapless::Vec<CacheEntry, N>,
+    redrawn: heapless::Vec<Rectangle, N>,
+    background: Gray4,
+    hits: u32,
+    misses: u32,
+}
+
+impl<const N: usize> RenderCache<N> {
+    /// Empty cache; redrawn components are first cleared to `background`.
+    pub fn new(background: Gray4) -> Self {
+        Self {
+            entries: heapless::Vec::new(),
+            redrawn: heapless::Vec::new(),
+            background,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Draw `component` at `position` unless it is unchanged.
+    pub fn render<C, D>(
+        &mut self,
+        key: &'static str,
+        component: &C,
+        display: &mut D,
+        position: Point,
+    ) -> Result<CacheOutcome, D::Error>
+    where
+        C: Cacheable,
+        D: DrawTarget<Color = Gray4>,
+    {
+        let bounds = Rectangle::new(position, component.cached_size());
+        self.render_with(key, component.state_hash(), bounds, display, |d| {
+            component.draw_cached(d, position)
+        })
+    }
+
+    /// Run `draw` for the component `key` unless `hash` and `bounds` match
+    /// what was drawn last time.
+    ///
+    /// For components without a [`Cacheable`] impl; `draw` must paint
+    /// inside `bounds` only.
+    pub fn render_with<D, F>(
+        &mut self,
+        key: &'static str,
+        hash: u64,
+        bounds: Rectangle,
+        display: &mut D,
+        draw: F,
+    ) -> Result<CacheOutcome, D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+        F: FnOnce(&mut D) -> Result<(), D::Error>,
+    {
+        let entry = self.entries.iter().position(|e| e.key == key);
+        let previous = entry.and_then(|i| self.entries.get(i)).copied();
+        if previous.is_some_and(|e| e.hash == hash && e.bounds == bounds) {
+            self.hits = self.hits.saturating_add(1);
+            return Ok(CacheOutcome::Skipped);
+        }
+        self.misses = self.misses.saturating_add(1);
+
+        if let Some(old) = previous.filter(|e| e.bounds != bounds) {
+            self.clear(display, old.bounds)?;
+        }
+        self.clear(display, bounds)?;
+        draw(display)?;
+
+        let new = CacheEntry { key, hash, bounds };
+        match entry.and_then(|i| self.entries.get_mut(i)) {
+            Some(slot) => *slot = new,
+            // A full cache just stops remembering; the component is drawn
+            // every frame.
+            None => {
+                let _ = self.entries.push(new);
+            }
+        }
+        Ok(CacheOutcome::Drawn)
+    }
+
+    fn clear<D>(&mut self, display: &mut D, region: Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        region
+            .into_styled(PrimitiveStyle::with_fill(self.background))
+            .draw(display)?;
+        self.note_redrawn(region);
+        Ok(())
+    }
+
+    fn note_redrawn(&mut self, region: Rectangle) {
+        if self.redrawn.contains(&region) {
+            return;
+        }
+        if let Err(region) = self.redrawn.push(region) {
+            // Out of slots: keep one rectangle covering everything.
+            let all = self.redrawn.iter().copied().fold(region, envelope);
+            self.redrawn.clear();
+            let _ = self.redrawn.push(all);
+        }
+    }
+
+    /// Regions drawn since the last [`clear_redrawn`](Self::clear_redrawn).
+    pub fn redrawn(&self) -> &[Rectangle] {
+        &self.redrawn
+    }
+
+    /// Forget the redrawn regions, after they were refreshed.
+    pub fn clear_redrawn(&mut self) {
+        self.redrawn.clear();
+    }
+
+    /// Draw `key` again next time, e.g. after something else painted over it.
+    pub fn invalidate(&mut self, key: &str) {
+        self.entries.retain(|e| e.key != key);
+    }
+
+    /// Draw everything again next time, e.g. after a screen change cleared
+    /// the frame.
+    pub fn invalidate_all(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Components skipped since creation.
+    pub fn hits(&self) -> u32 {
+        self.hits
+    }
+
+    /// Components drawn since creation.
+    pub fn misses(&self) -> u32 {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+    use super::*;
+    use crate::label::Label;
+    use crate::progress_bar::ProgressBar;
+    use core::convert::Infallible;
+
+    /// Counts pixel writes, so skipped draws are visible.
+    struct Counter {
+        pixels: u32,
+    }
+
+    impl OriginDimensions for Counter {
+        fn size(&self) -> Size {
+            Size::new(200, 100)
+        }
+    }
+
+    impl DrawTarget for Counter {
+        type Color = Gray4;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            self.pixels = self
+                .pixels
+                .saturating_add(pixels.into_iter().count() as u32);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_unchanged_component_is_skipped() {
+        let mut cache = RenderCache::<4>::new(Gray4::WHITE);
+        let mut display = Counter { pixels: 0 };
+        let label = Label::new("Albums");
+        let at = Point::new(4, 4);
+
+        assert_eq!(
+            cache.render("title", &label, &mut display, at).unwrap(),
+            CacheOutcome::Drawn
+        );
+        let drawn = display.pixels;
+        assert!(drawn > 0);
+        assert_eq!(cache.redrawn(), [Rectangle::new(at, label.dimensions())]);
+        cache.clear_redrawn();
+
+        assert_eq!(
+            cache.render("title", &label, &mut display, at).unwrap(),
+            CacheOutcome::Skipped
+        );
+        assert_eq!(display.pixels, drawn);
+        assert!(cache.redrawn().is_empty());
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+    }
+
+    #[test]
+    fn test_state_change_redraws() {
+        let mut cache = RenderCache::<4>::new(Gray4::WHITE);
+        let mut display = Counter { pixels: 0 };
+        let at = Point::new(0, 50);
+        let half = ProgressBar::new(100, 8).progress(0.5);
+        cache.render("progress", &half, &mut display, at).unwrap();
+        cache.clear_redrawn();
+
+        let same = ProgressBar::new(100, 8).progress(0.5);
+        let more = ProgressBar::new(100, 8).progress(0.6);
+        assert_eq!(same.state_hash(), half.state_hash());
+        assert_ne!(more.state_hash(), half.state_hash());
+        assert_eq!(
+            cache.render("progress", &more, &mut display, at).unwrap(),
+            CacheOutcome::Drawn
+        );
+        assert_eq!(cache.redrawn(), [Rectangle::new(at, more.size())]);
+    }
+
+    #[test]
+    fn test_moved_component_clears_old_bounds() {
+        let mut cache = RenderCache::<4>::new(Gray4::WHITE);
+        let mut display = Counter { pixels: 0 };
+        let label = Label::new("Now");
+        cache
+            .render("cursor", &label, &mut display, Point::new(0, 0))
+            .unwrap();
+        cache.clear_redrawn();
+        cache
+            .render("cursor", &label, &mut display, Point::new(0, 40))
+            .unwrap();
+        assert_eq!(
+            cache.redrawn(),
+            [
+                Rectangle::new(Point::new(0, 0), label.dimensions()),
+                Rectangle::new(Point::new(0, 40), label.dimensions()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalidate_forces_redraw() {
+        let mut cache = RenderCache::<4>::new(Gray4::WHITE);
+        let mut display = Counter { pixels: 0 };
+        let label = Label::new("A");
+        cache
+            .render("a", &label, &mut display, Point::zero())
+            .unwrap();
+        cache.invalidate("a");
+        assert_eq!(
+            cache
+                .render("a", &label, &mut display, Point::zero())
+                .unwrap(),
+            CacheOutcome::Drawn
+        );
+        cache.invalidate_all();
+        assert_eq!(
+            cache
+                .render("a", &label, &mut display, Point::zero())
+                .unwrap(),
+            CacheOutcome::Drawn
+        );
+    }
+
+    #[test]
+    fn test_full_cache_still_draws_and_collapses_regions() {
+        let mut cache = RenderCache::<1>::new(Gray4::WHITE);
+        let mut display = Counter { pixels: 0 };
+        let label = Label::new("A");
+        cache
+            .render("a", &label, &mut display, Point::zero())
+            .unwrap();
+        assert_eq!(
+            cache
+                .render("b", &label, &mut display, Point::new(20, 0))
+                .unwrap(),
+            CacheOutcome::Drawn
+        );
+        assert_eq!(
+            cache
+                .render("b", &label, &mut display, Point::new(20, 0))
+                .unwrap(),
+            CacheOutcome::Drawn
+        );
+        assert_eq!(
+            cache.redrawn(),
+            [Rectangle::new(
+                Point::zero(),
+                Size::new(20 + label.dimensions().width, 20)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_hash_ignores_nothing_visible() {
+        let a = Label::new("Play");
+        let b = Label::new("Play").color(Gray4::new(0x6));
+        assert_ne!(a.state_hash(), b.state_hash());
+        let mut h1 = StateHasher::new();
+        h1.write_text("ab");
+        h1.write_text("c");
+        let mut h2 = StateHasher::new();
+        h2.write_text("a");
+        h2.write_text("bc");
+        assert_ne!(h1.finish(), h2.finish());
+    }
+}
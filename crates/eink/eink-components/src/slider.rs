@@ -0,0 +1,368 @@
+//! Slider component for stepped numeric values
+
+use core::hash::Hasher;
+
+use crate::form::{self, FocusState, FormStyle};
+use crate::render_cache::{Cacheable, StateHash, StateHasher};
+use eink_system::layout::{Constraints, Layout, LayoutResult};
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+/// Track thickness in pixels.
+const TRACK_THICKNESS: u32 = 6;
+/// Thumb size along the track / across it.
+const THUMB_LEN: u32 = 8;
+const THUMB_CROSS: u32 = 18;
+
+/// Slider orientation
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Orientation {
+    /// Left = min, right = max (volume limit)
+    Horizontal,
+    /// Bottom = min, top = max (EQ bands)
+    Vertical,
+}
+
+/// Slider over an integer range, moved in whole steps
+///
+/// Values are integers (dB, percent) so a step always moves the thumb by a
+/// whole number of pixels and one button press maps to one partial refresh.
+/// When the range spans zero a centre tick marks 0 — the flat position of an
+/// EQ band.
+pub struct Slider {
+    min: i32,
+    max: i32,
+    value: i32,
+    step: u32,
+    length: u32,
+    orientation: Orientation,
+    state: FocusState,
+    style: FormStyle,
+    #[cfg(feature = "std")]
+    pub test_id: Option<String>,
+}
+
+impl Slider {
+    /// Create a horizontal slider over `min..=max`, starting at `min`
+    pub fn new(min: i32, max: i32) -> Self {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        Self {
+            min,
+            max,
+            value: min,
+            step: 1,
+            length: 100,
+            orientation: Orientation::Horizontal,
+            state: FocusState::Normal,
+            style: FormStyle::one_bit(),
+            #[cfg(feature = "std")]
+            test_id: None,
+        }
+    }
+
+    /// Set value (clamped to the range)
+    pub fn value(mut self, value: i32) -> Self {
+        self.value = value.clamp(self.min, self.max);
+        self
+    }
+
+    /// Set step size for [`increment`](Self::increment) / [`decrement`](Self::decrement)
+    pub fn step(mut self, step: u32) -> Self {
+        self.step = step.max(1);
+        self
+    }
+
+    /// Set track length in pixels (width, or height when vertical)
+    pub fn length(mut self, length: u32) -> Self {
+        self.length = length.max(THUMB_LEN);
+        self
+    }
+
+    /// Lay the slider out vertically (bottom = min)
+    pub fn vertical(mut self) -> Self {
+        self.orientation = Orientation::Vertical;
+        self
+    }
+
+    /// Set style
+    pub fn style(mut self, style: FormStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set focus state
+    pub fn focus(mut self, state: FocusState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Set the test ID for this component (used by eink-testing query_by_test_id).
+    #[cfg(feature = "std")]
+    pub fn test_id(mut self, id: impl Into<String>) -> Self {
+        self.test_id = Some(id.into());
+        self
+    }
+
+    /// Get the test ID for this component.
+    #[cfg(feature = "std")]
+    pub fn get_test_id(&self) -> Option<&str> {
+        self.test_id.as_deref()
+    }
+
+    /// Current value
+    pub fn get_value(&self) -> i32 {
+        self.value
+    }
+
+    /// Range as `(min, max)`
+    pub fn range(&self) -> (i32, i32) {
+        (self.min, self.max)
+    }
+
+    /// Current focus state
+    pub fn focus_state(&self) -> FocusState {
+        self.state
+    }
+
+    /// Set value in place (clamped). Returns `true` if it changed.
+    pub fn set_value(&mut self, value: i32) -> bool {
+        let value = value.clamp(self.min, self.max);
+        let changed = value != self.value;
+        self.value = value;
+        changed
+    }
+
+    /// Move one step up. Returns `true` if the value changed.
+    pub fn increment(&mut self) -> bool {
+        self.nudge(i64::from(self.step))
+    }
+
+    /// Move one step down. Returns `true` if the value changed.
+    pub fn decrement(&mut self) -> bool {
+        self.nudge(0i64.saturating_sub(i64::from(self.step)))
+    }
+
+    /// Move by `steps` steps (rotary encoder detents). Returns `true` if the value changed.
+    pub fn step_by(&mut self, steps: i32) -> bool {
+        self.nudge(i64::from(steps).saturating_mul(i64::from(self.step)))
+    }
+
+    fn nudge(&mut self, delta: i64) -> bool {
+        if self.state == FocusState::Disabled {
+            return false;
+        }
+        let target = i64::from(self.value)
+            .saturating_add(delta)
+            .clamp(i64::from(self.min), i64::from(self.max));
+        // Clamped to the i32 range above.
+        self.set_value(target as i32)
+    }
+
+    /// Position of the value along the range, 0.0 (min) to 1.0 (max)
+    pub fn fraction(&self) -> f32 {
+        let span = i64::from(self.max).saturating_sub(i64::from(self.min));
+        if span == 0 {
+            return 0.0;
+        }
+        i64::from(self.value).saturating_sub(i64::from(self.min)) as f32 / span as f32
+    }
+
+    /// Control size without padding
+    fn content_size(&self) -> Size {
+        match self.orientation {
+            Orientation::Horizontal => Size::new(self.length, THUMB_CROSS),
+            Orientation::Vertical => Size::new(THUMB_CROSS, self.length),
+        }
+    }
+
+    /// Size including padding
+    pub fn size(&self) -> Size {
+        form::padded_size(self.content_size(), &self.style)
+    }
+
+    /// Get slider bounding box
+    pub fn bounds(&self, position: Point) -> Rectangle {
+        Rectangle::new(position, self.size())
+    }
+
+    /// Offset of the thumb's leading edge along the track, in pixels from the min end
+    // SAFETY: length >= THUMB_LEN is enforced by the builder; fraction is in 0.0..=1.0.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn thumb_offset(&self, fraction: f32) -> u32 {
+        ((self.length - THUMB_LEN) as f32 * fraction + 0.5) as u32
+    }
+
+    /// Render slider to display
+    // SAFETY: all geometry is bounded by the slider length and fixed thumb size.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn render<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        let bounds = self.bounds(position);
+        form::draw_background(display, bounds, &self.style)?;
+
+        let origin = form::content_origin(position, &self.style);
+        let (fg, bg) = (self.style.foreground, self.style.background);
+        let inset = ((THUMB_CROSS - TRACK_THICKNESS) / 2) as i32;
+        let thumb_at = self.thumb_offset(self.fraction());
+        let filled = thumb_at + THUMB_LEN / 2;
+        let zero_at = (self.min < 0 && self.max > 0).then(|| {
+            let span = self.max as f32 - self.min as f32;
+            self.thumb_offset(-self.min as f32 / span) + THUMB_LEN / 2
+        });
+
+        // Map (along, across, len_along, len_across) to a rectangle; "along"
+        // runs from the min end, which is the bottom when vertical.
+        let rect = |along: u32, across: i32, len: u32, cross: u32| match self.orientation {
+            Orientation::Horizontal => Rectangle::new(
+                origin + Point::new(along as i32, across),
+                Size::new(len, cross),
+            ),
+            Orientation::Vertical => Rectangle::new(
+                origin + Point::new(across, (self.length - along - len) as i32),
+                Size::new(cross, len),
+            ),
+        };
+
+        // Track: outline, filled from the min end up to the thumb centre.
+        rect(0, inset, self.length, TRACK_THICKNESS)
+            .into_styled(PrimitiveStyle::with_stroke(fg, 1))
+            .draw(display)?;
+        rect(0, inset, filled, TRACK_THICKNESS)
+            .into_styled(PrimitiveStyle::with_fill(fg))
+            .draw(display)?;
+
+        // Zero tick spanning the full cross size, so it stays visible under the fill.
+        if let Some(zero) = zero_at {
+            let tick_color = if zero < filled { bg } else { fg };
+            rect(zero, inset, 1, TRACK_THICKNESS)
+                .into_styled(PrimitiveStyle::with_fill(tick_color))
+                .draw(display)?;
+            rect(zero, 0, 1, inset as u32)
+                .into_styled(PrimitiveStyle::with_fill(fg))
+                .draw(display)?;
+        }
+
+        // Thumb: solid bar with a background core so it reads as a handle
+        // against the filled track.
+        let thumb = rect(thumb_at, 0, THUMB_LEN, THUMB_CROSS);
+        thumb
+            .into_styled(PrimitiveStyle::with_fill(fg))
+            .draw(display)?;
+        thumb
+            .offset(-2)
+            .into_styled(PrimitiveStyle::with_fill(bg))
+            .draw(display)?;
+
+        form::draw_state(display, bounds, self.state, &self.style)
+    }
+}
+
+impl Layout for Slider {
+    fn layout(&self, constraints: Constraints) -> LayoutResult {
+        LayoutResult::leaf(constraints.constrain(self.size()))
+    }
+}
+
+impl StateHash for Slider {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_i32(self.min);
+        hasher.write_i32(self.max);
+        hasher.write_i32(self.value);
+        hasher.write_u32(self.step);
+        hasher.write_u32(self.length);
+        hasher.write_u8(self.orientation as u8);
+        hasher.write_u8(self.state as u8);
+        self.style.hash_state(hasher);
+    }
+}
+
+impl Cacheable for Slider {
+    fn cached_size(&self) -> Size {
+        self.size()
+    }
+
+    fn draw_cached<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        self.render(display, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_value_is_clamped() {
+        let slider = Slider::new(-12, 12).value(40);
+        assert_eq!(slider.get_value(), 12);
+        let slider = Slider::new(-12, 12).value(-40);
+        assert_eq!(slider.get_value(), -12);
+    }
+
+    #[test]
+    fn test_reversed_range_is_normalised() {
+        let slider = Slider::new(10, 0);
+        assert_eq!(slider.range(), (0, 10));
+    }
+
+    #[test]
+    fn test_stepping() {
+        let mut slider = Slider::new(0, 100).value(50).step(10);
+        assert!(slider.increment());
+        assert_eq!(slider.get_value(), 60);
+        assert!(slider.step_by(-3));
+        assert_eq!(slider.get_value(), 30);
+        assert!(slider.step_by(100));
+        assert_eq!(slider.get_value(), 100);
+        assert!(!slider.increment());
+    }
+
+    #[test]
+    fn test_disabled_slider_ignores_input() {
+        let mut slider = Slider::new(0, 10).value(5).focus(FocusState::Disabled);
+        assert!(!slider.increment());
+        assert!(!slider.decrement());
+        assert_eq!(slider.get_value(), 5);
+    }
+
+    #[test]
+    fn test_fraction() {
+        assert_eq!(Slider::new(-12, 12).value(0).fraction(), 0.5);
+        assert_eq!(Slider::new(0, 100).value(100).fraction(), 1.0);
+        assert_eq!(Slider::new(5, 5).fraction(), 0.0);
+    }
+
+    #[test]
+    fn test_render_stays_in_bounds_at_extremes() {
+        use embedded_graphics::mock_display::MockDisplay;
+
+        for value in [-12, 0, 12] {
+            for slider in [
+                Slider::new(-12, 12).length(50).value(value),
+                Slider::new(-12, 12).length(50).value(value).vertical(),
+            ] {
+                let slider = slider.focus(FocusState::Focused);
+                let mut display: MockDisplay<Gray4> = MockDisplay::new();
+                display.set_allow_overdraw(true);
+                slider.render(&mut display, Point::zero()).unwrap();
+                assert_eq!(display.affected_area(), slider.bounds(Point::zero()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_vertical_swaps_dimensions() {
+        let h = Slider::new(0, 10).length(80);
+        let v = Slider::new(0, 10).length(80).vertical();
+        assert_eq!(h.size().width, v.size().height);
+        assert_eq!(h.size().height, v.size().width);
+    }
+}
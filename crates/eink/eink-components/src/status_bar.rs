@@ -0,0 +1,556 @@
+//! Status bar composite component
+//!
+//! One row hosting playback state, Bluetooth state, clock, volume and
+//! battery, shown at the top of every screen.  Each segment tracks its own
+//! dirty flag: changing the clock's minute marks only the clock segment, and
+//! [`StatusBar::render_dirty`] redraws just that cell and returns its
+//! rectangle for a partial refresh — instead of flashing the whole bar.
+//!
+//! Segments are laid out as equal-width cells, the same distribution
+//! [`HStack`] gives its children, and [`Layout`] for the bar delegates to an
+//! `HStack` so it composes with other containers.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_components::prelude::*;
+//! use embedded_graphics::{pixelcolor::Gray4, prelude::*};
+//!
+//! # fn demo<D: DrawTarget<Color = Gray4>>(display: &mut D) -> Result<(), D::Error> {
+//! let mut bar = StatusBar::new(250);
+//! bar.render_dirty(display, Point::zero())?; // first frame: everything
+//!
+//! bar.set_clock(12, 35);
+//! if let Some(_region) = bar.render_dirty(display, Point::zero())? {
+//!     // partial refresh of `_region` only (the clock cell)
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::icon::{Icon, IconType};
+use core::fmt::Write;
+use eink_system::layout::{Constraints, Layout, LayoutResult};
+use eink_system::prelude::HStack;
+use eink_system::render::envelope;
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+
+/// Default bar height in pixels.
+pub const STATUS_BAR_HEIGHT: u32 = 14;
+
+/// Bluetooth connection state
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum BluetoothState {
+    /// Radio off — segment left blank.
+    #[default]
+    Off,
+    /// On, not connected — outlined "BT".
+    On,
+    /// Connected to a sink — inverted "BT".
+    Connected,
+}
+
+/// Playback state
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum PlaybackState {
+    #[default]
+    Stopped,
+    Playing,
+    Paused,
+}
+
+/// Status bar segments, in display order (left to right)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Playback,
+    Bluetooth,
+    Clock,
+    Volume,
+    Battery,
+}
+
+impl Segment {
+    /// Every segment, left to right.
+    pub const ALL: [Segment; 5] = [
+        Segment::Playback,
+        Segment::Bluetooth,
+        Segment::Clock,
+        Segment::Volume,
+        Segment::Battery,
+    ];
+
+    /// Cell index from the left.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// Values shown by the status bar
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct StatusInfo {
+    pub playback: PlaybackState,
+    pub bluetooth: BluetoothState,
+    /// Hour (0–23) and minute (0–59).
+    pub clock: (u8, u8),
+    /// Volume 0–100.
+    pub volume: u8,
+    /// Battery 0–100.
+    pub battery_percent: u8,
+    pub charging: bool,
+}
+
+/// Status bar with per-segment dirty tracking
+pub struct StatusBar {
+    info: StatusInfo,
+    width: u32,
+    height: u32,
+    foreground: Gray4,
+    background: Gray4,
+    /// One bit per [`Segment`].
+    dirty: u8,
+    #[cfg(feature = "std")]
+    pub test_id: Option<String>,
+}
+
+impl StatusBar {
+    /// Create a status bar `width` pixels wide; every segment starts dirty
+    pub fn new(width: u32) -> Self {
+        Self {
+            info: StatusInfo::default(),
+            width,
+            height: STATUS_BAR_HEIGHT,
+            foreground: Gray4::BLACK,
+            background: Gray4::WHITE,
+            dirty: Self::ALL_DIRTY,
+            #[cfg(feature = "std")]
+            test_id: None,
+        }
+    }
+
+    const ALL_DIRTY: u8 = (1 << Segment::ALL.len()) - 1;
+
+    /// Set bar height
+    pub fn height(mut self, height: u32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Set colors
+    pub fn colors(mut self, background: Gray4, foreground: Gray4) -> Self {
+        self.background = background;
+        self.foreground = foreground;
+        self
+    }
+
+    /// Set the initial values (all segments stay dirty)
+    pub fn info(mut self, info: StatusInfo) -> Self {
+        self.info = info;
+        self
+    }
+
+    /// Set the test ID for this component (used by eink-testing query_by_test_id).
+    #[cfg(feature = "std")]
+    pub fn test_id(mut self, id: impl Into<String>) -> Self {
+        self.test_id = Some(id.into());
+        self
+    }
+
+    /// Get the test ID for this component.
+    #[cfg(feature = "std")]
+    pub fn get_test_id(&self) -> Option<&str> {
+        self.test_id.as_deref()
+    }
+
+    /// Current values
+    pub fn status(&self) -> &StatusInfo {
+        &self.info
+    }
+
+    /// Get dimensions
+    pub fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+
+    // --- Updates -------------------------------------------------------------
+
+    /// Apply new values, marking only the segments whose value changed.
+    /// Returns `true` if anything changed.
+    pub fn update(&mut self, info: StatusInfo) -> bool {
+        let old = self.info;
+        self.info = info;
+        let changes = [
+            (Segment::Playback, old.playback != info.playback),
+            (Segment::Bluetooth, old.bluetooth != info.bluetooth),
+            (Segment::Clock, old.clock != info.clock),
+            (Segment::Volume, old.volume != info.volume),
+            (
+                Segment::Battery,
+                old.battery_percent != info.battery_percent || old.charging != info.charging,
+            ),
+        ];
+        let mut any = false;
+        for (segment, changed) in changes {
+            if changed {
+                self.mark_dirty(segment);
+                any = true;
+            }
+        }
+        any
+    }
+
+    pub fn set_playback(&mut self, playback: PlaybackState) -> bool {
+        self.update(StatusInfo {
+            playback,
+            ..self.info
+        })
+    }
+
+    pub fn set_bluetooth(&mut self, bluetooth: BluetoothState) -> bool {
+        self.update(StatusInfo {
+            bluetooth,
+            ..self.info
+        })
+    }
+
+    /// Set the clock (values are clamped to 23:59)
+    pub fn set_clock(&mut self, hour: u8, minute: u8) -> bool {
+        self.update(StatusInfo {
+            clock: (hour.min(23), minute.min(59)),
+            ..self.info
+        })
+    }
+
+    /// Set volume (clamped to 100)
+    pub fn set_volume(&mut self, volume: u8) -> bool {
+        self.update(StatusInfo {
+            volume: volume.min(100),
+            ..self.info
+        })
+    }
+
+    /// Set battery level (clamped to 100) and charging state
+    pub fn set_battery(&mut self, percent: u8, charging: bool) -> bool {
+        self.update(StatusInfo {
+            battery_percent: percent.min(100),
+            charging,
+            ..self.info
+        })
+    }
+
+    // --- Dirty tracking ------------------------------------------------------
+
+    pub fn mark_dirty(&mut self, segment: Segment) {
+        self.dirty |= segment.bit();
+    }
+
+    /// Mark every segment dirty (e.g. after a full refresh cleared the screen)
+    pub fn invalidate(&mut self) {
+        self.dirty = Self::ALL_DIRTY;
+    }
+
+    pub fn is_dirty(&self, segment: Segment) -> bool {
+        self.dirty & segment.bit() != 0
+    }
+
+    /// Dirty segments, left to right
+    pub fn dirty_segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        Segment::ALL.into_iter().filter(|&s| self.is_dirty(s))
+    }
+
+    /// Bounding box of all dirty segments with the bar at `position`
+    pub fn dirty_region(&self, position: Point) -> Option<Rectangle> {
+        self.dirty_segments()
+            .map(|s| self.segment_bounds(s, position))
+            .reduce(envelope)
+    }
+
+    /// Cell occupied by `segment` with the bar at `position`
+    ///
+    /// Cells share the width equally; the last cell absorbs the remainder.
+    // SAFETY: cell geometry is bounded by the bar width (display dimension).
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn segment_bounds(&self, segment: Segment, position: Point) -> Rectangle {
+        let count = Segment::ALL.len() as u32;
+        let cell_w = self.width / count;
+        let index = segment.index() as u32;
+        let width = if index + 1 == count {
+            self.width - cell_w * index
+        } else {
+            cell_w
+        };
+        Rectangle::new(
+            position + Point::new((cell_w * index) as i32, 0),
+            Size::new(width, self.height),
+        )
+    }
+
+    // --- Rendering -----------------------------------------------------------
+
+    /// Render every segment (dirty flags are left untouched)
+    pub fn render<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        for segment in Segment::ALL {
+            self.render_segment(display, segment, position)?;
+        }
+        Ok(())
+    }
+
+    /// Render only dirty segments and clear their flags
+    ///
+    /// Returns the region to refresh, or `None` when nothing changed.
+    pub fn render_dirty<D>(
+        &mut self,
+        display: &mut D,
+        position: Point,
+    ) -> Result<Option<Rectangle>, D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        let region = self.dirty_region(position);
+        for segment in Segment::ALL {
+            if self.is_dirty(segment) {
+                self.render_segment(display, segment, position)?;
+            }
+        }
+        self.dirty = 0;
+        Ok(region)
+    }
+
+    /// Clear one cell and draw its content
+    // SAFETY: cell geometry is bounded by the bar size (display dimensions).
+    #[allow(clippy::arithmetic_side_effects)]
+    fn render_segment<D>(
+        &self,
+        display: &mut D,
+        segment: Segment,
+        position: Point,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        let cell = self.segment_bounds(segment, position);
+        cell.into_styled(PrimitiveStyle::with_fill(self.background))
+            .draw(display)?;
+        let center = cell.center();
+        let mut text: heapless::String<8> = heapless::String::new();
+
+        match segment {
+            Segment::Playback => {
+                let icon_type = match self.info.playback {
+                    PlaybackState::Stopped => IconType::Stop,
+                    PlaybackState::Playing => IconType::Play,
+                    PlaybackState::Paused => IconType::Pause,
+                };
+                let size = self.height.saturating_sub(6).max(4);
+                let half = (size / 2) as i32;
+                Icon::new(icon_type, size)
+                    .color(self.foreground)
+                    .render(display, center - Point::new(half, half))?;
+                return Ok(());
+            }
+            Segment::Bluetooth => {
+                if self.info.bluetooth == BluetoothState::Off {
+                    return Ok(());
+                }
+                let badge = Rectangle::with_center(center, Size::new(17, 11));
+                let fg = if self.info.bluetooth == BluetoothState::Connected {
+                    badge
+                        .into_styled(PrimitiveStyle::with_fill(self.foreground))
+                        .draw(display)?;
+                    self.background
+                } else {
+                    badge
+                        .into_styled(PrimitiveStyle::with_stroke(self.foreground, 1))
+                        .draw(display)?;
+                    self.foreground
+                };
+                return self.centered_text(display, "BT", center, fg);
+            }
+            Segment::Clock => {
+                let (h, m) = self.info.clock;
+                let _ = write!(text, "{h:02}:{m:02}");
+            }
+            Segment::Volume => {
+                let _ = write!(text, "V{}", self.info.volume);
+            }
+            Segment::Battery => {
+                // 16×8 body + 2×4 nub, filled in proportion; '+' when charging.
+                let body = Rectangle::new(
+                    Point::new(cell.top_left.x + 2, center.y - 4),
+                    Size::new(16, 8),
+                );
+                body.into_styled(PrimitiveStyle::with_stroke(self.foreground, 1))
+                    .draw(display)?;
+                Rectangle::new(
+                    Point::new(body.top_left.x + 16, center.y - 2),
+                    Size::new(2, 4),
+                )
+                .into_styled(PrimitiveStyle::with_fill(self.foreground))
+                .draw(display)?;
+                let fill = 14 * u32::from(self.info.battery_percent) / 100;
+                if fill > 0 {
+                    Rectangle::new(body.top_left + Point::new(1, 1), Size::new(fill, 6))
+                        .into_styled(PrimitiveStyle::with_fill(self.foreground))
+                        .draw(display)?;
+                }
+                let _ = write!(
+                    text,
+                    "{}{}",
+                    self.info.battery_percent,
+                    if self.info.charging { "+" } else { "%" }
+                );
+                let text_center = Point::new(
+                    body.top_left.x + 20 + (cell.size.width as i32 - 22) / 2,
+                    center.y,
+                );
+                return self.centered_text(display, &text, text_center, self.foreground);
+            }
+        }
+        self.centered_text(display, &text, center, self.foreground)
+    }
+
+    fn centered_text<D>(
+        &self,
+        display: &mut D,
+        text: &str,
+        center: Point,
+        color: Gray4,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        let style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Middle)
+            .build();
+        Text::with_text_style(text, center, MonoTextStyle::new(&FONT_6X10, color), style)
+            .draw(display)?;
+        Ok(())
+    }
+}
+
+/// Fixed-size cell standing in for one segment inside the layout `HStack`.
+struct Cell(Size);
+
+impl Layout for Cell {
+    fn layout(&self, constraints: Constraints) -> LayoutResult {
+        LayoutResult::leaf(constraints.constrain(self.0))
+    }
+}
+
+impl Layout for StatusBar {
+    // SAFETY: cell sizes are bounded by the bar width (display dimension).
+    #[allow(clippy::arithmetic_side_effects)]
+    fn layout(&self, constraints: Constraints) -> LayoutResult {
+        let cell_w = self.width / Segment::ALL.len() as u32;
+        let stack = HStack::<5>::new().children(
+            Segment::ALL
+                .into_iter()
+                .map(|_| Box::new(Cell(Size::new(cell_w, self.height))) as Box<dyn Layout>),
+        );
+        let stacked = stack.layout(constraints).size;
+        // The last cell absorbs the division remainder, so the bar is always
+        // its full width when the constraints allow it.
+        LayoutResult::leaf(
+            constraints.constrain(Size::new(stacked.width.max(self.width), stacked.height)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use embedded_graphics::mock_display::MockDisplay;
+
+    fn clean_bar() -> StatusBar {
+        let mut bar = StatusBar::new(60);
+        let mut display: MockDisplay<Gray4> = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        display.set_allow_out_of_bounds_drawing(true);
+        bar.render_dirty(&mut display, Point::zero()).unwrap();
+        bar
+    }
+
+    #[test]
+    fn test_new_bar_is_fully_dirty() {
+        let bar = StatusBar::new(250);
+        assert_eq!(bar.dirty_segments().count(), Segment::ALL.len());
+        assert_eq!(
+            bar.dirty_region(Point::zero()),
+            Some(Rectangle::new(
+                Point::zero(),
+                Size::new(250, STATUS_BAR_HEIGHT)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_clock_tick_dirties_only_clock() {
+        let mut bar = clean_bar();
+        assert!(bar.dirty_region(Point::zero()).is_none());
+
+        assert!(bar.set_clock(12, 35));
+        assert!(bar.dirty_segments().eq([Segment::Clock]));
+        assert_eq!(
+            bar.dirty_region(Point::new(0, 10)),
+            Some(bar.segment_bounds(Segment::Clock, Point::new(0, 10)))
+        );
+    }
+
+    #[test]
+    fn test_unchanged_values_stay_clean() {
+        let mut bar = clean_bar();
+        assert!(!bar.set_volume(0));
+        assert!(!bar.set_bluetooth(BluetoothState::Off));
+        assert!(bar.dirty_region(Point::zero()).is_none());
+    }
+
+    #[test]
+    fn test_render_dirty_clears_flags_and_reports_region() {
+        let mut bar = clean_bar();
+        bar.set_battery(80, false);
+        bar.set_playback(PlaybackState::Playing);
+
+        let mut display: MockDisplay<Gray4> = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        display.set_allow_out_of_bounds_drawing(true);
+        let region = bar.render_dirty(&mut display, Point::zero()).unwrap();
+        let expected = envelope(
+            bar.segment_bounds(Segment::Playback, Point::zero()),
+            bar.segment_bounds(Segment::Battery, Point::zero()),
+        );
+        assert_eq!(region, Some(expected));
+        assert_eq!(bar.dirty_segments().count(), 0);
+        assert_eq!(bar.render_dirty(&mut display, Point::zero()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_segments_tile_the_bar() {
+        let bar = StatusBar::new(252);
+        let widths: u32 = Segment::ALL
+            .iter()
+            .map(|&s| bar.segment_bounds(s, Point::zero()).size.width)
+            .sum();
+        assert_eq!(widths, 252);
+        let result = bar.layout(Constraints::loose(Size::new(400, 100)));
+        assert_eq!(result.size, Size::new(252, STATUS_BAR_HEIGHT));
+    }
+}
@@ -0,0 +1,201 @@
+//! Toggle (on/off switch) component
+
+use core::hash::Hasher;
+
+use crate::form::{self, FocusState, FormStyle};
+use crate::render_cache::{Cacheable, StateHash, StateHasher};
+use eink_system::layout::{Constraints, Layout, LayoutResult};
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{Circle, CornerRadii, PrimitiveStyle, Rectangle, RoundedRectangle},
+};
+
+/// Track size of the switch (without padding).
+pub const TOGGLE_TRACK: Size = Size::new(36, 18);
+
+/// On/off switch
+///
+/// Off draws an outlined track with the knob on the left; on fills the track
+/// and moves a hollow knob to the right, so the two states differ in shape
+/// as well as ink coverage and stay distinguishable in 1-bit mode.
+pub struct Toggle {
+    on: bool,
+    state: FocusState,
+    style: FormStyle,
+    #[cfg(feature = "std")]
+    pub test_id: Option<String>,
+}
+
+impl Toggle {
+    /// Create a toggle that is initially on or off
+    pub fn new(on: bool) -> Self {
+        Self {
+            on,
+            state: FocusState::Normal,
+            style: FormStyle::one_bit(),
+            #[cfg(feature = "std")]
+            test_id: None,
+        }
+    }
+
+    /// Set style
+    pub fn style(mut self, style: FormStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set focus state
+    pub fn focus(mut self, state: FocusState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Set the test ID for this component (used by eink-testing query_by_test_id).
+    #[cfg(feature = "std")]
+    pub fn test_id(mut self, id: impl Into<String>) -> Self {
+        self.test_id = Some(id.into());
+        self
+    }
+
+    /// Get the test ID for this component.
+    #[cfg(feature = "std")]
+    pub fn get_test_id(&self) -> Option<&str> {
+        self.test_id.as_deref()
+    }
+
+    /// Whether the switch is on
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    /// Current focus state
+    pub fn focus_state(&self) -> FocusState {
+        self.state
+    }
+
+    /// Flip the switch (ignored while disabled). Returns the new value.
+    pub fn toggle(&mut self) -> bool {
+        if self.state != FocusState::Disabled {
+            self.on = !self.on;
+        }
+        self.on
+    }
+
+    /// Size including padding
+    pub fn size(&self) -> Size {
+        form::padded_size(TOGGLE_TRACK, &self.style)
+    }
+
+    /// Get toggle bounding box
+    pub fn bounds(&self, position: Point) -> Rectangle {
+        Rectangle::new(position, self.size())
+    }
+
+    /// Render toggle to display
+    // SAFETY: knob geometry is derived from the fixed 36×18 track; overflow is not possible.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn render<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        let bounds = self.bounds(position);
+        form::draw_background(display, bounds, &self.style)?;
+
+        let origin = form::content_origin(position, &self.style);
+        let radius = TOGGLE_TRACK.height / 2;
+        let track = RoundedRectangle::new(
+            Rectangle::new(origin, TOGGLE_TRACK),
+            CornerRadii::new(Size::new(radius, radius)),
+        );
+        let (fg, bg) = (self.style.foreground, self.style.background);
+
+        let knob_d = TOGGLE_TRACK.height - 6;
+        let knob_y = origin.y + 3;
+        if self.on {
+            track
+                .into_styled(PrimitiveStyle::with_fill(fg))
+                .draw(display)?;
+            let knob_x = origin.x + (TOGGLE_TRACK.width - knob_d - 3) as i32;
+            Circle::new(Point::new(knob_x, knob_y), knob_d)
+                .into_styled(PrimitiveStyle::with_fill(bg))
+                .draw(display)?;
+        } else {
+            track
+                .into_styled(PrimitiveStyle::with_stroke(fg, 2))
+                .draw(display)?;
+            Circle::new(Point::new(origin.x + 3, knob_y), knob_d)
+                .into_styled(PrimitiveStyle::with_fill(fg))
+                .draw(display)?;
+        }
+
+        form::draw_state(display, bounds, self.state, &self.style)
+    }
+}
+
+impl Layout for Toggle {
+    fn layout(&self, constraints: Constraints) -> LayoutResult {
+        LayoutResult::leaf(constraints.constrain(self.size()))
+    }
+}
+
+impl StateHash for Toggle {
+    fn hash_state(&self, hasher: &mut StateHasher) {
+        hasher.write_u8(u8::from(self.on));
+        hasher.write_u8(self.state as u8);
+        self.style.hash_state(hasher);
+    }
+}
+
+impl Cacheable for Toggle {
+    fn cached_size(&self) -> Size {
+        self.size()
+    }
+
+    fn draw_cached<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        self.render(display, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eink_system::prelude::Edges;
+
+    #[test]
+    fn test_toggle_flips() {
+        let mut toggle = Toggle::new(false);
+        assert!(toggle.toggle());
+        assert!(toggle.is_on());
+        assert!(!toggle.toggle());
+    }
+
+    #[test]
+    fn test_disabled_toggle_ignores_input() {
+        let mut toggle = Toggle::new(true).focus(FocusState::Disabled);
+        assert!(toggle.toggle());
+        assert!(toggle.is_on());
+    }
+
+    #[test]
+    fn test_size_includes_padding() {
+        let style = FormStyle {
+            padding: Edges::all(4),
+            ..FormStyle::one_bit()
+        };
+        let toggle = Toggle::new(false).style(style);
+        assert_eq!(toggle.size(), Size::new(44, 26));
+        let result = toggle.layout(Constraints::loose(Size::new(200, 100)));
+        assert_eq!(result.size, Size::new(44, 26));
+    }
+
+    #[test]
+    fn test_focus_does_not_change_size() {
+        let normal = Toggle::new(false);
+        let focused = Toggle::new(false).focus(FocusState::Focused);
+        assert_eq!(normal.size(), focused.size());
+    }
+}
@@ -9,6 +9,14 @@
 //! - Temperature effects with custom LUTs
 //! - Ghosting differences
 
+// Example code: allow debug output, fixed indexing and unchecked layout maths.
+#![allow(
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_wrap,
+    clippy::indexing_slicing,
+    clippy::use_debug
+)]
+
 use eink_emulator::lut::{LutPhase, WaveformLut, WaveformLutSet};
 use eink_emulator::{PixelState, WaveformMode};
 use std::fs;
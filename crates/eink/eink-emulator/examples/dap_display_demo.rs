@@ -1,3 +1,19 @@
+//! DAP Display Demo
+//!
+//! Draws a sample player screen on the DAP's 3.97" panel (GDEM0397T81P)
+//! and reports its power consumption.
+//!
+//! Run with: cargo run --example dap_display_demo
+
+// Example code: allow unwraps and unchecked layout maths.
+#![allow(
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::unwrap_used
+)]
+
 use eink_emulator::{DisplayDriver, Emulator};
 use eink_specs::displays::gooddisplay::GDEM0397T81P;
 use embedded_graphics::{
@@ -34,9 +50,9 @@ async fn main() {
     println!("  Controller: SSD1677");
     println!("  Panel: Carta 1200");
     println!("  Grayscale: {} levels", GDEM0397T81P.grayscale_levels);
-    println!("  Full Refresh: {}ms", GDEM0397T81P.full_refresh_ms);
-    println!("  Fast Refresh: {}ms", GDEM0397T81P.fast_refresh_ms);
-    println!("  Partial Refresh: {}ms", GDEM0397T81P.partial_refresh_ms);
+    println!("  Full Refresh: {}", GDEM0397T81P.full_refresh_ms);
+    println!("  Fast Refresh: {}", GDEM0397T81P.fast_refresh_ms);
+    println!("  Partial Refresh: {}", GDEM0397T81P.partial_refresh_ms);
     println!("  Active Area: 86.40 × 51.84mm");
     println!("  PPI: 235 (high resolution!)");
     println!();
@@ -7,6 +7,9 @@
 //!
 //! Run with: cargo run --example hello_window
 
+// Example code: allow unwraps.
+#![allow(clippy::unwrap_used)]
+
 use eink_emulator::{DisplayDriver, Emulator};
 use embedded_graphics::mono_font::{
     ascii::{FONT_6X10, FONT_9X18_BOLD},
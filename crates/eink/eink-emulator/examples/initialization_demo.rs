@@ -4,6 +4,9 @@
 //!
 //! Run with: cargo run --example initialization_demo --target x86_64-pc-windows-msvc
 
+// Example code: allow unwraps and debug output.
+#![allow(clippy::unwrap_used, clippy::use_debug)]
+
 use eink_emulator::{DisplayDriver, Emulator, InitializationState};
 use embedded_graphics::mono_font::{ascii::FONT_6X10, MonoTextStyle};
 use embedded_graphics::pixelcolor::Gray4;
@@ -0,0 +1,43 @@
+//! Mockup Viewer Example
+//!
+//! Previews static PNG mockups on the DAP's 3.97" panel (GDEM0397T81P,
+//! 800×480) with realistic e-ink refresh and ghosting — no Rust required.
+//!
+//! - Drag a `.png` onto the window to show it (full GC16 refresh)
+//! - Drop another one to see how it ghosts over the previous screen
+//!
+//! Images are fitted to the panel (aspect ratio kept, letterboxed in white)
+//! and quantized to 16 grey levels.
+//!
+//! Run with: cargo run --example mockup_viewer [-- path/to/first.png]
+
+// Example code: allow unwraps.
+#![allow(clippy::unwrap_used)]
+
+use eink_emulator::{DisplayDriver, Emulator};
+use eink_specs::displays::gooddisplay::GDEM0397T81P;
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() {
+    let mut emulator = Emulator::with_spec(&GDEM0397T81P);
+    emulator.framebuffer.clear();
+    emulator.refresh_full().await.unwrap();
+
+    if let Some(path) = std::env::args().nth(1) {
+        println!("Loading {path}...");
+        if let Err(e) = emulator.show_image(&path).await {
+            eprintln!("Could not load {path}: {e}");
+        }
+    }
+
+    println!("Drop a PNG onto the window to preview it. Close the window to exit.");
+    while emulator.pump_window_events() {
+        match emulator.show_dropped_image().await {
+            Ok(true) => println!("Mockup refreshed"),
+            Ok(false) => {}
+            Err(e) => eprintln!("Could not load mockup: {e}"),
+        }
+        tokio::time::sleep(Duration::from_millis(16)).await;
+    }
+}
@@ -3,6 +3,9 @@
 //! This example demonstrates how to use the partial window and dirty
 //! region tracking features of the eink-emulator.
 
+// Example code: allow debug output.
+#![allow(clippy::use_debug)]
+
 use eink_emulator::{Emulator, PartialWindow};
 use embedded_graphics::pixelcolor::Gray4;
 use embedded_graphics::prelude::*;
@@ -8,8 +8,15 @@
 //!
 //! Run with: cargo run --target x86_64-pc-windows-msvc --example phase2_demo
 
+// Example code: allow unwraps, debug output and unchecked layout maths.
+#![allow(
+    clippy::arithmetic_side_effects,
+    clippy::unwrap_used,
+    clippy::use_debug
+)]
+
 use eink_emulator::{DisplayDriver, Emulator};
-use eink_specs::displays;
+use eink_specs::{displays, DisplayMs};
 use embedded_graphics::pixelcolor::Gray4;
 use embedded_graphics::prelude::*;
 use embedded_graphics::primitives::{Circle, PrimitiveStyle, Rectangle};
@@ -51,7 +58,7 @@ async fn demonstrate_multibrand_support() {
     );
     println!("  Controller: {:?}", waveshare.spec().controller);
     println!("  Panel: {:?}", waveshare.spec().panel_type);
-    println!("  Full refresh: {}ms", waveshare.spec().full_refresh_ms);
+    println!("  Full refresh: {}", waveshare.spec().full_refresh_ms);
 
     // GoodDisplay
     let gooddisplay = Emulator::headless_with_spec(&displays::GDEW042T2);
@@ -63,7 +70,7 @@ async fn demonstrate_multibrand_support() {
     );
     println!("  Controller: {:?}", gooddisplay.spec().controller);
     println!("  Panel: {:?}", gooddisplay.spec().panel_type);
-    println!("  Full refresh: {}ms", gooddisplay.spec().full_refresh_ms);
+    println!("  Full refresh: {}", gooddisplay.spec().full_refresh_ms);
 
     println!();
 }
@@ -170,14 +177,18 @@ async fn demonstrate_temperature() {
 
     // Normal temperature
     println!("Normal temperature (25°C):");
-    let adjusted = emulator.spec().adjusted_refresh_ms(2000, 25);
-    println!("  Full refresh: {}ms", adjusted);
+    let adjusted = emulator
+        .spec()
+        .adjusted_refresh_ms(DisplayMs::new(2000), 25);
+    println!("  Full refresh: {}", adjusted);
 
     // Cold temperature
     emulator.set_temperature(-5);
     println!("\nCold temperature (-5°C):");
-    let adjusted = emulator.spec().adjusted_refresh_ms(2000, -5);
-    println!("  Full refresh: {}ms (50% slower)", adjusted);
+    let adjusted = emulator
+        .spec()
+        .adjusted_refresh_ms(DisplayMs::new(2000), -5);
+    println!("  Full refresh: {} (50% slower)", adjusted);
     println!(
         "  In optimal range: {}",
         emulator.spec().is_optimal_temp(-5)
@@ -190,8 +201,10 @@ async fn demonstrate_temperature() {
     // Hot temperature
     emulator.set_temperature(45);
     println!("\nHot temperature (45°C):");
-    let adjusted = emulator.spec().adjusted_refresh_ms(2000, 45);
-    println!("  Full refresh: {}ms (20% slower)", adjusted);
+    let adjusted = emulator
+        .spec()
+        .adjusted_refresh_ms(DisplayMs::new(2000), 45);
+    println!("  Full refresh: {} (20% slower)", adjusted);
     println!(
         "  In optimal range: {}",
         emulator.spec().is_optimal_temp(45)
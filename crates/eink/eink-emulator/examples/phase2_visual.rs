@@ -8,6 +8,15 @@
 //!
 //! Run with: cargo run --target x86_64-pc-windows-msvc --example phase2_visual
 
+// Example code: allow unwraps and unchecked layout maths.
+#![allow(
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::unwrap_used
+)]
+
 use eink_emulator::{DisplayDriver, Emulator};
 use embedded_graphics::mono_font::{
     ascii::{FONT_6X10, FONT_9X18_BOLD},
@@ -7,6 +7,9 @@
 //! - Comparison of full vs partial refresh energy
 //! - Power breakdown by state
 
+// Example code: allow unwraps and unchecked layout maths.
+#![allow(clippy::arithmetic_side_effects, clippy::unwrap_used)]
+
 use eink_emulator::{DisplayDriver, Emulator};
 use embedded_graphics::mono_font::{ascii::FONT_6X10, MonoTextStyle};
 use embedded_graphics::pixelcolor::Gray4;
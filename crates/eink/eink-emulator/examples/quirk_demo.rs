@@ -7,8 +7,11 @@
 //! - Handle quirk errors
 //! - View quirk warnings in the UI
 
-use eink_emulator::{DisplayDriver, Emulator};
-use eink_specs::{quirks_for_controller, ColorMode, Controller, DisplaySpec, PanelType};
+#[cfg(not(feature = "headless"))]
+use eink_emulator::DisplayDriver;
+use eink_emulator::Emulator;
+use eink_specs::{quirks_for_controller, ColorMode, Controller, DisplayMs, DisplaySpec, PanelType};
+#[cfg(not(feature = "headless"))]
 use embedded_graphics::{
     mono_font::{ascii::FONT_6X10, MonoTextStyle},
     pixelcolor::Gray4,
@@ -32,9 +35,9 @@ fn create_spec_with_controller(controller: Controller) -> &'static DisplaySpec {
         panel_type: PanelType::Carta1000,
         color_mode: Some(ColorMode::Grayscale),
         grayscale_levels: 4,
-        full_refresh_ms: 2000,
-        partial_refresh_ms: 300,
-        fast_refresh_ms: 260,
+        full_refresh_ms: DisplayMs::new(2000),
+        partial_refresh_ms: DisplayMs::new(300),
+        fast_refresh_ms: DisplayMs::new(260),
         ghosting_rate_partial: 0.15,
         ghosting_rate_fast: 0.25,
         flash_count_full: 3,
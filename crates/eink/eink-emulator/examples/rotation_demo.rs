@@ -2,6 +2,9 @@
 //!
 //! Shows how to configure the emulator for different orientations and scales.
 
+// Example code: allow unchecked layout maths.
+#![allow(clippy::arithmetic_side_effects, clippy::cast_possible_wrap)]
+
 use eink_emulator::{DisplayDriver, Emulator, EmulatorConfig, Rotation};
 use embedded_graphics::mono_font::{ascii::FONT_10X20, MonoTextStyle};
 use embedded_graphics::pixelcolor::Gray4;
@@ -18,6 +21,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = EmulatorConfig {
         rotation: Rotation::Degrees90,
         scale: 1,
+        ..Default::default()
     };
 
     println!("Creating emulator with:");
@@ -5,6 +5,9 @@
 //!
 //! Run with: cargo run --target x86_64-pc-windows-msvc --example spec_emulation
 
+// Example code: allow unwraps and unchecked layout maths.
+#![allow(clippy::arithmetic_side_effects, clippy::unwrap_used)]
+
 use eink_emulator::{DisplayDriver, Emulator};
 use embedded_graphics::mono_font::{
     ascii::{FONT_5X8, FONT_6X10},
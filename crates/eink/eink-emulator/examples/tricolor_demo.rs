@@ -14,6 +14,13 @@
 //! cargo run --package eink-emulator --example tricolor_demo
 //! ```
 
+// Example code: allow unwraps and unchecked layout maths.
+#![allow(
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation,
+    clippy::unwrap_used
+)]
+
 use eink_emulator::{ColorMode, EinkColor, Emulator, Framebuffer, SpectraColor};
 use embedded_graphics::{pixelcolor::Gray4, prelude::*};
 
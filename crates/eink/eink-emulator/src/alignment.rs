@@ -1,17 +1,23 @@
 //! Alignment Constraints for E-Ink Partial Updates
 //!
 //! E-ink controllers have addressing limitations that require coordinates
-//! to be aligned to multiples of 8 pixels.
+//! to be aligned to multiples of a controller-specific unit. The per-chip
+//! units live in [`eink_specs::PartialAlignment`]; the `*_with` functions
+//! here apply them to `embedded-graphics` rectangles. The unsuffixed
+//! functions keep the conservative 8×8 grid used for unknown controllers.
 
+use eink_specs::PartialAlignment;
 use embedded_graphics::prelude::*;
 use embedded_graphics::primitives::Rectangle;
 
 /// Alignment constraint for e-ink partial updates
 ///
-/// Most e-ink controllers (SSD1680, UC8151, etc.) require partial update
-/// regions to be aligned to 8-pixel boundaries due to how the display
-/// RAM is addressed.
-pub const ALIGNMENT: u32 = 8;
+/// The conservative grid ([`PartialAlignment::GENERIC`]) applied when the
+/// controller is unknown. Most controllers (SSD1680, UC8151, etc.) need
+/// only X aligned to 8 pixels; see [`Controller::partial_alignment`].
+///
+/// [`Controller::partial_alignment`]: eink_specs::Controller::partial_alignment
+pub const ALIGNMENT: u32 = PartialAlignment::GENERIC.x;
 
 /// Align a coordinate down to the nearest multiple of ALIGNMENT
 ///
@@ -77,36 +83,70 @@ pub fn is_aligned(value: u32) -> bool {
 /// assert_eq!(aligned.top_left, Point::new(0, 0));
 /// assert_eq!(aligned.size, Size::new(16, 16));
 /// ```
-// SAFETY: align_rectangle operates on display coordinates; aligned_right >= aligned_x
-// by construction (align_up >= align_down on non-negative inputs), so subtraction is safe.
-#[allow(clippy::arithmetic_side_effects)]
 pub fn align_rectangle(rect: &Rectangle) -> Rectangle {
-    let top_left = rect.top_left;
-    let bottom_right = rect.bottom_right().unwrap_or(top_left);
-
-    // Align top-left down (expand outward)
-    let aligned_x = align_down(top_left.x.max(0) as u32);
-    let aligned_y = align_down(top_left.y.max(0) as u32);
+    align_rectangle_with(rect, PartialAlignment::GENERIC)
+}
 
-    // Align bottom-right up (expand outward)
-    let aligned_right = align_up(bottom_right.x.max(0) as u32);
-    let aligned_bottom = align_up(bottom_right.y.max(0) as u32);
+/// Align a rectangle to a specific controller's partial-window grid
+///
+/// Negative coordinates are clipped to the display origin before aligning.
+///
+/// # Examples
+/// ```
+/// # use eink_emulator::alignment::align_rectangle_with;
+/// # use eink_specs::Controller;
+/// # use embedded_graphics::prelude::*;
+/// # use embedded_graphics::primitives::Rectangle;
+///
+/// let rect = Rectangle::new(Point::new(5, 5), Size::new(10, 10));
+/// let aligned = align_rectangle_with(&rect, Controller::SSD1680.partial_alignment());
+///
+/// // SSD1680 addresses X in bytes but Y per gate line
+/// assert_eq!(aligned.top_left, Point::new(0, 5));
+/// assert_eq!(aligned.size, Size::new(16, 10));
+/// ```
+// SAFETY (cast): coordinates are clipped to >= 0 before the i32 -> u32 casts, and
+// aligned display coordinates (max ~4000px) fit back into i32.
+#[allow(clippy::cast_sign_loss, clippy::cast_possible_wrap)]
+pub fn align_rectangle_with(rect: &Rectangle, alignment: PartialAlignment) -> Rectangle {
+    let left = rect.top_left.x.max(0);
+    let top = rect.top_left.y.max(0);
+    let right = rect
+        .top_left
+        .x
+        .saturating_add(rect.size.width as i32)
+        .max(left);
+    let bottom = rect
+        .top_left
+        .y
+        .saturating_add(rect.size.height as i32)
+        .max(top);
 
-    // Calculate aligned size
-    let width = aligned_right - aligned_x;
-    let height = aligned_bottom - aligned_y;
+    let (x, y, width, height) = alignment.align_window(
+        left as u32,
+        top as u32,
+        right.abs_diff(left),
+        bottom.abs_diff(top),
+    );
 
-    Rectangle::new(
-        Point::new(aligned_x as i32, aligned_y as i32),
-        Size::new(width, height),
-    )
+    Rectangle::new(Point::new(x as i32, y as i32), Size::new(width, height))
 }
 
 /// Validate and align a partial update region
 ///
 /// Returns the aligned rectangle and a boolean indicating if alignment was needed.
 pub fn validate_and_align(rect: &Rectangle) -> (Rectangle, bool) {
-    let aligned = align_rectangle(rect);
+    validate_and_align_with(rect, PartialAlignment::GENERIC)
+}
+
+/// Validate and align a partial update region for a specific controller
+///
+/// Returns the aligned rectangle and a boolean indicating if alignment was needed.
+pub fn validate_and_align_with(
+    rect: &Rectangle,
+    alignment: PartialAlignment,
+) -> (Rectangle, bool) {
+    let aligned = align_rectangle_with(rect, alignment);
     let needed_alignment = aligned != *rect;
     (aligned, needed_alignment)
 }
@@ -195,6 +235,38 @@ mod tests {
         assert!(needed);
     }
 
+    #[test]
+    fn test_align_rectangle_covers_last_pixel() {
+        // 9×9 at the origin touches pixel 8, so the window must reach 16.
+        let rect = Rectangle::new(Point::new(0, 0), Size::new(9, 9));
+        let aligned = align_rectangle(&rect);
+        assert_eq!(aligned.size, Size::new(16, 16));
+    }
+
+    #[test]
+    fn test_align_rectangle_with_controller() {
+        use eink_specs::Controller;
+
+        let rect = Rectangle::new(Point::new(13, 7), Size::new(2, 3));
+
+        let ssd1677 = align_rectangle_with(&rect, Controller::SSD1677.partial_alignment());
+        assert_eq!(ssd1677, Rectangle::new(Point::new(8, 7), Size::new(8, 3)));
+
+        let it8951 = align_rectangle_with(&rect, Controller::IT8951.partial_alignment());
+        assert_eq!(it8951, Rectangle::new(Point::new(12, 7), Size::new(4, 3)));
+
+        let (_, needed) =
+            validate_and_align_with(&ssd1677, Controller::SSD1677.partial_alignment());
+        assert!(!needed);
+    }
+
+    #[test]
+    fn test_align_rectangle_clips_negative_origin() {
+        let rect = Rectangle::new(Point::new(-4, -4), Size::new(10, 10));
+        let aligned = align_rectangle(&rect);
+        assert_eq!(aligned, Rectangle::new(Point::new(0, 0), Size::new(8, 8)));
+    }
+
     #[test]
     fn test_alignment_constant() {
         // Ensure alignment is 8 as per hardware requirements
@@ -0,0 +1,368 @@
+//! Emulator commands and the in-window command palette
+//!
+//! Common emulator actions — screenshot, debug overlays, temperature,
+//! waveform mode, ghosting reset, display spec — are listed in a palette
+//! drawn over the display.  Press **F1** (or **Ctrl+P**) in the emulator
+//! window to open it; **↑/↓** select, **Enter** runs, **Esc** closes.  Each
+//! entry shows its direct shortcut, so the Ctrl+key bindings are
+//! discoverable from the window itself.
+//!
+//! Screenshot and debug-overlay commands run inside the window.  The others
+//! change emulator state and are applied by
+//! [`Emulator::pump_window_events`](crate::Emulator::pump_window_events), or
+//! directly with [`Emulator::apply_command`](crate::Emulator::apply_command).
+//! [`Emulator::run`](crate::Emulator::run) hands the window over to a
+//! blocking loop with no emulator left to apply them, so only window-local
+//! commands work there.
+
+use embedded_graphics::mono_font::{ascii::FONT_6X10, MonoTextStyle};
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::{Baseline, Text};
+
+/// Temperature change per [`EmulatorCommand::TemperatureUp`] /
+/// [`EmulatorCommand::TemperatureDown`] (°C).
+pub const TEMPERATURE_STEP: i8 = 5;
+
+/// An action offered by the command palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmulatorCommand {
+    /// Save the current frame as a PNG in the working directory.
+    SaveScreenshot,
+    /// Show or hide the debug side panel.
+    TogglePanel,
+    /// Show or hide component borders.
+    ToggleBorders,
+    /// Enable or disable the hover inspector.
+    ToggleInspector,
+    /// Raise the simulated temperature by [`TEMPERATURE_STEP`].
+    TemperatureUp,
+    /// Lower the simulated temperature by [`TEMPERATURE_STEP`].
+    TemperatureDown,
+    /// Switch `display()` to the next [`WaveformMode`](crate::WaveformMode).
+    CycleWaveformMode,
+    /// Clear accumulated ghosting and DC imbalance.
+    ResetGhosting,
+    /// Switch to the next display spec with the same resolution.
+    NextDisplaySpec,
+}
+
+impl EmulatorCommand {
+    /// Every command, in palette order.
+    pub const ALL: [Self; 9] = [
+        Self::SaveScreenshot,
+        Self::TogglePanel,
+        Self::ToggleBorders,
+        Self::ToggleInspector,
+        Self::TemperatureUp,
+        Self::TemperatureDown,
+        Self::CycleWaveformMode,
+        Self::ResetGhosting,
+        Self::NextDisplaySpec,
+    ];
+
+    /// Commands available in this build (debug overlays need the `debug`
+    /// feature).
+    pub fn available() -> impl Iterator<Item = Self> {
+        Self::ALL
+            .into_iter()
+            .filter(|cmd| cfg!(feature = "debug") || !cmd.is_debug_overlay())
+    }
+
+    /// Palette label.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::SaveScreenshot => "Save screenshot",
+            Self::TogglePanel => "Toggle debug panel",
+            Self::ToggleBorders => "Toggle borders",
+            Self::ToggleInspector => "Toggle inspector",
+            Self::TemperatureUp => "Temperature +5C",
+            Self::TemperatureDown => "Temperature -5C",
+            Self::CycleWaveformMode => "Next waveform mode",
+            Self::ResetGhosting => "Reset ghosting",
+            Self::NextDisplaySpec => "Next display spec",
+        }
+    }
+
+    /// Direct keyboard shortcut, as shown in the palette.
+    pub fn shortcut(self) -> &'static str {
+        match self {
+            Self::SaveScreenshot => "Ctrl+S",
+            Self::TogglePanel => "Ctrl+1",
+            Self::ToggleBorders => "Ctrl+2",
+            Self::ToggleInspector => "Ctrl+3",
+            Self::TemperatureUp => "Ctrl+]",
+            Self::TemperatureDown => "Ctrl+[",
+            Self::CycleWaveformMode => "Ctrl+W",
+            Self::ResetGhosting => "Ctrl+G",
+            Self::NextDisplaySpec => "Ctrl+D",
+        }
+    }
+
+    /// Whether the command only affects debug overlays.
+    pub fn is_debug_overlay(self) -> bool {
+        matches!(
+            self,
+            Self::TogglePanel | Self::ToggleBorders | Self::ToggleInspector
+        )
+    }
+
+    /// Whether the window runs the command itself rather than handing it to
+    /// the emulator.
+    pub fn is_window_local(self) -> bool {
+        self == Self::SaveScreenshot || self.is_debug_overlay()
+    }
+}
+
+/// Line height of the palette text (FONT_6X10 plus one pixel of leading).
+const LINE_H: u32 = 11;
+const PAD: u32 = 3;
+const INK: u32 = 0xFF00_0000;
+const PAPER: u32 = 0xFFFF_FFFF;
+
+/// Open/closed state and selection of the command palette.
+#[derive(Debug, Clone)]
+pub struct CommandPalette {
+    commands: Vec<EmulatorCommand>,
+    selected: usize,
+    open: bool,
+}
+
+impl CommandPalette {
+    /// Closed palette listing [`EmulatorCommand::available`].
+    pub fn new() -> Self {
+        Self {
+            commands: EmulatorCommand::available().collect(),
+            selected: 0,
+            open: false,
+        }
+    }
+
+    /// Listed commands, in order.
+    pub fn commands(&self) -> &[EmulatorCommand] {
+        &self.commands
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Open the palette with the first command selected.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.selected = 0;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Currently highlighted command.
+    pub fn selected(&self) -> Option<EmulatorCommand> {
+        self.commands.get(self.selected).copied()
+    }
+
+    /// Move the selection up, wrapping to the bottom.
+    pub fn select_previous(&mut self) {
+        self.selected = match self.selected.checked_sub(1) {
+            Some(i) => i,
+            None => self.commands.len().saturating_sub(1),
+        };
+    }
+
+    /// Move the selection down, wrapping to the top.
+    pub fn select_next(&mut self) {
+        let next = self.selected.saturating_add(1);
+        self.selected = if next < self.commands.len() { next } else { 0 };
+    }
+
+    /// Close the palette and return the selected command.
+    pub fn activate(&mut self) -> Option<EmulatorCommand> {
+        if !self.open {
+            return None;
+        }
+        self.open = false;
+        self.selected()
+    }
+
+    /// Draw the palette over an ARGB frame of `width`×`height`.
+    ///
+    /// Black-on-white with an inverted selection bar, like an e-ink menu.
+    /// When the list is taller than the display it scrolls to keep the
+    /// selection visible.
+    // SAFETY: all values are display pixel coordinates and row counts, far
+    // below u32/i32 limits.
+    #[allow(
+        clippy::arithmetic_side_effects,
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap
+    )]
+    pub fn render(&self, argb: &mut [u32], width: u32, height: u32) {
+        if !self.open {
+            return;
+        }
+        let mut canvas = ArgbCanvas {
+            buf: argb,
+            width,
+            height,
+        };
+
+        let list_top = PAD + LINE_H + PAD;
+        let visible = (height.saturating_sub(list_top + PAD) / LINE_H).max(1) as usize;
+        let first = (self.selected + 1).saturating_sub(visible);
+        let rows = self.commands.len().min(visible);
+        let box_h = (list_top + rows as u32 * LINE_H + PAD).min(height);
+
+        canvas.fill(0, 0, width, box_h, PAPER);
+        canvas.fill(0, box_h.saturating_sub(1), width, 1, INK);
+        canvas.text("Commands  (Esc closes)", PAD, PAD, INK);
+        canvas.fill(0, PAD + LINE_H, width, 1, INK);
+
+        for (row, cmd) in self.commands.iter().skip(first).take(rows).enumerate() {
+            let y = list_top + row as u32 * LINE_H;
+            let fg = if first + row == self.selected {
+                canvas.fill(0, y, width, LINE_H, INK);
+                PAPER
+            } else {
+                INK
+            };
+            canvas.text(cmd.label(), PAD, y + 1, fg);
+            let shortcut = cmd.shortcut();
+            let shortcut_w = shortcut.len() as u32 * 6;
+            canvas.text(shortcut, width.saturating_sub(PAD + shortcut_w), y + 1, fg);
+        }
+    }
+}
+
+impl Default for CommandPalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Minimal [`DrawTarget`] over an ARGB buffer for palette text.
+struct ArgbCanvas<'a> {
+    buf: &'a mut [u32],
+    width: u32,
+    height: u32,
+}
+
+impl ArgbCanvas<'_> {
+    // SAFETY: x/y are clamped to the canvas before indexing via `get_mut`.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn fill(&mut self, x: u32, y: u32, w: u32, h: u32, color: u32) {
+        for py in y..(y + h).min(self.height) {
+            for px in x..(x + w).min(self.width) {
+                if let Some(p) = self.buf.get_mut((py * self.width + px) as usize) {
+                    *p = color;
+                }
+            }
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn text(&mut self, s: &str, x: u32, y: u32, color: u32) {
+        let color = Rgb888::new((color >> 16) as u8, (color >> 8) as u8, color as u8);
+        let style = MonoTextStyle::new(&FONT_6X10, color);
+        let _ =
+            Text::with_baseline(s, Point::new(x as i32, y as i32), style, Baseline::Top).draw(self);
+    }
+}
+
+impl OriginDimensions for ArgbCanvas<'_> {
+    fn size(&self) -> Size {
+        Size::new(self.width, self.height)
+    }
+}
+
+impl DrawTarget for ArgbCanvas<'_> {
+    type Color = Rgb888;
+    type Error = core::convert::Infallible;
+
+    // SAFETY: negative and out-of-range points are skipped before indexing.
+    #[allow(
+        clippy::arithmetic_side_effects,
+        clippy::cast_sign_loss,
+        clippy::cast_possible_wrap
+    )]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(p, c) in pixels {
+            if p.x < 0 || p.y < 0 || p.x >= self.width as i32 || p.y >= self.height as i32 {
+                continue;
+            }
+            let idx = (p.y as u32 * self.width + p.x as u32) as usize;
+            if let Some(px) = self.buf.get_mut(idx) {
+                *px = 0xFF00_0000
+                    | (u32::from(c.r()) << 16)
+                    | (u32::from(c.g()) << 8)
+                    | u32::from(c.b());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+    use super::*;
+
+    #[test]
+    fn navigation_wraps_and_activate_closes() {
+        let mut palette = CommandPalette::new();
+        assert!(!palette.is_open());
+        assert_eq!(palette.activate(), None);
+
+        palette.open();
+        assert_eq!(palette.selected(), Some(EmulatorCommand::SaveScreenshot));
+        palette.select_previous();
+        assert_eq!(palette.selected(), Some(EmulatorCommand::NextDisplaySpec));
+        palette.select_next();
+        palette.select_next();
+        assert_eq!(palette.selected(), palette.commands().get(1).copied());
+
+        let cmd = palette.activate();
+        assert!(!palette.is_open());
+        assert_eq!(cmd, palette.commands().get(1).copied());
+    }
+
+    #[test]
+    fn every_command_has_a_distinct_shortcut() {
+        let mut shortcuts: Vec<_> = EmulatorCommand::ALL.iter().map(|c| c.shortcut()).collect();
+        shortcuts.sort_unstable();
+        shortcuts.dedup();
+        assert_eq!(shortcuts.len(), EmulatorCommand::ALL.len());
+    }
+
+    #[test]
+    fn debug_overlays_depend_on_feature() {
+        let listed = EmulatorCommand::available().any(|c| c.is_debug_overlay());
+        assert_eq!(listed, cfg!(feature = "debug"));
+        assert!(EmulatorCommand::SaveScreenshot.is_window_local());
+        assert!(!EmulatorCommand::ResetGhosting.is_window_local());
+    }
+
+    #[test]
+    fn render_draws_only_when_open_and_scrolls_to_selection() {
+        // Room for three rows only, so selecting the last command scrolls.
+        let (w, h) = (250, 60);
+        let mut frame = vec![0xFF80_8080; (w * h) as usize];
+        let mut palette = CommandPalette::new();
+        palette.render(&mut frame, w, h);
+        assert!(frame.iter().all(|&p| p == 0xFF80_8080));
+
+        palette.open();
+        palette.select_previous();
+        palette.render(&mut frame, w, h);
+        assert_eq!(frame[0], PAPER);
+        // The right edge carries no text: title rule + bottom rule + the
+        // inverted selection bar, which must be on screen.
+        let ink_rows = (0..h)
+            .filter(|&y| frame[(y * w + w - 1) as usize] == INK)
+            .count();
+        assert_eq!(ink_rows, LINE_H as usize + 2);
+    }
+}
@@ -1,5 +1,7 @@
 //! Emulator configuration
 
+use std::path::PathBuf;
+
 /// Configuration for emulator display presentation
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EmulatorConfig {
@@ -7,6 +9,8 @@ pub struct EmulatorConfig {
     pub rotation: Rotation,
     /// Upscaling factor (1 = no scaling, 2 = 2x for visibility, etc.)
     pub scale: u32,
+    /// Where frames are presented
+    pub backend: Backend,
 }
 
 impl EmulatorConfig {
@@ -14,27 +18,44 @@ impl EmulatorConfig {
     pub const DEFAULT: Self = Self {
         rotation: Rotation::Degrees0,
         scale: 2,
+        backend: Backend::Window,
     };
 
     /// No rotation, no upscaling (1:1 pixel mapping)
     pub const NATIVE: Self = Self {
         rotation: Rotation::Degrees0,
         scale: 1,
+        backend: Backend::Window,
     };
 
     /// Portrait mode (90° rotation), no upscaling
     pub const PORTRAIT: Self = Self {
         rotation: Rotation::Degrees90,
         scale: 1,
+        backend: Backend::Window,
     };
 
     /// Portrait mode (90° rotation), 2x upscaling
     pub const PORTRAIT_2X: Self = Self {
         rotation: Rotation::Degrees90,
         scale: 2,
+        backend: Backend::Window,
     };
 }
 
+impl EmulatorConfig {
+    /// Present on a Linux framebuffer device (e.g. `/dev/fb0`) instead of a
+    /// window, keeping the default rotation and scale.
+    ///
+    /// Requires the `fbdev` feature on Linux.
+    pub fn framebuffer(device: impl Into<PathBuf>) -> Self {
+        Self {
+            backend: Backend::Framebuffer(device.into()),
+            ..Self::DEFAULT
+        }
+    }
+}
+
 impl Default for EmulatorConfig {
     fn default() -> Self {
         Self::DEFAULT
@@ -73,3 +94,15 @@ impl Rotation {
         }
     }
 }
+
+/// Presentation backend
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// winit + softbuffer desktop window (ignored with the `headless` feature)
+    #[default]
+    Window,
+    /// Linux framebuffer device such as `/dev/fb0` — for machines without a
+    /// desktop session (Raspberry Pi on the bench, SSH). Requires the
+    /// `fbdev` feature; see [`crate::fbdev`].
+    Framebuffer(PathBuf),
+}
@@ -0,0 +1,923 @@
+//! Command-level controller emulation
+//!
+//! The rest of the emulator works at the framebuffer level: callers draw and
+//! then ask for a refresh. Controller mode instead accepts the raw byte
+//! stream a driver puts on the SPI bus — commands (DC low) and data (DC
+//! high) — and decodes it the way an SSD16xx-family controller (SSD1677,
+//! SSD1680) would:
+//!
+//! - RAM window (`0x44`/`0x45`) and address counters (`0x4E`/`0x4F`)
+//! - Data entry mode (`0x11`) for the counter step direction
+//! - B/W and red RAM writes (`0x24`/`0x26`) and auto-fill (`0x46`/`0x47`)
+//! - Display update sequence (`0x22`) and master activation (`0x20`)
+//! - B/W RAM inverse option of display update control 1 (`0x21`)
+//! - Software reset, deep sleep and the BUSY line
+//!
+//! On master activation the B/W RAM is copied into the wrapped [`Emulator`]
+//! and the matching refresh runs, so ghosting and timing behave as usual.
+//!
+//! # Timing
+//!
+//! Two clocks are involved. The wrapped emulator renders against a private
+//! [`VirtualClock`] so refresh animations complete instantly; the measured
+//! refresh time is then applied to the *bus clock*, which the host advances
+//! as the driver delays. BUSY reads high until the bus clock catches up, so
+//! a driver's polling loop sees realistic busy periods without real sleeps.
+//!
+//! Commands without a refresh hold BUSY for the durations in [`BusyTiming`]
+//! (resets, RAM auto-fill, power-on/off sequences). Deep sleep holds BUSY
+//! high until the next hardware reset, as the SSD16xx does.
+//!
+//! # Faults
+//!
+//! [`ControllerEmulator::inject_busy_fault`] makes BUSY stick high, so a
+//! driver's timeout and recovery path can be exercised on the host:
+//! [`BusyFault::StuckUntilReset`] is cleared by a hardware reset,
+//! [`BusyFault::StuckHigh`] is not.
+//!
+//! X addresses are in RAM bytes (8 pixels), matching
+//! [`eink_specs::PartialAlignment`] for the SSD16xx family.
+//!
+//! # Example
+//!
+//! ```
+//! use eink_emulator::controller::{cmd, ControllerEmulator};
+//! use eink_specs::displays::WAVESHARE_2_13_V4;
+//!
+//! # async fn example() {
+//! let mut ctrl = ControllerEmulator::new(&WAVESHARE_2_13_V4);
+//! ctrl.write_command(cmd::WRITE_RAM_BW).await.unwrap();
+//! ctrl.write_data(&[0x00; 4]).await.unwrap();
+//! assert_eq!(ctrl.bw_ram()[..4], [0x00; 4]);
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+
+use crate::spi_trace::{SpiOp, SpiTrace};
+use crate::{DisplayDriver, Emulator, VirtualClock};
+
+/// SSD16xx command codes understood by [`ControllerEmulator`].
+pub mod cmd {
+    /// Driver output control — 3 data bytes.
+    pub const DRIVER_OUTPUT_CONTROL: u8 = 0x01;
+    /// Gate driving voltage — 1 data byte.
+    pub const GATE_VOLTAGE: u8 = 0x03;
+    /// Source driving voltage — 3 data bytes.
+    pub const SOURCE_VOLTAGE: u8 = 0x04;
+    /// Booster soft-start — 5 data bytes.
+    pub const BOOSTER_SOFT_START: u8 = 0x0C;
+    /// Deep sleep — 1 data byte.
+    pub const DEEP_SLEEP: u8 = 0x10;
+    /// Data entry mode — 1 data byte.
+    pub const DATA_ENTRY_MODE: u8 = 0x11;
+    /// Software reset — no data.
+    pub const SOFT_RESET: u8 = 0x12;
+    /// Temperature sensor control — 1 data byte.
+    pub const TEMP_SENSOR_CONTROL: u8 = 0x18;
+    /// Write temperature register — 2 data bytes.
+    pub const WRITE_TEMP_VALUE: u8 = 0x1A;
+    /// Master activation — no data.
+    pub const MASTER_ACTIVATION: u8 = 0x20;
+    /// Display update control 1 — 2 data bytes.
+    pub const DISPLAY_UPDATE_CTRL1: u8 = 0x21;
+    /// Display update control 2 — 1 data byte.
+    pub const DISPLAY_UPDATE_CTRL2: u8 = 0x22;
+    /// Write B/W RAM — streamed data.
+    pub const WRITE_RAM_BW: u8 = 0x24;
+    /// Write red RAM — streamed data.
+    pub const WRITE_RAM_RED: u8 = 0x26;
+    /// Write VCOM register — 1 data byte.
+    pub const WRITE_VCOM: u8 = 0x2C;
+    /// Write LUT register — 112 data bytes.
+    pub const WRITE_LUT: u8 = 0x32;
+    /// Border waveform control — 1 data byte.
+    pub const BORDER_WAVEFORM: u8 = 0x3C;
+    /// Set RAM X start/end — 4 data bytes.
+    pub const SET_RAM_X_RANGE: u8 = 0x44;
+    /// Set RAM Y start/end — 4 data bytes.
+    pub const SET_RAM_Y_RANGE: u8 = 0x45;
+    /// Auto-write B/W RAM — 1 data byte.
+    pub const AUTO_WRITE_BW_RAM: u8 = 0x46;
+    /// Auto-write red RAM — 1 data byte.
+    pub const AUTO_WRITE_RED_RAM: u8 = 0x47;
+    /// Set RAM X counter — 2 data bytes.
+    pub const SET_RAM_X_COUNTER: u8 = 0x4E;
+    /// Set RAM Y counter — 2 data bytes.
+    pub const SET_RAM_Y_COUNTER: u8 = 0x4F;
+    /// No operation.
+    pub const NOP: u8 = 0xFF;
+}
+
+/// Human-readable name of an SSD16xx command, for trace annotation.
+pub fn command_name(command: u8) -> Option<&'static str> {
+    Some(match command {
+        cmd::DRIVER_OUTPUT_CONTROL => "DriverOutputControl",
+        cmd::GATE_VOLTAGE => "GateVoltage",
+        cmd::SOURCE_VOLTAGE => "SourceVoltage",
+        cmd::BOOSTER_SOFT_START => "BoosterSoftStart",
+        cmd::DEEP_SLEEP => "DeepSleep",
+        cmd::DATA_ENTRY_MODE => "DataEntryMode",
+        cmd::SOFT_RESET => "SoftReset",
+        cmd::TEMP_SENSOR_CONTROL => "TempSensorControl",
+        cmd::WRITE_TEMP_VALUE => "WriteTempValue",
+        cmd::MASTER_ACTIVATION => "MasterActivation",
+        cmd::DISPLAY_UPDATE_CTRL1 => "DisplayUpdateCtrl1",
+        cmd::DISPLAY_UPDATE_CTRL2 => "DisplayUpdateCtrl2",
+        cmd::WRITE_RAM_BW => "WriteRamBW",
+        cmd::WRITE_RAM_RED => "WriteRamRed",
+        cmd::WRITE_VCOM => "WriteVcom",
+        cmd::WRITE_LUT => "WriteLut",
+        cmd::BORDER_WAVEFORM => "BorderWaveform",
+        cmd::SET_RAM_X_RANGE => "SetRamXRange",
+        cmd::SET_RAM_Y_RANGE => "SetRamYRange",
+        cmd::AUTO_WRITE_BW_RAM => "AutoWriteBwRam",
+        cmd::AUTO_WRITE_RED_RAM => "AutoWriteRedRam",
+        cmd::SET_RAM_X_COUNTER => "SetRamXCounter",
+        cmd::SET_RAM_Y_COUNTER => "SetRamYCounter",
+        cmd::NOP => "Nop",
+        _ => return None,
+    })
+}
+
+/// Number of parameter bytes after which a command takes effect.
+///
+/// `None` for streamed RAM writes and unknown commands.
+fn param_len(command: u8) -> Option<usize> {
+    match command {
+        cmd::SOFT_RESET | cmd::MASTER_ACTIVATION | cmd::NOP => Some(0),
+        cmd::GATE_VOLTAGE
+        | cmd::DEEP_SLEEP
+        | cmd::DATA_ENTRY_MODE
+        | cmd::TEMP_SENSOR_CONTROL
+        | cmd::DISPLAY_UPDATE_CTRL2
+        | cmd::WRITE_VCOM
+        | cmd::BORDER_WAVEFORM
+        | cmd::AUTO_WRITE_BW_RAM
+        | cmd::AUTO_WRITE_RED_RAM => Some(1),
+        cmd::WRITE_TEMP_VALUE
+        | cmd::DISPLAY_UPDATE_CTRL1
+        | cmd::SET_RAM_X_COUNTER
+        | cmd::SET_RAM_Y_COUNTER => Some(2),
+        cmd::DRIVER_OUTPUT_CONTROL | cmd::SOURCE_VOLTAGE => Some(3),
+        cmd::SET_RAM_X_RANGE | cmd::SET_RAM_Y_RANGE => Some(4),
+        cmd::BOOSTER_SOFT_START => Some(5),
+        cmd::WRITE_LUT => Some(112),
+        _ => None,
+    }
+}
+
+/// BUSY time after a hardware reset.
+pub const HARDWARE_RESET_BUSY_MS: u64 = 1;
+
+/// BUSY time after a software reset.
+pub const SOFT_RESET_BUSY_MS: u64 = 10;
+
+/// BUSY time after an auto-write RAM fill.
+pub const AUTO_WRITE_BUSY_MS: u64 = 5;
+
+/// BUSY time of a power-on sequence (clock and analog enable, no display).
+pub const POWER_ON_BUSY_MS: u64 = 100;
+
+/// BUSY time of a power-off sequence (analog disable, no display).
+pub const POWER_OFF_BUSY_MS: u64 = 200;
+
+/// How long BUSY stays high after each non-refresh operation.
+///
+/// Refreshes take the time the wrapped emulator measures for the waveform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusyTiming {
+    /// After the RST pulse.
+    pub hardware_reset_ms: u64,
+    /// After `SoftReset` (`0x12`).
+    pub soft_reset_ms: u64,
+    /// After `AutoWriteBwRam` / `AutoWriteRedRam` (`0x46`/`0x47`).
+    pub auto_write_ms: u64,
+    /// Activation of a sequence that enables the analog supply without a
+    /// display step (e.g. `0xC0`).
+    pub power_on_ms: u64,
+    /// Activation of a sequence that disables the analog supply without a
+    /// display step (e.g. `0x83`).
+    pub power_off_ms: u64,
+}
+
+impl BusyTiming {
+    /// SSD1677 timings, from the datasheet and GxEPD2's GDEM0397T81P driver.
+    pub const SSD1677: Self = Self {
+        hardware_reset_ms: HARDWARE_RESET_BUSY_MS,
+        soft_reset_ms: SOFT_RESET_BUSY_MS,
+        auto_write_ms: AUTO_WRITE_BUSY_MS,
+        power_on_ms: POWER_ON_BUSY_MS,
+        power_off_ms: POWER_OFF_BUSY_MS,
+    };
+}
+
+impl Default for BusyTiming {
+    fn default() -> Self {
+        Self::SSD1677
+    }
+}
+
+/// An injected BUSY-line fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusyFault {
+    /// The next busy period never ends. A hardware reset recovers the
+    /// controller and clears the fault (a latched-up controller).
+    StuckUntilReset,
+    /// BUSY reads high from now on, through resets (a shorted line or a
+    /// dead controller).
+    StuckHigh,
+}
+
+/// Which refresh a display update sequence selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateKind {
+    /// Full waveform (`0xF7`): clears ghosting.
+    Full,
+    /// Full waveform without the temperature load (`0xD7`).
+    Fast,
+    /// Display mode 2 (`0xFC`): partial update.
+    Partial,
+    /// Sequence without the display step (power on/off only).
+    NoDisplay,
+}
+
+impl UpdateKind {
+    /// Decode a `DisplayUpdateCtrl2` sequence byte.
+    pub fn from_ctrl2(ctrl2: u8) -> Self {
+        const DISPLAY: u8 = 0x04;
+        const MODE_2: u8 = 0x08;
+        const LOAD_TEMP: u8 = 0x20;
+
+        if ctrl2 & DISPLAY == 0 {
+            Self::NoDisplay
+        } else if ctrl2 & MODE_2 != 0 {
+            Self::Partial
+        } else if ctrl2 & LOAD_TEMP == 0 {
+            Self::Fast
+        } else {
+            Self::Full
+        }
+    }
+}
+
+/// Read-only handle to a controller's BUSY line.
+///
+/// Cloneable so a GPIO adapter can sample BUSY while another component owns
+/// the [`ControllerEmulator`].
+#[derive(Debug, Clone)]
+pub struct BusyLine {
+    bus_clock: VirtualClock,
+    busy_until_ms: Arc<AtomicU64>,
+    stuck: Arc<AtomicBool>,
+}
+
+impl BusyLine {
+    /// Whether BUSY is currently high.
+    pub fn is_high(&self) -> bool {
+        self.stuck.load(Ordering::Acquire)
+            || self.bus_clock.now_ms() < self.busy_until_ms.load(Ordering::Acquire)
+    }
+}
+
+/// SSD16xx command decoder driving an [`Emulator`].
+pub struct ControllerEmulator {
+    emulator: Emulator,
+    render_clock: VirtualClock,
+    busy: BusyLine,
+    timing: BusyTiming,
+    fault: Option<BusyFault>,
+
+    bytes_per_row: u16,
+    rows: u16,
+    reverse_gates: bool,
+    bw_ram: Vec<u8>,
+    red_ram: Vec<u8>,
+
+    x_range: (u16, u16),
+    y_range: (u16, u16),
+    x_counter: u16,
+    y_counter: u16,
+    entry_mode: u8,
+    update_ctrl1: u8,
+    update_ctrl2: u8,
+    deep_sleep: bool,
+
+    command: Option<u8>,
+    params: Vec<u8>,
+
+    activations: u32,
+    recorder: Option<SpiTrace>,
+}
+
+impl ControllerEmulator {
+    /// Create a headless controller for `spec`.
+    pub fn new(spec: &'static eink_specs::DisplaySpec) -> Self {
+        Self::with_emulator(Emulator::headless_with_spec(spec))
+    }
+
+    /// Wrap an existing emulator (windowed or headless).
+    ///
+    /// The emulator's virtual clock is replaced with a private render clock.
+    pub fn with_emulator(mut emulator: Emulator) -> Self {
+        let render_clock = VirtualClock::new();
+        emulator.set_virtual_clock(render_clock.clone());
+
+        let bytes_per_row = emulator.framebuffer.width.div_ceil(8) as u16;
+        let rows = emulator.framebuffer.height as u16;
+        let ram_len = usize::from(bytes_per_row).saturating_mul(usize::from(rows));
+
+        let mut ctrl = Self {
+            emulator,
+            render_clock,
+            busy: BusyLine {
+                bus_clock: VirtualClock::new(),
+                busy_until_ms: Arc::new(AtomicU64::new(0)),
+                stuck: Arc::new(AtomicBool::new(false)),
+            },
+            timing: BusyTiming::default(),
+            fault: None,
+            bytes_per_row,
+            rows,
+            reverse_gates: false,
+            bw_ram: vec![0xFF; ram_len],
+            red_ram: vec![0xFF; ram_len],
+            x_range: (0, 0),
+            y_range: (0, 0),
+            x_counter: 0,
+            y_counter: 0,
+            entry_mode: 0,
+            update_ctrl1: 0,
+            update_ctrl2: 0,
+            deep_sleep: false,
+            command: None,
+            params: Vec::new(),
+            activations: 0,
+            recorder: None,
+        };
+        ctrl.reset_registers();
+        ctrl
+    }
+
+    /// Map RAM row 0 to the bottom display row.
+    ///
+    /// Set for panels whose gates are wired in reverse (GDEM0397T81P).
+    pub fn with_reversed_gates(mut self, reverse: bool) -> Self {
+        self.reverse_gates = reverse;
+        self
+    }
+
+    /// Use `clock` as the bus clock (e.g. shared with a host delay).
+    pub fn with_bus_clock(mut self, clock: VirtualClock) -> Self {
+        self.busy.bus_clock = clock;
+        self
+    }
+
+    /// Use `timing` for the BUSY periods of non-refresh commands.
+    pub fn with_busy_timing(mut self, timing: BusyTiming) -> Self {
+        self.timing = timing;
+        self
+    }
+
+    /// The BUSY periods of non-refresh commands.
+    pub fn busy_timing(&self) -> BusyTiming {
+        self.timing
+    }
+
+    /// Make BUSY stick high; see [`BusyFault`].
+    pub fn inject_busy_fault(&mut self, fault: BusyFault) {
+        self.fault = Some(fault);
+        if fault == BusyFault::StuckHigh {
+            self.busy.stuck.store(true, Ordering::Release);
+        }
+    }
+
+    /// Remove any injected fault; BUSY follows the command timing again.
+    pub fn clear_busy_fault(&mut self) {
+        self.fault = None;
+        self.busy.stuck.store(false, Ordering::Release);
+    }
+
+    /// The injected fault still in effect, if any.
+    pub fn busy_fault(&self) -> Option<BusyFault> {
+        self.fault
+    }
+
+    /// The bus clock that BUSY timing is measured against.
+    pub fn bus_clock(&self) -> &VirtualClock {
+        &self.busy.bus_clock
+    }
+
+    /// A handle that samples this controller's BUSY line.
+    pub fn busy_line(&self) -> BusyLine {
+        self.busy.clone()
+    }
+
+    /// The wrapped emulator.
+    pub fn emulator(&self) -> &Emulator {
+        &self.emulator
+    }
+
+    /// The wrapped emulator (mutable).
+    pub fn emulator_mut(&mut self) -> &mut Emulator {
+        &mut self.emulator
+    }
+
+    /// Contents of the B/W RAM (1bpp, MSB-first, 1 = white).
+    pub fn bw_ram(&self) -> &[u8] {
+        &self.bw_ram
+    }
+
+    /// Contents of the red / previous-frame RAM.
+    pub fn red_ram(&self) -> &[u8] {
+        &self.red_ram
+    }
+
+    /// Current RAM address counters `(x_byte, y)`.
+    pub fn counters(&self) -> (u16, u16) {
+        (self.x_counter, self.y_counter)
+    }
+
+    /// Whether the controller is in deep sleep.
+    pub fn is_deep_sleep(&self) -> bool {
+        self.deep_sleep
+    }
+
+    /// Number of master activations that ran a display update.
+    pub fn activations(&self) -> u32 {
+        self.activations
+    }
+
+    /// Level of the BUSY line (true = busy).
+    pub fn is_busy(&self) -> bool {
+        self.busy.is_high()
+    }
+
+    /// Start capturing bus traffic, discarding any previous capture.
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(SpiTrace::new());
+    }
+
+    /// Stop capturing and return what was recorded.
+    pub fn stop_recording(&mut self) -> Option<SpiTrace> {
+        self.recorder.take()
+    }
+
+    /// The capture in progress, if any.
+    pub fn trace(&self) -> Option<&SpiTrace> {
+        self.recorder.as_ref()
+    }
+
+    fn record(&mut self, op: SpiOp) {
+        if let Some(trace) = &mut self.recorder {
+            trace.push(self.busy.bus_clock.now_ms(), op);
+        }
+    }
+
+    fn set_busy_for(&mut self, ms: u64) {
+        let until = self.busy.bus_clock.now_ms().saturating_add(ms);
+        self.busy.busy_until_ms.store(until, Ordering::Release);
+        if self.fault == Some(BusyFault::StuckUntilReset) {
+            self.busy.stuck.store(true, Ordering::Release);
+        }
+    }
+
+    fn reset_registers(&mut self) {
+        self.x_range = (0, self.bytes_per_row.saturating_sub(1));
+        self.y_range = (0, self.rows.saturating_sub(1));
+        self.x_counter = 0;
+        self.y_counter = 0;
+        self.entry_mode = 0x03; // X+, Y+
+        self.update_ctrl1 = 0x00;
+        self.update_ctrl2 = 0xF7;
+        self.command = None;
+        self.params.clear();
+    }
+
+    /// Pulse the RST line: wakes from deep sleep and resets registers.
+    ///
+    /// RAM contents are retained. Clears a [`BusyFault::StuckUntilReset`].
+    pub fn hardware_reset(&mut self) {
+        self.record(SpiOp::Reset);
+        self.deep_sleep = false;
+        if self.fault == Some(BusyFault::StuckUntilReset) {
+            self.clear_busy_fault();
+        }
+        self.set_busy_for(self.timing.hardware_reset_ms);
+        self.reset_registers();
+    }
+
+    /// Receive a command byte (DC low).
+    pub async fn write_command(&mut self, command: u8) -> Result<(), std::io::Error> {
+        self.record(SpiOp::Command(command));
+        if self.deep_sleep {
+            return Ok(());
+        }
+        self.command = Some(command);
+        self.params.clear();
+        if param_len(command) == Some(0) {
+            self.execute(command).await?;
+        }
+        Ok(())
+    }
+
+    /// Receive data bytes (DC high) for the current command.
+    pub async fn write_data(&mut self, data: &[u8]) -> Result<(), std::io::Error> {
+        self.record(SpiOp::Data(data.to_vec()));
+        if self.deep_sleep {
+            return Ok(());
+        }
+        let Some(command) = self.command else {
+            return Ok(());
+        };
+        match command {
+            cmd::WRITE_RAM_BW | cmd::WRITE_RAM_RED => {
+                for &byte in data {
+                    self.write_ram_byte(command == cmd::WRITE_RAM_RED, byte);
+                }
+            }
+            _ => {
+                for &byte in data {
+                    self.params.push(byte);
+                    if param_len(command) == Some(self.params.len()) {
+                        self.execute(command).await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn ram_index(&self, x_byte: u16, y: u16) -> usize {
+        usize::from(y)
+            .saturating_mul(usize::from(self.bytes_per_row))
+            .saturating_add(usize::from(x_byte))
+    }
+
+    fn write_ram_byte(&mut self, red: bool, byte: u8) {
+        let index = self.ram_index(self.x_counter, self.y_counter);
+        let ram = if red { &mut self.red_ram } else { &mut self.bw_ram };
+        if let Some(slot) = ram.get_mut(index) {
+            *slot = byte;
+        }
+        self.advance_counters();
+    }
+
+    /// Step the address counters per the data entry mode (X first).
+    fn advance_counters(&mut self) {
+        let x_inc = self.entry_mode & 0x01 != 0;
+        let y_inc = self.entry_mode & 0x02 != 0;
+        let (x_start, x_end) = self.x_range;
+        let (y_start, y_end) = self.y_range;
+
+        let x_at_end = if x_inc {
+            self.x_counter >= x_end
+        } else {
+            self.x_counter <= x_end
+        };
+        if !x_at_end {
+            self.x_counter = step(self.x_counter, x_inc);
+            return;
+        }
+
+        self.x_counter = x_start;
+        let y_at_end = if y_inc {
+            self.y_counter >= y_end
+        } else {
+            self.y_counter <= y_end
+        };
+        self.y_counter = if y_at_end {
+            y_start
+        } else {
+            step(self.y_counter, y_inc)
+        };
+    }
+
+    async fn execute(&mut self, command: u8) -> Result<(), std::io::Error> {
+        let p = std::mem::take(&mut self.params);
+        let byte = |i: usize| p.get(i).copied().unwrap_or(0);
+        let word = |i: usize| u16::from_le_bytes([byte(i), byte(i.saturating_add(1))]);
+
+        match command {
+            cmd::SOFT_RESET => {
+                self.reset_registers();
+                self.set_busy_for(self.timing.soft_reset_ms);
+            }
+            cmd::DEEP_SLEEP => {
+                self.deep_sleep = byte(0) != 0;
+                if self.deep_sleep {
+                    // BUSY stays high while asleep; only RST wakes the chip.
+                    self.set_busy_for(u64::MAX);
+                }
+            }
+            cmd::DATA_ENTRY_MODE => self.entry_mode = byte(0) & 0x07,
+            cmd::DISPLAY_UPDATE_CTRL1 => self.update_ctrl1 = byte(0),
+            cmd::DISPLAY_UPDATE_CTRL2 => self.update_ctrl2 = byte(0),
+            cmd::SET_RAM_X_RANGE => {
+                self.x_range = (word(0), word(2));
+                self.x_counter = word(0);
+            }
+            cmd::SET_RAM_Y_RANGE => {
+                self.y_range = (word(0), word(2));
+                self.y_counter = word(0);
+            }
+            cmd::SET_RAM_X_COUNTER => self.x_counter = word(0),
+            cmd::SET_RAM_Y_COUNTER => self.y_counter = word(0),
+            cmd::AUTO_WRITE_BW_RAM => {
+                self.bw_ram.fill(auto_fill_byte(byte(0)));
+                self.set_busy_for(self.timing.auto_write_ms);
+            }
+            cmd::AUTO_WRITE_RED_RAM => {
+                self.red_ram.fill(auto_fill_byte(byte(0)));
+                self.set_busy_for(self.timing.auto_write_ms);
+            }
+            cmd::MASTER_ACTIVATION => self.master_activation().await?,
+            // Analog / waveform configuration has no visible effect here.
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Run the configured display update sequence.
+    async fn master_activation(&mut self) -> Result<(), std::io::Error> {
+        let kind = UpdateKind::from_ctrl2(self.update_ctrl2);
+        if kind == UpdateKind::NoDisplay {
+            self.set_busy_for(power_sequence_ms(self.update_ctrl2, &self.timing));
+            return Ok(());
+        }
+
+        self.render_ram();
+        let started = self.render_clock.now_ms();
+        match kind {
+            UpdateKind::Full => self.emulator.refresh_full().await?,
+            UpdateKind::Fast => self.emulator.refresh_fast().await?,
+            UpdateKind::Partial => self.emulator.refresh_partial().await?,
+            UpdateKind::NoDisplay => {}
+        }
+        let elapsed = self.render_clock.now_ms().saturating_sub(started);
+        self.set_busy_for(elapsed);
+        self.activations = self.activations.saturating_add(1);
+        Ok(())
+    }
+
+    /// Whether display update control 1 selects the inverse of the B/W
+    /// RAM (bits 3:0 = `1000`).
+    pub fn bw_inverse(&self) -> bool {
+        self.update_ctrl1 & 0x0F == 0x08
+    }
+
+    /// Copy the B/W RAM into the emulator framebuffer.
+    fn render_ram(&mut self) {
+        let width = self.emulator.framebuffer.width;
+        let rows = self.rows;
+        let reverse = self.reverse_gates;
+        let inverse = self.bw_inverse();
+        let bytes_per_row = usize::from(self.bytes_per_row);
+        let ram = &self.bw_ram;
+
+        let pixels = (0..rows).flat_map(move |y| {
+            let ram_y = if reverse {
+                rows.saturating_sub(1).saturating_sub(y)
+            } else {
+                y
+            };
+            let row_start = usize::from(ram_y).saturating_mul(bytes_per_row);
+            (0..width).map(move |x| {
+                let byte = ram
+                    .get(row_start.saturating_add((x / 8) as usize))
+                    .copied()
+                    .unwrap_or(0xFF);
+                let white = (byte & (0x80 >> (x % 8)) != 0) != inverse;
+                let color = if white { Gray4::WHITE } else { Gray4::BLACK };
+                Pixel(Point::new(x as i32, i32::from(y)), color)
+            })
+        });
+        let Ok(()) = self.emulator.draw_iter(pixels);
+    }
+}
+
+/// BUSY time of a sequence without a display step: analog enable (bit 6)
+/// and analog disable (bit 1) each add their power-rail settling time.
+fn power_sequence_ms(ctrl2: u8, timing: &BusyTiming) -> u64 {
+    const ENABLE_ANALOG: u8 = 0x40;
+    const DISABLE_ANALOG: u8 = 0x02;
+
+    let mut ms = 0u64;
+    if ctrl2 & ENABLE_ANALOG != 0 {
+        ms = ms.saturating_add(timing.power_on_ms);
+    }
+    if ctrl2 & DISABLE_ANALOG != 0 {
+        ms = ms.saturating_add(timing.power_off_ms);
+    }
+    ms
+}
+
+/// Auto-write pattern byte → RAM fill value (bit 7 selects white).
+fn auto_fill_byte(pattern: u8) -> u8 {
+    if pattern & 0x80 != 0 {
+        0xFF
+    } else {
+        0x00
+    }
+}
+
+fn step(counter: u16, increment: bool) -> u16 {
+    if increment {
+        counter.saturating_add(1)
+    } else {
+        counter.saturating_sub(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+
+    use super::*;
+    use eink_specs::displays::WAVESHARE_2_13_V4;
+
+    fn ctrl() -> ControllerEmulator {
+        ControllerEmulator::new(&WAVESHARE_2_13_V4)
+    }
+
+    #[tokio::test]
+    async fn test_window_and_counter_addressing() {
+        let mut c = ctrl();
+        c.write_command(cmd::DATA_ENTRY_MODE).await.unwrap();
+        c.write_data(&[0x03]).await.unwrap();
+        c.write_command(cmd::SET_RAM_X_RANGE).await.unwrap();
+        c.write_data(&[1, 0, 2, 0]).await.unwrap();
+        c.write_command(cmd::SET_RAM_Y_RANGE).await.unwrap();
+        c.write_data(&[5, 0, 6, 0]).await.unwrap();
+        c.write_command(cmd::WRITE_RAM_BW).await.unwrap();
+        c.write_data(&[0x11, 0x22, 0x33, 0x44]).await.unwrap();
+
+        let bpr = usize::from(c.bytes_per_row);
+        assert_eq!(c.bw_ram()[5 * bpr + 1..5 * bpr + 3], [0x11, 0x22]);
+        assert_eq!(c.bw_ram()[6 * bpr + 1..6 * bpr + 3], [0x33, 0x44]);
+        assert_eq!(c.counters(), (1, 5), "counters wrap to window start");
+    }
+
+    #[tokio::test]
+    async fn test_y_decrement_entry_mode() {
+        let mut c = ctrl();
+        c.write_command(cmd::DATA_ENTRY_MODE).await.unwrap();
+        c.write_data(&[0x01]).await.unwrap();
+        c.write_command(cmd::SET_RAM_X_RANGE).await.unwrap();
+        c.write_data(&[0, 0, 0, 0]).await.unwrap();
+        c.write_command(cmd::SET_RAM_Y_RANGE).await.unwrap();
+        c.write_data(&[9, 0, 8, 0]).await.unwrap();
+        c.write_command(cmd::WRITE_RAM_BW).await.unwrap();
+        c.write_data(&[0xA0, 0xB0]).await.unwrap();
+
+        let bpr = usize::from(c.bytes_per_row);
+        assert_eq!(c.bw_ram()[9 * bpr], 0xA0);
+        assert_eq!(c.bw_ram()[8 * bpr], 0xB0);
+    }
+
+    #[tokio::test]
+    async fn test_activation_renders_ram_and_holds_busy() {
+        let mut c = ctrl();
+        c.write_command(cmd::AUTO_WRITE_BW_RAM).await.unwrap();
+        c.write_data(&[0x00]).await.unwrap();
+        assert!(c.is_busy());
+        c.bus_clock().advance(AUTO_WRITE_BUSY_MS);
+        assert!(!c.is_busy());
+
+        c.write_command(cmd::DISPLAY_UPDATE_CTRL2).await.unwrap();
+        c.write_data(&[0xF7]).await.unwrap();
+        c.write_command(cmd::MASTER_ACTIVATION).await.unwrap();
+
+        assert_eq!(c.activations(), 1);
+        assert!(c.is_busy(), "BUSY stays high for the refresh duration");
+        assert_eq!(c.emulator().framebuffer.pixels[0], crate::EinkColor::Gray(Gray4::BLACK));
+
+        c.bus_clock().advance(10_000);
+        assert!(!c.is_busy());
+    }
+
+    #[tokio::test]
+    async fn test_ctrl1_bw_inverse_renders_ram_inverted() {
+        let mut c = ctrl();
+        c.write_command(cmd::DISPLAY_UPDATE_CTRL1).await.unwrap();
+        c.write_data(&[0x48, 0x00]).await.unwrap();
+        assert!(c.bw_inverse());
+        c.write_command(cmd::MASTER_ACTIVATION).await.unwrap();
+        assert_eq!(
+            c.emulator().framebuffer.pixels[0],
+            crate::EinkColor::Gray(Gray4::BLACK),
+            "white RAM shows black"
+        );
+
+        c.write_command(cmd::SOFT_RESET).await.unwrap();
+        assert!(!c.bw_inverse(), "reset restores normal polarity");
+    }
+
+    #[tokio::test]
+    async fn test_deep_sleep_ignores_until_reset() {
+        let mut c = ctrl();
+        c.write_command(cmd::DEEP_SLEEP).await.unwrap();
+        c.write_data(&[0x01]).await.unwrap();
+        assert!(c.is_deep_sleep());
+
+        c.write_command(cmd::WRITE_RAM_BW).await.unwrap();
+        c.write_data(&[0x00]).await.unwrap();
+        assert_eq!(c.bw_ram()[0], 0xFF);
+
+        c.hardware_reset();
+        assert!(!c.is_deep_sleep());
+    }
+
+    #[tokio::test]
+    async fn test_power_sequences_hold_busy() {
+        let mut c = ctrl();
+        c.write_command(cmd::DISPLAY_UPDATE_CTRL2).await.unwrap();
+        c.write_data(&[0xC0]).await.unwrap();
+        c.write_command(cmd::MASTER_ACTIVATION).await.unwrap();
+        c.bus_clock().advance(POWER_ON_BUSY_MS - 1);
+        assert!(c.is_busy(), "power-on holds BUSY");
+        c.bus_clock().advance(1);
+        assert!(!c.is_busy());
+
+        c.write_command(cmd::DISPLAY_UPDATE_CTRL2).await.unwrap();
+        c.write_data(&[0x83]).await.unwrap();
+        c.write_command(cmd::MASTER_ACTIVATION).await.unwrap();
+        c.bus_clock().advance(POWER_OFF_BUSY_MS - 1);
+        assert!(c.is_busy(), "power-off holds BUSY");
+        c.bus_clock().advance(1);
+        assert!(!c.is_busy());
+        assert_eq!(c.activations(), 0, "no display step ran");
+    }
+
+    #[tokio::test]
+    async fn test_busy_timing_is_configurable() {
+        let timing = BusyTiming {
+            soft_reset_ms: 40,
+            ..BusyTiming::SSD1677
+        };
+        let mut c = ctrl().with_busy_timing(timing);
+        c.write_command(cmd::SOFT_RESET).await.unwrap();
+        c.bus_clock().advance(39);
+        assert!(c.is_busy());
+        c.bus_clock().advance(1);
+        assert!(!c.is_busy());
+    }
+
+    #[tokio::test]
+    async fn test_deep_sleep_holds_busy_until_reset() {
+        let mut c = ctrl();
+        c.write_command(cmd::DEEP_SLEEP).await.unwrap();
+        c.write_data(&[0x01]).await.unwrap();
+        c.bus_clock().advance(60_000);
+        assert!(c.is_busy());
+
+        c.hardware_reset();
+        c.bus_clock().advance(HARDWARE_RESET_BUSY_MS);
+        assert!(!c.is_busy());
+    }
+
+    #[tokio::test]
+    async fn test_stuck_until_reset_recovers_on_hardware_reset() {
+        let mut c = ctrl();
+        c.inject_busy_fault(BusyFault::StuckUntilReset);
+        assert!(!c.is_busy(), "armed, not yet stuck");
+
+        c.write_command(cmd::SOFT_RESET).await.unwrap();
+        c.bus_clock().advance(60_000);
+        assert!(c.is_busy(), "busy period never ends");
+
+        c.hardware_reset();
+        assert_eq!(c.busy_fault(), None);
+        c.bus_clock().advance(HARDWARE_RESET_BUSY_MS);
+        assert!(!c.is_busy());
+        c.write_command(cmd::SOFT_RESET).await.unwrap();
+        c.bus_clock().advance(SOFT_RESET_BUSY_MS);
+        assert!(!c.is_busy(), "fault does not come back");
+    }
+
+    #[tokio::test]
+    async fn test_stuck_high_survives_reset() {
+        let mut c = ctrl();
+        c.inject_busy_fault(BusyFault::StuckHigh);
+        assert!(c.busy_line().is_high());
+        c.hardware_reset();
+        c.bus_clock().advance(60_000);
+        assert!(c.is_busy());
+
+        c.clear_busy_fault();
+        assert!(!c.is_busy());
+    }
+
+    #[test]
+    fn test_update_kind_matches_driver_sequences() {
+        assert_eq!(UpdateKind::from_ctrl2(0xF7), UpdateKind::Full);
+        assert_eq!(UpdateKind::from_ctrl2(0xD7), UpdateKind::Fast);
+        assert_eq!(UpdateKind::from_ctrl2(0xFC), UpdateKind::Partial);
+        assert_eq!(UpdateKind::from_ctrl2(0x83), UpdateKind::NoDisplay);
+        assert_eq!(UpdateKind::from_ctrl2(0xE0), UpdateKind::NoDisplay);
+    }
+}
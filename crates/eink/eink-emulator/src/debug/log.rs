@@ -0,0 +1,471 @@
+//! Log capture for the debug panel's LOG tab
+//!
+//! The firmware logs through `tracing` on the emulator target.  A subscriber
+//! layer on the firmware side forwards each event into a [`LogSink`], a
+//! cloneable handle to a bounded [`LogBuffer`] shared with the window thread.
+//! The panel reads it through a [`LogView`], which holds the per-pane state:
+//! level and module filters, pause, and scroll position.
+//!
+//! Pausing freezes the view at the newest record seen, so the pane stops
+//! moving while records keep arriving in the buffer; resuming jumps back to
+//! the live tail.  When the buffer is full the oldest record is dropped and
+//! counted.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Records kept by [`LogSink::default`].
+pub const DEFAULT_LOG_CAPACITY: usize = 2_000;
+
+/// Severity of a log record, lowest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Three-letter tag shown in the pane.
+    pub const fn tag(self) -> &'static str {
+        match self {
+            Self::Trace => "TRC",
+            Self::Debug => "DBG",
+            Self::Info => "INF",
+            Self::Warn => "WRN",
+            Self::Error => "ERR",
+        }
+    }
+
+    /// Next minimum level for the filter (wraps from `Error` to `Trace`).
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Trace => Self::Debug,
+            Self::Debug => Self::Info,
+            Self::Info => Self::Warn,
+            Self::Warn => Self::Error,
+            Self::Error => Self::Trace,
+        }
+    }
+}
+
+/// One captured log line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    /// Position in the stream; increases by one per record, never reused.
+    pub seq: u64,
+    /// Time since the sink was created.
+    pub elapsed: Duration,
+    pub level: LogLevel,
+    /// Module path of the call site (`tracing` target), e.g. `firmware::ui`.
+    pub target: String,
+    pub message: String,
+}
+
+impl fmt::Display for LogRecord {
+    /// `[   1.234] INF firmware::ui: message` — the format used for copying.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{:>4}.{:03}] {} {}: {}",
+            self.elapsed.as_secs(),
+            self.elapsed.subsec_millis(),
+            self.level.tag(),
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// Bounded FIFO of log records.
+#[derive(Debug)]
+pub struct LogBuffer {
+    records: VecDeque<LogRecord>,
+    capacity: usize,
+    next_seq: u64,
+    dropped: u64,
+    epoch: Instant,
+}
+
+impl LogBuffer {
+    /// Empty buffer holding at most `capacity` records (at least one).
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+            next_seq: 0,
+            dropped: 0,
+            epoch: Instant::now(),
+        }
+    }
+
+    /// Append a record, dropping the oldest one when full.
+    pub fn push(&mut self, level: LogLevel, target: &str, message: String) {
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+            self.dropped = self.dropped.saturating_add(1);
+        }
+        self.records.push_back(LogRecord {
+            seq: self.next_seq,
+            elapsed: self.epoch.elapsed(),
+            level,
+            target: target.to_string(),
+            message,
+        });
+        self.next_seq = self.next_seq.saturating_add(1);
+    }
+
+    /// Records currently held, oldest first.
+    pub fn records(&self) -> impl DoubleEndedIterator<Item = &LogRecord> {
+        self.records.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Records evicted because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Distinct targets currently held, sorted.
+    pub fn targets(&self) -> Vec<String> {
+        let mut targets: Vec<String> = self.records.iter().map(|r| r.target.clone()).collect();
+        targets.sort_unstable();
+        targets.dedup();
+        targets
+    }
+}
+
+/// Shared, thread-safe handle to a [`LogBuffer`].
+///
+/// Clone it into whatever produces log records; the debug manager keeps one
+/// to render from.
+#[derive(Debug, Clone)]
+pub struct LogSink(Arc<Mutex<LogBuffer>>);
+
+impl LogSink {
+    pub fn new(capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(LogBuffer::new(capacity))))
+    }
+
+    /// Append a record.  A poisoned lock (a panic while logging) drops it.
+    pub fn push(&self, level: LogLevel, target: &str, message: impl Into<String>) {
+        if let Ok(mut buf) = self.0.lock() {
+            buf.push(level, target, message.into());
+        }
+    }
+
+    /// Run `f` with the buffer locked.
+    pub fn with<R>(&self, f: impl FnOnce(&LogBuffer) -> R) -> Option<R> {
+        self.0.lock().ok().map(|buf| f(&buf))
+    }
+}
+
+impl Default for LogSink {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+/// Which records the pane shows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Records below this level are hidden.
+    pub min_level: LogLevel,
+    /// Only targets equal to this module or nested inside it are shown.
+    pub module: Option<String>,
+}
+
+impl LogFilter {
+    pub fn matches(&self, record: &LogRecord) -> bool {
+        record.level >= self.min_level
+            && self.module.as_deref().is_none_or(|m| {
+                record
+                    .target
+                    .strip_prefix(m)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+    }
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            min_level: LogLevel::Trace,
+            module: None,
+        }
+    }
+}
+
+/// Per-pane view state over a [`LogBuffer`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogView {
+    pub filter: LogFilter,
+    /// While paused, records after this sequence number are hidden.
+    paused_at: Option<u64>,
+    /// Matching lines hidden below the bottom of the pane (0 = following the tail).
+    scroll: usize,
+}
+
+/// What the LOG tab draws: the matching records plus view state.
+#[derive(Debug, Clone, Default)]
+pub struct LogSnapshot {
+    /// Matching records, oldest first.
+    pub records: Vec<LogRecord>,
+    /// Lines scrolled up from the tail.
+    pub scroll: usize,
+    pub paused: bool,
+    pub filter: LogFilter,
+    /// Records held in the buffer, before filtering.
+    pub total: usize,
+    pub dropped: u64,
+}
+
+impl LogSnapshot {
+    /// The `rows` records to draw, oldest first, honouring the scroll offset.
+    pub fn window(&self, rows: usize) -> &[LogRecord] {
+        let end = self.records.len().saturating_sub(self.scroll);
+        let start = end.saturating_sub(rows);
+        self.records.get(start..end).unwrap_or(&[])
+    }
+}
+
+impl LogView {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Freeze at the newest record in `buf`, or resume following the tail.
+    pub fn toggle_pause(&mut self, buf: &LogBuffer) {
+        self.paused_at = match self.paused_at {
+            Some(_) => {
+                self.scroll = 0;
+                None
+            }
+            None => Some(buf.records().next_back().map_or(0, |r| r.seq)),
+        };
+    }
+
+    /// Raise the minimum level by one step (wrapping back to `Trace`).
+    pub fn cycle_level(&mut self) {
+        self.filter.min_level = self.filter.min_level.next();
+        self.scroll = 0;
+    }
+
+    /// Step the module filter through all modules seen in `buf`, then back
+    /// to showing every module.
+    pub fn cycle_module(&mut self, buf: &LogBuffer) {
+        let targets = buf.targets();
+        self.filter.module = match &self.filter.module {
+            None => targets.first().cloned(),
+            Some(current) => targets.iter().find(|t| *t > current).cloned(),
+        };
+        self.scroll = 0;
+    }
+
+    /// Scroll towards older records.  Following stops until scrolled back
+    /// to the bottom.
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_add(lines);
+    }
+
+    /// Scroll towards newer records.
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    /// Jump back to the newest record.
+    pub fn scroll_to_end(&mut self) {
+        self.scroll = 0;
+    }
+
+    fn visible<'a>(&'a self, buf: &'a LogBuffer) -> impl Iterator<Item = &'a LogRecord> + 'a {
+        buf.records()
+            .filter(move |r| self.paused_at.is_none_or(|last| r.seq <= last))
+            .filter(move |r| self.filter.matches(r))
+    }
+
+    /// Matching records and view state for rendering.  The scroll offset is
+    /// clamped so the pane never scrolls past the oldest record.
+    pub fn snapshot(&self, buf: &LogBuffer) -> LogSnapshot {
+        let records: Vec<LogRecord> = self.visible(buf).cloned().collect();
+        LogSnapshot {
+            scroll: self.scroll.min(records.len().saturating_sub(1)),
+            records,
+            paused: self.is_paused(),
+            filter: self.filter.clone(),
+            total: buf.len(),
+            dropped: buf.dropped(),
+        }
+    }
+
+    /// All matching records, one formatted line each, for the clipboard.
+    pub fn copy_text(&self, buf: &LogBuffer) -> String {
+        let mut out = String::new();
+        for record in self.visible(buf) {
+            out.push_str(&record.to_string());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(records: &[(LogLevel, &str, &str)]) -> LogBuffer {
+        let mut buf = LogBuffer::new(16);
+        for &(level, target, message) in records {
+            buf.push(level, target, message.to_string());
+        }
+        buf
+    }
+
+    fn messages(snapshot: &LogSnapshot) -> Vec<&str> {
+        snapshot
+            .records
+            .iter()
+            .map(|r| r.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_buffer_drops_oldest_when_full() {
+        let mut buf = LogBuffer::new(2);
+        buf.push(LogLevel::Info, "a", "one".into());
+        buf.push(LogLevel::Info, "a", "two".into());
+        buf.push(LogLevel::Info, "a", "three".into());
+        assert_eq!(buf.len(), 2);
+        assert_eq!(buf.dropped(), 1);
+        let seqs: Vec<u64> = buf.records().map(|r| r.seq).collect();
+        assert_eq!(seqs, [1, 2]);
+    }
+
+    #[test]
+    fn test_level_and_module_filters() {
+        let buf = buffer(&[
+            (LogLevel::Debug, "firmware::display", "refresh"),
+            (LogLevel::Info, "firmware::ui", "menu"),
+            (LogLevel::Warn, "firmware::ui::menu", "overflow"),
+            (LogLevel::Error, "firmware::uix", "not a submodule"),
+        ]);
+        let mut view = LogView::new();
+        assert_eq!(view.snapshot(&buf).records.len(), 4);
+
+        view.cycle_level(); // Debug
+        view.cycle_level(); // Info
+        assert_eq!(
+            messages(&view.snapshot(&buf)),
+            ["menu", "overflow", "not a submodule"]
+        );
+
+        view.filter.module = Some("firmware::ui".into());
+        assert_eq!(messages(&view.snapshot(&buf)), ["menu", "overflow"]);
+    }
+
+    #[test]
+    fn test_cycle_module_walks_targets_then_clears() {
+        let buf = buffer(&[
+            (LogLevel::Info, "b", "1"),
+            (LogLevel::Info, "a", "2"),
+            (LogLevel::Info, "b", "3"),
+        ]);
+        let mut view = LogView::new();
+        view.cycle_module(&buf);
+        assert_eq!(view.filter.module.as_deref(), Some("a"));
+        view.cycle_module(&buf);
+        assert_eq!(view.filter.module.as_deref(), Some("b"));
+        view.cycle_module(&buf);
+        assert_eq!(view.filter.module, None);
+    }
+
+    #[test]
+    fn test_pause_freezes_view_until_resumed() {
+        let mut buf = buffer(&[(LogLevel::Info, "a", "before")]);
+        let mut view = LogView::new();
+        view.toggle_pause(&buf);
+        buf.push(LogLevel::Info, "a", "after".into());
+
+        let snapshot = view.snapshot(&buf);
+        assert!(snapshot.paused);
+        assert_eq!(messages(&snapshot), ["before"]);
+        assert_eq!(snapshot.total, 2);
+
+        view.toggle_pause(&buf);
+        assert_eq!(messages(&view.snapshot(&buf)), ["before", "after"]);
+    }
+
+    #[test]
+    fn test_window_scrolls_and_clamps() {
+        let buf = buffer(&[
+            (LogLevel::Info, "a", "1"),
+            (LogLevel::Info, "a", "2"),
+            (LogLevel::Info, "a", "3"),
+            (LogLevel::Info, "a", "4"),
+        ]);
+        let mut view = LogView::new();
+        let snapshot = view.snapshot(&buf);
+        let tail: Vec<&str> = snapshot
+            .window(2)
+            .iter()
+            .map(|r| r.message.as_str())
+            .collect();
+        assert_eq!(tail, ["3", "4"]);
+
+        view.scroll_up(1);
+        let snapshot = view.snapshot(&buf);
+        let lines: Vec<&str> = snapshot
+            .window(2)
+            .iter()
+            .map(|r| r.message.as_str())
+            .collect();
+        assert_eq!(lines, ["2", "3"]);
+
+        view.scroll_up(100);
+        let snapshot = view.snapshot(&buf);
+        assert_eq!(snapshot.scroll, 3);
+        assert_eq!(snapshot.window(2)[0].message, "1");
+
+        view.scroll_to_end();
+        assert_eq!(view.snapshot(&buf).scroll, 0);
+    }
+
+    #[test]
+    fn test_copy_text_uses_filtered_lines() {
+        let buf = buffer(&[
+            (LogLevel::Debug, "firmware", "hidden"),
+            (LogLevel::Warn, "firmware::ui", "shown"),
+        ]);
+        let mut view = LogView::new();
+        view.filter.min_level = LogLevel::Info;
+        let text = view.copy_text(&buf);
+        assert_eq!(text.lines().count(), 1);
+        assert!(text.ends_with("WRN firmware::ui: shown\n"), "{text}");
+    }
+
+    #[test]
+    fn test_sink_is_shared_between_clones() {
+        let sink = LogSink::new(4);
+        let producer = sink.clone();
+        std::thread::spawn(move || producer.push(LogLevel::Error, "t", "from thread"))
+            .join()
+            .unwrap();
+        assert_eq!(sink.with(LogBuffer::len), Some(1));
+    }
+}
@@ -1,6 +1,7 @@
 //! Debug manager - central coordinator
 
 use super::inspector::Inspector;
+use super::log::{LogSink, LogSnapshot, LogView};
 use super::power_graph::PowerGraph;
 use super::state::{ComponentInfo, DebugState};
 use winit::event::{ElementState, WindowEvent};
@@ -32,8 +33,15 @@ pub struct DebugManager {
     cursor_pos: Option<(f64, f64)>,
     /// Timestamp of the last idle power sample added by `maybe_add_idle_sample`.
     last_idle_sample_time: std::time::Instant,
+    /// Records shown in the LOG tab; producers hold clones of the same sink.
+    log_sink: Option<LogSink>,
+    /// Filter, pause and scroll state of the LOG tab.
+    log_view: LogView,
 }
 
+/// Lines moved per PageUp / PageDown in the LOG tab.
+const LOG_PAGE_LINES: usize = 10;
+
 impl DebugManager {
     /// Creates a new DebugManager with default state
     ///
@@ -54,6 +62,8 @@ impl DebugManager {
             modifiers: ModifiersState::empty(),
             cursor_pos: None,
             last_idle_sample_time: std::time::Instant::now(),
+            log_sink: None,
+            log_view: LogView::new(),
         }
     }
 
@@ -220,6 +230,72 @@ impl DebugManager {
         &mut self.inspector
     }
 
+    /// Returns the sink the LOG tab reads from, if one is attached.
+    pub fn log_sink(&self) -> Option<&LogSink> {
+        self.log_sink.as_ref()
+    }
+
+    /// Show `sink` in the LOG tab.  Keep a clone in the log producer (e.g. a
+    /// `tracing` layer); the view's filters and scroll position are reset.
+    pub fn set_log_sink(&mut self, sink: LogSink) {
+        self.log_sink = Some(sink);
+        self.log_view = LogView::new();
+    }
+
+    /// Returns the LOG tab's filter, pause and scroll state.
+    pub fn log_view(&self) -> &LogView {
+        &self.log_view
+    }
+
+    /// Filtered records and view state for rendering the LOG tab.  `None`
+    /// when no sink is attached.
+    pub fn log_snapshot(&self) -> Option<LogSnapshot> {
+        let sink = self.log_sink.as_ref()?;
+        Some(
+            sink.with(|buf| self.log_view.snapshot(buf))
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Copy the filtered log lines to the system clipboard.
+    fn copy_log(&self) {
+        let Some(text) = self
+            .log_sink
+            .as_ref()
+            .and_then(|sink| sink.with(|buf| self.log_view.copy_text(buf)))
+        else {
+            return;
+        };
+        match arboard::Clipboard::new().and_then(|mut cb| cb.set_text(text)) {
+            Ok(()) => eprintln!("[debug] Log copied to clipboard"),
+            Err(e) => eprintln!("[debug] Log copy failed: {e}"),
+        }
+    }
+
+    /// LOG tab hotkeys; only called while that tab is showing.
+    fn handle_log_key(&mut self, key_code: KeyCode) -> EventResult {
+        let Some(sink) = &self.log_sink else {
+            return EventResult::NotHandled;
+        };
+        let ctrl = self.modifiers.control_key();
+        let view = &mut self.log_view;
+        match key_code {
+            KeyCode::KeyL if ctrl => view.cycle_level(),
+            KeyCode::KeyM if ctrl => {
+                sink.with(|buf| view.cycle_module(buf));
+            }
+            KeyCode::Space if ctrl => {
+                sink.with(|buf| view.toggle_pause(buf));
+            }
+            KeyCode::KeyC if ctrl => self.copy_log(),
+            KeyCode::PageUp => view.scroll_up(LOG_PAGE_LINES),
+            KeyCode::PageDown => view.scroll_down(LOG_PAGE_LINES),
+            KeyCode::End => view.scroll_to_end(),
+            _ => return EventResult::NotHandled,
+        }
+        EventResult::Consumed
+    }
+
     /// Handle a mouse click at panel-local coordinates `(px, py)`.
     ///
     /// Hit-test priority (highest first):
@@ -274,12 +350,16 @@ impl DebugManager {
 
         // ── Priority 3: tab bar ───────────────────────────────────────────
         if (TAB_HIT_Y_START..TAB_HIT_Y_END).contains(&y) {
-            let tab_w = (panel_w.saturating_sub(1)) / 3;
+            let tab_w = (panel_w.saturating_sub(1)) / 4;
             if tab_w > 0 {
                 let tab_x = x.saturating_sub(1);
-                let tab_idx = (tab_x / tab_w).min(2) as usize;
-                self.state.active_tab =
-                    [DebugTab::Scene, DebugTab::Display, DebugTab::Power][tab_idx];
+                let tab_idx = (tab_x / tab_w).min(3) as usize;
+                self.state.active_tab = [
+                    DebugTab::Scene,
+                    DebugTab::Display,
+                    DebugTab::Power,
+                    DebugTab::Log,
+                ][tab_idx];
             }
             return true;
         }
@@ -297,6 +377,10 @@ impl DebugManager {
     /// - Ctrl+2: Toggle component border rendering
     /// - Ctrl+3: Toggle inspector mode
     ///
+    /// With the LOG tab showing, also: Ctrl+L (minimum level), Ctrl+M
+    /// (module filter), Ctrl+Space (pause), Ctrl+C (copy), PageUp / PageDown /
+    /// End (scroll).
+    ///
     /// # Arguments
     ///
     /// * `event` - The window event to process
@@ -359,6 +443,14 @@ impl DebugManager {
                         return EventResult::Consumed;
                     }
 
+                    // Log pane controls (LOG tab only)
+                    if self.state.panel_visible
+                        && self.state.active_tab == super::state::DebugTab::Log
+                        && self.handle_log_key(key_code) == EventResult::Consumed
+                    {
+                        return EventResult::Consumed;
+                    }
+
                     // Scene tree navigation (arrow keys active when panel is open)
                     if self.state.panel_visible {
                         const VISIBLE: usize = 10;
@@ -487,6 +579,37 @@ mod tests {
         assert!(manager.state().inspector_mode);
     }
 
+    #[test]
+    fn test_log_sink_attach_and_snapshot() {
+        use crate::debug::log::{LogLevel, LogSink};
+
+        let mut manager = DebugManager::new();
+        assert!(manager.log_sink().is_none());
+        assert!(manager.log_snapshot().is_none());
+
+        let sink = LogSink::new(16);
+        manager.set_log_sink(sink.clone());
+        sink.push(LogLevel::Info, "firmware::ui", "menu opened");
+
+        let snapshot = manager.log_snapshot().unwrap();
+        assert_eq!(snapshot.records.len(), 1);
+        assert_eq!(snapshot.records[0].message, "menu opened");
+        assert!(!snapshot.paused);
+    }
+
+    #[test]
+    fn test_tab_bar_click_selects_log_tab() {
+        use crate::debug::panel::TAB_HIT_Y_START;
+        use crate::debug::state::DebugTab;
+
+        let mut manager = DebugManager::new();
+        manager.state_mut().toggle_panel();
+        assert!(manager.handle_panel_click(270.0, f64::from(TAB_HIT_Y_START), 280, 800));
+        assert_eq!(manager.state().active_tab, DebugTab::Log);
+        assert!(manager.handle_panel_click(2.0, f64::from(TAB_HIT_Y_START), 280, 800));
+        assert_eq!(manager.state().active_tab, DebugTab::Scene);
+    }
+
     #[test]
     fn test_power_graph_accessors() {
         use crate::debug::state::RefreshType;
@@ -20,6 +20,12 @@ pub mod manager;
 #[cfg(feature = "debug")]
 pub mod inspector;
 
+#[cfg(feature = "debug")]
+pub mod profiler;
+
+#[cfg(feature = "debug")]
+pub mod log;
+
 #[cfg(feature = "debug")]
 pub use inspector::{Inspector, InspectorTab};
 
@@ -29,6 +35,9 @@ pub use state::*;
 #[cfg(feature = "debug")]
 pub use manager::*;
 
+#[cfg(feature = "debug")]
+pub use log::{LogLevel, LogRecord, LogSink, LogSnapshot, LogView};
+
 #[cfg(feature = "debug")]
 pub use overlay::OverlayRenderer;
 
@@ -40,3 +49,6 @@ pub use panel::PanelInfo;
 
 #[cfg(feature = "debug")]
 pub use power_graph::PowerGraph;
+
+#[cfg(feature = "debug")]
+pub use profiler::{DrawCallSample, RenderProfiler, ScreenProfile, TargetModel};
@@ -66,6 +66,7 @@ use embedded_graphics::{
     text::Text,
 };
 
+use super::log::LogLevel;
 use super::state::{DebugState, DebugTab, RefreshType};
 
 // ---------------------------------------------------------------------------
@@ -154,6 +155,16 @@ pub struct PanelInfo<'a> {
     pub power_graph: Option<&'a super::power_graph::PowerGraph>,
     /// Accumulated hardware power statistics.  `None` when not tracked.
     pub power_stats: Option<&'a crate::power::PowerStats>,
+    /// Per-screen render timing.  `None` when not tracked.
+    pub render_profiler: Option<&'a super::profiler::RenderProfiler>,
+    /// Latest statistics for each reported cache (album art, fonts, …).
+    pub caches: &'a [super::state::CacheReport],
+    /// Latest background test run (`xtask dev --watch-tests`).  `None` when not watching.
+    pub tests: Option<&'a super::state::TestReport>,
+    /// Interaction latency (input → refresh complete).  `None` when not tracked.
+    pub latency: Option<&'a platform::LatencyTracker>,
+    /// Filtered firmware log for the LOG tab.  `None` when no sink is attached.
+    pub log: Option<&'a super::log::LogSnapshot>,
 }
 
 // ---------------------------------------------------------------------------
@@ -561,14 +572,15 @@ pub fn render_into(buf: &mut [u32], panel_w: u32, height: u32, info: &PanelInfo<
     cy += 1;
 
     // =========================================================================
-    // TAB BAR  –  [SCENE] [DISP] [PWR]  (Tab key cycles)
+    // TAB BAR  –  [SCENE] [DISP] [PWR] [LOG]  (Tab key cycles)
     // =========================================================================
     cy += 3;
-    let tab_w = (panel_w - 1) / 3; // distribute available width across 3 tabs
+    let tab_w = (panel_w - 1) / 4; // distribute available width across 4 tabs
     let tab_defs: &[(&str, DebugTab)] = &[
         ("SCENE", DebugTab::Scene),
         ("DISP", DebugTab::Display),
         ("PWR", DebugTab::Power),
+        ("LOG", DebugTab::Log),
     ];
     for (i, &(label, tab)) in tab_defs.iter().enumerate() {
         let tx = 1 + i as u32 * tab_w;
@@ -896,6 +908,207 @@ pub fn render_into(buf: &mut [u32], panel_w: u32, height: u32, info: &PanelInfo<
             cy += 1;
             cy += 4;
 
+            push!(PL, cy, "RENDER", col_dim);
+            cy += LH;
+            match info.render_profiler.and_then(|p| p.last_screen().map(|s| (p, s))) {
+                Some((profiler, last)) => {
+                    let budget_ms = profiler.model().budget_us as f32 / 1000.0;
+                    push!(PL, cy, last.screen.chars().take(30).collect::<String>(), col_normal);
+                    cy += LH;
+                    push!(
+                        PL,
+                        cy,
+                        format!("{} calls  {} px", last.draw_calls, last.pixels),
+                        col_normal
+                    );
+                    cy += LH;
+                    push!(
+                        PL,
+                        cy,
+                        format!(
+                            "Host: {:.2}ms  lay {:.2}ms",
+                            last.draw_host_us as f32 / 1000.0,
+                            last.layout_host_us as f32 / 1000.0
+                        ),
+                        col_normal
+                    );
+                    cy += LH;
+                    let target_col = if last.over_budget { col_err } else { col_green };
+                    push!(
+                        PL,
+                        cy,
+                        format!(
+                            "H743: ~{:.1}ms / {:.0}ms",
+                            last.estimated_target_us as f32 / 1000.0,
+                            budget_ms
+                        ),
+                        target_col
+                    );
+                    cy += LH;
+                    if let Some(worst) = profiler.slowest_screen() {
+                        let worst_col = if worst.over_budget { col_warn } else { col_dim };
+                        push!(
+                            PL,
+                            cy,
+                            format!(
+                                "Worst: {} {:.1}ms",
+                                worst.screen.chars().take(16).collect::<String>(),
+                                worst.estimated_target_us as f32 / 1000.0
+                            ),
+                            worst_col
+                        );
+                        cy += LH;
+                    }
+                }
+                None => {
+                    push!(PL, cy, "No data", col_dim);
+                    cy += LH;
+                }
+            }
+
+            seps.push(cy as u32);
+            cy += 1;
+            cy += 4;
+
+            push!(PL, cy, "LATENCY", col_dim);
+            cy += LH;
+            match info.latency.and_then(|l| l.last().map(|last| (l, last))) {
+                Some((tracker, last)) => {
+                    let budget = tracker.budget_ms();
+                    push!(
+                        PL,
+                        cy,
+                        format!(
+                            "Last: {}ms (draw {}ms)",
+                            last.total_ms(),
+                            last.input_to_render_ms()
+                        ),
+                        if last.within(budget) {
+                            col_green
+                        } else {
+                            col_err
+                        }
+                    );
+                    cy += LH;
+                    let worst = tracker.worst().map_or(0, |w| w.total_ms());
+                    push!(
+                        PL,
+                        cy,
+                        format!("Worst: {worst}ms / {budget}ms"),
+                        if worst > budget { col_warn } else { col_dim }
+                    );
+                    cy += LH;
+                    push!(
+                        PL,
+                        cy,
+                        format!(
+                            "{} interactions  {} over",
+                            tracker.count(),
+                            tracker.over_budget_count()
+                        ),
+                        col_dim
+                    );
+                    cy += LH;
+                }
+                None => {
+                    push!(PL, cy, "No data", col_dim);
+                    cy += LH;
+                }
+            }
+
+            seps.push(cy as u32);
+            cy += 1;
+            cy += 4;
+
+            if let Some(tests) = info.tests {
+                push!(PL, cy, "TESTS", col_dim);
+                cy += LH;
+                let (summary, summary_col) = if tests.running {
+                    ("Running...".to_string(), col_cyan)
+                } else if tests.is_green() {
+                    (format!("{} passed", tests.passed), col_green)
+                } else {
+                    (
+                        format!("{} failed  {} passed", tests.failed.len(), tests.passed),
+                        col_err,
+                    )
+                };
+                push!(PL, cy, summary, summary_col);
+                cy += LH;
+                push!(
+                    PL,
+                    cy,
+                    tests.scope.chars().take(40).collect::<String>(),
+                    col_dim
+                );
+                cy += LH;
+                // Bounded so a broken build cannot push the hotkeys off-screen.
+                for name in tests.failed.iter().take(6) {
+                    push!(
+                        PL,
+                        cy,
+                        format!("  {}", name.chars().take(38).collect::<String>()),
+                        col_err
+                    );
+                    cy += LH;
+                }
+                if tests.failed.len() > 6 {
+                    push!(
+                        PL,
+                        cy,
+                        format!("  +{} more", tests.failed.len().saturating_sub(6)),
+                        col_dim
+                    );
+                    cy += LH;
+                }
+
+                seps.push(cy as u32);
+                cy += 1;
+                cy += 4;
+            }
+
+            if !info.caches.is_empty() {
+                push!(PL, cy, "CACHES", col_dim);
+                cy += LH;
+                for cache in info.caches {
+                    let rate = cache.hit_rate_percent();
+                    let rate_col = if cache.hits + cache.misses == 0 {
+                        col_dim
+                    } else if rate < 50 {
+                        col_warn
+                    } else {
+                        col_green
+                    };
+                    push!(
+                        PL,
+                        cy,
+                        format!(
+                            "{}  {}%  {}/{}",
+                            cache.name.chars().take(12).collect::<String>(),
+                            rate,
+                            cache.resident,
+                            cache.capacity
+                        ),
+                        rate_col
+                    );
+                    cy += LH;
+                    push!(
+                        PL,
+                        cy,
+                        format!(
+                            "  h{} m{} e{} pf{}",
+                            cache.hits, cache.misses, cache.evictions, cache.prefetched
+                        ),
+                        col_dim
+                    );
+                    cy += LH;
+                }
+
+                seps.push(cy as u32);
+                cy += 1;
+                cy += 4;
+            }
+
             push!(PL, cy, "HOTKEYS", col_dim);
             cy += LH;
             let keys: &[(&str, &str)] = &[
@@ -999,6 +1212,77 @@ pub fn render_into(buf: &mut [u32], panel_w: u32, height: u32, info: &PanelInfo<
                 }
             }
         }
+
+        // -----------------------------------------------------------------
+        // LOG TAB
+        // -----------------------------------------------------------------
+        DebugTab::Log => match info.log {
+            None => {
+                push!(PL, cy, "No log sink attached.", col_dim);
+                cy += LH;
+                push!(PL, cy, "Call Emulator::attach_log_sink", col_dim);
+                cy += LH;
+                push!(PL, cy, "to route firmware logs here.", col_dim);
+            }
+            Some(log) => {
+                let module: String = log
+                    .filter
+                    .module
+                    .as_deref()
+                    .unwrap_or("*")
+                    .chars()
+                    .take(max_chars.saturating_sub(14))
+                    .collect();
+                push!(
+                    PL,
+                    cy,
+                    format!(">={} {module}", log.filter.min_level.tag()),
+                    col_normal
+                );
+                if log.paused {
+                    push!(panel_w as i32 - PL - 6 * 6, cy, "PAUSED", col_warn);
+                }
+                cy += LH;
+                let mut counts = format!("{}/{} lines", log.records.len(), log.total);
+                if log.dropped > 0 {
+                    counts.push_str(&format!(" ({} dropped)", log.dropped));
+                }
+                if log.scroll > 0 {
+                    counts.push_str(&format!(" -{}", log.scroll));
+                }
+                let counts: String = counts.chars().take(max_chars).collect();
+                push!(PL, cy, counts, col_dim);
+                cy += LH;
+                seps.push(cy as u32);
+                cy += 1;
+                cy += 3;
+
+                // Leave two lines at the bottom for the key hints.
+                let budget = height as i32 - cy - 2 * LH - 4;
+                let rows = (budget / LH).max(1) as usize;
+                for record in log.window(rows) {
+                    let col = match record.level {
+                        LogLevel::Error => col_err,
+                        LogLevel::Warn => col_warn,
+                        LogLevel::Info => col_normal,
+                        LogLevel::Debug | LogLevel::Trace => col_dim,
+                    };
+                    // Last path segment keeps the module readable in 45 columns.
+                    let module = record.target.rsplit("::").next().unwrap_or("");
+                    let line: String =
+                        format!("{} {module}: {}", record.level.tag(), record.message)
+                            .chars()
+                            .take(max_chars)
+                            .collect();
+                    push!(PL, cy, line, col);
+                    cy += LH;
+                }
+
+                let hint_y = height as i32 - 2 * LH + 6;
+                push!(PL, hint_y, "^L level  ^M module  ^Spc pause", col_dim);
+                push!(PL, hint_y + LH, "^C copy  PgUp/PgDn/End scroll", col_dim);
+            }
+        },
     }
 
     let _ = cy;
@@ -1100,6 +1384,11 @@ mod tests {
             temperature: 25,
             power_graph: None,
             power_stats: None,
+            render_profiler: None,
+            caches: &[],
+            tests: None,
+            latency: None,
+            log: None,
         }
     }
 
@@ -1338,6 +1627,11 @@ mod tests {
             temperature: -5,
             power_graph: Some(&graph),
             power_stats: None,
+            render_profiler: None,
+            caches: &[],
+            tests: None,
+            latency: None,
+            log: None,
         };
 
         // Must not panic
@@ -1353,6 +1647,153 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_render_into_display_tab_with_render_profile() {
+        use crate::debug::profiler::RenderProfiler;
+        use std::time::Duration;
+
+        let panel_w = 280u32;
+        let height = 800u32;
+        let mut buf = vec![0u32; (panel_w * height) as usize];
+
+        let mut state = DebugState::new();
+        state.active_tab = DebugTab::Display;
+        let mut profiler = RenderProfiler::new();
+        profiler.begin_screen("A screen name far too long for the panel width");
+        profiler.record_draw_call(Duration::from_micros(120), 480 * 800);
+        profiler.finish_screen();
+
+        let info = PanelInfo {
+            render_profiler: Some(&profiler),
+            ..make_info(&state)
+        };
+
+        // Must not panic
+        render_into(&mut buf, panel_w, height, &info);
+        assert_eq!(buf[0], 0xFF4A4A6A);
+    }
+
+    #[test]
+    fn test_render_into_display_tab_with_latency() {
+        let panel_w = 280u32;
+        let height = 800u32;
+        let mut buf = vec![0u32; (panel_w * height) as usize];
+
+        let mut state = DebugState::new();
+        state.active_tab = DebugTab::Display;
+        let mut latency = platform::LatencyTracker::new();
+        latency.on_input(0);
+        latency.on_render(20);
+        latency.on_refresh_complete(2_400);
+
+        let info = PanelInfo {
+            latency: Some(&latency),
+            ..make_info(&state)
+        };
+
+        // Must not panic
+        render_into(&mut buf, panel_w, height, &info);
+        assert_eq!(buf[0], 0xFF4A4A6A);
+    }
+
+    #[test]
+    fn test_render_into_log_tab() {
+        use crate::debug::log::{LogBuffer, LogLevel, LogView};
+
+        let panel_w = 280u32;
+        let height = 800u32;
+        let mut buf = vec![0u32; (panel_w * height) as usize];
+
+        let mut state = DebugState::new();
+        state.active_tab = DebugTab::Log;
+
+        // No sink attached: placeholder text only.
+        render_into(&mut buf, panel_w, height, &make_info(&state));
+        assert_eq!(buf[0], 0xFF4A4A6A);
+
+        let mut log = LogBuffer::new(8);
+        for i in 0..20 {
+            log.push(
+                LogLevel::Warn,
+                "firmware::ui::menu",
+                format!("line {i} {}", "x".repeat(80)),
+            );
+        }
+        let mut view = LogView::new();
+        view.toggle_pause(&log);
+        view.scroll_up(3);
+        let snapshot = view.snapshot(&log);
+        let info = PanelInfo {
+            log: Some(&snapshot),
+            ..make_info(&state)
+        };
+
+        // Long lines, dropped records, pause and scroll must not panic.
+        render_into(&mut buf, panel_w, height, &info);
+        assert_eq!(buf[0], 0xFF4A4A6A);
+    }
+
+    #[test]
+    fn test_render_into_display_tab_with_caches() {
+        use crate::debug::state::CacheReport;
+
+        let panel_w = 280u32;
+        let height = 800u32;
+        let mut buf = vec![0u32; (panel_w * height) as usize];
+
+        let mut state = DebugState::new();
+        state.active_tab = DebugTab::Display;
+        let caches = [
+            CacheReport {
+                name: "album-art".into(),
+                hits: 30,
+                misses: 10,
+                evictions: 2,
+                prefetched: 4,
+                resident: 12,
+                capacity: 512,
+            },
+            CacheReport {
+                name: "glyphs".into(),
+                ..CacheReport::default()
+            },
+        ];
+        let info = PanelInfo {
+            caches: &caches,
+            ..make_info(&state)
+        };
+
+        // Must not panic
+        render_into(&mut buf, panel_w, height, &info);
+        assert_eq!(buf[0], 0xFF4A4A6A);
+    }
+
+    #[test]
+    fn test_render_into_display_tab_with_test_failures() {
+        use crate::debug::state::TestReport;
+
+        let panel_w = 280u32;
+        let height = 800u32;
+        let mut buf = vec![0u32; (panel_w * height) as usize];
+
+        let mut state = DebugState::new();
+        state.active_tab = DebugTab::Display;
+        let report = TestReport {
+            running: false,
+            scope: "firmware-ui eink-testing".into(),
+            passed: 40,
+            failed: (0..9).map(|i| format!("golden::screen_{i}")).collect(),
+        };
+        let info = PanelInfo {
+            tests: Some(&report),
+            ..make_info(&state)
+        };
+
+        // Must not panic, even with more failures than the section shows
+        render_into(&mut buf, panel_w, height, &info);
+        assert_eq!(buf[0], 0xFF4A4A6A);
+    }
+
     #[test]
     fn test_render_into_temperature_warning() {
         let panel_w = 280u32;
@@ -1416,6 +1857,11 @@ mod tests {
                 temperature: 20,
                 power_graph: None,
                 power_stats: None,
+                render_profiler: None,
+                caches: &[],
+                tests: None,
+                latency: None,
+                log: None,
             };
             render_into(&mut buf, panel_w, height, &info);
         }
@@ -0,0 +1,379 @@
+//! Render CPU-budget profiler
+//!
+//! Captures per-`draw_iter` timing and pixel counts, grouped into *screens*
+//! (everything drawn between two refreshes), so slow screens can be spotted
+//! on the desktop before they ship to the 480 MHz STM32H743.
+//!
+//! Host timings are wall-clock and only meaningful relative to each other.
+//! The target estimate comes from a fixed cost model ([`TargetModel`]) driven
+//! by pixel and call counts, so it is deterministic and comparable across
+//! machines.
+//!
+//! # Example
+//!
+//! ```ignore
+//! emulator.begin_screen("NowPlaying");
+//! let layout = emulator.time_layout(|| root.layout(constraints));
+//! root.draw(&mut emulator)?;
+//! emulator.refresh_full().await?;   // closes the screen
+//!
+//! let profiler = emulator.render_profiler();
+//! for screen in profiler.over_budget() {
+//!     println!("{} needs ~{} us on target", screen.screen, screen.estimated_target_us);
+//! }
+//! std::fs::write("render_profile.json", profiler.to_json()?)?;
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Number of finished screens retained.
+const MAX_SCREENS: usize = 64;
+
+/// Per-call samples kept per screen; later calls still count towards totals.
+const MAX_DRAW_CALLS_PER_SCREEN: usize = 2048;
+
+/// Screen name used until [`RenderProfiler::begin_screen`] is called.
+const UNNAMED_SCREEN: &str = "(unnamed)";
+
+/// Cost model for estimating render time on the target MCU.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TargetModel {
+    /// Target core clock in Hz.
+    pub cpu_hz: u64,
+    /// Cycles to push one pixel through `DrawTarget::draw_iter` into the
+    /// SRAM framebuffer.
+    pub cycles_per_pixel: u64,
+    /// Fixed per-call overhead (iterator setup, clipping, dispatch).
+    pub cycles_per_call: u64,
+    /// Multiplier applied to host layout time (desktop core vs Cortex-M7).
+    pub layout_slowdown: u64,
+    /// Render budget per screen in microseconds.
+    pub budget_us: u64,
+}
+
+impl TargetModel {
+    /// STM32H743 at 480 MHz with a 50 ms per-screen budget.
+    pub const STM32H743: Self = Self {
+        cpu_hz: 480_000_000,
+        cycles_per_pixel: 20,
+        cycles_per_call: 2_000,
+        layout_slowdown: 10,
+        budget_us: 50_000,
+    };
+
+    /// Estimated target time in microseconds for the given workload.
+    pub fn estimate_us(&self, draw_calls: u64, pixels: u64, host_layout_us: u64) -> u64 {
+        let cycles = pixels
+            .saturating_mul(self.cycles_per_pixel)
+            .saturating_add(draw_calls.saturating_mul(self.cycles_per_call));
+        let draw_us = cycles
+            .saturating_mul(1_000_000)
+            .checked_div(self.cpu_hz)
+            .unwrap_or(0);
+        draw_us.saturating_add(host_layout_us.saturating_mul(self.layout_slowdown))
+    }
+}
+
+impl Default for TargetModel {
+    fn default() -> Self {
+        Self::STM32H743
+    }
+}
+
+/// One `draw_iter` call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DrawCallSample {
+    /// Host time spent inside `draw_iter`, in microseconds.
+    pub host_us: u64,
+    /// Pixels pushed by the iterator (including clipped ones).
+    pub pixels: u64,
+}
+
+/// Everything drawn between two refreshes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScreenProfile {
+    /// Monotonic screen number.
+    pub index: u64,
+    /// Name set via [`RenderProfiler::begin_screen`].
+    pub screen: String,
+    /// Number of `draw_iter` calls.
+    pub draw_calls: u64,
+    /// Total pixels pushed.
+    pub pixels: u64,
+    /// Host time spent in `draw_iter`, in microseconds.
+    pub draw_host_us: u64,
+    /// Host time spent in layout, in microseconds.
+    pub layout_host_us: u64,
+    /// Estimated render time on the target, in microseconds.
+    pub estimated_target_us: u64,
+    /// Whether the estimate exceeds the model's budget.
+    pub over_budget: bool,
+    /// Individual calls, capped at an internal limit.
+    pub calls: Vec<DrawCallSample>,
+}
+
+impl ScreenProfile {
+    fn new(index: u64, screen: String) -> Self {
+        Self {
+            index,
+            screen,
+            draw_calls: 0,
+            pixels: 0,
+            draw_host_us: 0,
+            layout_host_us: 0,
+            estimated_target_us: 0,
+            over_budget: false,
+            calls: Vec::new(),
+        }
+    }
+
+    /// Nothing was drawn or laid out.
+    pub fn is_empty(&self) -> bool {
+        self.draw_calls == 0 && self.layout_host_us == 0
+    }
+
+    /// The most expensive call by host time.
+    pub fn slowest_call(&self) -> Option<&DrawCallSample> {
+        self.calls.iter().max_by_key(|c| c.host_us)
+    }
+}
+
+/// Serialised form of the profiler.
+#[derive(Serialize)]
+struct Report<'a> {
+    model: &'a TargetModel,
+    screens: &'a VecDeque<ScreenProfile>,
+}
+
+/// Collects draw-call and layout timing per screen.
+#[derive(Debug, Clone)]
+pub struct RenderProfiler {
+    model: TargetModel,
+    current: ScreenProfile,
+    screens: VecDeque<ScreenProfile>,
+}
+
+impl RenderProfiler {
+    /// Create a profiler using [`TargetModel::STM32H743`].
+    pub fn new() -> Self {
+        Self::with_model(TargetModel::default())
+    }
+
+    /// Create a profiler with a custom cost model.
+    pub fn with_model(model: TargetModel) -> Self {
+        Self {
+            model,
+            current: ScreenProfile::new(0, UNNAMED_SCREEN.to_string()),
+            screens: VecDeque::with_capacity(MAX_SCREENS),
+        }
+    }
+
+    /// The active cost model.
+    pub fn model(&self) -> &TargetModel {
+        &self.model
+    }
+
+    /// Name the screen currently being drawn.
+    ///
+    /// Anything already recorded since the last refresh is kept and
+    /// attributed to `name`.
+    pub fn begin_screen(&mut self, name: impl Into<String>) {
+        self.current.screen = name.into();
+    }
+
+    /// Record one `draw_iter` call.
+    pub fn record_draw_call(&mut self, elapsed: Duration, pixels: u64) {
+        let host_us = duration_us(elapsed);
+        let cur = &mut self.current;
+        cur.draw_calls = cur.draw_calls.saturating_add(1);
+        cur.pixels = cur.pixels.saturating_add(pixels);
+        cur.draw_host_us = cur.draw_host_us.saturating_add(host_us);
+        if cur.calls.len() < MAX_DRAW_CALLS_PER_SCREEN {
+            cur.calls.push(DrawCallSample { host_us, pixels });
+        }
+    }
+
+    /// Record time spent computing layout for the current screen.
+    pub fn record_layout(&mut self, elapsed: Duration) {
+        let cur = &mut self.current;
+        cur.layout_host_us = cur.layout_host_us.saturating_add(duration_us(elapsed));
+    }
+
+    /// Run `f` and record its duration as layout time.
+    pub fn time_layout<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let start = Instant::now();
+        let result = f();
+        self.record_layout(start.elapsed());
+        result
+    }
+
+    /// The screen being recorded (not yet closed by a refresh).
+    pub fn current(&self) -> &ScreenProfile {
+        &self.current
+    }
+
+    /// Close the current screen and start a new unnamed one.
+    ///
+    /// Empty screens (a refresh with nothing drawn) are discarded.
+    pub fn finish_screen(&mut self) -> Option<&ScreenProfile> {
+        if self.current.is_empty() {
+            self.current.screen = UNNAMED_SCREEN.to_string();
+            return None;
+        }
+        let next = ScreenProfile::new(
+            self.current.index.saturating_add(1),
+            UNNAMED_SCREEN.to_string(),
+        );
+        let mut done = std::mem::replace(&mut self.current, next);
+        done.estimated_target_us =
+            self.model
+                .estimate_us(done.draw_calls, done.pixels, done.layout_host_us);
+        done.over_budget = done.estimated_target_us > self.model.budget_us;
+        if self.screens.len() >= MAX_SCREENS {
+            self.screens.pop_front();
+        }
+        self.screens.push_back(done);
+        self.screens.back()
+    }
+
+    /// Finished screens, oldest first.
+    pub fn screens(&self) -> &VecDeque<ScreenProfile> {
+        &self.screens
+    }
+
+    /// The most recently finished screen.
+    pub fn last_screen(&self) -> Option<&ScreenProfile> {
+        self.screens.back()
+    }
+
+    /// The finished screen with the highest target estimate.
+    pub fn slowest_screen(&self) -> Option<&ScreenProfile> {
+        self.screens.iter().max_by_key(|s| s.estimated_target_us)
+    }
+
+    /// Finished screens whose estimate exceeds the budget.
+    pub fn over_budget(&self) -> impl Iterator<Item = &ScreenProfile> {
+        self.screens.iter().filter(|s| s.over_budget)
+    }
+
+    /// Drop all recorded data.
+    pub fn clear(&mut self) {
+        self.screens.clear();
+        self.current = ScreenProfile::new(self.current.index, UNNAMED_SCREEN.to_string());
+    }
+
+    /// Export the model and all finished screens as pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&Report {
+            model: &self.model,
+            screens: &self.screens,
+        })
+    }
+}
+
+impl Default for RenderProfiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn duration_us(d: Duration) -> u64 {
+    u64::try_from(d.as_micros()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_scales_with_pixels() {
+        let model = TargetModel::STM32H743;
+        // 480 000 px × 20 cycles = 9.6 M cycles = 20 ms at 480 MHz.
+        assert_eq!(model.estimate_us(0, 480_000, 0), 20_000);
+        assert_eq!(model.estimate_us(240, 0, 0), 1_000);
+        assert_eq!(model.estimate_us(0, 0, 100), 1_000);
+    }
+
+    #[test]
+    fn test_screens_are_closed_by_finish() {
+        let mut p = RenderProfiler::new();
+        p.begin_screen("Library");
+        p.record_draw_call(Duration::from_micros(40), 100);
+        p.record_draw_call(Duration::from_micros(60), 300);
+        p.record_layout(Duration::from_micros(5));
+
+        let screen = p.finish_screen().unwrap();
+        assert_eq!(screen.screen, "Library");
+        assert_eq!(screen.draw_calls, 2);
+        assert_eq!(screen.pixels, 400);
+        assert_eq!(screen.draw_host_us, 100);
+        assert_eq!(screen.layout_host_us, 5);
+        assert_eq!(screen.slowest_call().unwrap().pixels, 300);
+        assert!(!screen.over_budget);
+
+        assert_eq!(p.current().screen, UNNAMED_SCREEN);
+        assert_eq!(p.current().draw_calls, 0);
+    }
+
+    #[test]
+    fn test_empty_screen_is_discarded() {
+        let mut p = RenderProfiler::new();
+        assert!(p.finish_screen().is_none());
+        assert!(p.screens().is_empty());
+    }
+
+    #[test]
+    fn test_over_budget_flagged() {
+        let mut p = RenderProfiler::new();
+        p.begin_screen("Cheap");
+        p.record_draw_call(Duration::ZERO, 1_000);
+        p.finish_screen();
+        p.begin_screen("Heavy");
+        for _ in 0..10 {
+            p.record_draw_call(Duration::ZERO, 800 * 480);
+        }
+        p.finish_screen();
+
+        let slow: Vec<_> = p.over_budget().map(|s| s.screen.as_str()).collect();
+        assert_eq!(slow, ["Heavy"]);
+        assert_eq!(p.slowest_screen().unwrap().screen, "Heavy");
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut p = RenderProfiler::new();
+        for _ in 0..MAX_SCREENS + 5 {
+            p.record_draw_call(Duration::ZERO, 1);
+            p.finish_screen();
+        }
+        assert_eq!(p.screens().len(), MAX_SCREENS);
+        assert_eq!(p.screens().front().unwrap().index, 5);
+    }
+
+    #[test]
+    fn test_per_call_samples_are_capped() {
+        let mut p = RenderProfiler::new();
+        for _ in 0..MAX_DRAW_CALLS_PER_SCREEN + 10 {
+            p.record_draw_call(Duration::ZERO, 1);
+        }
+        let screen = p.finish_screen().unwrap();
+        assert_eq!(screen.calls.len(), MAX_DRAW_CALLS_PER_SCREEN);
+        assert_eq!(screen.draw_calls, (MAX_DRAW_CALLS_PER_SCREEN + 10) as u64);
+    }
+
+    #[test]
+    fn test_json_export() {
+        let mut p = RenderProfiler::new();
+        p.begin_screen("Settings");
+        p.record_draw_call(Duration::from_micros(3), 42);
+        p.finish_screen();
+
+        let json: serde_json::Value = serde_json::from_str(&p.to_json().unwrap()).unwrap();
+        assert_eq!(json["model"]["cpu_hz"], 480_000_000);
+        assert_eq!(json["screens"][0]["screen"], "Settings");
+        assert_eq!(json["screens"][0]["pixels"], 42);
+        assert_eq!(json["screens"][0]["calls"][0]["host_us"], 3);
+    }
+}
@@ -3,6 +3,8 @@
 use std::collections::HashSet;
 use std::time::Instant;
 
+use eink_specs::RefreshCount;
+
 /// Box-model spacing (margin / border / padding) in pixels.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub struct Spacing {
@@ -85,15 +87,103 @@ pub enum RefreshType {
     Fast,
 }
 
+/// Hit/miss counters for one runtime cache, shown in the Display tab.
+///
+/// Producers (e.g. `library::ArtCache`) cannot depend on the emulator, so the
+/// host glue copies their own stats type into this one before reporting it
+/// with `Emulator::report_cache`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheReport {
+    /// Stable display name; reports with the same name replace each other.
+    pub name: String,
+    pub hits: u32,
+    pub misses: u32,
+    pub evictions: u32,
+    /// Entries loaded speculatively rather than on demand.
+    pub prefetched: u32,
+    pub resident: u32,
+    pub capacity: u32,
+}
+
+impl CacheReport {
+    /// Hits as a percentage of lookups (0 when nothing has been looked up).
+    pub fn hit_rate_percent(&self) -> u32 {
+        let lookups = u64::from(self.hits) + u64::from(self.misses);
+        if lookups == 0 {
+            return 0;
+        }
+        (u64::from(self.hits) * 100 / lookups) as u32
+    }
+}
+
+/// Insert `report` into `caches`, replacing any entry with the same name.
+pub fn upsert_cache_report(caches: &mut Vec<CacheReport>, report: CacheReport) {
+    match caches.iter_mut().find(|c| c.name == report.name) {
+        Some(existing) => *existing = report,
+        None => caches.push(report),
+    }
+}
+
+/// Outcome of the latest background test run, shown in the Display tab.
+///
+/// `cargo xtask dev --watch-tests` writes it to the file named by
+/// `EINK_TEST_STATUS` as one `key value` pair per line:
+///
+/// ```text
+/// state running|done
+/// scope firmware-ui eink-testing
+/// passed 41
+/// failed now_playing_golden
+/// ```
+///
+/// `failed` may repeat; unknown keys are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestReport {
+    /// A run is in progress; counts are from the previous run.
+    pub running: bool,
+    /// Packages covered by the run, space separated.
+    pub scope: String,
+    pub passed: u32,
+    /// Names of the failing tests.
+    pub failed: Vec<String>,
+}
+
+impl TestReport {
+    /// Parse the status-file format described above.
+    pub fn parse(text: &str) -> Self {
+        let mut report = Self::default();
+        for line in text.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+            match key {
+                "state" => report.running = value == "running",
+                "scope" => report.scope = value.to_string(),
+                "passed" => report.passed = value.parse().unwrap_or(0),
+                "failed" if !value.is_empty() => report.failed.push(value.to_string()),
+                _ => {}
+            }
+        }
+        report
+    }
+
+    /// True when the last completed run had no failures.
+    pub fn is_green(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
 /// Which tab is currently active in the debug side panel.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DebugTab {
     /// Scene hierarchy tree + component inspector (default).
     Scene,
-    /// Display dimensions, rotation, temperature, refresh counts, hotkeys.
+    /// Display dimensions, rotation, temperature, refresh counts, render
+    /// timing, hotkeys.
     Display,
     /// Live power graph and battery statistics.
     Power,
+    /// Firmware log output with level/module filters.
+    Log,
 }
 
 /// A single row in the flattened, visibility-resolved scene tree.
@@ -130,9 +220,9 @@ pub struct DebugState {
     /// When empty, the overlay falls back to auto-generated display regions.
     pub registered_components: Vec<ComponentInfo>,
     /// Number of full refreshes recorded
-    pub full_refresh_count: u64,
+    pub full_refresh_count: RefreshCount,
     /// Number of partial refreshes recorded
-    pub partial_refresh_count: u64,
+    pub partial_refresh_count: RefreshCount,
     /// Currently active tab in the debug panel.
     pub active_tab: DebugTab,
     /// Set of component bounding-box keys whose subtrees have been explicitly
@@ -156,8 +246,8 @@ impl Default for DebugState {
             selected_component: None,
             power_history: Vec::new(),
             registered_components: Vec::new(),
-            full_refresh_count: 0,
-            partial_refresh_count: 0,
+            full_refresh_count: RefreshCount::ZERO,
+            partial_refresh_count: RefreshCount::ZERO,
             active_tab: DebugTab::Scene,
             expanded_nodes: HashSet::new(),
             scene_selected: None,
@@ -171,12 +261,13 @@ impl DebugState {
         Self::default()
     }
 
-    /// Advance to the next tab (Scene → Display → Power → Scene).
+    /// Advance to the next tab (Scene → Display → Power → Log → Scene).
     pub fn cycle_tab(&mut self) {
         self.active_tab = match self.active_tab {
             DebugTab::Scene => DebugTab::Display,
             DebugTab::Display => DebugTab::Power,
-            DebugTab::Power => DebugTab::Scene,
+            DebugTab::Power => DebugTab::Log,
+            DebugTab::Log => DebugTab::Scene,
         };
     }
 
@@ -211,12 +302,12 @@ impl DebugState {
 
     /// Record a full refresh event, incrementing the full refresh counter.
     pub fn record_full_refresh(&mut self) {
-        self.full_refresh_count += 1;
+        self.full_refresh_count.increment();
     }
 
     /// Record a partial refresh event, incrementing the partial refresh counter.
     pub fn record_partial_refresh(&mut self) {
-        self.partial_refresh_count += 1;
+        self.partial_refresh_count.increment();
     }
 
     /// Return the bounding-box key used to look up a component in
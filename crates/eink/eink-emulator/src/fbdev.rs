@@ -0,0 +1,285 @@
+//! Linux framebuffer (fbdev) presentation backend
+//!
+//! Writes frames straight to `/dev/fbN` instead of opening a winit window, so
+//! the emulator runs on a bench Raspberry Pi (HDMI or SPI TFT) over SSH with
+//! no X11/Wayland session.  Select it with
+//! [`EmulatorConfig::framebuffer`](crate::EmulatorConfig::framebuffer); it
+//! works with or without the `headless` feature.
+//!
+//! Geometry is read from sysfs (`/sys/class/graphics/fbN/`), which avoids the
+//! `FBIOGET_*` ioctls and keeps the backend dependency-free.  32 bpp
+//! (XRGB8888) and 16 bpp (RGB565) framebuffers are supported.  The display
+//! is scaled by [`EmulatorConfig::scale`](crate::EmulatorConfig) and centred;
+//! the rest of the screen is cleared to black.
+//!
+//! fbdev has no input and no debug side panel: use SSH key forwarding or the
+//! real device's buttons, and read debug state from reports instead.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+
+/// Framebuffer geometry as reported by sysfs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FbGeometry {
+    /// Visible width in pixels.
+    pub width: u32,
+    /// Visible height in pixels.
+    pub height: u32,
+    /// Bytes per scanline (may exceed `width * bytes_per_pixel`).
+    pub stride: u32,
+    /// 16 or 32.
+    pub bits_per_pixel: u32,
+}
+
+impl FbGeometry {
+    /// Read geometry for `device` (e.g. `/dev/fb0`) from `/sys/class/graphics`.
+    pub fn from_sysfs(device: &Path) -> io::Result<Self> {
+        let name = device
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no device name"))?;
+        Self::from_sysfs_dir(&Path::new("/sys/class/graphics").join(name))
+    }
+
+    /// Read geometry from a sysfs directory containing `virtual_size`,
+    /// `bits_per_pixel` and `stride`.
+    pub fn from_sysfs_dir(dir: &Path) -> io::Result<Self> {
+        let read = |file: &str| std::fs::read_to_string(dir.join(file));
+        let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, what.to_string());
+
+        let size = read("virtual_size")?;
+        let (w, h) = size
+            .trim()
+            .split_once(',')
+            .ok_or_else(|| invalid("virtual_size"))?;
+        let parse = |s: &str, what: &str| s.trim().parse::<u32>().map_err(|_| invalid(what));
+        let width = parse(w, "virtual_size")?;
+        let height = parse(h, "virtual_size")?;
+        let bits_per_pixel = parse(&read("bits_per_pixel")?, "bits_per_pixel")?;
+        let stride = match read("stride") {
+            Ok(s) => parse(&s, "stride")?,
+            // Older kernels lack `stride`; assume tightly packed rows.
+            Err(_) => width.saturating_mul(bits_per_pixel / 8),
+        };
+        let geometry = Self {
+            width,
+            height,
+            stride,
+            bits_per_pixel,
+        };
+        geometry.validate()?;
+        Ok(geometry)
+    }
+
+    fn validate(&self) -> io::Result<()> {
+        if !matches!(self.bits_per_pixel, 16 | 32) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} bpp framebuffer (need 16 or 32)", self.bits_per_pixel),
+            ));
+        }
+        if self.stride < self.width.saturating_mul(self.bits_per_pixel / 8) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "stride < width"));
+        }
+        Ok(())
+    }
+
+    fn bytes_per_pixel(&self) -> usize {
+        (self.bits_per_pixel / 8) as usize
+    }
+}
+
+/// An open framebuffer device.
+pub struct FbDevice {
+    path: PathBuf,
+    file: File,
+    geometry: FbGeometry,
+    /// Reused output buffer (one full screen).
+    scratch: Vec<u8>,
+}
+
+impl FbDevice {
+    /// Open `device` for writing and read its geometry.
+    pub fn open(device: impl AsRef<Path>) -> io::Result<Self> {
+        let path = device.as_ref().to_path_buf();
+        let geometry = FbGeometry::from_sysfs(&path)?;
+        let file = OpenOptions::new().write(true).open(&path)?;
+        Ok(Self {
+            path,
+            file,
+            geometry,
+            scratch: Vec::new(),
+        })
+    }
+
+    /// Device path this framebuffer was opened from.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Framebuffer geometry.
+    pub fn geometry(&self) -> FbGeometry {
+        self.geometry
+    }
+
+    /// Write an ARGB frame of `width`×`height`, scaled by `scale` and centred.
+    pub fn present(&mut self, argb: &[u32], width: u32, height: u32, scale: u32) -> io::Result<()> {
+        encode_frame(
+            &mut self.scratch,
+            &self.geometry,
+            argb,
+            width,
+            height,
+            scale,
+        );
+        self.file.write_all_at(&self.scratch, 0)
+    }
+}
+
+/// Encode an ARGB frame into framebuffer memory layout.
+///
+/// `out` is resized to `stride * height` bytes.  The frame is scaled by
+/// `scale` (nearest neighbour, reduced to fit if the screen is too small),
+/// centred, and surrounded by black.
+// SAFETY: all coordinates are bounded by the framebuffer geometry (a few
+// thousand pixels per side) and by the source frame dimensions, so the index
+// arithmetic cannot overflow; the u32 → u8/u16 casts extract colour channels.
+#[allow(
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation,
+    clippy::indexing_slicing
+)]
+pub fn encode_frame(
+    out: &mut Vec<u8>,
+    geometry: &FbGeometry,
+    argb: &[u32],
+    width: u32,
+    height: u32,
+    scale: u32,
+) {
+    let bpp = geometry.bytes_per_pixel();
+    let stride = geometry.stride as usize;
+    out.clear();
+    out.resize(stride * geometry.height as usize, 0);
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let fit = (geometry.width / width).min(geometry.height / height);
+    let scale = scale.max(1).min(fit.max(1));
+    let (out_w, out_h) = (
+        (width * scale).min(geometry.width),
+        (height * scale).min(geometry.height),
+    );
+    let x0 = (geometry.width - out_w) / 2;
+    let y0 = (geometry.height - out_h) / 2;
+
+    for oy in 0..out_h {
+        let sy = oy / scale;
+        let row = (y0 + oy) as usize * stride;
+        for ox in 0..out_w {
+            let sx = ox / scale;
+            let Some(&px) = argb.get((sy * width + sx) as usize) else {
+                continue;
+            };
+            let at = row + (x0 + ox) as usize * bpp;
+            match bpp {
+                4 => out[at..at + 4].copy_from_slice(&(px | 0xFF00_0000).to_le_bytes()),
+                _ => out[at..at + 2].copy_from_slice(&rgb565(px).to_le_bytes()),
+            }
+        }
+    }
+}
+
+/// Convert ARGB8888 to RGB565.
+#[allow(clippy::cast_possible_truncation)]
+fn rgb565(argb: u32) -> u16 {
+    let r = (argb >> 19) & 0x1F;
+    let g = (argb >> 10) & 0x3F;
+    let b = (argb >> 3) & 0x1F;
+    ((r << 11) | (g << 5) | b) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+    use super::*;
+
+    fn geometry(width: u32, height: u32, bpp: u32) -> FbGeometry {
+        FbGeometry {
+            width,
+            height,
+            stride: width * bpp / 8,
+            bits_per_pixel: bpp,
+        }
+    }
+
+    #[test]
+    fn xrgb8888_is_scaled_and_centred() {
+        let geo = geometry(8, 4, 32);
+        let frame = [0xFF00_0000, 0xFFFF_FFFF];
+        let mut out = Vec::new();
+        encode_frame(&mut out, &geo, &frame, 2, 1, 2);
+        assert_eq!(out.len(), 8 * 4 * 4);
+
+        let px = |x: usize, y: usize| {
+            let at = y * geo.stride as usize + x * 4;
+            u32::from_le_bytes(out[at..at + 4].try_into().unwrap())
+        };
+        // 2×1 at scale 2 → 4×2, centred at (2, 1).
+        assert_eq!(px(0, 0), 0);
+        assert_eq!(px(2, 1), 0xFF00_0000);
+        assert_eq!(px(3, 2), 0xFF00_0000);
+        assert_eq!(px(4, 1), 0xFFFF_FFFF);
+        assert_eq!(px(5, 2), 0xFFFF_FFFF);
+        assert_eq!(px(6, 1), 0);
+        assert_eq!(px(2, 3), 0);
+    }
+
+    #[test]
+    fn scale_is_reduced_to_fit() {
+        let geo = geometry(4, 4, 32);
+        let frame = [0xFFFF_FFFF; 4 * 4];
+        let mut out = Vec::new();
+        encode_frame(&mut out, &geo, &frame, 4, 4, 3);
+        assert!(out.iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn rgb565_packs_channels() {
+        assert_eq!(rgb565(0xFFFF_FFFF), 0xFFFF);
+        assert_eq!(rgb565(0xFF00_0000), 0x0000);
+        assert_eq!(rgb565(0xFFFF_0000), 0xF800);
+        assert_eq!(rgb565(0xFF00_FF00), 0x07E0);
+
+        let geo = geometry(2, 1, 16);
+        let mut out = Vec::new();
+        encode_frame(&mut out, &geo, &[0xFF00_00FF, 0xFF80_8080], 2, 1, 1);
+        assert_eq!(out[..2], 0x001Fu16.to_le_bytes());
+        assert_eq!(out.len(), 4);
+    }
+
+    #[test]
+    fn geometry_from_sysfs_dir() {
+        let dir = std::env::temp_dir().join(format!("eink_fbdev_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("virtual_size"), "800,480\n").unwrap();
+        std::fs::write(dir.join("bits_per_pixel"), "16\n").unwrap();
+        std::fs::write(dir.join("stride"), "1664\n").unwrap();
+        assert_eq!(
+            FbGeometry::from_sysfs_dir(&dir).unwrap(),
+            FbGeometry {
+                width: 800,
+                height: 480,
+                stride: 1664,
+                bits_per_pixel: 16,
+            }
+        );
+
+        std::fs::write(dir.join("bits_per_pixel"), "8\n").unwrap();
+        let err = FbGeometry::from_sysfs_dir(&dir).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
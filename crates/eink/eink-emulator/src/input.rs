@@ -22,12 +22,22 @@
 //! | Enter               | [`Button::Select`]            |
 //! | Scroll up           | `RotaryIncrement(+1)`         |
 //! | Scroll down         | `RotaryIncrement(-1)`         |
+//!
+//! # Timestamps and latency
+//!
+//! Events are stamped when the window receives them, using the emulator's
+//! clock (the attached [`VirtualClock`](crate::VirtualClock), or milliseconds
+//! since the emulator was created).  [`EmulatorInput`] also implements
+//! [`platform::TimestampedInput`], and every event it hands to the
+//! application opens an interaction in the emulator's
+//! [`LatencyTracker`](platform::LatencyTracker); see
+//! [`Emulator::latency`](crate::Emulator::latency).
 
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 
-use platform::InputDevice;
-pub use platform::{Button, InputEvent};
+pub use platform::{Button, InputEvent, TimestampedEvent};
+use platform::{InputDevice, LatencyTracker, TimestampedInput};
 use winit::keyboard::KeyCode;
 
 /// Maximum number of unread events buffered in the queue.
@@ -38,6 +48,28 @@ use winit::keyboard::KeyCode;
 #[cfg_attr(feature = "headless", allow(dead_code))]
 const QUEUE_CAP: usize = 64;
 
+// ---------------------------------------------------------------------------
+// InputClock — timestamp source shared by the emulator and the input queue
+// ---------------------------------------------------------------------------
+
+/// Time source for event timestamps.
+#[derive(Debug, Clone)]
+pub(crate) enum InputClock {
+    /// Milliseconds since the given instant (the emulator's creation).
+    Wall(std::time::Instant),
+    /// Simulated time.
+    Virtual(crate::VirtualClock),
+}
+
+impl InputClock {
+    pub fn now_ms(&self) -> u64 {
+        match self {
+            Self::Wall(epoch) => u64::try_from(epoch.elapsed().as_millis()).unwrap_or(u64::MAX),
+            Self::Virtual(clock) => clock.now_ms(),
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // InputQueue — producer (owned by the winit event loop inside Window)
 // ---------------------------------------------------------------------------
@@ -48,22 +80,34 @@ const QUEUE_CAP: usize = 64;
 /// `WindowEvent::KeyboardInput` and `WindowEvent::MouseWheel` handlers.
 #[cfg_attr(feature = "headless", allow(dead_code))]
 pub(crate) struct InputQueue {
-    queue: Arc<Mutex<VecDeque<InputEvent>>>,
+    queue: Arc<Mutex<VecDeque<TimestampedEvent>>>,
+    clock: InputClock,
 }
 
 impl InputQueue {
     /// Create a linked (producer, consumer) pair.
-    pub fn new() -> (Self, EmulatorInput) {
+    ///
+    /// Events are stamped with `clock`; the consumer reports each event it
+    /// delivers to `latency`.
+    pub fn new(clock: InputClock, latency: Arc<Mutex<LatencyTracker>>) -> (Self, EmulatorInput) {
         let q = Arc::new(Mutex::new(VecDeque::new()));
-        (InputQueue { queue: q.clone() }, EmulatorInput { queue: q })
+        (
+            InputQueue {
+                queue: q.clone(),
+                clock,
+            },
+            EmulatorInput { queue: q, latency },
+        )
     }
 
-    /// Enqueue an event. Silently drops the event if the queue is full.
+    /// Enqueue an event stamped with the current time. Silently drops the
+    /// event if the queue is full.
     #[cfg_attr(feature = "headless", allow(dead_code))]
     pub fn push(&self, event: InputEvent) {
+        let stamped = TimestampedEvent::new(event, self.clock.now_ms());
         if let Ok(mut q) = self.queue.lock() {
             if q.len() < QUEUE_CAP {
-                q.push_back(event);
+                q.push_back(stamped);
             }
         }
     }
@@ -80,22 +124,38 @@ impl InputQueue {
 /// the hardware [`HardwareInput`](firmware::input::hardware::HardwareInput)
 /// driver.
 pub struct EmulatorInput {
-    queue: Arc<Mutex<VecDeque<InputEvent>>>,
+    queue: Arc<Mutex<VecDeque<TimestampedEvent>>>,
+    latency: Arc<Mutex<LatencyTracker>>,
 }
 
 impl InputDevice for EmulatorInput {
     /// Async wait: polls the queue every 5 ms until an event is available.
     async fn wait_for_event(&mut self) -> InputEvent {
+        self.wait_for_timestamped_event().await.event
+    }
+
+    fn poll_event(&mut self) -> Option<InputEvent> {
+        self.poll_timestamped_event().map(|e| e.event)
+    }
+}
+
+impl TimestampedInput for EmulatorInput {
+    /// Async wait: polls the queue every 5 ms until an event is available.
+    async fn wait_for_timestamped_event(&mut self) -> TimestampedEvent {
         loop {
-            if let Some(e) = self.poll_event() {
+            if let Some(e) = self.poll_timestamped_event() {
                 return e;
             }
             tokio::time::sleep(std::time::Duration::from_millis(5)).await;
         }
     }
 
-    fn poll_event(&mut self) -> Option<InputEvent> {
-        self.queue.lock().ok()?.pop_front()
+    fn poll_timestamped_event(&mut self) -> Option<TimestampedEvent> {
+        let event = self.queue.lock().ok()?.pop_front()?;
+        if let Ok(mut latency) = self.latency.lock() {
+            latency.on_input(event.at_ms);
+        }
+        Some(event)
     }
 }
 
@@ -152,6 +212,10 @@ pub(crate) fn map_scroll(acc: &mut f64, delta: f64) -> Option<InputEvent> {
 mod tests {
     use super::*;
 
+    fn queue() -> (InputQueue, EmulatorInput) {
+        InputQueue::new(InputClock::Wall(std::time::Instant::now()), Arc::default())
+    }
+
     #[test]
     fn map_key_play_buttons() {
         assert_eq!(
@@ -194,7 +258,7 @@ mod tests {
 
     #[test]
     fn input_queue_push_and_poll() {
-        let (producer, mut consumer) = InputQueue::new();
+        let (producer, mut consumer) = queue();
         producer.push(InputEvent::ButtonPress(Button::Menu));
         assert_eq!(
             consumer.poll_event(),
@@ -205,7 +269,7 @@ mod tests {
 
     #[test]
     fn input_queue_capacity_limit() {
-        let (producer, mut consumer) = InputQueue::new();
+        let (producer, mut consumer) = queue();
         // Fill beyond capacity
         for _ in 0..QUEUE_CAP + 10 {
             producer.push(InputEvent::ButtonPress(Button::Select));
@@ -216,4 +280,25 @@ mod tests {
         }
         assert_eq!(count, QUEUE_CAP);
     }
+
+    #[test]
+    fn events_are_stamped_and_open_an_interaction() {
+        let clock = crate::VirtualClock::new();
+        let latency = Arc::new(Mutex::new(LatencyTracker::new()));
+        let (producer, mut consumer) =
+            InputQueue::new(InputClock::Virtual(clock.clone()), latency.clone());
+        clock.advance(1_000);
+        producer.push(InputEvent::RotaryIncrement(1));
+        clock.advance(40);
+        assert!(!latency.lock().unwrap().is_pending());
+
+        let ev = consumer.poll_timestamped_event().unwrap();
+        assert_eq!(
+            ev,
+            TimestampedEvent::new(InputEvent::RotaryIncrement(1), 1_000)
+        );
+        let mut tracker = latency.lock().unwrap();
+        assert!(tracker.is_pending());
+        assert_eq!(tracker.on_refresh_complete(1_300).unwrap().total_ms(), 300);
+    }
 }
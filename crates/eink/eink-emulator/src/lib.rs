@@ -77,17 +77,28 @@
 #![allow(missing_docs)]
 
 pub mod alignment;
+pub mod commands;
 pub mod config;
+pub mod controller;
 mod display_driver;
 mod framebuffer;
 mod initialization;
+pub mod lighting;
 pub mod lut;
+pub mod mockup;
+pub mod multi;
 pub mod partial_window;
 pub mod pixel_color;
 mod pixel_state;
 pub mod power;
+pub mod recording;
 mod refresh_mode;
+pub mod report;
+pub mod settings;
+pub mod sidecar;
+pub mod spi_trace;
 mod waveform_mode;
+pub mod wear;
 
 #[cfg(not(feature = "headless"))]
 mod window;
@@ -98,24 +109,42 @@ pub mod debug;
 #[cfg(feature = "keyboard-input")]
 pub mod input;
 
-pub use config::{EmulatorConfig, Rotation};
+#[cfg(all(feature = "fbdev", not(target_os = "linux")))]
+compile_error!("the `fbdev` feature is only supported on Linux");
+
+#[cfg(feature = "fbdev")]
+pub mod fbdev;
+
+pub use commands::EmulatorCommand;
+pub use config::{Backend, EmulatorConfig, Rotation};
+pub use controller::{BusyFault, BusyLine, BusyTiming, ControllerEmulator};
 pub use display_driver::{DisplayDriver, EinkDisplay};
 pub use framebuffer::{ColorMode, Framebuffer};
 pub use initialization::{InitSequence, InitStep, InitializationState};
+pub use lighting::{AmbientLight, FrontLight, Lighting};
 pub use lut::{LutError, LutPhase, WaveformLut, WaveformLutSet};
+pub use multi::{MultiEmulator, MultiLayout, Panel, VirtualClock};
 pub use partial_window::PartialWindow;
 pub use pixel_color::{EinkColor, SpectraColor};
 pub use pixel_state::{PixelState, PixelStateBuffer};
+pub use platform::{InteractionLatency, LatencyTracker, LATENCY_BUDGET_MS};
 pub use power::{PowerProfile, PowerState, PowerStats, PowerTracker, StatePercentages};
 pub use refresh_mode::{RefreshMode, RefreshStrategy};
+pub use settings::EmulatorSettings;
+pub use spi_trace::{SpiOp, SpiTrace, TraceDiff, TraceEntry};
 pub use waveform_mode::WaveformMode;
+pub use wear::{WearModel, WearProjection, WearReport};
+
+pub use eink_specs::{DisplayMs, RefreshCount};
 
 use embedded_graphics::pixelcolor::Gray4;
 use embedded_graphics::prelude::*;
 
+/// Rectangles one automatic dirty-tracking pass may add before the changes
+/// collapse into their bounding box.
+const AUTO_DIRTY_RECTS: usize = 16;
+
 /// Convert EinkColor framebuffer to RGBA buffer for rendering
-// In headless+no-debug mode nothing calls this, so silence the dead_code lint.
-#[cfg_attr(all(feature = "headless", not(feature = "debug")), allow(dead_code))]
 fn framebuffer_to_rgba(framebuffer: &[EinkColor]) -> Vec<u32> {
     framebuffer.iter().map(|pixel| pixel.to_rgba()).collect()
 }
@@ -123,26 +152,41 @@ fn framebuffer_to_rgba(framebuffer: &[EinkColor]) -> Vec<u32> {
 /// Display statistics tracking
 #[derive(Debug, Clone, Copy, Default)]
 pub struct DisplayStats {
-    pub full_refresh_count: u64,
-    pub partial_refresh_count: u64,
-    pub fast_refresh_count: u64,
+    pub full_refresh_count: RefreshCount,
+    pub partial_refresh_count: RefreshCount,
+    pub fast_refresh_count: RefreshCount,
+    /// Sum of refresh durations; a `u64` total since it outgrows [`DisplayMs`].
     pub total_refresh_time_ms: u64,
     pub dc_warnings: u32,
+    /// Refreshes per waveform mode, indexed by [`WaveformMode::index`].
+    pub refreshes_by_mode: [RefreshCount; 7],
 }
 
 impl DisplayStats {
-    // SAFETY: refresh counters are u64 totals; duration in ms fits in u64 for any reasonable
-    // total display runtime.
-    #[allow(clippy::arithmetic_side_effects)]
-    fn record_refresh(&mut self, mode: WaveformMode, duration_ms: u32) {
+    fn record_refresh(&mut self, mode: WaveformMode, duration: DisplayMs) {
         match mode {
             WaveformMode::GC16 | WaveformMode::GL16 | WaveformMode::GCC16 => {
-                self.full_refresh_count += 1
+                self.full_refresh_count.increment()
+            }
+            WaveformMode::DU4 => self.partial_refresh_count.increment(),
+            WaveformMode::DU | WaveformMode::A2 | WaveformMode::GCU => {
+                self.fast_refresh_count.increment()
             }
-            WaveformMode::DU4 => self.partial_refresh_count += 1,
-            WaveformMode::DU | WaveformMode::A2 | WaveformMode::GCU => self.fast_refresh_count += 1,
         }
-        self.total_refresh_time_ms += duration_ms as u64;
+        if let Some(count) = self.refreshes_by_mode.get_mut(mode.index()) {
+            count.increment();
+        }
+        self.total_refresh_time_ms = self
+            .total_refresh_time_ms
+            .saturating_add(u64::from(duration.get()));
+    }
+
+    /// Number of refreshes performed with `mode`
+    pub fn refresh_count(&self, mode: WaveformMode) -> RefreshCount {
+        self.refreshes_by_mode
+            .get(mode.index())
+            .copied()
+            .unwrap_or_default()
     }
 }
 
@@ -189,6 +233,8 @@ fn classify_draw_record(w: u32, h: u32, pixel_count: u64) -> &'static str {
 }
 
 /// E-Ink display emulator with realistic behavior simulation
+// Independent simulation toggles, not a state machine in disguise.
+#[allow(clippy::struct_excessive_bools)]
 pub struct Emulator {
     pub framebuffer: Framebuffer,
     staged_buffer: Vec<EinkColor>,
@@ -210,6 +256,30 @@ pub struct Emulator {
     // Power tracking
     power_tracker: PowerTracker,
 
+    /// Shared simulated time source. When set, refresh timing advances this
+    /// clock instead of sleeping (see [`multi`]).
+    virtual_clock: Option<VirtualClock>,
+
+    /// Idle ghost decay (driven by the virtual clock).
+    ghost_decay: bool,
+    /// Virtual time up to which ghost decay has been applied.
+    decay_synced_ms: u64,
+
+    /// Long-horizon wear tracking (off by default, see [`wear`]).
+    wear: Option<wear::WearModel>,
+
+    /// Front/ambient light compositing for presented frames (off by
+    /// default, see [`lighting`]).
+    lighting: Option<Lighting>,
+
+    /// Night mode: black and white are swapped as pixels are drawn.
+    inverted: bool,
+
+    /// Time origin for [`now_ms`](Self::now_ms) without a virtual clock.
+    epoch: std::time::Instant,
+    /// Input → render → refresh-complete timing, shared with `EmulatorInput`.
+    latency: std::sync::Arc<std::sync::Mutex<platform::LatencyTracker>>,
+
     // Debug system
     #[cfg(feature = "debug")]
     debug_manager: Option<debug::DebugManager>,
@@ -219,18 +289,34 @@ pub struct Emulator {
     #[cfg(feature = "debug")]
     layout_records: Vec<DrawRecord>,
 
+    /// Per-screen draw-call timing.  Kept on the emulator (not the debug
+    /// manager) so it keeps recording after the manager moves to the window.
+    #[cfg(feature = "debug")]
+    render_profiler: debug::RenderProfiler,
+
+    /// Latest statistics per named cache, mirrored to the window on report.
+    #[cfg(feature = "debug")]
+    caches: Vec<debug::CacheReport>,
+    /// Latest background test run, mirrored to the window on report.
+    #[cfg(feature = "debug")]
+    tests: Option<debug::TestReport>,
+
     // Hardware quirks simulation
     pub quirks_enabled: bool,
     pub active_quirk: Option<String>,
 
     // Presentation configuration (rotation, scaling).
-    // Read in debug mode for cursor→display coordinate mapping.
-    #[cfg_attr(not(feature = "debug"), allow(dead_code))]
+    // Read in debug mode for cursor→display coordinate mapping, by the
+    // fbdev backend for scaling and by `settings()` for the saved scale.
     config: config::EmulatorConfig,
 
     #[cfg(not(feature = "headless"))]
     window: Option<window::Window>,
 
+    /// Linux framebuffer output (`Backend::Framebuffer`).
+    #[cfg(feature = "fbdev")]
+    fbdev: Option<fbdev::FbDevice>,
+
     /// Keyboard/scroll input queue (producer half).
     /// Populated by the winit event loop; consumed via `input_receiver()`.
     #[cfg(feature = "keyboard-input")]
@@ -275,7 +361,27 @@ impl Emulator {
         let window_config = config::EmulatorConfig {
             rotation: config::Rotation::Degrees0,
             scale: config.scale,
+            backend: config::Backend::Window,
+        };
+
+        #[cfg(feature = "fbdev")]
+        let fbdev = match &config.backend {
+            config::Backend::Framebuffer(device) => match fbdev::FbDevice::open(device) {
+                Ok(fb) => Some(fb),
+                Err(e) => {
+                    eprintln!("⚠️  Cannot open framebuffer {}: {e}", device.display());
+                    None
+                }
+            },
+            config::Backend::Window => None,
         };
+        #[cfg(not(feature = "fbdev"))]
+        if let config::Backend::Framebuffer(device) = &config.backend {
+            eprintln!(
+                "⚠️  Framebuffer backend ({}) requires the `fbdev` feature; nothing will be shown.",
+                device.display()
+            );
+        }
 
         #[cfg(feature = "debug")]
         let debug_manager = Some(debug::DebugManager::new());
@@ -294,20 +400,33 @@ impl Emulator {
             init_sequence: InitSequence::new(),
             requires_init: false, // Disabled by default for backward compatibility
             power_tracker: PowerTracker::new(power_profile),
+            virtual_clock: None,
+            ghost_decay: true,
+            decay_synced_ms: 0,
+            wear: None,
+            lighting: None,
+            inverted: false,
+            epoch: std::time::Instant::now(),
+            latency: std::sync::Arc::default(),
             #[cfg(feature = "debug")]
             debug_manager,
             #[cfg(feature = "debug")]
             layout_records: Vec::new(),
+            #[cfg(feature = "debug")]
+            render_profiler: debug::RenderProfiler::new(),
+            #[cfg(feature = "debug")]
+            caches: Vec::new(),
+            #[cfg(feature = "debug")]
+            tests: None,
             quirks_enabled: true, // Enabled by default for realistic simulation
             active_quirk: None,
             config: config.clone(),
 
             #[cfg(not(feature = "headless"))]
-            window: Some(window::Window::new(
-                logical_width,
-                logical_height,
-                &window_config,
-            )),
+            window: (config.backend == config::Backend::Window)
+                .then(|| window::Window::new(logical_width, logical_height, &window_config)),
+            #[cfg(feature = "fbdev")]
+            fbdev,
 
             #[cfg(feature = "keyboard-input")]
             input_queue: None,
@@ -366,16 +485,32 @@ impl Emulator {
             init_sequence: InitSequence::new(),
             requires_init: false, // Disabled by default for backward compatibility
             power_tracker: PowerTracker::new(power_profile),
+            virtual_clock: None,
+            ghost_decay: true,
+            decay_synced_ms: 0,
+            wear: None,
+            lighting: None,
+            inverted: false,
+            epoch: std::time::Instant::now(),
+            latency: std::sync::Arc::default(),
             #[cfg(feature = "debug")]
             debug_manager: Some(crate::debug::DebugManager::new()),
             #[cfg(feature = "debug")]
             layout_records: Vec::new(),
+            #[cfg(feature = "debug")]
+            render_profiler: debug::RenderProfiler::new(),
+            #[cfg(feature = "debug")]
+            caches: Vec::new(),
+            #[cfg(feature = "debug")]
+            tests: None,
             quirks_enabled: true, // Enabled by default for realistic simulation
             active_quirk: None,
             config: config::EmulatorConfig::default(), // Config not used in headless mode
 
             #[cfg(not(feature = "headless"))]
             window: None,
+            #[cfg(feature = "fbdev")]
+            fbdev: None,
 
             #[cfg(feature = "keyboard-input")]
             input_queue: None,
@@ -394,6 +529,137 @@ impl Emulator {
         }
     }
 
+    /// Outer window position in physical pixels.
+    ///
+    /// `None` in headless mode, before the window opens, or where the
+    /// platform does not report window positions.
+    pub fn window_position(&self) -> Option<(i32, i32)> {
+        #[cfg(not(feature = "headless"))]
+        if let Some(window) = &self.window {
+            return window.outer_position();
+        }
+        None
+    }
+
+    /// Move the window (ignored in headless mode).
+    pub fn set_window_position(&mut self, position: (i32, i32)) {
+        #[cfg(not(feature = "headless"))]
+        if let Some(window) = &mut self.window {
+            window.set_outer_position(position);
+        }
+        #[cfg(feature = "headless")]
+        let _ = position;
+    }
+
+    /// Snapshot of the settings [`settings`](crate::settings) persists
+    /// between runs.
+    ///
+    /// Overlay toggles are only known with the `debug` feature and read as
+    /// off otherwise.
+    pub fn settings(&self) -> EmulatorSettings {
+        #[cfg_attr(not(feature = "debug"), allow(unused_mut))]
+        let mut overlays = settings::OverlaySettings::default();
+        #[cfg(feature = "debug")]
+        if let Some(dm) = &self.debug_manager {
+            let state = dm.state();
+            overlays = settings::OverlaySettings {
+                panel: state.panel_visible,
+                borders: state.borders_enabled,
+                inspector: state.inspector_mode,
+            };
+        }
+        EmulatorSettings {
+            scale: self.config.scale,
+            window_position: self.window_position(),
+            display: Some(self.spec.name.to_string()),
+            overlays,
+            temperature_c: self.current_temp,
+        }
+    }
+
+    /// Restore saved settings onto a running emulator.
+    ///
+    /// Temperature, window position and overlays apply directly.  The saved
+    /// display spec is switched to only when one of that name has this
+    /// panel's resolution, since the framebuffer keeps its size (see
+    /// [`EmulatorCommand::NextDisplaySpec`]).  The scale is fixed when the
+    /// window opens; fold it in beforehand with
+    /// [`EmulatorSettings::apply_to_config`].
+    pub fn apply_settings(&mut self, saved: &EmulatorSettings) {
+        self.set_temperature(saved.temperature_c);
+        if let Some(position) = saved.window_position {
+            self.set_window_position(position);
+        }
+        let same_size: Vec<&'static eink_specs::DisplaySpec> = eink_specs::displays::ALL
+            .into_iter()
+            .filter(|s| s.width == self.spec.width && s.height == self.spec.height)
+            .collect();
+        if let Some(spec) = saved
+            .display_spec(&same_size)
+            .filter(|s| s.name != self.spec.name)
+        {
+            self.switch_spec(spec);
+        }
+        #[cfg(feature = "debug")]
+        if let Some(dm) = &mut self.debug_manager {
+            let state = dm.state_mut();
+            state.panel_visible = saved.overlays.panel;
+            state.borders_enabled = saved.overlays.borders;
+            state.inspector_mode = saved.overlays.inspector;
+        }
+    }
+
+    /// Attach a shared [`VirtualClock`].
+    ///
+    /// Refresh and initialization delays then advance the clock instead of
+    /// sleeping, keeping several emulators on one deterministic timeline.
+    /// Power statistics are measured on the same clock.
+    pub fn set_virtual_clock(&mut self, clock: VirtualClock) {
+        self.decay_synced_ms = clock.now_ms();
+        self.power_tracker.set_clock(clock.clone());
+        self.virtual_clock = Some(clock);
+    }
+
+    /// The attached virtual clock, if any.
+    pub fn virtual_clock(&self) -> Option<&VirtualClock> {
+        self.virtual_clock.as_ref()
+    }
+
+    /// Current emulator time in milliseconds
+    ///
+    /// The attached [`VirtualClock`] if any, otherwise wall time since the
+    /// emulator was created.  Input timestamps and latency use this timeline.
+    pub fn now_ms(&self) -> u64 {
+        match &self.virtual_clock {
+            Some(clock) => clock.now_ms(),
+            None => u64::try_from(self.epoch.elapsed().as_millis()).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Report an input event that happened at `at_ms` to the latency tracker
+    ///
+    /// Events delivered through [`input_receiver`](Self::input_receiver) are
+    /// reported automatically; call this for input injected by tests or
+    /// other sources.  The next draw marks the render and the next refresh
+    /// completes the interaction.
+    pub fn record_input(&mut self, at_ms: u64) {
+        if let Ok(mut latency) = self.latency.lock() {
+            latency.on_input(at_ms);
+        }
+    }
+
+    /// Snapshot of interaction latency statistics
+    pub fn latency(&self) -> platform::LatencyTracker {
+        self.latency.lock().map(|l| l.clone()).unwrap_or_default()
+    }
+
+    /// Clear latency statistics (e.g. after a warm-up phase)
+    pub fn reset_latency(&mut self) {
+        if let Ok(mut latency) = self.latency.lock() {
+            latency.reset();
+        }
+    }
+
     /// Get power statistics
     pub fn power_stats(&self) -> &PowerStats {
         self.power_tracker.stats()
@@ -414,6 +680,115 @@ impl Emulator {
         self.power_tracker.is_enabled()
     }
 
+    /// Enable or disable idle ghost decay (enabled by default)
+    ///
+    /// With a [`VirtualClock`] attached, ghosting fades between refreshes
+    /// according to the virtual time that passed (see
+    /// [`PixelState::decay`]).  Without a clock, only [`idle`](Self::idle)
+    /// decays ghosting.
+    pub fn set_ghost_decay(&mut self, enabled: bool) {
+        self.ghost_decay = enabled;
+    }
+
+    /// Check if idle ghost decay is enabled
+    pub fn ghost_decay_enabled(&self) -> bool {
+        self.ghost_decay
+    }
+
+    /// Enable or disable long-horizon wear tracking (disabled by default)
+    ///
+    /// While enabled, every refresh feeds a [`WearModel`] that
+    /// [`wear_report`](Self::wear_report) and
+    /// [`save_wear_overlay`](Self::save_wear_overlay) project over months of
+    /// the same workload.  Disabling discards the recorded history.
+    pub fn enable_wear_tracking(&mut self, enabled: bool) {
+        if !enabled {
+            self.wear = None;
+        } else if self.wear.is_none() {
+            self.wear = Some(wear::WearModel::new(self.spec.width, self.spec.height));
+        }
+    }
+
+    /// The wear model, if wear tracking is enabled.
+    pub fn wear(&self) -> Option<&wear::WearModel> {
+        self.wear.as_ref()
+    }
+
+    /// Composite front-light and ambient-light conditions over presented
+    /// frames, or `None` to show raw panel levels (the default).
+    ///
+    /// Takes effect from the next presented frame; the framebuffer,
+    /// ghosting and power model are unaffected (see [`lighting`]).
+    pub fn set_lighting(&mut self, lighting: Option<Lighting>) {
+        self.lighting = lighting;
+    }
+
+    /// The simulated lighting conditions, if enabled.
+    pub fn lighting(&self) -> Option<&Lighting> {
+        self.lighting.as_ref()
+    }
+
+    /// Swap black and white (night mode).
+    ///
+    /// This works on the framebuffer, for displays without a controller
+    /// that can invert: what is already drawn is inverted in place and
+    /// later draws are inverted as they land.  (In
+    /// [`controller`](crate::controller) mode the driver uses the
+    /// controller's own inverse option instead.)  Like the hardware drivers,
+    /// nothing changes on the panel until the next refresh, which should be
+    /// a full one since every pixel flips.
+    pub fn set_inverted(&mut self, inverted: bool) {
+        if inverted == self.inverted {
+            return;
+        }
+        self.inverted = inverted;
+        for pixel in &mut self.framebuffer.pixels {
+            *pixel = pixel.inverted();
+        }
+    }
+
+    /// Whether night mode is on.
+    pub fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+
+    /// Let the panel sit untouched for `ms` milliseconds
+    ///
+    /// Advances the attached virtual clock (if any) and applies ghost decay
+    /// for the idle period, e.g. `idle(5 * 60_000)` to simulate reading a
+    /// page for five minutes.  Display content is not re-presented; the
+    /// faded ghosts show on the next refresh.
+    pub fn idle(&mut self, ms: u64) {
+        match &self.virtual_clock {
+            Some(clock) => {
+                clock.advance(ms);
+                self.sync_ghost_decay();
+            }
+            None if self.ghost_decay => self.pixel_states.decay_all(ms, self.current_temp),
+            None => {}
+        }
+        let now = self.now_ms();
+        if let Some(wear) = &mut self.wear {
+            wear.settle(now);
+        }
+    }
+
+    /// Apply ghost decay for virtual time elapsed since the last sync.
+    ///
+    /// Time spent inside refreshes is excluded: the sync point is moved to
+    /// the end of every refresh.
+    fn sync_ghost_decay(&mut self) {
+        let Some(clock) = &self.virtual_clock else {
+            return;
+        };
+        let now = clock.now_ms();
+        let elapsed = now.saturating_sub(self.decay_synced_ms);
+        self.decay_synced_ms = now;
+        if self.ghost_decay {
+            self.pixel_states.decay_all(elapsed, self.current_temp);
+        }
+    }
+
     /// Get current ghosting level (average across all pixels)
     pub fn ghosting_level(&self) -> f32 {
         self.pixel_states.average_ghosting()
@@ -509,7 +884,7 @@ impl Emulator {
                     7 => {
                         // Clear to white
                         self.framebuffer.clear();
-                        #[cfg(not(feature = "headless"))]
+                        #[cfg(any(not(feature = "headless"), feature = "fbdev"))]
                         {
                             let rgba = framebuffer_to_rgba(&self.framebuffer.pixels);
                             self.present_frame(&rgba).await;
@@ -554,7 +929,7 @@ impl Emulator {
         }
 
         // Present the checkerboard
-        #[cfg(not(feature = "headless"))]
+        #[cfg(any(not(feature = "headless"), feature = "fbdev"))]
         {
             let rgba = framebuffer_to_rgba(&self.framebuffer.pixels);
             self.present_frame(&rgba).await;
@@ -608,26 +983,44 @@ impl Emulator {
 
     /// Present solid color frame (for flashing)
     // SAFETY: spec.width * spec.height is a display pixel count that fits in u32.
-    #[cfg(not(feature = "headless"))]
+    #[cfg(any(not(feature = "headless"), feature = "fbdev"))]
     #[allow(clippy::arithmetic_side_effects)]
     async fn present_solid_color(&mut self, color: u32) {
-        if let Some(window) = &mut self.window {
-            let frame = vec![color; (self.spec.width * self.spec.height) as usize];
-            window.present(&frame);
-        }
+        let frame = vec![color; (self.spec.width * self.spec.height) as usize];
+        self.present_frame(&frame).await;
     }
 
-    /// Present frame with RGBA data
-    #[cfg(not(feature = "headless"))]
+    /// Present frame with RGBA data, under the simulated lighting if any
+    #[cfg(any(not(feature = "headless"), feature = "fbdev"))]
     async fn present_frame(&mut self, rgba: &[u32]) {
+        let lit;
+        let rgba = match &self.lighting {
+            Some(lighting) => {
+                let mut frame = rgba.to_vec();
+                lighting.apply(&mut frame);
+                lit = frame;
+                &lit
+            }
+            None => rgba,
+        };
+        #[cfg(not(feature = "headless"))]
         if let Some(window) = &mut self.window {
             window.present(rgba);
         }
+        #[cfg(feature = "fbdev")]
+        if let Some(fb) = &mut self.fbdev {
+            let (w, h) = (self.framebuffer.width, self.framebuffer.height);
+            if let Err(e) = fb.present(rgba, w, h, self.config.scale) {
+                eprintln!(
+                    "⚠️  Framebuffer write to {} failed: {e}",
+                    fb.path().display()
+                );
+            }
+        }
     }
 
     /// Render with flash animations based on waveform mode
-    // SAFETY: flash_count * 3 is bounded by small u32 values (max 4 flashes * 3 = 12);
-    // adjusted / 3 is integer division on a refresh duration in ms, both safely bounded.
+    // SAFETY: flash_count * 3 is bounded by small u32 values (max 30 flashes * 3 = 90).
     #[allow(clippy::arithmetic_side_effects)]
     #[cfg_attr(not(feature = "debug"), allow(unused_mut))]
     #[cfg_attr(
@@ -646,26 +1039,26 @@ impl Emulator {
         let flash_count = mode.flash_count();
 
         if flash_count > 0 {
-            let flash_duration = adjusted / (flash_count as u32 * 3);
+            let flash_duration = adjusted.split(u32::from(flash_count) * 3);
 
             for _ in 0..flash_count {
                 // Flash black
-                #[cfg(not(feature = "headless"))]
+                #[cfg(any(not(feature = "headless"), feature = "fbdev"))]
                 self.present_solid_color(0xFF000000).await;
 
                 // Sleep while keeping the window responsive via OS event pumping
-                self.sleep_with_event_pump(flash_duration as u64);
+                self.sleep_with_event_pump(u64::from(flash_duration.get()));
 
                 // Flash white
-                #[cfg(not(feature = "headless"))]
+                #[cfg(any(not(feature = "headless"), feature = "fbdev"))]
                 self.present_solid_color(0xFFFFFFFF).await;
 
-                self.sleep_with_event_pump(flash_duration as u64);
+                self.sleep_with_event_pump(u64::from(flash_duration.get()));
             }
         }
 
-        // Present final image (needed for windowed mode and/or debug overlays)
-        #[cfg(any(not(feature = "headless"), feature = "debug"))]
+        // Present final image (needed for windowed/fbdev mode and/or debug overlays)
+        #[cfg(any(not(feature = "headless"), feature = "debug", feature = "fbdev"))]
         let mut rgba = framebuffer_to_rgba(framebuffer);
 
         // Render debug overlays (feature-gated)
@@ -674,10 +1067,10 @@ impl Emulator {
             self.render_debug_overlays(&mut rgba, debug_manager);
         }
 
-        #[cfg(not(feature = "headless"))]
+        #[cfg(any(not(feature = "headless"), feature = "fbdev"))]
         self.present_frame(&rgba).await;
 
-        self.sleep_with_event_pump((adjusted / 3) as u64);
+        self.sleep_with_event_pump(u64::from(adjusted.split(3).get()));
 
         Ok(())
     }
@@ -689,7 +1082,15 @@ impl Emulator {
     /// "Not Responding" during long refresh animations.
     ///
     /// In headless mode (CI/tests) falls back to `std::thread::sleep`.
+    ///
+    /// With a [`VirtualClock`] attached, the clock is advanced instead and
+    /// the call returns immediately.
     fn sleep_with_event_pump(&mut self, duration_ms: u64) {
+        if let Some(clock) = &self.virtual_clock {
+            clock.advance(duration_ms);
+            return;
+        }
+
         let duration = std::time::Duration::from_millis(duration_ms);
 
         #[cfg(not(feature = "headless"))]
@@ -710,10 +1111,17 @@ impl Emulator {
     /// Calling this method a second time silently replaces the previous queue
     /// (the old `EmulatorInput` will stop receiving events).
     ///
+    /// Events are timestamped on [`now_ms`](Self::now_ms)'s timeline; attach
+    /// a virtual clock first if one is used.
+    ///
     /// [`InputEvent`]: platform::InputEvent
     #[cfg(feature = "keyboard-input")]
     pub fn input_receiver(&mut self) -> input::EmulatorInput {
-        let (queue, rx) = input::InputQueue::new();
+        let clock = match &self.virtual_clock {
+            Some(clock) => input::InputClock::Virtual(clock.clone()),
+            None => input::InputClock::Wall(self.epoch),
+        };
+        let (queue, rx) = input::InputQueue::new(clock, self.latency.clone());
         self.input_queue = Some(queue);
         rx
     }
@@ -740,24 +1148,82 @@ impl Emulator {
 
             window.run();
         }
+        #[cfg(feature = "fbdev")]
+        self.hold_framebuffer();
     }
 
     #[cfg(feature = "headless")]
     pub fn run(self) {
-        // No-op in headless mode
+        // No-op in headless mode (unless presenting on a framebuffer)
+        #[cfg(feature = "fbdev")]
+        self.hold_framebuffer();
+    }
+
+    /// Keep the process alive with the last frame on the framebuffer.
+    ///
+    /// fbdev has no close button; `run()` blocks until the process is
+    /// interrupted, mirroring the window backend.
+    #[cfg(feature = "fbdev")]
+    fn hold_framebuffer(&self) {
+        if let Some(fb) = &self.fbdev {
+            eprintln!(
+                "Presenting on {} — press Ctrl+C to exit",
+                fb.path().display()
+            );
+            loop {
+                std::thread::park();
+            }
+        }
     }
 
     /// Save screenshot to PNG (for testing)
     ///
     /// Per embedded-graphics-simulator pattern: use for
     /// automated testing and visual regression.
-    // SAFETY: pixel color arithmetic operates on small values (0-255 RGB, 0-15 grayscale);
-    // no overflow is possible for these display-scale values.
-    #[allow(clippy::arithmetic_side_effects)]
     pub fn screenshot(
         &self,
         path: impl AsRef<std::path::Path>,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        self.grayscale_image().save(path)?;
+        Ok(())
+    }
+
+    /// Save the framebuffer as an RGB PNG as it looks under the simulated
+    /// lighting (see [`set_lighting`](Self::set_lighting)), or in raw panel
+    /// colours when lighting is off.
+    ///
+    /// Unlike [`screenshot`](Self::screenshot) this keeps colour, so front
+    /// light tint and Spectra/Kaleido pigments survive.
+    pub fn screenshot_lit(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.lit_image().save(path)?;
+        Ok(())
+    }
+
+    /// The framebuffer under the simulated lighting as an RGB image.
+    fn lit_image(&self) -> image::RgbImage {
+        let mut argb = framebuffer_to_rgba(&self.framebuffer.pixels);
+        if let Some(lighting) = &self.lighting {
+            lighting.apply(&mut argb);
+        }
+        let bytes = argb
+            .iter()
+            .flat_map(|px| {
+                let [_, r, g, b] = px.to_be_bytes();
+                [r, g, b]
+            })
+            .collect();
+        image::RgbImage::from_raw(self.framebuffer.width, self.framebuffer.height, bytes)
+            .unwrap_or_default()
+    }
+
+    /// The framebuffer as an 8-bit grayscale image.
+    // SAFETY: pixel color arithmetic operates on small values (0-255 RGB, 0-15 grayscale);
+    // no overflow is possible for these display-scale values.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn grayscale_image(&self) -> image::GrayImage {
         use image::{GrayImage, Luma};
 
         let mut img = GrayImage::new(self.framebuffer.width, self.framebuffer.height);
@@ -777,10 +1243,148 @@ impl Emulator {
             };
             img.put_pixel(x, y, Luma([gray as u8]));
         }
+        img
+    }
+
+    /// Snapshot of the emulator state for a screenshot sidecar.
+    ///
+    /// Components come from the debug registry (`debug` feature, before the
+    /// manager moves to the window in [`run`](Self::run)); callers with their
+    /// own registry can append to [`components`](sidecar::ScreenshotMetadata::components).
+    pub fn screenshot_metadata(&self) -> sidecar::ScreenshotMetadata {
+        let power = self.power_tracker.stats();
+        #[cfg_attr(not(feature = "debug"), allow(unused_mut))]
+        let mut components = Vec::new();
+        #[cfg(feature = "debug")]
+        if let Some(dm) = &self.debug_manager {
+            components.extend(dm.state().registered_components.iter().map(|c| {
+                sidecar::ComponentEntry {
+                    test_id: c.test_id.clone(),
+                    component_type: c.component_type.clone(),
+                    position: c.position,
+                    size: c.size,
+                }
+            }));
+        }
+        sidecar::ScreenshotMetadata {
+            display: self.display_info(),
+            waveform_mode: format!("{:?}", self.waveform_mode),
+            temperature_c: self.current_temp,
+            ghosting: sidecar::GhostingInfo {
+                average: self.pixel_states.average_ghosting(),
+                max: self.pixel_states.max_ghosting(),
+                max_dc_balance: self.pixel_states.max_dc_balance(),
+            },
+            refreshes: sidecar::RefreshInfo {
+                full: self.stats.full_refresh_count.get(),
+                partial: self.stats.partial_refresh_count.get(),
+                fast: self.stats.fast_refresh_count.get(),
+                total_time_ms: self.stats.total_refresh_time_ms,
+                dc_warnings: self.stats.dc_warnings,
+            },
+            power: sidecar::PowerInfo {
+                total_energy_uwh: power.total_energy_uwh,
+                average_current_ua: power.average_current_ua,
+                peak_current_ua: power.peak_current_ua,
+                idle_time_ms: power.idle_time_ms,
+                active_time_ms: power.active_time_ms,
+                sleep_time_ms: power.sleep_time_ms,
+            },
+            components,
+        }
+    }
+
+    /// Display wear and energy report for CI trending (see [`report`]).
+    pub fn report(&self) -> report::EmulatorReport {
+        let power = self.power_tracker.stats();
+        let by_mode = WaveformMode::ALL
+            .iter()
+            .map(|mode| (format!("{mode:?}"), self.stats.refresh_count(*mode).get()))
+            .collect();
+        let time_ms = [
+            ("idle", power.idle_time_ms),
+            ("refreshing", power.refresh_time_ms),
+            ("transferring", power.transfer_time_ms),
+            ("initializing", power.init_time_ms),
+            ("sleeping", power.sleep_time_ms),
+        ]
+        .into_iter()
+        .map(|(state, ms)| (state.to_string(), ms))
+        .collect();
+        report::EmulatorReport {
+            display: self.display_info(),
+            refreshes: report::RefreshReport {
+                by_mode,
+                full: self.stats.full_refresh_count.get(),
+                partial: self.stats.partial_refresh_count.get(),
+                fast: self.stats.fast_refresh_count.get(),
+                total_time_ms: self.stats.total_refresh_time_ms,
+                dc_warnings: self.stats.dc_warnings,
+            },
+            power: report::PowerReport {
+                total_energy_uwh: power.total_energy_uwh,
+                average_current_ua: power.average_current_ua,
+                peak_current_ua: power.peak_current_ua,
+                time_ms,
+                battery_life_hours_1000mah: power.estimated_battery_life_hours(1000),
+            },
+            ghosting: report::GhostingReport {
+                average: self.pixel_states.average_ghosting(),
+                max: self.pixel_states.max_ghosting(),
+                histogram: self
+                    .pixel_states
+                    .ghosting_histogram(report::GHOSTING_BUCKETS),
+            },
+            dc_balance: report::DcBalanceReport {
+                average: self.pixel_states.average_dc_balance(),
+                max: self.pixel_states.max_dc_balance(),
+                critical_pixels: self.pixel_states.dc_critical_count(),
+            },
+        }
+    }
+
+    /// Wear projected over `months` of the recorded workload, if wear
+    /// tracking is enabled (see [`wear`]).
+    pub fn wear_report(&self, months: f32) -> Option<wear::WearReport> {
+        self.wear.as_ref().map(|w| w.project(months).report())
+    }
 
-        img.save(path)?;
+    /// Save the wear heatmap for `months` of use over the current screen.
+    pub fn save_wear_overlay(
+        &self,
+        path: impl AsRef<std::path::Path>,
+        months: f32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let wear = self.wear.as_ref().ok_or("wear tracking is not enabled")?;
+        wear.project(months)
+            .overlay(&self.grayscale_image())
+            .save(path)?;
         Ok(())
     }
+
+    fn display_info(&self) -> sidecar::DisplayInfo {
+        sidecar::DisplayInfo {
+            name: self.spec.name.to_string(),
+            width: self.spec.width,
+            height: self.spec.height,
+            controller: format!("{:?}", self.spec.controller),
+            panel_type: format!("{:?}", self.spec.panel_type),
+        }
+    }
+
+    /// Save a screenshot plus a JSON metadata sidecar (see [`sidecar`]).
+    ///
+    /// Returns the sidecar path (`path` with a `.json` extension).
+    pub fn screenshot_annotated(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        self.screenshot(path)?;
+        let sidecar = sidecar::sidecar_path(path);
+        self.screenshot_metadata().save(&sidecar)?;
+        Ok(sidecar)
+    }
 }
 
 impl DrawTarget for Emulator {
@@ -799,11 +1403,28 @@ impl DrawTarget for Emulator {
             i32,
             u64,
         ) = (i32::MAX, i32::MAX, i32::MIN, i32::MIN, 0);
+        #[cfg(feature = "debug")]
+        let (started, mut pushed) = (std::time::Instant::now(), 0u64);
+
+        let now = self.now_ms();
+        if let Ok(mut latency) = self.latency.lock() {
+            latency.on_render(now);
+        }
 
         for Pixel(point, color) in pixels {
+            #[cfg(feature = "debug")]
+            {
+                pushed = pushed.saturating_add(1);
+            }
             if point.x >= 0 && point.y >= 0 {
+                let color = EinkColor::Gray(color);
+                let color = if self.inverted {
+                    color.inverted()
+                } else {
+                    color
+                };
                 self.framebuffer
-                    .set_pixel(point.x as u32, point.y as u32, EinkColor::Gray(color));
+                    .set_pixel(point.x as u32, point.y as u32, color);
 
                 #[cfg(feature = "debug")]
                 {
@@ -824,6 +1445,10 @@ impl DrawTarget for Emulator {
             }
         }
 
+        #[cfg(feature = "debug")]
+        self.render_profiler
+            .record_draw_call(started.elapsed(), pushed);
+
         #[cfg(feature = "debug")]
         if px_count >= 10 {
             self.layout_records.push(DrawRecord {
@@ -858,6 +1483,10 @@ impl DisplayDriver for Emulator {
         self.power_tracker
             .transition_to(PowerState::TransferringBuffer);
 
+        if self.auto_track_dirty {
+            self.track_changed_tiles();
+        }
+
         // Copy framebuffer to staged buffer (simulates SPI transfer to controller SRAM)
         self.staged_buffer.copy_from_slice(&self.framebuffer.pixels);
 
@@ -947,6 +1576,9 @@ impl Emulator {
             ));
         }
 
+        // Ghosts left by earlier refreshes fade while the panel sits idle.
+        self.sync_ghost_decay();
+
         // Transition to refreshing state with appropriate flash count
         let flash_count = mode.flash_count();
         self.power_tracker
@@ -954,6 +1586,10 @@ impl Emulator {
 
         // 1. Quantize staged buffer based on waveform mode
         let quantized = self.quantize_buffer(&self.staged_buffer, mode);
+        let now = self.now_ms();
+        if let Some(wear) = &mut self.wear {
+            wear.record_refresh(now, &quantized, mode);
+        }
 
         // 2. Update pixel states with physics (including temperature effects)
         match mode {
@@ -998,9 +1634,17 @@ impl Emulator {
         // 5. Render with flash animation
         let base_duration = mode.base_duration_ms();
         self.render_with_flashes(mode, &effective_fb_eink).await?;
+        if let Some(clock) = &self.virtual_clock {
+            // The refresh itself is not idle time.
+            self.decay_synced_ms = clock.now_ms();
+        }
 
         // 6. Update statistics
         self.stats.record_refresh(mode, base_duration);
+        let now = self.now_ms();
+        if let Ok(mut latency) = self.latency.lock() {
+            latency.on_refresh_complete(now);
+        }
 
         // 7. Record power sample in the debug power graph and update refresh counters
         #[cfg(feature = "debug")]
@@ -1054,6 +1698,21 @@ impl Emulator {
             }
         }
 
+        // 9. Close the render-profiler screen: everything drawn since the
+        //    previous refresh belongs to the screen that was just shown.
+        #[cfg(feature = "debug")]
+        {
+            self.render_profiler.finish_screen();
+            #[cfg(not(feature = "headless"))]
+            {
+                let latency = self.latency();
+                if let Some(window) = &mut self.window {
+                    window.set_render_profiler(&self.render_profiler);
+                    window.set_latency(latency);
+                }
+            }
+        }
+
         // Return to idle after refresh
         self.power_tracker.transition_to(PowerState::Idle);
 
@@ -1095,21 +1754,65 @@ impl Emulator {
     }
 
     /// Enable or disable auto-dirty tracking
+    ///
+    /// When enabled, `update_buffer()` compares the framebuffer with the
+    /// staged buffer in 8×8 tiles (see [`platform::frame_diff`]) and adds
+    /// the changed rectangles to the dirty regions, so drawing code does
+    /// not have to call `mark_dirty()`.  Regions accumulate until
+    /// `clear_dirty()`.
     pub fn enable_auto_dirty_tracking(&mut self, enable: bool) {
         self.auto_track_dirty = enable;
     }
 
+    /// Add the tiles where the framebuffer differs from the staged buffer
+    /// to the dirty regions.
+    // SAFETY: tile coordinates are bounded by the framebuffer size, so the
+    // row offsets stay within the pixel buffers.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn track_changed_tiles(&mut self) {
+        use platform::frame_diff::{diff_tiles, DirtyRects, TILE};
+
+        let (width, height) = (self.framebuffer.width, self.framebuffer.height);
+        let (drawn, shown) = (&self.framebuffer.pixels, &self.staged_buffer);
+        let dirty: DirtyRects<AUTO_DIRTY_RECTS> = diff_tiles(Size::new(width, height), |tx, ty| {
+            let x0 = (tx * TILE) as usize;
+            let x1 = ((tx + 1) * TILE).min(width) as usize;
+            (ty * TILE..((ty + 1) * TILE).min(height)).any(|y| {
+                let row = y as usize * width as usize;
+                let span = row + x0..row + x1;
+                drawn.get(span.clone()) != shown.get(span)
+            })
+        });
+        self.dirty_regions.extend_from_slice(dirty.rects());
+    }
+
+    /// Partial-window alignment required by this display's controller
+    pub fn partial_alignment(&self) -> eink_specs::PartialAlignment {
+        self.spec.controller.partial_alignment()
+    }
+
+    /// Build a [`PartialWindow`] aligned for this display's controller
+    ///
+    /// `was_aligned` is set when the requested region would be mis-addressed
+    /// on the real controller.
+    pub fn partial_window(&self, rect: embedded_graphics::primitives::Rectangle) -> PartialWindow {
+        PartialWindow::for_controller(rect, self.spec.controller)
+    }
+
     /// Refresh a specific partial window
+    ///
+    /// The window is aligned using the controller's constraints from
+    /// `eink-specs`, the same data the hardware drivers use.
     pub async fn refresh_partial_window(
         &mut self,
         window: embedded_graphics::primitives::Rectangle,
     ) -> Result<(), std::io::Error> {
-        let partial_window = PartialWindow::new(window);
+        let partial_window = self.partial_window(window);
         self.refresh_partial().await?;
         if partial_window.was_aligned {
             eprintln!(
-                "Partial window aligned: {:?} -> {:?}",
-                window, partial_window.aligned_rect
+                "Partial window aligned for {:?}: {:?} -> {:?}",
+                self.spec.controller, window, partial_window.aligned_rect
             );
         }
         Ok(())
@@ -1231,28 +1934,293 @@ impl Emulator {
         self.quirks_enabled
     }
 
+    /// Like [`check_quirks`](Self::check_quirks), but a quirk that fails the
+    /// operation has its [`Workaround`](eink_specs::Workaround) applied, as a
+    /// driver would.
+    ///
+    /// Returns the workaround that recovered the operation (`None` if no quirk
+    /// failed it), or the quirk message if its workaround cannot recover.
+    pub async fn check_quirks_with_workarounds(
+        &mut self,
+        operation: &str,
+    ) -> Result<Option<eink_specs::Workaround>, String> {
+        let Err(message) = self.check_quirks(operation) else {
+            return Ok(None);
+        };
+        let workaround = self
+            .spec
+            .quirks
+            .unwrap_or(&[])
+            .iter()
+            .find(|q| Some(q.description()) == self.active_quirk.as_deref())
+            .map(eink_specs::Quirk::workaround);
+        match workaround {
+            Some(workaround) if workaround.recovery().is_some() => {
+                self.apply_workaround(workaround)
+                    .await
+                    .map_err(|e| format!("{message} (workaround failed: {e})"))?;
+                Ok(Some(workaround))
+            }
+            _ => Err(message),
+        }
+    }
+
+    /// Execute `workaround` against the emulated panel.
+    ///
+    /// Recovery sequences take their reset time (virtual when a
+    /// [`VirtualClock`] is attached), clear the active quirk and re-run
+    /// [`initialize`](Self::initialize) if the panel had been initialized.
+    /// [`FullRefreshEvery`](eink_specs::Workaround::FullRefreshEvery) refreshes
+    /// fully once any pixel has seen that many partial refreshes.  Workarounds
+    /// that only change driver timing or configuration are no-ops here.
+    pub async fn apply_workaround(
+        &mut self,
+        workaround: eink_specs::Workaround,
+    ) -> Result<(), std::io::Error> {
+        use eink_specs::Workaround;
+
+        if let Some(recovery) = workaround.recovery() {
+            self.recover(recovery).await?;
+        }
+        match workaround {
+            Workaround::RecoverThenFullRefresh { .. } => self.refresh_full().await,
+            Workaround::FullRefreshEvery { max_partials }
+                if self
+                    .pixel_states
+                    .needs_full_refresh(u16::from(max_partials)) =>
+            {
+                self.refresh_full().await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Simulate a controller reset.
+    async fn recover(&mut self, recovery: eink_specs::Recovery) -> Result<(), std::io::Error> {
+        let reset_ms = match recovery {
+            eink_specs::Recovery::HardwareReset { low_ms, settle_ms } => {
+                u64::from(low_ms).saturating_add(u64::from(settle_ms))
+            }
+            eink_specs::Recovery::SoftReset => 10,
+        };
+        self.sleep_with_event_pump(reset_ms);
+        self.active_quirk = None;
+
+        #[cfg(not(feature = "headless"))]
+        if let Some(window) = &mut self.window {
+            window.set_quirk_warning(None);
+        }
+
+        // A reset clears every register, so init has to run again.
+        if self.init_state().is_ready() {
+            self.init_sequence.reset();
+            self.initialize().await?;
+        }
+        Ok(())
+    }
+
     /// Poll all pending OS events without blocking.
     ///
     /// Forwards `KeyboardInput` and `MouseWheel` events to the `InputQueue`
     /// (when `keyboard-input` feature is active) so that
     /// `EmulatorInput::poll_event()` sees them.
     ///
+    /// Commands chosen from the window's command palette (F1) that change
+    /// emulator state are applied here (see [`commands`]).
+    ///
     /// Returns `true` if the window is still open, `false` if the user clicked
     /// the close button or there is no window (headless mode).
     pub fn pump_window_events(&mut self) -> bool {
         #[cfg(not(feature = "headless"))]
         if let Some(ref mut w) = self.window {
-            return w.pump_window_events();
+            let open = w.pump_window_events();
+            for cmd in w.take_commands() {
+                if let Err(e) = self.apply_command(cmd) {
+                    eprintln!("⚠️  {}: {e}", cmd.label());
+                }
+            }
+            return open;
         }
         false
     }
 
-    /// Get debug manager reference
+    /// Run a palette command against the emulator.
     ///
-    /// Returns `None` in headless mode or when debug feature is disabled.
-    ///
-    /// # Example
-    /// ```no_run
+    /// Screenshots are written as `eink-screenshot-<unix secs>.png` plus a
+    /// metadata sidecar.  Switching display spec only cycles between specs
+    /// with this panel's resolution, since the framebuffer and window keep
+    /// their size; it fails when there is no such spec.
+    pub fn apply_command(
+        &mut self,
+        cmd: commands::EmulatorCommand,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use commands::{EmulatorCommand, TEMPERATURE_STEP};
+
+        match cmd {
+            EmulatorCommand::SaveScreenshot => {
+                let secs = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                let path = format!("eink-screenshot-{secs}.png");
+                self.screenshot_annotated(&path)?;
+                eprintln!("Saved screenshot: {path}");
+            }
+            EmulatorCommand::TogglePanel
+            | EmulatorCommand::ToggleBorders
+            | EmulatorCommand::ToggleInspector => {
+                #[cfg(feature = "debug")]
+                if let Some(ref mut dm) = self.debug_manager {
+                    let state = dm.state_mut();
+                    match cmd {
+                        EmulatorCommand::TogglePanel => state.toggle_panel(),
+                        EmulatorCommand::ToggleBorders => state.toggle_borders(),
+                        _ => state.toggle_inspector(),
+                    }
+                    return Ok(());
+                }
+                return Err("no debug manager (needs the `debug` feature)".into());
+            }
+            EmulatorCommand::TemperatureUp => {
+                self.set_temperature(self.current_temp.saturating_add(TEMPERATURE_STEP));
+            }
+            EmulatorCommand::TemperatureDown => {
+                self.set_temperature(self.current_temp.saturating_sub(TEMPERATURE_STEP));
+            }
+            EmulatorCommand::CycleWaveformMode => {
+                let modes = WaveformMode::ALL;
+                let next = modes
+                    .iter()
+                    .position(|&m| m == self.waveform_mode)
+                    .and_then(|i| modes.get(i.saturating_add(1)))
+                    .or(modes.first());
+                self.waveform_mode = next.copied().unwrap_or_default();
+                eprintln!("Waveform mode: {:?}", self.waveform_mode);
+            }
+            EmulatorCommand::ResetGhosting => {
+                self.pixel_states =
+                    PixelStateBuffer::new(self.framebuffer.width, self.framebuffer.height);
+            }
+            EmulatorCommand::NextDisplaySpec => {
+                let same_size: Vec<&'static eink_specs::DisplaySpec> = eink_specs::displays::ALL
+                    .into_iter()
+                    .filter(|s| s.width == self.spec.width && s.height == self.spec.height)
+                    .collect();
+                let next = same_size
+                    .iter()
+                    .position(|s| s.name == self.spec.name)
+                    .and_then(|i| same_size.get(i.saturating_add(1)).or(same_size.first()))
+                    .filter(|s| s.name != self.spec.name)
+                    .ok_or_else(|| {
+                        format!(
+                            "no other display spec is {}×{}",
+                            self.spec.width, self.spec.height
+                        )
+                    })?;
+                self.switch_spec(next);
+                eprintln!("Display spec: {}", next.name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Swap in a display spec of the same resolution, resetting the power
+    /// model and quirk state that depend on it.
+    fn switch_spec(&mut self, spec: &'static eink_specs::DisplaySpec) {
+        self.spec = spec;
+        self.power_tracker = PowerTracker::new(Self::select_power_profile(spec));
+        if let Some(clock) = &self.virtual_clock {
+            self.power_tracker.set_clock(clock.clone());
+        }
+        self.active_quirk = None;
+        #[cfg(not(feature = "headless"))]
+        if let Some(window) = &mut self.window {
+            window.set_quirk_warning(None);
+            window.set_power_stats(self.power_tracker.stats());
+        }
+    }
+
+    /// Load a PNG into the framebuffer, fitted to the display and quantized
+    /// to Gray4 (see [`mockup`]).
+    ///
+    /// Like drawing, this only changes the framebuffer; call a refresh to
+    /// show it, or use [`show_image`](Self::show_image).
+    pub fn load_image(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), image::ImageError> {
+        let gray = mockup::load(path, self.framebuffer.width, self.framebuffer.height, false)?;
+        let pixels: Vec<EinkColor> = gray
+            .into_iter()
+            .map(|g| self.framebuffer.gray4_to_mode(g))
+            .collect();
+        self.framebuffer.pixels = pixels;
+        if self.auto_track_dirty {
+            self.mark_dirty(embedded_graphics::primitives::Rectangle::new(
+                Point::zero(),
+                self.size(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Load a PNG (see [`load_image`](Self::load_image)) and show it with a
+    /// full GC16 refresh, so previous content ghosts realistically.
+    pub async fn show_image(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.load_image(path)?;
+        self.refresh_full().await?;
+        Ok(())
+    }
+
+    /// Take the most recent PNG dropped onto the window since the last call.
+    ///
+    /// Drops are collected while events are pumped
+    /// ([`pump_window_events`](Self::pump_window_events) or a refresh).
+    /// Always `None` in headless mode.
+    pub fn take_dropped_image(&mut self) -> Option<std::path::PathBuf> {
+        #[cfg(not(feature = "headless"))]
+        if let Some(ref mut w) = self.window {
+            return w.take_dropped_image();
+        }
+        None
+    }
+
+    /// Show the most recently dropped PNG, if any.
+    ///
+    /// Call from the application loop after
+    /// [`pump_window_events`](Self::pump_window_events).  Returns `Ok(true)`
+    /// when an image was loaded and refreshed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use eink_emulator::Emulator;
+    /// # async fn demo() {
+    /// let mut emulator = Emulator::new(250, 122);
+    /// while emulator.pump_window_events() {
+    ///     if let Err(e) = emulator.show_dropped_image().await {
+    ///         eprintln!("Could not load mockup: {e}");
+    ///     }
+    ///     tokio::time::sleep(std::time::Duration::from_millis(16)).await;
+    /// }
+    /// # }
+    /// ```
+    pub async fn show_dropped_image(&mut self) -> Result<bool, Box<dyn std::error::Error>> {
+        let Some(path) = self.take_dropped_image() else {
+            return Ok(false);
+        };
+        self.show_image(&path).await?;
+        Ok(true)
+    }
+
+    /// Get debug manager reference
+    ///
+    /// Returns `None` in headless mode or when debug feature is disabled.
+    ///
+    /// # Example
+    /// ```no_run
     /// # use eink_emulator::Emulator;
     /// let mut emulator = Emulator::new(250, 122);
     /// #[cfg(feature = "debug")]
@@ -1283,6 +2251,86 @@ impl Emulator {
         self.debug_manager.as_mut()
     }
 
+    /// Show the records written to `sink` in the debug panel's LOG tab.
+    ///
+    /// Keep a clone of `sink` in the log producer — the firmware's `tracing`
+    /// layer, for example.  Call before [`run`](Self::run), which hands the
+    /// debug manager to the window.
+    #[cfg(feature = "debug")]
+    pub fn attach_log_sink(&mut self, sink: debug::LogSink) {
+        if let Some(dm) = &mut self.debug_manager {
+            dm.set_log_sink(sink);
+        }
+    }
+
+    /// Name the screen currently being drawn in the render profiler.
+    ///
+    /// The screen is closed by the next refresh; anything drawn before this
+    /// call (since the previous refresh) is attributed to `name` as well.
+    #[cfg(feature = "debug")]
+    pub fn begin_screen(&mut self, name: impl Into<String>) {
+        self.render_profiler.begin_screen(name);
+    }
+
+    /// Run a layout pass and record its duration against the current screen.
+    ///
+    /// ```ignore
+    /// let layout = emulator.time_layout(|| root.layout(Constraints::tight(size)));
+    /// ```
+    #[cfg(feature = "debug")]
+    pub fn time_layout<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        self.render_profiler.time_layout(f)
+    }
+
+    /// Per-screen render timing collected so far.
+    #[cfg(feature = "debug")]
+    pub fn render_profiler(&self) -> &debug::RenderProfiler {
+        &self.render_profiler
+    }
+
+    /// Mutable access to the render profiler (e.g. to swap the cost model).
+    #[cfg(feature = "debug")]
+    pub fn render_profiler_mut(&mut self) -> &mut debug::RenderProfiler {
+        &mut self.render_profiler
+    }
+
+    /// Publish statistics for a runtime cache to the debug panel.
+    ///
+    /// A report replaces any earlier one with the same `name`.
+    #[cfg(feature = "debug")]
+    pub fn report_cache(&mut self, report: debug::CacheReport) {
+        #[cfg(not(feature = "headless"))]
+        if let Some(window) = &mut self.window {
+            window.report_cache(report.clone());
+        }
+        debug::upsert_cache_report(&mut self.caches, report);
+    }
+
+    /// Latest cache statistics, one entry per reported name.
+    #[cfg(feature = "debug")]
+    pub fn cache_reports(&self) -> &[debug::CacheReport] {
+        &self.caches
+    }
+
+    /// Show the latest background test run in the debug panel's Display tab.
+    ///
+    /// `None` hides the section.  Fed by the host from the status file that
+    /// `cargo xtask dev --watch-tests` writes (see [`debug::TestReport`]).
+    #[cfg(feature = "debug")]
+    pub fn report_tests(&mut self, report: Option<debug::TestReport>) {
+        #[cfg(not(feature = "headless"))]
+        if let Some(window) = &mut self.window {
+            window.report_tests(report.clone());
+        }
+        self.tests = report;
+    }
+
+    /// Latest background test run, if one was reported.
+    #[cfg(feature = "debug")]
+    pub fn test_report(&self) -> Option<&debug::TestReport> {
+        self.tests.as_ref()
+    }
+
     /// Render debug overlays onto the RGBA buffer
     ///
     /// This renders borders, inspector tooltip, power graph overlay, and the
@@ -1517,8 +2565,8 @@ mod tests {
         // Temperature affects timing (tested through spec)
         // -5Â°C: factor = 1.5 + (0 - (-5)) * 0.05 = 1.5 + 0.25 = 1.75
         assert_eq!(
-            emulator.spec().adjusted_refresh_ms(2000, -5),
-            3500 // 1.75x slower at -5Â°C
+            emulator.spec().adjusted_refresh_ms(DisplayMs::new(2000), -5),
+            DisplayMs::new(3500) // 1.75x slower at -5Â°C
         );
     }
 
@@ -1756,8 +2804,8 @@ mod tests {
 
         // Check statistics
         let stats = emulator.stats();
-        assert!(stats.full_refresh_count >= 2); // Initial + cleanup
-        assert!(stats.fast_refresh_count >= 4); // Page turns
+        assert!(stats.full_refresh_count.get() >= 2); // Initial + cleanup
+        assert!(stats.fast_refresh_count.get() >= 4); // Page turns
         assert!(stats.total_refresh_time_ms > 0);
     }
 
@@ -1795,9 +2843,9 @@ mod tests {
         let emulator = Emulator::headless(100, 100);
         let stats = emulator.stats();
 
-        assert_eq!(stats.full_refresh_count, 0);
-        assert_eq!(stats.partial_refresh_count, 0);
-        assert_eq!(stats.fast_refresh_count, 0);
+        assert_eq!(stats.full_refresh_count.get(), 0);
+        assert_eq!(stats.partial_refresh_count.get(), 0);
+        assert_eq!(stats.fast_refresh_count.get(), 0);
         assert_eq!(stats.total_refresh_time_ms, 0);
         assert_eq!(stats.dc_warnings, 0);
     }
@@ -2130,7 +3178,7 @@ mod tests {
         emulator.display_with_mode(WaveformMode::DU4).await.unwrap();
 
         // Verify stats were updated
-        assert_eq!(emulator.stats().partial_refresh_count, 1);
+        assert_eq!(emulator.stats().partial_refresh_count.get(), 1);
 
         // Verify pixel states were updated
         assert!(
@@ -2145,14 +3193,14 @@ mod tests {
         let mut emulator = Emulator::headless(100, 100);
 
         emulator.refresh_full().await.unwrap();
-        assert_eq!(emulator.stats().full_refresh_count, 1);
+        assert_eq!(emulator.stats().full_refresh_count.get(), 1);
         assert_eq!(emulator.ghosting_level(), 0.0);
 
         emulator.refresh_partial().await.unwrap();
-        assert_eq!(emulator.stats().partial_refresh_count, 1);
+        assert_eq!(emulator.stats().partial_refresh_count.get(), 1);
 
         emulator.refresh_fast().await.unwrap();
-        assert_eq!(emulator.stats().fast_refresh_count, 1);
+        assert_eq!(emulator.stats().fast_refresh_count.get(), 1);
     }
 
     #[tokio::test]
@@ -2167,7 +3215,7 @@ mod tests {
         emulator.display().await.unwrap();
 
         // Should have triggered full refresh
-        assert_eq!(emulator.stats().full_refresh_count, 1);
+        assert_eq!(emulator.stats().full_refresh_count.get(), 1);
         assert_eq!(emulator.ghosting_level(), 0.0);
     }
 
@@ -2245,10 +3293,388 @@ mod tests {
         assert_eq!(RefreshType::Full, RefreshType::Full);
     }
 
+    #[cfg(feature = "debug")]
+    #[tokio::test]
+    async fn test_render_profiler_groups_draws_per_screen() {
+        use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+        let mut emulator = Emulator::headless(128, 64);
+        emulator.set_virtual_clock(VirtualClock::new());
+
+        emulator.begin_screen("Menu");
+        let laid_out = emulator.time_layout(|| 7);
+        assert_eq!(laid_out, 7);
+        Rectangle::new(Point::new(0, 0), Size::new(10, 4))
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut emulator)
+            .unwrap();
+        emulator.refresh_full().await.unwrap();
+
+        let screen = emulator.render_profiler().last_screen().unwrap();
+        assert_eq!(screen.screen, "Menu");
+        assert_eq!(screen.draw_calls, 1);
+        assert_eq!(screen.pixels, 40);
+        assert!(!screen.over_budget);
+
+        // A refresh with nothing drawn does not produce a screen.
+        emulator.refresh_full().await.unwrap();
+        assert_eq!(emulator.render_profiler().screens().len(), 1);
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn test_report_cache_replaces_by_name() {
+        use crate::debug::CacheReport;
+
+        let mut emulator = Emulator::headless(16, 16);
+        emulator.report_cache(CacheReport {
+            name: "album-art".into(),
+            hits: 1,
+            ..CacheReport::default()
+        });
+        emulator.report_cache(CacheReport {
+            name: "album-art".into(),
+            hits: 5,
+            misses: 5,
+            ..CacheReport::default()
+        });
+        let reports = emulator.cache_reports();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].hit_rate_percent(), 50);
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn test_report_tests_parses_status_file() {
+        use crate::debug::TestReport;
+
+        let mut emulator = Emulator::headless(16, 16);
+        assert!(emulator.test_report().is_none());
+
+        let report = TestReport::parse(
+            "state done\nscope firmware-ui eink-testing\npassed 12\n\
+             failed now_playing_golden\nfailed menu_golden\nextra ignored\n",
+        );
+        emulator.report_tests(Some(report));
+        let report = emulator.test_report().unwrap();
+        assert!(!report.running);
+        assert_eq!(report.scope, "firmware-ui eink-testing");
+        assert_eq!(report.passed, 12);
+        assert_eq!(report.failed, ["now_playing_golden", "menu_golden"]);
+        assert!(!report.is_green());
+
+        emulator.report_tests(Some(TestReport::parse("state running\npassed 12\n")));
+        let report = emulator.test_report().unwrap();
+        assert!(report.running);
+        assert!(report.is_green());
+    }
+
     #[test]
     fn pump_window_events_headless_returns_false() {
         // In headless mode (no window) pump_window_events() must return false.
         let mut emulator = Emulator::headless(128, 64);
         assert!(!emulator.pump_window_events());
     }
+
+    #[tokio::test]
+    async fn test_apply_palette_commands() {
+        use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+        let mut emulator = Emulator::headless_with_spec(&eink_specs::displays::GDEM0397T81P);
+        emulator
+            .apply_command(EmulatorCommand::TemperatureDown)
+            .unwrap();
+        assert_eq!(emulator.current_temp, 20);
+
+        emulator.set_waveform_mode(WaveformMode::ALL[WaveformMode::ALL.len() - 1]);
+        emulator
+            .apply_command(EmulatorCommand::CycleWaveformMode)
+            .unwrap();
+        assert_eq!(emulator.waveform_mode(), WaveformMode::ALL[0]);
+
+        Rectangle::new(Point::new(0, 0), Size::new(100, 100))
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut emulator)
+            .unwrap();
+        emulator.refresh_partial().await.unwrap();
+        assert!(emulator.ghosting_level() > 0.0);
+        emulator
+            .apply_command(EmulatorCommand::ResetGhosting)
+            .unwrap();
+        assert_eq!(emulator.ghosting_level(), 0.0);
+
+        // 800×480 has three specs; cycling comes back to the start.
+        let mut names = Vec::new();
+        for _ in 0..3 {
+            emulator
+                .apply_command(EmulatorCommand::NextDisplaySpec)
+                .unwrap();
+            names.push(emulator.spec.name);
+        }
+        assert_eq!(names[2], eink_specs::displays::GDEM0397T81P.name);
+        assert_ne!(names[0], names[1]);
+
+        // 250×122 is unique.
+        let mut small = Emulator::headless(250, 122);
+        assert!(small
+            .apply_command(EmulatorCommand::NextDisplaySpec)
+            .is_err());
+    }
+
+    #[test]
+    fn test_settings_restore_temperature_and_spec() {
+        let waveshare = eink_specs::displays::WAVESHARE_7_5_V2.name;
+        let mut emulator = Emulator::headless_with_spec(&eink_specs::displays::GDEM0397T81P);
+        let saved = EmulatorSettings {
+            display: Some(waveshare.to_string()),
+            temperature_c: 5,
+            ..EmulatorSettings::default()
+        };
+        emulator.apply_settings(&saved);
+        assert_eq!(emulator.temperature(), Some(5));
+        assert_eq!(emulator.spec.name, waveshare);
+
+        let captured = emulator.settings();
+        assert_eq!(captured.display, saved.display);
+        assert_eq!(captured.temperature_c, 5);
+        assert_eq!(captured.window_position, None);
+
+        // A spec of another resolution is not switched to.
+        emulator.apply_settings(&EmulatorSettings {
+            display: Some(eink_specs::displays::WAVESHARE_2_13_V4.name.to_string()),
+            ..saved
+        });
+        assert_eq!(emulator.spec.name, waveshare);
+    }
+
+    #[tokio::test]
+    async fn test_ghosting_decays_with_virtual_time() {
+        use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+        async fn ghosted(decay: bool) -> Emulator {
+            let mut emulator = Emulator::headless(250, 122);
+            emulator.set_virtual_clock(VirtualClock::new());
+            emulator.set_ghost_decay(decay);
+            for color in [Gray4::BLACK, Gray4::WHITE] {
+                Rectangle::new(Point::new(10, 10), Size::new(50, 50))
+                    .into_styled(PrimitiveStyle::with_fill(color))
+                    .draw(&mut emulator)
+                    .unwrap();
+                emulator.refresh_partial().await.unwrap();
+            }
+            emulator
+        }
+
+        // Back-to-back refreshes: refresh time is not idle time.
+        let mut emulator = ghosted(true).await;
+        let fresh = emulator.ghosting_level();
+        assert!(fresh > 0.0);
+        assert_eq!(ghosted(false).await.ghosting_level(), fresh);
+
+        // Five minutes of reading at 25°C: slight fade.
+        emulator.idle(5 * 60_000);
+        let read = emulator.ghosting_level();
+        assert!(read < fresh && read > fresh * 0.5, "{fresh} → {read}");
+
+        // Virtual time advanced elsewhere (shared clock) is picked up on refresh.
+        emulator.virtual_clock().unwrap().advance(60 * 60_000);
+        emulator.refresh_partial().await.unwrap();
+        assert!(emulator.ghosting_level() < read * 0.1);
+
+        let mut frozen = ghosted(false).await;
+        frozen.idle(60 * 60_000);
+        assert_eq!(frozen.ghosting_level(), fresh);
+    }
+
+    #[tokio::test]
+    async fn test_wear_tracking_projects_static_status_bar() {
+        use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+        let mut emulator = Emulator::headless(250, 122);
+        emulator.set_virtual_clock(VirtualClock::new());
+        assert!(emulator.wear_report(6.0).is_none());
+        assert!(emulator.save_wear_overlay("unused.png", 6.0).is_err());
+
+        emulator.enable_wear_tracking(true);
+        for hour in 0..12 {
+            // Static status bar; the page below changes every hour.
+            Rectangle::new(Point::new(0, 0), Size::new(250, 12))
+                .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+                .draw(&mut emulator)
+                .unwrap();
+            let page = if hour % 2 == 0 {
+                Gray4::BLACK
+            } else {
+                Gray4::WHITE
+            };
+            Rectangle::new(Point::new(0, 40), Size::new(250, 60))
+                .into_styled(PrimitiveStyle::with_fill(page))
+                .draw(&mut emulator)
+                .unwrap();
+            emulator.refresh_partial().await.unwrap();
+            emulator.idle(60 * 60_000);
+        }
+
+        let wear = emulator.wear().unwrap();
+        assert_eq!(wear.refreshes(), 12);
+        assert!(wear.observed_ms() >= 12 * 60 * 60_000);
+
+        let report = emulator.wear_report(6.0).unwrap();
+        let hotspot = report.hotspot.unwrap();
+        assert!(hotspot.y < 12, "{hotspot:?}");
+        assert!(report.months_to_visible.unwrap() < 6.0);
+
+        let path = std::env::temp_dir().join("eink-wear-overlay.png");
+        emulator.save_wear_overlay(&path, 6.0).unwrap();
+        assert!(path.exists());
+
+        emulator.enable_wear_tracking(false);
+        assert!(emulator.wear().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_latency_input_to_refresh_complete() {
+        use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+        let mut emulator = Emulator::headless(250, 122);
+        emulator.set_virtual_clock(VirtualClock::new());
+        let clock = emulator.virtual_clock().unwrap().clone();
+
+        // A refresh with no pending input is not an interaction.
+        emulator.refresh_partial().await.unwrap();
+        assert_eq!(emulator.latency().count(), 0);
+
+        clock.advance(1_000);
+        let input_ms = emulator.now_ms();
+        emulator.record_input(input_ms);
+        clock.advance(15); // event handling before the UI draws
+        Rectangle::new(Point::new(0, 0), Size::new(20, 20))
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut emulator)
+            .unwrap();
+        emulator.refresh_partial().await.unwrap();
+
+        let latency = emulator.latency();
+        let last = latency.last().unwrap();
+        assert_eq!(latency.count(), 1);
+        assert_eq!(last.input_ms, input_ms);
+        assert_eq!(last.input_to_render_ms(), 15);
+        assert_eq!(last.complete_ms, emulator.now_ms());
+        assert!(last.render_to_complete_ms() > 0);
+
+        emulator.reset_latency();
+        assert!(emulator.latency().last().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_report_counts_modes_and_histogram() {
+        use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+        let mut emulator = Emulator::headless(250, 122);
+        emulator.refresh_full().await.unwrap();
+        Rectangle::new(Point::new(0, 0), Size::new(50, 50))
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut emulator)
+            .unwrap();
+        emulator.refresh_partial().await.unwrap();
+        emulator.refresh_fast().await.unwrap();
+
+        let summary = emulator.report();
+        assert_eq!(summary.refreshes.by_mode.len(), WaveformMode::ALL.len());
+        assert_eq!(summary.refreshes.by_mode["GC16"], 1);
+        assert_eq!(summary.refreshes.by_mode["DU4"], 1);
+        assert_eq!(summary.refreshes.by_mode["DU"], 1);
+        assert_eq!(summary.refreshes.by_mode["A2"], 0);
+        assert_eq!(summary.power.time_ms.len(), 5);
+
+        let pixels: u64 = summary.ghosting.histogram.iter().sum();
+        assert_eq!(pixels, 250 * 122);
+        assert_eq!(summary.ghosting.histogram.len(), report::GHOSTING_BUCKETS);
+        assert!(summary.ghosting.histogram[0] < pixels, "rectangle ghosts");
+
+        let json = summary.to_json().unwrap();
+        assert_eq!(report::EmulatorReport::from_json(&json).unwrap(), summary);
+    }
+
+    #[tokio::test]
+    async fn test_screenshot_annotated_writes_sidecar() {
+        use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+        let mut emulator = Emulator::headless(250, 122);
+        emulator.set_temperature(12);
+        emulator.refresh_full().await.unwrap();
+        Rectangle::new(Point::new(10, 10), Size::new(50, 50))
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut emulator)
+            .unwrap();
+        emulator.refresh_partial().await.unwrap();
+
+        let png =
+            std::env::temp_dir().join(format!("eink_emulator_sidecar_{}.png", std::process::id()));
+        let json = emulator.screenshot_annotated(&png).unwrap();
+        assert_eq!(json, png.with_extension("json"));
+
+        let meta = sidecar::ScreenshotMetadata::load(&json).unwrap();
+        assert_eq!(meta, emulator.screenshot_metadata());
+        assert_eq!(meta.display.width, emulator.spec().width);
+        assert_eq!(meta.temperature_c, 12);
+        assert_eq!((meta.refreshes.full, meta.refreshes.partial), (1, 1));
+        assert!(meta.ghosting.average > 0.0);
+
+        let _ = std::fs::remove_file(&png);
+        let _ = std::fs::remove_file(&json);
+    }
+
+    #[test]
+    fn test_lit_image_follows_lighting() {
+        let mut emulator = Emulator::headless(250, 122);
+        emulator.framebuffer.clear();
+        let raw = emulator.lit_image();
+        assert!(raw.get_pixel(0, 0).0.iter().all(|&c| c > 245));
+
+        emulator.set_lighting(Some(Lighting::scenario(AmbientLight::DarkRoom)));
+        let dark = emulator.lit_image();
+        assert!(dark.get_pixel(0, 0).0.iter().all(|&c| c < 8));
+
+        emulator.set_lighting(Some(
+            Lighting::scenario(AmbientLight::DarkRoom)
+                .with_front_light(FrontLight::new(1.0).with_warmth(1.0)),
+        ));
+        let [r, g, b] = emulator.lit_image().get_pixel(0, 0).0;
+        assert!(r > g && g > b, "warm white {r} {g} {b}");
+        assert_eq!(
+            emulator.lighting().map(|l| l.ambient),
+            Some(AmbientLight::DarkRoom)
+        );
+    }
+
+    #[test]
+    fn test_inverted_flips_drawn_and_new_pixels() {
+        let mut emulator = Emulator::headless(16, 8);
+        emulator.framebuffer.clear();
+        emulator.set_inverted(true);
+        assert!(emulator.is_inverted());
+        assert_eq!(
+            emulator.framebuffer.get_pixel(0, 0),
+            Some(EinkColor::Gray(Gray4::BLACK))
+        );
+
+        Pixel(Point::new(1, 0), Gray4::BLACK)
+            .draw(&mut emulator)
+            .unwrap();
+        assert_eq!(
+            emulator.framebuffer.get_pixel(1, 0),
+            Some(EinkColor::Gray(Gray4::WHITE))
+        );
+
+        emulator.set_inverted(false);
+        assert_eq!(
+            emulator.framebuffer.get_pixel(0, 0),
+            Some(EinkColor::Gray(Gray4::WHITE))
+        );
+        assert_eq!(
+            emulator.framebuffer.get_pixel(1, 0),
+            Some(EinkColor::Gray(Gray4::BLACK))
+        );
+    }
 }
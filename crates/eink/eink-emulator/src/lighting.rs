@@ -0,0 +1,311 @@
+//! Front-light and ambient-light simulation
+//!
+//! An e-paper panel emits nothing; what the reader sees is the panel's
+//! reflectance lit by the room plus, if fitted, an LED front light shining
+//! through a light guide.  The emulator normally shows raw panel levels
+//! (white = 0xFF, black = 0x00), which flatters the UI: a real panel's black
+//! reflects ~3 % of the light its white does, direct sun adds surface glare,
+//! and the light guide scatters some front light straight back at the eye.
+//!
+//! [`Lighting`] composites those effects over each presented frame so
+//! contrast decisions (thin strokes, mid-grey text, icon weight) can be
+//! judged under the conditions the player is actually used in:
+//!
+//! - [`AmbientLight`] picks a scenario — dark room, indoor, overcast,
+//!   direct sunlight — setting illuminance and glare.
+//! - [`FrontLight`] adds LED illuminance with a warm/cool tint.
+//!
+//! The result is normalised to what the eye adapts to: a lit panel keeps
+//! its white near full scale, while a dark room without front light fades
+//! towards black.  Enable it with
+//! [`Emulator::set_lighting`](crate::Emulator::set_lighting); it affects
+//! presentation and [`Emulator::screenshot_lit`](crate::Emulator::screenshot_lit),
+//! never the framebuffer, ghosting or power model.
+
+/// Reflectance of a full-white pixel.
+pub const PANEL_WHITE_REFLECTANCE: f32 = 0.45;
+
+/// Reflectance of a full-black pixel (≈15:1 panel contrast).
+pub const PANEL_BLACK_REFLECTANCE: f32 = 0.03;
+
+/// Illuminance of the front light at full brightness (lux).
+pub const FRONT_LIGHT_MAX_LUX: f32 = 300.0;
+
+/// Fraction of the front light the light guide scatters towards the viewer
+/// regardless of pixel content.
+pub const FRONT_LIGHT_SCATTER: f32 = 0.03;
+
+/// Illuminance below which the eye stops adapting (lux).  Darker scenes
+/// render proportionally dimmer instead of being normalised to white.
+pub const ADAPTATION_FLOOR_LUX: f32 = 150.0;
+
+/// Linear RGB multipliers of the cool (≈6500 K) front-light LEDs.
+const COOL_TINT: [f32; 3] = [0.92, 0.97, 1.0];
+
+/// Linear RGB multipliers of the warm (≈2700 K) front-light LEDs.
+const WARM_TINT: [f32; 3] = [1.0, 0.72, 0.42];
+
+/// Viewing environment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum AmbientLight {
+    /// Bedroom at night: front light or nothing.
+    DarkRoom,
+    /// Living room or office lighting.
+    #[default]
+    Indoor,
+    /// Outdoors under cloud.
+    Overcast,
+    /// Direct sunlight, with strong surface glare.
+    Sunlight,
+}
+
+impl AmbientLight {
+    /// Every scenario, from darkest to brightest.
+    pub const ALL: [Self; 4] = [Self::DarkRoom, Self::Indoor, Self::Overcast, Self::Sunlight];
+
+    /// Illuminance falling on the panel (lux).
+    pub fn lux(self) -> f32 {
+        match self {
+            Self::DarkRoom => 2.0,
+            Self::Indoor => 300.0,
+            Self::Overcast => 10_000.0,
+            Self::Sunlight => 100_000.0,
+        }
+    }
+
+    /// Fraction of the ambient light reflected off the panel surface
+    /// (specular glare) on top of the pixel reflectance.
+    pub fn glare(self) -> f32 {
+        match self {
+            Self::DarkRoom => 0.0,
+            Self::Indoor => 0.01,
+            Self::Overcast => 0.02,
+            Self::Sunlight => 0.06,
+        }
+    }
+
+    /// The next brighter scenario, wrapping to [`DarkRoom`](Self::DarkRoom).
+    pub fn next(self) -> Self {
+        match self {
+            Self::DarkRoom => Self::Indoor,
+            Self::Indoor => Self::Overcast,
+            Self::Overcast => Self::Sunlight,
+            Self::Sunlight => Self::DarkRoom,
+        }
+    }
+}
+
+/// LED front light setting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrontLight {
+    /// Brightness, 0.0 (off) ..= 1.0 ([`FRONT_LIGHT_MAX_LUX`]).
+    pub brightness: f32,
+    /// Colour temperature mix, 0.0 (all cool LEDs) ..= 1.0 (all warm).
+    pub warmth: f32,
+}
+
+impl FrontLight {
+    /// Front light at `brightness` with a neutral (half warm) tint.
+    pub fn new(brightness: f32) -> Self {
+        Self {
+            brightness,
+            warmth: 0.5,
+        }
+    }
+
+    /// Set the warm/cool mix.
+    pub fn with_warmth(mut self, warmth: f32) -> Self {
+        self.warmth = warmth;
+        self
+    }
+
+    /// Illuminance added to the panel (lux).
+    pub fn lux(&self) -> f32 {
+        self.brightness.clamp(0.0, 1.0) * FRONT_LIGHT_MAX_LUX
+    }
+
+    /// Linear RGB multipliers of the mixed LED colour.
+    pub fn tint(&self) -> [f32; 3] {
+        let w = self.warmth.clamp(0.0, 1.0);
+        let mut tint = [0.0; 3];
+        for ((t, cool), warm) in tint.iter_mut().zip(COOL_TINT).zip(WARM_TINT) {
+            *t = cool + (warm - cool) * w;
+        }
+        tint
+    }
+}
+
+/// Lighting conditions composited over presented frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Lighting {
+    /// Viewing environment.
+    pub ambient: AmbientLight,
+    /// Front light, if fitted and switched on.
+    pub front_light: Option<FrontLight>,
+}
+
+impl Lighting {
+    /// `ambient` conditions with the front light off.
+    pub fn scenario(ambient: AmbientLight) -> Self {
+        Self {
+            ambient,
+            front_light: None,
+        }
+    }
+
+    /// Switch the front light on.
+    pub fn with_front_light(mut self, front_light: FrontLight) -> Self {
+        self.front_light = Some(front_light);
+        self
+    }
+
+    /// Per-channel illuminance reaching the panel (lux).
+    fn illuminance(&self) -> [f32; 3] {
+        let ambient = self.ambient.lux();
+        let mut rgb = [ambient; 3];
+        if let Some(fl) = &self.front_light {
+            let lux = fl.lux();
+            for (c, t) in rgb.iter_mut().zip(fl.tint()) {
+                *c += lux * t;
+            }
+        }
+        rgb
+    }
+
+    /// Per-channel light reaching the eye independent of pixel content:
+    /// surface glare plus light-guide scatter (lux-equivalent).
+    fn veil(&self) -> [f32; 3] {
+        let glare = self.ambient.lux() * self.ambient.glare();
+        let mut rgb = [glare; 3];
+        if let Some(fl) = &self.front_light {
+            let scatter = fl.lux() * FRONT_LIGHT_SCATTER;
+            for (c, t) in rgb.iter_mut().zip(fl.tint()) {
+                *c += scatter * t;
+            }
+        }
+        rgb
+    }
+
+    /// Light level the eye adapts to: a white pixel at this level shows as
+    /// full scale.
+    fn adaptation(&self) -> f32 {
+        let total = self.ambient.lux() + self.front_light.map_or(0.0, |fl| fl.lux());
+        PANEL_WHITE_REFLECTANCE * total.max(ADAPTATION_FLOOR_LUX)
+    }
+
+    /// Map one panel channel value (0..=255) to its lit value.
+    fn channel(value: u32, illuminance: f32, veil: f32, adaptation: f32) -> u32 {
+        let level = (value & 0xFF) as f32 / 255.0;
+        let reflectance =
+            PANEL_BLACK_REFLECTANCE + (PANEL_WHITE_REFLECTANCE - PANEL_BLACK_REFLECTANCE) * level;
+        let seen = (reflectance * illuminance + veil) / adaptation;
+        (seen.clamp(0.0, 1.0) * 255.0).round() as u32
+    }
+
+    /// Apply the lighting to a 0xAARRGGBB frame in place.
+    pub fn apply(&self, argb: &mut [u32]) {
+        let illuminance = self.illuminance();
+        let veil = self.veil();
+        let adaptation = self.adaptation();
+        for px in argb.iter_mut() {
+            let mut out = *px & 0xFF00_0000;
+            for (i, shift) in [16u32, 8, 0].into_iter().enumerate() {
+                let (e, v) = (illuminance.get(i), veil.get(i));
+                let lit = Self::channel(
+                    *px >> shift,
+                    e.copied().unwrap_or(0.0),
+                    v.copied().unwrap_or(0.0),
+                    adaptation,
+                );
+                out |= lit << shift;
+            }
+            *px = out;
+        }
+    }
+
+    /// Luminance ratio of a full-white to a full-black pixel as seen under
+    /// these conditions (the panel alone is ≈15:1).
+    pub fn contrast_ratio(&self) -> f32 {
+        let e: f32 = self.illuminance().iter().sum();
+        let veil: f32 = self.veil().iter().sum();
+        (PANEL_WHITE_REFLECTANCE * e + veil) / (PANEL_BLACK_REFLECTANCE * e + veil)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing)]
+    use super::*;
+
+    const WHITE: u32 = 0xFFFF_FFFF;
+    const BLACK: u32 = 0xFF00_0000;
+
+    fn lit(lighting: Lighting, px: u32) -> [u32; 3] {
+        let mut frame = [px];
+        lighting.apply(&mut frame);
+        let [p] = frame;
+        [(p >> 16) & 0xFF, (p >> 8) & 0xFF, p & 0xFF]
+    }
+
+    #[test]
+    fn indoor_white_is_full_scale_and_black_is_lifted() {
+        let indoor = Lighting::scenario(AmbientLight::Indoor);
+        assert!(lit(indoor, WHITE).iter().all(|&c| c >= 250));
+        let black = lit(indoor, BLACK);
+        // 3 % black reflectance plus 1 % glare, over 45 % white.
+        assert!(black.iter().all(|&c| (18..=28).contains(&c)), "{black:?}");
+    }
+
+    #[test]
+    fn alpha_is_preserved() {
+        let mut frame = [0x80FF_FFFF];
+        Lighting::scenario(AmbientLight::Sunlight).apply(&mut frame);
+        assert_eq!(frame[0] >> 24, 0x80);
+    }
+
+    #[test]
+    fn dark_room_needs_the_front_light() {
+        let dark = Lighting::scenario(AmbientLight::DarkRoom);
+        assert!(lit(dark, WHITE).iter().all(|&c| c < 8));
+
+        let lit_white = lit(dark.with_front_light(FrontLight::new(1.0)), WHITE);
+        assert!(lit_white.iter().any(|&c| c > 200), "{lit_white:?}");
+    }
+
+    #[test]
+    fn warm_front_light_tints_white_towards_amber() {
+        let dark = Lighting::scenario(AmbientLight::DarkRoom);
+        let [r, g, b] = lit(
+            dark.with_front_light(FrontLight::new(1.0).with_warmth(1.0)),
+            WHITE,
+        );
+        assert!(r > g && g > b, "{r} {g} {b}");
+
+        let [r, _, b] = lit(
+            dark.with_front_light(FrontLight::new(1.0).with_warmth(0.0)),
+            WHITE,
+        );
+        assert!(b >= r, "{r} {b}");
+    }
+
+    #[test]
+    fn glare_and_scatter_cost_contrast() {
+        let indoor = Lighting::scenario(AmbientLight::Indoor).contrast_ratio();
+        let sun = Lighting::scenario(AmbientLight::Sunlight).contrast_ratio();
+        let night = Lighting::scenario(AmbientLight::DarkRoom)
+            .with_front_light(FrontLight::new(0.5))
+            .contrast_ratio();
+        assert!(indoor > 10.0 && indoor < 15.0, "indoor {indoor}");
+        assert!(sun < indoor * 0.6, "sun {sun} vs indoor {indoor}");
+        assert!(night < indoor, "night {night} vs indoor {indoor}");
+    }
+
+    #[test]
+    fn next_cycles_through_all_scenarios() {
+        let mut ambient = AmbientLight::DarkRoom;
+        for expected in AmbientLight::ALL.iter().cycle().skip(1).take(4) {
+            ambient = ambient.next();
+            assert_eq!(ambient, *expected);
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Static image (mockup) loading.
+//!
+//! Converts a PNG into display content: the image is fitted to the display
+//! (aspect ratio preserved, centred on white), composited over white where
+//! transparent, and quantized to 16-level [`Gray4`].  The result is written
+//! to the framebuffer and shown through the normal refresh path, so a mockup
+//! gets the same flashing, waveform quantization and ghosting as UI drawn by
+//! firmware code.
+//!
+//! Dropping a `.png` onto the emulator window queues it; see
+//! [`Emulator::show_dropped_image`](crate::Emulator::show_dropped_image).
+
+use embedded_graphics::pixelcolor::Gray4;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use std::path::Path;
+
+/// Luma step between adjacent Gray4 levels (255 / 15).
+const LEVEL_STEP: f32 = 17.0;
+
+/// Whether `path` has an extension the mockup loader accepts (`.png`).
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("png"))
+}
+
+/// Decode `path` and convert it with [`to_gray4`].
+pub fn load(
+    path: impl AsRef<Path>,
+    width: u32,
+    height: u32,
+    dither: bool,
+) -> Result<Vec<Gray4>, image::ImageError> {
+    let img = image::open(path)?;
+    Ok(to_gray4(&img, width, height, dither))
+}
+
+/// Fit `img` into `width`×`height` and quantize it to row-major [`Gray4`].
+///
+/// Areas not covered by the image (letterboxing) and transparent pixels are
+/// white.  With `dither`, Floyd–Steinberg error diffusion spreads the
+/// quantization error so photographs keep their tonal range; without it each
+/// pixel rounds to the nearest level, which keeps flat UI mockups crisp.
+// SAFETY: coordinates are bounded by width/height (display-sized, ~800×480);
+// luma values stay within 0.0..=255.0 after clamping, so the casts to u8 and
+// the index arithmetic cannot overflow.
+#[allow(
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn to_gray4(img: &DynamicImage, width: u32, height: u32, dither: bool) -> Vec<Gray4> {
+    let fitted = if img.width() == width && img.height() == height {
+        img.to_luma_alpha8()
+    } else {
+        img.resize(width, height, FilterType::Triangle)
+            .to_luma_alpha8()
+    };
+    let x0 = width.saturating_sub(fitted.width()) / 2;
+    let y0 = height.saturating_sub(fitted.height()) / 2;
+
+    let mut canvas = vec![255.0f32; (width * height) as usize];
+    for (x, y, px) in fitted.enumerate_pixels() {
+        let [luma, alpha] = px.0;
+        let a = f32::from(alpha) / 255.0;
+        let (cx, cy) = (x + x0, y + y0);
+        if cx < width && cy < height {
+            if let Some(c) = canvas.get_mut((cy * width + cx) as usize) {
+                *c = f32::from(luma) * a + 255.0 * (1.0 - a);
+            }
+        }
+    }
+
+    let mut out = Vec::with_capacity(canvas.len());
+    for y in 0..height {
+        for x in 0..width {
+            let idx = (y * width + x) as usize;
+            let value = canvas.get(idx).copied().unwrap_or(255.0).clamp(0.0, 255.0);
+            let level = (value / LEVEL_STEP).round();
+            out.push(Gray4::new(level as u8));
+
+            if dither {
+                let err = value - level * LEVEL_STEP;
+                let mut spread = |dx: i64, dy: u32, weight: f32| {
+                    let nx = i64::from(x) + dx;
+                    let ny = y + dy;
+                    if nx >= 0 && nx < i64::from(width) && ny < height {
+                        if let Some(c) = canvas.get_mut((ny * width) as usize + nx as usize) {
+                            *c += err * weight;
+                        }
+                    }
+                };
+                spread(1, 0, 7.0 / 16.0);
+                spread(-1, 1, 3.0 / 16.0);
+                spread(0, 1, 5.0 / 16.0);
+                spread(1, 1, 1.0 / 16.0);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+    use super::*;
+    use embedded_graphics::prelude::GrayColor;
+    use image::{GrayImage, Luma, LumaA, RgbaImage};
+
+    fn luma(pixels: &[Gray4]) -> Vec<u8> {
+        pixels.iter().map(|p| p.luma()).collect()
+    }
+
+    #[test]
+    fn exact_size_maps_levels() {
+        let img = GrayImage::from_fn(4, 1, |x, _| Luma([[0, 85, 170, 255][x as usize]]));
+        let out = to_gray4(&DynamicImage::ImageLuma8(img), 4, 1, false);
+        assert_eq!(luma(&out), [0, 5, 10, 15]);
+    }
+
+    #[test]
+    fn wide_image_is_letterboxed_in_white() {
+        let img = GrayImage::from_pixel(8, 2, Luma([0]));
+        let out = to_gray4(&DynamicImage::ImageLuma8(img), 8, 8, false);
+        let rows: Vec<u8> = (0..8).map(|y| out[y * 8].luma()).collect();
+        // 8×2 fits as 8×2, centred vertically at rows 3..5.
+        assert_eq!(rows, [15, 15, 15, 0, 0, 15, 15, 15]);
+    }
+
+    #[test]
+    fn transparent_pixels_are_white() {
+        let img =
+            image::ImageBuffer::from_fn(2, 1, |x, _| LumaA([0u8, if x == 0 { 0 } else { 255 }]));
+        let out = to_gray4(&DynamicImage::ImageLumaA8(img), 2, 1, false);
+        assert_eq!(luma(&out), [15, 0]);
+    }
+
+    #[test]
+    fn colour_is_converted_to_luma() {
+        let img = RgbaImage::from_pixel(1, 1, image::Rgba([255, 255, 255, 255]));
+        let out = to_gray4(&DynamicImage::ImageRgba8(img), 1, 1, false);
+        assert_eq!(luma(&out), [15]);
+    }
+
+    #[test]
+    fn dither_preserves_mean_tone() {
+        // 8 sits between levels 0 (0) and 1 (17); rounding flattens it to 0.
+        let img = GrayImage::from_pixel(32, 32, Luma([8]));
+        let img = DynamicImage::ImageLuma8(img);
+        let flat = to_gray4(&img, 32, 32, false);
+        assert!(flat.iter().all(|p| p.luma() == 0));
+
+        let dithered = to_gray4(&img, 32, 32, true);
+        let ones = dithered.iter().filter(|p| p.luma() == 1).count();
+        // ≈ 8/17 of the pixels should be raised to level 1.
+        assert!((400..560).contains(&ones), "ones = {ones}");
+    }
+
+    #[test]
+    fn only_png_is_supported() {
+        assert!(is_supported(Path::new("screen.png")));
+        assert!(is_supported(Path::new("SCREEN.PNG")));
+        assert!(!is_supported(Path::new("screen.jpg")));
+        assert!(!is_supported(Path::new("screen")));
+    }
+}
@@ -0,0 +1,309 @@
+//! Multi-display emulation
+//!
+//! Drives two [`Emulator`] instances from one process so dual-display UI
+//! concepts (main panel + small secondary status display) can be prototyped
+//! before any hardware exists.
+//!
+//! # Shared virtual clock
+//!
+//! Both emulators are attached to one [`VirtualClock`]. Refresh animations
+//! advance the clock instead of sleeping on the wall clock, so a 3 s full
+//! refresh on the main panel and a 300 ms partial on the status panel are
+//! reported on the same timeline and tests run instantly.
+//!
+//! # Combined window
+//!
+//! winit allows a single event loop per process, so the two panels are
+//! composed into one window according to a [`MultiLayout`]. Each emulator is
+//! created headless; [`MultiEmulator::present`] blits both panels' visible
+//! state (including ghosting) into the shared window.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_emulator::{DisplayDriver, MultiEmulator, MultiLayout};
+//! use eink_specs::displays::{GDEM0397T81P, WAVESHARE_2_13_V4};
+//!
+//! # async fn example() {
+//! let mut multi = MultiEmulator::new(
+//!     &GDEM0397T81P,
+//!     &WAVESHARE_2_13_V4,
+//!     MultiLayout::Stacked { gap: 16 },
+//! );
+//! multi.primary_mut().refresh_full().await.unwrap();
+//! multi.secondary_mut().refresh_partial().await.unwrap();
+//! multi.present();
+//! multi.run();
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::{EinkColor, Emulator};
+
+/// Background colour of the gap between panels in the combined window.
+const GAP_RGBA: u32 = 0xFF40_4040;
+
+/// Simulated time source shared between emulator instances.
+///
+/// Cloning is cheap; all clones observe and advance the same counter.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualClock {
+    now_ms: Arc<AtomicU64>,
+}
+
+impl VirtualClock {
+    /// Create a clock starting at t = 0 ms.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current simulated time in milliseconds.
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms.load(Ordering::Acquire)
+    }
+
+    /// Advance simulated time by `ms` milliseconds.
+    pub fn advance(&self, ms: u64) {
+        self.now_ms.fetch_add(ms, Ordering::AcqRel);
+    }
+}
+
+/// How two panels are arranged in the combined window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultiLayout {
+    /// Primary on the left, secondary on the right, top-aligned.
+    SideBySide {
+        /// Horizontal gap between panels in pixels.
+        gap: u32,
+    },
+    /// Primary on top, secondary below, left-aligned.
+    Stacked {
+        /// Vertical gap between panels in pixels.
+        gap: u32,
+    },
+}
+
+impl MultiLayout {
+    /// Combined canvas size and secondary panel origin for the given panels.
+    // SAFETY: panel sizes and gaps are display dimensions (a few thousand
+    // pixels at most), so their sums cannot overflow u32.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn arrange(self, primary: (u32, u32), secondary: (u32, u32)) -> ((u32, u32), (u32, u32)) {
+        match self {
+            Self::SideBySide { gap } => {
+                let x = primary.0 + gap;
+                ((x + secondary.0, primary.1.max(secondary.1)), (x, 0))
+            }
+            Self::Stacked { gap } => {
+                let y = primary.1 + gap;
+                ((primary.0.max(secondary.0), y + secondary.1), (0, y))
+            }
+        }
+    }
+}
+
+/// Which panel of a [`MultiEmulator`] to address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    /// The main display.
+    Primary,
+    /// The secondary status display.
+    Secondary,
+}
+
+/// Two emulated panels sharing a virtual clock and a combined window.
+pub struct MultiEmulator {
+    primary: Emulator,
+    secondary: Emulator,
+    layout: MultiLayout,
+    clock: VirtualClock,
+    #[cfg(not(feature = "headless"))]
+    window: Option<crate::window::Window>,
+}
+
+impl MultiEmulator {
+    /// Create a dual-display emulator with a combined window.
+    pub fn new(
+        primary: &'static eink_specs::DisplaySpec,
+        secondary: &'static eink_specs::DisplaySpec,
+        layout: MultiLayout,
+    ) -> Self {
+        #[cfg_attr(feature = "headless", allow(unused_mut))]
+        let mut multi = Self::headless(primary, secondary, layout);
+
+        #[cfg(not(feature = "headless"))]
+        {
+            let (w, h) = multi.canvas_size();
+            multi.window = Some(crate::window::Window::new(
+                w,
+                h,
+                &crate::config::EmulatorConfig::default(),
+            ));
+        }
+
+        multi
+    }
+
+    /// Create a dual-display emulator without a window (tests/CI).
+    pub fn headless(
+        primary: &'static eink_specs::DisplaySpec,
+        secondary: &'static eink_specs::DisplaySpec,
+        layout: MultiLayout,
+    ) -> Self {
+        let clock = VirtualClock::new();
+        let mut primary = Emulator::headless_with_spec(primary);
+        let mut secondary = Emulator::headless_with_spec(secondary);
+        primary.set_virtual_clock(clock.clone());
+        secondary.set_virtual_clock(clock.clone());
+
+        Self {
+            primary,
+            secondary,
+            layout,
+            clock,
+            #[cfg(not(feature = "headless"))]
+            window: None,
+        }
+    }
+
+    /// The main panel.
+    pub fn primary(&self) -> &Emulator {
+        &self.primary
+    }
+
+    /// The main panel (mutable).
+    pub fn primary_mut(&mut self) -> &mut Emulator {
+        &mut self.primary
+    }
+
+    /// The secondary panel.
+    pub fn secondary(&self) -> &Emulator {
+        &self.secondary
+    }
+
+    /// The secondary panel (mutable).
+    pub fn secondary_mut(&mut self) -> &mut Emulator {
+        &mut self.secondary
+    }
+
+    /// Address a panel by [`Panel`] id.
+    pub fn panel_mut(&mut self, panel: Panel) -> &mut Emulator {
+        match panel {
+            Panel::Primary => &mut self.primary,
+            Panel::Secondary => &mut self.secondary,
+        }
+    }
+
+    /// The shared virtual clock.
+    pub fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    /// Window layout in use.
+    pub fn layout(&self) -> MultiLayout {
+        self.layout
+    }
+
+    /// Size of the combined canvas in display pixels.
+    pub fn canvas_size(&self) -> (u32, u32) {
+        self.layout
+            .arrange(panel_size(&self.primary), panel_size(&self.secondary))
+            .0
+    }
+
+    /// Top-left corner of `panel` within the combined canvas.
+    pub fn panel_origin(&self, panel: Panel) -> (u32, u32) {
+        match panel {
+            Panel::Primary => (0, 0),
+            Panel::Secondary => {
+                self.layout
+                    .arrange(panel_size(&self.primary), panel_size(&self.secondary))
+                    .1
+            }
+        }
+    }
+
+    /// Compose both panels' visible state into one RGBA buffer.
+    ///
+    /// Uses the ghosting-aware effective framebuffer, i.e. what the physical
+    /// panels would currently show, not the pending draw buffer.
+    // SAFETY: canvas coordinates are bounded by the combined canvas size, so
+    // the index arithmetic cannot overflow u32.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn composite_rgba(&self) -> Vec<u32> {
+        let (cw, ch) = self.canvas_size();
+        let mut canvas = vec![GAP_RGBA; (cw * ch) as usize];
+        for panel in [Panel::Primary, Panel::Secondary] {
+            let emu = match panel {
+                Panel::Primary => &self.primary,
+                Panel::Secondary => &self.secondary,
+            };
+            let (ox, oy) = self.panel_origin(panel);
+            let (pw, _) = panel_size(emu);
+            for (i, gray) in emu
+                .pixel_states()
+                .effective_framebuffer()
+                .iter()
+                .enumerate()
+            {
+                let x = ox + i as u32 % pw;
+                let y = oy + i as u32 / pw;
+                if let Some(px) = canvas.get_mut((y * cw + x) as usize) {
+                    *px = EinkColor::Gray(*gray).to_rgba();
+                }
+            }
+        }
+        canvas
+    }
+
+    /// Push the composite of both panels to the combined window.
+    ///
+    /// No-op without a window.
+    pub fn present(&mut self) {
+        #[cfg(not(feature = "headless"))]
+        if self.window.is_some() {
+            let rgba = self.composite_rgba();
+            if let Some(window) = &mut self.window {
+                window.present(&rgba);
+            }
+        }
+    }
+
+    /// Save the combined canvas as a PNG.
+    // SAFETY: `cw` is the non-zero canvas width and `i` a canvas pixel index.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn screenshot(
+        &self,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use image::{Rgba, RgbaImage};
+
+        let (cw, ch) = self.canvas_size();
+        let mut img = RgbaImage::new(cw, ch);
+        for (i, argb) in self.composite_rgba().into_iter().enumerate() {
+            let [a, r, g, b] = argb.to_be_bytes();
+            img.put_pixel(i as u32 % cw, i as u32 / cw, Rgba([r, g, b, a]));
+        }
+        img.save(path)?;
+        Ok(())
+    }
+
+    /// Run the combined window event loop (blocks until closed).
+    #[cfg(not(feature = "headless"))]
+    pub fn run(self) {
+        if let Some(window) = self.window {
+            window.run();
+        }
+    }
+
+    #[cfg(feature = "headless")]
+    pub fn run(self) {
+        // No-op in headless mode
+    }
+}
+
+fn panel_size(emu: &Emulator) -> (u32, u32) {
+    (emu.framebuffer.width, emu.framebuffer.height)
+}
@@ -1,10 +1,13 @@
 //! Partial Update Window Management
 //!
 //! Handles dirty region tracking and alignment for e-ink partial updates.
-//! E-ink controllers require partial update regions to be aligned to 8-pixel
-//! boundaries for correct addressing.
+//! E-ink controllers require partial update regions to be aligned to
+//! controller-specific boundaries for correct addressing (see
+//! [`eink_specs::PartialAlignment`]). Without a controller the conservative
+//! 8×8 grid is used.
 
-use crate::alignment::{align_rectangle, validate_and_align};
+use crate::alignment::{align_rectangle_with, validate_and_align_with};
+use eink_specs::{Controller, PartialAlignment};
 use embedded_graphics::prelude::*;
 use embedded_graphics::primitives::Rectangle;
 
@@ -32,10 +35,12 @@ use embedded_graphics::primitives::Rectangle;
 pub struct PartialWindow {
     /// Original rectangle before alignment
     pub rect: Rectangle,
-    /// Rectangle after 8-pixel alignment
+    /// Rectangle after alignment
     pub aligned_rect: Rectangle,
     /// Whether alignment was necessary
     pub was_aligned: bool,
+    /// Alignment grid applied to `rect`
+    pub alignment: PartialAlignment,
 }
 
 impl PartialWindow {
@@ -56,11 +61,37 @@ impl PartialWindow {
     /// assert_eq!(window.aligned_rect.top_left, Point::new(8, 8));
     /// ```
     pub fn new(rect: Rectangle) -> Self {
-        let (aligned_rect, was_aligned) = validate_and_align(&rect);
+        Self::with_alignment(rect, PartialAlignment::GENERIC)
+    }
+
+    /// Create a partial window aligned for a specific controller
+    ///
+    /// # Examples
+    /// ```
+    /// use eink_emulator::partial_window::PartialWindow;
+    /// use eink_specs::Controller;
+    /// use embedded_graphics::prelude::*;
+    /// use embedded_graphics::primitives::Rectangle;
+    ///
+    /// let rect = Rectangle::new(Point::new(10, 10), Size::new(20, 20));
+    /// let window = PartialWindow::for_controller(rect, Controller::SSD1677);
+    ///
+    /// // X snaps to RAM bytes, Y is gate-addressable
+    /// assert_eq!(window.aligned_rect.top_left, Point::new(8, 10));
+    /// assert_eq!(window.aligned_rect.size, Size::new(24, 20));
+    /// ```
+    pub fn for_controller(rect: Rectangle, controller: Controller) -> Self {
+        Self::with_alignment(rect, controller.partial_alignment())
+    }
+
+    /// Create a partial window aligned to an explicit grid
+    pub fn with_alignment(rect: Rectangle, alignment: PartialAlignment) -> Self {
+        let (aligned_rect, was_aligned) = validate_and_align_with(&rect, alignment);
         Self {
             rect,
             aligned_rect,
             was_aligned,
+            alignment,
         }
     }
 
@@ -69,7 +100,7 @@ impl PartialWindow {
     /// Re-aligns the current rectangle. This is useful if the rectangle
     /// has been modified after creation.
     pub fn align(&mut self) {
-        let (aligned_rect, was_aligned) = validate_and_align(&self.rect);
+        let (aligned_rect, was_aligned) = validate_and_align_with(&self.rect, self.alignment);
         self.aligned_rect = aligned_rect;
         self.was_aligned = was_aligned;
     }
@@ -88,7 +119,8 @@ impl PartialWindow {
 
     /// Merge with another window to create a bounding box
     ///
-    /// Returns a new PartialWindow that encompasses both windows.
+    /// Returns a new PartialWindow that encompasses both windows, aligned
+    /// with this window's grid.
     // SAFETY: all arithmetic here operates on display coordinates (max ~4000px) and their
     // differences; no overflow is possible with display-sized rectangles.
     #[allow(clippy::arithmetic_side_effects)]
@@ -115,7 +147,7 @@ impl PartialWindow {
             (bottom_right.y - top_left.y) as u32,
         );
         let merged_rect = Rectangle::new(top_left, size);
-        PartialWindow::new(merged_rect)
+        PartialWindow::with_alignment(merged_rect, self.alignment)
     }
 }
 
@@ -139,10 +171,20 @@ impl PartialWindow {
 /// assert_eq!(merged.top_left, Point::new(0, 0));
 /// assert_eq!(merged.size, Size::new(40, 40));
 /// ```
+pub fn merge_rectangles(rects: &[Rectangle]) -> Option<Rectangle> {
+    merge_rectangles_with(rects, PartialAlignment::GENERIC)
+}
+
+/// Merge multiple rectangles and align the bounding box to `alignment`
+///
+/// Returns None if the input is empty.
 // SAFETY: all arithmetic here operates on display coordinates (max ~4000px) bounded by
 // rectangle dimensions; no overflow is possible with display-sized rectangles.
 #[allow(clippy::arithmetic_side_effects)]
-pub fn merge_rectangles(rects: &[Rectangle]) -> Option<Rectangle> {
+pub fn merge_rectangles_with(
+    rects: &[Rectangle],
+    alignment: PartialAlignment,
+) -> Option<Rectangle> {
     if rects.is_empty() {
         return None;
     }
@@ -171,7 +213,7 @@ pub fn merge_rectangles(rects: &[Rectangle]) -> Option<Rectangle> {
     }
 
     // Align the final merged rectangle
-    Some(align_rectangle(&merged))
+    Some(align_rectangle_with(&merged, alignment))
 }
 
 #[cfg(test)]
@@ -245,6 +287,32 @@ mod tests {
         assert_eq!(merged.aligned_rect.size, Size::new(24, 24));
     }
 
+    #[test]
+    fn test_partial_window_for_controller() {
+        let rect = Rectangle::new(Point::new(5, 5), Size::new(10, 10));
+        let window = PartialWindow::for_controller(rect, Controller::SSD1680);
+
+        assert!(window.was_aligned);
+        assert_eq!(window.aligned_rect, Rectangle::new(Point::new(0, 5), Size::new(16, 10)));
+    }
+
+    #[test]
+    fn test_partial_window_merge_keeps_alignment() {
+        let a = PartialWindow::for_controller(
+            Rectangle::new(Point::new(0, 3), Size::new(8, 2)),
+            Controller::SSD1677,
+        );
+        let b = PartialWindow::for_controller(
+            Rectangle::new(Point::new(16, 9), Size::new(8, 2)),
+            Controller::SSD1677,
+        );
+
+        let merged = a.merge(&b);
+
+        assert_eq!(merged.alignment, Controller::SSD1677.partial_alignment());
+        assert_eq!(merged.aligned_rect, Rectangle::new(Point::new(0, 3), Size::new(24, 8)));
+    }
+
     #[test]
     fn test_merge_rectangles_empty() {
         let result = merge_rectangles(&[]);
@@ -137,6 +137,24 @@ impl EinkColor {
         !self.is_grayscale()
     }
 
+    /// Swap black and white (night mode).  Colour pigments are kept; only
+    /// the black/white plane and Kaleido filter levels are inverted.
+    pub fn inverted(self) -> Self {
+        let flip = |level: u8| 15u8.saturating_sub(level.min(15));
+        match self {
+            EinkColor::Gray(gray) => EinkColor::Gray(Gray4::new(flip(gray.luma()))),
+            EinkColor::Spectra6 { bw, color } => EinkColor::Spectra6 {
+                bw: Gray4::new(flip(bw.luma())),
+                color,
+            },
+            EinkColor::Kaleido3 { r, g, b } => EinkColor::Kaleido3 {
+                r: flip(r),
+                g: flip(g),
+                b: flip(b),
+            },
+        }
+    }
+
     /// Quantize to mode-specific levels (for grayscale)
     // SAFETY: levels is at most 4 (Gray4 range); step = 3 / (levels-1) is an integer
     // division of small values; (value / step) * step cannot overflow u8.
@@ -301,6 +319,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_inverted_swaps_black_and_white() {
+        assert_eq!(
+            EinkColor::Gray(Gray4::WHITE).inverted(),
+            EinkColor::Gray(Gray4::BLACK)
+        );
+        let gray = EinkColor::Gray(Gray4::new(5));
+        assert_eq!(gray.inverted().inverted(), gray);
+        let red = EinkColor::Spectra6 {
+            bw: Gray4::WHITE,
+            color: SpectraColor::Red,
+        };
+        assert_eq!(
+            red.inverted(),
+            EinkColor::Spectra6 {
+                bw: Gray4::BLACK,
+                color: SpectraColor::Red,
+            }
+        );
+        let kaleido = EinkColor::Kaleido3 { r: 15, g: 0, b: 5 };
+        assert_eq!(
+            kaleido.inverted(),
+            EinkColor::Kaleido3 { r: 0, g: 15, b: 10 }
+        );
+    }
+
     #[test]
     fn test_from_trait() {
         let gray = Gray4::new(2);
@@ -1,12 +1,21 @@
 //! Per-Pixel State Tracking
 //!
 //! Implements robust e-ink physics simulation with content-dependent ghosting,
-//! DC balance tracking, and particle state modeling.
+//! DC balance tracking, and particle state modeling.  Ghosting also fades
+//! slowly while the panel is idle ([`PixelState::decay`]); the emulator
+//! drives this from its virtual clock.
 
 use crate::lut::WaveformLut;
 use embedded_graphics::pixelcolor::Gray4;
 use embedded_graphics::prelude::GrayColor;
 
+/// Ghost half-life at 25°C (10 minutes).
+const GHOST_HALF_LIFE_25C_MS: f32 = 600_000.0;
+
+/// Ghosting below this is invisible and snapped to zero so decayed pixels
+/// compare equal to clean ones.
+const GHOST_FLOOR: f32 = 0.001;
+
 /// Physical state of a single e-ink pixel
 ///
 /// Tracks all the stateful properties needed for realistic simulation:
@@ -313,6 +322,53 @@ impl PixelState {
         }
     }
 
+    /// Let residual ghosting fade for `elapsed_ms` of idle time
+    ///
+    /// Trapped charge slowly leaks away between refreshes, so a ghost left
+    /// by a partial refresh is visibly fainter after a few minutes of
+    /// reading.  Decay is exponential with a temperature-dependent
+    /// half-life (see [`Self::ghost_half_life_ms`]); DC balance is not
+    /// affected.
+    pub fn decay(&mut self, elapsed_ms: u64, temperature: i8) {
+        self.apply_decay(Self::decay_factor(elapsed_ms, temperature));
+    }
+
+    /// Multiply ghosting by a precomputed decay `factor` (0.0–1.0).
+    // SAFETY: f32 multiplication of values in [0.0, 1.0]; cannot overflow.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn apply_decay(&mut self, factor: f32) {
+        self.ghosting *= factor;
+        if self.ghosting < GHOST_FLOOR {
+            self.ghosting = 0.0;
+        }
+        if let Some(ref mut color) = self.color_state {
+            color.color_ghosting *= factor;
+            if color.color_ghosting < GHOST_FLOOR {
+                color.color_ghosting = 0.0;
+            }
+        }
+    }
+
+    /// Ghost half-life at `temperature` in milliseconds
+    ///
+    /// 10 minutes at 25°C, doubling every 10°C colder (particles are
+    /// sluggish) and halving every 10°C warmer.  Clamped to the -20..=60°C
+    /// range so extreme inputs stay finite.
+    // SAFETY: f32 arithmetic on a clamped temperature; result is bounded.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn ghost_half_life_ms(temperature: i8) -> f32 {
+        let t = f32::from(temperature.clamp(-20, 60));
+        GHOST_HALF_LIFE_25C_MS * 2.0f32.powf((25.0 - t) / 10.0)
+    }
+
+    /// Fraction of ghosting left after `elapsed_ms` at `temperature`.
+    // SAFETY: f32 division by a strictly positive half-life.
+    #[allow(clippy::arithmetic_side_effects)]
+    fn decay_factor(elapsed_ms: u64, temperature: i8) -> f32 {
+        let half_lives = elapsed_ms as f32 / Self::ghost_half_life_ms(temperature);
+        0.5f32.powf(half_lives)
+    }
+
     /// Get effective gray level with ghosting applied
     ///
     /// Blends current with previous based on ghosting level.
@@ -445,6 +501,25 @@ impl PixelStateBuffer {
             .fold(0.0f32, |a, b| a.max(b))
     }
 
+    /// Pixel counts per ghosting level, in `buckets` equal-width bins over
+    /// 0.0–1.0 (ghosting of exactly 1.0 lands in the last bin)
+    // SAFETY: bucket index is clamped to buckets - 1; counts are bounded by pixel count.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn ghosting_histogram(&self, buckets: usize) -> Vec<u64> {
+        let mut counts = vec![0u64; buckets];
+        if buckets == 0 {
+            return counts;
+        }
+        let last = buckets - 1;
+        for state in &self.states {
+            let bin = ((state.ghosting.clamp(0.0, 1.0) * buckets as f32) as usize).min(last);
+            if let Some(count) = counts.get_mut(bin) {
+                *count += 1;
+            }
+        }
+        counts
+    }
+
     /// Count pixels with critical DC balance
     pub fn dc_critical_count(&self) -> usize {
         self.states.iter().filter(|s| s.dc_critical()).count()
@@ -496,6 +571,19 @@ impl PixelStateBuffer {
         }
     }
 
+    /// Decay ghosting on all pixels for `elapsed_ms` of idle time
+    ///
+    /// See [`PixelState::decay`].
+    pub fn decay_all(&mut self, elapsed_ms: u64, temperature: i8) {
+        if elapsed_ms == 0 {
+            return;
+        }
+        let factor = PixelState::decay_factor(elapsed_ms, temperature);
+        for state in &mut self.states {
+            state.apply_decay(factor);
+        }
+    }
+
     /// Get effective framebuffer with ghosting applied
     pub fn effective_framebuffer(&self) -> Vec<Gray4> {
         self.states.iter().map(|s| s.effective_color()).collect()
@@ -864,4 +952,71 @@ mod tests {
             pixel2.ghosting
         );
     }
+
+    #[test]
+    fn test_ghost_decay_halves_per_half_life() {
+        let mut pixel = PixelState::new();
+        pixel.ghosting = 0.4;
+        pixel.dc_balance = 12.0;
+        pixel.decay(PixelState::ghost_half_life_ms(25) as u64, 25);
+        assert!((pixel.ghosting - 0.2).abs() < 1e-4, "{}", pixel.ghosting);
+        assert_eq!(pixel.dc_balance, 12.0, "decay must not touch DC balance");
+
+        // Five minutes at room temperature: slight fade, not gone.
+        let mut reading = PixelState::new();
+        reading.ghosting = 0.3;
+        reading.decay(5 * 60 * 1000, 25);
+        assert!(reading.ghosting > 0.2 && reading.ghosting < 0.3);
+    }
+
+    #[test]
+    fn test_ghost_decay_depends_on_temperature() {
+        assert_eq!(PixelState::ghost_half_life_ms(25), 600_000.0);
+        assert_eq!(PixelState::ghost_half_life_ms(15), 1_200_000.0);
+        assert_eq!(PixelState::ghost_half_life_ms(35), 300_000.0);
+        assert_eq!(
+            PixelState::ghost_half_life_ms(-128),
+            PixelState::ghost_half_life_ms(-20)
+        );
+
+        let mut cold = PixelState::new();
+        let mut warm = PixelState::new();
+        cold.ghosting = 0.5;
+        warm.ghosting = 0.5;
+        cold.decay(300_000, 0);
+        warm.decay(300_000, 40);
+        assert!(cold.ghosting > warm.ghosting);
+    }
+
+    #[test]
+    fn test_ghost_decay_snaps_to_zero() {
+        let mut buffer = PixelStateBuffer::new(2, 1);
+        for x in 0..2 {
+            let px = buffer.get_mut(x, 0).unwrap();
+            px.ghosting = 0.5;
+            px.color_state = Some(ColorPixelState {
+                color_ghosting: 0.5,
+                ..ColorPixelState::new()
+            });
+        }
+        buffer.decay_all(0, 25);
+        assert_eq!(buffer.max_ghosting(), 0.5);
+        buffer.decay_all(24 * 60 * 60 * 1000, 25);
+        assert_eq!(buffer.max_ghosting(), 0.0);
+        let color = buffer.get(0, 0).unwrap().color_state.unwrap();
+        assert_eq!(color.color_ghosting, 0.0);
+    }
+
+    #[test]
+    fn test_ghosting_histogram_bins() {
+        let mut buffer = PixelStateBuffer::new(4, 1);
+        for (x, g) in [(0, 0.0), (1, 0.05), (2, 0.55), (3, 1.0)] {
+            buffer.get_mut(x, 0).unwrap().ghosting = g;
+        }
+        assert_eq!(
+            buffer.ghosting_histogram(10),
+            [2, 0, 0, 0, 0, 1, 0, 0, 0, 1]
+        );
+        assert!(buffer.ghosting_histogram(0).is_empty());
+    }
 }
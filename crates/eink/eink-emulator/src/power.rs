@@ -5,6 +5,8 @@
 
 use std::time::Instant;
 
+use crate::VirtualClock;
+
 /// Power consumption profile for a display
 ///
 /// All current values in microamps (µA) for precision.
@@ -118,8 +120,20 @@ pub struct PowerStats {
     pub idle_time_ms: u64,
 
     /// Time spent actively refreshing (milliseconds)
+    ///
+    /// Includes refresh, initialization and buffer transfer; the
+    /// `*_time_ms` fields below break it down.
     pub active_time_ms: u64,
 
+    /// Part of `active_time_ms` spent driving waveforms
+    pub refresh_time_ms: u64,
+
+    /// Part of `active_time_ms` spent in the initialization sequence
+    pub init_time_ms: u64,
+
+    /// Part of `active_time_ms` spent transferring buffers to display SRAM
+    pub transfer_time_ms: u64,
+
     /// Time spent in sleep state (milliseconds)
     pub sleep_time_ms: u64,
 }
@@ -208,6 +222,10 @@ pub struct PowerTracker {
     /// Last time power was updated
     last_update: Instant,
 
+    /// Time source replacing the wall clock, and its reading at the last
+    /// update
+    clock: Option<(VirtualClock, u64)>,
+
     /// Whether power tracking is enabled
     enabled: bool,
 }
@@ -220,10 +238,37 @@ impl PowerTracker {
             stats: PowerStats::default(),
             profile,
             last_update: Instant::now(),
+            clock: None,
             enabled: true,
         }
     }
 
+    /// Measure time on `clock` instead of the wall clock
+    ///
+    /// Time spent in each state is then simulated time, so refreshes that
+    /// advance the clock instead of sleeping still cost energy.
+    pub fn set_clock(&mut self, clock: VirtualClock) {
+        let now = clock.now_ms();
+        self.clock = Some((clock, now));
+    }
+
+    /// Milliseconds since the last update, and mark a new update
+    fn lap_ms(&mut self) -> u64 {
+        match &mut self.clock {
+            Some((clock, last)) => {
+                let now = clock.now_ms();
+                let elapsed = now.saturating_sub(*last);
+                *last = now;
+                elapsed
+            }
+            None => {
+                let elapsed = self.last_update.elapsed().as_millis() as u64;
+                self.last_update = Instant::now();
+                elapsed
+            }
+        }
+    }
+
     /// Update to new power state and record energy consumption
     // SAFETY: energy arithmetic uses u64 for accumulation (no overflow for realistic runtimes);
     // f64 division and casting for average current calculation is safe for display-scale values.
@@ -233,7 +278,7 @@ impl PowerTracker {
             return;
         }
 
-        let elapsed_ms = self.last_update.elapsed().as_millis() as u64;
+        let elapsed_ms = self.lap_ms();
 
         // Calculate energy for previous state
         let current_ua = self.current_draw_ua();
@@ -251,15 +296,23 @@ impl PowerTracker {
         // Update time tracking
         match self.state {
             PowerState::Idle => self.stats.idle_time_ms += elapsed_ms,
-            PowerState::Refreshing { .. } => self.stats.active_time_ms += elapsed_ms,
+            PowerState::Refreshing { .. } => {
+                self.stats.active_time_ms += elapsed_ms;
+                self.stats.refresh_time_ms += elapsed_ms;
+            }
             PowerState::Sleeping => self.stats.sleep_time_ms += elapsed_ms,
-            PowerState::Initializing => self.stats.active_time_ms += elapsed_ms,
-            PowerState::TransferringBuffer => self.stats.active_time_ms += elapsed_ms,
+            PowerState::Initializing => {
+                self.stats.active_time_ms += elapsed_ms;
+                self.stats.init_time_ms += elapsed_ms;
+            }
+            PowerState::TransferringBuffer => {
+                self.stats.active_time_ms += elapsed_ms;
+                self.stats.transfer_time_ms += elapsed_ms;
+            }
         }
 
         // Update state
         self.state = new_state;
-        self.last_update = Instant::now();
 
         // Recalculate average current
         let total_time_ms = self.stats.total_runtime_ms();
@@ -295,7 +348,7 @@ impl PowerTracker {
     /// Reset all statistics
     pub fn reset(&mut self) {
         self.stats = PowerStats::default();
-        self.last_update = Instant::now();
+        self.lap_ms();
         self.state = PowerState::Idle;
     }
 
@@ -367,7 +420,7 @@ mod tests {
 
         // Need ~8s for 150µA idle current to accumulate 1µWh with integer math
         // Formula: 150µA × 8000ms × 33 / 36_000_000 = 1.1µWh
-        std::thread::sleep(std::time::Duration::from_millis(8000));
+        std::thread::sleep(std::time::Duration::from_secs(8));
         tracker.transition_to(PowerState::Idle);
 
         let stats = tracker.stats();
@@ -391,6 +444,26 @@ mod tests {
         assert!(stats.active_time_ms >= 100);
     }
 
+    #[test]
+    fn test_power_tracker_virtual_clock() {
+        let profile = &PowerProfile::WAVESHARE_2_13_V4;
+        let clock = VirtualClock::new();
+        let mut tracker = PowerTracker::new(profile);
+        tracker.set_clock(clock.clone());
+
+        tracker.transition_to(PowerState::Sleeping);
+        clock.advance(3_600_000);
+        tracker.transition_to(PowerState::Idle);
+
+        // One simulated hour asleep, no wall time needed.
+        let stats = tracker.stats();
+        assert_eq!(stats.sleep_time_ms, 3_600_000);
+        assert_eq!(
+            stats.total_energy_uwh,
+            u64::from(profile.sleep_current_ua) * 33 / 10
+        );
+    }
+
     #[test]
     fn test_power_tracker_disable() {
         let mut tracker = PowerTracker::new(&PowerProfile::WAVESHARE_2_13_V4);
@@ -0,0 +1,126 @@
+//! Animated GIF recording
+//!
+//! [`GifRecorder`] collects framebuffer snapshots, each with the time it
+//! stays on screen, and writes them as a looping GIF.  Consecutive identical
+//! frames are merged into one longer frame, so recording at a fixed rate
+//! costs nothing while the panel holds still.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_emulator::{recording::GifRecorder, Emulator};
+//!
+//! let emulator = Emulator::headless(250, 122);
+//! let mut recorder = GifRecorder::new();
+//! for _ in 0..10 {
+//!     // ... draw the next frame ...
+//!     recorder.capture(&emulator, 1_000);
+//! }
+//! recorder.save("target/demo.gif").unwrap();
+//! ```
+
+use std::path::Path;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, GrayImage, Rgba, RgbaImage};
+
+use crate::Emulator;
+
+/// Frames captured from an emulator, ready to be written as a GIF.
+#[derive(Debug, Default)]
+pub struct GifRecorder {
+    frames: Vec<(GrayImage, u32)>,
+}
+
+impl GifRecorder {
+    /// Empty recording.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture the framebuffer as it is now, to be shown for `delay_ms`.
+    /// A frame identical to the previous one extends it instead.
+    pub fn capture(&mut self, emulator: &Emulator, delay_ms: u32) {
+        let image = emulator.grayscale_image();
+        if let Some((last, delay)) = self.frames.last_mut() {
+            if *last == image {
+                *delay = delay.saturating_add(delay_ms);
+                return;
+            }
+        }
+        self.frames.push((image, delay_ms));
+    }
+
+    /// Number of distinct frames captured.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Whether nothing has been captured.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Total play time of one loop in milliseconds.
+    pub fn duration_ms(&self) -> u64 {
+        self.frames.iter().map(|(_, ms)| u64::from(*ms)).sum()
+    }
+
+    /// Write the frames as a GIF that loops forever.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        // Four gray levels quantise exactly; spend no time on palette search.
+        let mut encoder = GifEncoder::new_with_speed(file, 30);
+        encoder.set_repeat(Repeat::Infinite)?;
+        encoder.encode_frames(self.frames.iter().map(|(image, ms)| {
+            let rgba = RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+                let [luma] = image.get_pixel(x, y).0;
+                Rgba([luma, luma, luma, 0xFF])
+            });
+            Frame::from_parts(rgba, 0, 0, Delay::from_numer_denom_ms(*ms, 1))
+        }))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::pixelcolor::Gray4;
+    use embedded_graphics::prelude::*;
+    use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+    #[test]
+    fn test_identical_frames_are_merged() {
+        let mut emulator = Emulator::headless(32, 16);
+        let mut recorder = GifRecorder::new();
+        recorder.capture(&emulator, 500);
+        recorder.capture(&emulator, 500);
+        Rectangle::new(Point::zero(), Size::new(8, 8))
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut emulator)
+            .unwrap();
+        recorder.capture(&emulator, 250);
+        assert_eq!(recorder.len(), 2);
+        assert_eq!(recorder.duration_ms(), 1_250);
+    }
+
+    #[test]
+    fn test_save_writes_an_animated_gif() {
+        let mut emulator = Emulator::headless(32, 16);
+        let mut recorder = GifRecorder::new();
+        recorder.capture(&emulator, 100);
+        Rectangle::new(Point::zero(), Size::new(8, 8))
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut emulator)
+            .unwrap();
+        recorder.capture(&emulator, 100);
+
+        let path =
+            std::env::temp_dir().join(format!("eink_emulator_demo_{}.gif", std::process::id()));
+        recorder.save(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert!(bytes.starts_with(b"GIF89a"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
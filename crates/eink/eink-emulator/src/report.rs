@@ -0,0 +1,108 @@
+//! Machine-readable display wear and energy report
+//!
+//! [`Emulator::report`](crate::Emulator::report) gathers refresh counters,
+//! power-state timing, energy and the ghosting distribution into one
+//! serializable [`EmulatorReport`], so CI can archive it per run and trend
+//! display wear and energy per PR instead of only pass/fail.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_emulator::Emulator;
+//!
+//! let emulator = Emulator::headless(250, 122);
+//! // ... drive the UI ...
+//! emulator.report().save("target/display-report.json").unwrap();
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sidecar::DisplayInfo;
+
+/// Number of bins in [`GhostingReport::histogram`] (0.1 ghosting each).
+pub const GHOSTING_BUCKETS: usize = 10;
+
+/// Snapshot of everything worth trending about a display session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmulatorReport {
+    /// Panel the session ran on.
+    pub display: DisplayInfo,
+    /// Refresh counters.
+    pub refreshes: RefreshReport,
+    /// Power-state timing and energy.
+    pub power: PowerReport,
+    /// Ghosting distribution.
+    pub ghosting: GhostingReport,
+    /// DC balance summary.
+    pub dc_balance: DcBalanceReport,
+}
+
+/// Refresh counters since the emulator was created.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshReport {
+    /// Refresh count per waveform mode (e.g. `"GC16"`), modes never used included.
+    pub by_mode: BTreeMap<String, u64>,
+    pub full: u64,
+    pub partial: u64,
+    pub fast: u64,
+    /// Sum of nominal waveform durations.
+    pub total_time_ms: u64,
+    pub dc_warnings: u32,
+}
+
+/// Power totals since the last power-stats reset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PowerReport {
+    pub total_energy_uwh: u64,
+    pub average_current_ua: u32,
+    pub peak_current_ua: u32,
+    /// Time in each power state (`idle`, `refreshing`, `transferring`,
+    /// `initializing`, `sleeping`).
+    pub time_ms: BTreeMap<String, u64>,
+    /// Battery life at the observed average current, for a 1000 mAh cell.
+    pub battery_life_hours_1000mah: f32,
+}
+
+/// Distribution of per-pixel ghosting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GhostingReport {
+    pub average: f32,
+    pub max: f32,
+    /// Pixel counts in [`GHOSTING_BUCKETS`] equal bins over 0.0–1.0.
+    pub histogram: Vec<u64>,
+}
+
+/// DC balance summary.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DcBalanceReport {
+    pub average: f32,
+    pub max: f32,
+    /// Pixels past the critical threshold (need a full refresh).
+    pub critical_pixels: usize,
+}
+
+impl EmulatorReport {
+    /// Serialize to pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Deserialize from JSON produced by [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Write the report to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Read a report from `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::from_json(&std::fs::read_to_string(path)?)?)
+    }
+}
@@ -0,0 +1,283 @@
+//! Per-user emulator settings persisted between runs
+//!
+//! Scale, window position, display spec, debug overlay toggles and the
+//! simulated temperature are saved as JSON in the user's config directory
+//! ([`default_path`]) and restored on the next launch, so a long dev session
+//! does not start with re-setting them every run.
+//!
+//! Only the launcher knows when a run starts and ends, so it drives the
+//! round-trip: load before creating the emulator, fold the scale into its
+//! [`EmulatorConfig`] with [`EmulatorSettings::apply_to_config`], restore the
+//! rest with [`Emulator::apply_settings`](crate::Emulator::apply_settings)
+//! once the window is open, and save
+//! [`Emulator::settings`](crate::Emulator::settings) on exit.  A corrupt file
+//! is reported and ignored rather than blocking startup; deleting it
+//! ([`EmulatorSettings::reset`]) returns to the built-in defaults.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_emulator::{settings::{self, EmulatorSettings}, Emulator, EmulatorConfig};
+//!
+//! let path = settings::default_path().unwrap();
+//! let saved = EmulatorSettings::load_or_default(&path);
+//! let mut config = EmulatorConfig::default();
+//! saved.apply_to_config(&mut config);
+//! let mut emulator = Emulator::with_config(config);
+//! emulator.apply_settings(&saved);
+//! // ... run ...
+//! emulator.settings().save(&path).unwrap();
+//! ```
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::EmulatorConfig;
+
+/// Directory under the user config directory holding the settings file.
+pub const APP_DIR: &str = "soul-listener";
+
+/// Settings file name inside [`APP_DIR`].
+pub const FILE_NAME: &str = "emulator.json";
+
+/// Environment variable overriding [`default_path`].
+pub const PATH_ENV: &str = "EINK_EMULATOR_SETTINGS";
+
+/// Largest scale restored from a settings file.  Anything bigger is a
+/// hand-edited typo, not a window that fits on a monitor.
+pub const MAX_SCALE: u32 = 8;
+
+/// Emulator presentation state saved between runs.
+///
+/// Missing fields take their [`Default`] values, so a file written by an
+/// older emulator still loads.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmulatorSettings {
+    /// Window upscaling factor.
+    pub scale: u32,
+    /// Outer window position in physical pixels, if the platform reports one.
+    pub window_position: Option<(i32, i32)>,
+    /// Name of the selected [`DisplaySpec`](eink_specs::DisplaySpec).
+    pub display: Option<String>,
+    /// Debug overlay toggles.
+    pub overlays: OverlaySettings,
+    /// Simulated panel temperature (°C).
+    pub temperature_c: i8,
+}
+
+/// Debug overlay toggles (the `debug` feature's Ctrl+1/2/3).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlaySettings {
+    /// Debug side panel shown.
+    pub panel: bool,
+    /// Component borders drawn.
+    pub borders: bool,
+    /// Hover inspector enabled.
+    pub inspector: bool,
+}
+
+impl Default for EmulatorSettings {
+    fn default() -> Self {
+        Self {
+            scale: EmulatorConfig::DEFAULT.scale,
+            window_position: None,
+            display: None,
+            overlays: OverlaySettings::default(),
+            temperature_c: 25,
+        }
+    }
+}
+
+/// Per-user settings file: `$EINK_EMULATOR_SETTINGS` if set, otherwise
+/// `soul-listener/emulator.json` under `%APPDATA%` (Windows),
+/// `$XDG_CONFIG_HOME` or `~/.config`.
+///
+/// Returns `None` when none of those variables is set.
+pub fn default_path() -> Option<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty());
+    if let Some(path) = var(PATH_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let config_dir = if cfg!(windows) {
+        var("APPDATA").map(PathBuf::from)
+    } else {
+        var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| Path::new(&home).join(".config")))
+    };
+    config_dir.map(|dir| dir.join(APP_DIR).join(FILE_NAME))
+}
+
+impl EmulatorSettings {
+    /// Read settings from `path`.
+    ///
+    /// Returns `Ok(None)` when the file does not exist, and an
+    /// [`io::ErrorKind::InvalidData`] error when it is not valid settings
+    /// JSON.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Read settings from `path`, falling back to the defaults when the file
+    /// is missing or unreadable (the latter is reported on stderr).
+    pub fn load_or_default(path: &Path) -> Self {
+        match Self::load(path) {
+            Ok(settings) => settings.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("⚠️  Ignoring emulator settings {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Write settings to `path`, creating its directory if needed.
+    ///
+    /// The file is written next to `path` and renamed over it, so a crash
+    /// mid-write never leaves a truncated file behind.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Delete the settings file at `path` (`--reset-config`).  A missing file
+    /// is not an error.
+    pub fn reset(path: &Path) -> io::Result<()> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fold the saved scale into `config`, clamped to `1..=MAX_SCALE`.
+    ///
+    /// Rotation and backend stay as the launcher chose them: they describe
+    /// the target device, not a viewing preference.
+    pub fn apply_to_config(&self, config: &mut EmulatorConfig) {
+        config.scale = self.scale.clamp(1, MAX_SCALE);
+    }
+
+    /// The saved display spec among `candidates`, matched by name.
+    pub fn display_spec(
+        &self,
+        candidates: &[&'static eink_specs::DisplaySpec],
+    ) -> Option<&'static eink_specs::DisplaySpec> {
+        let name = self.display.as_deref()?;
+        candidates.iter().copied().find(|spec| spec.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("eink_emulator_settings_{}", std::process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = temp_path("round_trip.json");
+        let settings = EmulatorSettings {
+            scale: 3,
+            window_position: Some((-40, 120)),
+            display: Some(eink_specs::displays::GDEM0397T81P.name.to_string()),
+            overlays: OverlaySettings {
+                panel: true,
+                borders: false,
+                inspector: true,
+            },
+            temperature_c: -5,
+        };
+        settings.save(&path).unwrap();
+        assert_eq!(EmulatorSettings::load(&path).unwrap(), Some(settings));
+
+        EmulatorSettings::reset(&path).unwrap();
+        assert_eq!(EmulatorSettings::load(&path).unwrap(), None);
+        // Resetting twice is fine.
+        EmulatorSettings::reset(&path).unwrap();
+    }
+
+    #[test]
+    fn missing_fields_take_defaults() {
+        let settings: EmulatorSettings = serde_json::from_str(r#"{"scale": 1}"#).unwrap();
+        assert_eq!(
+            settings,
+            EmulatorSettings {
+                scale: 1,
+                ..EmulatorSettings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn corrupt_file_falls_back_to_defaults() {
+        let path = temp_path("corrupt.json");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let err = EmulatorSettings::load(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            EmulatorSettings::load_or_default(&path),
+            EmulatorSettings::default()
+        );
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn scale_is_clamped_into_config() {
+        let mut config = EmulatorConfig::PORTRAIT;
+        EmulatorSettings {
+            scale: 0,
+            ..EmulatorSettings::default()
+        }
+        .apply_to_config(&mut config);
+        assert_eq!(config.scale, 1);
+
+        EmulatorSettings {
+            scale: 40,
+            ..EmulatorSettings::default()
+        }
+        .apply_to_config(&mut config);
+        assert_eq!(config.scale, MAX_SCALE);
+        assert_eq!(config.rotation, EmulatorConfig::PORTRAIT.rotation);
+    }
+
+    #[test]
+    fn display_spec_matches_by_name() {
+        let all = eink_specs::displays::ALL;
+        let settings = EmulatorSettings {
+            display: Some(eink_specs::displays::WAVESHARE_4_2_V2.name.to_string()),
+            ..EmulatorSettings::default()
+        };
+        assert_eq!(
+            settings.display_spec(&all).map(|s| s.name),
+            Some(eink_specs::displays::WAVESHARE_4_2_V2.name)
+        );
+        let unknown = EmulatorSettings {
+            display: Some("No Such Panel".to_string()),
+            ..EmulatorSettings::default()
+        };
+        assert!(unknown.display_spec(&all).is_none());
+        assert!(EmulatorSettings::default().display_spec(&all).is_none());
+    }
+}
@@ -0,0 +1,144 @@
+//! Screenshot metadata sidecars
+//!
+//! [`Emulator::screenshot_annotated`](crate::Emulator::screenshot_annotated)
+//! writes the usual PNG plus a JSON file next to it describing the emulator
+//! state at capture time: display spec, waveform mode, temperature, ghosting,
+//! refresh counters, power totals and registered components.  A screenshot
+//! attached to a bug report then says which panel it was taken on and how
+//! worn the image was, without anyone having to ask.
+//!
+//! The sidecar for `screen.png` is `screen.json` ([`sidecar_path`]).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_emulator::{sidecar::ScreenshotMetadata, Emulator};
+//!
+//! let emulator = Emulator::headless(250, 122);
+//! let json = emulator.screenshot_annotated("bug-1234.png").unwrap();
+//! let meta = ScreenshotMetadata::load(&json).unwrap();
+//! println!("{} @ {}°C", meta.display.name, meta.temperature_c);
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Emulator state captured alongside a screenshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScreenshotMetadata {
+    /// Panel the screenshot was rendered for.
+    pub display: DisplayInfo,
+    /// Waveform mode used by `display()` at capture time (e.g. `"GC16"`).
+    pub waveform_mode: String,
+    /// Simulated panel temperature (°C).
+    pub temperature_c: i8,
+    /// Ghosting and DC-balance state of the pixel physics model.
+    pub ghosting: GhostingInfo,
+    /// Refresh counters since the emulator was created.
+    pub refreshes: RefreshInfo,
+    /// Power totals since the last reset.
+    pub power: PowerInfo,
+    /// Components registered at capture time (debug registry and/or tests).
+    pub components: Vec<ComponentEntry>,
+}
+
+/// Subset of [`DisplaySpec`](eink_specs::DisplaySpec) identifying the panel.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisplayInfo {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub controller: String,
+    pub panel_type: String,
+}
+
+/// Ghosting summary.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GhostingInfo {
+    /// Mean ghosting across all pixels (0.0–1.0).
+    pub average: f32,
+    /// Worst single pixel (0.0–1.0).
+    pub max: f32,
+    /// Largest absolute DC balance of any pixel.
+    pub max_dc_balance: f32,
+}
+
+/// Refresh counters (mirrors [`DisplayStats`](crate::DisplayStats)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefreshInfo {
+    pub full: u64,
+    pub partial: u64,
+    pub fast: u64,
+    pub total_time_ms: u64,
+    pub dc_warnings: u32,
+}
+
+/// Power totals (mirrors [`PowerStats`](crate::PowerStats)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerInfo {
+    pub total_energy_uwh: u64,
+    pub average_current_ua: u32,
+    pub peak_current_ua: u32,
+    pub idle_time_ms: u64,
+    pub active_time_ms: u64,
+    pub sleep_time_ms: u64,
+}
+
+/// One registered UI component.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentEntry {
+    pub test_id: Option<String>,
+    pub component_type: String,
+    pub position: (i32, i32),
+    pub size: (u32, u32),
+}
+
+impl ScreenshotMetadata {
+    /// Serialize to pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Deserialize from JSON produced by [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Write the sidecar to `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Read a sidecar from `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::from_json(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Find a component by test ID.
+    pub fn component(&self, test_id: &str) -> Option<&ComponentEntry> {
+        self.components
+            .iter()
+            .find(|c| c.test_id.as_deref() == Some(test_id))
+    }
+}
+
+/// Sidecar location for a screenshot: the same path with a `.json` extension.
+pub fn sidecar_path(screenshot: impl AsRef<Path>) -> PathBuf {
+    screenshot.as_ref().with_extension("json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidecar_path_replaces_extension() {
+        assert_eq!(
+            sidecar_path("out/screen.png"),
+            PathBuf::from("out/screen.json")
+        );
+        assert_eq!(sidecar_path("screen"), PathBuf::from("screen.json"));
+    }
+}
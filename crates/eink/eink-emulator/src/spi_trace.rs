@@ -0,0 +1,283 @@
+//! SPI transaction trace capture and replay
+//!
+//! [`ControllerEmulator`] can record every reset pulse, command byte and data
+//! write it receives, stamped with the bus clock. A trace can be:
+//!
+//! - dumped as an annotated log (`Display`) for bug reports,
+//! - saved and loaded as JSON,
+//! - compared against another trace to answer "did the init sequence
+//!   change?" ([`SpiTrace::first_difference`]), and
+//! - replayed into a fresh controller to reproduce exact wire traffic.
+//!
+//! Comparison ignores timestamps and how data writes were chunked, so a
+//! driver that switches from 256-byte to 512-byte SPI transfers produces an
+//! identical trace.
+//!
+//! # Example
+//!
+//! ```
+//! use eink_emulator::controller::{cmd, ControllerEmulator};
+//! use eink_specs::displays::WAVESHARE_2_13_V4;
+//!
+//! # async fn example() {
+//! let mut ctrl = ControllerEmulator::new(&WAVESHARE_2_13_V4);
+//! ctrl.start_recording();
+//! ctrl.write_command(cmd::SOFT_RESET).await.unwrap();
+//! ctrl.write_command(cmd::DATA_ENTRY_MODE).await.unwrap();
+//! ctrl.write_data(&[0x03]).await.unwrap();
+//! let trace = ctrl.stop_recording().unwrap();
+//!
+//! println!("{trace}");
+//! //        0 ms  CMD  0x12 SoftReset
+//! //        0 ms  CMD  0x11 DataEntryMode
+//! //        0 ms  DATA 03
+//! # }
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::controller::{command_name, ControllerEmulator};
+
+/// Data bytes shown per entry in the annotated log before eliding.
+const ANNOTATE_MAX_BYTES: usize = 16;
+
+/// One bus-level operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpiOp {
+    /// RST line pulsed.
+    Reset,
+    /// Command byte (DC low).
+    Command(u8),
+    /// Data bytes (DC high).
+    Data(Vec<u8>),
+}
+
+/// A timestamped [`SpiOp`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// Bus clock time in milliseconds when the operation started.
+    pub at_ms: u64,
+    /// The operation.
+    pub op: SpiOp,
+}
+
+/// First point at which two traces diverge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceDiff {
+    /// Index into the normalised operation lists.
+    pub index: usize,
+    /// Operation in the reference trace (`None` if it ended first).
+    pub expected: Option<SpiOp>,
+    /// Operation in the compared trace (`None` if it ended first).
+    pub actual: Option<SpiOp>,
+}
+
+/// Recorded SPI traffic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpiTrace {
+    entries: Vec<TraceEntry>,
+}
+
+impl SpiTrace {
+    /// Create an empty trace.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an operation at `at_ms`.
+    pub fn push(&mut self, at_ms: u64, op: SpiOp) {
+        self.entries.push(TraceEntry { at_ms, op });
+    }
+
+    /// All recorded entries in order.
+    pub fn entries(&self) -> &[TraceEntry] {
+        &self.entries
+    }
+
+    /// Number of recorded entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Command bytes in the order they were sent.
+    pub fn commands(&self) -> impl Iterator<Item = u8> + '_ {
+        self.entries.iter().filter_map(|e| match e.op {
+            SpiOp::Command(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    /// Operations with timestamps dropped and consecutive data writes merged.
+    pub fn normalized_ops(&self) -> Vec<SpiOp> {
+        let mut ops: Vec<SpiOp> = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            if let (SpiOp::Data(bytes), Some(SpiOp::Data(prev))) = (&entry.op, ops.last_mut()) {
+                prev.extend_from_slice(bytes);
+                continue;
+            }
+            ops.push(entry.op.clone());
+        }
+        ops
+    }
+
+    /// Whether both traces put the same bytes on the wire.
+    pub fn wire_eq(&self, other: &Self) -> bool {
+        self.first_difference(other).is_none()
+    }
+
+    /// Find where `other` first deviates from this (reference) trace.
+    pub fn first_difference(&self, other: &Self) -> Option<TraceDiff> {
+        let expected = self.normalized_ops();
+        let actual = other.normalized_ops();
+        let len = expected.len().max(actual.len());
+        (0..len).find_map(|index| {
+            let e = expected.get(index);
+            let a = actual.get(index);
+            (e != a).then(|| TraceDiff {
+                index,
+                expected: e.cloned(),
+                actual: a.cloned(),
+            })
+        })
+    }
+
+    /// Serialise to pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Parse a trace previously written by [`to_json`](Self::to_json).
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Feed the trace into `ctrl`, advancing its bus clock to each entry's
+    /// timestamp so BUSY timing is reproduced.
+    pub async fn replay(&self, ctrl: &mut ControllerEmulator) -> Result<(), std::io::Error> {
+        for entry in &self.entries {
+            let now = ctrl.bus_clock().now_ms();
+            ctrl.bus_clock().advance(entry.at_ms.saturating_sub(now));
+            match &entry.op {
+                SpiOp::Reset => ctrl.hardware_reset(),
+                SpiOp::Command(c) => ctrl.write_command(*c).await?,
+                SpiOp::Data(bytes) => ctrl.write_data(bytes).await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SpiOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reset => write!(f, "RESET"),
+            Self::Command(c) => match command_name(*c) {
+                Some(name) => write!(f, "CMD  0x{c:02X} {name}"),
+                None => write!(f, "CMD  0x{c:02X}"),
+            },
+            Self::Data(bytes) => {
+                write!(f, "DATA")?;
+                if bytes.len() > ANNOTATE_MAX_BYTES {
+                    write!(f, " [{} bytes]", bytes.len())?;
+                }
+                for byte in bytes.iter().take(ANNOTATE_MAX_BYTES) {
+                    write!(f, " {byte:02X}")?;
+                }
+                if bytes.len() > ANNOTATE_MAX_BYTES {
+                    write!(f, " …")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for SpiTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{:>8} ms  {}", entry.at_ms, entry.op)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::cmd;
+    use eink_specs::displays::WAVESHARE_2_13_V4;
+
+    fn trace(ops: &[SpiOp]) -> SpiTrace {
+        let mut t = SpiTrace::new();
+        for op in ops {
+            t.push(0, op.clone());
+        }
+        t
+    }
+
+    #[test]
+    fn test_chunking_does_not_affect_comparison() {
+        let a = trace(&[SpiOp::Command(0x24), SpiOp::Data(vec![1, 2, 3, 4])]);
+        let b = trace(&[
+            SpiOp::Command(0x24),
+            SpiOp::Data(vec![1, 2]),
+            SpiOp::Data(vec![3, 4]),
+        ]);
+        assert!(a.wire_eq(&b));
+    }
+
+    #[test]
+    fn test_first_difference_reports_position() {
+        let a = trace(&[SpiOp::Command(0x11), SpiOp::Data(vec![0x03])]);
+        let b = trace(&[SpiOp::Command(0x11), SpiOp::Data(vec![0x01])]);
+        let diff = a.first_difference(&b).unwrap();
+        assert_eq!(diff.index, 1);
+        assert_eq!(diff.expected, Some(SpiOp::Data(vec![0x03])));
+        assert_eq!(diff.actual, Some(SpiOp::Data(vec![0x01])));
+
+        let shorter = trace(&[SpiOp::Command(0x11)]);
+        let diff = a.first_difference(&shorter).unwrap();
+        assert_eq!(diff.actual, None);
+    }
+
+    #[test]
+    fn test_annotated_log() {
+        let t = trace(&[SpiOp::Command(0x12), SpiOp::Data(vec![0xFF; 20])]);
+        let log = t.to_string();
+        assert!(log.contains("CMD  0x12 SoftReset"));
+        assert!(log.contains("DATA [20 bytes] FF"));
+        assert!(log.contains('…'));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let t = trace(&[SpiOp::Reset, SpiOp::Command(0x20)]);
+        let parsed = SpiTrace::from_json(&t.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, t);
+    }
+
+    #[tokio::test]
+    async fn test_replay_reproduces_ram_and_trace() {
+        let mut original = ControllerEmulator::new(&WAVESHARE_2_13_V4);
+        original.start_recording();
+        original.hardware_reset();
+        original.write_command(cmd::WRITE_RAM_BW).await.unwrap();
+        original.write_data(&[0x0F, 0xF0]).await.unwrap();
+        let recorded = original.stop_recording().unwrap();
+        assert_eq!(recorded.commands().collect::<Vec<_>>(), vec![cmd::WRITE_RAM_BW]);
+
+        let mut replayed = ControllerEmulator::new(&WAVESHARE_2_13_V4);
+        replayed.start_recording();
+        recorded.replay(&mut replayed).await.unwrap();
+
+        assert_eq!(replayed.bw_ram(), original.bw_ram());
+        assert!(recorded.wire_eq(replayed.trace().unwrap()));
+    }
+}
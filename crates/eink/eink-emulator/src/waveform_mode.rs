@@ -3,6 +3,8 @@
 //! Based on E Ink Corporation's waveform specifications and controller datasheets.
 //! Each mode has different characteristics for grayscale levels, speed, and quality.
 
+use eink_specs::DisplayMs;
+
 /// Waveform modes supported by e-ink displays
 ///
 /// These correspond to actual hardware modes used by controllers like
@@ -91,6 +93,30 @@ pub enum WaveformMode {
 }
 
 impl WaveformMode {
+    /// Every mode, in declaration order
+    pub const ALL: [Self; 7] = [
+        WaveformMode::GC16,
+        WaveformMode::GL16,
+        WaveformMode::DU,
+        WaveformMode::DU4,
+        WaveformMode::A2,
+        WaveformMode::GCC16,
+        WaveformMode::GCU,
+    ];
+
+    /// Position of this mode in [`Self::ALL`]
+    pub fn index(&self) -> usize {
+        match self {
+            WaveformMode::GC16 => 0,
+            WaveformMode::GL16 => 1,
+            WaveformMode::DU => 2,
+            WaveformMode::DU4 => 3,
+            WaveformMode::A2 => 4,
+            WaveformMode::GCC16 => 5,
+            WaveformMode::GCU => 6,
+        }
+    }
+
     /// Get the number of grayscale levels supported by this mode
     pub fn grayscale_levels(&self) -> u8 {
         match self {
@@ -113,15 +139,15 @@ impl WaveformMode {
         }
     }
 
-    /// Get typical refresh duration in milliseconds
-    pub fn base_duration_ms(&self) -> u32 {
-        match self {
+    /// Get typical refresh duration
+    pub fn base_duration_ms(&self) -> DisplayMs {
+        DisplayMs::new(match self {
             WaveformMode::GC16 | WaveformMode::GL16 => 980,
             WaveformMode::DU4 | WaveformMode::DU => 260,
             WaveformMode::A2 => 200,
             WaveformMode::GCC16 => 15000, // 15 seconds for Spectra 6
             WaveformMode::GCU => 500,     // 500ms for Kaleido 3
-        }
+        })
     }
 
     /// Get the number of flashes for this mode
@@ -175,13 +201,13 @@ impl WaveformMode {
         matches!(self, WaveformMode::GCC16 | WaveformMode::GCU)
     }
 
-    /// Get color refresh duration in milliseconds
-    pub fn color_refresh_duration_ms(&self) -> u32 {
-        match self {
+    /// Get color refresh duration
+    pub fn color_refresh_duration_ms(&self) -> DisplayMs {
+        DisplayMs::new(match self {
             WaveformMode::GCC16 => 15000, // 15 seconds for Spectra 6
             WaveformMode::GCU => 500,     // 500ms for Kaleido 3
             _ => 0,                       // Not a color mode
-        }
+        })
     }
 
     /// Get human-readable name
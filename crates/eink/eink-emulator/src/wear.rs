@@ -0,0 +1,498 @@
+//! Long-horizon panel wear (burn-in) model
+//!
+//! Electrophoretic panels do not burn in like OLED, but pixels held in one
+//! state for weeks, or driven far more often than their neighbours, retain a
+//! faint image that a full refresh no longer clears.  [`WearModel`]
+//! accumulates per-pixel stress from the refresh history of a representative
+//! session (dwell time weighted by ink density, plus drive pulses per
+//! refresh), and [`WearModel::project`] scales that session up to months of
+//! the same workload.
+//!
+//! What is visible is not stress itself but its *local* non-uniformity: a
+//! static status bar leaves an outline where heavily stressed pixels meet
+//! lightly stressed ones.  A projection therefore reports contrast against
+//! the mean of a small neighbourhood, in gray levels, and counts pixels over
+//! [`VISIBLE_CONTRAST_LEVELS`].  The constants are a rough calibration —
+//! compare workloads (static bar vs. a bar shifted every few minutes) rather
+//! than trusting the absolute month count.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_emulator::{DisplayDriver, Emulator, VirtualClock};
+//!
+//! # async fn example() {
+//! let mut emulator = Emulator::headless(250, 122);
+//! emulator.set_virtual_clock(VirtualClock::new());
+//! emulator.enable_wear_tracking(true);
+//! for _ in 0..24 {
+//!     // ... draw one hour of the UI workload ...
+//!     emulator.refresh_partial().await.unwrap();
+//!     emulator.idle(60 * 60_000);
+//! }
+//! let report = emulator.wear_report(6.0).unwrap();
+//! println!("visible after {:?} months", report.months_to_visible);
+//! emulator.save_wear_overlay("target/wear.png", 6.0).unwrap();
+//! # }
+//! ```
+
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::GrayColor;
+use image::{GrayImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+use crate::WaveformMode;
+
+/// Stress per hour a pixel spends fully black (scaled by ink density).
+const DWELL_STRESS_PER_HOUR: f32 = 1.0;
+
+/// Stress per full-swing (black ↔ white) transition.
+const DRIVE_STRESS: f32 = 0.01;
+
+/// Stress per waveform flash applied to a driven pixel.
+const FLASH_STRESS: f32 = 0.002;
+
+/// Gray levels of retained image per unit of stress non-uniformity.
+const LEVELS_PER_STRESS: f32 = 0.001;
+
+/// Radius (pixels) of the neighbourhood a pixel is compared against.
+pub const NEIGHBOURHOOD_RADIUS: u32 = 6;
+
+/// Retained-image contrast (gray levels) a reader starts to notice.
+pub const VISIBLE_CONTRAST_LEVELS: f32 = 1.0;
+
+/// Length of a simulated month.
+pub const MONTH_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+
+const HOUR_MS: f32 = 3_600_000.0;
+
+/// Per-pixel stress accumulated from refresh history.
+#[derive(Debug, Clone)]
+pub struct WearModel {
+    width: u32,
+    height: u32,
+    stress: Vec<f32>,
+    /// Luma (0–3) each pixel currently shows.
+    shown: Vec<u8>,
+    started_ms: Option<u64>,
+    settled_ms: u64,
+    refreshes: u64,
+}
+
+impl WearModel {
+    /// Fresh panel of `width` × `height` pixels, showing white.
+    // SAFETY: width * height is a display pixel count that fits in usize.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn new(width: u32, height: u32) -> Self {
+        let size = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            stress: vec![0.0; size],
+            shown: vec![3; size],
+            started_ms: None,
+            settled_ms: 0,
+            refreshes: 0,
+        }
+    }
+
+    /// Account for the time since the last refresh (or settle) at `now_ms`.
+    ///
+    /// Every pixel accrues dwell stress for the content it has been showing.
+    /// Called by [`record_refresh`](Self::record_refresh); call it directly
+    /// before projecting if the session ends on an idle period.
+    pub fn settle(&mut self, now_ms: u64) {
+        if self.started_ms.is_none() {
+            return;
+        }
+        let hours = now_ms.saturating_sub(self.settled_ms) as f32 / HOUR_MS;
+        self.settled_ms = self.settled_ms.max(now_ms);
+        if hours <= 0.0 {
+            return;
+        }
+        for (stress, luma) in self.stress.iter_mut().zip(&self.shown) {
+            *stress += ink_density(*luma) * hours * DWELL_STRESS_PER_HOUR;
+        }
+    }
+
+    /// Record a refresh of `frame` with `mode` at `now_ms`.
+    ///
+    /// Full-clearing modes drive every pixel through their flashes; other
+    /// modes drive only pixels whose level changes.
+    pub fn record_refresh(&mut self, now_ms: u64, frame: &[Gray4], mode: WaveformMode) {
+        if self.started_ms.is_none() {
+            self.started_ms = Some(now_ms);
+            self.settled_ms = now_ms;
+        }
+        self.settle(now_ms);
+        self.refreshes = self.refreshes.saturating_add(1);
+
+        let flashes = f32::from(mode.flash_count().max(1));
+        let drives_all = mode.clears_ghosting();
+        for ((stress, shown), target) in self.stress.iter_mut().zip(&mut self.shown).zip(frame) {
+            let target = target.luma().min(3);
+            let swing = f32::from(shown.abs_diff(target)) / 3.0;
+            if drives_all || swing > 0.0 {
+                *stress += flashes * FLASH_STRESS + swing * DRIVE_STRESS;
+            }
+            *shown = target;
+        }
+    }
+
+    /// Length of the recorded session (first refresh to last settle).
+    pub fn observed_ms(&self) -> u64 {
+        self.started_ms
+            .map_or(0, |start| self.settled_ms.saturating_sub(start))
+    }
+
+    /// Refreshes recorded.
+    pub fn refreshes(&self) -> u64 {
+        self.refreshes
+    }
+
+    /// Accumulated stress, row-major.
+    pub fn stress(&self) -> &[f32] {
+        &self.stress
+    }
+
+    /// Panel size in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Project the recorded workload, repeated for `months`, onto the panel.
+    ///
+    /// An empty session (no time observed) projects no wear.
+    pub fn project(&self, months: f32) -> WearProjection {
+        let observed = self.observed_ms();
+        let scale = if observed == 0 {
+            0.0
+        } else {
+            months.max(0.0) * MONTH_MS as f32 / observed as f32
+        };
+        let projected: Vec<f32> = self.stress.iter().map(|s| s * scale).collect();
+        let means = neighbourhood_means(&projected, self.width, self.height);
+        let contrast = projected
+            .iter()
+            .zip(&means)
+            .map(|(s, mean)| (s - mean).abs() * LEVELS_PER_STRESS)
+            .collect();
+        WearProjection {
+            width: self.width,
+            height: self.height,
+            months,
+            observed_ms: observed,
+            refreshes: self.refreshes,
+            contrast,
+        }
+    }
+}
+
+/// Fraction of ink particles a pixel of `luma` (0 = black, 3 = white) holds up.
+fn ink_density(luma: u8) -> f32 {
+    f32::from(3u8.saturating_sub(luma)) / 3.0
+}
+
+/// Mean of every pixel's `(2r+1)²` neighbourhood, clipped at the panel edge.
+// SAFETY: indices are bounded by width/height via the summed-area table of
+// (width + 1) * (height + 1) entries; coordinates are clamped before use.
+#[allow(clippy::arithmetic_side_effects)]
+fn neighbourhood_means(values: &[f32], width: u32, height: u32) -> Vec<f32> {
+    let (w, h) = (width as usize, height as usize);
+    let stride = w + 1;
+    let mut table = vec![0.0f64; stride * (h + 1)];
+    for y in 0..h {
+        let mut row = 0.0f64;
+        for x in 0..w {
+            row += f64::from(values.get(y * w + x).copied().unwrap_or(0.0));
+            let above = table.get(y * stride + x + 1).copied().unwrap_or(0.0);
+            if let Some(cell) = table.get_mut((y + 1) * stride + x + 1) {
+                *cell = above + row;
+            }
+        }
+    }
+    let at = |x: usize, y: usize| table.get(y * stride + x).copied().unwrap_or(0.0);
+    let r = NEIGHBOURHOOD_RADIUS as usize;
+    let mut means = Vec::with_capacity(w * h);
+    for y in 0..h {
+        let (y0, y1) = (y.saturating_sub(r), (y + r + 1).min(h));
+        for x in 0..w {
+            let (x0, x1) = (x.saturating_sub(r), (x + r + 1).min(w));
+            let sum = at(x1, y1) - at(x0, y1) - at(x1, y0) + at(x0, y0);
+            let count = ((x1 - x0) * (y1 - y0)) as f64;
+            means.push((sum / count) as f32);
+        }
+    }
+    means
+}
+
+/// Retained-image contrast per pixel after a projected period of use.
+#[derive(Debug, Clone)]
+pub struct WearProjection {
+    width: u32,
+    height: u32,
+    months: f32,
+    observed_ms: u64,
+    refreshes: u64,
+    /// Contrast against the neighbourhood, in gray levels, row-major.
+    contrast: Vec<f32>,
+}
+
+impl WearProjection {
+    /// Projected contrast at `(x, y)`, in gray levels.
+    // SAFETY: x < width and y < height are checked; the index is bounded by width * height.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn contrast_at(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.contrast
+            .get(y as usize * self.width as usize + x as usize)
+            .copied()
+    }
+
+    /// Highest projected contrast on the panel.
+    pub fn max_contrast(&self) -> f32 {
+        self.contrast.iter().copied().fold(0.0, f32::max)
+    }
+
+    /// Pixels at or above [`VISIBLE_CONTRAST_LEVELS`].
+    pub fn visible_pixels(&self) -> usize {
+        self.contrast
+            .iter()
+            .filter(|c| **c >= VISIBLE_CONTRAST_LEVELS)
+            .count()
+    }
+
+    /// Summary for CI and design reviews.
+    // SAFETY: pixel coordinates derive from indices below width * height.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn report(&self) -> WearReport {
+        let max = self.max_contrast();
+        let width = self.width.max(1) as usize;
+        let mut hotspot: Option<WearRegion> = None;
+        for (i, _) in self
+            .contrast
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| **c >= VISIBLE_CONTRAST_LEVELS)
+        {
+            let (x, y) = ((i % width) as u32, (i / width) as u32);
+            hotspot = Some(match hotspot {
+                None => WearRegion {
+                    x,
+                    y,
+                    width: 1,
+                    height: 1,
+                },
+                Some(r) => {
+                    let (x0, y0) = (r.x.min(x), r.y.min(y));
+                    let (x1, y1) = ((r.x + r.width).max(x + 1), (r.y + r.height).max(y + 1));
+                    WearRegion {
+                        x: x0,
+                        y: y0,
+                        width: x1 - x0,
+                        height: y1 - y0,
+                    }
+                }
+            });
+        }
+        let visible = self.visible_pixels();
+        WearReport {
+            simulated_months: self.months,
+            observed_ms: self.observed_ms,
+            refreshes: self.refreshes,
+            max_contrast_levels: max,
+            visible_pixels: visible,
+            visible_fraction: visible as f32 / self.contrast.len().max(1) as f32,
+            // Contrast grows linearly with time under a repeated workload.
+            months_to_visible: (max > 0.0).then(|| self.months * VISIBLE_CONTRAST_LEVELS / max),
+            hotspot,
+        }
+    }
+
+    /// Heatmap of the projection: transparent where no artifact is expected,
+    /// yellow approaching visibility, red past it.
+    pub fn heatmap(&self) -> RgbaImage {
+        RgbaImage::from_fn(self.width, self.height, |x, y| {
+            heat_color(self.contrast_at(x, y).unwrap_or(0.0))
+        })
+    }
+
+    /// The heatmap blended over `base` (e.g. the current screen), for
+    /// judging which UI element causes an artifact.
+    // SAFETY: colour blending on u8 channels widened to u16; alpha ≤ 255.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn overlay(&self, base: &GrayImage) -> RgbaImage {
+        RgbaImage::from_fn(self.width, self.height, |x, y| {
+            let luma = if x < base.width() && y < base.height() {
+                base.get_pixel(x, y).0[0]
+            } else {
+                255
+            };
+            let Rgba([r, g, b, a]) = heat_color(self.contrast_at(x, y).unwrap_or(0.0));
+            let blend = |c: u8| {
+                ((u16::from(c) * u16::from(a) + u16::from(luma) * (255 - u16::from(a))) / 255) as u8
+            };
+            Rgba([blend(r), blend(g), blend(b), 255])
+        })
+    }
+}
+
+/// Heatmap colour for `contrast` gray levels.
+// SAFETY: float-to-u8 casts saturate; t is clamped to 0.0–1.0.
+#[allow(clippy::arithmetic_side_effects)]
+fn heat_color(contrast: f32) -> Rgba<u8> {
+    let t = (contrast / VISIBLE_CONTRAST_LEVELS).clamp(0.0, 1.0);
+    if t < 0.1 {
+        return Rgba([0, 0, 0, 0]);
+    }
+    let green = (255.0 * (1.0 - t)) as u8;
+    let alpha = (64.0 + 176.0 * t) as u8;
+    Rgba([255, green, 0, alpha])
+}
+
+/// Axis-aligned region of the panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WearRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Projected wear for a workload.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WearReport {
+    /// Projection horizon.
+    pub simulated_months: f32,
+    /// Length of the recorded session the projection repeats.
+    pub observed_ms: u64,
+    /// Refreshes in the recorded session.
+    pub refreshes: u64,
+    /// Strongest retained-image contrast, in gray levels.
+    pub max_contrast_levels: f32,
+    /// Pixels past [`VISIBLE_CONTRAST_LEVELS`].
+    pub visible_pixels: usize,
+    pub visible_fraction: f32,
+    /// When the first artifact becomes visible, if the workload wears the
+    /// panel unevenly at all.
+    pub months_to_visible: Option<f32>,
+    /// Bounding box of visible artifacts.
+    pub hotspot: Option<WearRegion>,
+}
+
+impl WearReport {
+    /// Serialize to pretty-printed JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+    use super::*;
+
+    const W: u32 = 64;
+    const H: u32 = 64;
+    const HOUR: u64 = 3_600_000;
+
+    /// White frame with a black bar across rows `top..top + 8`.
+    fn bar_frame(top: u32) -> Vec<Gray4> {
+        (0..W * H)
+            .map(|i| {
+                let y = i / W;
+                if (top..top + 8).contains(&y) {
+                    Gray4::BLACK
+                } else {
+                    Gray4::WHITE
+                }
+            })
+            .collect()
+    }
+
+    /// A day of hourly partial refreshes, the bar at `top(hour)`.
+    fn day(top: impl Fn(u64) -> u32) -> WearModel {
+        let mut wear = WearModel::new(W, H);
+        for hour in 0..24 {
+            wear.record_refresh(hour * HOUR, &bar_frame(top(hour)), WaveformMode::DU4);
+        }
+        wear.settle(24 * HOUR);
+        wear
+    }
+
+    #[test]
+    fn static_bar_burns_in_at_its_edges() {
+        let wear = day(|_| 20);
+        assert_eq!(wear.observed_ms(), 24 * HOUR);
+        assert_eq!(wear.refreshes(), 24);
+
+        let projection = wear.project(6.0);
+        // Edge of the bar against white background: strong contrast.
+        let edge = projection.contrast_at(32, 20).unwrap();
+        // Deep inside the white area: uniform, nothing retained.
+        let clear = projection.contrast_at(32, 50).unwrap();
+        assert!(edge >= VISIBLE_CONTRAST_LEVELS, "{edge}");
+        assert_eq!(clear, 0.0);
+
+        let report = projection.report();
+        let hotspot = report.hotspot.unwrap();
+        assert!(hotspot.y <= 20 && hotspot.y + hotspot.height >= 28);
+        let months = report.months_to_visible.unwrap();
+        assert!(months > 0.0 && months < 6.0, "{months}");
+    }
+
+    #[test]
+    fn contrast_scales_with_projection_horizon() {
+        let wear = day(|_| 20);
+        let one = wear.project(1.0).max_contrast();
+        let twelve = wear.project(12.0).max_contrast();
+        assert!((twelve / one - 12.0).abs() < 0.01, "{one} → {twelve}");
+        let soon = wear.project(1.0).report().months_to_visible.unwrap();
+        let late = wear.project(12.0).report().months_to_visible.unwrap();
+        assert!((soon - late).abs() < 0.01, "{soon} vs {late}");
+    }
+
+    #[test]
+    fn shifting_the_bar_reduces_retention() {
+        let fixed = day(|_| 20).project(6.0).report();
+        let shifted = day(|hour| 20 + (hour % 6) as u32 * 2).project(6.0).report();
+        assert!(
+            shifted.max_contrast_levels < fixed.max_contrast_levels * 0.75,
+            "{} vs {}",
+            shifted.max_contrast_levels,
+            fixed.max_contrast_levels
+        );
+        assert!(shifted.months_to_visible.unwrap() > fixed.months_to_visible.unwrap());
+    }
+
+    #[test]
+    fn uniform_content_and_empty_sessions_project_nothing() {
+        let mut wear = WearModel::new(W, H);
+        assert_eq!(wear.project(12.0).max_contrast(), 0.0);
+
+        let black = vec![Gray4::BLACK; (W * H) as usize];
+        wear.record_refresh(0, &black, WaveformMode::GC16);
+        wear.record_refresh(10 * HOUR, &black, WaveformMode::GC16);
+        let report = wear.project(12.0).report();
+        assert!(report.max_contrast_levels < 1e-3, "{report:?}");
+        assert_eq!(report.visible_pixels, 0);
+        assert_eq!(report.hotspot, None);
+    }
+
+    #[test]
+    fn heatmap_marks_visible_artifacts() {
+        let projection = day(|_| 20).project(6.0);
+        let heat = projection.heatmap();
+        assert_eq!(heat.dimensions(), (W, H));
+        assert_eq!(heat.get_pixel(32, 50).0[3], 0);
+        assert!(heat.get_pixel(32, 20).0[3] > 200);
+
+        let base = GrayImage::from_pixel(W, H, image::Luma([255]));
+        let overlay = projection.overlay(&base);
+        assert_eq!(overlay.get_pixel(32, 50).0, [255, 255, 255, 255]);
+        assert_eq!(overlay.get_pixel(32, 20).0[0], 255);
+        assert!(overlay.get_pixel(32, 20).0[2] < 128);
+    }
+}
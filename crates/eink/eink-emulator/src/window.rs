@@ -23,6 +23,7 @@
 //!   `_context` drops before `window`
 //!   `window` drops before `event_loop`
 
+use crate::commands::{CommandPalette, EmulatorCommand};
 use crate::config::Rotation;
 use crate::power::PowerStats;
 use softbuffer::{Context, Surface};
@@ -30,7 +31,7 @@ use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::Duration;
 use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::event::WindowEvent;
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, OwnedDisplayHandle};
 use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
@@ -316,6 +317,18 @@ pub struct Window {
     quirk_warning: Option<String>,
     #[cfg(feature = "debug")]
     debug_manager: Option<crate::debug::DebugManager>,
+    /// Snapshot of the emulator's render profiler, refreshed after each refresh.
+    #[cfg(feature = "debug")]
+    render_profiler: Option<crate::debug::RenderProfiler>,
+    /// Latest cache statistics reported through the emulator.
+    #[cfg(feature = "debug")]
+    caches: Vec<crate::debug::CacheReport>,
+    /// Latest background test run reported through the emulator.
+    #[cfg(feature = "debug")]
+    tests: Option<crate::debug::TestReport>,
+    /// Snapshot of interaction latency, refreshed after each refresh.
+    #[cfg(feature = "debug")]
+    latency: Option<platform::LatencyTracker>,
     /// Keyboard/scroll input queue (producer half). Populated by winit events.
     #[cfg(feature = "keyboard-input")]
     input_queue: Option<crate::input::InputQueue>,
@@ -324,6 +337,15 @@ pub struct Window {
     scroll_acc: f64,
     /// Last clean frame (no debug overlays) for re-presentation on hotkey press.
     last_rgba: Vec<u32>,
+    /// Most recent supported image dropped onto the window, not yet taken.
+    dropped_image: Option<std::path::PathBuf>,
+    /// F1 command palette.
+    palette: CommandPalette,
+    /// Emulator-level commands waiting for [`take_commands`](Self::take_commands).
+    pending_commands: Vec<EmulatorCommand>,
+    /// Set by [`run`](Self::run): no emulator is left to take commands.
+    blocking: bool,
+    modifiers: winit::keyboard::ModifiersState,
 }
 
 /// `Window` IS the run-phase ApplicationHandler — no separate EventHandler needed.
@@ -360,6 +382,27 @@ impl ApplicationHandler for Window {
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
+        // Command palette and its shortcuts take precedence over debug
+        // hotkeys and app input while the palette is open.
+        if let WindowEvent::ModifiersChanged(m) = &event {
+            self.modifiers = m.state();
+        }
+        if let WindowEvent::KeyboardInput {
+            event:
+                winit::event::KeyEvent {
+                    physical_key: winit::keyboard::PhysicalKey::Code(code),
+                    state,
+                    ..
+                },
+            ..
+        } = &event
+        {
+            if self.handle_command_key(*code, *state == winit::event::ElementState::Pressed) {
+                self.re_present();
+                return;
+            }
+        }
+
         #[cfg(feature = "debug")]
         {
             let consumed = if let Some(ref mut dm) = self.debug_manager {
@@ -510,6 +553,14 @@ impl ApplicationHandler for Window {
                     }
                 }
             }
+            // Drag-and-drop: remember the latest PNG for the emulator to load.
+            WindowEvent::DroppedFile(path) => {
+                if crate::mockup::is_supported(&path) {
+                    self.dropped_image = Some(path);
+                } else {
+                    eprintln!("Ignoring dropped file (PNG only): {}", path.display());
+                }
+            }
             // Update cursor icon: pointer when over the panel or an inspectable component.
             // handle_event() already stored the position in dm.cursor_pos() above.
             WindowEvent::CursorMoved { .. } => {
@@ -662,11 +713,24 @@ impl Window {
             quirk_warning: None,
             #[cfg(feature = "debug")]
             debug_manager: None,
+            #[cfg(feature = "debug")]
+            render_profiler: None,
+            #[cfg(feature = "debug")]
+            caches: Vec::new(),
+            #[cfg(feature = "debug")]
+            tests: None,
+            #[cfg(feature = "debug")]
+            latency: None,
             #[cfg(feature = "keyboard-input")]
             input_queue: None,
             #[cfg(feature = "keyboard-input")]
             scroll_acc: 0.0,
             last_rgba: Vec::new(),
+            dropped_image: None,
+            palette: CommandPalette::new(),
+            pending_commands: Vec::new(),
+            blocking: false,
+            modifiers: winit::keyboard::ModifiersState::empty(),
         };
 
         obj.update_title();
@@ -728,7 +792,6 @@ impl Window {
 
     /// Re-present the last clean frame with the current debug overlay state.
     /// Called immediately after a debug hotkey toggles state.
-    #[cfg(feature = "debug")]
     fn re_present(&mut self) {
         #[cfg(feature = "debug")]
         self.sync_window_width();
@@ -872,6 +935,7 @@ impl Window {
         let mut rgba = self.last_rgba.clone();
         #[cfg(feature = "debug")]
         self.apply_debug_overlays(&mut rgba);
+        self.palette.render(&mut rgba, self.disp_w, self.disp_h);
         self.present_internal(&rgba);
     }
 
@@ -989,6 +1053,7 @@ impl Window {
                     crate::config::Rotation::Degrees180 => 180,
                     crate::config::Rotation::Degrees270 => 270,
                 };
+                let log = dm.log_snapshot();
                 let info = crate::debug::PanelInfo {
                     state,
                     disp_w: self.disp_w,
@@ -998,6 +1063,11 @@ impl Window {
                     temperature: self.temperature,
                     power_graph: Some(dm.power_graph()),
                     power_stats: self.power_stats.as_ref(),
+                    render_profiler: self.render_profiler.as_ref(),
+                    caches: &self.caches,
+                    tests: self.tests.as_ref(),
+                    latency: self.latency.as_ref(),
+                    log: log.as_ref(),
                 };
                 crate::debug::panel::render_into(&mut panel_buf, PANEL_W, panel_h, &info);
 
@@ -1034,6 +1104,7 @@ impl Window {
 
     /// Enter the blocking event loop (blocks until window is closed).
     pub fn run(mut self) {
+        self.blocking = true;
         if let Some(event_loop) = self.event_loop.take() {
             // Wait: sleep when idle; wake on events.  Prevents 100% CPU spin.
             event_loop.set_control_flow(ControlFlow::Wait);
@@ -1049,11 +1120,42 @@ impl Window {
         self.update_title();
     }
 
+    #[cfg(feature = "debug")]
+    pub fn set_render_profiler(&mut self, profiler: &crate::debug::RenderProfiler) {
+        self.render_profiler = Some(profiler.clone());
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn set_latency(&mut self, latency: platform::LatencyTracker) {
+        self.latency = Some(latency);
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn report_cache(&mut self, report: crate::debug::CacheReport) {
+        crate::debug::upsert_cache_report(&mut self.caches, report);
+    }
+
+    #[cfg(feature = "debug")]
+    pub fn report_tests(&mut self, report: Option<crate::debug::TestReport>) {
+        self.tests = report;
+    }
+
     pub fn set_temperature(&mut self, temp: i8) {
         self.temperature = temp;
         self.update_title();
     }
 
+    /// Outer window position in physical pixels, or `None` where the
+    /// platform does not report one (Wayland).
+    pub fn outer_position(&self) -> Option<(i32, i32)> {
+        self.window.outer_position().ok().map(|p| (p.x, p.y))
+    }
+
+    /// Move the window; ignored where the platform does not allow it.
+    pub fn set_outer_position(&mut self, (x, y): (i32, i32)) {
+        self.window.set_outer_position(PhysicalPosition::new(x, y));
+    }
+
     pub fn set_quirk_warning(&mut self, warning: Option<&str>) {
         self.quirk_warning = warning.map(str::to_string);
         self.update_title();
@@ -1074,6 +1176,122 @@ impl Window {
         self.input_queue = Some(iq);
     }
 
+    /// Take the most recent PNG dropped onto the window since the last call.
+    pub fn take_dropped_image(&mut self) -> Option<std::path::PathBuf> {
+        self.dropped_image.take()
+    }
+
+    /// Take emulator-level commands chosen since the last call.
+    pub fn take_commands(&mut self) -> Vec<EmulatorCommand> {
+        std::mem::take(&mut self.pending_commands)
+    }
+
+    // --- Command palette ------------------------------------------------------
+
+    /// Handle a key for the command palette.  Returns `true` if consumed.
+    ///
+    /// While the palette is open it swallows every key; otherwise only F1,
+    /// Ctrl+P and the command shortcuts are taken.  Ctrl+1/2/3 are left to
+    /// the debug manager, which already owns them.
+    fn handle_command_key(&mut self, code: winit::keyboard::KeyCode, pressed: bool) -> bool {
+        use winit::keyboard::KeyCode;
+
+        if self.palette.is_open() {
+            if pressed {
+                match code {
+                    KeyCode::ArrowUp => self.palette.select_previous(),
+                    KeyCode::ArrowDown => self.palette.select_next(),
+                    KeyCode::Enter | KeyCode::NumpadEnter => {
+                        if let Some(cmd) = self.palette.activate() {
+                            self.run_command(cmd);
+                        }
+                    }
+                    KeyCode::Escape | KeyCode::F1 => self.palette.close(),
+                    _ => {}
+                }
+            }
+            return true;
+        }
+
+        let ctrl = self.modifiers.control_key() || self.modifiers.super_key();
+        if code == KeyCode::F1 || (ctrl && code == KeyCode::KeyP) {
+            if pressed {
+                self.palette.open();
+            }
+            return true;
+        }
+        if !ctrl {
+            return false;
+        }
+        let cmd = match code {
+            KeyCode::KeyS => EmulatorCommand::SaveScreenshot,
+            KeyCode::BracketRight => EmulatorCommand::TemperatureUp,
+            KeyCode::BracketLeft => EmulatorCommand::TemperatureDown,
+            KeyCode::KeyW => EmulatorCommand::CycleWaveformMode,
+            KeyCode::KeyG => EmulatorCommand::ResetGhosting,
+            KeyCode::KeyD => EmulatorCommand::NextDisplaySpec,
+            _ => return false,
+        };
+        if pressed {
+            self.run_command(cmd);
+        }
+        true
+    }
+
+    /// Run a window-local command, or queue it for the emulator.
+    fn run_command(&mut self, cmd: EmulatorCommand) {
+        match cmd {
+            EmulatorCommand::SaveScreenshot => self.save_screenshot(),
+            #[cfg(feature = "debug")]
+            EmulatorCommand::TogglePanel
+            | EmulatorCommand::ToggleBorders
+            | EmulatorCommand::ToggleInspector => {
+                if let Some(ref mut dm) = self.debug_manager {
+                    let state = dm.state_mut();
+                    match cmd {
+                        EmulatorCommand::TogglePanel => state.toggle_panel(),
+                        EmulatorCommand::ToggleBorders => state.toggle_borders(),
+                        _ => state.toggle_inspector(),
+                    }
+                }
+            }
+            _ if cmd.is_window_local() => {}
+            _ if self.blocking => eprintln!(
+                "⚠️  \"{}\" changes emulator state and needs a pump_window_events() loop; \
+                 it is unavailable after Emulator::run().",
+                cmd.label()
+            ),
+            _ => self.pending_commands.push(cmd),
+        }
+    }
+
+    /// Save the last presented frame (without overlays) as a timestamped PNG
+    /// in the working directory.
+    // SAFETY: channel extraction from ARGB pixels; truncating casts are intended.
+    #[allow(clippy::cast_possible_truncation)]
+    fn save_screenshot(&self) {
+        if self.last_rgba.is_empty() {
+            return;
+        }
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = format!("eink-screenshot-{secs}.png");
+        let img = image::RgbImage::from_fn(self.disp_w, self.disp_h, |x, y| {
+            let px = self
+                .last_rgba
+                .get(y.saturating_mul(self.disp_w).saturating_add(x) as usize)
+                .copied()
+                .unwrap_or(0);
+            image::Rgb([(px >> 16) as u8, (px >> 8) as u8, px as u8])
+        });
+        match img.save(&path) {
+            Ok(()) => eprintln!("Saved screenshot: {path}"),
+            Err(e) => eprintln!("⚠️  Cannot save screenshot {path}: {e}"),
+        }
+    }
+
     fn update_title(&self) {
         let temp_warn = if self.temperature < 5 || self.temperature > 35 {
             " ⚠ OUTSIDE OPTIMAL RANGE"
@@ -1101,6 +1319,7 @@ impl Window {
                 self.temperature, temp_warn, quirk
             ),
         };
+        let title = format!("{title} | F1: commands");
 
         self.window.set_title(&title);
     }
@@ -0,0 +1,74 @@
+//! Integration tests for dual-display emulation (shared clock + combined layout)
+
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::arithmetic_side_effects,
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::indexing_slicing
+)]
+
+use eink_emulator::{DisplayDriver, MultiEmulator, MultiLayout, Panel};
+use eink_specs::displays::{GDEM0397T81P, WAVESHARE_2_13_V4};
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+fn dual(layout: MultiLayout) -> MultiEmulator {
+    MultiEmulator::headless(&GDEM0397T81P, &WAVESHARE_2_13_V4, layout)
+}
+
+#[test]
+fn test_side_by_side_canvas_size() {
+    let multi = dual(MultiLayout::SideBySide { gap: 10 });
+    let (pw, ph) = (GDEM0397T81P.width, GDEM0397T81P.height);
+    let (sw, sh) = (WAVESHARE_2_13_V4.width, WAVESHARE_2_13_V4.height);
+    assert_eq!(multi.canvas_size(), (pw + 10 + sw, ph.max(sh)));
+    assert_eq!(multi.panel_origin(Panel::Secondary), (pw + 10, 0));
+}
+
+#[test]
+fn test_stacked_canvas_size() {
+    let multi = dual(MultiLayout::Stacked { gap: 4 });
+    let (pw, ph) = (GDEM0397T81P.width, GDEM0397T81P.height);
+    let (sw, sh) = (WAVESHARE_2_13_V4.width, WAVESHARE_2_13_V4.height);
+    assert_eq!(multi.canvas_size(), (pw.max(sw), ph + 4 + sh));
+    assert_eq!(multi.panel_origin(Panel::Secondary), (0, ph + 4));
+}
+
+#[tokio::test]
+async fn test_refreshes_share_one_virtual_clock() {
+    let mut multi = dual(MultiLayout::SideBySide { gap: 0 });
+    assert_eq!(multi.clock().now_ms(), 0);
+
+    multi.primary_mut().refresh_full().await.unwrap();
+    let after_primary = multi.clock().now_ms();
+    assert!(after_primary > 0, "full refresh must advance virtual time");
+
+    multi.secondary_mut().refresh_partial().await.unwrap();
+    let after_secondary = multi.clock().now_ms();
+    assert!(after_secondary > after_primary);
+
+    let primary_clock = multi.primary().virtual_clock().unwrap().now_ms();
+    let secondary_clock = multi.secondary().virtual_clock().unwrap().now_ms();
+    assert_eq!(primary_clock, secondary_clock);
+}
+
+#[tokio::test]
+async fn test_composite_places_secondary_content_at_origin() {
+    let mut multi = dual(MultiLayout::Stacked { gap: 2 });
+
+    Rectangle::new(Point::zero(), Size::new(4, 4))
+        .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+        .draw(multi.panel_mut(Panel::Secondary))
+        .unwrap();
+    multi.secondary_mut().refresh_full().await.unwrap();
+
+    let (cw, _) = multi.canvas_size();
+    let (ox, oy) = multi.panel_origin(Panel::Secondary);
+    let rgba = multi.composite_rgba();
+    let black = 0xFF00_0000;
+    assert_eq!(rgba[(oy * cw + ox) as usize], black);
+    // Gap row directly above the secondary panel is background, not content.
+    assert_ne!(rgba[((oy - 1) * cw + ox) as usize], black);
+}
@@ -100,6 +100,38 @@ fn test_enable_auto_dirty_tracking() {
     assert!(!emulator.is_auto_dirty_tracking_enabled());
 }
 
+#[tokio::test]
+async fn test_auto_dirty_tracking_diffs_framebuffer() {
+    use eink_emulator::DisplayDriver;
+
+    let mut emulator = Emulator::headless(250, 122);
+    emulator.enable_auto_dirty_tracking(true);
+
+    Rectangle::new(Point::new(10, 20), Size::new(4, 4))
+        .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+        .draw(&mut emulator)
+        .unwrap();
+    emulator.update_buffer().await.unwrap();
+    assert_eq!(
+        emulator.dirty_regions(),
+        [Rectangle::new(Point::new(8, 16), Size::new(8, 8))]
+    );
+
+    // Nothing drawn since the last transfer: nothing new is dirty.
+    emulator.clear_dirty();
+    emulator.update_buffer().await.unwrap();
+    assert!(emulator.dirty_regions().is_empty());
+
+    // Disabled: changes are not tracked.
+    emulator.enable_auto_dirty_tracking(false);
+    Rectangle::new(Point::new(40, 40), Size::new(2, 2))
+        .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+        .draw(&mut emulator)
+        .unwrap();
+    emulator.update_buffer().await.unwrap();
+    assert!(emulator.dirty_regions().is_empty());
+}
+
 // Note: This test is commented out to avoid stack overflow in the test runner
 // The refresh_partial_window function works correctly, but the async test
 // infrastructure causes issues in this specific configuration.
@@ -157,6 +189,31 @@ fn test_partial_window_creation() {
     assert_eq!(window.aligned_rect.size, Size::new(16, 16));
 }
 
+#[test]
+fn test_partial_window_uses_spec_controller_alignment() {
+    // Headless default is the Waveshare 2.13" V4 (SSD1680): X in bytes, Y per gate.
+    let emulator = Emulator::headless(250, 122);
+    let align = emulator.partial_alignment();
+    assert_eq!(align, eink_specs::Controller::SSD1680.partial_alignment());
+
+    let window = emulator.partial_window(Rectangle::new(Point::new(10, 10), Size::new(20, 5)));
+
+    assert!(window.was_aligned);
+    assert_eq!(window.aligned_rect.top_left, Point::new(8, 10));
+    assert_eq!(window.aligned_rect.size, Size::new(24, 5));
+}
+
+#[test]
+fn test_partial_window_aligned_for_controller_is_untouched() {
+    let emulator = Emulator::headless_with_spec(&eink_specs::displays::GDEM0397T81P);
+    let rect = Rectangle::new(Point::new(16, 3), Size::new(8, 7));
+
+    let window = emulator.partial_window(rect);
+
+    assert!(!window.was_aligned);
+    assert_eq!(window.aligned_rect, rect);
+}
+
 #[test]
 fn test_dirty_tracking_with_drawing() {
     let mut emulator = Emulator::headless(250, 122);
@@ -12,11 +12,11 @@
     clippy::unwrap_used,
     clippy::expect_used,
     clippy::panic,
-    clippy::indexing_slicing,
+    clippy::indexing_slicing
 )]
 
 use eink_emulator::{DisplayDriver, Emulator};
-use eink_specs::{quirks_for_controller, ColorMode, Controller, DisplaySpec, PanelType};
+use eink_specs::{quirks_for_controller, ColorMode, Controller, DisplayMs, DisplaySpec, PanelType};
 
 /// Create test display spec with specific controller
 fn test_spec_with_controller(controller: Controller) -> DisplaySpec {
@@ -28,9 +28,9 @@ fn test_spec_with_controller(controller: Controller) -> DisplaySpec {
         panel_type: PanelType::Carta1000,
         color_mode: Some(ColorMode::Grayscale),
         grayscale_levels: 4,
-        full_refresh_ms: 2000,
-        partial_refresh_ms: 300,
-        fast_refresh_ms: 260,
+        full_refresh_ms: DisplayMs::new(2000),
+        partial_refresh_ms: DisplayMs::new(300),
+        fast_refresh_ms: DisplayMs::new(260),
         ghosting_rate_partial: 0.15,
         ghosting_rate_fast: 0.25,
         flash_count_full: 3,
@@ -261,3 +261,97 @@ fn test_all_quirks_have_type_names() {
     };
     assert_eq!(quirk3.quirk_type(), "PanelSpecific");
 }
+
+#[tokio::test]
+async fn test_spi_hang_recovered_by_workaround() {
+    use eink_emulator::VirtualClock;
+    use eink_specs::{Recovery, Workaround};
+
+    let spec = test_spec_with_controller(Controller::UC8151);
+    let spec_ref: &'static DisplaySpec = Box::leak(Box::new(spec));
+    let mut emulator = Emulator::headless_with_spec(spec_ref);
+    emulator.set_virtual_clock(VirtualClock::new());
+    emulator.initialize().await.unwrap();
+    let before = emulator.now_ms();
+
+    let applied = emulator
+        .check_quirks_with_workarounds("spi_write")
+        .await
+        .unwrap();
+    assert!(matches!(
+        applied,
+        Some(Workaround::RetryWithRecovery {
+            recovery: Recovery::HardwareReset { .. },
+            ..
+        })
+    ));
+    // Reset cleared the hang and re-ran init.
+    assert!(emulator.active_quirk().is_none());
+    assert!(emulator.init_state().is_ready());
+    assert!(emulator.now_ms() > before);
+
+    assert_eq!(
+        emulator
+            .check_quirks_with_workarounds("update_buffer")
+            .await,
+        Ok(None)
+    );
+}
+
+#[tokio::test]
+async fn test_rotation_glitch_workaround_forces_full_refresh() {
+    let spec = test_spec_with_controller(Controller::UC8151);
+    let spec_ref: &'static DisplaySpec = Box::leak(Box::new(spec));
+    let mut emulator = Emulator::headless_with_spec(spec_ref);
+    emulator.set_virtual_clock(eink_emulator::VirtualClock::new());
+
+    emulator
+        .check_quirks_with_workarounds("rotation")
+        .await
+        .unwrap();
+    assert_eq!(emulator.stats().full_refresh_count.get(), 1);
+}
+
+#[tokio::test]
+async fn test_partial_lut_drift_workaround() {
+    use eink_specs::{Quirk, Workaround};
+
+    let spec = test_spec_with_controller(Controller::SSD1680);
+    let spec_ref: &'static DisplaySpec = Box::leak(Box::new(spec));
+    let mut emulator = Emulator::headless_with_spec(spec_ref);
+    emulator.set_virtual_clock(eink_emulator::VirtualClock::new());
+
+    let workaround = Controller::SSD1680
+        .quirks()
+        .iter()
+        .map(Quirk::workaround)
+        .find(|w| matches!(w, Workaround::FullRefreshEvery { .. }))
+        .unwrap();
+    let Workaround::FullRefreshEvery { max_partials } = workaround else {
+        unreachable!()
+    };
+
+    emulator.refresh_full().await.unwrap();
+    for _ in 1..max_partials {
+        emulator.refresh_partial().await.unwrap();
+        emulator.apply_workaround(workaround).await.unwrap();
+    }
+    assert_eq!(emulator.stats().full_refresh_count.get(), 1);
+
+    emulator.refresh_partial().await.unwrap();
+    emulator.apply_workaround(workaround).await.unwrap();
+    assert_eq!(emulator.stats().full_refresh_count.get(), 2);
+}
+
+#[tokio::test]
+async fn test_workarounds_idle_when_quirks_disabled() {
+    let spec = test_spec_with_controller(Controller::UC8151);
+    let spec_ref: &'static DisplaySpec = Box::leak(Box::new(spec));
+    let mut emulator = Emulator::headless_with_spec(spec_ref);
+
+    emulator.disable_quirks();
+    assert_eq!(
+        emulator.check_quirks_with_workarounds("rotation").await,
+        Ok(None)
+    );
+}
@@ -338,16 +338,16 @@ fn test_waveform_mode_color_support() {
 #[test]
 fn test_color_refresh_durations() {
     // Spectra 6 (GCC16) - 15 seconds
-    assert_eq!(WaveformMode::GCC16.color_refresh_duration_ms(), 15000);
-    assert_eq!(WaveformMode::GCC16.base_duration_ms(), 15000);
+    assert_eq!(WaveformMode::GCC16.color_refresh_duration_ms().get(), 15000);
+    assert_eq!(WaveformMode::GCC16.base_duration_ms().get(), 15000);
 
     // Kaleido 3 (GCU) - 500ms
-    assert_eq!(WaveformMode::GCU.color_refresh_duration_ms(), 500);
-    assert_eq!(WaveformMode::GCU.base_duration_ms(), 500);
+    assert_eq!(WaveformMode::GCU.color_refresh_duration_ms().get(), 500);
+    assert_eq!(WaveformMode::GCU.base_duration_ms().get(), 500);
 
     // Non-color modes return 0
-    assert_eq!(WaveformMode::GC16.color_refresh_duration_ms(), 0);
-    assert_eq!(WaveformMode::DU4.color_refresh_duration_ms(), 0);
+    assert_eq!(WaveformMode::GC16.color_refresh_duration_ms().get(), 0);
+    assert_eq!(WaveformMode::DU4.color_refresh_duration_ms().get(), 0);
 }
 
 #[test]
@@ -542,10 +542,10 @@ fn test_color_mode_clear() {
 #[test]
 fn test_color_refresh_is_slower() {
     // Verify that color refresh modes are significantly slower than B&W
-    let gcc16_duration = WaveformMode::GCC16.base_duration_ms();
-    let gcu_duration = WaveformMode::GCU.base_duration_ms();
-    let gc16_duration = WaveformMode::GC16.base_duration_ms();
-    let du4_duration = WaveformMode::DU4.base_duration_ms();
+    let gcc16_duration = WaveformMode::GCC16.base_duration_ms().get();
+    let gcu_duration = WaveformMode::GCU.base_duration_ms().get();
+    let gc16_duration = WaveformMode::GC16.base_duration_ms().get();
+    let du4_duration = WaveformMode::DU4.base_duration_ms().get();
 
     // Spectra 6 (GCC16) should be much slower than any B&W mode
     assert!(
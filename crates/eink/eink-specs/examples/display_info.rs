@@ -4,6 +4,9 @@
 //!
 //! Run with: cargo run --example display_info
 
+// Example code: allow debug output.
+#![allow(clippy::use_debug)]
+
 use eink_specs::displays::*;
 use eink_specs::DisplaySpec;
 
@@ -22,11 +25,11 @@ fn print_display_info(spec: &DisplaySpec) {
 
     println!("\n⏱️  Refresh Timing:");
     println!(
-        "  Full Refresh: {}ms ({} flashes)",
+        "  Full Refresh: {} ({} flashes)",
         spec.full_refresh_ms, spec.flash_count_full
     );
-    println!("  Partial Refresh: {}ms", spec.partial_refresh_ms);
-    println!("  Fast Refresh: {}ms", spec.fast_refresh_ms);
+    println!("  Partial Refresh: {}", spec.partial_refresh_ms);
+    println!("  Fast Refresh: {}", spec.fast_refresh_ms);
 
     println!("\n👻 Ghosting Rates:");
     println!(
@@ -50,15 +53,15 @@ fn print_display_info(spec: &DisplaySpec) {
 
     println!("\n❄️  Temperature Compensation:");
     println!(
-        "  At 25°C: {}ms (normal)",
+        "  At 25°C: {} (normal)",
         spec.adjusted_refresh_ms(spec.full_refresh_ms, 25)
     );
     println!(
-        "  At -5°C: {}ms (50% slower)",
+        "  At -5°C: {} (50% slower)",
         spec.adjusted_refresh_ms(spec.full_refresh_ms, -5)
     );
     println!(
-        "  At 45°C: {}ms (20% slower)",
+        "  At 45°C: {} (20% slower)",
         spec.adjusted_refresh_ms(spec.full_refresh_ms, 45)
     );
 }
@@ -19,6 +19,16 @@
 //!   (Source: GitHub issues with epd-waveshare)
 //! - UC8151: Display rotation glitch, SPI write hangs
 //!   (Source: Community forums, Waveshare wiki)
+//! - SSD1680 / SSD1677: OTP partial LUT drift, deep sleep only exits on
+//!   hardware reset (Source: Waveshare wiki, Solomon Systech datasheets)
+//!
+//! # Workarounds
+//!
+//! Every quirk maps to a typed [`Workaround`] via [`Quirk::workaround`].
+//! Drivers execute it (retry with a reset, force a full refresh after N
+//! partials, poll BUSY instead of sleeping a fixed time, ...); the emulator
+//! interprets the same value, so a workaround is exercised on the desktop
+//! before it runs on a panel.
 
 use crate::Controller;
 
@@ -62,6 +72,77 @@ pub enum Quirk {
     /// The IT8951 has limited support in common e-ink libraries, requiring
     /// custom drivers or workarounds.
     LimitedLibrarySupport { description: &'static str },
+
+    /// SSD1680 / SSD1677: the OTP partial-update LUT has no DC-balancing
+    /// phase, so consecutive partial refreshes drift (ghosting, grey haze).
+    ///
+    /// `max_partials` is the vendor-recommended number of partial refreshes
+    /// between full refreshes.
+    PartialLutDrift {
+        max_partials: u8,
+        description: &'static str,
+    },
+
+    /// SSD1680 / SSD1677: deep sleep mode 1 holds BUSY high and ignores SPI
+    /// until RST is pulsed; a software reset does not wake the controller.
+    DeepSleepNeedsReset { description: &'static str },
+}
+
+/// How a controller is brought back to a known state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Pulse RST low for `low_ms`, wait `settle_ms`, then re-run the full
+    /// init sequence (the reset clears every register).
+    HardwareReset { low_ms: u16, settle_ms: u16 },
+    /// Send the software-reset command, wait for BUSY, then re-run init.
+    SoftReset,
+}
+
+impl Recovery {
+    /// RST pulse used by the Waveshare and Good Display reference drivers.
+    pub const HARDWARE_RESET: Self = Self::HardwareReset {
+        low_ms: 2,
+        settle_ms: 20,
+    };
+}
+
+/// Typed strategy a driver executes to avoid or recover from a [`Quirk`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Workaround {
+    /// Retry an operation that timed out up to `attempts` more times,
+    /// running `recovery` before each retry.
+    RetryWithRecovery { attempts: u8, recovery: Recovery },
+    /// After the triggering operation, run `recovery` and make the next
+    /// refresh a full one (partial updates over stale registers garble).
+    RecoverThenFullRefresh { recovery: Recovery },
+    /// Refresh duration is not predictable: poll BUSY for up to
+    /// `timeout_ms` instead of waiting a fixed time.
+    PollBusy { timeout_ms: u32 },
+    /// Force a full refresh once `max_partials` partial refreshes have run
+    /// since the last full one.
+    FullRefreshEvery { max_partials: u8 },
+    /// Wake from sleep with `recovery` (never by sending commands).
+    WakeWithReset { recovery: Recovery },
+    /// Panel-specific VCOM and flash parameters must be loaded before init;
+    /// the controller defaults are wrong for most panels.
+    ConfigureBeforeInit,
+    /// Nothing to do at run time; the quirk is informational.
+    None,
+}
+
+impl Workaround {
+    /// The recovery sequence this workaround runs, if any.
+    pub const fn recovery(&self) -> Option<Recovery> {
+        match *self {
+            Self::RetryWithRecovery { recovery, .. }
+            | Self::RecoverThenFullRefresh { recovery }
+            | Self::WakeWithReset { recovery } => Some(recovery),
+            Self::PollBusy { .. }
+            | Self::FullRefreshEvery { .. }
+            | Self::ConfigureBeforeInit
+            | Self::None => None,
+        }
+    }
 }
 
 impl Quirk {
@@ -73,6 +154,8 @@ impl Quirk {
             Quirk::RotationGlitch { description } => description,
             Quirk::SpiWriteHang { description } => description,
             Quirk::LimitedLibrarySupport { description } => description,
+            Quirk::PartialLutDrift { description, .. } => description,
+            Quirk::DeepSleepNeedsReset { description } => description,
         }
     }
 
@@ -84,6 +167,33 @@ impl Quirk {
             Quirk::RotationGlitch { .. } => "RotationGlitch",
             Quirk::SpiWriteHang { .. } => "SpiWriteHang",
             Quirk::LimitedLibrarySupport { .. } => "LimitedLibrarySupport",
+            Quirk::PartialLutDrift { .. } => "PartialLutDrift",
+            Quirk::DeepSleepNeedsReset { .. } => "DeepSleepNeedsReset",
+        }
+    }
+
+    /// The strategy a driver should execute for this quirk
+    pub const fn workaround(&self) -> Workaround {
+        match *self {
+            // Reference drivers re-run the reset + init sequence; three
+            // attempts clear the hang on every panel seen so far.
+            Quirk::SpiWriteHang { .. } => Workaround::RetryWithRecovery {
+                attempts: 3,
+                recovery: Recovery::HARDWARE_RESET,
+            },
+            Quirk::RotationGlitch { .. } => Workaround::RecoverThenFullRefresh {
+                recovery: Recovery::HARDWARE_RESET,
+            },
+            // Twice the slowest OTP full refresh observed at 0°C.
+            Quirk::UncontrollableRefreshRate { .. } => Workaround::PollBusy { timeout_ms: 6_000 },
+            Quirk::PartialLutDrift { max_partials, .. } => {
+                Workaround::FullRefreshEvery { max_partials }
+            }
+            Quirk::DeepSleepNeedsReset { .. } => Workaround::WakeWithReset {
+                recovery: Recovery::HARDWARE_RESET,
+            },
+            Quirk::PanelSpecific { .. } => Workaround::ConfigureBeforeInit,
+            Quirk::LimitedLibrarySupport { .. } => Workaround::None,
         }
     }
 }
@@ -96,8 +206,9 @@ pub const fn quirks_for_controller(controller: Controller) -> &'static [Quirk] {
         Controller::UC8151 => UC8151_QUIRKS,
         Controller::IL0373 => IL0373_QUIRKS,
         Controller::ACeP => ACEP_QUIRKS,
-        Controller::SSD1619 => &[],  // No known quirks
-        Controller::SSD1677 => &[],  // No known quirks (reliable controller)
+        Controller::SSD1619 => &[], // No known quirks
+        Controller::UC8176 => &[],  // No known quirks
+        Controller::SSD1677 => SSD1677_QUIRKS,
         Controller::ED075TC1 => &[], // No known quirks
         Controller::GDEW => &[],     // No known quirks
         Controller::Generic => &[],  // Generic controller
@@ -117,10 +228,35 @@ const IT8951_QUIRKS: &[Quirk] = &[
 ];
 
 /// SSD1680 controller quirks
-const SSD1680_QUIRKS: &[Quirk] = &[Quirk::UncontrollableRefreshRate {
-    description: "SSD1680 refresh timing can be inconsistent with certain driver implementations. \
-                 Actual refresh time may vary from specified values.",
-}];
+const SSD1680_QUIRKS: &[Quirk] = &[
+    Quirk::UncontrollableRefreshRate {
+        description:
+            "SSD1680 refresh timing can be inconsistent with certain driver implementations. \
+                     Actual refresh time may vary from specified values.",
+    },
+    Quirk::PartialLutDrift {
+        max_partials: 5,
+        description: "SSD1680 OTP partial LUT is not DC balanced. \
+                     Waveshare recommends a full refresh after every 5 partial refreshes.",
+    },
+    Quirk::DeepSleepNeedsReset {
+        description: "SSD1680 deep sleep ignores SPI until RST is pulsed. \
+                     Wake with a hardware reset and re-init.",
+    },
+];
+
+/// SSD1677 controller quirks (Good Display GDEM0397T81P and friends)
+const SSD1677_QUIRKS: &[Quirk] = &[
+    Quirk::PartialLutDrift {
+        max_partials: 30,
+        description: "SSD1677 OTP partial LUT drifts over long partial runs. \
+                     Force a full refresh after 30 partial refreshes.",
+    },
+    Quirk::DeepSleepNeedsReset {
+        description: "SSD1677 deep sleep holds BUSY high until RST is pulsed. \
+                     Wake with a hardware reset and re-init.",
+    },
+];
 
 /// UC8151 controller quirks
 const UC8151_QUIRKS: &[Quirk] = &[
@@ -198,8 +334,64 @@ mod tests {
     #[test]
     fn test_ssd1680_quirks() {
         let quirks = quirks_for_controller(Controller::SSD1680);
-        assert_eq!(quirks.len(), 1);
+        assert_eq!(quirks.len(), 3);
         assert!(matches!(quirks[0], Quirk::UncontrollableRefreshRate { .. }));
+        assert!(quirks.iter().any(|q| matches!(
+            q,
+            Quirk::PartialLutDrift {
+                max_partials: 5,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn test_ssd1677_quirks() {
+        let quirks = quirks_for_controller(Controller::SSD1677);
+        assert_eq!(quirks.len(), 2);
+        assert!(quirks
+            .iter()
+            .any(|q| q.workaround() == Workaround::FullRefreshEvery { max_partials: 30 }));
+        assert!(quirks
+            .iter()
+            .any(|q| matches!(q.workaround(), Workaround::WakeWithReset { .. })));
+    }
+
+    #[test]
+    fn test_spi_hang_workaround_retries_with_reset() {
+        let quirk = Quirk::SpiWriteHang { description: "" };
+        let workaround = quirk.workaround();
+        assert!(matches!(
+            workaround,
+            Workaround::RetryWithRecovery { attempts, .. } if attempts > 0
+        ));
+        assert_eq!(workaround.recovery(), Some(Recovery::HARDWARE_RESET));
+    }
+
+    #[test]
+    fn test_every_quirk_has_a_workaround() {
+        let all = [
+            Controller::IT8951,
+            Controller::SSD1680,
+            Controller::SSD1677,
+            Controller::UC8151,
+            Controller::UC8176,
+            Controller::ACeP,
+        ];
+        for quirk in all.iter().flat_map(Controller::quirks) {
+            let workaround = quirk.workaround();
+            match quirk {
+                Quirk::LimitedLibrarySupport { .. } => assert_eq!(workaround, Workaround::None),
+                _ => assert_ne!(workaround, Workaround::None, "{}", quirk.quirk_type()),
+            }
+        }
+        assert_eq!(
+            Quirk::RotationGlitch { description: "" }.workaround(),
+            Workaround::RecoverThenFullRefresh {
+                recovery: Recovery::HARDWARE_RESET
+            }
+        );
+        assert_eq!(Workaround::PollBusy { timeout_ms: 1 }.recovery(), None);
     }
 
     #[test]
@@ -4,6 +4,8 @@
 
 use core::time::Duration;
 
+use crate::units::DisplayMs;
+
 /// Complete specification of an e-ink display
 ///
 /// Contains all characteristics needed for realistic emulation:
@@ -34,14 +36,14 @@ pub struct DisplaySpec {
     /// Number of grayscale levels (typically 4 or 16)
     pub grayscale_levels: u8,
 
-    /// Full refresh duration in milliseconds (typical: 2000ms)
-    pub full_refresh_ms: u32,
+    /// Full refresh duration (typical: 2000ms)
+    pub full_refresh_ms: DisplayMs,
 
-    /// Partial refresh duration in milliseconds (typical: 300ms)
-    pub partial_refresh_ms: u32,
+    /// Partial refresh duration (typical: 300ms)
+    pub partial_refresh_ms: DisplayMs,
 
-    /// Fast refresh duration in milliseconds (typical: 260ms)
-    pub fast_refresh_ms: u32,
+    /// Fast refresh duration (typical: 260ms)
+    pub fast_refresh_ms: DisplayMs,
 
     /// Ghosting accumulation rate per partial refresh (0.0-1.0)
     pub ghosting_rate_partial: f32,
@@ -99,17 +101,17 @@ impl DisplaySpec {
 
     /// Get full refresh duration as Duration
     pub fn full_refresh_duration(&self) -> Duration {
-        Duration::from_millis(self.full_refresh_ms as u64)
+        self.full_refresh_ms.as_duration()
     }
 
     /// Get partial refresh duration as Duration
     pub fn partial_refresh_duration(&self) -> Duration {
-        Duration::from_millis(self.partial_refresh_ms as u64)
+        self.partial_refresh_ms.as_duration()
     }
 
     /// Get fast refresh duration as Duration
     pub fn fast_refresh_duration(&self) -> Duration {
-        Duration::from_millis(self.fast_refresh_ms as u64)
+        self.fast_refresh_ms.as_duration()
     }
 
     /// Adjust refresh timing based on temperature with realistic non-linear model
@@ -120,10 +122,10 @@ impl DisplaySpec {
     /// - 5-35°C: Optimal performance (1.0x speed)
     /// - 35-45°C: Gradual slowdown due to increased viscosity
     /// - Above 45°C: Significant slowdown
-    // SAFETY: all arithmetic here is f32 display-timing math with temperature values in
-    // [-128, 127] and refresh times in [0, ~5000] ms. No meaningful overflow is possible.
+    // SAFETY: all arithmetic here is f32 factor math with temperature values in
+    // [-128, 127]; the duration itself is scaled with saturation by `DisplayMs::scale`.
     #[allow(clippy::arithmetic_side_effects)]
-    pub fn adjusted_refresh_ms(&self, base_ms: u32, temp: i8) -> u32 {
+    pub fn adjusted_refresh_ms(&self, base: DisplayMs, temp: i8) -> DisplayMs {
         let factor = match temp {
             t if t < 0 => {
                 // Below freezing: exponential slowdown
@@ -146,7 +148,7 @@ impl DisplaySpec {
                 1.2 + ((t - 45) as f32) * 0.03
             }
         };
-        (base_ms as f32 * factor) as u32
+        base.scale(factor)
     }
 
     /// Check if temperature is in optimal range
@@ -184,6 +186,8 @@ pub enum Controller {
     ACeP,
     /// Solomon Systech SSD1677 (3.97" and larger displays)
     SSD1677,
+    /// UltraChip UC8176 (Waveshare 4.2" V1)
+    UC8176,
 }
 
 /// E-ink panel technology types
@@ -228,9 +232,9 @@ mod tests {
             controller: Controller::SSD1680,
             panel_type: PanelType::Carta1000,
             grayscale_levels: 4,
-            full_refresh_ms: 2000,
-            partial_refresh_ms: 300,
-            fast_refresh_ms: 260,
+            full_refresh_ms: DisplayMs::new(2000),
+            partial_refresh_ms: DisplayMs::new(300),
+            fast_refresh_ms: DisplayMs::new(260),
             ghosting_rate_partial: 0.15,
             ghosting_rate_fast: 0.25,
             flash_count_full: 3,
@@ -261,15 +265,15 @@ mod tests {
         let spec = test_spec();
 
         // Normal temp - no adjustment
-        assert_eq!(spec.adjusted_refresh_ms(2000, 25), 2000);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), 25).get(), 2000);
 
         // Cold temp: -5°C is in the exponential range
         // factor = 1.5 + (0 - (-5)) * 0.05 = 1.5 + 0.25 = 1.75
-        assert_eq!(spec.adjusted_refresh_ms(2000, -5), 3500);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), -5).get(), 3500);
 
         // Hot temp: 45°C is at the boundary
         // factor = 1.0 + ((45 - 35) / 10.0) * 0.2 = 1.0 + 0.2 = 1.2
-        assert_eq!(spec.adjusted_refresh_ms(2000, 45), 2400);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), 45).get(), 2400);
     }
 
     #[test]
@@ -277,23 +281,23 @@ mod tests {
         let spec = test_spec();
 
         // Test -10°C: 1.5 + (0 - (-10)) * 0.05 = 1.5 + 0.5 = 2.0x slower
-        assert_eq!(spec.adjusted_refresh_ms(2000, -10), 4000);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), -10).get(), 4000);
 
         // Test 0°C: 1.5x slower (at transition boundary)
-        assert_eq!(spec.adjusted_refresh_ms(2000, 0), 3000);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), 0).get(), 3000);
 
         // Test 2.5°C: 1.5 - (2.5 / 5.0) * 0.3 = 1.5 - 0.15 = 1.35x
-        let result = spec.adjusted_refresh_ms(2000, 2);
+        let result = spec.adjusted_refresh_ms(DisplayMs::new(2000), 2).get();
         assert!((2640..=2760).contains(&result)); // ~2700 ± 60ms tolerance
 
         // Test 25°C: optimal (1.0x)
-        assert_eq!(spec.adjusted_refresh_ms(2000, 25), 2000);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), 25).get(), 2000);
 
         // Test 40°C: 1.0 + ((40 - 35) / 10.0) * 0.2 = 1.0 + 0.1 = 1.1x
-        assert_eq!(spec.adjusted_refresh_ms(2000, 40), 2200);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), 40).get(), 2200);
 
         // Test 50°C: 1.2 + ((50 - 45)) * 0.03 = 1.2 + 0.15 = 1.35x
-        assert_eq!(spec.adjusted_refresh_ms(2000, 50), 2700);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), 50).get(), 2700);
     }
 
     #[test]
@@ -307,7 +311,7 @@ mod tests {
         let mut previous_time = 0u32;
 
         for temp in temps {
-            let time = spec.adjusted_refresh_ms(2000, temp);
+            let time = spec.adjusted_refresh_ms(DisplayMs::new(2000), temp).get();
 
             // Ensure monotonic decrease as we warm up (except at boundaries)
             // Allow for rounding and transition zones
@@ -333,11 +337,11 @@ mod tests {
         let spec = test_spec();
 
         // Test -20°C: 1.5 + 20 * 0.05 = 2.5x slower
-        assert_eq!(spec.adjusted_refresh_ms(2000, -20), 5000);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), -20).get(), 5000);
 
         // Verify it keeps getting worse at extreme cold
-        let at_minus_10 = spec.adjusted_refresh_ms(2000, -10);
-        let at_minus_20 = spec.adjusted_refresh_ms(2000, -20);
+        let at_minus_10 = spec.adjusted_refresh_ms(DisplayMs::new(2000), -10).get();
+        let at_minus_20 = spec.adjusted_refresh_ms(DisplayMs::new(2000), -20).get();
         assert!(at_minus_20 > at_minus_10);
     }
 
@@ -346,11 +350,11 @@ mod tests {
         let spec = test_spec();
 
         // Test 60°C: 1.2 + (60 - 45) * 0.03 = 1.2 + 0.45 = 1.65x
-        assert_eq!(spec.adjusted_refresh_ms(2000, 60), 3300);
+        assert_eq!(spec.adjusted_refresh_ms(DisplayMs::new(2000), 60).get(), 3300);
 
         // Verify it keeps getting worse at extreme heat
-        let at_50 = spec.adjusted_refresh_ms(2000, 50);
-        let at_60 = spec.adjusted_refresh_ms(2000, 60);
+        let at_50 = spec.adjusted_refresh_ms(DisplayMs::new(2000), 50).get();
+        let at_60 = spec.adjusted_refresh_ms(DisplayMs::new(2000), 60).get();
         assert!(at_60 > at_50);
     }
 
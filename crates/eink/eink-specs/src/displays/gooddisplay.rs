@@ -2,7 +2,9 @@
 //!
 //! Pre-configured specs for common Good Display panels based on official datasheets.
 
-use crate::{controller_quirks::quirks_for_controller, Controller, DisplaySpec, PanelType};
+use crate::{
+    controller_quirks::quirks_for_controller, Controller, DisplayMs, DisplaySpec, PanelType,
+};
 
 /// Good Display GDEW0213I5F (212×104, UC8151, Pearl)
 ///
@@ -22,9 +24,9 @@ pub const GDEW0213I5F: DisplaySpec = DisplaySpec {
     controller: Controller::UC8151,
     panel_type: PanelType::Pearl,
     grayscale_levels: 4,
-    full_refresh_ms: 2000,
-    partial_refresh_ms: 500,
-    fast_refresh_ms: 500,
+    full_refresh_ms: DisplayMs::new(2000),
+    partial_refresh_ms: DisplayMs::new(500),
+    fast_refresh_ms: DisplayMs::new(500),
     ghosting_rate_partial: 0.18, // Higher ghosting on Pearl
     ghosting_rate_fast: 0.18,
     flash_count_full: 4, // More flashes needed
@@ -50,9 +52,9 @@ pub const GDEW029T5: DisplaySpec = DisplaySpec {
     controller: Controller::GDEW,
     panel_type: PanelType::Carta1000,
     grayscale_levels: 4,
-    full_refresh_ms: 2000,
-    partial_refresh_ms: 300,
-    fast_refresh_ms: 280,
+    full_refresh_ms: DisplayMs::new(2000),
+    partial_refresh_ms: DisplayMs::new(300),
+    fast_refresh_ms: DisplayMs::new(280),
     ghosting_rate_partial: 0.15,
     ghosting_rate_fast: 0.24,
     flash_count_full: 3,
@@ -78,9 +80,9 @@ pub const GDEW042T2: DisplaySpec = DisplaySpec {
     controller: Controller::SSD1619,
     panel_type: PanelType::Carta1200,
     grayscale_levels: 4,
-    full_refresh_ms: 2000,
-    partial_refresh_ms: 800,
-    fast_refresh_ms: 500,
+    full_refresh_ms: DisplayMs::new(2000),
+    partial_refresh_ms: DisplayMs::new(800),
+    fast_refresh_ms: DisplayMs::new(500),
     ghosting_rate_partial: 0.12,
     ghosting_rate_fast: 0.22,
     flash_count_full: 3,
@@ -106,9 +108,9 @@ pub const GDEW075T7: DisplaySpec = DisplaySpec {
     controller: Controller::GDEW,
     panel_type: PanelType::Carta1200,
     grayscale_levels: 4,
-    full_refresh_ms: 5000,
-    partial_refresh_ms: 2000,
-    fast_refresh_ms: 1500,
+    full_refresh_ms: DisplayMs::new(5000),
+    partial_refresh_ms: DisplayMs::new(2000),
+    fast_refresh_ms: DisplayMs::new(1500),
     ghosting_rate_partial: 0.12,
     ghosting_rate_fast: 0.20,
     flash_count_full: 4,
@@ -164,9 +166,9 @@ pub const GDEM0397T81P: DisplaySpec = DisplaySpec {
     controller: Controller::SSD1677,
     panel_type: PanelType::Carta1200,
     grayscale_levels: 4,
-    full_refresh_ms: 3000,       // 3 seconds per datasheet
-    partial_refresh_ms: 300,     // 0.3 seconds per datasheet
-    fast_refresh_ms: 1500,       // 1.5 seconds per datasheet
+    full_refresh_ms: DisplayMs::new(3000),       // 3 seconds per datasheet
+    partial_refresh_ms: DisplayMs::new(300),     // 0.3 seconds per datasheet
+    fast_refresh_ms: DisplayMs::new(1500),       // 1.5 seconds per datasheet
     ghosting_rate_partial: 0.10, // Low ghosting on Carta panel
     ghosting_rate_fast: 0.18,    // Moderate ghosting on fast refresh
     flash_count_full: 3,         // Typical for SSD1677
@@ -230,9 +232,9 @@ mod tests {
         assert_eq!(GDEM0397T81P.panel_type, PanelType::Carta1200);
 
         // Verify refresh timings match datasheet
-        assert_eq!(GDEM0397T81P.full_refresh_ms, 3000);
-        assert_eq!(GDEM0397T81P.partial_refresh_ms, 300);
-        assert_eq!(GDEM0397T81P.fast_refresh_ms, 1500);
+        assert_eq!(GDEM0397T81P.full_refresh_ms, DisplayMs::new(3000));
+        assert_eq!(GDEM0397T81P.partial_refresh_ms, DisplayMs::new(300));
+        assert_eq!(GDEM0397T81P.fast_refresh_ms, DisplayMs::new(1500));
 
         // Verify temperature ranges
         assert_eq!(GDEM0397T81P.temp_operating_min, 0);
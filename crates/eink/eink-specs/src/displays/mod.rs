@@ -7,3 +7,20 @@ pub mod waveshare;
 
 pub use gooddisplay::*;
 pub use waveshare::*;
+
+use crate::DisplaySpec;
+
+/// Every pre-configured display, Good Display first then Waveshare.
+pub const ALL: [&DisplaySpec; 11] = [
+    &GDEW0213I5F,
+    &GDEW029T5,
+    &GDEW042T2,
+    &GDEW075T7,
+    &GDEM0397T81P,
+    &WAVESHARE_2_13_V4,
+    &WAVESHARE_2_9_V2,
+    &WAVESHARE_4_2_V1,
+    &WAVESHARE_4_2_V2,
+    &WAVESHARE_7_5_V2,
+    &WAVESHARE_5_65_SPECTRA6,
+];
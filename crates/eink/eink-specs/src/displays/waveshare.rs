@@ -3,7 +3,8 @@
 //! Pre-configured specs for common Waveshare displays based on official datasheets.
 
 use crate::{
-    controller_quirks::quirks_for_controller, ColorMode, Controller, DisplaySpec, PanelType,
+    controller_quirks::quirks_for_controller, ColorMode, Controller, DisplayMs, DisplaySpec,
+    PanelType,
 };
 
 /// Waveshare 2.13" V4 (250×122, SSD1680, Carta 1000)
@@ -23,9 +24,9 @@ pub const WAVESHARE_2_13_V4: DisplaySpec = DisplaySpec {
     controller: Controller::SSD1680,
     panel_type: PanelType::Carta1000,
     grayscale_levels: 4,
-    full_refresh_ms: 2000,
-    partial_refresh_ms: 300,
-    fast_refresh_ms: 260,
+    full_refresh_ms: DisplayMs::new(2000),
+    partial_refresh_ms: DisplayMs::new(300),
+    fast_refresh_ms: DisplayMs::new(260),
     ghosting_rate_partial: 0.15,
     ghosting_rate_fast: 0.25,
     flash_count_full: 3,
@@ -51,9 +52,9 @@ pub const WAVESHARE_2_9_V2: DisplaySpec = DisplaySpec {
     controller: Controller::IL0373,
     panel_type: PanelType::Carta1000,
     grayscale_levels: 4,
-    full_refresh_ms: 2000,
-    partial_refresh_ms: 300,
-    fast_refresh_ms: 300, // No dedicated fast mode
+    full_refresh_ms: DisplayMs::new(2000),
+    partial_refresh_ms: DisplayMs::new(300),
+    fast_refresh_ms: DisplayMs::new(300), // No dedicated fast mode
     ghosting_rate_partial: 0.15,
     ghosting_rate_fast: 0.15,
     flash_count_full: 3,
@@ -65,6 +66,33 @@ pub const WAVESHARE_2_9_V2: DisplaySpec = DisplaySpec {
     quirks: Some(quirks_for_controller(Controller::IL0373)),
 };
 
+/// Waveshare 4.2" V1 (400×300, UC8176, Carta 1200)
+///
+/// Same glass as the V2 on the older UltraChip controller.
+/// - Grayscale: 4 levels
+/// - Full refresh: ~4s
+/// - No partial or fast waveform in OTP; both run as full refreshes
+pub const WAVESHARE_4_2_V1: DisplaySpec = DisplaySpec {
+    name: "Waveshare 4.2\" V1",
+    width: 400,
+    height: 300,
+    controller: Controller::UC8176,
+    panel_type: PanelType::Carta1200,
+    grayscale_levels: 4,
+    full_refresh_ms: DisplayMs::new(4000),
+    partial_refresh_ms: DisplayMs::new(4000), // No OTP partial waveform
+    fast_refresh_ms: DisplayMs::new(4000),
+    ghosting_rate_partial: 0.0,
+    ghosting_rate_fast: 0.0,
+    flash_count_full: 3,
+    temp_optimal_min: 15,
+    temp_optimal_max: 35,
+    temp_operating_min: 0,
+    temp_operating_max: 50,
+    color_mode: None,
+    quirks: Some(quirks_for_controller(Controller::UC8176)),
+};
+
 /// Waveshare 4.2" V2 (400×300, SSD1619, Carta 1200)
 ///
 /// Large display with improved Carta 1200 panel.
@@ -79,9 +107,9 @@ pub const WAVESHARE_4_2_V2: DisplaySpec = DisplaySpec {
     controller: Controller::SSD1619,
     panel_type: PanelType::Carta1200,
     grayscale_levels: 4,
-    full_refresh_ms: 2000,
-    partial_refresh_ms: 800,
-    fast_refresh_ms: 500,
+    full_refresh_ms: DisplayMs::new(2000),
+    partial_refresh_ms: DisplayMs::new(800),
+    fast_refresh_ms: DisplayMs::new(500),
     ghosting_rate_partial: 0.12,
     ghosting_rate_fast: 0.22,
     flash_count_full: 3,
@@ -107,9 +135,9 @@ pub const WAVESHARE_7_5_V2: DisplaySpec = DisplaySpec {
     controller: Controller::ED075TC1,
     panel_type: PanelType::Carta1200,
     grayscale_levels: 4,
-    full_refresh_ms: 5000,
-    partial_refresh_ms: 2000,
-    fast_refresh_ms: 1500,
+    full_refresh_ms: DisplayMs::new(5000),
+    partial_refresh_ms: DisplayMs::new(2000),
+    fast_refresh_ms: DisplayMs::new(1500),
     ghosting_rate_partial: 0.12,
     ghosting_rate_fast: 0.20,
     flash_count_full: 4,
@@ -141,9 +169,9 @@ pub const WAVESHARE_5_65_SPECTRA6: DisplaySpec = DisplaySpec {
     controller: Controller::ACeP,
     panel_type: PanelType::Spectra6,
     grayscale_levels: 6,         // 6 distinct colors
-    full_refresh_ms: 15000,      // 15 seconds for color
-    partial_refresh_ms: 15000,   // Same as full (not recommended)
-    fast_refresh_ms: 15000,      // No fast mode for color
+    full_refresh_ms: DisplayMs::new(15000),      // 15 seconds for color
+    partial_refresh_ms: DisplayMs::new(15000),   // Same as full (not recommended)
+    fast_refresh_ms: DisplayMs::new(15000),      // No fast mode for color
     ghosting_rate_partial: 0.12, // Higher ghosting for color
     ghosting_rate_fast: 0.12,
     flash_count_full: 30, // Many flashes for color particles
@@ -176,6 +204,13 @@ mod tests {
         assert_eq!(WAVESHARE_2_9_V2.controller, Controller::IL0373);
     }
 
+    #[test]
+    fn test_waveshare_4_2_v1() {
+        assert_eq!(WAVESHARE_4_2_V1.width, WAVESHARE_4_2_V2.width);
+        assert_eq!(WAVESHARE_4_2_V1.height, WAVESHARE_4_2_V2.height);
+        assert_eq!(WAVESHARE_4_2_V1.controller, Controller::UC8176);
+    }
+
     #[test]
     fn test_waveshare_4_2_v2() {
         assert_eq!(WAVESHARE_4_2_V2.width, 400);
@@ -190,6 +225,7 @@ mod tests {
         for spec in &[
             &WAVESHARE_2_13_V4,
             &WAVESHARE_2_9_V2,
+            &WAVESHARE_4_2_V1,
             &WAVESHARE_4_2_V2,
             &WAVESHARE_7_5_V2,
         ] {
@@ -20,17 +20,17 @@
 //! println!("Display: {}", spec.name);
 //! println!("Resolution: {}×{}", spec.width, spec.height);
 //! println!("Diagonal: {:.2}\"", spec.diagonal_inches());
-//! println!("Full refresh: {}ms", spec.full_refresh_ms);
+//! println!("Full refresh: {}", spec.full_refresh_ms);
 //!
 //! // Temperature-adjusted timing
 //! let cold_refresh = spec.adjusted_refresh_ms(spec.full_refresh_ms, -5);
-//! println!("Full refresh at -5°C: {}ms", cold_refresh);  // ~3000ms (50% slower)
+//! println!("Full refresh at -5°C: {}", cold_refresh);  // ~3000ms (50% slower)
 //! ```
 //!
 //! # Custom Display Specs
 //!
 //! ```
-//! use eink_specs::{DisplaySpec, Controller, PanelType};
+//! use eink_specs::{DisplaySpec, DisplayMs, Controller, PanelType};
 //!
 //! const MY_DISPLAY: DisplaySpec = DisplaySpec {
 //!     name: "Custom Display",
@@ -40,9 +40,9 @@
 //!     panel_type: PanelType::Carta1300,
 //!     color_mode: None,
 //!     grayscale_levels: 4,
-//!     full_refresh_ms: 2000,
-//!     partial_refresh_ms: 300,
-//!     fast_refresh_ms: 260,
+//!     full_refresh_ms: DisplayMs::new(2000),
+//!     partial_refresh_ms: DisplayMs::new(300),
+//!     fast_refresh_ms: DisplayMs::new(260),
 //!     ghosting_rate_partial: 0.15,
 //!     ghosting_rate_fast: 0.25,
 //!     flash_count_full: 3,
@@ -84,9 +84,13 @@
 pub mod controller_quirks;
 mod display_spec;
 pub mod displays;
+pub mod partial_alignment;
+pub mod units;
 
-pub use controller_quirks::{quirks_for_controller, ControllerQuirks, Quirk};
+pub use controller_quirks::{quirks_for_controller, ControllerQuirks, Quirk, Recovery, Workaround};
 pub use display_spec::{ColorMode, Controller, DisplaySpec, PanelType};
+pub use partial_alignment::{partial_alignment_for_controller, PartialAlignment};
+pub use units::{DisplayMs, Px, RefreshCount};
 
 /// Crate version string, injected by Cargo at compile time.
 ///
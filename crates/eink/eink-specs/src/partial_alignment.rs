@@ -0,0 +1,209 @@
+//! Partial-window alignment constraints per controller
+//!
+//! Controllers address display RAM in units larger than one pixel, so a
+//! partial update window must start and end on those unit boundaries. The
+//! unit differs between chips:
+//!
+//! | Controller | X unit | Y unit | Reason |
+//! |------------|--------|--------|--------|
+//! | SSD1680 / SSD1619 / SSD1677 | 8 | 1 | X RAM address counts bytes (8 px at 1bpp); gates are row-addressable |
+//! | IL0373 / UC8151 / UC8176 / GDEW (UC81xx family) | 8 | 1 | `HRST`/`HRED` drop the low 3 bits |
+//! | ED075TC1 | 8 | 1 | Byte-addressed source data |
+//! | IT8951 | 4 | 1 | 4bpp load area is packed into 16-bit words |
+//! | ACeP | 2 | 1 | 4bpp colour, two pixels per byte |
+//! | Generic | 8 | 8 | Conservative default for unknown chips |
+//!
+//! Both the emulator and the hardware drivers read these values, so a window
+//! that would be mis-addressed on the real controller is also visibly
+//! expanded (and reported) in emulation.
+//!
+//! # Example
+//!
+//! ```
+//! use eink_specs::Controller;
+//!
+//! let align = Controller::SSD1680.partial_alignment();
+//! assert_eq!(align.align_window(5, 3, 10, 4), (0, 3, 16, 4));
+//! assert!(!align.is_window_aligned(5, 3, 10, 4));
+//! ```
+
+use crate::Controller;
+
+/// Pixel granularity a controller requires for partial update windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub struct PartialAlignment {
+    /// Horizontal unit: window x and width must be multiples of this.
+    pub x: u32,
+    /// Vertical unit: window y and height must be multiples of this.
+    pub y: u32,
+}
+
+impl PartialAlignment {
+    /// Conservative 8×8 alignment used for unknown controllers.
+    pub const GENERIC: Self = Self::new(8, 8);
+
+    /// Create an alignment. Zero units are treated as 1 (no constraint).
+    pub const fn new(x: u32, y: u32) -> Self {
+        Self {
+            x: if x == 0 { 1 } else { x },
+            y: if y == 0 { 1 } else { y },
+        }
+    }
+
+    /// Round `value` down to a multiple of the X unit.
+    pub const fn align_down_x(&self, value: u32) -> u32 {
+        align_down(value, self.x)
+    }
+
+    /// Round `value` up to a multiple of the X unit.
+    pub const fn align_up_x(&self, value: u32) -> u32 {
+        align_up(value, self.x)
+    }
+
+    /// Round `value` down to a multiple of the Y unit.
+    pub const fn align_down_y(&self, value: u32) -> u32 {
+        align_down(value, self.y)
+    }
+
+    /// Round `value` up to a multiple of the Y unit.
+    pub const fn align_up_y(&self, value: u32) -> u32 {
+        align_up(value, self.y)
+    }
+
+    /// Expand the window `(x, y, width, height)` outward to unit boundaries.
+    ///
+    /// Returns the aligned `(x, y, width, height)`.
+    pub const fn align_window(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> (u32, u32, u32, u32) {
+        let x0 = self.align_down_x(x);
+        let y0 = self.align_down_y(y);
+        let x1 = self.align_up_x(x.saturating_add(width));
+        let y1 = self.align_up_y(y.saturating_add(height));
+        (x0, y0, x1.saturating_sub(x0), y1.saturating_sub(y0))
+    }
+
+    /// Whether the window already satisfies this alignment.
+    pub const fn is_window_aligned(&self, x: u32, y: u32, width: u32, height: u32) -> bool {
+        x.is_multiple_of(self.x)
+            && width.is_multiple_of(self.x)
+            && y.is_multiple_of(self.y)
+            && height.is_multiple_of(self.y)
+    }
+}
+
+impl Default for PartialAlignment {
+    fn default() -> Self {
+        Self::GENERIC
+    }
+}
+
+/// Partial-window alignment required by `controller`.
+pub const fn partial_alignment_for_controller(controller: Controller) -> PartialAlignment {
+    match controller {
+        Controller::SSD1680 | Controller::SSD1619 | Controller::SSD1677 => {
+            PartialAlignment::new(8, 1)
+        }
+        Controller::IL0373 | Controller::UC8151 | Controller::UC8176 | Controller::GDEW => {
+            PartialAlignment::new(8, 1)
+        }
+        Controller::ED075TC1 => PartialAlignment::new(8, 1),
+        Controller::IT8951 => PartialAlignment::new(4, 1),
+        Controller::ACeP => PartialAlignment::new(2, 1),
+        Controller::Generic => PartialAlignment::GENERIC,
+    }
+}
+
+impl Controller {
+    /// Partial-window alignment this controller requires.
+    pub const fn partial_alignment(self) -> PartialAlignment {
+        partial_alignment_for_controller(self)
+    }
+}
+
+// `unit` is never zero (enforced by `PartialAlignment::new`), so the division
+// and remainder cannot panic; `value - value % unit` cannot underflow.
+#[allow(clippy::arithmetic_side_effects)]
+const fn align_down(value: u32, unit: u32) -> u32 {
+    value - value % unit
+}
+
+// Saturates at the largest multiple of `unit` rather than overflowing.
+#[allow(clippy::arithmetic_side_effects)]
+const fn align_up(value: u32, unit: u32) -> u32 {
+    let rem = value % unit;
+    if rem == 0 {
+        value
+    } else {
+        match value.checked_add(unit - rem) {
+            Some(v) => v,
+            None => align_down(u32::MAX, unit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ssd1680_aligns_x_to_bytes_only() {
+        let a = Controller::SSD1680.partial_alignment();
+        assert_eq!(a, PartialAlignment::new(8, 1));
+        assert_eq!(a.align_window(13, 7, 2, 3), (8, 7, 8, 3));
+    }
+
+    #[test]
+    fn test_generic_matches_legacy_8px_grid() {
+        let a = Controller::Generic.partial_alignment();
+        assert_eq!(a.align_window(5, 5, 10, 10), (0, 0, 16, 16));
+        assert!(a.is_window_aligned(8, 16, 24, 8));
+    }
+
+    #[test]
+    fn test_it8951_and_acep_use_finer_x_units() {
+        let it8951 = Controller::IT8951.partial_alignment();
+        let acep = Controller::ACeP.partial_alignment();
+        assert_eq!(it8951.align_window(5, 0, 2, 1), (4, 0, 4, 1));
+        assert_eq!(acep.align_window(5, 0, 2, 1), (4, 0, 4, 1));
+        assert_eq!(acep.align_window(4, 0, 2, 1), (4, 0, 2, 1));
+    }
+
+    #[test]
+    fn test_zero_unit_is_unconstrained() {
+        let a = PartialAlignment::new(0, 0);
+        assert_eq!(a, PartialAlignment::new(1, 1));
+        assert!(a.is_window_aligned(3, 5, 7, 9));
+    }
+
+    #[test]
+    fn test_align_up_saturates() {
+        let a = PartialAlignment::new(8, 1);
+        assert_eq!(a.align_up_x(u32::MAX), u32::MAX - 7);
+    }
+
+    #[test]
+    fn test_every_controller_has_nonzero_units() {
+        for c in [
+            Controller::SSD1680,
+            Controller::IL0373,
+            Controller::UC8151,
+            Controller::UC8176,
+            Controller::SSD1619,
+            Controller::ED075TC1,
+            Controller::IT8951,
+            Controller::GDEW,
+            Controller::Generic,
+            Controller::ACeP,
+            Controller::SSD1677,
+        ] {
+            let a = c.partial_alignment();
+            assert!(a.x > 0 && a.y > 0);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Typed units for display geometry and timing
+//!
+//! Pixel extents, refresh durations and refresh counts all travel as plain
+//! integers, which makes it easy to add a `u32` width to an `i32` coordinate
+//! or to multiply a per-refresh duration by a frame count in the wrong place.
+//! These newtypes keep the three apart; unwrap them with `get()` at the edge
+//! where a raw integer is really needed (a register write, a `Point`, a log
+//! line).
+//!
+//! All arithmetic saturates, so none of it can panic on-device.
+//!
+//! # Example
+//!
+//! ```rust
+//! use eink_specs::{DisplayMs, Px, RefreshCount};
+//!
+//! // Right edge of a 480 px row that starts at x = -8.
+//! assert_eq!(Px::new(480).end_of(-8), 472);
+//!
+//! // Time spent on 12 partial refreshes of 300 ms each.
+//! let busy = DisplayMs::new(300).times(RefreshCount::new(12));
+//! assert_eq!(busy, DisplayMs::new(3_600));
+//! ```
+
+use core::fmt;
+use core::time::Duration;
+
+/// A non-negative pixel extent: a width, height, inset or gap.
+///
+/// Coordinates stay `i32` (as in `embedded-graphics`); use [`Px::offset`] and
+/// [`Px::end_of`] to move between the two without a bare `as` cast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(transparent)
+)]
+#[repr(transparent)]
+pub struct Px(u32);
+
+impl Px {
+    /// Zero pixels.
+    pub const ZERO: Self = Self(0);
+
+    /// Wrap a pixel count.
+    pub const fn new(px: u32) -> Self {
+        Self(px)
+    }
+
+    /// The raw pixel count.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// Extent from a signed coordinate difference; negative spans clamp to
+    /// zero.
+    pub fn from_offset(offset: i32) -> Self {
+        Self(u32::try_from(offset).unwrap_or(0))
+    }
+
+    /// This extent as a coordinate delta, saturating at `i32::MAX`.
+    pub fn offset(self) -> i32 {
+        i32::try_from(self.0).unwrap_or(i32::MAX)
+    }
+
+    /// Coordinate one past the far edge of a span of this extent starting at
+    /// `start` (the exclusive right or bottom edge).
+    pub fn end_of(self, start: i32) -> i32 {
+        start.saturating_add(self.offset())
+    }
+
+    /// Sum of two extents.
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    /// Difference of two extents, clamped at zero.
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// This extent repeated `n` times (e.g. the gaps between `n + 1` items).
+    pub const fn times(self, n: u32) -> Self {
+        Self(self.0.saturating_mul(n))
+    }
+}
+
+impl From<Px> for u32 {
+    fn from(px: Px) -> Self {
+        px.0
+    }
+}
+
+impl fmt::Display for Px {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}px", self.0)
+    }
+}
+
+/// A display-side duration in milliseconds: a refresh, a flash phase, a
+/// timeout.
+///
+/// 32 bits covers about 49 days, far beyond any single panel operation; sum
+/// long-running totals in `u64` via [`DisplayMs::get`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(transparent)
+)]
+#[repr(transparent)]
+pub struct DisplayMs(u32);
+
+impl DisplayMs {
+    /// No time at all.
+    pub const ZERO: Self = Self(0);
+
+    /// Wrap a millisecond count.
+    pub const fn new(ms: u32) -> Self {
+        Self(ms)
+    }
+
+    /// The raw millisecond count.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// This duration as a [`Duration`].
+    pub const fn as_duration(self) -> Duration {
+        Duration::from_millis(self.0 as u64)
+    }
+
+    /// Sum of two durations.
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+
+    /// Difference of two durations, clamped at zero.
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Total time for `count` back-to-back operations of this duration.
+    pub fn times(self, count: RefreshCount) -> Self {
+        let total = u64::from(self.0).saturating_mul(count.get());
+        Self(u32::try_from(total).unwrap_or(u32::MAX))
+    }
+
+    /// This duration split evenly into `parts` (e.g. flash phases); zero
+    /// parts yields zero.
+    pub fn split(self, parts: u32) -> Self {
+        Self(self.0.checked_div(parts).unwrap_or(0))
+    }
+
+    /// This duration stretched by `factor` (e.g. a temperature slowdown),
+    /// truncated to whole milliseconds; negative or NaN factors yield zero.
+    pub fn scale(self, factor: f32) -> Self {
+        // Float-to-int `as` saturates and maps NaN to 0.
+        Self((self.0 as f32 * factor) as u32)
+    }
+}
+
+impl From<DisplayMs> for Duration {
+    fn from(ms: DisplayMs) -> Self {
+        ms.as_duration()
+    }
+}
+
+impl fmt::Display for DisplayMs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}ms", self.0)
+    }
+}
+
+/// A number of panel refreshes (frames pushed through a waveform), as
+/// opposed to a duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Deserialize, serde::Serialize),
+    serde(transparent)
+)]
+#[repr(transparent)]
+pub struct RefreshCount(u64);
+
+impl RefreshCount {
+    /// No refreshes.
+    pub const ZERO: Self = Self(0);
+
+    /// Wrap a refresh count.
+    pub const fn new(count: u64) -> Self {
+        Self(count)
+    }
+
+    /// The raw count.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Count one more refresh.
+    pub fn increment(&mut self) {
+        self.0 = self.0.saturating_add(1);
+    }
+
+    /// Sum of two counts.
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl From<RefreshCount> for u64 {
+    fn from(count: RefreshCount) -> Self {
+        count.0
+    }
+}
+
+impl fmt::Display for RefreshCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn px_offsets_saturate_instead_of_wrapping() {
+        assert_eq!(Px::new(u32::MAX).offset(), i32::MAX);
+        assert_eq!(Px::new(10).end_of(i32::MAX - 4), i32::MAX);
+        assert_eq!(Px::from_offset(-3), Px::ZERO);
+        assert_eq!(Px::from_offset(42), Px::new(42));
+    }
+
+    #[test]
+    fn px_arithmetic_clamps() {
+        assert_eq!(Px::new(4).saturating_sub(Px::new(9)), Px::ZERO);
+        assert_eq!(Px::new(8).times(3), Px::new(24));
+        assert_eq!(
+            Px::new(u32::MAX).saturating_add(Px::new(1)),
+            Px::new(u32::MAX)
+        );
+    }
+
+    #[test]
+    fn duration_times_count_is_total_time() {
+        let total = DisplayMs::new(260).times(RefreshCount::new(4));
+        assert_eq!(total, DisplayMs::new(1_040));
+        let huge = DisplayMs::new(u32::MAX).times(RefreshCount::new(2));
+        assert_eq!(huge, DisplayMs::new(u32::MAX));
+    }
+
+    #[test]
+    fn duration_split_and_scale() {
+        assert_eq!(DisplayMs::new(980).split(12), DisplayMs::new(81));
+        assert_eq!(DisplayMs::new(980).split(0), DisplayMs::ZERO);
+        assert_eq!(DisplayMs::new(2_000).scale(1.5), DisplayMs::new(3_000));
+        assert_eq!(DisplayMs::new(2_000).scale(-1.0), DisplayMs::ZERO);
+        assert_eq!(
+            DisplayMs::new(300).as_duration(),
+            Duration::from_millis(300)
+        );
+    }
+
+    #[test]
+    fn refresh_count_increments() {
+        let mut count = RefreshCount::ZERO;
+        count.increment();
+        count.increment();
+        assert_eq!(count.get(), 2);
+        let mut full = RefreshCount::new(u64::MAX);
+        full.increment();
+        assert_eq!(full.get(), u64::MAX);
+    }
+}
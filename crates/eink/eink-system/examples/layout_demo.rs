@@ -18,6 +18,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = EmulatorConfig {
         rotation: Rotation::Degrees90,
         scale: 2,
+        ..Default::default()
     };
 
     let mut emulator = Emulator::with_config(config);
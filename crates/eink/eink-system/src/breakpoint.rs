@@ -0,0 +1,308 @@
+//! Responsive breakpoints for screens that run on more than one panel.
+//!
+//! The same screen is drawn on the 800×480 main panel (landscape, or
+//! 480×800 portrait), on the smaller Waveshare and Good Display panels the
+//! emulator offers, and on whatever panel a future board uses.  Rather than
+//! branching on exact resolutions, a screen asks which [`Breakpoint`] the
+//! display falls into and picks its values from that:
+//!
+//! - [`Breakpoint`] is a size class: [`Compact`](Breakpoint::Compact)
+//!   (2.13"–2.9" strips), [`Medium`](Breakpoint::Medium) (4.2") and
+//!   [`Expanded`](Breakpoint::Expanded) (5.65" and up).
+//! - [`Breakpoints`] holds the minimum sizes of the two larger classes;
+//!   [`Breakpoints::DEFAULT`] is what [`Breakpoint::of`] uses.
+//! - [`Responsive`] is a value per class, declared mobile-first as in CSS
+//!   `min-width` media queries: the compact value applies everywhere until
+//!   a larger class overrides it.  `Responsive<bool>` from
+//!   [`visible_from`] hides secondary information on small panels.
+//!
+//! [`SUPPORTED_RESOLUTIONS`] lists every panel size the UI must lay out
+//! at, so screen tests can iterate over it.
+//!
+//! # Example
+//!
+//! ```
+//! use eink_system::breakpoint::{visible_from, Breakpoint, Responsive};
+//! use embedded_graphics::prelude::Size;
+//!
+//! let header_h = Responsive::new(0u32).medium(48).expanded(64);
+//! let show_artist = visible_from(Breakpoint::Medium);
+//!
+//! let bp = Breakpoint::of(Size::new(296, 128));
+//! assert_eq!(bp, Breakpoint::Compact);
+//! assert_eq!(header_h.at(bp), 0);
+//! assert!(!show_artist.at(bp));
+//!
+//! let bp = Breakpoint::of(Size::new(800, 480));
+//! assert_eq!(header_h.at(bp), 64);
+//! assert!(show_artist.at(bp));
+//! ```
+
+use crate::class::StyleRules;
+use embedded_graphics::prelude::Size;
+
+/// Size class of a display.  Ordered from smallest to largest.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Breakpoint {
+    /// Narrow strips: room for one line of primary content and a control.
+    Compact,
+    /// Mid-size panels: primary and secondary text, no artwork.
+    Medium,
+    /// The main panel and anything larger.
+    Expanded,
+}
+
+impl Breakpoint {
+    /// Every class, smallest first.
+    pub const ALL: [Self; 3] = [Self::Compact, Self::Medium, Self::Expanded];
+
+    /// Class of a display of `size` under [`Breakpoints::DEFAULT`].
+    pub const fn of(size: Size) -> Self {
+        Breakpoints::DEFAULT.classify(size)
+    }
+
+    /// Short name for logs and test output.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Compact => "compact",
+            Self::Medium => "medium",
+            Self::Expanded => "expanded",
+        }
+    }
+}
+
+/// Minimum sizes of the [`Medium`](Breakpoint::Medium) and
+/// [`Expanded`](Breakpoint::Expanded) classes.
+///
+/// A display must meet both the width and the height of a class to fall
+/// into it, so a wide but short strip stays compact.  Sizes are compared
+/// with the shorter side against the smaller threshold, so a panel is in
+/// the same class in portrait and landscape.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Breakpoints {
+    /// Smallest medium display.
+    pub medium: Size,
+    /// Smallest expanded display.
+    pub expanded: Size,
+}
+
+impl Breakpoints {
+    /// 4.2" panels (400×300) are medium; 5.65" (600×448) and the 800×480
+    /// main panel are expanded.
+    pub const DEFAULT: Self = Self::new(Size::new(400, 240), Size::new(600, 448));
+
+    /// Thresholds with the given minimum sizes.
+    pub const fn new(medium: Size, expanded: Size) -> Self {
+        Self { medium, expanded }
+    }
+
+    /// Class of a display of `size`.
+    pub const fn classify(&self, size: Size) -> Breakpoint {
+        if fits(size, self.expanded) {
+            Breakpoint::Expanded
+        } else if fits(size, self.medium) {
+            Breakpoint::Medium
+        } else {
+            Breakpoint::Compact
+        }
+    }
+}
+
+impl Default for Breakpoints {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Whether `size` is at least `min` in either orientation.
+const fn fits(size: Size, min: Size) -> bool {
+    let (short, long) = min_max(size);
+    let (min_short, min_long) = min_max(min);
+    short >= min_short && long >= min_long
+}
+
+const fn min_max(size: Size) -> (u32, u32) {
+    if size.width <= size.height {
+        (size.width, size.height)
+    } else {
+        (size.height, size.width)
+    }
+}
+
+/// A value per [`Breakpoint`], declared mobile-first.
+///
+/// [`Responsive::new`] sets the compact value; [`medium`](Self::medium) and
+/// [`expanded`](Self::expanded) override it from that class upwards.  A
+/// class without its own value uses the next smaller one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Responsive<T> {
+    compact: T,
+    medium: Option<T>,
+    expanded: Option<T>,
+}
+
+impl<T: Copy> Responsive<T> {
+    /// `value` on every class.
+    pub const fn new(value: T) -> Self {
+        Self {
+            compact: value,
+            medium: None,
+            expanded: None,
+        }
+    }
+
+    /// Use `value` on medium displays and, unless overridden, expanded ones.
+    pub const fn medium(mut self, value: T) -> Self {
+        self.medium = Some(value);
+        self
+    }
+
+    /// Use `value` on expanded displays.
+    pub const fn expanded(mut self, value: T) -> Self {
+        self.expanded = Some(value);
+        self
+    }
+
+    /// Value for `breakpoint`.
+    pub fn at(&self, breakpoint: Breakpoint) -> T {
+        let medium = self.medium.unwrap_or(self.compact);
+        match breakpoint {
+            Breakpoint::Compact => self.compact,
+            Breakpoint::Medium => medium,
+            Breakpoint::Expanded => self.expanded.unwrap_or(medium),
+        }
+    }
+
+    /// Value for a display of `size` under [`Breakpoints::DEFAULT`].
+    pub fn for_size(&self, size: Size) -> T {
+        self.at(Breakpoint::of(size))
+    }
+}
+
+impl Responsive<StyleRules> {
+    /// Rules for `breakpoint`: the compact rules with every larger class up
+    /// to `breakpoint` merged over them, so a medium override of the gap
+    /// keeps the compact font.
+    pub fn cascade(&self, breakpoint: Breakpoint) -> StyleRules {
+        let mut rules = self.compact;
+        if breakpoint >= Breakpoint::Medium {
+            if let Some(medium) = &self.medium {
+                rules = rules.merge(medium);
+            }
+        }
+        if breakpoint >= Breakpoint::Expanded {
+            if let Some(expanded) = &self.expanded {
+                rules = rules.merge(expanded);
+            }
+        }
+        rules
+    }
+}
+
+/// Shown from `breakpoint` upwards, hidden below it.
+pub const fn visible_from(breakpoint: Breakpoint) -> Responsive<bool> {
+    match breakpoint {
+        Breakpoint::Compact => Responsive::new(true),
+        Breakpoint::Medium => Responsive::new(false).medium(true),
+        Breakpoint::Expanded => Responsive::new(false).medium(false).expanded(true),
+    }
+}
+
+/// Every panel size the UI must lay out at: each pre-configured display in
+/// [`eink_specs::displays::ALL`] in its native orientation, plus the main
+/// panel in portrait.
+pub const SUPPORTED_RESOLUTIONS: [Size; 7] = [
+    Size::new(212, 104),
+    Size::new(250, 122),
+    Size::new(296, 128),
+    Size::new(400, 300),
+    Size::new(600, 448),
+    Size::new(800, 480),
+    Size::new(480, 800),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::Edges;
+    use embedded_graphics::mono_font::ascii::FONT_6X10;
+
+    #[test]
+    fn test_panels_fall_into_expected_classes() {
+        assert_eq!(Breakpoint::of(Size::new(212, 104)), Breakpoint::Compact);
+        assert_eq!(Breakpoint::of(Size::new(250, 122)), Breakpoint::Compact);
+        assert_eq!(Breakpoint::of(Size::new(296, 128)), Breakpoint::Compact);
+        assert_eq!(Breakpoint::of(Size::new(400, 300)), Breakpoint::Medium);
+        assert_eq!(Breakpoint::of(Size::new(600, 448)), Breakpoint::Expanded);
+        assert_eq!(Breakpoint::of(Size::new(800, 480)), Breakpoint::Expanded);
+    }
+
+    #[test]
+    fn test_orientation_does_not_change_class() {
+        for size in SUPPORTED_RESOLUTIONS {
+            let rotated = Size::new(size.height, size.width);
+            assert_eq!(Breakpoint::of(size), Breakpoint::of(rotated));
+        }
+    }
+
+    #[test]
+    fn test_wide_but_short_strip_stays_compact() {
+        assert_eq!(Breakpoint::of(Size::new(800, 128)), Breakpoint::Compact);
+    }
+
+    #[test]
+    fn test_every_preset_display_is_supported() {
+        for spec in eink_specs::displays::ALL {
+            let size = Size::new(spec.width, spec.height);
+            assert!(
+                SUPPORTED_RESOLUTIONS.contains(&size),
+                "{} ({}x{}) missing from SUPPORTED_RESOLUTIONS",
+                spec.name,
+                spec.width,
+                spec.height
+            );
+        }
+    }
+
+    #[test]
+    fn test_responsive_falls_back_to_smaller_class() {
+        let only_medium = Responsive::new(1).medium(2);
+        assert_eq!(only_medium.at(Breakpoint::Compact), 1);
+        assert_eq!(only_medium.at(Breakpoint::Medium), 2);
+        assert_eq!(only_medium.at(Breakpoint::Expanded), 2);
+
+        let only_expanded = Responsive::new(1).expanded(3);
+        assert_eq!(only_expanded.at(Breakpoint::Medium), 1);
+        assert_eq!(only_expanded.at(Breakpoint::Expanded), 3);
+    }
+
+    #[test]
+    fn test_visible_from() {
+        for from in Breakpoint::ALL {
+            let visible = visible_from(from);
+            for at in Breakpoint::ALL {
+                assert_eq!(visible.at(at), at >= from, "from {from:?} at {at:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_style_rules_cascade_upwards() {
+        let rules = Responsive::new(StyleRules::new().font(&FONT_6X10).gap(2))
+            .medium(StyleRules::new().gap(8))
+            .expanded(StyleRules::new().padding(Edges::all(16)));
+
+        let compact = rules.cascade(Breakpoint::Compact);
+        assert_eq!(compact.gap, Some(2));
+        assert_eq!(compact.padding, None);
+
+        let medium = rules.cascade(Breakpoint::Medium);
+        assert_eq!(medium.gap, Some(8));
+        assert!(medium.font.is_some());
+
+        let expanded = rules.cascade(Breakpoint::Expanded);
+        assert_eq!(expanded.gap, Some(8));
+        assert_eq!(expanded.padding, Some(Edges::all(16)));
+        assert!(expanded.font.is_some());
+    }
+}
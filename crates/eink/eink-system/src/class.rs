@@ -0,0 +1,579 @@
+//! Named style classes with inheritance and composition.
+//!
+//! A [`Style`] literal on every screen means a font or grey level change
+//! has to be found and repeated everywhere.  Classes name those choices
+//! once:
+//!
+//! - [`StyleRules`] is a partial style: every property is optional and
+//!   only the ones that are set override anything.
+//! - [`StyleClass`] gives rules a name and an optional parent class
+//!   ([`StyleClass::extends`]); [`MUTED`] is [`BODY`] with a lighter
+//!   colour, so changing `BODY`'s font changes both.
+//! - [`ComputedStyle`] is the result: a layout [`Style`] plus the
+//!   [`TextStyle`] used to draw text.  [`ComputedStyle::child_layout`]
+//!   hands it to the [`FlexLayout`](crate::flex::FlexLayout) engine.
+//!
+//! # Cascade
+//!
+//! [`ComputedStyle::cascade`] resolves a node from its parent node, its
+//! classes and its inline rules.  Later steps win:
+//!
+//! 1. **Inherited** from the parent node: the text properties (font,
+//!    colour, line spacing), as in CSS.  Box properties (size, margin,
+//!    padding, gap, background, flex) start from [`Style::new`] on every
+//!    node and are never inherited.
+//! 2. **Classes**, in the order given; within a class, its parent's rules
+//!    apply first.
+//! 3. **Inline** rules on the node itself.
+//!
+//! # Example
+//!
+//! ```
+//! use eink_system::class::{ComputedStyle, StyleRules, BODY, MUTED};
+//! use eink_system::style::Edges;
+//! use embedded_graphics::pixelcolor::{Gray4, GrayColor};
+//!
+//! let screen = ComputedStyle::ROOT;
+//! let artist = ComputedStyle::cascade(
+//!     &screen,
+//!     &[&MUTED],
+//!     &StyleRules::new().margin(Edges::all(4)),
+//! );
+//! assert_eq!(artist.text.font, BODY.resolve().text.font);
+//! assert_ne!(artist.text.color, Gray4::BLACK);
+//! assert_eq!(artist.layout.margin, Edges::all(4));
+//! ```
+
+use embedded_graphics::{
+    mono_font::{
+        ascii::{FONT_10X20, FONT_6X10, FONT_9X15},
+        MonoFont, MonoTextStyle,
+    },
+    pixelcolor::Gray4,
+    prelude::*,
+};
+
+use crate::flex::ChildLayout;
+use crate::style::{Align, Dimension, Edges, FlexDirection, Justify, Style};
+
+/// How text is drawn.  Every field is inherited by child nodes.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextStyle {
+    /// Monospace font.
+    pub font: &'static MonoFont<'static>,
+    /// Text colour.
+    pub color: Gray4,
+    /// Extra pixels between lines, on top of the font's own height.
+    pub line_spacing: u32,
+}
+
+impl TextStyle {
+    /// Black `FONT_10X20`, no extra line spacing.
+    pub const DEFAULT: Self = Self {
+        font: &FONT_10X20,
+        color: Gray4::BLACK,
+        line_spacing: 0,
+    };
+
+    /// embedded-graphics character style for drawing.
+    pub fn mono(&self) -> MonoTextStyle<'static, Gray4> {
+        MonoTextStyle::new(self.font, self.color)
+    }
+
+    /// Distance between baselines of consecutive lines.
+    pub fn line_height(&self) -> u32 {
+        self.font
+            .character_size
+            .height
+            .saturating_add(self.line_spacing)
+    }
+
+    /// Size of `text` on one line, for a component's intrinsic size.
+    pub fn text_size(&self, text: &str) -> Size {
+        let chars = u32::try_from(text.chars().count()).unwrap_or(u32::MAX);
+        if chars == 0 {
+            return Size::zero();
+        }
+        let advance = self
+            .font
+            .character_size
+            .width
+            .saturating_add(self.font.character_spacing);
+        let width = advance
+            .saturating_mul(chars)
+            .saturating_sub(self.font.character_spacing);
+        Size::new(width, self.font.character_size.height)
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A partial style: only the properties that are `Some` are applied.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct StyleRules {
+    /// Font (inherited).
+    pub font: Option<&'static MonoFont<'static>>,
+    /// Text colour (inherited).
+    pub color: Option<Gray4>,
+    /// Extra line spacing (inherited).
+    pub line_spacing: Option<u32>,
+    /// Width.
+    pub width: Option<Dimension>,
+    /// Height.
+    pub height: Option<Dimension>,
+    /// Margin.
+    pub margin: Option<Edges>,
+    /// Padding.
+    pub padding: Option<Edges>,
+    /// Gap between children.
+    pub gap: Option<u32>,
+    /// Main axis direction.
+    pub flex_direction: Option<FlexDirection>,
+    /// Main axis alignment.
+    pub justify_content: Option<Justify>,
+    /// Cross axis alignment.
+    pub align_items: Option<Align>,
+    /// Flex grow factor.
+    pub flex_grow: Option<f32>,
+    /// Background colour; `Some(None)` clears one set earlier.
+    pub background: Option<Option<Gray4>>,
+}
+
+impl StyleRules {
+    /// No rules.
+    pub const fn new() -> Self {
+        Self {
+            font: None,
+            color: None,
+            line_spacing: None,
+            width: None,
+            height: None,
+            margin: None,
+            padding: None,
+            gap: None,
+            flex_direction: None,
+            justify_content: None,
+            align_items: None,
+            flex_grow: None,
+            background: None,
+        }
+    }
+
+    /// Set the font.
+    pub const fn font(mut self, font: &'static MonoFont<'static>) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Set the text colour.
+    pub const fn color(mut self, color: Gray4) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Set the extra line spacing.
+    pub const fn line_spacing(mut self, spacing: u32) -> Self {
+        self.line_spacing = Some(spacing);
+        self
+    }
+
+    /// Set the width.
+    pub const fn width(mut self, width: Dimension) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Set the height.
+    pub const fn height(mut self, height: Dimension) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    /// Set the margin.
+    pub const fn margin(mut self, margin: Edges) -> Self {
+        self.margin = Some(margin);
+        self
+    }
+
+    /// Set the padding.
+    pub const fn padding(mut self, padding: Edges) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// Set the gap between children.
+    pub const fn gap(mut self, gap: u32) -> Self {
+        self.gap = Some(gap);
+        self
+    }
+
+    /// Set the main axis direction.
+    pub const fn flex_direction(mut self, direction: FlexDirection) -> Self {
+        self.flex_direction = Some(direction);
+        self
+    }
+
+    /// Set the main axis alignment.
+    pub const fn justify_content(mut self, justify: Justify) -> Self {
+        self.justify_content = Some(justify);
+        self
+    }
+
+    /// Set the cross axis alignment.
+    pub const fn align_items(mut self, align: Align) -> Self {
+        self.align_items = Some(align);
+        self
+    }
+
+    /// Set the flex grow factor.
+    pub const fn flex_grow(mut self, grow: f32) -> Self {
+        self.flex_grow = Some(grow);
+        self
+    }
+
+    /// Set the background colour.
+    pub const fn background(mut self, background: Gray4) -> Self {
+        self.background = Some(Some(background));
+        self
+    }
+
+    /// Remove any background set by an earlier class.
+    pub const fn no_background(mut self) -> Self {
+        self.background = Some(None);
+        self
+    }
+
+    /// `self` with every rule set in `other` replaced by `other`'s.
+    pub fn merge(self, other: &Self) -> Self {
+        Self {
+            font: other.font.or(self.font),
+            color: other.color.or(self.color),
+            line_spacing: other.line_spacing.or(self.line_spacing),
+            width: other.width.or(self.width),
+            height: other.height.or(self.height),
+            margin: other.margin.or(self.margin),
+            padding: other.padding.or(self.padding),
+            gap: other.gap.or(self.gap),
+            flex_direction: other.flex_direction.or(self.flex_direction),
+            justify_content: other.justify_content.or(self.justify_content),
+            align_items: other.align_items.or(self.align_items),
+            flex_grow: other.flex_grow.or(self.flex_grow),
+            background: other.background.or(self.background),
+        }
+    }
+
+    /// Apply the rules that are set to `style`.
+    pub fn apply(&self, style: &mut ComputedStyle) {
+        let text = &mut style.text;
+        if let Some(font) = self.font {
+            text.font = font;
+        }
+        if let Some(color) = self.color {
+            text.color = color;
+        }
+        if let Some(spacing) = self.line_spacing {
+            text.line_spacing = spacing;
+        }
+        let layout = &mut style.layout;
+        if let Some(width) = self.width {
+            layout.width = width;
+        }
+        if let Some(height) = self.height {
+            layout.height = height;
+        }
+        if let Some(margin) = self.margin {
+            layout.margin = margin;
+        }
+        if let Some(padding) = self.padding {
+            layout.padding = padding;
+        }
+        if let Some(gap) = self.gap {
+            layout.gap = gap;
+        }
+        if let Some(direction) = self.flex_direction {
+            layout.flex_direction = direction;
+        }
+        if let Some(justify) = self.justify_content {
+            layout.justify_content = justify;
+        }
+        if let Some(align) = self.align_items {
+            layout.align_items = align;
+        }
+        if let Some(grow) = self.flex_grow {
+            layout.flex_grow = grow;
+        }
+        if let Some(background) = self.background {
+            layout.background = background;
+        }
+    }
+}
+
+/// Rules with a name and an optional parent class.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct StyleClass {
+    /// Class name, for debugging and lookups.
+    pub name: &'static str,
+    /// Class whose rules apply before this one's.
+    pub parent: Option<&'static StyleClass>,
+    /// This class's own rules.
+    pub rules: StyleRules,
+}
+
+impl StyleClass {
+    /// A root class.
+    pub const fn new(name: &'static str, rules: StyleRules) -> Self {
+        Self {
+            name,
+            parent: None,
+            rules,
+        }
+    }
+
+    /// This class, inheriting every rule of `parent` it does not set itself.
+    pub const fn extends(mut self, parent: &'static StyleClass) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// The rules of this class and all its ancestors, flattened.
+    pub fn flatten(&self) -> StyleRules {
+        match self.parent {
+            Some(parent) => parent.flatten().merge(&self.rules),
+            None => self.rules,
+        }
+    }
+
+    /// This class applied to [`ComputedStyle::ROOT`].
+    pub fn resolve(&self) -> ComputedStyle {
+        ComputedStyle::cascade(&ComputedStyle::ROOT, &[self], &StyleRules::new())
+    }
+}
+
+/// Section and screen titles.
+pub const HEADING: StyleClass = StyleClass::new(
+    "heading",
+    StyleRules::new()
+        .font(&FONT_10X20)
+        .color(Gray4::BLACK)
+        .margin(Edges::new(0, 0, 8, 0)),
+);
+
+/// Running text.
+pub const BODY: StyleClass = StyleClass::new(
+    "body",
+    StyleRules::new()
+        .font(&FONT_9X15)
+        .color(Gray4::BLACK)
+        .line_spacing(4),
+);
+
+/// Secondary text: body with a lighter grey.
+pub const MUTED: StyleClass =
+    StyleClass::new("muted", StyleRules::new().color(Gray4::new(0x6))).extends(&BODY);
+
+/// Small print: muted in the small font.
+pub const CAPTION: StyleClass = StyleClass::new(
+    "caption",
+    StyleRules::new().font(&FONT_6X10).line_spacing(2),
+)
+.extends(&MUTED);
+
+/// Every built-in class, for lookups by name.
+pub const CLASSES: [&StyleClass; 4] = [&HEADING, &BODY, &MUTED, &CAPTION];
+
+/// Built-in class called `name`.
+pub fn class(name: &str) -> Option<&'static StyleClass> {
+    CLASSES.into_iter().find(|class| class.name == name)
+}
+
+/// Fully resolved style of one node.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ComputedStyle {
+    /// Box and flex properties, for the layout engine.
+    pub layout: Style,
+    /// Text properties, for drawing.
+    pub text: TextStyle,
+}
+
+impl ComputedStyle {
+    /// Style of the screen root: [`Style::new`] and [`TextStyle::DEFAULT`].
+    pub const ROOT: Self = Self {
+        layout: Style::new(),
+        text: TextStyle::DEFAULT,
+    };
+
+    /// Starting point for a child node: the text properties of `self`,
+    /// default box properties.
+    pub const fn inherit(&self) -> Self {
+        Self {
+            layout: Style::new(),
+            text: self.text,
+        }
+    }
+
+    /// Resolve a node under `parent` with `classes` (later ones win) and
+    /// `inline` rules on top.  See the [module docs](self) for the order.
+    pub fn cascade(parent: &Self, classes: &[&StyleClass], inline: &StyleRules) -> Self {
+        let mut style = parent.inherit();
+        for class in classes {
+            class.flatten().apply(&mut style);
+        }
+        inline.apply(&mut style);
+        style
+    }
+
+    /// Flex child for content of `content` size in this style.
+    ///
+    /// The flex engine places margins and padding inside the child's
+    /// slot, so both are added to the content size here.
+    pub fn child_layout(&self, content: Size) -> ChildLayout {
+        let size = Size::new(
+            content.width.saturating_add(self.layout.horizontal_space()),
+            content.height.saturating_add(self.layout.vertical_space()),
+        );
+        ChildLayout::new(self.layout, size)
+    }
+}
+
+impl Default for ComputedStyle {
+    fn default() -> Self {
+        Self::ROOT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::indexing_slicing)]
+    use super::*;
+    use crate::flex::FlexLayout;
+    use crate::layout::Constraints;
+
+    #[test]
+    fn test_class_inherits_parent_rules() {
+        let muted = MUTED.resolve();
+        assert_eq!(muted.text.font, &FONT_9X15);
+        assert_eq!(muted.text.line_spacing, 4);
+        assert_eq!(muted.text.color, Gray4::new(0x6));
+
+        let caption = CAPTION.resolve();
+        assert_eq!(caption.text.font, &FONT_6X10);
+        assert_eq!(caption.text.color, Gray4::new(0x6));
+        assert_eq!(caption.text.line_height(), 12);
+    }
+
+    #[test]
+    fn test_later_class_overrides_earlier() {
+        let root = ComputedStyle::ROOT;
+        let a = ComputedStyle::cascade(&root, &[&MUTED, &HEADING], &StyleRules::new());
+        assert_eq!(a.text.font, &FONT_10X20);
+        assert_eq!(a.text.color, Gray4::BLACK);
+
+        let b = ComputedStyle::cascade(&root, &[&HEADING, &MUTED], &StyleRules::new());
+        // MUTED brings BODY's font along with its own colour.
+        assert_eq!(b.text.font, &FONT_9X15);
+        assert_eq!(b.text.color, Gray4::new(0x6));
+        // HEADING's margin survives: MUTED does not set one.
+        assert_eq!(b.layout.margin, Edges::new(0, 0, 8, 0));
+    }
+
+    #[test]
+    fn test_inline_rules_beat_classes() {
+        let style = ComputedStyle::cascade(
+            &ComputedStyle::ROOT,
+            &[&HEADING],
+            &StyleRules::new().color(Gray4::WHITE).margin(Edges::all(2)),
+        );
+        assert_eq!(style.text.color, Gray4::WHITE);
+        assert_eq!(style.text.font, &FONT_10X20);
+        assert_eq!(style.layout.margin, Edges::all(2));
+    }
+
+    #[test]
+    fn test_text_inherits_but_box_does_not() {
+        let card = ComputedStyle::cascade(
+            &ComputedStyle::ROOT,
+            &[&MUTED],
+            &StyleRules::new()
+                .padding(Edges::all(12))
+                .background(Gray4::new(0xE)),
+        );
+        let child = ComputedStyle::cascade(&card, &[], &StyleRules::new());
+        assert_eq!(child.text, card.text);
+        assert_eq!(child.layout, Style::new());
+
+        let cleared = ComputedStyle::cascade(&card, &[], &StyleRules::new().no_background());
+        assert_eq!(cleared.layout.background, None);
+    }
+
+    #[test]
+    fn test_changing_a_base_class_propagates() {
+        static SMALL_BODY: StyleClass = StyleClass::new("body", StyleRules::new().font(&FONT_6X10));
+        static SMALL_MUTED: StyleClass =
+            StyleClass::new("muted", StyleRules::new().color(Gray4::new(0x6))).extends(&SMALL_BODY);
+        assert_eq!(SMALL_MUTED.resolve().text.font, &FONT_6X10);
+    }
+
+    #[test]
+    fn test_lookup_by_name() {
+        // `MonoFont` equality compares `dyn GlyphMapping` pointers, which
+        // differ between const promotions, so skip the inherited font.
+        let muted = class("muted").unwrap();
+        assert_eq!((muted.name, muted.rules), (MUTED.name, MUTED.rules));
+        assert!(class("missing").is_none());
+    }
+
+    #[test]
+    fn test_text_size() {
+        let text = BODY.resolve().text;
+        assert_eq!(text.text_size(""), Size::zero());
+        assert_eq!(text.text_size("abc"), Size::new(27, 15));
+    }
+
+    /// Lay out a heading and a muted line in a column, as a screen would.
+    fn column(heading_inline: &StyleRules, muted_inline: &StyleRules) -> [Point; 2] {
+        let screen = ComputedStyle::cascade(
+            &ComputedStyle::ROOT,
+            &[],
+            &StyleRules::new()
+                .flex_direction(FlexDirection::Column)
+                .padding(Edges::all(4)),
+        );
+        let heading = ComputedStyle::cascade(&screen, &[&HEADING], heading_inline);
+        let muted = ComputedStyle::cascade(&screen, &[&MUTED], muted_inline);
+        let children = [
+            heading.child_layout(heading.text.text_size("Albums")),
+            muted.child_layout(muted.text.text_size("12 albums")),
+        ];
+        let placed = FlexLayout::new(screen.layout)
+            .layout(Constraints::tight(Size::new(200, 100)), &children);
+        [placed[0].position, placed[1].position]
+    }
+
+    #[test]
+    fn test_layout_uses_class_spacing() {
+        let [heading, muted] = column(&StyleRules::new(), &StyleRules::new());
+        assert_eq!(heading, Point::new(4, 4));
+        // 20 px heading font plus HEADING's 8 px bottom margin.
+        assert_eq!(muted, Point::new(4, 32));
+    }
+
+    #[test]
+    fn test_layout_inline_margin_overrides_class() {
+        let [heading, muted] = column(
+            &StyleRules::new().margin(Edges::new(2, 0, 0, 6)),
+            &StyleRules::new(),
+        );
+        assert_eq!(heading, Point::new(10, 6));
+        assert_eq!(muted, Point::new(4, 26));
+    }
+
+    #[test]
+    fn test_layout_inline_font_changes_intrinsic_size() {
+        let [_, muted] = column(&StyleRules::new().font(&FONT_6X10), &StyleRules::new());
+        assert_eq!(muted, Point::new(4, 22));
+    }
+}
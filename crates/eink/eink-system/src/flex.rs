@@ -43,6 +43,7 @@ use crate::layout::Constraints;
 // False positive: Dimension and Edges are used in tests (9 and 6 occurrences respectively)
 #[allow(unused_imports)]
 use crate::style::{Align, Dimension, Edges, FlexDirection, Justify, Style};
+use eink_specs::Px;
 use embedded_graphics::prelude::*;
 use heapless::Vec as HeaplessVec;
 
@@ -174,7 +175,9 @@ impl FlexLayout {
         let total_used = total_intrinsic + gap_space;
 
         // Step 4: Distribute remaining space (flex-grow) or shrink (flex-shrink)
-        let remaining_space = available_main as i32 - total_used as i32;
+        let remaining_space = Px::new(available_main)
+            .offset()
+            .saturating_sub(Px::new(total_used).offset());
         self.apply_flex_sizing(&mut flex_items, remaining_space);
 
         // Step 5: Apply justification on main axis
@@ -211,7 +214,7 @@ impl FlexLayout {
 
             result
                 .push(ChildLayoutResult {
-                    position: Point::new(final_x as i32, final_y as i32),
+                    position: Point::new(Px::new(final_x).offset(), Px::new(final_y).offset()),
                     size: Size::new(final_width, final_height),
                 })
                 .ok();
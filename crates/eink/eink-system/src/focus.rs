@@ -0,0 +1,594 @@
+//! Focus management for encoder and button navigation
+//!
+//! The player has no touch screen: one component at a time holds focus and
+//! the rotary encoder moves it.  [`FocusTree`] replaces per-screen selection
+//! index math with a list of focusable nodes kept in layout order and
+//! identified by a stable [`FocusId`], so focus stays on the same component
+//! when the layout is rebuilt (items added, removed or reflowed).
+//!
+//! - Encoder rotation calls [`FocusTree::move_focus`]; each detent moves one
+//!   node forward (clockwise) or back, skipping disabled nodes.
+//! - [`FocusTree::set_next`] / [`FocusTree::set_prev`] override layout order
+//!   for individual nodes (e.g. jump from the last list row to "Back").
+//! - [`FocusModifier`] is the style change applied to the focused component;
+//!   [`FocusTree::style_for`] applies it only when the node has focus.
+//!
+//! Every focus change is returned as a [`FocusChange`] carrying the bounds
+//! of the old and new node — the two regions that need a partial refresh.
+//!
+//! # Example
+//!
+//! ```rust
+//! use eink_system::focus::{FocusId, FocusTree};
+//! use embedded_graphics::{prelude::*, primitives::Rectangle};
+//!
+//! const PLAY: FocusId = FocusId(1);
+//! const SHUFFLE: FocusId = FocusId(2);
+//! const BACK: FocusId = FocusId(3);
+//!
+//! let mut focus = FocusTree::<8>::new();
+//! focus
+//!     .rebuild([
+//!         (PLAY, Rectangle::new(Point::new(0, 0), Size::new(100, 20))),
+//!         (SHUFFLE, Rectangle::new(Point::new(0, 20), Size::new(100, 20))),
+//!         (BACK, Rectangle::new(Point::new(0, 40), Size::new(100, 20))),
+//!     ])
+//!     .unwrap();
+//!
+//! assert_eq!(focus.focused(), Some(PLAY));
+//! let change = focus.move_focus(1).unwrap();
+//! assert_eq!(change.current, SHUFFLE);
+//! ```
+
+use crate::layout::LayoutResult;
+use crate::render::envelope;
+use crate::style::Style;
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+use heapless::Vec;
+
+/// Stable identifier of a focusable component.
+///
+/// Screens usually declare these as constants; the value only has to be
+/// unique within one [`FocusTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FocusId(pub u16);
+
+/// A focusable node: its id and on-screen bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusNode {
+    /// Component identifier.
+    pub id: FocusId,
+    /// Absolute bounds of the component.
+    pub bounds: Rectangle,
+}
+
+/// Result of a focus move: what lost focus and what gained it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusChange {
+    /// Previously focused node and its bounds, if any.
+    pub previous: Option<FocusNode>,
+    /// Newly focused node.
+    pub current: FocusId,
+    /// Bounds of the newly focused node.
+    pub current_bounds: Rectangle,
+}
+
+impl FocusChange {
+    /// Smallest rectangle covering both affected components.
+    pub fn dirty_region(&self) -> Rectangle {
+        match self.previous {
+            Some(prev) => envelope(prev.bounds, self.current_bounds),
+            None => self.current_bounds,
+        }
+    }
+}
+
+/// Style change applied to the focused component.
+///
+/// The default draws a 2 px black outline and leaves the fill alone, which
+/// renders identically in 1-bit (DU) and Gray4 modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FocusModifier {
+    /// Background override for the focused component.
+    pub background: Option<Gray4>,
+    /// Outline drawn inside the component bounds (0 = none).
+    pub outline_width: u32,
+    /// Outline colour.
+    pub outline_color: Gray4,
+}
+
+impl FocusModifier {
+    /// Outline-only modifier.
+    pub const fn outline(width: u32, color: Gray4) -> Self {
+        Self {
+            background: None,
+            outline_width: width,
+            outline_color: color,
+        }
+    }
+
+    /// Builder method to set the background override.
+    pub const fn background(mut self, color: Gray4) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Apply the modifier to a component style.
+    pub fn apply(self, style: Style) -> Style {
+        match self.background {
+            Some(color) => style.background(color),
+            None => style,
+        }
+    }
+
+    /// Draw the focus outline inside `bounds`.
+    pub fn draw<D>(self, display: &mut D, bounds: Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        if self.outline_width == 0 {
+            return Ok(());
+        }
+        bounds
+            .into_styled(PrimitiveStyle::with_stroke(
+                self.outline_color,
+                self.outline_width,
+            ))
+            .draw(display)
+    }
+}
+
+impl Default for FocusModifier {
+    fn default() -> Self {
+        Self::outline(2, Gray4::BLACK)
+    }
+}
+
+/// Focusable components of one screen, in layout order.
+///
+/// `N` is the maximum number of focusable nodes.
+#[derive(Debug, Clone)]
+pub struct FocusTree<const N: usize> {
+    nodes: Vec<FocusNode, N>,
+    /// Explicit `(from, to)` overrides for forward moves.
+    next: Vec<(FocusId, FocusId), N>,
+    /// Explicit `(from, to)` overrides for backward moves.
+    prev: Vec<(FocusId, FocusId), N>,
+    disabled: Vec<FocusId, N>,
+    focused: Option<FocusId>,
+    wrap: bool,
+    modifier: FocusModifier,
+}
+
+impl<const N: usize> FocusTree<N> {
+    /// Create an empty tree that wraps around at either end.
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            next: Vec::new(),
+            prev: Vec::new(),
+            disabled: Vec::new(),
+            focused: None,
+            wrap: true,
+            modifier: FocusModifier::default(),
+        }
+    }
+
+    /// Builder method to stop at the first/last node instead of wrapping.
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Builder method to set the focus style modifier.
+    pub fn modifier(mut self, modifier: FocusModifier) -> Self {
+        self.modifier = modifier;
+        self
+    }
+
+    /// Focus style modifier.
+    pub fn focus_modifier(&self) -> &FocusModifier {
+        &self.modifier
+    }
+
+    /// Focusable nodes in layout order.
+    pub fn nodes(&self) -> &[FocusNode] {
+        &self.nodes
+    }
+
+    /// Currently focused component.
+    pub fn focused(&self) -> Option<FocusId> {
+        self.focused
+    }
+
+    /// Bounds of the currently focused component.
+    pub fn focused_bounds(&self) -> Option<Rectangle> {
+        self.focused
+            .and_then(|id| self.node(id))
+            .map(|node| node.bounds)
+    }
+
+    /// Whether `id` has focus.
+    pub fn is_focused(&self, id: FocusId) -> bool {
+        self.focused == Some(id)
+    }
+
+    /// Look up a node by id.
+    pub fn node(&self, id: FocusId) -> Option<&FocusNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    fn position(&self, id: FocusId) -> Option<usize> {
+        self.nodes.iter().position(|node| node.id == id)
+    }
+
+    /// Whether `id` can receive focus.
+    pub fn is_enabled(&self, id: FocusId) -> bool {
+        !self.disabled.contains(&id)
+    }
+
+    /// Replace the nodes with a new layout pass, in layout order.
+    ///
+    /// Focus stays on the same id if it is still present. Otherwise it moves
+    /// to the enabled node now at (or nearest after) the old position, so
+    /// deleting the focused list row focuses the row that took its place.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first id that did not fit into the tree's capacity `N`.
+    pub fn rebuild<I>(&mut self, nodes: I) -> Result<(), FocusId>
+    where
+        I: IntoIterator<Item = (FocusId, Rectangle)>,
+    {
+        let old_index = self.focused.and_then(|id| self.position(id));
+        self.nodes.clear();
+        let mut overflow = None;
+        for (id, bounds) in nodes {
+            if self.nodes.push(FocusNode { id, bounds }).is_err() && overflow.is_none() {
+                overflow = Some(id);
+            }
+        }
+
+        let still_present = self.focused.is_some_and(|id| self.position(id).is_some());
+        if !still_present {
+            self.focused = None;
+            let start = old_index.unwrap_or(0);
+            let candidates = self.nodes.iter().skip(start).chain(self.nodes.iter().rev());
+            for node in candidates {
+                if self.is_enabled(node.id) {
+                    self.focused = Some(node.id);
+                    break;
+                }
+            }
+        }
+        overflow.map_or(Ok(()), Err)
+    }
+
+    /// Rebuild from a computed layout positioned at `origin`.
+    ///
+    /// `ids` pairs with `layout.children` in order; `None` marks a child that
+    /// is not focusable (labels, spacers).
+    ///
+    /// # Errors
+    ///
+    /// See [`rebuild`](Self::rebuild).
+    // SAFETY: origin and child offsets are display coordinates; their sum fits in i32.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn rebuild_from_layout(
+        &mut self,
+        origin: Point,
+        layout: &LayoutResult,
+        ids: &[Option<FocusId>],
+    ) -> Result<(), FocusId> {
+        self.rebuild(
+            layout
+                .children
+                .iter()
+                .zip(ids.iter())
+                .filter_map(|(child, id)| {
+                    id.map(|id| {
+                        let bounds = child.bounds();
+                        (id, Rectangle::new(origin + bounds.top_left, bounds.size))
+                    })
+                }),
+        )
+    }
+
+    /// Override the node that follows `from` when moving forward.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(from)` if the override table is full.
+    pub fn set_next(&mut self, from: FocusId, to: FocusId) -> Result<(), FocusId> {
+        set_override(&mut self.next, from, to)
+    }
+
+    /// Override the node that precedes `from` when moving backward.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(from)` if the override table is full.
+    pub fn set_prev(&mut self, from: FocusId, to: FocusId) -> Result<(), FocusId> {
+        set_override(&mut self.prev, from, to)
+    }
+
+    /// Enable or disable a node. Disabling the focused node moves focus
+    /// forward to the next enabled node.
+    ///
+    /// Returns the resulting focus change, if any.
+    pub fn set_enabled(&mut self, id: FocusId, enabled: bool) -> Option<FocusChange> {
+        if enabled {
+            self.disabled.retain(|&d| d != id);
+            if self.focused.is_none() && self.node(id).is_some() {
+                return self.focus(id);
+            }
+            return None;
+        }
+        if !self.disabled.contains(&id) {
+            // Capacity N covers every node; an unknown id past capacity is ignored.
+            let _ = self.disabled.push(id);
+        }
+        if self.is_focused(id) {
+            let change = self.move_focus(1);
+            if self.is_focused(id) {
+                // No other enabled node.
+                self.focused = None;
+            }
+            return change;
+        }
+        None
+    }
+
+    /// Focus `id` directly (e.g. restoring a remembered selection).
+    ///
+    /// Returns `None` if `id` is unknown, disabled or already focused.
+    pub fn focus(&mut self, id: FocusId) -> Option<FocusChange> {
+        if self.is_focused(id) || !self.is_enabled(id) {
+            return None;
+        }
+        let node = *self.node(id)?;
+        let previous = self.focused.and_then(|prev| self.node(prev).copied());
+        self.focused = Some(id);
+        Some(FocusChange {
+            previous,
+            current: id,
+            current_bounds: node.bounds,
+        })
+    }
+
+    /// Move focus by `steps` encoder detents (positive = forward/clockwise).
+    ///
+    /// Returns `None` when focus did not change (no enabled nodes, or at
+    /// the end of a non-wrapping tree).
+    pub fn move_focus(&mut self, steps: i32) -> Option<FocusChange> {
+        let Some(start) = self.focused else {
+            let first = self.nodes.iter().find(|node| self.is_enabled(node.id))?;
+            return self.focus(first.id);
+        };
+        let forward = steps > 0;
+        let mut target = start;
+        for _ in 0..steps.unsigned_abs() {
+            match self.step(target, forward) {
+                Some(id) => target = id,
+                None => break,
+            }
+        }
+        if target == start {
+            return None;
+        }
+        self.focus(target)
+    }
+
+    /// The enabled node one step from `from`, following overrides first.
+    fn step(&self, from: FocusId, forward: bool) -> Option<FocusId> {
+        let overrides = if forward { &self.next } else { &self.prev };
+        if let Some(&(_, to)) = overrides.iter().find(|(f, _)| *f == from) {
+            if self.is_enabled(to) && self.node(to).is_some() {
+                return Some(to);
+            }
+        }
+
+        let len = self.nodes.len();
+        let mut index = self.position(from)?;
+        // At most one lap around the tree.
+        for _ in 0..len {
+            index = if forward {
+                match index.checked_add(1).filter(|&i| i < len) {
+                    Some(i) => i,
+                    None if self.wrap => 0,
+                    None => return None,
+                }
+            } else {
+                match index.checked_sub(1) {
+                    Some(i) => i,
+                    None if self.wrap => len.saturating_sub(1),
+                    None => return None,
+                }
+            };
+            let node = self.nodes.get(index)?;
+            if node.id == from {
+                return None;
+            }
+            if self.is_enabled(node.id) {
+                return Some(node.id);
+            }
+        }
+        None
+    }
+
+    /// `style` with the focus modifier applied if `id` has focus.
+    pub fn style_for(&self, id: FocusId, style: Style) -> Style {
+        if self.is_focused(id) {
+            self.modifier.apply(style)
+        } else {
+            style
+        }
+    }
+
+    /// Draw the focus outline around the focused node, if any.
+    pub fn draw_focus<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        match self.focused_bounds() {
+            Some(bounds) => self.modifier.draw(display, bounds),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<const N: usize> Default for FocusTree<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn set_override<const N: usize>(
+    table: &mut Vec<(FocusId, FocusId), N>,
+    from: FocusId,
+    to: FocusId,
+) -> Result<(), FocusId> {
+    if let Some(entry) = table.iter_mut().find(|(f, _)| *f == from) {
+        entry.1 = to;
+        return Ok(());
+    }
+    table.push((from, to)).map_err(|_| from)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+    #![allow(clippy::unwrap_used, clippy::expect_used)]
+    use super::*;
+    use crate::layout::ChildLayout;
+
+    fn row(i: u16) -> (FocusId, Rectangle) {
+        (
+            FocusId(i),
+            Rectangle::new(Point::new(0, i32::from(i) * 20), Size::new(100, 20)),
+        )
+    }
+
+    fn tree(count: u16) -> FocusTree<8> {
+        let mut tree = FocusTree::new();
+        tree.rebuild((0..count).map(row)).unwrap();
+        tree
+    }
+
+    #[test]
+    fn test_first_node_focused_after_build() {
+        let tree = tree(3);
+        assert_eq!(tree.focused(), Some(FocusId(0)));
+        assert_eq!(tree.focused_bounds(), Some(row(0).1));
+    }
+
+    #[test]
+    fn test_encoder_moves_and_wraps() {
+        let mut tree = tree(3);
+        let change = tree.move_focus(1).unwrap();
+        assert_eq!(change.current, FocusId(1));
+        assert_eq!(change.previous.unwrap().id, FocusId(0));
+        assert_eq!(
+            change.dirty_region(),
+            Rectangle::new(Point::zero(), Size::new(100, 40))
+        );
+        tree.move_focus(2);
+        assert_eq!(tree.focused(), Some(FocusId(0)));
+        tree.move_focus(-1);
+        assert_eq!(tree.focused(), Some(FocusId(2)));
+    }
+
+    #[test]
+    fn test_non_wrapping_stops_at_ends() {
+        let mut tree = tree(3).wrap(false);
+        assert!(tree.move_focus(-1).is_none());
+        tree.move_focus(10);
+        assert_eq!(tree.focused(), Some(FocusId(2)));
+        assert!(tree.move_focus(1).is_none());
+    }
+
+    #[test]
+    fn test_disabled_nodes_are_skipped() {
+        let mut tree = tree(3);
+        tree.set_enabled(FocusId(1), false);
+        tree.move_focus(1);
+        assert_eq!(tree.focused(), Some(FocusId(2)));
+        // Disabling the focused node moves focus on.
+        let change = tree.set_enabled(FocusId(2), false).unwrap();
+        assert_eq!(change.current, FocusId(0));
+        assert!(tree.focus(FocusId(1)).is_none());
+    }
+
+    #[test]
+    fn test_explicit_override() {
+        let mut tree = tree(4);
+        tree.set_next(FocusId(0), FocusId(3)).unwrap();
+        tree.set_prev(FocusId(3), FocusId(0)).unwrap();
+        tree.move_focus(1);
+        assert_eq!(tree.focused(), Some(FocusId(3)));
+        tree.move_focus(-1);
+        assert_eq!(tree.focused(), Some(FocusId(0)));
+    }
+
+    #[test]
+    fn test_focus_survives_rebuild() {
+        let mut tree = tree(4);
+        tree.focus(FocusId(2));
+        // A row is inserted above: same id, new position.
+        tree.rebuild([row(0), row(9), row(1), row(2), row(3)])
+            .unwrap();
+        assert_eq!(tree.focused(), Some(FocusId(2)));
+
+        // The focused row is removed: its successor takes focus.
+        tree.rebuild([row(0), row(9), row(1), row(3)]).unwrap();
+        assert_eq!(tree.focused(), Some(FocusId(3)));
+
+        // The last row is removed while focused: fall back to the new last.
+        tree.rebuild([row(0), row(9), row(1)]).unwrap();
+        assert_eq!(tree.focused(), Some(FocusId(1)));
+    }
+
+    #[test]
+    fn test_rebuild_reports_overflow() {
+        let mut tree = FocusTree::<2>::new();
+        assert_eq!(tree.rebuild((0..3).map(row)), Err(FocusId(2)));
+        assert_eq!(tree.nodes().len(), 2);
+    }
+
+    #[test]
+    fn test_rebuild_from_layout_skips_unfocusable_children() {
+        let mut layout = LayoutResult::leaf(Size::new(100, 60));
+        for y in [0, 20, 40] {
+            layout
+                .add_child(ChildLayout::new(Point::new(0, y), Size::new(100, 20)))
+                .unwrap();
+        }
+        let mut tree = FocusTree::<4>::new();
+        tree.rebuild_from_layout(
+            Point::new(10, 10),
+            &layout,
+            &[None, Some(FocusId(1)), Some(FocusId(2))],
+        )
+        .unwrap();
+        assert_eq!(tree.nodes().len(), 2);
+        assert_eq!(
+            tree.focused_bounds(),
+            Some(Rectangle::new(Point::new(10, 30), Size::new(100, 20)))
+        );
+    }
+
+    #[test]
+    fn test_style_modifier_only_for_focused() {
+        let tree = tree(2).modifier(FocusModifier::default().background(Gray4::new(0x4)));
+        let base = Style::new();
+        assert_eq!(
+            tree.style_for(FocusId(0), base).background,
+            Some(Gray4::new(0x4))
+        );
+        assert_eq!(tree.style_for(FocusId(1), base).background, None);
+    }
+}
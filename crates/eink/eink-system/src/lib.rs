@@ -5,9 +5,13 @@
 //! # Architecture
 //!
 //! - Core types: Dimension, Edges, Style, Constraints
+//! - Style classes: named, inheritable styles (heading, body, muted)
+//! - Breakpoints: per-panel-size values so one screen fits every display
 //! - Flexbox engine: Full flexbox layout algorithm
 //! - Containers: VStack, HStack, Spacer
+//! - Focus: encoder/button navigation between focusable components
 //! - Rendering: Integration with embedded-graphics
+//! - Screens: `screen!` macro declaring layout, test ids and focus order
 //!
 //! # Example
 //!
@@ -49,27 +53,45 @@
 // TODO: Add rustdoc to all public items (tracked as tech debt)
 #![allow(missing_docs)]
 
+pub mod breakpoint;
+pub mod class;
 pub mod containers;
 #[cfg(feature = "debug")]
 pub mod debug;
 pub mod flex;
+pub mod focus;
 pub mod layout;
 pub mod render;
 pub mod style;
+pub mod ui;
+
+pub use eink_specs::Px;
 
 pub mod prelude {
     // Style system (public API)
     pub use crate::style::*;
 
+    // Responsive breakpoints (public API)
+    pub use crate::breakpoint::{visible_from, Breakpoint, Breakpoints, Responsive};
+
+    // Style classes (public API)
+    pub use crate::class::{ComputedStyle, StyleClass, StyleRules, TextStyle};
+
     // Flex layout (public API - includes ChildLayout)
     pub use crate::flex::{ChildLayout, FlexLayout};
 
     // Containers (public API)
     pub use crate::containers::*;
 
+    // Focus navigation (public API)
+    pub use crate::focus::{FocusChange, FocusId, FocusModifier, FocusNode, FocusTree};
+
     // Render utilities (public API)
     pub use crate::render::*;
 
     // Layout traits (public API)
     pub use crate::layout::{Constraints, Layout, LayoutResult};
+
+    // Typed pixel extents (public API)
+    pub use eink_specs::Px;
 }
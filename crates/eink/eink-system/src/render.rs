@@ -34,6 +34,7 @@
 //! # Ok::<(), core::convert::Infallible>(())
 //! ```
 
+use eink_specs::Px;
 use embedded_graphics::{
     pixelcolor::Gray4,
     prelude::*,
@@ -343,14 +344,11 @@ pub fn render_layout_with_background<D: DrawTarget<Color = Gray4>>(
 /// let offscreen = Rectangle::new(Point::new(200, 200), Size::new(50, 50));
 /// assert!(!is_visible(offscreen, clip));
 /// ```
-// SAFETY: coordinate arithmetic here adds i32 positions and i32-cast pixel sizes.
-// Display dimensions (max ~4000px) added to typical screen coordinates are far from i32::MAX.
-#[allow(clippy::arithmetic_side_effects)]
 pub fn is_visible(rect: Rectangle, clip_bounds: Rectangle) -> bool {
-    let rect_right = rect.top_left.x + rect.size.width as i32;
-    let rect_bottom = rect.top_left.y + rect.size.height as i32;
-    let clip_right = clip_bounds.top_left.x + clip_bounds.size.width as i32;
-    let clip_bottom = clip_bounds.top_left.y + clip_bounds.size.height as i32;
+    let rect_right = Px::new(rect.size.width).end_of(rect.top_left.x);
+    let rect_bottom = Px::new(rect.size.height).end_of(rect.top_left.y);
+    let clip_right = Px::new(clip_bounds.size.width).end_of(clip_bounds.top_left.x);
+    let clip_bottom = Px::new(clip_bounds.size.height).end_of(clip_bounds.top_left.y);
 
     // Check if rectangles intersect
     !(rect.top_left.x >= clip_right
@@ -359,6 +357,37 @@ pub fn is_visible(rect: Rectangle, clip_bounds: Rectangle) -> bool {
         || rect_bottom <= clip_bounds.top_left.y)
 }
 
+/// Smallest rectangle containing both `a` and `b`
+///
+/// Used to merge dirty regions. A zero-sized rectangle covers nothing and
+/// is ignored, so folding from [`Rectangle::zero`] is safe.
+///
+/// # Example
+///
+/// ```
+/// use eink_system::render::*;
+/// use embedded_graphics::prelude::*;
+/// use embedded_graphics::primitives::Rectangle;
+///
+/// let a = Rectangle::new(Point::new(0, 0), Size::new(10, 10));
+/// let b = Rectangle::new(Point::new(20, 5), Size::new(10, 10));
+/// assert_eq!(
+///     envelope(a, b),
+///     Rectangle::new(Point::new(0, 0), Size::new(30, 15))
+/// );
+/// assert_eq!(envelope(Rectangle::zero(), b), b);
+/// ```
+pub fn envelope(a: Rectangle, b: Rectangle) -> Rectangle {
+    match (a.bottom_right(), b.bottom_right()) {
+        (Some(a_end), Some(b_end)) => Rectangle::with_corners(
+            a.top_left.component_min(b.top_left),
+            a_end.component_max(b_end),
+        ),
+        (Some(_), None) => a,
+        _ => b,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used, clippy::expect_used)]
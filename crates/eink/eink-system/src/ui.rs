@@ -0,0 +1,493 @@
+//! Declarative screen descriptions
+//!
+//! A screen used to be three hand-maintained lists: the draw calls, the
+//! `register_component` calls a test makes so `query_by_test_id` works, and
+//! the focus order.  They drifted — a component moved in the render code
+//! kept its old bounds in the tests.  The [`screen!`](crate::screen) macro
+//! declares the components, their test ids and focus order once and
+//! generates all three from the same layout pass:
+//!
+//! - `layout(origin)` runs the container [`Style`] through [`FlexLayout`]
+//!   and returns a [`ScreenLayout`]: one [`ScreenNode`] per component with
+//!   its test id, type name, absolute bounds and focus order.
+//! - `render(display, origin)` draws every component at its node's
+//!   top-left corner and returns the layout it used.
+//! - `focus_tree(origin)` builds a [`FocusTree`] in the declared focus order.
+//!
+//! The declared `size` is the default.  `layout_in(origin, size)` and
+//! `render_in(display, origin)` lay the same screen out for another panel;
+//! a child marked `from Medium` (any [`Breakpoint`]) is left out of the
+//! layout, and not drawn, on panels below that class.
+//!
+//! Tests hand the [`ScreenLayout`] to `eink_testing::TestEmulator::register_screen`,
+//! so registrations always match what was drawn.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use eink_components::prelude::*;
+//! use eink_system::prelude::*;
+//! use embedded_graphics::prelude::*;
+//!
+//! eink_system::screen! {
+//!     /// Playback controls
+//!     pub struct Controls {
+//!         size: Size::new(250, 122),
+//!         style: Style::new()
+//!             .flex_direction(FlexDirection::Column)
+//!             .gap(4)
+//!             .padding(Edges::all(8)),
+//!         children: {
+//!             progress: ProgressBar = ProgressBar::new(234, 8) => "progress";
+//!             play: Toggle = Toggle::new(false) => "play", focus 1;
+//!             gapless: Checkbox = Checkbox::new(true).label("Gapless") => "gapless", focus 2;
+//!             hint: Label = Label::new("Hold to seek") => "hint", from Medium;
+//!         }
+//!     }
+//! }
+//!
+//! let screen = Controls::new();
+//! let layout = screen.render(&mut display, Point::zero())?;
+//! test_emulator.register_screen(&layout);
+//! let mut focus = screen.focus_tree::<4>(Point::zero());
+//! ```
+//!
+//! Every child type must implement [`Layout`](crate::layout::Layout) (for
+//! its size) and have an inherent
+//! `render(&self, &mut D, Point) -> Result<(), D::Error>` method, the
+//! signature shared by all `eink-components` widgets.
+
+use crate::breakpoint::Breakpoint;
+use crate::flex::{ChildLayout, FlexLayout};
+use crate::focus::{FocusId, FocusTree};
+use crate::layout::{Constraints, Layout, MAX_CHILDREN};
+use crate::style::Style;
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use heapless::Vec;
+
+#[doc(hidden)]
+pub use embedded_graphics as __embedded_graphics;
+#[doc(hidden)]
+pub use heapless as __heapless;
+
+/// One component of a declared screen, before layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenEntry {
+    /// Test identifier (`data-testid` equivalent).
+    pub test_id: &'static str,
+    /// Component type name, e.g. `"Button"`.
+    pub component_type: &'static str,
+    /// Intrinsic size reported by the component's [`Layout`] impl.
+    pub size: Size,
+    /// Position in the focus order, if focusable.
+    pub focus: Option<u16>,
+}
+
+/// One component of a laid-out screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenNode {
+    /// Test identifier (`data-testid` equivalent).
+    pub test_id: &'static str,
+    /// Component type name, e.g. `"Button"`.
+    pub component_type: &'static str,
+    /// Absolute bounds on the display.
+    pub bounds: Rectangle,
+    /// Focus id (its position in the focus order), if focusable.
+    pub focus: Option<FocusId>,
+}
+
+/// Result of laying out a declared screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenLayout {
+    /// Screen bounds.
+    pub bounds: Rectangle,
+    nodes: Vec<ScreenNode, MAX_CHILDREN>,
+}
+
+impl ScreenLayout {
+    /// Lay out `entries` in declaration order with the container `style`.
+    ///
+    /// Entries beyond [`MAX_CHILDREN`] are dropped.
+    // SAFETY: origin and child positions are display coordinates; sums fit in i32.
+    #[allow(clippy::arithmetic_side_effects)]
+    pub fn compute(style: Style, size: Size, origin: Point, entries: &[ScreenEntry]) -> Self {
+        let children: Vec<ChildLayout, MAX_CHILDREN> = entries
+            .iter()
+            .take(MAX_CHILDREN)
+            .map(|entry| ChildLayout::new(Style::default(), entry.size))
+            .collect();
+        let placed = FlexLayout::new(style).layout(Constraints::tight(size), &children);
+
+        let nodes = entries
+            .iter()
+            .zip(placed.iter())
+            .map(|(entry, child)| ScreenNode {
+                test_id: entry.test_id,
+                component_type: entry.component_type,
+                bounds: Rectangle::new(origin + child.position, child.size),
+                focus: entry.focus.map(FocusId),
+            })
+            .collect();
+        Self {
+            bounds: Rectangle::new(origin, size),
+            nodes,
+        }
+    }
+
+    /// Components in declaration order.
+    pub fn nodes(&self) -> &[ScreenNode] {
+        &self.nodes
+    }
+
+    /// Find a component by test id.
+    pub fn node(&self, test_id: &str) -> Option<&ScreenNode> {
+        self.nodes.iter().find(|node| node.test_id == test_id)
+    }
+
+    /// Focus tree over the focusable components, in focus order.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first focus id that did not fit into capacity `N`.
+    pub fn focus_tree<const N: usize>(&self) -> Result<FocusTree<N>, FocusId> {
+        let mut focusable: Vec<(FocusId, Rectangle), MAX_CHILDREN> = self
+            .nodes
+            .iter()
+            .filter_map(|node| node.focus.map(|id| (id, node.bounds)))
+            .collect();
+        focusable.sort_unstable_by_key(|&(id, _)| id);
+        let mut tree = FocusTree::new();
+        tree.rebuild(focusable)?;
+        Ok(tree)
+    }
+}
+
+/// Intrinsic size of `component` on a screen of `max` size.
+#[doc(hidden)]
+pub fn intrinsic_size<L: Layout>(component: &L, max: Size) -> Size {
+    component.layout(Constraints::loose(max)).size
+}
+
+/// Whether a child declared `from` a breakpoint is shown on a screen of
+/// `size`; children without one always are.
+#[doc(hidden)]
+pub fn shown(from: Option<Breakpoint>, size: Size) -> bool {
+    from.is_none_or(|from| Breakpoint::of(size) >= from)
+}
+
+/// Declare a screen: components, test ids and focus order in one place.
+///
+/// Generates a struct holding the components plus `new()`, `TEST_IDS`,
+/// `layout()`, `render()` and `focus_tree()`; see the [`ui`](crate::ui)
+/// module docs for an example.
+///
+/// Each child is `field: Type = init => "test-id"`, optionally followed by
+/// `, focus N` to make it focusable at position `N` of the focus order and
+/// `, from Class` to show it only on [`Breakpoint`] `Class` and larger, and
+/// terminated by `;`.
+#[macro_export]
+macro_rules! screen {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            size: $size:expr,
+            style: $style:expr,
+            children: {
+                $(
+                    $field:ident : $ty:ty = $init:expr => $test_id:literal
+                    $(, focus $order:literal)?
+                    $(, from $from:ident)?
+                );* $(;)?
+            } $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(pub $field: $ty,)*
+        }
+
+        impl $name {
+            /// Test ids of every component, in declaration order.
+            pub const TEST_IDS: &'static [&'static str] = &[$($test_id),*];
+
+            /// Create the screen with its declared initial components.
+            pub fn new() -> Self {
+                Self {
+                    $($field: $init,)*
+                }
+            }
+
+            /// Screen size.
+            pub fn size() -> $crate::ui::__embedded_graphics::prelude::Size {
+                $size
+            }
+
+            /// Lay the screen out with its top-left corner at `origin`.
+            pub fn layout(
+                &self,
+                origin: $crate::ui::__embedded_graphics::prelude::Point,
+            ) -> $crate::ui::ScreenLayout {
+                self.layout_in(origin, Self::size())
+            }
+
+            /// Lay the screen out on a panel of `size` instead of the
+            /// declared one, leaving out children below their breakpoint.
+            pub fn layout_in(
+                &self,
+                origin: $crate::ui::__embedded_graphics::prelude::Point,
+                size: $crate::ui::__embedded_graphics::prelude::Size,
+            ) -> $crate::ui::ScreenLayout {
+                #[allow(unused_mut)]
+                let mut shown: $crate::ui::__heapless::Vec<
+                    $crate::ui::ScreenEntry,
+                    { $crate::layout::MAX_CHILDREN },
+                > = $crate::ui::__heapless::Vec::new();
+                $(
+                    let from = Option::<$crate::breakpoint::Breakpoint>::None
+                        $(.or(Some($crate::breakpoint::Breakpoint::$from)))?;
+                    if $crate::ui::shown(from, size) {
+                        // Entries past MAX_CHILDREN are dropped, as in
+                        // `ScreenLayout::compute`.
+                        let _ = shown.push($crate::ui::ScreenEntry {
+                            test_id: $test_id,
+                            component_type: stringify!($ty),
+                            size: $crate::ui::intrinsic_size(&self.$field, size),
+                            focus: Option::<u16>::None $(.or(Some($order)))?,
+                        });
+                    }
+                )*
+                $crate::ui::ScreenLayout::compute($style, size, origin, &shown)
+            }
+
+            /// Render every component and return the layout used.
+            pub fn render<D>(
+                &self,
+                display: &mut D,
+                origin: $crate::ui::__embedded_graphics::prelude::Point,
+            ) -> Result<$crate::ui::ScreenLayout, D::Error>
+            where
+                D: $crate::ui::__embedded_graphics::prelude::DrawTarget<
+                    Color = $crate::ui::__embedded_graphics::pixelcolor::Gray4,
+                >,
+            {
+                let layout = self.layout(origin);
+                self.draw_layout(display, &layout)?;
+                Ok(layout)
+            }
+
+            /// Render every component shown on `display`'s size, laid out
+            /// to fill it, and return the layout used.
+            pub fn render_in<D>(
+                &self,
+                display: &mut D,
+                origin: $crate::ui::__embedded_graphics::prelude::Point,
+            ) -> Result<$crate::ui::ScreenLayout, D::Error>
+            where
+                D: $crate::ui::__embedded_graphics::prelude::DrawTarget<
+                    Color = $crate::ui::__embedded_graphics::pixelcolor::Gray4,
+                >,
+            {
+                let size = display.bounding_box().size;
+                let layout = self.layout_in(origin, size);
+                self.draw_layout(display, &layout)?;
+                Ok(layout)
+            }
+
+            fn draw_layout<D>(
+                &self,
+                display: &mut D,
+                layout: &$crate::ui::ScreenLayout,
+            ) -> Result<(), D::Error>
+            where
+                D: $crate::ui::__embedded_graphics::prelude::DrawTarget<
+                    Color = $crate::ui::__embedded_graphics::pixelcolor::Gray4,
+                >,
+            {
+                $(
+                    if let Some(node) = layout.node($test_id) {
+                        self.$field.render(display, node.bounds.top_left)?;
+                    }
+                )*
+                Ok(())
+            }
+
+            /// Focus tree in the declared focus order.
+            ///
+            /// # Errors
+            ///
+            /// Returns the first focus id that did not fit into capacity `N`.
+            pub fn focus_tree<const N: usize>(
+                &self,
+                origin: $crate::ui::__embedded_graphics::prelude::Point,
+            ) -> Result<$crate::focus::FocusTree<N>, $crate::focus::FocusId> {
+                self.layout(origin).focus_tree()
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+    #![allow(clippy::unwrap_used, clippy::expect_used)]
+    // Each test uses only part of what `screen!` generates.
+    #![allow(dead_code)]
+    use super::*;
+    use crate::layout::LayoutResult;
+    use crate::style::{Edges, FlexDirection};
+    use embedded_graphics::{
+        mock_display::MockDisplay,
+        pixelcolor::Gray4,
+        primitives::{PrimitiveStyle, Rectangle},
+    };
+
+    /// Minimal widget with the eink-components render signature.
+    struct Block(Size);
+
+    impl Block {
+        fn render<D>(&self, display: &mut D, position: Point) -> Result<(), D::Error>
+        where
+            D: DrawTarget<Color = Gray4>,
+        {
+            Rectangle::new(position, self.0)
+                .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+                .draw(display)
+        }
+    }
+
+    impl Layout for Block {
+        fn layout(&self, constraints: Constraints) -> LayoutResult {
+            LayoutResult::leaf(constraints.constrain(self.0))
+        }
+    }
+
+    crate::screen! {
+        /// Test screen
+        struct Menu {
+            size: Size::new(40, 40),
+            style: Style::new()
+                .flex_direction(FlexDirection::Column)
+                .gap(2)
+                .padding(Edges::all(4)),
+            children: {
+                header: Block = Block(Size::new(32, 6)) => "header";
+                second: Block = Block(Size::new(20, 8)) => "second", focus 2;
+                first: Block = Block(Size::new(20, 8)) => "first", focus 1;
+            }
+        }
+    }
+
+    crate::screen! {
+        /// Test screen with a child hidden on compact panels
+        struct Player {
+            size: Size::new(400, 300),
+            style: Style::new().flex_direction(FlexDirection::Column).gap(2),
+            children: {
+                title: Block = Block(Size::new(32, 6)) => "title";
+                artist: Block = Block(Size::new(20, 8)) => "artist", from Medium;
+                play: Block = Block(Size::new(20, 8)) => "play", focus 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_layout_follows_declaration_order() {
+        let screen = Menu::new();
+        assert_eq!(Menu::TEST_IDS, ["header", "second", "first"]);
+
+        let layout = screen.layout(Point::new(10, 0));
+        let header = layout.node("header").unwrap();
+        assert_eq!(header.component_type, "Block");
+        assert_eq!(
+            header.bounds,
+            Rectangle::new(Point::new(14, 4), Size::new(32, 6))
+        );
+        assert_eq!(
+            layout.node("second").unwrap().bounds.top_left,
+            Point::new(14, 12)
+        );
+        assert_eq!(
+            layout.node("first").unwrap().bounds.top_left,
+            Point::new(14, 22)
+        );
+        assert!(layout.node("missing").is_none());
+    }
+
+    #[test]
+    fn test_render_matches_layout() {
+        let screen = Menu::new();
+        let mut display: MockDisplay<Gray4> = MockDisplay::new();
+        let layout = screen.render(&mut display, Point::zero()).unwrap();
+        for node in layout.nodes() {
+            assert_eq!(display.get_pixel(node.bounds.top_left), Some(Gray4::BLACK));
+        }
+        assert_eq!(
+            display.affected_area(),
+            Rectangle::new(Point::new(4, 4), Size::new(32, 26))
+        );
+    }
+
+    #[test]
+    fn test_focus_tree_uses_declared_order() {
+        let screen = Menu::new();
+        let mut focus = screen.focus_tree::<4>(Point::zero()).unwrap();
+        assert_eq!(focus.nodes().len(), 2);
+        assert_eq!(focus.focused(), Some(FocusId(1)));
+        assert_eq!(
+            focus.focused_bounds(),
+            screen.layout(Point::zero()).node("first").map(|n| n.bounds)
+        );
+        focus.move_focus(1);
+        assert_eq!(focus.focused(), Some(FocusId(2)));
+    }
+
+    #[test]
+    fn test_child_shown_from_its_breakpoint() {
+        let screen = Player::new();
+        let layout = screen.layout(Point::zero());
+        assert_eq!(
+            layout.node("artist").unwrap().bounds.top_left,
+            Point::new(0, 8)
+        );
+        assert_eq!(
+            layout.node("play").unwrap().bounds.top_left,
+            Point::new(0, 18)
+        );
+    }
+
+    #[test]
+    fn test_child_hidden_below_its_breakpoint() {
+        let screen = Player::new();
+        let layout = screen.layout_in(Point::zero(), Size::new(212, 104));
+        assert_eq!(layout.bounds.size, Size::new(212, 104));
+        assert!(layout.node("artist").is_none());
+        assert_eq!(
+            layout.node("play").unwrap().bounds.top_left,
+            Point::new(0, 8)
+        );
+        // Focus order is unaffected by the hidden child.
+        assert_eq!(
+            layout.focus_tree::<4>().unwrap().focused(),
+            Some(FocusId(1))
+        );
+    }
+
+    #[test]
+    fn test_render_in_uses_display_size() {
+        // MockDisplay is 64×64: a compact panel.
+        let screen = Player::new();
+        let mut display: MockDisplay<Gray4> = MockDisplay::new();
+        let layout = screen.render_in(&mut display, Point::zero()).unwrap();
+        assert!(layout.node("artist").is_none());
+        assert_eq!(
+            display.affected_area(),
+            Rectangle::new(Point::zero(), Size::new(32, 16))
+        );
+    }
+}
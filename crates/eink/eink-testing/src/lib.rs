@@ -30,7 +30,7 @@
 //! | Feature | What it unlocks |
 //! |---------|-----------------|
 //! | `debug` | `query_from_debug_manager()` — read components registered in the emulator's debug overlay |
-//! | `keyboard-input` | `simulate_key()`, `simulate_scroll()`, `take_events()` |
+//! | `keyboard-input` | `simulate_key()`, `simulate_long_press()`, `simulate_scroll()`, `take_events()` |
 //!
 //! # Golden screenshot testing
 //!
@@ -40,6 +40,46 @@
 //! // First run: set UPDATE_GOLDEN=1 to create/update the reference file.
 //! t.assert_matches_golden("tests/golden/my_screen.png", 0).unwrap();
 //! ```
+//!
+//! # Annotated screenshots
+//!
+//! ```no_run
+//! # use eink_testing::{Sidecar, TestEmulator};
+//! # let t = TestEmulator::new(100, 100);
+//! t.screenshot_annotated("target/screens/home.png").unwrap();
+//! let meta = Sidecar::load("target/screens/home.png").unwrap();
+//! meta.assert_field("waveform_mode", "GC16").unwrap();
+//! meta.assert_field("display.width", 100).unwrap();
+//! ```
+//!
+//! # Wear and energy reports
+//!
+//! ```no_run
+//! # use eink_testing::TestEmulator;
+//! # let t = TestEmulator::new(100, 100);
+//! // Archive in CI to trend refresh counts, energy and ghosting per PR.
+//! t.write_report("target/reports/now_playing.json").unwrap();
+//! ```
+//!
+//! # Property-based testing
+//!
+//! [`property`] has `proptest` strategies for random screens and input
+//! sequences, plus invariant checks — nothing drawn out of bounds, no
+//! overlapping focusable components, refresh budget respected — to fuzz a
+//! screen instead of testing hand-picked cases.
+//!
+//! # Interaction latency
+//!
+//! Simulated input (`simulate_key`, `simulate_scroll`, or
+//! `record_input(now_ms())` without the `keyboard-input` feature) starts an
+//! interaction; the next draw and refresh complete it.
+//!
+//! ```no_run
+//! # use eink_testing::TestEmulator;
+//! # let t = TestEmulator::new(100, 100);
+//! // ... input, draw, refresh ...
+//! t.assert_latency_budget().unwrap(); // every interaction ≤ 350 ms
+//! ```
 
 #![warn(clippy::all)]
 // Testing lib — println is allowed (clippy.toml has allow-print-in-tests = true)
@@ -62,12 +102,17 @@
     clippy::indexing_slicing,
 )]
 
+pub mod property;
+
 use std::path::Path;
 
 use embedded_graphics::{pixelcolor::Gray4, prelude::*, primitives::Rectangle};
 
-pub use eink_emulator::{EinkColor, Emulator};
+pub use eink_emulator::report::EmulatorReport;
+pub use eink_emulator::sidecar::ScreenshotMetadata;
+pub use eink_emulator::{EinkColor, Emulator, InteractionLatency, LATENCY_BUDGET_MS};
 pub use eink_specs::DisplaySpec;
+pub use eink_system::ui::ScreenLayout;
 
 // Re-export input types when the feature is active so callers only need
 // `eink_testing::Button` / `eink_testing::InputEvent`.
@@ -145,7 +190,7 @@ impl TestEmulator {
     /// tests work at the size they request (unlike `Emulator::headless` which
     /// ignores its arguments and uses a fixed Waveshare spec).
     pub fn new(width: u32, height: u32) -> Self {
-        use eink_specs::{ColorMode, Controller, DisplaySpec, PanelType};
+        use eink_specs::{ColorMode, Controller, DisplayMs, DisplaySpec, PanelType};
         // Box::leak is intentional: specs must be `&'static`.
         // Memory cost is negligible for testing (<100 bytes per emulator).
         let spec: &'static DisplaySpec = Box::leak(Box::new(DisplaySpec {
@@ -156,9 +201,9 @@ impl TestEmulator {
             panel_type: PanelType::Carta1000,
             color_mode: Some(ColorMode::Grayscale),
             grayscale_levels: 4,
-            full_refresh_ms: 100,
-            partial_refresh_ms: 50,
-            fast_refresh_ms: 30,
+            full_refresh_ms: DisplayMs::new(100),
+            partial_refresh_ms: DisplayMs::new(50),
+            fast_refresh_ms: DisplayMs::new(30),
             ghosting_rate_partial: 0.1,
             ghosting_rate_fast: 0.2,
             flash_count_full: 1,
@@ -287,6 +332,21 @@ impl TestEmulator {
         }
     }
 
+    /// Register every component of a laid-out [`screen!`](eink_system::screen).
+    ///
+    /// Pass the [`ScreenLayout`] returned by the screen's `render()` so the
+    /// registry always matches what was drawn.
+    pub fn register_screen(&mut self, layout: &ScreenLayout) {
+        for node in layout.nodes() {
+            self.register_component(
+                node.test_id,
+                node.component_type,
+                (node.bounds.top_left.x, node.bounds.top_left.y),
+                (node.bounds.size.width, node.bounds.size.height),
+            );
+        }
+    }
+
     /// Remove all registered components.
     pub fn clear_components(&mut self) {
         self.components.clear();
@@ -321,6 +381,39 @@ impl TestEmulator {
         self.inner.screenshot(path)
     }
 
+    /// Emulator state for a screenshot sidecar, including components
+    /// registered with [`Self::register_component`].
+    pub fn screenshot_metadata(&self) -> ScreenshotMetadata {
+        let mut meta = self.inner.screenshot_metadata();
+        meta.components.extend(self.components.iter().map(|c| {
+            eink_emulator::sidecar::ComponentEntry {
+                test_id: Some(c.test_id.clone()),
+                component_type: c.component_type.clone(),
+                position: c.position,
+                size: c.size,
+            }
+        }));
+        meta
+    }
+
+    /// Save a PNG plus its JSON metadata sidecar; returns the sidecar path.
+    ///
+    /// Parent directories are created automatically.  Read it back with
+    /// [`Sidecar::load`].
+    pub fn screenshot_annotated(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+        let p = path.as_ref();
+        if let Some(parent) = p.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        self.inner.screenshot(p)?;
+        let sidecar = eink_emulator::sidecar::sidecar_path(p);
+        self.screenshot_metadata().save(&sidecar)?;
+        Ok(sidecar)
+    }
+
     /// Save the current framebuffer as the golden reference PNG.
     ///
     /// Parent directories are created automatically.
@@ -415,14 +508,84 @@ impl TestEmulator {
         }
     }
 
+    // ── Reports ──────────────────────────────────────────────────────────────
+
+    /// Write the emulator's wear/energy [`EmulatorReport`] as JSON.
+    ///
+    /// Parent directories are created automatically.  CI jobs archive these
+    /// per run to trend refresh counts, energy and ghosting across PRs.
+    pub fn write_report(&self, path: impl AsRef<Path>) -> Result<(), Box<dyn std::error::Error>> {
+        let p = path.as_ref();
+        if let Some(parent) = p.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        self.inner.report().save(p)
+    }
+
+    // ── Latency ──────────────────────────────────────────────────────────────
+
+    /// Timing of the most recent completed interaction.
+    pub fn last_latency(&self) -> Option<InteractionLatency> {
+        self.inner.latency().last()
+    }
+
+    /// Assert every interaction so far completed within `max_ms`
+    /// (input → refresh complete).
+    ///
+    /// Fails if no interaction has completed yet, so a test that forgets to
+    /// refresh does not pass vacuously.
+    pub fn assert_latency_within(&self, max_ms: u64) -> Result<(), String> {
+        let latency = self.inner.latency();
+        let Some(worst) = latency.worst() else {
+            return Err(if latency.is_pending() {
+                "Input is still waiting for a refresh; no interaction completed".to_string()
+            } else {
+                "No interaction latency recorded (no input before a refresh)".to_string()
+            });
+        };
+        if worst.total_ms() > max_ms {
+            return Err(format!(
+                "Interaction took {}ms (input→draw {}ms, draw→refresh {}ms), budget {}ms",
+                worst.total_ms(),
+                worst.input_to_render_ms(),
+                worst.render_to_complete_ms(),
+                max_ms
+            ));
+        }
+        Ok(())
+    }
+
+    /// [`assert_latency_within`](Self::assert_latency_within) the
+    /// [`LATENCY_BUDGET_MS`] interaction budget.
+    pub fn assert_latency_budget(&self) -> Result<(), String> {
+        self.assert_latency_within(LATENCY_BUDGET_MS)
+    }
+
     // ── Input simulation (keyboard-input feature) ────────────────────────────
 
     /// Enqueue a [`ButtonPress`] + [`ButtonRelease`] pair.
     ///
+    /// Also starts an interaction for latency measurement.
+    ///
     /// Retrieve the events with [`take_events`].
     #[cfg(feature = "keyboard-input")]
     pub fn simulate_key(&mut self, button: Button) {
+        let now = self.inner.now_ms();
+        self.inner.record_input(now);
+        self.pending_events.push(InputEvent::ButtonPress(button));
+        self.pending_events.push(InputEvent::ButtonRelease(button));
+    }
+
+    /// Enqueue a held button: [`ButtonPress`], [`ButtonLongPress`], then
+    /// [`ButtonRelease`], in the order the input task reports them.
+    ///
+    /// Also starts an interaction for latency measurement.
+    #[cfg(feature = "keyboard-input")]
+    pub fn simulate_long_press(&mut self, button: Button) {
+        let now = self.inner.now_ms();
+        self.inner.record_input(now);
         self.pending_events.push(InputEvent::ButtonPress(button));
+        self.pending_events.push(InputEvent::ButtonLongPress(button));
         self.pending_events.push(InputEvent::ButtonRelease(button));
     }
 
@@ -431,6 +594,8 @@ impl TestEmulator {
     /// Positive `steps` = clockwise; negative = counter-clockwise.
     #[cfg(feature = "keyboard-input")]
     pub fn simulate_scroll(&mut self, steps: i32) {
+        let now = self.inner.now_ms();
+        self.inner.record_input(now);
         self.pending_events.push(InputEvent::RotaryIncrement(steps));
     }
 
@@ -543,6 +708,113 @@ impl TestEmulator {
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Sidecar
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A screenshot's JSON metadata sidecar, loaded for assertions.
+///
+/// Fields are addressed by dotted path into the JSON document, e.g.
+/// `"display.name"`, `"power.total_energy_uwh"` or `"components.0.test_id"`.
+#[derive(Debug, Clone)]
+pub struct Sidecar {
+    json: serde_json::Value,
+    path: std::path::PathBuf,
+}
+
+impl Sidecar {
+    /// Load the sidecar for `screenshot` (`x.png` → `x.json`; a `.json`
+    /// path is used as is).
+    pub fn load(screenshot: impl AsRef<Path>) -> Result<Self, String> {
+        let path = eink_emulator::sidecar::sidecar_path(screenshot);
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read sidecar '{}': {e}", path.display()))?;
+        let json = serde_json::from_str(&text)
+            .map_err(|e| format!("Failed to parse sidecar '{}': {e}", path.display()))?;
+        Ok(Self { json, path })
+    }
+
+    /// Typed view of the sidecar.
+    pub fn metadata(&self) -> Result<ScreenshotMetadata, String> {
+        serde_json::from_value(self.json.clone()).map_err(|e| {
+            format!(
+                "Sidecar '{}' has unexpected shape: {e}",
+                self.path.display()
+            )
+        })
+    }
+
+    /// Value at dotted `field`, or `None` if any segment is missing.
+    pub fn field(&self, field: &str) -> Option<&serde_json::Value> {
+        field.split('.').try_fold(&self.json, |v, key| match v {
+            serde_json::Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => v.get(key),
+        })
+    }
+
+    /// Assert that `field` equals `expected`.
+    ///
+    /// Numbers compare by value, so `assert_field("temperature_c", 25)`
+    /// matches regardless of JSON integer/float representation.
+    pub fn assert_field(
+        &self,
+        field: &str,
+        expected: impl Into<serde_json::Value>,
+    ) -> Result<(), String> {
+        let expected = expected.into();
+        let actual = self
+            .field(field)
+            .ok_or_else(|| format!("Sidecar '{}' has no field '{field}'", self.path.display()))?;
+        let equal = match (actual.as_f64(), expected.as_f64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => *actual == expected,
+        };
+        if equal {
+            Ok(())
+        } else {
+            Err(format!(
+                "Sidecar field '{field}': expected {expected}, got {actual}"
+            ))
+        }
+    }
+
+    /// Assert that numeric `field` lies within `min..=max`.
+    pub fn assert_field_in_range(&self, field: &str, min: f64, max: f64) -> Result<(), String> {
+        let actual = self
+            .field(field)
+            .and_then(serde_json::Value::as_f64)
+            .ok_or_else(|| format!("Sidecar field '{field}' is missing or not a number"))?;
+        if (min..=max).contains(&actual) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Sidecar field '{field}': {actual} is outside {min}..={max}"
+            ))
+        }
+    }
+
+    /// Assert that a component with `test_id` was registered at capture time.
+    pub fn assert_has_component(&self, test_id: &str) -> Result<(), String> {
+        let found = self
+            .json
+            .get("components")
+            .and_then(serde_json::Value::as_array)
+            .is_some_and(|items| {
+                items
+                    .iter()
+                    .any(|c| c.get("test_id").and_then(serde_json::Value::as_str) == Some(test_id))
+            });
+        if found {
+            Ok(())
+        } else {
+            Err(format!(
+                "Component '{test_id}' not in sidecar '{}'",
+                self.path.display()
+            ))
+        }
+    }
+}
+
 impl std::ops::Deref for TestEmulator {
     type Target = Emulator;
     fn deref(&self) -> &Self::Target {
@@ -621,6 +893,32 @@ mod tests {
         assert_eq!(t.query_by_test_id("btn").unwrap().position, (5, 5));
     }
 
+    #[test]
+    fn register_screen_registers_every_node() {
+        use eink_system::style::{FlexDirection, Style};
+        use eink_system::ui::ScreenEntry;
+
+        let entry = |test_id, height| ScreenEntry {
+            test_id,
+            component_type: "Button",
+            size: Size::new(40, height),
+            focus: None,
+        };
+        let layout = ScreenLayout::compute(
+            Style::new().flex_direction(FlexDirection::Column).gap(5),
+            Size::new(100, 100),
+            Point::new(0, 10),
+            &[entry("play", 20), entry("next", 10)],
+        );
+
+        let mut t = TestEmulator::new(100, 120);
+        t.register_screen(&layout);
+        assert_eq!(t.component_count(), 2);
+        let next = t.query_by_test_id("next").unwrap();
+        assert_eq!(next.position, (0, 35));
+        assert_eq!(next.size, (40, 10));
+    }
+
     #[test]
     fn region_assertions() {
         let mut t = TestEmulator::new(50, 50);
@@ -653,6 +951,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn sidecar_field_lookup() {
+        let t = TestEmulator::new(40, 20);
+        let json = serde_json::to_value(t.screenshot_metadata()).unwrap();
+        let sidecar = Sidecar {
+            json,
+            path: "mem.json".into(),
+        };
+        assert_eq!(sidecar.field("display.width"), Some(&40.into()));
+        assert!(sidecar.field("display.missing").is_none());
+        assert!(sidecar.assert_field("temperature_c", 25).is_ok());
+        assert!(sidecar.assert_field("temperature_c", 30).is_err());
+        assert!(sidecar.assert_field("nope", 1).is_err());
+    }
+
     #[cfg(feature = "keyboard-input")]
     #[test]
     fn simulate_key_produces_press_release() {
@@ -0,0 +1,682 @@
+//! Property-based UI testing: generators and invariants
+//!
+//! Hand-picked cases only cover the screens someone thought of.  This module
+//! lets a test fuzz a screen instead: [`proptest`] strategies produce random
+//! screens ([`random_screen`]) and random interaction sequences
+//! ([`interactions`]), and the checks below state what must hold for *every*
+//! input:
+//!
+//! - **Nothing is drawn outside its bounds** — draw through a
+//!   [`BoundsProbe`] and call [`BoundsProbe::check`].
+//! - **Every component lies on the display** —
+//!   [`TestEmulator::assert_components_in_bounds`].
+//! - **Focusable components do not overlap** —
+//!   [`TestEmulator::assert_no_overlapping_focusables`], or
+//!   [`check_no_overlapping_focus`] straight on a [`ScreenLayout`].
+//! - **The refresh budget is respected** — snapshot
+//!   [`TestEmulator::refresh_counts`] before and after, and check the
+//!   difference against a [`RefreshBudget`].
+//!
+//! Every check returns `Result<(), String>` like the rest of the crate, so
+//! inside `proptest!` it is turned into a test failure with
+//! `prop_assert!(check.is_ok(), "{:?}", check)` and proptest shrinks the
+//! input to a minimal counter-example.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use eink_testing::property::{random_screen, BoundsProbe};
+//! use eink_testing::TestEmulator;
+//! use embedded_graphics::prelude::*;
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn random_screens_stay_in_bounds(screen in random_screen(Size::new(480, 800))) {
+//!         let mut t = TestEmulator::new(480, 800);
+//!         let mut probe = BoundsProbe::new(&mut *t);
+//!         let layout = screen.render(&mut probe).unwrap();
+//!         prop_assert!(probe.check().is_ok());
+//!         t.register_screen(&layout);
+//!         prop_assert!(t.assert_components_in_bounds().is_ok());
+//!         prop_assert!(t.assert_no_overlapping_focusables().is_ok());
+//!     }
+//! }
+//! ```
+
+use std::fmt::Debug;
+
+use eink_system::focus::FocusId;
+use eink_system::style::{Align, Edges, FlexDirection, Justify, Style};
+use eink_system::ui::{ScreenEntry, ScreenLayout};
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
+    Pixel,
+};
+use proptest::prelude::*;
+
+use crate::{ComponentRef, TestEmulator};
+
+/// Component types treated as focusable by
+/// [`TestEmulator::assert_no_overlapping_focusables`].
+pub const FOCUSABLE_TYPES: &[&str] = &[
+    "Button", "Toggle", "Checkbox", "Slider", "ListItem", "MenuItem", "Input",
+];
+
+/// Most children in a [`random_screen`].
+pub const MAX_RANDOM_CHILDREN: usize = 8;
+
+/// Largest padding a [`random_screen`] uses on each edge.
+const MAX_PADDING: u32 = 4;
+
+/// Largest gap a [`random_screen`] puts between children.
+const MAX_GAP: u32 = 4;
+
+/// Smallest screen side [`random_screen`] accepts.
+pub const MIN_RANDOM_SIDE: u32 = 64;
+
+/// Test ids handed out to generated components, in order.
+const RANDOM_IDS: [&str; MAX_RANDOM_CHILDREN] = [
+    "random-0", "random-1", "random-2", "random-3", "random-4", "random-5", "random-6", "random-7",
+];
+
+/// Component types a generated component is given.
+const RANDOM_TYPES: [&str; 4] = ["Button", "Label", "Toggle", "Container"];
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Random screens
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A generated screen: a flex container of randomly sized components.
+///
+/// Sizes are bounded so the children always fit the container, which makes
+/// "in bounds" and "no overlap" hold for a correct layout engine — a failure
+/// is a layout bug, not a generator artifact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomScreen {
+    /// Screen size.
+    pub size: Size,
+    /// Container style.
+    pub style: Style,
+    /// Components in declaration order.
+    pub entries: Vec<ScreenEntry>,
+}
+
+impl RandomScreen {
+    /// Lay the screen out at the display origin.
+    pub fn layout(&self) -> ScreenLayout {
+        ScreenLayout::compute(self.style, self.size, Point::zero(), &self.entries)
+    }
+
+    /// Fill every component's bounds (focusable ones with an outline) and
+    /// return the layout used.
+    pub fn render<D>(&self, display: &mut D) -> Result<ScreenLayout, D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        let layout = self.layout();
+        for node in layout.nodes() {
+            let style = if node.focus.is_some() {
+                PrimitiveStyleBuilder::new()
+                    .fill_color(Gray4::new(0xC))
+                    .stroke_color(Gray4::BLACK)
+                    .stroke_width(1)
+                    .build()
+            } else {
+                PrimitiveStyle::with_fill(Gray4::new(0x8))
+            };
+            node.bounds.into_styled(style).draw(display)?;
+        }
+        Ok(layout)
+    }
+}
+
+/// Strategy for container styles: direction, justification, alignment and
+/// small gaps and padding.
+pub fn screen_style() -> impl Strategy<Value = Style> {
+    let direction = prop_oneof![
+        Just(FlexDirection::Row),
+        Just(FlexDirection::Column),
+        Just(FlexDirection::RowReverse),
+        Just(FlexDirection::ColumnReverse),
+    ];
+    let justify = prop_oneof![
+        Just(Justify::Start),
+        Just(Justify::End),
+        Just(Justify::Center),
+        Just(Justify::SpaceBetween),
+        Just(Justify::SpaceAround),
+        Just(Justify::SpaceEvenly),
+    ];
+    let align = prop_oneof![
+        Just(Align::Start),
+        Just(Align::End),
+        Just(Align::Center),
+        Just(Align::Stretch),
+    ];
+    (direction, justify, align, 0..=MAX_GAP, 0..=MAX_PADDING).prop_map(
+        |(direction, justify, align, gap, padding)| {
+            Style::new()
+                .flex_direction(direction)
+                .justify_content(justify)
+                .align_items(align)
+                .gap(gap)
+                .padding(Edges::all(padding))
+        },
+    )
+}
+
+/// Strategy for screens of `size` with 1 to [`MAX_RANDOM_CHILDREN`]
+/// components, some of them focusable.
+///
+/// Sides below [`MIN_RANDOM_SIDE`] are raised to it.
+pub fn random_screen(size: Size) -> impl Strategy<Value = RandomScreen> {
+    let size = Size::new(
+        size.width.max(MIN_RANDOM_SIDE),
+        size.height.max(MIN_RANDOM_SIDE),
+    );
+    // Worst case on either axis: every child at the maximum extent plus the
+    // largest gaps and padding still fits the shorter side.
+    let overhead = 2 * MAX_PADDING + (MAX_RANDOM_CHILDREN as u32 - 1) * MAX_GAP;
+    let max_extent =
+        (size.width.min(size.height).saturating_sub(overhead) / MAX_RANDOM_CHILDREN as u32).max(1);
+    let child = (
+        1..=max_extent,
+        1..=max_extent,
+        0..RANDOM_TYPES.len(),
+        any::<bool>(),
+    );
+    (
+        screen_style(),
+        proptest::collection::vec(child, 1..=MAX_RANDOM_CHILDREN),
+    )
+        .prop_map(move |(style, children)| {
+            let mut next_focus = 0u16;
+            let entries = children
+                .into_iter()
+                .zip(RANDOM_IDS)
+                .map(|((width, height, kind, focusable), test_id)| {
+                    let focus = focusable.then(|| {
+                        next_focus += 1;
+                        next_focus
+                    });
+                    ScreenEntry {
+                        test_id,
+                        component_type: RANDOM_TYPES[kind],
+                        size: Size::new(width, height),
+                        focus,
+                    }
+                })
+                .collect();
+            RandomScreen {
+                size,
+                style,
+                entries,
+            }
+        })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Random interactions
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Strategy for sequences of up to `max_len` actions drawn from `alphabet`.
+///
+/// The alphabet is whatever the screen under test consumes — `InputEvent`s,
+/// its own action enum, or closures' inputs — so one generator covers every
+/// screen.  Replay the sequence against the screen and check the invariants
+/// after each step.
+///
+/// # Panics
+///
+/// Panics if `alphabet` is empty.
+pub fn interactions<T>(alphabet: Vec<T>, max_len: usize) -> impl Strategy<Value = Vec<T>>
+where
+    T: Clone + Debug + 'static,
+{
+    proptest::collection::vec(proptest::sample::select(alphabet), 0..=max_len)
+}
+
+/// Strategy for single input events: a press, release or long press of any
+/// button, or a rotary step of ±1 to ±3 detents.
+#[cfg(feature = "keyboard-input")]
+pub fn input_events() -> impl Strategy<Value = crate::InputEvent> {
+    use crate::{Button, InputEvent};
+
+    let button = proptest::sample::select(vec![
+        Button::Play,
+        Button::Next,
+        Button::Previous,
+        Button::VolumeUp,
+        Button::VolumeDown,
+        Button::Menu,
+        Button::Back,
+        Button::Select,
+    ]);
+    prop_oneof![
+        button.clone().prop_map(InputEvent::ButtonPress),
+        button.clone().prop_map(InputEvent::ButtonRelease),
+        button.prop_map(InputEvent::ButtonLongPress),
+        prop_oneof![-3..=-1, 1..=3].prop_map(InputEvent::RotaryIncrement),
+    ]
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Drawing bounds
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Draw target that forwards to `D` and records pixels drawn outside an
+/// allowed area.
+///
+/// The emulator silently clips off-screen pixels, so a component that
+/// overdraws its slot or the display edge looks fine in a screenshot.  The
+/// probe reports it instead.  Its [`bounding_box`](Dimensions::bounding_box)
+/// is the wrapped target's, so components lay out exactly as they would
+/// without it.
+pub struct BoundsProbe<'a, D> {
+    inner: &'a mut D,
+    allowed: Rectangle,
+    outside: usize,
+    first_outside: Option<Point>,
+}
+
+impl<'a, D: DrawTarget> BoundsProbe<'a, D> {
+    /// Probe allowing the whole of `inner`.
+    pub fn new(inner: &'a mut D) -> Self {
+        let allowed = inner.bounding_box();
+        Self::within(inner, allowed)
+    }
+
+    /// Probe allowing only `allowed` — e.g. one component's layout slot.
+    pub fn within(inner: &'a mut D, allowed: Rectangle) -> Self {
+        Self {
+            inner,
+            allowed,
+            outside: 0,
+            first_outside: None,
+        }
+    }
+
+    /// Pixels drawn outside the allowed area so far.
+    pub fn outside_count(&self) -> usize {
+        self.outside
+    }
+
+    /// First pixel drawn outside the allowed area.
+    pub fn first_outside(&self) -> Option<Point> {
+        self.first_outside
+    }
+
+    /// `Err` describing the overdraw if any pixel fell outside.
+    pub fn check(&self) -> Result<(), String> {
+        match self.first_outside {
+            None => Ok(()),
+            Some(p) => Err(format!(
+                "{} pixel(s) drawn outside {:?}, first at ({}, {})",
+                self.outside, self.allowed, p.x, p.y
+            )),
+        }
+    }
+}
+
+impl<D: DrawTarget> Dimensions for BoundsProbe<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.inner.bounding_box()
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for BoundsProbe<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let allowed = self.allowed;
+        let outside = &mut self.outside;
+        let first = &mut self.first_outside;
+        self.inner
+            .draw_iter(pixels.into_iter().inspect(|Pixel(p, _)| {
+                if !allowed.contains(*p) {
+                    *outside += 1;
+                    first.get_or_insert(*p);
+                }
+            }))
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Component geometry
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// `true` when `a` and `b` share at least one pixel.  Touching edges do not
+/// count.
+fn overlaps(a: Rectangle, b: Rectangle) -> bool {
+    let shared = a.intersection(&b).size;
+    shared.width > 0 && shared.height > 0
+}
+
+/// `true` when `inner` lies entirely within `outer`.
+fn contains_rect(outer: Rectangle, inner: Rectangle) -> bool {
+    let o_end = outer.top_left + outer.size;
+    let i_end = inner.top_left + inner.size;
+    inner.top_left.x >= outer.top_left.x
+        && inner.top_left.y >= outer.top_left.y
+        && i_end.x <= o_end.x
+        && i_end.y <= o_end.y
+}
+
+/// `Err` naming the first pair of overlapping rectangles.
+fn check_disjoint<'a>(items: impl IntoIterator<Item = (&'a str, Rectangle)>) -> Result<(), String> {
+    let items: Vec<_> = items.into_iter().collect();
+    for (i, &(a_id, a)) in items.iter().enumerate() {
+        for &(b_id, b) in &items[i + 1..] {
+            if overlaps(a, b) {
+                return Err(format!("'{a_id}' {a:?} overlaps '{b_id}' {b:?}"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that no two focusable nodes of `layout` overlap.
+pub fn check_no_overlapping_focus(layout: &ScreenLayout) -> Result<(), String> {
+    check_disjoint(
+        layout
+            .nodes()
+            .iter()
+            .filter(|node| node.focus.is_some())
+            .map(|node| (node.test_id, node.bounds)),
+    )
+}
+
+/// Check that every node of `layout` lies within the screen bounds.
+pub fn check_layout_in_bounds(layout: &ScreenLayout) -> Result<(), String> {
+    match layout
+        .nodes()
+        .iter()
+        .find(|node| !contains_rect(layout.bounds, node.bounds))
+    {
+        None => Ok(()),
+        Some(node) => Err(format!(
+            "'{}' {:?} extends outside the screen {:?}",
+            node.test_id, node.bounds, layout.bounds
+        )),
+    }
+}
+
+/// Focus ids of `layout` in focus order, for asserting on the focus chain.
+pub fn focus_order(layout: &ScreenLayout) -> Vec<FocusId> {
+    let mut ids: Vec<FocusId> = layout.nodes().iter().filter_map(|n| n.focus).collect();
+    ids.sort_unstable();
+    ids
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Refresh budget
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Refresh counters of an emulator, by cost class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RefreshCounts {
+    /// Full (flashing) refreshes: GC16, GL16, GCC16.
+    pub full: u64,
+    /// Partial refreshes: DU4.
+    pub partial: u64,
+    /// Fast refreshes: DU, A2, GCU.
+    pub fast: u64,
+}
+
+impl RefreshCounts {
+    /// All refreshes.
+    pub fn total(self) -> u64 {
+        self.full + self.partial + self.fast
+    }
+
+    /// Refreshes made since the `earlier` snapshot.
+    pub fn since(self, earlier: Self) -> Self {
+        Self {
+            full: self.full.saturating_sub(earlier.full),
+            partial: self.partial.saturating_sub(earlier.partial),
+            fast: self.fast.saturating_sub(earlier.fast),
+        }
+    }
+}
+
+/// Upper bound on the refreshes a sequence of interactions may cause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshBudget {
+    /// Most full (flashing) refreshes.
+    pub max_full: u64,
+    /// Most refreshes of any kind.
+    pub max_total: u64,
+}
+
+impl RefreshBudget {
+    /// Budget of `max_full` full and `max_total` refreshes in all.
+    pub const fn new(max_full: u64, max_total: u64) -> Self {
+        Self {
+            max_full,
+            max_total,
+        }
+    }
+
+    /// This budget allowed once per interaction, for `interactions`
+    /// interactions.
+    pub fn per_interaction(self, interactions: usize) -> Self {
+        let n = u64::try_from(interactions).unwrap_or(u64::MAX);
+        Self {
+            max_full: self.max_full.saturating_mul(n),
+            max_total: self.max_total.saturating_mul(n),
+        }
+    }
+
+    /// `Err` describing the overrun if `used` exceeds the budget.
+    pub fn check(self, used: RefreshCounts) -> Result<(), String> {
+        if used.full > self.max_full {
+            return Err(format!(
+                "{} full refreshes exceed the budget of {}",
+                used.full, self.max_full
+            ));
+        }
+        if used.total() > self.max_total {
+            return Err(format!(
+                "{} refreshes ({} full, {} partial, {} fast) exceed the budget of {}",
+                used.total(),
+                used.full,
+                used.partial,
+                used.fast,
+                self.max_total
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TestEmulator invariants
+// ─────────────────────────────────────────────────────────────────────────────
+
+impl TestEmulator {
+    /// Snapshot of the emulator's refresh counters.
+    pub fn refresh_counts(&self) -> RefreshCounts {
+        let stats = self.emulator().stats();
+        RefreshCounts {
+            full: stats.full_refresh_count.get(),
+            partial: stats.partial_refresh_count.get(),
+            fast: stats.fast_refresh_count.get(),
+        }
+    }
+
+    /// Assert every registered component lies within the display.
+    pub fn assert_components_in_bounds(&self) -> Result<(), String> {
+        let display = Rectangle::new(Point::zero(), Size::new(self.width(), self.height()));
+        match self
+            .components()
+            .iter()
+            .find(|c| !contains_rect(display, c.bounds()))
+        {
+            None => Ok(()),
+            Some(c) => Err(format!(
+                "'{}' at {:?} size {:?} extends outside the {}×{} display",
+                c.test_id,
+                c.position,
+                c.size,
+                self.width(),
+                self.height()
+            )),
+        }
+    }
+
+    /// Assert no two registered components of a [`FOCUSABLE_TYPES`] type
+    /// overlap — a tap or focus ring must land on exactly one of them.
+    pub fn assert_no_overlapping_focusables(&self) -> Result<(), String> {
+        self.assert_no_overlapping(|c| FOCUSABLE_TYPES.contains(&c.component_type.as_str()))
+    }
+
+    /// Assert no two registered components selected by `filter` overlap.
+    pub fn assert_no_overlapping(
+        &self,
+        filter: impl Fn(&ComponentRef) -> bool,
+    ) -> Result<(), String> {
+        check_disjoint(
+            self.components()
+                .iter()
+                .filter(|c| filter(c))
+                .map(|c| (c.test_id.as_str(), c.bounds())),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used, clippy::expect_used)]
+
+    use super::*;
+
+    fn rect(x: i32, y: i32, w: u32, h: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(w, h))
+    }
+
+    #[test]
+    fn probe_reports_overdraw() {
+        let mut t = TestEmulator::new(50, 50);
+        let mut probe = BoundsProbe::within(&mut *t, rect(10, 10, 20, 20));
+        rect(10, 10, 20, 20)
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut probe)
+            .unwrap();
+        assert!(probe.check().is_ok());
+
+        rect(25, 10, 10, 1)
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut probe)
+            .unwrap();
+        assert_eq!(probe.outside_count(), 5);
+        assert_eq!(probe.first_outside(), Some(Point::new(30, 10)));
+        assert!(probe.check().is_err());
+        // Pixels still reach the display.
+        assert_eq!(t.pixel_at(27, 10), Some(Gray4::BLACK));
+    }
+
+    #[test]
+    fn probe_reports_pixels_off_the_display() {
+        let mut t = TestEmulator::new(50, 50);
+        let mut probe = BoundsProbe::new(&mut *t);
+        rect(45, 0, 10, 1)
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut probe)
+            .unwrap();
+        assert_eq!(probe.outside_count(), 5);
+    }
+
+    #[test]
+    fn components_outside_display_fail() {
+        let mut t = TestEmulator::new(100, 50);
+        t.register_component("ok", "Label", (0, 0), (100, 50));
+        assert!(t.assert_components_in_bounds().is_ok());
+        t.register_component("wide", "Label", (60, 0), (41, 10));
+        assert!(t
+            .assert_components_in_bounds()
+            .unwrap_err()
+            .contains("'wide'"));
+    }
+
+    #[test]
+    fn overlapping_focusables_fail_but_labels_may_overlap() {
+        let mut t = TestEmulator::new(100, 100);
+        t.register_component("title", "Label", (0, 0), (100, 20));
+        t.register_component("play", "Button", (0, 10), (40, 20));
+        t.register_component("next", "Button", (40, 10), (40, 20));
+        assert!(t.assert_no_overlapping_focusables().is_ok());
+
+        t.register_component("menu", "Button", (70, 20), (20, 20));
+        let err = t.assert_no_overlapping_focusables().unwrap_err();
+        assert!(err.contains("'next'") && err.contains("'menu'"));
+    }
+
+    #[test]
+    fn refresh_budget_counts_since_snapshot() {
+        let before = RefreshCounts {
+            full: 1,
+            partial: 4,
+            fast: 0,
+        };
+        let after = RefreshCounts {
+            full: 2,
+            partial: 9,
+            fast: 1,
+        };
+        let used = after.since(before);
+        assert_eq!(used.total(), 7);
+
+        let budget = RefreshBudget::new(1, 2);
+        assert!(budget.per_interaction(4).check(used).is_ok());
+        assert!(budget.per_interaction(3).check(used).is_err());
+        assert!(RefreshBudget::new(0, 10)
+            .check(used)
+            .unwrap_err()
+            .contains("full"));
+    }
+
+    #[tokio::test]
+    async fn refresh_counts_track_the_emulator() {
+        use eink_emulator::{DisplayDriver, VirtualClock};
+
+        let mut t = TestEmulator::new(50, 50);
+        t.set_virtual_clock(VirtualClock::new());
+        let before = t.refresh_counts();
+        t.refresh_full().await.unwrap();
+        t.refresh_partial().await.unwrap();
+        let used = t.refresh_counts().since(before);
+        assert_eq!(used.full, 1);
+        assert_eq!(used.total(), 2);
+    }
+
+    proptest! {
+        #[test]
+        fn random_screens_hold_layout_invariants(
+            screen in random_screen(Size::new(240, 400))
+        ) {
+            let layout = screen.layout();
+            prop_assert_eq!(layout.nodes().len(), screen.entries.len());
+            let in_bounds = check_layout_in_bounds(&layout);
+            prop_assert!(in_bounds.is_ok(), "{:?}", in_bounds);
+            let disjoint = check_no_overlapping_focus(&layout);
+            prop_assert!(disjoint.is_ok(), "{:?}", disjoint);
+            let focus = focus_order(&layout);
+            prop_assert!(focus.windows(2).all(|w| w[0] < w[1]));
+        }
+
+        #[test]
+        fn interactions_draw_from_the_alphabet(
+            seq in interactions(vec!['a', 'b', 'c'], 12)
+        ) {
+            prop_assert!(seq.len() <= 12);
+            prop_assert!(seq.iter().all(|c| "abc".contains(*c)));
+        }
+    }
+}
@@ -34,3 +34,86 @@ fn test_pixel_defaults_to_white() {
     assert_eq!(t.pixel_at(0, 0), Some(Gray4::WHITE));
     assert_eq!(t.pixel_at(100, 64), Some(Gray4::WHITE));
 }
+
+#[test]
+fn test_screenshot_annotated_sidecar() {
+    use eink_testing::Sidecar;
+
+    let mut t = TestEmulator::new(120, 60);
+    t.register_component("title", "Label", (4, 4), (80, 12));
+    let png = std::env::temp_dir().join(format!("eink_testing_sidecar_{}.png", std::process::id()));
+    let json = t.screenshot_annotated(&png).unwrap();
+
+    let sidecar = Sidecar::load(&png).unwrap();
+    sidecar.assert_field("display.name", "TestDisplay").unwrap();
+    sidecar.assert_field("display.width", 120).unwrap();
+    sidecar
+        .assert_field("components.0.component_type", "Label")
+        .unwrap();
+    sidecar
+        .assert_field_in_range("ghosting.average", 0.0, 0.0)
+        .unwrap();
+    sidecar.assert_has_component("title").unwrap();
+    assert!(sidecar.assert_has_component("footer").is_err());
+    assert_eq!(sidecar.metadata().unwrap(), t.screenshot_metadata());
+
+    let _ = std::fs::remove_file(&png);
+    let _ = std::fs::remove_file(&json);
+}
+
+#[test]
+fn test_write_report() {
+    use eink_testing::EmulatorReport;
+
+    let t = TestEmulator::new(64, 32);
+    let path = std::env::temp_dir()
+        .join(format!("eink_testing_report_{}", std::process::id()))
+        .join("report.json");
+    t.write_report(&path).unwrap();
+
+    let report = EmulatorReport::load(&path).unwrap();
+    assert_eq!(report.display.width, 64);
+    assert_eq!(report.refreshes.by_mode.get("GC16"), Some(&0));
+    assert_eq!(report.ghosting.histogram.iter().sum::<u64>(), 64 * 32);
+
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[tokio::test]
+async fn test_latency_budget() {
+    use eink_emulator::{DisplayDriver, VirtualClock};
+    use embedded_graphics::{
+        pixelcolor::Gray4,
+        prelude::*,
+        primitives::{PrimitiveStyle, Rectangle},
+    };
+
+    let mut t = TestEmulator::new(64, 32);
+    t.set_virtual_clock(VirtualClock::new());
+    assert!(t.assert_latency_budget().is_err());
+
+    let draw = |t: &mut TestEmulator| {
+        Rectangle::new(Point::zero(), Size::new(8, 8))
+            .into_styled(PrimitiveStyle::with_fill(Gray4::BLACK))
+            .draw(&mut **t)
+            .unwrap();
+    };
+
+    // Partial refresh in response to input fits the budget.
+    let now = t.now_ms();
+    t.record_input(now);
+    assert!(t.assert_latency_budget().is_err());
+    draw(&mut t);
+    t.refresh_partial().await.unwrap();
+    t.assert_latency_budget().unwrap();
+    assert_eq!(t.last_latency().unwrap().input_to_render_ms(), 0);
+
+    // A full refresh (GC16 flashes) does not.
+    let now = t.now_ms();
+    t.record_input(now);
+    draw(&mut t);
+    t.refresh_full().await.unwrap();
+    let err = t.assert_latency_budget().unwrap_err();
+    assert!(err.contains("budget 350ms"), "{err}");
+    t.assert_latency_within(2_000).unwrap();
+}
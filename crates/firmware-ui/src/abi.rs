@@ -0,0 +1,186 @@
+//! Typed, hash-checked interface for the hot-reload dylib boundary.
+//!
+//! The binary (display_emulator) calls into a `firmware_ui` dylib that may
+//! have been rebuilt since the binary was compiled.  Nothing in the type
+//! system checks that call: if an exported signature changes and the binary
+//! still runs against the old contract, the call is undefined behaviour.
+//!
+//! [`hot_reload_abi!`] declares the exported functions once and generates:
+//!
+//! - the `#[no_mangle]` exports, taking `&mut Emulator` rather than a raw
+//!   pointer (hot-lib-reloader calls them through the Rust ABI, and both
+//!   sides are built by the same toolchain);
+//! - `ABI_SIGNATURES` / `ABI_HASH` — the FNV-1a hash of
+//!   the declared signature set, computed at compile time, and the
+//!   `ui_abi_hash()` export reporting it from the loaded dylib;
+//! - `HotUi`, the binary-side wrapper: it only hands out
+//!   calls after the loaded dylib's hash matched the hash compiled into the
+//!   binary, and its constructor only accepts function items with the
+//!   declared signatures, so a stale declaration fails to compile.
+//!
+//! Changing a signature changes the hash automatically — there is no
+//! version number to forget to bump.
+
+use core::fmt;
+
+/// FNV-1a 32-bit hash — stable, deterministic and usable in `const` context.
+///
+/// Reference: <http://www.isthe.com/chongo/tech/comp/fnv/>
+pub const fn fnv1a_32(s: &str) -> u32 {
+    const FNV_OFFSET_BASIS: u32 = 2_166_136_261;
+    const FNV_PRIME: u32 = 16_777_619;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut bytes = s.as_bytes();
+    while let [byte, rest @ ..] = bytes {
+        hash ^= *byte as u32;
+        hash = hash.wrapping_mul(FNV_PRIME);
+        bytes = rest;
+    }
+    hash
+}
+
+/// The loaded dylib was built against a different signature set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbiMismatch {
+    /// Hash compiled into the binary.
+    pub expected: u32,
+    /// Hash reported by the loaded dylib.
+    pub found: u32,
+}
+
+impl fmt::Display for AbiMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hot-reload ABI mismatch: binary expects {:08x}, dylib reports {:08x}. \
+             Rebuild both with: cargo build -p firmware-ui --features hot-reload",
+            self.expected, self.found
+        )
+    }
+}
+
+#[cfg(not(feature = "no-std"))]
+impl std::error::Error for AbiMismatch {}
+
+/// Compare a loaded dylib's hash against the one compiled into this binary.
+pub fn check_abi(expected: u32, found: u32) -> Result<(), AbiMismatch> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(AbiMismatch { expected, found })
+    }
+}
+
+/// Declare the functions exported across the hot-reload boundary.
+///
+/// Everything generated is gated on the `hot-reload` feature.
+///
+/// Each entry is `fn name(emulator, extra: Type, ...) => implementation;`
+/// where `implementation` is a safe `fn(&mut Emulator, extra...)`.  Extra
+/// arguments must be plain `Copy` values.
+macro_rules! hot_reload_abi {
+    ($(
+        $(#[$meta:meta])*
+        fn $name:ident(emulator $(, $arg:ident: $ty:ty)*) => $imp:path;
+    )*) => {
+        $(
+            $(#[$meta])*
+            #[cfg(feature = "hot-reload")]
+            #[no_mangle]
+            pub fn $name(emulator: &mut eink_emulator::Emulator $(, $arg: $ty)*) {
+                $imp(emulator $(, $arg)*)
+            }
+        )*
+
+        /// Every exported signature, in declaration order — the input to [`ABI_HASH`].
+        #[cfg(feature = "hot-reload")]
+        pub const ABI_SIGNATURES: &str = concat!(
+            "ui_abi_hash() -> u32;",
+            "ui_version() -> u64;",
+            $(
+                stringify!($name(&mut Emulator $(, $ty)*)), ";",
+            )*
+        );
+
+        /// Hash of [`ABI_SIGNATURES`]; changes whenever any exported signature does.
+        #[cfg(feature = "hot-reload")]
+        pub const ABI_HASH: u32 = $crate::abi::fnv1a_32(ABI_SIGNATURES);
+
+        /// Report the ABI hash this dylib was built with.
+        #[cfg(feature = "hot-reload")]
+        #[no_mangle]
+        pub fn ui_abi_hash() -> u32 {
+            ABI_HASH
+        }
+
+        /// Binary-side handle to the hot-reloaded UI functions.
+        ///
+        /// Built from the hot-lib-reloader wrappers.  The hash is checked once
+        /// in [`connect`](Self::connect); call [`recheck`](Self::recheck)
+        /// after each reload.
+        #[cfg(feature = "hot-reload")]
+        pub struct HotUi {
+            abi_hash: fn() -> u32,
+            $($name: fn(&mut eink_emulator::Emulator $(, $ty)*),)*
+        }
+
+        #[cfg(feature = "hot-reload")]
+        impl HotUi {
+            /// Verify the loaded dylib's ABI hash and wrap its functions.
+            ///
+            /// # Errors
+            ///
+            /// Returns [`AbiMismatch`]($crate::abi::AbiMismatch) when the
+            /// dylib was built from a different signature set.
+            pub fn connect(
+                abi_hash: fn() -> u32,
+                $($name: fn(&mut eink_emulator::Emulator $(, $ty)*),)*
+            ) -> Result<Self, $crate::abi::AbiMismatch> {
+                $crate::abi::check_abi(ABI_HASH, abi_hash())?;
+                Ok(Self { abi_hash, $($name,)* })
+            }
+
+            /// Re-verify the ABI hash after the dylib was reloaded.
+            ///
+            /// # Errors
+            ///
+            /// Returns [`AbiMismatch`]($crate::abi::AbiMismatch) when the
+            /// new dylib was built from a different signature set.
+            pub fn recheck(&self) -> Result<(), $crate::abi::AbiMismatch> {
+                $crate::abi::check_abi(ABI_HASH, (self.abi_hash)())
+            }
+
+            $(
+                $(#[$meta])*
+                pub fn $name(&self, emulator: &mut eink_emulator::Emulator $(, $arg: $ty)*) {
+                    (self.$name)(emulator $(, $arg)*)
+                }
+            )*
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_reference_vectors() {
+        assert_eq!(fnv1a_32(""), 0x811c_9dc5);
+        assert_eq!(fnv1a_32("a"), 0xe40c_292c);
+        assert_eq!(fnv1a_32("foobar"), 0xbf9c_f968);
+    }
+
+    #[test]
+    fn check_abi_reports_both_hashes() {
+        assert!(check_abi(7, 7).is_ok());
+        assert_eq!(
+            check_abi(7, 9),
+            Err(AbiMismatch {
+                expected: 7,
+                found: 9
+            })
+        );
+    }
+}
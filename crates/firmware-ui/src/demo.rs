@@ -0,0 +1,552 @@
+//! Demo mode: an unattended attract loop for trade shows.
+//!
+//! [`Demo`] runs a [`DemoStep`] script ([`SCRIPT`] by default) against a
+//! simulated player: it switches between representative screens, scrolls
+//! and toggles the way a visitor would, and lets playback run in between, so
+//! the progress bar advances and tracks change on their own.  Time only
+//! moves when the caller calls [`Demo::advance`], which makes a run
+//! reproducible frame for frame.
+//!
+//! Two front ends drive it:
+//!
+//! - `cargo xtask demo` steps one loop headless and writes it as a GIF.
+//! - `cargo run -p firmware --example demo_mode --features demo` loops it
+//!   in the emulator window until the window is closed.
+//!
+//! At the end of the script the player is reset, so every loop (and the
+//! exported GIF) is identical.
+
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use library::lyrics::Lyrics;
+use platform::{OutputProfiles, OversamplingFilter};
+use ui::audio_settings::AudioSettings;
+use ui::lyrics::LyricsView;
+use ui::now_playing::NowPlayingState;
+use ui::queue::QueueView;
+use ui::quick_menu::QuickMenu;
+
+use crate::screens::{audio_settings, lyrics, now_playing, queue, quick_menu};
+use crate::theme::Theme;
+
+/// Time between two demo frames.  E-ink redraws the progress bar once a
+/// second on the device, so finer steps would only repeat frames.
+pub const FRAME_MS: u32 = 1_000;
+
+/// A screen the demo can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoScreen {
+    /// Now Playing for the current track.
+    NowPlaying,
+    /// The play queue with the current track marked.
+    Queue,
+    /// Lyrics of the current track, following its position.
+    Lyrics,
+    /// The output-profile quick menu.
+    QuickMenu,
+    /// DAC filter settings.
+    AudioSettings,
+}
+
+/// One step of a demo script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DemoStep {
+    /// Switch to a screen; its scroll position starts at the top.
+    Show(DemoScreen),
+    /// Let playback run for this many milliseconds.
+    Hold(u32),
+    /// Turn the wheel on the current screen by this many detents.
+    Scroll(i32),
+    /// Press play/pause.
+    PlayPause,
+    /// Skip to the next track.
+    Next,
+    /// Change the volume by this many percent.
+    Volume(i8),
+}
+
+/// A track in the simulated player's queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DemoTrack {
+    /// Track title.
+    pub title: &'static str,
+    /// Artist name.
+    pub artist: &'static str,
+    /// Track length in milliseconds (non-zero).
+    pub duration_ms: u32,
+    /// LRC lyrics, if the track has any.
+    pub lyrics: Option<&'static str>,
+}
+
+const ROADS_LRC: &str = "[ti:Roads]\n\
+                         [00:01.00]Oh, can't anybody see\n\
+                         [00:06.00]We've got a war to fight\n\
+                         [00:11.00]Never found our way\n\
+                         [00:16.00]Regardless of what they say\n";
+
+/// The queue the demo plays through.
+pub const TRACKS: &[DemoTrack] = &[
+    DemoTrack {
+        title: "Mysterons",
+        artist: "Portishead",
+        duration_ms: 302_000,
+        lyrics: None,
+    },
+    DemoTrack {
+        title: "Sour Times",
+        artist: "Portishead",
+        duration_ms: 251_000,
+        lyrics: None,
+    },
+    DemoTrack {
+        title: "Strangers",
+        artist: "Portishead",
+        duration_ms: 238_000,
+        lyrics: None,
+    },
+    DemoTrack {
+        title: "Roads",
+        artist: "Portishead",
+        duration_ms: 305_000,
+        lyrics: Some(ROADS_LRC),
+    },
+];
+
+/// The default attract loop, about 45 seconds long.  It starts near the end
+/// of "Strangers" so the first track change happens on its own.
+pub const SCRIPT: &[DemoStep] = &[
+    DemoStep::Show(DemoScreen::NowPlaying),
+    DemoStep::Hold(6_000),
+    DemoStep::Volume(10),
+    DemoStep::Hold(3_000),
+    DemoStep::Show(DemoScreen::Lyrics),
+    DemoStep::Hold(8_000),
+    DemoStep::Show(DemoScreen::Queue),
+    DemoStep::Hold(2_000),
+    DemoStep::Scroll(1),
+    DemoStep::Hold(2_000),
+    DemoStep::Scroll(1),
+    DemoStep::Hold(2_000),
+    DemoStep::Show(DemoScreen::NowPlaying),
+    DemoStep::Next,
+    DemoStep::Hold(4_000),
+    DemoStep::Show(DemoScreen::QuickMenu),
+    DemoStep::Hold(2_000),
+    DemoStep::Scroll(1),
+    DemoStep::Hold(2_000),
+    DemoStep::Show(DemoScreen::AudioSettings),
+    DemoStep::Hold(2_000),
+    DemoStep::Scroll(2),
+    DemoStep::Hold(2_000),
+    DemoStep::Show(DemoScreen::NowPlaying),
+    DemoStep::PlayPause,
+    DemoStep::Hold(3_000),
+    DemoStep::PlayPause,
+    DemoStep::Hold(3_000),
+];
+
+/// Track [`Demo::new`] starts on.
+const START_TRACK: usize = 2;
+/// [`SCRIPT`] starts this long before the end of its track.
+const START_BEFORE_END_MS: u32 = 8_000;
+
+/// Simulated playback: a position that advances while playing and rolls
+/// over into the next track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DemoPlayer {
+    tracks: &'static [DemoTrack],
+    index: usize,
+    position_ms: u32,
+    playing: bool,
+    volume: u8,
+}
+
+impl DemoPlayer {
+    /// Play `tracks[index]` from `position_ms`.
+    #[must_use]
+    pub fn new(tracks: &'static [DemoTrack], index: usize, position_ms: u32) -> Self {
+        Self {
+            tracks,
+            index,
+            position_ms,
+            playing: true,
+            volume: 70,
+        }
+    }
+
+    /// The current track.
+    #[must_use]
+    pub fn track(&self) -> Option<&'static DemoTrack> {
+        self.tracks.get(self.index)
+    }
+
+    /// Index of the current track in the queue.
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Position in the current track.
+    #[must_use]
+    pub fn position_ms(&self) -> u32 {
+        self.position_ms
+    }
+
+    /// Whether playback is running.
+    #[must_use]
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Let `ms` of playback pass, moving on to the next track (and from the
+    /// last back to the first) at the end of each.
+    pub fn advance(&mut self, ms: u32) {
+        if !self.playing {
+            return;
+        }
+        let mut position = self.position_ms.saturating_add(ms);
+        while let Some(track) = self.track() {
+            if position < track.duration_ms || track.duration_ms == 0 {
+                break;
+            }
+            position = position.saturating_sub(track.duration_ms);
+            self.skip();
+        }
+        self.position_ms = position;
+    }
+
+    /// Start the next track from the beginning.
+    pub fn next(&mut self) {
+        self.skip();
+        self.position_ms = 0;
+    }
+
+    /// Toggle play/pause.
+    pub fn play_pause(&mut self) {
+        self.playing = !self.playing;
+    }
+
+    /// Change the volume, clamped to `0..=100`.
+    pub fn change_volume(&mut self, delta: i8) {
+        self.volume = self.volume.saturating_add_signed(delta).min(100);
+    }
+
+    /// The Now Playing state for the current track.
+    #[must_use]
+    pub fn now_playing(&self) -> NowPlayingState {
+        let mut state = NowPlayingState::default();
+        state.set_playing(self.playing);
+        state.set_volume(self.volume);
+        state.set_position_ms(u64::from(self.position_ms));
+        if let Some(track) = self.track() {
+            state.set_duration_ms(u64::from(track.duration_ms));
+            // Demo titles fit; a longer one would only be cut short.
+            let _ = state.title.push_str(track.title);
+            let _ = state.artist.push_str(track.artist);
+        }
+        state
+    }
+
+    fn skip(&mut self) {
+        self.index = self
+            .index
+            .saturating_add(1)
+            .checked_rem(self.tracks.len())
+            .unwrap_or(0);
+    }
+}
+
+/// Runs a demo script against a [`DemoPlayer`].
+#[derive(Debug, Clone)]
+pub struct Demo {
+    script: &'static [DemoStep],
+    start: DemoPlayer,
+    player: DemoPlayer,
+    /// Next step to run.
+    step: usize,
+    /// Time left in the current [`DemoStep::Hold`].
+    hold_ms: u32,
+    screen: DemoScreen,
+    scroll: i32,
+    loops: u32,
+    profiles: OutputProfiles,
+}
+
+impl Demo {
+    /// The default attract loop: [`SCRIPT`] over [`TRACKS`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::starting_on(START_TRACK)
+    }
+
+    /// The default attract loop starting near the end of `TRACKS[track]`
+    /// (wrapping), so its first track change still happens on its own.
+    /// The emulator window picks `track` at random so units side by side
+    /// on a show floor are not in lockstep.
+    #[must_use]
+    pub fn starting_on(track: usize) -> Self {
+        let index = track.checked_rem(TRACKS.len()).unwrap_or(0);
+        let position_ms = TRACKS
+            .get(index)
+            .map_or(0, |t| t.duration_ms.saturating_sub(START_BEFORE_END_MS));
+        Self::with_script(SCRIPT, DemoPlayer::new(TRACKS, index, position_ms))
+    }
+
+    /// Run `script` from the top with `player` as it stands.  Each loop
+    /// starts again from this player.
+    #[must_use]
+    pub fn with_script(script: &'static [DemoStep], player: DemoPlayer) -> Self {
+        let mut demo = Self {
+            script,
+            start: player.clone(),
+            player,
+            step: 0,
+            hold_ms: 0,
+            screen: DemoScreen::NowPlaying,
+            scroll: 0,
+            loops: 0,
+            profiles: OutputProfiles::defaults(),
+        };
+        demo.run_steps();
+        demo
+    }
+
+    /// Length of one loop of the script.
+    #[must_use]
+    pub fn loop_ms(&self) -> u32 {
+        self.script
+            .iter()
+            .map(|step| match step {
+                DemoStep::Hold(ms) => *ms,
+                _ => 0,
+            })
+            .fold(0, u32::saturating_add)
+    }
+
+    /// The screen on display.
+    #[must_use]
+    pub fn screen(&self) -> DemoScreen {
+        self.screen
+    }
+
+    /// The simulated player.
+    #[must_use]
+    pub fn player(&self) -> &DemoPlayer {
+        &self.player
+    }
+
+    /// How many times the script has wrapped around.
+    #[must_use]
+    pub fn loops(&self) -> u32 {
+        self.loops
+    }
+
+    /// Let `ms` pass: playback runs through each hold, and the steps in
+    /// between run as their time comes.  Returns whether the screen changed
+    /// on the way, which calls for a full rather than partial refresh.
+    pub fn advance(&mut self, mut ms: u32) -> bool {
+        let before = (self.screen, self.loops);
+        while ms > 0 && self.hold_ms > 0 {
+            let step = ms.min(self.hold_ms);
+            self.player.advance(step);
+            self.hold_ms = self.hold_ms.saturating_sub(step);
+            ms = ms.saturating_sub(step);
+            if self.hold_ms == 0 {
+                self.run_steps();
+            }
+        }
+        (self.screen, self.loops) != before
+    }
+
+    /// Draw the current screen.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(D::Error)` if any draw call fails.
+    pub fn render<D, R>(&self, display: &mut D, register: R) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+        R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+    {
+        let theme = &Theme::STANDARD;
+        let size = display.bounding_box().size;
+        match self.screen {
+            DemoScreen::NowPlaying => now_playing::render_now_playing_to(
+                display,
+                theme,
+                &self.player.now_playing(),
+                None,
+                register,
+            ),
+            DemoScreen::Queue => {
+                let titles: Vec<&str> = self.player.tracks.iter().map(|t| t.title).collect();
+                let rows = queue::queue_rows(size, theme);
+                let mut view = QueueView::new(titles.len(), rows, Some(self.player.index));
+                view.select(self.player.index);
+                view.scroll(self.scroll);
+                queue::render_queue_to(display, theme, &titles, &view, register)
+            }
+            DemoScreen::Lyrics => {
+                let parsed: Option<Lyrics> = self
+                    .player
+                    .track()
+                    .and_then(|t| t.lyrics)
+                    .and_then(|lrc| Lyrics::parse(lrc).ok());
+                let mut view = LyricsView::new(lyrics::lyrics_rows(size));
+                if let Some(parsed) = parsed.as_ref() {
+                    view.follow(parsed.line_at(u64::from(self.player.position_ms)));
+                }
+                lyrics::render_lyrics_to(display, parsed.as_ref(), &view, register)
+            }
+            DemoScreen::QuickMenu => {
+                let mut menu = QuickMenu::new(self.profiles.len(), self.profiles.active_index());
+                menu.scroll(self.scroll);
+                quick_menu::render_quick_menu_to(display, theme, &self.profiles, &menu, register)
+            }
+            DemoScreen::AudioSettings => {
+                let mut settings = AudioSettings::new(
+                    OversamplingFilter::ALL.len(),
+                    OversamplingFilter::FastRollOffMinimumPhase.index(),
+                );
+                settings.scroll(self.scroll);
+                audio_settings::render_audio_settings_to(display, &settings, register)
+            }
+        }
+    }
+
+    /// Run steps up to and including the next hold.  Wrapping past the end
+    /// resets the player; a script with no holds stops after one pass.
+    fn run_steps(&mut self) {
+        for _ in 0..=self.script.len() {
+            if self.step >= self.script.len() && self.step > 0 {
+                self.step = 0;
+                self.loops = self.loops.saturating_add(1);
+                self.player = self.start.clone();
+            }
+            let Some(&step) = self.script.get(self.step) else {
+                return;
+            };
+            self.step = self.step.saturating_add(1);
+            match step {
+                DemoStep::Show(screen) => {
+                    self.screen = screen;
+                    self.scroll = 0;
+                }
+                DemoStep::Hold(0) => {}
+                DemoStep::Hold(ms) => {
+                    self.hold_ms = ms;
+                    return;
+                }
+                DemoStep::Scroll(steps) => self.scroll = self.scroll.saturating_add(steps),
+                DemoStep::PlayPause => self.player.play_pause(),
+                DemoStep::Next => self.player.next(),
+                DemoStep::Volume(delta) => self.player.change_volume(delta),
+            }
+        }
+    }
+}
+
+impl Default for Demo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::arithmetic_side_effects)]
+mod tests {
+    use super::*;
+    use crate::gallery;
+
+    #[test]
+    fn player_rolls_over_into_the_next_track() {
+        let mut player = DemoPlayer::new(TRACKS, 3, 300_000);
+        player.advance(7_000);
+        assert_eq!((player.index(), player.position_ms()), (0, 2_000));
+        player.play_pause();
+        player.advance(60_000);
+        assert_eq!(player.position_ms(), 2_000);
+    }
+
+    #[test]
+    fn volume_is_clamped() {
+        let mut player = DemoPlayer::new(TRACKS, 0, 0);
+        player.change_volume(100);
+        assert_eq!(player.now_playing().volume, 100);
+        player.change_volume(-128);
+        player.change_volume(-128);
+        assert_eq!(player.now_playing().volume, 0);
+    }
+
+    #[test]
+    fn script_changes_screens_and_tracks_on_cue() {
+        let mut demo = Demo::new();
+        assert_eq!(demo.screen(), DemoScreen::NowPlaying);
+        let mut changes = 0;
+        for _ in 0..8 {
+            changes += usize::from(demo.advance(FRAME_MS));
+        }
+        // Strangers ran out on its own.
+        assert_eq!(demo.player().track().unwrap().title, "Roads");
+        assert_eq!(changes, 0);
+        assert!(demo.advance(FRAME_MS));
+        assert_eq!(demo.screen(), DemoScreen::Lyrics);
+    }
+
+    #[test]
+    fn every_loop_starts_from_the_same_state() {
+        let mut demo = Demo::new();
+        let start = demo.player().clone();
+        let loop_ms = demo.loop_ms();
+        assert_eq!(loop_ms % FRAME_MS, 0);
+        for _ in 0..loop_ms / FRAME_MS {
+            demo.advance(FRAME_MS);
+        }
+        assert_eq!(demo.loops(), 1);
+        assert_eq!(demo.player(), &start);
+        assert_eq!(demo.screen(), DemoScreen::NowPlaying);
+    }
+
+    #[test]
+    fn any_start_track_changes_on_its_own() {
+        for track in 0..TRACKS.len() + 1 {
+            let mut demo = Demo::starting_on(track);
+            let first = demo.player().index();
+            assert_eq!(first, track % TRACKS.len());
+            for _ in 0..8 {
+                demo.advance(FRAME_MS);
+            }
+            assert_eq!(demo.player().index(), (first + 1) % TRACKS.len());
+        }
+        assert_eq!(
+            Demo::starting_on(START_TRACK).player(),
+            Demo::new().player()
+        );
+    }
+
+    #[test]
+    fn script_without_holds_stands_still() {
+        static STILL: &[DemoStep] = &[DemoStep::Show(DemoScreen::Queue), DemoStep::Next];
+        let mut demo = Demo::with_script(STILL, DemoPlayer::new(TRACKS, 0, 0));
+        assert!(!demo.advance(FRAME_MS));
+        assert_eq!(demo.screen(), DemoScreen::Queue);
+        assert_eq!(demo.loop_ms(), 0);
+    }
+
+    #[test]
+    fn every_scripted_screen_renders() {
+        let mut demo = Demo::new();
+        let mut seen = Vec::new();
+        while demo.loops() == 0 {
+            if !seen.contains(&demo.screen()) {
+                let mut display = gallery::emulator();
+                let mut count = 0usize;
+                demo.render(&mut display, |_, _, _, _| count += 1).unwrap();
+                assert!(count > 0, "{:?} registered no components", demo.screen());
+                seen.push(demo.screen());
+            }
+            demo.advance(FRAME_MS);
+        }
+        assert_eq!(seen.len(), 5);
+    }
+}
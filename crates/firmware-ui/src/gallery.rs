@@ -0,0 +1,311 @@
+//! Screen gallery: every screen renderer paired with representative fixture
+//! data, for design review.
+//!
+//! `cargo xtask screens` walks [`SCREENS`], renders each entry onto a
+//! headless emulator and writes one PNG per screen plus an HTML index.  Add
+//! an entry here when a new screen lands so it shows up in the review set.
+//!
+//! The fixtures mirror the visual tests in `crates/firmware-ui/tests/`.
+
+use core::convert::Infallible;
+
+use eink_emulator::Emulator;
+use eink_specs::displays::GDEM0397T81P;
+use eink_specs::DisplaySpec;
+use embedded_graphics::prelude::*;
+use library::chapters::Chapters;
+use library::lyrics::Lyrics;
+use library::{ScanProgress, TagField};
+use platform::boot_timing::{BootPhase, BootTimeline};
+use platform::diagnostics::{
+    DiagnosticsReport, GhostingEstimate, RefreshSpec, RefreshTiming, SdHealth, TestPattern,
+};
+use platform::soul_library::LibraryIntegrity;
+use platform::{OutputProfiles, OversamplingFilter};
+use ui::audio_settings::AudioSettings;
+use ui::chapters::ChapterList;
+use ui::diagnostics::Diagnostics;
+use ui::library_scan::LibraryScan;
+use ui::lyrics::LyricsView;
+use ui::now_playing::NowPlayingState;
+use ui::queue::QueueView;
+use ui::quick_menu::QuickMenu;
+use ui::tag_editor::TagEditor;
+
+use crate::screens::{
+    audio_settings, chapters, diagnostics, library_scan, lyrics, now_playing, queue, quick_menu,
+    tag_editor,
+};
+use crate::theme::Theme;
+
+/// The GDEM0397T81P panel in the portrait orientation the UI is laid out for.
+pub static PANEL: DisplaySpec = DisplaySpec {
+    name: "GDEM0397T81P (portrait)",
+    width: 480,
+    height: 800,
+    ..GDEM0397T81P
+};
+
+/// Component registration callback, as taken by the `render_*_to` functions.
+pub type Register<'a> = &'a mut dyn FnMut(&str, &str, (i32, i32), (u32, u32));
+
+/// One screen in the gallery.
+pub struct GalleryScreen {
+    /// File-name stem of the exported PNG (`<id>.png`).
+    pub id: &'static str,
+    /// Caption shown in the HTML index.
+    pub title: &'static str,
+    /// Draw the screen with its fixture data, reporting components.
+    pub render: fn(&mut Emulator, Register<'_>) -> Result<(), Infallible>,
+}
+
+/// Headless emulator sized to [`PANEL`], ready for a gallery render.
+pub fn emulator() -> Emulator {
+    Emulator::headless_with_spec(&PANEL)
+}
+
+/// Every registered screen, in review order.
+pub const SCREENS: &[GalleryScreen] = &[
+    GalleryScreen {
+        id: "now-playing",
+        title: "Now Playing",
+        render: render_now_playing,
+    },
+    GalleryScreen {
+        id: "now-playing-accessible",
+        title: "Now Playing (accessible theme)",
+        render: render_now_playing_accessible,
+    },
+    GalleryScreen {
+        id: "now-playing-skeleton",
+        title: "Now Playing (resumed at boot, index loading)",
+        render: render_now_playing_skeleton,
+    },
+    GalleryScreen {
+        id: "queue",
+        title: "Queue",
+        render: render_queue,
+    },
+    GalleryScreen {
+        id: "queue-select",
+        title: "Queue (batch select)",
+        render: render_queue_select,
+    },
+    GalleryScreen {
+        id: "library-scan",
+        title: "Library scan",
+        render: render_library_scan,
+    },
+    GalleryScreen {
+        id: "lyrics",
+        title: "Lyrics",
+        render: render_lyrics,
+    },
+    GalleryScreen {
+        id: "chapters",
+        title: "Chapters",
+        render: render_chapters,
+    },
+    GalleryScreen {
+        id: "audio-settings",
+        title: "Audio settings",
+        render: render_audio_settings,
+    },
+    GalleryScreen {
+        id: "quick-menu",
+        title: "Quick menu",
+        render: render_quick_menu,
+    },
+    GalleryScreen {
+        id: "tag-editor",
+        title: "Tag editor (typing)",
+        render: render_tag_editor,
+    },
+    GalleryScreen {
+        id: "diagnostics",
+        title: "Diagnostics results",
+        render: render_diagnostics,
+    },
+];
+
+const QUEUE_TITLES: [&str; 5] = ["Mysterons", "Sour Times", "", "Strangers", "Roads"];
+
+const LRC: &str = "[ti:Roads]\n\
+                   [00:01.00]Oh, can't anybody see\n\
+                   [00:06.00]We've got a war to fight\n\
+                   [00:11.00]Never found our way\n\
+                   [00:16.00]Regardless of what they say\n";
+
+fn now_playing_state() -> NowPlayingState {
+    let mut state = NowPlayingState::default();
+    state.set_playing(true);
+    state.set_volume(75);
+    state.set_duration_ms(240_000);
+    state.set_position_ms(60_000);
+    state
+}
+
+fn render_now_playing(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    now_playing::render_now_playing_to(
+        display,
+        &Theme::STANDARD,
+        &now_playing_state(),
+        None,
+        register,
+    )
+}
+
+fn render_now_playing_accessible(
+    display: &mut Emulator,
+    register: Register<'_>,
+) -> Result<(), Infallible> {
+    now_playing::render_now_playing_to(
+        display,
+        &Theme::ACCESSIBLE,
+        &now_playing_state(),
+        None,
+        register,
+    )
+}
+
+fn render_now_playing_skeleton(
+    display: &mut Emulator,
+    register: Register<'_>,
+) -> Result<(), Infallible> {
+    let mut state = now_playing_state();
+    state.set_loading(true);
+    now_playing::render_now_playing_to(display, &Theme::STANDARD, &state, None, register)
+}
+
+fn render_queue(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    let theme = &Theme::STANDARD;
+    let rows = queue::queue_rows(display.bounding_box().size, theme);
+    let view = QueueView::new(QUEUE_TITLES.len(), rows, Some(1));
+    queue::render_queue_to(display, theme, &QUEUE_TITLES, &view, register)
+}
+
+fn render_queue_select(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    let theme = &Theme::STANDARD;
+    let rows = queue::queue_rows(display.bounding_box().size, theme);
+    let mut view = QueueView::new(QUEUE_TITLES.len(), rows, Some(0));
+    view.hold();
+    view.scroll(2);
+    view.click();
+    queue::render_queue_to(display, theme, &QUEUE_TITLES, &view, register)
+}
+
+fn render_library_scan(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    let progress = ScanProgress {
+        dirs_visited: 12,
+        files_parsed: 140,
+        tracks_added: 138,
+    };
+    library_scan::render_library_scan_to(display, &progress, &LibraryScan::new(), register)
+}
+
+fn render_lyrics(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    let parsed: Option<Lyrics> = Lyrics::parse(LRC).ok();
+    let mut view = LyricsView::new(lyrics::lyrics_rows(display.bounding_box().size));
+    if let Some(parsed) = parsed.as_ref() {
+        view.follow(parsed.line_at(7_000));
+    }
+    lyrics::render_lyrics_to(display, parsed.as_ref(), &view, register)
+}
+
+fn render_chapters(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    let mut book: Chapters = Chapters::new();
+    for (start_ms, title) in [
+        (0, "Opening Credits"),
+        (95_000, "Chapter 1"),
+        (1_460_000, "Chapter 2"),
+        (2_810_000, ""),
+        (4_020_000, "Epilogue"),
+    ] {
+        book.push(start_ms, title);
+    }
+    let rows = chapters::chapter_rows(display.bounding_box().size);
+    let mut list = ChapterList::new(book.len(), rows, Some(1));
+    list.scroll(1);
+    chapters::render_chapters_to(display, Some(&book), &list, register)
+}
+
+fn render_audio_settings(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    let mut settings = AudioSettings::new(
+        OversamplingFilter::ALL.len(),
+        OversamplingFilter::FastRollOffMinimumPhase.index(),
+    );
+    settings.scroll(2);
+    audio_settings::render_audio_settings_to(display, &settings, register)
+}
+
+fn render_quick_menu(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let mut menu = QuickMenu::new(profiles.len(), profiles.active_index());
+    menu.scroll(1);
+    quick_menu::render_quick_menu_to(display, &Theme::STANDARD, &profiles, &menu, register)
+}
+
+fn render_tag_editor(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    let mut editor = TagEditor::new(
+        ["Track 03", "Unknown Artist", "Dummy"],
+        TagField::ALL.map(TagField::max_len),
+    );
+    editor.scroll(1);
+    editor.click();
+    editor.scroll(15); // 'P'
+    tag_editor::render_tag_editor_to(display, &Theme::STANDARD, &editor, register)
+}
+
+fn render_diagnostics(display: &mut Emulator, register: Register<'_>) -> Result<(), Infallible> {
+    let mut diag = Diagnostics::new();
+    while diag.advance() {}
+    let mut report = DiagnosticsReport::new();
+    for pattern in TestPattern::ALL {
+        let mode = pattern.refresh_mode();
+        let spec_ms = RefreshSpec::GDEM0397T81P.for_mode(mode);
+        report.record_timing(RefreshTiming {
+            pattern,
+            mode,
+            measured_ms: spec_ms,
+            spec_ms,
+        });
+    }
+    report.ghosting = Some(GhostingEstimate::new(None, 40));
+    report.sd = Some(SdHealth::Ok {
+        bytes: 512,
+        read_ms: 6,
+    });
+    let mut boot = BootTimeline::new();
+    boot.mark(BootPhase::Clocks, 60);
+    boot.mark(BootPhase::FirstFrame, 1_400);
+    report.boot = Some(boot.report());
+    report.library = Some(LibraryIntegrity::new());
+    diagnostics::render_diagnostics_to(display, &diag, &report, register)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_unique_file_stems() {
+        for (i, screen) in SCREENS.iter().enumerate() {
+            assert!(screen
+                .id
+                .bytes()
+                .all(|b| b.is_ascii_lowercase() || b == b'-'));
+            assert!(SCREENS.iter().skip(i + 1).all(|s| s.id != screen.id));
+        }
+    }
+
+    #[test]
+    fn every_screen_registers_components() {
+        for screen in SCREENS {
+            let mut display = emulator();
+            let mut count = 0usize;
+            (screen.render)(&mut display, &mut |_, _, _, _| count += 1).unwrap();
+            assert!(count > 0, "{} registered no components", screen.id);
+        }
+    }
+}
@@ -19,7 +19,7 @@
 //!
 //! # Dependency Design
 //!
-//! The C ABI boundary uses eink_emulator::Emulator directly (not
+//! The hot-reload boundary uses eink_emulator::Emulator directly (not
 //! firmware::EmulatorDisplay) to avoid a circular dependency:
 //!   firmware -> firmware-ui (for the hot-reload feature)
 //!   firmware-ui -> firmware (for EmulatorDisplay) -- CIRCULAR, AVOIDED
@@ -31,48 +31,33 @@
 //!
 //! Step 1: cargo build --package firmware-ui --features hot-reload
 //! Step 2: cargo run --example display_emulator --features emulator,hot-reload
+//!
+//! The exported functions are declared once with `hot_reload_abi!` (see
+//! [`abi`]); the binary connects through `HotUi`, which refuses a dylib whose
+//! signature hash differs from its own.
 
+#[macro_use]
+pub mod abi;
+pub mod playback_events;
 pub mod screens;
+pub mod theme;
 
+#[cfg(feature = "emulator")]
+pub mod demo;
+#[cfg(feature = "emulator")]
+pub mod gallery;
 #[cfg(feature = "emulator")]
 mod render;
 
 #[cfg(feature = "emulator")]
 pub use render::render_demo_menu;
 
-/// Hot-reload entry point: render the DAP UI onto an eink_emulator::Emulator.
-///
-/// The binary side passes a pointer to the inner Emulator from EmulatorDisplay.
-/// This avoids the circular dependency: firmware -> firmware-ui -> firmware.
-///
-/// # Safety
-///
-/// emulator_ptr must be a valid, exclusively-owned Emulator pointer.
-#[cfg(feature = "hot-reload")]
-#[no_mangle]
-pub unsafe extern "C" fn render_ui(emulator_ptr: *mut eink_emulator::Emulator) {
-    assert!(
-        !emulator_ptr.is_null(),
-        "render_ui: emulator_ptr must not be null"
-    );
-    let emulator = unsafe { &mut *emulator_ptr };
-    if let Err(e) = render::render_onto_emulator(emulator) {
-        eprintln!("[firmware-ui] render_ui error: {:?}", e);
-    }
-}
-
-/// Manual ABI version -- bump this whenever the C-ABI signature of `render_ui`
-/// (or any other `#[no_mangle]` exported function) changes.
-/// The binary checks this at startup to ensure the loaded dylib matches.
-#[cfg(feature = "hot-reload")]
-pub const ABI_VERSION: u32 = 1;
-
-/// ABI version check -- exported so the binary can verify the loaded dylib
-/// was compiled with the same ABI contract.
-#[cfg(feature = "hot-reload")]
-#[no_mangle]
-pub extern "C" fn ui_abi_version() -> u32 {
-    ABI_VERSION
+hot_reload_abi! {
+    /// Hot-reload entry point: render the DAP UI onto an eink_emulator::Emulator.
+    ///
+    /// The binary side passes the inner Emulator from EmulatorDisplay.
+    /// This avoids the circular dependency: firmware -> firmware-ui -> firmware.
+    fn render_ui(emulator) => render::render_ui;
 }
 
 /// Load-time version of this dylib for hot-reload change detection.
@@ -83,7 +68,7 @@ pub extern "C" fn ui_abi_version() -> u32 {
 /// though the package version string stays constant.
 #[cfg(feature = "hot-reload")]
 #[no_mangle]
-pub extern "C" fn ui_version() -> u64 {
+pub fn ui_version() -> u64 {
     use std::sync::OnceLock;
     static VERSION: OnceLock<u64> = OnceLock::new();
     *VERSION.get_or_init(|| {
@@ -0,0 +1,131 @@
+//! Now Playing as a playback event subscriber.
+//!
+//! The UI task holds a [`PlaybackEventSubscriber`] and, each time it wakes,
+//! passes the queued events through [`apply_all`] into its
+//! [`NowPlayingState`] instead of reading the engine's state and position
+//! on a timer.  A returned track id is the cue to look up title, artist
+//! and album in the library.
+//!
+//! ```ignore
+//! let started = playback_events::apply_all(&mut state, core::iter::from_fn(|| sub.try_next_event()));
+//! if let Some(id) = started { /* library lookup, then set title / artist / album */ }
+//! ```
+//!
+//! [`PlaybackEventSubscriber`]: playback::events::PlaybackEventSubscriber
+
+use playback::engine::PlaybackState;
+use playback::events::PlaybackEvent;
+use ui::now_playing::NowPlayingState;
+
+/// Update `state` for `event`.  Returns the id of a track that just
+/// started.
+///
+/// Events the screen does not show (underruns, faults, EQ) leave `state`
+/// alone.
+pub fn apply(state: &mut NowPlayingState, event: PlaybackEvent) -> Option<u32> {
+    match event {
+        PlaybackEvent::TrackStarted {
+            track_id,
+            duration_ms,
+        } => {
+            state.set_duration_ms(u64::from(duration_ms));
+            state.set_position_ms(0);
+            return Some(track_id);
+        }
+        PlaybackEvent::StateChanged(playback_state) => {
+            state.set_playing(playback_state == PlaybackState::Playing);
+            if playback_state == PlaybackState::Stopped {
+                state.set_position_ms(0);
+            }
+        }
+        PlaybackEvent::PositionTick { position_ms } => {
+            state.set_position_ms(u64::from(position_ms));
+        }
+        PlaybackEvent::BufferUnderrun { .. }
+        | PlaybackEvent::EqChanged { .. }
+        | PlaybackEvent::Fault { .. } => {}
+    }
+    None
+}
+
+/// Apply `events` to `state` in order.  Returns the last track that
+/// started.
+pub fn apply_all(
+    state: &mut NowPlayingState,
+    events: impl IntoIterator<Item = PlaybackEvent>,
+) -> Option<u32> {
+    events
+        .into_iter()
+        .fold(None, |started, event| apply(state, event).or(started))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn track_start_resets_position_and_sets_duration() {
+        let mut state = NowPlayingState::default();
+        state.set_position_ms(90_000);
+        let started = apply(
+            &mut state,
+            PlaybackEvent::TrackStarted {
+                track_id: 4,
+                duration_ms: 200_000,
+            },
+        );
+        assert_eq!(started, Some(4));
+        assert_eq!(state.position_ms, 0);
+        assert_eq!(state.duration_ms, 200_000);
+    }
+
+    #[test]
+    fn state_and_position_follow_the_engine() {
+        let mut state = NowPlayingState::default();
+        let started = apply_all(
+            &mut state,
+            [
+                PlaybackEvent::StateChanged(PlaybackState::Playing),
+                PlaybackEvent::PositionTick { position_ms: 1_250 },
+                PlaybackEvent::EqChanged { preset: 2 },
+            ],
+        );
+        assert_eq!(started, None);
+        assert!(state.playing);
+        assert_eq!(state.position_ms, 1_250);
+
+        apply(
+            &mut state,
+            PlaybackEvent::StateChanged(PlaybackState::Paused),
+        );
+        assert!(!state.playing);
+        assert_eq!(state.position_ms, 1_250);
+
+        apply(
+            &mut state,
+            PlaybackEvent::StateChanged(PlaybackState::Stopped),
+        );
+        assert!(!state.playing);
+        assert_eq!(state.position_ms, 0);
+    }
+
+    #[test]
+    fn apply_all_returns_the_last_started_track() {
+        let mut state = NowPlayingState::default();
+        let started = apply_all(
+            &mut state,
+            [
+                PlaybackEvent::TrackStarted {
+                    track_id: 1,
+                    duration_ms: 0,
+                },
+                PlaybackEvent::TrackStarted {
+                    track_id: 2,
+                    duration_ms: 0,
+                },
+                PlaybackEvent::PositionTick { position_ms: 10 },
+            ],
+        );
+        assert_eq!(started, Some(2));
+    }
+}
@@ -39,24 +39,28 @@ pub fn render_demo_menu(
         "5. About",
     ];
 
-    for (idx, item) in menu_items.iter().enumerate() {
-        let y = 100 + (idx as i32 * 50);
-
+    for (y, (idx, item)) in (100i32..).step_by(50).zip(menu_items.iter().enumerate()) {
         // Item background (alternate shading)
         if idx % 2 == 0 {
-            Rectangle::new(Point::new(10, y - 5), Size::new(size.width - 20, 45))
-                .into_styled(PrimitiveStyle::with_fill(Gray4::new(0xE)))
-                .draw(display)?;
+            Rectangle::new(
+                Point::new(10, y.saturating_sub(5)),
+                Size::new(size.width.saturating_sub(20), 45),
+            )
+            .into_styled(PrimitiveStyle::with_fill(Gray4::new(0xE)))
+            .draw(display)?;
         }
 
-        Text::new(item, Point::new(30, y + 20), menu_style).draw(display)?;
+        Text::new(item, Point::new(30, y.saturating_add(20)), menu_style).draw(display)?;
     }
 
     // Footer hint
     let footer_style = MonoTextStyle::new(&FONT_10X20, Gray4::new(0x8));
     Text::new(
         "Edit render.rs and save to hot-reload!",
-        Point::new(30, (size.height - 30) as i32),
+        Point::new(
+            30,
+            i32::try_from(size.height.saturating_sub(30)).unwrap_or(i32::MAX),
+        ),
         footer_style,
     )
     .draw(display)?;
@@ -64,12 +68,19 @@ pub fn render_demo_menu(
     Ok(())
 }
 
-/// Render onto a mutable Emulator reference (used by the hot-reload C ABI).
+/// Hot-reload entry point, exported as `render_ui` by `hot_reload_abi!` in lib.rs.
+#[cfg(feature = "hot-reload")]
+pub fn render_ui(emulator: &mut eink_emulator::Emulator) {
+    if let Err(e) = render_onto_emulator(emulator) {
+        eprintln!("[firmware-ui] render_ui error: {:?}", e);
+    }
+}
+
+/// Render onto a mutable Emulator reference.
 ///
-/// This is called by render_ui() in lib.rs via the raw pointer interface.
 /// Delegates directly to render_demo_menu().
 #[cfg(feature = "emulator")]
-#[allow(dead_code)] // called via raw fn pointer by hot-lib-reloader; clippy can't see it
+#[allow(dead_code)] // only reached through render_ui with the hot-reload feature
 pub fn render_onto_emulator(
     emulator: &mut eink_emulator::Emulator,
 ) -> Result<(), core::convert::Infallible> {
@@ -0,0 +1,136 @@
+//! Audio settings screen renderer
+//!
+//! Lists the DAC's oversampling filters (`OversamplingFilter::ALL`, in
+//! datasheet order) under an "Oversampling filter" caption.  The selected
+//! row is drawn as a dark bar; the filter programmed into the DAC now is
+//! marked with `*`.  All seven rows fit on screen, so the list never
+//! scrolls.  Rows are [`ROW_H`] pixels from [`LIST_TOP`], both on the
+//! 8-pixel partial window grid.
+//!
+//! # Registered test IDs
+//!
+//! | test ID                   | Component type |
+//! |---------------------------|----------------|
+//! | `"audio-filter-list"`     | `"List"`       |
+//! | `"audio-filter-selected"` | `"Label"`      |
+//! | `"audio-filter-applied"`  | `"Label"`      |
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::Text,
+};
+use platform::OversamplingFilter;
+use ui::audio_settings::AudioSettings;
+
+use super::on_screen;
+
+/// Height of the title bar.
+const HEADER_H: u32 = 48;
+/// Baseline of the section caption.
+const CAPTION_Y: i32 = 80;
+/// Top of the first filter row.
+pub const LIST_TOP: u32 = 96;
+/// Height of one filter row.
+pub const ROW_H: u32 = 40;
+/// Left inset of the applied marker.
+const MARKER_X: i32 = 8;
+/// Left text inset.
+const TEXT_X: i32 = 28;
+/// Baseline offset of FONT_10X20 within a row.
+const BASELINE: i32 = 26;
+
+/// Screen rectangle of filter `index`, or `None` when out of range.
+#[must_use]
+pub fn filter_rect(size: Size, index: usize) -> Option<Rectangle> {
+    OversamplingFilter::from_index(index)?;
+    let row = u32::try_from(index).ok()?;
+    let y = LIST_TOP.saturating_add(row.saturating_mul(ROW_H));
+    Some(Rectangle::new(
+        Point::new(0, i32::try_from(y).ok()?),
+        Size::new(size.width, ROW_H),
+    ))
+}
+
+/// Render the audio settings screen onto any `DrawTarget<Color = Gray4>`.
+///
+/// The `register` closure works as in
+/// [`render_now_playing_to`](super::now_playing::render_now_playing_to).
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn render_audio_settings_to<D, R>(
+    display: &mut D,
+    settings: &AudioSettings,
+    register: R,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    let size = display.bounding_box().size;
+    let mut register = on_screen(size, register);
+
+    Rectangle::new(Point::zero(), size)
+        .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+        .draw(display)?;
+
+    // ── Header bar ────────────────────────────────────────────────────────
+    Rectangle::new(Point::zero(), Size::new(size.width, HEADER_H))
+        .into_styled(PrimitiveStyle::with_fill(Gray4::new(0x2)))
+        .draw(display)?;
+    let header_style = MonoTextStyle::new(&FONT_10X20, Gray4::WHITE);
+    Text::new("Audio", Point::new(TEXT_X, 32), header_style).draw(display)?;
+
+    let caption_style = MonoTextStyle::new(&FONT_10X20, Gray4::new(0x6));
+    Text::new(
+        "Oversampling filter",
+        Point::new(TEXT_X, CAPTION_Y),
+        caption_style,
+    )
+    .draw(display)?;
+
+    for (index, filter) in OversamplingFilter::ALL.iter().enumerate() {
+        let Some(rect) = filter_rect(size, index) else {
+            continue;
+        };
+        let (bg, fg) = if settings.selected() == index {
+            (Gray4::new(0x2), Gray4::WHITE)
+        } else {
+            (Gray4::WHITE, Gray4::BLACK)
+        };
+        rect.into_styled(PrimitiveStyle::with_fill(bg))
+            .draw(display)?;
+        let style = MonoTextStyle::new(&FONT_10X20, fg);
+        let baseline = rect.top_left.y.saturating_add(BASELINE);
+        if settings.applied() == index {
+            Text::new("*", Point::new(MARKER_X, baseline), style).draw(display)?;
+        }
+        Text::new(filter.label(), Point::new(TEXT_X, baseline), style).draw(display)?;
+    }
+
+    let rows = u32::try_from(OversamplingFilter::ALL.len()).unwrap_or(0);
+    register(
+        "audio-filter-list",
+        "List",
+        (0, i32::try_from(LIST_TOP).unwrap_or(0)),
+        (size.width, rows.saturating_mul(ROW_H)),
+    );
+    for (id, index) in [
+        ("audio-filter-selected", settings.selected()),
+        ("audio-filter-applied", settings.applied()),
+    ] {
+        if let Some(rect) = filter_rect(size, index) {
+            register(
+                id,
+                "Label",
+                (rect.top_left.x, rect.top_left.y),
+                (rect.size.width, rect.size.height),
+            );
+        }
+    }
+    Ok(())
+}
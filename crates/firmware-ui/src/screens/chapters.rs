@@ -0,0 +1,227 @@
+//! Chapters screen renderer
+//!
+//! Lists the chapters of the current audiobook with their start times.  The
+//! selected row is drawn as a dark bar; the chapter playing now is marked
+//! with `>`.  Untitled chapters (common in CUE sheets) show as `Chapter N`.
+//! Rows are [`ROW_H`] pixels from [`LIST_TOP`], both on the 8-pixel partial
+//! window grid.
+//!
+//! # Registered test IDs
+//!
+//! | test ID               | Component type |
+//! |-----------------------|----------------|
+//! | `"chapters-list"`     | `"List"`       |
+//! | `"chapters-selected"` | `"Label"`      |
+//! | `"chapters-playing"`  | `"Label"`      |
+//! | `"chapters-empty"`    | `"Label"`      |
+
+use core::fmt::Write as _;
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Text},
+};
+use library::chapters::Chapters;
+use ui::chapters::ChapterList;
+
+use super::{on_screen, TextBuf};
+
+/// Height of the title bar.
+const HEADER_H: u32 = 48;
+/// Top of the first chapter row.
+pub const LIST_TOP: u32 = 56;
+/// Height of one chapter row.
+pub const ROW_H: u32 = 40;
+/// Left inset of the playing marker.
+const MARKER_X: i32 = 8;
+/// Left/right text inset.
+const TEXT_X: i32 = 28;
+/// Baseline offset of FONT_10X20 within a row.
+const BASELINE: i32 = 26;
+/// FONT_10X20 advance.
+const CHAR_W: u32 = 10;
+/// Width reserved for the start time ("10:00:00" plus a gap).
+const TIME_W: u32 = 100;
+
+/// Rows of chapters that fit on a screen of `size` (at least one).
+#[must_use]
+pub fn chapter_rows(size: Size) -> usize {
+    let rows = size.height.saturating_sub(LIST_TOP).checked_div(ROW_H);
+    usize::try_from(rows.unwrap_or(0)).unwrap_or(0).max(1)
+}
+
+/// Screen rectangle of chapter `index`, or `None` when it is scrolled away.
+#[must_use]
+pub fn chapter_rect(size: Size, list: &ChapterList, index: usize) -> Option<Rectangle> {
+    if !list.is_visible(index) {
+        return None;
+    }
+    let row = u32::try_from(index.saturating_sub(list.top())).ok()?;
+    let y = LIST_TOP.saturating_add(row.saturating_mul(ROW_H));
+    Some(Rectangle::new(
+        Point::new(0, i32::try_from(y).ok()?),
+        Size::new(size.width, ROW_H),
+    ))
+}
+
+/// Render the chapters screen onto any `DrawTarget<Color = Gray4>`.
+///
+/// `chapters` is `None` (or empty) when the track has no chapter marks.
+/// The `register` closure works as in
+/// [`render_now_playing_to`](super::now_playing::render_now_playing_to).
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn render_chapters_to<D, R, const N: usize>(
+    display: &mut D,
+    chapters: Option<&Chapters<N>>,
+    list: &ChapterList,
+    register: R,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    let size = display.bounding_box().size;
+    let mut register = on_screen(size, register);
+
+    Rectangle::new(Point::zero(), size)
+        .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+        .draw(display)?;
+
+    // ── Header bar ────────────────────────────────────────────────────────
+    Rectangle::new(Point::zero(), Size::new(size.width, HEADER_H))
+        .into_styled(PrimitiveStyle::with_fill(Gray4::new(0x2)))
+        .draw(display)?;
+    let header_style = MonoTextStyle::new(&FONT_10X20, Gray4::WHITE);
+    Text::new("Chapters", Point::new(TEXT_X, 32), header_style).draw(display)?;
+
+    let Some(chapters) = chapters.filter(|c| !c.is_empty()) else {
+        // SAFETY: display dimensions (800×480) are far below i32::MAX.
+        #[allow(clippy::cast_possible_wrap)]
+        let centre = Point::new((size.width / 2) as i32, (size.height / 2) as i32);
+        let style = MonoTextStyle::new(&FONT_10X20, Gray4::new(0x6));
+        Text::with_alignment("No chapters", centre, style, Alignment::Center).draw(display)?;
+        register(
+            "chapters-empty",
+            "Label",
+            (centre.x.saturating_sub(55), centre.y.saturating_sub(16)),
+            (110, 20),
+        );
+        return Ok(());
+    };
+
+    for row in 0..list.rows() {
+        draw_row(display, chapters, list, list.top().saturating_add(row))?;
+    }
+    let shown =
+        u32::try_from(list.rows().min(list.count().saturating_sub(list.top()))).unwrap_or(0);
+    register(
+        "chapters-list",
+        "List",
+        (0, i32::try_from(LIST_TOP).unwrap_or(0)),
+        (size.width, shown.saturating_mul(ROW_H)),
+    );
+    for (id, index) in [
+        ("chapters-selected", Some(list.selected())),
+        ("chapters-playing", list.playing()),
+    ] {
+        if let Some(rect) = index.and_then(|i| chapter_rect(size, list, i)) {
+            register(
+                id,
+                "Label",
+                (rect.top_left.x, rect.top_left.y),
+                (rect.size.width, rect.size.height),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Draw chapter `index` in its row: title (or `Chapter N`) on the left,
+/// start time on the right, inverted when selected.
+fn draw_row<D, const N: usize>(
+    display: &mut D,
+    chapters: &Chapters<N>,
+    list: &ChapterList,
+    index: usize,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    let size = display.bounding_box().size;
+    let Some(rect) = chapter_rect(size, list, index) else {
+        return Ok(());
+    };
+    let (bg, fg) = if list.selected() == index {
+        (Gray4::new(0x2), Gray4::WHITE)
+    } else {
+        (Gray4::WHITE, Gray4::BLACK)
+    };
+    rect.into_styled(PrimitiveStyle::with_fill(bg))
+        .draw(display)?;
+
+    let Some(start_ms) = chapters.start_ms(index) else {
+        return Ok(());
+    };
+    let style = MonoTextStyle::new(&FONT_10X20, fg);
+    let baseline = rect.top_left.y.saturating_add(BASELINE);
+
+    if list.playing() == Some(index) {
+        Text::new(">", Point::new(MARKER_X, baseline), style).draw(display)?;
+    }
+
+    let mut fallback = TextBuf::<16>::new();
+    let title = match chapters.title(index).filter(|t| !t.is_empty()) {
+        Some(title) => title,
+        None => {
+            let _ = write!(fallback, "Chapter {}", index.saturating_add(1));
+            fallback.as_str()
+        }
+    };
+    let max_chars = usize::try_from(
+        size.width
+            .saturating_sub(TIME_W)
+            .saturating_sub(40)
+            .checked_div(CHAR_W)
+            .unwrap_or(0),
+    )
+    .unwrap_or(0);
+    let end = title
+        .char_indices()
+        .nth(max_chars)
+        .map_or(title.len(), |(i, _)| i);
+    Text::new(
+        title.get(..end).unwrap_or(title),
+        Point::new(TEXT_X, baseline),
+        style,
+    )
+    .draw(display)?;
+
+    let mut time = TextBuf::<16>::new();
+    let secs = start_ms / 1000;
+    let _ = if secs >= 3600 {
+        write!(
+            time,
+            "{}:{:02}:{:02}",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    } else {
+        write!(time, "{}:{:02}", secs / 60, secs % 60)
+    };
+    let right = i32::try_from(size.width).unwrap_or(0).saturating_sub(20);
+    Text::with_alignment(
+        time.as_str(),
+        Point::new(right, baseline),
+        style,
+        Alignment::Right,
+    )
+    .draw(display)?;
+    Ok(())
+}
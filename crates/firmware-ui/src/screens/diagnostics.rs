@@ -0,0 +1,357 @@
+//! Diagnostics screen renderer — hidden self-test patterns and results
+//!
+//! On a pattern step the whole panel shows the
+//! `platform::diagnostics::TestPattern` for that step and nothing else, so
+//! the pattern itself is what gets inspected.  The results page lists one
+//! check per row — the three timed refreshes, ghosting, SD card, battery,
+//! audio loopback, `soul.toml`, library checksums, boot time, audio
+//! dropouts and failed boots — with the value on the left and `ok`/`FAIL`
+//! right-aligned, then the overall result.  Checks that have
+//! not run show `-`.  After a crash loop the title reads "Safe mode"
+//! instead, since this page is where the safe-mode prompt sends the user.  Problems in `soul.toml` are listed under the result,
+//! one per row and cut at the panel edge; the full text is in the log.
+//! Rows are [`ROW_H`] pixels from [`LIST_TOP`], both on the 8-pixel
+//! partial window grid.
+//!
+//! # Registered test IDs
+//!
+//! | test ID           | Component type |
+//! |-------------------|----------------|
+//! | `"diag-pattern"`  | `"Image"`      |
+//! | `"diag-full"`     | `"Label"`      |
+//! | `"diag-partial"`  | `"Label"`      |
+//! | `"diag-fast"`     | `"Label"`      |
+//! | `"diag-ghosting"` | `"Label"`      |
+//! | `"diag-sd"`       | `"Label"`      |
+//! | `"diag-battery"`  | `"Label"`      |
+//! | `"diag-audio"`    | `"Label"`      |
+//! | `"diag-config"`   | `"Label"`      |
+//! | `"diag-library"`  | `"Label"`      |
+//! | `"diag-boot"`     | `"Label"`      |
+//! | `"diag-dropouts"` | `"Label"`      |
+//! | `"diag-boot-guard"` | `"Label"`    |
+//! | `"diag-result"`   | `"Label"`      |
+//! | `"diag-config-1"` … `"diag-config-8"` | `"Label"` |
+//!
+//! Pattern steps register only `"diag-pattern"`; the results page
+//! registers the rest, with one `"diag-config-N"` per listed problem.
+
+use core::fmt::Write as _;
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Text},
+};
+use platform::audio_loopback::LoopbackFault;
+use platform::crash_loop::BootMode;
+use platform::diagnostics::{DiagnosticsReport, SdHealth, TestPattern};
+use platform::soul_config::MAX_ERRORS;
+use platform::soul_library::LibrarySection;
+use platform::RefreshMode;
+use ui::diagnostics::{Diagnostics, DiagnosticsStep};
+
+use super::{on_screen, TextBuf};
+
+/// Height of the title bar.
+const HEADER_H: u32 = 48;
+/// Top of the first result row.
+pub const LIST_TOP: u32 = 64;
+/// Height of one result row.
+pub const ROW_H: u32 = 32;
+/// Left text inset.
+const TEXT_X: i32 = 28;
+/// Baseline offset of FONT_10X20 within a row.
+const BASELINE: i32 = 22;
+
+/// Result rows, in display order.  The last is the overall result.
+pub const ROWS: [&str; 13] = [
+    "diag-full",
+    "diag-partial",
+    "diag-fast",
+    "diag-ghosting",
+    "diag-sd",
+    "diag-battery",
+    "diag-audio",
+    "diag-config",
+    "diag-library",
+    "diag-boot",
+    "diag-dropouts",
+    "diag-boot-guard",
+    "diag-result",
+];
+
+/// `soul.toml` problem rows, shown below [`ROWS`].
+pub const CONFIG_ERROR_ROWS: [&str; MAX_ERRORS] = [
+    "diag-config-1",
+    "diag-config-2",
+    "diag-config-3",
+    "diag-config-4",
+    "diag-config-5",
+    "diag-config-6",
+    "diag-config-7",
+    "diag-config-8",
+];
+
+/// Characters of a problem row that fit beside the left inset.
+const ERROR_TEXT_LEN: usize = 44;
+
+fn grid_rect(size: Size, row: usize) -> Option<Rectangle> {
+    let row = u32::try_from(row).ok()?;
+    let y = LIST_TOP.saturating_add(row.saturating_mul(ROW_H));
+    Some(Rectangle::new(
+        Point::new(0, i32::try_from(y).ok()?),
+        Size::new(size.width, ROW_H),
+    ))
+}
+
+/// Screen rectangle of result row `row` (see [`ROWS`]), or `None` past the
+/// last row.
+#[must_use]
+pub fn row_rect(size: Size, row: usize) -> Option<Rectangle> {
+    ROWS.get(row)?;
+    grid_rect(size, row)
+}
+
+/// Screen rectangle of `soul.toml` problem row `index` (see
+/// [`CONFIG_ERROR_ROWS`]), or `None` past the last.
+#[must_use]
+pub fn config_error_rect(size: Size, index: usize) -> Option<Rectangle> {
+    CONFIG_ERROR_ROWS.get(index)?;
+    grid_rect(size, ROWS.len().saturating_add(index))
+}
+
+/// Pattern shown on `step`, or `None` on the results page.
+#[must_use]
+pub fn step_pattern(step: DiagnosticsStep) -> Option<TestPattern> {
+    match step {
+        DiagnosticsStep::Checkerboard => Some(TestPattern::Checkerboard),
+        DiagnosticsStep::Gradient => Some(TestPattern::Gradient),
+        DiagnosticsStep::Border => Some(TestPattern::Border),
+        DiagnosticsStep::Results => None,
+    }
+}
+
+/// Row text and verdict (`None` when the check has not run).
+fn row_text(report: &DiagnosticsReport, row: usize, text: &mut TextBuf<40>) -> Option<bool> {
+    let mode = match row {
+        0 => Some(RefreshMode::Full),
+        1 => Some(RefreshMode::Partial),
+        2 => Some(RefreshMode::Fast),
+        _ => None,
+    };
+    if let Some(mode) = mode {
+        let label = match mode {
+            RefreshMode::Full => "Full",
+            RefreshMode::Partial => "Partial",
+            RefreshMode::Fast => "Fast",
+        };
+        let _ = write!(text, "{label}");
+        let t = report.timings.iter().find(|t| t.mode == mode)?;
+        let _ = write!(text, " {}/{} ms", t.measured_ms, t.spec_ms);
+        return Some(t.within_spec());
+    }
+    match row {
+        3 => {
+            let _ = write!(text, "Ghosting");
+            let g = report.ghosting?;
+            let _ = write!(text, " {}.{}%", g.permille / 10, g.permille % 10);
+            Some(g.within_limit())
+        }
+        4 => {
+            let _ = write!(text, "SD card");
+            let sd = report.sd?;
+            let _ = match sd {
+                SdHealth::Ok { read_ms, .. } => write!(text, " {read_ms} ms"),
+                SdHealth::Missing => write!(text, " no probe file"),
+                SdHealth::Failed => write!(text, " no response"),
+            };
+            Some(sd.is_ok())
+        }
+        5 => {
+            let _ = write!(text, "Battery");
+            let b = report.battery?;
+            let _ = write!(text, " {} mV", b.voltage_mv);
+            if let Some(pct) = b.percent {
+                let _ = write!(text, " {pct}%");
+            }
+            if b.charging {
+                let _ = write!(text, " chg");
+            }
+            Some(b.is_ok())
+        }
+        6 => {
+            let _ = write!(text, "Audio loop");
+            let a = report.audio?;
+            match LoopbackFault::ALL.into_iter().find(|&f| a.has(f)) {
+                Some(fault) => {
+                    let _ = write!(text, " {}", fault.name());
+                }
+                None => {
+                    let _ = write!(text, " {}/{}", a.left.left, a.right.right);
+                }
+            }
+            Some(a.passed())
+        }
+        7 => {
+            let _ = write!(text, "Config");
+            let c = report.config.as_ref()?;
+            let _ = match c.error_count() {
+                0 => write!(text, " soul.toml"),
+                1 => write!(text, " 1 problem"),
+                n => write!(text, " {n} problems"),
+            };
+            Some(c.error_count() == 0)
+        }
+        8 => {
+            let _ = write!(text, "Library");
+            let l = report.library?;
+            if let Some(section) = l.corrupt_sections().next() {
+                let _ = write!(text, " {}", section.file_name());
+            } else if l.bad_tracks > 0 {
+                let _ = write!(text, " {} bad tracks", l.bad_tracks);
+            } else {
+                let _ = write!(text, " {} sections", LibrarySection::ALL.len());
+            }
+            Some(l.is_intact())
+        }
+        9 => {
+            let _ = write!(text, "Boot");
+            let b = report.boot?;
+            let _ = write!(text, " {} ms", b.total_ms);
+            if let Some(slow) = b.over_budget().next() {
+                let _ = write!(text, " {}", slow.phase.name());
+            }
+            Some(b.within_budget())
+        }
+        10 => {
+            let _ = write!(text, "Dropouts");
+            let d = report.dropouts?;
+            let _ = write!(text, " {}", d.total());
+            // Name the likeliest cause: the one most dropouts coincided with.
+            let cause = [
+                ("refresh", d.during_refresh),
+                ("sd", d.slow_sd),
+                ("cpu", d.high_cpu),
+            ]
+            .into_iter()
+            .filter(|&(_, n)| n > 0)
+            .max_by_key(|&(_, n)| n);
+            if let Some((name, _)) = cause {
+                let _ = write!(text, " {name}");
+            }
+            Some(d.is_clean())
+        }
+        11 => {
+            let _ = write!(text, "Failed boots");
+            let g = report.boot_guard?;
+            let _ = write!(text, " {}", g.failures);
+            if !g.is_clean() {
+                let _ = write!(text, " {}", g.reset.name());
+            }
+            Some(g.mode == BootMode::Normal)
+        }
+        _ => {
+            let _ = write!(text, "Result");
+            Some(report.passed())
+        }
+    }
+}
+
+/// Render the diagnostics screen onto any `DrawTarget<Color = Gray4>`.
+///
+/// The `register` closure works as in
+/// [`render_now_playing_to`](super::now_playing::render_now_playing_to).
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn render_diagnostics_to<D, R>(
+    display: &mut D,
+    diagnostics: &Diagnostics,
+    report: &DiagnosticsReport,
+    register: R,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    let size = display.bounding_box().size;
+    let mut register = on_screen(size, register);
+
+    if let Some(pattern) = step_pattern(diagnostics.step()) {
+        pattern.draw(display)?;
+        register("diag-pattern", "Image", (0, 0), (size.width, size.height));
+        return Ok(());
+    }
+
+    Rectangle::new(Point::zero(), size)
+        .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+        .draw(display)?;
+
+    // ── Header bar ────────────────────────────────────────────────────────
+    Rectangle::new(Point::zero(), Size::new(size.width, HEADER_H))
+        .into_styled(PrimitiveStyle::with_fill(Gray4::new(0x2)))
+        .draw(display)?;
+    let header_style = MonoTextStyle::new(&FONT_10X20, Gray4::WHITE);
+    let safe_mode = report.boot_guard.is_some_and(|g| g.mode == BootMode::Safe);
+    let title = if safe_mode {
+        "Safe mode"
+    } else {
+        "Diagnostics"
+    };
+    Text::new(title, Point::new(TEXT_X, 32), header_style).draw(display)?;
+
+    // ── Result rows ───────────────────────────────────────────────────────
+    let style = MonoTextStyle::new(&FONT_10X20, Gray4::BLACK);
+    let right = i32::try_from(size.width).unwrap_or(0).saturating_sub(20);
+    for (row, id) in ROWS.iter().enumerate() {
+        let Some(rect) = row_rect(size, row) else {
+            break;
+        };
+        let baseline = rect.top_left.y.saturating_add(BASELINE);
+        let mut text = TextBuf::<40>::new();
+        let verdict = match row_text(report, row, &mut text) {
+            Some(true) if row == ROWS.len().saturating_sub(1) => "PASS",
+            Some(true) => "ok",
+            Some(false) => "FAIL",
+            None => "-",
+        };
+        Text::new(text.as_str(), Point::new(TEXT_X, baseline), style).draw(display)?;
+        Text::with_alignment(
+            verdict,
+            Point::new(right, baseline),
+            style,
+            Alignment::Right,
+        )
+        .draw(display)?;
+        register(
+            id,
+            "Label",
+            (rect.top_left.x, rect.top_left.y),
+            (rect.size.width, rect.size.height),
+        );
+    }
+
+    // ── soul.toml problems ────────────────────────────────────────────────
+    let errors = report.config.iter().flat_map(|c| c.errors.iter());
+    for (index, (error, id)) in errors.zip(CONFIG_ERROR_ROWS).enumerate() {
+        let Some(rect) = config_error_rect(size, index) else {
+            break;
+        };
+        let baseline = rect.top_left.y.saturating_add(BASELINE);
+        // A message that does not fit stops at the last piece that did.
+        let mut text = TextBuf::<ERROR_TEXT_LEN>::new();
+        let _ = write!(text, "{error}");
+        Text::new(text.as_str(), Point::new(TEXT_X, baseline), style).draw(display)?;
+        register(
+            id,
+            "Label",
+            (rect.top_left.x, rect.top_left.y),
+            (rect.size.width, rect.size.height),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,206 @@
+//! Library scan screen renderer — progress of the initial card scan
+//!
+//! Shows the running totals from `library::ScanProgress` as three rows
+//! (folders, files, tracks) with the count right-aligned, then a status
+//! line.  While scanning, a "Back: Cancel" button is drawn at the bottom;
+//! it disappears once the cancel is requested.  Rows are [`ROW_H`] pixels
+//! from [`LIST_TOP`], both on the 8-pixel partial window grid, so a count
+//! update only refreshes its own row.
+//!
+//! # Registered test IDs
+//!
+//! | test ID          | Component type |
+//! |------------------|----------------|
+//! | `"scan-folders"` | `"Label"`      |
+//! | `"scan-files"`   | `"Label"`      |
+//! | `"scan-tracks"`  | `"Label"`      |
+//! | `"scan-status"`  | `"Label"`      |
+//! | `"scan-cancel"`  | `"Button"`     |
+
+use core::fmt::Write as _;
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Text},
+};
+use library::ScanProgress;
+use ui::library_scan::{LibraryScan, ScanPhase};
+
+use super::{on_screen, TextBuf};
+
+/// Height of the title bar.
+const HEADER_H: u32 = 48;
+/// Top of the first count row.
+pub const LIST_TOP: u32 = 64;
+/// Height of one count row.
+pub const ROW_H: u32 = 40;
+/// Top of the status line, one row gap below the counts.
+pub const STATUS_TOP: u32 = 224;
+/// Height of the cancel button.
+pub const BUTTON_H: u32 = 48;
+/// Gap between the cancel button and the bottom edge.
+const BUTTON_MARGIN: u32 = 16;
+/// Left text inset.
+const TEXT_X: i32 = 28;
+/// Baseline offset of FONT_10X20 within a row.
+const BASELINE: i32 = 26;
+
+/// Row labels, in display order.
+const ROWS: [(&str, &str); 3] = [
+    ("scan-folders", "Folders"),
+    ("scan-files", "Files"),
+    ("scan-tracks", "Tracks"),
+];
+
+/// Screen rectangle of count row `row` (0 = folders), or `None` past the
+/// last row.
+#[must_use]
+pub fn count_rect(size: Size, row: usize) -> Option<Rectangle> {
+    ROWS.get(row)?;
+    let row = u32::try_from(row).ok()?;
+    let y = LIST_TOP.saturating_add(row.saturating_mul(ROW_H));
+    Some(Rectangle::new(
+        Point::new(0, i32::try_from(y).ok()?),
+        Size::new(size.width, ROW_H),
+    ))
+}
+
+/// Screen rectangle of the status line.
+#[must_use]
+pub fn status_rect(size: Size) -> Rectangle {
+    Rectangle::new(
+        Point::new(0, i32::try_from(STATUS_TOP).unwrap_or(0)),
+        Size::new(size.width, ROW_H),
+    )
+}
+
+/// Screen rectangle of the cancel button: full width less a 16 px margin,
+/// [`BUTTON_H`] tall, 16 px above the bottom edge.
+#[must_use]
+pub fn cancel_rect(size: Size) -> Rectangle {
+    let top = size
+        .height
+        .saturating_sub(BUTTON_H)
+        .saturating_sub(BUTTON_MARGIN);
+    let margin = i32::try_from(BUTTON_MARGIN).unwrap_or(0);
+    Rectangle::new(
+        Point::new(margin, i32::try_from(top).unwrap_or(0)),
+        Size::new(
+            size.width.saturating_sub(BUTTON_MARGIN.saturating_mul(2)),
+            BUTTON_H,
+        ),
+    )
+}
+
+/// Render the library scan screen onto any `DrawTarget<Color = Gray4>`.
+///
+/// The `register` closure works as in
+/// [`render_now_playing_to`](super::now_playing::render_now_playing_to).
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn render_library_scan_to<D, R>(
+    display: &mut D,
+    progress: &ScanProgress,
+    scan: &LibraryScan,
+    register: R,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    let size = display.bounding_box().size;
+    let mut register = on_screen(size, register);
+
+    Rectangle::new(Point::zero(), size)
+        .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+        .draw(display)?;
+
+    // ── Header bar ────────────────────────────────────────────────────────
+    Rectangle::new(Point::zero(), Size::new(size.width, HEADER_H))
+        .into_styled(PrimitiveStyle::with_fill(Gray4::new(0x2)))
+        .draw(display)?;
+    let header_style = MonoTextStyle::new(&FONT_10X20, Gray4::WHITE);
+    Text::new("Scanning library", Point::new(TEXT_X, 32), header_style).draw(display)?;
+
+    // ── Counts ────────────────────────────────────────────────────────────
+    let style = MonoTextStyle::new(&FONT_10X20, Gray4::BLACK);
+    let right = i32::try_from(size.width).unwrap_or(0).saturating_sub(20);
+    let counts = [
+        progress.dirs_visited,
+        progress.files_parsed,
+        progress.tracks_added,
+    ];
+    for (row, ((id, label), count)) in ROWS.iter().zip(counts).enumerate() {
+        let Some(rect) = count_rect(size, row) else {
+            break;
+        };
+        let baseline = rect.top_left.y.saturating_add(BASELINE);
+        Text::new(label, Point::new(TEXT_X, baseline), style).draw(display)?;
+        let mut value = TextBuf::<12>::new();
+        let _ = write!(value, "{count}");
+        Text::with_alignment(
+            value.as_str(),
+            Point::new(right, baseline),
+            style,
+            Alignment::Right,
+        )
+        .draw(display)?;
+        register(
+            id,
+            "Label",
+            (rect.top_left.x, rect.top_left.y),
+            (rect.size.width, rect.size.height),
+        );
+    }
+
+    // ── Status line ───────────────────────────────────────────────────────
+    let rect = status_rect(size);
+    let mut status = TextBuf::<40>::new();
+    let _ = match scan.phase() {
+        ScanPhase::Scanning => write!(status, "Scanning..."),
+        ScanPhase::Cancelling => write!(status, "Stopping..."),
+        ScanPhase::Done { cancelled: false } => write!(status, "Done"),
+        ScanPhase::Done { cancelled: true } => {
+            write!(status, "Stopped - {} tracks ready", progress.tracks_added)
+        }
+    };
+    Text::new(
+        status.as_str(),
+        Point::new(TEXT_X, rect.top_left.y.saturating_add(BASELINE)),
+        style,
+    )
+    .draw(display)?;
+    register(
+        "scan-status",
+        "Label",
+        (rect.top_left.x, rect.top_left.y),
+        (rect.size.width, rect.size.height),
+    );
+
+    // ── Cancel button ─────────────────────────────────────────────────────
+    if scan.phase() == ScanPhase::Scanning {
+        let rect = cancel_rect(size);
+        rect.into_styled(PrimitiveStyle::with_stroke(Gray4::BLACK, 2))
+            .draw(display)?;
+        let center = rect.center();
+        Text::with_alignment(
+            "Back: Cancel",
+            Point::new(center.x, center.y.saturating_add(6)),
+            style,
+            Alignment::Center,
+        )
+        .draw(display)?;
+        register(
+            "scan-cancel",
+            "Button",
+            (rect.top_left.x, rect.top_left.y),
+            (rect.size.width, rect.size.height),
+        );
+    }
+    Ok(())
+}
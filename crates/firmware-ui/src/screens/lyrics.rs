@@ -0,0 +1,234 @@
+//! Lyrics screen renderer
+//!
+//! Shows a page of timed lyrics with the line being sung highlighted.  The
+//! screen is drawn in full once with [`render_lyrics_to`]; as playback moves
+//! on, [`redraw_lyrics`] repaints only the rows a [`LyricsView::follow`]
+//! step changed and returns the area to refresh — two [`LINE_H`]-pixel rows
+//! for a highlight move, the lyrics area for a page turn.  Rows sit on the
+//! 8-pixel partial-window grid so those refreshes cover exactly the rows.
+//!
+//! # Registered test IDs
+//!
+//! | test ID            | Component type |
+//! |--------------------|----------------|
+//! | `"lyrics-area"`    | `"List"`       |
+//! | `"lyrics-current"` | `"Label"`      |
+//! | `"lyrics-empty"`   | `"Label"`      |
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Text},
+};
+use library::lyrics::Lyrics;
+use ui::lyrics::{LyricsRedraw, LyricsView};
+
+use super::on_screen;
+
+/// Height of the title bar.
+const HEADER_H: u32 = 48;
+/// Top of the first lyric row.
+pub const LYRICS_TOP: u32 = 56;
+/// Height of one lyric row.
+pub const LINE_H: u32 = 32;
+/// Left/right text inset.
+const TEXT_X: i32 = 20;
+/// Baseline offset of FONT_10X20 within a row.
+const BASELINE: i32 = 22;
+/// FONT_10X20 advance.
+const CHAR_W: u32 = 10;
+
+/// Rows of lyrics that fit on a screen of `size` (at least one).
+#[must_use]
+pub fn lyrics_rows(size: Size) -> usize {
+    let rows = size.height.saturating_sub(LYRICS_TOP).checked_div(LINE_H);
+    usize::try_from(rows.unwrap_or(0)).unwrap_or(0).max(1)
+}
+
+/// The area holding all lyric rows.
+#[must_use]
+pub fn lyrics_area(size: Size) -> Rectangle {
+    let rows = u32::try_from(lyrics_rows(size)).unwrap_or(1);
+    Rectangle::new(
+        Point::new(0, i32::try_from(LYRICS_TOP).unwrap_or(0)),
+        Size::new(size.width, rows.saturating_mul(LINE_H)),
+    )
+}
+
+/// Screen rectangle of lyric `line`, or `None` when it is not on the page.
+#[must_use]
+pub fn row_rect(size: Size, view: &LyricsView, line: usize) -> Option<Rectangle> {
+    if !view.is_visible(line) {
+        return None;
+    }
+    let row = u32::try_from(line.saturating_sub(view.top())).ok()?;
+    let y = LYRICS_TOP.saturating_add(row.saturating_mul(LINE_H));
+    Some(Rectangle::new(
+        Point::new(0, i32::try_from(y).ok()?),
+        Size::new(size.width, LINE_H),
+    ))
+}
+
+/// Render the lyrics screen onto any `DrawTarget<Color = Gray4>`.
+///
+/// `lyrics` is `None` when the track has no (synchronised) lyrics file.
+/// The `register` closure works as in
+/// [`render_now_playing_to`](super::now_playing::render_now_playing_to).
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn render_lyrics_to<D, R, const N: usize>(
+    display: &mut D,
+    lyrics: Option<&Lyrics<N>>,
+    view: &LyricsView,
+    register: R,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    let size = display.bounding_box().size;
+    let mut register = on_screen(size, register);
+
+    Rectangle::new(Point::zero(), size)
+        .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+        .draw(display)?;
+
+    // ── Header bar ────────────────────────────────────────────────────────
+    Rectangle::new(Point::zero(), Size::new(size.width, HEADER_H))
+        .into_styled(PrimitiveStyle::with_fill(Gray4::new(0x2)))
+        .draw(display)?;
+    let header_style = MonoTextStyle::new(&FONT_10X20, Gray4::WHITE);
+    Text::new("Lyrics", Point::new(TEXT_X, 32), header_style).draw(display)?;
+
+    let Some(lyrics) = lyrics.filter(|l| !l.is_empty()) else {
+        // SAFETY: display dimensions (800×480) are far below i32::MAX.
+        #[allow(clippy::cast_possible_wrap)]
+        let centre = Point::new((size.width / 2) as i32, (size.height / 2) as i32);
+        let style = MonoTextStyle::new(&FONT_10X20, Gray4::new(0x6));
+        Text::with_alignment("No lyrics", centre, style, Alignment::Center).draw(display)?;
+        register(
+            "lyrics-empty",
+            "Label",
+            (centre.x.saturating_sub(45), centre.y.saturating_sub(16)),
+            (90, 20),
+        );
+        return Ok(());
+    };
+
+    let area = lyrics_area(size);
+    for row in 0..view.rows() {
+        draw_row(display, lyrics, view, view.top().saturating_add(row))?;
+    }
+    register(
+        "lyrics-area",
+        "List",
+        (area.top_left.x, area.top_left.y),
+        (area.size.width, area.size.height),
+    );
+    if let Some(rect) = view.current().and_then(|l| row_rect(size, view, l)) {
+        register(
+            "lyrics-current",
+            "Label",
+            (rect.top_left.x, rect.top_left.y),
+            (rect.size.width, rect.size.height),
+        );
+    }
+    Ok(())
+}
+
+/// Repaint what `redraw` changed and return the area to refresh, or `None`
+/// when nothing visible changed.
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn redraw_lyrics<D, const N: usize>(
+    display: &mut D,
+    lyrics: &Lyrics<N>,
+    view: &LyricsView,
+    redraw: LyricsRedraw,
+) -> Result<Option<Rectangle>, D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    let size = display.bounding_box().size;
+    match redraw {
+        LyricsRedraw::None => Ok(None),
+        LyricsRedraw::Lines { previous, current } => {
+            let mut dirty: Option<Rectangle> = None;
+            for line in [previous, current].into_iter().flatten() {
+                let Some(rect) = row_rect(size, view, line) else {
+                    continue;
+                };
+                draw_row(display, lyrics, view, line)?;
+                dirty = Some(dirty.map_or(rect, |d| union(d, rect)));
+            }
+            Ok(dirty)
+        }
+        LyricsRedraw::Page => {
+            for row in 0..view.rows() {
+                draw_row(display, lyrics, view, view.top().saturating_add(row))?;
+            }
+            Ok(Some(lyrics_area(size)))
+        }
+    }
+}
+
+/// Draw lyric `line` in its row: dark bar with white text when it is the
+/// current line, grey text otherwise, blank past the last line.
+fn draw_row<D, const N: usize>(
+    display: &mut D,
+    lyrics: &Lyrics<N>,
+    view: &LyricsView,
+    line: usize,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    let size = display.bounding_box().size;
+    let Some(rect) = row_rect(size, view, line) else {
+        return Ok(());
+    };
+    let highlighted = view.current() == Some(line);
+    let (bg, fg) = if highlighted {
+        (Gray4::new(0x2), Gray4::WHITE)
+    } else {
+        (Gray4::WHITE, Gray4::new(0x6))
+    };
+    rect.into_styled(PrimitiveStyle::with_fill(bg))
+        .draw(display)?;
+
+    let Some(text) = lyrics.get(line).map(|l| l.text.as_str()) else {
+        return Ok(());
+    };
+    let max_chars = usize::try_from(size.width.saturating_sub(40) / CHAR_W).unwrap_or(0);
+    let end = text
+        .char_indices()
+        .nth(max_chars)
+        .map_or(text.len(), |(i, _)| i);
+    let style = MonoTextStyle::new(&FONT_10X20, fg);
+    Text::new(
+        text.get(..end).unwrap_or(text),
+        Point::new(TEXT_X, rect.top_left.y.saturating_add(BASELINE)),
+        style,
+    )
+    .draw(display)?;
+    Ok(())
+}
+
+fn union(a: Rectangle, b: Rectangle) -> Rectangle {
+    let top = a.top_left.y.min(b.top_left.y);
+    let bottom = a
+        .bottom_right()
+        .map_or(top, |p| p.y)
+        .max(b.bottom_right().map_or(top, |p| p.y));
+    let height = u32::try_from(bottom.saturating_sub(top)).unwrap_or(0);
+    Rectangle::new(
+        Point::new(a.top_left.x.min(b.top_left.x), top),
+        Size::new(a.size.width.max(b.size.width), height.saturating_add(1)),
+    )
+}
@@ -1,3 +1,81 @@
 //! Screen renderers for the DAP UI.
+//!
+//! Every renderer lays itself out from the display's size, so the same
+//! screen runs on the 800×480 main panel and on the smaller panels the
+//! emulator offers (see [`eink_system::breakpoint`]).  Components that do
+//! not fit on a small panel — list rows past the bottom edge — are clipped
+//! by the display and not registered ([`on_screen`]), so tests only find
+//! what is visible.
 
+pub mod audio_settings;
+pub mod chapters;
+pub mod diagnostics;
+pub mod library_scan;
+pub mod lyrics;
 pub mod now_playing;
+pub mod queue;
+pub mod quick_menu;
+pub mod tag_editor;
+pub mod text_input;
+
+use embedded_graphics::prelude::Size;
+
+/// Wrap a renderer's `register` closure so it only sees components that
+/// lie entirely on a screen of `size`.
+pub(crate) fn on_screen<R>(
+    size: Size,
+    mut register: R,
+) -> impl FnMut(&str, &str, (i32, i32), (u32, u32))
+where
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    move |id: &str, ty: &str, pos: (i32, i32), dims: (u32, u32)| {
+        if fits(size, pos, dims) {
+            register(id, ty, pos, dims);
+        }
+    }
+}
+
+/// Whether the `dims` rectangle at `pos` lies entirely on a screen of `size`.
+fn fits(size: Size, pos: (i32, i32), dims: (u32, u32)) -> bool {
+    let (Ok(x), Ok(y)) = (u32::try_from(pos.0), u32::try_from(pos.1)) else {
+        return false;
+    };
+    x.checked_add(dims.0)
+        .is_some_and(|right| right <= size.width)
+        && y.checked_add(dims.1)
+            .is_some_and(|bottom| bottom <= size.height)
+}
+
+/// Fixed-capacity text buffer for formatting labels without allocation;
+/// output past `N` bytes is dropped.
+pub(crate) struct TextBuf<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> TextBuf<N> {
+    pub(crate) fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        self.bytes
+            .get(..self.len)
+            .and_then(|b| core::str::from_utf8(b).ok())
+            .unwrap_or("")
+    }
+}
+
+impl<const N: usize> core::fmt::Write for TextBuf<N> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len.saturating_add(s.len());
+        let dst = self.bytes.get_mut(self.len..end).ok_or(core::fmt::Error)?;
+        dst.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
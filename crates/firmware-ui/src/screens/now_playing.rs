@@ -2,6 +2,42 @@
 //!
 //! Renders the main playback screen using embedded-graphics primitives.
 //!
+//! # Album art
+//!
+//! On screens tall enough for it (the 480×800 portrait layout), a
+//! [`ART_SIZE`]² region between the progress bar and the play button shows
+//! the album's pre-dithered 2bpp thumbnail — the format the art cache holds
+//! (`library::ArtCache`).  Missing or malformed art draws a placeholder.
+//!
+//! Art needs GC16's 16 grey levels, but the controls change every second
+//! and only need DU4.  [`NowPlayingRefreshPlanner`] decides, per redraw,
+//! whether the art region gets its own GC16 window (on album change) and
+//! which mode the rest of the screen uses, through the shared
+//! [`RefreshPolicy`].
+//!
+//! # Skeleton
+//!
+//! A track resumed at boot plays before the library index has loaded, so
+//! its title and artist are unknown.  While [`NowPlayingState::loading`]
+//! is set, light grey bars stand in for the two labels instead of the
+//! "Unknown" fallbacks; the labels register as usual, and the next redraw
+//! with metadata replaces the bars in the same regions.
+//!
+//! # Panel sizes
+//!
+//! The layout follows the display's [`Breakpoint`].  Medium and expanded
+//! panels (4.2" and up) use the standard or accessible layout.  Compact
+//! panels (the 2.13" to 2.9" strips in the emulator) have no header bar
+//! and no artist line: just the title, a thinner progress bar and a
+//! smaller play button, with the button label at scale 1 in either theme.
+//!
+//! # Accessibility mode
+//!
+//! With [`Theme::ACCESSIBLE`] the header, title and button label are drawn
+//! at twice the size, the artist in FONT_10X20 instead of FONT_6X10, and
+//! the dark greys become black.  The progress bar keeps its position, so
+//! the album art sits where it does in the standard layout.
+//!
 //! # Registered test IDs
 //!
 //! | test ID                   | Component type |
@@ -9,17 +45,248 @@
 //! | `"now-playing-title"`     | `"Label"`      |
 //! | `"now-playing-artist"`    | `"Label"`      |
 //! | `"now-playing-progress"`  | `"ProgressBar"`|
+//! | `"now-playing-art"`       | `"Image"`      |
 //! | `"now-playing-play-btn"`  | `"Button"`     |
+//!
+//! The artist is not registered on compact panels, nor the art where it
+//! does not fit.
 
+use eink_system::breakpoint::{visible_from, Breakpoint, Responsive};
 use embedded_graphics::{
-    mono_font::{ascii::FONT_10X20, ascii::FONT_6X10, MonoTextStyle},
+    geometry::AnchorPoint,
+    mono_font::MonoTextStyle,
     pixelcolor::Gray4,
     prelude::*,
-    primitives::{PrimitiveStyle, Rectangle},
-    text::Text,
+    primitives::{Line, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, StrokeAlignment},
+    text::{Alignment, Text},
+};
+use platform::refresh_policy::{
+    ContentHint, PanelState, RefreshChoice, RefreshPolicy, RefreshReason, Update,
 };
+use platform::RefreshMode;
 use ui::now_playing::NowPlayingState;
 
+use super::on_screen;
+use crate::theme::{draw_text, Theme, ThemeMode};
+
+/// Album art edge length in pixels.
+pub const ART_SIZE: u32 = 240;
+
+/// Bytes in one album art thumbnail: [`ART_SIZE`]² pixels at 2bpp.
+pub const ART_BYTES: usize = (ART_SIZE as usize * ART_SIZE as usize) / 4;
+
+/// Top of the progress bar on medium and expanded panels.
+const PROGRESS_Y: i32 = 150;
+/// Progress bar height.
+const PROGRESS_H: u32 = 12;
+/// Distance from the bottom edge to the top of the play button on medium
+/// and expanded panels.
+const BUTTON_BOTTOM_OFFSET: u32 = 80;
+/// Play button size.
+const BUTTON_W: u32 = 100;
+const BUTTON_H: u32 = 40;
+/// Registered heights of the title and artist labels.
+const TITLE_H: u32 = 24;
+const ARTIST_H: u32 = 14;
+/// Minimum gap between the art and the progress bar / play button.
+const ART_MARGIN: u32 = 20;
+/// Partial-window grid the art region is aligned to, so a windowed GC16
+/// covers exactly the art on the SSD1677.
+const WINDOW_ALIGN: u32 = 8;
+/// Left inset of the labels and the progress bar.
+const INSET: i32 = 20;
+/// Fill of the placeholder bars drawn while metadata loads.
+const SKELETON: Gray4 = Gray4::new(0xC);
+/// The artist line is secondary information, dropped on compact panels.
+const SHOW_ARTIST: Responsive<bool> = visible_from(Breakpoint::Medium);
+
+/// Now Playing geometry for one [`ThemeMode`] and [`Breakpoint`].
+struct Layout {
+    /// Header bar height; `0` draws no header.
+    header_h: u32,
+    /// Title baseline, and the top and height of its registered label.
+    title_y: i32,
+    title_top: i32,
+    title_h: u32,
+    /// Artist baseline, and the top and height of its registered label.
+    artist_y: i32,
+    artist_top: i32,
+    artist_h: u32,
+    /// Progress bar top and height.
+    progress_y: i32,
+    progress_h: u32,
+    /// Distance from the bottom edge to the top of the play button.
+    button_bottom: u32,
+    /// Play button size, label baseline-left offset inside it and label
+    /// text scale.
+    button: Size,
+    label: Point,
+    label_scale: u32,
+}
+
+const STANDARD_LAYOUT: Layout = Layout {
+    header_h: 50,
+    title_y: 80,
+    title_top: 60,
+    title_h: TITLE_H,
+    artist_y: 110,
+    artist_top: 100,
+    artist_h: ARTIST_H,
+    progress_y: PROGRESS_Y,
+    progress_h: PROGRESS_H,
+    button_bottom: BUTTON_BOTTOM_OFFSET,
+    button: Size::new(BUTTON_W, BUTTON_H),
+    label: Point::new(10, 26),
+    label_scale: 1,
+};
+
+/// Twice-size title, full-size artist, taller progress bar and button.
+/// The button still starts [`BUTTON_BOTTOM_OFFSET`] from the bottom.
+const ACCESSIBLE_LAYOUT: Layout = Layout {
+    header_h: 64,
+    title_y: 102,
+    title_top: 68,
+    title_h: 44,
+    artist_y: 136,
+    artist_top: 118,
+    artist_h: 24,
+    progress_y: PROGRESS_Y,
+    progress_h: 16,
+    button_bottom: BUTTON_BOTTOM_OFFSET,
+    button: Size::new(160, 72),
+    label: Point::new(16, 46),
+    label_scale: 2,
+};
+
+/// Compact panels: title, progress bar and a 32 px button from 104 px
+/// high.  The artist fields are unused ([`SHOW_ARTIST`]).
+const COMPACT_LAYOUT: Layout = Layout {
+    header_h: 0,
+    title_y: 24,
+    title_top: 4,
+    title_h: TITLE_H,
+    artist_y: 0,
+    artist_top: 0,
+    artist_h: 0,
+    progress_y: 36,
+    progress_h: 8,
+    button_bottom: 40,
+    button: Size::new(BUTTON_W, 32),
+    label: Point::new(10, 22),
+    label_scale: 1,
+};
+
+/// [`COMPACT_LAYOUT`] with the twice-size title; the button stays small
+/// enough to fit under it.
+const COMPACT_ACCESSIBLE_LAYOUT: Layout = Layout {
+    title_y: 38,
+    title_h: 44,
+    progress_y: 52,
+    progress_h: 10,
+    ..COMPACT_LAYOUT
+};
+
+const STANDARD_LAYOUTS: Responsive<&Layout> =
+    Responsive::new(&COMPACT_LAYOUT).medium(&STANDARD_LAYOUT);
+const ACCESSIBLE_LAYOUTS: Responsive<&Layout> =
+    Responsive::new(&COMPACT_ACCESSIBLE_LAYOUT).medium(&ACCESSIBLE_LAYOUT);
+
+fn layout(theme: &Theme, breakpoint: Breakpoint) -> &'static Layout {
+    match theme.mode {
+        ThemeMode::Standard => STANDARD_LAYOUTS.at(breakpoint),
+        ThemeMode::Accessible => ACCESSIBLE_LAYOUTS.at(breakpoint),
+    }
+}
+
+/// Where the album art goes on a screen of `size`, or `None` when it does
+/// not fit between the progress bar and the play button.
+#[must_use]
+pub fn art_region(size: Size) -> Option<Rectangle> {
+    let top = u32::try_from(PROGRESS_Y)
+        .ok()?
+        .saturating_add(PROGRESS_H)
+        .saturating_add(ART_MARGIN);
+    let bottom = size
+        .height
+        .checked_sub(BUTTON_BOTTOM_OFFSET)?
+        .checked_sub(ART_MARGIN)?;
+    let slack_y = bottom.checked_sub(top)?.checked_sub(ART_SIZE)?;
+    let slack_x = size.width.checked_sub(ART_SIZE)?;
+    // Rounding down moves the art up by less than ART_MARGIN.
+    let x = align_down(slack_x / 2);
+    let y = align_down(top.saturating_add(slack_y / 2));
+    Some(Rectangle::new(
+        Point::new(i32::try_from(x).ok()?, i32::try_from(y).ok()?),
+        Size::new(ART_SIZE, ART_SIZE),
+    ))
+}
+
+fn align_down(v: u32) -> u32 {
+    v.checked_rem(WINDOW_ALIGN)
+        .map_or(v, |rem| v.saturating_sub(rem))
+}
+
+/// Draw a 2bpp album art thumbnail at `area.top_left`.
+///
+/// `art` is [`ART_BYTES`] long, row-major, four pixels per byte with the
+/// leftmost pixel in the top bits; values 0–3 map to the panel's four grey
+/// levels (0 = black, 3 = white).  `None`, or data of any other length,
+/// draws a placeholder over `area` instead.
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn draw_album_art<D>(
+    display: &mut D,
+    area: Rectangle,
+    art: Option<&[u8]>,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    match art.filter(|a| a.len() == ART_BYTES) {
+        Some(art) => {
+            let pixels = art.iter().flat_map(|&byte| {
+                [6u32, 4, 2, 0].map(|s| Gray4::new(byte.checked_shr(s).unwrap_or(0) & 0x3))
+            });
+            display.fill_contiguous(
+                &Rectangle::new(area.top_left, Size::new(ART_SIZE, ART_SIZE)),
+                pixels,
+            )
+        }
+        None => {
+            area.into_styled(PrimitiveStyle::with_fill(Gray4::new(0xC)))
+                .draw(display)?;
+            let cross = PrimitiveStyle::with_stroke(Gray4::new(0x6), 1);
+            Line::new(area.top_left, area.bottom_right().unwrap_or(area.top_left))
+                .into_styled(cross)
+                .draw(display)?;
+            Line::new(
+                area.anchor_point(AnchorPoint::TopRight),
+                area.anchor_point(AnchorPoint::BottomLeft),
+            )
+            .into_styled(cross)
+            .draw(display)?;
+            area.into_styled(PrimitiveStyle::with_stroke(Gray4::BLACK, 1))
+                .draw(display)
+        }
+    }
+}
+
+/// Placeholder for a label whose text is not known yet: a light grey bar a
+/// few pixels shorter than the label.
+fn draw_skeleton_bar<D>(display: &mut D, top_left: Point, size: Size) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    Rectangle::new(
+        Point::new(top_left.x, top_left.y.saturating_add(2)),
+        Size::new(size.width, size.height.saturating_sub(4)),
+    )
+    .into_styled(PrimitiveStyle::with_fill(SKELETON))
+    .draw(display)
+}
+
 /// Render the Now Playing screen onto any `DrawTarget<Color = Gray4>`.
 ///
 /// The `register` closure is invoked for each named logical component so that
@@ -28,7 +295,10 @@ use ui::now_playing::NowPlayingState;
 /// # Arguments
 ///
 /// * `display`  – Any `DrawTarget<Color = Gray4>` (e-ink hardware, emulator, or test emulator).
+/// * `theme`    – Standard or accessibility layout (see [`crate::theme`]).
 /// * `state`    – Current now-playing state (track, position, volume, playing flag).
+/// * `art`      – 2bpp art for `state.album_id` from the art cache (see
+///   [`draw_album_art`]); `None` draws the placeholder.
 /// * `register` – Called with `(test_id, component_type, (x, y), (w, h))` for each component.
 ///
 /// # Errors
@@ -36,14 +306,19 @@ use ui::now_playing::NowPlayingState;
 /// Returns `Err(D::Error)` if any draw call fails.
 pub fn render_now_playing_to<D, R>(
     display: &mut D,
+    theme: &Theme,
     state: &NowPlayingState,
-    mut register: R,
+    art: Option<&[u8]>,
+    register: R,
 ) -> Result<(), D::Error>
 where
     D: DrawTarget<Color = Gray4>,
     R: FnMut(&str, &str, (i32, i32), (u32, u32)),
 {
     let bounds = display.bounding_box();
+    let breakpoint = Breakpoint::of(bounds.size);
+    let layout = layout(theme, breakpoint);
+    let mut register = on_screen(bounds.size, register);
     let w = bounds.size.width;
     let h = bounds.size.height;
 
@@ -53,55 +328,97 @@ where
         .draw(display)?;
 
     // ── Header bar (dark strip at top) ────────────────────────────────────
-    let header_height = 50u32;
-    Rectangle::new(Point::zero(), Size::new(w, header_height))
-        .into_styled(PrimitiveStyle::with_fill(Gray4::new(0x2)))
-        .draw(display)?;
-
-    let header_style = MonoTextStyle::new(&FONT_10X20, Gray4::WHITE);
-    Text::new("Now Playing", Point::new(20, 32), header_style).draw(display)?;
+    if layout.header_h > 0 {
+        Rectangle::new(Point::zero(), Size::new(w, layout.header_h))
+            .into_styled(PrimitiveStyle::with_fill(theme.bar))
+            .draw(display)?;
+        let header = Point::new(INSET, theme.header_baseline);
+        draw_text(
+            display,
+            theme,
+            "Now Playing",
+            header,
+            Gray4::WHITE,
+            Alignment::Left,
+        )?;
+    }
 
     // ── Track title ───────────────────────────────────────────────────────
-    let title_y = 80i32;
-    let title_text: &str = if state.title.is_empty() {
-        "Unknown Track"
+    if state.loading {
+        draw_skeleton_bar(
+            display,
+            Point::new(INSET, layout.title_top),
+            Size::new(w / 2, layout.title_h),
+        )?;
     } else {
-        state.title.as_str()
-    };
-    let title_style = MonoTextStyle::new(&FONT_10X20, Gray4::BLACK);
-    Text::new(title_text, Point::new(20, title_y), title_style).draw(display)?;
+        let title_text: &str = if state.title.is_empty() {
+            "Unknown Track"
+        } else {
+            state.title.as_str()
+        };
+        let title = Point::new(INSET, layout.title_y);
+        draw_text(
+            display,
+            theme,
+            title_text,
+            title,
+            Gray4::BLACK,
+            Alignment::Left,
+        )?;
+    }
     register(
         "now-playing-title",
         "Label",
-        (20, title_y - 20),
-        (w.saturating_sub(40), 24),
+        (INSET, layout.title_top),
+        (w.saturating_sub(40), layout.title_h),
     );
 
     // ── Artist ────────────────────────────────────────────────────────────
-    let artist_y = 110i32;
-    let artist_text: &str = if state.artist.is_empty() {
-        "Unknown Artist"
-    } else {
-        state.artist.as_str()
-    };
-    let artist_style = MonoTextStyle::new(&FONT_6X10, Gray4::new(0x6));
-    Text::new(artist_text, Point::new(20, artist_y), artist_style).draw(display)?;
-    register(
-        "now-playing-artist",
-        "Label",
-        (20, artist_y - 10),
-        (w.saturating_sub(40), 14),
-    );
+    if SHOW_ARTIST.at(breakpoint) {
+        if state.loading {
+            draw_skeleton_bar(
+                display,
+                Point::new(INSET, layout.artist_top),
+                Size::new(w / 3, layout.artist_h),
+            )?;
+        } else {
+            let artist_text: &str = if state.artist.is_empty() {
+                "Unknown Artist"
+            } else {
+                state.artist.as_str()
+            };
+            let artist_style = MonoTextStyle::new(theme.detail_font(), theme.secondary);
+            Text::new(
+                artist_text,
+                Point::new(INSET, layout.artist_y),
+                artist_style,
+            )
+            .draw(display)?;
+        }
+        register(
+            "now-playing-artist",
+            "Label",
+            (INSET, layout.artist_top),
+            (w.saturating_sub(40), layout.artist_h),
+        );
+    }
 
     // ── Progress bar ──────────────────────────────────────────────────────
-    let bar_y = 150i32;
-    let bar_h = 12u32;
+    let bar_y = layout.progress_y;
+    let bar_h = layout.progress_h;
     let bar_w = w.saturating_sub(40);
     let progress = state.progress();
+    // Outlines stay inside their rectangles, so a thicker one does not
+    // grow the bar or the button.
+    let outline = PrimitiveStyleBuilder::new()
+        .stroke_color(Gray4::BLACK)
+        .stroke_width(theme.stroke)
+        .stroke_alignment(StrokeAlignment::Inside)
+        .build();
 
     // Background track
-    Rectangle::new(Point::new(20, bar_y), Size::new(bar_w, bar_h))
-        .into_styled(PrimitiveStyle::with_fill(Gray4::new(0xC)))
+    Rectangle::new(Point::new(INSET, bar_y), Size::new(bar_w, bar_h))
+        .into_styled(PrimitiveStyle::with_fill(theme.track))
         .draw(display)?;
 
     // Filled portion
@@ -112,45 +429,68 @@ where
     )]
     let filled_w = ((bar_w as f32) * progress) as u32;
     if filled_w > 0 {
-        Rectangle::new(Point::new(20, bar_y), Size::new(filled_w, bar_h))
-            .into_styled(PrimitiveStyle::with_fill(Gray4::new(0x2)))
+        Rectangle::new(Point::new(INSET, bar_y), Size::new(filled_w, bar_h))
+            .into_styled(PrimitiveStyle::with_fill(theme.bar))
             .draw(display)?;
     }
 
     // Border
-    Rectangle::new(Point::new(20, bar_y), Size::new(bar_w, bar_h))
-        .into_styled(PrimitiveStyle::with_stroke(Gray4::BLACK, 1))
+    Rectangle::new(Point::new(INSET, bar_y), Size::new(bar_w, bar_h))
+        .into_styled(outline)
         .draw(display)?;
     register(
         "now-playing-progress",
         "ProgressBar",
-        (20, bar_y),
+        (INSET, bar_y),
         (bar_w, bar_h),
     );
 
+    // ── Album art ─────────────────────────────────────────────────────────
+    if let Some(area) = art_region(bounds.size) {
+        draw_album_art(display, area, art)?;
+        register(
+            "now-playing-art",
+            "Image",
+            (area.top_left.x, area.top_left.y),
+            (area.size.width, area.size.height),
+        );
+    }
+
     // ── Play/Pause button ─────────────────────────────────────────────────
-    let btn_h = 40u32;
-    let btn_w = 100u32;
+    let btn_h = layout.button.height;
+    let btn_w = layout.button.width;
     // SAFETY: display dimensions (800×480) are far below i32::MAX; wrapping is impossible.
     #[allow(clippy::cast_possible_wrap)]
-    let btn_y = (h as i32).saturating_sub(80);
+    let btn_y = (h as i32).saturating_sub(layout.button_bottom as i32);
     // SAFETY: display dimensions (800×480) are far below i32::MAX; wrapping is impossible.
     #[allow(clippy::cast_possible_wrap)]
     let btn_x = ((w as i32).saturating_sub(btn_w as i32)) / 2;
     let btn_label = if state.playing { "Pause" } else { "Play" };
 
     Rectangle::new(Point::new(btn_x, btn_y), Size::new(btn_w, btn_h))
-        .into_styled(PrimitiveStyle::with_fill(Gray4::new(0x2)))
+        .into_styled(PrimitiveStyle::with_fill(theme.bar))
         .draw(display)?;
     // Border
     Rectangle::new(Point::new(btn_x, btn_y), Size::new(btn_w, btn_h))
-        .into_styled(PrimitiveStyle::with_stroke(Gray4::BLACK, 1))
+        .into_styled(outline)
         .draw(display)?;
-    let btn_style = MonoTextStyle::new(&FONT_10X20, Gray4::WHITE);
     // SAFETY: btn_x and btn_y are derived from display dimensions (800×480 max) minus
-    // btn dimensions, so btn_x + 10 and btn_y + 26 are well within i32 range.
+    // btn dimensions, and the label offset is inside the button, so the sum is
+    // well within i32 range.
     #[allow(clippy::arithmetic_side_effects)]
-    Text::new(btn_label, Point::new(btn_x + 10, btn_y + 26), btn_style).draw(display)?;
+    let label = Point::new(btn_x + layout.label.x, btn_y + layout.label.y);
+    let label_theme = Theme {
+        text_scale: layout.label_scale,
+        ..*theme
+    };
+    draw_text(
+        display,
+        &label_theme,
+        btn_label,
+        label,
+        Gray4::WHITE,
+        Alignment::Left,
+    )?;
     register(
         "now-playing-play-btn",
         "Button",
@@ -160,3 +500,94 @@ where
 
     Ok(())
 }
+
+/// How to refresh the panel after a Now Playing redraw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NowPlayingRefresh {
+    /// Art region to refresh with GC16 on its own, before `screen`.  Only
+    /// set on an album change when `screen` is not a full refresh anyway.
+    pub art: Option<Rectangle>,
+    /// Refresh for the rest of the screen.
+    pub screen: RefreshChoice,
+}
+
+/// Chooses refreshes for Now Playing redraws: GC16 for new album art, the
+/// [`RefreshPolicy`]'s choice (normally DU4) for everything else.
+///
+/// A windowed GC16 over the art only cleans that region, so it is not
+/// recorded as a full refresh; the policy's wear tracking only sees the
+/// screen refreshes.
+#[derive(Debug, Clone)]
+pub struct NowPlayingRefreshPlanner {
+    policy: RefreshPolicy,
+    /// Album whose art is on the panel; `None` until the first plan.
+    shown_album: Option<Option<u32>>,
+}
+
+impl NowPlayingRefreshPlanner {
+    /// Planner for a screen of `size` with the default policy.
+    #[must_use]
+    pub fn new(size: Size) -> Self {
+        Self::with_policy(RefreshPolicy::new(size.width.saturating_mul(size.height)))
+    }
+
+    /// Planner using `policy` (e.g. one shared with other screens).
+    #[must_use]
+    pub fn with_policy(policy: RefreshPolicy) -> Self {
+        Self {
+            policy,
+            shown_album: None,
+        }
+    }
+
+    /// The underlying policy.
+    #[must_use]
+    pub fn policy(&self) -> &RefreshPolicy {
+        &self.policy
+    }
+
+    /// Plan the refresh after rendering `state` on a screen of `size`.
+    pub fn plan(
+        &mut self,
+        state: &NowPlayingState,
+        size: Size,
+        panel: PanelState,
+        now_ms: u64,
+    ) -> NowPlayingRefresh {
+        let album_changed = self.shown_album != Some(state.album_id);
+        self.shown_album = Some(state.album_id);
+
+        let art_area = art_region(size).filter(|_| album_changed);
+        // The policy may want the whole panel cleaned (first frame, wear
+        // limits); then the art goes out with the screen refresh.
+        let art_window = art_area.filter(|area| {
+            let update = Update::new(area_pixels(area), ContentHint::Image);
+            self.policy.choose(update, panel, now_ms).reason == RefreshReason::Image
+        });
+
+        let mut dirty = controls_pixels(size);
+        if let (Some(area), None) = (art_area, art_window) {
+            dirty = dirty.saturating_add(area_pixels(&area));
+        }
+        let screen = self
+            .policy
+            .decide(Update::new(dirty, ContentHint::Text), panel, now_ms);
+
+        NowPlayingRefresh {
+            art: art_window.filter(|_| screen.mode != RefreshMode::Full),
+            screen,
+        }
+    }
+}
+
+fn area_pixels(area: &Rectangle) -> u32 {
+    area.size.width.saturating_mul(area.size.height)
+}
+
+/// Pixels redrawn outside the art: title, artist, progress bar and button.
+fn controls_pixels(size: Size) -> u32 {
+    size.width
+        .saturating_sub(40)
+        .saturating_mul(TITLE_H.saturating_add(ARTIST_H).saturating_add(PROGRESS_H))
+        .saturating_add(BUTTON_W.saturating_mul(BUTTON_H))
+}
@@ -0,0 +1,306 @@
+//! Queue screen renderer
+//!
+//! Lists the play queue by title.  The cursor row is drawn as a dark bar and
+//! the track playing now is marked with `>`.  In select mode every row gets
+//! a `[ ]`/`[x]` box and the footer shows how many tracks are marked and
+//! the batch action a Select hold will apply.  The footer band is reserved
+//! in both modes so entering select mode does not reflow the list.  Rows
+//! are `theme.row_h` pixels from `theme.list_top`, both on the 8-pixel
+//! partial window grid.  In accessibility mode ([`Theme::ACCESSIBLE`])
+//! rows are twice as tall and the text twice as large, so half as many
+//! tracks fit per page.
+//!
+//! # Registered test IDs
+//!
+//! | test ID           | Component type |
+//! |-------------------|----------------|
+//! | `"queue-list"`    | `"List"`       |
+//! | `"queue-cursor"`  | `"Label"`      |
+//! | `"queue-playing"` | `"Label"`      |
+//! | `"queue-footer"`  | `"Label"`      |
+//! | `"queue-empty"`   | `"Label"`      |
+//!
+//! `"queue-footer"` is registered only in select mode.
+
+use core::fmt::Write as _;
+
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::Alignment,
+};
+use ui::queue::{QueueMode, QueueView};
+
+use super::{on_screen, TextBuf};
+use crate::theme::{draw_text, Theme};
+
+/// Left inset of the playing marker.
+const MARKER_X: i32 = 8;
+
+/// Left text inset in browse mode: two characters in from the marker.
+fn text_x(theme: &Theme) -> i32 {
+    MARKER_X.saturating_add(chars(theme, 2))
+}
+
+/// Left inset of the select-mode check box, just before the browse-mode
+/// text column.
+fn box_x(theme: &Theme) -> i32 {
+    text_x(theme).saturating_sub(4)
+}
+
+/// Left text inset in select mode, after the `[x]` box and a space.
+fn select_text_x(theme: &Theme) -> i32 {
+    box_x(theme).saturating_add(chars(theme, 4))
+}
+
+/// Width of `n` characters at the theme's text scale.
+fn chars(theme: &Theme, n: u32) -> i32 {
+    i32::try_from(theme.char_w().saturating_mul(n)).unwrap_or(0)
+}
+
+/// Rows of tracks that fit above the footer on a screen of `size` (at
+/// least one).
+#[must_use]
+pub fn queue_rows(size: Size, theme: &Theme) -> usize {
+    let rows = size
+        .height
+        .saturating_sub(theme.list_top)
+        .saturating_sub(theme.header_h)
+        .checked_div(theme.row_h);
+    usize::try_from(rows.unwrap_or(0)).unwrap_or(0).max(1)
+}
+
+/// Screen rectangle of queue entry `index`, or `None` when it is scrolled
+/// away.
+#[must_use]
+pub fn queue_rect(size: Size, theme: &Theme, view: &QueueView, index: usize) -> Option<Rectangle> {
+    if !view.is_visible(index) {
+        return None;
+    }
+    let row = u32::try_from(index.saturating_sub(view.top())).ok()?;
+    let y = theme
+        .list_top
+        .saturating_add(row.saturating_mul(theme.row_h));
+    Some(Rectangle::new(
+        Point::new(0, i32::try_from(y).ok()?),
+        Size::new(size.width, theme.row_h),
+    ))
+}
+
+/// Screen rectangle of the select-mode footer, as tall as the title bar.
+#[must_use]
+pub fn footer_rect(size: Size, theme: &Theme) -> Rectangle {
+    let y = size.height.saturating_sub(theme.header_h);
+    Rectangle::new(
+        Point::new(0, i32::try_from(y).unwrap_or(0)),
+        Size::new(size.width, theme.header_h),
+    )
+}
+
+/// Render the queue screen onto any `DrawTarget<Color = Gray4>`.
+///
+/// `titles` holds one title per queue entry, in queue order; entries past
+/// its end show as `Track N`.  The `register` closure works as in
+/// [`render_now_playing_to`](super::now_playing::render_now_playing_to).
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn render_queue_to<D, R>(
+    display: &mut D,
+    theme: &Theme,
+    titles: &[&str],
+    view: &QueueView,
+    register: R,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    let size = display.bounding_box().size;
+    let mut register = on_screen(size, register);
+
+    Rectangle::new(Point::zero(), size)
+        .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+        .draw(display)?;
+
+    // ── Header bar ────────────────────────────────────────────────────────
+    Rectangle::new(Point::zero(), Size::new(size.width, theme.header_h))
+        .into_styled(PrimitiveStyle::with_fill(theme.bar))
+        .draw(display)?;
+    let header = Point::new(text_x(theme), theme.header_baseline);
+    draw_text(
+        display,
+        theme,
+        "Queue",
+        header,
+        Gray4::WHITE,
+        Alignment::Left,
+    )?;
+
+    if view.count() == 0 {
+        // SAFETY: display dimensions (800×480) are far below i32::MAX.
+        #[allow(clippy::cast_possible_wrap)]
+        let centre = Point::new((size.width / 2) as i32, (size.height / 2) as i32);
+        let message = "Queue is empty";
+        draw_text(
+            display,
+            theme,
+            message,
+            centre,
+            theme.secondary,
+            Alignment::Center,
+        )?;
+        let width = chars(theme, u32::try_from(message.len()).unwrap_or(0));
+        register(
+            "queue-empty",
+            "Label",
+            (
+                centre.x.saturating_sub(width / 2),
+                centre.y.saturating_sub(theme.baseline_in(0)),
+            ),
+            (width.unsigned_abs(), theme.line_h()),
+        );
+        return Ok(());
+    }
+
+    for row in 0..view.rows() {
+        draw_row(display, theme, titles, view, view.top().saturating_add(row))?;
+    }
+    let shown =
+        u32::try_from(view.rows().min(view.count().saturating_sub(view.top()))).unwrap_or(0);
+    register(
+        "queue-list",
+        "List",
+        (0, i32::try_from(theme.list_top).unwrap_or(0)),
+        (size.width, shown.saturating_mul(theme.row_h)),
+    );
+    for (id, index) in [
+        ("queue-cursor", Some(view.cursor())),
+        ("queue-playing", view.playing()),
+    ] {
+        if let Some(rect) = index.and_then(|i| queue_rect(size, theme, view, i)) {
+            register(
+                id,
+                "Label",
+                (rect.top_left.x, rect.top_left.y),
+                (rect.size.width, rect.size.height),
+            );
+        }
+    }
+
+    // ── Select-mode footer ────────────────────────────────────────────────
+    if view.mode() == QueueMode::Select {
+        let rect = footer_rect(size, theme);
+        rect.into_styled(PrimitiveStyle::with_fill(theme.bar))
+            .draw(display)?;
+        let baseline = rect
+            .top_left
+            .y
+            .saturating_add(theme.baseline_in(rect.size.height));
+        let mut text = TextBuf::<24>::new();
+        let _ = write!(text, "{} selected", view.marks().count());
+        let count = Point::new(text_x(theme), baseline);
+        draw_text(
+            display,
+            theme,
+            text.as_str(),
+            count,
+            Gray4::WHITE,
+            Alignment::Left,
+        )?;
+        let right = i32::try_from(size.width).unwrap_or(0).saturating_sub(20);
+        draw_text(
+            display,
+            theme,
+            view.action().label(),
+            Point::new(right, baseline),
+            Gray4::WHITE,
+            Alignment::Right,
+        )?;
+        register(
+            "queue-footer",
+            "Label",
+            (rect.top_left.x, rect.top_left.y),
+            (rect.size.width, rect.size.height),
+        );
+    }
+    Ok(())
+}
+
+/// Draw queue entry `index` in its row, inverted under the cursor.
+fn draw_row<D>(
+    display: &mut D,
+    theme: &Theme,
+    titles: &[&str],
+    view: &QueueView,
+    index: usize,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    let size = display.bounding_box().size;
+    let Some(rect) = queue_rect(size, theme, view, index) else {
+        return Ok(());
+    };
+    let (bg, fg) = if view.cursor() == index {
+        (theme.bar, Gray4::WHITE)
+    } else {
+        (Gray4::WHITE, Gray4::BLACK)
+    };
+    rect.into_styled(PrimitiveStyle::with_fill(bg))
+        .draw(display)?;
+
+    let baseline = rect
+        .top_left
+        .y
+        .saturating_add(theme.baseline_in(theme.row_h));
+
+    if view.playing() == Some(index) {
+        let marker = Point::new(MARKER_X, baseline);
+        draw_text(display, theme, ">", marker, fg, Alignment::Left)?;
+    }
+
+    let text_x = if view.mode() == QueueMode::Select {
+        let mark = if view.marks().is_marked(index) {
+            "[x]"
+        } else {
+            "[ ]"
+        };
+        let mark_at = Point::new(box_x(theme), baseline);
+        draw_text(display, theme, mark, mark_at, fg, Alignment::Left)?;
+        select_text_x(theme)
+    } else {
+        text_x(theme)
+    };
+
+    let mut fallback = TextBuf::<16>::new();
+    let title = match titles.get(index).copied().filter(|t| !t.is_empty()) {
+        Some(title) => title,
+        None => {
+            let _ = write!(fallback, "Track {}", index.saturating_add(1));
+            fallback.as_str()
+        }
+    };
+    let max_chars = usize::try_from(
+        size.width
+            .saturating_sub(u32::try_from(text_x).unwrap_or(0))
+            .saturating_sub(20)
+            .checked_div(theme.char_w())
+            .unwrap_or(0),
+    )
+    .unwrap_or(0);
+    let end = title
+        .char_indices()
+        .nth(max_chars)
+        .map_or(title.len(), |(i, _)| i);
+    draw_text(
+        display,
+        theme,
+        title.get(..end).unwrap_or(title),
+        Point::new(text_x, baseline),
+        fg,
+        Alignment::Left,
+    )
+}
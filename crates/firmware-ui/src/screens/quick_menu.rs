@@ -0,0 +1,329 @@
+//! Quick menu renderer — output profile switcher, playback speed and
+//! night mode
+//!
+//! Lists the output profiles by name with their EQ preset on the right.
+//! The selected row is drawn as a dark bar; the profile in use is marked
+//! with `*`.  Rows are `theme.row_h` pixels from `theme.list_top`, both on
+//! the 8-pixel partial window grid.  In accessibility mode the large name
+//! leaves no room beside it, so the EQ preset goes on a second line under
+//! the name.
+//!
+//! When the menu has a speed row it follows the profiles, labelled `Speed`
+//! with the current speed (`1.5x`) where the EQ preset would be.  The
+//! night mode row comes last, labelled `Night mode` with `On` or `Off`.
+//!
+//! When the rows do not all fit below the header (a full profile list with
+//! both extra rows in accessibility mode), the list scrolls just far
+//! enough to keep the selected row on screen.
+//!
+//! # Registered test IDs
+//!
+//! | test ID                 | Component type |
+//! |-------------------------|----------------|
+//! | `"quick-menu-list"`     | `"List"`       |
+//! | `"quick-menu-selected"` | `"Label"`      |
+//! | `"quick-menu-active"`   | `"Label"`      |
+//! | `"quick-menu-speed"`    | `"Label"`      |
+//! | `"quick-menu-night"`    | `"Label"`      |
+
+use core::fmt::Write as _;
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Text},
+};
+use platform::{OutputProfiles, PlaybackSpeed};
+use ui::quick_menu::QuickMenu;
+
+use super::{on_screen, TextBuf};
+use crate::theme::{draw_text, Theme, ThemeMode};
+
+/// Left inset of the active marker.
+const MARKER_X: i32 = 8;
+/// Gap between the name and the EQ line in a stacked row.
+const DETAIL_GAP: u32 = 2;
+
+/// Left text inset: two characters in from the marker.
+fn text_x(theme: &Theme) -> i32 {
+    MARKER_X.saturating_add(i32::try_from(theme.char_w().saturating_mul(2)).unwrap_or(0))
+}
+
+/// Baselines of the name and the EQ line in a stacked row starting at
+/// `top`, the pair centred in the row.  The name is at `theme.text_scale`,
+/// the EQ line at scale 1.
+fn stacked_baselines(theme: &Theme, top: i32) -> (i32, i32) {
+    let glyph_h = FONT_10X20.character_size.height;
+    let block = theme
+        .line_h()
+        .saturating_add(DETAIL_GAP)
+        .saturating_add(glyph_h);
+    let inset = theme.row_h.saturating_sub(block) / 2;
+    let name_top = top.saturating_add(i32::try_from(inset).unwrap_or(0));
+    let ascent = FONT_10X20.baseline;
+    let scale = theme.text_scale;
+    let name = u32_offset(name_top, ascent.saturating_mul(scale));
+    let eq = u32_offset(
+        name_top,
+        theme
+            .line_h()
+            .saturating_add(DETAIL_GAP)
+            .saturating_add(ascent),
+    );
+    (name, eq)
+}
+
+fn u32_offset(y: i32, offset: u32) -> i32 {
+    y.saturating_add(i32::try_from(offset).unwrap_or(0))
+}
+
+/// Screen rectangle of profile `index`, or `None` when out of range.
+#[must_use]
+pub fn profile_rect(
+    size: Size,
+    theme: &Theme,
+    menu: &QuickMenu,
+    index: usize,
+) -> Option<Rectangle> {
+    if index >= menu.count() {
+        return None;
+    }
+    row_rect(size, theme, menu, index)
+}
+
+/// Screen rectangle of the speed row, or `None` when the menu has none.
+#[must_use]
+pub fn speed_rect(size: Size, theme: &Theme, menu: &QuickMenu) -> Option<Rectangle> {
+    menu.speed_step()?;
+    row_rect(size, theme, menu, menu.count())
+}
+
+/// Screen rectangle of the night mode row, or `None` when the menu has
+/// none.
+#[must_use]
+pub fn night_rect(size: Size, theme: &Theme, menu: &QuickMenu) -> Option<Rectangle> {
+    menu.night_mode()?;
+    let index = menu.rows().saturating_sub(1);
+    row_rect(size, theme, menu, index)
+}
+
+/// Rows that fit between the list top and the bottom of the screen (at
+/// least one).
+fn visible_rows(size: Size, theme: &Theme) -> usize {
+    let rows = size
+        .height
+        .saturating_sub(theme.list_top)
+        .checked_div(theme.row_h)
+        .unwrap_or(0);
+    usize::try_from(rows).unwrap_or(0).max(1)
+}
+
+/// First row on screen: 0 when every row fits, otherwise just far enough
+/// down to show the selected row at the bottom.
+fn first_row(size: Size, theme: &Theme, menu: &QuickMenu) -> usize {
+    let visible = visible_rows(size, theme);
+    if menu.rows() <= visible {
+        return 0;
+    }
+    menu.selected().saturating_add(1).saturating_sub(visible)
+}
+
+/// Screen rectangle of list row `index`, or `None` when it is scrolled
+/// off the top.
+fn row_rect(size: Size, theme: &Theme, menu: &QuickMenu, index: usize) -> Option<Rectangle> {
+    let row = index.checked_sub(first_row(size, theme, menu))?;
+    let row = u32::try_from(row).ok()?;
+    let y = theme
+        .list_top
+        .saturating_add(row.saturating_mul(theme.row_h));
+    Some(Rectangle::new(
+        Point::new(0, i32::try_from(y).ok()?),
+        Size::new(size.width, theme.row_h),
+    ))
+}
+
+/// Render the quick menu onto any `DrawTarget<Color = Gray4>`.
+///
+/// The `register` closure works as in
+/// [`render_now_playing_to`](super::now_playing::render_now_playing_to).
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn render_quick_menu_to<D, R, const N: usize>(
+    display: &mut D,
+    theme: &Theme,
+    profiles: &OutputProfiles<N>,
+    menu: &QuickMenu,
+    register: R,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    let size = display.bounding_box().size;
+    let mut register = on_screen(size, register);
+
+    Rectangle::new(Point::zero(), size)
+        .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+        .draw(display)?;
+
+    // ── Header bar ────────────────────────────────────────────────────────
+    Rectangle::new(Point::zero(), Size::new(size.width, theme.header_h))
+        .into_styled(PrimitiveStyle::with_fill(theme.bar))
+        .draw(display)?;
+    let text_x = text_x(theme);
+    draw_text(
+        display,
+        theme,
+        "Output",
+        Point::new(text_x, theme.header_baseline),
+        Gray4::WHITE,
+        Alignment::Left,
+    )?;
+
+    for (index, profile) in profiles.iter().enumerate() {
+        let Some(rect) = profile_rect(size, theme, menu, index) else {
+            continue;
+        };
+        let marked = menu.active() == index;
+        let row = Row {
+            name: profile.name.as_str(),
+            detail: profile.eq.label(),
+        };
+        draw_row(display, theme, rect, row, menu.selected() == index, marked)?;
+    }
+    if let Some(rect) = speed_rect(size, theme, menu) {
+        let speed = menu
+            .speed_step()
+            .and_then(|step| PlaybackSpeed::STEPS.get(step))
+            .copied()
+            .unwrap_or_default();
+        let mut label = TextBuf::<8>::new();
+        let _ = write!(label, "{speed}");
+        let row = Row {
+            name: "Speed",
+            detail: label.as_str(),
+        };
+        draw_row(display, theme, rect, row, menu.speed_selected(), false)?;
+    }
+    if let Some(rect) = night_rect(size, theme, menu) {
+        let row = Row {
+            name: "Night mode",
+            detail: if menu.night_mode() == Some(true) {
+                "On"
+            } else {
+                "Off"
+            },
+        };
+        draw_row(display, theme, rect, row, menu.night_selected(), false)?;
+    }
+
+    let extra_rows = menu.rows().saturating_sub(menu.count());
+    let rows = menu.count().min(profiles.len()).saturating_add(extra_rows);
+    let first = first_row(size, theme, menu);
+    let shown = if first == 0 {
+        rows
+    } else {
+        rows.saturating_sub(first).min(visible_rows(size, theme))
+    };
+    let shown = u32::try_from(shown).unwrap_or(0);
+    register(
+        "quick-menu-list",
+        "List",
+        (0, i32::try_from(theme.list_top).unwrap_or(0)),
+        (size.width, shown.saturating_mul(theme.row_h)),
+    );
+    let selected = if menu.speed_selected() {
+        speed_rect(size, theme, menu)
+    } else if menu.night_selected() {
+        night_rect(size, theme, menu)
+    } else {
+        profile_rect(size, theme, menu, menu.selected())
+    };
+    let active = profile_rect(size, theme, menu, menu.active());
+    for (id, rect) in [
+        ("quick-menu-selected", selected),
+        ("quick-menu-active", active),
+    ] {
+        if let Some(rect) = rect {
+            register(
+                id,
+                "Label",
+                (rect.top_left.x, rect.top_left.y),
+                (rect.size.width, rect.size.height),
+            );
+        }
+    }
+    for (id, rect) in [
+        ("quick-menu-speed", speed_rect(size, theme, menu)),
+        ("quick-menu-night", night_rect(size, theme, menu)),
+    ] {
+        if let Some(rect) = rect {
+            register(
+                id,
+                "Label",
+                (rect.top_left.x, rect.top_left.y),
+                (rect.size.width, rect.size.height),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Text of one menu row: the name and the detail beside or under it.
+struct Row<'a> {
+    name: &'a str,
+    detail: &'a str,
+}
+
+/// Draw one row into `rect`: a bar when `selected`, `*` when `marked`.
+fn draw_row<D>(
+    display: &mut D,
+    theme: &Theme,
+    rect: Rectangle,
+    row: Row<'_>,
+    selected: bool,
+    marked: bool,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    let text_x = text_x(theme);
+    let right = i32::try_from(rect.size.width)
+        .unwrap_or(0)
+        .saturating_sub(20);
+    let (bg, fg) = if selected {
+        (theme.bar, Gray4::WHITE)
+    } else {
+        (Gray4::WHITE, Gray4::BLACK)
+    };
+    rect.into_styled(PrimitiveStyle::with_fill(bg))
+        .draw(display)?;
+    let style = MonoTextStyle::new(&FONT_10X20, fg);
+    let (baseline, detail) = if theme.mode == ThemeMode::Accessible {
+        let (name, detail) = stacked_baselines(theme, rect.top_left.y);
+        (
+            name,
+            Text::new(row.detail, Point::new(text_x, detail), style),
+        )
+    } else {
+        let baseline = rect
+            .top_left
+            .y
+            .saturating_add(theme.baseline_in(theme.row_h));
+        let right = Point::new(right, baseline);
+        let detail = Text::with_alignment(row.detail, right, style, Alignment::Right);
+        (baseline, detail)
+    };
+    if marked {
+        let marker = Point::new(MARKER_X, baseline);
+        draw_text(display, theme, "*", marker, fg, Alignment::Left)?;
+    }
+    let name = Point::new(text_x, baseline);
+    draw_text(display, theme, row.name, name, fg, Alignment::Left)?;
+    detail.draw(display)?;
+    Ok(())
+}
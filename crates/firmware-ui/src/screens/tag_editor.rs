@@ -0,0 +1,153 @@
+//! Tag editor renderer — title, artist and album corrections
+//!
+//! Lists the three fields of `library::TagField::ALL` as `Label: value`
+//! rows; the selected row is drawn as a dark bar.  While a field is open
+//! the [text input](super::text_input) sits under the list, at
+//! [`input_top`].  Rows are `theme.row_h` pixels high and stay on the
+//! 8-pixel partial window grid.
+//!
+//! # Registered test IDs
+//!
+//! | test ID                | Component type |
+//! |------------------------|----------------|
+//! | `"tag-field-list"`     | `"List"`       |
+//! | `"tag-field-selected"` | `"Label"`      |
+//!
+//! plus those of [`render_text_input_to`] while a field is open.
+
+use core::fmt::Write as _;
+
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::Alignment,
+};
+use library::TagField;
+use ui::tag_editor::{TagEditor, TAG_FIELDS};
+
+use super::text_input::{line_chars, render_text_input_to, INPUT_GAP};
+use super::{on_screen, TextBuf};
+use crate::theme::{draw_text, Theme};
+
+/// Left text inset.
+const TEXT_X: i32 = 16;
+
+/// Screen rectangle of field `index`, or `None` when out of range.
+#[must_use]
+pub fn field_rect(size: Size, theme: &Theme, index: usize) -> Option<Rectangle> {
+    if index >= TAG_FIELDS {
+        return None;
+    }
+    let row = u32::try_from(index).ok()?;
+    let y = theme
+        .list_top
+        .saturating_add(row.saturating_mul(theme.row_h));
+    Some(Rectangle::new(
+        Point::new(0, i32::try_from(y).ok()?),
+        Size::new(size.width, theme.row_h),
+    ))
+}
+
+/// Top of the text input shown while a field is open.
+#[must_use]
+pub fn input_top(theme: &Theme) -> u32 {
+    let fields = u32::try_from(TAG_FIELDS).unwrap_or(0);
+    theme
+        .list_top
+        .saturating_add(fields.saturating_mul(theme.row_h))
+        .saturating_add(INPUT_GAP)
+}
+
+/// Render the tag editor onto any `DrawTarget<Color = Gray4>`.
+///
+/// The `register` closure works as in
+/// [`render_now_playing_to`](super::now_playing::render_now_playing_to).
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn render_tag_editor_to<D, R>(
+    display: &mut D,
+    theme: &Theme,
+    editor: &TagEditor,
+    register: R,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    let size = display.bounding_box().size;
+    let mut register = on_screen(size, register);
+
+    Rectangle::new(Point::zero(), size)
+        .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+        .draw(display)?;
+
+    // ── Header bar ────────────────────────────────────────────────────────
+    Rectangle::new(Point::zero(), Size::new(size.width, theme.header_h))
+        .into_styled(PrimitiveStyle::with_fill(theme.bar))
+        .draw(display)?;
+    draw_text(
+        display,
+        theme,
+        "Edit tags",
+        Point::new(TEXT_X, theme.header_baseline),
+        Gray4::WHITE,
+        Alignment::Left,
+    )?;
+
+    // ── Field list ────────────────────────────────────────────────────────
+    let max_chars = line_chars(size, theme);
+    for (index, field) in TagField::ALL.iter().enumerate() {
+        let Some(rect) = field_rect(size, theme, index) else {
+            continue;
+        };
+        let (bg, fg) = if editor.selected() == index {
+            (theme.bar, Gray4::WHITE)
+        } else {
+            (Gray4::WHITE, Gray4::BLACK)
+        };
+        rect.into_styled(PrimitiveStyle::with_fill(bg))
+            .draw(display)?;
+        let mut row = TextBuf::<160>::new();
+        let _ = write!(row, "{}: {}", field.label(), editor.value(index));
+        let end = row
+            .as_str()
+            .char_indices()
+            .nth(max_chars)
+            .map_or(row.as_str().len(), |(i, _)| i);
+        let baseline = rect
+            .top_left
+            .y
+            .saturating_add(theme.baseline_in(theme.row_h));
+        draw_text(
+            display,
+            theme,
+            row.as_str().get(..end).unwrap_or_default(),
+            Point::new(TEXT_X, baseline),
+            fg,
+            Alignment::Left,
+        )?;
+    }
+    let fields = u32::try_from(TAG_FIELDS).unwrap_or(0);
+    register(
+        "tag-field-list",
+        "List",
+        (0, i32::try_from(theme.list_top).unwrap_or(0)),
+        (size.width, fields.saturating_mul(theme.row_h)),
+    );
+    if let Some(rect) = field_rect(size, theme, editor.selected()) {
+        register(
+            "tag-field-selected",
+            "Label",
+            (rect.top_left.x, rect.top_left.y),
+            (rect.size.width, rect.size.height),
+        );
+    }
+
+    match editor.editing() {
+        Some(input) => render_text_input_to(display, theme, input, input_top(theme), register),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,209 @@
+//! Rotary text input renderer — the text box and character wheel
+//!
+//! Shared by every screen that asks for text (tag editor, search, playlist
+//! names).  The caller places it with `top`, the y of the box; the band is
+//! [`text_input_height`] pixels tall: an outlined box showing the text with
+//! a `_` cursor, a gap, then the wheel — the highlighted key in a dark cell
+//! at the centre with its neighbours on either side, as many as fit the
+//! width.  Text too long for the box shows its end, where typing happens.
+//! With `top` on the 8-pixel partial window grid, so are the box and the
+//! wheel, and a turn of the encoder refreshes only the wheel row.
+//!
+//! # Registered test IDs
+//!
+//! | test ID            | Component type |
+//! |--------------------|----------------|
+//! | `"text-input-box"` | `"TextInput"`  |
+//! | `"text-input-key"` | `"Button"`     |
+
+use core::fmt::Write as _;
+
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::Alignment,
+};
+use ui::text_input::{Key, TextInput};
+
+use super::{on_screen, TextBuf};
+use crate::theme::{draw_text, Theme};
+
+/// Left text inset.
+const TEXT_X: i32 = 16;
+/// Gap between the text box and the wheel.
+pub const INPUT_GAP: u32 = 16;
+/// Horizontal padding inside a wheel cell.
+const KEY_PAD: u32 = 8;
+/// Widest key label, in characters (`DEL`).
+const KEY_CHARS: u32 = 3;
+
+/// Height of the text box, gap and wheel together.
+#[must_use]
+pub fn text_input_height(theme: &Theme) -> u32 {
+    theme.row_h.saturating_mul(2).saturating_add(INPUT_GAP)
+}
+
+/// Screen rectangle of the text box placed at `top`.
+#[must_use]
+pub fn input_rect(size: Size, theme: &Theme, top: u32) -> Rectangle {
+    Rectangle::new(
+        Point::new(0, i32::try_from(top).unwrap_or(0)),
+        Size::new(size.width, theme.row_h),
+    )
+}
+
+/// Width of one wheel cell.
+fn key_w(theme: &Theme) -> u32 {
+    theme
+        .char_w()
+        .saturating_mul(KEY_CHARS)
+        .saturating_add(KEY_PAD.saturating_mul(2))
+}
+
+/// Wheel cells on each side of the highlighted key.
+#[must_use]
+pub fn wheel_reach(size: Size, theme: &Theme) -> i32 {
+    let cells = size.width.checked_div(key_w(theme)).unwrap_or(0);
+    i32::try_from(cells.saturating_sub(1) / 2).unwrap_or(0)
+}
+
+/// Screen rectangle of the wheel cell `offset` keys from the highlighted
+/// one, for a text input placed at `top`, or `None` when it is off screen.
+#[must_use]
+pub fn key_rect(size: Size, theme: &Theme, top: u32, offset: i32) -> Option<Rectangle> {
+    if offset.unsigned_abs() > wheel_reach(size, theme).unsigned_abs() {
+        return None;
+    }
+    let w = i32::try_from(key_w(theme)).ok()?;
+    let centre = i32::try_from(size.width / 2).ok()?;
+    let x = centre
+        .saturating_sub(w / 2)
+        .saturating_add(offset.saturating_mul(w));
+    let y = top.saturating_add(theme.row_h).saturating_add(INPUT_GAP);
+    Some(Rectangle::new(
+        Point::new(x, i32::try_from(y).ok()?),
+        Size::new(key_w(theme), theme.row_h),
+    ))
+}
+
+/// Label drawn for `key`.
+fn key_label(key: Key, buf: &mut [u8; 4]) -> &str {
+    match key {
+        Key::Char(' ') => "SP",
+        Key::Char(c) => c.encode_utf8(buf),
+        Key::Delete => "DEL",
+        Key::Done => "OK",
+    }
+}
+
+/// The last `max_chars` characters of `text`.
+fn tail(text: &str, max_chars: usize) -> &str {
+    let skip = text.chars().count().saturating_sub(max_chars);
+    let start = text.char_indices().nth(skip).map_or(text.len(), |(i, _)| i);
+    text.get(start..).unwrap_or(text)
+}
+
+/// Characters that fit on one line from [`TEXT_X`] to the right inset.
+#[must_use]
+pub fn line_chars(size: Size, theme: &Theme) -> usize {
+    let inset = u32::try_from(TEXT_X).unwrap_or(0).saturating_mul(2);
+    let chars = size
+        .width
+        .saturating_sub(inset)
+        .checked_div(theme.char_w())
+        .unwrap_or(0);
+    usize::try_from(chars).unwrap_or(0)
+}
+
+/// Render `input` with its box at `top` onto any `DrawTarget<Color = Gray4>`.
+///
+/// Only the band of [`text_input_height`] is drawn; the wheel cells are
+/// filled, so a turn redraws the wheel without clearing it first.  The `register` closure works as in
+/// [`render_now_playing_to`](super::now_playing::render_now_playing_to).
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn render_text_input_to<D, R, const N: usize>(
+    display: &mut D,
+    theme: &Theme,
+    input: &TextInput<N>,
+    top: u32,
+    register: R,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+    R: FnMut(&str, &str, (i32, i32), (u32, u32)),
+{
+    let size = display.bounding_box().size;
+    let mut register = on_screen(size, register);
+
+    // ── Text box ──────────────────────────────────────────────────────────
+    let boxed = input_rect(size, theme, top);
+    boxed
+        .into_styled(PrimitiveStyle::with_stroke(Gray4::BLACK, theme.stroke))
+        .draw(display)?;
+    let mut shown = TextBuf::<160>::new();
+    let _ = write!(
+        shown,
+        "{}_",
+        tail(input.text(), line_chars(size, theme).saturating_sub(1))
+    );
+    let baseline = boxed
+        .top_left
+        .y
+        .saturating_add(theme.baseline_in(theme.row_h));
+    draw_text(
+        display,
+        theme,
+        shown.as_str(),
+        Point::new(TEXT_X, baseline),
+        Gray4::BLACK,
+        Alignment::Left,
+    )?;
+    register(
+        "text-input-box",
+        "TextInput",
+        (boxed.top_left.x, boxed.top_left.y),
+        (boxed.size.width, boxed.size.height),
+    );
+
+    // ── Character wheel ───────────────────────────────────────────────────
+    let reach = wheel_reach(size, theme);
+    for offset in reach.saturating_neg()..=reach {
+        let Some(cell) = key_rect(size, theme, top, offset) else {
+            continue;
+        };
+        let (bg, fg) = if offset == 0 {
+            (theme.bar, Gray4::WHITE)
+        } else {
+            (Gray4::WHITE, theme.secondary)
+        };
+        cell.into_styled(PrimitiveStyle::with_fill(bg))
+            .draw(display)?;
+        let mut buf = [0u8; 4];
+        let label = key_label(input.key_near(offset), &mut buf);
+        let baseline = cell
+            .top_left
+            .y
+            .saturating_add(theme.baseline_in(theme.row_h));
+        draw_text(
+            display,
+            theme,
+            label,
+            Point::new(cell.center().x, baseline),
+            fg,
+            Alignment::Center,
+        )?;
+    }
+    if let Some(cell) = key_rect(size, theme, top, 0) {
+        register(
+            "text-input-key",
+            "Button",
+            (cell.top_left.x, cell.top_left.y),
+            (cell.size.width, cell.size.height),
+        );
+    }
+    Ok(())
+}
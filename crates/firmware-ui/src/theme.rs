@@ -0,0 +1,242 @@
+//! Display themes: standard and accessibility mode
+//!
+//! A [`Theme`] carries what the core screens (Now Playing, Queue, Quick
+//! Menu) vary between the two modes: the text scale, the bar and text
+//! colours, and the list template (header height, row height).  Screen
+//! geometry is derived from it, so a screen renders either variant from
+//! the same code.
+//!
+//! [`Theme::ACCESSIBLE`] draws FONT_10X20 at twice its size through
+//! [`draw_text`], uses black instead of dark grey for bars and secondary
+//! text, and doubles the row height so lists show half as many entries.
+//! FONT_10X20 is the largest embedded-graphics font; upscaling it keeps the
+//! same glyphs the rest of the UI uses.
+//!
+//! The mode comes from `[ui] accessible` in `soul.toml`
+//! ([`Theme::from_overrides`]).
+
+use embedded_graphics::{
+    mono_font::{
+        ascii::{FONT_10X20, FONT_6X10},
+        MonoFont, MonoTextStyle,
+    },
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Alignment, Text},
+};
+use platform::soul_config::UiOverrides;
+
+/// FONT_10X20 glyph size at scale 1.
+const GLYPH_W: u32 = 10;
+const GLYPH_H: u32 = 20;
+/// Rows from a FONT_10X20 glyph's top to the baseline used to centre it
+/// in a row (one below the font's own baseline, matching the row layout
+/// the list screens have always used).
+const ROW_BASELINE: u32 = 16;
+
+/// Which layout variant the screens draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeMode {
+    /// Default layout.
+    Standard,
+    /// Large text, high contrast, fewer rows.
+    Accessible,
+}
+
+/// Colours, text scale and list template for one [`ThemeMode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// Mode this theme implements.
+    pub mode: ThemeMode,
+    /// Integer upscale of FONT_10X20 for headers, titles and list rows.
+    pub text_scale: u32,
+    /// Header bars, cursor bars, progress fill and buttons.
+    pub bar: Gray4,
+    /// Secondary text on white (artist, empty-list messages).
+    pub secondary: Gray4,
+    /// Unfilled part of progress bars.
+    pub track: Gray4,
+    /// Outline width of progress bars and buttons.
+    pub stroke: u32,
+    /// Height of the list screens' title bar.
+    pub header_h: u32,
+    /// Baseline of title bar text, from the top of the bar.
+    pub header_baseline: i32,
+    /// Top of the first list row.
+    pub list_top: u32,
+    /// Height of one list row.
+    pub row_h: u32,
+}
+
+impl Theme {
+    /// The default layout.
+    pub const STANDARD: Self = Self {
+        mode: ThemeMode::Standard,
+        text_scale: 1,
+        bar: Gray4::new(0x2),
+        secondary: Gray4::new(0x6),
+        track: Gray4::new(0xC),
+        stroke: 1,
+        header_h: 48,
+        header_baseline: 32,
+        list_top: 56,
+        row_h: 40,
+    };
+
+    /// Accessibility mode.  Metrics stay on the 8-pixel partial window
+    /// grid.
+    pub const ACCESSIBLE: Self = Self {
+        mode: ThemeMode::Accessible,
+        text_scale: 2,
+        bar: Gray4::BLACK,
+        secondary: Gray4::BLACK,
+        track: Gray4::WHITE,
+        stroke: 2,
+        header_h: 64,
+        header_baseline: 44,
+        list_top: 72,
+        row_h: 80,
+    };
+
+    /// Theme for `mode`.
+    #[must_use]
+    pub const fn for_mode(mode: ThemeMode) -> &'static Self {
+        match mode {
+            ThemeMode::Standard => &Self::STANDARD,
+            ThemeMode::Accessible => &Self::ACCESSIBLE,
+        }
+    }
+
+    /// Theme selected by `[ui] accessible` (standard when unset).
+    #[must_use]
+    pub const fn from_overrides(ui: &UiOverrides) -> &'static Self {
+        match ui.accessible {
+            Some(true) => &Self::ACCESSIBLE,
+            _ => &Self::STANDARD,
+        }
+    }
+
+    /// Advance of one scaled character.
+    #[must_use]
+    pub const fn char_w(&self) -> u32 {
+        GLYPH_W.saturating_mul(self.text_scale)
+    }
+
+    /// Height of one scaled line of text.
+    #[must_use]
+    pub const fn line_h(&self) -> u32 {
+        GLYPH_H.saturating_mul(self.text_scale)
+    }
+
+    /// Baseline offset that centres one scaled line in a band of `height`.
+    #[must_use]
+    pub fn baseline_in(&self, height: u32) -> i32 {
+        let top = height.saturating_sub(self.line_h()) / 2;
+        let offset = top.saturating_add(ROW_BASELINE.saturating_mul(self.text_scale));
+        i32::try_from(offset).unwrap_or(0)
+    }
+
+    /// Font for secondary lines drawn at scale 1.
+    #[must_use]
+    pub const fn detail_font(&self) -> &'static MonoFont<'static> {
+        match self.mode {
+            ThemeMode::Standard => &FONT_6X10,
+            ThemeMode::Accessible => &FONT_10X20,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+/// Draw `text` in FONT_10X20 at `theme.text_scale`.
+///
+/// `position` and `alignment` work as for [`Text::with_alignment`]:
+/// `position` is on the baseline, and the glyphs grow up and away from it.
+///
+/// # Errors
+///
+/// Returns `Err(D::Error)` if any draw call fails.
+pub fn draw_text<D>(
+    display: &mut D,
+    theme: &Theme,
+    text: &str,
+    position: Point,
+    color: Gray4,
+    alignment: Alignment,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Gray4>,
+{
+    let style = MonoTextStyle::new(&FONT_10X20, color);
+    if theme.text_scale <= 1 {
+        return Text::with_alignment(text, position, style, alignment)
+            .draw(display)
+            .map(|_| ());
+    }
+    let mut scaled = Scaled {
+        inner: display,
+        origin: position,
+        scale: theme.text_scale,
+    };
+    Text::with_alignment(text, Point::zero(), style, alignment)
+        .draw(&mut scaled)
+        .map(|_| ())
+}
+
+/// Draws every pixel as a `scale`² block, with `Point::zero()` at `origin`.
+struct Scaled<'a, D> {
+    inner: &'a mut D,
+    origin: Point,
+    scale: u32,
+}
+
+impl<D> Scaled<'_, D> {
+    fn to_inner(&self, point: Point) -> Point {
+        let s = i32::try_from(self.scale).unwrap_or(1);
+        Point::new(
+            self.origin.x.saturating_add(point.x.saturating_mul(s)),
+            self.origin.y.saturating_add(point.y.saturating_mul(s)),
+        )
+    }
+}
+
+impl<D: Dimensions> Dimensions for Scaled<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        let inner = self.inner.bounding_box();
+        let s = i32::try_from(self.scale).unwrap_or(1);
+        let unscale = |v: i32, o: i32| v.saturating_sub(o).checked_div(s).unwrap_or(0);
+        Rectangle::new(
+            Point::new(
+                unscale(inner.top_left.x, self.origin.x),
+                unscale(inner.top_left.y, self.origin.y),
+            ),
+            Size::new(
+                inner.size.width.checked_div(self.scale).unwrap_or(0),
+                inner.size.height.checked_div(self.scale).unwrap_or(0),
+            ),
+        )
+    }
+}
+
+impl<D: DrawTarget<Color = Gray4>> DrawTarget for Scaled<'_, D> {
+    type Color = Gray4;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let block = Size::new(self.scale, self.scale);
+        for Pixel(point, color) in pixels {
+            let top_left = self.to_inner(point);
+            self.inner
+                .fill_solid(&Rectangle::new(top_left, block), color)?;
+        }
+        Ok(())
+    }
+}
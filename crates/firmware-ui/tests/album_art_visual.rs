@@ -0,0 +1,210 @@
+//! Album art region and refresh planning for the Now Playing screen.
+//!
+//! The art goldens hold the raw thumbnail at 240×240 so they do not churn
+//! with the rest of the layout; regenerate with `UPDATE_GOLDEN=1`.
+//!
+//! Run: cargo test -p firmware-ui --test album_art_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects, clippy::indexing_slicing)]
+#![allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+
+use eink_testing::TestEmulator;
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use firmware_ui::screens::now_playing::{
+    art_region, draw_album_art, render_now_playing_to, NowPlayingRefreshPlanner, ART_BYTES,
+    ART_SIZE,
+};
+use firmware_ui::theme::Theme;
+use platform::refresh_policy::{PanelState, RefreshReason};
+use platform::RefreshMode;
+use ui::now_playing::NowPlayingState;
+
+const PORTRAIT: Size = Size::new(480, 800);
+
+/// 4×4 ordered-dither thresholds (0–15), as used by the art cache's ditherer.
+const BAYER: [[u32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Pack a 240×240 grid of 0–3 levels, MSB-first.
+fn pack(level: impl Fn(u32, u32) -> u8) -> Vec<u8> {
+    let mut art = vec![0u8; ART_BYTES];
+    for y in 0..ART_SIZE {
+        for x in 0..ART_SIZE {
+            let i = (y * ART_SIZE + x) as usize;
+            art[i / 4] |= (level(x, y) & 0x3) << (6 - 2 * (i % 4));
+        }
+    }
+    art
+}
+
+/// Diagonal black→white ramp, Bayer-dithered: exercises all four levels and
+/// the in-between dither patterns a photo cover produces.
+fn gradient_art() -> Vec<u8> {
+    pack(|x, y| {
+        let t = (x + y) * 48 / (2 * (ART_SIZE - 1));
+        ((t + BAYER[(y % 4) as usize][(x % 4) as usize]) / 16).min(3) as u8
+    })
+}
+
+/// Concentric bands: hard edges at every angle, like typographic covers.
+fn rings_art() -> Vec<u8> {
+    pack(|x, y| {
+        let dx = x.abs_diff(ART_SIZE / 2);
+        let dy = y.abs_diff(ART_SIZE / 2);
+        ((dx * dx + dy * dy) / 400 % 4) as u8
+    })
+}
+
+fn mock_state(album_id: Option<u32>) -> NowPlayingState {
+    let mut s = NowPlayingState::default();
+    s.set_playing(true);
+    s.set_duration_ms(240_000);
+    s.set_position_ms(60_000);
+    s.set_album_id(album_id);
+    s
+}
+
+fn render(t: &mut TestEmulator, state: &NowPlayingState, art: Option<&[u8]>) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    render_now_playing_to(
+        &mut **t,
+        &Theme::STANDARD,
+        state,
+        art,
+        |id, ty, pos, size| {
+            regs.push((id.to_owned(), ty.to_owned(), pos, size));
+        },
+    )
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+fn art_only(art: &[u8]) -> TestEmulator {
+    let mut t = TestEmulator::new(ART_SIZE, ART_SIZE);
+    let area = Rectangle::new(Point::zero(), Size::new(ART_SIZE, ART_SIZE));
+    draw_album_art(&mut *t, area, Some(art)).unwrap();
+    t
+}
+
+// ── Rendering ────────────────────────────────────────────────────────────
+
+#[test]
+fn gradient_art_golden() {
+    art_only(&gradient_art())
+        .assert_matches_golden("tests/golden/album_art_gradient.png", 0)
+        .unwrap();
+}
+
+#[test]
+fn rings_art_golden() {
+    art_only(&rings_art())
+        .assert_matches_golden("tests/golden/album_art_rings.png", 0)
+        .unwrap();
+}
+
+#[test]
+fn art_region_is_window_aligned_and_clear_of_controls() {
+    let area = art_region(PORTRAIT).unwrap();
+    assert_eq!(area.size, Size::new(ART_SIZE, ART_SIZE));
+    assert_eq!(area.top_left.x % 8, 0);
+    assert_eq!(area.top_left.y % 8, 0);
+    // Below the progress bar (y 150..162), above the play button (y 720).
+    assert!(area.top_left.y >= 162);
+    assert!(area.top_left.y + ART_SIZE as i32 <= 720);
+}
+
+#[test]
+fn art_region_absent_on_small_screens() {
+    assert_eq!(art_region(Size::new(400, 300)), None);
+
+    let mut t = TestEmulator::new(400, 300);
+    render(&mut t, &mock_state(Some(1)), Some(&gradient_art()));
+    assert!(t.query_by_test_id("now-playing-art").is_none());
+}
+
+#[test]
+fn now_playing_draws_art_in_region() {
+    let art = rings_art();
+    let mut t = TestEmulator::new(PORTRAIT.width, PORTRAIT.height);
+    render(&mut t, &mock_state(Some(7)), Some(&art));
+
+    let c = t.query_by_test_id("now-playing-art").unwrap();
+    assert_eq!(c.component_type, "Image");
+    let area = art_region(PORTRAIT).unwrap();
+    assert_eq!(c.position, (area.top_left.x, area.top_left.y));
+    assert_eq!(c.size, (ART_SIZE, ART_SIZE));
+
+    let reference = art_only(&art);
+    let (ox, oy) = (area.top_left.x as u32, area.top_left.y as u32);
+    for (x, y) in [(0, 0), (120, 120), (37, 201), (239, 239)] {
+        assert_eq!(
+            t.pixel_at(ox + x, oy + y),
+            reference.pixel_at(x, y),
+            "art pixel ({x}, {y})"
+        );
+    }
+}
+
+#[test]
+fn missing_or_truncated_art_draws_placeholder() {
+    let area = art_region(PORTRAIT).unwrap();
+    for art in [None, Some(&gradient_art()[..100])] {
+        let mut t = TestEmulator::new(PORTRAIT.width, PORTRAIT.height);
+        render(&mut t, &mock_state(Some(7)), art);
+        t.assert_has_component("now-playing-art").unwrap();
+        // Bordered tile, never left blank.
+        t.assert_pixel(
+            area.top_left.x as u32,
+            area.top_left.y as u32 + 10,
+            Gray4::BLACK,
+        )
+        .unwrap();
+        t.assert_region_non_uniform(area).unwrap();
+    }
+}
+
+// ── Refresh planning ─────────────────────────────────────────────────────
+
+#[test]
+fn first_frame_refreshes_everything_without_art_window() {
+    let mut planner = NowPlayingRefreshPlanner::new(PORTRAIT);
+    let plan = planner.plan(&mock_state(Some(1)), PORTRAIT, PanelState::UNKNOWN, 0);
+    assert_eq!(plan.screen.mode, RefreshMode::Full);
+    assert_eq!(plan.screen.reason, RefreshReason::FirstFrame);
+    assert_eq!(plan.art, None);
+}
+
+#[test]
+fn album_change_gets_gc16_art_window_and_partial_screen() {
+    let mut planner = NowPlayingRefreshPlanner::new(PORTRAIT);
+    planner.plan(&mock_state(Some(1)), PORTRAIT, PanelState::UNKNOWN, 0);
+
+    let plan = planner.plan(&mock_state(Some(2)), PORTRAIT, PanelState::UNKNOWN, 1_000);
+    assert_eq!(plan.art, art_region(PORTRAIT));
+    assert_ne!(plan.screen.mode, RefreshMode::Full);
+}
+
+#[test]
+fn same_album_only_refreshes_controls() {
+    let mut planner = NowPlayingRefreshPlanner::new(PORTRAIT);
+    planner.plan(&mock_state(Some(1)), PORTRAIT, PanelState::UNKNOWN, 0);
+
+    let plan = planner.plan(&mock_state(Some(1)), PORTRAIT, PanelState::UNKNOWN, 1_000);
+    assert_eq!(plan.art, None);
+    assert_eq!(plan.screen.reason, RefreshReason::Incremental);
+}
+
+#[test]
+fn small_screen_never_plans_art_window() {
+    let size = Size::new(400, 300);
+    let mut planner = NowPlayingRefreshPlanner::new(size);
+    planner.plan(&mock_state(Some(1)), size, PanelState::UNKNOWN, 0);
+    let plan = planner.plan(&mock_state(Some(2)), size, PanelState::UNKNOWN, 1_000);
+    assert_eq!(plan.art, None);
+}
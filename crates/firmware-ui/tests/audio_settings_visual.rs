@@ -0,0 +1,81 @@
+//! Visual tests for the audio settings screen: filter list, selection bar
+//! and applied-filter marker.
+//!
+//! Run: cargo test -p firmware-ui --test audio_settings_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects)]
+#![allow(clippy::cast_sign_loss)]
+
+use eink_testing::TestEmulator;
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use firmware_ui::screens::audio_settings::{filter_rect, render_audio_settings_to, ROW_H};
+use platform::OversamplingFilter;
+use ui::audio_settings::AudioSettings;
+
+const SIZE: Size = Size::new(480, 800);
+
+fn render(t: &mut TestEmulator, settings: &AudioSettings) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    render_audio_settings_to(&mut **t, settings, |id, ty, pos, size| {
+        regs.push((id.to_owned(), ty.to_owned(), pos, size));
+    })
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+fn settings(applied: OversamplingFilter) -> AudioSettings {
+    AudioSettings::new(OversamplingFilter::ALL.len(), applied.index())
+}
+
+#[test]
+fn lists_every_filter() {
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, &settings(OversamplingFilter::default()));
+
+    let list = t.query_by_test_id("audio-filter-list").unwrap();
+    assert_eq!(list.size, (SIZE.width, 7 * ROW_H));
+    let applied = t.query_by_test_id("audio-filter-applied").unwrap();
+    assert_eq!(applied.bounds(), filter_rect(SIZE, 0).unwrap());
+}
+
+#[test]
+fn selection_bar_moves_without_applying() {
+    let mut state = settings(OversamplingFilter::FastRollOffMinimumPhase);
+    state.scroll(2);
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, &state);
+
+    let selected = t.query_by_test_id("audio-filter-selected").unwrap();
+    let rect = filter_rect(SIZE, OversamplingFilter::ApodizingFastRollOff.index()).unwrap();
+    assert_eq!(selected.bounds(), rect);
+    let y = selected.position.1 as u32 + 2;
+    t.assert_pixel(SIZE.width - 2, y, Gray4::new(0x2)).unwrap();
+    t.assert_pixel(SIZE.width - 2, y - ROW_H, Gray4::WHITE)
+        .unwrap();
+
+    let applied = t.query_by_test_id("audio-filter-applied").unwrap();
+    let rect = filter_rect(SIZE, OversamplingFilter::FastRollOffMinimumPhase.index()).unwrap();
+    assert_eq!(applied.bounds(), rect);
+}
+
+#[test]
+fn confirm_moves_applied_marker() {
+    let mut state = settings(OversamplingFilter::default());
+    state.scroll(6);
+    let applied = state.confirm().and_then(OversamplingFilter::from_index);
+    assert_eq!(applied, Some(OversamplingFilter::HybridFastRollOff));
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, &state);
+    let marker = t.query_by_test_id("audio-filter-applied").unwrap();
+    let selected = t.query_by_test_id("audio-filter-selected").unwrap();
+    assert_eq!(marker.bounds(), selected.bounds());
+    assert!(filter_rect(SIZE, 7).is_none());
+}
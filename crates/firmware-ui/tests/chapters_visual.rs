@@ -0,0 +1,95 @@
+//! Visual tests for the chapters screen: selection bar, playing marker and
+//! untitled chapter labels.
+//!
+//! Run: cargo test -p firmware-ui --test chapters_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+
+use eink_testing::TestEmulator;
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use firmware_ui::screens::chapters::{chapter_rect, chapter_rows, render_chapters_to, ROW_H};
+use library::chapters::Chapters;
+use ui::chapters::ChapterList;
+
+const SIZE: Size = Size::new(480, 800);
+
+fn book(count: u32) -> Chapters {
+    let mut chapters = Chapters::new();
+    for i in 0..count {
+        let title = if i == 1 { "" } else { "Part" };
+        chapters.push(i * 600_000, title);
+    }
+    chapters
+}
+
+fn render(t: &mut TestEmulator, chapters: Option<&Chapters>, list: &ChapterList) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    render_chapters_to(&mut **t, chapters, list, |id, ty, pos, size| {
+        regs.push((id.to_owned(), ty.to_owned(), pos, size));
+    })
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+#[test]
+fn selected_row_is_inverted() {
+    let chapters = book(4);
+    let mut list = ChapterList::new(chapters.len(), chapter_rows(SIZE), Some(0));
+    list.scroll(2);
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, Some(&chapters), &list);
+
+    let selected = t.query_by_test_id("chapters-selected").unwrap();
+    assert_eq!(selected.bounds(), chapter_rect(SIZE, &list, 2).unwrap());
+    let y = selected.position.1 as u32 + 2;
+    t.assert_pixel(SIZE.width - 2, y, Gray4::new(0x2)).unwrap();
+    t.assert_pixel(SIZE.width - 2, y + ROW_H, Gray4::WHITE)
+        .unwrap();
+    let playing = t.query_by_test_id("chapters-playing").unwrap();
+    assert_eq!(playing.bounds(), chapter_rect(SIZE, &list, 0).unwrap());
+}
+
+#[test]
+fn list_covers_only_existing_chapters() {
+    let chapters = book(3);
+    let list = ChapterList::new(chapters.len(), chapter_rows(SIZE), None);
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, Some(&chapters), &list);
+
+    let area = t.query_by_test_id("chapters-list").unwrap();
+    assert_eq!(area.size, (SIZE.width, 3 * ROW_H));
+    assert!(t.query_by_test_id("chapters-playing").is_none());
+}
+
+#[test]
+fn long_book_scrolls_to_playing_chapter() {
+    let rows = chapter_rows(SIZE);
+    let chapters = book(rows as u32 + 10);
+    let list = ChapterList::new(chapters.len(), rows, Some(rows + 5));
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, Some(&chapters), &list);
+
+    let playing = t.query_by_test_id("chapters-playing").unwrap();
+    let last = chapter_rect(SIZE, &list, list.top() + rows - 1).unwrap();
+    assert_eq!(playing.bounds(), last);
+}
+
+#[test]
+fn missing_chapters_shows_placeholder() {
+    let list = ChapterList::new(0, chapter_rows(SIZE), None);
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, None, &list);
+    t.assert_has_component("chapters-empty").unwrap();
+    assert!(t.query_by_test_id("chapters-list").is_none());
+}
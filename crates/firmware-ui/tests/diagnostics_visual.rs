@@ -0,0 +1,167 @@
+//! Visual tests for the diagnostics screen: full-screen test patterns and
+//! the results page.
+//!
+//! Run: cargo test -p firmware-ui --test diagnostics_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects)]
+
+use eink_testing::TestEmulator;
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use firmware_ui::screens::diagnostics::{
+    config_error_rect, render_diagnostics_to, row_rect, CONFIG_ERROR_ROWS, ROWS,
+};
+use platform::boot_timing::{BootPhase, BootTimeline};
+use platform::crash_loop::{BootMode, BootRecord, ResetCause};
+use platform::diagnostics::{
+    DiagnosticsReport, GhostingEstimate, RefreshSpec, RefreshTiming, SdHealth, TestPattern,
+};
+use platform::soul_config::SoulConfig;
+use platform::soul_library::{LibraryIntegrity, LibrarySection};
+use ui::diagnostics::{Diagnostics, DiagnosticsStep};
+
+const SIZE: Size = Size::new(480, 800);
+
+fn render(t: &mut TestEmulator, diagnostics: &Diagnostics, report: &DiagnosticsReport) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    render_diagnostics_to(&mut **t, diagnostics, report, |id, ty, pos, size| {
+        regs.push((id.to_owned(), ty.to_owned(), pos, size));
+    })
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+fn timing(pattern: TestPattern, measured_ms: u32) -> RefreshTiming {
+    let mode = pattern.refresh_mode();
+    RefreshTiming {
+        pattern,
+        mode,
+        measured_ms,
+        spec_ms: RefreshSpec::GDEM0397T81P.for_mode(mode),
+    }
+}
+
+#[test]
+fn pattern_steps_fill_the_panel() {
+    let mut diag = Diagnostics::new();
+    let report = DiagnosticsReport::new();
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, &diag, &report);
+    let pattern = t.query_by_test_id("diag-pattern").unwrap();
+    assert_eq!(pattern.bounds().size, SIZE);
+    assert!(t.query_by_test_id("diag-result").is_none());
+    t.assert_pixel(0, 0, Gray4::BLACK).unwrap();
+    t.assert_pixel(8, 0, Gray4::WHITE).unwrap();
+    t.assert_pixel(479, 799, Gray4::BLACK).unwrap();
+
+    assert!(diag.advance());
+    assert!(diag.advance());
+    assert_eq!(diag.step(), DiagnosticsStep::Border);
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, &diag, &report);
+    t.assert_pixel(0, 400, Gray4::BLACK).unwrap();
+    t.assert_pixel(479, 400, Gray4::BLACK).unwrap();
+    t.assert_pixel(8, 400, Gray4::BLACK).unwrap();
+    t.assert_pixel(240, 400, Gray4::WHITE).unwrap();
+}
+
+#[test]
+fn results_page_registers_every_check_row() {
+    let mut diag = Diagnostics::new();
+    while diag.advance() {}
+    let mut report = DiagnosticsReport::new();
+    for pattern in TestPattern::ALL {
+        let spec = RefreshSpec::GDEM0397T81P.for_mode(pattern.refresh_mode());
+        report.record_timing(timing(pattern, spec));
+    }
+    report.ghosting = Some(GhostingEstimate::new(None, 40));
+    report.sd = Some(SdHealth::Ok {
+        bytes: 512,
+        read_ms: 6,
+    });
+    let mut boot = BootTimeline::new();
+    boot.mark(BootPhase::Clocks, 60);
+    boot.mark(BootPhase::FirstFrame, 2_000);
+    report.boot = Some(boot.report());
+    let mut library = LibraryIntegrity::new();
+    library.mark_corrupt(LibrarySection::Browse);
+    report.library = Some(library);
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, &diag, &report);
+    assert!(t.query_by_test_id("diag-pattern").is_none());
+    assert!(!report.passed());
+    for (row, id) in ROWS.into_iter().enumerate() {
+        let label = t.query_by_test_id(id).unwrap();
+        assert_eq!(label.bounds(), row_rect(SIZE, row).unwrap());
+    }
+    assert!(row_rect(SIZE, ROWS.len()).is_none());
+}
+
+#[test]
+fn config_problems_are_listed_under_the_result() {
+    let mut diag = Diagnostics::new();
+    while diag.advance() {}
+    let mut report = DiagnosticsReport::new();
+    report.config = Some(SoulConfig::parse(
+        "[audio]\nmax_volume = 300\n[ui]\nfull_refresh_every = 4\ncolour = 1\n",
+    ));
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, &diag, &report);
+    assert!(t.query_by_test_id("diag-config").is_some());
+    for (index, id) in CONFIG_ERROR_ROWS.into_iter().enumerate() {
+        let row = t.query_by_test_id(id);
+        if index < 2 {
+            assert_eq!(
+                row.unwrap().bounds(),
+                config_error_rect(SIZE, index).unwrap()
+            );
+        } else {
+            assert!(row.is_none(), "{id}");
+        }
+    }
+    let last = config_error_rect(SIZE, CONFIG_ERROR_ROWS.len() - 1).unwrap();
+    assert!(last.bottom_right().unwrap().y < 800);
+}
+
+#[test]
+fn safe_mode_retitles_the_results_page() {
+    let mut diag = Diagnostics::new();
+    while diag.advance() {}
+    let mut report = DiagnosticsReport::new();
+    report.boot_guard = Some(BootRecord {
+        failures: 0,
+        reset: ResetCause::PowerOn,
+        mode: BootMode::Normal,
+    });
+    let mut normal = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut normal, &diag, &report);
+    assert!(report.passed());
+
+    report.boot_guard = Some(BootRecord {
+        failures: 3,
+        reset: ResetCause::Watchdog,
+        mode: BootMode::Safe,
+    });
+    let mut safe = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut safe, &diag, &report);
+    assert!(!report.passed());
+    let row = safe.query_by_test_id("diag-boot-guard").unwrap();
+    let index = ROWS.iter().position(|&id| id == "diag-boot-guard").unwrap();
+    assert_eq!(row.bounds(), row_rect(SIZE, index).unwrap());
+
+    // "Safe mode" is shorter than "Diagnostics": less white text in the bar.
+    let header = Rectangle::new(Point::zero(), Size::new(SIZE.width, 48));
+    assert!(
+        safe.pixel_count_of_color(header, Gray4::WHITE)
+            < normal.pixel_count_of_color(header, Gray4::WHITE)
+    );
+}
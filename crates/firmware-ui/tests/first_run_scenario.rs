@@ -0,0 +1,449 @@
+//! First-run scenario: a brand new device from first boot to standby.
+//!
+//! The card holds fixture music and no `soul.toml` or `/soul` library, so
+//! the device boots with default settings, scans the card with the scan
+//! screen up, opens the new library, plays a track and goes to standby.
+//! Screens are checked against goldens; refresh counts and display energy
+//! over the whole run are checked against budgets.
+//!
+//! Time comes from a [`VirtualClock`]: refresh durations and the standby
+//! period advance it, so the run takes no wall-clock time.
+//!
+//! Run: cargo test -p firmware-ui --test first_run_scenario
+//! Regenerate goldens: UPDATE_GOLDEN=1 cargo test -p firmware-ui --test first_run_scenario
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects, clippy::indexing_slicing)]
+
+use eink_emulator::{DisplayDriver, VirtualClock, WaveformMode};
+use eink_testing::property::RefreshBudget;
+use eink_testing::TestEmulator;
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+use embedded_graphics::prelude::*;
+use firmware_ui::playback_events;
+use firmware_ui::screens::library_scan::render_library_scan_to;
+use firmware_ui::screens::now_playing::{render_now_playing_to, NowPlayingRefreshPlanner};
+use firmware_ui::theme::Theme;
+use library::writer::LibraryWriter;
+use library::{
+    detect_format, sort_key_for, AudioFormat, FatAttributes, ReaderError, ScanCancel, ScanFilter,
+    ScanProgress, ScanSession, Scanner, SoulLibraryReader, TrackMeta,
+};
+use platform::refresh_policy::{
+    ContentHint, PanelState, RefreshPolicy, RefreshPolicyConfig, Update,
+};
+use platform::soul_config::{self, SoulConfig, CONFIG_PATH, MAX_CONFIG_BYTES};
+use platform::storage_fixture::{FixtureBuilder, TrackTags};
+use platform::storage_mem::{MemoryStorageError, MemoryVolume};
+use platform::{File, RefreshMode, Storage};
+use playback::engine::PlaybackEngine;
+use playback::events::{PlaybackEventBus, PlaybackEventPublisher, PlaybackEventSubscriber};
+use ui::library_scan::LibraryScan;
+use ui::navigation::Navigator;
+use ui::now_playing::NowPlayingState;
+use ui::screen::Screen;
+
+const SIZE: Size = Size::new(480, 800);
+const SOUL_ROOT: &str = "/soul";
+const MUSIC_ROOT: &str = "/Music";
+
+/// Virtual time spent reading and parsing one file during the scan.
+const SCAN_FILE_MS: u64 = 40;
+/// Time the track plays before it is paused.
+const PLAY_MS: u64 = 3 * 60 * 1000;
+/// Time the device sits in standby.
+const STANDBY_MS: u64 = 30 * 60 * 1000;
+
+/// Whole run: two flashes — the first frame and the GC16 window over the
+/// new album art.  Scan progress, Now Playing and the paused redraw all go
+/// out as partials.
+const RUN_BUDGET: RefreshBudget = RefreshBudget::new(2, 8);
+/// Display energy for the whole run, standby included (measured: 964).
+const RUN_ENERGY_UWH: u64 = 1_200;
+
+/// A fresh card: three albums in the three fixture containers, plus the
+/// clutter a real card carries (cover art, a macOS resource fork, a
+/// system folder).
+fn fresh_card() -> MemoryVolume {
+    FixtureBuilder::new()
+        .track(
+            "/Music/Portishead/Dummy/01 Mysterons.flac",
+            &TrackTags::new("Mysterons")
+                .artist("Portishead")
+                .album("Dummy")
+                .track(1),
+        )
+        .track(
+            "/Music/Portishead/Dummy/02 Sour Times.flac",
+            &TrackTags::new("Sour Times")
+                .artist("Portishead")
+                .album("Dummy")
+                .track(2),
+        )
+        .track(
+            "/Music/Portishead/Dummy/03 Strangers.flac",
+            &TrackTags::new("Strangers")
+                .artist("Portishead")
+                .album("Dummy")
+                .track(3),
+        )
+        .file(
+            "/Music/Portishead/Dummy/cover.jpg",
+            [0xFF, 0xD8, 0xFF, 0xE0],
+        )
+        .file("/Music/Portishead/Dummy/._01 Mysterons.flac", [0u8; 16])
+        .track(
+            "/Music/Boards of Canada/Geogaddi/01 Ready Lets Go.mp3",
+            &TrackTags::new("Ready Lets Go")
+                .artist("Boards of Canada")
+                .album("Geogaddi")
+                .track(1),
+        )
+        .track(
+            "/Music/Boards of Canada/Geogaddi/02 Music Is Math.mp3",
+            &TrackTags::new("Music Is Math")
+                .artist("Boards of Canada")
+                .album("Geogaddi")
+                .track(2),
+        )
+        .track(
+            "/Music/Massive Attack/Mezzanine/01 Angel.wav",
+            &TrackTags::new("Angel")
+                .artist("Massive Attack")
+                .album("Mezzanine")
+                .track(1),
+        )
+        .file("/System Volume Information/IndexerVolumeGuid", [0u8; 8])
+        .build()
+        .unwrap()
+}
+
+/// Perform the refresh the policy chose.
+async fn refresh(t: &mut TestEmulator, mode: RefreshMode) {
+    match mode {
+        RefreshMode::Full => t.refresh_full().await.unwrap(),
+        RefreshMode::Partial => t.refresh_partial().await.unwrap(),
+        RefreshMode::Fast => t.refresh_fast().await.unwrap(),
+    }
+}
+
+fn render_scan(t: &mut TestEmulator, progress: &ScanProgress, scan: &LibraryScan) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    t.clear_components();
+    render_library_scan_to(&mut **t, progress, scan, |id, ty, pos, size| {
+        regs.push((id.to_owned(), ty.to_owned(), pos, size));
+    })
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+fn render_now_playing(t: &mut TestEmulator, state: &NowPlayingState) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    t.clear_components();
+    render_now_playing_to(
+        &mut **t,
+        &Theme::STANDARD,
+        state,
+        None,
+        |id, ty, pos, size| {
+            regs.push((id.to_owned(), ty.to_owned(), pos, size));
+        },
+    )
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+/// Format code stored in `TrackMeta::format`, as written by the
+/// `scan-library` xtask.
+fn format_code(format: AudioFormat) -> u8 {
+    match format {
+        AudioFormat::Flac => 0,
+        AudioFormat::Mp3 => 1,
+        AudioFormat::Wav => 2,
+        AudioFormat::Aac => 3,
+        AudioFormat::Opus => 4,
+    }
+}
+
+/// Metadata for the track at `path`, laid out `/Music/<artist>/<album>/NN
+/// <title>.<ext>`.  There is no tag parser yet, so — like the xtask — the
+/// path is the source of truth.
+fn meta_from_path(path: &str, format: AudioFormat, soul_id: u32, album_id: u32) -> TrackMeta {
+    let mut parts = path.trim_start_matches('/').split('/').skip(1);
+    let artist = parts.next().unwrap();
+    let album = parts.next().unwrap();
+    let file = parts.next().unwrap();
+    let stem = file.rsplit_once('.').map_or(file, |(stem, _)| stem);
+    let (number, title) = stem.split_once(' ').unwrap();
+    TrackMeta {
+        soul_id,
+        album_id,
+        track_number: number.parse().unwrap(),
+        disc_number: 1,
+        year: 0,
+        format: format_code(format),
+        channels: 2,
+        duration_secs: 0,
+        sample_rate: 44_100,
+        title: title.try_into().unwrap(),
+        artist: artist.try_into().unwrap(),
+        album: album.try_into().unwrap(),
+        file_path: path.try_into().unwrap(),
+        loudness: None,
+    }
+}
+
+/// The first bytes of `path`, enough for every codec's sniffer.
+async fn read_header(card: &mut MemoryVolume, path: &str) -> Vec<u8> {
+    let mut file = card.open_file(path).await.unwrap();
+    let mut head = [0u8; 64];
+    let n = file.read(&mut head).await.unwrap();
+    head[..n].to_vec()
+}
+
+#[tokio::test]
+async fn first_run_from_fresh_card_to_standby() {
+    let mut card = fresh_card();
+    let clock = VirtualClock::new();
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    t.set_virtual_clock(clock.clone());
+    let before = t.refresh_counts();
+    let mut nav = Navigator::new();
+
+    // ── Boot: no settings file, so everything is default ────────────────
+    let mut buf = vec![0u8; MAX_CONFIG_BYTES];
+    let loaded = soul_config::load(&mut card, CONFIG_PATH, &mut buf)
+        .await
+        .unwrap();
+    assert!(loaded.is_none(), "fresh card must not carry soul.toml");
+    let config = SoulConfig::default();
+    let mut policy_config = RefreshPolicyConfig::DEFAULT;
+    config.apply_to_refresh_policy(&mut policy_config);
+    let mut policy = RefreshPolicy::with_config(SIZE.width * SIZE.height, policy_config);
+
+    // No library on the card yet: the device goes straight to the scan.
+    match SoulLibraryReader::open(card.clone(), SOUL_ROOT).await {
+        Err(ReaderError::Storage(MemoryStorageError::NotFound)) => {}
+        Err(e) => panic!("expected a missing manifest, got {e:?}"),
+        Ok(_) => panic!("fresh card must not carry a library"),
+    }
+    nav.replace(Screen::LibraryScan);
+
+    // ── Scan: walk the card, redrawing the progress after each folder ──
+    let filter = ScanFilter::with_config(&config.library);
+    let cancel = ScanCancel::new();
+    let mut scan = LibraryScan::new();
+    let mut session = ScanSession::new(&cancel, |_| {});
+    let mut found: Vec<(AudioFormat, String)> = Vec::new();
+
+    render_scan(&mut t, &session.progress(), &scan);
+    let first = policy.decide(
+        Update::new(SIZE.width * SIZE.height, ContentHint::Text),
+        PanelState::UNKNOWN,
+        clock.now_ms(),
+    );
+    assert_eq!(first.mode, RefreshMode::Full);
+    refresh(&mut t, first.mode).await;
+
+    let mut dirs = vec![MUSIC_ROOT.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        session.enter_dir().unwrap();
+        let entries = card.read_dir(&dir).unwrap();
+        let mut parsed_here = false;
+        for entry in entries.iter().rev() {
+            if filter.is_excluded(&entry.path, FatAttributes(0)) {
+                continue;
+            }
+            if entry.is_dir {
+                dirs.push(entry.path.clone());
+                continue;
+            }
+            let ext = entry.name.rsplit_once('.').map_or("", |(_, ext)| ext);
+            let Some(by_name) = Scanner::format_for_extension(ext) else {
+                continue;
+            };
+            let head = read_header(&mut card, &entry.path).await;
+            clock.advance(SCAN_FILE_MS);
+            session.file_parsed().unwrap();
+            parsed_here = true;
+            // Sniffing and the extension use the same codec registry.
+            assert_eq!(detect_format(&head), Some(by_name), "{}", entry.path);
+            session.track_added().unwrap();
+            found.push((by_name, entry.path.clone()));
+        }
+        if parsed_here {
+            render_scan(&mut t, &session.progress(), &scan);
+            let choice = policy.decide(
+                Update::new(SIZE.width * 120, ContentHint::Text),
+                PanelState::UNKNOWN,
+                clock.now_ms(),
+            );
+            assert_eq!(choice.mode, RefreshMode::Partial);
+            refresh(&mut t, choice.mode).await;
+        }
+    }
+
+    let progress = session.finish();
+    assert_eq!(
+        progress,
+        ScanProgress {
+            dirs_visited: 7,
+            files_parsed: 6,
+            tracks_added: 6,
+        }
+    );
+    scan.finish(false);
+    render_scan(&mut t, &progress, &scan);
+    assert!(t.query_by_test_id("scan-cancel").is_none());
+    let done = policy.decide(
+        Update::new(SIZE.width * 200, ContentHint::Text),
+        PanelState::UNKNOWN,
+        clock.now_ms(),
+    );
+    refresh(&mut t, done.mode).await;
+    t.assert_matches_golden("tests/golden/first_run_scan.png", 5)
+        .unwrap();
+
+    // Write the library in sort-key order and put it on the card.
+    let mut albums: Vec<String> = Vec::new();
+    let mut metas: Vec<([u8; 16], TrackMeta)> = Vec::new();
+    for (soul_id, (format, path)) in (1u32..).zip(&found) {
+        let album_dir = path.rsplit_once('/').unwrap().0.to_owned();
+        let album_id = match albums.iter().position(|a| *a == album_dir) {
+            Some(i) => u32::try_from(i + 1).unwrap(),
+            None => {
+                albums.push(album_dir);
+                u32::try_from(albums.len()).unwrap()
+            }
+        };
+        let meta = meta_from_path(path, *format, soul_id, album_id);
+        let key = sort_key_for(
+            &meta.artist,
+            &meta.album,
+            meta.track_number,
+            meta.disc_number,
+        );
+        metas.push((key, meta));
+    }
+    metas.sort_by_key(|(key, _)| *key);
+    let host = tempfile::TempDir::new().unwrap();
+    let root = host.path().join("soul");
+    let mut writer = LibraryWriter::new(root.to_str().unwrap()).unwrap();
+    for (key, meta) in metas {
+        writer.add_track(key, meta).unwrap();
+    }
+    writer
+        .finish(u32::try_from(albums.len()).unwrap(), 0)
+        .unwrap();
+    card.import_dir(&root, SOUL_ROOT).unwrap();
+
+    // ── Browse: the new library opens, sorted artist → album → track ───
+    nav.replace(Screen::LibraryBrowse);
+    assert_eq!(nav.current(), Screen::LibraryBrowse);
+    let mut reader = SoulLibraryReader::open(card.clone(), SOUL_ROOT)
+        .await
+        .unwrap();
+    assert_eq!(reader.track_count(), 6);
+    let tracks = reader.page(0, 6).await.unwrap();
+    let titles: Vec<&str> = tracks.iter().map(|m| m.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        [
+            "Ready Lets Go",
+            "Music Is Math",
+            "Angel",
+            "Mysterons",
+            "Sour Times",
+            "Strangers",
+        ]
+    );
+    // There is no browse renderer yet; the list is checked through the
+    // reader and the scan screen is the last thing drawn before playback.
+
+    // ── Play: pick Mysterons, open it and show Now Playing ──────────────
+    let track = tracks.iter().find(|m| m.title == "Mysterons").unwrap();
+    let head = read_header(&mut card, &track.file_path).await;
+    let format = detect_format(&head).unwrap();
+    assert_eq!(format, AudioFormat::Flac);
+    let info = format.info().unwrap();
+    assert!(info.matches_extension("flac"));
+
+    // Now Playing follows the engine through the event bus.
+    let bus = PlaybackEventBus::<NoopRawMutex>::new();
+    let publisher = PlaybackEventPublisher::new(&bus);
+    let mut events = PlaybackEventSubscriber::new(&bus).unwrap();
+    let mut engine = PlaybackEngine::new();
+    engine.load_track(track.soul_id, 300_000);
+    engine.play().unwrap();
+    engine.publish_events(&publisher);
+
+    let mut state = NowPlayingState::default();
+    let started =
+        playback_events::apply_all(&mut state, core::iter::from_fn(|| events.try_next_event()));
+    assert_eq!(started, Some(track.soul_id));
+    state.title.clone_from(&track.title);
+    state.artist.clone_from(&track.artist);
+    state.set_album_id(Some(track.album_id));
+    state.set_volume(60);
+    assert!(state.playing);
+    assert_eq!(state.duration_ms, 300_000);
+    nav.push(Screen::NowPlaying);
+    assert_eq!(nav.current(), Screen::NowPlaying);
+
+    let mut planner = NowPlayingRefreshPlanner::with_policy(policy);
+    render_now_playing(&mut t, &state);
+    let plan = planner.plan(&state, SIZE, PanelState::UNKNOWN, clock.now_ms());
+    if plan.art.is_some() {
+        t.refresh_with_waveform(WaveformMode::GC16).await.unwrap();
+    }
+    refresh(&mut t, plan.screen.mode).await;
+    t.assert_matches_golden("tests/golden/first_run_now_playing.png", 5)
+        .unwrap();
+
+    // ── Standby: pause after a few minutes, redraw, sleep the panel ─────
+    for _ in 0..PLAY_MS / 1_000 {
+        clock.advance(1_000);
+        engine.advance_ms(1_000);
+        engine.publish_events(&publisher);
+        playback_events::apply_all(&mut state, core::iter::from_fn(|| events.try_next_event()));
+    }
+    engine.pause().unwrap();
+    engine.publish_events(&publisher);
+    playback_events::apply_all(&mut state, core::iter::from_fn(|| events.try_next_event()));
+    assert!(!state.playing);
+    assert_eq!(state.position_ms, PLAY_MS);
+    assert_eq!(events.lagged(), 0);
+    render_now_playing(&mut t, &state);
+    let plan = planner.plan(&state, SIZE, PanelState::UNKNOWN, clock.now_ms());
+    assert!(plan.art.is_none(), "same album, art stays on the panel");
+    assert_eq!(plan.screen.mode, RefreshMode::Partial);
+    refresh(&mut t, plan.screen.mode).await;
+
+    t.sleep().await.unwrap();
+    clock.advance(STANDBY_MS);
+    // E-ink keeps the last image with the controller asleep.
+    t.assert_matches_golden("tests/golden/first_run_standby.png", 5)
+        .unwrap();
+    t.wake().await.unwrap();
+
+    // ── Budgets over the whole run ──────────────────────────────────────
+    RUN_BUDGET.check(t.refresh_counts().since(before)).unwrap();
+    let power = t.power_stats();
+    assert!(
+        power.sleep_time_ms >= STANDBY_MS,
+        "asleep {} ms, expected at least {STANDBY_MS}",
+        power.sleep_time_ms
+    );
+    assert!(
+        power.total_energy_uwh <= RUN_ENERGY_UWH,
+        "{} µWh exceeds the budget of {RUN_ENERGY_UWH}",
+        power.total_energy_uwh
+    );
+}
@@ -0,0 +1,95 @@
+//! Visual tests for the library scan screen: progress counts, status line
+//! and cancel button.
+//!
+//! Run: cargo test -p firmware-ui --test library_scan_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects)]
+#![allow(clippy::cast_sign_loss)]
+
+use eink_testing::TestEmulator;
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use firmware_ui::screens::library_scan::{
+    cancel_rect, count_rect, render_library_scan_to, status_rect,
+};
+use library::{ScanCancel, ScanProgress, ScanSession};
+use ui::library_scan::LibraryScan;
+
+const SIZE: Size = Size::new(480, 800);
+
+fn render(t: &mut TestEmulator, progress: &ScanProgress, scan: &LibraryScan) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    render_library_scan_to(&mut **t, progress, scan, |id, ty, pos, size| {
+        regs.push((id.to_owned(), ty.to_owned(), pos, size));
+    })
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+#[test]
+fn shows_count_rows_and_cancel_button_while_scanning() {
+    let progress = ScanProgress {
+        dirs_visited: 12,
+        files_parsed: 140,
+        tracks_added: 138,
+    };
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, &progress, &LibraryScan::new());
+
+    for (row, id) in ["scan-folders", "scan-files", "scan-tracks"]
+        .into_iter()
+        .enumerate()
+    {
+        let label = t.query_by_test_id(id).unwrap();
+        assert_eq!(label.bounds(), count_rect(SIZE, row).unwrap());
+    }
+    assert!(count_rect(SIZE, 3).is_none());
+
+    let status = t.query_by_test_id("scan-status").unwrap();
+    assert_eq!(status.bounds(), status_rect(SIZE));
+
+    let button = t.query_by_test_id("scan-cancel").unwrap();
+    let rect = cancel_rect(SIZE);
+    assert_eq!(button.bounds(), rect);
+    let left = rect.top_left.x as u32;
+    let top = rect.top_left.y as u32;
+    t.assert_pixel(left, top + 10, Gray4::BLACK).unwrap();
+    t.assert_pixel(left - 2, top + 10, Gray4::WHITE).unwrap();
+}
+
+#[test]
+fn cancel_hides_button_and_keeps_progress() {
+    let cancel = ScanCancel::new();
+    let mut scan = LibraryScan::new();
+    let mut session = ScanSession::new(&cancel, |_| {});
+    session.enter_dir().unwrap();
+    session.file_parsed().unwrap();
+    session.track_added().unwrap();
+
+    if scan.request_cancel() {
+        cancel.cancel();
+    }
+    assert!(session.file_parsed().is_err());
+    let progress = session.finish();
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, &progress, &scan);
+    assert!(t.query_by_test_id("scan-cancel").is_none());
+
+    scan.finish(true);
+    render(&mut t, &progress, &scan);
+    assert!(t.query_by_test_id("scan-cancel").is_none());
+    let rect = cancel_rect(SIZE);
+    t.assert_pixel(
+        rect.top_left.x as u32,
+        rect.top_left.y as u32 + 10,
+        Gray4::WHITE,
+    )
+    .unwrap();
+    assert_eq!(progress.tracks_added, 1);
+}
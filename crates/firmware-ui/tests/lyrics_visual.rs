@@ -0,0 +1,111 @@
+//! Visual tests for the lyrics screen: highlight placement and the partial
+//! redraw areas reported as playback moves on.
+//!
+//! Run: cargo test -p firmware-ui --test lyrics_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects)]
+#![allow(clippy::cast_sign_loss)]
+
+use eink_testing::TestEmulator;
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use firmware_ui::screens::lyrics::{
+    lyrics_area, lyrics_rows, redraw_lyrics, render_lyrics_to, row_rect, LINE_H,
+};
+use library::lyrics::Lyrics;
+use ui::lyrics::{LyricsRedraw, LyricsView};
+
+const SIZE: Size = Size::new(480, 800);
+
+const LRC: &str = "[ti:Test]\n\
+                   [00:01.00]One\n\
+                   [00:02.00]Two\n\
+                   [00:03.00]Three\n\
+                   [00:04.00]Four\n\
+                   [00:05.00]Five\n";
+
+fn render(t: &mut TestEmulator, lyrics: Option<&Lyrics>, view: &LyricsView) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    render_lyrics_to(&mut **t, lyrics, view, |id, ty, pos, size| {
+        regs.push((id.to_owned(), ty.to_owned(), pos, size));
+    })
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+#[test]
+fn current_line_is_highlighted() {
+    let lyrics: Lyrics = Lyrics::parse(LRC).unwrap();
+    let mut view = LyricsView::new(lyrics_rows(SIZE));
+    view.follow(lyrics.line_at(2_500));
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, Some(&lyrics), &view);
+
+    let current = t.query_by_test_id("lyrics-current").unwrap();
+    assert_eq!(current.bounds(), row_rect(SIZE, &view, 1).unwrap());
+    // Highlight bar is dark at its right edge (past any text).
+    let y = current.position.1 as u32 + 2;
+    t.assert_pixel(SIZE.width - 2, y, Gray4::new(0x2)).unwrap();
+    // The next row is not highlighted.
+    t.assert_pixel(SIZE.width - 2, y + LINE_H, Gray4::WHITE)
+        .unwrap();
+}
+
+#[test]
+fn highlight_move_refreshes_only_two_rows() {
+    let lyrics: Lyrics = Lyrics::parse(LRC).unwrap();
+    let mut view = LyricsView::new(lyrics_rows(SIZE));
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    view.follow(lyrics.line_at(1_000));
+    render(&mut t, Some(&lyrics), &view);
+
+    let redraw = view.follow(lyrics.line_at(2_000));
+    let area = redraw_lyrics(&mut *t, &lyrics, &view, redraw)
+        .unwrap()
+        .unwrap();
+    assert_eq!(area.top_left, row_rect(SIZE, &view, 0).unwrap().top_left);
+    assert_eq!(area.size, Size::new(SIZE.width, 2 * LINE_H));
+    assert_eq!(area.top_left.y % 8, 0, "window aligned to 8 px");
+
+    // Same line again: nothing to refresh.
+    let redraw = view.follow(lyrics.line_at(2_500));
+    assert_eq!(
+        redraw_lyrics(&mut *t, &lyrics, &view, redraw).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn page_turn_refreshes_lyrics_area() {
+    let rows = lyrics_rows(SIZE);
+    let lrc: String = (0..rows + 5)
+        .map(|i| format!("[00:{i:02}.00]Line {i}\n"))
+        .collect();
+    let lyrics: Lyrics = Lyrics::parse(&lrc).unwrap();
+    let mut view = LyricsView::new(rows);
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    view.follow(Some(rows - 1));
+    render(&mut t, Some(&lyrics), &view);
+
+    let redraw = view.follow(Some(rows));
+    assert_eq!(redraw, LyricsRedraw::Page);
+    let area = redraw_lyrics(&mut *t, &lyrics, &view, redraw)
+        .unwrap()
+        .unwrap();
+    assert_eq!(area, lyrics_area(SIZE));
+}
+
+#[test]
+fn missing_lyrics_shows_placeholder() {
+    let view = LyricsView::new(lyrics_rows(SIZE));
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, None, &view);
+    t.assert_has_component("lyrics-empty").unwrap();
+    assert!(t.query_by_test_id("lyrics-area").is_none());
+}
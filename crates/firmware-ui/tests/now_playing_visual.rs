@@ -5,10 +5,12 @@
 
 // Test file — unwrap/expect/panic acceptable in test code.
 #![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::cast_sign_loss)]
 
 use eink_testing::TestEmulator;
 use embedded_graphics::pixelcolor::Gray4;
 use embedded_graphics::prelude::*;
+use firmware_ui::theme::Theme;
 use ui::now_playing::NowPlayingState;
 
 /// Helper: build a standard test state
@@ -27,11 +29,17 @@ fn mock_state() -> NowPlayingState {
 /// the double-borrow problem (drawing borrows via DerefMut, registration
 /// borrows TestEmulator directly).
 fn render(t: &mut TestEmulator, state: &NowPlayingState) {
+    render_themed(t, &Theme::STANDARD, state);
+}
+
+fn render_themed(t: &mut TestEmulator, theme: &Theme, state: &NowPlayingState) {
     #[allow(clippy::type_complexity)]
     let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
     firmware_ui::screens::now_playing::render_now_playing_to(
         &mut **t,
+        theme,
         state,
+        None,
         |id, ty, pos, size| {
             regs.push((id.to_owned(), ty.to_owned(), pos, size));
         },
@@ -141,3 +149,56 @@ fn now_playing_golden_screenshot() {
     t.assert_matches_golden("tests/golden/now_playing.png", 5)
         .unwrap();
 }
+
+#[test]
+fn now_playing_accessible_uses_large_high_contrast_text() {
+    let mut t = TestEmulator::new(400, 300);
+    render_themed(&mut t, &Theme::ACCESSIBLE, &mock_state());
+    let title = t.query_by_test_id("now-playing-title").unwrap();
+    assert!(title.size.1 >= 40, "title should be double height");
+    // Header and progress fill are black rather than dark grey.
+    t.assert_pixel(399, 2, Gray4::BLACK).unwrap();
+    let bar = t.query_by_test_id("now-playing-progress").unwrap();
+    t.assert_pixel(
+        bar.position.0 as u32 + 4,
+        bar.position.1 as u32 + 4,
+        Gray4::BLACK,
+    )
+    .unwrap();
+    // The art region is unaffected, so the bar stays where it was.
+    let mut standard = TestEmulator::new(400, 300);
+    render(&mut standard, &mock_state());
+    let standard_bar = standard.query_by_test_id("now-playing-progress").unwrap();
+    assert_eq!(bar.position, standard_bar.position);
+}
+
+#[test]
+fn now_playing_accessible_golden_screenshot() {
+    let mut t = TestEmulator::new(400, 300);
+    render_themed(&mut t, &Theme::ACCESSIBLE, &mock_state());
+    t.assert_matches_golden("tests/golden/now_playing_accessible.png", 5)
+        .unwrap();
+}
+
+#[test]
+fn now_playing_skeleton_while_metadata_loads() {
+    let mut state = mock_state();
+    state.set_loading(true);
+    for theme in [&Theme::STANDARD, &Theme::ACCESSIBLE] {
+        let mut t = TestEmulator::new(400, 300);
+        render_themed(&mut t, theme, &state);
+        let title = t.query_by_test_id("now-playing-title").unwrap();
+        let artist = t.query_by_test_id("now-playing-artist").unwrap();
+        // Grey bars stand in for both labels, left-aligned like the text.
+        for label in [title, artist] {
+            t.assert_pixel(
+                label.position.0 as u32 + 2,
+                label.position.1 as u32 + label.size.1 / 2,
+                Gray4::new(0xC),
+            )
+            .unwrap();
+        }
+        t.assert_has_component("now-playing-progress").unwrap();
+        t.assert_has_component("now-playing-play-btn").unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+//! Visual tests for the queue screen: cursor bar, select-mode check boxes
+//! and the batch action footer, in the standard and accessibility themes.
+//!
+//! Run: cargo test -p firmware-ui --test queue_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects)]
+#![allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+
+use eink_testing::TestEmulator;
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use firmware_ui::screens::queue::{footer_rect, queue_rect, queue_rows, render_queue_to};
+use firmware_ui::theme::Theme;
+use ui::queue::{BatchAction, QueueView};
+
+const SIZE: Size = Size::new(480, 800);
+const TITLES: [&str; 5] = ["Mysterons", "Sour Times", "", "Strangers", "Roads"];
+const THEME: &Theme = &Theme::STANDARD;
+const ACCESSIBLE: &Theme = &Theme::ACCESSIBLE;
+
+fn render(t: &mut TestEmulator, theme: &Theme, view: &QueueView) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    render_queue_to(&mut **t, theme, &TITLES, view, |id, ty, pos, size| {
+        regs.push((id.to_owned(), ty.to_owned(), pos, size));
+    })
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+#[test]
+fn browse_mode_has_no_footer() {
+    let view = QueueView::new(TITLES.len(), queue_rows(SIZE, THEME), Some(1));
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &view);
+
+    let list = t.query_by_test_id("queue-list").unwrap();
+    assert_eq!(list.size, (SIZE.width, 5 * THEME.row_h));
+    let cursor = t.query_by_test_id("queue-cursor").unwrap();
+    assert_eq!(cursor.bounds(), queue_rect(SIZE, THEME, &view, 1).unwrap());
+    assert!(t.query_by_test_id("queue-footer").is_none());
+    t.assert_pixel(SIZE.width - 2, SIZE.height - 2, Gray4::WHITE)
+        .unwrap();
+}
+
+#[test]
+fn select_mode_shows_footer_without_reflowing_rows() {
+    let mut view = QueueView::new(TITLES.len(), queue_rows(SIZE, THEME), Some(0));
+    view.hold();
+    view.scroll(2);
+    view.click();
+    view.cycle_action();
+    assert_eq!(view.action(), BatchAction::FavoriteAlbums);
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &view);
+    let footer = t.query_by_test_id("queue-footer").unwrap();
+    assert_eq!(footer.bounds(), footer_rect(SIZE, THEME));
+    t.assert_pixel(SIZE.width - 2, SIZE.height - 2, Gray4::new(0x2))
+        .unwrap();
+
+    let last_row = THEME.list_top + queue_rows(SIZE, THEME) as u32 * THEME.row_h;
+    assert!(last_row <= SIZE.height - footer.size.1);
+    let cursor = t.query_by_test_id("queue-cursor").unwrap();
+    assert_eq!(cursor.bounds(), queue_rect(SIZE, THEME, &view, 2).unwrap());
+}
+
+#[test]
+fn empty_queue_shows_message() {
+    let view = QueueView::new(0, queue_rows(SIZE, THEME), None);
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &view);
+    assert!(t.query_by_test_id("queue-empty").is_some());
+    assert!(t.query_by_test_id("queue-list").is_none());
+}
+
+/// Select mode with one mark, shared by the golden tests.
+fn select_view(theme: &Theme) -> QueueView {
+    let mut view = QueueView::new(TITLES.len(), queue_rows(SIZE, theme), Some(1));
+    view.hold();
+    view.scroll(3);
+    view.click();
+    view
+}
+
+#[test]
+fn accessible_rows_are_twice_as_tall() {
+    let standard = queue_rows(SIZE, THEME);
+    let accessible = queue_rows(SIZE, ACCESSIBLE);
+    assert!(accessible < standard);
+    assert_eq!(accessible, 8);
+
+    let view = QueueView::new(TITLES.len(), accessible, Some(0));
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, ACCESSIBLE, &view);
+    let cursor = t.query_by_test_id("queue-cursor").unwrap();
+    assert_eq!(cursor.size, (SIZE.width, 2 * THEME.row_h));
+    assert_eq!(
+        cursor.bounds(),
+        queue_rect(SIZE, ACCESSIBLE, &view, 0).unwrap()
+    );
+    // High contrast: the cursor bar and header are black, not dark grey.
+    t.assert_pixel(SIZE.width - 2, cursor.position.1 as u32 + 2, Gray4::BLACK)
+        .unwrap();
+    t.assert_pixel(SIZE.width - 2, 2, Gray4::BLACK).unwrap();
+}
+
+#[test]
+fn accessible_footer_fits_below_last_row() {
+    let view = select_view(ACCESSIBLE);
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, ACCESSIBLE, &view);
+    let footer = t.query_by_test_id("queue-footer").unwrap();
+    assert_eq!(footer.bounds(), footer_rect(SIZE, ACCESSIBLE));
+    let last_row = ACCESSIBLE.list_top + queue_rows(SIZE, ACCESSIBLE) as u32 * ACCESSIBLE.row_h;
+    assert!(last_row <= footer.position.1 as u32);
+}
+
+#[test]
+fn queue_golden_standard() {
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &select_view(THEME));
+    t.assert_matches_golden("tests/golden/queue.png", 5)
+        .unwrap();
+}
+
+#[test]
+fn queue_golden_accessible() {
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, ACCESSIBLE, &select_view(ACCESSIBLE));
+    t.assert_matches_golden("tests/golden/queue_accessible.png", 5)
+        .unwrap();
+}
@@ -0,0 +1,271 @@
+//! Visual tests for the quick menu: profile list, selection bar, active
+//! profile marker, speed and night mode rows, in the standard and
+//! accessibility themes.
+//!
+//! Run: cargo test -p firmware-ui --test quick_menu_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects, clippy::indexing_slicing)]
+#![allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+
+use eink_testing::TestEmulator;
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use firmware_ui::screens::quick_menu::{
+    night_rect, profile_rect, render_quick_menu_to, speed_rect,
+};
+use firmware_ui::theme::Theme;
+use platform::{OutputProfile, OutputProfiles, PlaybackSpeed};
+use ui::quick_menu::{QuickMenu, QuickMenuAction};
+
+const SIZE: Size = Size::new(480, 800);
+const THEME: &Theme = &Theme::STANDARD;
+const ACCESSIBLE: &Theme = &Theme::ACCESSIBLE;
+
+fn render(t: &mut TestEmulator, theme: &Theme, profiles: &OutputProfiles, menu: &QuickMenu) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    render_quick_menu_to(&mut **t, theme, profiles, menu, |id, ty, pos, size| {
+        regs.push((id.to_owned(), ty.to_owned(), pos, size));
+    })
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+#[test]
+fn lists_every_profile_with_active_marked() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let menu = QuickMenu::new(profiles.len(), profiles.active_index());
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &profiles, &menu);
+
+    let list = t.query_by_test_id("quick-menu-list").unwrap();
+    assert_eq!(list.size, (SIZE.width, 3 * THEME.row_h));
+    let active = t.query_by_test_id("quick-menu-active").unwrap();
+    assert_eq!(
+        active.bounds(),
+        profile_rect(SIZE, THEME, &menu, 0).unwrap()
+    );
+}
+
+#[test]
+fn selection_wraps_to_last_profile() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let mut menu = QuickMenu::new(profiles.len(), 0);
+    menu.scroll(-1);
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &profiles, &menu);
+
+    let selected = t.query_by_test_id("quick-menu-selected").unwrap();
+    assert_eq!(
+        selected.bounds(),
+        profile_rect(SIZE, THEME, &menu, 2).unwrap()
+    );
+    let y = selected.position.1 as u32 + 2;
+    t.assert_pixel(SIZE.width - 2, y, Gray4::new(0x2)).unwrap();
+    t.assert_pixel(SIZE.width - 2, y + THEME.row_h, Gray4::WHITE)
+        .unwrap();
+}
+
+#[test]
+fn confirm_switches_active_profile() {
+    let mut profiles: OutputProfiles = OutputProfiles::defaults();
+    let mut menu = QuickMenu::new(profiles.len(), profiles.active_index());
+    menu.scroll(1);
+    let switched = menu
+        .confirm()
+        .and_then(|action| match action {
+            QuickMenuAction::Profile(i) => profiles.select(i),
+            QuickMenuAction::Speed(_) | QuickMenuAction::NightMode(_) => None,
+        })
+        .map(|p| p.name.as_str().to_owned());
+    assert_eq!(switched.as_deref(), Some("Planar headphones"));
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &profiles, &menu);
+    let active = t.query_by_test_id("quick-menu-active").unwrap();
+    let selected = t.query_by_test_id("quick-menu-selected").unwrap();
+    assert_eq!(active.bounds(), selected.bounds());
+    assert!(profile_rect(SIZE, THEME, &menu, 3).is_none());
+}
+
+#[test]
+fn accessible_rows_keep_every_profile_on_screen() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let menu = QuickMenu::new(profiles.len(), profiles.active_index());
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, ACCESSIBLE, &profiles, &menu);
+
+    let list = t.query_by_test_id("quick-menu-list").unwrap();
+    assert_eq!(list.size, (SIZE.width, 3 * ACCESSIBLE.row_h));
+    assert!(list.position.1 as u32 + list.size.1 <= SIZE.height);
+    let selected = t.query_by_test_id("quick-menu-selected").unwrap();
+    assert_eq!(
+        selected.bounds(),
+        profile_rect(SIZE, ACCESSIBLE, &menu, 0).unwrap()
+    );
+    t.assert_pixel(SIZE.width - 2, selected.position.1 as u32 + 2, Gray4::BLACK)
+        .unwrap();
+}
+
+/// The menu as opened during an audiobook at `speed`.
+fn spoken_word_menu(profiles: &OutputProfiles, speed: PlaybackSpeed) -> QuickMenu {
+    QuickMenu::new(profiles.len(), profiles.active_index())
+        .with_speed(speed.step(), PlaybackSpeed::STEPS.len())
+}
+
+#[test]
+fn speed_row_follows_the_profiles() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    for theme in [THEME, ACCESSIBLE] {
+        let mut menu = spoken_word_menu(&profiles, PlaybackSpeed::NORMAL);
+        menu.scroll(-1);
+        let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+        render(&mut t, theme, &profiles, &menu);
+
+        let list = t.query_by_test_id("quick-menu-list").unwrap();
+        assert_eq!(list.size, (SIZE.width, 4 * theme.row_h));
+        let speed = t.query_by_test_id("quick-menu-speed").unwrap();
+        assert_eq!(speed.bounds(), speed_rect(SIZE, theme, &menu).unwrap());
+        assert_eq!(speed.position.1 as u32, theme.list_top + 3 * theme.row_h);
+        assert!(speed.position.1 as u32 + speed.size.1 <= SIZE.height);
+        let selected = t.query_by_test_id("quick-menu-selected").unwrap();
+        assert_eq!(selected.bounds(), speed.bounds());
+    }
+}
+
+#[test]
+fn no_speed_row_for_music() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let menu = QuickMenu::new(profiles.len(), profiles.active_index());
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &profiles, &menu);
+    assert!(t.query_by_test_id("quick-menu-speed").is_none());
+    assert!(speed_rect(SIZE, THEME, &menu).is_none());
+}
+
+#[test]
+fn select_on_the_speed_row_shows_the_next_speed() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let mut menu = spoken_word_menu(&profiles, PlaybackSpeed::NORMAL);
+    menu.scroll(-1);
+    let mut before = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut before, THEME, &profiles, &menu);
+
+    let Some(QuickMenuAction::Speed(step)) = menu.confirm() else {
+        panic!("Select on the speed row steps the speed");
+    };
+    assert_eq!(
+        PlaybackSpeed::STEPS[step],
+        PlaybackSpeed::NORMAL.next_step()
+    );
+    let mut after = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut after, THEME, &profiles, &menu);
+
+    let row = speed_rect(SIZE, THEME, &menu).unwrap();
+    let (top, bottom) = (
+        row.top_left.y as u32,
+        row.top_left.y as u32 + row.size.height,
+    );
+    let changed: Vec<(u32, u32)> = (0..SIZE.height)
+        .flat_map(|y| (0..SIZE.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| before.pixel_at(x, y) != after.pixel_at(x, y))
+        .collect();
+    assert!(!changed.is_empty(), "the speed label changed");
+    assert!(
+        changed.iter().all(|&(_, y)| (top..bottom).contains(&y)),
+        "only the speed row changed"
+    );
+
+    // The label matches a menu opened at the new speed.
+    let mut fresh = TestEmulator::new(SIZE.width, SIZE.height);
+    let mut reopened = spoken_word_menu(&profiles, PlaybackSpeed::STEPS[step]);
+    reopened.scroll(-1);
+    render(&mut fresh, THEME, &profiles, &reopened);
+    assert_eq!(after.pixel_diff_count(&fresh), 0);
+}
+
+#[test]
+fn night_mode_row_comes_last_and_toggles() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let mut menu = spoken_word_menu(&profiles, PlaybackSpeed::NORMAL).with_night_mode(false);
+    menu.scroll(-1);
+    let mut before = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut before, THEME, &profiles, &menu);
+
+    let night = before.query_by_test_id("quick-menu-night").unwrap();
+    assert_eq!(night.bounds(), night_rect(SIZE, THEME, &menu).unwrap());
+    assert_eq!(night.position.1 as u32, THEME.list_top + 4 * THEME.row_h);
+    let selected = before.query_by_test_id("quick-menu-selected").unwrap();
+    assert_eq!(selected.bounds(), night.bounds());
+
+    assert_eq!(menu.confirm(), Some(QuickMenuAction::NightMode(true)));
+    let mut after = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut after, THEME, &profiles, &menu);
+    let row = night_rect(SIZE, THEME, &menu).unwrap();
+    let (top, bottom) = (
+        row.top_left.y as u32,
+        row.top_left.y as u32 + row.size.height,
+    );
+    let changed: Vec<(u32, u32)> = (0..SIZE.height)
+        .flat_map(|y| (0..SIZE.width).map(move |x| (x, y)))
+        .filter(|&(x, y)| before.pixel_at(x, y) != after.pixel_at(x, y))
+        .collect();
+    assert!(!changed.is_empty(), "Off became On");
+    assert!(changed.iter().all(|&(_, y)| (top..bottom).contains(&y)));
+}
+
+#[test]
+fn full_accessible_menu_scrolls_to_the_selected_row() {
+    let mut profiles: OutputProfiles = OutputProfiles::defaults();
+    while profiles.push(OutputProfile::new("Spare")).is_ok() {}
+    let mut menu = spoken_word_menu(&profiles, PlaybackSpeed::NORMAL).with_night_mode(true);
+    assert!(ACCESSIBLE.list_top + menu.rows() as u32 * ACCESSIBLE.row_h > SIZE.height);
+
+    // Opened on the first profile, the list starts at the top.
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, ACCESSIBLE, &profiles, &menu);
+    assert_eq!(
+        profile_rect(SIZE, ACCESSIBLE, &menu, 0).unwrap().top_left.y as u32,
+        ACCESSIBLE.list_top
+    );
+    assert!(t.query_by_test_id("quick-menu-night").is_none());
+
+    // On the night mode row, the list has scrolled it onto the screen.
+    menu.scroll(-1);
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, ACCESSIBLE, &profiles, &menu);
+    let night = t.query_by_test_id("quick-menu-night").unwrap();
+    let selected = t.query_by_test_id("quick-menu-selected").unwrap();
+    assert_eq!(selected.bounds(), night.bounds());
+    assert!(night.position.1 as u32 + night.size.1 <= SIZE.height);
+    assert!(profile_rect(SIZE, ACCESSIBLE, &menu, 0).is_none());
+    let list = t.query_by_test_id("quick-menu-list").unwrap();
+    assert!(list.position.1 as u32 + list.size.1 <= SIZE.height);
+}
+
+#[test]
+fn quick_menu_golden_standard() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let mut menu = QuickMenu::new(profiles.len(), profiles.active_index());
+    menu.scroll(1);
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &profiles, &menu);
+    t.assert_matches_golden("tests/golden/quick_menu.png", 5)
+        .unwrap();
+}
+
+#[test]
+fn quick_menu_golden_accessible() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let mut menu = QuickMenu::new(profiles.len(), profiles.active_index());
+    menu.scroll(1);
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, ACCESSIBLE, &profiles, &menu);
+    t.assert_matches_golden("tests/golden/quick_menu_accessible.png", 5)
+        .unwrap();
+}
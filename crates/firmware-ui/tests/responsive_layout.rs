@@ -0,0 +1,273 @@
+//! Every screen laid out at every supported panel resolution.
+//!
+//! Screens size themselves from the display, and Now Playing switches layout
+//! at the compact [`Breakpoint`].  These tests render each screen on each
+//! size in [`SUPPORTED_RESOLUTIONS`] and check that every registered
+//! component is on the panel, so a screen tuned on the 480×800 panel cannot
+//! silently register rows a 2.9" panel never shows.
+//!
+//! Run: cargo test -p firmware-ui --test responsive_layout
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use eink_system::breakpoint::{Breakpoint, SUPPORTED_RESOLUTIONS};
+use eink_testing::TestEmulator;
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use firmware_ui::screens::{
+    audio_settings, chapters, diagnostics, library_scan, lyrics, now_playing, queue, quick_menu,
+    tag_editor, text_input,
+};
+use firmware_ui::theme::Theme;
+use library::chapters::Chapters;
+use library::lyrics::Lyrics;
+use library::tag_overrides::TagField;
+use library::ScanProgress;
+use platform::diagnostics::DiagnosticsReport;
+use platform::{OutputProfiles, OversamplingFilter};
+use ui::audio_settings::AudioSettings;
+use ui::chapters::ChapterList;
+use ui::diagnostics::Diagnostics;
+use ui::library_scan::LibraryScan;
+use ui::lyrics::LyricsView;
+use ui::now_playing::NowPlayingState;
+use ui::queue::QueueView;
+use ui::quick_menu::QuickMenu;
+use ui::tag_editor::TagEditor;
+use ui::text_input::TextInput;
+
+const THEMES: [&Theme; 2] = [&Theme::STANDARD, &Theme::ACCESSIBLE];
+const TITLES: [&str; 5] = ["Mysterons", "Sour Times", "", "Strangers", "Roads"];
+const LRC: &str = "[00:01.00]One\n[00:02.00]Two\n[00:03.00]Three\n";
+
+/// One `register` call: test id and bounds.
+type Registration = (String, Rectangle);
+
+/// Render with `draw` on a panel of `size` and collect the registrations.
+fn render_at<F>(size: Size, draw: F) -> Vec<Registration>
+where
+    F: FnOnce(&mut TestEmulator, &mut dyn FnMut(&str, &str, (i32, i32), (u32, u32))),
+{
+    let mut t = TestEmulator::new(size.width, size.height);
+    let mut regs = Vec::new();
+    draw(&mut t, &mut |id, _ty, (x, y), (w, h)| {
+        regs.push((
+            id.to_owned(),
+            Rectangle::new(Point::new(x, y), Size::new(w, h)),
+        ));
+    });
+    regs
+}
+
+/// Every registration of `screen` lies on a panel of `size`.
+fn assert_on_screen(screen: &str, size: Size, regs: &[Registration]) {
+    let panel = Rectangle::new(Point::zero(), size);
+    for (id, bounds) in regs {
+        assert_eq!(
+            panel.intersection(bounds),
+            *bounds,
+            "{screen}: {id} at {bounds:?} is off a {}x{} panel",
+            size.width,
+            size.height
+        );
+    }
+}
+
+fn ids(regs: &[Registration]) -> Vec<&str> {
+    regs.iter().map(|(id, _)| id.as_str()).collect()
+}
+
+fn playing_state() -> NowPlayingState {
+    let mut s = NowPlayingState::default();
+    s.set_playing(true);
+    s.set_duration_ms(240_000);
+    s.set_position_ms(60_000);
+    s
+}
+
+#[test]
+fn supported_resolutions_cover_every_breakpoint() {
+    for breakpoint in Breakpoint::ALL {
+        assert!(
+            SUPPORTED_RESOLUTIONS
+                .iter()
+                .any(|&size| Breakpoint::of(size) == breakpoint),
+            "no supported resolution is {}",
+            breakpoint.name()
+        );
+    }
+}
+
+#[test]
+fn now_playing_fits_every_resolution() {
+    for size in SUPPORTED_RESOLUTIONS {
+        for theme in THEMES {
+            for loading in [false, true] {
+                let mut state = playing_state();
+                state.set_loading(loading);
+                let regs = render_at(size, |t, register| {
+                    now_playing::render_now_playing_to(&mut **t, theme, &state, None, register)
+                        .unwrap();
+                });
+                assert_on_screen("now playing", size, &regs);
+
+                let ids = ids(&regs);
+                for id in [
+                    "now-playing-title",
+                    "now-playing-progress",
+                    "now-playing-play-btn",
+                ] {
+                    assert!(ids.contains(&id), "{id} missing at {size:?}");
+                }
+                assert_eq!(
+                    ids.contains(&"now-playing-artist"),
+                    Breakpoint::of(size) >= Breakpoint::Medium,
+                    "artist visibility at {size:?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn now_playing_components_never_overlap() {
+    for size in SUPPORTED_RESOLUTIONS {
+        for theme in THEMES {
+            let regs = render_at(size, |t, register| {
+                now_playing::render_now_playing_to(
+                    &mut **t,
+                    theme,
+                    &playing_state(),
+                    None,
+                    register,
+                )
+                .unwrap();
+            });
+            for (i, (a, a_bounds)) in regs.iter().enumerate() {
+                for (b, b_bounds) in regs.iter().skip(i + 1) {
+                    assert!(
+                        a_bounds.intersection(b_bounds).is_zero_sized(),
+                        "{a} overlaps {b} at {size:?} ({:?})",
+                        theme.mode
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn compact_now_playing_has_no_header_bar() {
+    let size = Size::new(296, 128);
+    let mut t = TestEmulator::new(size.width, size.height);
+    now_playing::render_now_playing_to(
+        &mut *t,
+        &Theme::STANDARD,
+        &playing_state(),
+        None,
+        |_, _, _, _| {},
+    )
+    .unwrap();
+    // From the medium breakpoint up, the header bar darkens these rows.
+    t.assert_pixel(size.width - 2, 2, Gray4::WHITE).unwrap();
+}
+
+#[test]
+fn list_screens_fit_every_resolution() {
+    let profiles: OutputProfiles = OutputProfiles::defaults();
+    let menu = QuickMenu::new(profiles.len(), profiles.active_index());
+    let settings = AudioSettings::new(OversamplingFilter::ALL.len(), 0);
+    let mut book: Chapters = Chapters::new();
+    for i in 0..12 {
+        book.push(i * 600_000, "Part");
+    }
+    let lrc: Lyrics = Lyrics::parse(LRC).unwrap();
+
+    for size in SUPPORTED_RESOLUTIONS {
+        let regs = render_at(size, |t, register| {
+            audio_settings::render_audio_settings_to(&mut **t, &settings, register).unwrap();
+        });
+        assert_on_screen("audio settings", size, &regs);
+
+        let list = ChapterList::new(book.len(), chapters::chapter_rows(size), Some(0));
+        let regs = render_at(size, |t, register| {
+            chapters::render_chapters_to(&mut **t, Some(&book), &list, register).unwrap();
+        });
+        assert_on_screen("chapters", size, &regs);
+
+        let view = LyricsView::new(lyrics::lyrics_rows(size));
+        let regs = render_at(size, |t, register| {
+            lyrics::render_lyrics_to(&mut **t, Some(&lrc), &view, register).unwrap();
+        });
+        assert_on_screen("lyrics", size, &regs);
+
+        for theme in THEMES {
+            let view = QueueView::new(TITLES.len(), queue::queue_rows(size, theme), Some(0));
+            let regs = render_at(size, |t, register| {
+                queue::render_queue_to(&mut **t, theme, &TITLES, &view, register).unwrap();
+            });
+            assert_on_screen("queue", size, &regs);
+
+            let regs = render_at(size, |t, register| {
+                quick_menu::render_quick_menu_to(&mut **t, theme, &profiles, &menu, register)
+                    .unwrap();
+            });
+            assert_on_screen("quick menu", size, &regs);
+        }
+    }
+}
+
+#[test]
+fn status_screens_fit_every_resolution() {
+    let diag = Diagnostics::new();
+    let report = DiagnosticsReport::new();
+    let progress = ScanProgress {
+        dirs_visited: 12,
+        files_parsed: 140,
+        tracks_added: 138,
+    };
+
+    for size in SUPPORTED_RESOLUTIONS {
+        let regs = render_at(size, |t, register| {
+            diagnostics::render_diagnostics_to(&mut **t, &diag, &report, register).unwrap();
+        });
+        assert_on_screen("diagnostics", size, &regs);
+
+        let regs = render_at(size, |t, register| {
+            library_scan::render_library_scan_to(
+                &mut **t,
+                &progress,
+                &LibraryScan::new(),
+                register,
+            )
+            .unwrap();
+        });
+        assert_on_screen("library scan", size, &regs);
+    }
+}
+
+#[test]
+fn editors_fit_every_resolution() {
+    let editor = TagEditor::new(
+        ["Track 03", "Unknown Artist", "Dummy"],
+        TagField::ALL.map(TagField::max_len),
+    );
+    let input = TextInput::<32>::new("Glory Box", 32);
+
+    for size in SUPPORTED_RESOLUTIONS {
+        for theme in THEMES {
+            let regs = render_at(size, |t, register| {
+                tag_editor::render_tag_editor_to(&mut **t, theme, &editor, register).unwrap();
+            });
+            assert_on_screen("tag editor", size, &regs);
+
+            let regs = render_at(size, |t, register| {
+                text_input::render_text_input_to(&mut **t, theme, &input, theme.header_h, register)
+                    .unwrap();
+            });
+            assert_on_screen("text input", size, &regs);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Visual tests for the tag editor: field list, selection bar and where
+//! the text input opens.
+//!
+//! Run: cargo test -p firmware-ui --test tag_editor_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects)]
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::indexing_slicing
+)]
+
+use eink_testing::TestEmulator;
+use embedded_graphics::prelude::*;
+use firmware_ui::screens::tag_editor::{field_rect, input_top, render_tag_editor_to};
+use firmware_ui::screens::text_input::{input_rect, key_rect, text_input_height};
+use firmware_ui::theme::Theme;
+use library::tag_overrides::{FileHash, TagField, TagOverrides};
+use ui::tag_editor::TagEditor;
+use ui::text_input::Key;
+
+const SIZE: Size = Size::new(480, 800);
+const THEME: &Theme = &Theme::STANDARD;
+const ACCESSIBLE: &Theme = &Theme::ACCESSIBLE;
+
+fn render(t: &mut TestEmulator, theme: &Theme, editor: &TagEditor) {
+    #[allow(clippy::type_complexity)]
+    let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+    render_tag_editor_to(&mut **t, theme, editor, |id, ty, pos, size| {
+        regs.push((id.to_owned(), ty.to_owned(), pos, size));
+    })
+    .unwrap();
+    for (id, ty, pos, size) in regs {
+        t.register_component(&id, &ty, pos, size);
+    }
+}
+
+fn editor() -> TagEditor {
+    TagEditor::new(
+        ["Track 03", "Unknown Artist", "Dummy"],
+        TagField::ALL.map(TagField::max_len),
+    )
+}
+
+#[test]
+fn lists_every_field_without_an_open_input() {
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &editor());
+
+    let list = t.query_by_test_id("tag-field-list").unwrap();
+    assert_eq!(list.size, (SIZE.width, 3 * THEME.row_h));
+    let selected = t.query_by_test_id("tag-field-selected").unwrap();
+    assert_eq!(selected.bounds(), field_rect(SIZE, THEME, 0).unwrap());
+    assert!(t.query_by_test_id("text-input-box").is_none());
+    assert!(t.query_by_test_id("text-input-key").is_none());
+}
+
+#[test]
+fn open_field_shows_the_text_input_under_the_list() {
+    let mut state = editor();
+    state.scroll(1);
+    state.click();
+    state.scroll(-1);
+    assert_eq!(state.editing().map(|i| i.key()), Some(Key::Done));
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, THEME, &state);
+
+    let text = t.query_by_test_id("text-input-box").unwrap();
+    assert_eq!(text.bounds(), input_rect(SIZE, THEME, input_top(THEME)));
+    let last_field = field_rect(SIZE, THEME, 2).unwrap();
+    assert!(text.position.1 >= last_field.top_left.y + THEME.row_h as i32);
+    let key = t.query_by_test_id("text-input-key").unwrap();
+    let cell = key_rect(SIZE, THEME, input_top(THEME), 0).unwrap();
+    assert_eq!(key.bounds(), cell);
+    // Selection bar stays on the field being edited.
+    let selected = t.query_by_test_id("tag-field-selected").unwrap();
+    assert_eq!(selected.bounds(), field_rect(SIZE, THEME, 1).unwrap());
+}
+
+#[test]
+fn every_layout_stays_on_the_partial_window_grid() {
+    for theme in [THEME, ACCESSIBLE] {
+        let top = input_top(theme);
+        assert_eq!(top % 8, 0);
+        assert!(top + text_input_height(theme) <= SIZE.height);
+        for index in 0..3 {
+            let row = field_rect(SIZE, theme, index).unwrap();
+            assert_eq!(row.top_left.y % 8, 0);
+        }
+    }
+}
+
+#[test]
+fn done_hands_the_new_value_to_the_override_table() {
+    let mut state = editor();
+    state.scroll(1);
+    state.click();
+    for _ in "Unknown Artist".chars() {
+        // Delete is two detents back from the first character.
+        state.scroll(-2);
+        state.click();
+        state.scroll(2);
+    }
+    for c in "Portishead".chars() {
+        let target = ui::text_input::CHARSET.find(c).unwrap();
+        let here = state.editing().unwrap().position();
+        state.scroll(target as i32 - here as i32);
+        state.click();
+    }
+    assert_eq!(state.editing().unwrap().text(), "Portishead");
+
+    let mut t = TestEmulator::new(SIZE.width, SIZE.height);
+    render(&mut t, ACCESSIBLE, &state);
+    assert!(t.query_by_test_id("text-input-box").is_some());
+
+    state.scroll(-(state.editing().unwrap().position() as i32) - 1);
+    let field = state.click().map(|i| TagField::ALL[i]);
+    assert_eq!(field, Some(TagField::Artist));
+
+    let hash = FileHash {
+        size: 4_096,
+        crc32: 0x1234_5678,
+    };
+    let mut table: TagOverrides = TagOverrides::new();
+    table.set(hash, TagField::Artist, state.value(1)).unwrap();
+    assert_eq!(
+        table.get(hash).and_then(|o| o.get(TagField::Artist)),
+        Some("Portishead")
+    );
+}
@@ -0,0 +1,278 @@
+//! Interaction tests for the rotary text input: encoder turns, Select
+//! clicks and holds from the emulator's simulated input, run through the
+//! `ui` text input and rendered after every event batch.
+//!
+//! Run: cargo test -p firmware-ui --test text_input_visual
+
+// Test file — unwrap/expect/panic acceptable in test code.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::arithmetic_side_effects, clippy::indexing_slicing)]
+#![allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+
+use eink_testing::{Button, InputEvent, TestEmulator};
+use embedded_graphics::pixelcolor::Gray4;
+use embedded_graphics::prelude::*;
+use firmware_ui::screens::text_input::{
+    input_rect, key_rect, line_chars, render_text_input_to, text_input_height, wheel_reach,
+};
+use firmware_ui::theme::Theme;
+use ui::text_input::{Key, TextInput, CHARSET, WHEEL_LEN};
+
+const SIZE: Size = Size::new(480, 800);
+/// On the partial window grid, below a header and a few list rows.
+const TOP: u32 = 224;
+
+/// The input under test, driven by events from the emulator.
+struct Harness {
+    t: TestEmulator,
+    theme: &'static Theme,
+    input: TextInput<128>,
+    done: bool,
+}
+
+impl Harness {
+    fn new(theme: &'static Theme, initial: &str, limit: usize) -> Self {
+        let mut h = Self {
+            t: TestEmulator::new(SIZE.width, SIZE.height),
+            theme,
+            input: TextInput::new(initial, limit),
+            done: false,
+        };
+        h.t.clear_display();
+        h.render();
+        h
+    }
+
+    /// Feed the pending emulator events to the input, as the firmware's
+    /// event loop does, and redraw.
+    fn dispatch(&mut self) {
+        for event in self.t.take_events() {
+            match event {
+                InputEvent::RotaryIncrement(steps) => self.input.scroll(steps),
+                InputEvent::ButtonLongPress(Button::Select) => {
+                    self.input.long_press();
+                }
+                InputEvent::ButtonRelease(Button::Select) => {
+                    self.done |= self.input.release();
+                }
+                _ => {}
+            }
+        }
+        self.render();
+    }
+
+    fn render(&mut self) {
+        #[allow(clippy::type_complexity)]
+        let mut regs: Vec<(String, String, (i32, i32), (u32, u32))> = Vec::new();
+        render_text_input_to(
+            &mut *self.t,
+            self.theme,
+            &self.input,
+            TOP,
+            |id, ty, pos, size| {
+                regs.push((id.to_owned(), ty.to_owned(), pos, size));
+            },
+        )
+        .unwrap();
+        self.t.clear_components();
+        for (id, ty, pos, size) in regs {
+            self.t.register_component(&id, &ty, pos, size);
+        }
+    }
+
+    fn turn(&mut self, steps: i32) {
+        self.t.simulate_scroll(steps);
+        self.dispatch();
+    }
+
+    fn select(&mut self) {
+        self.t.simulate_key(Button::Select);
+        self.dispatch();
+    }
+
+    fn hold(&mut self) {
+        self.t.simulate_long_press(Button::Select);
+        self.dispatch();
+    }
+
+    /// Turn the shorter way round to `key`.
+    fn turn_to(&mut self, key: Key) {
+        let target = (0..WHEEL_LEN).find(|&i| Key::at(i) == key).unwrap() as i32;
+        let len = WHEEL_LEN as i32;
+        let forward = (target - self.input.position() as i32).rem_euclid(len);
+        let steps = if forward > len / 2 {
+            forward - len
+        } else {
+            forward
+        };
+        self.turn(steps);
+        assert_eq!(self.input.key(), key);
+    }
+
+    fn type_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.turn_to(Key::Char(c));
+            self.select();
+        }
+    }
+}
+
+#[test]
+fn every_wheel_position_highlights_its_key() {
+    for theme in [&Theme::STANDARD, &Theme::ACCESSIBLE] {
+        let mut h = Harness::new(theme, "", 16);
+        let cell = key_rect(SIZE, theme, TOP, 0).unwrap();
+        let left = key_rect(SIZE, theme, TOP, -1).unwrap();
+        for position in 0..WHEEL_LEN {
+            assert_eq!(h.input.position(), position);
+            let key = h.t.query_by_test_id("text-input-key").unwrap();
+            assert_eq!(key.bounds(), cell);
+            let corner = cell.top_left + Point::new(2, 2);
+            h.t.assert_pixel(corner.x as u32, corner.y as u32, theme.bar)
+                .unwrap();
+            let corner = left.top_left + Point::new(2, 2);
+            h.t.assert_pixel(corner.x as u32, corner.y as u32, Gray4::WHITE)
+                .unwrap();
+            assert!(
+                h.t.pixel_count_of_color(cell, Gray4::WHITE) > 0,
+                "{:?} has a label",
+                h.input.key()
+            );
+            h.turn(1);
+        }
+        assert_eq!(
+            h.input.key(),
+            Key::Char('A'),
+            "a full turn comes back round"
+        );
+    }
+}
+
+#[test]
+fn every_character_can_be_typed() {
+    let mut h = Harness::new(&Theme::STANDARD, "", 128);
+    h.type_text(CHARSET);
+    assert_eq!(h.input.text(), CHARSET);
+    assert!(!h.done);
+    h.t.assert_region_non_uniform(input_rect(SIZE, &Theme::STANDARD, TOP))
+        .unwrap();
+}
+
+#[test]
+fn delete_key_and_hold_both_remove_the_last_character() {
+    let mut h = Harness::new(&Theme::STANDARD, "Du", 16);
+    h.type_text("mmy");
+    assert_eq!(h.input.text(), "Dummy");
+
+    h.hold();
+    assert_eq!(h.input.text(), "Dumm", "the hold's release typed nothing");
+    h.turn_to(Key::Delete);
+    h.select();
+    assert_eq!(h.input.text(), "Dum");
+    for _ in 0..3 {
+        h.hold();
+    }
+    assert_eq!(h.input.text(), "");
+    h.hold();
+    h.select();
+    assert_eq!(h.input.text(), "", "deleting from empty text is harmless");
+    assert!(!h.done);
+}
+
+#[test]
+fn done_finishes_with_the_typed_text() {
+    let mut h = Harness::new(&Theme::ACCESSIBLE, "", 16);
+    h.type_text("Roads");
+    h.turn_to(Key::Done);
+    assert!(!h.done, "turning to Done does not finish");
+    h.select();
+    assert!(h.done);
+    assert_eq!(h.input.text(), "Roads");
+}
+
+#[test]
+fn hold_on_done_deletes_instead_of_finishing() {
+    let mut h = Harness::new(&Theme::STANDARD, "abc", 16);
+    h.turn_to(Key::Done);
+    h.hold();
+    assert!(!h.done);
+    assert_eq!(h.input.text(), "ab");
+}
+
+#[test]
+fn typing_stops_at_the_limit() {
+    let mut h = Harness::new(&Theme::STANDARD, "", 4);
+    h.type_text("Mysterons");
+    assert_eq!(h.input.text(), "Myst");
+    h.hold();
+    h.type_text("!");
+    assert_eq!(h.input.text(), "Mys!");
+}
+
+#[test]
+fn box_shows_the_end_of_long_text() {
+    let theme = &Theme::STANDARD;
+    let fits = line_chars(SIZE, theme) - 1;
+    let long: String = CHARSET.chars().cycle().take(fits + 20).collect();
+    let tail: String = long.chars().skip(20).collect();
+
+    let a = Harness::new(theme, &long, 128);
+    let b = Harness::new(theme, &tail, 128);
+    assert_eq!(a.t.pixel_diff_count(&b.t), 0);
+    let c = Harness::new(theme, &long[..fits + 19], 128);
+    assert!(a.t.pixel_diff_count(&c.t) > 0);
+}
+
+#[test]
+fn turning_redraws_only_the_wheel_row() {
+    for theme in [&Theme::STANDARD, &Theme::ACCESSIBLE] {
+        let mut h = Harness::new(theme, "Glory Box", 32);
+        let before = Harness::new(theme, "Glory Box", 32);
+        h.turn(7);
+
+        let wheel = key_rect(SIZE, theme, TOP, 0).unwrap();
+        let wheel_top = wheel.top_left.y as u32;
+        let wheel_bottom = wheel_top + wheel.size.height;
+        for y in (0..SIZE.height).filter(|y| !(wheel_top..wheel_bottom).contains(y)) {
+            for x in 0..SIZE.width {
+                assert_eq!(h.t.pixel_at(x, y), before.t.pixel_at(x, y), "({x}, {y})");
+            }
+        }
+
+        // Drawn over the previous wheel without a clear, the result matches
+        // a fresh render at the new position.
+        let mut fresh = Harness::new(theme, "Glory Box", 32);
+        fresh.input.scroll(7);
+        fresh.render();
+        assert_eq!(h.t.pixel_diff_count(&fresh.t), 0);
+    }
+}
+
+#[test]
+fn layout_stays_on_the_partial_window_grid() {
+    for theme in [&Theme::STANDARD, &Theme::ACCESSIBLE] {
+        let h = Harness::new(theme, "", 16);
+        let text = h.t.query_by_test_id("text-input-box").unwrap();
+        assert_eq!(text.bounds(), input_rect(SIZE, theme, TOP));
+        let key = h.t.query_by_test_id("text-input-key").unwrap();
+        for y in [text.position.1, key.position.1] {
+            assert_eq!(y % 8, 0);
+        }
+        assert_eq!(
+            (key.position.1 + key.size.1 as i32) as u32,
+            TOP + text_input_height(theme)
+        );
+
+        let reach = wheel_reach(SIZE, theme);
+        assert!(reach >= 2, "at least two keys either side");
+        let far_left = key_rect(SIZE, theme, TOP, -reach).unwrap();
+        let far_right = key_rect(SIZE, theme, TOP, reach).unwrap();
+        assert!(far_left.top_left.x >= 0);
+        assert!(far_right.top_left.x as u32 + far_right.size.width <= SIZE.width);
+        assert!(key_rect(SIZE, theme, TOP, reach + 1).is_none());
+    }
+}
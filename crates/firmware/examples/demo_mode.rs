@@ -0,0 +1,49 @@
+//! Demo mode — the trade-show attract loop in the emulator window.
+//!
+//! Loops `firmware_ui::demo::Demo` until the window is closed: screens
+//! change on a script while simulated playback advances the progress bar
+//! and moves through the queue.  Screen changes get a full refresh, frames
+//! within a screen a partial one, as on the device.  The loop starts on a
+//! track drawn from the screensaver stream of the host-clock seed.
+//!
+//! Run with: cargo run -p firmware --example demo_mode --features demo
+//!
+//! `cargo xtask demo` records the same loop headless as a GIF.
+
+use std::time::Duration;
+
+use eink_emulator::{DisplayDriver, Emulator};
+use firmware::entropy::{self, RngStreams};
+use firmware_ui::demo::{Demo, FRAME_MS, TRACKS};
+use firmware_ui::gallery::PANEL;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut emulator = Emulator::with_spec(&PANEL);
+    let RngStreams {
+        mut screensaver, ..
+    } = RngStreams::split(entropy::seed_from_host_clock());
+    let track = u32::try_from(TRACKS.len()).map_or(0, |len| screensaver.below(len));
+    let mut demo = Demo::starting_on(usize::try_from(track)?);
+
+    println!(
+        "Demo mode: {} s loop on {} — close the window to exit.",
+        demo.loop_ms() / 1_000,
+        PANEL.name
+    );
+
+    let Ok(()) = demo.render(&mut emulator, |_, _, _, _| {});
+    emulator.refresh_full().await?;
+
+    while emulator.pump_window_events() {
+        tokio::time::sleep(Duration::from_millis(u64::from(FRAME_MS))).await;
+        let changed = demo.advance(FRAME_MS);
+        let Ok(()) = demo.render(&mut emulator, |_, _, _, _| {});
+        if changed {
+            emulator.refresh_full().await?;
+        } else {
+            emulator.refresh_partial().await?;
+        }
+    }
+    Ok(())
+}
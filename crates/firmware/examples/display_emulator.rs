@@ -17,37 +17,76 @@
 //!
 //! Without the hot-reload feature, xtask dev uses kill-and-restart.
 //! That mode works for all code changes (not just render.rs).
+//!
+//! # Saved Settings
+//!
+//! Window scale and position, display spec, debug overlays and temperature
+//! are saved to the per-user emulator settings file when the window closes
+//! (see `eink_emulator::settings`) and restored on the next launch.  Pass
+//! `--reset-config` to delete the file and start from the defaults:
+//!
+//! cargo run --example display_emulator --features emulator -- --reset-config
+//!
+//! # Accessibility Mode
+//!
+//! Pass `--accessible` to render Now Playing with the large-text,
+//! high-contrast theme (`firmware_ui::theme::Theme::ACCESSIBLE`), as
+//! `[ui] accessible = true` in `soul.toml` does on the device:
+//!
+//! cargo run --example display_emulator --features emulator,keyboard-input -- --accessible
 
 use embedded_graphics::pixelcolor::Gray4;
 use embedded_graphics::prelude::*;
 use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 
+use eink_emulator::EmulatorSettings;
 use firmware::EmulatorDisplay;
 use platform::config;
 
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+use firmware::entropy::{self, RngStreams};
 #[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
 use firmware::input::{Button, InputEvent};
 #[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
-use firmware_ui::screens::now_playing::render_now_playing_to;
+use firmware_ui::screens::now_playing::{
+    render_now_playing_to, NowPlayingRefreshPlanner, ART_BYTES, ART_SIZE,
+};
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+use firmware_ui::theme::Theme;
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+use library::ArtCache;
 #[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
 use ui::navigation::Navigator;
 #[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
 use ui::now_playing::NowPlayingState;
 #[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
 use ui::screen::Screen;
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+use util::Rng;
 
 // The #[hot_module] attribute macro (hot-lib-reloader 0.8) generates
 // a mod with hot-reloadable wrappers for the dylib functions.
 // Build the dylib FIRST: cargo build --package firmware-ui --features hot-reload
 //
-// C ABI boundary uses eink_emulator::Emulator (not EmulatorDisplay)
-// to avoid circular dep: firmware -> firmware-ui -> firmware.
-// We pass display.emulator_mut() as raw pointer to the dylib.
+// The exports are declared by `hot_reload_abi!` in crates/firmware-ui/src/lib.rs
+// and take eink_emulator::Emulator (not EmulatorDisplay) to avoid the circular
+// dep firmware -> firmware-ui -> firmware. The declarations below must match;
+// firmware_ui::HotUi::connect only accepts functions with the declared
+// signatures and checks the loaded dylib's ABI hash.
 #[cfg(feature = "hot-reload")]
 #[hot_lib_reloader::hot_module(dylib = "firmware_ui")]
 mod hot_ui {
-    hot_functions_from_file!("crates/firmware-ui/src/lib.rs");
+    use eink_emulator::Emulator;
+
+    #[hot_functions]
+    extern "Rust" {
+        pub fn render_ui(emulator: &mut Emulator);
+        pub fn ui_abi_hash() -> u32;
+        pub fn ui_version() -> u64;
+    }
 
     #[lib_change_subscription]
     pub fn subscribe() -> hot_lib_reloader::LibReloadObserver {}
@@ -58,27 +97,62 @@ struct AppState {
     now_playing: NowPlayingState,
     nav: Navigator,
     needs_redraw: bool,
+    /// Index into [`DEMO_ALBUMS`].
+    album: usize,
 }
 
+/// `(album_id, title, artist)` cycled with Next/Previous.
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+const DEMO_ALBUMS: [(u32, &str, &str); 3] = [
+    (1, "Sample Track", "Sample Artist"),
+    (2, "Second Album", "Another Artist"),
+    (3, "Third Album", "Third Artist"),
+];
+
 #[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
 impl Default for AppState {
     fn default() -> Self {
         let mut now_playing = NowPlayingState::default();
-        now_playing.title.push_str("Sample Track").ok();
-        now_playing.artist.push_str("Sample Artist").ok();
         now_playing.set_duration_ms(180_000); // 3 minutes demo
-        AppState {
+        let mut state = AppState {
             now_playing,
             nav: Navigator::new(),
             needs_redraw: true,
-        }
+            album: 0,
+        };
+        state.select_album(0);
+        state
     }
 }
 
 #[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
 impl AppState {
+    fn select_album(&mut self, album: usize) {
+        self.album = album.checked_rem(DEMO_ALBUMS.len()).unwrap_or(0);
+        let Some(&(id, title, artist)) = DEMO_ALBUMS.get(self.album) else {
+            return;
+        };
+        self.now_playing.title.clear();
+        self.now_playing.title.push_str(title).ok();
+        self.now_playing.artist.clear();
+        self.now_playing.artist.push_str(artist).ok();
+        self.now_playing.set_album_id(Some(id));
+        self.now_playing.set_position_ms(0);
+    }
+
     fn handle_input(&mut self, ev: InputEvent) {
         match ev {
+            InputEvent::ButtonPress(Button::Next) => {
+                self.select_album(self.album.saturating_add(1));
+                self.needs_redraw = true;
+            }
+            InputEvent::ButtonPress(Button::Previous) => {
+                self.select_album(
+                    self.album
+                        .saturating_add(DEMO_ALBUMS.len().saturating_sub(1)),
+                );
+                self.needs_redraw = true;
+            }
             InputEvent::ButtonPress(Button::Play) => {
                 self.now_playing.set_playing(!self.now_playing.playing);
                 self.needs_redraw = true;
@@ -121,14 +195,21 @@ impl AppState {
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing subscriber. Controlled by RUST_LOG env var (default: info).
     // cargo dev sets RUST_LOG=info automatically; override with e.g. RUST_LOG=debug cargo dev.
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-        )
+    let fmt_layer = tracing_subscriber::fmt::layer()
         .with_target(false)
         .with_timer(tracing_subscriber::fmt::time::uptime())
-        .compact()
-        .init();
+        .compact();
+    let subscriber = tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(fmt_layer);
+
+    // With the debug panel, the same events also go to its LOG tab.
+    #[cfg(feature = "debug")]
+    let log_sink = eink_emulator::debug::LogSink::default();
+    #[cfg(feature = "debug")]
+    let subscriber = subscriber.with(firmware::log_pane::LogPaneLayer::new(log_sink.clone()));
+
+    subscriber.init();
 
     tracing::info!(
         app = config::APP_NAME,
@@ -144,13 +225,32 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let rt = tokio::runtime::Runtime::new()?;
 
-    let emulator_config = eink_emulator::EmulatorConfig {
+    let settings_path = eink_emulator::settings::default_path();
+    if std::env::args().skip(1).any(|arg| arg == RESET_CONFIG_FLAG) {
+        if let Some(path) = &settings_path {
+            EmulatorSettings::reset(path)?;
+            tracing::info!(path = %path.display(), "Emulator settings reset");
+        }
+    }
+    let saved = settings_path.as_deref().and_then(load_settings);
+
+    let mut emulator_config = eink_emulator::EmulatorConfig {
         rotation: eink_emulator::Rotation::Degrees90,
         scale: 1,
+        ..Default::default()
     };
+    if let Some(saved) = &saved {
+        saved.apply_to_config(&mut emulator_config);
+    }
 
     let mut display =
         EmulatorDisplay::with_spec_and_config(&firmware::GDEM0397T81P_SPEC, emulator_config);
+    #[cfg(feature = "debug")]
+    display.emulator_mut().attach_log_sink(log_sink);
+    if let Some(saved) = &saved {
+        display.emulator_mut().apply_settings(saved);
+        tracing::info!(scale = saved.scale, "Restored emulator settings");
+    }
     tracing::info!(mode = "portrait", resolution = "480x800", "Window opened");
 
     // Attach keyboard/scroll input before initializing so the queue is wired
@@ -169,7 +269,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(feature = "debug")]
     {
         tracing::debug!(
-            "Debug mode enabled — hotkeys: Ctrl+1=panel Ctrl+2=borders Ctrl+3=inspector"
+            "Debug mode enabled — hotkeys: Ctrl+1=panel Ctrl+2=borders Ctrl+3=inspector Tab=LOG tab"
         );
     }
 
@@ -196,23 +296,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         let mut last_version = hot_ui::ui_version();
 
-        // ABI version guard: panic early if the loaded dylib was compiled with a
-        // different ABI than what this binary expects.
-        let dylib_abi = hot_ui::ui_abi_version();
-        assert_eq!(
-            dylib_abi,
-            firmware_ui::ABI_VERSION,
-            "hot-reload ABI mismatch: binary expects ABI v{}, dylib reports v{}. \
-             Rebuild firmware-ui with: cargo build -p firmware-ui --features hot-reload",
-            firmware_ui::ABI_VERSION,
-            dylib_abi,
-        );
+        // ABI guard: refuse a dylib built from a different set of exported
+        // signatures than this binary was compiled against.
+        let ui = firmware_ui::HotUi::connect(hot_ui::ui_abi_hash, hot_ui::render_ui)?;
         tracing::info!("Initial render");
-        // SAFETY: display.emulator_mut() is valid, non-null, exclusively owned.
-        // Unwrapping EmulatorDisplay to its inner Emulator avoids circular dep.
-        // Verified: ui_abi_version() == firmware_ui::ABI_VERSION (asserted above).
-        unsafe { hot_ui::render_ui(display.emulator_mut() as *mut eink_emulator::Emulator) };
+        ui.render_ui(display.emulator_mut());
         rt.block_on(async { display.refresh_full().await })?;
+        // The reload loop below never returns; save the restored settings now.
+        save_settings(settings_path.as_deref(), display.emulator());
         tracing::info!(
             path = "crates/firmware-ui/src/render.rs",
             "Ready — edit to hot-reload"
@@ -226,15 +317,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             if new_version != last_version {
                 last_version = new_version;
                 tracing::info!("Hot-reloaded firmware_ui dylib");
+                if let Err(e) = ui.recheck() {
+                    tracing::error!(error = %e, "Skipping render of incompatible dylib");
+                    continue;
+                }
 
                 let size = display.bounding_box().size;
                 Rectangle::new(Point::zero(), size)
                     .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
                     .draw(&mut display)?;
-                // SAFETY: display.emulator_mut() is valid, non-null, exclusively owned.
-                unsafe {
-                    hot_ui::render_ui(display.emulator_mut() as *mut eink_emulator::Emulator)
-                };
+                ui.render_ui(display.emulator_mut());
                 rt.block_on(async { display.refresh_full().await })?;
                 tracing::info!(elapsed = ?Instant::now(), "Reload complete");
             }
@@ -257,6 +349,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             tracing::info!("Demo menu rendered");
             tracing::info!("Close window to exit");
+            // run() consumes the window, so save what is known up front.
+            save_settings(settings_path.as_deref(), display.emulator());
             display.into_inner().run();
         }
 
@@ -264,13 +358,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         {
             tracing::info!("Starting interactive Now Playing screen");
 
+            // Seed once from the host clock; the demo art takes the
+            // dither stream.
+            let RngStreams { dither, .. } = RngStreams::split(entropy::seed_from_host_clock());
             let mut state = AppState::default();
+            let mut art = DemoArt::new(dither);
+            let size = display.size();
+            let mut planner = NowPlayingRefreshPlanner::new(size);
+            let theme = if std::env::args().skip(1).any(|arg| arg == ACCESSIBLE_FLAG) {
+                &Theme::ACCESSIBLE
+            } else {
+                &Theme::STANDARD
+            };
+            let started = std::time::Instant::now();
 
             rt.block_on(async {
-                // Initial full render
-                render_now_playing_to(&mut display, &state.now_playing, |_, _, _, _| {})?;
-                display.refresh_full().await?;
-                state.needs_redraw = false;
+
+                // `cargo xtask dev --watch-tests` names a status file to mirror
+                // into the debug panel.
+                #[cfg(feature = "debug")]
+                let mut test_status = TestStatusFile::from_env();
 
                 tracing::info!("Now Playing screen ready");
                 tracing::info!("Keyboard input: Space/K=Play  </J=Prev  >/L=Next  Up/==Vol+  Down/-=Vol-  M=Menu  Esc/BS=Back  Scroll=Encoder");
@@ -280,6 +387,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     // Returns false when the close button is clicked.
                     if !display.emulator_mut().pump_window_events() {
                         tracing::info!("Window closed");
+                        save_settings(settings_path.as_deref(), display.emulator());
                         break;
                     }
 
@@ -295,15 +403,46 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         }
                     }
 
+                    #[cfg(feature = "debug")]
+                    if let Some(report) = test_status.as_mut().and_then(TestStatusFile::poll) {
+                        display.emulator_mut().report_tests(Some(report));
+                        // A refresh re-presents the window, repainting the panel.
+                        state.needs_redraw = true;
+                    }
+
                     // Re-render only when state changed.
                     if state.needs_redraw {
+                        let art_bytes = art.get(state.now_playing.album_id);
                         render_now_playing_to(
                             &mut display,
+                            theme,
                             &state.now_playing,
+                            art_bytes,
                             |_, _, _, _| {},
                         )?;
-                        // Use partial refresh for responsiveness (~300 ms).
-                        display.refresh_partial().await?;
+                        let now_ms =
+                            u64::try_from(started.elapsed().as_millis()).unwrap_or(u64::MAX);
+                        let plan = planner.plan(
+                            &state.now_playing,
+                            size,
+                            platform::refresh_policy::PanelState::UNKNOWN,
+                            now_ms,
+                        );
+                        // New art gets GC16.  The emulator has no windowed
+                        // GC16, so it refreshes the whole panel with it; the
+                        // SSD1677 driver limits it to `plan.art`.
+                        if let Some(area) = plan.art {
+                            tracing::debug!(?area, "GC16 album art");
+                            display
+                                .emulator_mut()
+                                .refresh_with_waveform(eink_emulator::WaveformMode::GC16)
+                                .await?;
+                        }
+                        match plan.screen.mode {
+                            platform::RefreshMode::Full => display.refresh_full().await?,
+                            // Partial refresh for responsiveness (~300 ms).
+                            _ => display.refresh_partial().await?,
+                        }
                         state.needs_redraw = false;
                         tracing::debug!(
                             playing = state.now_playing.playing,
@@ -324,6 +463,38 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Delete the saved emulator settings before opening the window.
+const RESET_CONFIG_FLAG: &str = "--reset-config";
+
+/// Render with the accessibility theme.
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+const ACCESSIBLE_FLAG: &str = "--accessible";
+
+/// Saved settings at `path`, if any.  Without a file the example keeps its
+/// own portrait 1× defaults rather than the emulator's.
+fn load_settings(path: &std::path::Path) -> Option<EmulatorSettings> {
+    match EmulatorSettings::load(path) {
+        Ok(saved) => saved,
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "Ignoring emulator settings");
+            None
+        }
+    }
+}
+
+/// Persist the emulator's settings for the next launch.
+fn save_settings(path: Option<&std::path::Path>, emulator: &eink_emulator::Emulator) {
+    let Some(path) = path else {
+        return;
+    };
+    match emulator.settings().save(path) {
+        Ok(()) => tracing::debug!(path = %path.display(), "Saved emulator settings"),
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "Could not save emulator settings")
+        }
+    }
+}
+
 #[cfg(all(test, feature = "keyboard-input", not(feature = "hot-reload")))]
 mod tests {
     use super::*;
@@ -333,9 +504,31 @@ mod tests {
             now_playing: NowPlayingState::default(),
             nav: Navigator::new(),
             needs_redraw: false,
+            album: 0,
         }
     }
 
+    #[test]
+    fn next_and_previous_cycle_albums() {
+        let mut s = make_state();
+        s.handle_input(InputEvent::ButtonPress(Button::Next));
+        assert_eq!(s.now_playing.album_id, Some(DEMO_ALBUMS[1].0));
+        assert!(s.needs_redraw);
+        s.handle_input(InputEvent::ButtonPress(Button::Previous));
+        s.handle_input(InputEvent::ButtonPress(Button::Previous));
+        assert_eq!(s.now_playing.album_id, Some(DEMO_ALBUMS[2].0));
+        assert_eq!(s.now_playing.title.as_str(), DEMO_ALBUMS[2].1);
+    }
+
+    #[test]
+    fn demo_art_is_cached_after_first_load() {
+        let mut art = DemoArt::new(Rng::seed_from_u64(1));
+        assert_eq!(art.get(Some(1)).map(<[u8]>::len), Some(ART_BYTES));
+        art.get(Some(1));
+        assert_eq!(art.cache.stats().hits, 1);
+        assert!(art.get(None).is_none());
+    }
+
     #[test]
     fn play_button_toggles_playing() {
         let mut s = make_state();
@@ -428,6 +621,134 @@ mod tests {
     }
 }
 
+/// Album art for the demo, served through the library's [`ArtCache`] the
+/// way the firmware does; misses are filled with generated art instead of
+/// an SD card read.
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+struct DemoArt {
+    cache: ArtCache<HeapRam, 8>,
+    buf: Vec<u8>,
+    dither: Rng,
+}
+
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+impl DemoArt {
+    fn new(dither: Rng) -> Self {
+        let region = platform::RamRegion {
+            offset: 0,
+            len: 8 * library::art_cache::SLOT_BYTES,
+        };
+        Self {
+            cache: ArtCache::with_region(HeapRam(vec![0; region.len]), region),
+            buf: vec![0; ART_BYTES],
+            dither,
+        }
+    }
+
+    /// Art for `album_id`, or `None` for the placeholder.
+    fn get(&mut self, album_id: Option<u32>) -> Option<&[u8]> {
+        let id = album_id?;
+        if self.cache.get(id, &mut self.buf).ok()?.is_none() {
+            generate_demo_art(id, &mut self.dither, &mut self.buf);
+            self.cache.insert(id, &self.buf).ok()?;
+        }
+        Some(&self.buf)
+    }
+}
+
+/// Ordered-dithered radial ramp, phase-shifted per album so track changes
+/// visibly swap the art.  The Bayer matrix is offset by a draw from
+/// `dither`, so the pattern differs from one run to the next.
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+#[allow(clippy::arithmetic_side_effects, clippy::indexing_slicing)] // bounded by ART_SIZE
+fn generate_demo_art(album_id: u32, dither: &mut Rng, buf: &mut [u8]) {
+    const BAYER: [[u32; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+    let (dx0, dy0) = (dither.below(4), dither.below(4));
+    buf.fill(0);
+    for y in 0..ART_SIZE {
+        for x in 0..ART_SIZE {
+            let dx = x.abs_diff(ART_SIZE / 2);
+            let dy = y.abs_diff(ART_SIZE / 2);
+            let t = ((dx * dx + dy * dy) / 64 + album_id * 16) % 64;
+            let level =
+                ((t + BAYER[((y + dy0) % 4) as usize][((x + dx0) % 4) as usize]) / 16).min(3) as u8;
+            let i = (y * ART_SIZE + x) as usize;
+            buf[i / 4] |= level << (6 - 2 * (i % 4));
+        }
+    }
+}
+
+/// Heap-backed stand-in for the SDRAM behind the art cache.
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+struct HeapRam(Vec<u8>);
+
+#[cfg(all(feature = "keyboard-input", not(feature = "hot-reload")))]
+#[allow(clippy::arithmetic_side_effects)] // offsets come from the cache's slot table
+impl platform::ExternalRam for HeapRam {
+    type Error = ();
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), ()> {
+        let src = self.0.get(offset..offset + buf.len()).ok_or(())?;
+        buf.copy_from_slice(src);
+        Ok(())
+    }
+
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), ()> {
+        let dst = self.0.get_mut(offset..offset + data.len()).ok_or(())?;
+        dst.copy_from_slice(data);
+        Ok(())
+    }
+
+    fn zero(&mut self, offset: usize, len: usize) -> Result<(), ()> {
+        self.0.get_mut(offset..offset + len).ok_or(())?.fill(0);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Test-status file written by `cargo xtask dev --watch-tests`.
+///
+/// The path comes from `EINK_TEST_STATUS`; the file is re-read whenever its
+/// modification time changes.
+#[cfg(all(
+    feature = "keyboard-input",
+    feature = "debug",
+    not(feature = "hot-reload")
+))]
+struct TestStatusFile {
+    path: std::path::PathBuf,
+    modified: Option<std::time::SystemTime>,
+}
+
+#[cfg(all(
+    feature = "keyboard-input",
+    feature = "debug",
+    not(feature = "hot-reload")
+))]
+impl TestStatusFile {
+    fn from_env() -> Option<Self> {
+        let path = std::env::var_os("EINK_TEST_STATUS")?;
+        Some(Self {
+            path: path.into(),
+            modified: None,
+        })
+    }
+
+    /// The parsed report when the file changed since the last poll.
+    fn poll(&mut self) -> Option<eink_emulator::debug::TestReport> {
+        let modified = std::fs::metadata(&self.path).ok()?.modified().ok()?;
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+        let text = std::fs::read_to_string(&self.path).ok()?;
+        Some(eink_emulator::debug::TestReport::parse(&text))
+    }
+}
+
 /// Register named DAP scene components with the debug inspector.
 ///
 /// Positions are in the display's native coordinate space (landscape 800×480
@@ -0,0 +1,121 @@
+//! Hardware-in-the-loop test firmware.
+//!
+//! Flashed by `cargo xtask hil-test`, which drives it through the
+//! `HIL_MAILBOX` symbol (see `platform::hil` for the protocol) and asserts
+//! the results on the host.  Each command is also logged over defmt.
+//!
+//! Build manually with:
+//! cargo build -p firmware --example hil_test --target thumbv7em-none-eabihf --features hardware
+
+#![no_std]
+#![no_main]
+
+use embassy_executor::Spawner;
+use embassy_stm32::gpio::{Input, Level, Output, Pull, Speed};
+use embassy_stm32::spi::{Config as SpiConfig, Spi};
+use embassy_stm32::time::Hertz;
+use embassy_time::{Delay, Duration, Instant, Timer};
+use embedded_hal_bus::spi::ExclusiveDevice;
+use platform::hil::{self, HilCommand, HilMailbox, HilResponse, HilScreen, HilStatus};
+use platform::DisplayDriver;
+
+use firmware::ui::{SplashScreen, TestPattern};
+use firmware::Ssd1677Display;
+
+use panic_probe as _;
+
+/// Command mailbox written by the host over SWD.
+///
+/// `#[no_mangle]` keeps the symbol name the host looks up in the ELF;
+/// `#[used]` keeps the linker from dropping it before the first command.
+#[no_mangle]
+#[used]
+static HIL_MAILBOX: HilMailbox = HilMailbox::new();
+
+/// How often the mailbox is polled for a new command.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    // Same ordering as main.rs: MPU before embassy_stm32::init() enables the D-cache.
+    let mpu_token = firmware::boot::hardware::apply_mpu_config_from_peripherals();
+    let p = embassy_stm32::init(firmware::boot::build_embassy_config(&mpu_token));
+    defmt::info!("HIL test firmware, protocol v{=u32}", hil::PROTOCOL_VERSION);
+
+    // Display wiring as in main.rs (SPI1 + DMA1_CH0/CH1, DC=PB0, CS=PB1, RST=PB2, BUSY=PE3).
+    let mut spi_config = SpiConfig::default();
+    spi_config.frequency = Hertz(4_000_000);
+    let spi = Spi::new(
+        p.SPI1, p.PA5, p.PA7, p.PA6, p.DMA1_CH0, p.DMA1_CH1, spi_config,
+    );
+    let dc = Output::new(p.PB0, Level::Low, Speed::VeryHigh);
+    let cs = Output::new(p.PB1, Level::High, Speed::VeryHigh);
+    let rst = Output::new(p.PB2, Level::High, Speed::VeryHigh);
+    let busy = Input::new(p.PE3, Pull::Up);
+    let spi_device = ExclusiveDevice::new(spi, cs, Delay).expect("CS pin init failed");
+    let mut display = Ssd1677Display::new(spi_device, dc, rst, busy, Delay);
+
+    // A display that fails to init still answers Ping, so the host can
+    // report the failure instead of timing out.
+    let display_ok = match display.init().await {
+        Ok(()) => true,
+        Err(e) => {
+            defmt::error!("HIL: display init failed: {}", e);
+            false
+        }
+    };
+
+    HIL_MAILBOX.mark_ready();
+    defmt::info!("HIL: ready");
+
+    let mut last_seq = 0;
+    loop {
+        Timer::after(POLL_INTERVAL).await;
+        let Some((seq, command)) = HIL_MAILBOX.take(last_seq) else {
+            continue;
+        };
+        last_seq = seq;
+
+        let Some(command) = command else {
+            defmt::warn!("HIL: #{=u32} unknown command", seq);
+            HIL_MAILBOX.complete(seq, HilResponse::status(HilStatus::UnknownCommand));
+            continue;
+        };
+        defmt::info!("HIL: #{=u32} {}", seq, command);
+
+        let response = match command {
+            HilCommand::Ping => {
+                let uptime_ms = u32::try_from(Instant::now().as_millis()).unwrap_or(u32::MAX);
+                HilResponse::ok([hil::PROTOCOL_VERSION, uptime_ms])
+            }
+            HilCommand::RenderScreen(_) | HilCommand::FramebufferChecksum if !display_ok => {
+                HilResponse::status(HilStatus::Failed)
+            }
+            HilCommand::RenderScreen(screen) => {
+                let drawn = match screen {
+                    HilScreen::Splash => SplashScreen::render(&mut display),
+                    HilScreen::TestPattern => TestPattern::render(&mut display),
+                };
+                let start = Instant::now();
+                match drawn {
+                    Ok(()) if display.refresh_full().await.is_ok() => {
+                        let refresh_ms =
+                            u32::try_from(start.elapsed().as_millis()).unwrap_or(u32::MAX);
+                        HilResponse::ok([hil::checksum(display.framebuffer()), refresh_ms])
+                    }
+                    _ => HilResponse::status(HilStatus::Failed),
+                }
+            }
+            HilCommand::FramebufferChecksum => {
+                HilResponse::ok([hil::checksum(display.framebuffer()), 0])
+            }
+            // SAI1 is not wired yet (see firmware::audio::sai_task); say so
+            // rather than pretend, and the host marks the scenario skipped.
+            HilCommand::PlaySine { .. } | HilCommand::AudioLoopback => {
+                HilResponse::status(HilStatus::Unsupported)
+            }
+        };
+        defmt::info!("HIL: #{=u32} -> {}", seq, response);
+        HIL_MAILBOX.complete(seq, response);
+    }
+}
@@ -18,6 +18,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = EmulatorConfig {
         rotation: Rotation::Degrees90,
         scale: 1, // Native resolution (larger display)
+        ..Default::default()
     };
     let mut emulator = Emulator::with_spec_and_config(&WAVESHARE_7_5_V2, config);
 
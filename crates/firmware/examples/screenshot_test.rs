@@ -14,7 +14,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Create emulator with 7.5" display, rotated for portrait
     let rotation = Rotation::Degrees90;
-    let config = EmulatorConfig { rotation, scale: 1 };
+    let config = EmulatorConfig {
+        rotation,
+        scale: 1,
+        ..Default::default()
+    };
     let mut emulator = Emulator::with_spec_and_config(&WAVESHARE_7_5_V2, config);
 
     // Render test content
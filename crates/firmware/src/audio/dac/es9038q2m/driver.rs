@@ -39,7 +39,10 @@
 //!    This should be written before any I²S clock is applied, so it belongs in
 //!    init (not after the I²S link is running).
 //!
-//! 7. **Restore volume** — unmute to the requested operating level.
+//! 7. **Oversampling filter** — re-apply the selected filter; the soft reset
+//!    returned `REG_OSF_FILTER` to filter 1.
+//!
+//! 8. **Restore volume** — unmute to the requested operating level.
 //!
 //! # Single-byte read constraint
 //!
@@ -98,6 +101,7 @@ impl<I: core::fmt::Debug> core::fmt::Display for Es9038q2mError<I> {
 pub struct Es9038q2mDriver<I> {
     i2c: I,
     volume: u8,
+    filter: OversamplingFilter,
 }
 
 impl<I: I2c> Es9038q2mDriver<I> {
@@ -108,7 +112,31 @@ impl<I: I2c> Es9038q2mDriver<I> {
     /// after muting on startup.
     #[must_use]
     pub fn new(i2c: I) -> Self {
-        Self { i2c, volume: 80 }
+        Self {
+            i2c,
+            volume: 80,
+            filter: OversamplingFilter::default(),
+        }
+    }
+
+    /// The oversampling filter last selected with
+    /// [`AudioCodec::set_filter`] (re-applied by every `hardware_init`).
+    #[must_use]
+    pub fn filter(&self) -> OversamplingFilter {
+        self.filter
+    }
+
+    /// `REG_OSF_FILTER` bits [2:0] for `filter` (filter N is code N−1).
+    fn filter_bits(filter: OversamplingFilter) -> u8 {
+        match filter {
+            OversamplingFilter::FastRollOffLinearPhase => 0b000,
+            OversamplingFilter::SlowRollOffLinearPhase => 0b001,
+            OversamplingFilter::FastRollOffMinimumPhase => 0b010,
+            OversamplingFilter::SlowRollOffMinimumPhase => 0b011,
+            OversamplingFilter::ApodizingFastRollOff => 0b100,
+            OversamplingFilter::BrickWall => 0b101,
+            OversamplingFilter::HybridFastRollOff => 0b110,
+        }
     }
 
     /// Write a single register over I²C.
@@ -133,6 +161,10 @@ impl<I: I2c> Es9038q2mDriver<I> {
     /// enforces the single-byte-only rule: exactly 1 address byte is written,
     /// then exactly 1 data byte is read back. Never call `read_reg` in a loop
     /// without re-sending the address each time.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Es9038q2mError::I2c`] if the I²C transaction fails.
     pub async fn read_reg(&mut self, reg: u8) -> Result<u8, Es9038q2mError<I::Error>> {
         let mut buf = [0u8; 1];
         self.i2c
@@ -159,6 +191,7 @@ impl<I: I2c> Es9038q2mDriver<I> {
     /// - `volume_to_att(100)` =   0 (0 dB / loudest)
     /// - `volume_to_att(50)`  = 127 (midpoint)
     /// - `volume_to_att(80)`  =  51 (default startup level)
+    #[must_use]
     pub fn volume_to_att(volume: u8) -> u8 {
         // `volume.min(100)` clamps the input to [0, 100]. Then:
         //   (100 - clamped) is in [0, 100] ⊆ u8, no underflow.
@@ -230,7 +263,14 @@ impl<I: I2c> DacDriver for Es9038q2mDriver<I> {
         };
         self.write_reg(REG_DSD_CONFIG, dsd_reg).await?;
 
-        // Step 7: Restore volume from mute to the configured operating level.
+        // Step 7: Oversampling filter.
+        //
+        // The soft reset put REG_OSF_FILTER back to filter 1; write the user's
+        // selection again so it survives power cycles and re-initialisation.
+        self.write_reg(REG_OSF_FILTER, Self::filter_bits(self.filter))
+            .await?;
+
+        // Step 8: Restore volume from mute to the configured operating level.
         //
         // The stored `self.volume` (default 80) is converted to the chip's
         // attenuation register format and written to both channels.
@@ -297,16 +337,10 @@ impl<I: I2c> AudioCodec for Es9038q2mDriver<I> {
     }
 
     async fn set_filter(&mut self, filter: OversamplingFilter) -> Result<(), Self::Error> {
-        let bits: u8 = match filter {
-            OversamplingFilter::FastRollOffLinearPhase => 0b000,
-            OversamplingFilter::SlowRollOffLinearPhase => 0b001,
-            OversamplingFilter::FastRollOffMinimumPhase => 0b010,
-            OversamplingFilter::SlowRollOffMinimumPhase => 0b011,
-            OversamplingFilter::ApodizingFastRollOff => 0b100,
-            OversamplingFilter::BrickWall => 0b101,
-            OversamplingFilter::HybridFastRollOff => 0b110,
-        };
-        self.write_reg(REG_OSF_FILTER, bits).await
+        self.write_reg(REG_OSF_FILTER, Self::filter_bits(filter))
+            .await?;
+        self.filter = filter;
+        Ok(())
     }
 }
 
@@ -362,7 +396,9 @@ mod tests {
             I2cTx::write(ADDR, vec![REG_VOLUME_CTRL, VOLUME_CTRL_INDIVIDUAL_CHANNELS]),
             // Step 6: DSD disabled (default config)
             I2cTx::write(ADDR, vec![REG_DSD_CONFIG, 0x00]),
-            // Step 7: restore volume (80 → att=51)
+            // Step 7: oversampling filter 1 (default)
+            I2cTx::write(ADDR, vec![REG_OSF_FILTER, 0b000]),
+            // Step 8: restore volume (80 → att=51)
             I2cTx::write(ADDR, vec![REG_VOLUME_LEFT, att_80]),
             I2cTx::write(ADDR, vec![REG_VOLUME_RIGHT, att_80]),
         ]
@@ -631,4 +667,36 @@ mod tests {
             mock.done();
         }
     }
+
+    // ---------------------------------------------------------------------------
+    // Test I: the selected filter survives re-initialisation
+    // ---------------------------------------------------------------------------
+    //
+    // The soft reset in hardware_init restores REG_OSF_FILTER to filter 1, so
+    // the driver must write the user's selection again after it.
+
+    #[tokio::test]
+    async fn test_init_reapplies_selected_filter() {
+        let mut transactions = vec![I2cTx::write(ADDR, vec![REG_OSF_FILTER, 0b101])];
+        transactions.extend(default_init_transactions().into_iter().map(|tx| {
+            if tx == I2cTx::write(ADDR, vec![REG_OSF_FILTER, 0b000]) {
+                I2cTx::write(ADDR, vec![REG_OSF_FILTER, 0b101])
+            } else {
+                tx
+            }
+        }));
+        let mut mock = I2cMock::new(&transactions);
+        let mut driver = Es9038q2mDriver::new(mock.clone());
+
+        driver
+            .set_filter(OversamplingFilter::BrickWall)
+            .await
+            .expect("set_filter must succeed");
+        assert_eq!(driver.filter(), OversamplingFilter::BrickWall);
+        driver
+            .hardware_init(AudioConfig::default())
+            .await
+            .expect("hardware_init must succeed");
+        mock.done();
+    }
 }
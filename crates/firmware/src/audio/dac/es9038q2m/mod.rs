@@ -12,5 +12,5 @@ pub mod registers;
 #[cfg(any(feature = "hardware", test))]
 mod driver;
 
-#[cfg(feature = "hardware")]
+#[cfg(any(feature = "hardware", test))]
 pub use driver::Es9038q2mDriver;
@@ -38,7 +38,8 @@ pub struct MockDac {
     pub volume: u8,
     /// Total number of i32 samples written via [`AudioCodec::write_samples`]
     pub samples_written: usize,
-    /// Last filter set via [`AudioCodec::set_filter`]
+    /// Last filter set via [`AudioCodec::set_filter`]; kept across `init`, as
+    /// the ES9038Q2M driver re-applies it after its soft reset
     pub filter: OversamplingFilter,
     /// DSD mode from last [`AudioCodec::init`]
     pub dsd_mode: DsdMode,
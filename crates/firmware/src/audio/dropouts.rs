@@ -0,0 +1,78 @@
+//! Firmware-wide dropout journal and the activity it is correlated with.
+//!
+//! [`ACTIVITY`] is updated by the subsystems that compete with audio:
+//!
+//! - the display service brackets every refresh with
+//!   `refresh_started`/`refresh_finished`;
+//! - the main loop stores the busy share of each
+//!   [`CpuReport`](crate::cpu_usage::CpuReport);
+//! - SD reads on the decode path report their duration through
+//!   `sd_read`.
+//!
+//! The audio task calls [`record`] (or [`record_write_error`] with a SAI
+//! result) on each underrun or overrun; the entry is stored in
+//! [`JOURNAL`] together with an [`ACTIVITY`] snapshot.  The diagnostics
+//! screen reads [`summary`], and [`log`] dumps the journal over defmt.
+//!
+//! Only [`ACTIVITY`] exists in host builds, so the display service can
+//! update it unconditionally; the journal logic itself is tested in
+//! [`platform::dropout_journal`].
+
+use platform::dropout_journal::ActivityMonitor;
+
+#[cfg(feature = "hardware")]
+pub use hardware::{log, record, record_write_error, summary, JOURNAL, JOURNAL_DEPTH};
+
+/// Concurrent activity, updated by the display, storage and main loop.
+pub static ACTIVITY: ActivityMonitor = ActivityMonitor::new();
+
+#[cfg(feature = "hardware")]
+mod hardware {
+    use core::cell::RefCell;
+
+    use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+    use embassy_sync::blocking_mutex::Mutex;
+    use platform::dropout_journal::{DropoutJournal, DropoutKind, DropoutSummary};
+
+    use super::ACTIVITY;
+    use crate::audio::sai_recovery::SaiWriteError;
+
+    /// Dropouts kept in RAM; older entries are overwritten.
+    pub const JOURNAL_DEPTH: usize = 32;
+
+    /// The last [`JOURNAL_DEPTH`] dropouts.
+    ///
+    /// `CriticalSectionRawMutex` so a DMA interrupt can record as well as the
+    /// audio task.
+    pub static JOURNAL: Mutex<CriticalSectionRawMutex, RefCell<DropoutJournal<JOURNAL_DEPTH>>> =
+        Mutex::new(RefCell::new(DropoutJournal::new()));
+
+    /// Record a dropout of `kind` at `at_ms` with the current [`ACTIVITY`].
+    pub fn record(kind: DropoutKind, at_ms: u64) {
+        let activity = ACTIVITY.snapshot();
+        JOURNAL.lock(|journal| journal.borrow_mut().record(at_ms, kind, activity));
+    }
+
+    /// Record a failed SAI write; only overruns are dropouts.
+    pub fn record_write_error(error: SaiWriteError, at_ms: u64) {
+        if error == SaiWriteError::Overrun {
+            record(DropoutKind::Overrun, at_ms);
+        }
+    }
+
+    /// Counts since boot, for the diagnostics report.
+    pub fn summary() -> DropoutSummary {
+        JOURNAL.lock(|journal| journal.borrow().summary())
+    }
+
+    /// Dump the summary and every retained entry over defmt.
+    pub fn log() {
+        JOURNAL.lock(|journal| {
+            let journal = journal.borrow();
+            defmt::info!("Audio dropouts: {}", journal.summary());
+            for entry in journal.iter() {
+                defmt::info!("  {}", entry);
+            }
+        });
+    }
+}
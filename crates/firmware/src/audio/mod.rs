@@ -6,6 +6,8 @@
 //!
 //! - `dac/` — DAC drivers (`Es9038q2mDriver` hardware, `MockDac` for tests)
 //! - `amp/` — Headphone amplifier control (`Tpa6120a2` hardware, `MockAmp` for tests)
+//! - `output` — `AudioOutputManager`: volume scaling and pop-free output profile switching
+//! - `dropouts` — underrun/overrun journal with concurrent display, SD and CPU activity
 //!
 //! # Dependency Injection
 //!
@@ -23,6 +25,8 @@
 
 pub mod amp;
 pub mod dac;
+pub mod dropouts;
+pub mod output;
 pub mod sai_recovery;
 pub mod clock_math;
 pub mod sai_task;
@@ -38,3 +42,5 @@ pub use dac::mock::MockDac;
 pub use amp::tpa6120a2::Tpa6120a2;
 
 pub use amp::mock::MockAmp;
+
+pub use output::AudioOutputManager;
@@ -0,0 +1,213 @@
+//! Audio output manager — owns the DAC and applies output profiles.
+//!
+//! The volume control works in user units (0–100); the active
+//! [`OutputProfile`] scales them into its volume ceiling before they reach
+//! the DAC, so an IEM profile can never be driven at line-out level.
+//!
+//! Changing the DAC filter while audio plays can click, so
+//! [`AudioOutputManager::apply_profile`] mutes first, writes the filter,
+//! adopts the profile and only then restores the re-scaled volume.  If a DAC
+//! write fails the previous profile stays in effect and its volume is
+//! restored — callers never see a half-applied profile.
+
+use platform::{AudioCodec, AudioConfig, OutputProfile};
+
+/// DAC plus the active output profile and user volume.
+pub struct AudioOutputManager<C> {
+    codec: C,
+    profile: OutputProfile,
+    volume: u8,
+}
+
+impl<C: AudioCodec> AudioOutputManager<C> {
+    /// Wrap `codec`, starting on `profile` at user volume `volume` (clamped
+    /// to 100).  Nothing is written until [`init`](Self::init).
+    #[must_use]
+    pub fn new(codec: C, profile: OutputProfile, volume: u8) -> Self {
+        Self {
+            codec,
+            profile,
+            volume: volume.min(100),
+        }
+    }
+
+    /// Initialise the DAC and program the profile's filter and volume.
+    ///
+    /// # Errors
+    ///
+    /// Propagates the first failing codec call.
+    pub async fn init(&mut self, config: AudioConfig) -> Result<(), C::Error> {
+        self.codec.init(config).await?;
+        self.codec.set_filter(self.profile.filter).await?;
+        self.codec
+            .set_volume(self.profile.output_volume(self.volume))
+            .await
+    }
+
+    /// The active profile.
+    #[must_use]
+    pub fn profile(&self) -> &OutputProfile {
+        &self.profile
+    }
+
+    /// User volume (0–100), before profile scaling.
+    #[must_use]
+    pub fn volume(&self) -> u8 {
+        self.volume
+    }
+
+    /// The wrapped codec.
+    #[must_use]
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Set the user volume (clamped to 100), scaled by the active profile.
+    ///
+    /// # Errors
+    ///
+    /// Propagates the codec error; the stored volume is unchanged on error.
+    pub async fn set_volume(&mut self, volume: u8) -> Result<(), C::Error> {
+        let volume = volume.min(100);
+        self.codec
+            .set_volume(self.profile.output_volume(volume))
+            .await?;
+        self.volume = volume;
+        Ok(())
+    }
+
+    /// Switch to `profile` without a pop.
+    ///
+    /// When the filter changes the output is muted around the filter write;
+    /// otherwise only the volume is re-scaled.  The EQ and crossfeed take
+    /// effect with the new profile, read by the signal path through
+    /// [`profile`](Self::profile).
+    ///
+    /// # Errors
+    ///
+    /// Propagates the first failing codec call.  A failed mute or filter
+    /// write leaves the previous profile active, at its previous volume.
+    pub async fn apply_profile(&mut self, profile: &OutputProfile) -> Result<(), C::Error> {
+        if profile.filter != self.profile.filter {
+            let previous = self.profile.output_volume(self.volume);
+            self.codec.set_volume(0).await?;
+            if let Err(e) = self.codec.set_filter(profile.filter).await {
+                // Best effort: the filter error is the one worth reporting.
+                let _ = self.codec.set_volume(previous).await;
+                return Err(e);
+            }
+        }
+        self.profile = profile.clone();
+        self.codec
+            .set_volume(self.profile.output_volume(self.volume))
+            .await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTx};
+    use platform::{Crossfeed, OutputProfiles, OversamplingFilter};
+
+    use super::*;
+    use crate::audio::dac::es9038q2m::registers::{
+        REG_OSF_FILTER, REG_VOLUME_LEFT, REG_VOLUME_RIGHT, VOLUME_MUTE,
+    };
+    use crate::audio::dac::es9038q2m::Es9038q2mDriver;
+    use crate::audio::dac::mock::MockDac;
+
+    const ADDR: u8 = 0x48;
+
+    fn volume_writes(att: u8) -> [I2cTx; 2] {
+        [
+            I2cTx::write(ADDR, vec![REG_VOLUME_LEFT, att]),
+            I2cTx::write(ADDR, vec![REG_VOLUME_RIGHT, att]),
+        ]
+    }
+
+    fn profiles() -> OutputProfiles {
+        OutputProfiles::defaults()
+    }
+
+    #[tokio::test]
+    async fn test_volume_is_scaled_by_profile() {
+        let profiles = profiles();
+        let iem = profiles.get(0).unwrap();
+        let mut output = AudioOutputManager::new(MockDac::new(), iem.clone(), 100);
+        output.init(AudioConfig::default()).await.unwrap();
+        assert_eq!(output.codec().volume, 60);
+
+        output.set_volume(50).await.unwrap();
+        assert_eq!(output.volume(), 50);
+        assert_eq!(output.codec().volume, 30);
+    }
+
+    #[tokio::test]
+    async fn test_apply_profile_updates_dac_and_settings() {
+        let profiles = profiles();
+        let mut output =
+            AudioOutputManager::new(MockDac::new(), profiles.get(0).unwrap().clone(), 50);
+        output.init(AudioConfig::default()).await.unwrap();
+
+        output
+            .apply_profile(profiles.get(2).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(output.profile().name.as_str(), "Line out");
+        assert_eq!(
+            output.codec().filter,
+            OversamplingFilter::SlowRollOffLinearPhase
+        );
+        assert_eq!(output.codec().volume, 50, "user volume re-scaled");
+
+        output
+            .apply_profile(profiles.get(1).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(output.profile().crossfeed, Crossfeed::Light);
+    }
+
+    #[tokio::test]
+    async fn test_filter_change_is_muted() {
+        // IEM (filter 1, ceiling 60) at user volume 50 → Line out (filter 2, ceiling 100).
+        let expectations: Vec<I2cTx> = [
+            volume_writes(VOLUME_MUTE).as_slice(),
+            &[I2cTx::write(ADDR, vec![REG_OSF_FILTER, 0b001])],
+            &volume_writes(Es9038q2mDriver::<I2cMock>::volume_to_att(50)),
+        ]
+        .concat();
+        let mut mock = I2cMock::new(&expectations);
+        let profiles = profiles();
+        let mut output = AudioOutputManager::new(
+            Es9038q2mDriver::new(mock.clone()),
+            profiles.get(0).unwrap().clone(),
+            50,
+        );
+
+        output
+            .apply_profile(profiles.get(2).unwrap())
+            .await
+            .unwrap();
+        mock.done();
+    }
+
+    #[tokio::test]
+    async fn test_same_filter_only_rescales_volume() {
+        // IEM → Planar headphones share filter 1: no mute, no filter write.
+        let expectations = volume_writes(Es9038q2mDriver::<I2cMock>::volume_to_att(50));
+        let mut mock = I2cMock::new(&expectations);
+        let profiles = profiles();
+        let mut output = AudioOutputManager::new(
+            Es9038q2mDriver::new(mock.clone()),
+            profiles.get(0).unwrap().clone(),
+            50,
+        );
+
+        output
+            .apply_profile(profiles.get(1).unwrap())
+            .await
+            .unwrap();
+        mock.done();
+    }
+}
@@ -29,6 +29,8 @@
 
 #![allow(clippy::doc_markdown)] // SAI task docs use hardware signal names (e.g. SAI1_SD_A) that are clearer as plain text
 use platform::dma_safety::{AxiSramRegion, DmaBuffer, AUDIO_DMA_BUFFER_BYTES};
+#[cfg(feature = "hardware")]
+use crate::watchdog::Heartbeat;
 #[allow(unused_imports)]
 use crate::audio::clock_math::{
     MCLK_TARGET_HZ, SAMPLE_RATE_HZ, MCLK_FS_RATIO,
@@ -38,26 +40,31 @@ use crate::audio::clock_math::{
 /// Embassy task wrapper for the SAI audio output — hardware target only.
 ///
 /// Enabled only when `feature = "hardware"` is active (links `embassy-executor`).
-/// Call via `spawner.must_spawn(audio_task_embassy(audio_buf))` in main().
+/// Call via `spawner.must_spawn(audio_task_embassy(audio_buf, heartbeat))` in main().
 ///
 /// # Arguments
 ///
 /// * `buffer` — Unique mutable reference to the AXI SRAM audio DMA buffer.
 ///   `DmaBuffer<AxiSramRegion, _>` enforces at compile time that the buffer is
 ///   in a DMA1/DMA2-accessible memory region (not DTCM).
+/// * `heartbeat` — [`TaskId::Audio`](crate::watchdog::TaskId::Audio) handle
+///   from [`HEARTBEATS`](crate::watchdog::HEARTBEATS); ticked every loop.
 #[cfg(feature = "hardware")]
 #[embassy_executor::task]
 pub async fn audio_task_embassy(
     buffer: &'static mut DmaBuffer<AxiSramRegion, [u8; AUDIO_DMA_BUFFER_BYTES]>,
+    heartbeat: Heartbeat,
 ) {
-    audio_task(buffer).await;
+    audio_task(buffer, heartbeat).await;
 }
 
 /// SAI audio output task implementation — hardware target only.
 ///
 /// Streams silence (zero-fill) via SAI1 DMA in the initial implementation.
-/// When the audio decode pipeline is ready, this task will read from the decode
-/// channel and copy samples into the DMA half-buffer on HTIE/TCIE interrupts.
+/// When the audio decode pipeline is ready, the decoder will fill a
+/// `playback::ring_buffer::RingBuffer` placed in AXI SRAM, and this task will
+/// point each DMA half-transfer at `Consumer::linear_chunk` directly, with no
+/// intermediate copy, calling `Consumer::consume` on HTIE/TCIE.
 ///
 /// # Safety of the DMA buffer
 ///
@@ -69,6 +76,7 @@ pub async fn audio_task_embassy(
 #[cfg(feature = "hardware")]
 pub async fn audio_task(
     _buffer: &'static mut DmaBuffer<AxiSramRegion, [u8; AUDIO_DMA_BUFFER_BYTES]>,
+    heartbeat: Heartbeat,
 ) {
     // TODO: Initialize SAI1 peripheral here via embassy-stm32 when full audio pipeline is ready.
     //
@@ -94,8 +102,15 @@ pub async fn audio_task(
     // Blocked on: PLL3 configuration in firmware::boot::build_embassy_config().
     // PLL1Q is currently 200 MHz (SDMMC clock). SAI1 needs a dedicated PLL3 branch.
     // See: hardware/CLAUDE.md for PLL3 divisor target (49.152 MHz).
+    //
+    // Dropouts: pass each failed write to
+    // crate::audio::dropouts::record_write_error, and call
+    // dropouts::record(DropoutKind::Underrun, ..) when a half-transfer finds
+    // the decode ring buffer short, so the journal sees both directions.
 
+    defmt::info!("{=str}", platform::smoke::SmokeMarker::AudioAlive.tag());
     loop {
+        heartbeat.tick();
         embassy_time::Timer::after_secs(1).await;
     }
 }
@@ -0,0 +1,656 @@
+//! Cooperative scheduler for long-running background work.
+//!
+//! Library scans, loudness analysis and thumbnail generation each take
+//! seconds to minutes.  Running one inside a plain Embassy task would hold
+//! the executor until it finished, starving the display and input tasks
+//! that share it.  Instead each job is written as a resumable state machine
+//! ([`BackgroundJob::step`] does one small unit of work) and the
+//! [`Scheduler`] runs steps until a per-slice time budget is spent, then
+//! returns so the caller can yield.
+//!
+//! # Scheduling policy
+//!
+//! - The highest [`Priority`] runnable job gets the slice.
+//! - Jobs of equal priority take turns (least recently run first).
+//! - Every slice runs at least one step, so a budget shorter than one step
+//!   still makes progress.
+//! - [`Scheduler::pause`] stops all work, e.g. while a full display refresh
+//!   or an SD-card heavy track change is in flight.
+//!
+//! # Progress events
+//!
+//! The scheduler queues [`SchedulerEvent`]s (started, progress, finished,
+//! failed, cancelled) for the UI to drain with [`Scheduler::pop_event`].
+//! A progress event is only queued when the whole-percent value changes,
+//! so a job stepping thousands of times does not flood the queue.  When the
+//! queue is full the oldest event is dropped and counted.
+//!
+//! # Usage
+//!
+//! ```rust,ignore
+//! let mut scan = LibraryScanJob::new(&mut sd);
+//! let mut scheduler: Scheduler<'_> = Scheduler::new();
+//! scheduler.submit(&mut scan, Priority::Normal)?;
+//!
+//! // Hardware: slices of DEFAULT_SLICE_US with a yield in between.
+//! background::run_until_idle(&mut scheduler, DEFAULT_SLICE_US).await;
+//! ```
+
+use heapless::{Deque, Vec};
+
+/// Maximum number of jobs queued at once.
+pub const MAX_JOBS: usize = 8;
+
+/// Depth of the progress-event queue.
+pub const EVENT_QUEUE_DEPTH: usize = 16;
+
+/// Default slice budget in microseconds.
+///
+/// 2 ms keeps input latency imperceptible and is well inside the SAI DMA
+/// half-buffer period, which the audio task must service.
+pub const DEFAULT_SLICE_US: u64 = 2_000;
+
+/// Relative urgency of a background job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Priority {
+    /// Opportunistic work (e.g. pre-generating thumbnails).
+    Low,
+    /// Default for user-visible but non-blocking work.
+    Normal,
+    /// Work the user is actively waiting on (e.g. first library scan).
+    High,
+}
+
+/// What a job does, for labelling progress in the UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum JobKind {
+    /// Walking the SD card and indexing tracks.
+    LibraryScan,
+    /// Measuring integrated loudness for ReplayGain-style normalisation.
+    LoudnessAnalysis,
+    /// Decoding and downscaling embedded album art.
+    ThumbnailGeneration,
+}
+
+/// Units of work completed out of the total.
+///
+/// `total == 0` means the total is not known yet (e.g. a scan that has not
+/// finished counting files).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Progress {
+    /// Units completed.
+    pub done: u32,
+    /// Units in total, or 0 if unknown.
+    pub total: u32,
+}
+
+impl Progress {
+    /// Create a progress value.
+    #[must_use]
+    pub const fn new(done: u32, total: u32) -> Self {
+        Self { done, total }
+    }
+
+    /// Whole-percent completion, clamped to 0–100 (0 when the total is unknown).
+    #[must_use]
+    pub fn percent(&self) -> u8 {
+        let done = u64::from(self.done.min(self.total));
+        let pct = done
+            .saturating_mul(100)
+            .checked_div(u64::from(self.total))
+            .unwrap_or(0);
+        u8::try_from(pct).unwrap_or(100)
+    }
+}
+
+/// Result of one [`BackgroundJob::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// More work remains.
+    Pending(Progress),
+    /// The job completed successfully.
+    Done,
+    /// The job hit an unrecoverable error and should be dropped.
+    Failed,
+}
+
+/// A resumable unit of background work.
+///
+/// Implementations keep all their state in `self` and do a *small* amount
+/// of work per call — one directory entry, one block of samples, one image
+/// row band.  A single step should take well under [`DEFAULT_SLICE_US`].
+pub trait BackgroundJob {
+    /// What kind of work this is.
+    fn kind(&self) -> JobKind;
+
+    /// Do one unit of work.
+    fn step(&mut self) -> Step;
+}
+
+/// Handle identifying a submitted job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct JobId(u16);
+
+/// Lifecycle notification for the UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SchedulerEvent {
+    /// The job ran its first step.
+    Started {
+        /// Job handle.
+        id: JobId,
+        /// Job kind.
+        kind: JobKind,
+    },
+    /// The job's whole-percent progress changed.
+    Progress {
+        /// Job handle.
+        id: JobId,
+        /// Job kind.
+        kind: JobKind,
+        /// Latest progress.
+        progress: Progress,
+    },
+    /// The job completed.
+    Finished {
+        /// Job handle.
+        id: JobId,
+        /// Job kind.
+        kind: JobKind,
+    },
+    /// The job reported [`Step::Failed`].
+    Failed {
+        /// Job handle.
+        id: JobId,
+        /// Job kind.
+        kind: JobKind,
+    },
+    /// The job was removed with [`Scheduler::cancel`].
+    Cancelled {
+        /// Job handle.
+        id: JobId,
+        /// Job kind.
+        kind: JobKind,
+    },
+}
+
+/// Error returned by [`Scheduler::submit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SubmitError {
+    /// All [`MAX_JOBS`] slots are in use.
+    QueueFull,
+}
+
+/// What happened during one [`Scheduler::run_slice`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SliceReport {
+    /// The job that ran, if any.
+    pub job: Option<JobId>,
+    /// Number of steps executed.
+    pub steps: u32,
+    /// Time spent, in microseconds.
+    pub elapsed_us: u64,
+}
+
+struct Entry<'a> {
+    id: JobId,
+    priority: Priority,
+    job: &'a mut dyn BackgroundJob,
+    started: bool,
+    last_run: u32,
+    last_percent: Option<u8>,
+}
+
+/// Cooperative background-job scheduler.
+///
+/// Jobs are borrowed, not owned, so they can live in `StaticCell`s on the
+/// device without a heap.
+pub struct Scheduler<'a, const N: usize = MAX_JOBS> {
+    jobs: Vec<Entry<'a>, N>,
+    events: Deque<SchedulerEvent, EVENT_QUEUE_DEPTH>,
+    next_id: u16,
+    round: u32,
+    paused: bool,
+    dropped_events: u32,
+}
+
+impl<const N: usize> Default for Scheduler<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, const N: usize> Scheduler<'a, N> {
+    /// Create an empty scheduler.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            jobs: Vec::new(),
+            events: Deque::new(),
+            next_id: 0,
+            round: 0,
+            paused: false,
+            dropped_events: 0,
+        }
+    }
+
+    /// Queue a job.
+    ///
+    /// # Errors
+    ///
+    /// [`SubmitError::QueueFull`] when `N` jobs are already queued.
+    pub fn submit(
+        &mut self,
+        job: &'a mut dyn BackgroundJob,
+        priority: Priority,
+    ) -> Result<JobId, SubmitError> {
+        let id = JobId(self.next_id);
+        let entry = Entry {
+            id,
+            priority,
+            job,
+            started: false,
+            // New jobs go to the back of their priority's rotation.
+            last_run: self.round,
+            last_percent: None,
+        };
+        self.jobs.push(entry).map_err(|_| SubmitError::QueueFull)?;
+        self.next_id = self.next_id.wrapping_add(1);
+        Ok(id)
+    }
+
+    /// Remove a job before it finishes.  Returns `false` if `id` is unknown.
+    pub fn cancel(&mut self, id: JobId) -> bool {
+        let Some(idx) = self.index_of(id) else {
+            return false;
+        };
+        let entry = self.jobs.remove(idx);
+        self.push_event(SchedulerEvent::Cancelled {
+            id,
+            kind: entry.job.kind(),
+        });
+        true
+    }
+
+    /// Change a queued job's priority.  Returns `false` if `id` is unknown.
+    pub fn set_priority(&mut self, id: JobId, priority: Priority) -> bool {
+        match self.index_of(id).and_then(|idx| self.jobs.get_mut(idx)) {
+            Some(entry) => {
+                entry.priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop running jobs until [`resume`](Self::resume).
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume running jobs after [`pause`](Self::pause).
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// `true` while paused.
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Number of queued jobs.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    /// `true` when no jobs are queued.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// `true` when [`run_slice`](Self::run_slice) would do nothing.
+    #[must_use]
+    pub fn is_idle(&self) -> bool {
+        self.paused || self.jobs.is_empty()
+    }
+
+    /// Take the oldest pending event.
+    pub fn pop_event(&mut self) -> Option<SchedulerEvent> {
+        self.events.pop_front()
+    }
+
+    /// Events discarded because the queue was full.
+    #[must_use]
+    pub fn dropped_events(&self) -> u32 {
+        self.dropped_events
+    }
+
+    /// Run the most urgent job until `budget_us` has elapsed or it finishes.
+    ///
+    /// `now_us` is a monotonic microsecond clock (`Instant::now().as_micros()`
+    /// on the device, a fake counter in tests).
+    pub fn run_slice(&mut self, budget_us: u64, mut now_us: impl FnMut() -> u64) -> SliceReport {
+        if self.paused {
+            return SliceReport::default();
+        }
+        let Some(idx) = self.pick() else {
+            return SliceReport::default();
+        };
+        self.round = self.round.wrapping_add(1);
+        let round = self.round;
+        let start = now_us();
+
+        let mut report = SliceReport::default();
+        let Some(entry) = self.jobs.get_mut(idx) else {
+            return report;
+        };
+        report.job = Some(entry.id);
+        entry.last_run = round;
+        let id = entry.id;
+        let kind = entry.job.kind();
+
+        let mut events: Vec<SchedulerEvent, 2> = Vec::new();
+        if !entry.started {
+            entry.started = true;
+            let _ = events.push(SchedulerEvent::Started { id, kind });
+        }
+        let finished = loop {
+            let step = entry.job.step();
+            report.steps = report.steps.saturating_add(1);
+            match step {
+                Step::Pending(progress) => {
+                    let pct = progress.percent();
+                    if entry.last_percent != Some(pct) {
+                        entry.last_percent = Some(pct);
+                        // Only the latest progress of this slice matters.
+                        if let Some(SchedulerEvent::Progress { .. }) = events.last() {
+                            let _ = events.pop();
+                        }
+                        let _ = events.push(SchedulerEvent::Progress { id, kind, progress });
+                    }
+                    if now_us().saturating_sub(start) >= budget_us {
+                        break None;
+                    }
+                }
+                Step::Done => break Some(SchedulerEvent::Finished { id, kind }),
+                Step::Failed => break Some(SchedulerEvent::Failed { id, kind }),
+            }
+        };
+        report.elapsed_us = now_us().saturating_sub(start);
+
+        for event in events {
+            self.push_event(event);
+        }
+
+        if let Some(event) = finished {
+            self.jobs.remove(idx);
+            self.push_event(event);
+        }
+        report
+    }
+
+    /// Index of the job that should run next.
+    fn pick(&self) -> Option<usize> {
+        self.jobs
+            .iter()
+            .enumerate()
+            // Highest priority first, then least recently run, then oldest
+            // submission.  Stamps are compared as distances from the current
+            // round so the u32 wrap after ~4 billion slices does not reorder
+            // the rotation.
+            .max_by_key(|&(i, e)| {
+                (
+                    e.priority,
+                    self.round.wrapping_sub(e.last_run),
+                    core::cmp::Reverse(i),
+                )
+            })
+            .map(|(i, _)| i)
+    }
+
+    fn index_of(&self, id: JobId) -> Option<usize> {
+        self.jobs.iter().position(|e| e.id == id)
+    }
+
+    fn push_event(&mut self, event: SchedulerEvent) {
+        if self.events.is_full() {
+            let _ = self.events.pop_front();
+            self.dropped_events = self.dropped_events.saturating_add(1);
+        }
+        let _ = self.events.push_back(event);
+    }
+}
+
+/// Run slices until the scheduler is idle, yielding to the executor between
+/// slices so display, input and audio tasks keep their deadlines.
+///
+/// Slice time is charged to [`CpuTask::Scanner`](crate::cpu_usage::CpuTask::Scanner).
+#[cfg(feature = "hardware")]
+pub async fn run_until_idle<const N: usize>(scheduler: &mut Scheduler<'_, N>, slice_us: u64) {
+    while !scheduler.is_idle() {
+        crate::cpu_usage::measure(crate::cpu_usage::CpuTask::Scanner, || {
+            scheduler.run_slice(slice_us, || embassy_time::Instant::now().as_micros());
+        });
+        embassy_futures::yield_now().await;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+#[allow(clippy::indexing_slicing)] // Tests index with known bounds
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+#[allow(clippy::many_single_char_names)] // Jobs a, b, c and scheduler s
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    /// Job that takes `total` steps, each advancing the fake clock.
+    struct Counter {
+        kind: JobKind,
+        done: u32,
+        total: u32,
+        fail_at: Option<u32>,
+    }
+
+    impl Counter {
+        fn new(kind: JobKind, total: u32) -> Self {
+            Self {
+                kind,
+                done: 0,
+                total,
+                fail_at: None,
+            }
+        }
+    }
+
+    impl BackgroundJob for Counter {
+        fn kind(&self) -> JobKind {
+            self.kind
+        }
+
+        fn step(&mut self) -> Step {
+            if self.fail_at == Some(self.done) {
+                return Step::Failed;
+            }
+            self.done += 1;
+            if self.done >= self.total {
+                Step::Done
+            } else {
+                Step::Pending(Progress::new(self.done, self.total))
+            }
+        }
+    }
+
+    /// Fake clock advancing `per_read` µs on every read.
+    fn clock(t: &Cell<u64>, per_read: u64) -> impl FnMut() -> u64 + '_ {
+        move || {
+            let now = t.get();
+            t.set(now + per_read);
+            now
+        }
+    }
+
+    fn drain(s: &mut Scheduler<'_>) -> std::vec::Vec<SchedulerEvent> {
+        core::iter::from_fn(|| s.pop_event()).collect()
+    }
+
+    #[test]
+    fn test_progress_percent() {
+        assert_eq!(Progress::new(0, 0).percent(), 0);
+        assert_eq!(Progress::new(1, 3).percent(), 33);
+        assert_eq!(Progress::new(5, 4).percent(), 100);
+    }
+
+    #[test]
+    fn test_slice_stops_at_budget() {
+        let mut job = Counter::new(JobKind::LibraryScan, 1_000);
+        let mut s: Scheduler<'_> = Scheduler::new();
+        s.submit(&mut job, Priority::Normal).unwrap();
+
+        let t = Cell::new(0);
+        // Each step costs 100 µs of clock reads; 1 ms budget ≈ 10 steps.
+        let report = s.run_slice(1_000, clock(&t, 100));
+        assert!(report.steps >= 5 && report.steps <= 11, "{report:?}");
+        assert!(!s.is_empty());
+    }
+
+    #[test]
+    fn test_always_runs_one_step() {
+        let mut job = Counter::new(JobKind::LibraryScan, 10);
+        let mut s: Scheduler<'_> = Scheduler::new();
+        s.submit(&mut job, Priority::Normal).unwrap();
+        let t = Cell::new(0);
+        assert_eq!(s.run_slice(0, clock(&t, 1)).steps, 1);
+    }
+
+    #[test]
+    fn test_events_started_progress_finished() {
+        let mut job = Counter::new(JobKind::LoudnessAnalysis, 4);
+        let mut s: Scheduler<'_> = Scheduler::new();
+        let id = s.submit(&mut job, Priority::Normal).unwrap();
+        let t = Cell::new(0);
+        s.run_slice(u64::MAX, clock(&t, 1));
+        assert!(s.is_empty());
+
+        let kind = JobKind::LoudnessAnalysis;
+        assert_eq!(
+            drain(&mut s),
+            [
+                SchedulerEvent::Started { id, kind },
+                SchedulerEvent::Progress {
+                    id,
+                    kind,
+                    progress: Progress::new(3, 4)
+                },
+                SchedulerEvent::Finished { id, kind },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_progress_event_only_on_percent_change() {
+        let mut job = Counter::new(JobKind::ThumbnailGeneration, 10_000);
+        let mut s: Scheduler<'_> = Scheduler::new();
+        s.submit(&mut job, Priority::Low).unwrap();
+        let t = Cell::new(0);
+        // One step per slice (zero budget): 50 slices cover under 1 %.
+        for _ in 0..50 {
+            s.run_slice(0, clock(&t, 1));
+        }
+        let progress = drain(&mut s)
+            .into_iter()
+            .filter(|e| matches!(e, SchedulerEvent::Progress { .. }))
+            .count();
+        assert_eq!(progress, 1);
+    }
+
+    #[test]
+    fn test_higher_priority_runs_first() {
+        let mut low = Counter::new(JobKind::ThumbnailGeneration, 100);
+        let mut high = Counter::new(JobKind::LibraryScan, 100);
+        let mut s: Scheduler<'_> = Scheduler::new();
+        s.submit(&mut low, Priority::Low).unwrap();
+        let high_id = s.submit(&mut high, Priority::High).unwrap();
+        let t = Cell::new(0);
+        for _ in 0..3 {
+            assert_eq!(s.run_slice(0, clock(&t, 1)).job, Some(high_id));
+        }
+    }
+
+    #[test]
+    fn test_equal_priority_round_robin() {
+        let mut a = Counter::new(JobKind::LibraryScan, 100);
+        let mut b = Counter::new(JobKind::LoudnessAnalysis, 100);
+        let mut s: Scheduler<'_> = Scheduler::new();
+        let a_id = s.submit(&mut a, Priority::Normal).unwrap();
+        let b_id = s.submit(&mut b, Priority::Normal).unwrap();
+        let t = Cell::new(0);
+        let order: std::vec::Vec<_> = (0..4)
+            .map(|_| s.run_slice(0, clock(&t, 1)).job.unwrap())
+            .collect();
+        assert_eq!(order, [a_id, b_id, a_id, b_id]);
+    }
+
+    #[test]
+    fn test_pause_and_cancel() {
+        let mut job = Counter::new(JobKind::LibraryScan, 100);
+        let mut s: Scheduler<'_> = Scheduler::new();
+        let id = s.submit(&mut job, Priority::Normal).unwrap();
+        let t = Cell::new(0);
+
+        s.pause();
+        assert!(s.is_idle());
+        assert_eq!(s.run_slice(1_000, clock(&t, 1)).steps, 0);
+        s.resume();
+
+        assert!(s.cancel(id));
+        assert!(!s.cancel(id));
+        assert_eq!(
+            drain(&mut s),
+            [SchedulerEvent::Cancelled {
+                id,
+                kind: JobKind::LibraryScan
+            }]
+        );
+    }
+
+    #[test]
+    fn test_failed_job_removed() {
+        let mut job = Counter::new(JobKind::LoudnessAnalysis, 100);
+        job.fail_at = Some(2);
+        let mut s: Scheduler<'_> = Scheduler::new();
+        let id = s.submit(&mut job, Priority::Normal).unwrap();
+        let t = Cell::new(0);
+        s.run_slice(u64::MAX, clock(&t, 1));
+        assert!(s.is_empty());
+        assert_eq!(
+            drain(&mut s).last(),
+            Some(&SchedulerEvent::Failed {
+                id,
+                kind: JobKind::LoudnessAnalysis
+            })
+        );
+    }
+
+    #[test]
+    fn test_queue_full() {
+        let mut jobs = [
+            Counter::new(JobKind::LibraryScan, 1),
+            Counter::new(JobKind::LibraryScan, 1),
+            Counter::new(JobKind::LibraryScan, 1),
+        ];
+        let mut s: Scheduler<'_, 2> = Scheduler::new();
+        let [a, b, c] = &mut jobs;
+        s.submit(a, Priority::Normal).unwrap();
+        s.submit(b, Priority::Normal).unwrap();
+        assert_eq!(s.submit(c, Priority::Normal), Err(SubmitError::QueueFull));
+    }
+}
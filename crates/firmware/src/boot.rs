@@ -12,6 +12,7 @@
 //! These steps must run from privileged mode before any RTOS tasks start.
 
 #![allow(clippy::doc_markdown)] // Embedded firmware docs use hardware register names (e.g. PLL2R, HSI48) that are not code but are clearer without forced backticks
+use platform::crash_loop::ResetCause;
 use platform::mpu::MpuApplier;
 use platform::sdram::{SdramTiming, W9825G6KH6_REFRESH_COUNT};
 
@@ -466,6 +467,9 @@ pub fn build_embassy_config(_mpu_configured: &MpuConfigured) -> embassy_stm32::C
     config.rcc.hsi48 = Some(Hsi48Config {
         sync_from_usb: false,
     });
+    // LSE: 32.768 kHz crystal clocking the RTC in the backup domain, so
+    // wall-clock time survives resets (see firmware::rtc).
+    config.rcc.ls = LsConfig::default_lse();
 
     // ── PLL1: system clock + SDMMC kernel clock ──────────────────────────────
     // HSI (64 MHz) / prediv(4) = 16 MHz → × mul(50) = 800 MHz VCO
@@ -1062,6 +1066,117 @@ pub mod hardware {
     }
 }
 
+// ── Reset cause ───────────────────────────────────────────────────────────────
+
+/// `RCC_RSR` reset flags (RM0433 §8.7.39).  They accumulate until cleared
+/// with `RMVF`, and one reset can set several: a power-on reset also sets
+/// BOR and PIN, a software or watchdog reset also pulses NRST (PIN).
+const RSR_BORRSTF: u32 = 0x0020_0000;
+const RSR_PINRSTF: u32 = 0x0040_0000;
+const RSR_PORRSTF: u32 = 0x0080_0000;
+const RSR_SFTRSTF: u32 = 0x0100_0000;
+const RSR_IWDG1RSTF: u32 = 0x0400_0000;
+const RSR_WWDG1RSTF: u32 = 0x1000_0000;
+
+/// The cause behind an `RCC_RSR` value, most specific flag first.
+#[must_use]
+pub fn reset_cause_from_rsr(rsr: u32) -> ResetCause {
+    if rsr & (RSR_IWDG1RSTF | RSR_WWDG1RSTF) != 0 {
+        ResetCause::Watchdog
+    } else if rsr & RSR_PORRSTF != 0 {
+        ResetCause::PowerOn
+    } else if rsr & RSR_BORRSTF != 0 {
+        ResetCause::Brownout
+    } else if rsr & RSR_SFTRSTF != 0 {
+        ResetCause::Software
+    } else if rsr & RSR_PINRSTF != 0 {
+        ResetCause::Pin
+    } else {
+        ResetCause::Unknown
+    }
+}
+
+/// Read and clear the reset flags, for
+/// [`crash_loop::begin_boot`](platform::crash_loop::begin_boot).
+///
+/// Call once per boot: the next call only sees resets after this one.
+#[cfg(feature = "hardware")]
+pub fn take_reset_cause() -> ResetCause {
+    let rsr = embassy_stm32::pac::RCC.rsr().read().0;
+    embassy_stm32::pac::RCC.rsr().modify(|w| w.set_rmvf(true));
+    reset_cause_from_rsr(rsr)
+}
+
+// ── Boot timing ───────────────────────────────────────────────────────────────
+
+/// Core clock cycles per millisecond out of reset (HSI, 64 MHz).
+///
+/// The Embassy time driver only starts inside `embassy_stm32::init()`, so
+/// the phases before it are timed with DWT `CYCCNT` at this rate.
+pub const RESET_CLOCK_CYCLES_PER_MS: u32 = 64_000;
+
+/// Milliseconds since reset for a DWT cycle count taken before the time
+/// driver runs.
+///
+/// Cycles spent after `embassy_stm32::init()` switches SYSCLK to PLL1 run
+/// 7.5× faster than HSI, so a count that spans the switch gives an upper
+/// bound.
+#[must_use]
+pub fn reset_clock_ms(cycles: u32) -> u64 {
+    u64::from(cycles / RESET_CLOCK_CYCLES_PER_MS)
+}
+
+/// Emit `report` over defmt: one line per marked phase, first sound if a
+/// track was resumed, then the total.
+#[cfg(feature = "hardware")]
+pub fn log_boot_report(report: &platform::boot_timing::BootReport) {
+    for t in report.phases() {
+        if t.within_budget() {
+            defmt::info!(
+                "boot {=str}: {=u64} ms (budget {=u64} ms)",
+                t.phase.name(),
+                t.duration_ms(),
+                t.phase.budget_ms()
+            );
+        } else {
+            defmt::warn!(
+                "boot {=str}: {=u64} ms over budget ({=u64} ms)",
+                t.phase.name(),
+                t.duration_ms(),
+                t.phase.budget_ms()
+            );
+        }
+    }
+    if let Some(ms) = report.first_sound_ms {
+        if report.sound_within_budget() {
+            defmt::info!(
+                "boot: first sound at {=u64} ms (budget {=u64} ms)",
+                ms,
+                platform::boot_timing::SOUND_BUDGET_MS
+            );
+        } else {
+            defmt::warn!(
+                "boot: first sound at {=u64} ms over budget ({=u64} ms)",
+                ms,
+                platform::boot_timing::SOUND_BUDGET_MS
+            );
+        }
+    }
+    if report.total_ms <= platform::boot_timing::BOOT_BUDGET_MS {
+        defmt::info!(
+            "boot: first frame at {=u64} ms (budget {=u64} ms)",
+            report.total_ms,
+            platform::boot_timing::BOOT_BUDGET_MS
+        );
+    } else {
+        defmt::warn!(
+            "boot: first frame at {=u64} ms over budget ({=u64} ms)",
+            report.total_ms,
+            platform::boot_timing::BOOT_BUDGET_MS
+        );
+    }
+}
+
 // ─── Tests ────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -1309,6 +1424,25 @@ mod tests {
             "boot.rs hardware module must import AtomicBool and Ordering for the              Peripherals::steal() once-guard. Add:              `use core::sync::atomic::{{AtomicBool, Ordering}};` in the hardware module."
         );
     }
+
+    #[test]
+    fn test_reset_cause_prefers_watchdog_and_power_on_over_pin() {
+        // A watchdog reset pulses NRST too; a power-on reset sets BOR and PIN.
+        assert_eq!(
+            reset_cause_from_rsr(RSR_IWDG1RSTF | RSR_PINRSTF),
+            ResetCause::Watchdog
+        );
+        assert_eq!(
+            reset_cause_from_rsr(RSR_PORRSTF | RSR_BORRSTF | RSR_PINRSTF),
+            ResetCause::PowerOn
+        );
+        assert_eq!(
+            reset_cause_from_rsr(RSR_SFTRSTF | RSR_PINRSTF),
+            ResetCause::Software
+        );
+        assert_eq!(reset_cause_from_rsr(RSR_PINRSTF), ResetCause::Pin);
+        assert_eq!(reset_cause_from_rsr(0), ResetCause::Unknown);
+    }
 }
 
 // ── GAP D1: PLL frequency constant tests ─────────────────────────────────────
@@ -1385,7 +1519,7 @@ mod pll_tests {
         let vco = PLL3_HSI_HZ / PLL3_M * PLL3_N;
         let pll3p = vco / PLL3_P;
         // Check within 1000 ppm of target
-        let diff_hz = if pll3p > TARGET_MCLK_HZ { pll3p - TARGET_MCLK_HZ } else { TARGET_MCLK_HZ - pll3p };
+        let diff_hz = pll3p.abs_diff(TARGET_MCLK_HZ);
         let ppm_error = diff_hz * 1_000_000 / TARGET_MCLK_HZ;
         assert!(
             ppm_error <= MAX_PPM_ERROR,
@@ -1404,7 +1538,7 @@ mod pll_tests {
         let fmc_clk = pll2r / FMC_INTERNAL_DIV;
         assert_eq!(
             fmc_clk,
-            crate::sdram::FMC_CLK_HZ as u64,
+            u64::from(crate::sdram::FMC_CLK_HZ),
             "FMC_CLK_HZ ({} Hz) must equal PLL2R/2 ({} Hz)",
             crate::sdram::FMC_CLK_HZ,
             fmc_clk
@@ -1415,21 +1549,31 @@ mod pll_tests {
     fn pll1_vco_within_spec() {
         // STM32H743 VCO range: 192 MHz to 836 MHz (RM0433 section 8.3.2)
         let vco = PLL1_HSI_HZ / PLL1_M * PLL1_N;
-        assert!(vco >= 192_000_000 && vco <= 836_000_000,
+        assert!((192_000_000..=836_000_000).contains(&vco),
             "PLL1 VCO = {vco} Hz is outside STM32H743 spec (192-836 MHz)");
     }
 
     #[test]
     fn pll2_vco_within_spec() {
         let vco = PLL2_HSI_HZ / PLL2_M * PLL2_N;
-        assert!(vco >= 192_000_000 && vco <= 836_000_000,
+        assert!((192_000_000..=836_000_000).contains(&vco),
             "PLL2 VCO = {vco} Hz is outside STM32H743 spec (192-836 MHz)");
     }
 
     #[test]
     fn pll3_vco_within_spec() {
         let vco = PLL3_HSI_HZ / PLL3_M * PLL3_N;
-        assert!(vco >= 192_000_000 && vco <= 836_000_000,
+        assert!((192_000_000..=836_000_000).contains(&vco),
             "PLL3 VCO = {vco} Hz is outside STM32H743 spec (192-836 MHz)");
     }
+
+    #[test]
+    fn reset_clock_ms_counts_hsi_cycles() {
+        assert_eq!(
+            PLL1_HSI_HZ / 1_000,
+            u64::from(super::RESET_CLOCK_CYCLES_PER_MS)
+        );
+        assert_eq!(super::reset_clock_ms(63_999), 0);
+        assert_eq!(super::reset_clock_ms(640_000), 10);
+    }
 }
@@ -0,0 +1,235 @@
+//! Bridge from the playback event bus to the BLE companion server.
+//!
+//! `playback` and `bluetooth` are separate vertical slices and may not
+//! depend on each other, so the firmware does the wiring: the BLE task
+//! holds a bus subscriber and, each time it wakes, passes the queued
+//! events through [`forward`] into its
+//! [`CompanionServer`](bluetooth::companion::CompanionServer), looks up the
+//! metadata of the track it returns, and asks [`LagWatch`] whether events
+//! were lost while it was busy with the radio.
+//!
+//! ```ignore
+//! let started = companion::forward(&mut server, core::iter::from_fn(|| sub.try_next_event()));
+//! if let Some(id) = started { /* library lookup, then server.set_metadata(..) */ }
+//! lag.check(&mut server, sub.lagged(), || engine_snapshot());
+//! ```
+//!
+//! Files the phone uploads over the transfer characteristic land in a
+//! [`TransferReceiver`]; once one is verified, [`apply_transfer`] hands it
+//! to the library and reports the outcome to the phone.
+
+use bluetooth::companion::{CompanionServer, PlayerEvent, PlayerSnapshot};
+use bluetooth::gatt::TransportState;
+use bluetooth::transfer::{
+    TransferError, TransferKind, TransferReceiver, TransferStatus, TransferStore,
+};
+use library::delta::{apply_delta, DeltaError, DeltaStore, LibraryDelta};
+use library::reader::SoulLibraryReader;
+use library::tag_overrides::TagOverrides;
+use platform::storage::{File, Storage};
+use playback::engine::PlaybackState;
+use playback::events::PlaybackEvent;
+
+/// Wire state for an engine state.
+#[must_use]
+pub const fn transport_state(state: PlaybackState) -> TransportState {
+    match state {
+        PlaybackState::Stopped => TransportState::Stopped,
+        PlaybackState::Playing => TransportState::Playing,
+        PlaybackState::Paused => TransportState::Paused,
+    }
+}
+
+/// The companion server's view of a bus event, or `None` for events the
+/// phone does not see (underruns and faults).
+#[must_use]
+pub const fn player_event(event: PlaybackEvent) -> Option<PlayerEvent> {
+    match event {
+        PlaybackEvent::TrackStarted {
+            track_id,
+            duration_ms,
+        } => Some(PlayerEvent::TrackStarted {
+            track_id,
+            duration_ms,
+        }),
+        PlaybackEvent::StateChanged(state) => {
+            Some(PlayerEvent::StateChanged(transport_state(state)))
+        }
+        PlaybackEvent::PositionTick { position_ms } => {
+            Some(PlayerEvent::PositionTick { position_ms })
+        }
+        PlaybackEvent::EqChanged { preset } => Some(PlayerEvent::EqChanged { preset }),
+        PlaybackEvent::BufferUnderrun { .. } | PlaybackEvent::Fault { .. } => None,
+    }
+}
+
+/// Apply `events` to `server` in order.  Returns the last track that
+/// started, whose title and artist the caller should look up and pass to
+/// [`CompanionServer::set_metadata`].
+pub fn forward(
+    server: &mut CompanionServer,
+    events: impl IntoIterator<Item = PlaybackEvent>,
+) -> Option<u32> {
+    let mut started = None;
+    for event in events.into_iter().filter_map(player_event) {
+        if let PlayerEvent::TrackStarted { track_id, .. } = event {
+            started = Some(track_id);
+        }
+        server.apply(event);
+    }
+    started
+}
+
+/// Notices growth in a subscriber's lag counter and resynchronises the
+/// server when it happens.
+#[derive(Debug, Default)]
+pub struct LagWatch {
+    seen: u64,
+}
+
+impl LagWatch {
+    /// Start with no lag seen.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { seen: 0 }
+    }
+
+    /// Compare `lagged` (the subscriber's running total) with the last
+    /// value seen, and on growth replace the server's state with
+    /// `snapshot()`.  Returns whether it resynchronised.
+    pub fn check(
+        &mut self,
+        server: &mut CompanionServer,
+        lagged: u64,
+        snapshot: impl FnOnce() -> PlayerSnapshot,
+    ) -> bool {
+        if lagged <= self.seen {
+            return false;
+        }
+        self.seen = lagged;
+        server.resync(snapshot());
+        true
+    }
+}
+
+/// Apply the file `receiver` has verified, if any, and queue the
+/// [`Done`](bluetooth::transfer::TransferResponse::Done) notification on
+/// `server`.  `buf` must hold the whole file (up to the receiver's limit).
+/// Returns the status sent, `None` when no file was waiting.
+///
+/// A delta that does not parse, targets another library export or
+/// overflows the tag table is [`Rejected`](TransferStatus::Rejected);
+/// nothing of it is applied.
+///
+/// # Errors
+///
+/// The transfer store's error if the file cannot be discarded afterwards;
+/// the phone then gets no answer and the file is offered again.
+pub async fn apply_transfer<T, S, D, const N: usize>(
+    receiver: &mut TransferReceiver<T>,
+    server: &mut CompanionServer,
+    buf: &mut [u8],
+    reader: &mut SoulLibraryReader<S>,
+    overrides: &mut TagOverrides<N>,
+    store: &mut D,
+) -> Result<Option<TransferStatus>, T::Error>
+where
+    T: TransferStore,
+    S: Storage,
+    S::File: File<Error = S::Error>,
+    D: DeltaStore,
+{
+    let Some(completed) = receiver.completed() else {
+        return Ok(None);
+    };
+    let status = match receiver.read_completed(buf).await {
+        Err(TransferError::Gatt(_)) => TransferStatus::Rejected,
+        Err(TransferError::Store(_)) => TransferStatus::StorageError,
+        Ok(bytes) => match completed.kind {
+            TransferKind::LibraryDelta => match LibraryDelta::parse(bytes) {
+                Err(_) => TransferStatus::Rejected,
+                Ok(delta) => match apply_delta(&delta, reader, overrides, store).await {
+                    Ok(_) => TransferStatus::Applied,
+                    Err(DeltaError::WrongLibrary | DeltaError::Tags(_)) => TransferStatus::Rejected,
+                    Err(DeltaError::Library(_) | DeltaError::Store(_)) => {
+                        TransferStatus::StorageError
+                    }
+                },
+            },
+        },
+    };
+    receiver.finish(status).await?;
+    if let Some(response) = receiver.take_response() {
+        server.notify_transfer(response);
+    }
+    Ok(Some(status))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use bluetooth::gatt::{Characteristic, PlayerStatus};
+    use playback::fault::{PlaybackFault, RecoveryAction, StorageError};
+
+    fn server() -> CompanionServer {
+        let mut server = CompanionServer::new();
+        server.connected();
+        server.subscribe(Characteristic::Status, true).unwrap();
+        while server.poll_notification().is_some() {}
+        server
+    }
+
+    #[test]
+    fn test_forward_maps_events_and_reports_new_track() {
+        let mut server = server();
+        let started = forward(
+            &mut server,
+            [
+                PlaybackEvent::TrackStarted {
+                    track_id: 4,
+                    duration_ms: 1_000,
+                },
+                PlaybackEvent::StateChanged(PlaybackState::Playing),
+                PlaybackEvent::BufferUnderrun { missed_frames: 64 },
+                PlaybackEvent::EqChanged { preset: 2 },
+            ],
+        );
+        assert_eq!(started, Some(4));
+        assert_eq!(server.track_id(), Some(4));
+        let status = server.status();
+        assert_eq!(status.state, TransportState::Playing);
+        assert_eq!(status.eq_preset, 2);
+    }
+
+    #[test]
+    fn test_faults_are_not_forwarded() {
+        let event = PlaybackEvent::Fault {
+            fault: PlaybackFault::Storage(StorageError::NotFound),
+            action: RecoveryAction::SkipTrack,
+        };
+        assert_eq!(player_event(event), None);
+    }
+
+    #[test]
+    fn test_lag_growth_triggers_one_resync() {
+        let mut server = server();
+        let mut lag = LagWatch::new();
+        let snapshot = || PlayerSnapshot {
+            track_id: Some(9),
+            duration_ms: 0,
+            state: TransportState::Paused,
+            position_ms: 30_000,
+            eq_preset: 0,
+        };
+        assert!(!lag.check(&mut server, 0, snapshot));
+        assert!(lag.check(&mut server, 3, snapshot));
+        assert!(!lag.check(&mut server, 3, snapshot));
+
+        let value = server.poll_notification().unwrap().value;
+        let status = PlayerStatus::decode(&value).unwrap();
+        assert_eq!(status.state, TransportState::Paused);
+        assert_eq!(status.position_ms, 30_000);
+        assert_eq!(server.track_id(), Some(9));
+    }
+}
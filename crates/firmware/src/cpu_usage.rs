@@ -0,0 +1,327 @@
+//! Per-task CPU accounting from cycle-counter deltas.
+//!
+//! Each task wraps its CPU-bound work in [`measure`] (or
+//! [`CpuUsage::measure`] with an explicit clock), which adds the elapsed
+//! cycles to that task's counter in [`CPU_USAGE`].  The main loop calls
+//! [`CpuUsage::sample`] every [`REPORT_INTERVAL_SECS`] with the cycles that
+//! passed in the window and gets a [`CpuReport`] of per-task load in
+//! permille, which it logs over defmt and the debug screen
+//! ([`crate::ui::CpuUsageScreen`]) draws.
+//!
+//! # What to measure
+//!
+//! Wrap synchronous work only — a decode call, a render pass, a scheduler
+//! slice.  A span that crosses an `.await` also counts the time other tasks
+//! ran while it was suspended.  Cycles not attributed to any task show up
+//! as idle, so instrumenting the big consumers is enough to see which of
+//! decode, display refresh or UI rendering is eating the budget.
+//!
+//! # Counter width
+//!
+//! Counters are 32-bit, matching `DWT_CYCCNT`.  Deltas use wrapping
+//! subtraction, so a span across a counter rollover is still measured
+//! correctly; the window itself must stay under 2³² cycles (~8.9 s at
+//! 480 MHz), which [`REPORT_INTERVAL_SECS`] respects.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Seconds between [`CpuUsage::sample`] calls in the main loop.
+pub const REPORT_INTERVAL_SECS: u64 = 5;
+
+/// A unit of work whose CPU time is accounted separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CpuTask {
+    /// SAI/DMA buffer servicing.
+    Audio,
+    /// FLAC/MP3/WAV frame decoding.
+    Decode,
+    /// SSD1677 refresh: framebuffer transfer and waveform handling.
+    Display,
+    /// Screen layout and rendering into the framebuffer.
+    Ui,
+    /// Encoder and button event handling.
+    Input,
+    /// Library scanner and other background jobs.
+    Scanner,
+}
+
+impl CpuTask {
+    /// Every accounted task, in report order.
+    pub const ALL: [Self; 6] = [
+        Self::Audio,
+        Self::Decode,
+        Self::Display,
+        Self::Ui,
+        Self::Input,
+        Self::Scanner,
+    ];
+
+    const fn index(self) -> usize {
+        match self {
+            Self::Audio => 0,
+            Self::Decode => 1,
+            Self::Display => 2,
+            Self::Ui => 3,
+            Self::Input => 4,
+            Self::Scanner => 5,
+        }
+    }
+
+    /// Short label for logs and the debug screen.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Audio => "audio",
+            Self::Decode => "decode",
+            Self::Display => "display",
+            Self::Ui => "ui",
+            Self::Input => "input",
+            Self::Scanner => "scanner",
+        }
+    }
+}
+
+/// Number of [`CpuTask`] variants.
+const TASK_COUNT: usize = CpuTask::ALL.len();
+
+/// Cycles spent per task since the last [`sample`](Self::sample).
+pub struct CpuUsage {
+    cycles: [AtomicU32; TASK_COUNT],
+}
+
+/// The counters every task records into.
+pub static CPU_USAGE: CpuUsage = CpuUsage::new();
+
+impl CpuUsage {
+    /// All counters at zero.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            cycles: [
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+                AtomicU32::new(0),
+            ],
+        }
+    }
+
+    /// Add `cycles` to `task`'s counter.
+    pub fn record(&self, task: CpuTask, cycles: u32) {
+        if let Some(counter) = self.cycles.get(task.index()) {
+            counter.fetch_add(cycles, Ordering::Relaxed);
+        }
+    }
+
+    /// Run `f` and charge the `clock` ticks it took to `task`.
+    pub fn measure<R>(
+        &self,
+        task: CpuTask,
+        mut clock: impl FnMut() -> u32,
+        f: impl FnOnce() -> R,
+    ) -> R {
+        let start = clock();
+        let result = f();
+        self.record(task, clock().wrapping_sub(start));
+        result
+    }
+
+    /// Take the counters accumulated over a window of `window_cycles` and
+    /// start the next window from zero.
+    pub fn sample(&self, window_cycles: u32) -> CpuReport {
+        let mut cycles = [0; TASK_COUNT];
+        for (out, counter) in cycles.iter_mut().zip(&self.cycles) {
+            *out = counter.swap(0, Ordering::Relaxed);
+        }
+        CpuReport {
+            window_cycles,
+            cycles,
+        }
+    }
+}
+
+impl Default for CpuUsage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-task load over one sampling window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuReport {
+    window_cycles: u32,
+    cycles: [u32; TASK_COUNT],
+}
+
+impl CpuReport {
+    /// Cycles in the sampling window.
+    #[must_use]
+    pub const fn window_cycles(&self) -> u32 {
+        self.window_cycles
+    }
+
+    /// Cycles `task` used in the window.
+    #[must_use]
+    pub fn cycles(&self, task: CpuTask) -> u32 {
+        self.cycles.get(task.index()).copied().unwrap_or(0)
+    }
+
+    /// `task`'s share of the window in permille (0–1000).
+    #[must_use]
+    pub fn permille(&self, task: CpuTask) -> u16 {
+        self.share(u64::from(self.cycles(task)))
+    }
+
+    /// All accounted tasks together, in permille (0–1000).
+    #[must_use]
+    pub fn busy_permille(&self) -> u16 {
+        self.share(self.cycles.iter().map(|&c| u64::from(c)).sum())
+    }
+
+    /// Cycles not attributed to any task, in permille (0–1000).
+    #[must_use]
+    pub fn idle_permille(&self) -> u16 {
+        1000_u16.saturating_sub(self.busy_permille())
+    }
+
+    /// The task that used the most cycles, or `None` if none ran.
+    #[must_use]
+    pub fn busiest(&self) -> Option<CpuTask> {
+        CpuTask::ALL
+            .into_iter()
+            .filter(|&t| self.cycles(t) > 0)
+            .max_by_key(|&t| self.cycles(t))
+    }
+
+    /// `(task, permille)` for every task, in [`CpuTask::ALL`] order.
+    pub fn iter(&self) -> impl Iterator<Item = (CpuTask, u16)> + '_ {
+        CpuTask::ALL.into_iter().map(|t| (t, self.permille(t)))
+    }
+
+    fn share(&self, cycles: u64) -> u16 {
+        cycles
+            .saturating_mul(1000)
+            .checked_div(u64::from(self.window_cycles))
+            .map_or(0, |p| u16::try_from(p.min(1000)).unwrap_or(1000))
+    }
+}
+
+/// Cortex-M7 DWT cycle counter.
+#[cfg(feature = "hardware")]
+pub mod dwt {
+    use super::{CpuReport, CpuTask};
+
+    /// `DEMCR.TRCENA`: powers the DWT block.
+    const DEMCR_TRCENA: u32 = 1 << 24;
+    /// `DWT_CTRL.CYCCNTENA`.
+    const DWT_CTRL_CYCCNTENA: u32 = 1;
+    /// CoreSight lock-access key; the M7 ignores DWT writes until unlocked.
+    const LAR_KEY: u32 = 0xC5AC_CE55;
+
+    /// Enable and zero the cycle counter.  Call once at boot.
+    #[allow(unsafe_code)]
+    pub fn enable() {
+        // SAFETY: DEMCR and the DWT block are architecturally defined,
+        // always-mapped System Control Space registers on Armv7-M.  Only the
+        // trace-enable and cycle-counter-enable bits are set; the rest of the
+        // firmware only ever reads CYCCNT.
+        unsafe {
+            let dcb = &*cortex_m::peripheral::DCB::PTR;
+            dcb.demcr.modify(|r| r | DEMCR_TRCENA);
+            let dwt = &*cortex_m::peripheral::DWT::PTR;
+            dwt.lar.write(LAR_KEY);
+            dwt.cyccnt.write(0);
+            dwt.ctrl.modify(|r| r | DWT_CTRL_CYCCNTENA);
+        }
+    }
+
+    /// Current cycle count (wraps every 2³² cycles).
+    #[must_use]
+    pub fn cycles() -> u32 {
+        cortex_m::peripheral::DWT::cycle_count()
+    }
+
+    /// Emit `report` as one defmt line, loads in permille.
+    pub fn log(report: &CpuReport) {
+        defmt::info!(
+            "cpu: audio={=u16} decode={=u16} display={=u16} ui={=u16} input={=u16} scanner={=u16} idle={=u16} permille",
+            report.permille(CpuTask::Audio),
+            report.permille(CpuTask::Decode),
+            report.permille(CpuTask::Display),
+            report.permille(CpuTask::Ui),
+            report.permille(CpuTask::Input),
+            report.permille(CpuTask::Scanner),
+            report.idle_permille()
+        );
+    }
+}
+
+/// Run `f` and charge its DWT cycles to `task` in [`CPU_USAGE`].
+#[cfg(feature = "hardware")]
+pub fn measure<R>(task: CpuTask, f: impl FnOnce() -> R) -> R {
+    CPU_USAGE.measure(task, dwt::cycles, f)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_reports_permille_and_resets() {
+        let usage = CpuUsage::new();
+        usage.record(CpuTask::Decode, 300);
+        usage.record(CpuTask::Decode, 200);
+        usage.record(CpuTask::Ui, 100);
+
+        let report = usage.sample(1_000);
+        assert_eq!(report.cycles(CpuTask::Decode), 500);
+        assert_eq!(report.permille(CpuTask::Decode), 500);
+        assert_eq!(report.permille(CpuTask::Ui), 100);
+        assert_eq!(report.permille(CpuTask::Audio), 0);
+        assert_eq!(report.busy_permille(), 600);
+        assert_eq!(report.idle_permille(), 400);
+        assert_eq!(report.busiest(), Some(CpuTask::Decode));
+
+        let next = usage.sample(1_000);
+        assert_eq!(next.busy_permille(), 0);
+        assert_eq!(next.busiest(), None);
+    }
+
+    #[test]
+    fn test_measure_handles_counter_rollover() {
+        let usage = CpuUsage::new();
+        let mut ticks = [u32::MAX - 9, 40].into_iter();
+        let out = usage.measure(CpuTask::Display, || ticks.next().unwrap(), || 7);
+        assert_eq!(out, 7);
+        assert_eq!(usage.sample(100).cycles(CpuTask::Display), 50);
+    }
+
+    #[test]
+    fn test_shares_are_clamped_and_empty_window_is_zero() {
+        let usage = CpuUsage::new();
+        usage.record(CpuTask::Audio, 900);
+        usage.record(CpuTask::Scanner, 900);
+        let report = usage.sample(1_000);
+        assert_eq!(report.permille(CpuTask::Audio), 900);
+        assert_eq!(report.busy_permille(), 1000);
+        assert_eq!(report.idle_permille(), 0);
+
+        usage.record(CpuTask::Input, 5);
+        assert_eq!(usage.sample(0).permille(CpuTask::Input), 0);
+    }
+
+    #[test]
+    fn test_iter_follows_all_order() {
+        let usage = CpuUsage::new();
+        usage.record(CpuTask::Scanner, 10);
+        let report = usage.sample(100);
+        let tasks: Vec<_> = report.iter().map(|(t, _)| t).collect();
+        assert_eq!(tasks, CpuTask::ALL);
+        assert_eq!(report.iter().last(), Some((CpuTask::Scanner, 100)));
+    }
+}
@@ -0,0 +1,357 @@
+//! Animation ticks paced for e-ink.
+//!
+//! An LCD animates at 60 Hz; the fastest e-ink refresh takes ~260 ms and
+//! every refresh costs power and ghosting.  Screens therefore do not run
+//! their own timers: they start a tick at an e-ink rate ([`TickRate`]) on
+//! the [`TickService`] and redraw when it fires.  The service
+//!
+//! - **clamps** every period to [`min_interval_ms`] of the panel — the
+//!   fastest refresh plus the display service's
+//!   [`COALESCE_WINDOW_MS`] — and never fires twice within that interval,
+//!   so animations cannot ask for refreshes faster than the panel can
+//!   honour;
+//! - **batches** ticks that fall due within [`COALESCE_WINDOW_MS`] of each
+//!   other into one firing, so a marquee step and the clock minute land in
+//!   the same coalesced refresh instead of two back to back;
+//! - **takes time as an argument** (`now_ms`), like the
+//!   [`Coalescer`](super::service::Coalescer): the Embassy tick on hardware,
+//!   the emulator's `VirtualClock`, or [`TickService::fast_forward`] in
+//!   tests, which jumps straight from one firing to the next.
+//!
+//! # Example
+//!
+//! ```
+//! use firmware::display::animation::{TickRate, TickService};
+//!
+//! let mut ticks = TickService::new(300);
+//! let marquee = ticks.start(TickRate::Marquee, 0).unwrap();
+//! let clock = ticks.start(TickRate::Clock, 0).unwrap();
+//!
+//! // Ten simulated seconds, without waiting for them.
+//! let mut marquee_steps = 0;
+//! for (_at_ms, fired) in ticks.fast_forward(10_000) {
+//!     if fired.contains(marquee) {
+//!         marquee_steps += 1;
+//!     }
+//!     let _ = fired.contains(clock);
+//! }
+//! assert_eq!(marquee_steps, 30);
+//! ```
+
+use super::service::COALESCE_WINDOW_MS;
+
+/// Ticks one service can run at the same time.
+pub const MAX_TICKS: usize = 8;
+
+/// Shortest interval between firings `spec`'s panel can follow: its
+/// fastest refresh plus the render coalescing window.
+#[must_use]
+pub const fn min_interval_ms(spec: &eink_specs::DisplaySpec) -> u64 {
+    (spec.fast_refresh_ms.get() as u64).saturating_add(COALESCE_WINDOW_MS)
+}
+
+/// How often a tick fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TickRate {
+    /// ~3 Hz: stepped marquee scrolling and other short motion.
+    Marquee,
+    /// 2 Hz: busy spinners and blinking cursors.
+    Spinner,
+    /// 1 Hz, on whole seconds: clocks and elapsed-time readouts.
+    Clock,
+    /// A custom period in milliseconds.
+    Every(u32),
+}
+
+impl TickRate {
+    /// Nominal period in milliseconds, before clamping.
+    #[must_use]
+    pub const fn period_ms(self) -> u64 {
+        match self {
+            Self::Marquee => 333,
+            Self::Spinner => 500,
+            Self::Clock => 1_000,
+            Self::Every(ms) => ms as u64,
+        }
+    }
+
+    /// Whether firings land on multiples of the period, so a clock changes
+    /// with the second rather than some time after it.
+    const fn aligned(self) -> bool {
+        matches!(self, Self::Clock)
+    }
+}
+
+/// Handle of a running tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TickId(u8);
+
+/// The ticks that fired together.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Ticks(u8);
+
+impl Ticks {
+    /// `id` fired.
+    #[must_use]
+    pub const fn contains(self, id: TickId) -> bool {
+        self.0 & bit(id.0) != 0
+    }
+
+    /// Nothing fired.
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Number of ticks that fired.
+    #[must_use]
+    pub const fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+/// Mask bit of slot `index` (always < [`MAX_TICKS`]).
+const fn bit(index: u8) -> u8 {
+    1u8.wrapping_shl(index as u32)
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    period_ms: u64,
+    next_ms: u64,
+}
+
+/// Hands out e-ink-rate animation ticks; see the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct TickService {
+    slots: [Option<Slot>; MAX_TICKS],
+    min_interval_ms: u64,
+    last_fired_ms: Option<u64>,
+}
+
+impl TickService {
+    /// Service that fires at most once per `min_interval_ms`.
+    #[must_use]
+    pub const fn new(min_interval_ms: u64) -> Self {
+        Self {
+            slots: [None; MAX_TICKS],
+            min_interval_ms,
+            last_fired_ms: None,
+        }
+    }
+
+    /// Service paced for `spec`'s panel ([`min_interval_ms`]).
+    #[must_use]
+    pub const fn for_panel(spec: &eink_specs::DisplaySpec) -> Self {
+        Self::new(min_interval_ms(spec))
+    }
+
+    /// Start a tick at `rate`, first firing one period after `now_ms` (or
+    /// on the next period boundary for [`TickRate::Clock`]).
+    ///
+    /// Returns `None` if [`MAX_TICKS`] ticks are already running.
+    pub fn start(&mut self, rate: TickRate, now_ms: u64) -> Option<TickId> {
+        let period_ms = rate.period_ms().max(self.min_interval_ms).max(1);
+        let next_ms = if rate.aligned() {
+            next_after(0, period_ms, now_ms)
+        } else {
+            now_ms.saturating_add(period_ms)
+        };
+        let (index, slot) = self
+            .slots
+            .iter_mut()
+            .enumerate()
+            .find(|(_, s)| s.is_none())?;
+        *slot = Some(Slot { period_ms, next_ms });
+        u8::try_from(index).ok().map(TickId)
+    }
+
+    /// Stop `id`.  Its handle may be reused by a later [`start`](Self::start).
+    pub fn stop(&mut self, id: TickId) {
+        if let Some(slot) = self.slots.get_mut(usize::from(id.0)) {
+            *slot = None;
+        }
+    }
+
+    /// Effective period of `id` after clamping, if it is running.
+    #[must_use]
+    pub fn period_ms(&self, id: TickId) -> Option<u64> {
+        self.slots
+            .get(usize::from(id.0))
+            .copied()
+            .flatten()
+            .map(|s| s.period_ms)
+    }
+
+    /// When the next firing is due: the earliest tick, held back to
+    /// `min_interval_ms` after the previous firing.  `None` if no tick runs.
+    #[must_use]
+    pub fn next_deadline_ms(&self) -> Option<u64> {
+        let earliest = self.slots.iter().flatten().map(|s| s.next_ms).min()?;
+        let paced = self
+            .last_fired_ms
+            .map_or(0, |t| t.saturating_add(self.min_interval_ms));
+        Some(earliest.max(paced))
+    }
+
+    /// Fire the ticks due at `now_ms`, together with any due within
+    /// [`COALESCE_WINDOW_MS`] after it, and schedule their next firing.
+    ///
+    /// Empty before [`next_deadline_ms`](Self::next_deadline_ms).  A tick
+    /// that is more than a period late fires once and skips the missed
+    /// firings, keeping its phase.
+    pub fn poll(&mut self, now_ms: u64) -> Ticks {
+        match self.next_deadline_ms() {
+            Some(deadline) if deadline <= now_ms => {}
+            _ => return Ticks::default(),
+        }
+        let horizon = now_ms.saturating_add(COALESCE_WINDOW_MS);
+        let mut fired = Ticks::default();
+        for (index, slot) in (0u8..).zip(self.slots.iter_mut()) {
+            let Some(slot) = slot else { continue };
+            if slot.next_ms > horizon {
+                continue;
+            }
+            slot.next_ms = next_after(slot.next_ms, slot.period_ms, now_ms);
+            fired.0 |= bit(index);
+        }
+        self.last_fired_ms = Some(now_ms);
+        fired
+    }
+
+    /// Fire every tick due up to `until_ms`, jumping from one deadline to
+    /// the next without waiting.  Yields `(at_ms, ticks)` per firing.
+    pub fn fast_forward(&mut self, until_ms: u64) -> FastForward<'_> {
+        FastForward {
+            service: self,
+            until_ms,
+        }
+    }
+}
+
+/// First `phase + k * period_ms` (k ≥ 1) strictly after `now_ms`.
+fn next_after(phase: u64, period_ms: u64, now_ms: u64) -> u64 {
+    let missed = now_ms
+        .saturating_sub(phase)
+        .checked_div(period_ms)
+        .unwrap_or(0);
+    phase.saturating_add(period_ms.saturating_mul(missed.saturating_add(1)))
+}
+
+/// Iterator returned by [`TickService::fast_forward`].
+pub struct FastForward<'a> {
+    service: &'a mut TickService,
+    until_ms: u64,
+}
+
+impl Iterator for FastForward<'_> {
+    type Item = (u64, Ticks);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let at_ms = self.service.next_deadline_ms()?;
+        if at_ms > self.until_ms {
+            return None;
+        }
+        Some((at_ms, self.service.poll(at_ms)))
+    }
+}
+
+#[cfg(feature = "hardware")]
+impl TickService {
+    /// Sleep until the next firing and return it.  Never returns while no
+    /// tick is running.
+    pub async fn wait(&mut self) -> Ticks {
+        use embassy_time::{Instant, Timer};
+        loop {
+            let Some(deadline) = self.next_deadline_ms() else {
+                return core::future::pending().await;
+            };
+            Timer::at(Instant::from_millis(deadline)).await;
+            let fired = self.poll(Instant::now().as_millis());
+            if !fired.is_empty() {
+                return fired;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::arithmetic_side_effects,
+    clippy::indexing_slicing
+)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_interval_follows_the_panel() {
+        let spec = &super::super::GDEM0397T81P_SPEC;
+        assert_eq!(min_interval_ms(spec), 260 + COALESCE_WINDOW_MS);
+        let mut ticks = TickService::for_panel(spec);
+        let fast = ticks.start(TickRate::Every(16), 0).unwrap();
+        assert_eq!(ticks.period_ms(fast), Some(300));
+    }
+
+    #[test]
+    fn clock_fires_on_whole_seconds() {
+        let mut ticks = TickService::new(300);
+        let clock = ticks.start(TickRate::Clock, 1_250).unwrap();
+        let at: Vec<u64> = ticks.fast_forward(4_000).map(|(t, _)| t).collect();
+        assert_eq!(at, [2_000, 3_000, 4_000]);
+        assert_eq!(ticks.period_ms(clock), Some(1_000));
+    }
+
+    #[test]
+    fn ticks_due_within_the_coalesce_window_fire_together() {
+        let mut ticks = TickService::new(300);
+        let clock = ticks.start(TickRate::Clock, 0).unwrap();
+        // First firing at 980, 20 ms before the clock's.
+        let other = ticks.start(TickRate::Every(980), 0).unwrap();
+        let (at, fired) = ticks.fast_forward(1_000).next().unwrap();
+        assert_eq!(at, 980);
+        assert!(fired.contains(clock) && fired.contains(other));
+        assert_eq!(fired.len(), 2);
+        // The clock keeps its phase despite firing early.
+        ticks.stop(other);
+        assert_eq!(ticks.poll(1_999), Ticks::default());
+        assert!(ticks.poll(2_000).contains(clock));
+    }
+
+    #[test]
+    fn firings_never_come_closer_than_the_min_interval() {
+        let mut ticks = TickService::new(300);
+        ticks.start(TickRate::Marquee, 0).unwrap();
+        ticks.start(TickRate::Every(450), 100).unwrap();
+        let at: Vec<u64> = ticks.fast_forward(5_000).map(|(t, _)| t).collect();
+        assert!(at.len() > 10);
+        assert!(at.windows(2).all(|w| w[1] - w[0] >= 300), "{at:?}");
+    }
+
+    #[test]
+    fn late_poll_fires_once_and_keeps_phase() {
+        let mut ticks = TickService::new(300);
+        let spinner = ticks.start(TickRate::Spinner, 0).unwrap();
+        assert!(ticks.poll(2_100).contains(spinner));
+        assert_eq!(ticks.next_deadline_ms(), Some(2_500));
+    }
+
+    #[test]
+    fn stopped_ticks_free_their_slot() {
+        let mut ticks = TickService::new(300);
+        let ids: Vec<TickId> = (0..MAX_TICKS)
+            .map(|_| ticks.start(TickRate::Clock, 0).unwrap())
+            .collect();
+        assert!(ticks.start(TickRate::Clock, 0).is_none());
+        ticks.stop(ids[3]);
+        assert_eq!(ticks.start(TickRate::Spinner, 0), Some(ids[3]));
+        for id in ids {
+            ticks.stop(id);
+        }
+        assert_eq!(ticks.next_deadline_ms(), None);
+        assert!(ticks.poll(u64::MAX).is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+//! Shared plumbing for SPI e-paper controllers.
+//!
+//! The SSD1677, SSD1680 and UC8176 all speak the same 4-wire protocol: a
+//! command byte with DC LOW, its parameters with DC HIGH, a RST line for
+//! hardware reset and a BUSY line the controller holds while it works.  They
+//! differ only in command codes, BUSY polarity and reset timing.
+//!
+//! [`ControllerBus`] owns the five peripherals and implements that protocol
+//! once; each driver adds its command set on top.  The helpers below read the
+//! `eink-specs` [`Workaround`]s every driver honours, and [`set_pixel`] packs
+//! `BinaryColor` pixels into the 1bpp layout all three RAMs share.
+
+#![allow(clippy::doc_markdown)] // Controller names and signal names read better without backticks
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::Point;
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::{delay::DelayNs, spi::SpiDevice};
+
+use eink_specs::{Quirk, Workaround};
+
+use super::driver::DisplayError;
+
+/// BUSY poll interval in milliseconds.
+pub const BUSY_POLL_MS: u32 = 10;
+
+/// BUSY timeout without a `PollBusy` workaround.
+pub const DEFAULT_BUSY_TIMEOUT_MS: u32 = 2_000;
+
+/// Largest single SPI write when streaming RAM contents.
+const RAM_CHUNK: usize = 256;
+
+/// BUSY level that means "controller working".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BusyPolarity {
+    /// BUSY is HIGH while busy (Solomon Systech SSD16xx).
+    ActiveHigh,
+    /// BUSY is LOW while busy (UltraChip UC81xx).
+    ActiveLow,
+}
+
+/// RST pulse timing: HIGH `settle_ms` → LOW `low_ms` → HIGH `settle_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ResetPulse {
+    /// Time RST is held HIGH before and after the pulse.
+    pub settle_ms: u32,
+    /// Width of the LOW pulse.
+    pub low_ms: u32,
+}
+
+/// SPI, DC, RST, BUSY and delay of one e-paper controller.
+///
+/// Generic over the same peripheral traits as the drivers built on it; see
+/// [`Ssd1677`](super::Ssd1677) for what to pass on hardware and in tests.
+pub struct ControllerBus<SPI, DC, RST, BUSY, DELAY> {
+    spi: SPI,
+    dc: DC,
+    rst: RST,
+    busy: BUSY,
+    delay: DELAY,
+    busy_polarity: BusyPolarity,
+}
+
+impl<SPI, DC, RST, BUSY, DELAY> ControllerBus<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    /// Bundle the peripherals of a controller whose BUSY line has `busy_polarity`.
+    pub fn new(
+        spi: SPI,
+        dc: DC,
+        rst: RST,
+        busy: BUSY,
+        delay: DELAY,
+        busy_polarity: BusyPolarity,
+    ) -> Self {
+        Self {
+            spi,
+            dc,
+            rst,
+            busy,
+            delay,
+            busy_polarity,
+        }
+    }
+
+    /// Consume the bus and return its peripherals.
+    pub fn release(self) -> (SPI, DC, RST, BUSY, DELAY) {
+        (self.spi, self.dc, self.rst, self.busy, self.delay)
+    }
+
+    /// Assert DC low (command mode) and send one command byte.
+    ///
+    /// `SpiDevice` handles CS assertion/de-assertion per transaction.
+    ///
+    /// # Errors
+    ///
+    /// [`DisplayError::Gpio`] if DC cannot be driven,
+    /// [`DisplayError::Communication`] if the SPI write fails.  The same
+    /// applies to every method that talks to the controller.
+    pub async fn command(&mut self, cmd: u8) -> Result<(), DisplayError> {
+        self.dc.set_low().map_err(|_| DisplayError::Gpio)?;
+        self.spi
+            .write(&[cmd])
+            .await
+            .map_err(|_| DisplayError::Communication)
+    }
+
+    /// Assert DC high (data mode) and send bytes.  Empty data sends nothing.
+    ///
+    /// # Errors
+    ///
+    /// See [`command`](Self::command).
+    pub async fn data(&mut self, data: &[u8]) -> Result<(), DisplayError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        self.dc.set_high().map_err(|_| DisplayError::Gpio)?;
+        self.spi
+            .write(data)
+            .await
+            .map_err(|_| DisplayError::Communication)
+    }
+
+    /// Send one command followed immediately by its data bytes.
+    ///
+    /// # Errors
+    ///
+    /// See [`command`](Self::command).
+    pub async fn cmd_data(&mut self, cmd: u8, data: &[u8]) -> Result<(), DisplayError> {
+        self.command(cmd).await?;
+        self.data(data).await
+    }
+
+    /// Send a RAM write command followed by `ram` in 256-byte SPI writes,
+    /// so DMA-backed SPI implementations never see one huge transfer.
+    ///
+    /// # Errors
+    ///
+    /// See [`command`](Self::command).
+    pub async fn write_ram(&mut self, cmd: u8, ram: &[u8]) -> Result<(), DisplayError> {
+        self.command(cmd).await?;
+        for chunk in ram.chunks(RAM_CHUNK) {
+            self.data(chunk).await?;
+        }
+        Ok(())
+    }
+
+    /// Poll BUSY every [`BUSY_POLL_MS`] until the controller is idle.
+    ///
+    /// # Errors
+    ///
+    /// [`DisplayError::Timeout`] if it is still busy after `polls` polls,
+    /// [`DisplayError::Gpio`] if BUSY cannot be read.
+    pub async fn wait_busy(&mut self, polls: u32) -> Result<(), DisplayError> {
+        for _ in 0..polls {
+            let high = self.busy.is_high().map_err(|_| DisplayError::Gpio)?;
+            let is_busy = match self.busy_polarity {
+                BusyPolarity::ActiveHigh => high,
+                BusyPolarity::ActiveLow => !high,
+            };
+            if !is_busy {
+                return Ok(());
+            }
+            self.delay.delay_ms(BUSY_POLL_MS).await;
+        }
+        Err(DisplayError::Timeout)
+    }
+
+    /// Pulse RST LOW with the given timing.
+    ///
+    /// # Errors
+    ///
+    /// [`DisplayError::Gpio`] if RST cannot be driven.
+    pub async fn hardware_reset(&mut self, pulse: ResetPulse) -> Result<(), DisplayError> {
+        self.rst.set_high().map_err(|_| DisplayError::Gpio)?;
+        self.delay.delay_ms(pulse.settle_ms).await;
+        self.rst.set_low().map_err(|_| DisplayError::Gpio)?;
+        self.delay.delay_ms(pulse.low_ms).await;
+        self.rst.set_high().map_err(|_| DisplayError::Gpio)?;
+        self.delay.delay_ms(pulse.settle_ms).await;
+        Ok(())
+    }
+
+    /// Wait `ms` milliseconds.
+    pub async fn delay_ms(&mut self, ms: u32) {
+        self.delay.delay_ms(ms).await;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Quirk workarounds
+// ---------------------------------------------------------------------------
+
+fn workarounds(quirks: &[Quirk]) -> impl Iterator<Item = Workaround> + '_ {
+    quirks.iter().map(Quirk::workaround)
+}
+
+/// BUSY polls before timing out: [`DEFAULT_BUSY_TIMEOUT_MS`], or the
+/// `PollBusy` workaround's timeout.
+#[must_use]
+pub fn busy_polls(quirks: &[Quirk]) -> u32 {
+    let timeout_ms = workarounds(quirks)
+        .find_map(|w| match w {
+            Workaround::PollBusy { timeout_ms } => Some(timeout_ms),
+            _ => None,
+        })
+        .unwrap_or(DEFAULT_BUSY_TIMEOUT_MS);
+    timeout_ms / BUSY_POLL_MS
+}
+
+/// Partial refreshes allowed before one is promoted to a full refresh.
+#[must_use]
+pub fn partial_limit(quirks: &[Quirk]) -> Option<u8> {
+    workarounds(quirks).find_map(|w| match w {
+        Workaround::FullRefreshEvery { max_partials } => Some(max_partials),
+        _ => None,
+    })
+}
+
+/// Extra init attempts after a BUSY timeout (`RetryWithRecovery`).
+#[must_use]
+pub fn init_retries(quirks: &[Quirk]) -> u8 {
+    workarounds(quirks)
+        .find_map(|w| match w {
+            Workaround::RetryWithRecovery { attempts, .. } => Some(attempts),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+// ---------------------------------------------------------------------------
+// 1bpp packing
+// ---------------------------------------------------------------------------
+
+/// Write one pixel into a 1bpp buffer of `width`×`height` pixels.
+///
+/// Rows are padded to whole bytes, pixels are MSB-first, and
+/// `BinaryColor::Off` (white) is a 1 bit.  Pixels outside the panel are
+/// ignored.
+pub fn set_pixel(buffer: &mut [u8], width: u32, height: u32, point: Point, color: BinaryColor) {
+    let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+        return;
+    };
+    if x >= width || y >= height {
+        return;
+    }
+    let bytes_per_row = width.div_ceil(8);
+    let Some(index) = y
+        .checked_mul(bytes_per_row)
+        .and_then(|row| row.checked_add(x / 8))
+        .and_then(|index| usize::try_from(index).ok())
+    else {
+        return;
+    };
+    let mask = 0x80u8 >> (x % 8);
+    if let Some(byte) = buffer.get_mut(index) {
+        match color {
+            BinaryColor::Off => *byte |= mask,
+            BinaryColor::On => *byte &= !mask,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::digital::{
+        Mock as PinMock, State as PinState, Transaction as PinTransaction,
+    };
+    use embedded_hal_mock::eh1::spi::{Mock as SpiMock, Transaction as SpiTransaction};
+
+    fn bus_with_busy(
+        busy: PinMock,
+        polarity: BusyPolarity,
+    ) -> ControllerBus<SpiMock<u8>, PinMock, PinMock, PinMock, NoopDelay> {
+        ControllerBus::new(
+            SpiMock::new(&[]),
+            PinMock::new(&[]),
+            PinMock::new(&[]),
+            busy,
+            NoopDelay,
+            polarity,
+        )
+    }
+
+    fn done(bus: ControllerBus<SpiMock<u8>, PinMock, PinMock, PinMock, NoopDelay>) {
+        let (mut spi, mut dc, mut rst, mut busy, _) = bus.release();
+        spi.done();
+        dc.done();
+        rst.done();
+        busy.done();
+    }
+
+    /// `test_busy_polarity` — an active-low BUSY line is idle when HIGH.
+    #[tokio::test]
+    async fn test_busy_polarity() {
+        for (polarity, busy, idle) in [
+            (BusyPolarity::ActiveHigh, PinState::High, PinState::Low),
+            (BusyPolarity::ActiveLow, PinState::Low, PinState::High),
+        ] {
+            let pin = PinMock::new(&[
+                PinTransaction::get(busy),
+                PinTransaction::get(busy),
+                PinTransaction::get(idle),
+            ]);
+            let mut bus = bus_with_busy(pin, polarity);
+            assert_eq!(bus.wait_busy(3).await, Ok(()));
+            done(bus);
+
+            let pin = PinMock::new(&[PinTransaction::get(busy), PinTransaction::get(busy)]);
+            let mut bus = bus_with_busy(pin, polarity);
+            assert_eq!(bus.wait_busy(2).await, Err(DisplayError::Timeout));
+            done(bus);
+        }
+    }
+
+    /// `test_write_ram_chunks` — RAM contents go out in 256-byte writes
+    /// after a single command byte.
+    #[tokio::test]
+    async fn test_write_ram_chunks() {
+        let ram = [0xA5u8; 300];
+        let mut expectations = vec![
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(vec![0x24]),
+            SpiTransaction::transaction_end(),
+        ];
+        for chunk in ram.chunks(256) {
+            expectations.push(SpiTransaction::transaction_start());
+            expectations.push(SpiTransaction::write_vec(chunk.to_vec()));
+            expectations.push(SpiTransaction::transaction_end());
+        }
+        let dc = PinMock::new(&[
+            PinTransaction::set(PinState::Low),
+            PinTransaction::set(PinState::High),
+            PinTransaction::set(PinState::High),
+        ]);
+        let mut bus = ControllerBus::new(
+            SpiMock::new(&expectations),
+            dc,
+            PinMock::new(&[]),
+            PinMock::new(&[]),
+            NoopDelay,
+            BusyPolarity::ActiveHigh,
+        );
+        bus.write_ram(0x24, &ram).await.unwrap();
+        done(bus);
+    }
+
+    /// `test_set_pixel_pads_rows` — rows start on a byte boundary even when
+    /// the width is not a multiple of 8.
+    #[test]
+    fn test_set_pixel_pads_rows() {
+        // 10 px wide → 2 bytes per row.
+        let mut buf = [0xFFu8; 4];
+        set_pixel(&mut buf, 10, 2, Point::new(0, 1), BinaryColor::On);
+        set_pixel(&mut buf, 10, 2, Point::new(9, 0), BinaryColor::On);
+        assert_eq!(buf, [0xFF, 0xBF, 0x7F, 0xFF]);
+
+        set_pixel(&mut buf, 10, 2, Point::new(0, 1), BinaryColor::Off);
+        set_pixel(&mut buf, 10, 2, Point::new(10, 0), BinaryColor::On);
+        set_pixel(&mut buf, 10, 2, Point::new(-1, 0), BinaryColor::On);
+        assert_eq!(buf, [0xFF, 0xBF, 0xFF, 0xFF]);
+    }
+
+    /// `test_quirk_workarounds` — the lookups fall back to no workaround.
+    #[test]
+    fn test_quirk_workarounds() {
+        assert_eq!(busy_polls(&[]), 200);
+        assert_eq!(partial_limit(&[]), None);
+        assert_eq!(init_retries(&[]), 0);
+
+        let ssd1677 = eink_specs::Controller::SSD1677.quirks();
+        assert_eq!(partial_limit(ssd1677), Some(30));
+        assert_eq!(init_retries(eink_specs::Controller::UC8151.quirks()), 3);
+    }
+}
@@ -50,20 +50,36 @@ use embedded_hal_async::{delay::DelayNs, spi::SpiDevice};
 use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::*;
 
+use eink_specs::Quirk;
 use platform::{DisplayDriver, EinkDisplay, RefreshMode};
 
-use super::{DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use super::controller::{self, BusyPolarity, ControllerBus, ResetPulse};
+use super::{DISPLAY_HEIGHT, DISPLAY_WIDTH, GDEM0397T81P_SPEC};
+use crate::hal::{Color, DapDisplay};
 
 // ---------------------------------------------------------------------------
 // Constants
 // ---------------------------------------------------------------------------
 
+/// RST timing: HIGH 20 ms → LOW 2 ms → HIGH 20 ms.
+const RESET_PULSE: ResetPulse = ResetPulse {
+    settle_ms: 20,
+    low_ms: 2,
+};
+
 /// Bytes per row: 800 pixels / 8 bits = 100 bytes.
 pub const BYTES_PER_ROW: usize = DISPLAY_WIDTH as usize / 8;
 
 /// Total framebuffer size for 1bpp (black/white) at 800×480.
 pub const FRAMEBUFFER_SIZE_1BPP: usize = BYTES_PER_ROW * DISPLAY_HEIGHT as usize;
 
+/// Partial-window alignment for the SSD1677, taken from `eink-specs` so the
+/// emulator and this driver expand windows identically.
+///
+/// X is addressed in RAM bytes (8 px); Y is addressed per gate line.
+pub const PARTIAL_ALIGNMENT: eink_specs::PartialAlignment =
+    GDEM0397T81P_SPEC.controller.partial_alignment();
+
 // ---------------------------------------------------------------------------
 // Command enum
 // ---------------------------------------------------------------------------
@@ -138,6 +154,15 @@ pub const UPDATE_POWER_OFF: u8 = 0x83;
 /// Power on only.
 pub const UPDATE_POWER_ON: u8 = 0xE0;
 
+// ---------------------------------------------------------------------------
+// DisplayUpdateCtrl1 option constants
+// ---------------------------------------------------------------------------
+
+/// Bypass the red RAM (read as 0): B/W only mode.
+pub const CTRL1_BYPASS_RED: u8 = 0x40;
+/// Show the inverse of the B/W RAM.
+pub const CTRL1_INVERT_BW: u8 = 0x08;
+
 // ---------------------------------------------------------------------------
 // Driver struct
 // ---------------------------------------------------------------------------
@@ -155,13 +180,17 @@ pub const UPDATE_POWER_ON: u8 = 0xE0;
 /// parameter.  In host tests supply `embedded_hal_mock::eh1::delay::NoopDelay`
 /// or a custom implementation.
 pub struct Ssd1677<SPI, DC, RST, BUSY, DELAY> {
-    spi: SPI,
-    dc: DC,
-    rst: RST,
-    busy: BUSY,
-    delay: DELAY,
+    /// Peripherals and the shared command/BUSY protocol.
+    bus: ControllerBus<SPI, DC, RST, BUSY, DELAY>,
     refresh_mode: RefreshMode,
     partial_refresh_count: u8,
+    /// Controller quirks whose [`Workaround`](eink_specs::Workaround)s this driver applies.
+    quirks: &'static [Quirk],
+    /// Show the B/W RAM inverted (night mode), via `DisplayUpdateCtrl1`.
+    inverted: bool,
+    /// The controller's polarity does not match `inverted` yet; the next
+    /// refresh runs as a full refresh to apply it.
+    invert_pending: bool,
     /// 1bpp packed framebuffer (800×480 / 8 bytes = 48 000 bytes).
     ///
     /// `draw_iter` accumulates pixel writes here; a subsequent call to
@@ -188,48 +217,67 @@ where
         // the large array size is expected and acceptable.
         #[allow(clippy::large_stack_arrays)]
         Self {
-            spi,
-            dc,
-            rst,
-            busy,
-            delay,
+            bus: ControllerBus::new(spi, dc, rst, busy, delay, BusyPolarity::ActiveHigh),
             refresh_mode: RefreshMode::Full,
             partial_refresh_count: 0,
+            quirks: match GDEM0397T81P_SPEC.quirks {
+                Some(quirks) => quirks,
+                None => &[],
+            },
+            inverted: false,
+            invert_pending: false,
             framebuffer: [0xFF; FRAMEBUFFER_SIZE_1BPP],
         }
     }
 
+    /// Replace the quirk list (default: the GDEM0397T81P spec's quirks).
+    ///
+    /// Panels on the same controller differ in OTP waveforms; pass `&[]`
+    /// to run without workarounds.
+    pub fn set_quirks(&mut self, quirks: &'static [Quirk]) {
+        self.quirks = quirks;
+    }
+
+    /// BUSY polls before timing out: 2 s, or the `PollBusy` workaround's timeout.
+    fn busy_polls(&self) -> u32 {
+        controller::busy_polls(self.quirks)
+    }
+
+    /// Partial refreshes allowed before one is promoted to a full refresh.
+    fn partial_limit(&self) -> Option<u8> {
+        controller::partial_limit(self.quirks)
+    }
+
+    /// Extra init attempts after a BUSY timeout (`RetryWithRecovery`).
+    fn init_retries(&self) -> u8 {
+        controller::init_retries(self.quirks)
+    }
+
+    /// Consume the driver and return its peripherals.
+    pub fn release(self) -> (SPI, DC, RST, BUSY, DELAY) {
+        self.bus.release()
+    }
+
+    /// The packed 1bpp framebuffer (MSB-first, 1 = white).
+    ///
+    /// Holds what the next refresh will push, i.e. what the panel shows after
+    /// a refresh.  Used by the HIL firmware to checksum rendered screens.
+    pub fn framebuffer(&self) -> &[u8] {
+        &self.framebuffer
+    }
+
     // -----------------------------------------------------------------------
     // Low-level SPI helpers
     // -----------------------------------------------------------------------
 
-    /// Assert DC low (command mode) and send one command byte over SPI.
-    ///
-    /// `SpiDevice` handles CS assertion/de-assertion per transaction.
+    /// Send one command byte (DC low).
     async fn send_command(&mut self, cmd: Command) -> Result<(), DisplayError> {
-        self.dc.set_low().map_err(|_| DisplayError::Gpio)?;
-        self.spi
-            .write(&[cmd as u8])
-            .await
-            .map_err(|_| DisplayError::Communication)
-    }
-
-    /// Assert DC high (data mode) and send bytes over SPI.
-    async fn send_data(&mut self, data: &[u8]) -> Result<(), DisplayError> {
-        if data.is_empty() {
-            return Ok(());
-        }
-        self.dc.set_high().map_err(|_| DisplayError::Gpio)?;
-        self.spi
-            .write(data)
-            .await
-            .map_err(|_| DisplayError::Communication)
+        self.bus.command(cmd as u8).await
     }
 
     /// Send one command followed immediately by its data bytes.
     async fn cmd_data(&mut self, cmd: Command, data: &[u8]) -> Result<(), DisplayError> {
-        self.send_command(cmd).await?;
-        self.send_data(data).await
+        self.bus.cmd_data(cmd as u8, data).await
     }
 
     // -----------------------------------------------------------------------
@@ -238,21 +286,11 @@ where
 
     /// Block until BUSY goes LOW (controller idle) or timeout expires.
     ///
-    /// BUSY is active HIGH on SSD1677 / SSD2677.  Poll every 10 ms.
-    /// After `MAX_BUSY_POLLS` attempts without BUSY going LOW the function
-    /// returns `Err(DisplayError::Timeout)`.
+    /// BUSY is active HIGH on SSD1677 / SSD2677.  Poll every 10 ms, for
+    /// 2 000 ms (longer under a `PollBusy` workaround).  If BUSY never goes
+    /// LOW the function returns `Err(DisplayError::Timeout)`.
     async fn wait_busy(&mut self) -> Result<(), DisplayError> {
-        const POLL_MS: u32 = 10; // poll interval in milliseconds
-        const MAX_BUSY_POLLS: u32 = 200; // 200 × 10 ms = 2 000 ms total
-
-        for _ in 0..MAX_BUSY_POLLS {
-            let is_busy = self.busy.is_high().map_err(|_| DisplayError::Gpio)?;
-            if !is_busy {
-                return Ok(());
-            }
-            self.delay.delay_ms(POLL_MS).await;
-        }
-        Err(DisplayError::Timeout)
+        self.bus.wait_busy(self.busy_polls()).await
     }
 
     // -----------------------------------------------------------------------
@@ -263,13 +301,7 @@ where
     ///
     /// RST HIGH 20 ms → LOW 2 ms → HIGH 20 ms.
     async fn hardware_reset(&mut self) -> Result<(), DisplayError> {
-        self.rst.set_high().map_err(|_| DisplayError::Gpio)?;
-        self.delay.delay_ms(20).await;
-        self.rst.set_low().map_err(|_| DisplayError::Gpio)?;
-        self.delay.delay_ms(2).await;
-        self.rst.set_high().map_err(|_| DisplayError::Gpio)?;
-        self.delay.delay_ms(20).await;
-        Ok(())
+        self.bus.hardware_reset(RESET_PULSE).await
     }
 
     // -----------------------------------------------------------------------
@@ -371,40 +403,70 @@ where
         self.set_ram_y_counter_raw(y_start_ram).await
     }
 
+    /// Compute the RAM window for the logical region `(x, y, width, height)`.
+    ///
+    /// The region is first expanded to [`PARTIAL_ALIGNMENT`].  Returns
+    /// `(x_byte_start, x_byte_end, y_start_ram, y_end_ram)` with inclusive
+    /// ends and Y already reversed, or `None` if the aligned region is empty
+    /// or extends past the panel.
+    // Opt back in to the workspace lints this module allows.
+    #[deny(clippy::arithmetic_side_effects, clippy::cast_possible_truncation)]
+    pub(crate) fn partial_ram_window(
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Option<(u8, u8, u16, u16)> {
+        let (x, y, width, height) = PARTIAL_ALIGNMENT.align_window(x, y, width, height);
+        let x_end = x.checked_add(width)?;
+        let y_end = y.checked_add(height)?;
+        if width == 0 || height == 0 || x_end > DISPLAY_WIDTH || y_end > DISPLAY_HEIGHT {
+            return None;
+        }
+        // X RAM addresses count bytes of 8 pixels; ends are inclusive.
+        let x_byte_start = u8::try_from(x.checked_div(8)?).ok()?;
+        let x_byte_end = u8::try_from(x_end.checked_sub(1)?.checked_div(8)?).ok()?;
+        let y_start_ram = Self::y_to_ram(u16::try_from(y).ok()?)?;
+        let y_end_ram = Self::y_to_ram(u16::try_from(y_end.checked_sub(1)?).ok()?)?;
+        Some((x_byte_start, x_byte_end, y_start_ram, y_end_ram))
+    }
+
+    /// Configure the RAM window for a partial update of a logical region.
+    ///
+    /// The region is expanded to the controller's partial-window alignment
+    /// ([`PARTIAL_ALIGNMENT`]) — the same rule the emulator applies — and
+    /// the counters are set to its top-left corner.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DisplayError::InvalidCoordinate`] if the aligned region is
+    /// empty or does not fit on the panel.
+    pub async fn set_partial_window(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), DisplayError> {
+        let (x_start, x_end, y_start_ram, y_end_ram) = Self::partial_ram_window(x, y, width, height)
+            .ok_or(DisplayError::InvalidCoordinate)?;
+
+        self.set_ram_x_range(x_start, x_end).await?;
+        self.set_ram_y_range_raw(y_start_ram, y_end_ram).await?;
+        self.set_ram_x_counter(x_start).await?;
+        self.set_ram_y_counter_raw(y_start_ram).await
+    }
+
     // -----------------------------------------------------------------------
     // Framebuffer flush
     // -----------------------------------------------------------------------
 
-    /// Push the internal framebuffer to the controller's B/W RAM via SPI.
-    ///
-    /// Sends in fixed 256-byte chunks to avoid borrow-checker conflicts.
-    #[allow(clippy::items_after_statements)] // const CHUNK defined after first statement for locality
+    /// Push the internal framebuffer to the controller's B/W RAM via SPI,
+    /// in 256-byte writes.
     async fn flush_framebuffer(&mut self) -> Result<(), DisplayError> {
-        self.send_command(Command::WriteRamBW).await?;
-
-        const CHUNK: usize = 256;
-        let mut offset = 0;
-        while offset < FRAMEBUFFER_SIZE_1BPP {
-            // offset < FRAMEBUFFER_SIZE_1BPP (loop guard), offset + CHUNK then capped
-            // by .min(FRAMEBUFFER_SIZE_1BPP), so end <= FRAMEBUFFER_SIZE_1BPP.
-            // offset + CHUNK: max is FRAMEBUFFER_SIZE_1BPP - 1 + 256 < usize::MAX.
-            #[allow(clippy::arithmetic_side_effects)]
-            let end = (offset + CHUNK).min(FRAMEBUFFER_SIZE_1BPP);
-            let mut buf = [0u8; CHUNK];
-            // end = (offset + CHUNK).min(FRAMEBUFFER_SIZE_1BPP) >= offset (since CHUNK > 0
-            // and FRAMEBUFFER_SIZE_1BPP > offset), so end - offset >= 0.
-            // end <= offset + CHUNK = offset + 256, so len <= 256 = CHUNK.
-            #[allow(clippy::arithmetic_side_effects)]
-            let len = end - offset;
-            // offset <= end <= FRAMEBUFFER_SIZE_1BPP = len(self.framebuffer): valid slice.
-            // len <= CHUNK = len(buf): valid slice.
-            #[allow(clippy::indexing_slicing)]
-            buf[..len].copy_from_slice(&self.framebuffer[offset..end]);
-            #[allow(clippy::indexing_slicing)]
-            self.send_data(&buf[..len]).await?;
-            offset = end;
-        }
-        Ok(())
+        self.bus
+            .write_ram(Command::WriteRamBW as u8, &self.framebuffer)
+            .await
     }
 
     // -----------------------------------------------------------------------
@@ -419,13 +481,25 @@ where
     /// # Errors
     ///
     /// Returns [`DisplayError`] if any SPI command or reset sequence fails.
+    /// A BUSY timeout is retried under a `RetryWithRecovery` workaround; the
+    /// sequence starts with a hardware reset, which is the recovery.
     pub async fn init(&mut self) -> Result<(), DisplayError> {
+        let mut retries = self.init_retries();
+        loop {
+            match self.init_sequence().await {
+                Err(DisplayError::Timeout) if retries > 0 => retries -= 1,
+                result => return result,
+            }
+        }
+    }
+
+    async fn init_sequence(&mut self) -> Result<(), DisplayError> {
         // 1. Hardware reset
         self.hardware_reset().await?;
 
         // 2. Software reset — wait 10 ms then poll BUSY
         self.send_command(Command::SoftReset).await?;
-        self.delay.delay_ms(10).await;
+        self.bus.delay_ms(10).await;
         self.wait_busy().await?;
 
         // 3. Internal temperature sensor
@@ -476,10 +550,8 @@ where
     type DriverError = DisplayError;
 
     fn spec(&self) -> platform::DisplayInfo {
-        platform::DisplayInfo {
-            width: DISPLAY_WIDTH,
-            height: DISPLAY_HEIGHT,
-        }
+        // Only the B/W RAM is written (1bpp framebuffer).
+        super::display_info(&GDEM0397T81P_SPEC).with_grayscale_levels(2)
     }
 
     /// Copy a framebuffer into the internal buffer and push it to the
@@ -503,8 +575,13 @@ where
         self.set_full_window().await?;
         self.flush_framebuffer().await?;
 
-        // Bypass Red RAM (B/W only mode)
-        self.cmd_data(Command::DisplayUpdateCtrl1, &[0x40, 0x00])
+        // Bypass Red RAM (B/W only mode); the controller inverts in night mode
+        let ctrl1 = if self.inverted {
+            CTRL1_BYPASS_RED | CTRL1_INVERT_BW
+        } else {
+            CTRL1_BYPASS_RED
+        };
+        self.cmd_data(Command::DisplayUpdateCtrl1, &[ctrl1, 0x00])
             .await?;
 
         // Full refresh via OTP LUT
@@ -514,13 +591,24 @@ where
         self.wait_busy().await?;
 
         self.partial_refresh_count = 0;
+        self.invert_pending = false;
         Ok(())
     }
 
     /// Trigger a partial panel refresh (~300 ms).
     ///
-    /// Uses `UPDATE_PARTIAL` (0xFC) sequence flag.
+    /// Uses `UPDATE_PARTIAL` (0xFC) sequence flag.  Under a
+    /// `FullRefreshEvery` workaround, the partial refresh that would exceed
+    /// the limit runs as a full refresh instead, as does the first refresh
+    /// after the inversion changed.
     async fn refresh_partial(&mut self) -> Result<(), Self::DriverError> {
+        if self.invert_pending
+            || self
+                .partial_limit()
+                .is_some_and(|max| self.partial_refresh_count >= max)
+        {
+            return self.refresh_full().await;
+        }
         self.set_full_window().await?;
         self.flush_framebuffer().await?;
 
@@ -536,12 +624,17 @@ where
     /// Enter deep sleep (preserves RAM, ~1 µA).
     async fn sleep(&mut self) -> Result<(), Self::DriverError> {
         self.cmd_data(Command::DeepSleep, &[0x01]).await?;
-        self.delay.delay_ms(100).await;
+        self.bus.delay_ms(100).await;
         Ok(())
     }
 
     /// Wake from deep sleep by running a hardware reset and re-init.
+    ///
+    /// Always resets, which is also the `WakeWithReset` workaround.  The
+    /// reset clears `DisplayUpdateCtrl1`, so night mode is re-applied by
+    /// the next (full) refresh.
     async fn wake(&mut self) -> Result<(), Self::DriverError> {
+        self.invert_pending = self.inverted;
         self.hardware_reset().await?;
         self.init().await
     }
@@ -552,6 +645,36 @@ where
     }
 }
 
+// ---------------------------------------------------------------------------
+// DapDisplay
+// ---------------------------------------------------------------------------
+
+impl<SPI, DC, RST, BUSY, DELAY> DapDisplay for Ssd1677<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self) -> Result<(), Self::DriverError> {
+        Ssd1677::init(self).await
+    }
+
+    fn framebuffer_size(&self) -> usize {
+        FRAMEBUFFER_SIZE_1BPP
+    }
+
+    /// Fill the framebuffer with `color` and run a full refresh.
+    async fn clear(&mut self, color: Color) -> Result<(), Self::DriverError> {
+        self.framebuffer.fill(match color {
+            Color::White => 0xFF,
+            Color::Black => 0x00,
+        });
+        self.refresh_full().await
+    }
+}
+
 // ---------------------------------------------------------------------------
 // DrawTarget — pixel accumulation into the framebuffer
 // ---------------------------------------------------------------------------
@@ -576,39 +699,13 @@ where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
         for Pixel(point, color) in pixels {
-            if point.x < 0
-                || point.y < 0
-                || point.x >= DISPLAY_WIDTH as i32
-                || point.y >= DISPLAY_HEIGHT as i32
-            {
-                continue;
-            }
-            let x = point.x as usize;
-            let y = point.y as usize;
-            // Bounds guard above: x < DISPLAY_WIDTH (800), y < DISPLAY_HEIGHT (480).
-            //   y * DISPLAY_WIDTH: max = 479 * 800 = 383200 < usize::MAX.
-            //   + x: max total = 479 * 800 + 799 = 384399 < usize::MAX.
-            #[allow(clippy::arithmetic_side_effects)]
-            let pixel_index = y * DISPLAY_WIDTH as usize + x;
-            let byte_index = pixel_index / 8;
-            // pixel_index % 8 is in [0, 7]; 7 - (0..=7) is in [0, 7]. No underflow.
-            #[allow(clippy::arithmetic_side_effects)]
-            // MSB-first: pixel 0 of a row is in bit 7 of byte 0.
-            let bit_shift = 7 - (pixel_index % 8);
-            match color {
-                BinaryColor::Off => {
-                    // white → set bit
-                    // byte_index = pixel_index / 8 <= (DISPLAY_WIDTH*DISPLAY_HEIGHT - 1) / 8
-                    //   = (800*480 - 1) / 8 = 47999, which is < FRAMEBUFFER_SIZE_1BPP (48000).
-                    #[allow(clippy::indexing_slicing)]
-                    { self.framebuffer[byte_index] |= 1 << bit_shift; }
-                }
-                BinaryColor::On => {
-                    // black → clear bit (same bound as above).
-                    #[allow(clippy::indexing_slicing)]
-                    { self.framebuffer[byte_index] &= !(1 << bit_shift); }
-                }
-            }
+            controller::set_pixel(
+                &mut self.framebuffer,
+                DISPLAY_WIDTH,
+                DISPLAY_HEIGHT,
+                point,
+                color,
+            );
         }
         Ok(())
     }
@@ -652,6 +749,19 @@ where
         // Reading the on-chip temperature ADC register is not yet implemented.
         None
     }
+
+    /// The controller inverts on output (`DisplayUpdateCtrl1`); the
+    /// framebuffer keeps its normal polarity.
+    fn set_inverted(&mut self, inverted: bool) {
+        if inverted != self.inverted {
+            self.inverted = inverted;
+            self.invert_pending = true;
+        }
+    }
+
+    fn is_inverted(&self) -> bool {
+        self.inverted
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -862,6 +972,38 @@ mod tests {
         assert_eq!((px_end / 8) as u8, 1);
     }
 
+    // -----------------------------------------------------------------------
+    // Test: partial window alignment
+    // -----------------------------------------------------------------------
+
+    /// `test_partial_ram_window_alignment` — X snaps outward to RAM bytes,
+    /// Y stays at gate resolution, matching the emulator's alignment.
+    #[test]
+    fn test_partial_ram_window_alignment() {
+        assert_eq!(PARTIAL_ALIGNMENT, eink_specs::Controller::SSD1677.partial_alignment());
+
+        // x=13..15 → bytes 1..=1; y=10..=19 → RAM 469..=460
+        assert_eq!(TestDriver::partial_ram_window(13, 10, 2, 10), Some((1, 1, 469, 460)));
+
+        // x=0..9 touches pixel 8, so the window spans bytes 0..=1
+        assert_eq!(TestDriver::partial_ram_window(0, 0, 9, 1), Some((0, 1, 479, 479)));
+
+        // Full panel
+        assert_eq!(
+            TestDriver::partial_ram_window(0, 0, DISPLAY_WIDTH, DISPLAY_HEIGHT),
+            Some((0, 99, 479, 0))
+        );
+
+        // Empty and out-of-bounds regions are rejected
+        assert_eq!(TestDriver::partial_ram_window(0, 0, 0, 10), None);
+        assert_eq!(TestDriver::partial_ram_window(796, 0, 8, 1), None);
+        assert_eq!(TestDriver::partial_ram_window(0, 479, 8, 2), None);
+
+        // Coordinates near u32::MAX are rejected, not wrapped
+        assert_eq!(TestDriver::partial_ram_window(u32::MAX - 3, 0, 8, 1), None);
+        assert_eq!(TestDriver::partial_ram_window(0, u32::MAX, 8, u32::MAX), None);
+    }
+
     // -----------------------------------------------------------------------
     // Test: deep sleep command
     // -----------------------------------------------------------------------
@@ -957,6 +1099,62 @@ mod tests {
         busy_handle.done();
     }
 
+    /// `test_busy_timeout_follows_quirks` — a `PollBusy` workaround extends
+    /// the 2 s BUSY timeout; without it the same panel times out.
+    #[tokio::test]
+    async fn test_busy_timeout_follows_quirks() {
+        static SLOW_REFRESH: [Quirk; 1] = [Quirk::UncontrollableRefreshRate { description: "" }];
+
+        for (quirks, expected) in [
+            (&[][..], Err(DisplayError::Timeout)),
+            (&SLOW_REFRESH[..], Ok(())),
+        ] {
+            // Longer than 2 s of polling either way; a timeout stops at 200 polls.
+            let busy = if expected.is_ok() {
+                busy_pin_sequence(250)
+            } else {
+                let high: Vec<_> = (0..200)
+                    .map(|_| PinTransaction::get(PinState::High))
+                    .collect();
+                PinMock::new(&high)
+            };
+            let mut drv = Ssd1677::new(SpiMock::new(&[]), idle_pin(), idle_pin(), busy, NoopDelay);
+            drv.set_quirks(quirks);
+            assert_eq!(drv.wait_busy().await, expected);
+
+            let (mut spi, mut dc, mut rst, mut busy, _) = drv.release();
+            spi.done();
+            dc.done();
+            rst.done();
+            busy.done();
+        }
+    }
+
+    /// `test_default_quirk_workarounds` — the GDEM0397T81P spec carries the
+    /// SSD1677 quirks: partial refreshes are capped, init is not retried.
+    #[test]
+    fn test_default_quirk_workarounds() {
+        let mut drv = Ssd1677::new(
+            SpiMock::new(&[]),
+            idle_pin(),
+            idle_pin(),
+            idle_pin(),
+            NoopDelay,
+        );
+        assert_eq!(drv.partial_limit(), Some(30));
+        assert_eq!(drv.init_retries(), 0);
+        assert_eq!(drv.busy_polls(), 200);
+
+        drv.set_quirks(eink_specs::Controller::UC8151.quirks());
+        assert_eq!(drv.partial_limit(), None);
+        assert_eq!(drv.init_retries(), 3);
+        let (mut spi, mut dc, mut rst, mut busy, _) = drv.release();
+        spi.done();
+        dc.done();
+        rst.done();
+        busy.done();
+    }
+
     // -----------------------------------------------------------------------
     // Test: clear screen uses AutoWrite commands
     // -----------------------------------------------------------------------
@@ -1489,6 +1687,11 @@ mod tests {
         assert_eq!(drv.refresh_mode(), RefreshMode::Fast);
         // Temperature not implemented on hardware
         assert_eq!(drv.temperature(), None);
+        // Night mode only marks the next refresh; nothing reaches the bus
+        assert!(!drv.is_inverted());
+        drv.set_inverted(true);
+        assert!(drv.is_inverted());
+        assert!(drv.invert_pending);
 
         spi_h.done();
         dc_h.done();
@@ -0,0 +1,289 @@
+//! embedded-hal bridge onto the emulator's controller mode
+//!
+//! Implements the SPI, GPIO and delay traits that [`Ssd1677`] is generic
+//! over on top of [`eink_emulator::ControllerEmulator`], so the *real*
+//! driver can run on the host and its wire traffic is decoded exactly as
+//! the SSD1677 would decode it.
+//!
+//! ```text
+//! Ssd1677 ──SpiDevice──▶ EmulatedSpi ──cmd/data──▶ ControllerEmulator ──▶ Emulator
+//!         ──OutputPin──▶ EmulatedDc / EmulatedReset
+//!         ──InputPin───▶ EmulatedBusy ◀── BusyLine
+//!         ──DelayNs────▶ EmulatedDelay ──advances──▶ bus clock
+//! ```
+//!
+//! # Timing
+//!
+//! Nothing sleeps.  [`EmulatedDelay`] advances the controller's bus clock,
+//! and BUSY stays high until that clock passes the end of the emulated
+//! refresh.  A driver that stops polling too early (or gives up too soon)
+//! fails here exactly as it would on the panel.
+//!
+//! # Reset
+//!
+//! A low→high edge on RST is latched and applied at the start of the next
+//! SPI transaction, because the SPI adapter owns the controller.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use firmware::display::emulated_bus::EmulatedBus;
+//! use platform::DisplayDriver;
+//!
+//! # async fn example() {
+//! let mut display = EmulatedBus::gdem0397t81p().into_driver();
+//! display.init().await.unwrap();
+//! display.refresh_full().await.unwrap();
+//!
+//! let (spi, ..) = display.release();
+//! assert_eq!(spi.controller().activations(), 1);
+//! # }
+//! ```
+
+use core::cell::Cell;
+use core::convert::Infallible;
+use std::rc::Rc;
+
+use eink_emulator::{BusyLine, ControllerEmulator, VirtualClock};
+use embedded_hal::digital::{ErrorType as DigitalErrorType, InputPin, OutputPin};
+use embedded_hal::spi::{ErrorKind, ErrorType as SpiErrorType, Operation};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::spi::SpiDevice;
+
+use super::{Ssd1677, GDEM0397T81P_SPEC};
+
+/// [`Ssd1677`] wired to an emulated bus.
+pub type EmulatedSsd1677 =
+    Ssd1677<EmulatedSpi, EmulatedDc, EmulatedReset, EmulatedBusy, EmulatedDelay>;
+
+/// RST line state shared between [`EmulatedReset`] and [`EmulatedSpi`].
+#[derive(Debug)]
+struct ResetLatch {
+    level_high: Cell<bool>,
+    pending: Cell<bool>,
+}
+
+/// SPI device that feeds a [`ControllerEmulator`].
+pub struct EmulatedSpi {
+    controller: ControllerEmulator,
+    dc_high: Rc<Cell<bool>>,
+    reset: Rc<ResetLatch>,
+}
+
+impl EmulatedSpi {
+    /// The controller receiving the traffic.
+    pub fn controller(&self) -> &ControllerEmulator {
+        &self.controller
+    }
+
+    /// The controller receiving the traffic (mutable).
+    pub fn controller_mut(&mut self) -> &mut ControllerEmulator {
+        &mut self.controller
+    }
+
+    /// Consume the adapter and return the controller.
+    pub fn into_controller(self) -> ControllerEmulator {
+        self.controller
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), ErrorKind> {
+        if self.dc_high.get() {
+            self.controller
+                .write_data(bytes)
+                .await
+                .map_err(|_| ErrorKind::Other)
+        } else {
+            for &command in bytes {
+                self.controller
+                    .write_command(command)
+                    .await
+                    .map_err(|_| ErrorKind::Other)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+impl SpiErrorType for EmulatedSpi {
+    type Error = ErrorKind;
+}
+
+impl SpiDevice for EmulatedSpi {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), ErrorKind> {
+        if self.reset.pending.replace(false) {
+            self.controller.hardware_reset();
+        }
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => self.write(bytes).await?,
+                // The SSD1677 MISO line is not wired; reads return zeros.
+                Operation::Read(buf) => buf.fill(0),
+                Operation::Transfer(read, write) => {
+                    self.write(write).await?;
+                    read.fill(0);
+                }
+                Operation::TransferInPlace(buf) => {
+                    self.write(buf).await?;
+                    buf.fill(0);
+                }
+                Operation::DelayNs(ns) => {
+                    self.controller.bus_clock().advance(ns_to_ms(*ns));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Data/command select pin.
+pub struct EmulatedDc {
+    dc_high: Rc<Cell<bool>>,
+}
+
+impl DigitalErrorType for EmulatedDc {
+    type Error = Infallible;
+}
+
+impl OutputPin for EmulatedDc {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.dc_high.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.dc_high.set(true);
+        Ok(())
+    }
+}
+
+/// Reset pin; a low→high edge resets the controller.
+pub struct EmulatedReset {
+    latch: Rc<ResetLatch>,
+}
+
+impl DigitalErrorType for EmulatedReset {
+    type Error = Infallible;
+}
+
+impl OutputPin for EmulatedReset {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.latch.level_high.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        if !self.latch.level_high.replace(true) {
+            self.latch.pending.set(true);
+        }
+        Ok(())
+    }
+}
+
+/// BUSY input pin (high while the controller is busy).
+pub struct EmulatedBusy {
+    line: BusyLine,
+}
+
+impl DigitalErrorType for EmulatedBusy {
+    type Error = Infallible;
+}
+
+impl InputPin for EmulatedBusy {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(self.line.is_high())
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(!self.line.is_high())
+    }
+}
+
+/// Delay that advances the bus clock instead of sleeping.
+pub struct EmulatedDelay {
+    clock: VirtualClock,
+}
+
+impl EmulatedDelay {
+    /// Total simulated time elapsed on the bus clock.
+    #[must_use]
+    pub fn now_ms(&self) -> u64 {
+        self.clock.now_ms()
+    }
+}
+
+impl DelayNs for EmulatedDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.clock.advance(ns_to_ms(ns));
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.clock.advance(u64::from(us.div_ceil(1_000)));
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.clock.advance(u64::from(ms));
+    }
+}
+
+/// The bus clock has millisecond resolution; sub-ms delays round up.
+fn ns_to_ms(ns: u32) -> u64 {
+    u64::from(ns.div_ceil(1_000_000))
+}
+
+/// The five peripherals [`Ssd1677`] needs, all backed by one controller.
+pub struct EmulatedBus {
+    /// SPI device.
+    pub spi: EmulatedSpi,
+    /// Data/command pin.
+    pub dc: EmulatedDc,
+    /// Reset pin.
+    pub rst: EmulatedReset,
+    /// BUSY pin.
+    pub busy: EmulatedBusy,
+    /// Delay source.
+    pub delay: EmulatedDelay,
+}
+
+impl EmulatedBus {
+    /// Wire up a bus around `controller`.
+    #[must_use]
+    pub fn new(controller: ControllerEmulator) -> Self {
+        let dc_high = Rc::new(Cell::new(false));
+        let reset = Rc::new(ResetLatch {
+            level_high: Cell::new(true),
+            pending: Cell::new(false),
+        });
+        let busy = EmulatedBusy {
+            line: controller.busy_line(),
+        };
+        let delay = EmulatedDelay {
+            clock: controller.bus_clock().clone(),
+        };
+        Self {
+            spi: EmulatedSpi {
+                controller,
+                dc_high: Rc::clone(&dc_high),
+                reset: Rc::clone(&reset),
+            },
+            dc: EmulatedDc { dc_high },
+            rst: EmulatedReset { latch: reset },
+            busy,
+            delay,
+        }
+    }
+
+    /// A headless GDEM0397T81P (SSD1677, gates wired in reverse).
+    #[must_use]
+    pub fn gdem0397t81p() -> Self {
+        Self::new(ControllerEmulator::new(&GDEM0397T81P_SPEC).with_reversed_gates(true))
+    }
+
+    /// Build an [`Ssd1677`] driver on this bus.
+    #[must_use]
+    pub fn into_driver(self) -> EmulatedSsd1677 {
+        Ssd1677::new(self.spi, self.dc, self.rst, self.busy, self.delay)
+    }
+}
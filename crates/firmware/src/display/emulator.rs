@@ -43,6 +43,7 @@ impl EmulatorDisplay {
     /// let config = EmulatorConfig {
     ///     rotation: Rotation::Degrees90,  // Portrait mode
     ///     scale: 1,                        // No upscaling
+    ///     ..Default::default()
     /// };
     /// let display = EmulatorDisplay::with_config(config);
     /// ```
@@ -150,10 +151,7 @@ impl platform::DisplayDriver for EmulatorDisplay {
     type DriverError = EmulatorError;
 
     fn spec(&self) -> platform::display::DisplayInfo {
-        platform::display::DisplayInfo {
-            width: DISPLAY_WIDTH,
-            height: DISPLAY_HEIGHT,
-        }
+        super::display_info(eink_emulator::DisplayDriver::spec(&self.emulator))
     }
 
     /// Parse a 2bpp packed framebuffer and draw each pixel into the emulator.
@@ -283,6 +281,15 @@ impl platform::EinkDisplay for EmulatorDisplay {
     fn ghosting_level(&self) -> Option<f32> {
         Some(self.emulator.ghosting_level())
     }
+
+    /// The emulator inverts at the framebuffer level.
+    fn set_inverted(&mut self, inverted: bool) {
+        self.emulator.set_inverted(inverted);
+    }
+
+    fn is_inverted(&self) -> bool {
+        self.emulator.is_inverted()
+    }
 }
 
 /// Emulator display errors
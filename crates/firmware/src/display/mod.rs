@@ -3,16 +3,37 @@
 //! This module provides both hardware and emulator implementations of the
 //! display interface for the GDEM0397T81P (Good Display 3.97" 800×480) panel
 //! with SSD1677 controller.
+//!
+//! Hardware variants use other controllers behind the same [`DapDisplay`]
+//! trait: [`Ssd1680`] for small 2.13" status panels and [`Uc8176`] for 4.2"
+//! panels.  All three share the command/BUSY plumbing in [`controller`].
+//!
+//! Screens redraw through the [`service`]; animated ones pace their redraws
+//! with the e-ink tick rates in [`animation`].  Panel faults are reset and
+//! reinitialised under the [`recovery`] policy.
+//!
+//! [`DapDisplay`]: crate::hal::DapDisplay
 
 #![allow(clippy::doc_markdown)] // Display module docs reference hardware model names (GDEM0397T81P) as plain text
+pub mod animation;
+pub mod controller;
 pub mod driver;
+pub mod recovery;
+pub mod service;
+pub mod ssd1680;
+pub mod uc8176;
 
 #[cfg(feature = "emulator")]
 pub mod emulator;
 
+#[cfg(feature = "emulator")]
+pub mod emulated_bus;
+
 // The driver module is always compiled (no hardware gate) so that
 // `cargo test` can exercise the SSD1677 driver tests on the host.
-pub use driver::{DisplayError, Ssd1677, BYTES_PER_ROW, FRAMEBUFFER_SIZE_1BPP};
+pub use driver::{DisplayError, Ssd1677, BYTES_PER_ROW, FRAMEBUFFER_SIZE_1BPP, PARTIAL_ALIGNMENT};
+pub use ssd1680::{Ssd1680, SSD1680_FRAMEBUFFER_SIZE, SSD1680_HEIGHT, SSD1680_WIDTH};
+pub use uc8176::{Uc8176, UC8176_FRAMEBUFFER_SIZE, UC8176_HEIGHT, UC8176_WIDTH};
 
 // Re-export hardware type alias when building for the embedded target.
 #[cfg(feature = "hardware")]
@@ -21,6 +42,9 @@ pub use driver::Ssd1677Display;
 #[cfg(feature = "emulator")]
 pub use emulator::EmulatorDisplay;
 
+#[cfg(feature = "emulator")]
+pub use emulated_bus::{EmulatedBus, EmulatedSsd1677};
+
 /// Display specification for GDEM0397T81P
 pub const GDEM0397T81P_SPEC: eink_specs::DisplaySpec = eink_specs::DisplaySpec {
     name: "Good Display GDEM0397T81P",
@@ -30,9 +54,9 @@ pub const GDEM0397T81P_SPEC: eink_specs::DisplaySpec = eink_specs::DisplaySpec {
     panel_type: eink_specs::PanelType::Carta1200,
     color_mode: None,    // Monochrome
     grayscale_levels: 4, // 2-bit per pixel
-    full_refresh_ms: 2000,
-    partial_refresh_ms: 300,
-    fast_refresh_ms: 260,
+    full_refresh_ms: eink_specs::DisplayMs::new(2000),
+    partial_refresh_ms: eink_specs::DisplayMs::new(300),
+    fast_refresh_ms: eink_specs::DisplayMs::new(260),
     ghosting_rate_partial: 0.15,
     ghosting_rate_fast: 0.25,
     flash_count_full: 3,
@@ -40,9 +64,35 @@ pub const GDEM0397T81P_SPEC: eink_specs::DisplaySpec = eink_specs::DisplaySpec {
     temp_optimal_max: 35,
     temp_operating_min: 0,
     temp_operating_max: 50,
-    quirks: None,
+    quirks: Some(eink_specs::quirks_for_controller(
+        eink_specs::Controller::SSD1677,
+    )),
 };
 
+/// Capabilities of the panel described by `spec`, for the UI layer.
+///
+/// Partial refresh counts as supported only when it is faster than a full
+/// refresh (colour panels run the full waveform either way).  Drivers that
+/// write fewer grey levels than the panel has override
+/// [`grayscale_levels`](platform::DisplayInfo::grayscale_levels).
+#[must_use]
+pub const fn display_info(spec: &eink_specs::DisplaySpec) -> platform::DisplayInfo {
+    let alignment = spec.controller.partial_alignment();
+    platform::DisplayInfo {
+        width: spec.width,
+        height: spec.height,
+        grayscale_levels: spec.grayscale_levels,
+        supports_partial: spec.partial_refresh_ms.get() < spec.full_refresh_ms.get(),
+        min_partial_width: alignment.x,
+        min_partial_height: alignment.y,
+        color_mode: match spec.color_mode {
+            None | Some(eink_specs::ColorMode::Grayscale) => platform::ColorMode::Grayscale,
+            Some(eink_specs::ColorMode::Spectra6) => platform::ColorMode::Spectra6,
+            Some(eink_specs::ColorMode::Kaleido3) => platform::ColorMode::Kaleido3,
+        },
+    }
+}
+
 /// Display width in pixels (GDEM0397T81P)
 pub const DISPLAY_WIDTH: u32 = 800;
 
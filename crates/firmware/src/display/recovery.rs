@@ -0,0 +1,257 @@
+//! Display error recovery — when to reset the panel and when to give up.
+//!
+//! A refresh that fails with an SPI error or a BUSY timeout usually means
+//! the controller has wedged, not that the panel is gone: the UC8151's
+//! [`SpiWriteHang`](eink_specs::Quirk::SpiWriteHang) is the documented case,
+//! and ESD or a brown-out can do the same to any controller.  The
+//! [`DisplayService`](super::service::DisplayService) answers each such
+//! failure with [`RecoveryPolicy::on_failure`]:
+//!
+//! 1. **Reinit.**  [`RecoveryAction::Reinit`]: a few rounds of hardware
+//!    reset + init sequence.  The count comes from the controller's
+//!    `RetryWithRecovery` workaround when it has one
+//!    ([`RecoveryPolicy::for_quirks`]), otherwise [`DEFAULT_REINIT_ATTEMPTS`].
+//!    A round that fails counts as another failure.
+//! 2. **Restore.**  Init clears the controller RAM but not the driver's
+//!    framebuffer, so a full refresh after it puts the last frame back.
+//! 3. **Escalate.**  Only after [`MAX_CONSECUTIVE_FAILURES`] failed
+//!    refreshes in a row does the policy say [`RecoveryAction::Escalate`]:
+//!    the service shows the display fault screen once and stops driving the
+//!    panel.  Audio keeps playing; a reboot is the user's retry.
+//!
+//! A successful refresh clears the count.  Errors that no reset can fix
+//! (a wrong buffer size, a coordinate out of range) are bugs, not panel
+//! faults; [`TransientError`] tells the two apart and only the first kind
+//! reaches the policy.
+
+use core::convert::Infallible;
+
+use eink_specs::Quirk;
+
+use super::controller;
+use super::driver::DisplayError;
+
+/// Failed refreshes in a row, each already retried with a reinit, before
+/// the panel is given up on.
+pub const MAX_CONSECUTIVE_FAILURES: u8 = 3;
+
+/// Reset + init rounds per failure for controllers without a
+/// `RetryWithRecovery` workaround.
+pub const DEFAULT_REINIT_ATTEMPTS: u8 = 1;
+
+/// Driver errors a reset and reinit can clear.
+pub trait TransientError {
+    /// The controller may work again after a hardware reset and init.
+    fn is_transient(&self) -> bool;
+}
+
+impl TransientError for DisplayError {
+    fn is_transient(&self) -> bool {
+        match self {
+            Self::Communication | Self::Gpio | Self::Busy | Self::Timeout | Self::InvalidState => {
+                true
+            }
+            Self::InvalidBuffer | Self::InvalidCoordinate | Self::Unsupported => false,
+        }
+    }
+}
+
+impl TransientError for Infallible {
+    fn is_transient(&self) -> bool {
+        match *self {}
+    }
+}
+
+/// What to do about a failed refresh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecoveryAction {
+    /// Reset and reinit the controller, up to `attempts` times, then
+    /// restore the last frame with a full refresh.
+    Reinit {
+        /// Reset + init rounds before this failure counts as unrecovered.
+        attempts: u8,
+    },
+    /// Too many failures in a row: show the fault screen and stop.
+    Escalate,
+}
+
+/// Result of one [`DisplayService::recover`](super::service::DisplayService::recover).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RecoveryOutcome {
+    /// The controller was reinitialised and the last frame is back.
+    Restored,
+    /// Recovery gave up; the fault screen was shown if the panel allowed.
+    Escalated,
+    /// The error was not a panel fault; nothing was done.
+    NotTransient,
+}
+
+/// How the panel is doing, for diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PanelHealth {
+    /// The last refresh succeeded.
+    Ok,
+    /// `failures` refreshes in a row have failed; recovery is under way.
+    Recovering {
+        /// Consecutive failed refreshes.
+        failures: u8,
+    },
+    /// Recovery gave up; the panel is no longer driven.
+    Failed,
+}
+
+/// Counts consecutive display failures and picks the next step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryPolicy {
+    reinit_attempts: u8,
+    max_failures: u8,
+    failures: u8,
+    recoveries: u32,
+    failed: bool,
+}
+
+impl RecoveryPolicy {
+    /// [`DEFAULT_REINIT_ATTEMPTS`] per failure, escalating after
+    /// [`MAX_CONSECUTIVE_FAILURES`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            reinit_attempts: DEFAULT_REINIT_ATTEMPTS,
+            max_failures: MAX_CONSECUTIVE_FAILURES,
+            failures: 0,
+            recoveries: 0,
+            failed: false,
+        }
+    }
+
+    /// Policy for a controller with `quirks`: a `RetryWithRecovery`
+    /// workaround (the `SpiWriteHang` quirk) sets the reinit attempts.
+    #[must_use]
+    pub fn for_quirks(quirks: &[Quirk]) -> Self {
+        Self {
+            reinit_attempts: controller::init_retries(quirks).max(DEFAULT_REINIT_ATTEMPTS),
+            ..Self::new()
+        }
+    }
+
+    /// Escalate after `failures` consecutive failed refreshes (at least 1).
+    #[must_use]
+    pub const fn with_max_failures(mut self, failures: u8) -> Self {
+        self.max_failures = if failures == 0 { 1 } else { failures };
+        self
+    }
+
+    /// Reset + init rounds per failure.
+    #[must_use]
+    pub const fn reinit_attempts(&self) -> u8 {
+        self.reinit_attempts
+    }
+
+    /// Record a failed refresh and choose what to do about it.
+    ///
+    /// Once escalated, every further failure escalates again.
+    pub fn on_failure(&mut self) -> RecoveryAction {
+        self.failures = self.failures.saturating_add(1);
+        if self.failed || self.failures >= self.max_failures {
+            self.failed = true;
+            return RecoveryAction::Escalate;
+        }
+        RecoveryAction::Reinit {
+            attempts: self.reinit_attempts,
+        }
+    }
+
+    /// Record a successful refresh or recovery, clearing the failure count.
+    pub fn on_success(&mut self) {
+        if self.failures > 0 && !self.failed {
+            self.recoveries = self.recoveries.saturating_add(1);
+        }
+        if !self.failed {
+            self.failures = 0;
+        }
+    }
+
+    /// Current state of the panel.
+    #[must_use]
+    pub const fn health(&self) -> PanelHealth {
+        if self.failed {
+            PanelHealth::Failed
+        } else if self.failures > 0 {
+            PanelHealth::Recovering {
+                failures: self.failures,
+            }
+        } else {
+            PanelHealth::Ok
+        }
+    }
+
+    /// Failures that a reinit cleared since boot.
+    #[must_use]
+    pub const fn recoveries(&self) -> u32 {
+        self.recoveries
+    }
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escalates_only_after_consecutive_failures() {
+        let mut policy = RecoveryPolicy::new();
+        for failures in 1..MAX_CONSECUTIVE_FAILURES {
+            assert_eq!(
+                policy.on_failure(),
+                RecoveryAction::Reinit {
+                    attempts: DEFAULT_REINIT_ATTEMPTS
+                }
+            );
+            assert_eq!(policy.health(), PanelHealth::Recovering { failures });
+        }
+        assert_eq!(policy.on_failure(), RecoveryAction::Escalate);
+        assert_eq!(policy.health(), PanelHealth::Failed);
+        // A late success does not bring a failed panel back.
+        policy.on_success();
+        assert_eq!(policy.health(), PanelHealth::Failed);
+        assert_eq!(policy.on_failure(), RecoveryAction::Escalate);
+    }
+
+    #[test]
+    fn success_clears_the_count() {
+        let mut policy = RecoveryPolicy::new().with_max_failures(2);
+        assert!(matches!(policy.on_failure(), RecoveryAction::Reinit { .. }));
+        policy.on_success();
+        assert_eq!(policy.health(), PanelHealth::Ok);
+        assert_eq!(policy.recoveries(), 1);
+        assert!(matches!(policy.on_failure(), RecoveryAction::Reinit { .. }));
+        assert_eq!(policy.on_failure(), RecoveryAction::Escalate);
+    }
+
+    #[test]
+    fn spi_write_hang_quirk_sets_reinit_attempts() {
+        let hang = [Quirk::SpiWriteHang { description: "" }];
+        assert_eq!(RecoveryPolicy::for_quirks(&hang).reinit_attempts(), 3);
+        assert_eq!(
+            RecoveryPolicy::for_quirks(&[]).reinit_attempts(),
+            DEFAULT_REINIT_ATTEMPTS
+        );
+    }
+
+    #[test]
+    fn only_bus_and_busy_errors_are_transient() {
+        assert!(DisplayError::Communication.is_transient());
+        assert!(DisplayError::Timeout.is_transient());
+        assert!(DisplayError::Busy.is_transient());
+        assert!(!DisplayError::InvalidBuffer.is_transient());
+        assert!(!DisplayError::InvalidCoordinate.is_transient());
+    }
+}
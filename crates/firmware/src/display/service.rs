@@ -0,0 +1,958 @@
+//! Display service — the one owner of the panel.
+//!
+//! Application code does not draw or refresh the display itself.  It sends
+//! a [`RenderRequest`] (which [`Frame`] to show, and how much of the screen
+//! changed) and the service does the rest:
+//!
+//! - **Coalescing.**  Requests that arrive within [`COALESCE_WINDOW_MS`] of
+//!   the first pending one are merged by a [`Coalescer`] and produce one
+//!   refresh: the latest frame is drawn, the dirty areas add up and the
+//!   most demanding [`ContentHint`] wins.  Requests that arrive while a
+//!   refresh is running queue up and are merged into the next one.
+//! - **Refresh policy.**  [`DisplayService`] asks
+//!   [`RefreshPolicy`](platform::refresh_policy::RefreshPolicy) for the mode
+//!   (DU / DU4 / GC16) of each refresh and records what was done.
+//! - **No overlap.**  Refreshes run one after another in a single task; a
+//!   refresh is never started while the previous one is still driving the
+//!   panel.
+//! - **Diffing.**  With a [`DoubleBuffer`] ([`DisplayService::with_frame_diff`])
+//!   the frame is drawn off-screen and compared with the one on the panel;
+//!   only the changed rectangles are copied to the driver, their area is
+//!   what the policy sees, and an unchanged frame is not refreshed at all.
+//!   Without one, the request's own [`Update`] is trusted.
+//! - **Recovery.**  A refresh that fails with a bus error or a BUSY
+//!   timeout is followed by [`DisplayService::recover`]: bounded reset +
+//!   reinit rounds that restore the last frame, and the fault screen after
+//!   repeated failures (see [`recovery`](super::recovery)).
+//! - **Night mode.**  [`DisplayService::set_inverted`] swaps black and
+//!   white in the driver and makes the next refresh a full one.
+//!
+//! On hardware, requests go through the static [`RENDER_REQUESTS`] channel
+//! ([`request`]) to [`run`], which the `display_task` in `main.rs` drives.
+//!
+//! # Example
+//!
+//! ```rust,ignore
+//! use firmware::display::service::{self, Frame, RenderRequest};
+//! use platform::refresh_policy::ContentHint;
+//!
+//! service::request(RenderRequest::full_screen(Frame::TestPattern, ContentHint::Graphics));
+//! ```
+
+use embedded_graphics::pixelcolor::{Gray2, Gray4};
+use embedded_graphics::prelude::*;
+use platform::crash_loop::BootRecord;
+use platform::frame_diff::{DirtyRects, DoubleBuffer};
+use platform::refresh_policy::{ContentHint, PanelState, RefreshChoice, RefreshPolicy, Update};
+use platform::{DisplayDriver, EinkDisplay, RefreshMode};
+
+use super::recovery::{
+    PanelHealth, RecoveryAction, RecoveryOutcome, RecoveryPolicy, TransientError,
+};
+use crate::audio::dropouts::ACTIVITY;
+use crate::cpu_usage::CpuReport;
+use crate::hal::DapDisplay;
+use crate::ui::{CpuUsageScreen, DisplayFaultScreen, SafeModeScreen, SplashScreen, TestPattern};
+
+/// How long the first request of a burst waits for more (ms).
+///
+/// Long enough to absorb a fast encoder spin or a screen that sends one
+/// request per widget; short next to the ~260 ms of the fastest refresh.
+pub const COALESCE_WINDOW_MS: u64 = 40;
+
+/// Depth of the request channel.
+pub const REQUEST_QUEUE_DEPTH: usize = 8;
+
+/// Changed rectangles one frame diff keeps before merging them into their
+/// bounding box.
+pub const DIFF_RECTS: usize = 16;
+
+/// What the display should show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frame {
+    /// Boot splash.
+    Splash,
+    /// Hardware validation pattern.
+    TestPattern,
+    /// Per-task CPU load.
+    CpuUsage(CpuReport),
+    /// Display recovery gave up.
+    DisplayFault,
+    /// Booted in safe mode after a crash loop.
+    SafeMode(BootRecord),
+}
+
+impl Frame {
+    /// Draw the frame into `display`'s buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `D::Error` if any drawing operation fails.
+    pub fn render<D, C>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        C: PixelColor + From<Gray2>,
+    {
+        match self {
+            Self::Splash => SplashScreen::render(display),
+            Self::TestPattern => TestPattern::render(display),
+            Self::CpuUsage(report) => CpuUsageScreen::render(display, report),
+            Self::DisplayFault => DisplayFaultScreen::render(display),
+            Self::SafeMode(record) => SafeModeScreen::render(display, record),
+        }
+    }
+}
+
+/// One request to redraw the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderRequest {
+    /// Frame to draw.
+    pub frame: Frame,
+    /// Changed area and content type, for the refresh policy.
+    pub update: Update,
+}
+
+impl RenderRequest {
+    /// Draw `frame`; `update` says how much of the screen it changes.
+    #[must_use]
+    pub const fn new(frame: Frame, update: Update) -> Self {
+        Self { frame, update }
+    }
+
+    /// Draw `frame` over the whole screen (a page change).  The policy
+    /// treats an update this large as a GC16 refresh.
+    #[must_use]
+    pub const fn full_screen(frame: Frame, hint: ContentHint) -> Self {
+        Self::new(frame, Update::new(u32::MAX, hint))
+    }
+}
+
+/// Rank of a hint when merging: the content that needs the most grey
+/// levels decides.
+const fn hint_rank(hint: ContentHint) -> u8 {
+    match hint {
+        ContentHint::Animation => 0,
+        ContentHint::Text => 1,
+        ContentHint::Graphics => 2,
+        ContentHint::Image => 3,
+    }
+}
+
+/// Merges a burst of [`RenderRequest`]s into one.
+#[derive(Debug, Clone, Default)]
+pub struct Coalescer {
+    pending: Option<RenderRequest>,
+    since_ms: u64,
+    merged: u32,
+}
+
+impl Coalescer {
+    /// Nothing pending.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            pending: None,
+            since_ms: 0,
+            merged: 0,
+        }
+    }
+
+    /// Add `request`, received at `now_ms`.
+    ///
+    /// The latest frame replaces the pending one.  Dirty areas add up (an
+    /// upper bound when requests redraw the same region) and the hint that
+    /// needs the most grey levels is kept.
+    pub fn submit(&mut self, request: RenderRequest, now_ms: u64) {
+        let Some(pending) = &mut self.pending else {
+            self.pending = Some(request);
+            self.since_ms = now_ms;
+            return;
+        };
+        let hint = if hint_rank(request.update.hint) > hint_rank(pending.update.hint) {
+            request.update.hint
+        } else {
+            pending.update.hint
+        };
+        let dirty = pending
+            .update
+            .dirty_pixels
+            .saturating_add(request.update.dirty_pixels);
+        *pending = RenderRequest::new(request.frame, Update::new(dirty, hint));
+        self.merged = self.merged.saturating_add(1);
+    }
+
+    /// When the pending request is due: [`COALESCE_WINDOW_MS`] after the
+    /// first request of the burst.  `None` if nothing is pending.
+    #[must_use]
+    pub fn deadline_ms(&self) -> Option<u64> {
+        self.pending
+            .map(|_| self.since_ms.saturating_add(COALESCE_WINDOW_MS))
+    }
+
+    /// Take the merged request, leaving nothing pending.
+    pub fn take(&mut self) -> Option<RenderRequest> {
+        self.pending.take()
+    }
+
+    /// Requests folded into another one so far.
+    #[must_use]
+    pub const fn merged(&self) -> u32 {
+        self.merged
+    }
+}
+
+/// Owns the panel driver and refreshes it according to the refresh policy.
+pub struct DisplayService<D> {
+    display: D,
+    policy: RefreshPolicy,
+    frames: Option<DoubleBuffer<'static>>,
+    /// `frames`' front buffer matches the driver's buffer.
+    frames_synced: bool,
+    refreshes: u32,
+    recovery: RecoveryPolicy,
+}
+
+impl<D, C> DisplayService<D>
+where
+    D: DisplayDriver<DriverError = <D as DrawTarget>::Error> + DrawTarget<Color = C>,
+    C: PixelColor + From<Gray2> + From<Gray4>,
+{
+    /// Service for `display`, with no refresh recorded yet: the first
+    /// request gets a full refresh.
+    pub fn new(display: D) -> Self {
+        let size = display.dimensions();
+        Self {
+            display,
+            policy: RefreshPolicy::new(size.width.saturating_mul(size.height)),
+            frames: None,
+            frames_synced: false,
+            refreshes: 0,
+            recovery: RecoveryPolicy::new(),
+        }
+    }
+
+    /// Record a full refresh made before the service took over (the boot
+    /// splash), so the first request does not repeat it.
+    #[must_use]
+    pub fn after_full_refresh(mut self, now_ms: u64) -> Self {
+        self.policy.record(RefreshMode::Full, now_ms);
+        self
+    }
+
+    /// Recover from panel faults with `policy` instead of the default
+    /// (e.g. [`RecoveryPolicy::for_quirks`] for the fitted controller).
+    #[must_use]
+    pub fn with_recovery(mut self, policy: RecoveryPolicy) -> Self {
+        self.recovery = policy;
+        self
+    }
+
+    /// Draw frames into `frames` and work out the dirty area by diffing
+    /// instead of trusting each request's [`Update`].
+    ///
+    /// The first frame is copied to the driver in full, since what the
+    /// panel shows before it is not known.
+    #[must_use]
+    pub fn with_frame_diff(mut self, frames: DoubleBuffer<'static>) -> Self {
+        self.frames = Some(frames);
+        self.frames_synced = false;
+        self
+    }
+
+    /// Draw `request.frame` into the driver's buffer without refreshing.
+    ///
+    /// Returns the update to refresh with: the request's own, or with
+    /// frame diffing the changed area, or `None` if the frame is the one
+    /// already drawn.
+    ///
+    /// # Errors
+    ///
+    /// Returns the driver error if a drawing operation fails.
+    pub fn draw(&mut self, request: &RenderRequest) -> Result<Option<Update>, D::DriverError> {
+        let Some(frames) = &mut self.frames else {
+            request.frame.render(&mut self.display)?;
+            return Ok(Some(request.update));
+        };
+        if let Err(never) = request.frame.render(frames) {
+            match never {}
+        }
+        if !self.frames_synced {
+            frames.blit(&frames.bounding_box(), &mut self.display)?;
+            frames.present();
+            self.frames_synced = true;
+            return Ok(Some(request.update));
+        }
+        let dirty: DirtyRects<DIFF_RECTS> = frames.diff();
+        if dirty.is_empty() {
+            return Ok(None);
+        }
+        for rect in dirty.rects() {
+            frames.blit(rect, &mut self.display)?;
+        }
+        frames.present();
+        Ok(Some(dirty.update(request.update.hint)))
+    }
+
+    /// Refresh the panel with the mode the policy picks for `update`.  The
+    /// refresh is recorded only if it succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Returns the driver error if the refresh fails.
+    pub async fn flush(
+        &mut self,
+        update: Update,
+        now_ms: u64,
+    ) -> Result<RefreshChoice, D::DriverError> {
+        let choice = self.policy.choose(update, PanelState::UNKNOWN, now_ms);
+        // Audio dropouts during the refresh are attributed to it.
+        ACTIVITY.refresh_started(choice.mode);
+        let refreshed = match choice.mode {
+            RefreshMode::Full => self.display.refresh_full().await,
+            RefreshMode::Partial => self.display.refresh_partial().await,
+            RefreshMode::Fast => self.display.refresh_fast().await,
+        };
+        ACTIVITY.refresh_finished();
+        refreshed?;
+        self.policy.record(choice.mode, now_ms);
+        self.refreshes = self.refreshes.saturating_add(1);
+        self.recovery.on_success();
+        Ok(choice)
+    }
+
+    /// [`draw`](Self::draw) `request.frame`, then [`flush`](Self::flush)
+    /// unless nothing changed or the panel has been given up on
+    /// (`Ok(None)`).
+    ///
+    /// # Errors
+    ///
+    /// Returns the driver error if drawing or the refresh fails.
+    pub async fn refresh(
+        &mut self,
+        request: RenderRequest,
+        now_ms: u64,
+    ) -> Result<Option<RefreshChoice>, D::DriverError> {
+        if self.health() == PanelHealth::Failed {
+            return Ok(None);
+        }
+        let Some(update) = self.draw(&request)? else {
+            return Ok(None);
+        };
+        self.flush(update, now_ms).await.map(Some)
+    }
+
+    /// Refreshes completed by the service.
+    #[must_use]
+    pub const fn refreshes(&self) -> u32 {
+        self.refreshes
+    }
+
+    /// Whether the panel is working, recovering or given up on.
+    #[must_use]
+    pub const fn health(&self) -> PanelHealth {
+        self.recovery.health()
+    }
+
+    /// The recovery policy, for its counters.
+    #[must_use]
+    pub const fn recovery(&self) -> &RecoveryPolicy {
+        &self.recovery
+    }
+
+    /// The refresh policy, for its wear counters.
+    #[must_use]
+    pub const fn policy(&self) -> &RefreshPolicy {
+        &self.policy
+    }
+
+    /// The panel driver.
+    pub fn display_mut(&mut self) -> &mut D {
+        &mut self.display
+    }
+}
+
+impl<D: EinkDisplay> DisplayService<D> {
+    /// Turn night mode (black background) on or off.
+    ///
+    /// Every pixel flips, so the next request is refreshed in full and,
+    /// with frame diffing, copied to the driver whole even if the frame
+    /// itself is unchanged.
+    pub fn set_inverted(&mut self, inverted: bool) {
+        if self.display.is_inverted() == inverted {
+            return;
+        }
+        self.display.set_inverted(inverted);
+        self.policy.invalidate();
+        self.frames_synced = false;
+    }
+}
+
+impl<D, C> DisplayService<D>
+where
+    D: DapDisplay<DriverError = <D as DrawTarget>::Error> + DrawTarget<Color = C>,
+    D::DriverError: TransientError,
+    C: PixelColor + From<Gray2> + From<Gray4>,
+{
+    /// Bring the panel back after a drawing or refresh call failed with
+    /// `error`.
+    ///
+    /// Each round the [`RecoveryPolicy`] allows resets and reinitialises
+    /// the controller, then refreshes in full from the driver's
+    /// framebuffer, which still holds the last frame.  Once the policy
+    /// escalates, [`Frame::DisplayFault`] is drawn and refreshed once (if
+    /// the panel still takes it) and later requests are ignored.
+    pub async fn recover(&mut self, error: &D::DriverError, now_ms: u64) -> RecoveryOutcome {
+        if !error.is_transient() {
+            return RecoveryOutcome::NotTransient;
+        }
+        loop {
+            match self.recovery.on_failure() {
+                RecoveryAction::Reinit { attempts } => {
+                    for _ in 0..attempts {
+                        if self.reinit_and_restore(now_ms).await.is_ok() {
+                            self.recovery.on_success();
+                            return RecoveryOutcome::Restored;
+                        }
+                    }
+                }
+                RecoveryAction::Escalate => {
+                    // Best effort: the panel is failing, so this may not
+                    // reach it either.
+                    self.frames_synced = false;
+                    if Frame::DisplayFault.render(&mut self.display).is_ok() {
+                        let _ = self.reinit_and_restore(now_ms).await;
+                    }
+                    return RecoveryOutcome::Escalated;
+                }
+            }
+        }
+    }
+
+    /// Reset and init the controller, then refresh the driver's
+    /// framebuffer in full.
+    async fn reinit_and_restore(&mut self, now_ms: u64) -> Result<(), D::DriverError> {
+        self.display.init().await?;
+        self.display.refresh_full().await?;
+        self.policy.record(RefreshMode::Full, now_ms);
+        self.refreshes = self.refreshes.saturating_add(1);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hardware")]
+pub use hardware::{request, run, set_night_mode, RENDER_REQUESTS};
+
+#[cfg(feature = "hardware")]
+mod hardware {
+    use core::sync::atomic::{AtomicBool, Ordering};
+
+    use embassy_futures::select::{select, Either};
+    use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+    use embassy_sync::channel::Channel;
+    use embassy_time::{Duration, Instant, Timer};
+
+    use super::{Coalescer, DisplayService, RenderRequest, REQUEST_QUEUE_DEPTH};
+    use crate::cpu_usage::CpuTask;
+    use crate::display::recovery::{PanelHealth, RecoveryOutcome, TransientError};
+    use crate::hal::DapDisplay;
+    use crate::watchdog::Heartbeat;
+    use embedded_graphics::pixelcolor::{Gray2, Gray4};
+    use embedded_graphics::prelude::*;
+    use platform::EinkDisplay;
+
+    /// Render requests from the application to [`run`].
+    ///
+    /// `CriticalSectionRawMutex` so interrupt-driven code can request a
+    /// redraw as well as tasks.
+    pub static RENDER_REQUESTS: Channel<
+        CriticalSectionRawMutex,
+        RenderRequest,
+        REQUEST_QUEUE_DEPTH,
+    > = Channel::new();
+
+    /// Queue `request` for the display service.
+    ///
+    /// Never waits: returns `false` and drops the request if the queue is
+    /// full, which only happens while a refresh is stuck.
+    pub fn request(request: RenderRequest) -> bool {
+        let sent = RENDER_REQUESTS.try_send(request).is_ok();
+        if !sent {
+            defmt::warn!("Display request dropped: queue full");
+        }
+        sent
+    }
+
+    /// Night mode as last set by [`set_night_mode`].
+    static NIGHT_MODE: AtomicBool = AtomicBool::new(false);
+
+    /// Turn night mode on or off.  [`run`] applies it before the next
+    /// request, which it then refreshes in full.
+    pub fn set_night_mode(on: bool) {
+        NIGHT_MODE.store(on, Ordering::Relaxed);
+    }
+
+    /// Serve [`RENDER_REQUESTS`] forever.
+    ///
+    /// `heartbeat` is ticked whenever the loop wakes, and at least once a
+    /// second while idle.  Failed refreshes go to
+    /// [`DisplayService::recover`]; once it gives up, requests are drained
+    /// and dropped so the rest of the firmware keeps running.
+    pub async fn run<D, C>(mut service: DisplayService<D>, heartbeat: Heartbeat) -> !
+    where
+        D: DapDisplay<DriverError = <D as DrawTarget>::Error> + EinkDisplay + DrawTarget<Color = C>,
+        D::DriverError: defmt::Format + TransientError,
+        C: PixelColor + From<Gray2> + From<Gray4>,
+    {
+        let rx = RENDER_REQUESTS.receiver();
+        let mut pending = Coalescer::new();
+        loop {
+            heartbeat.tick();
+            match select(rx.receive(), Timer::after(Duration::from_secs(1))).await {
+                Either::First(request) => pending.submit(request, Instant::now().as_millis()),
+                Either::Second(()) => continue,
+            }
+
+            // Fold in everything that arrives before the burst is due.
+            while let Some(deadline) = pending.deadline_ms() {
+                match select(rx.receive(), Timer::at(Instant::from_millis(deadline))).await {
+                    Either::First(request) => {
+                        pending.submit(request, Instant::now().as_millis());
+                    }
+                    Either::Second(()) => break,
+                }
+            }
+
+            let Some(request) = pending.take() else {
+                continue;
+            };
+            if service.health() == PanelHealth::Failed {
+                defmt::debug!("Display failed, request dropped");
+                continue;
+            }
+            service.set_inverted(NIGHT_MODE.load(Ordering::Relaxed));
+            let started = Instant::now();
+            let drawn = crate::cpu_usage::measure(CpuTask::Display, || service.draw(&request));
+            let update = match drawn {
+                Ok(Some(update)) => update,
+                Ok(None) => {
+                    defmt::debug!("Display unchanged, refresh skipped");
+                    continue;
+                }
+                Err(e) => {
+                    defmt::error!("Display render failed: {}", e);
+                    recover(&mut service, &e, started).await;
+                    continue;
+                }
+            };
+            match service.flush(update, started.as_millis()).await {
+                Ok(choice) => defmt::debug!(
+                    "Display refresh {} ({}) in {=u64} ms, {=u32} requests merged so far",
+                    choice.mode,
+                    choice.reason,
+                    started.elapsed().as_millis(),
+                    pending.merged()
+                ),
+                Err(e) => {
+                    defmt::error!("Display refresh failed: {}", e);
+                    recover(&mut service, &e, started).await;
+                }
+            }
+        }
+    }
+
+    /// Run [`DisplayService::recover`] and log how it went.
+    async fn recover<D, C>(service: &mut DisplayService<D>, error: &D::DriverError, at: Instant)
+    where
+        D: DapDisplay<DriverError = <D as DrawTarget>::Error> + DrawTarget<Color = C>,
+        D::DriverError: defmt::Format + TransientError,
+        C: PixelColor + From<Gray2> + From<Gray4>,
+    {
+        match service.recover(error, at.as_millis()).await {
+            RecoveryOutcome::Restored => defmt::warn!(
+                "Display recovered by reinit in {=u64} ms ({=u32} recoveries since boot)",
+                at.elapsed().as_millis(),
+                service.recovery().recoveries()
+            ),
+            RecoveryOutcome::Escalated => {
+                defmt::error!("Display recovery gave up; panel no longer driven");
+            }
+            RecoveryOutcome::NotTransient => {}
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::arithmetic_side_effects)]
+mod tests {
+    use super::*;
+    use crate::display::recovery::MAX_CONSECUTIVE_FAILURES;
+    use crate::display::DisplayError;
+    use core::convert::Infallible;
+    use embedded_graphics::pixelcolor::Gray4;
+    use platform::refresh_policy::RefreshReason;
+    use platform::DisplayInfo;
+
+    #[derive(Default)]
+    struct RecordingDisplay {
+        modes: Vec<RefreshMode>,
+        draws: u32,
+        pixels: u32,
+        inverted: bool,
+    }
+
+    impl OriginDimensions for RecordingDisplay {
+        fn size(&self) -> Size {
+            Size::new(800, 480)
+        }
+    }
+
+    impl DrawTarget for RecordingDisplay {
+        type Color = Gray4;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            self.draws += 1;
+            self.pixels += u32::try_from(pixels.into_iter().count()).unwrap();
+            Ok(())
+        }
+    }
+
+    impl DisplayDriver for RecordingDisplay {
+        type DriverError = Infallible;
+
+        fn spec(&self) -> DisplayInfo {
+            DisplayInfo::new(800, 480)
+        }
+
+        async fn update_buffer(&mut self, _framebuffer: &[u8]) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+
+        async fn refresh_full(&mut self) -> Result<(), Self::DriverError> {
+            self.modes.push(RefreshMode::Full);
+            Ok(())
+        }
+
+        async fn refresh_partial(&mut self) -> Result<(), Self::DriverError> {
+            self.modes.push(RefreshMode::Partial);
+            Ok(())
+        }
+
+        async fn refresh_fast(&mut self) -> Result<(), Self::DriverError> {
+            self.modes.push(RefreshMode::Fast);
+            Ok(())
+        }
+
+        async fn sleep(&mut self) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+
+        async fn wake(&mut self) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+    }
+
+    impl EinkDisplay for RecordingDisplay {
+        fn refresh_mode(&self) -> RefreshMode {
+            RefreshMode::Full
+        }
+
+        fn set_refresh_mode(&mut self, _mode: RefreshMode) {}
+
+        fn temperature(&self) -> Option<i8> {
+            None
+        }
+
+        fn set_inverted(&mut self, inverted: bool) {
+            self.inverted = inverted;
+        }
+
+        fn is_inverted(&self) -> bool {
+            self.inverted
+        }
+    }
+
+    /// Panel whose refreshes and inits fail while their counters are
+    /// non-zero.
+    #[derive(Default)]
+    struct FlakyDisplay {
+        failing_refreshes: u32,
+        failing_inits: u32,
+        inits: u32,
+        full_refreshes: u32,
+    }
+
+    impl FlakyDisplay {
+        fn refresh(&mut self) -> Result<(), DisplayError> {
+            if self.failing_refreshes > 0 {
+                self.failing_refreshes -= 1;
+                return Err(DisplayError::Timeout);
+            }
+            Ok(())
+        }
+    }
+
+    impl OriginDimensions for FlakyDisplay {
+        fn size(&self) -> Size {
+            Size::new(800, 480)
+        }
+    }
+
+    impl DrawTarget for FlakyDisplay {
+        type Color = Gray4;
+        type Error = DisplayError;
+
+        fn draw_iter<I>(&mut self, _pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            Ok(())
+        }
+    }
+
+    impl DisplayDriver for FlakyDisplay {
+        type DriverError = DisplayError;
+
+        fn spec(&self) -> DisplayInfo {
+            DisplayInfo::new(800, 480)
+        }
+
+        async fn update_buffer(&mut self, _framebuffer: &[u8]) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+
+        async fn refresh_full(&mut self) -> Result<(), Self::DriverError> {
+            self.refresh()?;
+            self.full_refreshes += 1;
+            Ok(())
+        }
+
+        async fn refresh_partial(&mut self) -> Result<(), Self::DriverError> {
+            self.refresh()
+        }
+
+        async fn sleep(&mut self) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+
+        async fn wake(&mut self) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+    }
+
+    impl DapDisplay for FlakyDisplay {
+        async fn init(&mut self) -> Result<(), Self::DriverError> {
+            self.inits += 1;
+            if self.failing_inits > 0 {
+                self.failing_inits -= 1;
+                return Err(DisplayError::Communication);
+            }
+            Ok(())
+        }
+
+        fn framebuffer_size(&self) -> usize {
+            0
+        }
+
+        async fn clear(&mut self, _color: crate::hal::Color) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+    }
+
+    fn small(hint: ContentHint) -> RenderRequest {
+        RenderRequest::new(Frame::TestPattern, Update::new(200 * 24, hint))
+    }
+
+    #[test]
+    fn burst_merges_into_latest_frame() {
+        let mut c = Coalescer::new();
+        assert_eq!(c.deadline_ms(), None);
+        c.submit(small(ContentHint::Text), 100);
+        c.submit(small(ContentHint::Image), 110);
+        c.submit(
+            RenderRequest::new(Frame::Splash, Update::new(10, ContentHint::Animation)),
+            130,
+        );
+        assert_eq!(c.deadline_ms(), Some(100 + COALESCE_WINDOW_MS));
+        let merged = c.take().unwrap();
+        assert_eq!(merged.frame, Frame::Splash);
+        assert_eq!(merged.update.dirty_pixels, 2 * 200 * 24 + 10);
+        assert_eq!(merged.update.hint, ContentHint::Image);
+        assert_eq!(c.merged(), 2);
+        assert!(c.take().is_none());
+    }
+
+    #[test]
+    fn new_burst_restarts_the_window() {
+        let mut c = Coalescer::new();
+        c.submit(small(ContentHint::Text), 100);
+        c.take();
+        c.submit(small(ContentHint::Text), 500);
+        assert_eq!(c.deadline_ms(), Some(500 + COALESCE_WINDOW_MS));
+    }
+
+    #[test]
+    fn full_screen_dirty_area_saturates() {
+        let mut c = Coalescer::new();
+        c.submit(
+            RenderRequest::full_screen(Frame::Splash, ContentHint::Graphics),
+            0,
+        );
+        c.submit(small(ContentHint::Text), 1);
+        assert_eq!(c.take().unwrap().update.dirty_pixels, u32::MAX);
+    }
+
+    #[tokio::test]
+    async fn first_refresh_is_full_then_policy_decides() {
+        let mut service = DisplayService::new(RecordingDisplay::default());
+        let first = service
+            .refresh(small(ContentHint::Text), 0)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.reason, RefreshReason::FirstFrame);
+        let next = service
+            .refresh(small(ContentHint::Animation), 1_000)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(next.mode, RefreshMode::Fast);
+        assert_eq!(
+            service.display_mut().modes,
+            [RefreshMode::Full, RefreshMode::Fast]
+        );
+        assert!(service.display_mut().draws > 0);
+        assert_eq!(service.refreshes(), 2);
+        assert_eq!(service.policy().partials_since_full(), 1);
+    }
+
+    #[tokio::test]
+    async fn splash_refresh_is_not_repeated() {
+        let mut service = DisplayService::new(RecordingDisplay::default()).after_full_refresh(0);
+        let choice = service
+            .refresh(small(ContentHint::Text), 3_000)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(choice.mode, RefreshMode::Partial);
+
+        let page = service
+            .refresh(
+                RenderRequest::full_screen(Frame::TestPattern, ContentHint::Graphics),
+                4_000,
+            )
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(page.reason, RefreshReason::LargeArea);
+    }
+
+    fn frames() -> DoubleBuffer<'static> {
+        let size = Size::new(800, 480);
+        let bytes = platform::frame_diff::frame_bytes(size);
+        let front = Box::leak(vec![0u8; bytes].into_boxed_slice());
+        let back = Box::leak(vec![0u8; bytes].into_boxed_slice());
+        DoubleBuffer::new(front, back, size).unwrap()
+    }
+
+    #[tokio::test]
+    async fn frame_diff_skips_unchanged_frames() {
+        let mut service = DisplayService::new(RecordingDisplay::default())
+            .after_full_refresh(0)
+            .with_frame_diff(frames());
+
+        // The first frame is copied whole: the panel content is unknown.
+        let page = RenderRequest::full_screen(Frame::TestPattern, ContentHint::Graphics);
+        let first = service.refresh(page, 3_000).await.unwrap().unwrap();
+        assert_eq!(first.reason, RefreshReason::LargeArea);
+        assert_eq!(service.display_mut().pixels, 800 * 480);
+
+        // Same frame again: nothing drawn, nothing refreshed.
+        service.display_mut().pixels = 0;
+        assert_eq!(service.refresh(page, 4_000).await.unwrap(), None);
+        assert_eq!(service.display_mut().pixels, 0);
+        assert_eq!(service.refreshes(), 1);
+
+        // A different frame: only the changed tiles are copied.
+        let splash = RenderRequest::full_screen(Frame::Splash, ContentHint::Graphics);
+        assert!(service.refresh(splash, 5_000).await.unwrap().is_some());
+        let copied = service.display_mut().pixels;
+        assert!(copied > 0 && copied % 64 == 0, "{copied} pixels copied");
+        assert_eq!(service.refreshes(), 2);
+    }
+
+    #[tokio::test]
+    async fn night_mode_forces_a_full_refresh_of_an_unchanged_frame() {
+        let mut service = DisplayService::new(RecordingDisplay::default())
+            .after_full_refresh(0)
+            .with_frame_diff(frames());
+        let page = RenderRequest::full_screen(Frame::TestPattern, ContentHint::Graphics);
+        service.refresh(page, 3_000).await.unwrap();
+
+        service.set_inverted(true);
+        assert!(service.display_mut().inverted);
+        let choice = service.refresh(page, 4_000).await.unwrap().unwrap();
+        assert_eq!(choice.reason, RefreshReason::Invalidated);
+        assert_eq!(choice.mode, RefreshMode::Full);
+
+        // Setting the same mode again changes nothing.
+        service.set_inverted(true);
+        assert_eq!(service.refresh(page, 5_000).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn refresh_timeout_is_recovered_by_reinit() {
+        let mut service = DisplayService::new(FlakyDisplay::default()).after_full_refresh(0);
+        service.display_mut().failing_refreshes = 1;
+        let err = service
+            .refresh(small(ContentHint::Text), 1_000)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            service.recover(&err, 1_000).await,
+            RecoveryOutcome::Restored
+        );
+        // One reinit, then the last frame restored with a full refresh.
+        assert_eq!(service.display_mut().inits, 1);
+        assert_eq!(service.display_mut().full_refreshes, 1);
+        assert_eq!(service.health(), PanelHealth::Ok);
+        assert_eq!(service.recovery().recoveries(), 1);
+        assert_eq!(service.policy().partials_since_full(), 0);
+    }
+
+    #[tokio::test]
+    async fn repeated_failures_escalate_to_the_fault_screen() {
+        let mut service = DisplayService::new(FlakyDisplay::default()).after_full_refresh(0);
+        // Every reinit fails; the last one, for the fault screen, works.
+        service.display_mut().failing_inits = u32::from(MAX_CONSECUTIVE_FAILURES) - 1;
+        let outcome = service.recover(&DisplayError::Timeout, 1_000).await;
+        assert_eq!(outcome, RecoveryOutcome::Escalated);
+        assert_eq!(
+            service.display_mut().inits,
+            u32::from(MAX_CONSECUTIVE_FAILURES)
+        );
+        assert_eq!(
+            service.display_mut().full_refreshes,
+            1,
+            "fault screen shown"
+        );
+        assert_eq!(service.health(), PanelHealth::Failed);
+
+        // The panel is no longer driven.
+        let skipped = service.refresh(small(ContentHint::Text), 2_000).await;
+        assert_eq!(skipped, Ok(None));
+        assert_eq!(service.display_mut().full_refreshes, 1);
+    }
+
+    #[tokio::test]
+    async fn non_transient_errors_are_not_recovered() {
+        let mut service = DisplayService::new(FlakyDisplay::default());
+        let outcome = service.recover(&DisplayError::InvalidBuffer, 0).await;
+        assert_eq!(outcome, RecoveryOutcome::NotTransient);
+        assert_eq!(service.display_mut().inits, 0);
+        assert_eq!(service.health(), PanelHealth::Ok);
+    }
+}
@@ -0,0 +1,574 @@
+//! SSD1680 driver for small status panels.
+//!
+//! Targets the Waveshare 2.13" V4 (122×250, Carta 1000).  The controller is a
+//! smaller sibling of the SSD1677: same command set and BUSY polarity, but
+//! one-byte RAM X addresses and a 250-gate Y range.
+//!
+//! Logical coordinates are the controller's native portrait orientation,
+//! 122 pixels wide and 250 tall; rotate in the UI layer for landscape.  RAM
+//! rows are 16 bytes, so the last 6 bits of every row are padding.
+//!
+//! Unlike the GDEM0397T81P, the gates are not reversed: data entry mode
+//! `0x03` (X+, Y+) scans from logical row 0.
+
+#![allow(clippy::doc_markdown)] // Register and panel names read better without backticks
+
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::{delay::DelayNs, spi::SpiDevice};
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+
+use eink_specs::Quirk;
+use platform::{DisplayDriver, EinkDisplay, RefreshMode};
+
+use super::controller::{self, BusyPolarity, ControllerBus, ResetPulse};
+use super::driver::DisplayError;
+use crate::hal::{Color, DapDisplay};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Panel width in pixels (RAM X, source lines).
+pub const SSD1680_WIDTH: u32 = 122;
+
+/// Panel height in pixels (RAM Y, gate lines).
+pub const SSD1680_HEIGHT: u32 = 250;
+
+/// Bytes per RAM row: 122 pixels rounded up to 16 bytes.
+pub const SSD1680_BYTES_PER_ROW: usize = SSD1680_WIDTH.div_ceil(8) as usize;
+
+/// 1bpp framebuffer size (16 × 250 = 4 000 bytes).
+pub const SSD1680_FRAMEBUFFER_SIZE: usize = SSD1680_BYTES_PER_ROW * SSD1680_HEIGHT as usize;
+
+/// RST timing from the Waveshare reference driver.
+const RESET_PULSE: ResetPulse = ResetPulse {
+    settle_ms: 20,
+    low_ms: 2,
+};
+
+/// SSD1680 command codes.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+pub enum Command {
+    /// Driver output control — 3 data bytes (MUX gate lines, scan order).
+    DriverOutputControl = 0x01,
+    /// Deep sleep — 1 data byte (0x01 = preserve RAM).
+    DeepSleep = 0x10,
+    /// Data entry mode — 1 data byte.
+    DataEntryMode = 0x11,
+    /// Software reset — 0 data bytes; poll BUSY after.
+    SoftReset = 0x12,
+    /// Temperature sensor selection — 1 data byte (0x80 = internal).
+    TempSensorControl = 0x18,
+    /// Master activation — 0 data bytes; runs the update sequence.
+    MasterActivation = 0x20,
+    /// Display update control 1 — 2 data bytes.
+    DisplayUpdateCtrl1 = 0x21,
+    /// Display update control 2 — 1 data byte (sequence flags).
+    DisplayUpdateCtrl2 = 0x22,
+    /// Write RAM (B/W) — 0 = black, 1 = white, MSB-first.
+    WriteRamBW = 0x24,
+    /// Write RAM (previous frame) — the reference for partial updates.
+    WriteRamRed = 0x26,
+    /// Border waveform control — 1 data byte.
+    BorderWaveform = 0x3C,
+    /// Set RAM X start/end address — 2 data bytes (byte addresses).
+    SetRamXRange = 0x44,
+    /// Set RAM Y start/end address — 4 data bytes.
+    SetRamYRange = 0x45,
+    /// Set RAM X address counter — 1 data byte.
+    SetRamXCounter = 0x4E,
+    /// Set RAM Y address counter — 2 data bytes.
+    SetRamYCounter = 0x4F,
+}
+
+/// Full refresh using the OTP waveform.
+pub const SSD1680_UPDATE_FULL: u8 = 0xF7;
+/// Fast full refresh (shorter OTP waveform, some ghosting).
+pub const SSD1680_UPDATE_FAST: u8 = 0xC7;
+/// Partial refresh against the previous-frame RAM.
+pub const SSD1680_UPDATE_PARTIAL: u8 = 0x0F;
+
+// ---------------------------------------------------------------------------
+// Driver struct
+// ---------------------------------------------------------------------------
+
+/// SSD1680 display driver.
+///
+/// Generic over the same peripherals as [`Ssd1677`](super::Ssd1677); BUSY
+/// is HIGH while the controller works.
+pub struct Ssd1680<SPI, DC, RST, BUSY, DELAY> {
+    bus: ControllerBus<SPI, DC, RST, BUSY, DELAY>,
+    refresh_mode: RefreshMode,
+    partial_refresh_count: u8,
+    /// Controller quirks whose workarounds this driver applies.
+    quirks: &'static [Quirk],
+    /// Show both RAMs inverted (night mode), via `DisplayUpdateCtrl1`.
+    inverted: bool,
+    /// `DisplayUpdateCtrl1` does not match `inverted` yet; the next refresh
+    /// is a full one that sends it.
+    invert_pending: bool,
+    /// 1bpp packed framebuffer, 16 bytes per row.
+    framebuffer: [u8; SSD1680_FRAMEBUFFER_SIZE],
+}
+
+impl<SPI, DC, RST, BUSY, DELAY> Ssd1680<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    /// Create a new driver instance with an all-white framebuffer.
+    #[must_use]
+    pub fn new(spi: SPI, dc: DC, rst: RST, busy: BUSY, delay: DELAY) -> Self {
+        // Like the SSD1677 driver, this lives in a static or a task stack
+        // sized for it on hardware.
+        #[allow(clippy::large_stack_arrays)]
+        Self {
+            bus: ControllerBus::new(spi, dc, rst, busy, delay, BusyPolarity::ActiveHigh),
+            refresh_mode: RefreshMode::Full,
+            partial_refresh_count: 0,
+            quirks: eink_specs::Controller::SSD1680.quirks(),
+            inverted: false,
+            invert_pending: false,
+            framebuffer: [0xFF; SSD1680_FRAMEBUFFER_SIZE],
+        }
+    }
+
+    /// Replace the quirk list (default: the SSD1680 controller quirks).
+    pub fn set_quirks(&mut self, quirks: &'static [Quirk]) {
+        self.quirks = quirks;
+    }
+
+    /// Consume the driver and return its peripherals.
+    pub fn release(self) -> (SPI, DC, RST, BUSY, DELAY) {
+        self.bus.release()
+    }
+
+    /// The packed 1bpp framebuffer (16 bytes per row, MSB-first, 1 = white).
+    pub fn framebuffer(&self) -> &[u8] {
+        &self.framebuffer
+    }
+
+    async fn wait_busy(&mut self) -> Result<(), DisplayError> {
+        self.bus
+            .wait_busy(controller::busy_polls(self.quirks))
+            .await
+    }
+
+    async fn cmd_data(&mut self, cmd: Command, data: &[u8]) -> Result<(), DisplayError> {
+        self.bus.cmd_data(cmd as u8, data).await
+    }
+
+    /// Full-screen RAM window with the counters at the top-left.
+    async fn set_full_window(&mut self) -> Result<(), DisplayError> {
+        // 16 bytes per row → X 0..=15; 250 gates → Y 0..=249 (0x00F9).
+        let [y_end_lo, y_end_hi] = 249u16.to_le_bytes();
+        self.cmd_data(Command::SetRamXRange, &[0x00, 0x0F]).await?;
+        self.cmd_data(Command::SetRamYRange, &[0x00, 0x00, y_end_lo, y_end_hi])
+            .await?;
+        self.cmd_data(Command::SetRamXCounter, &[0x00]).await?;
+        self.cmd_data(Command::SetRamYCounter, &[0x00, 0x00]).await
+    }
+
+    /// Push the framebuffer to the RAM written by `cmd`.
+    async fn flush_framebuffer(&mut self, cmd: Command) -> Result<(), DisplayError> {
+        self.set_full_window().await?;
+        self.bus.write_ram(cmd as u8, &self.framebuffer).await
+    }
+
+    /// `DisplayUpdateCtrl1`: B/W and previous-frame RAM normal or, in night
+    /// mode, both inverse so partial refreshes still diff like for like;
+    /// source output S8..S167.
+    async fn send_ctrl1(&mut self) -> Result<(), DisplayError> {
+        let ram_option = if self.inverted { 0x88 } else { 0x00 };
+        self.cmd_data(Command::DisplayUpdateCtrl1, &[ram_option, 0x80])
+            .await?;
+        self.invert_pending = false;
+        Ok(())
+    }
+
+    /// Run the update sequence selected by `flags` and wait for it to finish.
+    async fn activate(&mut self, flags: u8) -> Result<(), DisplayError> {
+        self.cmd_data(Command::DisplayUpdateCtrl2, &[flags]).await?;
+        self.bus.command(Command::MasterActivation as u8).await?;
+        self.wait_busy().await
+    }
+
+    /// Initialisation sequence for the Waveshare 2.13" V4.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DisplayError`] if any SPI command or reset sequence fails.
+    /// A BUSY timeout is retried under a `RetryWithRecovery` workaround.
+    pub async fn init(&mut self) -> Result<(), DisplayError> {
+        let mut retries = controller::init_retries(self.quirks);
+        loop {
+            match self.init_sequence().await {
+                Err(DisplayError::Timeout) if retries > 0 => retries = retries.saturating_sub(1),
+                result => return result,
+            }
+        }
+    }
+
+    async fn init_sequence(&mut self) -> Result<(), DisplayError> {
+        self.bus.hardware_reset(RESET_PULSE).await?;
+        self.wait_busy().await?;
+
+        self.bus.command(Command::SoftReset as u8).await?;
+        self.wait_busy().await?;
+
+        // 250 gates (MUX = 249 = 0x00F9), default scan order.
+        self.cmd_data(Command::DriverOutputControl, &[0xF9, 0x00, 0x00])
+            .await?;
+        // X+, Y+: logical row 0 is RAM row 0.
+        self.cmd_data(Command::DataEntryMode, &[0x03]).await?;
+        self.set_full_window().await?;
+        self.cmd_data(Command::BorderWaveform, &[0x05]).await?;
+        self.send_ctrl1().await?;
+        self.cmd_data(Command::TempSensorControl, &[0x80]).await?;
+        self.wait_busy().await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// platform::DisplayDriver implementation
+// ---------------------------------------------------------------------------
+
+impl<SPI, DC, RST, BUSY, DELAY> DisplayDriver for Ssd1680<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    type DriverError = DisplayError;
+
+    fn spec(&self) -> platform::DisplayInfo {
+        // Native portrait orientation; only the B/W RAM is written.
+        platform::DisplayInfo {
+            width: SSD1680_WIDTH,
+            height: SSD1680_HEIGHT,
+            grayscale_levels: 2,
+            ..super::display_info(&eink_specs::displays::WAVESHARE_2_13_V4)
+        }
+    }
+
+    /// Copy a packed 1bpp framebuffer ([`SSD1680_FRAMEBUFFER_SIZE`] bytes)
+    /// and push it to the B/W RAM.
+    async fn update_buffer(&mut self, framebuffer: &[u8]) -> Result<(), Self::DriverError> {
+        if framebuffer.len() != SSD1680_FRAMEBUFFER_SIZE {
+            return Err(DisplayError::InvalidBuffer);
+        }
+        self.framebuffer.copy_from_slice(framebuffer);
+        self.flush_framebuffer(Command::WriteRamBW).await
+    }
+
+    /// Full refresh.  Also writes the previous-frame RAM, which later
+    /// partial refreshes diff against, and applies a changed inversion.
+    async fn refresh_full(&mut self) -> Result<(), Self::DriverError> {
+        if self.invert_pending {
+            self.send_ctrl1().await?;
+        }
+        self.flush_framebuffer(Command::WriteRamBW).await?;
+        self.flush_framebuffer(Command::WriteRamRed).await?;
+        self.activate(SSD1680_UPDATE_FULL).await?;
+        self.partial_refresh_count = 0;
+        Ok(())
+    }
+
+    /// Partial refresh; promoted to a full refresh under a
+    /// `FullRefreshEvery` workaround once the limit is reached, and after
+    /// the inversion changed.
+    async fn refresh_partial(&mut self) -> Result<(), Self::DriverError> {
+        if self.invert_pending
+            || controller::partial_limit(self.quirks)
+                .is_some_and(|max| self.partial_refresh_count >= max)
+        {
+            return self.refresh_full().await;
+        }
+        self.flush_framebuffer(Command::WriteRamBW).await?;
+        self.activate(SSD1680_UPDATE_PARTIAL).await?;
+        self.partial_refresh_count = self.partial_refresh_count.saturating_add(1);
+        Ok(())
+    }
+
+    /// Fast full refresh using the shorter OTP waveform.
+    async fn refresh_fast(&mut self) -> Result<(), Self::DriverError> {
+        if self.invert_pending {
+            self.send_ctrl1().await?;
+        }
+        self.flush_framebuffer(Command::WriteRamBW).await?;
+        self.flush_framebuffer(Command::WriteRamRed).await?;
+        self.activate(SSD1680_UPDATE_FAST).await?;
+        self.partial_refresh_count = 0;
+        Ok(())
+    }
+
+    /// Enter deep sleep (preserves RAM).
+    async fn sleep(&mut self) -> Result<(), Self::DriverError> {
+        self.cmd_data(Command::DeepSleep, &[0x01]).await?;
+        self.bus.delay_ms(100).await;
+        Ok(())
+    }
+
+    /// Wake from deep sleep; only a hardware reset leaves it.
+    async fn wake(&mut self) -> Result<(), Self::DriverError> {
+        self.init().await
+    }
+
+    async fn wait_ready(&mut self) -> Result<(), Self::DriverError> {
+        self.wait_busy().await
+    }
+}
+
+impl<SPI, DC, RST, BUSY, DELAY> DapDisplay for Ssd1680<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self) -> Result<(), Self::DriverError> {
+        Ssd1680::init(self).await
+    }
+
+    fn framebuffer_size(&self) -> usize {
+        SSD1680_FRAMEBUFFER_SIZE
+    }
+
+    async fn clear(&mut self, color: Color) -> Result<(), Self::DriverError> {
+        self.framebuffer.fill(match color {
+            Color::White => 0xFF,
+            Color::Black => 0x00,
+        });
+        self.refresh_full().await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// embedded-graphics
+// ---------------------------------------------------------------------------
+
+impl<SPI, DC, RST, BUSY, DELAY> DrawTarget for Ssd1680<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    type Color = BinaryColor;
+    type Error = DisplayError;
+
+    /// Write pixels into the framebuffer; `BinaryColor::On` is black.
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            controller::set_pixel(
+                &mut self.framebuffer,
+                SSD1680_WIDTH,
+                SSD1680_HEIGHT,
+                point,
+                color,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<SPI, DC, RST, BUSY, DELAY> OriginDimensions for Ssd1680<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    fn size(&self) -> Size {
+        Size::new(SSD1680_WIDTH, SSD1680_HEIGHT)
+    }
+}
+
+impl<SPI, DC, RST, BUSY, DELAY> EinkDisplay for Ssd1680<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    fn refresh_mode(&self) -> RefreshMode {
+        self.refresh_mode
+    }
+
+    fn set_refresh_mode(&mut self, mode: RefreshMode) {
+        self.refresh_mode = mode;
+    }
+
+    #[allow(clippy::unused_self)] // Required by EinkDisplay; the temperature register is not read yet
+    fn temperature(&self) -> Option<i8> {
+        None
+    }
+
+    /// Inverted by the controller (`DisplayUpdateCtrl1`); the framebuffer
+    /// keeps its normal polarity.
+    fn set_inverted(&mut self, inverted: bool) {
+        if inverted != self.inverted {
+            self.inverted = inverted;
+            self.invert_pending = true;
+        }
+    }
+
+    fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::digital::{
+        Mock as PinMock, State as PinState, Transaction as PinTransaction,
+    };
+    use embedded_hal_mock::eh1::spi::{Mock as SpiMock, Transaction as SpiTransaction};
+
+    type TestDriver = Ssd1680<SpiMock<u8>, PinMock, PinMock, PinMock, NoopDelay>;
+
+    fn idle_driver() -> TestDriver {
+        Ssd1680::new(
+            SpiMock::new(&[]),
+            PinMock::new(&[]),
+            PinMock::new(&[]),
+            PinMock::new(&[]),
+            NoopDelay,
+        )
+    }
+
+    fn done(drv: TestDriver) {
+        let (mut spi, mut dc, mut rst, mut busy, _) = drv.release();
+        spi.done();
+        dc.done();
+        rst.done();
+        busy.done();
+    }
+
+    /// One `SpiDevice::write` as the mock sees it.
+    fn write(data: &[u8]) -> [SpiTransaction<u8>; 3] {
+        [
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(data.to_vec()),
+            SpiTransaction::transaction_end(),
+        ]
+    }
+
+    #[test]
+    fn test_framebuffer_geometry() {
+        assert_eq!(SSD1680_BYTES_PER_ROW, 16);
+        assert_eq!(SSD1680_FRAMEBUFFER_SIZE, 4_000);
+        let drv = idle_driver();
+        assert_eq!(drv.framebuffer_size(), SSD1680_FRAMEBUFFER_SIZE);
+        assert_eq!(drv.size(), Size::new(122, 250));
+        done(drv);
+    }
+
+    /// `test_draw_uses_padded_rows` — pixel (0, 1) lands at byte 16, not
+    /// byte 15 as it would without row padding.
+    #[test]
+    fn test_draw_uses_padded_rows() {
+        let mut drv = idle_driver();
+        Pixel(Point::new(0, 1), BinaryColor::On)
+            .draw(&mut drv)
+            .unwrap();
+        Pixel(Point::new(121, 0), BinaryColor::On)
+            .draw(&mut drv)
+            .unwrap();
+        Pixel(Point::new(122, 0), BinaryColor::On)
+            .draw(&mut drv)
+            .unwrap();
+        assert_eq!(drv.framebuffer()[16], 0x7F);
+        assert_eq!(drv.framebuffer()[15], 0xBF);
+        assert_eq!(drv.framebuffer().iter().filter(|&&b| b != 0xFF).count(), 2);
+        done(drv);
+    }
+
+    /// `test_sleep_command` — deep sleep is 0x10 with RAM retention.
+    #[tokio::test]
+    async fn test_sleep_command() {
+        let mut spi_expectations = vec![];
+        spi_expectations.extend_from_slice(&write(&[0x10]));
+        spi_expectations.extend_from_slice(&write(&[0x01]));
+        let dc = PinMock::new(&[
+            PinTransaction::set(PinState::Low),
+            PinTransaction::set(PinState::High),
+        ]);
+        let mut drv = Ssd1680::new(
+            SpiMock::new(&spi_expectations),
+            dc,
+            PinMock::new(&[]),
+            PinMock::new(&[]),
+            NoopDelay,
+        );
+        drv.sleep().await.unwrap();
+        done(drv);
+    }
+
+    /// `test_refresh_flags` — the update sequences differ from the SSD1677's.
+    #[test]
+    fn test_refresh_flags() {
+        assert_eq!(SSD1680_UPDATE_FULL, 0xF7);
+        assert_eq!(SSD1680_UPDATE_FAST, 0xC7);
+        assert_eq!(SSD1680_UPDATE_PARTIAL, 0x0F);
+    }
+
+    /// `test_night_mode_inverts_both_rams` — `DisplayUpdateCtrl1` selects
+    /// the inverse of the B/W and previous-frame RAMs.
+    #[tokio::test]
+    async fn test_night_mode_inverts_both_rams() {
+        let mut spi_expectations = vec![];
+        spi_expectations.extend_from_slice(&write(&[0x21]));
+        spi_expectations.extend_from_slice(&write(&[0x88, 0x80]));
+        let dc = PinMock::new(&[
+            PinTransaction::set(PinState::Low),
+            PinTransaction::set(PinState::High),
+        ]);
+        let mut drv = Ssd1680::new(
+            SpiMock::new(&spi_expectations),
+            dc,
+            PinMock::new(&[]),
+            PinMock::new(&[]),
+            NoopDelay,
+        );
+        drv.set_inverted(true);
+        assert!(drv.is_inverted());
+        assert!(drv.invert_pending, "next refresh must apply it");
+        drv.send_ctrl1().await.unwrap();
+        assert!(!drv.invert_pending);
+        done(drv);
+    }
+
+    /// `test_wrong_buffer_size_rejected` — nothing reaches the bus.
+    #[tokio::test]
+    async fn test_wrong_buffer_size_rejected() {
+        let mut drv = idle_driver();
+        let result = drv.update_buffer(&[0u8; 48_000]).await;
+        assert_eq!(result, Err(DisplayError::InvalidBuffer));
+        done(drv);
+    }
+}
@@ -0,0 +1,521 @@
+//! UC8176 driver for 4.2" panels.
+//!
+//! Targets the Waveshare 4.2" V1 / Good Display GDEW042T2 class of panels
+//! (400×300, Carta 1200).  The UltraChip command set differs from the
+//! Solomon Systech one: there is no RAM window, the whole frame is streamed
+//! with `DTM2`, and BUSY is LOW while the controller works.
+//!
+//! The driver runs the OTP waveform only.  The OTP has no partial or fast
+//! waveform, so partial and fast refreshes run as full refreshes; partial
+//! updates would need register LUTs tuned per panel batch.
+//!
+//! Night mode inverts at the framebuffer level: the frame is inverted while
+//! it streams through `DTM2`, so [`Uc8176::framebuffer`] keeps its normal
+//! polarity.
+
+#![allow(clippy::doc_markdown)] // Register and panel names read better without backticks
+
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::{delay::DelayNs, spi::SpiDevice};
+
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+
+use eink_specs::Quirk;
+use platform::{DisplayDriver, EinkDisplay, RefreshMode};
+
+use super::controller::{self, BusyPolarity, ControllerBus, ResetPulse};
+use super::driver::DisplayError;
+use crate::hal::{Color, DapDisplay};
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/// Panel width in pixels.
+pub const UC8176_WIDTH: u32 = 400;
+
+/// Panel height in pixels.
+pub const UC8176_HEIGHT: u32 = 300;
+
+/// 1bpp framebuffer size (50 bytes × 300 rows = 15 000 bytes).
+pub const UC8176_FRAMEBUFFER_SIZE: usize = (UC8176_WIDTH / 8 * UC8176_HEIGHT) as usize;
+
+/// RST timing from the Waveshare reference driver.
+const RESET_PULSE: ResetPulse = ResetPulse {
+    settle_ms: 10,
+    low_ms: 10,
+};
+
+/// UC8176 command codes.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy)]
+#[allow(dead_code)]
+pub enum Command {
+    /// Panel setting (PSR) — 1 data byte.
+    PanelSetting = 0x00,
+    /// Power setting (PWR) — 5 data bytes.
+    PowerSetting = 0x01,
+    /// Power off (POF) — 0 data bytes; poll BUSY after.
+    PowerOff = 0x02,
+    /// Power on (PON) — 0 data bytes; poll BUSY after.
+    PowerOn = 0x04,
+    /// Booster soft start (BTST) — 3 data bytes.
+    BoosterSoftStart = 0x06,
+    /// Deep sleep (DSLP) — 1 data byte, the 0xA5 check code.
+    DeepSleep = 0x07,
+    /// Display start transmission 1 (DTM1) — previous frame.
+    DataStart1 = 0x10,
+    /// Display refresh (DRF) — 0 data bytes; poll BUSY after.
+    DisplayRefresh = 0x12,
+    /// Display start transmission 2 (DTM2) — new frame, 1 = white.
+    DataStart2 = 0x13,
+    /// VCOM and data interval setting (CDI) — 1 data byte.
+    VcomDataInterval = 0x50,
+    /// Resolution setting (TRES) — 4 data bytes.
+    Resolution = 0x61,
+}
+
+// ---------------------------------------------------------------------------
+// Driver struct
+// ---------------------------------------------------------------------------
+
+/// UC8176 display driver.
+///
+/// Generic over the same peripherals as [`Ssd1677`](super::Ssd1677); BUSY
+/// is LOW while the controller works.
+pub struct Uc8176<SPI, DC, RST, BUSY, DELAY> {
+    bus: ControllerBus<SPI, DC, RST, BUSY, DELAY>,
+    refresh_mode: RefreshMode,
+    /// Controller quirks whose workarounds this driver applies.
+    quirks: &'static [Quirk],
+    /// Stream the frame inverted (night mode).
+    inverted: bool,
+    /// 1bpp packed framebuffer, 50 bytes per row.
+    framebuffer: [u8; UC8176_FRAMEBUFFER_SIZE],
+}
+
+impl<SPI, DC, RST, BUSY, DELAY> Uc8176<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    /// Create a new driver instance with an all-white framebuffer.
+    #[must_use]
+    pub fn new(spi: SPI, dc: DC, rst: RST, busy: BUSY, delay: DELAY) -> Self {
+        // Like the SSD1677 driver, this lives in a static or a task stack
+        // sized for it on hardware.
+        #[allow(clippy::large_stack_arrays)]
+        Self {
+            bus: ControllerBus::new(spi, dc, rst, busy, delay, BusyPolarity::ActiveLow),
+            refresh_mode: RefreshMode::Full,
+            quirks: eink_specs::Controller::UC8176.quirks(),
+            inverted: false,
+            framebuffer: [0xFF; UC8176_FRAMEBUFFER_SIZE],
+        }
+    }
+
+    /// Replace the quirk list (default: the UC8176 controller quirks).
+    pub fn set_quirks(&mut self, quirks: &'static [Quirk]) {
+        self.quirks = quirks;
+    }
+
+    /// Consume the driver and return its peripherals.
+    pub fn release(self) -> (SPI, DC, RST, BUSY, DELAY) {
+        self.bus.release()
+    }
+
+    /// The packed 1bpp framebuffer (50 bytes per row, MSB-first, 1 = white).
+    pub fn framebuffer(&self) -> &[u8] {
+        &self.framebuffer
+    }
+
+    async fn wait_busy(&mut self) -> Result<(), DisplayError> {
+        self.bus
+            .wait_busy(controller::busy_polls(self.quirks))
+            .await
+    }
+
+    async fn cmd_data(&mut self, cmd: Command, data: &[u8]) -> Result<(), DisplayError> {
+        self.bus.cmd_data(cmd as u8, data).await
+    }
+
+    /// Initialisation sequence: power up and load the OTP waveform.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DisplayError`] if any SPI command or reset sequence fails.
+    /// A BUSY timeout is retried under a `RetryWithRecovery` workaround.
+    pub async fn init(&mut self) -> Result<(), DisplayError> {
+        let mut retries = controller::init_retries(self.quirks);
+        loop {
+            match self.init_sequence().await {
+                Err(DisplayError::Timeout) if retries > 0 => retries = retries.saturating_sub(1),
+                result => return result,
+            }
+        }
+    }
+
+    async fn init_sequence(&mut self) -> Result<(), DisplayError> {
+        self.bus.hardware_reset(RESET_PULSE).await?;
+
+        // Internal VDS/VDG, VGH/VGL ±16 V, VDH/VDL ±11 V, VDHR 15 V.
+        self.cmd_data(Command::PowerSetting, &[0x03, 0x00, 0x2B, 0x2B, 0xFF])
+            .await?;
+        self.cmd_data(Command::BoosterSoftStart, &[0x17, 0x17, 0x17])
+            .await?;
+        self.bus.command(Command::PowerOn as u8).await?;
+        self.wait_busy().await?;
+
+        // LUT from OTP, B/W mode, scan up, shift right, booster on.
+        self.cmd_data(Command::PanelSetting, &[0x1F]).await?;
+        // 400 × 300: HRES 0x0190, VRES 0x012C.
+        self.cmd_data(Command::Resolution, &[0x01, 0x90, 0x01, 0x2C])
+            .await?;
+        // White border, default data interval.
+        self.cmd_data(Command::VcomDataInterval, &[0x97]).await
+    }
+
+    /// Stream the framebuffer as the new frame and run the OTP waveform.
+    async fn refresh(&mut self) -> Result<(), DisplayError> {
+        // Invert in place for the transfer and restore afterwards, rather
+        // than hold a second 15 kB buffer.
+        if self.inverted {
+            invert(&mut self.framebuffer);
+        }
+        let written = self
+            .bus
+            .write_ram(Command::DataStart2 as u8, &self.framebuffer)
+            .await;
+        if self.inverted {
+            invert(&mut self.framebuffer);
+        }
+        written?;
+        self.bus.command(Command::DisplayRefresh as u8).await?;
+        self.wait_busy().await
+    }
+}
+
+fn invert(framebuffer: &mut [u8]) {
+    for byte in framebuffer {
+        *byte = !*byte;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// platform::DisplayDriver implementation
+// ---------------------------------------------------------------------------
+
+impl<SPI, DC, RST, BUSY, DELAY> DisplayDriver for Uc8176<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    type DriverError = DisplayError;
+
+    fn spec(&self) -> platform::DisplayInfo {
+        // Only the B/W RAM is written.
+        super::display_info(&eink_specs::displays::WAVESHARE_4_2_V1).with_grayscale_levels(2)
+    }
+
+    /// Copy a packed 1bpp framebuffer ([`UC8176_FRAMEBUFFER_SIZE`] bytes).
+    ///
+    /// The UC8176 has no addressable RAM window, so the frame is only sent
+    /// on the next refresh.
+    async fn update_buffer(&mut self, framebuffer: &[u8]) -> Result<(), Self::DriverError> {
+        if framebuffer.len() != UC8176_FRAMEBUFFER_SIZE {
+            return Err(DisplayError::InvalidBuffer);
+        }
+        self.framebuffer.copy_from_slice(framebuffer);
+        Ok(())
+    }
+
+    /// Full refresh with the OTP waveform.
+    async fn refresh_full(&mut self) -> Result<(), Self::DriverError> {
+        self.refresh().await
+    }
+
+    /// Runs a full refresh: the OTP has no partial waveform.
+    async fn refresh_partial(&mut self) -> Result<(), Self::DriverError> {
+        self.refresh().await
+    }
+
+    /// Power off, then deep sleep.  RAM is lost; wake re-initialises.
+    async fn sleep(&mut self) -> Result<(), Self::DriverError> {
+        self.bus.command(Command::PowerOff as u8).await?;
+        self.wait_busy().await?;
+        self.cmd_data(Command::DeepSleep, &[0xA5]).await
+    }
+
+    /// Wake from deep sleep; only a hardware reset leaves it.
+    async fn wake(&mut self) -> Result<(), Self::DriverError> {
+        self.init().await
+    }
+
+    async fn wait_ready(&mut self) -> Result<(), Self::DriverError> {
+        self.wait_busy().await
+    }
+}
+
+impl<SPI, DC, RST, BUSY, DELAY> DapDisplay for Uc8176<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self) -> Result<(), Self::DriverError> {
+        Uc8176::init(self).await
+    }
+
+    fn framebuffer_size(&self) -> usize {
+        UC8176_FRAMEBUFFER_SIZE
+    }
+
+    async fn clear(&mut self, color: Color) -> Result<(), Self::DriverError> {
+        self.framebuffer.fill(match color {
+            Color::White => 0xFF,
+            Color::Black => 0x00,
+        });
+        self.refresh().await
+    }
+}
+
+// ---------------------------------------------------------------------------
+// embedded-graphics
+// ---------------------------------------------------------------------------
+
+impl<SPI, DC, RST, BUSY, DELAY> DrawTarget for Uc8176<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    type Color = BinaryColor;
+    type Error = DisplayError;
+
+    /// Write pixels into the framebuffer; `BinaryColor::On` is black.
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            controller::set_pixel(
+                &mut self.framebuffer,
+                UC8176_WIDTH,
+                UC8176_HEIGHT,
+                point,
+                color,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<SPI, DC, RST, BUSY, DELAY> OriginDimensions for Uc8176<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    fn size(&self) -> Size {
+        Size::new(UC8176_WIDTH, UC8176_HEIGHT)
+    }
+}
+
+impl<SPI, DC, RST, BUSY, DELAY> EinkDisplay for Uc8176<SPI, DC, RST, BUSY, DELAY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+    DELAY: DelayNs,
+{
+    fn refresh_mode(&self) -> RefreshMode {
+        self.refresh_mode
+    }
+
+    fn set_refresh_mode(&mut self, mode: RefreshMode) {
+        self.refresh_mode = mode;
+    }
+
+    #[allow(clippy::unused_self)] // Required by EinkDisplay; the temperature register is not read yet
+    fn temperature(&self) -> Option<i8> {
+        None
+    }
+
+    /// Every refresh is a full one, so the change shows on the next.
+    fn set_inverted(&mut self, inverted: bool) {
+        self.inverted = inverted;
+    }
+
+    fn is_inverted(&self) -> bool {
+        self.inverted
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    use embedded_hal_mock::eh1::delay::NoopDelay;
+    use embedded_hal_mock::eh1::digital::{
+        Mock as PinMock, State as PinState, Transaction as PinTransaction,
+    };
+    use embedded_hal_mock::eh1::spi::{Mock as SpiMock, Transaction as SpiTransaction};
+
+    type TestDriver = Uc8176<SpiMock<u8>, PinMock, PinMock, PinMock, NoopDelay>;
+
+    fn done(drv: TestDriver) {
+        let (mut spi, mut dc, mut rst, mut busy, _) = drv.release();
+        spi.done();
+        dc.done();
+        rst.done();
+        busy.done();
+    }
+
+    /// One `SpiDevice::write` as the mock sees it.
+    fn write(data: &[u8]) -> [SpiTransaction<u8>; 3] {
+        [
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(data.to_vec()),
+            SpiTransaction::transaction_end(),
+        ]
+    }
+
+    #[test]
+    fn test_framebuffer_geometry() {
+        assert_eq!(UC8176_FRAMEBUFFER_SIZE, 15_000);
+    }
+
+    /// `test_init_sequence` — byte-level check of reset, power-up and panel
+    /// setup; BUSY is active LOW, so one LOW read then HIGH means idle.
+    #[tokio::test]
+    async fn test_init_sequence() {
+        let steps: [(u8, &[u8]); 6] = [
+            (0x01, &[0x03, 0x00, 0x2B, 0x2B, 0xFF]),
+            (0x06, &[0x17, 0x17, 0x17]),
+            (0x04, &[]),
+            (0x00, &[0x1F]),
+            (0x61, &[0x01, 0x90, 0x01, 0x2C]),
+            (0x50, &[0x97]),
+        ];
+        let mut spi_expectations = vec![];
+        let mut dc_expectations = vec![];
+        for (cmd, data) in steps {
+            spi_expectations.extend_from_slice(&write(&[cmd]));
+            dc_expectations.push(PinTransaction::set(PinState::Low));
+            if !data.is_empty() {
+                spi_expectations.extend_from_slice(&write(data));
+                dc_expectations.push(PinTransaction::set(PinState::High));
+            }
+        }
+        let rst = PinMock::new(&[
+            PinTransaction::set(PinState::High),
+            PinTransaction::set(PinState::Low),
+            PinTransaction::set(PinState::High),
+        ]);
+        let busy = PinMock::new(&[
+            PinTransaction::get(PinState::Low),
+            PinTransaction::get(PinState::High),
+        ]);
+        let mut drv = Uc8176::new(
+            SpiMock::new(&spi_expectations),
+            PinMock::new(&dc_expectations),
+            rst,
+            busy,
+            NoopDelay,
+        );
+        drv.init().await.unwrap();
+        done(drv);
+    }
+
+    /// `test_partial_refresh_runs_full` — a partial refresh streams the
+    /// whole frame through DTM2 and triggers DRF.
+    #[tokio::test]
+    async fn test_partial_refresh_runs_full() {
+        let mut spi_expectations = vec![];
+        let mut dc_expectations = vec![PinTransaction::set(PinState::Low)];
+        spi_expectations.extend_from_slice(&write(&[0x13]));
+        for chunk in vec![0xFFu8; UC8176_FRAMEBUFFER_SIZE].chunks(256) {
+            spi_expectations.extend_from_slice(&write(chunk));
+            dc_expectations.push(PinTransaction::set(PinState::High));
+        }
+        spi_expectations.extend_from_slice(&write(&[0x12]));
+        dc_expectations.push(PinTransaction::set(PinState::Low));
+        let busy = PinMock::new(&[PinTransaction::get(PinState::High)]);
+
+        let mut drv = Uc8176::new(
+            SpiMock::new(&spi_expectations),
+            PinMock::new(&dc_expectations),
+            PinMock::new(&[]),
+            busy,
+            NoopDelay,
+        );
+        drv.refresh_partial().await.unwrap();
+        done(drv);
+    }
+
+    /// `test_night_mode_streams_inverted_frame` — DTM2 receives the
+    /// inverse; the framebuffer is unchanged afterwards.
+    #[tokio::test]
+    async fn test_night_mode_streams_inverted_frame() {
+        let mut spi_expectations = vec![];
+        let mut dc_expectations = vec![PinTransaction::set(PinState::Low)];
+        spi_expectations.extend_from_slice(&write(&[0x13]));
+        for chunk in vec![0x00u8; UC8176_FRAMEBUFFER_SIZE].chunks(256) {
+            spi_expectations.extend_from_slice(&write(chunk));
+            dc_expectations.push(PinTransaction::set(PinState::High));
+        }
+        spi_expectations.extend_from_slice(&write(&[0x12]));
+        dc_expectations.push(PinTransaction::set(PinState::Low));
+        let busy = PinMock::new(&[PinTransaction::get(PinState::High)]);
+
+        let mut drv = Uc8176::new(
+            SpiMock::new(&spi_expectations),
+            PinMock::new(&dc_expectations),
+            PinMock::new(&[]),
+            busy,
+            NoopDelay,
+        );
+        drv.set_inverted(true);
+        assert!(drv.is_inverted());
+        drv.refresh_full().await.unwrap();
+        assert!(drv.framebuffer().iter().all(|&b| b == 0xFF));
+        done(drv);
+    }
+
+    /// `test_draw_black_pixel` — rows are 50 bytes and the last pixel of a
+    /// row is bit 0.
+    #[test]
+    fn test_draw_black_pixel() {
+        let mut drv = Uc8176::new(
+            SpiMock::new(&[]),
+            PinMock::new(&[]),
+            PinMock::new(&[]),
+            PinMock::new(&[]),
+            NoopDelay,
+        );
+        Pixel(Point::new(399, 1), BinaryColor::On)
+            .draw(&mut drv)
+            .unwrap();
+        assert_eq!(drv.framebuffer()[99], 0xFE);
+        done(drv);
+    }
+}
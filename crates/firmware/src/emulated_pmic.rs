@@ -0,0 +1,458 @@
+//! BQ25895 charger and `LiPo` battery emulation
+//!
+//! [`EmulatedBq25895`] implements [`embedded_hal::i2c::I2c`] with the
+//! BQ25895's register map on top of a simple battery model, so the *real*
+//! [`platform::bq25895`] code (`bq25895_init`, `read_status`) runs on the
+//! host and the power UI sees the same [`platform::PowerMonitor`] values it
+//! would see on the device.
+//!
+//! ```text
+//! bq25895_init ─┐
+//! read_status ──┴─I2c──▶ EmulatedBq25895 ──▶ register file + BatteryModel
+//!                               ▲
+//!               plug / unplug / set_load_ma / advance(virtual time)
+//! ```
+//!
+//! # Model
+//!
+//! - **Discharge:** unplugged, the system load comes out of the cell.
+//! - **Charge:** plugged in, the system runs from VBUS and whatever input
+//!   current is left (up to ICHG) goes into the cell.  The current tapers
+//!   over the last 15 % (constant-voltage phase) and charging terminates
+//!   below [`TERMINATION_MA`].  A load above the input limit is topped up
+//!   from the cell, as in the BQ25895's supplement mode.
+//! - **Voltage:** the `LiPo` open-circuit curve from
+//!   [`platform::power::lipo_mv_from_percent`] plus an IR drop across
+//!   [`BatteryModel::resistance_mohm`].
+//!
+//! Nothing sleeps: time only passes in [`EmulatedBq25895::advance`], so a
+//! five-hour discharge runs in milliseconds.
+//!
+//! # Example
+//!
+//! ```
+//! use core::time::Duration;
+//! use firmware::emulated_pmic::{EmulatedBq25895, VbusSource};
+//! use platform::bq25895::{bq25895_init, read_status, BQ25895_I2C_ADDR};
+//! use platform::{BatteryLevel, PowerMonitor};
+//!
+//! let mut pmic = EmulatedBq25895::new(3_000, 3);
+//! bq25895_init(&mut pmic, BQ25895_I2C_ADDR).unwrap();
+//!
+//! pmic.set_load_ma(150);
+//! pmic.advance(Duration::from_secs(30 * 60));
+//! let status = read_status(&mut pmic, BQ25895_I2C_ADDR).unwrap();
+//! assert_eq!(status.battery_level(), Some(BatteryLevel::Critical));
+//!
+//! pmic.plug(VbusSource::Dcp);
+//! let status = read_status(&mut pmic, BQ25895_I2C_ADDR).unwrap();
+//! assert!(status.is_charging());
+//! ```
+
+use core::time::Duration;
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation};
+use platform::bq25895::{
+    ChargeStatus, BQ25895_I2C_ADDR, REG01_POWER_ON_CONFIG, REG02_CHARGE_CURRENT,
+    REG04_CHARGE_VOLTAGE, REG0B_STATUS, REG0E_BATTERY_VOLTAGE, REG0F_SYSTEM_VOLTAGE,
+    REG11_VBUS_VOLTAGE, REG12_CHARGE_CURRENT_ADC, REG14_DEVICE_ID, STATUS_PG_MASK, VBUS_STAT_CDP,
+    VBUS_STAT_DCP, VBUS_STAT_NO_INPUT, VBUS_STAT_SDP, VREG_4208MV,
+};
+use platform::power::lipo_mv_from_percent;
+
+/// Charging stops once the taper current falls below this (ITERM).
+pub const TERMINATION_MA: u32 = 128;
+
+/// Charging restarts once the cell drops this far below VREG (VRECHG).
+const RECHARGE_DROP_MV: u16 = 100;
+
+/// Register file size (REG00..=REG14).
+const REG_COUNT: usize = 0x15;
+
+/// REG14 at power-on: `PN` = 111 (BQ25895), `DEV_REV` = 01.
+const DEVICE_ID: u8 = 0b0011_1001;
+
+/// REG01 `CHG_CONFIG` bit.
+const CHG_CONFIG: u8 = 1 << 4;
+
+/// System voltage floor while the cell is below it (`SYS_MIN`).
+const SYS_MIN_MV: u32 = 3_500;
+
+/// VBUS from a 5 V source.
+const VBUS_MV: u32 = 5_000;
+
+/// Milliseconds per hour, for mAh ↔ mA·ms.
+const MS_PER_HOUR: u64 = 3_600_000;
+
+/// Longest step [`EmulatedBq25895::advance`] integrates at once.
+const STEP: Duration = Duration::from_secs(1);
+
+/// A single `LiPo` cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryModel {
+    /// Rated capacity.
+    pub capacity_mah: u32,
+    /// Internal resistance, for the voltage drop under load.
+    pub resistance_mohm: u32,
+    /// Remaining charge in mA·ms, so short steps do not round away.
+    charge_ma_ms: u64,
+}
+
+impl BatteryModel {
+    /// A cell of `capacity_mah` at `percent` state of charge.
+    #[must_use]
+    pub fn new(capacity_mah: u32, percent: u8) -> Self {
+        let mut battery = Self {
+            capacity_mah,
+            resistance_mohm: 150,
+            charge_ma_ms: 0,
+        };
+        battery.set_percent(percent);
+        battery
+    }
+
+    /// State of charge, 0–100.
+    #[must_use]
+    pub fn percent(&self) -> u8 {
+        let pct = self
+            .charge_ma_ms
+            .saturating_mul(100)
+            .checked_div(self.capacity_ma_ms())
+            .unwrap_or(0);
+        u8::try_from(pct.min(100)).unwrap_or(100)
+    }
+
+    /// Set the state of charge (clamped to 100).
+    pub fn set_percent(&mut self, percent: u8) {
+        self.charge_ma_ms = self
+            .capacity_ma_ms()
+            .saturating_mul(u64::from(percent.min(100)))
+            / 100;
+    }
+
+    /// Remaining charge in mAh.
+    #[must_use]
+    pub fn remaining_mah(&self) -> u32 {
+        u32::try_from(self.charge_ma_ms / MS_PER_HOUR).unwrap_or(u32::MAX)
+    }
+
+    /// Open-circuit voltage.
+    #[must_use]
+    pub fn open_circuit_mv(&self) -> u16 {
+        lipo_mv_from_percent(self.percent())
+    }
+
+    /// Terminal voltage with `current_ma` flowing in (positive) or out.
+    #[must_use]
+    pub fn terminal_mv(&self, current_ma: i32) -> u16 {
+        let drop = i64::from(current_ma).saturating_mul(i64::from(self.resistance_mohm)) / 1_000;
+        let mv = i64::from(self.open_circuit_mv()).saturating_add(drop);
+        u16::try_from(mv.max(0)).unwrap_or(u16::MAX)
+    }
+
+    /// Move `current_ma` in (positive) or out of the cell for `dt`.
+    fn integrate(&mut self, current_ma: i32, dt: Duration) {
+        let ms = u64::try_from(dt.as_millis()).unwrap_or(u64::MAX);
+        let moved = u64::from(current_ma.unsigned_abs()).saturating_mul(ms);
+        self.charge_ma_ms = if current_ma >= 0 {
+            self.charge_ma_ms
+                .saturating_add(moved)
+                .min(self.capacity_ma_ms())
+        } else {
+            self.charge_ma_ms.saturating_sub(moved)
+        };
+    }
+
+    fn capacity_ma_ms(&self) -> u64 {
+        u64::from(self.capacity_mah).saturating_mul(MS_PER_HOUR)
+    }
+}
+
+/// What is plugged into the USB-C port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VbusSource {
+    /// Computer port (SDP), 500 mA.
+    Sdp,
+    /// Charging downstream port (CDP), 1.5 A.
+    Cdp,
+    /// Wall charger (DCP), 1.5 A.
+    Dcp,
+}
+
+impl VbusSource {
+    /// Current the port can supply.
+    #[must_use]
+    pub const fn input_limit_ma(self) -> u32 {
+        match self {
+            Self::Sdp => 500,
+            Self::Cdp | Self::Dcp => 1_500,
+        }
+    }
+
+    /// REG0B `VBUS_STAT` bits reported for this port.
+    #[must_use]
+    pub const fn vbus_stat(self) -> u8 {
+        match self {
+            Self::Sdp => VBUS_STAT_SDP,
+            Self::Cdp => VBUS_STAT_CDP,
+            Self::Dcp => VBUS_STAT_DCP,
+        }
+    }
+}
+
+/// BQ25895 register map over a [`BatteryModel`].  See the module docs.
+#[derive(Debug, Clone)]
+pub struct EmulatedBq25895 {
+    battery: BatteryModel,
+    source: Option<VbusSource>,
+    load_ma: u32,
+    regs: [u8; REG_COUNT],
+    pointer: u8,
+    /// Positive into the cell.
+    battery_current_ma: i32,
+    charge: ChargeStatus,
+    elapsed: Duration,
+}
+
+impl EmulatedBq25895 {
+    /// Unplugged charger with power-on register defaults, on a
+    /// `capacity_mah` cell at `percent`.
+    #[must_use]
+    pub fn new(capacity_mah: u32, percent: u8) -> Self {
+        let mut pmic = Self {
+            battery: BatteryModel::new(capacity_mah, percent),
+            source: None,
+            load_ma: 0,
+            regs: [0; REG_COUNT],
+            pointer: 0,
+            battery_current_ma: 0,
+            charge: ChargeStatus::NotCharging,
+            elapsed: Duration::ZERO,
+        };
+        // POR: charging enabled, ICHG 2048 mA, VREG 4.208 V.
+        pmic.write_reg(REG01_POWER_ON_CONFIG, CHG_CONFIG);
+        pmic.write_reg(REG02_CHARGE_CURRENT, 32);
+        pmic.write_reg(REG04_CHARGE_VOLTAGE, VREG_4208MV);
+        if let Some(id) = pmic.regs.get_mut(usize::from(REG14_DEVICE_ID)) {
+            *id = DEVICE_ID;
+        }
+        pmic.update();
+        pmic
+    }
+
+    /// Connect a USB power source.
+    pub fn plug(&mut self, source: VbusSource) {
+        self.source = Some(source);
+        self.update();
+    }
+
+    /// Disconnect USB power.
+    pub fn unplug(&mut self) {
+        self.source = None;
+        self.update();
+    }
+
+    /// Set the system load (display, DAC, amp, MCU).
+    pub fn set_load_ma(&mut self, load_ma: u32) {
+        self.load_ma = load_ma;
+        self.update();
+    }
+
+    /// Let `dt` of virtual time pass.
+    pub fn advance(&mut self, dt: Duration) {
+        let mut left = dt;
+        while !left.is_zero() {
+            let step = left.min(STEP);
+            self.battery.integrate(self.battery_current_ma, step);
+            self.elapsed = self.elapsed.saturating_add(step);
+            left = left.saturating_sub(step);
+            self.update();
+        }
+    }
+
+    /// Virtual time since creation.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The cell.
+    #[must_use]
+    pub fn battery(&self) -> &BatteryModel {
+        &self.battery
+    }
+
+    /// The cell, for jumping to a state of charge.
+    pub fn battery_mut(&mut self) -> &mut BatteryModel {
+        &mut self.battery
+    }
+
+    /// Charging phase, as reported in REG0B.
+    #[must_use]
+    pub fn charge_status(&self) -> ChargeStatus {
+        self.charge
+    }
+
+    /// Current into (positive) or out of the cell.
+    #[must_use]
+    pub fn battery_current_ma(&self) -> i32 {
+        self.battery_current_ma
+    }
+
+    /// Terminal voltage of the cell.
+    #[must_use]
+    pub fn battery_mv(&self) -> u16 {
+        self.battery.terminal_mv(self.battery_current_ma)
+    }
+
+    /// Stored value of a configuration register.
+    fn reg(&self, reg: u8) -> u8 {
+        self.regs.get(usize::from(reg)).copied().unwrap_or(0)
+    }
+
+    fn charging_enabled(&self) -> bool {
+        self.reg(REG01_POWER_ON_CONFIG) & CHG_CONFIG != 0
+    }
+
+    /// ICHG as programmed (64 mA/LSB).
+    fn ichg_ma(&self) -> u32 {
+        u32::from(self.reg(REG02_CHARGE_CURRENT) & 0x7F).saturating_mul(64)
+    }
+
+    /// VREG as programmed (3840 mV + 16 mV/LSB).
+    fn vreg_mv(&self) -> u16 {
+        let field = u16::from(self.reg(REG04_CHARGE_VOLTAGE) >> 2);
+        3_840_u16.saturating_add(field.saturating_mul(16))
+    }
+
+    /// Recompute the charge phase and cell current after any change.
+    fn update(&mut self) {
+        let Some(source) = self.source else {
+            self.charge = ChargeStatus::NotCharging;
+            self.battery_current_ma = negative(self.load_ma);
+            return;
+        };
+        let limit = source.input_limit_ma();
+        if self.load_ma > limit {
+            // Supplement mode: the cell covers what the port cannot.
+            self.charge = ChargeStatus::NotCharging;
+            self.battery_current_ma = negative(self.load_ma.saturating_sub(limit));
+            return;
+        }
+
+        if !self.charging_enabled() {
+            self.charge = ChargeStatus::NotCharging;
+            self.battery_current_ma = 0;
+            return;
+        }
+        let recharge = self.vreg_mv().saturating_sub(RECHARGE_DROP_MV);
+        if self.charge == ChargeStatus::Done && self.battery.open_circuit_mv() > recharge {
+            self.battery_current_ma = 0;
+            return;
+        }
+
+        let mut current = self.ichg_ma().min(limit.saturating_sub(self.load_ma));
+        let percent = u32::from(self.battery.percent());
+        if percent == 0 {
+            // Pre-charge at a fraction of ICHG until the cell recovers.
+            self.charge = ChargeStatus::PreCharge;
+            current /= 8;
+        } else {
+            self.charge = ChargeStatus::FastCharging;
+            if percent > 85 {
+                // Constant voltage: taper linearly to zero at 100 %.
+                current = current.saturating_mul(100_u32.saturating_sub(percent)) / 15;
+            }
+            if current < TERMINATION_MA {
+                self.charge = ChargeStatus::Done;
+                current = 0;
+            }
+        }
+        self.battery_current_ma = i32::try_from(current).unwrap_or(i32::MAX);
+    }
+
+    /// Value of a register as the host would read it.
+    fn read_reg(&self, reg: u8) -> u8 {
+        let battery_mv = u32::from(self.battery_mv());
+        match reg {
+            REG0B_STATUS => {
+                let (vbus, pg) = match self.source {
+                    Some(source) => (source.vbus_stat(), STATUS_PG_MASK),
+                    None => (VBUS_STAT_NO_INPUT, 0),
+                };
+                vbus | self.charge.status_bits() | pg
+            }
+            REG0E_BATTERY_VOLTAGE => adc(battery_mv, 2_304, 20),
+            REG0F_SYSTEM_VOLTAGE => adc(battery_mv.max(SYS_MIN_MV), 2_304, 20),
+            REG11_VBUS_VOLTAGE => match self.source {
+                // VBUS_GD in bit 7.
+                Some(_) => 0x80 | adc(VBUS_MV, 2_600, 100),
+                None => 0,
+            },
+            REG12_CHARGE_CURRENT_ADC => {
+                let charging = u32::try_from(self.battery_current_ma.max(0)).unwrap_or(0);
+                adc(charging, 0, 50)
+            }
+            _ => self.reg(reg),
+        }
+    }
+
+    fn write_reg(&mut self, reg: u8, value: u8) {
+        // Status, fault and ADC registers are read-only.
+        let writable = reg <= 0x0A || reg == 0x0D;
+        if let Some(slot) = self.regs.get_mut(usize::from(reg)).filter(|_| writable) {
+            *slot = value;
+        }
+    }
+}
+
+/// `-(ma)` as a signed current.
+fn negative(ma: u32) -> i32 {
+    0_i32.saturating_sub(i32::try_from(ma).unwrap_or(i32::MAX))
+}
+
+/// 7-bit ADC code for `value = offset + code × lsb`, clamped.
+fn adc(value: u32, offset: u32, lsb: u32) -> u8 {
+    let code = value.saturating_sub(offset).checked_div(lsb).unwrap_or(0);
+    u8::try_from(code.min(0x7F)).unwrap_or(0x7F)
+}
+
+impl ErrorType for EmulatedBq25895 {
+    type Error = ErrorKind;
+}
+
+impl I2c for EmulatedBq25895 {
+    /// A write sets the register pointer from its first byte and writes
+    /// the rest; reads continue from the pointer.  Both auto-increment.
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if address != BQ25895_I2C_ADDR {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        for op in operations {
+            match op {
+                Operation::Write(bytes) => {
+                    let Some((&reg, data)) = bytes.split_first() else {
+                        continue;
+                    };
+                    self.pointer = reg;
+                    for &value in data {
+                        self.write_reg(self.pointer, value);
+                        self.pointer = self.pointer.wrapping_add(1);
+                    }
+                    self.update();
+                }
+                Operation::Read(buf) => {
+                    for byte in buf.iter_mut() {
+                        *byte = self.read_reg(self.pointer);
+                        self.pointer = self.pointer.wrapping_add(1);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
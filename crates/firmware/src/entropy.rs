@@ -0,0 +1,110 @@
+//! PRNG seeding for the shared [`util::Rng`] service.
+//!
+//! On the device the seed comes from the STM32H7 true-RNG peripheral
+//! (clocked from HSI48, which `boot::build_embassy_config` already enables
+//! for SDMMC1).  On the desktop emulator it comes from the wall clock.
+//! Tests never call into this module — they use
+//! [`util::Rng::seed_from_u64`] with a literal so runs are reproducible.
+//!
+//! Seed once at boot, then split the seed into [`RngStreams`] and hand each
+//! subsystem (shuffle, dithering, screensaver) its own stream, so one
+//! subsystem drawing more numbers never shifts another's sequence.
+
+use util::Rng;
+
+/// Number of 32-bit hardware entropy words folded into the seed.
+pub const ENTROPY_WORDS: usize = 4;
+
+/// One forked generator per randomised subsystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RngStreams {
+    /// Play-queue shuffle order.
+    pub shuffle: Rng,
+    /// Dither noise for grey art and fills.
+    pub dither: Rng,
+    /// Screensaver and attract-loop placement.
+    pub screensaver: Rng,
+}
+
+impl RngStreams {
+    /// Fork every stream from the boot seed.
+    #[must_use]
+    pub fn split(mut seed: Rng) -> Self {
+        Self {
+            shuffle: seed.fork(),
+            dither: seed.fork(),
+            screensaver: seed.fork(),
+        }
+    }
+}
+
+/// Pack raw entropy bytes into little-endian words.
+#[must_use]
+pub fn words_from_bytes(bytes: &[u8; ENTROPY_WORDS * 4]) -> [u32; ENTROPY_WORDS] {
+    let mut words = [0u32; ENTROPY_WORDS];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        let mut le = [0u8; 4];
+        le.copy_from_slice(chunk);
+        *word = u32::from_le_bytes(le);
+    }
+    words
+}
+
+/// Seed the PRNG from the RNG peripheral.
+///
+/// If the peripheral reports a seed or clock error (e.g. HSI48 not ready),
+/// the timer tick count is used instead so boot never stalls on entropy;
+/// the result is then predictable but still differs between boots.
+#[cfg(feature = "hardware")]
+pub async fn seed_from_hardware(
+    rng: &mut embassy_stm32::rng::Rng<'_, embassy_stm32::peripherals::RNG>,
+) -> Rng {
+    let mut bytes = [0u8; ENTROPY_WORDS * 4];
+    match rng.async_fill_bytes(&mut bytes).await {
+        Ok(()) => Rng::from_entropy(&words_from_bytes(&bytes)),
+        Err(_) => {
+            defmt::warn!("RNG peripheral failed; seeding PRNG from timer ticks");
+            Rng::seed_from_u64(embassy_time::Instant::now().as_ticks())
+        }
+    }
+}
+
+/// Seed the PRNG from the host clock (emulator builds).
+#[cfg(feature = "std")]
+#[must_use]
+pub fn seed_from_host_clock() -> Rng {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    // SAFETY (cast): only the low 64 bits carry entropy worth keeping.
+    #[allow(clippy::cast_possible_truncation)]
+    Rng::seed_from_u64(nanos as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_from_bytes_little_endian() {
+        let bytes = [1, 0, 0, 0, 0, 0, 0, 0x80, 0, 0, 0, 0, 0, 0, 0, 0];
+        let words = words_from_bytes(&bytes);
+        assert_eq!(words, [0x0000_0001, 0x8000_0000, 0, 0]);
+    }
+
+    #[test]
+    fn test_distinct_entropy_gives_distinct_streams() {
+        let a = Rng::from_entropy(&words_from_bytes(&[0xAA; ENTROPY_WORDS * 4]));
+        let b = Rng::from_entropy(&words_from_bytes(&[0x55; ENTROPY_WORDS * 4]));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_streams_are_distinct_and_reproducible() {
+        let streams = RngStreams::split(Rng::seed_from_u64(42));
+        assert_ne!(streams.shuffle, streams.dither);
+        assert_ne!(streams.dither, streams.screensaver);
+        assert_ne!(streams.shuffle, streams.screensaver);
+        assert_eq!(streams, RngStreams::split(Rng::seed_from_u64(42)));
+    }
+}
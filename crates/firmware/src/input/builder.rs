@@ -27,6 +27,11 @@
 //! # }
 //! ```
 //!
+//! # Usage (GPIO expander)
+//!
+//! Buttons on an I2C expander are mapped pin by pin and built into an
+//! [`ExpanderInput`]; see [`super::expander`].
+//!
 //! # Usage (emulator)
 //!
 //! ```no_run
@@ -47,8 +52,12 @@
 //! # }
 //! ```
 
+use embedded_hal_async::delay::DelayNs;
+use platform::gpio_expander::GpioExpander;
 use platform::Button;
 
+use super::expander::{ExpanderInput, EXPANDER_PINS};
+
 // ---------------------------------------------------------------------------
 // EmulatedAxis — what physical scroll/encoder axis maps to
 // ---------------------------------------------------------------------------
@@ -110,6 +119,8 @@ enum InputKind {
     Rotary,
     /// Momentary push button.
     Button(Button),
+    /// Buttons on an I2C GPIO expander.
+    Expander,
 }
 
 // ---------------------------------------------------------------------------
@@ -118,7 +129,8 @@ enum InputKind {
 
 /// Fluent builder for input sources.
 ///
-/// Call [`InputBuilder::rotary()`] or [`InputBuilder::button()`] to start,
+/// Call [`InputBuilder::rotary()`], [`InputBuilder::button()`] or
+/// [`InputBuilder::expander()`] to start,
 /// then chain configuration methods, and finally call the appropriate
 /// `build_*` method for your platform.
 ///
@@ -128,6 +140,7 @@ enum InputKind {
 ///   only compiled with `keyboard-input` feature.
 /// - `build_hardware()`: hardware-specific wiring lives in
 ///   `input::hardware::spawn_input_task`.
+/// - `build_expander()`: always available (generic over the expander).
 #[allow(clippy::module_name_repetitions)] // Builder type named after its module; builder pattern convention
 pub struct InputBuilder {
     // `kind` is read only in `build_emulated` (keyboard-input feature).
@@ -136,6 +149,10 @@ pub struct InputBuilder {
     kind: InputKind,
     debounce_ms: u32,
 
+    // Expander pin map (expander builders only)
+    expander_pins: heapless::Vec<(u8, Button), EXPANDER_PINS>,
+    expander_active_low: bool,
+
     // Emulator configuration (compiled only with keyboard-input feature)
     #[cfg(feature = "keyboard-input")]
     emulated_axis: Option<EmulatedAxis>,
@@ -158,6 +175,9 @@ impl InputBuilder {
             kind: InputKind::Rotary,
             debounce_ms: 20,
 
+            expander_pins: heapless::Vec::new(),
+            expander_active_low: true,
+
             #[cfg(feature = "keyboard-input")]
             emulated_axis: None,
 
@@ -175,6 +195,9 @@ impl InputBuilder {
             kind: InputKind::Button(btn),
             debounce_ms: 50,
 
+            expander_pins: heapless::Vec::new(),
+            expander_active_low: true,
+
             #[cfg(feature = "keyboard-input")]
             emulated_axis: None,
 
@@ -183,6 +206,19 @@ impl InputBuilder {
         }
     }
 
+    /// Start building buttons on an I2C GPIO expander.
+    ///
+    /// Map pins with [`expander_pin`](Self::expander_pin).  Default
+    /// debounce: 50 ms; default polarity: active-low (pull-ups, switch to
+    /// ground).
+    #[must_use]
+    pub fn expander() -> Self {
+        Self {
+            kind: InputKind::Expander,
+            ..Self::button(Button::Play)
+        }
+    }
+
     // -----------------------------------------------------------------------
     // Common configuration
     // -----------------------------------------------------------------------
@@ -202,6 +238,52 @@ impl InputBuilder {
         self.debounce_ms
     }
 
+    // -----------------------------------------------------------------------
+    // Expander configuration
+    // -----------------------------------------------------------------------
+
+    /// Map expander input `pin` (0–15) to `button`.
+    ///
+    /// Mapping a pin again replaces its button; pins above 15 are ignored.
+    /// Only meaningful on a builder created with [`InputBuilder::expander()`].
+    #[must_use]
+    pub fn expander_pin(mut self, pin: u8, button: Button) -> Self {
+        if usize::from(pin) >= EXPANDER_PINS {
+            return self;
+        }
+        self.expander_pins.retain(|&(p, _)| p != pin);
+        // Cannot fail: at most one entry per pin, and pins are < capacity.
+        let _ = self.expander_pins.push((pin, button));
+        self
+    }
+
+    /// Treat a high expander input as pressed (pull-downs, switch to VCC).
+    #[must_use]
+    pub fn active_high(mut self) -> Self {
+        self.expander_active_low = false;
+        self
+    }
+
+    /// Build the expander input driver.
+    ///
+    /// `delay` times the debounce; `clock` timestamps events in
+    /// milliseconds (Embassy `Instant` on hardware).
+    pub fn build_expander<E: GpioExpander, D: DelayNs>(
+        self,
+        expander: E,
+        delay: D,
+        clock: fn() -> u64,
+    ) -> ExpanderInput<E, D> {
+        ExpanderInput::new(
+            expander,
+            delay,
+            self.expander_pins,
+            self.expander_active_low,
+            self.debounce_ms,
+            clock,
+        )
+    }
+
     // -----------------------------------------------------------------------
     // Emulator configuration — only compiled with keyboard-input feature
     // -----------------------------------------------------------------------
@@ -333,6 +415,12 @@ impl InputBuilder {
                     key_desc
                 );
             }
+            InputKind::Expander => {
+                eprintln!(
+                    "[InputBuilder] GPIO expander ({} pins) → global key map",
+                    self.expander_pins.len()
+                );
+            }
         }
 
         // Attach (or re-attach) the input queue to the emulator window.
@@ -369,6 +457,9 @@ impl InputBuilder {
 /// | Back            | PD4     | `btn_back`  |
 /// | Select          | PD5     | `btn_select`|
 ///
+/// `heartbeat` is the input task's watchdog handle, from
+/// `HEARTBEATS.register(TaskId::Input)` (see [`crate::watchdog`]).
+///
 /// # Example
 ///
 /// ```no_run
@@ -406,8 +497,10 @@ pub fn spawn_hardware_input(
     btn_menu: embassy_stm32::exti::ExtiInput<'static, embassy_stm32::gpio::AnyPin>,
     btn_back: embassy_stm32::exti::ExtiInput<'static, embassy_stm32::gpio::AnyPin>,
     btn_select: embassy_stm32::exti::ExtiInput<'static, embassy_stm32::gpio::AnyPin>,
+    heartbeat: crate::watchdog::Heartbeat,
 ) {
     super::hardware::spawn_input_task(
         spawner, enc_clk, enc_dt, btn_play, btn_next, btn_prev, btn_menu, btn_back, btn_select,
+        heartbeat,
     );
 }
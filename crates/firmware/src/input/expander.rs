@@ -0,0 +1,241 @@
+//! Buttons on an I2C GPIO expander.
+//!
+//! [`ExpanderInput`] turns any [`GpioExpander`] into a
+//! [`platform::InputDevice`]: it waits for the expander's interrupt line,
+//! lets the contacts settle for the debounce time, reads the input port and
+//! emits `ButtonPress` / `ButtonRelease` for every mapped pin that changed.
+//! Application code sees the same events as from the on-chip
+//! [`HardwareInput`](super::hardware) driver, so moving the button matrix
+//! off-chip only changes how the input source is built.
+//!
+//! Build it with [`InputBuilder::expander`](super::InputBuilder::expander):
+//!
+//! ```ignore
+//! use firmware::input::InputBuilder;
+//! use platform::gpio_expander::{RegisterExpander, PCA9555_INPUT_PORT0};
+//! use platform::Button;
+//!
+//! let expander = RegisterExpander::new(i2c, int_pin, 0x20, PCA9555_INPUT_PORT0);
+//! let input = InputBuilder::expander()
+//!     .expander_pin(0, Button::Play)
+//!     .expander_pin(1, Button::Next)
+//!     .build_expander(expander, embassy_time::Delay, || {
+//!         embassy_time::Instant::now().as_millis()
+//!     });
+//! ```
+
+use embedded_hal_async::delay::DelayNs;
+use heapless::{Deque, Vec};
+use platform::gpio_expander::GpioExpander;
+use platform::{Button, InputDevice, InputEvent, TimestampedEvent, TimestampedInput};
+
+/// Pins one expander can map (a 16-bit input port).
+pub const EXPANDER_PINS: usize = 16;
+
+/// Events buffered between reads; one read can change every mapped pin.
+const PENDING_DEPTH: usize = EXPANDER_PINS;
+
+/// Button input driver over a [`GpioExpander`].
+///
+/// [`poll_event`](InputDevice::poll_event) only returns events already
+/// decoded by a previous wait: reading the expander is an async bus
+/// transfer, so polling never touches the bus.
+pub struct ExpanderInput<E, D> {
+    expander: E,
+    delay: D,
+    buttons: Vec<(u8, Button), EXPANDER_PINS>,
+    active_low: bool,
+    debounce_ms: u32,
+    clock: fn() -> u64,
+    /// Bit `n` set = the button on pin `n` is held.
+    pressed: u16,
+    pending: Deque<TimestampedEvent, PENDING_DEPTH>,
+    errors: u32,
+}
+
+impl<E: GpioExpander, D: DelayNs> ExpanderInput<E, D> {
+    /// Map `buttons` (expander pin, button) on `expander`.
+    ///
+    /// `clock` timestamps events in milliseconds on the display's timeline
+    /// (Embassy `Instant` on hardware).  Prefer
+    /// [`InputBuilder::expander`](super::InputBuilder::expander).
+    pub fn new(
+        expander: E,
+        delay: D,
+        buttons: Vec<(u8, Button), EXPANDER_PINS>,
+        active_low: bool,
+        debounce_ms: u32,
+        clock: fn() -> u64,
+    ) -> Self {
+        Self {
+            expander,
+            delay,
+            buttons,
+            active_low,
+            debounce_ms,
+            clock,
+            pressed: 0,
+            pending: Deque::new(),
+            errors: 0,
+        }
+    }
+
+    /// Bus or interrupt errors seen so far; each one skips a scan.
+    pub fn errors(&self) -> u32 {
+        self.errors
+    }
+
+    /// Release the expander and delay.
+    pub fn release(self) -> (E, D) {
+        (self.expander, self.delay)
+    }
+
+    /// Wait for the interrupt, debounce, read and queue any changes.
+    async fn scan(&mut self) {
+        if self.expander.wait_for_interrupt().await.is_err() {
+            self.errors = self.errors.saturating_add(1);
+        }
+        // Also paces retries after an interrupt-pin error.
+        self.delay.delay_ms(self.debounce_ms).await;
+        match self.expander.read_inputs().await {
+            Ok(levels) => self.apply(levels),
+            Err(_) => self.errors = self.errors.saturating_add(1),
+        }
+    }
+
+    /// Queue press/release events for mapped pins whose level changed.
+    fn apply(&mut self, levels: u16) {
+        let active = if self.active_low { !levels } else { levels };
+        let at_ms = (self.clock)();
+        for &(pin, button) in &self.buttons {
+            let bit = 1u16.wrapping_shl(u32::from(pin));
+            let now = active & bit != 0;
+            if now == (self.pressed & bit != 0) {
+                continue;
+            }
+            self.pressed ^= bit;
+            let event = if now {
+                InputEvent::ButtonPress(button)
+            } else {
+                InputEvent::ButtonRelease(button)
+            };
+            // Cannot fill up: at most one event per mapped pin per scan, and
+            // the queue is drained before the next scan.
+            let _ = self.pending.push_back(TimestampedEvent::new(event, at_ms));
+        }
+    }
+}
+
+impl<E: GpioExpander, D: DelayNs> InputDevice for ExpanderInput<E, D> {
+    async fn wait_for_event(&mut self) -> InputEvent {
+        self.wait_for_timestamped_event().await.event
+    }
+
+    fn poll_event(&mut self) -> Option<InputEvent> {
+        self.poll_timestamped_event().map(|e| e.event)
+    }
+}
+
+impl<E: GpioExpander, D: DelayNs> TimestampedInput for ExpanderInput<E, D> {
+    async fn wait_for_timestamped_event(&mut self) -> TimestampedEvent {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return event;
+            }
+            self.scan().await;
+        }
+    }
+
+    fn poll_timestamped_event(&mut self) -> Option<TimestampedEvent> {
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::InputBuilder;
+
+    /// Expander replaying a script of input-port reads.
+    struct Scripted {
+        reads: std::vec::Vec<Result<u16, ()>>,
+    }
+
+    impl GpioExpander for Scripted {
+        type Error = ();
+
+        async fn wait_for_interrupt(&mut self) -> Result<(), ()> {
+            Ok(())
+        }
+
+        async fn read_inputs(&mut self) -> Result<u16, ()> {
+            if self.reads.is_empty() {
+                Ok(0xFFFF)
+            } else {
+                self.reads.remove(0)
+            }
+        }
+    }
+
+    /// Delay that only counts milliseconds.
+    struct Counted<'a>(&'a mut u32);
+
+    impl DelayNs for Counted<'_> {
+        async fn delay_ns(&mut self, ns: u32) {
+            *self.0 = self.0.saturating_add(ns / 1_000_000);
+        }
+    }
+
+    fn input(
+        reads: std::vec::Vec<Result<u16, ()>>,
+        delayed: &mut u32,
+    ) -> ExpanderInput<Scripted, Counted<'_>> {
+        InputBuilder::expander()
+            .expander_pin(0, Button::Play)
+            .expander_pin(3, Button::Next)
+            .debounce_ms(25)
+            .build_expander(Scripted { reads }, Counted(delayed), || 42)
+    }
+
+    #[tokio::test]
+    async fn active_low_presses_and_releases_mapped_pins() {
+        let mut delayed = 0;
+        let mut input = input(
+            vec![
+                Ok(0xFFFE), // pin 0 low: Play pressed
+                Ok(0xFFF6), // pin 3 low too: Next pressed
+                Ok(0xFFFF), // both released
+            ],
+            &mut delayed,
+        );
+
+        let mut events = std::vec::Vec::new();
+        for _ in 0..4 {
+            events.push(input.wait_for_timestamped_event().await);
+        }
+        assert!(input.poll_event().is_none());
+        assert_eq!(
+            events.iter().map(|e| e.event).collect::<std::vec::Vec<_>>(),
+            [
+                InputEvent::ButtonPress(Button::Play),
+                InputEvent::ButtonPress(Button::Next),
+                InputEvent::ButtonRelease(Button::Play),
+                InputEvent::ButtonRelease(Button::Next),
+            ]
+        );
+        assert!(events.iter().all(|e| e.at_ms == 42));
+        drop(input);
+        assert_eq!(delayed, 75, "one debounce per scan");
+    }
+
+    #[tokio::test]
+    async fn unmapped_pins_and_read_errors_are_ignored() {
+        let mut delayed = 0;
+        let mut input = input(vec![Ok(0x7FFF), Err(()), Ok(0x7FFE)], &mut delayed);
+        assert_eq!(
+            input.wait_for_event().await,
+            InputEvent::ButtonPress(Button::Play)
+        );
+        assert_eq!(input.errors(), 1);
+    }
+}
@@ -34,14 +34,16 @@
 //! begin.
 
 use embassy_executor::Spawner;
-use embassy_futures::join::{join, join5};
+use embassy_futures::join::{join, join3, join5};
 use embassy_stm32::exti::ExtiInput;
 use embassy_stm32::gpio::{AnyPin, Input};
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::channel::{Channel, Receiver, Sender};
-use embassy_time::Timer;
+use embassy_time::{Instant, Timer};
 
-use platform::{Button, InputDevice, InputEvent};
+use platform::{Button, InputDevice, InputEvent, TimestampedEvent, TimestampedInput};
+
+use crate::watchdog::{tick_forever, Heartbeat};
 
 // ---------------------------------------------------------------------------
 // Channel capacity
@@ -78,7 +80,11 @@ pub(crate) const CHANNEL_DEPTH: usize = 16;
 //                Task -> samples GPIO state, pushes to Channel<NoopRawMutex, ...>
 //   This eliminates PRIMASK from the receive() path entirely.
 /// Global event channel shared between the GPIO task and the application.
-pub static INPUT_CHANNEL: Channel<CriticalSectionRawMutex, InputEvent, CHANNEL_DEPTH> =
+///
+/// Events are stamped with the Embassy tick when the GPIO task sends them,
+/// so latency is measured from the debounced edge rather than from when the
+/// UI task reads the channel.
+pub static INPUT_CHANNEL: Channel<CriticalSectionRawMutex, TimestampedEvent, CHANNEL_DEPTH> =
     Channel::new();
 
 // ---------------------------------------------------------------------------
@@ -91,16 +97,18 @@ pub static INPUT_CHANNEL: Channel<CriticalSectionRawMutex, InputEvent, CHANNEL_D
 /// # Usage
 /// ```no_run
 /// use firmware::input::hardware::{HardwareInput, spawn_input_task};
+/// use firmware::watchdog::{TaskId, HEARTBEATS};
 ///
 /// // In your main / init:
 /// spawn_input_task(spawner, encoder_clk, encoder_dt, btn_play, btn_next,
-///                  btn_prev, btn_menu, btn_back, btn_select);
+///                  btn_prev, btn_menu, btn_back, btn_select,
+///                  HEARTBEATS.register(TaskId::Input));
 ///
 /// let mut input = HardwareInput::new();
 /// // Then pass `input` to your application task.
 /// ```
 pub struct HardwareInput {
-    rx: Receiver<'static, CriticalSectionRawMutex, InputEvent, CHANNEL_DEPTH>,
+    rx: Receiver<'static, CriticalSectionRawMutex, TimestampedEvent, CHANNEL_DEPTH>,
 }
 
 impl HardwareInput {
@@ -120,10 +128,20 @@ impl Default for HardwareInput {
 
 impl InputDevice for HardwareInput {
     async fn wait_for_event(&mut self) -> InputEvent {
-        self.rx.receive().await
+        self.wait_for_timestamped_event().await.event
     }
 
     fn poll_event(&mut self) -> Option<InputEvent> {
+        self.poll_timestamped_event().map(|e| e.event)
+    }
+}
+
+impl TimestampedInput for HardwareInput {
+    async fn wait_for_timestamped_event(&mut self) -> TimestampedEvent {
+        self.rx.receive().await
+    }
+
+    fn poll_timestamped_event(&mut self) -> Option<TimestampedEvent> {
         self.rx.try_receive().ok() // ok: TryReceiveError::Empty maps to None — correct poll_event semantics; channel never closes
     }
 }
@@ -134,6 +152,8 @@ impl InputDevice for HardwareInput {
 
 /// Attempt to send an [`InputEvent`] without blocking.
 ///
+/// The event is stamped with the current Embassy tick ([`TimestampedEvent`]).
+///
 /// Returns `true` if the event was enqueued, `false` if the channel was full
 /// and the event was dropped.  Callers may log a warning on `false` using
 /// `defmt::warn!` when the `defmt` feature is active.
@@ -143,10 +163,10 @@ impl InputDevice for HardwareInput {
 /// entire input task, preventing further encoder / button interrupts from being
 /// processed.
 pub(crate) fn try_send_event(
-    tx: &Sender<'static, CriticalSectionRawMutex, InputEvent, CHANNEL_DEPTH>,
+    tx: &Sender<'static, CriticalSectionRawMutex, TimestampedEvent, CHANNEL_DEPTH>,
     event: InputEvent,
 ) -> bool {
-    match tx.try_send(event) {
+    match tx.try_send(TimestampedEvent::new(event, Instant::now().as_millis())) {
         Ok(()) => true,
         Err(_) => {
             // Channel full — input event dropped. This prevents the input task
@@ -170,6 +190,7 @@ pub(crate) fn try_send_event(
 /// - `enc_clk` — Encoder CLK (A) pin with EXTI capability (PA8).
 /// - `enc_dt` — Encoder DT (B) pin (PA3, input only).
 /// - `btn_play` through `btn_select` — Button pins with EXTI capability (PA0–PA2, PD3–PD5).
+/// - `heartbeat` — [`TaskId::Input`](crate::watchdog::TaskId::Input) watchdog handle.
 pub fn spawn_input_task(
     spawner: &Spawner,
     enc_clk: ExtiInput<'static, AnyPin>,
@@ -180,10 +201,12 @@ pub fn spawn_input_task(
     btn_menu: ExtiInput<'static, AnyPin>,
     btn_back: ExtiInput<'static, AnyPin>,
     btn_select: ExtiInput<'static, AnyPin>,
+    heartbeat: Heartbeat,
 ) {
     spawner
         .spawn(input_task(
             enc_clk, enc_dt, btn_play, btn_next, btn_prev, btn_menu, btn_back, btn_select,
+            heartbeat,
         ))
         .expect("failed to spawn input_task");
 }
@@ -199,10 +222,12 @@ async fn input_task(
     mut btn_menu: ExtiInput<'static, AnyPin>,
     mut btn_back: ExtiInput<'static, AnyPin>,
     mut btn_select: ExtiInput<'static, AnyPin>,
+    heartbeat: Heartbeat,
 ) {
     let tx = INPUT_CHANNEL.sender();
 
-    // embassy-futures 0.1 tops out at join5; nest two join5 + join for 7 tasks.
+    // embassy-futures 0.1 tops out at join5; nest two join5 + join for the
+    // 7 loops plus the heartbeat ticker.
     join(
         join5(
             encoder_loop(&mut enc_clk, &enc_dt, tx),
@@ -211,9 +236,10 @@ async fn input_task(
             button_loop(&mut btn_prev, Button::Previous, tx),
             button_loop(&mut btn_menu, Button::Menu, tx),
         ),
-        join(
+        join3(
             button_loop(&mut btn_back, Button::Back, tx),
             button_loop(&mut btn_select, Button::Select, tx),
+            tick_forever(heartbeat),
         ),
     )
     .await;
@@ -230,7 +256,7 @@ async fn input_task(
 async fn encoder_loop(
     clk: &mut ExtiInput<'static, AnyPin>,
     dt: &Input<'static, AnyPin>,
-    tx: Sender<'static, CriticalSectionRawMutex, InputEvent, CHANNEL_DEPTH>,
+    tx: Sender<'static, CriticalSectionRawMutex, TimestampedEvent, CHANNEL_DEPTH>,
 ) {
     loop {
         clk.wait_for_rising_edge().await;
@@ -256,7 +282,7 @@ async fn encoder_loop(
 async fn button_loop(
     pin: &mut ExtiInput<'static, AnyPin>,
     btn: Button,
-    tx: Sender<'static, CriticalSectionRawMutex, InputEvent, CHANNEL_DEPTH>,
+    tx: Sender<'static, CriticalSectionRawMutex, TimestampedEvent, CHANNEL_DEPTH>,
 ) {
     loop {
         pin.wait_for_falling_edge().await;
@@ -307,7 +333,7 @@ mod tests {
         // `try_send_event` is a synchronous function (no .await) — confirm via
         // the type system that it returns bool, not a Future.
         let _: fn(
-            &Sender<'static, CriticalSectionRawMutex, InputEvent, CHANNEL_DEPTH>,
+            &Sender<'static, CriticalSectionRawMutex, TimestampedEvent, CHANNEL_DEPTH>,
             InputEvent,
         ) -> bool = try_send_event;
     }
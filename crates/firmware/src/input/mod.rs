@@ -7,6 +7,9 @@
 //! | `keyboard-input` | [`EmulatorInput`]   | winit keyboard   |
 //! | `hardware`       | [`HardwareInput`]   | GPIO / encoder   |
 //!
+//! Buttons on an I2C GPIO expander use [`ExpanderInput`] instead, on any
+//! target: it is generic over [`platform::gpio_expander::GpioExpander`].
+//!
 //! Both implement [`platform::InputDevice`], so application code is identical
 //! across targets.
 //!
@@ -28,7 +31,7 @@
 //! ```
 
 // Re-export the trait and event types so callers only need `firmware::input`.
-pub use platform::{Button, InputDevice, InputEvent};
+pub use platform::{Button, InputDevice, InputEvent, TimestampedEvent, TimestampedInput};
 
 /// Desktop emulator driver (keyboard + scroll wheel).
 #[cfg(feature = "keyboard-input")]
@@ -40,6 +43,10 @@ pub mod hardware;
 #[cfg(feature = "hardware")]
 pub use hardware::HardwareInput;
 
+/// Buttons behind an I2C GPIO expander.
+pub mod expander;
+pub use expander::ExpanderInput;
+
 /// Fluent builder API for configuring input sources.
 pub mod builder;
 pub use builder::{EmulatedAxis, EmulatedKey, InputBuilder};
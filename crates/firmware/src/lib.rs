@@ -86,13 +86,24 @@
 // ─────────────────────────────────────────────────────────────────────────────
 
 pub mod audio;
+pub mod background;
 pub mod boot;
+pub mod companion;
+pub mod cpu_usage;
 pub mod display;
 pub mod dma;
+#[cfg(feature = "emulator")]
+pub mod emulated_pmic;
+pub mod entropy;
 pub mod exception_handlers;
 pub mod hal;
+#[cfg(feature = "debug")]
+pub mod log_pane;
+#[cfg(feature = "hardware")]
+pub mod rtc;
 pub mod sdram;
 pub mod ui;
+pub mod watchdog;
 
 #[cfg(any(feature = "keyboard-input", feature = "hardware"))]
 pub mod input;
@@ -104,8 +115,8 @@ pub use hal::{Color, DapDisplay, DisplayConfig};
 #[cfg(any(test, feature = "emulator"))]
 pub use audio::{MockAmp, MockDac};
 
-// SSD1677 driver is always available (generic over HAL traits, no hardware gate).
-pub use display::{DisplayError, Ssd1677};
+// Panel drivers are always available (generic over HAL traits, no hardware gate).
+pub use display::{DisplayError, Ssd1677, Ssd1680, Uc8176};
 
 #[cfg(feature = "hardware")]
 pub use audio::Es9038q2mDriver;
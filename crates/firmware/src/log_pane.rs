@@ -0,0 +1,128 @@
+//! `tracing` layer feeding the emulator's debug-panel log pane.
+//!
+//! On the emulator target the firmware logs through `tracing` instead of
+//! defmt.  [`LogPaneLayer`] copies every event that passes the subscriber's
+//! filter into an [`eink_emulator::debug::LogSink`]; the same sink is
+//! attached to the emulator with [`Emulator::attach_log_sink`], and the LOG
+//! tab of the debug panel (Ctrl+1, then Tab) shows it with level and module
+//! filters, pause and copy.
+//!
+//! ```ignore
+//! let sink = LogSink::default();
+//! tracing_subscriber::registry()
+//!     .with(tracing_subscriber::fmt::layer())
+//!     .with(LogPaneLayer::new(sink.clone()))
+//!     .init();
+//! display.emulator_mut().attach_log_sink(sink);
+//! ```
+//!
+//! [`Emulator::attach_log_sink`]: eink_emulator::Emulator::attach_log_sink
+
+use core::fmt::{self, Write as _};
+
+use eink_emulator::debug::{LogLevel, LogSink};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Forwards `tracing` events to a [`LogSink`].
+#[derive(Debug, Clone)]
+pub struct LogPaneLayer {
+    sink: LogSink,
+}
+
+impl LogPaneLayer {
+    /// Forward events to `sink`.
+    #[must_use]
+    pub fn new(sink: LogSink) -> Self {
+        Self { sink }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogPaneLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let meta = event.metadata();
+        let mut line = LineVisitor::default();
+        event.record(&mut line);
+        self.sink
+            .push(log_level(meta.level()), meta.target(), line.finish());
+    }
+}
+
+fn log_level(level: &Level) -> LogLevel {
+    if *level == Level::ERROR {
+        LogLevel::Error
+    } else if *level == Level::WARN {
+        LogLevel::Warn
+    } else if *level == Level::INFO {
+        LogLevel::Info
+    } else if *level == Level::DEBUG {
+        LogLevel::Debug
+    } else {
+        LogLevel::Trace
+    }
+}
+
+/// Formats an event as `message key=value key=value`, like the compact
+/// `fmt` layer without timestamp and target.
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl LineVisitor {
+    fn finish(mut self) -> String {
+        if self.message.is_empty() {
+            self.fields.trim_start().to_string()
+        } else {
+            self.message.push_str(&self.fields);
+            self.message
+        }
+    }
+}
+
+impl Visit for LineVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            // Writing to a String cannot fail.
+            let _ = write!(self.fields, " {}={value}", field.name());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_events_reach_the_sink_with_fields() {
+        let sink = LogSink::new(8);
+        let subscriber = tracing_subscriber::registry().with(LogPaneLayer::new(sink.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(target: "firmware::ui", screen = "menu", depth = 2, "overflow");
+            tracing::debug!(target: "firmware::audio", "underrun");
+        });
+
+        let records: Vec<_> = sink.with(|buf| buf.records().cloned().collect()).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].level, LogLevel::Warn);
+        assert_eq!(records[0].target, "firmware::ui");
+        assert_eq!(records[0].message, "overflow screen=menu depth=2");
+        assert_eq!(records[1].level, LogLevel::Debug);
+        assert_eq!(records[1].message, "underrun");
+    }
+}
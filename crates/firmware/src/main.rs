@@ -5,27 +5,47 @@
 #![no_std]
 #![no_main]
 
-use core::sync::atomic::{AtomicBool, Ordering};
 use embassy_executor::Spawner;
+use embassy_stm32::bind_interrupts;
 use embassy_stm32::exti::{Channel, ExtiInput};
 use embassy_stm32::gpio::{AnyPin, Input, Level, Output, Pull, Speed};
+use embassy_stm32::peripherals::{self, DMA1_CH0, DMA1_CH1, PB0, PB1, PB2, PE3, SPI1};
 use embassy_stm32::spi::{Config as SpiConfig, Spi};
 use embassy_stm32::time::Hertz;
-use embassy_time::{Delay, Duration, Timer};
+use embassy_time::{Delay, Duration, Instant, Timer};
 use embedded_hal_bus::spi::ExclusiveDevice;
+use platform::boot_timing::{BootPhase, BootTimeline, LazyInit, Subsystem};
+use platform::crash_loop::{self, BootMode};
+use platform::smoke::SmokeMarker;
 use platform::DisplayDriver;
+use platform::Rtc;
 use platform::dma_safety::{AudioDmaBufBytes, AxiSramRegion, DmaBuffer};
+use platform::refresh_policy::ContentHint;
 use static_cell::StaticCell;
 
+use firmware::audio::dropouts;
+use firmware::cpu_usage::{self, CpuTask, CPU_USAGE};
+use firmware::display::recovery::RecoveryPolicy;
+use firmware::display::service::{
+    self as display_service, DisplayService, Frame, RenderRequest,
+};
 use firmware::dma::Align32;
+use firmware::entropy::{self, RngStreams};
 use firmware::input::builder::InputBuilder;
 use firmware::input::hardware::spawn_input_task;
-use firmware::ui::{SplashScreen, TestPattern};
-use firmware::{Ssd1677Display, DISPLAY_HEIGHT, DISPLAY_WIDTH, FRAMEBUFFER_SIZE};
+use firmware::ui::SplashScreen;
+use firmware::watchdog::{Heartbeat, TaskId, HEARTBEATS};
+use firmware::{
+    Ssd1677Display, DISPLAY_HEIGHT, DISPLAY_WIDTH, FRAMEBUFFER_SIZE, GDEM0397T81P_SPEC,
+};
 
 // Panic handler
 use panic_probe as _;
 
+bind_interrupts!(struct Irqs {
+    RNG => embassy_stm32::rng::InterruptHandler<peripherals::RNG>;
+});
+
 // Framebuffer stored in AXI SRAM (large buffer region).
 //
 // StaticCell<T> is sound under Rust's aliasing model: it uses UnsafeCell
@@ -54,27 +74,29 @@ static FRAMEBUFFER: StaticCell<Align32<[u8; FRAMEBUFFER_SIZE]>> = StaticCell::ne
 static AUDIO_BUFFER: StaticCell<DmaBuffer<AxiSramRegion, AudioDmaBufBytes>>
     = StaticCell::new();
 
-// Per-task heartbeat flags.
-//
-// Each critical task sets its flag to `true` every watchdog cycle.
-// The main loop checks all flags before feeding the IWDG watchdog.
-// If any flag remains `false` the task has stalled -- the watchdog is NOT
-// fed, the IWDG expires after WATCHDOG_TIMEOUT_MS and resets the device.
-//
-// Flags are cleared (swap to false) by the main loop each cycle so a task
-// that stalled in a later cycle is caught at the next watchdog deadline.
-//
-// NOTE: Currently only the main loop task is tracked. When Embassy audio,
-// display, and input tasks are added they MUST store(true) to their flag
-// each cycle, and the all_tasks_alive check below must include them.
-static TASK_ALIVE_MAIN: AtomicBool = AtomicBool::new(true);
-// Future tasks will add:
-// static TASK_ALIVE_AUDIO:   AtomicBool = AtomicBool::new(false);
-// static TASK_ALIVE_DISPLAY: AtomicBool = AtomicBool::new(false);
-// static TASK_ALIVE_INPUT:   AtomicBool = AtomicBool::new(false);
+/// The panel driver as wired in `main`: SPI1 with DMA, CS on PB1, DC on
+/// PB0, RST on PB2, BUSY on PE3.
+type PanelDisplay = Ssd1677Display<
+    ExclusiveDevice<Spi<'static, SPI1, DMA1_CH0, DMA1_CH1>, Output<'static, PB1>, Delay>,
+    Output<'static, PB0>,
+    Output<'static, PB2>,
+    Input<'static, PE3>,
+    Delay,
+>;
+
+/// Owns the panel after boot; everything else sends it render requests.
+#[embassy_executor::task]
+async fn display_task(service: DisplayService<PanelDisplay>, heartbeat: Heartbeat) {
+    display_service::run(service, heartbeat).await
+}
 
 #[embassy_executor::main]
 async fn main(spawner: Spawner) {
+    // Per-task CPU accounting (firmware::cpu_usage) reads DWT CYCCNT, and
+    // the boot timeline uses it until the Embassy time driver is running.
+    cpu_usage::dwt::enable();
+    let mut boot = BootTimeline::new();
+
     // Step 0: Configure MPU BEFORE embassy_stm32::init() enables D-cache.
     //
     // embassy_stm32::init() enables the Cortex-M7 D-cache on STM32H7. Without
@@ -87,6 +109,10 @@ async fn main(spawner: Spawner) {
     // References: ST AN4838/AN4839, ARM DDI0489F §B3.5.
     // See: firmware::boot::BOOT_SEQUENCE_STEPS for the full ordered sequence.
     let mpu_token = firmware::boot::hardware::apply_mpu_config_from_peripherals();
+    boot.mark(
+        BootPhase::Mpu,
+        firmware::boot::reset_clock_ms(cpu_usage::dwt::cycles()),
+    );
 
     // Initialize Embassy
     defmt::info!("SoulAudio DAP Firmware v{=str}", "0.1.0");
@@ -94,6 +120,13 @@ async fn main(spawner: Spawner) {
 
     let p = embassy_stm32::init(firmware::boot::build_embassy_config(&mpu_token));
 
+    // From here on, boot phases are timed with the Embassy clock, offset by
+    // the cycle-counted time to this point.
+    let clocks_ms = firmware::boot::reset_clock_ms(cpu_usage::dwt::cycles());
+    boot.mark(BootPhase::Clocks, clocks_ms);
+    let clocks_at = Instant::now();
+    let since_reset_ms = || clocks_ms.saturating_add(clocks_at.elapsed().as_millis());
+
     // Step 1: Initialize IWDG (Independent Watchdog).
     //
     // The IWDG must be fed every WATCHDOG_TIMEOUT_MS milliseconds or the MCU
@@ -113,6 +146,63 @@ async fn main(spawner: Spawner) {
         "IWDG watchdog armed: timeout={=u32}ms",
         firmware::boot::WATCHDOG_TIMEOUT_MS
     );
+    defmt::info!("{=str}", SmokeMarker::Boot.tag());
+    let main_heartbeat = HEARTBEATS.register(TaskId::Main);
+
+    // Step 2: Real-time clock (LSE, backup domain).
+    //
+    // The calendar keeps running across resets. After the backup domain lost
+    // power the time is invalid until the user sets it, and consumers (clock,
+    // log timestamps) must check is_time_valid() before using it.
+    let mut rtc = firmware::rtc::HardwareRtc::new(p.RTC);
+    match rtc.now() {
+        Ok(now) if rtc.is_time_valid() => defmt::info!("RTC time: {}", now),
+        Ok(_) => defmt::warn!("RTC time not set (backup domain reset)"),
+        Err(e) => defmt::error!("RTC read failed: {}", e),
+    }
+
+    // Step 2a: Crash-loop guard.
+    //
+    // Counts boots that ended in a watchdog reset or never reached
+    // crash_loop::STABLE_AFTER_MS (the count lives in a backup register).
+    // After crash_loop::SAFE_MODE_AFTER in a row this boot runs in safe
+    // mode: no Bluetooth, no stats, no soul.toml, and a prompt instead of
+    // the first screen.
+    let boot_record = crash_loop::begin_boot(&mut rtc, firmware::boot::take_reset_cause());
+    if boot_record.mode == BootMode::Safe {
+        defmt::warn!(
+            "Safe mode: {=u8} failed boots in a row (reset: {=str})",
+            boot_record.failures,
+            boot_record.reset.name()
+        );
+    } else if !boot_record.is_clean() {
+        defmt::warn!(
+            "Previous boot failed ({=u8} in a row, reset: {=str})",
+            boot_record.failures,
+            boot_record.reset.name()
+        );
+    }
+
+    // Step 2b: Seed the PRNG once from the RNG peripheral (clocked from
+    // HSI48, enabled in build_embassy_config()).  Every randomised subsystem
+    // takes its own forked stream from here; see firmware::entropy.
+    let mut rng = embassy_stm32::rng::Rng::new(p.RNG, Irqs);
+    let RngStreams {
+        shuffle: _shuffle_rng,
+        dither: _dither_rng,
+        screensaver: _screensaver_rng,
+    } = RngStreams::split(entropy::seed_from_hardware(&mut rng).await);
+    // The device spawns no shuffle, dithering or screensaver task yet; each
+    // takes its stream from here when it lands.
+
+    // Non-critical subsystems start once the first screen is up.  The
+    // library index is one of them: the resumed track opens by path.
+    let mut lazy = LazyInit::new();
+    for subsystem in Subsystem::ALL {
+        if boot_record.mode.starts(subsystem) {
+            lazy.defer(subsystem);
+        }
+    }
 
     // Initialize the framebuffer. StaticCell::init() gives a unique mutable static ref:
     // which is sound under Rust's aliasing model (uses UnsafeCell internally).
@@ -155,6 +245,8 @@ async fn main(spawner: Spawner) {
     // See: firmware::boot::SDMMC_INIT_NOTE for pin assignments and DMA config.
     // Clock source: HSI48 (already enabled in build_embassy_config()).
     // Priority: CRITICAL — SD card needed for music library access.
+    // Once mounted, log SmokeMarker::SdMounted and make it required() in
+    // platform::smoke so `cargo xtask flash --smoke` checks it.
     // #[cfg(feature = "hardware")]
     // let sdmmc = embassy_stm32::sdmmc::Sdmmc::new_4bit(
     //     p.SDMMC1, Irqs,
@@ -271,14 +363,18 @@ async fn main(spawner: Spawner) {
         2
     );
     match display.init().await {
-        Ok(_) => defmt::info!(
-            "Display ready: {}x{} GDEM0397T81P (SSD1677)",
-            DISPLAY_WIDTH,
-            DISPLAY_HEIGHT
-        ),
+        Ok(_) => {
+            defmt::info!(
+                "Display ready: {}x{} GDEM0397T81P (SSD1677)",
+                DISPLAY_WIDTH,
+                DISPLAY_HEIGHT
+            );
+            defmt::info!("{=str}", SmokeMarker::DisplayInit.tag());
+            boot.mark(BootPhase::DisplayInit, since_reset_ms());
+        }
         Err(e) => {
             defmt::error!("Display initialization failed: {}", e);
-            // Intentional: do NOT call TASK_ALIVE_MAIN.store(true) here.
+            // Intentional: do NOT tick main_heartbeat or feed the watchdog here.
             // The IWDG watchdog will detect the missing heartbeat after
             // WATCHDOG_TIMEOUT_MS (8 s) and reset the device --- this IS the
             // automatic retry strategy for display hardware failures.
@@ -291,7 +387,7 @@ async fn main(spawner: Spawner) {
 
     // Show splash screen
     defmt::info!("Rendering splash screen");
-    if let Err(e) = SplashScreen::render(&mut display) {
+    if let Err(e) = cpu_usage::measure(CpuTask::Ui, || SplashScreen::render(&mut display)) {
         defmt::error!("Failed to render splash screen: {}", e);
     }
 
@@ -302,6 +398,26 @@ async fn main(spawner: Spawner) {
 
     defmt::info!("Splash screen displayed — full refresh complete");
 
+    // TODO: once SDMMC1 is brought up here, mark BootPhase::SdMount, load
+    // soul.toml through boot_record.mode.config(), read the
+    // playback::resume::ResumeRecord, open its path, resume_at() its
+    // position and mark BootPhase::ResumeOpen; the audio task then calls
+    // boot.mark_first_sound() on the first DMA half, and Now Playing is
+    // drawn with `loading` set until Subsystem::LibraryIndex completes.
+    // Until then their time is absent from the report.
+    boot.mark(BootPhase::FirstFrame, since_reset_ms());
+    firmware::boot::log_boot_report(&boot.report());
+
+    // Deferred subsystems, one at a time.
+    lazy.first_frame_shown();
+    while let Some(subsystem) = lazy.start_next() {
+        // TODO: load the library index, start the Bluetooth module and load
+        // listening stats here once their drivers exist; none is fitted in
+        // this build.
+        defmt::warn!("{=str}: no driver in this build", subsystem.name());
+        lazy.complete(subsystem, false);
+    }
+
     // -----------------------------------------------------------------------
     // Wire input task
     //
@@ -346,7 +462,16 @@ async fn main(spawner: Spawner) {
         ExtiInput::new(Input::new(p.PD5, Pull::Up).degrade(), p.EXTI5.degrade());
 
     spawn_input_task(
-        &spawner, enc_clk, enc_dt, btn_play, btn_next, btn_prev, btn_menu, btn_back, btn_select,
+        &spawner,
+        enc_clk,
+        enc_dt,
+        btn_play,
+        btn_next,
+        btn_prev,
+        btn_menu,
+        btn_back,
+        btn_select,
+        HEARTBEATS.register(TaskId::Input),
     );
     defmt::info!("Input task spawned — channel depth={=usize}", 16usize);
 
@@ -398,55 +523,91 @@ async fn main(spawner: Spawner) {
     // Spawn audio task: runs concurrently, manages SAI1 DMA ping-pong.
     // When Embassy SAI support and PLL3 are wired, audio_task will
     // call Sai::new_asynchronous_with_mclk() and stream audio to the DAC.
-    spawner.must_spawn(firmware::audio::sai_task::audio_task_embassy(audio_buf));
+    spawner.must_spawn(firmware::audio::sai_task::audio_task_embassy(
+        audio_buf,
+        HEARTBEATS.register(TaskId::Audio),
+    ));
+
+    // From here on the display service owns the panel; the splash refresh
+    // above counts as its first full refresh.
+    // TODO: give it a DoubleBuffer (2 × 192 KB) in SDRAM via with_frame_diff()
+    // once FMC init lands; AXI SRAM cannot spare it.
+    let service = DisplayService::new(display)
+        .after_full_refresh(Instant::now().as_millis())
+        .with_recovery(RecoveryPolicy::for_quirks(
+            GDEM0397T81P_SPEC.quirks.unwrap_or(&[]),
+        ));
+    spawner.must_spawn(display_task(service, HEARTBEATS.register(TaskId::Display)));
 
     // Wait 3 seconds
     Timer::after(Duration::from_secs(3)).await;
 
-    // Show test pattern
-    defmt::info!("Rendering test pattern");
-    if let Err(e) = TestPattern::render(&mut display) {
-        defmt::error!("Failed to render test pattern: {}", e);
-    }
-
-    // Trigger full refresh
-    if let Err(e) = display.refresh_full().await {
-        defmt::error!("Failed to refresh display (full): {}", e);
-    }
-
-    defmt::info!("Test pattern displayed — full refresh complete");
+    // Show test pattern, or the safe mode prompt
+    let first = match boot_record.mode {
+        BootMode::Normal => Frame::TestPattern,
+        BootMode::Safe => Frame::SafeMode(boot_record),
+    };
+    defmt::info!("Requesting first screen");
+    display_service::request(RenderRequest::full_screen(first, ContentHint::Graphics));
 
     // Main loop - heartbeat + watchdog guard
     defmt::info!("Entering main loop");
     let mut counter = 0u32;
+    let mut cpu_window_start = cpu_usage::dwt::cycles();
+    let mut dropouts_logged = 0u32;
+    let mut boot_stable = false;
 
     loop {
         Timer::after(Duration::from_secs(1)).await;
         counter = counter.wrapping_add(1);
         defmt::debug!("Heartbeat tick={=u32}", counter);
 
+        // Per-task CPU load over the last REPORT_INTERVAL_SECS.
+        if u64::from(counter).checked_rem(cpu_usage::REPORT_INTERVAL_SECS) == Some(0) {
+            let now = cpu_usage::dwt::cycles();
+            let report = CPU_USAGE.sample(now.wrapping_sub(cpu_window_start));
+            cpu_window_start = now;
+            cpu_usage::dwt::log(&report);
+            dropouts::ACTIVITY.cpu_load(report.busy_permille());
+            // Dump the journal whenever new dropouts were recorded.
+            let total = dropouts::summary().total();
+            if total != dropouts_logged {
+                dropouts_logged = total;
+                dropouts::log();
+            }
+        }
+
         // Signal that the main task is alive this cycle.
-        TASK_ALIVE_MAIN.store(true, Ordering::Release);
+        main_heartbeat.tick();
 
-        // Feed the IWDG watchdog ONLY if all critical tasks are alive.
-        // If any task has not set its heartbeat flag, do NOT pet the watchdog --
-        // the IWDG will expire after WATCHDOG_TIMEOUT_MS (8s) and reset the device.
-        //
-        // swap(false) atomically reads the current value and clears the flag,
-        // so the task must set it again before the next watchdog cycle.
+        // Feed the IWDG watchdog ONLY if every registered task ticked its
+        // heartbeat since the last check. If one has not, do NOT pet the
+        // watchdog -- the IWDG will expire after WATCHDOG_TIMEOUT_MS (8s) and
+        // reset the device. check() clears the ticks, so each task must tick
+        // again before the next watchdog cycle.
         //
-        // Currently only tracking the main task. When audio/display tasks are
-        // added (Embassy #[task] functions), they must store(true) to their
-        // respective AtomicBool each cycle, and we add their checks here.
-        let all_tasks_alive = TASK_ALIVE_MAIN.swap(false, Ordering::AcqRel);
-        // Future: && TASK_ALIVE_AUDIO.swap(false, Ordering::AcqRel)
-        //         && TASK_ALIVE_DISPLAY.swap(false, Ordering::AcqRel)
-
-        if all_tasks_alive {
-            watchdog.pet();
-        } else {
-            defmt::error!("Task heartbeat missing -- watchdog NOT fed, reset imminent");
-            // Do not call watchdog.pet() -- let IWDG expire and reset
+        // Tasks join this check by registering with HEARTBEATS when spawned;
+        // see firmware::watchdog.
+        match HEARTBEATS.check() {
+            Ok(()) => {
+                watchdog.pet();
+                // Every task alive this far into the boot: clear the
+                // crash-loop count.
+                if !boot_stable && since_reset_ms() >= crash_loop::STABLE_AFTER_MS {
+                    boot_stable = true;
+                    match crash_loop::mark_stable(&mut rtc) {
+                        Ok(()) => defmt::info!("Boot stable — crash-loop count cleared"),
+                        Err(e) => defmt::error!("Crash-loop record write failed: {}", e),
+                    }
+                }
+            }
+            Err(task) => {
+                defmt::error!(
+                    "Task heartbeat missing ({=str}) -- watchdog NOT fed, reset imminent",
+                    task.name()
+                );
+                // Do not call watchdog.pet() -- let IWDG expire and reset
+            }
         }
     }
 }
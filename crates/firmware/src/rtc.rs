@@ -0,0 +1,140 @@
+//! STM32H7 RTC implementing [`platform::Rtc`].
+//!
+//! The RTC is clocked from the 32.768 kHz LSE crystal
+//! (`boot::build_embassy_config` selects it) and lives in the backup domain,
+//! so the calendar and the backup registers survive resets and, with VBAT
+//! supplied, power-off.  Backup register 31 holds [`TIME_SET_MAGIC`] once the
+//! clock has been set; after the backup domain loses power it reads zero and
+//! [`Rtc::is_time_valid`] returns `false` until the user sets the time.
+//!
+//! embassy-stm32 0.1.0 does not expose RTC alarm A, so [`Rtc::wait_alarm`]
+//! compares against the calendar on an Embassy timer instead of waking on
+//! the RTC interrupt.  That is enough for the sleep timer; waking from Stop
+//! mode on an alarm needs the PAC-level alarm setup.
+
+use embassy_stm32::rtc::{DateTime as Stm32DateTime, DayOfWeek, Rtc as Stm32Rtc, RtcConfig};
+use platform::rtc::{DateTime, Rtc, RtcError, Weekday, BACKUP_REGISTERS};
+
+/// Backup register reserved for [`TIME_SET_MAGIC`] (the one after the
+/// general-purpose registers).
+const TIME_SET_REGISTER: usize = BACKUP_REGISTERS;
+
+/// Written to [`TIME_SET_REGISTER`] by [`Rtc::set_time`] (`"RTC1"`).
+pub const TIME_SET_MAGIC: u32 = 0x5254_4331;
+
+/// Longest single sleep in [`Rtc::wait_alarm`] before re-reading the
+/// calendar, bounding drift between the Embassy timer and the RTC.
+const MAX_ALARM_SLEEP_SECS: u64 = 60;
+
+/// The STM32 RTC stores a two-digit year from 2000.
+const HARDWARE_MIN_YEAR: u16 = 2000;
+
+/// [`platform::Rtc`] on the STM32H7 RTC peripheral.
+pub struct HardwareRtc {
+    rtc: Stm32Rtc,
+    alarm: Option<DateTime>,
+}
+
+impl HardwareRtc {
+    /// Take the RTC peripheral.
+    ///
+    /// Only the prescalers are (re)written; a running calendar keeps its time.
+    pub fn new(rtc: embassy_stm32::peripherals::RTC) -> Self {
+        Self {
+            rtc: Stm32Rtc::new(rtc, RtcConfig::default()),
+            alarm: None,
+        }
+    }
+}
+
+impl Rtc for HardwareRtc {
+    type Error = RtcError;
+
+    fn now(&self) -> Result<DateTime, RtcError> {
+        let t = self.rtc.now().map_err(|_| RtcError::Hardware)?;
+        DateTime::new(
+            t.year(),
+            t.month(),
+            t.day(),
+            t.hour(),
+            t.minute(),
+            t.second(),
+        )
+    }
+
+    fn set_time(&mut self, time: DateTime) -> Result<(), RtcError> {
+        if time.year() < HARDWARE_MIN_YEAR {
+            return Err(RtcError::InvalidDateTime);
+        }
+        let t = Stm32DateTime::from(
+            time.year(),
+            time.month(),
+            time.day(),
+            day_of_week(time.weekday()),
+            time.hour(),
+            time.minute(),
+            time.second(),
+        )
+        .map_err(|_| RtcError::InvalidDateTime)?;
+        self.rtc.set_datetime(t).map_err(|_| RtcError::Hardware)?;
+        self.rtc
+            .write_backup_register(TIME_SET_REGISTER, TIME_SET_MAGIC);
+        Ok(())
+    }
+
+    fn is_time_valid(&self) -> bool {
+        self.rtc.read_backup_register(TIME_SET_REGISTER) == Some(TIME_SET_MAGIC)
+    }
+
+    fn set_alarm(&mut self, at: DateTime) -> Result<(), RtcError> {
+        self.alarm = Some(at);
+        Ok(())
+    }
+
+    fn cancel_alarm(&mut self) {
+        self.alarm = None;
+    }
+
+    fn alarm(&self) -> Option<DateTime> {
+        self.alarm
+    }
+
+    async fn wait_alarm(&mut self) -> Result<(), RtcError> {
+        let at = self.alarm.ok_or(RtcError::NoAlarm)?;
+        loop {
+            let Some(remaining) = at.secs_since(&self.now()?).filter(|&s| s > 0) else {
+                self.alarm = None;
+                return Ok(());
+            };
+            let sleep = remaining.min(MAX_ALARM_SLEEP_SECS);
+            embassy_time::Timer::after(embassy_time::Duration::from_secs(sleep)).await;
+        }
+    }
+
+    fn read_backup(&self, index: usize) -> Option<u32> {
+        if index >= BACKUP_REGISTERS {
+            return None;
+        }
+        self.rtc.read_backup_register(index)
+    }
+
+    fn write_backup(&mut self, index: usize, value: u32) -> Result<(), RtcError> {
+        if index >= BACKUP_REGISTERS {
+            return Err(RtcError::InvalidBackupRegister);
+        }
+        self.rtc.write_backup_register(index, value);
+        Ok(())
+    }
+}
+
+fn day_of_week(day: Weekday) -> DayOfWeek {
+    match day {
+        Weekday::Monday => DayOfWeek::Monday,
+        Weekday::Tuesday => DayOfWeek::Tuesday,
+        Weekday::Wednesday => DayOfWeek::Wednesday,
+        Weekday::Thursday => DayOfWeek::Thursday,
+        Weekday::Friday => DayOfWeek::Friday,
+        Weekday::Saturday => DayOfWeek::Saturday,
+        Weekday::Sunday => DayOfWeek::Sunday,
+    }
+}
@@ -144,6 +144,8 @@ pub const fn compute_refresh_count(fmc_clk_hz: u32) -> u32 {
 }
 
 #[cfg(test)]
+#[allow(clippy::assertions_on_constants)] // Tests pin the timing constants
+#[allow(clippy::cast_possible_truncation)]
 mod tests {
     use super::*;
 
@@ -18,6 +18,10 @@ use embedded_graphics::prelude::*;
 use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
 use embedded_graphics::text::Text;
 
+use platform::crash_loop::BootRecord;
+
+use crate::cpu_usage::CpuReport;
+
 /// Splash screen - shown on boot
 pub struct SplashScreen;
 
@@ -60,6 +64,96 @@ impl SplashScreen {
     }
 }
 
+/// Display fault screen - the last frame shown after display recovery
+/// gives up (see [`crate::display::recovery`])
+pub struct DisplayFaultScreen;
+
+impl DisplayFaultScreen {
+    /// Render a black-on-white notice that the display stopped responding
+    ///
+    /// # Errors
+    ///
+    /// Returns `D::Error` if any drawing operation fails.
+    pub fn render<D, C>(display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        C: PixelColor + From<Gray2>,
+    {
+        let bounds = display.bounding_box();
+        Rectangle::new(bounds.top_left, bounds.size)
+            .into_styled(PrimitiveStyle::with_fill(C::from(Gray2::WHITE)))
+            .draw(display)?;
+
+        let text_style = MonoTextStyle::new(&FONT_9X18, C::from(Gray2::BLACK));
+        let center = bounds.center();
+        Text::new(
+            "Display error",
+            Point::new(center.x - 58, center.y - 9),
+            text_style,
+        )
+        .draw(display)?;
+        Text::new(
+            "Restart to retry",
+            Point::new(center.x - 72, center.y + 18),
+            text_style,
+        )
+        .draw(display)?;
+
+        Ok(())
+    }
+}
+
+/// Safe mode prompt - shown instead of the first screen after a crash
+/// loop (see [`platform::crash_loop`])
+pub struct SafeModeScreen;
+
+impl SafeModeScreen {
+    /// Row pitch in pixels (FONT_9X18 line height plus spacing)
+    const ROW_HEIGHT: i32 = 24;
+
+    /// Render what was turned off and how to reach the diagnostics screen
+    ///
+    /// # Errors
+    ///
+    /// Returns `D::Error` if any drawing operation fails.
+    pub fn render<D, C>(display: &mut D, record: &BootRecord) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        C: PixelColor + From<Gray2>,
+    {
+        use core::fmt::Write as _;
+
+        let bounds = display.bounding_box();
+        Rectangle::new(bounds.top_left, bounds.size)
+            .into_styled(PrimitiveStyle::with_fill(C::from(Gray2::WHITE)))
+            .draw(display)?;
+
+        let text_style = MonoTextStyle::new(&FONT_9X18, C::from(Gray2::BLACK));
+        // "255 failed starts (watchdog)" is 28 chars; the buffer never fills.
+        let mut failures: heapless::String<40> = heapless::String::new();
+        let _ = write!(
+            failures,
+            "{} failed starts ({})",
+            record.failures,
+            record.reset.name()
+        );
+        let lines = [
+            "Safe mode",
+            failures.as_str(),
+            "",
+            "Bluetooth, library scan and",
+            "soul.toml settings are off.",
+            "Open Diagnostics from Settings.",
+        ];
+        for (row, line) in (0..).zip(lines) {
+            let y = 20 + row * Self::ROW_HEIGHT;
+            Text::new(line, Point::new(10, y), text_style).draw(display)?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Test pattern - for hardware validation
 pub struct TestPattern;
 
@@ -149,6 +243,65 @@ impl TestPattern {
     }
 }
 
+/// Debug screen - per-task CPU load from the last [`CpuReport`]
+pub struct CpuUsageScreen;
+
+impl CpuUsageScreen {
+    /// Row pitch in pixels (FONT_9X18 line height plus spacing)
+    const ROW_HEIGHT: i32 = 24;
+    /// Width of the label + percentage column before the bar
+    const LABEL_WIDTH: i32 = 9 * 16;
+
+    /// Render one row per task with its load as a percentage and a bar,
+    /// followed by the idle share
+    ///
+    /// # Errors
+    ///
+    /// Returns `D::Error` if any drawing operation fails.
+    pub fn render<D, C>(display: &mut D, report: &CpuReport) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        C: PixelColor + From<Gray2>,
+    {
+        use core::fmt::Write as _;
+
+        let bounds = display.bounding_box();
+        Rectangle::new(bounds.top_left, bounds.size)
+            .into_styled(PrimitiveStyle::with_fill(C::from(Gray2::WHITE)))
+            .draw(display)?;
+
+        let text_style = MonoTextStyle::new(&FONT_9X18, C::from(Gray2::BLACK));
+        let bar_width = (bounds.size.width as i32 - Self::LABEL_WIDTH - 20).max(0);
+        let bar_len = u32::try_from(bar_width).unwrap_or(0);
+
+        Text::new("CPU usage", Point::new(10, 20), text_style).draw(display)?;
+
+        let rows = report
+            .iter()
+            .map(|(task, permille)| (task.name(), permille))
+            .chain(core::iter::once(("idle", report.idle_permille())));
+        for (row, (name, permille)) in (1..).zip(rows) {
+            let y = 20 + row * Self::ROW_HEIGHT;
+
+            // "scanner 100.0%" is 14 chars; the buffer never fills.
+            let mut label: heapless::String<24> = heapless::String::new();
+            let _ = write!(label, "{name:<8}{:>3}.{}%", permille / 10, permille % 10);
+            Text::new(&label, Point::new(10, y), text_style).draw(display)?;
+
+            let bar_x = 10 + Self::LABEL_WIDTH;
+            let filled = u32::try_from(bar_width * i32::from(permille) / 1000).unwrap_or(0);
+            Rectangle::new(Point::new(bar_x, y - 12), Size::new(bar_len, 12))
+                .into_styled(PrimitiveStyle::with_stroke(C::from(Gray2::BLACK), 1))
+                .draw(display)?;
+            Rectangle::new(Point::new(bar_x, y - 12), Size::new(filled, 12))
+                .into_styled(PrimitiveStyle::with_fill(C::from(Gray2::BLACK)))
+                .draw(display)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -205,6 +358,30 @@ mod tests {
         assert!(display.pixel_count > 0, "TestPattern should draw pixels");
     }
 
+    #[test]
+    fn test_display_fault_screen_renders_without_error() {
+        let mut display = TestDisplay::new(200, 100);
+        assert!(DisplayFaultScreen::render(&mut display).is_ok());
+        assert!(
+            display.pixel_count > 0,
+            "DisplayFaultScreen should draw pixels"
+        );
+    }
+
+    #[test]
+    fn test_safe_mode_screen_renders_without_error() {
+        use platform::crash_loop::{BootMode, ResetCause};
+
+        let record = BootRecord {
+            failures: 3,
+            reset: ResetCause::Watchdog,
+            mode: BootMode::Safe,
+        };
+        let mut display = TestDisplay::new(400, 240);
+        assert!(SafeModeScreen::render(&mut display, &record).is_ok());
+        assert!(display.pixel_count > 0, "SafeModeScreen should draw pixels");
+    }
+
     #[test]
     fn test_splash_screen_small_display() {
         // Should not panic on a very small display
@@ -217,4 +394,22 @@ mod tests {
         let mut display = TestDisplay::new(50, 50);
         assert!(TestPattern::render(&mut display).is_ok());
     }
+
+    #[test]
+    fn test_cpu_usage_screen_renders_without_error() {
+        use crate::cpu_usage::{CpuTask, CpuUsage};
+
+        let usage = CpuUsage::new();
+        usage.record(CpuTask::Decode, 420);
+        usage.record(CpuTask::Display, 1_000);
+        let report = usage.sample(1_000);
+
+        let mut display = TestDisplay::new(400, 240);
+        assert!(CpuUsageScreen::render(&mut display, &report).is_ok());
+        assert!(display.pixel_count > 0, "CpuUsageScreen should draw pixels");
+
+        // Narrower than the label column: bars collapse instead of panicking
+        let mut display = TestDisplay::new(50, 50);
+        assert!(CpuUsageScreen::render(&mut display, &report).is_ok());
+    }
 }
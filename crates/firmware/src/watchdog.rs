@@ -0,0 +1,239 @@
+//! Per-task heartbeats gating the IWDG feed.
+//!
+//! The main loop only feeds the independent watchdog when every registered
+//! task has ticked its [`Heartbeat`] since the previous check, so a stalled
+//! audio, display, input or scanner task resets the device just like a
+//! stalled main loop does.
+//!
+//! A task gets its handle from [`HEARTBEATS.register`](HeartbeatRegistry::register)
+//! at spawn time and calls [`Heartbeat::tick`] from its loop.  Registering
+//! is what adds the task to the all-tasks-alive check, so there is no second
+//! list to keep in sync.  [`WATCHED_TASKS`] names the Embassy task function
+//! for each [`TaskId`]; the `every_embassy_task_has_a_heartbeat` arch test
+//! fails for any `#[embassy_executor::task]` missing from it, so adding a
+//! task without a heartbeat cannot silently weaken the watchdog.
+//!
+//! # Timing
+//!
+//! The main loop checks once per second and the IWDG expires after
+//! [`crate::boot::WATCHDOG_TIMEOUT_MS`], so a task must tick at least once a
+//! second to be counted every cycle; one late tick costs a single skipped
+//! feed, not a reset.  Event-driven tasks (input) tick from a one-second
+//! ticker joined with their event loops: the ticker shares the task's
+//! future, so a loop that blocks the task also stops the ticks.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// A task whose liveness gates the watchdog feed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TaskId {
+    /// The `main` task (boot, display refresh, watchdog feed).
+    Main,
+    /// SAI/DMA audio output.
+    Audio,
+    /// Display refresh task.
+    Display,
+    /// Encoder and button input.
+    Input,
+    /// Library scanner (background jobs).
+    Scanner,
+}
+
+impl TaskId {
+    /// Every watched task.
+    pub const ALL: [Self; 5] = [
+        Self::Main,
+        Self::Audio,
+        Self::Display,
+        Self::Input,
+        Self::Scanner,
+    ];
+
+    /// Bit in the registry masks.
+    const fn bit(self) -> u32 {
+        match self {
+            Self::Main => 0x01,
+            Self::Audio => 0x02,
+            Self::Display => 0x04,
+            Self::Input => 0x08,
+            Self::Scanner => 0x10,
+        }
+    }
+
+    /// Name for logs.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Main => "main",
+            Self::Audio => "audio",
+            Self::Display => "display",
+            Self::Input => "input",
+            Self::Scanner => "scanner",
+        }
+    }
+}
+
+/// Embassy task function behind each [`TaskId`].
+///
+/// The arch tests require every `#[embassy_executor::task]` in the firmware
+/// to appear here (and to take a [`Heartbeat`]).  `main` is listed under its
+/// own name; the scanner runs inside `main` until it gets its own task.
+pub const WATCHED_TASKS: &[(TaskId, &str)] = &[
+    (TaskId::Main, "main"),
+    (TaskId::Audio, "audio_task_embassy"),
+    (TaskId::Display, "display_task"),
+    (TaskId::Input, "input_task"),
+];
+
+/// Which tasks are watched and which have ticked this cycle.
+pub struct HeartbeatRegistry {
+    registered: AtomicU32,
+    alive: AtomicU32,
+}
+
+/// The registry the main loop checks before feeding the IWDG.
+pub static HEARTBEATS: HeartbeatRegistry = HeartbeatRegistry::new();
+
+impl HeartbeatRegistry {
+    /// An empty registry (nothing watched).
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            registered: AtomicU32::new(0),
+            alive: AtomicU32::new(0),
+        }
+    }
+
+    /// Add `task` to the all-tasks-alive check and return its handle.
+    ///
+    /// The task counts as alive for the first check so it has a full cycle
+    /// to start up.
+    pub fn register(&'static self, task: TaskId) -> Heartbeat {
+        self.alive.fetch_or(task.bit(), Ordering::Release);
+        self.registered.fetch_or(task.bit(), Ordering::Release);
+        Heartbeat {
+            registry: self,
+            task,
+        }
+    }
+
+    /// Whether `task` has been registered.
+    #[must_use]
+    pub fn is_registered(&self, task: TaskId) -> bool {
+        self.registered.load(Ordering::Acquire) & task.bit() != 0
+    }
+
+    /// Check that every registered task ticked since the last call, and
+    /// start a new cycle.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first registered task (in [`TaskId::ALL`] order) that did
+    /// not tick; the watchdog must not be fed.
+    pub fn check(&self) -> Result<(), TaskId> {
+        let alive = self.alive.swap(0, Ordering::AcqRel);
+        let missing = self.registered.load(Ordering::Acquire) & !alive;
+        match TaskId::ALL.into_iter().find(|t| missing & t.bit() != 0) {
+            Some(task) => Err(task),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for HeartbeatRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A task's proof-of-life handle.  Call [`tick`](Self::tick) every loop
+/// iteration (at least once a second).
+#[derive(Clone, Copy)]
+pub struct Heartbeat {
+    registry: &'static HeartbeatRegistry,
+    task: TaskId,
+}
+
+impl Heartbeat {
+    /// Report this task alive for the current watchdog cycle.
+    pub fn tick(&self) {
+        self.registry
+            .alive
+            .fetch_or(self.task.bit(), Ordering::Release);
+    }
+
+    /// The task this heartbeat belongs to.
+    #[must_use]
+    pub fn task(&self) -> TaskId {
+        self.task
+    }
+}
+
+/// Tick `heartbeat` once a second forever — for tasks that otherwise only
+/// wake on external events.  Join it with the task's event loops.
+#[cfg(feature = "hardware")]
+pub async fn tick_forever(heartbeat: Heartbeat) -> ! {
+    loop {
+        heartbeat.tick();
+        embassy_time::Timer::after_secs(1).await;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+
+    fn registry() -> &'static HeartbeatRegistry {
+        Box::leak(Box::new(HeartbeatRegistry::new()))
+    }
+
+    #[test]
+    fn test_unregistered_tasks_are_not_checked() {
+        let reg = registry();
+        assert_eq!(reg.check(), Ok(()));
+        let main = reg.register(TaskId::Main);
+        assert!(reg.is_registered(TaskId::Main));
+        assert!(!reg.is_registered(TaskId::Audio));
+
+        // Registration counts as the first tick.
+        assert_eq!(reg.check(), Ok(()));
+        main.tick();
+        assert_eq!(reg.check(), Ok(()));
+    }
+
+    #[test]
+    fn test_missing_tick_is_reported_then_clears() {
+        let reg = registry();
+        let main = reg.register(TaskId::Main);
+        let audio = reg.register(TaskId::Audio);
+        let input = reg.register(TaskId::Input);
+        assert_eq!(reg.check(), Ok(()));
+
+        main.tick();
+        input.tick();
+        assert_eq!(reg.check(), Err(TaskId::Audio));
+
+        // A tick from a previous cycle does not carry over.
+        main.tick();
+        audio.tick();
+        assert_eq!(reg.check(), Err(TaskId::Input));
+
+        main.tick();
+        audio.tick();
+        input.tick();
+        assert_eq!(reg.check(), Ok(()));
+    }
+
+    #[test]
+    fn test_watched_tasks_are_unique() {
+        for (i, (task, name)) in WATCHED_TASKS.iter().enumerate() {
+            for (other_task, other_name) in WATCHED_TASKS.iter().skip(i + 1) {
+                assert_ne!(task, other_task);
+                assert_ne!(name, other_name);
+            }
+        }
+    }
+}
@@ -1515,6 +1515,7 @@ fn all_workspace_members_opt_into_workspace_lints() {
         include_str!("../../../crates/eink/eink-system/Cargo.toml"),
         include_str!("../../../crates/eink/eink-components/Cargo.toml"),
         include_str!("../../../crates/firmware-ui/Cargo.toml"),
+        include_str!("../../../crates/util/Cargo.toml"),
         include_str!("../../../xtask/Cargo.toml"),
     ];
     for (i, cargo_toml) in crates_to_check.iter().enumerate() {
@@ -1652,41 +1653,39 @@ fn audio_sai_ping_pong_dma_pattern_documented() {
 // CI Hardening Tests (TDD Round 8 Slice 5)
 // =============================================================================
 
-// -- ABI version enforcement (GAP-M5) -----------------------------------------
+// -- ABI enforcement (GAP-M5) ------------------------------------------------
 
-/// firmware-ui ABI version must be non-zero and the binary must verify it at
-/// startup. CI must enforce that ABI_VERSION is bumped whenever the render_ui
-/// C-ABI signature changes.
-///
-/// The `ui_abi_version()` export is the runtime check; the build.rs hash is the
-/// static check that makes a forgotten bump visible in build output.
+/// The hot-reload exports must be declared through `hot_reload_abi!`, which
+/// derives the ABI hash from the signatures themselves. A hand-written
+/// `#[no_mangle]` export would bypass the hash and reintroduce the
+/// forgotten-bump class of crashes.
 #[test]
-fn firmware_ui_abi_version_is_nonzero() {
+fn firmware_ui_exports_are_declared_through_abi_macro() {
     let lib_src = include_str!("../../firmware-ui/src/lib.rs");
     assert!(
-        lib_src.contains("ABI_VERSION"),
-        "firmware-ui/src/lib.rs must define ABI_VERSION constant for hot-reload ABI contract"
+        lib_src.contains("hot_reload_abi!"),
+        "firmware-ui/src/lib.rs must declare its hot-reload exports with hot_reload_abi!"
     );
     assert!(
-        !lib_src.contains("ABI_VERSION: u32 = 0"),
-        "firmware-ui ABI_VERSION must not be 0 - zero means unversioned"
+        !lib_src.contains("extern \"C\""),
+        "firmware-ui exports must not use raw extern \"C\" pointers; declare them \
+         in hot_reload_abi! so they are covered by the ABI hash"
     );
 }
 
-/// The render_ui signature must be tracked so changes are visible when
-/// bumping ABI_VERSION is forgotten. The build.rs hash enforces this: if
-/// the signature string changes, the hash changes and the mismatch surfaces
-/// in build output before any code runs.
+/// The ABI hash must be computed from the signature set at compile time and
+/// checked by the binary when it connects to the dylib.
 #[test]
 fn firmware_ui_has_abi_enforcement_mechanism() {
-    let build_rs = include_str!("../../firmware-ui/build.rs");
+    let abi_src = include_str!("../../firmware-ui/src/abi.rs");
     assert!(
-        build_rs.contains("render_ui") || build_rs.contains("RENDER_UI"),
-        "firmware-ui/build.rs must track the render_ui signature hash          to detect forgotten ABI_VERSION bumps"
+        abi_src.contains("ABI_HASH") && abi_src.contains("fnv1a_32(ABI_SIGNATURES)"),
+        "firmware-ui/src/abi.rs must derive ABI_HASH from the declared signatures"
     );
+    let emulator_src = include_str!("../examples/display_emulator.rs");
     assert!(
-        build_rs.contains("SIGNATURE_HASH") || build_rs.contains("signature"),
-        "firmware-ui/build.rs must emit a RENDER_UI_SIGNATURE_HASH env var for ABI tracking"
+        emulator_src.contains("HotUi::connect"),
+        "display_emulator must connect to the dylib through firmware_ui::HotUi::connect"
     );
 }
 
@@ -3047,7 +3046,7 @@ fn sdram_refresh_count_is_correct() {
     // 64 ms * 100 MHz / 1000 / 8192 - 20 = 761
     let count = firmware::sdram::REFRESH_COUNT;
     assert!(
-        count >= 740 && count <= 790,
+        (740..=790).contains(&count),
         "SDRAM refresh count must be 740-790 at 100 MHz FMC clock, got {count}"
     );
 }
@@ -3586,3 +3585,70 @@ fn steal_calls_are_guarded_by_atomic_once_check() {
         );
     }
 }
+
+// ── Watchdog heartbeats for every Embassy task ───────────────────────────────
+
+/// Every `#[embassy_executor::task]` must be in `firmware::watchdog::WATCHED_TASKS`
+/// and take a `Heartbeat`.
+///
+/// The main loop feeds the IWDG only when every registered task has ticked.
+/// A task spawned without a heartbeat can hang forever while the watchdog
+/// keeps being fed, so adding one must fail here rather than silently
+/// weakening the watchdog.
+#[test]
+fn every_embassy_task_has_a_heartbeat() {
+    fn scan_dir(dir: &std::path::Path, tasks: &mut Vec<(String, String)>) {
+        if !dir.exists() {
+            return;
+        }
+        for entry in std::fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                scan_dir(&path, tasks);
+            } else if path.extension().is_some_and(|e| e == "rs") {
+                let content = std::fs::read_to_string(&path).unwrap();
+                let mut rest = content.as_str();
+                while let Some(pos) = rest.find("\n#[embassy_executor::task]") {
+                    rest = &rest[pos + 1..];
+                    // Signature runs from the attribute to the opening brace.
+                    let signature = rest.split('{').next().unwrap_or("");
+                    let name = signature
+                        .split("fn ")
+                        .nth(1)
+                        .and_then(|s| s.split(['(', '<']).next())
+                        .unwrap_or("")
+                        .trim()
+                        .to_string();
+                    tasks.push((name, format!("{}: {}", path.display(), signature.trim())));
+                    rest = &rest[1..];
+                }
+            }
+        }
+    }
+
+    let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut tasks: Vec<(String, String)> = Vec::new();
+    scan_dir(&root.join("src"), &mut tasks);
+    scan_dir(&root.join("examples"), &mut tasks);
+    assert!(
+        tasks.iter().any(|(name, _)| name == "audio_task_embassy"),
+        "task scan found nothing -- did the attribute syntax change?"
+    );
+
+    let watched: Vec<&str> = firmware::watchdog::WATCHED_TASKS
+        .iter()
+        .map(|(_, name)| *name)
+        .collect();
+    for (name, signature) in &tasks {
+        assert!(
+            watched.contains(&name.as_str()),
+            "Embassy task `{name}` is not in firmware::watchdog::WATCHED_TASKS.\n\
+             Give it a TaskId, register it with HEARTBEATS at spawn and tick the\n\
+             Heartbeat from its loop, or the watchdog cannot catch it hanging.\n{signature}"
+        );
+        assert!(
+            signature.contains("Heartbeat"),
+            "Embassy task `{name}` must take a watchdog::Heartbeat parameter:\n{signature}"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Run the real SSD1677 driver against the emulator's controller mode.
+//!
+//! Run with: cargo test -p firmware --features emulator --test emulated_display
+#![cfg(feature = "emulator")]
+// Integration test file: expect/unwrap/panic are intentional test mechanisms.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::large_stack_arrays
+)]
+
+use eink_emulator::controller::cmd;
+use eink_emulator::{BusyFault, EinkColor};
+use embedded_graphics::pixelcolor::{BinaryColor, Gray4};
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use firmware::display::{DisplayError, EmulatedBus};
+use firmware::{DISPLAY_HEIGHT, DISPLAY_WIDTH, FRAMEBUFFER_SIZE};
+use platform::{DisplayDriver, EinkDisplay};
+
+#[tokio::test]
+async fn test_init_sequence_reaches_controller() {
+    let mut bus = EmulatedBus::gdem0397t81p();
+    bus.spi.controller_mut().start_recording();
+    let mut display = bus.into_driver();
+
+    display.init().await.unwrap();
+
+    let (mut spi, ..) = display.release();
+    let trace = spi.controller_mut().stop_recording().unwrap();
+    let commands: Vec<u8> = trace.commands().collect();
+    assert_eq!(commands.first(), Some(&cmd::SOFT_RESET));
+    assert!(commands.contains(&cmd::DATA_ENTRY_MODE));
+    assert_eq!(commands.last(), Some(&cmd::AUTO_WRITE_RED_RAM));
+    assert!(spi.controller().bw_ram().iter().all(|&b| b == 0xFF), "RAM cleared to white");
+}
+
+#[tokio::test]
+async fn test_full_refresh_renders_pixels_with_y_reversal() {
+    let mut display = EmulatedBus::gdem0397t81p().into_driver();
+    display.init().await.unwrap();
+
+    Rectangle::new(Point::zero(), Size::new(16, 4))
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(&mut display)
+        .unwrap();
+    display.refresh_full().await.unwrap();
+
+    let (spi, ..) = display.release();
+    let ctrl = spi.controller();
+    assert_eq!(ctrl.activations(), 1);
+
+    // Logical row 0 lives in RAM row 479 on this panel.
+    let last_ram_row = (DISPLAY_HEIGHT as usize - 1) * (DISPLAY_WIDTH as usize / 8);
+    assert_eq!(ctrl.bw_ram()[last_ram_row], 0x00);
+    assert_eq!(ctrl.bw_ram()[0], 0xFF);
+
+    let fb = &ctrl.emulator().framebuffer;
+    assert_eq!(fb.pixels[0], EinkColor::Gray(Gray4::BLACK));
+    let bottom_right = fb.pixels.len() - 1;
+    assert_eq!(fb.pixels[bottom_right], EinkColor::Gray(Gray4::WHITE));
+}
+
+#[tokio::test]
+async fn test_night_mode_inverts_in_the_controller() {
+    let mut display = EmulatedBus::gdem0397t81p().into_driver();
+    display.init().await.unwrap();
+    Rectangle::new(Point::zero(), Size::new(16, 4))
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(&mut display)
+        .unwrap();
+
+    display.set_inverted(true);
+    assert!(display.is_inverted());
+    let before = display.framebuffer().to_vec();
+    // The first refresh after the change is promoted to a full refresh.
+    display.refresh_partial().await.unwrap();
+    assert_eq!(
+        display.framebuffer(),
+        &before[..],
+        "framebuffer keeps its polarity"
+    );
+
+    let (spi, ..) = display.release();
+    let ctrl = spi.controller();
+    assert!(ctrl.bw_inverse());
+    let fb = &ctrl.emulator().framebuffer;
+    assert_eq!(fb.pixels[0], EinkColor::Gray(Gray4::WHITE));
+    let bottom_right = fb.pixels.len() - 1;
+    assert_eq!(fb.pixels[bottom_right], EinkColor::Gray(Gray4::BLACK));
+}
+
+#[tokio::test]
+async fn test_busy_timing_spans_refresh() {
+    let mut display = EmulatedBus::gdem0397t81p().into_driver();
+    display.init().await.unwrap();
+    display.update_buffer(&[0xFF; FRAMEBUFFER_SIZE]).await.unwrap();
+
+    let (spi, dc, rst, busy, delay) = display.release();
+    let before = delay.now_ms();
+    let mut display = firmware::Ssd1677::new(spi, dc, rst, busy, delay);
+    display.refresh_full().await.unwrap();
+
+    let (spi, _, _, _, delay) = display.release();
+    let waited = delay.now_ms() - before;
+    assert!(waited > 0, "driver must poll BUSY through the refresh");
+    assert!(!spi.controller().is_busy(), "driver returned only after BUSY dropped");
+}
+
+#[tokio::test]
+async fn test_stuck_busy_times_out_and_reset_recovers() {
+    let mut display = EmulatedBus::gdem0397t81p().into_driver();
+    display.init().await.unwrap();
+
+    let (mut spi, dc, rst, busy, delay) = display.release();
+    spi.controller_mut()
+        .inject_busy_fault(BusyFault::StuckUntilReset);
+    let before = delay.now_ms();
+    let mut display = firmware::Ssd1677::new(spi, dc, rst, busy, delay);
+    assert_eq!(display.refresh_full().await, Err(DisplayError::Timeout));
+
+    // wake() is the driver's recovery path: RST pulse, then a full init.
+    display.wake().await.unwrap();
+    display.refresh_full().await.unwrap();
+
+    let (spi, _, _, _, delay) = display.release();
+    assert!(
+        delay.now_ms() - before >= 2_000,
+        "driver polled for its whole timeout before giving up"
+    );
+    assert_eq!(spi.controller().busy_fault(), None);
+    assert_eq!(spi.controller().activations(), 2);
+}
+
+#[tokio::test]
+async fn test_stuck_high_busy_fails_init() {
+    let mut bus = EmulatedBus::gdem0397t81p();
+    bus.spi
+        .controller_mut()
+        .inject_busy_fault(BusyFault::StuckHigh);
+    let mut display = bus.into_driver();
+
+    assert_eq!(display.init().await, Err(DisplayError::Timeout));
+    assert_eq!(display.wake().await, Err(DisplayError::Timeout));
+}
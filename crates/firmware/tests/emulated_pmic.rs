@@ -0,0 +1,138 @@
+//! Run the BQ25895 driver code against the emulated charger and battery.
+//!
+//! Run with: cargo test -p firmware --features emulator --test emulated_pmic
+#![cfg(feature = "emulator")]
+// Integration test file: expect/unwrap/panic are intentional test mechanisms.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects
+)]
+
+use core::time::Duration;
+
+use embedded_hal::i2c::{ErrorKind, I2c};
+use firmware::emulated_pmic::{EmulatedBq25895, VbusSource};
+use platform::bq25895::{
+    bq25895_init, read_status, Bq25895Status, ChargeStatus, BQ25895_I2C_ADDR, REG04_CHARGE_VOLTAGE,
+    REG14_DEVICE_ID, VBUS_STAT_SDP, VREG_4208MV,
+};
+use platform::{BatteryLevel, PowerMonitor};
+
+const HALF_HOUR: Duration = Duration::from_secs(30 * 60);
+
+fn status(pmic: &mut EmulatedBq25895) -> Bq25895Status {
+    read_status(pmic, BQ25895_I2C_ADDR).unwrap()
+}
+
+fn initialised(capacity_mah: u32, percent: u8) -> EmulatedBq25895 {
+    let mut pmic = EmulatedBq25895::new(capacity_mah, percent);
+    bq25895_init(&mut pmic, BQ25895_I2C_ADDR).unwrap();
+    pmic
+}
+
+#[test]
+fn test_init_writes_land_in_the_register_file() {
+    let mut pmic = initialised(3_000, 50);
+    let mut regs = [0u8; 1];
+    pmic.write_read(BQ25895_I2C_ADDR, &[REG04_CHARGE_VOLTAGE], &mut regs)
+        .unwrap();
+    assert_eq!(regs[0], VREG_4208MV);
+    pmic.write_read(BQ25895_I2C_ADDR, &[REG14_DEVICE_ID], &mut regs)
+        .unwrap();
+    assert_eq!(regs[0] & 0b0011_1000, 0b0011_1000, "PN = BQ25895");
+
+    assert!(matches!(
+        pmic.write(0x6B, &[REG04_CHARGE_VOLTAGE, 0]),
+        Err(ErrorKind::NoAcknowledge(_))
+    ));
+}
+
+#[test]
+fn test_discharge_passes_low_then_critical() {
+    let mut pmic = initialised(3_000, 100);
+    pmic.set_load_ma(200);
+
+    let mut last_mv = u32::MAX;
+    let mut levels = Vec::new();
+    let critical_at = loop {
+        let st = status(&mut pmic);
+        assert!(st.battery_mv <= last_mv, "voltage rises while discharging");
+        last_mv = st.battery_mv;
+        assert!(!st.is_usb_connected() && !st.is_charging());
+
+        let level = st.battery_level().unwrap();
+        if levels.last() != Some(&level) {
+            levels.push(level);
+        }
+        if level == BatteryLevel::Critical {
+            break pmic.elapsed();
+        }
+        assert!(
+            pmic.elapsed() < Duration::from_secs(20 * 3600),
+            "never critical"
+        );
+        pmic.advance(HALF_HOUR);
+    };
+
+    assert_eq!(
+        levels,
+        [
+            BatteryLevel::Normal,
+            BatteryLevel::Low,
+            BatteryLevel::Critical
+        ]
+    );
+    // 3000 mAh at 200 mA is 15 h; critical comes a little before empty.
+    let hours = critical_at.as_secs() / 3600;
+    assert!((13..=15).contains(&hours), "critical after {hours} h");
+}
+
+#[test]
+fn test_charge_runs_to_termination() {
+    let mut pmic = initialised(3_000, 20);
+    pmic.set_load_ma(100);
+    pmic.plug(VbusSource::Dcp);
+
+    let st = status(&mut pmic);
+    assert!(st.is_usb_connected() && st.is_charging());
+    assert_eq!(st.charge_status(), ChargeStatus::FastCharging);
+    assert!(st.charge_current_ma > 1_000, "{st:?}");
+    assert_eq!(st.battery_level(), Some(BatteryLevel::Normal));
+
+    while pmic.charge_status() != ChargeStatus::Done {
+        assert!(pmic.elapsed() < Duration::from_secs(6 * 3600), "never done");
+        pmic.advance(Duration::from_secs(60));
+    }
+    assert!(pmic.battery().percent() >= 98);
+    let st = status(&mut pmic);
+    assert!(!st.is_charging() && st.is_usb_connected());
+    assert_eq!(st.charge_current_ma, 0);
+
+    // Unplugging hands the load back to the cell.
+    pmic.unplug();
+    let st = status(&mut pmic);
+    assert!(!st.is_usb_connected());
+    assert_eq!(st.charge_status(), ChargeStatus::NotCharging);
+    assert!(pmic.battery_current_ma() < 0);
+}
+
+#[test]
+fn test_usb_port_limit_and_supplement_mode() {
+    let mut pmic = initialised(3_000, 50);
+    pmic.plug(VbusSource::Sdp);
+    pmic.set_load_ma(200);
+    let st = status(&mut pmic);
+    assert_eq!(st.vbus_status(), VBUS_STAT_SDP);
+    assert!(st.charge_current_ma <= 300, "{st:?}");
+
+    // More load than the port supplies: the cell makes up the difference.
+    pmic.set_load_ma(800);
+    let before = pmic.battery().remaining_mah();
+    pmic.advance(Duration::from_secs(3600));
+    assert_eq!(pmic.battery_current_ma(), -300);
+    assert_eq!(before - pmic.battery().remaining_mah(), 300);
+    assert!(!status(&mut pmic).is_charging());
+}
@@ -23,6 +23,7 @@ use firmware::audio::amp::mock::MockAmp;
 use firmware::audio::amp::AmpDriver;
 use firmware::audio::dac::mock::MockDac;
 use platform::{AudioCodec, AudioConfig, OversamplingFilter};
+use ui::audio_settings::AudioSettings;
 
 /// Verify MockDac implements AudioCodec correctly
 #[tokio::test]
@@ -102,6 +103,27 @@ async fn test_mock_dac_filter_variants() {
     }
 }
 
+/// Verify a filter picked on the audio settings screen reaches the DAC and
+/// survives re-initialisation
+#[tokio::test]
+async fn test_audio_settings_filter_reaches_dac() {
+    let mut dac = MockDac::new();
+    dac.init(AudioConfig::default()).await.unwrap();
+    let mut settings = AudioSettings::new(OversamplingFilter::ALL.len(), dac.filter.index());
+
+    settings.scroll(4);
+    let filter = settings
+        .confirm()
+        .and_then(OversamplingFilter::from_index)
+        .expect("selection changed");
+    dac.set_filter(filter).await.unwrap();
+    assert_eq!(dac.filter, OversamplingFilter::ApodizingFastRollOff);
+
+    dac.init(AudioConfig::default()).await.unwrap();
+    assert_eq!(dac.filter, OversamplingFilter::ApodizingFastRollOff);
+    assert_eq!(settings.confirm(), None, "re-confirming writes nothing");
+}
+
 /// Verify that MockDac start/stop cycle updates the started flag correctly
 #[tokio::test]
 async fn test_mock_dac_start_stop_lifecycle() {
@@ -86,24 +86,28 @@ fn firmware_has_runtime_dma_buffer_address_assertion() {
     );
 }
 
-/// Watchdog guard must use AtomicBool with explicit Ordering (not just any AtomicBool).
+/// Watchdog heartbeats must use atomics with explicit Ordering.
 /// Relaxed ordering is wrong for a visibility guarantee across task boundaries.
 #[test]
 fn watchdog_heartbeat_uses_correct_atomic_ordering() {
-    let main_rs = include_str!("../src/main.rs");
-    // Must have AtomicBool AND explicit Ordering usage
+    let watchdog_rs = include_str!("../src/watchdog.rs");
+    // Must have atomics AND explicit Ordering usage
     assert!(
-        main_rs.contains("AtomicBool"),
-        "Watchdog guard must use AtomicBool for per-task heartbeat flags."
+        watchdog_rs.contains("AtomicU32"),
+        "Watchdog guard must use atomics for per-task heartbeat flags."
     );
     assert!(
-        main_rs.contains("Ordering::Release")
-            || main_rs.contains("Ordering::AcqRel")
-            || main_rs.contains("Ordering::SeqCst"),
-        "AtomicBool watchdog heartbeat must use explicit Ordering (Release/AcqRel).\n\
+        watchdog_rs.contains("Ordering::Release")
+            || watchdog_rs.contains("Ordering::AcqRel")
+            || watchdog_rs.contains("Ordering::SeqCst"),
+        "Watchdog heartbeat must use explicit Ordering (Release/AcqRel).\n\
          Ordering::Relaxed does not provide the happens-before guarantee needed for\n\
          the main loop to observe task heartbeat stores from other tasks."
     );
+    assert!(
+        !watchdog_rs.contains("Ordering::Relaxed"),
+        "Watchdog heartbeats must not use Ordering::Relaxed."
+    );
 }
 
 /// All large static DMA buffers must pair #[link_section] with sound ownership.
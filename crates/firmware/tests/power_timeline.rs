@@ -0,0 +1,260 @@
+//! Power timelines: display, audio and CPU over hours of use.
+//!
+//! Each test drives a usage timeline — play 40 min, idle 5 min, standby
+//! overnight — through the display's [`PowerTracker`] and the emulated
+//! BQ25895, and checks display energy, display state residency and the
+//! charge taken from the cell against budgets.  Both run on virtual time,
+//! so a night of standby takes milliseconds and a battery regression shows
+//! up in CI instead of after a week on the bench.
+//!
+//! The firmware has no power policy engine yet.  [`Device`] applies the
+//! states it is meant to choose: audio and decode only while playing, the
+//! panel idle between refreshes and asleep in standby, the MCU in Stop mode
+//! overnight.  Loads other than the panel are the assumed figures below;
+//! replace them with measurements when a board is on the bench.
+//!
+//! Run with: cargo test -p firmware --features emulator --test power_timeline
+#![cfg(feature = "emulator")]
+// Integration test file: expect/unwrap/panic are intentional test mechanisms.
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects
+)]
+
+use core::time::Duration;
+
+use eink_emulator::{
+    PowerProfile, PowerState, PowerStats, PowerTracker, VirtualClock, WaveformMode,
+};
+use eink_specs::displays::GDEM0397T81P;
+use eink_specs::DisplayMs;
+use firmware::emulated_pmic::EmulatedBq25895;
+use platform::bq25895::{bq25895_init, read_status, BQ25895_I2C_ADDR};
+use platform::{BatteryLevel, PowerMonitor};
+
+const PROFILE: &PowerProfile = &PowerProfile::GDEM0397T81P;
+const BATTERY_MAH: u32 = 3_000;
+
+/// MCU decoding plus SD reads (assumed).
+const DECODE_MA: u32 = 45;
+/// DAC and headphone amplifier while audio plays (assumed).
+const AUDIO_MA: u32 = 30;
+/// MCU waiting for input with audio powered down (assumed).
+const AWAKE_MA: u32 = 6;
+/// Stop mode: RTC, SDRAM self-refresh and PMIC quiescent (assumed).
+const STANDBY_MA: u32 = 1;
+
+/// Framebuffer upload to the controller over SPI.
+const TRANSFER_MS: u64 = 150;
+/// Length of every track in the timeline.
+const TRACK_MS: u64 = 4 * 60 * 1000;
+/// Now Playing redraws its progress bar this often.
+const PROGRESS_MS: u64 = 60 * 1000;
+
+const PLAY_MS: u64 = 40 * 60 * 1000;
+const IDLE_MS: u64 = 5 * 60 * 1000;
+const NIGHT_MS: u64 = 8 * 60 * 60 * 1000;
+
+/// Display energy for play, idle and a night of standby (measured: 1_555).
+const DAY_DISPLAY_UWH: u64 = 1_900;
+/// Time the panel spends transferring and refreshing over the same day
+/// (measured: 24_750).
+const DAY_ACTIVE_MS: u64 = 30_000;
+/// Charge taken from the cell over the same day (measured: 60).
+const DAY_MAH: u32 = 70;
+/// Continuous playback from full to the low-battery warning (measured: 37).
+const MIN_PLAY_HOURS: u64 = 30;
+
+/// What the device is doing, which sets every load except the panel's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Playing,
+    Awake,
+    Standby,
+}
+
+impl Mode {
+    const fn system_ma(self) -> u32 {
+        match self {
+            Self::Playing => DECODE_MA + AUDIO_MA,
+            Self::Awake => AWAKE_MA,
+            Self::Standby => STANDBY_MA,
+        }
+    }
+}
+
+/// Panel current in `state`, as the tracker integrates it.
+fn panel_ua(state: PowerState) -> u32 {
+    match state {
+        PowerState::Idle => PROFILE.idle_current_ua,
+        PowerState::Sleeping => PROFILE.sleep_current_ua,
+        PowerState::Refreshing { flash_count } => {
+            PROFILE.refresh_current_ua + u32::from(flash_count) * PROFILE.refresh_boost_ua
+        }
+        PowerState::Initializing => PROFILE.init_current_ua,
+        PowerState::TransferringBuffer => PROFILE.sram_transfer_current_ua,
+    }
+}
+
+/// Display tracker and charger sharing one virtual clock.
+struct Device {
+    clock: VirtualClock,
+    display: PowerTracker,
+    pmic: EmulatedBq25895,
+    mode: Mode,
+}
+
+impl Device {
+    /// Awake, panel idle, on a cell at `percent`.
+    fn new(percent: u8) -> Self {
+        let clock = VirtualClock::new();
+        let mut display = PowerTracker::new(PROFILE);
+        display.set_clock(clock.clone());
+        let mut pmic = EmulatedBq25895::new(BATTERY_MAH, percent);
+        bq25895_init(&mut pmic, BQ25895_I2C_ADDR).unwrap();
+        Self {
+            clock,
+            display,
+            pmic,
+            mode: Mode::Awake,
+        }
+    }
+
+    /// Stay in the current mode and panel state for `ms`.
+    fn hold(&mut self, ms: u64) {
+        let load_ua = self.mode.system_ma() * 1_000 + panel_ua(self.display.state());
+        self.pmic.set_load_ma((load_ua + 500) / 1_000);
+        self.pmic.advance(Duration::from_millis(ms));
+        self.clock.advance(ms);
+    }
+
+    /// Upload a frame and refresh it with `waveform`, then idle the panel.
+    fn refresh(&mut self, waveform: WaveformMode, duration: DisplayMs) {
+        self.display.transition_to(PowerState::TransferringBuffer);
+        self.hold(TRANSFER_MS);
+        self.display.transition_to(PowerState::Refreshing {
+            flash_count: waveform.flash_count(),
+        });
+        self.hold(u64::from(duration.get()));
+        self.display.transition_to(PowerState::Idle);
+    }
+
+    /// Play `ms` of tracks: a full refresh for the first Now Playing
+    /// frame, a partial one for each next track and the progress bar.
+    fn play(&mut self, ms: u64) {
+        self.mode = Mode::Playing;
+        self.refresh(WaveformMode::GC16, GDEM0397T81P.full_refresh_ms);
+        let mut played = 0;
+        while played < ms {
+            self.hold(PROGRESS_MS);
+            played += PROGRESS_MS;
+            let waveform = if played % TRACK_MS == 0 {
+                WaveformMode::GL16
+            } else {
+                WaveformMode::DU
+            };
+            self.refresh(waveform, GDEM0397T81P.partial_refresh_ms);
+        }
+    }
+
+    /// Paused on Now Playing for `ms`.
+    fn idle(&mut self, ms: u64) {
+        self.mode = Mode::Awake;
+        self.refresh(WaveformMode::DU, GDEM0397T81P.partial_refresh_ms);
+        self.hold(ms);
+    }
+
+    /// Draw the standby screen, sleep the panel and stop the MCU for `ms`.
+    fn standby(&mut self, ms: u64) {
+        self.mode = Mode::Awake;
+        self.refresh(WaveformMode::GC16, GDEM0397T81P.full_refresh_ms);
+        self.display.transition_to(PowerState::Sleeping);
+        self.mode = Mode::Standby;
+        self.hold(ms);
+    }
+
+    /// Display statistics up to now.
+    fn display_stats(&mut self) -> PowerStats {
+        self.display.transition_to(self.display.state());
+        self.display.stats().clone()
+    }
+
+    fn battery_level(&mut self) -> BatteryLevel {
+        let status = read_status(&mut self.pmic, BQ25895_I2C_ADDR).unwrap();
+        assert!(!status.is_usb_connected());
+        status.battery_level().unwrap()
+    }
+}
+
+#[test]
+fn test_day_of_use_stays_within_budget() {
+    let mut device = Device::new(80);
+    let start_mah = device.pmic.battery().remaining_mah();
+
+    device.play(PLAY_MS);
+    device.idle(IDLE_MS);
+    device.standby(NIGHT_MS);
+
+    let stats = device.display_stats();
+    assert_eq!(
+        Duration::from_millis(stats.total_runtime_ms()),
+        device.pmic.elapsed()
+    );
+    assert_eq!(stats.sleep_time_ms, NIGHT_MS);
+    assert!(
+        stats.active_time_ms <= DAY_ACTIVE_MS,
+        "panel active {} ms, budget {DAY_ACTIVE_MS}",
+        stats.active_time_ms
+    );
+    assert!(
+        stats.idle_time_ms >= PLAY_MS + IDLE_MS - DAY_ACTIVE_MS,
+        "panel idle only {} ms",
+        stats.idle_time_ms
+    );
+    assert!(
+        stats.total_energy_uwh <= DAY_DISPLAY_UWH,
+        "display used {} µWh, budget {DAY_DISPLAY_UWH}",
+        stats.total_energy_uwh
+    );
+
+    let used_mah = start_mah - device.pmic.battery().remaining_mah();
+    assert!(used_mah <= DAY_MAH, "used {used_mah} mAh, budget {DAY_MAH}");
+    assert_eq!(device.battery_level(), BatteryLevel::Normal);
+}
+
+#[test]
+fn test_standby_overnight_costs_under_one_percent() {
+    let mut device = Device::new(60);
+    let start_mah = device.pmic.battery().remaining_mah();
+    device.standby(NIGHT_MS);
+
+    let stats = device.display_stats();
+    let percentages = stats.state_percentages();
+    assert!(
+        percentages.sleep > 99.9,
+        "panel asleep {:.2} % of the night",
+        percentages.sleep
+    );
+    let used_mah = start_mah - device.pmic.battery().remaining_mah();
+    assert!(used_mah <= BATTERY_MAH / 100, "standby used {used_mah} mAh");
+}
+
+#[test]
+fn test_continuous_playback_reaches_the_budgeted_hours() {
+    let mut device = Device::new(100);
+    let hours = loop {
+        device.play(60 * 60 * 1000);
+        let hours = device.pmic.elapsed().as_secs() / 3_600;
+        if device.battery_level() != BatteryLevel::Normal {
+            break hours;
+        }
+        assert!(hours < 200, "battery never ran low");
+    };
+    assert!(
+        hours >= MIN_PLAY_HOURS,
+        "low battery after {hours} h of playback, budget {MIN_PLAY_HOURS} h"
+    );
+}
@@ -56,6 +56,7 @@ fn make_meta(n: u32) -> TrackMeta {
         artist: heapless::String::try_from(artist.as_str()).unwrap(),
         album: heapless::String::try_from(album.as_str()).unwrap(),
         file_path: heapless::String::try_from(file_path.as_str()).unwrap(),
+        loudness: None,
     }
 }
 
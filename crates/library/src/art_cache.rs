@@ -0,0 +1,571 @@
+//! ArtCache — album art thumbnails resident in external SDRAM.
+//!
+//! Pre-dithered 2bpp (4-level Gray4) art is read from
+//! `{root}/art/{hi:02x}/{album_id:08x}.raw` on the SD card and kept in the
+//! [`RamRegion::ALBUM_ART`] window of SDRAM, so flicking back and forth in
+//! the album browser never waits on SD I/O.
+//!
+//! # Layout
+//!
+//! The region is split into fixed-size slots of [`SLOT_BYTES`]; slot `i`
+//! starts at `region.offset + i * SLOT_BYTES`.  Only the slot table (album
+//! id, length, recency stamp) lives in internal SRAM — roughly 12 bytes per
+//! slot.
+//!
+//! ```text
+//! ALBUM_ART (8 MB)
+//! ┌────────┬────────┬────────┬─────┬────────┐
+//! │ slot 0 │ slot 1 │ slot 2 │ ... │ slot N │   N = region.len / SLOT_BYTES
+//! └────────┴────────┴────────┴─────┴────────┘
+//!   16 KB each (240×240 @ 2bpp = 14 400 B)
+//! ```
+//!
+//! # Eviction
+//!
+//! Least-recently-used.  Every hit or insert stamps the slot with a
+//! monotonically increasing counter; when the cache is full the slot with
+//! the smallest stamp is overwritten.
+//!
+//! # Prefetch
+//!
+//! [`ArtCache::prefetch_plan`] lists the neighbours of the focused album in
+//! browse order (next first, then previous, widening outward) that are not
+//! yet resident.  The browse task loads each from SD and calls
+//! [`ArtCache::insert_prefetched`].  Prefetched entries are counted
+//! separately so [`ArtCacheStats::prefetch_hits`] shows whether the radius
+//! is worth its SD bandwidth.
+
+use heapless::Vec;
+use platform::{ExternalRam, RamRegion};
+
+/// Bytes reserved per cached thumbnail (240×240 px at 2bpp, rounded up).
+pub const SLOT_BYTES: usize = 16 * 1024;
+
+/// Slot-table capacity: enough to fill the 8 MB `ALBUM_ART` region.
+pub const ART_CACHE_SLOTS: usize = 512;
+
+/// Upper bound on albums returned by one [`ArtCache::prefetch_plan`] call.
+pub const MAX_PREFETCH: usize = 8;
+
+/// Error from [`ArtCache`] operations.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ArtCacheError<E: core::fmt::Debug> {
+    /// The underlying SDRAM access failed.
+    Ram(E),
+    /// Thumbnail data exceeds [`SLOT_BYTES`].
+    TooLarge,
+    /// Caller's buffer is smaller than the cached thumbnail.
+    BufferTooSmall,
+}
+
+/// Cache counters, surfaced in the emulator debug panel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArtCacheStats {
+    /// Lookups served from SDRAM.
+    pub hits: u32,
+    /// Lookups that had to go to the SD card.
+    pub misses: u32,
+    /// Entries overwritten to make room.
+    pub evictions: u32,
+    /// Entries written (demand loads and prefetches).
+    pub inserts: u32,
+    /// Entries written by prefetch.
+    pub prefetched: u32,
+    /// Hits on an entry that was prefetched and not yet used.
+    pub prefetch_hits: u32,
+    /// Slots currently occupied.
+    pub resident: u16,
+    /// Total slots available.
+    pub capacity: u16,
+}
+
+impl ArtCacheStats {
+    /// Hit rate in whole percent (0 when no lookups were made).
+    pub fn hit_rate_percent(&self) -> u8 {
+        let total = u64::from(self.hits).saturating_add(u64::from(self.misses));
+        if total == 0 {
+            return 0;
+        }
+        let pct = u64::from(self.hits)
+            .saturating_mul(100)
+            .checked_div(total)
+            .unwrap_or(0);
+        u8::try_from(pct).unwrap_or(100)
+    }
+}
+
+/// Slot-table entry for one resident thumbnail.
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    album_id: u32,
+    len: u16,
+    stamp: u32,
+    /// Inserted by prefetch and not yet read.
+    unused_prefetch: bool,
+}
+
+/// LRU cache of album art in external SDRAM.
+///
+/// `N` is the slot-table capacity; the effective capacity is the smaller of
+/// `N` and `region.len / SLOT_BYTES`.  Table entry `i` always describes
+/// SDRAM slot `i`; `None` marks a slot freed by [`invalidate`](Self::invalidate).
+pub struct ArtCache<R: ExternalRam, const N: usize = ART_CACHE_SLOTS> {
+    ram: R,
+    region: RamRegion,
+    slots: Vec<Option<Slot>, N>,
+    capacity: usize,
+    clock: u32,
+    stats: ArtCacheStats,
+}
+
+impl<R: ExternalRam, const N: usize> ArtCache<R, N> {
+    /// Cache backed by the standard [`RamRegion::ALBUM_ART`] window.
+    pub fn new(ram: R) -> Self {
+        Self::with_region(ram, RamRegion::ALBUM_ART)
+    }
+
+    /// Cache backed by an arbitrary region of `ram`.
+    pub fn with_region(ram: R, region: RamRegion) -> Self {
+        let capacity = region.len.checked_div(SLOT_BYTES).unwrap_or(0).min(N);
+        Self {
+            ram,
+            region,
+            slots: Vec::new(),
+            capacity,
+            clock: 0,
+            stats: ArtCacheStats {
+                capacity: u16::try_from(capacity).unwrap_or(u16::MAX),
+                ..ArtCacheStats::default()
+            },
+        }
+    }
+
+    /// Maximum number of thumbnails held at once.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of thumbnails currently resident.
+    pub fn len(&self) -> usize {
+        self.slots.iter().flatten().count()
+    }
+
+    /// `true` when nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `true` if `album_id` is resident.  Does not count as a lookup.
+    pub fn contains(&self, album_id: u32) -> bool {
+        self.position(album_id).is_some()
+    }
+
+    /// Counters since construction or the last [`reset_stats`](Self::reset_stats).
+    pub fn stats(&self) -> ArtCacheStats {
+        ArtCacheStats {
+            resident: u16::try_from(self.len()).unwrap_or(u16::MAX),
+            ..self.stats
+        }
+    }
+
+    /// Zero all counters (residency is unaffected).
+    pub fn reset_stats(&mut self) {
+        self.stats = ArtCacheStats {
+            capacity: self.stats.capacity,
+            ..ArtCacheStats::default()
+        };
+    }
+
+    /// Copy the thumbnail for `album_id` into `buf`.
+    ///
+    /// Returns `Ok(Some(len))` on a hit and `Ok(None)` on a miss; the caller
+    /// then loads from SD and calls [`insert`](Self::insert).
+    ///
+    /// # Errors
+    ///
+    /// `BufferTooSmall` if `buf` cannot hold the entry; `Ram` on SDRAM failure.
+    pub fn get(
+        &mut self,
+        album_id: u32,
+        buf: &mut [u8],
+    ) -> Result<Option<usize>, ArtCacheError<R::Error>> {
+        let Some(idx) = self.position(album_id) else {
+            self.stats.misses = self.stats.misses.saturating_add(1);
+            return Ok(None);
+        };
+        let stamp = self.tick();
+        let offset = self.slot_offset(idx);
+        let Some(Some(slot)) = self.slots.get_mut(idx) else {
+            return Ok(None);
+        };
+        let len = usize::from(slot.len);
+        let dest = buf.get_mut(..len).ok_or(ArtCacheError::BufferTooSmall)?;
+        self.ram.read(offset, dest).map_err(ArtCacheError::Ram)?;
+        slot.stamp = stamp;
+        self.stats.hits = self.stats.hits.saturating_add(1);
+        if core::mem::take(&mut slot.unused_prefetch) {
+            self.stats.prefetch_hits = self.stats.prefetch_hits.saturating_add(1);
+        }
+        Ok(Some(len))
+    }
+
+    /// Store a demand-loaded thumbnail, evicting the LRU entry if full.
+    ///
+    /// Re-inserting a resident album overwrites it in place.
+    ///
+    /// # Errors
+    ///
+    /// `TooLarge` if `data` exceeds [`SLOT_BYTES`]; `Ram` on SDRAM failure
+    /// (the album is then not resident).
+    pub fn insert(&mut self, album_id: u32, data: &[u8]) -> Result<(), ArtCacheError<R::Error>> {
+        self.store(album_id, data, false)
+    }
+
+    /// Store a thumbnail loaded ahead of need by [`prefetch_plan`](Self::prefetch_plan).
+    ///
+    /// # Errors
+    ///
+    /// As [`insert`](Self::insert).
+    pub fn insert_prefetched(
+        &mut self,
+        album_id: u32,
+        data: &[u8],
+    ) -> Result<(), ArtCacheError<R::Error>> {
+        self.store(album_id, data, true)
+    }
+
+    /// Drop `album_id` from the cache (e.g. after its art file changed).
+    pub fn invalidate(&mut self, album_id: u32) {
+        if let Some(idx) = self.position(album_id) {
+            self.free(idx);
+        }
+    }
+
+    /// Drop every entry.  SDRAM contents are left in place.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+
+    /// Albums around `focus` in `browse_order` that should be loaded now.
+    ///
+    /// Walks outward from `focus` — `+1, -1, +2, -2, …` up to `radius` —
+    /// skipping albums already resident.  Never returns more than
+    /// [`MAX_PREFETCH`] ids, nor more than the cache could hold alongside
+    /// the focused album.
+    pub fn prefetch_plan(
+        &self,
+        browse_order: &[u32],
+        focus: usize,
+        radius: usize,
+    ) -> Vec<u32, MAX_PREFETCH> {
+        let mut plan = Vec::new();
+        let budget = self.capacity.saturating_sub(1).min(MAX_PREFETCH);
+        for distance in 1..=radius {
+            let ahead = focus.checked_add(distance);
+            let behind = focus.checked_sub(distance);
+            for idx in [ahead, behind].into_iter().flatten() {
+                if plan.len() >= budget {
+                    return plan;
+                }
+                if let Some(&id) = browse_order.get(idx) {
+                    if !self.contains(id) && !plan.contains(&id) {
+                        let _ = plan.push(id);
+                    }
+                }
+            }
+        }
+        plan
+    }
+
+    fn store(
+        &mut self,
+        album_id: u32,
+        data: &[u8],
+        prefetched: bool,
+    ) -> Result<(), ArtCacheError<R::Error>> {
+        let len = u16::try_from(data.len())
+            .ok()
+            .filter(|_| data.len() <= SLOT_BYTES)
+            .ok_or(ArtCacheError::TooLarge)?;
+        if self.capacity == 0 {
+            return Err(ArtCacheError::TooLarge);
+        }
+
+        let idx = match self.position(album_id) {
+            Some(idx) => idx,
+            None => match self.slots.iter().position(Option::is_none) {
+                Some(idx) => idx,
+                None if self.slots.len() < self.capacity => {
+                    let idx = self.slots.len();
+                    if self.slots.push(None).is_err() {
+                        return Err(ArtCacheError::TooLarge);
+                    }
+                    idx
+                }
+                None => {
+                    self.stats.evictions = self.stats.evictions.saturating_add(1);
+                    self.lru_index()
+                }
+            },
+        };
+
+        let offset = self.slot_offset(idx);
+        if let Err(e) = self.ram.write(offset, data) {
+            // The slot's previous contents may be partly overwritten.
+            self.free(idx);
+            return Err(ArtCacheError::Ram(e));
+        }
+
+        let stamp = self.tick();
+        if let Some(entry) = self.slots.get_mut(idx) {
+            *entry = Some(Slot {
+                album_id,
+                len,
+                stamp,
+                unused_prefetch: prefetched,
+            });
+        }
+        self.stats.inserts = self.stats.inserts.saturating_add(1);
+        if prefetched {
+            self.stats.prefetched = self.stats.prefetched.saturating_add(1);
+        }
+        Ok(())
+    }
+
+    fn position(&self, album_id: u32) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|s| s.is_some_and(|s| s.album_id == album_id))
+    }
+
+    fn free(&mut self, idx: usize) {
+        if let Some(entry) = self.slots.get_mut(idx) {
+            *entry = None;
+        }
+    }
+
+    fn lru_index(&self) -> usize {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.map(|s| (i, s.stamp)))
+            .min_by_key(|&(_, stamp)| stamp)
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn slot_offset(&self, idx: usize) -> usize {
+        self.region
+            .offset
+            .saturating_add(idx.saturating_mul(SLOT_BYTES))
+    }
+
+    fn tick(&mut self) -> u32 {
+        self.clock = self.clock.wrapping_add(1);
+        if self.clock == 0 {
+            // Wrapped after 4 billion accesses: restart stamps from 1 while
+            // preserving the relative order of resident entries.  Slots are
+            // not reordered: their table index is their SDRAM address.
+            let mut order: Vec<(u32, usize), N> = self
+                .slots
+                .iter()
+                .enumerate()
+                .filter_map(|(i, s)| s.map(|s| (s.stamp, i)))
+                .collect();
+            order.sort_unstable();
+            let mut next = 0u32;
+            for (_, idx) in order {
+                next = next.saturating_add(1);
+                if let Some(Some(slot)) = self.slots.get_mut(idx) {
+                    slot.stamp = next;
+                }
+            }
+            self.clock = next.saturating_add(1);
+        }
+        self.clock
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+#[allow(clippy::indexing_slicing)] // Test helpers use direct indexing safely
+#[allow(clippy::arithmetic_side_effects)] // Test helpers use arithmetic safely
+mod tests {
+    use super::*;
+
+    /// Heap-backed SDRAM stand-in.
+    struct VecRam(std::vec::Vec<u8>);
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct OutOfBounds;
+
+    impl ExternalRam for VecRam {
+        type Error = OutOfBounds;
+
+        fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), OutOfBounds> {
+            let src = self.0.get(offset..offset + buf.len()).ok_or(OutOfBounds)?;
+            buf.copy_from_slice(src);
+            Ok(())
+        }
+
+        fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), OutOfBounds> {
+            let dst = self
+                .0
+                .get_mut(offset..offset + data.len())
+                .ok_or(OutOfBounds)?;
+            dst.copy_from_slice(data);
+            Ok(())
+        }
+
+        fn zero(&mut self, offset: usize, len: usize) -> Result<(), OutOfBounds> {
+            self.write(offset, &std::vec![0; len])
+        }
+
+        fn capacity(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    /// Three-slot cache over a small region.
+    fn cache() -> ArtCache<VecRam, 8> {
+        let region = RamRegion {
+            offset: 0,
+            len: 3 * SLOT_BYTES,
+        };
+        ArtCache::with_region(VecRam(std::vec![0; 3 * SLOT_BYTES]), region)
+    }
+
+    fn get(c: &mut ArtCache<VecRam, 8>, id: u32) -> Option<std::vec::Vec<u8>> {
+        let mut buf = std::vec![0; SLOT_BYTES];
+        c.get(id, &mut buf).unwrap().map(|n| buf[..n].to_vec())
+    }
+
+    #[test]
+    fn test_capacity_limited_by_region() {
+        let c = cache();
+        assert_eq!(c.capacity(), 3);
+        assert_eq!(c.stats().capacity, 3);
+    }
+
+    #[test]
+    fn test_miss_then_hit() {
+        let mut c = cache();
+        assert_eq!(get(&mut c, 7), None);
+        c.insert(7, &[1, 2, 3]).unwrap();
+        assert_eq!(get(&mut c, 7), Some(std::vec![1, 2, 3]));
+        let s = c.stats();
+        assert_eq!((s.hits, s.misses, s.inserts, s.resident), (1, 1, 1, 1));
+        assert_eq!(s.hit_rate_percent(), 50);
+    }
+
+    #[test]
+    fn test_lru_entry_is_evicted() {
+        let mut c = cache();
+        c.insert(1, &[1]).unwrap();
+        c.insert(2, &[2]).unwrap();
+        c.insert(3, &[3]).unwrap();
+        // Touch 1 so that 2 becomes least recently used.
+        assert!(get(&mut c, 1).is_some());
+        c.insert(4, &[4]).unwrap();
+
+        assert!(!c.contains(2));
+        assert!(c.contains(1) && c.contains(3) && c.contains(4));
+        assert_eq!(get(&mut c, 4), Some(std::vec![4]));
+        assert_eq!(c.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_reinsert_overwrites_in_place() {
+        let mut c = cache();
+        c.insert(1, &[1, 1]).unwrap();
+        c.insert(1, &[9]).unwrap();
+        assert_eq!(c.len(), 1);
+        assert_eq!(get(&mut c, 1), Some(std::vec![9]));
+    }
+
+    #[test]
+    fn test_oversized_and_short_buffer_rejected() {
+        let mut c = cache();
+        assert_eq!(
+            c.insert(1, &std::vec![0; SLOT_BYTES + 1]),
+            Err(ArtCacheError::TooLarge)
+        );
+        c.insert(1, &[0; 4]).unwrap();
+        let mut small = [0u8; 2];
+        assert_eq!(c.get(1, &mut small), Err(ArtCacheError::BufferTooSmall));
+    }
+
+    #[test]
+    fn test_prefetch_plan_alternates_and_skips_resident() {
+        let mut c: ArtCache<VecRam, 8> = ArtCache::with_region(
+            VecRam(std::vec![0; 8 * SLOT_BYTES]),
+            RamRegion {
+                offset: 0,
+                len: 8 * SLOT_BYTES,
+            },
+        );
+        let order = [10, 11, 12, 13, 14, 15];
+        c.insert(14, &[0]).unwrap();
+
+        let plan = c.prefetch_plan(&order, 2, 2);
+        // +1 → 13, -1 → 11, +2 → 14 (resident, skipped), -2 → 10
+        assert_eq!(plan.as_slice(), &[13, 11, 10]);
+
+        // At the edge of the list only one side contributes.
+        assert_eq!(c.prefetch_plan(&order, 0, 1).as_slice(), &[11]);
+    }
+
+    #[test]
+    fn test_prefetch_plan_respects_capacity() {
+        let c = cache();
+        let order = [1, 2, 3, 4, 5, 6, 7];
+        assert_eq!(c.prefetch_plan(&order, 3, 3).len(), 2);
+    }
+
+    #[test]
+    fn test_prefetch_hit_counted_once() {
+        let mut c = cache();
+        c.insert_prefetched(5, &[5]).unwrap();
+        assert!(get(&mut c, 5).is_some());
+        assert!(get(&mut c, 5).is_some());
+        let s = c.stats();
+        assert_eq!((s.prefetched, s.prefetch_hits, s.hits), (1, 1, 2));
+    }
+
+    #[test]
+    fn test_invalidate_and_clear() {
+        let mut c = cache();
+        c.insert(1, &[1]).unwrap();
+        c.insert(2, &[2]).unwrap();
+        c.invalidate(1);
+        assert!(!c.contains(1));
+        assert!(c.contains(2));
+        c.clear();
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn test_freed_slot_reused_without_disturbing_others() {
+        let mut c = cache();
+        c.insert(1, &[1]).unwrap();
+        c.insert(2, &[2]).unwrap();
+        c.insert(3, &[3]).unwrap();
+        c.invalidate(1);
+        c.insert(4, &[4]).unwrap();
+        assert_eq!(c.stats().evictions, 0);
+        assert_eq!(get(&mut c, 2), Some(std::vec![2]));
+        assert_eq!(get(&mut c, 3), Some(std::vec![3]));
+        assert_eq!(get(&mut c, 4), Some(std::vec![4]));
+    }
+
+    #[test]
+    fn test_ram_failure_leaves_album_absent() {
+        let region = RamRegion {
+            offset: 0,
+            len: 2 * SLOT_BYTES,
+        };
+        // Backing store is smaller than the region claims.
+        let mut c: ArtCache<VecRam, 8> = ArtCache::with_region(VecRam(std::vec![0; 16]), region);
+        assert!(matches!(c.insert(1, &[0; 32]), Err(ArtCacheError::Ram(_))));
+        assert!(!c.contains(1));
+    }
+}
@@ -5,6 +5,9 @@
 //! - `library.meta` — postcard-encoded `TrackMeta` per track (variable size)
 //!
 //! Both are prefixed by a 64-byte `ManifestBin` in `manifest.bin`.
+//!
+//! `library.browse` adds pre-sorted artist/album/title orderings so browse
+//! screens never sort on device; see [`BrowseHeader`].
 
 use serde::{Deserialize, Serialize};
 
@@ -34,7 +37,7 @@ pub enum LibraryError {
 /// Layout (64 bytes total):
 /// ```text
 /// [0..4]   magic            b"SOUL"
-/// [4]      version          u8 = 1
+/// [4]      version          u8 = 2  (2 added `TrackMeta::loudness`)
 /// [5..8]   _pad             [u8; 3]
 /// [8..12]  track_count      u32 le
 /// [12..16] album_count      u32 le
@@ -55,7 +58,7 @@ pub struct ManifestBin {
 impl ManifestBin {
     pub const SIZE: usize = 64;
     pub const MAGIC: &'static [u8; 4] = b"SOUL";
-    pub const VERSION: u8 = 1;
+    pub const VERSION: u8 = 2;
 
     /// Encode the manifest into a 64-byte buffer.
     ///
@@ -206,6 +209,191 @@ pub fn sort_key_for(artist: &str, album: &str, track_num: u16, disc_num: u16) ->
     key
 }
 
+// ---------------------------------------------------------------------------
+// BrowseHeader — 32-byte header of library.browse
+// ---------------------------------------------------------------------------
+
+/// A pre-sorted ordering in `library.browse`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowseOrder {
+    /// By artist, then album, disc and track number.  Grouped by artist.
+    Artist,
+    /// By album, then artist, disc and track number.  Grouped by album.
+    Album,
+    /// By title, then artist and album.  Not grouped.
+    Title,
+}
+
+impl BrowseOrder {
+    /// Position of this order's table in the file, after the header.
+    const fn slot(self) -> u64 {
+        match self {
+            Self::Artist => 0,
+            Self::Album => 1,
+            Self::Title => 2,
+        }
+    }
+}
+
+/// 32-byte header of `{soul_root}/library.browse`.
+///
+/// All integers are little-endian `u32`.  Layout:
+/// ```text
+/// [0..4]   magic          b"SBRW"
+/// [4]      version        u8 = 1
+/// [5..8]   _pad           [u8; 3]
+/// [8..12]  track_count    u32 le  (must match manifest.bin)
+/// [12..16] artist_count   u32 le
+/// [16..20] album_count    u32 le
+/// [20..24] checksum       u32 le  (CRC32 of everything after the header)
+/// [24..32] _pad           [u8; 8]
+/// [32..]   artist order   track_count × u32  — library.idx positions
+///          album order    track_count × u32
+///          title order    track_count × u32
+///          artist starts  artist_count × u32 — first artist-order position
+///          album starts   album_count × u32  — first album-order position
+/// ```
+///
+/// A group's tracks run from its start to the next group's start (or the
+/// end of the order), so opening an artist or album is two table reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowseHeader {
+    pub track_count: u32,
+    pub artist_count: u32,
+    pub album_count: u32,
+    pub checksum: u32,
+}
+
+impl BrowseHeader {
+    pub const SIZE: usize = 32;
+    pub const MAGIC: &'static [u8; 4] = b"SBRW";
+    pub const VERSION: u8 = 1;
+
+    /// Encode the header into a 32-byte buffer.
+    ///
+    /// # Safety (lint allow)
+    /// All range indices are compile-time constants within `[0, SIZE)`.
+    #[must_use]
+    #[allow(clippy::indexing_slicing)]
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let mut buf = [0u8; Self::SIZE];
+        buf[0..4].copy_from_slice(Self::MAGIC);
+        buf[4] = Self::VERSION;
+        buf[8..12].copy_from_slice(&self.track_count.to_le_bytes());
+        buf[12..16].copy_from_slice(&self.artist_count.to_le_bytes());
+        buf[16..20].copy_from_slice(&self.album_count.to_le_bytes());
+        buf[20..24].copy_from_slice(&self.checksum.to_le_bytes());
+        buf
+    }
+
+    /// Decode a header from a 32-byte buffer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`LibraryError::BadMagic`] if bytes `[0..4]` are not `b"SBRW"`.
+    /// Returns [`LibraryError::UnsupportedVersion`] if byte `[4]` is not
+    /// [`BrowseHeader::VERSION`].
+    ///
+    /// # Safety (lint allow)
+    /// All range indices are compile-time constants within `[0, SIZE)`.
+    #[allow(clippy::indexing_slicing)]
+    pub fn decode(buf: &[u8; Self::SIZE]) -> Result<Self, LibraryError> {
+        if buf.get(0..4) != Some(Self::MAGIC.as_ref()) {
+            return Err(LibraryError::BadMagic);
+        }
+        if buf.get(4).copied() != Some(Self::VERSION) {
+            return Err(LibraryError::UnsupportedVersion);
+        }
+        let word = |at: usize| -> Result<u32, LibraryError> {
+            buf[at..at.saturating_add(4)]
+                .try_into()
+                .map(u32::from_le_bytes)
+                .map_err(|_| LibraryError::DecodeError)
+        };
+        Ok(Self {
+            track_count: word(8)?,
+            artist_count: word(12)?,
+            album_count: word(16)?,
+            checksum: word(20)?,
+        })
+    }
+
+    /// Number of groups in `order` (artists, albums; titles are ungrouped).
+    #[must_use]
+    pub fn group_count(&self, order: BrowseOrder) -> u32 {
+        match order {
+            BrowseOrder::Artist => self.artist_count,
+            BrowseOrder::Album => self.album_count,
+            BrowseOrder::Title => 0,
+        }
+    }
+
+    /// File offset of entry `position` in the `order` table.
+    #[must_use]
+    pub fn order_offset(&self, order: BrowseOrder, position: u32) -> u64 {
+        let table = u64::from(self.track_count).saturating_mul(order.slot());
+        Self::word_offset(table.saturating_add(u64::from(position)))
+    }
+
+    /// File offset of the start of group `group` in the `order` table.
+    /// Meaningless for [`BrowseOrder::Title`].
+    #[must_use]
+    pub fn group_offset(&self, order: BrowseOrder, group: u32) -> u64 {
+        let mut table = u64::from(self.track_count).saturating_mul(3);
+        if order == BrowseOrder::Album {
+            table = table.saturating_add(u64::from(self.artist_count));
+        }
+        Self::word_offset(table.saturating_add(u64::from(group)))
+    }
+
+    /// Total file length described by this header.
+    #[must_use]
+    pub fn file_len(&self) -> u64 {
+        let words = u64::from(self.track_count)
+            .saturating_mul(3)
+            .saturating_add(u64::from(self.artist_count))
+            .saturating_add(u64::from(self.album_count));
+        Self::word_offset(words)
+    }
+
+    fn word_offset(word: u64) -> u64 {
+        // SIZE = 32 fits in u64 on every target.
+        #[allow(clippy::cast_possible_truncation)]
+        let header = Self::SIZE as u64;
+        header.saturating_add(word.saturating_mul(4))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Loudness
+// ---------------------------------------------------------------------------
+
+/// ReplayGain 2.0 reference level (LUFS): a track measured at this
+/// integrated loudness gets 0 dB of gain.
+pub const REPLAYGAIN_REFERENCE_LUFS: i16 = -18;
+
+/// ReplayGain values measured by the desktop scanner, so the device never
+/// has to analyse audio itself.
+///
+/// Loudness is EBU R128 integrated loudness; gains take it to
+/// [`REPLAYGAIN_REFERENCE_LUFS`] and are in hundredths of a dB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Loudness {
+    /// Gain for this track on its own (0.01 dB).
+    pub track_gain_cdb: i16,
+    /// Gain shared by every track of the album (0.01 dB).
+    pub album_gain_cdb: i16,
+    /// Largest absolute sample, 32 768 = full scale, saturating at
+    /// `u16::MAX`.  Playback limits a positive gain so this stays below
+    /// full scale.
+    pub track_peak: u16,
+}
+
+impl Loudness {
+    /// Peak value of a full-scale sample.
+    pub const FULL_SCALE_PEAK: u16 = 32_768;
+}
+
 // ---------------------------------------------------------------------------
 // TrackMeta
 // ---------------------------------------------------------------------------
@@ -228,6 +416,8 @@ pub struct TrackMeta {
     pub artist: heapless::String<64>,
     pub album: heapless::String<64>,
     pub file_path: heapless::String<256>,
+    /// `None` when the scanner could not decode the file.
+    pub loudness: Option<Loudness>,
 }
 
 // ---------------------------------------------------------------------------
@@ -311,6 +501,43 @@ mod tests {
         assert_eq!(decoded.meta_size, 180);
     }
 
+    #[test]
+    fn browse_header_roundtrip() {
+        let h = BrowseHeader {
+            track_count: 8192,
+            artist_count: 400,
+            album_count: 700,
+            checksum: 0x1234_5678,
+        };
+        let bytes = h.encode();
+        assert_eq!(&bytes[0..4], b"SBRW");
+        assert_eq!(BrowseHeader::decode(&bytes).unwrap(), h);
+
+        let mut bad = bytes;
+        bad[4] = 99;
+        assert_eq!(
+            BrowseHeader::decode(&bad),
+            Err(LibraryError::UnsupportedVersion)
+        );
+    }
+
+    #[test]
+    fn browse_header_table_offsets() {
+        let h = BrowseHeader {
+            track_count: 10,
+            artist_count: 3,
+            album_count: 4,
+            checksum: 0,
+        };
+        assert_eq!(h.order_offset(BrowseOrder::Artist, 0), 32);
+        assert_eq!(h.order_offset(BrowseOrder::Album, 0), 32 + 40);
+        assert_eq!(h.order_offset(BrowseOrder::Title, 9), 32 + 80 + 36);
+        assert_eq!(h.group_offset(BrowseOrder::Artist, 0), 32 + 120);
+        assert_eq!(h.group_offset(BrowseOrder::Album, 1), 32 + 120 + 12 + 4);
+        assert_eq!(h.group_count(BrowseOrder::Title), 0);
+        assert_eq!(h.file_len(), 32 + 4 * (30 + 3 + 4));
+    }
+
     #[test]
     fn sort_key_for_pads_short_strings() {
         let key = sort_key_for("AB", "CD", 1, 1);
@@ -364,6 +591,11 @@ mod tests {
             artist: heapless::String::try_from("Test Artist").unwrap(),
             album: heapless::String::try_from("Test Album").unwrap(),
             file_path: heapless::String::try_from("/soul/music/a/b/03.flac").unwrap(),
+            loudness: Some(Loudness {
+                track_gain_cdb: -512,
+                album_gain_cdb: -480,
+                track_peak: 31_000,
+            }),
         };
         let mut buf = [0u8; 512];
         let encoded = postcard::to_slice(&meta, &mut buf).unwrap();
@@ -371,6 +603,7 @@ mod tests {
         assert_eq!(decoded.soul_id, 42);
         assert_eq!(decoded.track_number, 3);
         assert_eq!(decoded.title.as_str(), "Test Track");
+        assert_eq!(decoded.loudness, meta.loudness);
     }
 
     #[test]
@@ -389,6 +622,7 @@ mod tests {
             artist: heapless::String::try_from("An Artist").unwrap(),
             album: heapless::String::try_from("An Album").unwrap(),
             file_path: heapless::String::try_from("/soul/m/artist/album/01.flac").unwrap(),
+            loudness: None,
         };
         let mut buf = [0u8; 512];
         let encoded = postcard::to_slice(&meta, &mut buf).unwrap();
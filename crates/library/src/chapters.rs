@@ -0,0 +1,445 @@
+//! Chapters — chapter marks for audiobooks from MP4/M4B atoms or CUE sheets.
+//!
+//! Two sources are supported:
+//!
+//! - **MP4 / M4B**: the Nero `chpl` atom at `moov/udta/chpl`, written by
+//!   most audiobook tools.  [`Chapters::from_mp4`] takes the start of the
+//!   file (or just the `moov` box) and walks the box tree itself.
+//! - **CUE sheets**: `book.cue` next to a single-file book (see
+//!   [`Scanner::cue_path_for`]); each `TRACK` of the first `FILE` becomes a
+//!   chapter starting at its `INDEX 01`.
+//!
+//! Start times are kept in a separate slice ([`Chapters::starts_ms`]) so the
+//! playback engine can navigate chapters without the titles.
+//!
+//! [`Scanner::cue_path_for`]: crate::scanner::Scanner::cue_path_for
+
+use heapless::{String, Vec};
+
+/// Longest chapter title kept, in UTF-8 bytes.
+pub const MAX_CHAPTER_TITLE_BYTES: usize = 64;
+
+/// Chapters held per book.
+pub const MAX_CHAPTERS: usize = 128;
+
+/// `chpl` start times are in 100 ns units.
+const CHPL_UNITS_PER_MS: u64 = 10_000;
+
+/// CUE `mm:ss:ff` frames per second.
+const CUE_FRAMES_PER_SEC: u32 = 75;
+
+/// Error from chapter extraction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChapterError {
+    /// The file has no chapter marks.
+    NotFound,
+    /// A chapter atom or CUE entry is truncated or inconsistent.
+    Malformed,
+}
+
+/// Chapter marks for one book, sorted by start time.
+#[derive(Debug, Clone, Default)]
+pub struct Chapters<const N: usize = MAX_CHAPTERS> {
+    starts: Vec<u32, N>,
+    titles: Vec<String<MAX_CHAPTER_TITLE_BYTES>, N>,
+    truncated: bool,
+}
+
+impl<const N: usize> Chapters<N> {
+    /// An empty chapter list.
+    pub fn new() -> Self {
+        Self {
+            starts: Vec::new(),
+            titles: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    /// Add a chapter, keeping start order; chapters past `N` are dropped
+    /// and [`is_truncated`](Self::is_truncated) set.  Long titles are cut
+    /// at a character boundary.
+    pub fn push(&mut self, start_ms: u32, title: &str) {
+        if self.starts.is_full() {
+            self.truncated = true;
+            return;
+        }
+        let idx = self.starts.partition_point(|&s| s <= start_ms);
+        let mut t = String::new();
+        for c in title.chars() {
+            if t.push(c).is_err() {
+                break;
+            }
+        }
+        // Cannot fail: both vectors have the same length and capacity.
+        self.starts.insert(idx, start_ms).ok();
+        self.titles.insert(idx, t).ok();
+    }
+
+    /// Chapter start times in milliseconds, ascending.
+    pub fn starts_ms(&self) -> &[u32] {
+        &self.starts
+    }
+
+    /// Start of chapter `index`.
+    pub fn start_ms(&self, index: usize) -> Option<u32> {
+        self.starts.get(index).copied()
+    }
+
+    /// Title of chapter `index` (may be empty).
+    pub fn title(&self, index: usize) -> Option<&str> {
+        self.titles.get(index).map(|t| t.as_str())
+    }
+
+    /// Number of chapters.
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// `true` when there are no chapters.
+    pub fn is_empty(&self) -> bool {
+        self.starts.is_empty()
+    }
+
+    /// `true` when chapters were dropped because the book exceeded `N`.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Chapter playing at `position_ms`, or `None` before the first mark.
+    pub fn index_at(&self, position_ms: u64) -> Option<usize> {
+        self.starts
+            .partition_point(|&s| u64::from(s) <= position_ms)
+            .checked_sub(1)
+    }
+
+    /// Read chapters from the `moov/udta/chpl` atom.
+    ///
+    /// `data` is either the start of the file, containing the top-level
+    /// `moov` box, or the `moov` box itself.  Boxes after `moov` (typically
+    /// `mdat`) need not be present.
+    ///
+    /// # Errors
+    ///
+    /// [`ChapterError::NotFound`] when there is no `chpl` atom;
+    /// [`ChapterError::Malformed`] when it is truncated.
+    pub fn from_mp4(data: &[u8]) -> Result<Self, ChapterError> {
+        let moov = find_box(data, *b"moov").ok_or(ChapterError::NotFound)?;
+        let udta = find_box(moov, *b"udta").ok_or(ChapterError::NotFound)?;
+        let chpl = find_box(udta, *b"chpl").ok_or(ChapterError::NotFound)?;
+        Self::from_chpl(chpl)
+    }
+
+    /// Parse a `chpl` payload: version, 24-bit flags, 4 reserved bytes when
+    /// version ≥ 1, a count byte, then per chapter a big-endian u64 start in
+    /// 100 ns units and a length-prefixed title.
+    fn from_chpl(payload: &[u8]) -> Result<Self, ChapterError> {
+        let mut r = Reader(payload);
+        let version = r.u8()?;
+        r.skip(3)?;
+        if version >= 1 {
+            r.skip(4)?;
+        }
+        let count = r.u8()?;
+        let mut chapters = Self::new();
+        for _ in 0..count {
+            let start = r.u64()?;
+            let len = usize::from(r.u8()?);
+            let title = utf8_prefix(r.take(len)?);
+            let ms = start.checked_div(CHPL_UNITS_PER_MS).unwrap_or(0);
+            chapters.push(u32::try_from(ms).unwrap_or(u32::MAX), title);
+        }
+        if chapters.is_empty() {
+            return Err(ChapterError::NotFound);
+        }
+        Ok(chapters)
+    }
+
+    /// Read chapters from a CUE sheet.
+    ///
+    /// Only the tracks of the first `FILE` are used: an audiobook CUE
+    /// describes one audio file, and tracks of later files would have start
+    /// times relative to a different file.
+    ///
+    /// # Errors
+    ///
+    /// [`ChapterError::NotFound`] when the sheet has no `INDEX 01` entries;
+    /// [`ChapterError::Malformed`] when an `INDEX 01` time does not parse.
+    pub fn from_cue(src: &str) -> Result<Self, ChapterError> {
+        let src = src.strip_prefix('\u{feff}').unwrap_or(src);
+        let mut chapters = Self::new();
+        let mut files = 0u32;
+        let mut title: String<MAX_CHAPTER_TITLE_BYTES> = String::new();
+        let mut in_track = false;
+
+        for line in src.lines() {
+            let line = line.trim();
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match keyword {
+                "FILE" => {
+                    files = files.saturating_add(1);
+                    if files > 1 {
+                        break;
+                    }
+                }
+                "TRACK" => {
+                    in_track = true;
+                    title.clear();
+                }
+                // Album-level TITLE precedes the first TRACK; ignore it.
+                "TITLE" if in_track => {
+                    title.clear();
+                    for c in unquote(rest).chars() {
+                        if title.push(c).is_err() {
+                            break;
+                        }
+                    }
+                }
+                "INDEX" if in_track => {
+                    let (number, time) = rest.trim().split_once(' ').unwrap_or((rest, ""));
+                    if number == "01" {
+                        let ms = parse_cue_time(time.trim()).ok_or(ChapterError::Malformed)?;
+                        chapters.push(ms, &title);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if chapters.is_empty() {
+            return Err(ChapterError::NotFound);
+        }
+        Ok(chapters)
+    }
+}
+
+/// Payload of the first box of type `kind` among the boxes in `data`.
+fn find_box(data: &[u8], kind: [u8; 4]) -> Option<&[u8]> {
+    let mut rest = data;
+    while rest.len() >= 8 {
+        let size32 = u32::from_be_bytes(rest.get(0..4)?.try_into().ok()?);
+        let typ: [u8; 4] = rest.get(4..8)?.try_into().ok()?;
+        let (header, size) = match size32 {
+            // Box extends to the end of the enclosing data.
+            0 => (8, rest.len()),
+            // 64-bit largesize follows the type.
+            1 => {
+                let large = u64::from_be_bytes(rest.get(8..16)?.try_into().ok()?);
+                (16, usize::try_from(large).ok()?)
+            }
+            n => (8, usize::try_from(n).ok()?),
+        };
+        if size < header {
+            return None;
+        }
+        // A box cut off by the end of `data` is still searched, so a file
+        // head that stops inside `moov` can be read as far as it goes.
+        let end = size.min(rest.len());
+        if typ == kind {
+            return rest.get(header..end);
+        }
+        rest = rest.get(end..)?;
+    }
+    None
+}
+
+/// Big-endian cursor over a byte slice.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ChapterError> {
+        if self.0.len() < n {
+            return Err(ChapterError::Malformed);
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), ChapterError> {
+        self.take(n).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, ChapterError> {
+        self.take(1)?
+            .first()
+            .copied()
+            .ok_or(ChapterError::Malformed)
+    }
+
+    fn u64(&mut self) -> Result<u64, ChapterError> {
+        let bytes = self
+            .take(8)?
+            .try_into()
+            .map_err(|_| ChapterError::Malformed)?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+}
+
+/// The longest valid UTF-8 prefix of `bytes`.
+fn utf8_prefix(bytes: &[u8]) -> &str {
+    match core::str::from_utf8(bytes) {
+        Ok(s) => s,
+        Err(e) => bytes
+            .get(..e.valid_up_to())
+            .and_then(|b| core::str::from_utf8(b).ok())
+            .unwrap_or(""),
+    }
+}
+
+/// Strip surrounding double quotes, if any.
+fn unquote(s: &str) -> &str {
+    let s = s.trim();
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+/// Parse CUE `mm:ss:ff` (75 frames per second) into milliseconds.
+fn parse_cue_time(s: &str) -> Option<u32> {
+    let mut parts = s.split(':');
+    let min: u32 = parts.next()?.parse().ok()?;
+    let sec: u32 = parts.next()?.parse().ok()?;
+    let frames: u32 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || sec >= 60 || frames >= CUE_FRAMES_PER_SEC {
+        return None;
+    }
+    let frame_ms = frames.checked_mul(1_000)?.checked_div(CUE_FRAMES_PER_SEC)?;
+    min.checked_mul(60_000)?
+        .checked_add(sec.checked_mul(1_000)?)?
+        .checked_add(frame_ms)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+#[allow(clippy::cast_possible_truncation)] // Fixture lengths are tiny
+mod tests {
+    use super::*;
+    use std::vec::Vec as StdVec;
+
+    fn mp4_box(kind: &[u8; 4], payload: &[u8]) -> StdVec<u8> {
+        let mut b = ((payload.len() + 8) as u32).to_be_bytes().to_vec();
+        b.extend_from_slice(kind);
+        b.extend_from_slice(payload);
+        b
+    }
+
+    fn chpl(version: u8, chapters: &[(u64, &str)]) -> StdVec<u8> {
+        let mut p = std::vec![version, 0, 0, 0];
+        if version >= 1 {
+            p.extend_from_slice(&[0; 4]);
+        }
+        p.push(chapters.len() as u8);
+        for (start_ms, title) in chapters {
+            p.extend_from_slice(&(start_ms * 10_000).to_be_bytes());
+            p.push(title.len() as u8);
+            p.extend_from_slice(title.as_bytes());
+        }
+        mp4_box(b"chpl", &p)
+    }
+
+    fn m4b(chpl: &[u8]) -> StdVec<u8> {
+        let mut file = mp4_box(b"ftyp", b"M4B \0\0\0\0");
+        let udta = mp4_box(b"udta", chpl);
+        let mut moov_payload = mp4_box(b"mvhd", &[0; 100]);
+        moov_payload.extend_from_slice(&udta);
+        file.extend_from_slice(&mp4_box(b"moov", &moov_payload));
+        file.extend_from_slice(&mp4_box(b"mdat", &[0; 16]));
+        file
+    }
+
+    #[test]
+    fn test_mp4_chpl_chapters() {
+        let file = m4b(&chpl(
+            1,
+            &[
+                (0, "Opening"),
+                (754_250, "Chapter 2"),
+                (1_500_000, "Épilogue"),
+            ],
+        ));
+        let chapters: Chapters = Chapters::from_mp4(&file).unwrap();
+        assert_eq!(chapters.starts_ms(), &[0, 754_250, 1_500_000]);
+        assert_eq!(chapters.title(2), Some("Épilogue"));
+        assert_eq!(chapters.index_at(754_249), Some(0));
+        assert_eq!(chapters.index_at(800_000), Some(1));
+    }
+
+    #[test]
+    fn test_mp4_chpl_version_zero_and_moov_only() {
+        let file = m4b(&chpl(0, &[(0, "A"), (60_000, "B")]));
+        // Start at the moov box itself.
+        let moov_at = 16;
+        let chapters: Chapters = Chapters::from_mp4(&file[moov_at..]).unwrap();
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters.title(1), Some("B"));
+    }
+
+    #[test]
+    fn test_mp4_without_chapters() {
+        let file = m4b(&mp4_box(b"meta", &[0; 8]));
+        assert_eq!(
+            Chapters::<8>::from_mp4(&file).unwrap_err(),
+            ChapterError::NotFound
+        );
+    }
+
+    #[test]
+    fn test_mp4_truncated_chpl_is_malformed() {
+        let mut atom = chpl(1, &[(0, "Opening"), (60_000, "Next")]);
+        atom.truncate(atom.len() - 3);
+        let len = (atom.len() as u32).to_be_bytes();
+        atom[..4].copy_from_slice(&len);
+        assert_eq!(
+            Chapters::<8>::from_mp4(&m4b(&atom)).unwrap_err(),
+            ChapterError::Malformed
+        );
+    }
+
+    #[test]
+    fn test_cue_chapters_from_first_file() {
+        let cue = "REM GENRE Audiobook\n\
+                   TITLE \"The Book\"\n\
+                   FILE \"book.mp3\" MP3\n\
+                   \x20 TRACK 01 AUDIO\n\
+                   \x20   TITLE \"Prologue\"\n\
+                   \x20   INDEX 01 00:00:00\n\
+                   \x20 TRACK 02 AUDIO\n\
+                   \x20   TITLE \"Chapter One\"\n\
+                   \x20   INDEX 00 12:29:00\n\
+                   \x20   INDEX 01 12:30:37\n\
+                   \x20 TRACK 03 AUDIO\n\
+                   \x20   INDEX 01 75:00:00\n\
+                   FILE \"other.mp3\" MP3\n\
+                   \x20 TRACK 04 AUDIO\n\
+                   \x20   INDEX 01 00:00:00\n";
+        let chapters: Chapters = Chapters::from_cue(cue).unwrap();
+        assert_eq!(chapters.starts_ms(), &[0, 750_493, 4_500_000]);
+        assert_eq!(chapters.title(0), Some("Prologue"));
+        assert_eq!(chapters.title(1), Some("Chapter One"));
+        assert_eq!(chapters.title(2), Some(""));
+    }
+
+    #[test]
+    fn test_cue_bad_index_is_malformed() {
+        let cue = "FILE \"a.mp3\" MP3\nTRACK 01 AUDIO\nINDEX 01 00:61:00\n";
+        assert_eq!(
+            Chapters::<8>::from_cue(cue).unwrap_err(),
+            ChapterError::Malformed
+        );
+        assert_eq!(
+            Chapters::<8>::from_cue("REM nothing").unwrap_err(),
+            ChapterError::NotFound
+        );
+    }
+
+    #[test]
+    fn test_push_caps_count_and_title_length() {
+        let mut chapters: Chapters<2> = Chapters::new();
+        chapters.push(20, "b");
+        chapters.push(10, &"ä".repeat(MAX_CHAPTER_TITLE_BYTES));
+        chapters.push(30, "c");
+        assert!(chapters.is_truncated());
+        assert_eq!(chapters.starts_ms(), &[10, 20]);
+        assert_eq!(chapters.title(0).unwrap().len(), MAX_CHAPTER_TITLE_BYTES);
+    }
+}
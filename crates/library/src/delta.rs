@@ -0,0 +1,485 @@
+//! Library deltas — playlists and tag corrections sent from the companion
+//! app.
+//!
+//! The phone cannot rewrite the library files, but it can describe changes
+//! to the parts the player keeps itself: new playlists and
+//! [tag overrides](crate::tag_overrides).  A delta file arrives over the
+//! BLE file transfer (`bluetooth::transfer`) and is applied with
+//! [`apply_delta`].
+//!
+//! # Format
+//!
+//! ```text
+//! [0..4]   magic              b"SDLT"
+//! [4]      version            u8 = 1
+//! [5..8]   reserved           zero
+//! [8..16]  library_timestamp  u64 le — ManifestBin::export_timestamp the
+//!                             delta was made against, 0 for any library
+//! [16..]   operations         postcard-encoded DeltaOp, back to back
+//! ```
+//!
+//! Playlist entries are library track indices, as in the companion
+//! protocol's queue pages, so a delta that names tracks only makes sense
+//! for the library export it was made against; the timestamp check stops it
+//! from landing on another one.  Tag corrections are keyed by
+//! [`FileHash`] and apply to any library.
+//!
+//! [`LibraryDelta::parse`] checks every operation before anything is
+//! applied, so a malformed delta changes nothing.  Tag corrections are then
+//! applied all or nothing; playlists are written one by one.
+
+use heapless::{String, Vec};
+use platform::storage::{File, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::binary::LibraryError;
+use crate::playlist::{
+    export_tracks, playlist_path, ExportError, ExportReport, M3uWriter, PlaylistError,
+    PlaylistSink, MAX_PLAYLIST_NAME_BYTES,
+};
+use crate::reader::SoulLibraryReader;
+use crate::tag_overrides::{FileHash, TagField, TagOverrideError, TagOverrides};
+
+/// Magic bytes at the start of a delta file.
+pub const DELTA_MAGIC: [u8; 4] = *b"SDLT";
+
+/// Delta format version.
+pub const DELTA_VERSION: u8 = 1;
+
+/// Bytes before the first operation.
+pub const DELTA_HEADER_SIZE: usize = 16;
+
+/// Most tracks in one delta playlist.
+pub const MAX_DELTA_PLAYLIST_TRACKS: usize = 256;
+
+/// Longest tag value in a delta ([`TagField::Title`]'s limit).
+const MAX_TAG_VALUE: usize = 128;
+
+/// One change carried by a delta.
+// Operations are decoded one at a time, so the size of a playlist op costs
+// one stack slot, not one per op.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeltaOp {
+    /// Correct one tag, as [`TagOverrides::set`]; an empty value restores
+    /// the file's own tag.
+    SetTag {
+        /// The file to correct.
+        hash: FileHash,
+        /// The tag to correct.
+        field: TagField,
+        /// Corrected value, at most [`TagField::max_len`] bytes.
+        value: String<MAX_TAG_VALUE>,
+    },
+    /// Drop every correction of one file, as [`TagOverrides::remove`].
+    ClearTags {
+        /// The file to restore.
+        hash: FileHash,
+    },
+    /// Write a playlist to `/Playlists/{name}.m3u8`, replacing one of the
+    /// same name.
+    Playlist {
+        /// Playlist name, cleaned up by [`playlist_path`].
+        name: String<MAX_PLAYLIST_NAME_BYTES>,
+        /// Library track indices, in play order.
+        tracks: Vec<u32, MAX_DELTA_PLAYLIST_TRACKS>,
+    },
+}
+
+impl DeltaOp {
+    /// Whether the operation can be applied at all: tag values fit their
+    /// field and playlist names leave something usable.
+    fn is_valid(&self) -> bool {
+        match self {
+            Self::SetTag { field, value, .. } => value.len() <= field.max_len(),
+            Self::ClearTags { .. } => true,
+            Self::Playlist { name, .. } => playlist_path(name).is_some(),
+        }
+    }
+}
+
+/// A checked delta file.  See the module docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LibraryDelta<'a> {
+    library_timestamp: u64,
+    ops: &'a [u8],
+}
+
+impl<'a> LibraryDelta<'a> {
+    /// Check the header and every operation of `bytes`.
+    ///
+    /// # Errors
+    ///
+    /// [`LibraryError::BadMagic`], [`LibraryError::UnsupportedVersion`], or
+    /// [`LibraryError::DecodeError`] for a truncated header, an operation
+    /// that does not decode, or one that can never be applied.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, LibraryError> {
+        let header = bytes
+            .get(..DELTA_HEADER_SIZE)
+            .ok_or(LibraryError::DecodeError)?;
+        let (magic, rest) = header.split_at(DELTA_MAGIC.len());
+        if magic != DELTA_MAGIC {
+            return Err(LibraryError::BadMagic);
+        }
+        if rest.first() != Some(&DELTA_VERSION) {
+            return Err(LibraryError::UnsupportedVersion);
+        }
+        let timestamp = rest
+            .get(4..12)
+            .and_then(|b| <[u8; 8]>::try_from(b).ok())
+            .ok_or(LibraryError::DecodeError)?;
+        let delta = Self {
+            library_timestamp: u64::from_le_bytes(timestamp),
+            ops: bytes.get(DELTA_HEADER_SIZE..).unwrap_or_default(),
+        };
+        for op in delta.ops() {
+            if !op?.is_valid() {
+                return Err(LibraryError::DecodeError);
+            }
+        }
+        Ok(delta)
+    }
+
+    /// Export timestamp of the library the delta was made against, 0 when
+    /// it fits any library.
+    #[must_use]
+    pub fn library_timestamp(&self) -> u64 {
+        self.library_timestamp
+    }
+
+    /// The operations, in file order.
+    #[must_use]
+    pub fn ops(&self) -> DeltaOps<'a> {
+        DeltaOps { rest: self.ops }
+    }
+}
+
+/// Iterator over the operations of a [`LibraryDelta`].
+#[derive(Debug, Clone)]
+pub struct DeltaOps<'a> {
+    rest: &'a [u8],
+}
+
+impl Iterator for DeltaOps<'_> {
+    type Item = Result<DeltaOp, LibraryError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        match postcard::take_from_bytes(self.rest) {
+            Ok((op, rest)) => {
+                self.rest = rest;
+                Some(Ok(op))
+            }
+            Err(_) => {
+                self.rest = &[];
+                Some(Err(LibraryError::DecodeError))
+            }
+        }
+    }
+}
+
+/// Builds a delta file in a caller-provided buffer — what the companion
+/// app does; used by the host tools and tests.
+#[derive(Debug)]
+pub struct DeltaWriter<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> DeltaWriter<'b> {
+    /// Start a delta for the library exported at `library_timestamp` (0 for
+    /// any library).
+    ///
+    /// # Errors
+    ///
+    /// [`LibraryError::DecodeError`] when `buf` cannot hold the header.
+    pub fn new(buf: &'b mut [u8], library_timestamp: u64) -> Result<Self, LibraryError> {
+        let header = buf
+            .get_mut(..DELTA_HEADER_SIZE)
+            .ok_or(LibraryError::DecodeError)?;
+        let (magic, rest) = header.split_at_mut(DELTA_MAGIC.len());
+        magic.copy_from_slice(&DELTA_MAGIC);
+        let (version, rest) = rest.split_at_mut(4);
+        version.copy_from_slice(&[DELTA_VERSION, 0, 0, 0]);
+        rest.copy_from_slice(&library_timestamp.to_le_bytes());
+        Ok(Self {
+            buf,
+            len: DELTA_HEADER_SIZE,
+        })
+    }
+
+    /// Append `op`.
+    ///
+    /// # Errors
+    ///
+    /// [`LibraryError::DecodeError`] when the buffer is full; the delta is
+    /// unchanged.
+    pub fn push(&mut self, op: &DeltaOp) -> Result<(), LibraryError> {
+        let free = self
+            .buf
+            .get_mut(self.len..)
+            .ok_or(LibraryError::DecodeError)?;
+        let used = postcard::to_slice(op, free)
+            .map_err(|_| LibraryError::DecodeError)?
+            .len();
+        self.len = self.len.saturating_add(used);
+        Ok(())
+    }
+
+    /// The finished delta.
+    #[must_use]
+    pub fn finish(self) -> &'b [u8] {
+        let Self { buf, len } = self;
+        let buf: &'b [u8] = buf;
+        buf.get(..len).unwrap_or_default()
+    }
+}
+
+/// Where [`apply_delta`] persists its changes.
+///
+/// The hardware implementation writes the tag table to
+/// [`tag_overrides_path`](platform::soul_library::tag_overrides_path) and
+/// playlists to files on the card.
+#[allow(async_fn_in_trait)]
+pub trait DeltaStore {
+    /// Storage error type.
+    type Error: core::fmt::Debug;
+    /// An open playlist file.
+    type Playlist: PlaylistSink<Error = Self::Error>;
+
+    /// Create (or truncate) the playlist at card path `path`.
+    async fn create_playlist(&mut self, path: &str) -> Result<Self::Playlist, Self::Error>;
+
+    /// Close a playlist returned by
+    /// [`create_playlist`](Self::create_playlist) once it is complete.
+    async fn close_playlist(&mut self, playlist: Self::Playlist) -> Result<(), Self::Error>;
+
+    /// Replace the saved tag table with `table`.
+    async fn save_tag_overrides<const N: usize>(
+        &mut self,
+        table: &TagOverrides<N>,
+    ) -> Result<(), Self::Error>;
+}
+
+/// Outcome of [`apply_delta`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeltaReport {
+    /// Tag operations applied.
+    pub tags: u32,
+    /// Playlists written.
+    pub playlists: u32,
+    /// Entries written to and left out of those playlists.
+    pub entries: ExportReport,
+}
+
+/// Error from [`apply_delta`].
+#[derive(Debug)]
+pub enum DeltaError<E: core::fmt::Debug, W> {
+    /// The delta was made against another library export; nothing was
+    /// applied.
+    WrongLibrary,
+    /// The tag corrections do not fit the table; none were applied.
+    Tags(TagOverrideError),
+    /// Reading the library failed.
+    Library(E),
+    /// Saving a change failed.
+    Store(W),
+}
+
+/// Apply `delta`: first every tag operation to `overrides` (saved through
+/// `store` once, only if all of them succeed), then every playlist, its
+/// entries looked up in `reader`.
+///
+/// # Errors
+///
+/// See [`DeltaError`].  A tag or library error leaves `overrides` and the
+/// saved table unchanged; a store error while writing playlists leaves
+/// the ones already written in place.
+pub async fn apply_delta<S, D, const N: usize>(
+    delta: &LibraryDelta<'_>,
+    reader: &mut SoulLibraryReader<S>,
+    overrides: &mut TagOverrides<N>,
+    store: &mut D,
+) -> Result<DeltaReport, DeltaError<S::Error, D::Error>>
+where
+    S: Storage,
+    S::File: File<Error = S::Error>,
+    D: DeltaStore,
+{
+    if delta.library_timestamp() != 0 && delta.library_timestamp() != reader.export_timestamp() {
+        return Err(DeltaError::WrongLibrary);
+    }
+    let mut report = DeltaReport::default();
+
+    // Tags: work on a copy so a full table leaves the original untouched.
+    let mut table = overrides.clone();
+    for op in delta.ops().flatten() {
+        match op {
+            DeltaOp::SetTag { hash, field, value } => {
+                table.set(hash, field, &value).map_err(DeltaError::Tags)?;
+            }
+            DeltaOp::ClearTags { hash } => table.remove(hash),
+            DeltaOp::Playlist { .. } => continue,
+        }
+        report.tags = report.tags.saturating_add(1);
+    }
+    if report.tags > 0 {
+        store
+            .save_tag_overrides(&table)
+            .await
+            .map_err(DeltaError::Store)?;
+        *overrides = table;
+    }
+
+    for op in delta.ops().flatten() {
+        let DeltaOp::Playlist { name, tracks } = op else {
+            continue;
+        };
+        // Checked by `LibraryDelta::parse`.
+        let Some(path) = playlist_path(&name) else {
+            continue;
+        };
+        let file = store
+            .create_playlist(&path)
+            .await
+            .map_err(DeltaError::Store)?;
+        let mut writer = match M3uWriter::new(file, &path).await {
+            Ok(writer) => writer,
+            Err(PlaylistError::Sink(e)) => return Err(DeltaError::Store(e)),
+            // Playlist paths are far shorter than card paths.
+            Err(PlaylistError::PathTooLong | PlaylistError::EntryTooLong) => continue,
+        };
+        let entries = export_tracks(reader, tracks.iter().copied(), &mut writer)
+            .await
+            .map_err(|e| match e {
+                ExportError::Storage(e) => DeltaError::Library(e),
+                ExportError::Sink(e) => DeltaError::Store(e),
+            })?;
+        store
+            .close_playlist(writer.finish())
+            .await
+            .map_err(DeltaError::Store)?;
+        report.playlists = report.playlists.saturating_add(1);
+        report.entries.written = report.entries.written.saturating_add(entries.written);
+        report.entries.skipped = report.entries.skipped.saturating_add(entries.skipped);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::large_stack_arrays
+)]
+mod tests {
+    use super::*;
+
+    const HASH: FileHash = FileHash {
+        size: 4_000_000,
+        crc32: 0x1234_5678,
+    };
+
+    fn ops() -> [DeltaOp; 3] {
+        [
+            DeltaOp::SetTag {
+                hash: HASH,
+                field: TagField::Title,
+                value: String::try_from("Glory Box").unwrap(),
+            },
+            DeltaOp::ClearTags { hash: HASH },
+            DeltaOp::Playlist {
+                name: String::try_from("Road trip").unwrap(),
+                tracks: Vec::from_slice(&[4, 1, 9]).unwrap(),
+            },
+        ]
+    }
+
+    fn encode(timestamp: u64, ops: &[DeltaOp], buf: &mut [u8]) -> usize {
+        let mut writer = DeltaWriter::new(buf, timestamp).unwrap();
+        for op in ops {
+            writer.push(op).unwrap();
+        }
+        writer.finish().len()
+    }
+
+    #[test]
+    fn test_delta_round_trips() {
+        let mut buf = [0u8; 512];
+        let len = encode(1_700_000_000, &ops(), &mut buf);
+        assert_eq!(&buf[..4], b"SDLT");
+        let delta = LibraryDelta::parse(&buf[..len]).unwrap();
+        assert_eq!(delta.library_timestamp(), 1_700_000_000);
+        let decoded: std::vec::Vec<DeltaOp> = delta.ops().map(Result::unwrap).collect();
+        assert_eq!(decoded, ops());
+    }
+
+    #[test]
+    fn test_empty_delta_is_valid() {
+        let mut buf = [0u8; DELTA_HEADER_SIZE];
+        let len = encode(0, &[], &mut buf);
+        let delta = LibraryDelta::parse(&buf[..len]).unwrap();
+        assert_eq!(delta.ops().count(), 0);
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_header_and_ops() {
+        let mut buf = [0u8; 512];
+        let len = encode(0, &ops(), &mut buf);
+
+        let mut bad = buf;
+        bad[0] = b'X';
+        assert_eq!(
+            LibraryDelta::parse(&bad[..len]),
+            Err(LibraryError::BadMagic)
+        );
+        let mut bad = buf;
+        bad[4] = 2;
+        assert_eq!(
+            LibraryDelta::parse(&bad[..len]),
+            Err(LibraryError::UnsupportedVersion)
+        );
+        assert_eq!(
+            LibraryDelta::parse(&buf[..DELTA_HEADER_SIZE - 1]),
+            Err(LibraryError::DecodeError)
+        );
+        // Cut inside the last operation.
+        assert_eq!(
+            LibraryDelta::parse(&buf[..len - 1]),
+            Err(LibraryError::DecodeError)
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_ops_that_cannot_apply() {
+        let long_artist = DeltaOp::SetTag {
+            hash: HASH,
+            field: TagField::Artist,
+            value: String::try_from("x".repeat(TagField::Artist.max_len() + 1).as_str()).unwrap(),
+        };
+        let unnamed = DeltaOp::Playlist {
+            name: String::try_from(" .. ").unwrap(),
+            tracks: Vec::new(),
+        };
+        for op in [long_artist, unnamed] {
+            let mut buf = [0u8; 512];
+            let len = encode(0, &[op], &mut buf);
+            assert_eq!(
+                LibraryDelta::parse(&buf[..len]),
+                Err(LibraryError::DecodeError)
+            );
+        }
+    }
+
+    #[test]
+    fn test_writer_reports_a_full_buffer() {
+        let mut buf = [0u8; DELTA_HEADER_SIZE + 4];
+        let mut writer = DeltaWriter::new(&mut buf, 0).unwrap();
+        assert_eq!(writer.push(&ops()[0]), Err(LibraryError::DecodeError));
+        assert_eq!(writer.finish().len(), DELTA_HEADER_SIZE);
+        assert!(DeltaWriter::new(&mut [0u8; 8], 0).is_err());
+    }
+}
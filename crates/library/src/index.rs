@@ -1,57 +1,245 @@
-//! TrackIndex — fixed-capacity, in-memory catalogue of scanned tracks.
+//! TrackIndex — fixed-capacity catalogue of scanned tracks.
 //!
-//! On hardware the `FullIndex` (capacity 8 192) must live in external SDRAM
-//! (0xC000_0000) because each `Track` is ~600 bytes, totalling ~4.8 MB.
-//! Tests use `SmallIndex` (capacity 64) which fits on the host stack.
+//! 8 192 `Track`s with inline strings are ~4.8 MB, far beyond on-chip RAM,
+//! so the index splits its storage:
+//!
+//! - **Bulk records** (title, path, duration, sample rate, format) are
+//!   appended to an [`ExternalRam`] region — [`RamRegion::LIBRARY_INDEX`]
+//!   in SDRAM on hardware.
+//! - **Artist and album names** are interned: each distinct string is
+//!   written once to the same region and referred to by a `u16` id.
+//! - **Hot lookup tables** stay on-chip: per track its record offset and
+//!   artist/album ids (8 bytes), per interned string its hash and offset
+//!   (8 bytes) plus its slot in a hash-sorted id table (2 bytes), which
+//!   interning binary-searches.  A full index needs 144 KB of these.
+//!
+//! [`TrackIndex::get`] reassembles a [`Track`] from SDRAM on demand.
+//! [`TrackIndex::new`] backs the index with a small on-chip [`InlineRam`],
+//! which is what tests and [`SmallIndex`] use.
+//!
+//! # API changes from the all-in-RAM index
+//!
+//! Two signatures changed; everything else (`new`, `insert`, `len`,
+//! `is_empty`, `clear`, [`SmallIndex`], [`MAX_TRACKS`]) is as before:
+//!
+//! - `get` returns an owned `Option<Track>` instead of `Option<&Track>`:
+//!   the record is read back from SDRAM, so there is nothing to borrow.
+//!   Code that only needs to group or filter should use
+//!   [`artist_id`](TrackIndex::artist_id) / [`album_id`](TrackIndex::album_id),
+//!   which stay on-chip.
+//! - [`FullIndex`] takes the record storage as a type parameter
+//!   (`FullIndex<R>`), built with [`TrackIndex::with_ram`].
+//!
+//! No caller in the workspace borrowed tracks from the index or named
+//! `FullIndex`, so none had to change; the UI screens do not use the index.
+//!
+//! # Record layout
+//!
+//! ```text
+//! track:  [title_len u8][title][path_len u16 le][path]
+//!         [duration_secs u32 le][sample_rate u32 le][format u8]
+//! string: [len u8][bytes]
+//! ```
 
-use crate::track::Track;
+use crate::track::{AudioFormat, Track};
 use heapless::Vec;
+use platform::{ExternalRam, RamRegion};
 
 /// Maximum number of tracks the hardware index holds.
 ///
-/// At ~600 bytes per `Track`, `FullIndex` occupies ≈ 4.8 MB and must be
-/// placed in external SDRAM — never allocated on the stack.
+/// Track records live in external SDRAM; only the 18-byte-per-track lookup
+/// tables of a `FullIndex` are on-chip.
 pub const MAX_TRACKS: usize = 8192;
 
+/// Record storage behind [`TrackIndex::new`]: roughly 64 tracks with
+/// typical paths, enough for a [`SmallIndex`].
+pub const INLINE_POOL_BYTES: usize = 16 * 1024;
+
 /// Error type for index operations.
 #[derive(Debug, PartialEq, Eq)]
 pub enum IndexError {
-    /// The index has reached its compile-time capacity.
+    /// The index has reached its compile-time capacity, or its record
+    /// region is exhausted.
     Full,
     /// The requested position does not exist.
     OutOfBounds,
+    /// The record storage failed or returned corrupt data.
+    Storage,
+}
+
+/// Per-track hot entry: where the record is and who it is by.
+#[derive(Debug, Clone, Copy)]
+struct TrackEntry {
+    record: u32,
+    artist: u16,
+    album: u16,
+}
+
+/// Interned string: FNV-1a hash for lookup, offset of its `[len][bytes]`.
+#[derive(Debug, Clone, Copy)]
+struct Interned {
+    hash: u32,
+    offset: u32,
 }
 
 /// A fixed-capacity, ordered catalogue of [`Track`] entries.
 ///
-/// `N` is the maximum number of tracks; use [`SmallIndex`] in tests and
-/// [`FullIndex`] only when placing the value in SDRAM.
-pub struct TrackIndex<const N: usize> {
-    tracks: Vec<Track, N>,
+/// `N` is the maximum number of tracks.  Artist and album names share one
+/// pool of `N` distinct names: a library where nearly every track has its
+/// own artist and album fills the pool before the track table, after about
+/// `N / 2` tracks, and [`insert`](Self::insert) then returns
+/// [`IndexError::Full`].  [`interned_count`](Self::interned_count) shows how
+/// much of the pool is used.  `R` holds the records; see the
+/// [module docs](self).
+pub struct TrackIndex<const N: usize, R: ExternalRam = InlineRam<INLINE_POOL_BYTES>> {
+    ram: R,
+    region: RamRegion,
+    tracks: Vec<TrackEntry, N>,
+    /// Interned names, indexed by id.
+    strings: Vec<Interned, N>,
+    /// Ids of `strings`, ordered by hash.
+    by_hash: Vec<u16, N>,
+    used: usize,
 }
 
-/// Alias for hardware full catalogue — **must live in SDRAM, never on stack**.
-pub type FullIndex = TrackIndex<MAX_TRACKS>;
+/// Alias for the hardware full catalogue, records in SDRAM via `R`.
+pub type FullIndex<R> = TrackIndex<MAX_TRACKS, R>;
 
-/// Alias used in tests (stack-safe, capacity 64).
+/// Alias used in tests (on-chip records, capacity 64).
 pub type SmallIndex = TrackIndex<64>;
 
 impl<const N: usize> TrackIndex<N> {
-    /// Create an empty index.
+    /// Create an empty index backed by on-chip [`InlineRam`].
     pub const fn new() -> Self {
-        TrackIndex { tracks: Vec::new() }
+        TrackIndex {
+            ram: InlineRam::new(),
+            region: RamRegion {
+                offset: 0,
+                len: INLINE_POOL_BYTES,
+            },
+            tracks: Vec::new(),
+            strings: Vec::new(),
+            by_hash: Vec::new(),
+            used: 0,
+        }
+    }
+}
+
+impl<const N: usize, R: ExternalRam> TrackIndex<N, R> {
+    /// Create an empty index storing records in [`RamRegion::LIBRARY_INDEX`].
+    pub fn with_ram(ram: R) -> Self {
+        Self::with_region(ram, RamRegion::LIBRARY_INDEX)
+    }
+
+    /// Create an empty index storing records in `region` of `ram`.
+    pub fn with_region(ram: R, region: RamRegion) -> Self {
+        TrackIndex {
+            ram,
+            region,
+            tracks: Vec::new(),
+            strings: Vec::new(),
+            by_hash: Vec::new(),
+            used: 0,
+        }
     }
 
     /// Append `track` to the index.
     ///
-    /// Returns `Err(IndexError::Full)` when capacity `N` is exhausted.
+    /// Returns `Err(IndexError::Full)` when capacity `N`, the shared name
+    /// pool or the record region is exhausted, `Err(IndexError::Storage)`
+    /// when a write fails.
+    /// The index is unchanged on error, apart from names interned before
+    /// the failure.
     pub fn insert(&mut self, track: Track) -> Result<(), IndexError> {
-        self.tracks.push(track).map_err(|_| IndexError::Full)
+        if self.tracks.is_full() {
+            return Err(IndexError::Full);
+        }
+        let artist = self.intern(track.artist.as_str())?;
+        let album = self.intern(track.album.as_str())?;
+
+        let title = track.title.as_bytes();
+        let path = track.file_path.as_bytes();
+        let title_len = u8::try_from(title.len()).map_err(|_| IndexError::Full)?;
+        let path_len = u16::try_from(path.len()).map_err(|_| IndexError::Full)?;
+        let mut tail = [0u8; 9];
+        let (duration, rest) = tail.split_at_mut(4);
+        let (rate, format) = rest.split_at_mut(4);
+        duration.copy_from_slice(&track.duration_secs.to_le_bytes());
+        rate.copy_from_slice(&track.sample_rate.to_le_bytes());
+        if let Some(f) = format.first_mut() {
+            *f = format_code(track.format);
+        }
+
+        let record = self.used;
+        let parts: [&[u8]; 5] = [&[title_len], title, &path_len.to_le_bytes(), path, &tail];
+        self.append(&parts)?;
+        let entry = TrackEntry {
+            record: u32::try_from(record).map_err(|_| IndexError::Full)?,
+            artist,
+            album,
+        };
+        self.tracks.push(entry).map_err(|_| IndexError::Full)
+    }
+
+    /// Return the track at zero-based `pos`, read back from record storage,
+    /// or `None` when out of range or unreadable.
+    pub fn get(&self, pos: usize) -> Option<Track> {
+        self.try_get(pos).ok()
+    }
+
+    /// As [`get`](Self::get), distinguishing a missing position from a
+    /// storage failure.
+    pub fn try_get(&self, pos: usize) -> Result<Track, IndexError> {
+        let entry = self.tracks.get(pos).ok_or(IndexError::OutOfBounds)?;
+        let mut at = usize::try_from(entry.record).map_err(|_| IndexError::Storage)?;
+        let mut buf = [0u8; 256];
+
+        let title_len = usize::from(self.read_u8(&mut at)?);
+        let title = self.read_str(&mut at, title_len, &mut buf)?;
+        let mut track = Track::default();
+        track
+            .title
+            .push_str(title)
+            .map_err(|_| IndexError::Storage)?;
+
+        let mut len = [0u8; 2];
+        self.read(&mut at, &mut len)?;
+        let path = self.read_str(&mut at, usize::from(u16::from_le_bytes(len)), &mut buf)?;
+        track.file_path.clear();
+        track
+            .file_path
+            .push_str(path)
+            .map_err(|_| IndexError::Storage)?;
+
+        let mut tail = [0u8; 9];
+        self.read(&mut at, &mut tail)?;
+        let [d0, d1, d2, d3, r0, r1, r2, r3, format] = tail;
+        track.duration_secs = u32::from_le_bytes([d0, d1, d2, d3]);
+        track.sample_rate = u32::from_le_bytes([r0, r1, r2, r3]);
+        track.format = format_from_code(format).ok_or(IndexError::Storage)?;
+
+        let artist = self.name(entry.artist, &mut buf)?;
+        track
+            .artist
+            .push_str(artist)
+            .map_err(|_| IndexError::Storage)?;
+        let album = self.name(entry.album, &mut buf)?;
+        track
+            .album
+            .push_str(album)
+            .map_err(|_| IndexError::Storage)?;
+        Ok(track)
     }
 
-    /// Return a reference to the track at zero-based `pos`, or `None`.
-    pub fn get(&self, pos: usize) -> Option<&Track> {
-        self.tracks.get(pos)
+    /// Interned artist id of the track at `pos`.  Tracks by the same artist
+    /// share an id, so filtering by artist never touches record storage.
+    pub fn artist_id(&self, pos: usize) -> Option<u16> {
+        self.tracks.get(pos).map(|t| t.artist)
+    }
+
+    /// Interned album id of the track at `pos`.
+    pub fn album_id(&self, pos: usize) -> Option<u16> {
+        self.tracks.get(pos).map(|t| t.album)
     }
 
     /// Number of tracks currently stored.
@@ -64,9 +252,130 @@ impl<const N: usize> TrackIndex<N> {
         self.tracks.is_empty()
     }
 
-    /// Remove all tracks, resetting length to zero.
+    /// Number of distinct artist and album names stored, out of `N`.
+    pub fn interned_count(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Bytes of record storage in use.
+    pub fn bytes_used(&self) -> usize {
+        self.used
+    }
+
+    /// Remove all tracks, resetting length to zero.  Record storage is
+    /// reused from the start; its contents are left in place.
     pub fn clear(&mut self) {
         self.tracks.clear();
+        self.strings.clear();
+        self.by_hash.clear();
+        self.used = 0;
+    }
+
+    /// Id of `name`, writing it to record storage on first sight.
+    fn intern(&mut self, name: &str) -> Result<u16, IndexError> {
+        let hash = fnv1a(name.as_bytes());
+        let first = self.by_hash.partition_point(|&id| self.hash_of(id) < hash);
+        let mut buf = [0u8; 256];
+        // Names sharing the hash sit next to each other from `first`.
+        for &id in self.by_hash.get(first..).unwrap_or_default() {
+            if self.hash_of(id) != hash {
+                break;
+            }
+            if self.name(id, &mut buf)? == name {
+                return Ok(id);
+            }
+        }
+
+        if self.strings.is_full() {
+            return Err(IndexError::Full);
+        }
+        let id = u16::try_from(self.strings.len()).map_err(|_| IndexError::Full)?;
+        let len = u8::try_from(name.len()).map_err(|_| IndexError::Full)?;
+        let offset = u32::try_from(self.used).map_err(|_| IndexError::Full)?;
+        self.append(&[&[len], name.as_bytes()])?;
+        self.strings
+            .push(Interned { hash, offset })
+            .map_err(|_| IndexError::Full)?;
+        self.by_hash
+            .insert(first, id)
+            .map_err(|_| IndexError::Full)?;
+        Ok(id)
+    }
+
+    /// Hash of interned name `id` (`u32::MAX` for an unknown id, which
+    /// cannot occur: `by_hash` only holds ids of `strings`).
+    fn hash_of(&self, id: u16) -> u32 {
+        self.strings
+            .get(usize::from(id))
+            .map_or(u32::MAX, |s| s.hash)
+    }
+
+    /// Name with interned id `id`, decoded into `buf`.
+    fn name<'b>(&self, id: u16, buf: &'b mut [u8; 256]) -> Result<&'b str, IndexError> {
+        let s = self
+            .strings
+            .get(usize::from(id))
+            .ok_or(IndexError::Storage)?;
+        let mut at = usize::try_from(s.offset).map_err(|_| IndexError::Storage)?;
+        let len = usize::from(self.read_u8(&mut at)?);
+        self.read_str(&mut at, len, buf)
+    }
+
+    /// Write `parts` back to back at the end of the used region.
+    fn append(&mut self, parts: &[&[u8]]) -> Result<(), IndexError> {
+        let total = parts
+            .iter()
+            .try_fold(0usize, |sum, p| sum.checked_add(p.len()))
+            .ok_or(IndexError::Full)?;
+        let end = self.used.checked_add(total).ok_or(IndexError::Full)?;
+        if end > self.region.len {
+            return Err(IndexError::Full);
+        }
+        let mut at = self.used;
+        for part in parts {
+            let offset = self.region.offset.checked_add(at).ok_or(IndexError::Full)?;
+            self.ram
+                .write(offset, part)
+                .map_err(|_| IndexError::Storage)?;
+            at = at.saturating_add(part.len());
+        }
+        self.used = end;
+        Ok(())
+    }
+
+    /// Read `buf.len()` bytes at region offset `*at`, advancing it.
+    fn read(&self, at: &mut usize, buf: &mut [u8]) -> Result<(), IndexError> {
+        let end = at.checked_add(buf.len()).ok_or(IndexError::Storage)?;
+        if end > self.used {
+            return Err(IndexError::Storage);
+        }
+        let offset = self
+            .region
+            .offset
+            .checked_add(*at)
+            .ok_or(IndexError::Storage)?;
+        self.ram
+            .read(offset, buf)
+            .map_err(|_| IndexError::Storage)?;
+        *at = end;
+        Ok(())
+    }
+
+    fn read_u8(&self, at: &mut usize) -> Result<u8, IndexError> {
+        let mut byte = [0u8; 1];
+        self.read(at, &mut byte)?;
+        Ok(byte[0])
+    }
+
+    fn read_str<'b>(
+        &self,
+        at: &mut usize,
+        len: usize,
+        buf: &'b mut [u8; 256],
+    ) -> Result<&'b str, IndexError> {
+        let dst = buf.get_mut(..len).ok_or(IndexError::Storage)?;
+        self.read(at, dst)?;
+        core::str::from_utf8(dst).map_err(|_| IndexError::Storage)
     }
 }
 
@@ -76,6 +385,89 @@ impl<const N: usize> Default for TrackIndex<N> {
     }
 }
 
+/// On-chip byte array implementing [`ExternalRam`], for indexes small
+/// enough not to need SDRAM.
+pub struct InlineRam<const BYTES: usize>([u8; BYTES]);
+
+impl<const BYTES: usize> InlineRam<BYTES> {
+    /// Zero-filled storage.
+    // The array is the point of the type: it is meant to live in a static
+    // or inside the owning index, not to be passed around on the stack.
+    #[allow(clippy::large_stack_arrays)]
+    pub const fn new() -> Self {
+        Self([0; BYTES])
+    }
+}
+
+impl<const BYTES: usize> Default for InlineRam<BYTES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BYTES: usize> ExternalRam for InlineRam<BYTES> {
+    type Error = IndexError;
+
+    fn read(&self, offset: usize, buf: &mut [u8]) -> Result<(), IndexError> {
+        let end = offset
+            .checked_add(buf.len())
+            .ok_or(IndexError::OutOfBounds)?;
+        let src = self.0.get(offset..end).ok_or(IndexError::OutOfBounds)?;
+        buf.copy_from_slice(src);
+        Ok(())
+    }
+
+    fn write(&mut self, offset: usize, data: &[u8]) -> Result<(), IndexError> {
+        let end = offset
+            .checked_add(data.len())
+            .ok_or(IndexError::OutOfBounds)?;
+        let dst = self.0.get_mut(offset..end).ok_or(IndexError::OutOfBounds)?;
+        dst.copy_from_slice(data);
+        Ok(())
+    }
+
+    fn zero(&mut self, offset: usize, len: usize) -> Result<(), IndexError> {
+        let end = offset.checked_add(len).ok_or(IndexError::OutOfBounds)?;
+        self.0
+            .get_mut(offset..end)
+            .ok_or(IndexError::OutOfBounds)?
+            .fill(0);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        BYTES
+    }
+}
+
+fn format_code(format: AudioFormat) -> u8 {
+    match format {
+        AudioFormat::Flac => 0,
+        AudioFormat::Mp3 => 1,
+        AudioFormat::Wav => 2,
+        AudioFormat::Aac => 3,
+        AudioFormat::Opus => 4,
+    }
+}
+
+fn format_from_code(code: u8) -> Option<AudioFormat> {
+    match code {
+        0 => Some(AudioFormat::Flac),
+        1 => Some(AudioFormat::Mp3),
+        2 => Some(AudioFormat::Wav),
+        3 => Some(AudioFormat::Aac),
+        4 => Some(AudioFormat::Opus),
+        _ => None,
+    }
+}
+
+/// FNV-1a hash used to find interned names.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811C_9DC5u32, |hash, &b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 #[allow(clippy::expect_used)] // Tests use expect() for readable assertions
@@ -89,6 +481,16 @@ mod tests {
         Track::new(path, AudioFormat::Flac)
     }
 
+    fn tagged(path: &str, artist: &str, album: &str, title: &str) -> Track {
+        let mut t = Track::new(path, AudioFormat::Opus);
+        t.artist.push_str(artist).expect("artist");
+        t.album.push_str(album).expect("album");
+        t.title.push_str(title).expect("title");
+        t.duration_secs = 321;
+        t.sample_rate = 48_000;
+        t
+    }
+
     #[test]
     fn test_index_starts_empty() {
         let idx = SmallIndex::new();
@@ -137,6 +539,7 @@ mod tests {
     fn test_index_get_out_of_bounds() {
         let idx = SmallIndex::new();
         assert!(idx.get(1000).is_none());
+        assert_eq!(idx.try_get(0).unwrap_err(), IndexError::OutOfBounds);
     }
 
     #[test]
@@ -145,6 +548,118 @@ mod tests {
         idx.insert(make_track("/a.flac")).expect("insert");
         idx.clear();
         assert_eq!(idx.len(), 0);
+        assert_eq!(idx.bytes_used(), 0);
+    }
+
+    #[test]
+    fn test_index_round_trips_every_field() {
+        let mut idx = SmallIndex::new();
+        idx.insert(tagged("/m/p/d/01.opus", "Portishead", "Dummy", "Mysterons"))
+            .expect("insert");
+        let t = idx.get(0).expect("track");
+        assert_eq!(t.title.as_str(), "Mysterons");
+        assert_eq!(t.artist.as_str(), "Portishead");
+        assert_eq!(t.album.as_str(), "Dummy");
+        assert_eq!(t.file_path.as_str(), "/m/p/d/01.opus");
+        assert_eq!(t.duration_secs, 321);
+        assert_eq!(t.sample_rate, 48_000);
+        assert_eq!(t.format, AudioFormat::Opus);
+    }
+
+    #[test]
+    fn test_index_interns_artist_and_album_names() {
+        let mut idx = SmallIndex::new();
+        idx.insert(tagged("/1.flac", "Portishead", "Dummy", "Mysterons"))
+            .expect("insert");
+        let after_first = idx.bytes_used();
+        idx.insert(tagged("/2.flac", "Portishead", "Dummy", "Sour Times"))
+            .expect("insert");
+        // The second record stores no names: just its own title and path.
+        assert_eq!(idx.bytes_used() - after_first, 1 + 10 + 2 + 7 + 9);
+        idx.insert(tagged("/3.flac", "Portishead", "Third", "Silence"))
+            .expect("insert");
+
+        assert_eq!(idx.interned_count(), 3, "Portishead, Dummy, Third");
+        assert_eq!(idx.artist_id(0), idx.artist_id(2));
+        assert_eq!(idx.album_id(0), idx.album_id(1));
+        assert_ne!(idx.album_id(1), idx.album_id(2));
+        assert_eq!(idx.get(2).expect("track").album.as_str(), "Third");
+    }
+
+    #[test]
+    fn test_index_finds_names_interned_in_any_order() {
+        let mut idx = TrackIndex::<64, InlineRam<8192>>::with_region(
+            InlineRam::new(),
+            RamRegion {
+                offset: 0,
+                len: 8192,
+            },
+        );
+        let artist = |i: usize| format!("Artist {}", (i * 7) % 20);
+        for i in 0..40 {
+            let path = format!("/{i}.flac");
+            idx.insert(tagged(&path, &artist(i), "Album", "T"))
+                .expect("insert");
+        }
+        assert_eq!(idx.interned_count(), 21, "20 artists and one album");
+        for i in 0..40 {
+            let first = (0..40).find(|&j| artist(j) == artist(i)).unwrap();
+            assert_eq!(idx.artist_id(i), idx.artist_id(first));
+            assert_eq!(idx.get(i).expect("track").artist.as_str(), artist(i));
+        }
+    }
+
+    #[test]
+    fn test_index_name_pool_is_shared_by_artists_and_albums() {
+        let mut idx = TrackIndex::<4, InlineRam<1024>>::with_region(
+            InlineRam::new(),
+            RamRegion {
+                offset: 0,
+                len: 1024,
+            },
+        );
+        idx.insert(tagged("/1.flac", "A", "B", "1"))
+            .expect("insert");
+        idx.insert(tagged("/2.flac", "C", "D", "2"))
+            .expect("insert");
+        assert_eq!(idx.interned_count(), 4);
+        assert_eq!(
+            idx.insert(tagged("/3.flac", "E", "F", "3")),
+            Err(IndexError::Full),
+            "four names fill the pool of a 4-track index"
+        );
+        idx.insert(tagged("/3.flac", "A", "D", "3"))
+            .expect("known names need no new slot");
+        assert_eq!(idx.len(), 3);
+    }
+
+    #[test]
+    fn test_index_full_when_region_exhausted() {
+        let region = RamRegion { offset: 0, len: 32 };
+        let mut idx = TrackIndex::<8, InlineRam<32>>::with_region(InlineRam::new(), region);
+        idx.insert(make_track("/a.flac")).expect("fits");
+        let used = idx.bytes_used();
+        assert_eq!(
+            idx.insert(make_track("/a-much-longer-path.flac")),
+            Err(IndexError::Full)
+        );
+        assert_eq!(idx.len(), 1);
+        assert_eq!(idx.bytes_used(), used, "failed insert writes no record");
+    }
+
+    #[test]
+    fn test_index_uses_region_offset() {
+        let region = RamRegion {
+            offset: 100,
+            len: 200,
+        };
+        let mut idx = TrackIndex::<4, InlineRam<300>>::with_region(InlineRam::new(), region);
+        idx.insert(make_track("/x.flac")).expect("insert");
+        assert_eq!(idx.get(0).expect("track").file_path.as_str(), "/x.flac");
+        // [""] interned at 100, then the record: title_len, path_len.
+        let mut path_len = [0u8; 2];
+        idx.ram.read(102, &mut path_len).expect("read");
+        assert_eq!(u16::from_le_bytes(path_len), 7);
     }
 
     /// Append the decimal representation of `n` to `s`.
@@ -2,20 +2,34 @@
 //!
 //! # Modules
 //!
+//! - [`art_cache`] — album art thumbnails cached in external SDRAM
+//! - [`chapters`] — audiobook chapter marks from MP4 `chpl` atoms and CUE sheets
+//! - [`delta`] — library deltas (playlists, tag corrections) from the companion app
 //! - [`track`] — `Track` record and `AudioFormat` enum
-//! - [`index`] — `TrackIndex<N>` fixed-capacity catalogue
-//! - [`scanner`] — directory walk and extension filtering
+//! - [`index`] — `TrackIndex<N>` catalogue with records in SDRAM and interned names
+//! - [`lyrics`] — LRC lyrics parsing and time-to-line lookup
+//! - [`playlist`] — M3U8 playlist export with paths relative to the card
+//! - [`podcast`] — podcast episode metadata, ordering and played/resume state
+//! - [`scanner`] — bounded iterative directory walk, extension filtering, exclusions and scan progress
 //! - [`metadata`] — magic-byte format detection
+//! - [`tag_overrides`] — title/artist/album corrections keyed by file hash
 
 #![cfg_attr(not(any(test, feature = "std")), no_std)]
 // unwrap_used, expect_used, panic enforced at workspace level (Cargo.toml)
 // TODO: Add rustdoc to all public items (tracked as tech debt)
 #![allow(missing_docs)]
 
+pub mod art_cache;
 pub mod binary;
+pub mod chapters;
+pub mod delta;
 pub mod index;
+pub mod lyrics;
 pub mod metadata;
+pub mod playlist;
+pub mod podcast;
 pub mod scanner;
+pub mod tag_overrides;
 pub mod track;
 
 #[cfg(feature = "std")]
@@ -25,8 +39,28 @@ pub mod reader;
 pub use reader::{ReaderError, SoulLibraryReader};
 
 // Top-level re-exports for convenience
-pub use binary::{IndexEntry, LibraryError, ManifestBin, TrackMeta, sort_key_for};
-pub use index::{FullIndex, IndexError, SmallIndex, TrackIndex, MAX_TRACKS};
+pub use art_cache::{ArtCache, ArtCacheError, ArtCacheStats};
+pub use binary::{
+    BrowseHeader, BrowseOrder, IndexEntry, LibraryError, ManifestBin, TrackMeta, sort_key_for,
+};
+pub use chapters::{ChapterError, Chapters};
+pub use delta::{
+    apply_delta, DeltaError, DeltaOp, DeltaReport, DeltaStore, DeltaWriter, LibraryDelta,
+};
+pub use index::{
+    FullIndex, IndexError, InlineRam, SmallIndex, TrackIndex, INLINE_POOL_BYTES, MAX_TRACKS,
+};
+pub use lyrics::{LyricLine, Lyrics, LyricsError};
 pub use metadata::detect_format;
-pub use scanner::{ScanEntry, Scanner};
+pub use playlist::{
+    export_tracks, playlist_path, ExportError, ExportReport, M3uWriter, PlaylistError, PlaylistSink,
+};
+pub use podcast::{Episode, EpisodeLog, EpisodeOrder, PodcastError};
+pub use scanner::{
+    FatAttributes, ScanCancel, ScanCancelled, ScanEntry, ScanFilter, ScanProgress, ScanSession,
+    ScanWalk, Scanner, WalkDir, WalkError, MAX_SCAN_DEPTH,
+};
+pub use tag_overrides::{
+    FileHash, TagField, TagOverride, TagOverrideError, TagOverrides, MAX_TAG_OVERRIDES,
+};
 pub use track::{AudioFormat, Track};
@@ -0,0 +1,293 @@
+//! Lyrics — LRC file parsing and time-to-line lookup.
+//!
+//! LRC files sit next to the track they belong to (`track.flac` →
+//! `track.lrc`, see [`Scanner::lyrics_path_for`]).  Each line carries one or
+//! more `[mm:ss.xx]` timestamps followed by the text:
+//!
+//! ```text
+//! [ar:Portishead]
+//! [offset:+250]
+//! [00:12.40]Dreamt of a ship...
+//! [00:31.00][01:45.20]Chorus line sung twice
+//! ```
+//!
+//! Repeated timestamps expand into one [`LyricLine`] each; ID tags other
+//! than `offset` are ignored, as are lines without a timestamp.  Text longer
+//! than [`MAX_LYRIC_LINE_BYTES`] is cut at a character boundary, and lines
+//! past the capacity are dropped with [`Lyrics::is_truncated`] set, so a
+//! malformed file never fails the whole parse.
+//!
+//! [`Scanner::lyrics_path_for`]: crate::scanner::Scanner::lyrics_path_for
+
+use heapless::{String, Vec};
+
+/// Longest lyric line kept, in UTF-8 bytes.
+pub const MAX_LYRIC_LINE_BYTES: usize = 80;
+
+/// Timed lines held per track (after repeated timestamps are expanded).
+pub const MAX_LYRIC_LINES: usize = 160;
+
+/// Error from [`Lyrics::parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricsError {
+    /// The file has no timestamped lines (plain-text lyrics).
+    Unsynced,
+}
+
+/// One timed line of lyrics.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LyricLine {
+    /// Start of the line in the track, offset already applied.
+    pub time_ms: u32,
+    /// Line text; empty for instrumental gaps.
+    pub text: String<MAX_LYRIC_LINE_BYTES>,
+}
+
+/// Parsed, time-sorted lyrics for one track.
+#[derive(Debug, Clone)]
+pub struct Lyrics<const N: usize = MAX_LYRIC_LINES> {
+    lines: Vec<LyricLine, N>,
+    truncated: bool,
+}
+
+impl<const N: usize> Lyrics<N> {
+    /// Parse the contents of an LRC file.
+    ///
+    /// # Errors
+    ///
+    /// [`LyricsError::Unsynced`] if no line carries a timestamp.
+    pub fn parse(src: &str) -> Result<Self, LyricsError> {
+        let src = src.strip_prefix('\u{feff}').unwrap_or(src);
+        let mut lyrics = Self {
+            lines: Vec::new(),
+            truncated: false,
+        };
+        let mut offset_ms: i32 = 0;
+        let mut any_timed = false;
+
+        for raw in src.lines() {
+            let mut rest = raw.trim();
+            let mut times: Vec<u32, 8> = Vec::new();
+            while let Some((tag, after)) = split_tag(rest) {
+                if let Some(ms) = parse_timestamp(tag) {
+                    // More than 8 stamps on a line is not worth keeping.
+                    times.push(ms).ok();
+                } else if let Some(value) = tag.strip_prefix("offset:") {
+                    offset_ms = value.trim().parse().unwrap_or(offset_ms);
+                }
+                rest = after;
+            }
+            if times.is_empty() {
+                continue;
+            }
+            any_timed = true;
+            let text = truncated_text(rest.trim());
+            for ms in times {
+                lyrics.insert(LyricLine {
+                    time_ms: ms,
+                    text: text.clone(),
+                });
+            }
+        }
+
+        if !any_timed {
+            return Err(LyricsError::Unsynced);
+        }
+        // A positive offset shows lyrics earlier.
+        for line in &mut lyrics.lines {
+            line.time_ms = apply_offset(line.time_ms, offset_ms);
+        }
+        Ok(lyrics)
+    }
+
+    /// Insert keeping time order; equal times keep file order.
+    fn insert(&mut self, line: LyricLine) {
+        let idx = self.lines.partition_point(|l| l.time_ms <= line.time_ms);
+        if self.lines.is_full() {
+            self.truncated = true;
+            return;
+        }
+        // Cannot fail: capacity checked above and idx <= len.
+        self.lines.insert(idx, line).ok();
+    }
+
+    /// All lines in time order.
+    pub fn lines(&self) -> &[LyricLine] {
+        &self.lines
+    }
+
+    /// Line `index`, if present.
+    pub fn get(&self, index: usize) -> Option<&LyricLine> {
+        self.lines.get(index)
+    }
+
+    /// Number of timed lines.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// `true` when no lines were parsed.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// `true` when lines were dropped because the file exceeded `N`.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Index of the line being sung at `position_ms`, or `None` before the
+    /// first line starts.
+    pub fn line_at(&self, position_ms: u64) -> Option<usize> {
+        self.lines
+            .partition_point(|l| u64::from(l.time_ms) <= position_ms)
+            .checked_sub(1)
+    }
+}
+
+/// Split `"[tag]rest"` into `("tag", "rest")`.
+fn split_tag(s: &str) -> Option<(&str, &str)> {
+    let inner = s.strip_prefix('[')?;
+    let end = inner.find(']')?;
+    let tag = inner.get(..end)?;
+    let rest = inner.get(end.saturating_add(1)..)?;
+    Some((tag, rest))
+}
+
+/// Parse `mm:ss`, `mm:ss.f`, `mm:ss.ff`, `mm:ss.fff` (or `:` before the
+/// fraction) into milliseconds.
+fn parse_timestamp(tag: &str) -> Option<u32> {
+    let (min, rest) = tag.split_once(':')?;
+    let (sec, frac) = match rest.find(['.', ':']) {
+        Some(i) => (rest.get(..i)?, rest.get(i.saturating_add(1)..)?),
+        None => (rest, ""),
+    };
+    let min: u32 = parse_digits(min)?;
+    let sec: u32 = parse_digits(sec)?;
+    if sec >= 60 {
+        return None;
+    }
+    let frac_ms = match frac.len() {
+        0 => 0,
+        1 => parse_digits(frac)?.checked_mul(100)?,
+        2 => parse_digits(frac)?.checked_mul(10)?,
+        3 => parse_digits(frac)?,
+        _ => return None,
+    };
+    min.checked_mul(60_000)?
+        .checked_add(sec.checked_mul(1_000)?)?
+        .checked_add(frac_ms)
+}
+
+/// Parse a non-empty run of ASCII digits (no sign, no spaces).
+fn parse_digits(s: &str) -> Option<u32> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Copy `text`, cutting at the last character that fits.
+fn truncated_text(text: &str) -> String<MAX_LYRIC_LINE_BYTES> {
+    let mut out = String::new();
+    for c in text.chars() {
+        if out.push(c).is_err() {
+            break;
+        }
+    }
+    out
+}
+
+fn apply_offset(time_ms: u32, offset_ms: i32) -> u32 {
+    if offset_ms >= 0 {
+        time_ms.saturating_sub(offset_ms.unsigned_abs())
+    } else {
+        time_ms.saturating_add(offset_ms.unsigned_abs())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "[ar:Portishead]\r\n\
+                          [ti:Mysterons]\r\n\
+                          [00:12.40]First line\r\n\
+                          [00:31.00][01:45.20]Chorus\r\n\
+                          [00:20.5]Second line\r\n\
+                          no timestamp here\r\n\
+                          [00:40.123]\r\n";
+
+    #[test]
+    fn test_parse_sorts_and_expands_repeated_timestamps() {
+        let lyrics: Lyrics = Lyrics::parse(SAMPLE).unwrap();
+        let times: std::vec::Vec<u32> = lyrics.lines().iter().map(|l| l.time_ms).collect();
+        assert_eq!(times, [12_400, 20_500, 31_000, 40_123, 105_200]);
+        assert_eq!(lyrics.lines()[1].text.as_str(), "Second line");
+        assert_eq!(lyrics.lines()[2].text.as_str(), "Chorus");
+        assert_eq!(lyrics.lines()[3].text.as_str(), "");
+        assert_eq!(lyrics.lines()[4].text.as_str(), "Chorus");
+    }
+
+    #[test]
+    fn test_line_at_follows_position() {
+        let lyrics: Lyrics = Lyrics::parse(SAMPLE).unwrap();
+        assert_eq!(lyrics.line_at(0), None);
+        assert_eq!(lyrics.line_at(12_399), None);
+        assert_eq!(lyrics.line_at(12_400), Some(0));
+        assert_eq!(lyrics.line_at(30_999), Some(1));
+        assert_eq!(lyrics.line_at(10 * 60_000), Some(4));
+    }
+
+    #[test]
+    fn test_offset_shifts_every_line() {
+        let earlier: Lyrics = Lyrics::parse("[offset:+500]\n[00:10.00]a\n[00:00.20]b").unwrap();
+        assert_eq!(earlier.lines()[0].time_ms, 0);
+        assert_eq!(earlier.lines()[1].time_ms, 9_500);
+        let later: Lyrics = Lyrics::parse("[offset:-500]\n[00:10.00]a").unwrap();
+        assert_eq!(later.lines()[0].time_ms, 10_500);
+    }
+
+    #[test]
+    fn test_long_lines_cut_at_char_boundary() {
+        let text = "é".repeat(MAX_LYRIC_LINE_BYTES);
+        let src = std::format!("[00:01.00]{text}");
+        let lyrics: Lyrics = Lyrics::parse(&src).unwrap();
+        let line = &lyrics.lines()[0].text;
+        assert_eq!(line.len(), MAX_LYRIC_LINE_BYTES);
+        assert!(line.chars().all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn test_excess_lines_are_dropped_and_flagged() {
+        let lyrics: Lyrics<2> = Lyrics::parse("[00:01]a\n[00:02]b\n[00:03]c").unwrap();
+        assert_eq!(lyrics.len(), 2);
+        assert!(lyrics.is_truncated());
+    }
+
+    #[test]
+    fn test_malformed_timestamps_are_not_lines() {
+        for bad in [
+            "[00:61.00]x",
+            "[aa:10.00]x",
+            "[00:10.0000]x",
+            "[0010]x",
+            "[00:10.00x",
+        ] {
+            assert_eq!(
+                Lyrics::<4>::parse(bad).unwrap_err(),
+                LyricsError::Unsynced,
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_plain_text_is_unsynced() {
+        assert_eq!(
+            Lyrics::<4>::parse("\u{feff}just words\nno times").unwrap_err(),
+            LyricsError::Unsynced
+        );
+    }
+}
@@ -5,9 +5,114 @@
 
 use crate::track::AudioFormat;
 
-/// Detect the audio format from the first bytes of a file.
+/// One format's entry in [`FORMATS`]: how the scanner and the playback
+/// task recognise it.
+///
+/// The playback codec registry (`playback::decoder::CODECS`) pairs each
+/// format with its decoder constructor; this table is the single source of
+/// truth for extensions and magic bytes.
+#[derive(Debug, Clone, Copy)]
+pub struct FormatInfo {
+    /// Format this entry describes.
+    pub format: AudioFormat,
+    /// Human-readable codec name.
+    pub name: &'static str,
+    /// Lowercase file extensions, the preferred one first.
+    pub extensions: &'static [&'static str],
+    /// Header bytes [`sniff`](Self::sniff) needs to recognise the format.
+    pub sniff_len: usize,
+    /// `true` when the file header carries this format's magic bytes.
+    pub sniff: fn(header: &[u8]) -> bool,
+}
+
+impl FormatInfo {
+    /// `true` when `ext` (any case) is one of this format's extensions.
+    pub fn matches_extension(&self, ext: &str) -> bool {
+        self.extensions
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(ext))
+    }
+}
+
+/// FLAC: `fLaC` stream marker.
+pub const FLAC: FormatInfo = FormatInfo {
+    format: AudioFormat::Flac,
+    name: "FLAC",
+    extensions: &["flac"],
+    sniff_len: 4,
+    sniff: |header| header.get(..4) == Some(b"fLaC".as_slice()),
+};
+
+/// MP3: an `ID3` tag, or an MPEG frame sync (0xFF followed by a byte with
+/// the top three bits set; MPEG-1/2/2.5, all layers).
+pub const MP3: FormatInfo = FormatInfo {
+    format: AudioFormat::Mp3,
+    name: "MP3",
+    extensions: &["mp3"],
+    sniff_len: 3,
+    sniff: |header| {
+        header.get(..3) == Some(b"ID3".as_slice())
+            || matches!(header, [0xFF, second, ..] if second & 0xE0 == 0xE0)
+    },
+};
+
+/// WAV: `RIFF` container.
+pub const WAV: FormatInfo = FormatInfo {
+    format: AudioFormat::Wav,
+    name: "WAV",
+    extensions: &["wav"],
+    sniff_len: 4,
+    sniff: |header| header.get(..4) == Some(b"RIFF".as_slice()),
+};
+
+/// AAC: an `ftyp` box at offset 4 (MP4, M4A, M4B).
 ///
-/// Pass at least 4 bytes for reliable detection.
+/// Any brand is claimed; the decoder rejects MP4 files whose audio track
+/// is another codec (e.g. ALAC).  Listed in [`FORMATS`] only with the `aac`
+/// feature.
+pub const AAC: FormatInfo = FormatInfo {
+    format: AudioFormat::Aac,
+    name: "AAC",
+    extensions: &["m4a", "m4b"],
+    sniff_len: 8,
+    sniff: |header| header.get(4..8) == Some(b"ftyp".as_slice()),
+};
+
+/// Opus: an `OggS` page whose single-segment first packet is the
+/// `OpusHead` identification header (the page header before it is 28
+/// bytes).  Ogg files carrying another codec (Vorbis, FLAC) are not
+/// claimed.  Listed in [`FORMATS`] only with the `opus` feature.
+pub const OPUS: FormatInfo = FormatInfo {
+    format: AudioFormat::Opus,
+    name: "Opus",
+    extensions: &["opus"],
+    sniff_len: 36,
+    sniff: |header| {
+        header.get(..4) == Some(b"OggS".as_slice())
+            && header.get(28..36) == Some(b"OpusHead".as_slice())
+    },
+};
+
+/// Every recognised format, in sniffing order.
+///
+/// MP3's frame-sync sniffer is the loosest, so formats with a fixed magic
+/// are tried around it in the order they have always been detected.
+///
+/// The AAC and Opus decoders need `std`, so their entries are behind the
+/// `aac` and `opus` features (enabled by the playback features of the same
+/// name): without them the scanner skips `.m4a` and `.opus` files instead
+/// of listing tracks the device cannot play.
+pub const FORMATS: &[FormatInfo] = &[
+    FLAC,
+    MP3,
+    WAV,
+    #[cfg(feature = "aac")]
+    AAC,
+    #[cfg(feature = "opus")]
+    OPUS,
+];
+
+/// Detect the audio format from the first bytes of a file.
 ///
 /// | Format | Magic bytes                          |
 /// |--------|--------------------------------------|
@@ -15,32 +120,17 @@ use crate::track::AudioFormat;
 /// | MP3    | `ID3`  (0x49 0x44 0x33)              |
 /// | MP3    | MPEG sync word (0xFF, high 3 bits of next byte = 0xE0) |
 /// | WAV    | `RIFF` (0x52 0x49 0x46 0x46)        |
+/// | AAC    | `ftyp` at offset 4 (MP4 container; `aac` feature) |
+/// | Opus   | `OggS` page starting with `OpusHead` (`opus` feature) |
 ///
-/// Returns `None` when the header is empty or does not match a known format.
-#[allow(clippy::indexing_slicing)] // Safety: all accesses guarded by header.len() >= N checks
+/// Pass at least the largest [`FormatInfo::sniff_len`] (36 bytes, for Ogg
+/// Opus) for reliable detection.  Returns `None` when the header is empty
+/// or no [`FORMATS`] entry matches.
 pub fn detect_format(header: &[u8]) -> Option<AudioFormat> {
-    // FLAC: 'f','L','a','C'
-    if header.len() >= 4 && &header[..4] == b"fLaC" {
-        return Some(AudioFormat::Flac);
-    }
-
-    // MP3 with ID3 tag: 'I','D','3'
-    if header.len() >= 3 && &header[..3] == b"ID3" {
-        return Some(AudioFormat::Mp3);
-    }
-
-    // MP3 sync word: 0xFF followed by a byte where the top 3 bits are all 1
-    // (covers MPEG-1/2/2.5, all layers)
-    if header.len() >= 2 && header[0] == 0xFF && (header[1] & 0xE0) == 0xE0 {
-        return Some(AudioFormat::Mp3);
-    }
-
-    // WAV / RIFF: 'R','I','F','F'
-    if header.len() >= 4 && &header[..4] == b"RIFF" {
-        return Some(AudioFormat::Wav);
-    }
-
-    None
+    FORMATS
+        .iter()
+        .find(|info| (info.sniff)(header))
+        .map(|info| info.format)
 }
 
 #[cfg(test)]
@@ -84,6 +174,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mp4_ftyp_signature() {
+        // 0x00000020 'ftyp' 'M4A '; only claimed when AAC can be decoded.
+        assert_eq!(
+            detect_format(b"\x00\x00\x00\x20ftypM4A "),
+            cfg!(feature = "aac").then_some(AudioFormat::Aac)
+        );
+        // Too short to reach the box type.
+        assert_eq!(detect_format(b"\x00\x00\x00\x20fty"), None);
+    }
+
+    #[test]
+    #[allow(clippy::indexing_slicing)] // Fixed offsets into a 36-byte array
+    fn test_ogg_opus_signature() {
+        let mut header = [0u8; 36];
+        header[..4].copy_from_slice(b"OggS");
+        header[26] = 1; // one lacing segment
+        header[27] = 19; // OpusHead is 19 bytes
+        header[28..].copy_from_slice(b"OpusHead");
+        assert_eq!(
+            detect_format(&header),
+            cfg!(feature = "opus").then_some(AudioFormat::Opus)
+        );
+
+        // Ogg Vorbis is not supported.
+        header[28..].copy_from_slice(b"\x01vorbis\0");
+        assert_eq!(detect_format(&header), None);
+    }
+
     #[test]
     fn test_unknown_signature() {
         assert_eq!(detect_format(&[0x00, 0x00]), None);
@@ -0,0 +1,476 @@
+//! Playlists — M3U8 export of the play queue or any list of tracks.
+//!
+//! Exported playlists go to `Playlists/{name}.m3u8` on the card.  Track
+//! paths are written relative to that folder, so the playlist still
+//! resolves when the card is copied to a desktop or opened in a card
+//! reader:
+//!
+//! ```text
+//! #EXTM3U
+//! #EXTINF:215,Portishead - Mysterons
+//! ../Music/Portishead/Dummy/01 Mysterons.flac
+//! ```
+//!
+//! Memory use does not depend on the playlist length: [`M3uWriter`] formats
+//! one entry at a time into a [`M3U_ENTRY_BYTES`] buffer and hands it to a
+//! [`PlaylistSink`].  [`export_tracks`] feeds it straight from the library
+//! reader, one [`TrackMeta`] at a time.
+//!
+//! [`MemoryPlaylistSink`] is the only sink so far: `platform::Storage` has
+//! no write API, so nothing can write the playlist to the card yet and the
+//! queue screen does not offer an export action.
+//!
+//! An entry that cannot be written — its path does not fit, or the track is
+//! gone from the library — is skipped and counted in [`ExportReport`]; only
+//! storage errors stop an export.
+
+use core::fmt::Write;
+
+use heapless::{CapacityError, String};
+use platform::storage::{File, Storage};
+
+use crate::binary::TrackMeta;
+use crate::reader::{ReaderError, SoulLibraryReader};
+
+/// Card directory holding exported playlists.
+pub const PLAYLISTS_DIR: &str = "Playlists";
+
+/// Playlist file extension (UTF-8 M3U).
+pub const PLAYLIST_EXTENSION: &str = "m3u8";
+
+/// Longest playlist name kept, in UTF-8 bytes.
+pub const MAX_PLAYLIST_NAME_BYTES: usize = 64;
+
+/// Capacity of [`playlist_path`]'s result: `/Playlists/` + name + `.m3u8`.
+pub const PLAYLIST_PATH_BYTES: usize = 80;
+
+/// Longest card path, as in [`TrackMeta::file_path`].
+pub const MAX_CARD_PATH_BYTES: usize = 256;
+
+/// Entry buffer: an `#EXTINF` line with the longest artist and title, then
+/// the longest track path with a few `../` steps to spare.
+pub const M3U_ENTRY_BYTES: usize = 512;
+
+/// First line of every playlist.
+const HEADER: &str = "#EXTM3U\n";
+
+/// Characters FAT does not allow in file names.
+const FAT_RESERVED: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Where exported playlist bytes go.
+///
+/// Bytes arrive in file order; a card-backed sink appends them to an open
+/// file.
+#[allow(async_fn_in_trait)]
+pub trait PlaylistSink {
+    /// Storage error type.
+    type Error: core::fmt::Debug;
+
+    /// Append `bytes` to the playlist.
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// [`PlaylistSink`] collecting up to `CAP` bytes in RAM.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPlaylistSink<const CAP: usize> {
+    bytes: heapless::Vec<u8, CAP>,
+}
+
+/// [`MemoryPlaylistSink`] is out of space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryPlaylistFull;
+
+impl<const CAP: usize> MemoryPlaylistSink<CAP> {
+    /// Create an empty sink.
+    pub const fn new() -> Self {
+        Self {
+            bytes: heapless::Vec::new(),
+        }
+    }
+
+    /// Everything written so far.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl<const CAP: usize> PlaylistSink for MemoryPlaylistSink<CAP> {
+    type Error = MemoryPlaylistFull;
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bytes
+            .extend_from_slice(bytes)
+            .map_err(|_| MemoryPlaylistFull)
+    }
+}
+
+/// Error from [`M3uWriter`] operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistError<E> {
+    /// The playlist path is longer than [`MAX_CARD_PATH_BYTES`].
+    PathTooLong,
+    /// The entry does not fit in [`M3U_ENTRY_BYTES`]; nothing was written.
+    EntryTooLong,
+    /// The sink failed.
+    Sink(E),
+}
+
+/// Card path for a playlist called `name`, e.g. `/Playlists/Road trip.m3u8`.
+///
+/// Characters FAT does not allow become `_`, control characters are
+/// dropped, and leading or trailing dots and spaces are trimmed.  Names
+/// longer than [`MAX_PLAYLIST_NAME_BYTES`] are cut at a character
+/// boundary.  `None` when nothing usable is left.
+pub fn playlist_path(name: &str) -> Option<String<PLAYLIST_PATH_BYTES>> {
+    let mut clean: String<MAX_PLAYLIST_NAME_BYTES> = String::new();
+    for c in name
+        .trim_matches(|c: char| c == '.' || c.is_whitespace())
+        .chars()
+    {
+        let c = if FAT_RESERVED.contains(&c) { '_' } else { c };
+        if c.is_control() {
+            continue;
+        }
+        if clean.push(c).is_err() {
+            break;
+        }
+    }
+    let clean = clean.trim_end_matches(|c: char| c == '.' || c.is_whitespace());
+    if clean.is_empty() {
+        return None;
+    }
+    let mut path = String::new();
+    write!(path, "/{PLAYLISTS_DIR}/{clean}.{PLAYLIST_EXTENSION}").ok()?;
+    Some(path)
+}
+
+/// Append the path of `target` relative to the directory `from_dir` to
+/// `out`.
+///
+/// Both are card paths; a path without a leading `/` counts from the card
+/// root and `\` is read as a separator (host-side exports).  Names compare
+/// case-insensitively, as on FAT.
+///
+/// # Errors
+///
+/// [`CapacityError`] when `out` is full.
+pub fn push_relative_path<const N: usize>(
+    out: &mut String<N>,
+    from_dir: &str,
+    target: &str,
+) -> Result<(), CapacityError> {
+    let mut from = from_dir.split(['/', '\\']).filter(|c| !c.is_empty());
+    let mut to = target
+        .split(['/', '\\'])
+        .filter(|c| !c.is_empty())
+        .peekable();
+    // Skip the shared leading directories.
+    let mut ups = 0usize;
+    for dir in from.by_ref() {
+        match to.peek() {
+            Some(next) if next.eq_ignore_ascii_case(dir) => {
+                to.next();
+            }
+            _ => {
+                ups = 1;
+                break;
+            }
+        }
+    }
+    ups = ups.saturating_add(from.count());
+    for _ in 0..ups {
+        out.push_str("../")?;
+    }
+    for (i, component) in to.enumerate() {
+        if i > 0 {
+            out.push('/')?;
+        }
+        out.push_str(component)?;
+    }
+    Ok(())
+}
+
+/// Streams an M3U8 playlist to a [`PlaylistSink`], one entry at a time.
+pub struct M3uWriter<K: PlaylistSink> {
+    sink: K,
+    /// Directory holding the playlist; entry paths are relative to it.
+    dir: String<MAX_CARD_PATH_BYTES>,
+    entries: u32,
+}
+
+impl<K: PlaylistSink> M3uWriter<K> {
+    /// Start the playlist at card path `path` (see [`playlist_path`]),
+    /// writing the `#EXTM3U` header.
+    ///
+    /// # Errors
+    ///
+    /// [`PlaylistError::PathTooLong`] before anything is written;
+    /// [`PlaylistError::Sink`] if the header cannot be written.
+    pub async fn new(mut sink: K, path: &str) -> Result<Self, PlaylistError<K::Error>> {
+        let dir = path.rsplit_once(['/', '\\']).map_or("", |(dir, _)| dir);
+        let dir = String::try_from(dir).map_err(|_| PlaylistError::PathTooLong)?;
+        sink.write(HEADER.as_bytes())
+            .await
+            .map_err(PlaylistError::Sink)?;
+        Ok(Self {
+            sink,
+            dir,
+            entries: 0,
+        })
+    }
+
+    /// Append `track`: an `#EXTINF` line with its duration (`-1` when
+    /// unknown) and `Artist - Title`, then its path.
+    ///
+    /// # Errors
+    ///
+    /// [`PlaylistError::EntryTooLong`] leaves the playlist unchanged;
+    /// [`PlaylistError::Sink`] if the write fails.
+    pub async fn add(&mut self, track: &TrackMeta) -> Result<(), PlaylistError<K::Error>> {
+        let entry = self
+            .encode(track)
+            .map_err(|_| PlaylistError::EntryTooLong)?;
+        self.sink
+            .write(entry.as_bytes())
+            .await
+            .map_err(PlaylistError::Sink)?;
+        self.entries = self.entries.saturating_add(1);
+        Ok(())
+    }
+
+    /// Entries written so far.
+    pub fn entries(&self) -> u32 {
+        self.entries
+    }
+
+    /// Finish the playlist and return the sink (e.g. to close the file).
+    pub fn finish(self) -> K {
+        self.sink
+    }
+
+    fn encode(&self, track: &TrackMeta) -> Result<String<M3U_ENTRY_BYTES>, CapacityError> {
+        let mut entry = String::new();
+        entry.push_str("#EXTINF:")?;
+        if track.duration_secs == 0 {
+            entry.push_str("-1,")?;
+        } else {
+            let mut secs: String<10> = String::new();
+            // Cannot fail: a u32 has at most 10 digits.
+            let _ = write!(secs, "{}", track.duration_secs);
+            entry.push_str(&secs)?;
+            entry.push(',')?;
+        }
+        if !track.artist.is_empty() {
+            push_line_text(&mut entry, &track.artist)?;
+            entry.push_str(" - ")?;
+        }
+        push_line_text(&mut entry, &track.title)?;
+        entry.push('\n')?;
+        push_relative_path(&mut entry, &self.dir, &track.file_path)?;
+        entry.push('\n')?;
+        Ok(entry)
+    }
+}
+
+/// Append `text` with line breaks flattened, so a tag cannot split an entry.
+fn push_line_text<const N: usize>(out: &mut String<N>, text: &str) -> Result<(), CapacityError> {
+    for c in text.chars() {
+        out.push(if c == '\r' || c == '\n' { ' ' } else { c })?;
+    }
+    Ok(())
+}
+
+/// Outcome of [`export_tracks`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportReport {
+    /// Entries written.
+    pub written: u32,
+    /// Tracks left out: no longer in the library, unreadable metadata, or
+    /// a path too long for an entry.
+    pub skipped: u32,
+}
+
+/// Error from [`export_tracks`].
+#[derive(Debug)]
+pub enum ExportError<E: core::fmt::Debug, W> {
+    /// Reading the library failed.
+    Storage(E),
+    /// Writing the playlist failed.
+    Sink(W),
+}
+
+/// Write the library tracks `track_ids` (e.g. the play queue's tracks) to
+/// `writer`, in order.
+///
+/// # Errors
+///
+/// [`ExportError::Storage`] if the library cannot be read,
+/// [`ExportError::Sink`] if the playlist cannot be written.  Entries
+/// written before the error stay in the playlist.
+pub async fn export_tracks<S, K>(
+    reader: &mut SoulLibraryReader<S>,
+    track_ids: impl IntoIterator<Item = u32>,
+    writer: &mut M3uWriter<K>,
+) -> Result<ExportReport, ExportError<S::Error, K::Error>>
+where
+    S: Storage,
+    S::File: File<Error = S::Error>,
+    K: PlaylistSink,
+{
+    let mut report = ExportReport::default();
+    for id in track_ids {
+        let track = match reader.track(id).await {
+            Ok(track) => track,
+            Err(ReaderError::Storage(e)) => return Err(ExportError::Storage(e)),
+            Err(_) => {
+                report.skipped = report.skipped.saturating_add(1);
+                continue;
+            }
+        };
+        match writer.add(&track).await {
+            Ok(()) => report.written = report.written.saturating_add(1),
+            Err(PlaylistError::Sink(e)) => return Err(ExportError::Sink(e)),
+            Err(PlaylistError::EntryTooLong | PlaylistError::PathTooLong) => {
+                report.skipped = report.skipped.saturating_add(1);
+            }
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    fn meta(artist: &str, title: &str, path: &str, duration_secs: u32) -> TrackMeta {
+        TrackMeta {
+            soul_id: 1,
+            album_id: 1,
+            track_number: 1,
+            disc_number: 1,
+            year: 0,
+            format: 0,
+            channels: 2,
+            duration_secs,
+            sample_rate: 44_100,
+            title: String::try_from(title).unwrap(),
+            artist: String::try_from(artist).unwrap(),
+            album: String::new(),
+            file_path: String::try_from(path).unwrap(),
+            loudness: None,
+        }
+    }
+
+    fn relative(from: &str, to: &str) -> String<256> {
+        let mut out = String::new();
+        push_relative_path(&mut out, from, to).unwrap();
+        out
+    }
+
+    #[test]
+    fn playlist_path_cleans_the_name() {
+        assert_eq!(
+            playlist_path("Road trip").unwrap().as_str(),
+            "/Playlists/Road trip.m3u8"
+        );
+        assert_eq!(
+            playlist_path(" AC/DC: live? ").unwrap().as_str(),
+            "/Playlists/AC_DC_ live_.m3u8"
+        );
+        assert_eq!(
+            playlist_path("..hidden.\n").unwrap().as_str(),
+            "/Playlists/hidden.m3u8"
+        );
+        assert!(playlist_path(" . ").is_none());
+
+        let long = "é".repeat(40);
+        let path = playlist_path(&long).unwrap();
+        assert_eq!(
+            path.len(),
+            "/Playlists/.m3u8".len() + MAX_PLAYLIST_NAME_BYTES
+        );
+    }
+
+    #[test]
+    fn relative_paths_climb_out_of_the_playlist_folder() {
+        assert_eq!(
+            relative("/Playlists", "/Music/A/01.flac").as_str(),
+            "../Music/A/01.flac"
+        );
+        assert_eq!(
+            relative("/Playlists", "/Playlists/x.flac").as_str(),
+            "x.flac"
+        );
+        assert_eq!(
+            relative("/playlists/Trips", "/PLAYLISTS/Other/x.flac").as_str(),
+            "../Other/x.flac"
+        );
+        assert_eq!(
+            relative("", "Music\\A\\01.flac").as_str(),
+            "Music/A/01.flac"
+        );
+    }
+
+    #[tokio::test]
+    async fn writes_header_and_entries() {
+        let sink = MemoryPlaylistSink::<1024>::new();
+        let path = playlist_path("Queue").unwrap();
+        let mut writer = M3uWriter::new(sink, &path).await.unwrap();
+        writer
+            .add(&meta(
+                "Portishead",
+                "Mysterons",
+                "/Music/Portishead/Dummy/01 Mysterons.flac",
+                305,
+            ))
+            .await
+            .unwrap();
+        writer
+            .add(&meta("", "Line\nbreak", "/Music/loose.wav", 0))
+            .await
+            .unwrap();
+        assert_eq!(writer.entries(), 2);
+
+        let sink = writer.finish();
+        assert_eq!(
+            core::str::from_utf8(sink.as_bytes()).unwrap(),
+            "#EXTM3U\n\
+             #EXTINF:305,Portishead - Mysterons\n\
+             ../Music/Portishead/Dummy/01 Mysterons.flac\n\
+             #EXTINF:-1,Line break\n\
+             ../Music/loose.wav\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn entry_too_long_leaves_the_playlist_unchanged() {
+        // A playlist 20 folders deep climbs out with 20 `../` steps, which
+        // with the longest tags and path overflows the entry buffer.
+        let sink = MemoryPlaylistSink::<4096>::new();
+        let deep = format!("{}/list.m3u8", "/d".repeat(20));
+        let mut writer = M3uWriter::new(sink, &deep).await.unwrap();
+        let long_path = format!("/{}", "p".repeat(MAX_CARD_PATH_BYTES - 1));
+        let track = meta(&"a".repeat(64), &"t".repeat(128), &long_path, 1);
+        assert_eq!(writer.add(&track).await, Err(PlaylistError::EntryTooLong));
+        assert_eq!(writer.entries(), 0);
+        assert_eq!(writer.finish().as_bytes(), HEADER.as_bytes());
+
+        let sink = MemoryPlaylistSink::<4096>::new();
+        let too_deep = format!("{}/list.m3u8", "/d".repeat(200));
+        assert!(matches!(
+            M3uWriter::new(sink, &too_deep).await,
+            Err(PlaylistError::PathTooLong)
+        ));
+    }
+
+    #[tokio::test]
+    async fn sink_errors_are_reported() {
+        let sink = MemoryPlaylistSink::<16>::new();
+        let mut writer = M3uWriter::new(sink, "/Playlists/x.m3u8").await.unwrap();
+        assert_eq!(
+            writer.add(&meta("A", "T", "/Music/a.flac", 1)).await,
+            Err(PlaylistError::Sink(MemoryPlaylistFull))
+        );
+    }
+}
@@ -0,0 +1,410 @@
+//! Podcasts — episode metadata, ordering, and played/resume state.
+//!
+//! `cargo xtask podcast-sync` downloads episodes to
+//! `Podcasts/{Show}/{YYYY-MM-DD} - {Title}.{ext}` and writes a `.episode`
+//! sidecar next to each one (found with
+//! [`Scanner::episode_path_for`](crate::Scanner::episode_path_for)):
+//!
+//! ```text
+//! podcast=Show name
+//! title=Episode title
+//! guid=feed-unique id
+//! published=1700000000
+//! duration=3600
+//! ```
+//!
+//! Episodes are tracked separately from music: music resumes from the play
+//! queue journal, while every episode keeps its own position and played
+//! flag in an [`EpisodeLog`], keyed by a hash of the feed's `guid` so state
+//! survives renames and re-downloads.
+
+use heapless::{String, Vec};
+use serde::{Deserialize, Serialize};
+
+use crate::binary::LibraryError;
+
+/// Card directory holding downloaded podcasts, one sub-directory per show.
+pub const PODCASTS_DIR: &str = "Podcasts";
+
+/// Episodes remembered by an [`EpisodeLog`] by default.
+pub const MAX_TRACKED_EPISODES: usize = 256;
+
+/// An episode counts as played once playback gets this close to the end
+/// (outros and ads are commonly skipped).
+pub const PLAYED_TAIL_MS: u32 = 30_000;
+
+/// Errors returned by [`Episode::parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PodcastError {
+    /// The sidecar has no `title` or no `guid`.
+    MissingField,
+    /// `published` or `duration` is not a number.
+    BadNumber,
+}
+
+/// Metadata of one downloaded episode, from its `.episode` sidecar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Episode {
+    /// Show name.
+    pub podcast: String<64>,
+    /// Episode title.
+    pub title: String<128>,
+    /// The feed's unique id for the episode.
+    pub guid: String<128>,
+    /// Publication time, Unix seconds (0 when unknown).
+    pub published: u32,
+    /// Duration in seconds (0 when unknown).
+    pub duration_secs: u32,
+}
+
+impl Episode {
+    /// Parse a `.episode` sidecar.  Unknown keys are ignored and over-long
+    /// values are truncated at a character boundary.
+    ///
+    /// # Errors
+    ///
+    /// See [`PodcastError`].
+    pub fn parse(src: &str) -> Result<Self, PodcastError> {
+        let mut episode = Self {
+            podcast: String::new(),
+            title: String::new(),
+            guid: String::new(),
+            published: 0,
+            duration_secs: 0,
+        };
+        for line in src.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "podcast" => push_truncated(&mut episode.podcast, value),
+                "title" => push_truncated(&mut episode.title, value),
+                "guid" => push_truncated(&mut episode.guid, value),
+                "published" => {
+                    episode.published = value.parse().map_err(|_| PodcastError::BadNumber)?
+                }
+                "duration" => {
+                    episode.duration_secs = value.parse().map_err(|_| PodcastError::BadNumber)?
+                }
+                _ => {}
+            }
+        }
+        if episode.title.is_empty() || episode.guid.is_empty() {
+            return Err(PodcastError::MissingField);
+        }
+        Ok(episode)
+    }
+
+    /// Stable id for the episode: FNV-1a hash of its `guid`.
+    pub fn id(&self) -> u32 {
+        episode_id(&self.guid)
+    }
+}
+
+/// FNV-1a hash of a feed `guid`, as used for [`EpisodeLog`] keys.
+pub fn episode_id(guid: &str) -> u32 {
+    guid.bytes().fold(0x811C_9DC5u32, |hash, b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Episode list order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EpisodeOrder {
+    /// Latest episode first — news and talk shows.
+    #[default]
+    NewestFirst,
+    /// First episode first — serialized shows.
+    OldestFirst,
+}
+
+/// Sort `episodes` by publication date, then by title.
+pub fn sort_episodes(episodes: &mut [Episode], order: EpisodeOrder) {
+    episodes.sort_unstable_by(|a, b| {
+        let by_date = match order {
+            EpisodeOrder::NewestFirst => b.published.cmp(&a.published),
+            EpisodeOrder::OldestFirst => a.published.cmp(&b.published),
+        };
+        by_date.then_with(|| a.title.cmp(&b.title))
+    });
+}
+
+/// Saved state of one episode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpisodeProgress {
+    /// [`Episode::id`].
+    pub id: u32,
+    /// Resume position in milliseconds.
+    pub position_ms: u32,
+    /// Listened to the end (or marked played by the user).
+    pub played: bool,
+}
+
+/// Played flags and resume positions for up to `N` episodes.
+///
+/// Episodes without an entry are unplayed and start from the beginning.
+/// When full, recording a new episode evicts the oldest played entry, or
+/// failing that the oldest entry.  Persist with [`EpisodeLog::encode`] on
+/// pause and episode change.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpisodeLog<const N: usize = MAX_TRACKED_EPISODES> {
+    entries: Vec<EpisodeProgress, N>,
+}
+
+impl<const N: usize> EpisodeLog<N> {
+    /// An empty log.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Number of episodes with saved state.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// `true` when no episode has saved state.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Saved state of episode `id`, if any.
+    pub fn get(&self, id: u32) -> Option<&EpisodeProgress> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// `true` when episode `id` has been played.
+    pub fn is_played(&self, id: u32) -> bool {
+        self.get(id).is_some_and(|e| e.played)
+    }
+
+    /// Where to resume episode `id`: its saved position, or 0 when it is
+    /// new or already played (replaying starts over).
+    pub fn resume_ms(&self, id: u32) -> u32 {
+        self.get(id)
+            .filter(|e| !e.played)
+            .map_or(0, |e| e.position_ms)
+    }
+
+    /// Record the playback position of episode `id`.  Reaching within
+    /// [`PLAYED_TAIL_MS`] of `duration_ms` (when known) marks it played.
+    pub fn record_position(&mut self, id: u32, position_ms: u32, duration_ms: u32) {
+        let finished =
+            duration_ms != 0 && position_ms.saturating_add(PLAYED_TAIL_MS) >= duration_ms;
+        if let Some(entry) = self.entry(id) {
+            entry.position_ms = position_ms;
+            entry.played = entry.played || finished;
+        }
+    }
+
+    /// Mark episode `id` played (from the episode list).
+    pub fn mark_played(&mut self, id: u32) {
+        if let Some(entry) = self.entry(id) {
+            entry.played = true;
+            entry.position_ms = 0;
+        }
+    }
+
+    /// Mark episode `id` unplayed, forgetting its position.
+    pub fn mark_unplayed(&mut self, id: u32) {
+        self.entries.retain(|e| e.id != id);
+    }
+
+    /// First unplayed episode of `episodes` (already sorted), if any.
+    pub fn next_unplayed<'a>(&self, episodes: &'a [Episode]) -> Option<&'a Episode> {
+        episodes.iter().find(|e| !self.is_played(e.id()))
+    }
+
+    /// Encode the log into `buf` (postcard), returning the used prefix.
+    ///
+    /// # Errors
+    ///
+    /// [`LibraryError::DecodeError`] when `buf` is too small.
+    pub fn encode<'b>(&self, buf: &'b mut [u8]) -> Result<&'b mut [u8], LibraryError> {
+        postcard::to_slice(self, buf).map_err(|_| LibraryError::DecodeError)
+    }
+
+    /// Decode a log written by [`encode`](Self::encode).
+    ///
+    /// # Errors
+    ///
+    /// [`LibraryError::DecodeError`] when `bytes` is corrupt or holds more
+    /// than `N` entries.
+    pub fn decode(bytes: &[u8]) -> Result<Self, LibraryError> {
+        postcard::from_bytes(bytes).map_err(|_| LibraryError::DecodeError)
+    }
+
+    /// The entry for `id`, created (evicting if full) when missing;
+    /// `None` only when `N` is 0.
+    fn entry(&mut self, id: u32) -> Option<&mut EpisodeProgress> {
+        let index = match self.entries.iter().position(|e| e.id == id) {
+            Some(index) => index,
+            None => {
+                if self.entries.is_full() && !self.entries.is_empty() {
+                    let evict = self.entries.iter().position(|e| e.played).unwrap_or(0);
+                    self.entries.remove(evict);
+                }
+                self.entries
+                    .push(EpisodeProgress {
+                        id,
+                        position_ms: 0,
+                        played: false,
+                    })
+                    .ok()?;
+                self.entries.len().saturating_sub(1)
+            }
+        };
+        self.entries.get_mut(index)
+    }
+}
+
+impl<const N: usize> Default for EpisodeLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Append as much of `value` to `out` as fits, on a character boundary.
+fn push_truncated<const N: usize>(out: &mut String<N>, value: &str) {
+    out.clear();
+    for c in value.chars() {
+        if out.push(c).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)] // Tests use expect() for readable assertions
+#[allow(clippy::indexing_slicing)] // Test indexing into known-length slices is safe
+#[allow(clippy::large_stack_arrays)] // A handful of episodes, host stack
+mod tests {
+    use super::*;
+
+    fn episode(guid: &str, title: &str, published: u32) -> Episode {
+        let src = std::format!("podcast=Show\ntitle={title}\nguid={guid}\npublished={published}\n");
+        Episode::parse(&src).expect("valid sidecar")
+    }
+
+    #[test]
+    fn test_episode_parses_sidecar() {
+        let e = Episode::parse(
+            "# written by xtask\npodcast=The Show\ntitle=Ep 1: a=b\nguid=abc-123\n\
+             published=1700000000\nduration=3600\nimage=ignored\n",
+        )
+        .expect("valid sidecar");
+        assert_eq!(e.podcast.as_str(), "The Show");
+        assert_eq!(e.title.as_str(), "Ep 1: a=b");
+        assert_eq!(e.guid.as_str(), "abc-123");
+        assert_eq!(e.published, 1_700_000_000);
+        assert_eq!(e.duration_secs, 3_600);
+        assert_eq!(e.id(), episode_id("abc-123"));
+    }
+
+    #[test]
+    fn test_episode_requires_title_and_guid() {
+        assert_eq!(
+            Episode::parse("title=Only a title\n"),
+            Err(PodcastError::MissingField)
+        );
+        assert_eq!(
+            Episode::parse("title=t\nguid=g\nduration=1h\n"),
+            Err(PodcastError::BadNumber)
+        );
+    }
+
+    #[test]
+    fn test_episode_truncates_long_titles_on_char_boundary() {
+        let long = "é".repeat(100);
+        let e = Episode::parse(&std::format!("title={long}\nguid=g\n")).expect("valid sidecar");
+        assert_eq!(e.title.len(), 128);
+        assert!(e.title.chars().all(|c| c == 'é'));
+    }
+
+    #[test]
+    fn test_sort_episodes_by_date_then_title() {
+        let mut episodes = [
+            episode("a", "B", 100),
+            episode("b", "A", 100),
+            episode("c", "C", 300),
+            episode("d", "D", 200),
+        ];
+        sort_episodes(&mut episodes, EpisodeOrder::NewestFirst);
+        {
+            let titles: Vec<&str, 4> = episodes.iter().map(|e| e.title.as_str()).collect();
+            assert_eq!(titles.as_slice(), &["C", "D", "A", "B"]);
+        }
+        sort_episodes(&mut episodes, EpisodeOrder::OldestFirst);
+        assert_eq!(episodes[0].title.as_str(), "A");
+        assert_eq!(episodes[3].title.as_str(), "C");
+    }
+
+    #[test]
+    fn test_episode_log_resume_and_played() {
+        let mut log: EpisodeLog = EpisodeLog::new();
+        let id = episode_id("ep");
+        assert_eq!(log.resume_ms(id), 0);
+        assert!(!log.is_played(id));
+
+        log.record_position(id, 600_000, 3_600_000);
+        assert_eq!(log.resume_ms(id), 600_000);
+        assert!(!log.is_played(id));
+
+        // Within the last 30 s counts as finished; replay starts over.
+        log.record_position(id, 3_580_000, 3_600_000);
+        assert!(log.is_played(id));
+        assert_eq!(log.resume_ms(id), 0);
+
+        log.mark_unplayed(id);
+        assert!(!log.is_played(id));
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn test_episode_log_next_unplayed() {
+        let episodes = [
+            episode("a", "A", 3),
+            episode("b", "B", 2),
+            episode("c", "C", 1),
+        ];
+        let mut log: EpisodeLog = EpisodeLog::new();
+        log.mark_played(episodes[0].id());
+        assert_eq!(
+            log.next_unplayed(&episodes).map(|e| e.guid.as_str()),
+            Some("b")
+        );
+        log.mark_played(episodes[1].id());
+        log.mark_played(episodes[2].id());
+        assert_eq!(log.next_unplayed(&episodes), None);
+    }
+
+    #[test]
+    fn test_episode_log_evicts_played_first_when_full() {
+        let mut log = EpisodeLog::<2>::new();
+        log.record_position(1, 1_000, 0);
+        log.mark_played(2);
+        log.record_position(3, 3_000, 0);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log.resume_ms(1), 1_000, "in-progress entry kept");
+        assert!(log.get(2).is_none(), "played entry evicted");
+        assert_eq!(log.resume_ms(3), 3_000);
+    }
+
+    #[test]
+    fn test_episode_log_roundtrip() {
+        let mut log: EpisodeLog = EpisodeLog::new();
+        log.record_position(7, 42_000, 0);
+        log.mark_played(9);
+        let mut buf = [0u8; 64];
+        let bytes = log.encode(&mut buf).expect("fits");
+        let decoded: EpisodeLog = EpisodeLog::decode(bytes).expect("roundtrip");
+        assert_eq!(decoded, log);
+        assert_eq!(
+            EpisodeLog::<1>::decode(bytes),
+            Err(LibraryError::DecodeError),
+            "more entries than capacity"
+        );
+    }
+}
@@ -10,11 +10,39 @@
 //! | `track(index)` | O(1) seek + O(meta_size) read | Single track by index |
 //! | `page(offset, count)` | O(count) seeks + reads | Page for UI browsing |
 //! | `search_by_artist(prefix)` | O(log N) binary search | Artist name prefix search |
-
-use platform::soul_library::{library_idx_path, library_meta_path, manifest_path};
+//! | `ordered_track(order, pos)` | 1 table read + `track` | Browse by artist/album/title |
+//! | `group_range(order, group)` | 2 table reads | Tracks of one artist or album |
+//!
+//! The browse methods read the pre-sorted tables in `library.browse` (see
+//! [`BrowseHeader`]); nothing is sorted on device.  A library exported
+//! without the file still opens, and the browse methods return
+//! [`ReaderError::NoBrowseIndex`].
+//!
+//! # Integrity
+//!
+//! [`open`](SoulLibraryReader::open) checks the CRC32 of each section
+//! (one sequential read per file) and keeps going when one fails; only a
+//! bad `manifest.bin` stops the load.  A corrupt browse index is dropped as
+//! if it were missing.  With a corrupt index or metadata file every track
+//! is also checked against its sort key as it is read, so the damaged ones
+//! fail with [`ReaderError::Format`] and the rest still load.  What was
+//! found is in [`integrity`](SoulLibraryReader::integrity).
+
+use core::ops::Range;
+
+use crc32fast::Hasher;
+use platform::soul_library::{
+    library_browse_path, library_idx_path, library_meta_path, manifest_path, LibraryIntegrity,
+    LibrarySection,
+};
 use platform::storage::{File, Storage};
 
-use crate::binary::{IndexEntry, LibraryError, ManifestBin, TrackMeta};
+use crate::binary::{
+    sort_key_for, BrowseHeader, BrowseOrder, IndexEntry, LibraryError, ManifestBin, TrackMeta,
+};
+
+/// Read size while checksumming a section.
+const CRC_CHUNK: usize = 512;
 
 // ---------------------------------------------------------------------------
 // Error type
@@ -29,6 +57,8 @@ pub enum ReaderError<E: core::fmt::Debug> {
     Format(LibraryError),
     /// Track index is out of range (>= `track_count()`).
     OutOfRange,
+    /// `library.browse` is missing or does not match the manifest.
+    NoBrowseIndex,
 }
 
 impl<E: core::fmt::Debug> From<LibraryError> for ReaderError<E> {
@@ -59,6 +89,8 @@ where
     storage: S,
     root: heapless::String<64>,
     manifest: ManifestBin,
+    browse: Option<BrowseHeader>,
+    integrity: LibraryIntegrity,
 }
 
 impl<S> SoulLibraryReader<S>
@@ -68,12 +100,15 @@ where
 {
     /// Open the library at `soul_root`.
     ///
-    /// Reads and validates `manifest.bin`. Does not pre-load the index.
+    /// Reads and validates `manifest.bin`, checks the section checksums
+    /// (see [Integrity](self#integrity)) and reads the `library.browse`
+    /// header when present. Does not pre-load the index.
     ///
     /// # Errors
     ///
-    /// Returns `ReaderError::Storage` if `manifest.bin` cannot be opened or read.
+    /// Returns `ReaderError::Storage` if a file cannot be opened or read.
     /// Returns `ReaderError::Format` if the manifest magic or version is invalid.
+    /// A section that fails its checksum is not an error.
     pub async fn open(mut storage: S, soul_root: &str) -> Result<Self, ReaderError<S::Error>> {
         let mpath = manifest_path(soul_root);
         let mut file = storage
@@ -93,7 +128,63 @@ where
         root.push_str(soul_root)
             .map_err(|_| ReaderError::Format(LibraryError::DecodeError))?;
 
-        Ok(Self { storage, root, manifest })
+        let mut integrity = LibraryIntegrity::new();
+        // track_count * 24 cannot overflow u64 (see read_index_entry).
+        #[allow(clippy::cast_possible_truncation)]
+        let idx_len = u64::from(manifest.track_count).saturating_mul(IndexEntry::SIZE as u64);
+        let sections = [
+            (
+                LibrarySection::Index,
+                library_idx_path(soul_root),
+                manifest.idx_checksum,
+                Some(idx_len),
+            ),
+            (
+                LibrarySection::Meta,
+                library_meta_path(soul_root),
+                manifest.meta_checksum,
+                None,
+            ),
+        ];
+        for (section, path, checksum, len) in sections {
+            if !section_matches(&mut storage, path.as_str(), checksum, len).await? {
+                integrity.mark_corrupt(section);
+            }
+        }
+        let browse = read_browse_header(&mut storage, soul_root, &manifest, &mut integrity).await?;
+
+        Ok(Self {
+            storage,
+            root,
+            manifest,
+            browse,
+            integrity,
+        })
+    }
+
+    /// Section checksum results, and the number of tracks that have failed
+    /// to read since [`open`](Self::open).
+    #[must_use]
+    pub fn integrity(&self) -> LibraryIntegrity {
+        self.integrity
+    }
+
+    /// Whether tracks need checking against their sort key as they are read.
+    fn check_tracks(&self) -> bool {
+        self.integrity.is_corrupt(LibrarySection::Index)
+            || self.integrity.is_corrupt(LibrarySection::Meta)
+    }
+
+    /// Count a track that failed to decode in
+    /// [`LibraryIntegrity::bad_tracks`].
+    fn tally<T>(
+        &mut self,
+        result: Result<T, ReaderError<S::Error>>,
+    ) -> Result<T, ReaderError<S::Error>> {
+        if matches!(result, Err(ReaderError::Format(_))) {
+            self.integrity.bad_tracks = self.integrity.bad_tracks.saturating_add(1);
+        }
+        result
     }
 
     /// Number of tracks in the library.
@@ -102,13 +193,128 @@ where
         self.manifest.track_count
     }
 
+    /// Unix time the library was exported (`ManifestBin::export_timestamp`);
+    /// identifies the export that track indices refer to.
+    #[must_use]
+    pub fn export_timestamp(&self) -> u64 {
+        self.manifest.export_timestamp
+    }
+
+    /// Whether `library.browse` was found and matches the manifest.
+    #[must_use]
+    pub fn has_browse_index(&self) -> bool {
+        self.browse.is_some()
+    }
+
+    /// Number of artists or albums in `order`; `0` for
+    /// [`BrowseOrder::Title`] or without a browse index.
+    #[must_use]
+    pub fn group_count(&self, order: BrowseOrder) -> u32 {
+        self.browse.as_ref().map_or(0, |h| h.group_count(order))
+    }
+
+    /// Position in `library.idx` of the track at `position` in `order`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReaderError::NoBrowseIndex` without a browse index.
+    /// Returns `ReaderError::OutOfRange` if `position >= track_count()`.
+    /// Returns `ReaderError::Storage` on I/O failure.
+    pub async fn track_position(
+        &mut self,
+        order: BrowseOrder,
+        position: u32,
+    ) -> Result<u32, ReaderError<S::Error>> {
+        let header = self.browse.as_ref().ok_or(ReaderError::NoBrowseIndex)?;
+        if position >= header.track_count {
+            return Err(ReaderError::OutOfRange);
+        }
+        let offset = header.order_offset(order, position);
+        let index = self.read_browse_word(offset).await?;
+        if index >= self.manifest.track_count {
+            return Err(ReaderError::Format(LibraryError::DecodeError));
+        }
+        Ok(index)
+    }
+
+    /// Load the track at `position` in `order` — row `position` of a
+    /// browse list.
+    ///
+    /// # Errors
+    ///
+    /// As [`track_position`](Self::track_position) and
+    /// [`track`](Self::track).
+    pub async fn ordered_track(
+        &mut self,
+        order: BrowseOrder,
+        position: u32,
+    ) -> Result<TrackMeta, ReaderError<S::Error>> {
+        let index = self.track_position(order, position).await?;
+        self.track(index).await
+    }
+
+    /// Positions in `order` holding the tracks of artist or album `group`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ReaderError::NoBrowseIndex` without a browse index.
+    /// Returns `ReaderError::OutOfRange` if `group >= group_count(order)`
+    /// (always, for [`BrowseOrder::Title`]).
+    /// Returns `ReaderError::Storage` on I/O failure.
+    pub async fn group_range(
+        &mut self,
+        order: BrowseOrder,
+        group: u32,
+    ) -> Result<Range<u32>, ReaderError<S::Error>> {
+        let header = self.browse.as_ref().ok_or(ReaderError::NoBrowseIndex)?;
+        let count = header.group_count(order);
+        if group >= count {
+            return Err(ReaderError::OutOfRange);
+        }
+        let track_count = header.track_count;
+        let next = group.saturating_add(1);
+        let start_offset = header.group_offset(order, group);
+        let end_offset = (next < count).then(|| header.group_offset(order, next));
+
+        let start = self.read_browse_word(start_offset).await?;
+        let end = match end_offset {
+            Some(offset) => self.read_browse_word(offset).await?,
+            None => track_count,
+        };
+        if start >= end || end > track_count {
+            return Err(ReaderError::Format(LibraryError::DecodeError));
+        }
+        Ok(start..end)
+    }
+
+    /// Read one little-endian `u32` from `library.browse` at `offset`.
+    async fn read_browse_word(&mut self, offset: u64) -> Result<u32, ReaderError<S::Error>> {
+        let path = library_browse_path(self.root.as_str());
+        let mut file = self
+            .storage
+            .open_file(path.as_str())
+            .await
+            .map_err(ReaderError::Storage)?;
+        if offset.saturating_add(4) > file.size() {
+            return Err(ReaderError::Format(LibraryError::DecodeError));
+        }
+        file.seek(offset).await.map_err(ReaderError::Storage)?;
+        let mut buf = [0u8; 4];
+        read_exact(&mut file, &mut buf)
+            .await
+            .map_err(ReaderError::Storage)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
     /// Load a single [`TrackMeta`] by 0-based track index.
     ///
     /// # Errors
     ///
     /// Returns `ReaderError::OutOfRange` if `index >= track_count()`.
     /// Returns `ReaderError::Storage` on I/O failure.
-    /// Returns `ReaderError::Format` if the meta blob is corrupt.
+    /// Returns `ReaderError::Format` if the index entry or meta blob is
+    /// corrupt; the track is counted in
+    /// [`bad_tracks`](LibraryIntegrity::bad_tracks).
     pub async fn track(&mut self, index: u32) -> Result<TrackMeta, ReaderError<S::Error>> {
         if index >= self.manifest.track_count {
             return Err(ReaderError::OutOfRange);
@@ -128,8 +334,9 @@ where
             .await
             .map_err(ReaderError::Storage)?;
 
-        let entry = read_index_entry(&mut idx_file, index).await?;
-        read_track_meta(&mut meta_file, &entry).await
+        let check = self.check_tracks();
+        let result = read_track(&mut idx_file, &mut meta_file, index, check).await;
+        self.tally(result)
     }
 
     // -- std/test-only methods below --
@@ -137,11 +344,12 @@ where
     /// Load a page of [`TrackMeta`] starting at 0-based `offset`.
     ///
     /// Returns up to `count` tracks. Returns an empty `Vec` if `offset >= track_count()`.
+    /// Tracks that fail to decode are skipped and counted in
+    /// [`bad_tracks`](LibraryIntegrity::bad_tracks), so the page may be short.
     ///
     /// # Errors
     ///
     /// Returns `ReaderError::Storage` on I/O failure.
-    /// Returns `ReaderError::Format` if any meta blob is corrupt.
     #[cfg(any(feature = "std", test))]
     pub async fn page(
         &mut self,
@@ -171,11 +379,15 @@ where
         // available <= count <= u32::MAX; usize is at least 32 bits on all targets.
         #[allow(clippy::cast_possible_truncation)]
         let mut result = Vec::with_capacity(available as usize);
+        let check = self.check_tracks();
         for i in 0..available {
             let track_idx = offset.saturating_add(i);
-            let entry = read_index_entry(&mut idx_file, track_idx).await?;
-            let meta = read_track_meta(&mut meta_file, &entry).await?;
-            result.push(meta);
+            let read = read_track(&mut idx_file, &mut meta_file, track_idx, check).await;
+            match self.tally(read) {
+                Ok(meta) => result.push(meta),
+                Err(ReaderError::Format(_)) => {}
+                Err(e) => return Err(e),
+            }
         }
         Ok(result)
     }
@@ -184,12 +396,13 @@ where
     ///
     /// Uses binary search on the sorted index. Only the first 6 ASCII bytes of
     /// `artist_prefix` are used (the artist portion of the sort key).
-    /// Returns up to 64 matching tracks.
+    /// Returns up to 64 matching tracks; matches that fail to decode are
+    /// skipped and counted as in [`page`](Self::page).
     ///
     /// # Errors
     ///
     /// Returns `ReaderError::Storage` on I/O failure.
-    /// Returns `ReaderError::Format` on corrupt data.
+    /// Returns `ReaderError::Format` on a corrupt index entry.
     #[cfg(any(feature = "std", test))]
     pub async fn search_by_artist(
         &mut self,
@@ -235,6 +448,7 @@ where
             }
         }
 
+        let check = self.check_tracks();
         let mut results = Vec::new();
         let mut i = lo;
         while i < total && results.len() < 64 {
@@ -244,8 +458,12 @@ where
             if entry.sort_key[..prefix_len] != prefix[..prefix_len] {
                 break;
             }
-            let meta = read_track_meta(&mut meta_file, &entry).await?;
-            results.push(meta);
+            let read = read_checked_meta(&mut meta_file, &entry, check).await;
+            match self.tally(read) {
+                Ok(meta) => results.push(meta),
+                Err(ReaderError::Format(_)) => {}
+                Err(e) => return Err(e),
+            }
             i = i.saturating_add(1);
         }
         Ok(results)
@@ -256,6 +474,142 @@ where
 // Private helpers
 // ---------------------------------------------------------------------------
 
+/// Whether the file at `path` exists, is `len` bytes long (when given) and
+/// has CRC32 `checksum`.
+async fn section_matches<S>(
+    storage: &mut S,
+    path: &str,
+    checksum: u32,
+    len: Option<u64>,
+) -> Result<bool, ReaderError<S::Error>>
+where
+    S: Storage,
+    S::File: File<Error = S::Error>,
+{
+    if !storage.exists(path).await.map_err(ReaderError::Storage)? {
+        return Ok(false);
+    }
+    let mut file = storage
+        .open_file(path)
+        .await
+        .map_err(ReaderError::Storage)?;
+    if len.is_some_and(|len| len != file.size()) {
+        return Ok(false);
+    }
+    let crc = crc_from(&mut file, 0).await.map_err(ReaderError::Storage)?;
+    Ok(crc == checksum)
+}
+
+/// CRC32 of `file` from `start` to the end.
+async fn crc_from<F: File>(file: &mut F, start: u64) -> Result<u32, F::Error> {
+    file.seek(start).await?;
+    let mut hasher = Hasher::new();
+    let mut buf = [0u8; CRC_CHUNK];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(buf.get(..n).unwrap_or_default());
+    }
+    Ok(hasher.finalize())
+}
+
+/// Read the `library.browse` header, or `None` when the file is absent,
+/// from a newer exporter, written for a different track count (a stale
+/// export) or corrupt.  Only the last is recorded in `integrity`.
+async fn read_browse_header<S>(
+    storage: &mut S,
+    soul_root: &str,
+    manifest: &ManifestBin,
+    integrity: &mut LibraryIntegrity,
+) -> Result<Option<BrowseHeader>, ReaderError<S::Error>>
+where
+    S: Storage,
+    S::File: File<Error = S::Error>,
+{
+    let path = library_browse_path(soul_root);
+    if !storage
+        .exists(path.as_str())
+        .await
+        .map_err(ReaderError::Storage)?
+    {
+        return Ok(None);
+    }
+    let mut file = storage
+        .open_file(path.as_str())
+        .await
+        .map_err(ReaderError::Storage)?;
+    let mut buf = [0u8; BrowseHeader::SIZE];
+    read_exact(&mut file, &mut buf)
+        .await
+        .map_err(ReaderError::Storage)?;
+    let header = match BrowseHeader::decode(&buf) {
+        Ok(header) => header,
+        Err(LibraryError::UnsupportedVersion) => return Ok(None),
+        Err(_) => {
+            integrity.mark_corrupt(LibrarySection::Browse);
+            return Ok(None);
+        }
+    };
+    if header.track_count != manifest.track_count {
+        return Ok(None);
+    }
+    // SIZE = 32 fits in u64 on every target.
+    #[allow(clippy::cast_possible_truncation)]
+    let body = crc_from(&mut file, BrowseHeader::SIZE as u64)
+        .await
+        .map_err(ReaderError::Storage)?;
+    if header.file_len() != file.size() || body != header.checksum {
+        integrity.mark_corrupt(LibrarySection::Browse);
+        return Ok(None);
+    }
+    Ok(Some(header))
+}
+
+/// Read track `index`: its index entry, then its meta blob.
+async fn read_track<F, E>(
+    idx_file: &mut F,
+    meta_file: &mut F,
+    index: u32,
+    check_key: bool,
+) -> Result<TrackMeta, ReaderError<E>>
+where
+    F: File<Error = E>,
+    E: core::fmt::Debug,
+{
+    let entry = read_index_entry(idx_file, index).await?;
+    read_checked_meta(meta_file, &entry, check_key).await
+}
+
+/// [`read_track_meta`], then with `check_key` compare the entry's sort key
+/// with the one rebuilt from the decoded metadata — the only way to catch
+/// an entry pointing at the wrong blob, or a blob altered in a way postcard
+/// still decodes.
+async fn read_checked_meta<F, E>(
+    file: &mut F,
+    entry: &IndexEntry,
+    check_key: bool,
+) -> Result<TrackMeta, ReaderError<E>>
+where
+    F: File<Error = E>,
+    E: core::fmt::Debug,
+{
+    let meta = read_track_meta(file, entry).await?;
+    if check_key {
+        let key = sort_key_for(
+            &meta.artist,
+            &meta.album,
+            meta.track_number,
+            meta.disc_number,
+        );
+        if key != entry.sort_key {
+            return Err(ReaderError::Format(LibraryError::DecodeError));
+        }
+    }
+    Ok(meta)
+}
+
 /// Read a single [`IndexEntry`] from the index file at position `index`.
 ///
 /// Seeks to `index * IndexEntry::SIZE` then reads exactly 24 bytes.
@@ -282,7 +636,8 @@ where
 /// # Stack usage
 ///
 /// Uses a 600-byte stack buffer which covers the worst-case postcard encoding of
-/// `TrackMeta` (title=128 + artist=64 + album=64 + file_path=256 + scalars + varints ≈ 540 B).
+/// `TrackMeta` (title=128 + artist=64 + album=64 + file_path=256 + scalars +
+/// loudness + varints ≈ 560 B).
 /// Validated by the writer test `track_meta_worst_case_fits_in_writer_buffer`.
 async fn read_track_meta<F, E>(
     file: &mut F,
@@ -301,7 +656,7 @@ where
     #[allow(clippy::cast_possible_truncation)]
     let size = entry.meta_size as usize;
 
-    // 600-byte stack buffer covers worst-case TrackMeta postcard encoding (~540 B).
+    // 600-byte stack buffer covers worst-case TrackMeta postcard encoding (~560 B).
     // large_stack_arrays fires at 512 B; suppressed here with justification above.
     #[allow(clippy::large_stack_arrays)]
     let mut buf = [0u8; 600];
@@ -391,6 +746,7 @@ mod tests {
                 format!("/soul/music/{}/{}/{:02}.flac", artist, album, n).as_str(),
             )
             .unwrap(),
+            loudness: None,
         }
     }
 
@@ -418,6 +774,7 @@ mod tests {
         let storage = LocalFileStorage::new(root);
         let reader = SoulLibraryReader::open(storage, root).await.unwrap();
         assert_eq!(reader.track_count(), 2);
+        assert!(reader.integrity().is_intact());
     }
 
     #[tokio::test]
@@ -481,6 +838,139 @@ mod tests {
         assert_eq!(results[0].artist.as_str(), "Amon Tobin");
     }
 
+    #[tokio::test]
+    async fn reader_browse_orders_are_lookups() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().to_str().unwrap();
+        build_library(
+            root,
+            &[
+                (2, "Portishead", "Dummy"),
+                (1, "Amon Tobin", "Foley Room"),
+                (1, "Portishead", "Dummy"),
+                (1, "Amon Tobin", "Bricolage"),
+            ],
+        );
+        let storage = LocalFileStorage::new(root);
+        let mut reader = SoulLibraryReader::open(storage, root).await.unwrap();
+        assert!(reader.has_browse_index());
+        assert_eq!(reader.group_count(BrowseOrder::Artist), 2);
+        assert_eq!(reader.group_count(BrowseOrder::Album), 3);
+        assert_eq!(reader.group_count(BrowseOrder::Title), 0);
+
+        let portishead = reader.group_range(BrowseOrder::Artist, 1).await.unwrap();
+        assert_eq!(portishead, 2..4);
+        let first = reader
+            .ordered_track(BrowseOrder::Artist, portishead.start)
+            .await
+            .unwrap();
+        assert_eq!(first.artist.as_str(), "Portishead");
+        assert_eq!(first.track_number, 1);
+
+        let mut names = Vec::new();
+        for g in 0..reader.group_count(BrowseOrder::Album) {
+            let range = reader.group_range(BrowseOrder::Album, g).await.unwrap();
+            let t = reader
+                .ordered_track(BrowseOrder::Album, range.start)
+                .await
+                .unwrap();
+            names.push(t.album.to_string());
+        }
+        assert_eq!(names, ["Bricolage", "Dummy", "Foley Room"]);
+        assert!(matches!(
+            reader.group_range(BrowseOrder::Album, 3).await,
+            Err(ReaderError::OutOfRange)
+        ));
+    }
+
+    #[tokio::test]
+    async fn reader_without_browse_index_still_opens() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().to_str().unwrap();
+        build_library(root, &[(1, "A", "B")]);
+        std::fs::remove_file(tmp.path().join("library.browse")).unwrap();
+        let storage = LocalFileStorage::new(root);
+        let mut reader = SoulLibraryReader::open(storage, root).await.unwrap();
+        assert!(!reader.has_browse_index());
+        assert!(matches!(
+            reader.ordered_track(BrowseOrder::Title, 0).await,
+            Err(ReaderError::NoBrowseIndex)
+        ));
+        assert_eq!(reader.track(0).await.unwrap().artist.as_str(), "A");
+    }
+
+    #[tokio::test]
+    async fn reader_drops_corrupt_browse_index() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().to_str().unwrap();
+        build_library(root, &[(1, "A", "B"), (2, "A", "B")]);
+        let path = tmp.path().join("library.browse");
+        let mut browse = std::fs::read(&path).unwrap();
+        browse[BrowseHeader::SIZE] ^= 0xFF;
+        std::fs::write(&path, browse).unwrap();
+
+        let storage = LocalFileStorage::new(root);
+        let mut reader = SoulLibraryReader::open(storage, root).await.unwrap();
+        assert!(!reader.has_browse_index());
+        let integrity = reader.integrity();
+        assert!(integrity.is_corrupt(LibrarySection::Browse));
+        assert!(!integrity.is_corrupt(LibrarySection::Index));
+        assert!(!integrity.is_corrupt(LibrarySection::Meta));
+        assert_eq!(reader.track(1).await.unwrap().track_number, 2);
+        assert_eq!(reader.integrity().bad_tracks, 0);
+    }
+
+    #[tokio::test]
+    async fn reader_salvages_tracks_around_a_corrupt_meta_blob() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().to_str().unwrap();
+        build_library(
+            root,
+            &[
+                (1, "Amon Tobin", "Foley Room"),
+                (1, "Portishead", "Dummy"),
+                (2, "Portishead", "Dummy"),
+            ],
+        );
+        // Still decodes, but no longer matches its sort key.
+        let path = tmp.path().join("library.meta");
+        let mut meta = std::fs::read(&path).unwrap();
+        let at = meta.windows(6).position(|w| w == b"Portis").unwrap();
+        meta[at] = b'X';
+        std::fs::write(&path, meta).unwrap();
+
+        let storage = LocalFileStorage::new(root);
+        let mut reader = SoulLibraryReader::open(storage, root).await.unwrap();
+        assert!(reader.integrity().is_corrupt(LibrarySection::Meta));
+        assert!(reader.has_browse_index());
+
+        let page = reader.page(0, 3).await.unwrap();
+        let titles: Vec<_> = page.iter().map(|t| t.artist.as_str()).collect();
+        assert_eq!(titles, ["Amon Tobin", "Portishead"]);
+        assert!(matches!(
+            reader.track(1).await,
+            Err(ReaderError::Format(LibraryError::DecodeError))
+        ));
+        assert_eq!(reader.integrity().bad_tracks, 2);
+    }
+
+    #[tokio::test]
+    async fn reader_opens_with_truncated_index() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().to_str().unwrap();
+        build_library(root, &[(1, "A", "B"), (2, "A", "B")]);
+        let path = tmp.path().join("library.idx");
+        let idx = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &idx[..IndexEntry::SIZE]).unwrap();
+
+        let storage = LocalFileStorage::new(root);
+        let mut reader = SoulLibraryReader::open(storage, root).await.unwrap();
+        assert!(reader.integrity().is_corrupt(LibrarySection::Index));
+        assert_eq!(reader.track(0).await.unwrap().track_number, 1);
+        assert!(reader.track(1).await.is_err());
+        assert!(!reader.integrity().is_intact());
+    }
+
     #[tokio::test]
     async fn reader_manifest_missing_returns_err() {
         let tmp = TempDir::new().unwrap();
@@ -1,7 +1,34 @@
 //! Scanner — walks a FAT32 directory tree and emits supported audio file entries.
+//!
+//! Sidecar files are associated by name: `Album/01 - Song.flac` uses
+//! `Album/01 - Song.lrc` for lyrics and `Book/book.m4b` uses `Book/book.cue`
+//! for chapters when they exist (see [`Scanner::lyrics_path_for`] and
+//! [`Scanner::cue_path_for`]).
+//!
+//! The initial scan of a large card takes minutes, so the walker reports
+//! through a [`ScanSession`]: every directory, parsed file and added track
+//! bumps a [`ScanProgress`] and fires the progress callback, and each step
+//! checks a shared [`ScanCancel`] flag.  Cancelling stops the walk at the
+//! next step; the tracks added so far stay in the index.
+//!
+//! Before entering a directory or reading a file the walker asks a
+//! [`ScanFilter`], which skips hidden and system entries (FAT attribute
+//! bits or a leading `.`), the junk folders operating systems leave on
+//! cards ([`BUILTIN_EXCLUDES`]) and the `[library] exclude` globs from
+//! `soul.toml`.  A skipped directory is not entered at all.
+//!
+//! The walk itself is a [`ScanWalk`]: an explicit stack of open directories
+//! instead of recursion, so a deeply nested or corrupt card cannot overflow
+//! the scan task's stack.  The stack is a fixed `heapless` vector bounded by
+//! the depth limit (at most [`MAX_SCAN_DEPTH`]), and a directory whose start
+//! cluster is already open further up is refused as a FAT loop.  Either case
+//! surfaces as a [`WalkError`] rather than a hang or a fault.
+
+use core::sync::atomic::{AtomicBool, Ordering};
 
 use crate::track::AudioFormat;
-use heapless::String;
+use heapless::{String, Vec};
+use platform::soul_config::{LibraryOverrides, EXCLUDE_LEN, MAX_EXCLUDES};
 
 /// A single audio file discovered during a directory scan.
 pub struct ScanEntry {
@@ -9,6 +36,8 @@ pub struct ScanEntry {
     pub path: String<256>,
     /// Detected audio format (from file extension).
     pub format: AudioFormat,
+    /// Sibling `.lrc` file, when the directory has one for this track.
+    pub lyrics_path: Option<String<256>>,
 }
 
 /// Stateless helper for file-system traversal and extension filtering.
@@ -20,24 +49,498 @@ impl Scanner {
     /// The comparison is **case-insensitive** and does not allocate; it
     /// operates entirely in `core` so it is `no_std` compatible.
     ///
-    /// Supported extensions: `flac`, `mp3`, `wav`.
+    /// Supported extensions: `flac`, `mp3`, `wav`, plus `m4a`, `m4b` with the
+    /// `aac` feature and `opus` with the `opus` feature.
     pub fn is_supported_extension(ext: &str) -> bool {
-        // Compare byte-by-byte with ASCII lowercasing to avoid std::string.
-        eq_ignore_ascii_case(ext, "flac")
-            || eq_ignore_ascii_case(ext, "mp3")
-            || eq_ignore_ascii_case(ext, "wav")
+        Self::format_for_extension(ext).is_some()
     }
 
     /// Derive an [`AudioFormat`] from a file extension, or return `None`.
+    ///
+    /// Extensions come from the format table
+    /// ([`FORMATS`](crate::metadata::FORMATS)).
     pub fn format_for_extension(ext: &str) -> Option<AudioFormat> {
-        if eq_ignore_ascii_case(ext, "flac") {
-            Some(AudioFormat::Flac)
-        } else if eq_ignore_ascii_case(ext, "mp3") {
-            Some(AudioFormat::Mp3)
-        } else if eq_ignore_ascii_case(ext, "wav") {
-            Some(AudioFormat::Wav)
-        } else {
-            None
+        AudioFormat::from_extension(ext)
+    }
+
+    /// Returns `true` when `ext` is the LRC lyrics extension (any case).
+    pub fn is_lyrics_extension(ext: &str) -> bool {
+        eq_ignore_ascii_case(ext, "lrc")
+    }
+
+    /// The LRC path associated with `track_path`: the same path with the
+    /// extension replaced by `.lrc`.
+    ///
+    /// Returns `None` when `track_path` has no extension or the result does
+    /// not fit in 256 bytes.  The caller checks whether the file exists.
+    pub fn lyrics_path_for(track_path: &str) -> Option<String<256>> {
+        sidecar_path(track_path, "lrc")
+    }
+
+    /// The CUE sheet associated with `track_path` (extension replaced by
+    /// `.cue`), holding chapter marks for single-file audiobooks.
+    ///
+    /// Same rules as [`lyrics_path_for`](Self::lyrics_path_for).
+    pub fn cue_path_for(track_path: &str) -> Option<String<256>> {
+        sidecar_path(track_path, "cue")
+    }
+
+    /// The `.episode` sidecar associated with `track_path`, holding podcast
+    /// episode metadata (see [`crate::podcast`]).
+    ///
+    /// Same rules as [`lyrics_path_for`](Self::lyrics_path_for).
+    pub fn episode_path_for(track_path: &str) -> Option<String<256>> {
+        sidecar_path(track_path, "episode")
+    }
+}
+
+/// Folder names skipped on every card, compared without case.
+///
+/// Dot-prefixed folders (`.thumbnails`, `.Trashes`, `.Spotlight-V100`,
+/// `.fseventsd`) are already skipped as hidden and are not listed.
+pub const BUILTIN_EXCLUDES: [&str; 4] = [
+    "System Volume Information",
+    "$RECYCLE.BIN",
+    "LOST.DIR",
+    "FOUND.000",
+];
+
+/// Attribute byte of a FAT directory entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FatAttributes(pub u8);
+
+impl FatAttributes {
+    /// Read-only bit.
+    pub const READ_ONLY: u8 = 0x01;
+    /// Hidden bit.
+    pub const HIDDEN: u8 = 0x02;
+    /// System bit.
+    pub const SYSTEM: u8 = 0x04;
+
+    /// Hidden or system: entries Windows itself does not show.
+    pub fn is_hidden_or_system(self) -> bool {
+        self.0 & (Self::HIDDEN | Self::SYSTEM) != 0
+    }
+}
+
+/// Decides which directory entries the walker skips.
+///
+/// Patterns are globs: `*` matches any run of characters, `?` one byte,
+/// and letters match without case.  A pattern without `/` is matched
+/// against the entry name; one with `/` against the whole path from the
+/// card root (a leading `/` is optional), so `"*.tmp"` skips temporary
+/// files anywhere and `"Audiobooks/old"` skips one folder.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanFilter {
+    patterns: Vec<String<EXCLUDE_LEN>, MAX_EXCLUDES>,
+}
+
+impl ScanFilter {
+    /// Only the built-in rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in rules plus the `soul.toml` exclusions.
+    pub fn with_config(library: &LibraryOverrides) -> Self {
+        Self {
+            patterns: library.exclude.clone(),
+        }
+    }
+
+    /// Add one exclusion glob.
+    ///
+    /// # Errors
+    ///
+    /// Returns the pattern back when it is longer than [`EXCLUDE_LEN`]
+    /// bytes or [`MAX_EXCLUDES`] patterns are already set.
+    pub fn exclude<'p>(&mut self, pattern: &'p str) -> Result<(), &'p str> {
+        let owned = String::try_from(pattern).map_err(|_| pattern)?;
+        self.patterns.push(owned).map_err(|_| pattern)
+    }
+
+    /// User patterns, in the order they were added.
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(String::as_str)
+    }
+
+    /// Whether the walker should skip the entry at `path` (a full path
+    /// with `/` separators) with FAT attributes `attributes`.
+    pub fn is_excluded(&self, path: &str, attributes: FatAttributes) -> bool {
+        let path = path.trim_end_matches('/');
+        let name = path.rsplit('/').next().unwrap_or(path);
+        if attributes.is_hidden_or_system() || name.starts_with('.') {
+            return true;
+        }
+        if BUILTIN_EXCLUDES
+            .iter()
+            .any(|builtin| builtin.eq_ignore_ascii_case(name))
+        {
+            return true;
+        }
+        let relative = path.trim_start_matches('/');
+        self.patterns().any(|pattern| {
+            if pattern.contains('/') {
+                glob_matches(pattern.trim_start_matches('/'), relative)
+            } else {
+                glob_matches(pattern, name)
+            }
+        })
+    }
+}
+
+/// Running totals of a library scan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanProgress {
+    /// Directories entered so far.
+    pub dirs_visited: u32,
+    /// Audio files whose metadata has been read.
+    pub files_parsed: u32,
+    /// Tracks written to the index.
+    pub tracks_added: u32,
+}
+
+/// Cooperative cancellation flag shared between the scan task and the UI.
+///
+/// `const`-constructible so it can live in a `static`; the UI calls
+/// [`cancel`](Self::cancel) and the walker stops at its next step.
+#[derive(Debug, Default)]
+pub struct ScanCancel(AtomicBool);
+
+impl ScanCancel {
+    /// A flag that is not cancelled.
+    pub const fn new() -> Self {
+        Self(AtomicBool::new(false))
+    }
+
+    /// Ask the running scan to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called since the last
+    /// [`reset`](Self::reset).
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Clear the flag before starting a new scan.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Returned by [`ScanSession`] steps once the scan has been cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanCancelled;
+
+/// Progress bookkeeping for one walk of the card.
+///
+/// The walker calls [`enter_dir`](Self::enter_dir),
+/// [`file_parsed`](Self::file_parsed) and [`track_added`](Self::track_added)
+/// as it goes and propagates [`ScanCancelled`] with `?`.  Each step first
+/// checks the cancel flag, then counts and reports, so a cancelled scan
+/// never reports work it did not do.
+pub struct ScanSession<'a, F> {
+    progress: ScanProgress,
+    cancel: &'a ScanCancel,
+    on_progress: F,
+}
+
+impl<'a, F: FnMut(&ScanProgress)> ScanSession<'a, F> {
+    /// Start a session reporting to `on_progress` and stopping on `cancel`.
+    pub fn new(cancel: &'a ScanCancel, on_progress: F) -> Self {
+        Self {
+            progress: ScanProgress::default(),
+            cancel,
+            on_progress,
+        }
+    }
+
+    /// Totals so far.
+    pub fn progress(&self) -> ScanProgress {
+        self.progress
+    }
+
+    /// Whether the scan has been asked to stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Record entering a directory.
+    ///
+    /// # Errors
+    ///
+    /// [`ScanCancelled`] when the scan has been cancelled.
+    pub fn enter_dir(&mut self) -> Result<(), ScanCancelled> {
+        self.step(|p| p.dirs_visited = p.dirs_visited.saturating_add(1))
+    }
+
+    /// Record reading one audio file's metadata.
+    ///
+    /// # Errors
+    ///
+    /// [`ScanCancelled`] when the scan has been cancelled.
+    pub fn file_parsed(&mut self) -> Result<(), ScanCancelled> {
+        self.step(|p| p.files_parsed = p.files_parsed.saturating_add(1))
+    }
+
+    /// Record adding one track to the index.
+    ///
+    /// # Errors
+    ///
+    /// [`ScanCancelled`] when the scan has been cancelled.
+    pub fn track_added(&mut self) -> Result<(), ScanCancelled> {
+        self.step(|p| p.tracks_added = p.tracks_added.saturating_add(1))
+    }
+
+    /// End the session, returning the final totals.
+    pub fn finish(self) -> ScanProgress {
+        self.progress
+    }
+
+    fn step(&mut self, count: impl FnOnce(&mut ScanProgress)) -> Result<(), ScanCancelled> {
+        if self.cancel.is_cancelled() {
+            return Err(ScanCancelled);
+        }
+        count(&mut self.progress);
+        (self.on_progress)(&self.progress);
+        Ok(())
+    }
+}
+
+/// Deepest directory nesting a [`ScanWalk`] will enter (the walk root is
+/// depth 0), and the default limit.
+pub const MAX_SCAN_DEPTH: usize = 16;
+
+/// Why a [`ScanWalk`] refused to go on.
+///
+/// The walk is left as it was, so [`ScanWalk::current`] still names the
+/// parent of the directory that was refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkError {
+    /// The directory is more than `limit` levels below the root.
+    TooDeep { limit: usize },
+    /// The directory starts at `cluster`, which is already open further up
+    /// the walk: the directory tree loops back on itself.
+    Loop { cluster: u32 },
+    /// The full path of the directory does not fit in 256 bytes.
+    PathTooLong,
+    /// The scan was cancelled.
+    Cancelled,
+}
+
+impl From<ScanCancelled> for WalkError {
+    fn from(_: ScanCancelled) -> Self {
+        Self::Cancelled
+    }
+}
+
+/// The directory a [`ScanWalk`] is currently in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalkDir<'w> {
+    /// Full path, no trailing `/` (except for a root of `/`).
+    pub path: &'w str,
+    /// Levels below the walk root.
+    pub depth: usize,
+    /// Entries of the listing already handled; skip this many.  Zero the
+    /// first time a directory comes up.
+    pub skip: u32,
+}
+
+impl WalkDir<'_> {
+    /// Whether this is the first time the walk comes to this directory
+    /// (rather than resuming it after a subdirectory).
+    pub fn is_new(&self) -> bool {
+        self.skip == 0
+    }
+}
+
+/// One open directory on the walk stack.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    /// First cluster of the directory; 0 when unknown.
+    cluster: u32,
+    /// Length of `ScanWalk::path` while this directory is current.
+    path_len: usize,
+    /// Listing index to resume at.
+    skip: u32,
+}
+
+/// Iterative depth-first walk of a directory tree with bounded memory.
+///
+/// The walk keeps only the chain of open directories: for each, its first
+/// cluster and where to resume its listing.  The caller drives it:
+///
+/// 1. [`current`](Self::current) names the directory to list and how many
+///    entries to skip; `None` means the walk is complete.
+/// 2. Files are handled in place.  At the first subdirectory that passes
+///    the [`ScanFilter`], call [`descend`](Self::descend) with the index of
+///    the entry after it, then go back to step 1.
+/// 3. When the listing runs out, call [`ascend`](Self::ascend).
+///
+/// A parent is listed again from its resume index after each child, which
+/// trades some re-reading of directory clusters for a stack whose size does
+/// not depend on how many folders a directory holds.
+///
+/// Clusters identify directories for loop detection; pass 0 on volumes
+/// that have none (the depth limit still bounds the walk).
+#[derive(Debug, Clone)]
+pub struct ScanWalk {
+    path: String<256>,
+    open: Vec<Frame, { MAX_SCAN_DEPTH + 1 }>,
+    max_depth: usize,
+}
+
+impl ScanWalk {
+    /// Start a walk at `root`, whose first cluster is `root_cluster`.
+    ///
+    /// # Errors
+    ///
+    /// [`WalkError::PathTooLong`] when `root` is longer than 256 bytes.
+    pub fn new(root: &str, root_cluster: u32) -> Result<Self, WalkError> {
+        let root = match root.trim_end_matches('/') {
+            "" => "/",
+            trimmed => trimmed,
+        };
+        let path = String::try_from(root).map_err(|_| WalkError::PathTooLong)?;
+        let mut open = Vec::new();
+        open.push(Frame {
+            cluster: root_cluster,
+            path_len: path.len(),
+            skip: 0,
+        })
+        .map_err(|_| WalkError::PathTooLong)?;
+        Ok(Self {
+            path,
+            open,
+            max_depth: MAX_SCAN_DEPTH,
+        })
+    }
+
+    /// Limit the walk to `max_depth` levels below the root (clamped to
+    /// [`MAX_SCAN_DEPTH`]).
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth.min(MAX_SCAN_DEPTH);
+        self
+    }
+
+    /// The depth limit in effect.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// The directory to list next, or `None` once the walk is complete.
+    pub fn current(&self) -> Option<WalkDir<'_>> {
+        let frame = self.open.last()?;
+        Some(WalkDir {
+            path: self.path.as_str(),
+            depth: self.open.len().saturating_sub(1),
+            skip: frame.skip,
+        })
+    }
+
+    /// Enter subdirectory `name` of the current directory, which starts at
+    /// `cluster`.  `resume` is the listing index of the entry after `name`,
+    /// where the current directory picks up once `name` is done.
+    ///
+    /// On error the walk is unchanged, so the caller may log the error and
+    /// carry on with the next entry instead of abandoning the scan.
+    ///
+    /// # Errors
+    ///
+    /// [`WalkError::PathTooLong`] when the child's path does not fit,
+    /// [`WalkError::Loop`] when `cluster` is already open on the walk and
+    /// [`WalkError::TooDeep`] when the child is past the depth limit.
+    pub fn descend(&mut self, name: &str, cluster: u32, resume: u32) -> Result<(), WalkError> {
+        let mut path = self.path.clone();
+        if !path.ends_with('/') {
+            path.push('/').map_err(|_| WalkError::PathTooLong)?;
+        }
+        path.push_str(name).map_err(|_| WalkError::PathTooLong)?;
+
+        if cluster != 0 && self.open.iter().any(|frame| frame.cluster == cluster) {
+            return Err(WalkError::Loop { cluster });
+        }
+        if self.open.len() > self.max_depth || self.open.is_full() {
+            return Err(WalkError::TooDeep {
+                limit: self.max_depth,
+            });
+        }
+        if let Some(parent) = self.open.last_mut() {
+            parent.skip = resume;
+        }
+        let child = Frame {
+            cluster,
+            path_len: path.len(),
+            skip: 0,
+        };
+        // Room was checked above.
+        let _ = self.open.push(child);
+        self.path = path;
+        Ok(())
+    }
+
+    /// The current directory's listing is exhausted; return to its parent.
+    pub fn ascend(&mut self) {
+        self.open.pop();
+        let len = self.open.last().map_or(0, |frame| frame.path_len);
+        self.path.truncate(len);
+    }
+
+    /// Whether every directory has been listed.
+    pub fn is_done(&self) -> bool {
+        self.open.is_empty()
+    }
+}
+
+/// `track_path` with its extension replaced by `ext`.
+fn sidecar_path(track_path: &str, ext: &str) -> Option<String<256>> {
+    let dot = track_path.rfind('.')?;
+    let name_start = track_path.rfind('/').map_or(0, |i| i.saturating_add(1));
+    if dot <= name_start {
+        // "/music/.hidden" or a dot in a directory name.
+        return None;
+    }
+    let mut path = String::new();
+    path.push_str(track_path.get(..=dot)?).ok()?;
+    path.push_str(ext).ok()?;
+    Some(path)
+}
+
+/// `*`/`?` glob match, ASCII case-insensitive.
+///
+/// Backtracks only to the most recent `*`, which is enough because a
+/// later `*` can absorb anything an earlier one could.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let (pattern, text) = (pattern.as_bytes(), text.as_bytes());
+    let (mut p, mut t) = (0usize, 0usize);
+    // Pattern index after the last `*`, and the text index it resumes at.
+    let mut star: Option<(usize, usize)> = None;
+    loop {
+        match (pattern.get(p), text.get(t)) {
+            (Some(b'*'), _) => {
+                p = p.saturating_add(1);
+                star = Some((p, t));
+                continue;
+            }
+            (Some(&c), Some(&x)) if c == b'?' || c.eq_ignore_ascii_case(&x) => {
+                p = p.saturating_add(1);
+                t = t.saturating_add(1);
+                continue;
+            }
+            (None, None) => return true,
+            _ => {}
+        }
+        match star {
+            Some((sp, st)) if st < text.len() => {
+                let st = st.saturating_add(1);
+                star = Some((sp, st));
+                p = sp;
+                t = st;
+            }
+            _ => return false,
         }
     }
 }
@@ -65,6 +568,25 @@ mod tests {
         assert!(Scanner::is_supported_extension("mp3"));
     }
 
+    #[test]
+    fn test_scanner_recognises_m4a_and_m4b_only_with_aac() {
+        let aac = cfg!(feature = "aac");
+        assert_eq!(Scanner::is_supported_extension("m4a"), aac);
+        assert_eq!(
+            Scanner::format_for_extension("M4B"),
+            aac.then_some(AudioFormat::Aac)
+        );
+    }
+
+    #[test]
+    fn test_scanner_recognises_opus_only_with_opus() {
+        assert_eq!(
+            Scanner::format_for_extension("opus"),
+            cfg!(feature = "opus").then_some(AudioFormat::Opus)
+        );
+        assert!(!Scanner::is_supported_extension("ogg"));
+    }
+
     #[test]
     fn test_scanner_rejects_jpg() {
         assert!(!Scanner::is_supported_extension("jpg"));
@@ -91,8 +613,245 @@ mod tests {
                 s
             },
             format: AudioFormat::Flac,
+            lyrics_path: Scanner::lyrics_path_for("/music/test.flac"),
         };
         assert_eq!(entry.path.as_str(), "/music/test.flac");
         assert_eq!(entry.format, AudioFormat::Flac);
+        assert_eq!(
+            entry.lyrics_path.as_ref().map(|p| p.as_str()),
+            Some("/music/test.lrc")
+        );
+    }
+
+    #[test]
+    fn test_lyrics_path_replaces_extension() {
+        let path = Scanner::lyrics_path_for("/music/A.B/01 - Song.v2.flac").expect("path");
+        assert_eq!(path.as_str(), "/music/A.B/01 - Song.v2.lrc");
+        assert!(Scanner::is_lyrics_extension("LRC"));
+    }
+
+    #[test]
+    fn test_cue_path_replaces_extension() {
+        let path = Scanner::cue_path_for("/books/Dune/Dune.m4b").expect("path");
+        assert_eq!(path.as_str(), "/books/Dune/Dune.cue");
+    }
+
+    #[test]
+    fn test_episode_path_replaces_extension() {
+        let path = Scanner::episode_path_for("/Podcasts/Show/2024-01-02 - Ep 1.mp3").expect("path");
+        assert_eq!(path.as_str(), "/Podcasts/Show/2024-01-02 - Ep 1.episode");
+    }
+
+    #[test]
+    fn test_scan_session_counts_and_reports_each_step() {
+        let cancel = ScanCancel::new();
+        let mut reports = 0u32;
+        let mut session = ScanSession::new(&cancel, |_| reports = reports.saturating_add(1));
+        session.enter_dir().expect("dir");
+        session.file_parsed().expect("file");
+        session.file_parsed().expect("file");
+        session.track_added().expect("track");
+        let progress = session.finish();
+        assert_eq!(
+            progress,
+            ScanProgress {
+                dirs_visited: 1,
+                files_parsed: 2,
+                tracks_added: 1,
+            }
+        );
+        assert_eq!(reports, 4);
+    }
+
+    #[test]
+    fn test_scan_session_stops_once_cancelled() {
+        let cancel = ScanCancel::new();
+        let mut last = ScanProgress::default();
+        let mut session = ScanSession::new(&cancel, |p| last = *p);
+        session.enter_dir().expect("dir");
+        session.track_added().expect("track");
+        cancel.cancel();
+        assert!(session.is_cancelled());
+        assert_eq!(session.file_parsed(), Err(ScanCancelled));
+        assert_eq!(session.enter_dir(), Err(ScanCancelled));
+        let progress = session.finish();
+        assert_eq!(progress.tracks_added, 1, "work done before cancel is kept");
+        assert_eq!(progress.files_parsed, 0);
+        assert_eq!(last, progress);
+
+        cancel.reset();
+        assert!(!cancel.is_cancelled());
+    }
+
+    #[test]
+    fn test_glob_matches_stars_and_case() {
+        assert!(glob_matches("*.tmp", "Track.TMP"));
+        assert!(glob_matches("a*b*c", "aXXbYbc"));
+        assert!(glob_matches("?ut*", "outtakes"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("*.tmp", "tmp"));
+        assert!(!glob_matches("a?c", "ac"));
+        assert!(!glob_matches("abc", "abcd"));
+    }
+
+    #[test]
+    fn test_filter_skips_hidden_system_and_builtin_entries() {
+        let filter = ScanFilter::new();
+        let plain = FatAttributes::default();
+        assert!(filter.is_excluded("/Music/.thumbnails", plain));
+        assert!(filter.is_excluded("/Music/Album/._01.flac", plain));
+        assert!(filter.is_excluded("/system volume information/", plain));
+        assert!(filter.is_excluded("/$Recycle.Bin", plain));
+        assert!(filter.is_excluded("/Music/desktop.ini", FatAttributes(FatAttributes::HIDDEN)));
+        assert!(filter.is_excluded("/Music/x.flac", FatAttributes(FatAttributes::SYSTEM)));
+        assert!(!filter.is_excluded("/Music/x.flac", FatAttributes(FatAttributes::READ_ONLY)));
+        assert!(!filter.is_excluded("/Music/LOST.DIR.flac", plain));
+    }
+
+    #[test]
+    fn test_filter_applies_user_globs_to_names_or_paths() {
+        let mut config = LibraryOverrides::default();
+        config
+            .exclude
+            .push(String::try_from("*.tmp").expect("fits"))
+            .expect("room");
+        let mut filter = ScanFilter::with_config(&config);
+        filter.exclude("/Audiobooks/old").expect("room");
+        let plain = FatAttributes::default();
+        assert!(filter.is_excluded("/Music/A/rip.TMP", plain));
+        assert!(filter.is_excluded("/audiobooks/OLD", plain));
+        assert!(!filter.is_excluded("/Audiobooks/older", plain));
+        assert!(!filter.is_excluded("/Music/Audiobooks/old", plain));
+        assert_eq!(filter.patterns().count(), 2);
+
+        let long = "x".repeat(EXCLUDE_LEN + 1);
+        assert_eq!(filter.exclude(&long), Err(long.as_str()));
+    }
+
+    /// A directory listing in a fake FAT tree: `(name, is_dir, cluster)`.
+    type Listing = &'static [(&'static str, bool, u32)];
+
+    /// Drive `walk` over `tree` (keyed by first cluster), collecting the
+    /// file paths in visit order.
+    fn walk_tree(
+        mut walk: ScanWalk,
+        tree: &[(u32, Listing)],
+    ) -> Result<std::vec::Vec<std::string::String>, WalkError> {
+        let mut files = std::vec::Vec::new();
+        let mut cluster_of = std::vec![2u32];
+        'dirs: while let Some(dir) = walk.current() {
+            let (path, skip, cluster) = (
+                dir.path.to_owned(),
+                dir.skip,
+                *cluster_of.last().expect("open"),
+            );
+            let listing = tree
+                .iter()
+                .find(|(c, _)| *c == cluster)
+                .map_or(&[][..], |(_, l)| l);
+            for (i, &(name, is_dir, child)) in (0u32..).zip(listing.iter()).skip(skip as usize) {
+                if is_dir {
+                    walk.descend(name, child, i.saturating_add(1))?;
+                    cluster_of.push(child);
+                    continue 'dirs;
+                }
+                files.push(format!("{}/{name}", path.trim_end_matches('/')));
+            }
+            walk.ascend();
+            cluster_of.pop();
+        }
+        assert!(walk.is_done());
+        Ok(files)
+    }
+
+    #[test]
+    fn test_walk_visits_every_file_depth_first_in_listing_order() {
+        let tree: &[(u32, Listing)] = &[
+            (
+                2,
+                &[
+                    ("a.flac", false, 0),
+                    ("A", true, 3),
+                    ("B", true, 5),
+                    ("z.mp3", false, 0),
+                ],
+            ),
+            (3, &[("Deep", true, 4), ("1.flac", false, 0)]),
+            (4, &[("2.flac", false, 0)]),
+            (5, &[]),
+        ];
+        let files = walk_tree(ScanWalk::new("/", 2).expect("root"), tree).expect("walk");
+        assert_eq!(files, ["/a.flac", "/A/Deep/2.flac", "/A/1.flac", "/z.mp3"]);
+    }
+
+    #[test]
+    fn test_walk_refuses_a_directory_that_loops_to_an_ancestor() {
+        let tree: &[(u32, Listing)] = &[
+            (2, &[("Music", true, 3)]),
+            (3, &[("Album", true, 4)]),
+            (4, &[("Again", true, 3)]),
+        ];
+        let err = walk_tree(ScanWalk::new("/", 2).expect("root"), tree).expect_err("loop");
+        assert_eq!(err, WalkError::Loop { cluster: 3 });
+    }
+
+    #[test]
+    fn test_walk_stops_at_the_depth_limit() {
+        // Every level is a fresh cluster, so only the depth limit ends it.
+        let tree: &[(u32, Listing)] = &[
+            (2, &[("d", true, 3)]),
+            (3, &[("d", true, 4)]),
+            (4, &[("d", true, 5)]),
+            (5, &[("d", true, 6)]),
+        ];
+        let mut walk = ScanWalk::new("/Music/", 2).expect("root").with_max_depth(2);
+        walk.descend("d", 3, 1).expect("depth 1");
+        walk.descend("d", 4, 1).expect("depth 2");
+        assert_eq!(
+            walk.descend("d", 5, 1),
+            Err(WalkError::TooDeep { limit: 2 })
+        );
+        assert_eq!(walk.current().map(|d| d.path), Some("/Music/d/d"));
+        let walk = ScanWalk::new("/Music/", 2).expect("root").with_max_depth(2);
+        let err = walk_tree(walk, tree).expect_err("too deep");
+        assert_eq!(err, WalkError::TooDeep { limit: 2 });
+        let clamped = ScanWalk::new("/", 2)
+            .expect("root")
+            .with_max_depth(usize::MAX);
+        assert_eq!(clamped.max_depth(), MAX_SCAN_DEPTH);
+    }
+
+    #[test]
+    fn test_walk_error_leaves_the_walk_usable() {
+        let mut walk = ScanWalk::new("/", 2).expect("root");
+        let long = "n".repeat(300);
+        assert_eq!(walk.descend(&long, 9, 1), Err(WalkError::PathTooLong));
+        assert!(matches!(
+            walk.descend("Self", 2, 1),
+            Err(WalkError::Loop { cluster: 2, .. })
+        ));
+        let dir = walk.current().expect("root still open");
+        assert_eq!((dir.path, dir.depth, dir.is_new()), ("/", 0, true));
+
+        // Clusters of 0 are never treated as loops.
+        walk.descend("Music", 0, 1).expect("descend");
+        walk.descend("Album", 0, 4).expect("descend");
+        assert_eq!(
+            walk.current().map(|d| (d.path, d.depth)),
+            Some(("/Music/Album", 2))
+        );
+        walk.ascend();
+        let music = walk.current().expect("parent");
+        assert_eq!(
+            (music.path, music.skip, music.is_new()),
+            ("/Music", 4, false)
+        );
+        assert_eq!(WalkError::from(ScanCancelled), WalkError::Cancelled);
+    }
+
+    #[test]
+    fn test_lyrics_path_needs_a_file_extension() {
+        assert!(Scanner::lyrics_path_for("/music/A.B/track").is_none());
+        assert!(Scanner::lyrics_path_for("/music/.hidden").is_none());
     }
 }
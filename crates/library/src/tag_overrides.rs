@@ -0,0 +1,410 @@
+//! Tag overrides — title, artist and album corrections made on the device.
+//!
+//! Files with broken tags are fixed from the tag editor without rewriting
+//! the audio file: each correction is stored in a [`TagOverrides`] table
+//! (`{soul_root}/tags.bin`, see
+//! [`tag_overrides_path`](platform::soul_library::tag_overrides_path)) and
+//! applied on top of the tags read from the file whenever the library is
+//! rebuilt.
+//!
+//! Overrides are keyed by a [`FileHash`] of the file's size and leading
+//! bytes rather than by path or `soul_id`, so they follow a file across
+//! renames, moves and rescans.  Hashing only the first [`HASH_SPAN`] bytes
+//! keeps the scan cost bounded; two different files would need the same
+//! size and the same first 64 KiB of tags and audio to collide.
+
+use heapless::{String, Vec};
+use platform::File;
+use serde::{Deserialize, Serialize};
+
+use crate::binary::{LibraryError, TrackMeta};
+
+/// Corrections remembered by a [`TagOverrides`] table by default.
+pub const MAX_TAG_OVERRIDES: usize = 64;
+
+/// Bytes from the start of a file covered by its [`FileHash`].
+pub const HASH_SPAN: u64 = 64 * 1024;
+
+/// Read chunk used while hashing.
+const HASH_CHUNK: usize = 512;
+
+/// Errors returned by [`TagOverrides::set`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagOverrideError {
+    /// The value is longer than the field holds ([`TagField::max_len`]).
+    TooLong,
+    /// The table already holds `N` files.
+    Full,
+}
+
+/// Identity of an audio file that survives renames: its size and the
+/// CRC32 of its first [`HASH_SPAN`] bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FileHash {
+    /// File size in bytes.
+    pub size: u64,
+    /// CRC32 of the first `min(size, HASH_SPAN)` bytes.
+    pub crc32: u32,
+}
+
+impl FileHash {
+    /// Hash of a file of `size` bytes whose leading bytes are `head`;
+    /// anything in `head` past [`HASH_SPAN`] is ignored.
+    pub fn from_head(size: u64, head: &[u8]) -> Self {
+        let span = usize::try_from(HASH_SPAN).unwrap_or(usize::MAX);
+        Self {
+            size,
+            crc32: crc32fast::hash(head.get(..span).unwrap_or(head)),
+        }
+    }
+
+    /// Hash `file`, reading its first [`HASH_SPAN`] bytes from the start.
+    ///
+    /// # Errors
+    ///
+    /// Any seek or read error of `file`.
+    pub async fn of_file<F: File>(file: &mut F) -> Result<Self, F::Error> {
+        file.seek(0).await?;
+        let mut hasher = crc32fast::Hasher::new();
+        let mut remaining = HASH_SPAN.min(file.size());
+        let mut buf = [0u8; HASH_CHUNK];
+        while remaining > 0 {
+            let want = usize::try_from(remaining).map_or(HASH_CHUNK, |r| r.min(HASH_CHUNK));
+            let n = file.read(buf.get_mut(..want).unwrap_or_default()).await?;
+            if n == 0 {
+                break;
+            }
+            hasher.update(buf.get(..n).unwrap_or_default());
+            remaining = remaining.saturating_sub(u64::try_from(n).unwrap_or(u64::MAX));
+        }
+        Ok(Self {
+            size: file.size(),
+            crc32: hasher.finalize(),
+        })
+    }
+}
+
+/// A tag the editor can correct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TagField {
+    /// Track title.
+    Title,
+    /// Track artist.
+    Artist,
+    /// Album name.
+    Album,
+}
+
+impl TagField {
+    /// Every field, in the order the tag editor lists them.
+    pub const ALL: [Self; 3] = [Self::Title, Self::Artist, Self::Album];
+
+    /// Label shown in the tag editor.
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Title => "Title",
+            Self::Artist => "Artist",
+            Self::Album => "Album",
+        }
+    }
+
+    /// Longest value in bytes, matching the [`TrackMeta`] field.
+    pub const fn max_len(self) -> usize {
+        match self {
+            Self::Title => 128,
+            Self::Artist | Self::Album => 64,
+        }
+    }
+
+    /// This field's value in `meta`.
+    pub fn of(self, meta: &TrackMeta) -> &str {
+        match self {
+            Self::Title => meta.title.as_str(),
+            Self::Artist => meta.artist.as_str(),
+            Self::Album => meta.album.as_str(),
+        }
+    }
+}
+
+/// Corrected tags of one file; `None` fields keep the file's own tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagOverride {
+    /// The file these corrections belong to.
+    pub hash: FileHash,
+    /// Corrected title.
+    pub title: Option<String<128>>,
+    /// Corrected artist.
+    pub artist: Option<String<64>>,
+    /// Corrected album.
+    pub album: Option<String<64>>,
+}
+
+impl TagOverride {
+    /// The corrected value of `field`, if any.
+    pub fn get(&self, field: TagField) -> Option<&str> {
+        match field {
+            TagField::Title => self.title.as_deref(),
+            TagField::Artist => self.artist.as_deref(),
+            TagField::Album => self.album.as_deref(),
+        }
+    }
+
+    /// Replace the tags in `meta` with the corrected ones.
+    pub fn apply(&self, meta: &mut TrackMeta) {
+        if let Some(title) = &self.title {
+            meta.title.clone_from(title);
+        }
+        if let Some(artist) = &self.artist {
+            meta.artist.clone_from(artist);
+        }
+        if let Some(album) = &self.album {
+            meta.album.clone_from(album);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.title.is_none() && self.artist.is_none() && self.album.is_none()
+    }
+}
+
+/// Tag corrections for up to `N` files.
+///
+/// Persist with [`TagOverrides::encode`] after each edit; library scans
+/// [`decode`](Self::decode) the table and call [`apply`](Self::apply) for
+/// every file they index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagOverrides<const N: usize = MAX_TAG_OVERRIDES> {
+    entries: Vec<TagOverride, N>,
+}
+
+impl<const N: usize> TagOverrides<N> {
+    /// An empty table.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Number of files with corrections.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// `true` when no file has corrections.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Corrections for the file with `hash`, if any.
+    pub fn get(&self, hash: FileHash) -> Option<&TagOverride> {
+        self.entries.iter().find(|e| e.hash == hash)
+    }
+
+    /// Correct `field` of the file with `hash` to `value`.  An empty
+    /// `value` drops the correction, restoring the file's own tag.
+    ///
+    /// # Errors
+    ///
+    /// See [`TagOverrideError`]; the table is unchanged on error.
+    pub fn set(
+        &mut self,
+        hash: FileHash,
+        field: TagField,
+        value: &str,
+    ) -> Result<(), TagOverrideError> {
+        if value.is_empty() {
+            self.clear(hash, field);
+            return Ok(());
+        }
+        if value.len() > field.max_len() {
+            return Err(TagOverrideError::TooLong);
+        }
+        let index = match self.entries.iter().position(|e| e.hash == hash) {
+            Some(index) => index,
+            None => {
+                self.entries
+                    .push(TagOverride {
+                        hash,
+                        title: None,
+                        artist: None,
+                        album: None,
+                    })
+                    .map_err(|_| TagOverrideError::Full)?;
+                self.entries.len().saturating_sub(1)
+            }
+        };
+        let Some(entry) = self.entries.get_mut(index) else {
+            return Err(TagOverrideError::Full);
+        };
+        // Lengths were checked against the field capacities above.
+        match field {
+            TagField::Title => entry.title = String::try_from(value).ok(),
+            TagField::Artist => entry.artist = String::try_from(value).ok(),
+            TagField::Album => entry.album = String::try_from(value).ok(),
+        }
+        Ok(())
+    }
+
+    /// Drop the correction of `field` for the file with `hash`.
+    pub fn clear(&mut self, hash: FileHash, field: TagField) {
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.hash == hash) {
+            match field {
+                TagField::Title => entry.title = None,
+                TagField::Artist => entry.artist = None,
+                TagField::Album => entry.album = None,
+            }
+        }
+        self.entries.retain(|e| !e.is_empty());
+    }
+
+    /// Drop every correction for the file with `hash`.
+    pub fn remove(&mut self, hash: FileHash) {
+        self.entries.retain(|e| e.hash != hash);
+    }
+
+    /// Apply the corrections for the file with `hash` to `meta`.  Returns
+    /// `true` when there were any.
+    pub fn apply(&self, hash: FileHash, meta: &mut TrackMeta) -> bool {
+        let Some(entry) = self.get(hash) else {
+            return false;
+        };
+        entry.apply(meta);
+        true
+    }
+
+    /// Encode the table into `buf` (postcard), returning the used prefix.
+    ///
+    /// # Errors
+    ///
+    /// [`LibraryError::DecodeError`] when `buf` is too small.
+    pub fn encode<'b>(&self, buf: &'b mut [u8]) -> Result<&'b mut [u8], LibraryError> {
+        postcard::to_slice(self, buf).map_err(|_| LibraryError::DecodeError)
+    }
+
+    /// Decode a table written by [`encode`](Self::encode).
+    ///
+    /// # Errors
+    ///
+    /// [`LibraryError::DecodeError`] when `bytes` is corrupt or holds more
+    /// than `N` entries.
+    pub fn decode(bytes: &[u8]) -> Result<Self, LibraryError> {
+        postcard::from_bytes(bytes).map_err(|_| LibraryError::DecodeError)
+    }
+}
+
+impl<const N: usize> Default for TagOverrides<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)] // Tests use expect() for readable assertions
+mod tests {
+    use super::*;
+
+    fn meta() -> TrackMeta {
+        TrackMeta {
+            soul_id: 1,
+            album_id: 1,
+            track_number: 1,
+            disc_number: 1,
+            year: 0,
+            format: 0,
+            channels: 2,
+            duration_secs: 200,
+            sample_rate: 44_100,
+            title: String::try_from("Track 01").expect("fits"),
+            artist: String::try_from("Unknown Artist").expect("fits"),
+            album: String::try_from("Dummy").expect("fits"),
+            file_path: String::try_from("/Music/x/01.flac").expect("fits"),
+            loudness: None,
+        }
+    }
+
+    const HASH: FileHash = FileHash {
+        size: 1_000,
+        crc32: 0xDEAD_BEEF,
+    };
+
+    #[test]
+    fn test_overrides_replace_only_the_corrected_fields() {
+        let mut table: TagOverrides = TagOverrides::new();
+        table.set(HASH, TagField::Title, "Mysterons").expect("set");
+        table
+            .set(HASH, TagField::Artist, "Portishead")
+            .expect("set");
+        assert_eq!(table.len(), 1);
+
+        let mut m = meta();
+        assert!(table.apply(HASH, &mut m));
+        assert_eq!(m.title.as_str(), "Mysterons");
+        assert_eq!(m.artist.as_str(), "Portishead");
+        assert_eq!(m.album.as_str(), "Dummy");
+
+        let other = FileHash { size: 999, ..HASH };
+        let mut untouched = meta();
+        assert!(!table.apply(other, &mut untouched));
+        assert_eq!(untouched, meta());
+    }
+
+    #[test]
+    fn test_empty_value_restores_the_file_tag() {
+        let mut table: TagOverrides = TagOverrides::new();
+        table
+            .set(HASH, TagField::Album, "Dummy (1994)")
+            .expect("set");
+        table.set(HASH, TagField::Album, "").expect("clear");
+        assert!(table.is_empty(), "entry without corrections is dropped");
+        table.set(HASH, TagField::Title, "Roads").expect("set");
+        table.remove(HASH);
+        assert!(table.get(HASH).is_none());
+    }
+
+    #[test]
+    fn test_set_rejects_long_values_and_a_full_table() {
+        let mut table = TagOverrides::<1>::new();
+        let long = "x".repeat(TagField::Artist.max_len() + 1);
+        assert_eq!(
+            table.set(HASH, TagField::Artist, &long),
+            Err(TagOverrideError::TooLong)
+        );
+        assert!(table.is_empty());
+        table
+            .set(HASH, TagField::Title, &long)
+            .expect("titles are longer");
+        let other = FileHash { size: 1, ..HASH };
+        assert_eq!(
+            table.set(other, TagField::Title, "B"),
+            Err(TagOverrideError::Full)
+        );
+        table.set(HASH, TagField::Album, "A").expect("same file");
+    }
+
+    #[test]
+    fn test_overrides_roundtrip_through_postcard() {
+        let mut table: TagOverrides = TagOverrides::new();
+        table.set(HASH, TagField::Title, "Sour Times").expect("set");
+        let mut buf = [0u8; 256];
+        let bytes = table.encode(&mut buf).expect("encode");
+        assert_eq!(TagOverrides::decode(bytes), Ok(table));
+        assert!(TagOverrides::<4>::decode(&[0xFF, 0xFF]).is_err());
+    }
+
+    #[test]
+    fn test_file_hash_covers_size_and_head_only() {
+        let head = [7u8; 16];
+        let a = FileHash::from_head(5_000_000, &head);
+        assert_eq!(a, FileHash::from_head(5_000_000, &head));
+        assert_ne!(a, FileHash::from_head(5_000_001, &head));
+        assert_ne!(a.crc32, FileHash::from_head(5_000_000, &[8u8; 16]).crc32);
+
+        let span = usize::try_from(HASH_SPAN).expect("fits");
+        let mut long = std::vec![1u8; span + 10];
+        let hash = FileHash::from_head(20, &long);
+        if let Some(tail) = long.get_mut(span..) {
+            tail.fill(2);
+        }
+        assert_eq!(FileHash::from_head(20, &long), hash);
+    }
+}
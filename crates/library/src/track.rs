@@ -2,15 +2,43 @@
 
 use heapless::String;
 
+use crate::metadata::{FormatInfo, FORMATS};
+
 /// Audio container/codec format.
+///
+/// Every variant keeps its index code whether or not this build recognises
+/// the format (see [`FORMATS`]), so an index written by one build reads
+/// back in another.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AudioFormat {
     /// Free Lossless Audio Codec
     Flac,
     /// MPEG Audio Layer III
     Mp3,
-    /// Waveform Audio File Format
+    /// Waveform Audio File Format (PCM or IEEE-float payload)
     Wav,
+    /// AAC-LC in an MP4 container (`.m4a`, `.m4b`)
+    Aac,
+    /// Opus in an Ogg container (`.opus`)
+    Opus,
+}
+
+impl AudioFormat {
+    /// Detect the audio format from a file extension (any case).
+    ///
+    /// Returns `None` when no [`FORMATS`] entry claims the extension.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        FORMATS
+            .iter()
+            .find(|info| info.matches_extension(ext))
+            .map(|info| info.format)
+    }
+
+    /// This format's [`FORMATS`] entry, or `None` when this build does not
+    /// recognise it.
+    pub fn info(self) -> Option<&'static FormatInfo> {
+        FORMATS.iter().find(|info| info.format == self)
+    }
 }
 
 /// A single scanned audio track stored in the library index.
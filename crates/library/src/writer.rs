@@ -2,6 +2,9 @@
 //!
 //! Only compiled with the `std` feature (used by the `scan-library` xtask).
 //! Tracks must be added in sorted order (ascending `sort_key`).
+//!
+//! The browse orderings in `library.browse` are sorted here, on the host,
+//! by full case-folded names — the device only looks them up.
 
 #[cfg(not(feature = "std"))]
 compile_error!("library::writer requires the `std` feature");
@@ -11,7 +14,7 @@ use std::path::PathBuf;
 
 use crc32fast::Hasher;
 
-use crate::binary::{IndexEntry, ManifestBin, TrackMeta};
+use crate::binary::{BrowseHeader, IndexEntry, ManifestBin, TrackMeta};
 
 /// Error type for `LibraryWriter` operations.
 #[derive(Debug)]
@@ -52,7 +55,8 @@ impl From<postcard::Error> for WriterError {
     }
 }
 
-/// Builds `manifest.bin`, `library.idx`, and `library.meta` under `soul_root`.
+/// Builds `manifest.bin`, `library.idx`, `library.meta` and `library.browse`
+/// under `soul_root`.
 ///
 /// Tracks **must** be added in ascending `sort_key` order.  The caller is
 /// responsible for sorting before calling [`LibraryWriter::add_track`].
@@ -63,6 +67,16 @@ pub struct LibraryWriter {
     root: PathBuf,
     idx_buf: Vec<u8>,
     meta_buf: Vec<u8>,
+    browse_keys: Vec<BrowseKey>,
+}
+
+/// Case-folded names of one track, in `library.idx` order.
+struct BrowseKey {
+    artist: String,
+    album: String,
+    title: String,
+    disc: u16,
+    track: u16,
 }
 
 impl LibraryWriter {
@@ -80,6 +94,7 @@ impl LibraryWriter {
             root,
             idx_buf: Vec::new(),
             meta_buf: Vec::new(),
+            browse_keys: Vec::new(),
         })
     }
 
@@ -100,7 +115,8 @@ impl LibraryWriter {
         // Use a 600-byte stack buffer: writer.rs is std-only (host tool with MB of stack).
         // 600 bytes covers the worst-case TrackMeta encoding:
         //   title(128) + artist(64) + album(64) + file_path(256) = 512 bytes of string data
-        //   + 4 varint length prefixes + scalar fields (≈20 bytes with varints) ≈ 536 bytes max.
+        //   + 4 varint length prefixes + scalar fields (≈30 bytes with varints)
+        //   + loudness (≤10 bytes) ≈ 560 bytes max.
         // postcard workspace dep has default-features=false (no use-std/alloc), so to_stdvec
         // and to_allocvec are unavailable; a fixed buffer is the correct approach here.
         #[allow(clippy::large_stack_arrays)]
@@ -117,6 +133,13 @@ impl LibraryWriter {
         };
         self.idx_buf.extend_from_slice(&entry.encode());
         self.meta_buf.extend_from_slice(encoded);
+        self.browse_keys.push(BrowseKey {
+            artist: meta.artist.to_lowercase(),
+            album: meta.album.to_lowercase(),
+            title: meta.title.to_lowercase(),
+            disc: meta.disc_number,
+            track: meta.track_number,
+        });
 
         Ok(())
     }
@@ -142,9 +165,13 @@ impl LibraryWriter {
         meta_hasher.update(&self.meta_buf);
         let meta_checksum = meta_hasher.finalize();
 
-        // Write idx and meta first; manifest last (atomic-ish)
+        // Write idx, meta and browse first; manifest last (atomic-ish)
         fs::write(self.root.join("library.idx"), &self.idx_buf)?;
         fs::write(self.root.join("library.meta"), &self.meta_buf)?;
+        fs::write(
+            self.root.join("library.browse"),
+            encode_browse(&self.browse_keys),
+        )?;
 
         let manifest = ManifestBin {
             track_count,
@@ -159,11 +186,72 @@ impl LibraryWriter {
     }
 }
 
+/// Build the `library.browse` file: three sorted orderings of the track
+/// positions plus the artist and album group starts (see [`BrowseHeader`]).
+fn encode_browse(keys: &[BrowseKey]) -> Vec<u8> {
+    // SAFETY: track counts are bounded by finish()'s u32 track_count.
+    #[allow(clippy::cast_possible_truncation)]
+    let positions = || (0..keys.len() as u32).collect::<Vec<u32>>();
+    let key = |i: &u32| usize::try_from(*i).ok().and_then(|i| keys.get(i));
+
+    let mut by_artist = positions();
+    by_artist.sort_by_key(|i| key(i).map(|k| (&k.artist, &k.album, k.disc, k.track, &k.title)));
+    let mut by_album = positions();
+    by_album.sort_by_key(|i| key(i).map(|k| (&k.album, &k.artist, k.disc, k.track)));
+    let mut by_title = positions();
+    by_title.sort_by_key(|i| key(i).map(|k| (&k.title, &k.artist, &k.album)));
+
+    let artist_starts = group_starts(&by_artist, |i| key(i).map(|k| &k.artist));
+    let album_starts = group_starts(&by_album, |i| key(i).map(|k| (&k.album, &k.artist)));
+
+    let mut body = Vec::new();
+    for word in by_artist
+        .iter()
+        .chain(&by_album)
+        .chain(&by_title)
+        .chain(&artist_starts)
+        .chain(&album_starts)
+    {
+        body.extend_from_slice(&word.to_le_bytes());
+    }
+    let mut hasher = Hasher::new();
+    hasher.update(&body);
+
+    // SAFETY: group counts are at most the track count, which fits in u32.
+    #[allow(clippy::cast_possible_truncation)]
+    let header = BrowseHeader {
+        track_count: keys.len() as u32,
+        artist_count: artist_starts.len() as u32,
+        album_count: album_starts.len() as u32,
+        checksum: hasher.finalize(),
+    };
+    let mut out = header.encode().to_vec();
+    out.extend_from_slice(&body);
+    out
+}
+
+/// Positions in `order` where the group key changes.
+fn group_starts<K: PartialEq>(order: &[u32], group: impl Fn(&u32) -> K) -> Vec<u32> {
+    let mut starts = Vec::new();
+    let mut previous = None;
+    for (pos, i) in order.iter().enumerate() {
+        let current = group(i);
+        if previous.as_ref() != Some(&current) {
+            // SAFETY: pos < order.len() which fits in u32 (see encode_browse).
+            #[allow(clippy::cast_possible_truncation)]
+            starts.push(pos as u32);
+            previous = Some(current);
+        }
+    }
+    starts
+}
+
 #[cfg(test)]
 #[allow(
     clippy::unwrap_used,
     clippy::expect_used,
     clippy::panic,
+    clippy::indexing_slicing,
     clippy::cast_possible_truncation
 )]
 mod tests {
@@ -189,6 +277,7 @@ mod tests {
                 format!("/soul/music/ta/ta/{:02}.flac", n).as_str(),
             )
             .unwrap(),
+            loudness: None,
         }
     }
 
@@ -238,6 +327,49 @@ mod tests {
         assert_eq!(idx.len(), 7 * IndexEntry::SIZE);
     }
 
+    #[test]
+    fn writer_browse_orders_and_groups() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path().to_str().unwrap();
+        let mut w = LibraryWriter::new(root).unwrap();
+        // idx order: 0 = beta/zed/1 "Cello", 1 = Alpha/Yak/1 "banjo", 2 = alpha/Yak/2 "Accordion"
+        for (n, artist, album, title) in [
+            (1u16, "beta", "Zed", "Cello"),
+            (1, "Alpha", "Yak", "banjo"),
+            (2, "alpha", "Yak", "Accordion"),
+        ] {
+            let mut meta = sample_meta(u32::from(n));
+            meta.artist = heapless::String::try_from(artist).unwrap();
+            meta.album = heapless::String::try_from(album).unwrap();
+            meta.title = heapless::String::try_from(title).unwrap();
+            w.add_track(sort_key_for(artist, album, n, 1), meta)
+                .unwrap();
+        }
+        w.finish(2, 0).unwrap();
+
+        let bytes = std::fs::read(tmp.path().join("library.browse")).unwrap();
+        let header: [u8; BrowseHeader::SIZE] = bytes[..BrowseHeader::SIZE].try_into().unwrap();
+        let header = BrowseHeader::decode(&header).unwrap();
+        assert_eq!(header.track_count, 3);
+        assert_eq!(header.artist_count, 2, "artist names are case-folded");
+        assert_eq!(header.album_count, 2);
+        assert_eq!(bytes.len() as u64, header.file_len());
+
+        let words: Vec<u32> = bytes[BrowseHeader::SIZE..]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        assert_eq!(&words[0..3], &[1, 2, 0], "artist order");
+        assert_eq!(&words[3..6], &[1, 2, 0], "album order");
+        assert_eq!(&words[6..9], &[2, 1, 0], "title order");
+        assert_eq!(&words[9..11], &[0, 2], "artist starts");
+        assert_eq!(&words[11..13], &[0, 2], "album starts");
+
+        let mut h = Hasher::new();
+        h.update(&bytes[BrowseHeader::SIZE..]);
+        assert_eq!(header.checksum, h.finalize());
+    }
+
     #[test]
     fn track_meta_worst_case_fits_in_writer_buffer() {
         // Construct a TrackMeta with maximum-length strings to verify the 600-byte
@@ -262,6 +394,11 @@ mod tests {
             artist: heapless::String::try_from(artist_str.as_str()).unwrap(),
             album: heapless::String::try_from(album_str.as_str()).unwrap(),
             file_path: heapless::String::try_from(file_path_str.as_str()).unwrap(),
+            loudness: Some(crate::binary::Loudness {
+                track_gain_cdb: i16::MIN,
+                album_gain_cdb: i16::MIN,
+                track_peak: u16::MAX,
+            }),
         };
 
         let tmp = TempDir::new().unwrap();
@@ -3,6 +3,12 @@
 //! No mocks. Uses tempfiles. Tests the complete pipeline as it runs on real hardware
 //! (with LocalFileStorage substituting for SdmmcStorage).
 
+#![allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::indexing_slicing
+)]
+
 use library::binary::{sort_key_for, TrackMeta};
 use library::reader::SoulLibraryReader;
 use library::writer::LibraryWriter;
@@ -34,6 +40,7 @@ fn build_meta(
         file_path: heapless::String::try_from(
             format!("/soul/music/{}/{}/{:02}.flac", artist, album, track_number).as_str()
         ).expect("path fits"),
+        loudness: None,
     }
 }
 
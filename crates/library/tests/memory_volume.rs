@@ -0,0 +1,464 @@
+//! Library code against `platform::storage_mem::MemoryVolume`.
+//!
+//! Scans a fixture tree of tagged audio files and reads a written library
+//! back through the in-memory volume, including the short-read and
+//! failed-open paths that are hard to provoke on a real file system, and
+//! exports a playlist that resolves against the same tree.
+
+#![allow(clippy::expect_used, clippy::large_stack_arrays)]
+
+use library::binary::{sort_key_for, TrackMeta};
+use library::delta::{
+    apply_delta, DeltaError, DeltaOp, DeltaReport, DeltaStore, DeltaWriter, LibraryDelta,
+};
+use library::metadata::detect_format;
+use library::playlist::{
+    export_tracks, playlist_path, ExportReport, M3uWriter, MemoryPlaylistSink, PlaylistSink,
+    PLAYLISTS_DIR,
+};
+use library::reader::{ReaderError, SoulLibraryReader};
+use library::scanner::{FatAttributes, ScanFilter, ScanWalk, Scanner, WalkError};
+use library::tag_overrides::{FileHash, TagField, TagOverrides, HASH_SPAN};
+use library::track::AudioFormat;
+use library::writer::LibraryWriter;
+use platform::soul_library::tag_overrides_path;
+use platform::storage_fixture::{FixtureBuilder, TrackTags};
+use platform::storage_mem::{MemoryStorageError, MemoryVolume};
+use platform::{File, Storage};
+use tempfile::TempDir;
+
+fn music_fixture() -> MemoryVolume {
+    FixtureBuilder::new()
+        .album("/Music/Portishead/Dummy", "Portishead", "Dummy", "flac", 3)
+        .album("/Music/Boards of Canada/Geogaddi", "Boards of Canada", "Geogaddi", "MP3", 2)
+        .track(
+            "/Music/Loose/interlude.wav",
+            &TrackTags::new("Interlude").artist("Unknown").year(1999),
+        )
+        .file("/Music/Portishead/Dummy/cover.jpg", [0xFF, 0xD8, 0xFF])
+        .file("/Music/Portishead/Dummy/notes.txt", *b"liner notes")
+        .build()
+        .expect("fixture")
+}
+
+#[tokio::test]
+async fn scanner_finds_supported_files_and_detects_format() {
+    let mut vol = music_fixture();
+    let entries = vol.walk_files("/Music").expect("walk");
+
+    let mut found = Vec::new();
+    for entry in entries {
+        let Some((_, ext)) = entry.name.rsplit_once('.') else {
+            continue;
+        };
+        let Some(by_ext) = Scanner::format_for_extension(ext) else {
+            continue;
+        };
+        let mut file = vol.open_file(&entry.path).await.expect("open");
+        let mut header = [0u8; 4];
+        let n = file.read(&mut header).await.expect("read");
+        assert_eq!(detect_format(header.get(..n).unwrap_or_default()), Some(by_ext));
+        found.push((entry.path, by_ext));
+    }
+
+    assert_eq!(found.len(), 6);
+    assert_eq!(
+        found.iter().filter(|(_, f)| *f == AudioFormat::Mp3).count(),
+        2
+    );
+    assert!(found
+        .iter()
+        .any(|(p, f)| p == "/Music/Loose/interlude.wav" && *f == AudioFormat::Wav));
+}
+
+#[test]
+fn scan_walk_matches_the_recursive_listing_and_honours_the_depth_limit() {
+    let mut vol = music_fixture();
+    vol.write_file("/Music/.Trashes/old.flac", *b"fLaC").expect("write");
+    let filter = ScanFilter::new();
+
+    // The memory volume has no clusters; the walk still bounds its depth.
+    let walk_card = |max_depth: usize| -> Result<Vec<String>, WalkError> {
+        let mut walk = ScanWalk::new("/Music", 0)?.with_max_depth(max_depth);
+        let mut files = Vec::new();
+        'dirs: while let Some(dir) = walk.current() {
+            let listing = vol.read_dir(dir.path).expect("list");
+            for (i, entry) in (0u32..).zip(&listing).skip(dir.skip as usize) {
+                if filter.is_excluded(&entry.path, FatAttributes::default()) {
+                    continue;
+                }
+                if entry.is_dir {
+                    walk.descend(&entry.name, 0, i.saturating_add(1))?;
+                    continue 'dirs;
+                }
+                files.push(entry.path.clone());
+            }
+            walk.ascend();
+        }
+        Ok(files)
+    };
+
+    let expected: Vec<String> = vol
+        .walk_files("/Music")
+        .expect("walk")
+        .into_iter()
+        .map(|e| e.path)
+        .filter(|p| !p.contains("/.Trashes/"))
+        .collect();
+    assert_eq!(walk_card(2).expect("walk"), expected);
+    assert_eq!(walk_card(1), Err(WalkError::TooDeep { limit: 1 }));
+}
+
+#[tokio::test]
+async fn tag_overrides_follow_a_renamed_file() {
+    let mut vol = music_fixture();
+    let path = "/Music/Portishead/Dummy/01 Track 01.flac";
+    let bytes = vol.file_bytes(path).expect("fixture track").to_vec();
+    let mut file = vol.open_file(path).await.expect("open");
+    let hash = FileHash::of_file(&mut file).await.expect("hash");
+    let span = usize::try_from(HASH_SPAN).expect("fits");
+    assert_eq!(
+        hash,
+        FileHash::from_head(bytes.len() as u64, bytes.get(..span).unwrap_or(&bytes))
+    );
+
+    let mut table: TagOverrides = TagOverrides::new();
+    table.set(hash, TagField::Title, "Mysterons").expect("set");
+
+    // Same content under another name hashes the same.
+    vol.write_file("/Music/Moved/renamed.flac", bytes).expect("write");
+    let mut moved = vol
+        .open_file("/Music/Moved/renamed.flac")
+        .await
+        .expect("open");
+    let moved = FileHash::of_file(&mut moved).await.expect("hash");
+    assert_eq!(
+        table.get(moved).and_then(|o| o.get(TagField::Title)),
+        Some("Mysterons")
+    );
+}
+
+fn write_library(root: &str) {
+    write_library_exported_at(root, 0);
+}
+
+fn write_library_exported_at(root: &str, export_timestamp: u64) {
+    let tracks = [(1u32, "Mysterons"), (2, "Sour Times"), (3, "Strangers")];
+    let mut w = LibraryWriter::new(root).expect("writer");
+    for (n, title) in tracks {
+        let track_number = u16::try_from(n).expect("small");
+        let meta = TrackMeta {
+            soul_id: n,
+            album_id: 1,
+            track_number,
+            disc_number: 1,
+            year: 1994,
+            format: 0,
+            channels: 2,
+            duration_secs: 240,
+            sample_rate: 44_100,
+            title: heapless::String::try_from(title).expect("title fits"),
+            artist: heapless::String::try_from("Portishead").expect("artist fits"),
+            album: heapless::String::try_from("Dummy").expect("album fits"),
+            file_path: heapless::String::try_from("/Music/Portishead/Dummy/01.flac")
+                .expect("path fits"),
+            loudness: None,
+        };
+        w.add_track(sort_key_for("Portishead", "Dummy", track_number, 1), meta)
+            .expect("add_track");
+    }
+    w.finish(1, export_timestamp).expect("finish");
+}
+
+#[tokio::test]
+async fn reader_tolerates_short_reads() {
+    let tmp = TempDir::new().expect("tempdir");
+    write_library(tmp.path().to_str().expect("utf-8 path"));
+
+    let mut vol = MemoryVolume::new();
+    vol.import_dir(tmp.path(), "/soul").expect("import");
+    // SDMMC-style short reads: every read returns at most 5 bytes.
+    vol.set_max_read(Some(5));
+
+    let mut reader = SoulLibraryReader::open(vol, "/soul").await.expect("open");
+    assert_eq!(reader.track_count(), 3);
+    let page = reader.page(0, 3).await.expect("page");
+    assert_eq!(page.len(), 3);
+    assert_eq!(page.first().map(|t| t.title.as_str()), Some("Mysterons"));
+}
+
+#[tokio::test]
+async fn reader_surfaces_storage_errors() {
+    let mut vol = MemoryVolume::new();
+    assert!(matches!(
+        SoulLibraryReader::open(vol.clone(), "/soul").await,
+        Err(ReaderError::Storage(MemoryStorageError::NotFound))
+    ));
+
+    vol.write_file("/soul/manifest.bin", [0u8; 64]).expect("write");
+    vol.fail_open("/soul/manifest.bin").expect("fail_open");
+    assert!(matches!(
+        SoulLibraryReader::open(vol, "/soul").await,
+        Err(ReaderError::Storage(MemoryStorageError::InjectedFault))
+    ));
+}
+
+#[tokio::test]
+async fn exported_playlist_resolves_on_the_card() {
+    let mut vol = music_fixture();
+    let tmp = TempDir::new().expect("tempdir");
+    let mut w = LibraryWriter::new(tmp.path().to_str().expect("utf-8 path")).expect("writer");
+    for n in 1..=3u16 {
+        let title = format!("Track {n:02}");
+        let meta = TrackMeta {
+            soul_id: u32::from(n),
+            album_id: 1,
+            track_number: n,
+            disc_number: 1,
+            year: 1994,
+            format: 0,
+            channels: 2,
+            duration_secs: 200,
+            sample_rate: 44_100,
+            title: heapless::String::try_from(title.as_str()).expect("title fits"),
+            artist: heapless::String::try_from("Portishead").expect("artist fits"),
+            album: heapless::String::try_from("Dummy").expect("album fits"),
+            file_path: heapless::String::try_from(
+                format!("/Music/Portishead/Dummy/{n:02} {title}.flac").as_str(),
+            )
+            .expect("path fits"),
+            loudness: None,
+        };
+        w.add_track(sort_key_for("Portishead", "Dummy", n, 1), meta)
+            .expect("add_track");
+    }
+    w.finish(1, 0).expect("finish");
+    vol.import_dir(tmp.path(), "/soul").expect("import");
+
+    // Queue: third track, first track, and an id the library no longer has.
+    let mut reader = SoulLibraryReader::open(vol.clone(), "/soul")
+        .await
+        .expect("open");
+    let path = playlist_path("Queue").expect("name");
+    let mut writer = M3uWriter::new(MemoryPlaylistSink::<2048>::new(), &path)
+        .await
+        .expect("header");
+    let report = export_tracks(&mut reader, [2, 0, 9], &mut writer)
+        .await
+        .expect("export");
+    assert_eq!(
+        report,
+        ExportReport {
+            written: 2,
+            skipped: 1
+        }
+    );
+    let sink = writer.finish();
+    vol.write_file(&path, sink.as_bytes())
+        .expect("write playlist");
+
+    let text = std::str::from_utf8(vol.file_bytes(&path).expect("saved")).expect("utf-8");
+    let entries: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+    assert_eq!(
+        entries,
+        [
+            "../Music/Portishead/Dummy/03 Track 03.flac",
+            "../Music/Portishead/Dummy/01 Track 01.flac",
+        ]
+    );
+    for entry in entries {
+        let resolved = format!(
+            "/{}",
+            entry
+                .strip_prefix("../")
+                .expect("climbs out of the playlist folder")
+        );
+        assert!(vol.file_bytes(&resolved).is_some(), "{resolved} missing");
+        assert!(!resolved.starts_with(&format!("/{PLAYLISTS_DIR}")));
+    }
+}
+
+/// Playlist file being written to a [`CardStore`].
+struct CardPlaylist {
+    path: String,
+    bytes: Vec<u8>,
+}
+
+impl PlaylistSink for CardPlaylist {
+    type Error = MemoryStorageError;
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bytes.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// [`DeltaStore`] writing to the memory volume, as the firmware writes to
+/// the card.
+struct CardStore {
+    vol: MemoryVolume,
+}
+
+impl DeltaStore for CardStore {
+    type Error = MemoryStorageError;
+    type Playlist = CardPlaylist;
+
+    async fn create_playlist(&mut self, path: &str) -> Result<CardPlaylist, Self::Error> {
+        Ok(CardPlaylist {
+            path: path.to_owned(),
+            bytes: Vec::new(),
+        })
+    }
+
+    async fn close_playlist(&mut self, playlist: CardPlaylist) -> Result<(), Self::Error> {
+        self.vol.write_file(&playlist.path, playlist.bytes)
+    }
+
+    async fn save_tag_overrides<const N: usize>(
+        &mut self,
+        table: &TagOverrides<N>,
+    ) -> Result<(), Self::Error> {
+        let mut buf = vec![0u8; 8192];
+        let bytes = table
+            .encode(&mut buf)
+            .map_err(|_| MemoryStorageError::FileTooLarge)?;
+        self.vol
+            .write_file(tag_overrides_path("/soul").as_str(), bytes.to_vec())
+    }
+}
+
+#[tokio::test]
+async fn delta_from_the_companion_app_adds_playlists_and_tags() {
+    const EXPORTED_AT: u64 = 1_700_000_000;
+    let tmp = TempDir::new().expect("tempdir");
+    write_library_exported_at(tmp.path().to_str().expect("utf-8 path"), EXPORTED_AT);
+    let mut vol = music_fixture();
+    vol.import_dir(tmp.path(), "/soul").expect("import");
+    let mut reader = SoulLibraryReader::open(vol.clone(), "/soul")
+        .await
+        .expect("open");
+    assert_eq!(reader.export_timestamp(), EXPORTED_AT);
+
+    let hash = FileHash {
+        size: 4_000,
+        crc32: 0xC0FF_EE00,
+    };
+    let mut buf = vec![0u8; 1024];
+    let mut writer = DeltaWriter::new(&mut buf, EXPORTED_AT).expect("header");
+    for op in [
+        DeltaOp::SetTag {
+            hash,
+            field: TagField::Album,
+            value: heapless::String::try_from("Dummy (Remastered)").expect("fits"),
+        },
+        DeltaOp::Playlist {
+            name: heapless::String::try_from("Night drive").expect("fits"),
+            tracks: heapless::Vec::from_slice(&[2, 7, 0]).expect("fits"),
+        },
+    ] {
+        writer.push(&op).expect("push");
+    }
+    let bytes = writer.finish().to_vec();
+    let delta = LibraryDelta::parse(&bytes).expect("valid delta");
+
+    let mut overrides: TagOverrides = TagOverrides::new();
+    let mut store = CardStore { vol };
+    let report = apply_delta(&delta, &mut reader, &mut overrides, &mut store)
+        .await
+        .expect("apply");
+    assert_eq!(
+        report,
+        DeltaReport {
+            tags: 1,
+            playlists: 1,
+            entries: ExportReport {
+                written: 2,
+                skipped: 1
+            },
+        }
+    );
+    assert_eq!(
+        overrides.get(hash).and_then(|o| o.get(TagField::Album)),
+        Some("Dummy (Remastered)")
+    );
+    let saved = store
+        .vol
+        .file_bytes(tag_overrides_path("/soul").as_str())
+        .expect("tag table saved");
+    assert_eq!(TagOverrides::decode(saved), Ok(overrides));
+
+    let path = playlist_path("Night drive").expect("name");
+    let text = std::str::from_utf8(store.vol.file_bytes(&path).expect("playlist")).expect("utf-8");
+    let titles: Vec<&str> = text
+        .lines()
+        .filter_map(|l| l.strip_prefix("#EXTINF:240,Portishead - "))
+        .collect();
+    assert_eq!(titles, ["Strangers", "Mysterons"]);
+}
+
+#[tokio::test]
+async fn delta_for_another_library_changes_nothing() {
+    let tmp = TempDir::new().expect("tempdir");
+    write_library_exported_at(tmp.path().to_str().expect("utf-8 path"), 1_700_000_000);
+    let mut vol = MemoryVolume::new();
+    vol.import_dir(tmp.path(), "/soul").expect("import");
+    let mut reader = SoulLibraryReader::open(vol.clone(), "/soul")
+        .await
+        .expect("open");
+
+    let mut buf = [0u8; 256];
+    let mut writer = DeltaWriter::new(&mut buf, 1_600_000_000).expect("header");
+    writer
+        .push(&DeltaOp::ClearTags {
+            hash: FileHash { size: 1, crc32: 1 },
+        })
+        .expect("push");
+    let bytes = writer.finish().to_vec();
+    let delta = LibraryDelta::parse(&bytes).expect("valid delta");
+
+    let mut overrides: TagOverrides = TagOverrides::new();
+    let files = vol.file_count();
+    let mut store = CardStore { vol };
+    assert!(matches!(
+        apply_delta(&delta, &mut reader, &mut overrides, &mut store).await,
+        Err(DeltaError::WrongLibrary)
+    ));
+    assert_eq!(store.vol.file_count(), files);
+}
+
+#[tokio::test]
+async fn delta_overflowing_the_tag_table_is_not_applied() {
+    let tmp = TempDir::new().expect("tempdir");
+    write_library(tmp.path().to_str().expect("utf-8 path"));
+    let mut vol = MemoryVolume::new();
+    vol.import_dir(tmp.path(), "/soul").expect("import");
+    let mut reader = SoulLibraryReader::open(vol.clone(), "/soul")
+        .await
+        .expect("open");
+
+    let mut buf = [0u8; 512];
+    let mut writer = DeltaWriter::new(&mut buf, 0).expect("header");
+    for size in 1..=3 {
+        writer
+            .push(&DeltaOp::SetTag {
+                hash: FileHash { size, crc32: 0 },
+                field: TagField::Title,
+                value: heapless::String::try_from("Fixed").expect("fits"),
+            })
+            .expect("push");
+    }
+    let bytes = writer.finish().to_vec();
+    let delta = LibraryDelta::parse(&bytes).expect("valid delta");
+
+    let mut overrides = TagOverrides::<2>::new();
+    let mut store = CardStore { vol };
+    assert!(matches!(
+        apply_delta(&delta, &mut reader, &mut overrides, &mut store).await,
+        Err(DeltaError::Tags(_))
+    ));
+    assert!(overrides.is_empty());
+    assert!(store
+        .vol
+        .file_bytes(tag_overrides_path("/soul").as_str())
+        .is_none());
+}
@@ -90,7 +90,9 @@ fn test_scan_entry_creation() {
     let entry = ScanEntry {
         path,
         format: AudioFormat::Flac,
+        lyrics_path: None,
     };
     assert_eq!(entry.format, AudioFormat::Flac);
     assert!(entry.path.as_str().ends_with(".flac"));
+    assert!(entry.lyrics_path.is_none());
 }
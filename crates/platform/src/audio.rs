@@ -133,6 +133,45 @@ pub enum OversamplingFilter {
     HybridFastRollOff,
 }
 
+impl OversamplingFilter {
+    /// Every filter, in datasheet order (filter 1 first).
+    pub const ALL: [Self; 7] = [
+        Self::FastRollOffLinearPhase,
+        Self::SlowRollOffLinearPhase,
+        Self::FastRollOffMinimumPhase,
+        Self::SlowRollOffMinimumPhase,
+        Self::ApodizingFastRollOff,
+        Self::BrickWall,
+        Self::HybridFastRollOff,
+    ];
+
+    /// Short name for the settings screen.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::FastRollOffLinearPhase => "Fast, linear phase",
+            Self::SlowRollOffLinearPhase => "Slow, linear phase",
+            Self::FastRollOffMinimumPhase => "Fast, minimum phase",
+            Self::SlowRollOffMinimumPhase => "Slow, minimum phase",
+            Self::ApodizingFastRollOff => "Apodizing, fast",
+            Self::BrickWall => "Brick wall",
+            Self::HybridFastRollOff => "Hybrid, fast",
+        }
+    }
+
+    /// Position of this filter in [`ALL`](Self::ALL).
+    #[must_use]
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|&f| f == self).unwrap_or(0)
+    }
+
+    /// The filter at `index` in [`ALL`](Self::ALL), if any.
+    #[must_use]
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,4 +210,15 @@ mod tests {
             assert!(cfg.validate().is_ok(), "sample rate {sr} must be valid");
         }
     }
+
+    #[test]
+    fn test_oversampling_filter_index_round_trips() {
+        for (i, &filter) in OversamplingFilter::ALL.iter().enumerate() {
+            assert_eq!(filter.index(), i);
+            assert_eq!(OversamplingFilter::from_index(i), Some(filter));
+            assert!(!filter.label().is_empty());
+        }
+        assert_eq!(OversamplingFilter::from_index(7), None);
+        assert_eq!(OversamplingFilter::default().index(), 0);
+    }
 }
@@ -0,0 +1,565 @@
+//! Audio loopback self-test: DAC output sampled back through the MCU ADC.
+//!
+//! A resistor divider taps each analogue output (after the output stage)
+//! into an STM32 ADC channel.  The test plays known tones and measures how
+//! much of each tone reaches each ADC channel, which catches the faults a
+//! listener would notice first:
+//!
+//! - a tone on the left output appears on the right ADC (swapped channels)
+//!   or on both (crosstalk, a shorted connector);
+//! - the 100 Hz or 8 kHz tone is much weaker than the 1 kHz reference
+//!   (a missing coupling capacitor, a wrong DAC filter);
+//! - the tone is still audible with the DAC muted.
+//!
+//! [`run_loopback`] steps through [`LoopbackStep::ALL`] on a
+//! [`LoopbackPort`] and returns a [`LoopbackReport`].  Levels come from a
+//! single-bin DFT at the tone frequency, so ADC noise, DC bias and mains hum
+//! barely move them.  All arithmetic is integer: the tones and the detector
+//! share one quarter-wave sine table.
+//!
+//! The diagnostics screen shows the report, and the HIL firmware runs it
+//! for [`HilCommand::AudioLoopback`](crate::hil::HilCommand::AudioLoopback).
+
+/// ADC sample rate the port must capture at (Hz).
+pub const CAPTURE_RATE_HZ: u32 = 48_000;
+
+/// Samples per channel per step: 100 ms, a whole number of cycles of every
+/// test tone so the DFT bins do not leak into each other.
+pub const CAPTURE_SAMPLES: usize = 4_800;
+
+/// Reference tone (Hz).
+pub const REFERENCE_HZ: u32 = 1_000;
+/// Low tone for the frequency response check (Hz).
+pub const LOW_HZ: u32 = 100;
+/// High tone for the frequency response check (Hz).
+pub const HIGH_HZ: u32 = 8_000;
+
+/// Weakest reference tone that counts as a signal (ADC counts, peak).
+pub const MIN_SIGNAL_COUNTS: u32 = 500;
+
+/// The other channel must be at least this many times weaker (20 dB).
+pub const ISOLATION_RATIO: u32 = 10;
+
+/// Low/high tone level relative to the reference must fall in this range
+/// (permille, about ±3 dB).
+pub const RESPONSE_RANGE_PERMILLE: (u32, u32) = (708, 1_413);
+
+/// A muted tone must be at least this many times weaker than the
+/// reference (40 dB).
+pub const MUTE_RATIO: u32 = 100;
+
+/// Quarter-wave sine, 64 steps plus the peak, scaled to `i16::MAX`.
+const QUARTER_SINE: [i16; 65] = [
+    0, 804, 1608, 2410, 3212, 4011, 4808, 5602, 6393, 7179, 7962, 8739, 9512, 10278, 11039, 11793,
+    12539, 13279, 14010, 14732, 15446, 16151, 16846, 17530, 18204, 18868, 19519, 20159, 20787,
+    21403, 22005, 22594, 23170, 23731, 24279, 24811, 25329, 25832, 26319, 26790, 27245, 27683,
+    28105, 28510, 28898, 29268, 29621, 29956, 30273, 30571, 30852, 31113, 31356, 31580, 31785,
+    31971, 32137, 32285, 32412, 32521, 32609, 32678, 32728, 32757, 32767,
+];
+
+/// Peak of [`sine`].
+const SINE_PEAK: i64 = 32_767;
+
+/// A quarter turn of the 32-bit phase accumulator.
+const QUARTER_TURN: u32 = 0x4000_0000;
+
+/// Tone samples are [`sine`] times this: -6 dBFS, leaving headroom for
+/// the output stage.
+const TONE_SCALE: i32 = 0x8000;
+
+/// Sine of a 32-bit phase (a full turn is 2^32), 256 steps per cycle.
+fn sine(phase: u32) -> i32 {
+    let step = phase.to_be_bytes()[0];
+    let index = usize::from(step & 0x3F);
+    let mirrored = 64usize.saturating_sub(index);
+    let value = |i: usize| i32::from(QUARTER_SINE.get(i).copied().unwrap_or(0));
+    match step / 64 {
+        0 => value(index),
+        1 => value(mirrored),
+        2 => value(index).saturating_neg(),
+        _ => value(mirrored).saturating_neg(),
+    }
+}
+
+/// Phase increment per sample for `freq_hz` at `rate_hz`.
+fn phase_step(freq_hz: u32, rate_hz: u32) -> u32 {
+    let turns = u64::from(freq_hz).checked_shl(32).unwrap_or(0);
+    turns
+        .checked_div(u64::from(rate_hz))
+        .and_then(|s| u32::try_from(s).ok())
+        .unwrap_or(0)
+}
+
+/// Integer square root (floor).
+fn isqrt(value: u64) -> u64 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x.saturating_add(1) / 2;
+    while y < x {
+        x = y;
+        y = x.saturating_add(value.checked_div(x).unwrap_or(0)) / 2;
+    }
+    x
+}
+
+/// Peak amplitude (ADC counts) of the `freq_hz` component of `samples`
+/// captured at `rate_hz`.
+///
+/// The mean is removed first, so the ADC's mid-scale bias does not matter.
+pub fn tone_level(samples: &[u16], freq_hz: u32, rate_hz: u32) -> u32 {
+    let Ok(count) = i64::try_from(samples.len()) else {
+        return 0;
+    };
+    let sum = samples
+        .iter()
+        .fold(0i64, |acc, &s| acc.saturating_add(i64::from(s)));
+    let Some(mean) = sum.checked_div(count) else {
+        return 0;
+    };
+    let step = phase_step(freq_hz, rate_hz);
+    let mut phase = 0u32;
+    let (mut in_phase, mut quadrature) = (0i64, 0i64);
+    for &s in samples {
+        let x = i64::from(s).saturating_sub(mean);
+        in_phase = in_phase.saturating_add(x.saturating_mul(i64::from(sine(phase))));
+        let cos = sine(phase.wrapping_add(QUARTER_TURN));
+        quadrature = quadrature.saturating_add(x.saturating_mul(i64::from(cos)));
+        phase = phase.wrapping_add(step);
+    }
+    // Σ A·sin(θ+φ)·P·sin(θ) ≈ A·P·n/2, so A = 2·|I + jQ| / (P·n).
+    let i = in_phase.checked_div(SINE_PEAK).unwrap_or(0).unsigned_abs();
+    let q = quadrature
+        .checked_div(SINE_PEAK)
+        .unwrap_or(0)
+        .unsigned_abs();
+    let magnitude = isqrt(i.saturating_mul(i).saturating_add(q.saturating_mul(q)));
+    let amplitude = magnitude
+        .saturating_mul(2)
+        .checked_div(count.unsigned_abs())
+        .unwrap_or(0);
+    u32::try_from(amplitude).unwrap_or(u32::MAX)
+}
+
+/// What the DAC plays during one step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Tone {
+    /// Tone frequency (Hz).
+    pub freq_hz: u32,
+    /// Play on the left output.
+    pub left: bool,
+    /// Play on the right output.
+    pub right: bool,
+    /// Engage the DAC's mute while the tone plays.
+    pub muted: bool,
+}
+
+/// Fills DAC buffers with a [`Tone`].
+#[derive(Debug, Clone)]
+pub struct ToneGenerator {
+    tone: Tone,
+    phase: u32,
+    step: u32,
+}
+
+impl ToneGenerator {
+    /// Generator for `tone` at the playback `sample_rate`.
+    pub fn new(tone: Tone, sample_rate: u32) -> Self {
+        Self {
+            tone,
+            phase: 0,
+            step: phase_step(tone.freq_hz, sample_rate),
+        }
+    }
+
+    /// Fill interleaved L/R 32-bit samples (the
+    /// [`AudioCodec::write_samples`](crate::AudioCodec::write_samples)
+    /// format), continuing the phase from the previous call.  Silent
+    /// channels get zeros.  Muting is the port's job, not the generator's.
+    pub fn fill(&mut self, samples: &mut [i32]) {
+        for frame in samples.chunks_exact_mut(2) {
+            let value = sine(self.phase).saturating_mul(TONE_SCALE);
+            if let [left, right] = frame {
+                *left = if self.tone.left { value } else { 0 };
+                *right = if self.tone.right { value } else { 0 };
+            }
+            self.phase = self.phase.wrapping_add(self.step);
+        }
+    }
+}
+
+/// One measurement of the loopback test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LoopbackStep {
+    /// Reference tone on the left output only.
+    Left,
+    /// Reference tone on the right output only.
+    Right,
+    /// Low tone on both outputs.
+    Low,
+    /// High tone on both outputs.
+    High,
+    /// Reference tone on both outputs with the DAC muted.
+    Muted,
+}
+
+impl LoopbackStep {
+    /// Every step, in the order [`run_loopback`] plays them.
+    pub const ALL: [Self; 5] = [Self::Left, Self::Right, Self::Low, Self::High, Self::Muted];
+
+    /// Tone played during this step.
+    pub const fn tone(self) -> Tone {
+        let (freq_hz, left, right, muted) = match self {
+            Self::Left => (REFERENCE_HZ, true, false, false),
+            Self::Right => (REFERENCE_HZ, false, true, false),
+            Self::Low => (LOW_HZ, true, true, false),
+            Self::High => (HIGH_HZ, true, true, false),
+            Self::Muted => (REFERENCE_HZ, true, true, true),
+        };
+        Tone {
+            freq_hz,
+            left,
+            right,
+            muted,
+        }
+    }
+}
+
+/// Plays a tone and captures both loopback ADC channels.
+#[allow(async_fn_in_trait)]
+pub trait LoopbackPort {
+    /// Error type.
+    type Error: core::fmt::Debug;
+
+    /// Play `tone` (see [`ToneGenerator`]), let the output settle, then
+    /// fill `left` and `right` with ADC samples taken at
+    /// [`CAPTURE_RATE_HZ`] while the tone is still playing.  With
+    /// `tone.muted` the DAC's mute must be engaged for the capture.
+    async fn capture(
+        &mut self,
+        tone: Tone,
+        left: &mut [u16],
+        right: &mut [u16],
+    ) -> Result<(), Self::Error>;
+}
+
+/// Tone level seen on each ADC channel (peak ADC counts).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChannelLevels {
+    /// Left loopback channel.
+    pub left: u32,
+    /// Right loopback channel.
+    pub right: u32,
+}
+
+/// A failed loopback check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LoopbackFault {
+    /// The reference tone did not reach one or both ADC channels.  The
+    /// other checks are skipped.
+    NoSignal,
+    /// Left plays on the right channel and vice versa.
+    ChannelsSwapped,
+    /// A single-channel tone leaks into the other channel.
+    Crosstalk,
+    /// The low tone is outside the response range.
+    LowResponse,
+    /// The high tone is outside the response range.
+    HighResponse,
+    /// The tone is still present with the DAC muted.
+    MuteLeak,
+}
+
+impl LoopbackFault {
+    /// Every fault, in check order.
+    pub const ALL: [Self; 6] = [
+        Self::NoSignal,
+        Self::ChannelsSwapped,
+        Self::Crosstalk,
+        Self::LowResponse,
+        Self::HighResponse,
+        Self::MuteLeak,
+    ];
+
+    /// Bit in [`LoopbackReport::fault_mask`].
+    pub const fn bit(self) -> u32 {
+        match self {
+            Self::NoSignal => 0x01,
+            Self::ChannelsSwapped => 0x02,
+            Self::Crosstalk => 0x04,
+            Self::LowResponse => 0x08,
+            Self::HighResponse => 0x10,
+            Self::MuteLeak => 0x20,
+        }
+    }
+
+    /// Short name for logs and the diagnostics screen.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::NoSignal => "no signal",
+            Self::ChannelsSwapped => "swapped",
+            Self::Crosstalk => "crosstalk",
+            Self::LowResponse => "low response",
+            Self::HighResponse => "high response",
+            Self::MuteLeak => "mute leak",
+        }
+    }
+}
+
+/// Levels measured by [`run_loopback`], one entry per [`LoopbackStep`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LoopbackReport {
+    /// [`LoopbackStep::Left`].
+    pub left: ChannelLevels,
+    /// [`LoopbackStep::Right`].
+    pub right: ChannelLevels,
+    /// [`LoopbackStep::Low`].
+    pub low: ChannelLevels,
+    /// [`LoopbackStep::High`].
+    pub high: ChannelLevels,
+    /// [`LoopbackStep::Muted`].
+    pub muted: ChannelLevels,
+}
+
+/// `level / reference` in permille is within [`RESPONSE_RANGE_PERMILLE`].
+fn in_response_range(level: u32, reference: u32) -> bool {
+    let permille = u64::from(level)
+        .saturating_mul(1_000)
+        .checked_div(u64::from(reference))
+        .unwrap_or(u64::MAX);
+    let (min, max) = RESPONSE_RANGE_PERMILLE;
+    (u64::from(min)..=u64::from(max)).contains(&permille)
+}
+
+impl LoopbackReport {
+    /// Store the levels measured during `step`.
+    pub fn set(&mut self, step: LoopbackStep, levels: ChannelLevels) {
+        let slot = match step {
+            LoopbackStep::Left => &mut self.left,
+            LoopbackStep::Right => &mut self.right,
+            LoopbackStep::Low => &mut self.low,
+            LoopbackStep::High => &mut self.high,
+            LoopbackStep::Muted => &mut self.muted,
+        };
+        *slot = levels;
+    }
+
+    /// Whether the check for `fault` failed.
+    pub fn has(&self, fault: LoopbackFault) -> bool {
+        self.fault_mask() & fault.bit() != 0
+    }
+
+    /// Failed checks as [`LoopbackFault::bit`]s; 0 when everything passed.
+    pub fn fault_mask(&self) -> u32 {
+        let (ref_l, ref_r) = (self.left.left, self.right.right);
+        let swapped = self.left.right > ref_l && self.right.left > ref_r;
+        let (ref_l, ref_r) = if swapped {
+            (self.left.right, self.right.left)
+        } else {
+            (ref_l, ref_r)
+        };
+        if ref_l < MIN_SIGNAL_COUNTS || ref_r < MIN_SIGNAL_COUNTS {
+            return LoopbackFault::NoSignal.bit();
+        }
+
+        let mut mask = 0;
+        let mut fail = |fault: LoopbackFault, failed: bool| {
+            if failed {
+                mask |= fault.bit();
+            }
+        };
+        fail(LoopbackFault::ChannelsSwapped, swapped);
+        let (leak_l, leak_r) = if swapped {
+            (self.left.left, self.right.right)
+        } else {
+            (self.left.right, self.right.left)
+        };
+        fail(
+            LoopbackFault::Crosstalk,
+            leak_l.saturating_mul(ISOLATION_RATIO) > ref_l
+                || leak_r.saturating_mul(ISOLATION_RATIO) > ref_r,
+        );
+        fail(
+            LoopbackFault::LowResponse,
+            !in_response_range(self.low.left, ref_l) || !in_response_range(self.low.right, ref_r),
+        );
+        fail(
+            LoopbackFault::HighResponse,
+            !in_response_range(self.high.left, ref_l) || !in_response_range(self.high.right, ref_r),
+        );
+        fail(
+            LoopbackFault::MuteLeak,
+            self.muted.left.saturating_mul(MUTE_RATIO) > ref_l
+                || self.muted.right.saturating_mul(MUTE_RATIO) > ref_r,
+        );
+        mask
+    }
+
+    /// Every check passed.
+    pub fn passed(&self) -> bool {
+        self.fault_mask() == 0
+    }
+}
+
+/// Run every [`LoopbackStep`] on `port`, using `left` and `right` as
+/// capture buffers (normally [`CAPTURE_SAMPLES`] long each).
+pub async fn run_loopback<P: LoopbackPort>(
+    port: &mut P,
+    left: &mut [u16],
+    right: &mut [u16],
+) -> Result<LoopbackReport, P::Error> {
+    let mut report = LoopbackReport::default();
+    for step in LoopbackStep::ALL {
+        let tone = step.tone();
+        port.capture(tone, left, right).await?;
+        let levels = ChannelLevels {
+            left: tone_level(left, tone.freq_hz, CAPTURE_RATE_HZ),
+            right: tone_level(right, tone.freq_hz, CAPTURE_RATE_HZ),
+        };
+        report.set(step, levels);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::large_futures
+)]
+mod tests {
+    use super::*;
+
+    /// Simulated divider + ADC with injectable faults.
+    struct Rig {
+        /// Peak ADC counts for a full tone.
+        counts: i32,
+        swapped: bool,
+        mute_leaks: bool,
+        /// Gain of the high tone relative to the reference (permille).
+        high_gain_permille: i32,
+    }
+
+    impl Rig {
+        fn healthy() -> Self {
+            Self {
+                counts: 4_000,
+                swapped: false,
+                mute_leaks: false,
+                high_gain_permille: 1_000,
+            }
+        }
+    }
+
+    impl LoopbackPort for Rig {
+        type Error = core::convert::Infallible;
+
+        async fn capture(
+            &mut self,
+            tone: Tone,
+            left: &mut [u16],
+            right: &mut [u16],
+        ) -> Result<(), Self::Error> {
+            let silent = tone.muted && !self.mute_leaks;
+            let gain = if tone.freq_hz == HIGH_HZ {
+                self.high_gain_permille
+            } else {
+                1_000
+            };
+            let peak = if silent {
+                0
+            } else {
+                self.counts * gain / 1_000
+            };
+            let (to_left, to_right) = if self.swapped {
+                (tone.right, tone.left)
+            } else {
+                (tone.left, tone.right)
+            };
+            let step = phase_step(tone.freq_hz, CAPTURE_RATE_HZ);
+            for (n, (l, r)) in left.iter_mut().zip(right.iter_mut()).enumerate() {
+                let s = sine(step.wrapping_mul(n as u32));
+                let level = |on: bool| {
+                    // 1% crosstalk from the driven channel, a little hum.
+                    let gain = if on { 100 } else { 1 };
+                    let hum = sine(phase_step(50, CAPTURE_RATE_HZ).wrapping_mul(n as u32)) / 200;
+                    (32_768 + s * peak / SINE_PEAK as i32 * gain / 100 + hum) as u16
+                };
+                *l = level(to_left);
+                *r = level(to_right);
+            }
+            Ok(())
+        }
+    }
+
+    async fn run(rig: &mut Rig) -> LoopbackReport {
+        let mut left = [0u16; CAPTURE_SAMPLES];
+        let mut right = [0u16; CAPTURE_SAMPLES];
+        run_loopback(rig, &mut left, &mut right).await.unwrap()
+    }
+
+    #[test]
+    fn sine_table_covers_all_quadrants() {
+        assert_eq!(sine(0), 0);
+        assert_eq!(sine(QUARTER_TURN), 32_767);
+        assert_eq!(sine(2 * QUARTER_TURN), 0);
+        assert_eq!(sine(3 * QUARTER_TURN), -32_767);
+    }
+
+    #[test]
+    fn tone_level_measures_only_its_frequency() {
+        let step = phase_step(REFERENCE_HZ, CAPTURE_RATE_HZ);
+        let samples: Vec<u16> = (0..CAPTURE_SAMPLES as u32)
+            .map(|n| (20_000 + sine(step.wrapping_mul(n)) / 32) as u16)
+            .collect();
+        let level = tone_level(&samples, REFERENCE_HZ, CAPTURE_RATE_HZ);
+        assert!((1_010..=1_030).contains(&level), "level {level}");
+        assert!(tone_level(&samples, HIGH_HZ, CAPTURE_RATE_HZ) < 10);
+        assert_eq!(tone_level(&[], REFERENCE_HZ, CAPTURE_RATE_HZ), 0);
+    }
+
+    #[test]
+    fn generator_gates_channels() {
+        let mut generator = ToneGenerator::new(LoopbackStep::Left.tone(), 48_000);
+        let mut samples = [0i32; 64];
+        generator.fill(&mut samples);
+        assert!(samples.iter().step_by(2).any(|&s| s > 1 << 29));
+        assert!(samples.iter().skip(1).step_by(2).all(|&s| s == 0));
+    }
+
+    #[tokio::test]
+    async fn healthy_rig_passes() {
+        let report = run(&mut Rig::healthy()).await;
+        assert!(report.passed(), "{report:?}");
+        assert!(report.left.left > 3_900 && report.left.right < 100);
+    }
+
+    #[tokio::test]
+    async fn faults_are_reported() {
+        let mut rig = Rig::healthy();
+        rig.swapped = true;
+        let report = run(&mut rig).await;
+        assert_eq!(report.fault_mask(), LoopbackFault::ChannelsSwapped.bit());
+
+        let mut rig = Rig::healthy();
+        rig.mute_leaks = true;
+        rig.high_gain_permille = 500;
+        let report = run(&mut rig).await;
+        assert!(report.has(LoopbackFault::MuteLeak));
+        assert!(report.has(LoopbackFault::HighResponse));
+        assert!(!report.has(LoopbackFault::LowResponse));
+
+        let mut rig = Rig::healthy();
+        rig.counts = 0;
+        let report = run(&mut rig).await;
+        assert_eq!(report.fault_mask(), LoopbackFault::NoSignal.bit());
+    }
+}
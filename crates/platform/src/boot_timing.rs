@@ -0,0 +1,585 @@
+//! Boot-time instrumentation and deferred subsystem start-up.
+//!
+//! The device should show its first screen within [`BOOT_BUDGET_MS`] of
+//! reset.  [`BootTimeline`] records when each [`BootPhase`] finishes and
+//! turns the marks into a [`BootReport`]: per-phase durations checked
+//! against per-phase budgets, plus the total time to first frame.  The
+//! report is logged over defmt at boot and shown on the service screen
+//! through [`DiagnosticsReport`](crate::diagnostics::DiagnosticsReport).
+//!
+//! Everything the first screen does not need waits for it: [`LazyInit`]
+//! holds deferred [`Subsystem`]s back until [`LazyInit::first_frame_shown`]
+//! and then hands them out one at a time.  That includes the library index:
+//! the boot path only reads the resume record and opens the last track from
+//! its stored path ([`BootPhase::ResumeOpen`]), so playback starts while
+//! the first screen, a skeleton Now Playing, is still refreshing.
+//!
+//! Cold boot to sound is timed on its own, against [`SOUND_BUDGET_MS`]:
+//! [`BootTimeline::mark_first_sound`] records when the first resumed
+//! samples reached the DAC.
+//!
+//! Timestamps are plain milliseconds since reset, as in
+//! [`latency`](crate::latency).  A phase that is never marked (a build
+//! without SD support, say) is left out of the report and its time counts
+//! towards the next phase that is.
+//!
+//! # Example
+//!
+//! ```
+//! use platform::boot_timing::{BootPhase, BootTimeline, LazyInit, Subsystem};
+//!
+//! let mut timeline = BootTimeline::new();
+//! timeline.mark(BootPhase::Mpu, 1);
+//! timeline.mark(BootPhase::Clocks, 40);
+//! timeline.mark(BootPhase::DisplayInit, 180);
+//! timeline.mark(BootPhase::FirstFrame, 2_100);
+//! timeline.mark_first_sound(640);
+//! let report = timeline.report();
+//! assert_eq!(report.total_ms, 2_100);
+//! assert_eq!(report.first_sound_ms, Some(640));
+//! assert!(report.within_budget());
+//!
+//! let mut lazy = LazyInit::new();
+//! lazy.defer(Subsystem::Bluetooth);
+//! assert_eq!(lazy.start_next(), None);
+//! lazy.first_frame_shown();
+//! assert_eq!(lazy.start_next(), Some(Subsystem::Bluetooth));
+//! ```
+
+use core::fmt;
+
+/// Reset to first frame on the panel (ms).
+pub const BOOT_BUDGET_MS: u64 = 3_000;
+
+/// Reset to the resumed track playing (ms).  Sound does not wait for the
+/// first frame's refresh, so this is well under [`BOOT_BUDGET_MS`].
+pub const SOUND_BUDGET_MS: u64 = 1_000;
+
+/// One step of the boot sequence, in the order `main` runs them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BootPhase {
+    /// MPU regions configured.
+    Mpu,
+    /// PLLs locked and the Embassy HAL initialised.
+    Clocks,
+    /// Panel reset and init sequence sent.
+    DisplayInit,
+    /// SD card mounted.
+    SdMount,
+    /// Resume record read and the last track opened from its stored path.
+    /// The library index is not needed for this and loads after the first
+    /// frame ([`Subsystem::LibraryIndex`]).
+    ResumeOpen,
+    /// First screen drawn and its refresh complete.
+    FirstFrame,
+}
+
+impl BootPhase {
+    /// Every phase, in boot order.
+    pub const ALL: [Self; 6] = [
+        Self::Mpu,
+        Self::Clocks,
+        Self::DisplayInit,
+        Self::SdMount,
+        Self::ResumeOpen,
+        Self::FirstFrame,
+    ];
+
+    /// Short name used in logs.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Mpu => "mpu",
+            Self::Clocks => "clocks",
+            Self::DisplayInit => "display",
+            Self::SdMount => "sd",
+            Self::ResumeOpen => "resume",
+            Self::FirstFrame => "first-frame",
+        }
+    }
+
+    /// Time the phase may take (ms).  The budgets add up to
+    /// [`BOOT_BUDGET_MS`]; the first frame is dominated by one GC16 refresh.
+    pub const fn budget_ms(self) -> u64 {
+        match self {
+            Self::Mpu => 10,
+            Self::Clocks => 90,
+            Self::DisplayInit => 200,
+            Self::SdMount => 300,
+            Self::ResumeOpen => 300,
+            Self::FirstFrame => 2_100,
+        }
+    }
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Start and end of one completed phase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PhaseTiming {
+    /// Phase measured.
+    pub phase: BootPhase,
+    /// End of the previous completed phase (0 for the first).
+    pub start_ms: u64,
+    /// When the phase was marked.
+    pub end_ms: u64,
+}
+
+impl PhaseTiming {
+    /// Time spent in the phase (ms).
+    pub const fn duration_ms(&self) -> u64 {
+        self.end_ms.saturating_sub(self.start_ms)
+    }
+
+    /// Whether the phase finished within its [`BootPhase::budget_ms`].
+    pub const fn within_budget(&self) -> bool {
+        self.duration_ms() <= self.phase.budget_ms()
+    }
+}
+
+/// Records the end of each [`BootPhase`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BootTimeline {
+    ends: [Option<u64>; 6],
+    first_sound: Option<u64>,
+}
+
+impl BootTimeline {
+    /// Timeline with no phases marked.
+    pub const fn new() -> Self {
+        Self {
+            ends: [None; 6],
+            first_sound: None,
+        }
+    }
+
+    /// Mark the first resumed samples reaching the DAC at `now_ms`.
+    ///
+    /// Returns `false` if sound was already marked; the first mark is kept.
+    /// Independent of the phases: it usually lands during
+    /// [`BootPhase::FirstFrame`].
+    pub fn mark_first_sound(&mut self, now_ms: u64) -> bool {
+        if self.first_sound.is_some() {
+            return false;
+        }
+        self.first_sound = Some(now_ms);
+        true
+    }
+
+    /// Mark `phase` as finished at `now_ms`.
+    ///
+    /// Returns `false` if the phase was already marked; the first mark
+    /// is kept.
+    pub fn mark(&mut self, phase: BootPhase, now_ms: u64) -> bool {
+        match self.ends.get_mut(phase.index()) {
+            Some(slot @ None) => {
+                *slot = Some(now_ms);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// When `phase` finished, if it has been marked.
+    pub fn end_ms(&self, phase: BootPhase) -> Option<u64> {
+        self.ends.get(phase.index()).copied().flatten()
+    }
+
+    /// Per-phase timings for every phase marked so far.
+    pub fn report(&self) -> BootReport {
+        let mut report = BootReport::default();
+        let mut start_ms = 0;
+        for phase in BootPhase::ALL {
+            let Some(end_ms) = self.end_ms(phase) else {
+                continue;
+            };
+            if let Some(slot) = report.phases.get_mut(phase.index()) {
+                *slot = Some(PhaseTiming {
+                    phase,
+                    start_ms,
+                    end_ms,
+                });
+            }
+            start_ms = end_ms;
+        }
+        report.total_ms = start_ms;
+        report.complete = self.end_ms(BootPhase::FirstFrame).is_some();
+        report.first_sound_ms = self.first_sound;
+        report
+    }
+}
+
+/// Boot timings, as logged at start-up and shown on the service screen.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BootReport {
+    phases: [Option<PhaseTiming>; 6],
+    /// Reset to the last marked phase (ms); the time to first frame once
+    /// [`complete`](Self::complete).
+    pub total_ms: u64,
+    /// The first frame has been marked.
+    pub complete: bool,
+    /// Reset to the resumed track playing (ms); `None` when nothing was
+    /// resumed.
+    pub first_sound_ms: Option<u64>,
+}
+
+impl BootReport {
+    /// Timing of `phase`, if it was marked.
+    pub fn phase(&self, phase: BootPhase) -> Option<PhaseTiming> {
+        self.phases.get(phase.index()).copied().flatten()
+    }
+
+    /// Timings of the marked phases, in boot order.
+    pub fn phases(&self) -> impl Iterator<Item = PhaseTiming> + '_ {
+        self.phases.iter().flatten().copied()
+    }
+
+    /// Phases that took longer than their budget.
+    pub fn over_budget(&self) -> impl Iterator<Item = PhaseTiming> + '_ {
+        self.phases().filter(|t| !t.within_budget())
+    }
+
+    /// Sound, if any, started within [`SOUND_BUDGET_MS`].
+    pub fn sound_within_budget(&self) -> bool {
+        self.first_sound_ms.is_none_or(|ms| ms <= SOUND_BUDGET_MS)
+    }
+
+    /// The first frame was reached within [`BOOT_BUDGET_MS`], no phase
+    /// overran its own budget and sound started in time.
+    pub fn within_budget(&self) -> bool {
+        self.complete
+            && self.total_ms <= BOOT_BUDGET_MS
+            && self.over_budget().next().is_none()
+            && self.sound_within_budget()
+    }
+}
+
+impl fmt::Display for BootReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for t in self.phases() {
+            writeln!(
+                f,
+                "boot {}: {} ms (budget {} ms) {}",
+                t.phase.name(),
+                t.duration_ms(),
+                t.phase.budget_ms(),
+                if t.within_budget() { "ok" } else { "FAIL" }
+            )?;
+        }
+        if let Some(ms) = self.first_sound_ms {
+            writeln!(
+                f,
+                "boot: first sound at {ms} ms (budget {SOUND_BUDGET_MS} ms) {}",
+                if self.sound_within_budget() {
+                    "ok"
+                } else {
+                    "FAIL"
+                }
+            )?;
+        }
+        if self.complete {
+            writeln!(
+                f,
+                "boot: first frame at {} ms (budget {BOOT_BUDGET_MS} ms) {}",
+                self.total_ms,
+                if self.within_budget() { "ok" } else { "FAIL" }
+            )
+        } else {
+            writeln!(f, "boot: no first frame FAIL")
+        }
+    }
+}
+
+/// A subsystem the first screen does not depend on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Subsystem {
+    /// Library index, browse lists and track metadata; until it is ready
+    /// Now Playing shows the resumed track as a skeleton.
+    LibraryIndex,
+    /// Bluetooth radio and pairing state.
+    Bluetooth,
+    /// Listening statistics.
+    Stats,
+}
+
+impl Subsystem {
+    /// Every subsystem, in the order deferred ones are started.
+    pub const ALL: [Self; 3] = [Self::LibraryIndex, Self::Bluetooth, Self::Stats];
+
+    /// Short name used in logs.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::LibraryIndex => "library-index",
+            Self::Bluetooth => "bluetooth",
+            Self::Stats => "stats",
+        }
+    }
+
+    const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Start-up state of a deferred [`Subsystem`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InitState {
+    /// Waiting for the first frame or its turn.
+    Pending,
+    /// Handed out by [`LazyInit::start_next`], not yet completed.
+    Running,
+    /// Initialised.
+    Ready,
+    /// Initialisation failed; the rest of the device runs without it.
+    Failed,
+}
+
+/// Holds non-critical subsystems back until the first screen is shown.
+///
+/// Subsystems are started one at a time, in [`Subsystem::ALL`] order, so
+/// a slow one cannot delay the first frame and they do not compete with
+/// each other for the SD card or SPI bus.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LazyInit {
+    states: [Option<InitState>; 3],
+    released: bool,
+}
+
+impl LazyInit {
+    /// Nothing deferred, first frame not yet shown.
+    pub const fn new() -> Self {
+        Self {
+            states: [None; 3],
+            released: false,
+        }
+    }
+
+    /// Defer `subsystem` until after the first frame.  Deferring it again
+    /// has no effect.
+    pub fn defer(&mut self, subsystem: Subsystem) {
+        if let Some(slot @ None) = self.states.get_mut(subsystem.index()) {
+            *slot = Some(InitState::Pending);
+        }
+    }
+
+    /// The first frame is on the panel; deferred subsystems may start.
+    pub fn first_frame_shown(&mut self) {
+        self.released = true;
+    }
+
+    /// Whether [`first_frame_shown`](Self::first_frame_shown) was called.
+    pub const fn is_released(&self) -> bool {
+        self.released
+    }
+
+    /// Next subsystem to start, marking it [`InitState::Running`].
+    ///
+    /// `None` before the first frame, while another subsystem is still
+    /// running, or when everything deferred has been started.
+    pub fn start_next(&mut self) -> Option<Subsystem> {
+        if !self.released || self.states.contains(&Some(InitState::Running)) {
+            return None;
+        }
+        let subsystem = Subsystem::ALL
+            .into_iter()
+            .find(|&s| self.state(s) == Some(InitState::Pending))?;
+        if let Some(slot) = self.states.get_mut(subsystem.index()) {
+            *slot = Some(InitState::Running);
+        }
+        Some(subsystem)
+    }
+
+    /// Record the outcome of starting `subsystem`.  Ignored unless it is
+    /// [`InitState::Running`].
+    pub fn complete(&mut self, subsystem: Subsystem, ok: bool) {
+        if let Some(slot @ Some(InitState::Running)) = self.states.get_mut(subsystem.index()) {
+            *slot = Some(if ok {
+                InitState::Ready
+            } else {
+                InitState::Failed
+            });
+        }
+    }
+
+    /// State of `subsystem`; `None` if it was never deferred.
+    pub fn state(&self, subsystem: Subsystem) -> Option<InitState> {
+        self.states.get(subsystem.index()).copied().flatten()
+    }
+
+    /// Every deferred subsystem has completed, successfully or not.
+    pub fn is_done(&self) -> bool {
+        self.states
+            .iter()
+            .flatten()
+            .all(|s| matches!(s, InitState::Ready | InitState::Failed))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::arithmetic_side_effects)]
+mod tests {
+    use super::*;
+
+    fn full_boot() -> BootTimeline {
+        let mut t = BootTimeline::new();
+        t.mark(BootPhase::Mpu, 2);
+        t.mark(BootPhase::Clocks, 50);
+        t.mark(BootPhase::DisplayInit, 200);
+        t.mark(BootPhase::SdMount, 400);
+        t.mark(BootPhase::ResumeOpen, 600);
+        t.mark(BootPhase::FirstFrame, 2_500);
+        t
+    }
+
+    #[test]
+    fn phase_budgets_add_up_to_boot_budget() {
+        let sum: u64 = BootPhase::ALL.iter().map(|p| p.budget_ms()).sum();
+        assert_eq!(sum, BOOT_BUDGET_MS);
+    }
+
+    #[test]
+    fn phases_start_where_the_previous_one_ended() {
+        let report = full_boot().report();
+        let mpu = report.phase(BootPhase::Mpu).unwrap();
+        assert_eq!((mpu.start_ms, mpu.end_ms), (0, 2));
+        let sd = report.phase(BootPhase::SdMount).unwrap();
+        assert_eq!((sd.start_ms, sd.duration_ms()), (200, 200));
+        assert_eq!(report.total_ms, 2_500);
+        assert!(report.complete);
+        assert!(report.within_budget());
+    }
+
+    #[test]
+    fn first_mark_wins() {
+        let mut t = BootTimeline::new();
+        assert!(t.mark(BootPhase::Mpu, 3));
+        assert!(!t.mark(BootPhase::Mpu, 9));
+        assert_eq!(t.end_ms(BootPhase::Mpu), Some(3));
+    }
+
+    #[test]
+    fn unmarked_phase_folds_into_the_next() {
+        let mut t = BootTimeline::new();
+        t.mark(BootPhase::Mpu, 1);
+        t.mark(BootPhase::Clocks, 40);
+        t.mark(BootPhase::DisplayInit, 150);
+        t.mark(BootPhase::FirstFrame, 2_000);
+        let report = t.report();
+        assert_eq!(report.phase(BootPhase::SdMount), None);
+        assert_eq!(report.phases().count(), 4);
+        let first = report.phase(BootPhase::FirstFrame).unwrap();
+        assert_eq!(first.start_ms, 150);
+        assert_eq!(first.duration_ms(), 1_850);
+    }
+
+    #[test]
+    fn slow_phase_fails_budget() {
+        let mut t = BootTimeline::new();
+        t.mark(BootPhase::Mpu, 1);
+        t.mark(BootPhase::DisplayInit, 900);
+        t.mark(BootPhase::FirstFrame, 2_000);
+        let report = t.report();
+        let over: Vec<_> = report.over_budget().map(|p| p.phase).collect();
+        assert_eq!(over, [BootPhase::DisplayInit]);
+        assert!(!report.within_budget());
+    }
+
+    #[test]
+    fn no_first_frame_is_not_within_budget() {
+        let mut t = BootTimeline::new();
+        t.mark(BootPhase::Mpu, 1);
+        let report = t.report();
+        assert!(!report.complete);
+        assert!(!report.within_budget());
+    }
+
+    #[test]
+    fn report_formats_one_line_per_phase() {
+        let report = full_boot().report();
+        let mut log = heapless::String::<512>::new();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert!(log.starts_with("boot mpu: 2 ms (budget 10 ms) ok\n"));
+        assert!(log.contains("boot first-frame: 1900 ms (budget 2100 ms) ok\n"));
+        assert!(log.ends_with("boot: first frame at 2500 ms (budget 3000 ms) ok\n"));
+    }
+
+    #[test]
+    fn first_sound_has_its_own_budget() {
+        let mut t = full_boot();
+        assert_eq!(t.report().first_sound_ms, None, "nothing resumed");
+        assert!(t.report().within_budget());
+
+        assert!(t.mark_first_sound(SOUND_BUDGET_MS + 1));
+        assert!(!t.mark_first_sound(700));
+        let report = t.report();
+        assert_eq!(report.first_sound_ms, Some(SOUND_BUDGET_MS + 1));
+        assert!(!report.sound_within_budget());
+        assert!(!report.within_budget());
+        assert_eq!(report.over_budget().count(), 0, "phases are still fine");
+
+        let mut t = full_boot();
+        t.mark_first_sound(720);
+        let mut log = heapless::String::<512>::new();
+        fmt::write(&mut log, format_args!("{}", t.report())).unwrap();
+        assert!(log.contains("boot: first sound at 720 ms (budget 1000 ms) ok\n"));
+        assert!(t.report().within_budget());
+    }
+
+    #[test]
+    fn library_index_starts_first_after_the_first_frame() {
+        let mut lazy = LazyInit::new();
+        for subsystem in Subsystem::ALL {
+            lazy.defer(subsystem);
+        }
+        assert_eq!(lazy.start_next(), None);
+        lazy.first_frame_shown();
+        assert_eq!(lazy.start_next(), Some(Subsystem::LibraryIndex));
+        lazy.complete(Subsystem::LibraryIndex, true);
+        assert_eq!(lazy.start_next(), Some(Subsystem::Bluetooth));
+    }
+
+    #[test]
+    fn lazy_init_waits_for_first_frame() {
+        let mut lazy = LazyInit::new();
+        lazy.defer(Subsystem::Stats);
+        lazy.defer(Subsystem::Bluetooth);
+        assert_eq!(lazy.start_next(), None);
+        assert_eq!(lazy.state(Subsystem::Stats), Some(InitState::Pending));
+
+        lazy.first_frame_shown();
+        assert_eq!(lazy.start_next(), Some(Subsystem::Bluetooth));
+        // One at a time.
+        assert_eq!(lazy.start_next(), None);
+        lazy.complete(Subsystem::Bluetooth, false);
+        assert_eq!(lazy.state(Subsystem::Bluetooth), Some(InitState::Failed));
+        assert!(!lazy.is_done());
+
+        assert_eq!(lazy.start_next(), Some(Subsystem::Stats));
+        lazy.complete(Subsystem::Stats, true);
+        assert_eq!(lazy.state(Subsystem::Stats), Some(InitState::Ready));
+        assert_eq!(lazy.start_next(), None);
+        assert!(lazy.is_done());
+    }
+
+    #[test]
+    fn lazy_init_ignores_undeferred_and_repeated_calls() {
+        let mut lazy = LazyInit::new();
+        lazy.defer(Subsystem::Stats);
+        lazy.first_frame_shown();
+        assert_eq!(lazy.start_next(), Some(Subsystem::Stats));
+        lazy.defer(Subsystem::Stats);
+        assert_eq!(lazy.state(Subsystem::Stats), Some(InitState::Running));
+        lazy.complete(Subsystem::Bluetooth, true);
+        assert_eq!(lazy.state(Subsystem::Bluetooth), None);
+        lazy.complete(Subsystem::Stats, true);
+        lazy.complete(Subsystem::Stats, false);
+        assert_eq!(lazy.state(Subsystem::Stats), Some(InitState::Ready));
+    }
+}
@@ -115,6 +115,140 @@ where
     Ok(())
 }
 
+/// REG0B charge status field (CHRG_STAT[1:0]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChargeStatus {
+    /// Not charging (no input, disabled, or battery absent).
+    NotCharging,
+    /// Pre-charge (battery below BATLOWV).
+    PreCharge,
+    /// Fast charging (constant current or constant voltage).
+    FastCharging,
+    /// Charge terminated.
+    Done,
+}
+
+impl ChargeStatus {
+    /// Decode CHRG_STAT from a raw REG0B value.
+    #[must_use]
+    #[allow(clippy::arithmetic_side_effects)]
+    pub const fn from_status_reg(reg0b: u8) -> Self {
+        match (reg0b & STATUS_CHRG_MASK) >> 3 {
+            0b00 => Self::NotCharging,
+            0b01 => Self::PreCharge,
+            0b10 => Self::FastCharging,
+            _ => Self::Done,
+        }
+    }
+
+    /// CHRG_STAT field bits, positioned for REG0B.
+    #[must_use]
+    #[allow(clippy::arithmetic_side_effects)]
+    pub const fn status_bits(self) -> u8 {
+        let field = match self {
+            Self::NotCharging => 0b00,
+            Self::PreCharge => 0b01,
+            Self::FastCharging => 0b10,
+            Self::Done => 0b11,
+        };
+        field << 3
+    }
+}
+
+/// Decode REG12 raw ADC byte to charge current in milliamps.
+///
+/// Formula from SLUSCD3B: I_CHG = ICHGR[6:0] × 50 mA.
+#[inline]
+#[must_use]
+#[allow(clippy::arithmetic_side_effects)]
+pub const fn decode_charge_current_ma(raw_adc: u8) -> u32 {
+    (raw_adc as u32 & 0x7F) * 50
+}
+
+/// One snapshot of the status and ADC registers.
+///
+/// [`PowerMonitor`](crate::PowerMonitor) takes `&self`, so the power task
+/// polls [`read_status`] and serves the UI from the latest snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Bq25895Status {
+    /// Raw REG0B (VBUS_STAT, CHRG_STAT, PG_STAT).
+    pub status: u8,
+    /// Battery voltage in millivolts.
+    pub battery_mv: u32,
+    /// VBUS voltage in millivolts.
+    pub vbus_mv: u32,
+    /// Charge current in milliamps.
+    pub charge_current_ma: u32,
+}
+
+impl Bq25895Status {
+    /// Charging phase.
+    #[must_use]
+    pub const fn charge_status(&self) -> ChargeStatus {
+        ChargeStatus::from_status_reg(self.status)
+    }
+
+    /// VBUS_STAT field (compare with the `VBUS_STAT_*` constants).
+    #[must_use]
+    pub const fn vbus_status(&self) -> u8 {
+        self.status & STATUS_VBUS_MASK
+    }
+
+    /// Input power is good.
+    #[must_use]
+    pub const fn power_good(&self) -> bool {
+        self.status & STATUS_PG_MASK != 0
+    }
+}
+
+impl crate::PowerMonitor for Bq25895Status {
+    fn battery_voltage(&self) -> Option<u16> {
+        u16::try_from(self.battery_mv).ok()
+    }
+
+    fn battery_percentage(&self) -> Option<u8> {
+        self.battery_voltage()
+            .map(crate::power::lipo_percent_from_mv)
+    }
+
+    fn is_charging(&self) -> bool {
+        matches!(
+            self.charge_status(),
+            ChargeStatus::PreCharge | ChargeStatus::FastCharging
+        )
+    }
+
+    fn is_usb_connected(&self) -> bool {
+        self.power_good() && !matches!(self.vbus_status(), VBUS_STAT_NO_INPUT | VBUS_STAT_OTG)
+    }
+}
+
+/// Read REG0B and the battery, VBUS and charge-current ADC registers.
+/// # Errors
+/// Returns Err if any I2C transfer fails.
+pub fn read_status<I>(i2c: &mut I, addr: u8) -> Result<Bq25895Status, I::Error>
+where
+    I: embedded_hal::i2c::I2c,
+{
+    let mut status = [0u8];
+    i2c.write_read(addr, &[REG0B_STATUS], &mut status)?;
+    let mut batv = [0u8];
+    i2c.write_read(addr, &[REG0E_BATTERY_VOLTAGE], &mut batv)?;
+    let mut adc = [0u8; 2];
+    i2c.write_read(addr, &[REG11_VBUS_VOLTAGE], &mut adc)?;
+    let [status] = status;
+    let [batv] = batv;
+    let [vbusv, ichgr] = adc;
+    Ok(Bq25895Status {
+        status,
+        battery_mv: decode_battery_voltage_mv(batv),
+        vbus_mv: decode_vbus_voltage_mv(vbusv),
+        charge_current_ma: decode_charge_current_ma(ichgr),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -257,4 +391,40 @@ mod tests {
         bq25895_init(&mut mock, BQ25895_I2C_ADDR).unwrap();
         for (addr, _) in &mock.writes { assert_eq!(*addr, BQ25895_I2C_ADDR); }
     }
+
+    #[test]
+    fn charge_status_bits_round_trip() {
+        for st in [ChargeStatus::NotCharging, ChargeStatus::PreCharge, ChargeStatus::FastCharging, ChargeStatus::Done] {
+            assert_eq!(ChargeStatus::from_status_reg(st.status_bits() | STATUS_PG_MASK), st);
+        }
+    }
+    #[allow(clippy::unwrap_used)] #[allow(clippy::indexing_slicing)] #[test]
+    fn read_status_decodes_registers_into_power_monitor() {
+        use crate::PowerMonitor;
+        struct RegI2c { regs: [u8; 0x15] }
+        impl embedded_hal::i2c::ErrorType for RegI2c { type Error = core::convert::Infallible; }
+        impl embedded_hal::i2c::I2c for RegI2c {
+            fn transaction(&mut self, _address: u8, operations: &mut [embedded_hal::i2c::Operation<'_>]) -> Result<(), Self::Error> {
+                let mut reg = 0usize;
+                for op in operations.iter_mut() {
+                    match op {
+                        embedded_hal::i2c::Operation::Write(data) => reg = usize::from(data.first().copied().unwrap_or(0)),
+                        embedded_hal::i2c::Operation::Read(buf) => {
+                            for (b, r) in buf.iter_mut().zip(self.regs.iter().skip(reg)) { *b = *r; }
+                        }
+                    }
+                }
+                Ok(()) } }
+        let mut regs = [0u8; 0x15];
+        regs[usize::from(REG0B_STATUS)] = VBUS_STAT_DCP | ChargeStatus::FastCharging.status_bits() | STATUS_PG_MASK;
+        regs[usize::from(REG0E_BATTERY_VOLTAGE)] = 75; // 3804 mV
+        regs[usize::from(REG11_VBUS_VOLTAGE)] = 0x80 | 0x18; // VBUS_GD, 5000 mV
+        regs[usize::from(REG12_CHARGE_CURRENT_ADC)] = 20; // 1000 mA
+        let mut i2c = RegI2c { regs };
+        let st = read_status(&mut i2c, BQ25895_I2C_ADDR).unwrap();
+        assert_eq!((st.battery_mv, st.vbus_mv, st.charge_current_ma), (3804, 5000, 1000));
+        assert!(st.is_charging() && st.is_usb_connected());
+        assert_eq!(st.battery_percentage(), Some(50));
+        assert_eq!(st.battery_level(), Some(crate::BatteryLevel::Normal));
+    }
 }
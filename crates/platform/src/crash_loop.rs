@@ -0,0 +1,337 @@
+//! Crash-loop detection and safe mode.
+//!
+//! A bad SD card, a corrupt `soul.toml` or a Bluetooth module that hangs
+//! on start-up can fault the device on every boot.  The watchdog resets
+//! it, it faults again, and the user sees nothing but the splash screen.
+//! This module notices the loop and boots the next attempt in
+//! [`BootMode::Safe`]: only the library index is started, `soul.toml`
+//! settings are ignored (default theme, no library scan) and a prompt
+//! points the user at the diagnostics screen.
+//!
+//! The count lives in RTC backup register [`RECORD_REGISTER`], which
+//! survives resets:
+//!
+//! - [`begin_boot`] runs early in `main`.  The previous boot failed if the
+//!   reset came from a watchdog, or if it never reached [`mark_stable`] and
+//!   the reset was a software or unrecognised one (a panic or HardFault
+//!   shows up as one of these).  Losing power early in a boot — a battery
+//!   pull, a power-off, a brownout — or pressing the reset button is not a
+//!   failure and clears the count.  [`SAFE_MODE_AFTER`] failures in a row
+//!   select safe mode.
+//! - [`mark_stable`] runs once the device has been up for
+//!   [`STABLE_AFTER_MS`] and clears the count.
+//!
+//! A safe-mode boot that stays up clears the count too, so the next boot
+//! tries the full set again: pulling the bad card or fixing the setting is
+//! enough to get out.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let record = crash_loop::begin_boot(&mut rtc, firmware::boot::take_reset_cause());
+//! if record.mode.starts(Subsystem::Bluetooth) { /* ... */ }
+//! // ... later, STABLE_AFTER_MS after reset:
+//! crash_loop::mark_stable(&mut rtc)?;
+//! ```
+
+use core::fmt;
+
+use crate::boot_timing::Subsystem;
+use crate::rtc::Rtc;
+use crate::soul_config::SoulConfig;
+
+/// Backup register holding the boot record.
+pub const RECORD_REGISTER: usize = 0;
+
+/// Failed boots in a row that select [`BootMode::Safe`].
+pub const SAFE_MODE_AFTER: u8 = 3;
+
+/// Uptime after which a boot counts as stable (ms).  Well past the last
+/// deferred subsystem and several watchdog periods.
+pub const STABLE_AFTER_MS: u64 = 30_000;
+
+/// Upper half of a valid record; anything else (e.g. zero after the
+/// backup domain lost power) reads as no failures.
+const RECORD_MAGIC: u32 = 0xB007_0000;
+const MAGIC_MASK: u32 = 0xFFFF_0000;
+/// Set by [`begin_boot`], cleared by [`mark_stable`].
+const IN_PROGRESS: u32 = 0x0000_8000;
+const COUNT_MASK: u32 = 0x0000_00FF;
+
+/// What reset the MCU, from the reset controller's flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ResetCause {
+    /// Power applied.
+    PowerOn,
+    /// Supply dropped below the brown-out threshold.
+    Brownout,
+    /// NRST pin (reset button or debugger).
+    Pin,
+    /// Software reset (`SCB::sys_reset`).
+    Software,
+    /// Independent or window watchdog expired.
+    Watchdog,
+    /// No flag recognised.
+    Unknown,
+}
+
+impl ResetCause {
+    /// Short name used in logs.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::PowerOn => "power-on",
+            Self::Brownout => "brownout",
+            Self::Pin => "pin",
+            Self::Software => "software",
+            Self::Watchdog => "watchdog",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// How much of the device this boot starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BootMode {
+    /// Everything.
+    Normal,
+    /// After [`SAFE_MODE_AFTER`] failed boots: minimal subsystems,
+    /// built-in settings.
+    Safe,
+}
+
+impl BootMode {
+    /// Whether deferred `subsystem` is started.  Safe mode keeps only the
+    /// library index, which browsing and playback need.
+    pub const fn starts(self, subsystem: Subsystem) -> bool {
+        match self {
+            Self::Normal => true,
+            Self::Safe => matches!(subsystem, Subsystem::LibraryIndex),
+        }
+    }
+
+    /// The settings to run with.  Safe mode drops everything `soul.toml`
+    /// set, theme and night mode included, and skips the library scan.
+    pub fn config(self, loaded: SoulConfig) -> SoulConfig {
+        match self {
+            Self::Normal => loaded,
+            Self::Safe => {
+                let mut config = SoulConfig::default();
+                config.developer.skip_library_scan = true;
+                config
+            }
+        }
+    }
+}
+
+/// What [`begin_boot`] found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BootRecord {
+    /// Failed boots in a row before this one.
+    pub failures: u8,
+    /// Why this boot happened.
+    pub reset: ResetCause,
+    /// Mode this boot runs in.
+    pub mode: BootMode,
+}
+
+impl BootRecord {
+    /// The last boot did not fail.
+    pub const fn is_clean(&self) -> bool {
+        self.failures == 0
+    }
+}
+
+impl fmt::Display for BootRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "boot guard: {} failed boots, reset {}",
+            self.failures,
+            self.reset.name()
+        )?;
+        if self.mode == BootMode::Safe {
+            f.write_str(", safe mode")?;
+        }
+        Ok(())
+    }
+}
+
+fn encode(failures: u8, in_progress: bool) -> u32 {
+    let flag = if in_progress { IN_PROGRESS } else { 0 };
+    RECORD_MAGIC | flag | u32::from(failures)
+}
+
+/// Count the previous boot and choose this boot's mode.
+///
+/// The record is written back with this boot marked in progress.  If the
+/// RTC refuses the write the boot goes ahead; only the next one loses
+/// track of it.
+pub fn begin_boot<R: Rtc>(rtc: &mut R, reset: ResetCause) -> BootRecord {
+    let (failures, unfinished) = match rtc.read_backup(RECORD_REGISTER) {
+        Some(value) if value & MAGIC_MASK == RECORD_MAGIC => (
+            u8::try_from(value & COUNT_MASK).unwrap_or(u8::MAX),
+            value & IN_PROGRESS != 0,
+        ),
+        _ => (0, false),
+    };
+    let crashed = match reset {
+        ResetCause::Watchdog => true,
+        ResetCause::Software | ResetCause::Unknown => unfinished,
+        ResetCause::PowerOn | ResetCause::Brownout | ResetCause::Pin => false,
+    };
+    let failures = if crashed {
+        failures.saturating_add(1)
+    } else {
+        0
+    };
+    let _ = rtc.write_backup(RECORD_REGISTER, encode(failures, true));
+    BootRecord {
+        failures,
+        reset,
+        mode: if failures >= SAFE_MODE_AFTER {
+            BootMode::Safe
+        } else {
+            BootMode::Normal
+        },
+    }
+}
+
+/// This boot has been up for [`STABLE_AFTER_MS`]: clear the count.
+pub fn mark_stable<R: Rtc>(rtc: &mut R) -> Result<(), R::Error> {
+    rtc.write_backup(RECORD_REGISTER, encode(0, false))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::rtc::{DateTime, RtcError, BACKUP_REGISTERS};
+
+    /// Backup registers only; the clock stays at the epoch.
+    struct Backup([u32; BACKUP_REGISTERS]);
+
+    impl Rtc for Backup {
+        type Error = RtcError;
+
+        fn now(&self) -> Result<DateTime, RtcError> {
+            Ok(DateTime::UNIX_EPOCH)
+        }
+        fn set_time(&mut self, _time: DateTime) -> Result<(), RtcError> {
+            Ok(())
+        }
+        fn is_time_valid(&self) -> bool {
+            false
+        }
+        fn set_alarm(&mut self, _at: DateTime) -> Result<(), RtcError> {
+            Ok(())
+        }
+        fn cancel_alarm(&mut self) {}
+        fn alarm(&self) -> Option<DateTime> {
+            None
+        }
+        async fn wait_alarm(&mut self) -> Result<(), RtcError> {
+            Ok(())
+        }
+        fn read_backup(&self, index: usize) -> Option<u32> {
+            self.0.get(index).copied()
+        }
+        fn write_backup(&mut self, index: usize, value: u32) -> Result<(), RtcError> {
+            let slot = self
+                .0
+                .get_mut(index)
+                .ok_or(RtcError::InvalidBackupRegister)?;
+            *slot = value;
+            Ok(())
+        }
+    }
+
+    fn rtc() -> Backup {
+        Backup([0; BACKUP_REGISTERS])
+    }
+
+    #[test]
+    fn cold_start_and_stable_boots_stay_normal() {
+        let mut rtc = rtc();
+        let record = begin_boot(&mut rtc, ResetCause::PowerOn);
+        assert!(record.is_clean());
+        assert_eq!(record.mode, BootMode::Normal);
+        mark_stable(&mut rtc).unwrap();
+        assert!(begin_boot(&mut rtc, ResetCause::Software).is_clean());
+    }
+
+    #[test]
+    fn unfinished_boots_and_watchdog_resets_lead_to_safe_mode() {
+        let mut rtc = rtc();
+        begin_boot(&mut rtc, ResetCause::PowerOn);
+        // Panicked before mark_stable and reset itself.
+        assert_eq!(begin_boot(&mut rtc, ResetCause::Software).failures, 1);
+        let record = begin_boot(&mut rtc, ResetCause::Watchdog);
+        assert_eq!((record.failures, record.mode), (2, BootMode::Normal));
+        let record = begin_boot(&mut rtc, ResetCause::Watchdog);
+        assert_eq!((record.failures, record.mode), (3, BootMode::Safe));
+    }
+
+    #[test]
+    fn power_loss_during_boot_clears_the_count() {
+        let mut rtc = rtc();
+        begin_boot(&mut rtc, ResetCause::PowerOn);
+        assert_eq!(begin_boot(&mut rtc, ResetCause::Watchdog).failures, 1);
+        // Battery pulled and brownouts before mark_stable, over and over.
+        for reset in [
+            ResetCause::PowerOn,
+            ResetCause::Brownout,
+            ResetCause::Brownout,
+        ] {
+            let record = begin_boot(&mut rtc, reset);
+            assert!(record.is_clean(), "{reset:?} counted as a failure");
+            assert_eq!(record.mode, BootMode::Normal);
+        }
+        begin_boot(&mut rtc, ResetCause::Unknown);
+        assert_eq!(begin_boot(&mut rtc, ResetCause::Unknown).failures, 2);
+    }
+
+    #[test]
+    fn watchdog_reset_after_a_stable_boot_counts() {
+        let mut rtc = rtc();
+        begin_boot(&mut rtc, ResetCause::PowerOn);
+        mark_stable(&mut rtc).unwrap();
+        assert_eq!(begin_boot(&mut rtc, ResetCause::Watchdog).failures, 1);
+    }
+
+    #[test]
+    fn stable_safe_boot_lets_the_next_boot_try_normally() {
+        let mut rtc = rtc();
+        for _ in 0..4 {
+            begin_boot(&mut rtc, ResetCause::Watchdog);
+        }
+        assert_eq!(
+            begin_boot(&mut rtc, ResetCause::Watchdog).mode,
+            BootMode::Safe
+        );
+        mark_stable(&mut rtc).unwrap();
+        assert_eq!(begin_boot(&mut rtc, ResetCause::Pin).mode, BootMode::Normal);
+    }
+
+    #[test]
+    fn foreign_register_contents_read_as_no_failures() {
+        let mut rtc = rtc();
+        rtc.write_backup(RECORD_REGISTER, 0x1234_8005).unwrap();
+        assert!(begin_boot(&mut rtc, ResetCause::PowerOn).is_clean());
+    }
+
+    #[test]
+    fn safe_mode_starts_only_the_library_and_ignores_soul_toml() {
+        let starts = Subsystem::ALL.map(|s| BootMode::Safe.starts(s));
+        assert_eq!(starts, [true, false, false]);
+        assert!(Subsystem::ALL.iter().all(|&s| BootMode::Normal.starts(s)));
+
+        let loaded = SoulConfig::parse("[ui]\nnight_mode = true\n").config;
+        assert_eq!(BootMode::Normal.config(loaded.clone()), loaded);
+        let safe = BootMode::Safe.config(loaded);
+        assert_eq!(safe.ui.night_mode, None);
+        assert!(safe.developer.skip_library_scan);
+    }
+}
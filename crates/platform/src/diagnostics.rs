@@ -0,0 +1,874 @@
+//! Device self-test for the hidden service screen.
+//!
+//! Factory checks and field debugging both want the same answers: does the
+//! panel draw every pixel and grey level, do its refreshes take as long as
+//! the datasheet says, how much ghosting has built up, does the SD card
+//! answer, and is the battery healthy.  This module holds the pieces the
+//! service screen strings together:
+//!
+//! - [`TestPattern`] draws the checkerboard, gradient and border patterns.
+//! - [`measure_refresh`] times one refresh and compares it with a
+//!   [`RefreshSpec`].
+//! - [`GhostingEstimate`], [`SdHealth`] and [`BatteryHealth`] capture the
+//!   other checks; the audio loopback test lives in
+//!   [`audio_loopback`](crate::audio_loopback), problems in `soul.toml`
+//!   come from [`soul_config`](crate::soul_config), library checksum
+//!   results from [`soul_library`](crate::soul_library), start-up
+//!   timings from [`boot_timing`](crate::boot_timing), failed boots from
+//!   [`crash_loop`](crate::crash_loop), and audio dropout counts from
+//!   [`dropout_journal`](crate::dropout_journal).
+//! - [`DiagnosticsReport`] collects everything and formats the log that is
+//!   appended to [`LOG_PATH`].
+//!
+//! Timestamps are plain milliseconds, as in [`latency`](crate::latency).
+//!
+//! # Example
+//!
+//! ```
+//! use platform::diagnostics::{DiagnosticsReport, GhostingEstimate};
+//!
+//! let mut report = DiagnosticsReport::new();
+//! report.ghosting = Some(GhostingEstimate::new(None, 120));
+//! assert!(report.passed());
+//! ```
+
+use core::fmt;
+
+use embedded_graphics::{
+    pixelcolor::Gray4,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+use crate::audio_loopback::{LoopbackFault, LoopbackReport};
+use crate::boot_timing::BootReport;
+use crate::crash_loop::{BootMode, BootRecord};
+use crate::dropout_journal::DropoutSummary;
+use crate::power::{BatteryLevel, PowerMonitor};
+use crate::refresh_policy::RefreshPolicyConfig;
+use crate::soul_config::{ConfigLoad, CONFIG_PATH};
+use crate::soul_library::LibraryIntegrity;
+use crate::storage::{File, Storage};
+use crate::{DisplayDriver, RefreshMode};
+
+/// Log file the service screen appends each report to.
+pub const LOG_PATH: &str = "/diagnostics.log";
+
+/// Allowed deviation of a measured refresh from its spec time (percent,
+/// either direction).  Too slow points at a cold or worn panel; too fast
+/// usually means BUSY was not honoured and the waveform was cut short.
+pub const TIMING_TOLERANCE_PERCENT: u32 = 25;
+
+/// Ghosting at or above this is reported as a failure (permille); the
+/// refresh policy forces a full refresh at the same level.
+pub const GHOSTING_LIMIT_PERMILLE: u16 = RefreshPolicyConfig::DEFAULT.max_ghosting_permille;
+
+/// Side of one checkerboard square (pixels), matching the 8-pixel partial
+/// window grid.
+pub const CHECKER_CELL: i32 = 8;
+
+/// Inset of the inner border rectangle (pixels).
+pub const BORDER_INSET: u32 = 8;
+
+/// Bytes read from the probe file by [`probe_sd`].
+const SD_PROBE_BYTES: usize = 512;
+
+/// Capacity of the formatted log ([`DiagnosticsReport::write_log`]).
+pub const LOG_CAPACITY: usize = 2048;
+
+/// A full-screen pattern that exercises one aspect of the panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TestPattern {
+    /// Black/white squares: every pixel flips, so stuck or dead pixels and
+    /// source/gate line faults show up.
+    Checkerboard,
+    /// Four vertical bands, black to white: checks every grey level.
+    Gradient,
+    /// One-pixel frame on the panel edge plus an inset frame: checks the
+    /// RAM window and that nothing is cropped.
+    Border,
+}
+
+impl TestPattern {
+    /// Every pattern, in the order the service screen runs them.
+    pub const ALL: [Self; 3] = [Self::Checkerboard, Self::Gradient, Self::Border];
+
+    /// Short name used in the log.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Checkerboard => "checkerboard",
+            Self::Gradient => "gradient",
+            Self::Border => "border",
+        }
+    }
+
+    /// Refresh mode the pattern is timed with.  The three patterns cover
+    /// the three modes: GC16 for the full-panel flip, DU4 for the grey
+    /// levels it can show, DU for the black-only frame.
+    pub const fn refresh_mode(self) -> RefreshMode {
+        match self {
+            Self::Checkerboard => RefreshMode::Full,
+            Self::Gradient => RefreshMode::Partial,
+            Self::Border => RefreshMode::Fast,
+        }
+    }
+
+    /// Draw the pattern over the whole of `display`.
+    pub fn draw<D>(self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Gray4>,
+    {
+        let area = display.bounding_box();
+        match self {
+            Self::Checkerboard => display.draw_iter(area.points().map(|p| {
+                let odd = ((p.x / CHECKER_CELL) ^ (p.y / CHECKER_CELL)) & 1 != 0;
+                Pixel(p, if odd { Gray4::WHITE } else { Gray4::BLACK })
+            })),
+            Self::Gradient => {
+                // Panel grey levels map to Gray4 0x0, 0x5, 0xA, 0xF.
+                const BANDS: [u8; 4] = [0x0, 0x5, 0xA, 0xF];
+                let width = area.size.width;
+                let mut left = 0u32;
+                for (i, luma) in (1u32..).zip(BANDS) {
+                    let right = width.saturating_mul(i) / 4;
+                    Rectangle::new(
+                        Point::new(i32::try_from(left).unwrap_or(0), area.top_left.y),
+                        Size::new(right.saturating_sub(left), area.size.height),
+                    )
+                    .into_styled(PrimitiveStyle::with_fill(Gray4::new(luma)))
+                    .draw(display)?;
+                    left = right;
+                }
+                Ok(())
+            }
+            Self::Border => {
+                area.into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+                    .draw(display)?;
+                let stroke = PrimitiveStyle::with_stroke(Gray4::BLACK, 1);
+                area.into_styled(stroke).draw(display)?;
+                let inset = i32::try_from(BORDER_INSET).unwrap_or(0);
+                Rectangle::new(
+                    Point::new(
+                        area.top_left.x.saturating_add(inset),
+                        area.top_left.y.saturating_add(inset),
+                    ),
+                    Size::new(
+                        area.size
+                            .width
+                            .saturating_sub(BORDER_INSET.saturating_mul(2)),
+                        area.size
+                            .height
+                            .saturating_sub(BORDER_INSET.saturating_mul(2)),
+                    ),
+                )
+                .into_styled(stroke)
+                .draw(display)
+            }
+        }
+    }
+}
+
+/// Nominal refresh times of a panel (ms at room temperature).
+///
+/// The firmware fills this from the `eink-specs` display spec so this crate
+/// does not depend on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RefreshSpec {
+    /// GC16 full refresh.
+    pub full_ms: u32,
+    /// DU4 partial refresh.
+    pub partial_ms: u32,
+    /// DU fast refresh.
+    pub fast_ms: u32,
+}
+
+impl RefreshSpec {
+    /// The 3.97" GDEM0397T81P.
+    pub const GDEM0397T81P: Self = Self {
+        full_ms: 2000,
+        partial_ms: 300,
+        fast_ms: 260,
+    };
+
+    /// Spec time for `mode`.
+    pub const fn for_mode(&self, mode: RefreshMode) -> u32 {
+        match mode {
+            RefreshMode::Full => self.full_ms,
+            RefreshMode::Partial => self.partial_ms,
+            RefreshMode::Fast => self.fast_ms,
+        }
+    }
+}
+
+/// Whether a measured refresh matched its spec time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimingVerdict {
+    /// Within [`TIMING_TOLERANCE_PERCENT`] of spec.
+    Ok,
+    /// Longer than spec plus tolerance.
+    Slow,
+    /// Shorter than spec minus tolerance.
+    Fast,
+}
+
+/// One timed refresh.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RefreshTiming {
+    /// Pattern on screen.
+    pub pattern: TestPattern,
+    /// Mode it was refreshed with.
+    pub mode: RefreshMode,
+    /// Refresh call to BUSY released (ms).
+    pub measured_ms: u32,
+    /// Spec time for `mode` (ms).
+    pub spec_ms: u32,
+}
+
+impl RefreshTiming {
+    /// Compare the measurement with the spec.
+    pub fn verdict(&self) -> TimingVerdict {
+        let slack = self.spec_ms.saturating_mul(TIMING_TOLERANCE_PERCENT) / 100;
+        if self.measured_ms > self.spec_ms.saturating_add(slack) {
+            TimingVerdict::Slow
+        } else if self.measured_ms < self.spec_ms.saturating_sub(slack) {
+            TimingVerdict::Fast
+        } else {
+            TimingVerdict::Ok
+        }
+    }
+
+    /// Within tolerance of spec.
+    pub fn within_spec(&self) -> bool {
+        self.verdict() == TimingVerdict::Ok
+    }
+}
+
+/// Refresh `display` with `pattern`'s mode and time it against `spec`.
+///
+/// The pattern must already be drawn; `now_ms` is read just before the
+/// refresh call and again once [`DisplayDriver::wait_ready`] returns.
+pub async fn measure_refresh<D, N>(
+    display: &mut D,
+    pattern: TestPattern,
+    spec: &RefreshSpec,
+    mut now_ms: N,
+) -> Result<RefreshTiming, D::DriverError>
+where
+    D: DisplayDriver,
+    N: FnMut() -> u64,
+{
+    let mode = pattern.refresh_mode();
+    let start = now_ms();
+    match mode {
+        RefreshMode::Full => display.refresh_full().await?,
+        RefreshMode::Partial => display.refresh_partial().await?,
+        RefreshMode::Fast => display.refresh_fast().await?,
+    }
+    display.wait_ready().await?;
+    let elapsed = now_ms().saturating_sub(start);
+    Ok(RefreshTiming {
+        pattern,
+        mode,
+        measured_ms: u32::try_from(elapsed).unwrap_or(u32::MAX),
+        spec_ms: spec.for_mode(mode),
+    })
+}
+
+/// Accumulated ghosting after the self-test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GhostingEstimate {
+    /// Ghosting (permille).
+    pub permille: u16,
+    /// `true` when measured by the panel model (emulator), `false` when
+    /// taken from the refresh policy's estimate (hardware).
+    pub measured: bool,
+}
+
+impl GhostingEstimate {
+    /// Prefer the measured level from
+    /// [`EinkDisplay::ghosting_level`](crate::EinkDisplay::ghosting_level);
+    /// fall back to
+    /// [`RefreshPolicy::estimated_ghosting_permille`](crate::RefreshPolicy::estimated_ghosting_permille).
+    pub fn new(measured: Option<f32>, estimated_permille: u16) -> Self {
+        let panel = measured.map(crate::refresh_policy::PanelState::with_ghosting);
+        match panel.and_then(|p| p.ghosting_permille) {
+            Some(permille) => Self {
+                permille,
+                measured: true,
+            },
+            None => Self {
+                permille: estimated_permille,
+                measured: false,
+            },
+        }
+    }
+
+    /// Below [`GHOSTING_LIMIT_PERMILLE`].
+    pub const fn within_limit(&self) -> bool {
+        self.permille < GHOSTING_LIMIT_PERMILLE
+    }
+}
+
+/// Result of [`probe_sd`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SdHealth {
+    /// The probe file was read.
+    Ok {
+        /// Bytes read (up to 512).
+        bytes: u32,
+        /// Open plus read time (ms).
+        read_ms: u32,
+    },
+    /// The card answered but the probe file does not exist.
+    Missing,
+    /// A storage call failed: no card, not mounted, or a bad card.
+    Failed,
+}
+
+impl SdHealth {
+    /// The card answered and the probe file was read.
+    pub const fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok { .. })
+    }
+}
+
+/// Check the card by opening `path` and reading its first block.
+///
+/// Use a file every card has, e.g. the library index.
+pub async fn probe_sd<S, N>(storage: &mut S, path: &str, mut now_ms: N) -> SdHealth
+where
+    S: Storage,
+    N: FnMut() -> u64,
+{
+    let start = now_ms();
+    match storage.exists(path).await {
+        Ok(true) => {}
+        Ok(false) => return SdHealth::Missing,
+        Err(_) => return SdHealth::Failed,
+    }
+    let Ok(mut file) = storage.open_file(path).await else {
+        return SdHealth::Failed;
+    };
+    let mut block = [0u8; SD_PROBE_BYTES];
+    let Ok(bytes) = file.read(&mut block).await else {
+        return SdHealth::Failed;
+    };
+    SdHealth::Ok {
+        bytes: u32::try_from(bytes).unwrap_or(u32::MAX),
+        read_ms: u32::try_from(now_ms().saturating_sub(start)).unwrap_or(u32::MAX),
+    }
+}
+
+/// Battery snapshot for the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BatteryHealth {
+    /// Cell voltage (mV).
+    pub voltage_mv: u16,
+    /// State of charge (%), if the monitor reports one.
+    pub percent: Option<u8>,
+    /// Alert level.
+    pub level: BatteryLevel,
+    /// Charger active.
+    pub charging: bool,
+    /// USB power present.
+    pub usb: bool,
+}
+
+impl BatteryHealth {
+    /// Snapshot `monitor`; `None` if it cannot read the cell voltage.
+    pub fn read<P: PowerMonitor>(monitor: &P) -> Option<Self> {
+        let voltage_mv = monitor.battery_voltage()?;
+        Some(Self {
+            voltage_mv,
+            percent: monitor.battery_percentage(),
+            level: BatteryLevel::from_mv(voltage_mv),
+            charging: monitor.is_charging(),
+            usb: monitor.is_usb_connected(),
+        })
+    }
+
+    /// Above [`CRITICAL_BATTERY_MV`](crate::power::CRITICAL_BATTERY_MV).
+    /// A low but not critical cell still passes; it only needs charging.
+    pub fn is_ok(&self) -> bool {
+        self.level != BatteryLevel::Critical
+    }
+}
+
+/// Everything the service screen measured.
+///
+/// Checks that were not run stay empty and do not fail the report.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticsReport {
+    /// One entry per pattern run.
+    pub timings: heapless::Vec<RefreshTiming, 3>,
+    /// Ghosting after the patterns.
+    pub ghosting: Option<GhostingEstimate>,
+    /// SD card probe.
+    pub sd: Option<SdHealth>,
+    /// Battery snapshot.
+    pub battery: Option<BatteryHealth>,
+    /// DAC → ADC loopback levels.
+    pub audio: Option<LoopbackReport>,
+    /// `soul.toml` as read at boot; `None` when the card has none.
+    pub config: Option<ConfigLoad>,
+    /// Library section checksums, as found when the library was opened.
+    pub library: Option<LibraryIntegrity>,
+    /// Phase timings of the current boot.
+    pub boot: Option<BootReport>,
+    /// Failed boots before this one, and whether it is in safe mode.
+    pub boot_guard: Option<BootRecord>,
+    /// Audio underruns/overruns since boot.
+    pub dropouts: Option<DropoutSummary>,
+}
+
+impl DiagnosticsReport {
+    /// Empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a timing, replacing an earlier one for the same pattern.
+    pub fn record_timing(&mut self, timing: RefreshTiming) {
+        if let Some(slot) = self
+            .timings
+            .iter_mut()
+            .find(|t| t.pattern == timing.pattern)
+        {
+            *slot = timing;
+        } else {
+            // Capacity is one per pattern, so a new pattern always fits.
+            let _ = self.timings.push(timing);
+        }
+    }
+
+    /// Every check that ran passed.
+    pub fn passed(&self) -> bool {
+        self.timings.iter().all(RefreshTiming::within_spec)
+            && self.ghosting.iter().all(GhostingEstimate::within_limit)
+            && self.sd.iter().all(SdHealth::is_ok)
+            && self.battery.iter().all(BatteryHealth::is_ok)
+            && self.audio.iter().all(LoopbackReport::passed)
+            && self.config.iter().all(|c| c.error_count() == 0)
+            && self.library.iter().all(LibraryIntegrity::is_intact)
+            && self.boot.iter().all(BootReport::within_budget)
+            && self.boot_guard.iter().all(|g| g.mode == BootMode::Normal)
+            && self.dropouts.iter().all(DropoutSummary::is_clean)
+    }
+
+    /// Append the report to `out` as text, one check per line.
+    ///
+    /// Lines past [`LOG_CAPACITY`] bytes are dropped.
+    pub async fn write_log<W>(&self, out: &mut W) -> Result<(), W::Error>
+    where
+        W: embedded_io_async::Write,
+    {
+        let mut text = heapless::String::<LOG_CAPACITY>::new();
+        let _ = fmt::write(&mut text, format_args!("{self}"));
+        out.write_all(text.as_bytes()).await?;
+        out.flush().await
+    }
+}
+
+fn pass(ok: bool) -> &'static str {
+    if ok {
+        "ok"
+    } else {
+        "FAIL"
+    }
+}
+
+impl fmt::Display for DiagnosticsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for t in &self.timings {
+            let mode = match t.mode {
+                RefreshMode::Full => "full",
+                RefreshMode::Partial => "partial",
+                RefreshMode::Fast => "fast",
+            };
+            let verdict = match t.verdict() {
+                TimingVerdict::Ok => "ok",
+                TimingVerdict::Slow => "FAIL slow",
+                TimingVerdict::Fast => "FAIL fast",
+            };
+            writeln!(
+                f,
+                "refresh {} {mode}: {} ms (spec {} ms) {verdict}",
+                t.pattern.name(),
+                t.measured_ms,
+                t.spec_ms
+            )?;
+        }
+        if let Some(g) = self.ghosting {
+            let source = if g.measured { "measured" } else { "estimated" };
+            writeln!(
+                f,
+                "ghosting: {} permille ({source}) {}",
+                g.permille,
+                pass(g.within_limit())
+            )?;
+        }
+        match self.sd {
+            Some(SdHealth::Ok { bytes, read_ms }) => {
+                writeln!(f, "sd: read {bytes} B in {read_ms} ms ok")?;
+            }
+            Some(SdHealth::Missing) => writeln!(f, "sd: probe file missing FAIL")?,
+            Some(SdHealth::Failed) => writeln!(f, "sd: not responding FAIL")?,
+            None => {}
+        }
+        if let Some(b) = self.battery {
+            write!(f, "battery: {} mV", b.voltage_mv)?;
+            if let Some(pct) = b.percent {
+                write!(f, " {pct}%")?;
+            }
+            if b.charging {
+                f.write_str(" charging")?;
+            }
+            if b.usb {
+                f.write_str(" usb")?;
+            }
+            writeln!(f, " {}", pass(b.is_ok()))?;
+        }
+        if let Some(a) = self.audio {
+            write!(
+                f,
+                "audio: 1 kHz L {} R {} counts",
+                a.left.left, a.right.right
+            )?;
+            if a.passed() {
+                writeln!(f, " ok")?;
+            } else {
+                f.write_str(" FAIL")?;
+                for fault in LoopbackFault::ALL.into_iter().filter(|&x| a.has(x)) {
+                    write!(f, " {}", fault.name())?;
+                }
+                writeln!(f)?;
+            }
+        }
+        if let Some(c) = &self.config {
+            match c.error_count() {
+                0 => writeln!(f, "config: {CONFIG_PATH} ok")?,
+                n => writeln!(f, "config: {CONFIG_PATH} {n} errors FAIL")?,
+            }
+            for e in &c.errors {
+                writeln!(f, "config: {e}")?;
+            }
+        }
+        if let Some(l) = self.library {
+            if l.is_intact() {
+                writeln!(f, "library: checksums ok")?;
+            } else {
+                writeln!(f, "library: {l} FAIL")?;
+            }
+        }
+        if let Some(b) = &self.boot {
+            write!(f, "{b}")?;
+        }
+        if let Some(g) = self.boot_guard {
+            writeln!(f, "{g} {}", pass(g.mode == BootMode::Normal))?;
+        }
+        if let Some(d) = self.dropouts {
+            writeln!(f, "{d} {}", pass(d.is_clean()))?;
+        }
+        writeln!(f, "result: {}", if self.passed() { "PASS" } else { "FAIL" })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::arithmetic_side_effects)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use embedded_graphics::mock_display::MockDisplay;
+    use std::rc::Rc;
+
+    fn timing(mode: RefreshMode, measured_ms: u32) -> RefreshTiming {
+        let pattern = match mode {
+            RefreshMode::Full => TestPattern::Checkerboard,
+            RefreshMode::Partial => TestPattern::Gradient,
+            RefreshMode::Fast => TestPattern::Border,
+        };
+        RefreshTiming {
+            pattern,
+            mode,
+            measured_ms,
+            spec_ms: RefreshSpec::GDEM0397T81P.for_mode(mode),
+        }
+    }
+
+    #[test]
+    fn checkerboard_alternates_every_cell() {
+        let mut display = MockDisplay::<Gray4>::new();
+        TestPattern::Checkerboard.draw(&mut display).unwrap();
+        assert_eq!(display.get_pixel(Point::new(0, 0)), Some(Gray4::BLACK));
+        assert_eq!(display.get_pixel(Point::new(8, 0)), Some(Gray4::WHITE));
+        assert_eq!(display.get_pixel(Point::new(8, 8)), Some(Gray4::BLACK));
+        assert_eq!(display.get_pixel(Point::new(7, 15)), Some(Gray4::WHITE));
+    }
+
+    #[test]
+    fn gradient_covers_four_levels_edge_to_edge() {
+        let mut display = MockDisplay::<Gray4>::new();
+        TestPattern::Gradient.draw(&mut display).unwrap();
+        let row: [u8; 4] =
+            [0, 16, 32, 63].map(|x| display.get_pixel(Point::new(x, 5)).unwrap().luma());
+        assert_eq!(row, [0x0, 0x5, 0xA, 0xF]);
+    }
+
+    #[test]
+    fn border_frames_the_panel_edge_and_inset() {
+        let mut display = MockDisplay::<Gray4>::new();
+        display.set_allow_overdraw(true);
+        TestPattern::Border.draw(&mut display).unwrap();
+        assert_eq!(display.get_pixel(Point::new(0, 30)), Some(Gray4::BLACK));
+        assert_eq!(display.get_pixel(Point::new(63, 63)), Some(Gray4::BLACK));
+        assert_eq!(display.get_pixel(Point::new(8, 30)), Some(Gray4::BLACK));
+        assert_eq!(display.get_pixel(Point::new(4, 30)), Some(Gray4::WHITE));
+        assert_eq!(display.get_pixel(Point::new(30, 30)), Some(Gray4::WHITE));
+    }
+
+    #[test]
+    fn timing_verdict_applies_tolerance_both_ways() {
+        assert_eq!(
+            timing(RefreshMode::Full, 2_400).verdict(),
+            TimingVerdict::Ok
+        );
+        assert_eq!(
+            timing(RefreshMode::Full, 2_600).verdict(),
+            TimingVerdict::Slow
+        );
+        assert_eq!(
+            timing(RefreshMode::Partial, 230).verdict(),
+            TimingVerdict::Ok
+        );
+        assert_eq!(
+            timing(RefreshMode::Partial, 40).verdict(),
+            TimingVerdict::Fast
+        );
+    }
+
+    #[test]
+    fn ghosting_prefers_measured_level() {
+        let g = GhostingEstimate::new(Some(0.05), 280);
+        assert_eq!(
+            g,
+            GhostingEstimate {
+                permille: 50,
+                measured: true
+            }
+        );
+        let g = GhostingEstimate::new(None, 320);
+        assert!(!g.measured);
+        assert!(!g.within_limit());
+    }
+
+    struct Fixed(u16, bool);
+
+    impl PowerMonitor for Fixed {
+        fn battery_voltage(&self) -> Option<u16> {
+            Some(self.0)
+        }
+        fn battery_percentage(&self) -> Option<u8> {
+            Some(crate::power::lipo_percent_from_mv(self.0))
+        }
+        fn is_charging(&self) -> bool {
+            self.1
+        }
+        fn is_usb_connected(&self) -> bool {
+            self.1
+        }
+    }
+
+    #[test]
+    fn battery_fails_only_when_critical() {
+        assert!(BatteryHealth::read(&Fixed(3_450, false)).unwrap().is_ok());
+        assert!(!BatteryHealth::read(&Fixed(3_300, false)).unwrap().is_ok());
+    }
+
+    #[test]
+    fn report_log_lists_each_check_and_result() {
+        let mut report = DiagnosticsReport::new();
+        report.record_timing(timing(RefreshMode::Full, 1_000));
+        report.record_timing(timing(RefreshMode::Full, 2_050));
+        report.ghosting = Some(GhostingEstimate::new(None, 16));
+        report.sd = Some(SdHealth::Ok {
+            bytes: 512,
+            read_ms: 4,
+        });
+        report.battery = BatteryHealth::read(&Fixed(3_900, true));
+        assert_eq!(report.timings.len(), 1);
+        assert!(report.passed());
+
+        let mut log = heapless::String::<LOG_CAPACITY>::new();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert_eq!(
+            log.as_str(),
+            "refresh checkerboard full: 2050 ms (spec 2000 ms) ok\n\
+             ghosting: 16 permille (estimated) ok\n\
+             sd: read 512 B in 4 ms ok\n\
+             battery: 3900 mV 65% charging usb ok\n\
+             result: PASS\n"
+        );
+
+        report.sd = Some(SdHealth::Failed);
+        assert!(!report.passed());
+
+        report.sd = None;
+        let mut audio = LoopbackReport::default();
+        audio.left.left = 2_000;
+        audio.right.right = 2_000;
+        audio.low = audio.left;
+        audio.low.right = 2_000;
+        audio.high = audio.low;
+        audio.muted.left = 900;
+        report.audio = Some(audio);
+        log.clear();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert!(log.contains("audio: 1 kHz L 2000 R 2000 counts FAIL mute leak\n"));
+        assert!(log.ends_with("result: FAIL\n"));
+
+        report.audio = None;
+        report.config = Some(crate::soul_config::SoulConfig::parse(
+            "[ui]\nfull_refresh_every = 0\n",
+        ));
+        log.clear();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert!(log.contains(
+            "config: /soul.toml 1 errors FAIL\n\
+             config: line 2: ui.full_refresh_every: must be 1-1000\n"
+        ));
+        assert!(!report.passed());
+
+        report.config = None;
+        let mut library = LibraryIntegrity::new();
+        library.mark_corrupt(crate::soul_library::LibrarySection::Browse);
+        report.library = Some(library);
+        log.clear();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert!(log.contains("library: library.browse corrupt FAIL\n"));
+        assert!(!report.passed());
+
+        report.library = Some(LibraryIntegrity::new());
+        log.clear();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert!(log.contains("library: checksums ok\n"));
+        assert!(report.passed());
+
+        report.library = None;
+        let mut boot = crate::boot_timing::BootTimeline::new();
+        boot.mark(crate::boot_timing::BootPhase::Mpu, 2);
+        boot.mark(crate::boot_timing::BootPhase::FirstFrame, 3_400);
+        report.boot = Some(boot.report());
+        log.clear();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert!(log.contains("boot: first frame at 3400 ms (budget 3000 ms) FAIL\n"));
+        assert!(!report.passed());
+
+        report.boot = None;
+        report.boot_guard = Some(BootRecord {
+            failures: 3,
+            reset: crate::crash_loop::ResetCause::Watchdog,
+            mode: BootMode::Safe,
+        });
+        log.clear();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert!(log.contains("boot guard: 3 failed boots, reset watchdog, safe mode FAIL\n"));
+        assert!(!report.passed());
+
+        report.boot_guard = None;
+        report.dropouts = Some(DropoutSummary::default());
+        log.clear();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert!(log.contains("dropouts: 0 underrun 0 overrun ok\n"));
+        assert!(report.passed());
+
+        report.dropouts = Some(DropoutSummary {
+            underruns: 2,
+            during_refresh: 2,
+            ..DropoutSummary::default()
+        });
+        log.clear();
+        fmt::write(&mut log, format_args!("{report}")).unwrap();
+        assert!(log.contains("dropouts: 2 underrun 0 overrun (refresh 2 sd 0 cpu 0) FAIL\n"));
+        assert!(!report.passed());
+    }
+
+    /// Advances a shared clock by a fixed time per refresh.
+    struct Timed {
+        clock: Rc<Cell<u64>>,
+    }
+
+    impl OriginDimensions for Timed {
+        fn size(&self) -> Size {
+            Size::new(16, 16)
+        }
+    }
+
+    impl DrawTarget for Timed {
+        type Color = Gray4;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, _pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            Ok(())
+        }
+    }
+
+    impl DisplayDriver for Timed {
+        type DriverError = Infallible;
+
+        fn spec(&self) -> crate::DisplayInfo {
+            crate::DisplayInfo::new(16, 16)
+        }
+
+        async fn update_buffer(&mut self, _framebuffer: &[u8]) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+
+        async fn refresh_full(&mut self) -> Result<(), Self::DriverError> {
+            self.clock.set(self.clock.get() + 2_100);
+            Ok(())
+        }
+
+        async fn refresh_partial(&mut self) -> Result<(), Self::DriverError> {
+            self.clock.set(self.clock.get() + 310);
+            Ok(())
+        }
+
+        async fn sleep(&mut self) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+
+        async fn wake(&mut self) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn measure_refresh_uses_pattern_mode_and_spec() {
+        let clock = Rc::new(Cell::new(500));
+        let mut display = Timed {
+            clock: Rc::clone(&clock),
+        };
+        let spec = RefreshSpec::GDEM0397T81P;
+        let t = measure_refresh(&mut display, TestPattern::Checkerboard, &spec, || {
+            clock.get()
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            (t.mode, t.measured_ms, t.spec_ms),
+            (RefreshMode::Full, 2_100, 2_000)
+        );
+        assert!(t.within_spec());
+    }
+}
@@ -2,10 +2,26 @@
 
 use embedded_graphics::prelude::*;
 
-/// Compact description of a display panel.
+/// Colour technology of a panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ColorMode {
+    /// Black, white and (optionally) greys.
+    #[default]
+    Grayscale,
+    /// Spectra 6 (black, white, red, yellow, blue, green).
+    Spectra6,
+    /// Kaleido 3 (colour filter over a greyscale panel).
+    Kaleido3,
+}
+
+/// Compact description of a display panel and what the driver can do with it.
 ///
 /// Returned by [`DisplayDriver::spec`] so application code can query display
-/// dimensions without depending on the `eink-specs` crate.
+/// dimensions and capabilities without depending on the `eink-specs` crate.
+/// UI code uses the capabilities to adapt one set of screens to different
+/// panel variants, e.g. dithering mid-greys on a 1bpp panel or skipping
+/// partial refreshes on a panel without them.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct DisplayInfo {
@@ -13,6 +29,46 @@ pub struct DisplayInfo {
     pub width: u32,
     /// Panel height in pixels.
     pub height: u32,
+    /// Distinct grey levels the driver can show (2 = black and white only).
+    pub grayscale_levels: u8,
+    /// Partial refresh is faster than a full refresh on this panel.
+    pub supports_partial: bool,
+    /// Partial-refresh window granularity in x: window x and width are
+    /// rounded out to multiples of this.
+    pub min_partial_width: u32,
+    /// Partial-refresh window granularity in y.
+    pub min_partial_height: u32,
+    /// Colour technology.
+    pub color_mode: ColorMode,
+}
+
+impl DisplayInfo {
+    /// A black-and-white `width` × `height` panel with byte-aligned partial
+    /// refresh — the capabilities of a plain 1bpp controller.
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            grayscale_levels: 2,
+            supports_partial: true,
+            min_partial_width: 8,
+            min_partial_height: 1,
+            color_mode: ColorMode::Grayscale,
+        }
+    }
+
+    /// Set the number of grey levels.
+    #[must_use]
+    pub const fn with_grayscale_levels(mut self, levels: u8) -> Self {
+        self.grayscale_levels = levels;
+        self
+    }
+
+    /// `true` if only black and white can be shown, so greys must be
+    /// dithered.
+    pub const fn is_monochrome(&self) -> bool {
+        self.grayscale_levels <= 2 && matches!(self.color_mode, ColorMode::Grayscale)
+    }
 }
 
 /// Unified display driver trait for e-ink panels.
@@ -83,6 +139,18 @@ pub trait EinkDisplay: DisplayDriver {
     /// Returns `None` if the sensor is unavailable.
     fn temperature(&self) -> Option<i8>;
 
+    /// Swap black and white on the panel (night mode).
+    ///
+    /// Drivers use the controller's RAM-inversion option where it has one
+    /// and invert the framebuffer otherwise; either way callers keep
+    /// drawing the normal image.  Takes effect at
+    /// the next refresh, which the driver runs as a full refresh because
+    /// every pixel flips.
+    fn set_inverted(&mut self, inverted: bool);
+
+    /// Whether night mode is on.
+    fn is_inverted(&self) -> bool;
+
     /// Get the current ghosting level as a value in `[0.0, 1.0]`.
     ///
     /// Only meaningful on the emulator; hardware implementations return `None`.
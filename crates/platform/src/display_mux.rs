@@ -0,0 +1,302 @@
+//! Display multiplexer — drive two panels through one [`DisplayDriver`].
+//!
+//! Dual-display concepts (main e-ink panel plus a small secondary status
+//! display) should not force every UI screen to be generic over two
+//! drivers.  [`DisplayMux`] owns both drivers and forwards drawing and
+//! refresh calls to whichever panel is currently selected, so existing
+//! screen code keeps rendering into a single `DisplayDriver`.
+//!
+//! ```text
+//! UI screen ──draw/refresh──▶ DisplayMux ──▶ primary   (selected)
+//!                                        └─▶ secondary
+//! ```
+//!
+//! Both drivers must share a colour type.  Errors from either side are
+//! surfaced as [`MuxError`] so the caller can tell which panel failed.
+
+use embedded_graphics::prelude::*;
+
+use crate::display::{DisplayDriver, DisplayInfo};
+
+/// Identifies one of the two panels behind a [`DisplayMux`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DisplayId {
+    /// The main display.
+    Primary,
+    /// The secondary (status) display.
+    Secondary,
+}
+
+/// Error from one side of a [`DisplayMux`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MuxError<P, S> {
+    /// The primary display failed.
+    Primary(P),
+    /// The secondary display failed.
+    Secondary(S),
+}
+
+/// Routes [`DisplayDriver`] and [`DrawTarget`] calls to one of two panels.
+pub struct DisplayMux<P, S> {
+    primary: P,
+    secondary: S,
+    active: DisplayId,
+}
+
+impl<P, S> DisplayMux<P, S>
+where
+    P: DisplayDriver,
+    S: DisplayDriver + DrawTarget<Color = P::Color>,
+{
+    /// Create a mux with the primary panel selected.
+    pub fn new(primary: P, secondary: S) -> Self {
+        Self {
+            primary,
+            secondary,
+            active: DisplayId::Primary,
+        }
+    }
+
+    /// Select the panel that subsequent calls are routed to.
+    pub fn select(&mut self, id: DisplayId) {
+        self.active = id;
+    }
+
+    /// The currently selected panel.
+    pub fn active(&self) -> DisplayId {
+        self.active
+    }
+
+    /// Borrow the primary driver directly.
+    pub fn primary_mut(&mut self) -> &mut P {
+        &mut self.primary
+    }
+
+    /// Borrow the secondary driver directly.
+    pub fn secondary_mut(&mut self) -> &mut S {
+        &mut self.secondary
+    }
+
+    /// Return both drivers.
+    pub fn into_inner(self) -> (P, S) {
+        (self.primary, self.secondary)
+    }
+
+    /// Put both panels into deep sleep, primary first.
+    pub async fn sleep_all(&mut self) -> Result<(), MuxError<P::DriverError, S::DriverError>> {
+        self.primary.sleep().await.map_err(MuxError::Primary)?;
+        self.secondary.sleep().await.map_err(MuxError::Secondary)
+    }
+
+    /// Wake both panels, primary first.
+    pub async fn wake_all(&mut self) -> Result<(), MuxError<P::DriverError, S::DriverError>> {
+        self.primary.wake().await.map_err(MuxError::Primary)?;
+        self.secondary.wake().await.map_err(MuxError::Secondary)
+    }
+}
+
+impl<P, S> OriginDimensions for DisplayMux<P, S>
+where
+    P: DisplayDriver,
+    S: DisplayDriver + DrawTarget<Color = P::Color>,
+{
+    fn size(&self) -> Size {
+        match self.active {
+            DisplayId::Primary => self.primary.dimensions(),
+            DisplayId::Secondary => self.secondary.dimensions(),
+        }
+    }
+}
+
+impl<P, S> DrawTarget for DisplayMux<P, S>
+where
+    P: DisplayDriver,
+    S: DisplayDriver + DrawTarget<Color = P::Color>,
+{
+    type Color = P::Color;
+    type Error = MuxError<P::Error, S::Error>;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        match self.active {
+            DisplayId::Primary => self.primary.draw_iter(pixels).map_err(MuxError::Primary),
+            DisplayId::Secondary => self.secondary.draw_iter(pixels).map_err(MuxError::Secondary),
+        }
+    }
+}
+
+/// Forwards a `DisplayDriver` async method to the selected panel.
+macro_rules! route {
+    ($self:ident, $method:ident ( $($arg:expr),* )) => {
+        match $self.active {
+            DisplayId::Primary => $self.primary.$method($($arg),*).await.map_err(MuxError::Primary),
+            DisplayId::Secondary => {
+                $self.secondary.$method($($arg),*).await.map_err(MuxError::Secondary)
+            }
+        }
+    };
+}
+
+impl<P, S> DisplayDriver for DisplayMux<P, S>
+where
+    P: DisplayDriver,
+    S: DisplayDriver + DrawTarget<Color = P::Color>,
+{
+    type DriverError = MuxError<P::DriverError, S::DriverError>;
+
+    fn spec(&self) -> DisplayInfo {
+        match self.active {
+            DisplayId::Primary => self.primary.spec(),
+            DisplayId::Secondary => self.secondary.spec(),
+        }
+    }
+
+    async fn update_buffer(&mut self, framebuffer: &[u8]) -> Result<(), Self::DriverError> {
+        route!(self, update_buffer(framebuffer))
+    }
+
+    async fn refresh_full(&mut self) -> Result<(), Self::DriverError> {
+        route!(self, refresh_full())
+    }
+
+    async fn refresh_partial(&mut self) -> Result<(), Self::DriverError> {
+        route!(self, refresh_partial())
+    }
+
+    async fn refresh_fast(&mut self) -> Result<(), Self::DriverError> {
+        route!(self, refresh_fast())
+    }
+
+    async fn sleep(&mut self) -> Result<(), Self::DriverError> {
+        route!(self, sleep())
+    }
+
+    async fn wake(&mut self) -> Result<(), Self::DriverError> {
+        route!(self, wake())
+    }
+
+    async fn wait_ready(&mut self) -> Result<(), Self::DriverError> {
+        route!(self, wait_ready())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_graphics::pixelcolor::Gray4;
+
+    /// Minimal driver that counts pixels and refreshes.
+    struct CountingDisplay {
+        width: u32,
+        pixels: u32,
+        full_refreshes: u32,
+        asleep: bool,
+    }
+
+    impl CountingDisplay {
+        fn new(width: u32) -> Self {
+            Self { width, pixels: 0, full_refreshes: 0, asleep: false }
+        }
+    }
+
+    impl OriginDimensions for CountingDisplay {
+        fn size(&self) -> Size {
+            Size::new(self.width, 10)
+        }
+    }
+
+    impl DrawTarget for CountingDisplay {
+        type Color = Gray4;
+        type Error = Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for _ in pixels {
+                self.pixels = self.pixels.saturating_add(1);
+            }
+            Ok(())
+        }
+    }
+
+    impl DisplayDriver for CountingDisplay {
+        type DriverError = Infallible;
+
+        fn spec(&self) -> DisplayInfo {
+            DisplayInfo::new(self.width, 10)
+        }
+
+        async fn update_buffer(&mut self, _framebuffer: &[u8]) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+
+        async fn refresh_full(&mut self) -> Result<(), Self::DriverError> {
+            self.full_refreshes = self.full_refreshes.saturating_add(1);
+            Ok(())
+        }
+
+        async fn refresh_partial(&mut self) -> Result<(), Self::DriverError> {
+            Ok(())
+        }
+
+        async fn sleep(&mut self) -> Result<(), Self::DriverError> {
+            self.asleep = true;
+            Ok(())
+        }
+
+        async fn wake(&mut self) -> Result<(), Self::DriverError> {
+            self.asleep = false;
+            Ok(())
+        }
+    }
+
+    fn mux() -> DisplayMux<CountingDisplay, CountingDisplay> {
+        DisplayMux::new(CountingDisplay::new(100), CountingDisplay::new(40))
+    }
+
+    #[test]
+    fn mux_starts_on_primary() {
+        let m = mux();
+        assert_eq!(m.active(), DisplayId::Primary);
+        assert_eq!(m.spec().width, 100);
+    }
+
+    #[test]
+    fn draw_routes_to_selected_panel() {
+        let mut m = mux();
+        m.select(DisplayId::Secondary);
+        Pixel(Point::zero(), Gray4::BLACK).draw(&mut m).unwrap();
+        assert_eq!(m.size().width, 40);
+        let (primary, secondary) = m.into_inner();
+        assert_eq!(primary.pixels, 0);
+        assert_eq!(secondary.pixels, 1);
+    }
+
+    #[tokio::test]
+    async fn refresh_routes_to_selected_panel() {
+        let mut m = mux();
+        m.refresh_full().await.unwrap();
+        m.select(DisplayId::Secondary);
+        m.refresh_full().await.unwrap();
+        m.refresh_full().await.unwrap();
+        assert_eq!(m.primary_mut().full_refreshes, 1);
+        assert_eq!(m.secondary_mut().full_refreshes, 2);
+    }
+
+    #[tokio::test]
+    async fn sleep_all_reaches_both_panels() {
+        let mut m = mux();
+        m.sleep_all().await.unwrap();
+        assert!(m.primary_mut().asleep);
+        assert!(m.secondary_mut().asleep);
+        m.wake_all().await.unwrap();
+        assert!(!m.secondary_mut().asleep);
+    }
+}
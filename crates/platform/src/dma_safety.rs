@@ -38,6 +38,36 @@
 //! #[link_section = ".sram4"]
 //! static mut SAI4_BUFFER: [u8; 256] = [0u8; 256];
 //! ```
+//!
+//! ## Buffer pool
+//!
+//! Subsystems that need a DMA buffer only for the length of a transfer (SD
+//! block reads, SAI refills, display SPI bursts) borrow one from a shared
+//! [`DmaPool`] instead of each declaring its own worst-case static.  The pool
+//! is typed by its [`PoolRegion`], and so is every [`DmaBlock`] it hands out,
+//! so a DMA1/2 driver that requires `DmaBlock<R: DmaAccessible, _>` still
+//! rejects an SRAM4 block at compile time.
+//!
+//! ```rust
+//! use platform::dma_safety::{AxiSramRegion, DmaBlock, DmaAccessible, DmaPool};
+//!
+//! #[link_section = ".axisram"]
+//! static SD_POOL: DmaPool<AxiSramRegion, 512, 4> = DmaPool::new();
+//!
+//! fn start_read<R: DmaAccessible>(buf: &mut DmaBlock<'_, R, 512>) {
+//!     buf.fill(0);
+//! }
+//!
+//! let mut block = SD_POOL.try_alloc().expect("pool exhausted");
+//! start_read(&mut block);
+//! drop(block); // back in the pool
+//! assert_eq!(SD_POOL.stats().peak_in_use, 1);
+//! ```
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicU32, Ordering};
 
 // ── Memory region addresses ──────────────────────────────────────────────────
 
@@ -349,6 +379,265 @@ impl HighLatencyRegion for SdramRegion {}
 // Any attempt to create a DmaBuffer<SdramRegion, T> will fail to compile,
 // preventing accidental real-time DMA from external SDRAM.
 
+// ── DMA buffer pool ───────────────────────────────────────────────────────────
+
+/// Cortex-M7 D-cache line size (bytes).
+///
+/// Pool blocks are aligned to and sized in whole cache lines so cleaning or
+/// invalidating one block never touches its neighbour.
+pub const DCACHE_LINE_BYTES: usize = 32;
+
+/// Most blocks one [`DmaPool`] can hold (one bit each in its `u32` mask).
+pub const DMA_POOL_MAX_BLOCKS: usize = 32;
+
+/// Marker trait: memory region a [`DmaPool`] may be placed in.
+///
+/// # Safety
+/// Only implement for zero-sized region types that some DMA controller can
+/// reach ([`AxiSramRegion`] for DMA1/2/MDMA, [`Sram4Region`] for BDMA), with
+/// `BASE`/`SIZE_BYTES` matching the physical region.  Never implement for
+/// [`DtcmRegion`] or [`SdramRegion`].
+pub unsafe trait PoolRegion: Sized {
+    /// Region base address.
+    const BASE: u32;
+    /// Region size in bytes; a pool larger than this fails to compile.
+    const SIZE_BYTES: usize;
+}
+
+// SAFETY: AXI SRAM is reachable by DMA1/DMA2/MDMA (see `DmaAccessible` above).
+unsafe impl PoolRegion for AxiSramRegion {
+    const BASE: u32 = AXI_SRAM_BASE;
+    const SIZE_BYTES: usize = AXI_SRAM_SIZE_BYTES;
+}
+
+// SAFETY: SRAM4 is reachable by BDMA (see `BdmaAccessible` above).
+unsafe impl PoolRegion for Sram4Region {
+    const BASE: u32 = SRAM4_BASE;
+    const SIZE_BYTES: usize = SRAM4_SIZE_BYTES;
+}
+
+/// One cache-line-aligned pool block.
+#[repr(C, align(32))]
+struct PoolBlock<const SIZE: usize>(UnsafeCell<[u8; SIZE]>);
+
+impl<const SIZE: usize> PoolBlock<SIZE> {
+    // Only used as the array-repeat initializer in `DmaPool::new`; each copy
+    // is a fresh cell.
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY: Self = Self(UnsafeCell::new([0; SIZE]));
+}
+
+/// Fixed-block pool of DMA buffers in memory region `R`.
+///
+/// `N` blocks of `SIZE` bytes each, handed out as [`DmaBlock`]s and
+/// returned when the block is dropped.  Allocation is a lock-free bitmap
+/// claim, so tasks and interrupt handlers can share one pool.  Declare the
+/// pool as a `static` with the region's `#[link_section]`; the type cannot
+/// check where the linker actually puts it.
+///
+/// Compile-time checks (at the first [`new`](Self::new) of each
+/// instantiation): `1 <= N <= 32`, `SIZE` is a non-zero multiple of
+/// [`DCACHE_LINE_BYTES`], and the pool fits in `R`.
+pub struct DmaPool<R: PoolRegion, const SIZE: usize, const N: usize> {
+    blocks: [PoolBlock<SIZE>; N],
+    /// Bit `i` set = block `i` is borrowed.
+    used: AtomicU32,
+    peak: AtomicU32,
+    exhaustions: AtomicU32,
+    _region: PhantomData<R>,
+}
+
+// SAFETY: a block's `UnsafeCell` is only dereferenced by the single
+// `DmaBlock` that won its bit in `used` (compare-exchange), and the bit is
+// released only when that `DmaBlock` drops, so no two references alias.
+unsafe impl<R: PoolRegion, const SIZE: usize, const N: usize> Sync for DmaPool<R, SIZE, N> {}
+
+impl<R: PoolRegion, const SIZE: usize, const N: usize> DmaPool<R, SIZE, N> {
+    const CHECK: () = {
+        assert!(
+            N >= 1 && N <= DMA_POOL_MAX_BLOCKS,
+            "DmaPool holds 1..=32 blocks"
+        );
+        assert!(
+            SIZE > 0 && SIZE.is_multiple_of(DCACHE_LINE_BYTES),
+            "DmaPool block size must be a non-zero multiple of the 32-byte cache line"
+        );
+        assert!(
+            SIZE * N <= R::SIZE_BYTES,
+            "DmaPool does not fit in its region"
+        );
+    };
+
+    // SAFETY: CHECK bounds N to 1..=32, so the shift and subtraction cannot
+    // overflow.
+    #[allow(clippy::arithmetic_side_effects)]
+    const ALL: u32 = if N >= DMA_POOL_MAX_BLOCKS {
+        u32::MAX
+    } else {
+        (1u32 << N) - 1
+    };
+
+    /// An empty pool (all blocks free, zeroed).
+    #[allow(clippy::let_unit_value)] // forces the compile-time CHECK
+    pub const fn new() -> Self {
+        let () = Self::CHECK;
+        Self {
+            blocks: [PoolBlock::EMPTY; N],
+            used: AtomicU32::new(0),
+            peak: AtomicU32::new(0),
+            exhaustions: AtomicU32::new(0),
+            _region: PhantomData,
+        }
+    }
+
+    /// Block size in bytes.
+    pub const fn block_size(&self) -> usize {
+        SIZE
+    }
+
+    /// Number of blocks.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Borrow a free block, or `None` (counted in
+    /// [`DmaPoolStats::exhaustions`]) if every block is out.
+    ///
+    /// The block keeps the contents its last user left behind.
+    pub fn try_alloc(&self) -> Option<DmaBlock<'_, R, SIZE>> {
+        let mut used = self.used.load(Ordering::Acquire);
+        loop {
+            let free = !used & Self::ALL;
+            if free == 0 {
+                self.exhaustions.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+            let index = free.trailing_zeros();
+            let bit = 1u32.wrapping_shl(index);
+            match self.used.compare_exchange_weak(
+                used,
+                used | bit,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.peak
+                        .fetch_max((used | bit).count_ones(), Ordering::Relaxed);
+                    let block = self.blocks.get(index as usize)?;
+                    // SAFETY: this call just claimed `bit`, so no other
+                    // `DmaBlock` refers to this block until it is dropped.
+                    let data = unsafe { &mut *block.0.get() };
+                    return Some(DmaBlock {
+                        data,
+                        bit,
+                        used: &self.used,
+                        _region: PhantomData,
+                    });
+                }
+                Err(now) => used = now,
+            }
+        }
+    }
+
+    /// Blocks currently borrowed.
+    pub fn in_use(&self) -> usize {
+        self.used.load(Ordering::Relaxed).count_ones() as usize
+    }
+
+    /// Usage counters since creation (or the last
+    /// [`reset_stats`](Self::reset_stats)).
+    pub fn stats(&self) -> DmaPoolStats {
+        DmaPoolStats {
+            capacity: N,
+            in_use: self.in_use(),
+            peak_in_use: self.peak.load(Ordering::Relaxed) as usize,
+            exhaustions: self.exhaustions.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Restart the peak and exhaustion counters from the current state.
+    pub fn reset_stats(&self) {
+        self.peak.store(
+            self.used.load(Ordering::Relaxed).count_ones(),
+            Ordering::Relaxed,
+        );
+        self.exhaustions.store(0, Ordering::Relaxed);
+    }
+}
+
+impl<R: PoolRegion, const SIZE: usize, const N: usize> Default for DmaPool<R, SIZE, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// [`DmaPool`] usage counters, for sizing pools from real workloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DmaPoolStats {
+    /// Number of blocks in the pool.
+    pub capacity: usize,
+    /// Blocks borrowed right now.
+    pub in_use: usize,
+    /// Most blocks borrowed at once.
+    pub peak_in_use: usize,
+    /// [`DmaPool::try_alloc`] calls that found no free block.
+    pub exhaustions: u32,
+}
+
+/// A block borrowed from a [`DmaPool`] in region `R`.
+///
+/// Dereferences to `[u8; SIZE]`; dropping it returns the block to the pool.
+/// Drivers constrain `R` ([`DmaAccessible`] for DMA1/2, [`BdmaAccessible`]
+/// for BDMA) to keep blocks from the wrong region out at compile time.
+pub struct DmaBlock<'a, R, const SIZE: usize> {
+    data: &'a mut [u8; SIZE],
+    bit: u32,
+    used: &'a AtomicU32,
+    _region: PhantomData<R>,
+}
+
+impl<R, const SIZE: usize> DmaBlock<'_, R, SIZE> {
+    /// Start address of the block, for programming a DMA stream.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.data.as_ptr()
+    }
+
+    /// Mutable start address of the block.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.data.as_mut_ptr()
+    }
+}
+
+impl<R, const SIZE: usize> Deref for DmaBlock<'_, R, SIZE> {
+    type Target = [u8; SIZE];
+
+    fn deref(&self) -> &Self::Target {
+        self.data
+    }
+}
+
+impl<R, const SIZE: usize> DerefMut for DmaBlock<'_, R, SIZE> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.data
+    }
+}
+
+impl<R, const SIZE: usize> Drop for DmaBlock<'_, R, SIZE> {
+    fn drop(&mut self) {
+        self.used.fetch_and(!self.bit, Ordering::Release);
+    }
+}
+
+impl<R, const SIZE: usize> core::fmt::Debug for DmaBlock<'_, R, SIZE> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DmaBlock")
+            .field("index", &self.bit.trailing_zeros())
+            .field("size", &SIZE)
+            .finish()
+    }
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -475,6 +764,64 @@ mod tests {
         );
     }
 
+    #[test]
+    #[allow(clippy::unwrap_used, clippy::many_single_char_names)]
+    fn pool_hands_out_each_block_once_and_reuses_dropped_ones() {
+        let pool: DmaPool<AxiSramRegion, 64, 3> = DmaPool::new();
+        let mut a = pool.try_alloc().unwrap();
+        let b = pool.try_alloc().unwrap();
+        let c = pool.try_alloc().unwrap();
+        assert!(pool.try_alloc().is_none());
+        assert_ne!(a.as_ptr(), b.as_ptr());
+        assert_ne!(b.as_ptr(), c.as_ptr());
+        assert_eq!(a.as_ptr() as usize % DCACHE_LINE_BYTES, 0);
+
+        a.fill(0xAB);
+        let reused = a.as_ptr();
+        drop(a);
+        let again = pool.try_alloc().unwrap();
+        assert_eq!(again.as_ptr(), reused);
+        assert!(again.iter().all(|&x| x == 0xAB));
+        drop((b, c));
+
+        assert_eq!(
+            pool.stats(),
+            DmaPoolStats {
+                capacity: 3,
+                in_use: 1,
+                peak_in_use: 3,
+                exhaustions: 1,
+            }
+        );
+        pool.reset_stats();
+        assert_eq!((pool.stats().peak_in_use, pool.stats().exhaustions), (1, 0));
+        drop(again);
+        assert_eq!(pool.in_use(), 0);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn full_width_pool_uses_every_mask_bit() {
+        let pool: DmaPool<Sram4Region, 32, 32> = DmaPool::new();
+        let blocks: [_; 32] = core::array::from_fn(|_| pool.try_alloc().unwrap());
+        assert!(pool.try_alloc().is_none());
+        assert_eq!(pool.in_use(), 32);
+        drop(blocks);
+        assert_eq!(pool.in_use(), 0);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn pool_blocks_keep_their_region_type() {
+        fn dma1_buffer<R: DmaAccessible, const SIZE: usize>(_: &DmaBlock<'_, R, SIZE>) {}
+        fn bdma_buffer<R: BdmaAccessible, const SIZE: usize>(_: &DmaBlock<'_, R, SIZE>) {}
+
+        let axi: DmaPool<AxiSramRegion, 32, 1> = DmaPool::new();
+        let sram4: DmaPool<Sram4Region, 32, 1> = DmaPool::new();
+        dma1_buffer(&axi.try_alloc().unwrap());
+        bdma_buffer(&sram4.try_alloc().unwrap());
+    }
+
     #[test]
     // GAP C3: Document the negative trait bound limitation.
     // Rust stable does not support negative trait bounds (nightly only).
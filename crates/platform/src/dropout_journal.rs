@@ -0,0 +1,435 @@
+//! Audio dropout journal — every underrun and overrun, with what else was
+//! going on at the time.
+//!
+//! A click in the audio is a missed SAI deadline, and the question is
+//! always *why*: was a GC16 refresh holding the SPI bus, did an SD read
+//! stall, was the decoder starved of CPU?  By the time someone reports it
+//! the moment is gone, so the journal keeps the evidence:
+//!
+//! - [`ActivityMonitor`] is a set of atomics the busy subsystems update as
+//!   they work — the display service around each refresh, the storage
+//!   layer after each read, the main loop after each CPU sample.  It costs
+//!   one relaxed store per update.
+//! - When the audio task sees a dropout it takes an [`Activity`] snapshot
+//!   and [`DropoutJournal::record`]s it.  The journal is a RAM ring of the
+//!   last `N` [`Dropout`]s; the oldest is overwritten, but the
+//!   [`DropoutSummary`] counters cover every dropout since boot.
+//! - The summary goes into the
+//!   [`DiagnosticsReport`](crate::diagnostics::DiagnosticsReport); the
+//!   journal's `Display` output is the defmt/log dump.
+//!
+//! # Example
+//!
+//! ```
+//! use platform::dropout_journal::{ActivityMonitor, DropoutJournal, DropoutKind};
+//! use platform::RefreshMode;
+//!
+//! static ACTIVITY: ActivityMonitor = ActivityMonitor::new();
+//! let mut journal = DropoutJournal::<8>::new();
+//!
+//! ACTIVITY.refresh_started(RefreshMode::Full);
+//! journal.record(1_200, DropoutKind::Underrun, ACTIVITY.snapshot());
+//! ACTIVITY.refresh_finished();
+//!
+//! assert_eq!(journal.summary().during_refresh, 1);
+//! ```
+
+use core::fmt;
+use core::sync::atomic::{AtomicU16, AtomicU32, AtomicU8, Ordering};
+
+use crate::RefreshMode;
+
+/// An SD read slower than this (µs) is counted as a likely cause.
+///
+/// The decoder reads ahead by one 4 KiB cluster; at 44.1 kHz / 24-bit a
+/// 20 ms stall already eats most of the DMA headroom.
+pub const SLOW_SD_READ_US: u32 = 20_000;
+
+/// CPU load at or above this (permille) is counted as a likely cause.
+pub const HIGH_CPU_PERMILLE: u16 = 900;
+
+/// Which way the audio buffer missed its deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum DropoutKind {
+    /// The decoder did not refill the buffer in time; the DAC played
+    /// silence or stale samples.
+    Underrun,
+    /// The SAI DMA lapped the writer; samples were dropped.
+    Overrun,
+}
+
+impl DropoutKind {
+    /// Short label for logs and the diagnostics screen.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Underrun => "underrun",
+            Self::Overrun => "overrun",
+        }
+    }
+}
+
+/// What the rest of the system was doing when a dropout happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Activity {
+    /// The display refresh in progress, if any.
+    pub refresh: Option<RefreshMode>,
+    /// Duration of the most recent SD read (µs); 0 before the first read.
+    pub sd_read_us: u32,
+    /// CPU load over the last sample window (permille).
+    pub cpu_permille: u16,
+}
+
+impl Activity {
+    /// The SD read was slow enough to explain a dropout.
+    pub const fn slow_sd(&self) -> bool {
+        self.sd_read_us >= SLOW_SD_READ_US
+    }
+
+    /// The CPU was busy enough to explain a dropout.
+    pub const fn high_cpu(&self) -> bool {
+        self.cpu_permille >= HIGH_CPU_PERMILLE
+    }
+}
+
+/// One journal entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Dropout {
+    /// Milliseconds since boot.
+    pub at_ms: u64,
+    /// Underrun or overrun.
+    pub kind: DropoutKind,
+    /// Concurrent activity.
+    pub activity: Activity,
+}
+
+impl fmt::Display for Dropout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ms {}", self.at_ms, self.kind.name())?;
+        match self.activity.refresh {
+            Some(RefreshMode::Full) => f.write_str(" refresh=full")?,
+            Some(RefreshMode::Partial) => f.write_str(" refresh=partial")?,
+            Some(RefreshMode::Fast) => f.write_str(" refresh=fast")?,
+            None => {}
+        }
+        write!(
+            f,
+            " sd={} us cpu={} permille",
+            self.activity.sd_read_us, self.activity.cpu_permille
+        )
+    }
+}
+
+const NO_REFRESH: u8 = 0;
+
+const fn refresh_code(mode: RefreshMode) -> u8 {
+    match mode {
+        RefreshMode::Full => 1,
+        RefreshMode::Partial => 2,
+        RefreshMode::Fast => 3,
+    }
+}
+
+const fn refresh_from_code(code: u8) -> Option<RefreshMode> {
+    match code {
+        1 => Some(RefreshMode::Full),
+        2 => Some(RefreshMode::Partial),
+        3 => Some(RefreshMode::Fast),
+        _ => None,
+    }
+}
+
+/// Lock-free record of concurrent activity, shared as a `static`.
+///
+/// Writers and the reader may run at different priorities; each field is
+/// its own atomic, so a snapshot can mix values from either side of an
+/// update, which is fine for a post-mortem hint.
+#[derive(Debug)]
+pub struct ActivityMonitor {
+    refresh: AtomicU8,
+    sd_read_us: AtomicU32,
+    cpu_permille: AtomicU16,
+}
+
+impl ActivityMonitor {
+    /// Nothing in progress.
+    pub const fn new() -> Self {
+        Self {
+            refresh: AtomicU8::new(NO_REFRESH),
+            sd_read_us: AtomicU32::new(0),
+            cpu_permille: AtomicU16::new(0),
+        }
+    }
+
+    /// A display refresh in `mode` has started.
+    pub fn refresh_started(&self, mode: RefreshMode) {
+        self.refresh.store(refresh_code(mode), Ordering::Relaxed);
+    }
+
+    /// The display refresh has finished (or failed).
+    pub fn refresh_finished(&self) {
+        self.refresh.store(NO_REFRESH, Ordering::Relaxed);
+    }
+
+    /// An SD read took `us` microseconds.
+    pub fn sd_read(&self, us: u32) {
+        self.sd_read_us.store(us, Ordering::Relaxed);
+    }
+
+    /// CPU load over the last sample window, in permille (clamped to 1000).
+    pub fn cpu_load(&self, permille: u16) {
+        self.cpu_permille
+            .store(permille.min(1_000), Ordering::Relaxed);
+    }
+
+    /// Current activity.
+    pub fn snapshot(&self) -> Activity {
+        Activity {
+            refresh: refresh_from_code(self.refresh.load(Ordering::Relaxed)),
+            sd_read_us: self.sd_read_us.load(Ordering::Relaxed),
+            cpu_permille: self.cpu_permille.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for ActivityMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Dropout counts since boot, for the diagnostics report.
+///
+/// A dropout with several likely causes counts under each of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DropoutSummary {
+    /// Underruns.
+    pub underruns: u32,
+    /// Overruns.
+    pub overruns: u32,
+    /// Dropouts while a display refresh was in progress.
+    pub during_refresh: u32,
+    /// Dropouts after an SD read of at least [`SLOW_SD_READ_US`].
+    pub slow_sd: u32,
+    /// Dropouts with CPU load at or above [`HIGH_CPU_PERMILLE`].
+    pub high_cpu: u32,
+}
+
+impl DropoutSummary {
+    /// Every dropout since boot.
+    pub const fn total(&self) -> u32 {
+        self.underruns.saturating_add(self.overruns)
+    }
+
+    /// No dropouts at all.
+    pub const fn is_clean(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+impl fmt::Display for DropoutSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "dropouts: {} underrun {} overrun",
+            self.underruns, self.overruns
+        )?;
+        if !self.is_clean() {
+            write!(
+                f,
+                " (refresh {} sd {} cpu {})",
+                self.during_refresh, self.slow_sd, self.high_cpu
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// RAM ring of the last `N` dropouts.
+#[derive(Debug, Clone)]
+pub struct DropoutJournal<const N: usize> {
+    entries: heapless::Deque<Dropout, N>,
+    summary: DropoutSummary,
+}
+
+impl<const N: usize> DropoutJournal<N> {
+    /// Empty journal.
+    pub const fn new() -> Self {
+        Self {
+            entries: heapless::Deque::new(),
+            summary: DropoutSummary {
+                underruns: 0,
+                overruns: 0,
+                during_refresh: 0,
+                slow_sd: 0,
+                high_cpu: 0,
+            },
+        }
+    }
+
+    /// Record a dropout at `at_ms`, overwriting the oldest entry when full.
+    pub fn record(&mut self, at_ms: u64, kind: DropoutKind, activity: Activity) {
+        let s = &mut self.summary;
+        match kind {
+            DropoutKind::Underrun => s.underruns = s.underruns.saturating_add(1),
+            DropoutKind::Overrun => s.overruns = s.overruns.saturating_add(1),
+        }
+        if activity.refresh.is_some() {
+            s.during_refresh = s.during_refresh.saturating_add(1);
+        }
+        if activity.slow_sd() {
+            s.slow_sd = s.slow_sd.saturating_add(1);
+        }
+        if activity.high_cpu() {
+            s.high_cpu = s.high_cpu.saturating_add(1);
+        }
+
+        if self.entries.is_full() {
+            let _ = self.entries.pop_front();
+        }
+        // Room was made above (or N is 0 and nothing is kept).
+        let _ = self.entries.push_back(Dropout {
+            at_ms,
+            kind,
+            activity,
+        });
+    }
+
+    /// Retained entries, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Dropout> {
+        self.entries.iter()
+    }
+
+    /// Most recent dropout.
+    pub fn last(&self) -> Option<&Dropout> {
+        self.entries.back()
+    }
+
+    /// Retained entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// No dropouts retained.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Counts over every dropout since boot (or the last [`clear`](Self::clear)).
+    pub const fn summary(&self) -> DropoutSummary {
+        self.summary
+    }
+
+    /// Forget every entry and reset the counts.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl<const N: usize> Default for DropoutJournal<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The dump: the summary line, then one line per retained entry.
+impl<const N: usize> fmt::Display for DropoutJournal<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.summary)?;
+        for entry in &self.entries {
+            writeln!(f, "  {entry}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_follows_refresh_and_clamps_cpu() {
+        let monitor = ActivityMonitor::new();
+        assert_eq!(monitor.snapshot(), Activity::default());
+        monitor.refresh_started(RefreshMode::Partial);
+        monitor.sd_read(4_500);
+        monitor.cpu_load(1_500);
+        let a = monitor.snapshot();
+        assert_eq!(a.refresh, Some(RefreshMode::Partial));
+        assert_eq!(a.sd_read_us, 4_500);
+        assert_eq!(a.cpu_permille, 1_000);
+        monitor.refresh_finished();
+        assert_eq!(monitor.snapshot().refresh, None);
+    }
+
+    #[test]
+    fn ring_overwrites_oldest_but_summary_counts_all() {
+        let mut journal = DropoutJournal::<2>::new();
+        for at_ms in 0..3 {
+            journal.record(at_ms, DropoutKind::Underrun, Activity::default());
+        }
+        assert_eq!(journal.len(), 2);
+        assert_eq!(journal.iter().next().unwrap().at_ms, 1);
+        assert_eq!(journal.last().unwrap().at_ms, 2);
+        assert_eq!(journal.summary().total(), 3);
+    }
+
+    #[test]
+    fn summary_attributes_each_likely_cause() {
+        let mut journal = DropoutJournal::<4>::new();
+        journal.record(
+            10,
+            DropoutKind::Underrun,
+            Activity {
+                refresh: Some(RefreshMode::Full),
+                sd_read_us: SLOW_SD_READ_US,
+                cpu_permille: 100,
+            },
+        );
+        journal.record(
+            20,
+            DropoutKind::Overrun,
+            Activity {
+                refresh: None,
+                sd_read_us: 300,
+                cpu_permille: HIGH_CPU_PERMILLE,
+            },
+        );
+        let s = journal.summary();
+        assert_eq!((s.underruns, s.overruns), (1, 1));
+        assert_eq!((s.during_refresh, s.slow_sd, s.high_cpu), (1, 1, 1));
+        assert!(!s.is_clean());
+        journal.clear();
+        assert!(journal.is_empty());
+        assert!(journal.summary().is_clean());
+    }
+
+    #[test]
+    fn dump_lists_summary_then_entries() {
+        let mut journal = DropoutJournal::<4>::new();
+        journal.record(
+            1_200,
+            DropoutKind::Underrun,
+            Activity {
+                refresh: Some(RefreshMode::Full),
+                sd_read_us: 800,
+                cpu_permille: 420,
+            },
+        );
+        let dump = journal.to_string();
+        let mut lines = dump.lines();
+        assert_eq!(
+            lines.next(),
+            Some("dropouts: 1 underrun 0 overrun (refresh 1 sd 0 cpu 0)")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("  1200 ms underrun refresh=full sd=800 us cpu=420 permille")
+        );
+        assert_eq!(lines.next(), None);
+    }
+}
@@ -0,0 +1,455 @@
+//! Haptic/audible confirmation of input.
+//!
+//! An e-ink refresh takes hundreds of milliseconds, so a button press can
+//! look ignored.  A short click (or, on later boards, a buzz) confirms it
+//! straight away.  The pieces:
+//!
+//! - [`Feedback`] is the output: anything that can play a
+//!   [`FeedbackEvent`].
+//! - [`FeedbackService`] sits in the input mapping layer.  It turns each
+//!   [`TimestampedEvent`] into a [`FeedbackEvent`], drops the ones
+//!   [`FeedbackSettings`] turns off, rate-limits encoder detents and plays
+//!   the rest.
+//! - [`ClickMixer`] and [`ClickVoice`] are the audio-path output: the input
+//!   task triggers the shared mixer, and the audio task adds the click
+//!   into the buffers it is about to write, so music keeps playing under
+//!   it.
+
+use core::convert::Infallible;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::audio_loopback::{Tone, ToneGenerator};
+use crate::input::{InputEvent, TimestampedEvent};
+
+/// Click tone frequency (Hz).
+pub const CLICK_FREQ_HZ: u32 = 2_000;
+
+/// Shortest time between two detent clicks (ms); a fast spin would
+/// otherwise buzz.
+pub const DETENT_MIN_INTERVAL_MS: u64 = 40;
+
+/// Something the user should feel or hear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FeedbackEvent {
+    /// A button went down.
+    Press,
+    /// A button was held.
+    LongPress,
+    /// The encoder moved one or more detents.
+    Detent,
+    /// The encoder hit the end of a list or range.
+    Boundary,
+}
+
+impl FeedbackEvent {
+    /// Every event, in settings order.
+    pub const ALL: [Self; 4] = [Self::Press, Self::LongPress, Self::Detent, Self::Boundary];
+
+    /// The feedback for an input event, or `None` for releases and
+    /// zero-step encoder reports.
+    pub fn for_input(event: InputEvent) -> Option<Self> {
+        match event {
+            InputEvent::ButtonPress(_) => Some(Self::Press),
+            InputEvent::ButtonLongPress(_) => Some(Self::LongPress),
+            InputEvent::RotaryIncrement(0) | InputEvent::ButtonRelease(_) => None,
+            InputEvent::RotaryIncrement(_) => Some(Self::Detent),
+        }
+    }
+
+    /// Click length (ms).
+    pub const fn click_ms(self) -> u32 {
+        match self {
+            Self::Press | Self::Detent => 3,
+            Self::LongPress | Self::Boundary => 8,
+        }
+    }
+
+    /// Click level relative to full scale (permille).  Detents are the
+    /// quietest since they come in runs.
+    pub const fn gain_permille(self) -> u32 {
+        match self {
+            Self::Press => 200,
+            Self::LongPress => 250,
+            Self::Detent => 100,
+            Self::Boundary => 300,
+        }
+    }
+
+    const fn bit(self) -> u8 {
+        match self {
+            Self::Press => 0x01,
+            Self::LongPress => 0x02,
+            Self::Detent => 0x04,
+            Self::Boundary => 0x08,
+        }
+    }
+
+    fn from_bit(bit: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.bit() == bit)
+    }
+}
+
+/// Per-event enable flags, part of the user settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FeedbackSettings {
+    bits: u8,
+}
+
+impl FeedbackSettings {
+    /// Presses, long presses and list ends on; detents off, since a click
+    /// per detent gets tiring while scrolling.
+    pub const DEFAULT: Self = Self { bits: 0x0B };
+
+    /// Everything off.
+    pub const OFF: Self = Self { bits: 0 };
+
+    /// Whether `event` gives feedback.
+    pub const fn enabled(self, event: FeedbackEvent) -> bool {
+        self.bits & event.bit() != 0
+    }
+
+    /// Turn feedback for `event` on or off.
+    pub fn set(&mut self, event: FeedbackEvent, on: bool) {
+        if on {
+            self.bits |= event.bit();
+        } else {
+            self.bits &= !event.bit();
+        }
+    }
+
+    /// Byte stored with the settings.
+    pub const fn to_bits(self) -> u8 {
+        self.bits
+    }
+
+    /// Settings from a stored byte; unknown bits are dropped.
+    pub fn from_bits(bits: u8) -> Self {
+        let known = FeedbackEvent::ALL.iter().fold(0, |acc, e| acc | e.bit());
+        Self { bits: bits & known }
+    }
+}
+
+impl Default for FeedbackSettings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// An output that can confirm input: a click in the audio path, a haptic
+/// motor, or nothing.
+pub trait Feedback {
+    /// Error type.
+    type Error: core::fmt::Debug;
+
+    /// Start the feedback for `event`.  Should return quickly; the input
+    /// task waits on it.
+    async fn play(&mut self, event: FeedbackEvent) -> Result<(), Self::Error>;
+}
+
+/// Feedback that does nothing, for boards without an output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoFeedback;
+
+impl Feedback for NoFeedback {
+    type Error = Infallible;
+
+    async fn play(&mut self, _event: FeedbackEvent) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Filters input events through the settings and plays the rest.
+#[derive(Debug)]
+pub struct FeedbackService<F> {
+    output: F,
+    settings: FeedbackSettings,
+    last_detent_ms: Option<u64>,
+}
+
+impl<F: Feedback> FeedbackService<F> {
+    /// Service playing on `output` with `settings`.
+    pub fn new(output: F, settings: FeedbackSettings) -> Self {
+        Self {
+            output,
+            settings,
+            last_detent_ms: None,
+        }
+    }
+
+    /// Current settings.
+    pub fn settings(&self) -> FeedbackSettings {
+        self.settings
+    }
+
+    /// Apply changed settings.
+    pub fn set_settings(&mut self, settings: FeedbackSettings) {
+        self.settings = settings;
+    }
+
+    /// The output.
+    pub fn output_mut(&mut self) -> &mut F {
+        &mut self.output
+    }
+
+    /// Give feedback for an input event.  Returns whether anything played.
+    pub async fn on_input(&mut self, event: TimestampedEvent) -> Result<bool, F::Error> {
+        match FeedbackEvent::for_input(event.event) {
+            Some(feedback) => self.notify(feedback, event.at_ms).await,
+            None => Ok(false),
+        }
+    }
+
+    /// Give feedback for something other than a raw input event, such as
+    /// the UI reporting a [`FeedbackEvent::Boundary`].  Returns whether
+    /// anything played.
+    pub async fn notify(&mut self, event: FeedbackEvent, at_ms: u64) -> Result<bool, F::Error> {
+        if !self.settings.enabled(event) {
+            return Ok(false);
+        }
+        if event == FeedbackEvent::Detent {
+            let too_soon = self
+                .last_detent_ms
+                .is_some_and(|last| at_ms.saturating_sub(last) < DETENT_MIN_INTERVAL_MS);
+            if too_soon {
+                return Ok(false);
+            }
+            self.last_detent_ms = Some(at_ms);
+        }
+        self.output.play(event).await?;
+        Ok(true)
+    }
+}
+
+/// Click request shared between the input task and the audio task.
+///
+/// `const`-constructible so it can live in a `static`.  A newer request
+/// replaces one the audio task has not picked up yet.
+#[derive(Debug, Default)]
+pub struct ClickMixer {
+    pending: AtomicU8,
+}
+
+impl ClickMixer {
+    /// No click pending.
+    pub const fn new() -> Self {
+        Self {
+            pending: AtomicU8::new(0),
+        }
+    }
+
+    /// Ask the audio task to play the click for `event`.
+    pub fn trigger(&self, event: FeedbackEvent) {
+        self.pending.store(event.bit(), Ordering::Relaxed);
+    }
+
+    /// Take the pending request, if any.
+    pub fn take(&self) -> Option<FeedbackEvent> {
+        FeedbackEvent::from_bit(self.pending.swap(0, Ordering::Relaxed))
+    }
+}
+
+impl Feedback for &ClickMixer {
+    type Error = Infallible;
+
+    async fn play(&mut self, event: FeedbackEvent) -> Result<(), Infallible> {
+        self.trigger(event);
+        Ok(())
+    }
+}
+
+/// Audio-task side of the click: a decaying tone added into outgoing
+/// buffers.
+#[derive(Debug, Clone)]
+pub struct ClickVoice {
+    sample_rate: u32,
+    tone: ToneGenerator,
+    gain_permille: u32,
+    total_frames: u32,
+    remaining_frames: u32,
+}
+
+impl ClickVoice {
+    /// Silent voice for output at `sample_rate`.
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            tone: Self::generator(sample_rate),
+            gain_permille: 0,
+            total_frames: 0,
+            remaining_frames: 0,
+        }
+    }
+
+    fn generator(sample_rate: u32) -> ToneGenerator {
+        let tone = Tone {
+            freq_hz: CLICK_FREQ_HZ,
+            left: true,
+            right: true,
+            muted: false,
+        };
+        ToneGenerator::new(tone, sample_rate)
+    }
+
+    /// Whether a click is still sounding.
+    pub fn is_active(&self) -> bool {
+        self.remaining_frames > 0
+    }
+
+    /// Start the click for `event`, cutting off one still sounding.
+    pub fn start(&mut self, event: FeedbackEvent) {
+        let frames = u64::from(self.sample_rate).saturating_mul(u64::from(event.click_ms())) / 1000;
+        self.tone = Self::generator(self.sample_rate);
+        self.gain_permille = event.gain_permille();
+        self.total_frames = u32::try_from(frames).unwrap_or(u32::MAX);
+        self.remaining_frames = self.total_frames;
+    }
+
+    /// Pick up a request from `mixer` and add the click into interleaved
+    /// L/R `samples` (the
+    /// [`AudioCodec::write_samples`](crate::AudioCodec::write_samples)
+    /// format), clipping rather than wrapping.
+    pub fn mix(&mut self, mixer: &ClickMixer, samples: &mut [i32]) {
+        if let Some(event) = mixer.take() {
+            self.start(event);
+        }
+        let mut tone = [0i32; 2];
+        for frame in samples.chunks_exact_mut(2) {
+            if self.remaining_frames == 0 {
+                break;
+            }
+            self.tone.fill(&mut tone);
+            // Linear decay from the event's gain to silence.
+            let scale =
+                u64::from(self.gain_permille).saturating_mul(u64::from(self.remaining_frames));
+            let divisor = u64::from(self.total_frames).saturating_mul(1000);
+            for (out, &value) in frame.iter_mut().zip(&tone) {
+                let click = i64::from(value)
+                    .saturating_mul(i64::try_from(scale).unwrap_or(i64::MAX))
+                    .checked_div(i64::try_from(divisor).unwrap_or(i64::MAX))
+                    .unwrap_or(0);
+                let click = i32::try_from(click).unwrap_or(0);
+                *out = out.saturating_add(click);
+            }
+            self.remaining_frames = self.remaining_frames.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects
+)]
+mod tests {
+    use super::*;
+    use crate::input::Button;
+
+    #[derive(Default)]
+    struct Recorder(std::vec::Vec<FeedbackEvent>);
+
+    impl Feedback for Recorder {
+        type Error = Infallible;
+
+        async fn play(&mut self, event: FeedbackEvent) -> Result<(), Infallible> {
+            self.0.push(event);
+            Ok(())
+        }
+    }
+
+    fn at(event: InputEvent, at_ms: u64) -> TimestampedEvent {
+        TimestampedEvent::new(event, at_ms)
+    }
+
+    #[test]
+    fn input_maps_to_feedback() {
+        let press = InputEvent::ButtonPress(Button::Select);
+        assert_eq!(FeedbackEvent::for_input(press), Some(FeedbackEvent::Press));
+        let release = InputEvent::ButtonRelease(Button::Select);
+        assert_eq!(FeedbackEvent::for_input(release), None);
+        let turn = InputEvent::RotaryIncrement(-2);
+        assert_eq!(FeedbackEvent::for_input(turn), Some(FeedbackEvent::Detent));
+        assert_eq!(
+            FeedbackEvent::for_input(InputEvent::RotaryIncrement(0)),
+            None
+        );
+    }
+
+    #[test]
+    fn settings_round_trip_and_drop_unknown_bits() {
+        let mut settings = FeedbackSettings::DEFAULT;
+        assert!(!settings.enabled(FeedbackEvent::Detent));
+        settings.set(FeedbackEvent::Detent, true);
+        settings.set(FeedbackEvent::Press, false);
+        let stored = settings.to_bits();
+        assert_eq!(FeedbackSettings::from_bits(stored | 0xF0), settings);
+        assert!(FeedbackEvent::ALL
+            .iter()
+            .all(|&e| !FeedbackSettings::OFF.enabled(e)));
+    }
+
+    #[tokio::test]
+    async fn service_honours_flags_and_rate_limits_detents() {
+        let mut settings = FeedbackSettings::DEFAULT;
+        settings.set(FeedbackEvent::Detent, true);
+        settings.set(FeedbackEvent::LongPress, false);
+        let mut service = FeedbackService::new(Recorder::default(), settings);
+
+        let events = [
+            at(InputEvent::ButtonPress(Button::Play), 0),
+            at(InputEvent::ButtonRelease(Button::Play), 80),
+            at(InputEvent::ButtonLongPress(Button::Play), 600),
+            at(InputEvent::RotaryIncrement(1), 1_000),
+            at(InputEvent::RotaryIncrement(1), 1_020),
+            at(InputEvent::RotaryIncrement(1), 1_050),
+        ];
+        for event in events {
+            service.on_input(event).await.unwrap();
+        }
+        assert!(service
+            .notify(FeedbackEvent::Boundary, 1_060)
+            .await
+            .unwrap());
+        assert_eq!(
+            service.output_mut().0,
+            [
+                FeedbackEvent::Press,
+                FeedbackEvent::Detent,
+                FeedbackEvent::Detent,
+                FeedbackEvent::Boundary,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn click_mixes_into_audio_and_decays() {
+        static MIXER: ClickMixer = ClickMixer::new();
+        let mut service = FeedbackService::new(&MIXER, FeedbackSettings::DEFAULT);
+        assert!(service
+            .on_input(at(InputEvent::ButtonPress(Button::Menu), 0))
+            .await
+            .unwrap());
+
+        let mut voice = ClickVoice::new(48_000);
+        let music = 1_000;
+        let mut buffer = [music; 2 * 200];
+        voice.mix(&MIXER, &mut buffer);
+        // 3 ms at 48 kHz = 144 frames of click, then the music untouched.
+        assert!(!voice.is_active());
+        assert!(buffer[..2 * 144].iter().any(|&s| s != music));
+        assert!(buffer[2 * 144..].iter().all(|&s| s == music));
+        let peak = buffer.iter().map(|s| (s - music).abs()).max().unwrap();
+        assert!(
+            peak <= i32::MAX / 5 + 1,
+            "press stays well below full scale"
+        );
+        assert_eq!(MIXER.take(), None, "request consumed");
+
+        let mut loud = [i32::MAX; 2 * 8];
+        MIXER.trigger(FeedbackEvent::Boundary);
+        voice.mix(&MIXER, &mut loud);
+        assert!(loud.iter().all(|&s| s > 0), "clips, never wraps");
+    }
+}
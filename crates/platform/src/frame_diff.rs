@@ -0,0 +1,460 @@
+//! Frame diffing — changed rectangles from two frames.
+//!
+//! Screens draw a whole frame; working out which part of the panel changed
+//! is left to this module instead of every component reporting its own
+//! dirty region.  The screen is split into [`TILE`]-pixel tiles (the
+//! partial window grid), each tile is compared against the frame on the
+//! panel, and changed tiles are merged into a few rectangles: horizontal
+//! runs first, then runs with the same columns in consecutive tile rows.
+//!
+//! [`diff_tiles`] does the merging for any frame representation — the
+//! emulator compares its own pixel buffers with it.  [`DoubleBuffer`] is
+//! the device-side store: a Gray4 back buffer the UI draws into and a
+//! front buffer holding what the panel shows.
+//!
+//! [`DoubleBuffer::diff_within`] compares only the tiles under given
+//! regions, for screens that already know where they drew (the
+//! `eink-components` render cache skips unchanged components entirely).
+//!
+//! [`DirtyRects::update`] turns the result into the
+//! [`Update`](crate::refresh_policy::Update) the refresh policy takes.
+//!
+//! # Example
+//!
+//! ```
+//! use embedded_graphics::{pixelcolor::Gray4, prelude::*, primitives::*};
+//! use platform::frame_diff::{DirtyRects, DoubleBuffer};
+//!
+//! let size = Size::new(64, 32);
+//! let mut front = [0u8; 64 * 32 / 2];
+//! let mut back = [0u8; 64 * 32 / 2];
+//! let mut frame = DoubleBuffer::new(&mut front, &mut back, size).unwrap();
+//!
+//! Rectangle::new(Point::new(10, 4), Size::new(4, 4))
+//!     .into_styled(PrimitiveStyle::with_fill(Gray4::WHITE))
+//!     .draw(&mut frame)
+//!     .unwrap();
+//! let dirty: DirtyRects<4> = frame.diff();
+//! assert_eq!(dirty.rects(), [Rectangle::new(Point::new(8, 0), Size::new(8, 8))]);
+//!
+//! frame.present();
+//! assert!(frame.diff::<4>().is_empty());
+//! ```
+
+use core::convert::Infallible;
+
+use embedded_graphics::{pixelcolor::Gray4, prelude::*, primitives::Rectangle};
+
+use crate::refresh_policy::{ContentHint, Update};
+
+/// Side of one diff tile (pixels), matching the 8-pixel partial window
+/// grid.
+pub const TILE: u32 = 8;
+
+/// Changed regions of a frame, on the [`TILE`] grid.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirtyRects<const N: usize> {
+    rects: heapless::Vec<Rectangle, N>,
+    collapsed: bool,
+}
+
+impl<const N: usize> DirtyRects<N> {
+    /// The changed rectangles.  They do not overlap.
+    pub fn rects(&self) -> &[Rectangle] {
+        &self.rects
+    }
+
+    /// Nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.rects.is_empty()
+    }
+
+    /// More than `N` rectangles were needed, so everything was merged into
+    /// one bounding box.
+    pub const fn collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    /// Smallest rectangle covering every change.
+    pub fn bounding_box(&self) -> Option<Rectangle> {
+        self.rects.iter().copied().reduce(union)
+    }
+
+    /// Total area of the rectangles (pixels).
+    pub fn dirty_pixels(&self) -> u32 {
+        self.rects
+            .iter()
+            .map(|r| r.size.width.saturating_mul(r.size.height))
+            .fold(0, u32::saturating_add)
+    }
+
+    /// The refresh-policy update for these changes.
+    pub fn update(&self, hint: ContentHint) -> Update {
+        Update::new(self.dirty_pixels(), hint)
+    }
+
+    fn add(&mut self, rect: Rectangle) {
+        if self.collapsed {
+            if let Some(all) = self.rects.first_mut() {
+                *all = union(*all, rect);
+            }
+            return;
+        }
+        let open = self.rects.iter_mut().find(|r| {
+            r.top_left.x == rect.top_left.x
+                && r.size.width == rect.size.width
+                && r.top_left.y.saturating_add(to_i32(r.size.height)) == rect.top_left.y
+        });
+        if let Some(open) = open {
+            open.size.height = open.size.height.saturating_add(rect.size.height);
+            return;
+        }
+        if let Err(rect) = self.rects.push(rect) {
+            let all = self.bounding_box().map_or(rect, |b| union(b, rect));
+            self.rects.clear();
+            // Capacity is at least one whenever a push can fail.
+            let _ = self.rects.push(all);
+            self.collapsed = true;
+        }
+    }
+}
+
+fn to_i32(v: u32) -> i32 {
+    i32::try_from(v).unwrap_or(i32::MAX)
+}
+
+fn union(a: Rectangle, b: Rectangle) -> Rectangle {
+    let left = a.top_left.x.min(b.top_left.x);
+    let top = a.top_left.y.min(b.top_left.y);
+    let right = a
+        .top_left
+        .x
+        .saturating_add(to_i32(a.size.width))
+        .max(b.top_left.x.saturating_add(to_i32(b.size.width)));
+    let bottom = a
+        .top_left
+        .y
+        .saturating_add(to_i32(a.size.height))
+        .max(b.top_left.y.saturating_add(to_i32(b.size.height)));
+    Rectangle::new(
+        Point::new(left, top),
+        Size::new(
+            u32::try_from(right.saturating_sub(left)).unwrap_or(0),
+            u32::try_from(bottom.saturating_sub(top)).unwrap_or(0),
+        ),
+    )
+}
+
+/// Changed rectangles of a `size` frame, given which tiles changed.
+///
+/// `tile_changed(tx, ty)` is called once per tile, row by row; tile
+/// `(tx, ty)` covers pixels from `(tx * TILE, ty * TILE)`.  Rectangles are
+/// clipped to `size`.  With more than `N` rectangles the result collapses
+/// into one bounding box.
+pub fn diff_tiles<const N: usize>(
+    size: Size,
+    mut tile_changed: impl FnMut(u32, u32) -> bool,
+) -> DirtyRects<N> {
+    let cols = size.width.div_ceil(TILE);
+    let rows = size.height.div_ceil(TILE);
+    let mut dirty = DirtyRects::default();
+    for ty in 0..rows {
+        let y = ty.saturating_mul(TILE);
+        let height = TILE.min(size.height.saturating_sub(y));
+        let mut run_start = None;
+        for tx in 0..=cols {
+            let changed = tx < cols && tile_changed(tx, ty);
+            match (run_start, changed) {
+                (None, true) => run_start = Some(tx),
+                (Some(start), false) => {
+                    let x = start.saturating_mul(TILE);
+                    let right = tx.saturating_mul(TILE).min(size.width);
+                    dirty.add(Rectangle::new(
+                        Point::new(to_i32(x), to_i32(y)),
+                        Size::new(right.saturating_sub(x), height),
+                    ));
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+    }
+    dirty
+}
+
+/// Bytes needed for one Gray4 frame of `size` (two pixels per byte, rows
+/// padded to a whole byte).
+pub const fn frame_bytes(size: Size) -> usize {
+    (size.width.div_ceil(2) as usize).saturating_mul(size.height as usize)
+}
+
+/// Back and front Gray4 frames.
+///
+/// Draw the next frame into it (it is a [`DrawTarget`] for the back
+/// buffer), [`diff`](Self::diff) to find what changed, copy those regions
+/// to the panel ([`blit`](Self::blit)), then [`present`](Self::present).
+/// An 800×480 frame takes 192 000 bytes per buffer.
+#[derive(Debug)]
+pub struct DoubleBuffer<'a> {
+    front: &'a mut [u8],
+    back: &'a mut [u8],
+    size: Size,
+    stride: usize,
+}
+
+impl<'a> DoubleBuffer<'a> {
+    /// Buffers for a `size` frame, or `None` if either is shorter than
+    /// [`frame_bytes`].
+    ///
+    /// Both start as they are; clear them to the panel's content (usually
+    /// white) if it is known.
+    pub fn new(front: &'a mut [u8], back: &'a mut [u8], size: Size) -> Option<Self> {
+        let bytes = frame_bytes(size);
+        if front.len() < bytes || back.len() < bytes {
+            return None;
+        }
+        Some(Self {
+            front,
+            back,
+            size,
+            stride: size.width.div_ceil(2) as usize,
+        })
+    }
+
+    /// Fill both buffers with `color`, e.g. after a full refresh to white.
+    pub fn reset(&mut self, color: Gray4) {
+        let byte = (color.luma() << 4) | color.luma();
+        let bytes = frame_bytes(self.size);
+        for buffer in [&mut *self.front, &mut *self.back] {
+            if let Some(frame) = buffer.get_mut(..bytes) {
+                frame.fill(byte);
+            }
+        }
+    }
+
+    fn index(&self, point: Point) -> Option<(usize, bool)> {
+        let x = u32::try_from(point.x).ok()?;
+        let y = u32::try_from(point.y).ok()?;
+        if x >= self.size.width || y >= self.size.height {
+            return None;
+        }
+        let index = (y as usize)
+            .saturating_mul(self.stride)
+            .saturating_add(x as usize / 2);
+        Some((index, x % 2 == 0))
+    }
+
+    /// Pixel of the back buffer (the frame being drawn).
+    pub fn pixel(&self, point: Point) -> Option<Gray4> {
+        let (index, high) = self.index(point)?;
+        let byte = *self.back.get(index)?;
+        Some(Gray4::new(if high { byte >> 4 } else { byte & 0x0F }))
+    }
+
+    /// Changed rectangles between the back and front buffers.
+    pub fn diff<const N: usize>(&self) -> DirtyRects<N> {
+        diff_tiles(self.size, |tx, ty| self.tile_differs(tx, ty))
+    }
+
+    /// Changed rectangles, comparing only the tiles that touch `regions`.
+    ///
+    /// For screens that know where they drew — a component render cache
+    /// reports the regions it redrew and skips the rest — this saves
+    /// comparing the untouched tiles, which cannot have changed.
+    pub fn diff_within<const N: usize>(&self, regions: &[Rectangle]) -> DirtyRects<N> {
+        diff_tiles(self.size, |tx, ty| {
+            let tile = Rectangle::new(
+                Point::new(
+                    to_i32(tx.saturating_mul(TILE)),
+                    to_i32(ty.saturating_mul(TILE)),
+                ),
+                Size::new(TILE, TILE),
+            );
+            regions
+                .iter()
+                .any(|r| !r.intersection(&tile).is_zero_sized())
+                && self.tile_differs(tx, ty)
+        })
+    }
+
+    fn tile_differs(&self, tx: u32, ty: u32) -> bool {
+        let tile_bytes = (TILE / 2) as usize;
+        let first = (ty.saturating_mul(TILE)) as usize;
+        let last = (ty.saturating_add(1).saturating_mul(TILE)).min(self.size.height) as usize;
+        let start = (tx as usize).saturating_mul(tile_bytes);
+        let end = start.saturating_add(tile_bytes).min(self.stride);
+        (first..last).any(|row| {
+            let offset = row.saturating_mul(self.stride);
+            let span = offset.saturating_add(start)..offset.saturating_add(end);
+            self.front.get(span.clone()) != self.back.get(span)
+        })
+    }
+
+    /// Draw the back buffer's pixels in `area` to `target`.
+    pub fn blit<D, C>(&self, area: &Rectangle, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        C: PixelColor + From<Gray4>,
+    {
+        let area = area.intersection(&self.bounding_box());
+        target.draw_iter(
+            area.points()
+                .filter_map(|p| self.pixel(p).map(|c| Pixel(p, C::from(c)))),
+        )
+    }
+
+    /// The back buffer is now on the panel: copy it to the front buffer.
+    pub fn present(&mut self) {
+        let bytes = frame_bytes(self.size);
+        if let (Some(front), Some(back)) = (self.front.get_mut(..bytes), self.back.get(..bytes)) {
+            front.copy_from_slice(back);
+        }
+    }
+}
+
+impl OriginDimensions for DoubleBuffer<'_> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for DoubleBuffer<'_> {
+    type Color = Gray4;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let Some((index, high)) = self.index(point) else {
+                continue;
+            };
+            if let Some(byte) = self.back.get_mut(index) {
+                *byte = if high {
+                    (*byte & 0x0F) | (color.luma() << 4)
+                } else {
+                    (*byte & 0xF0) | color.luma()
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::arithmetic_side_effects,
+    clippy::many_single_char_names
+)]
+mod tests {
+    use super::*;
+    use embedded_graphics::primitives::PrimitiveStyle;
+
+    fn rect(x: i32, y: i32, w: u32, h: u32) -> Rectangle {
+        Rectangle::new(Point::new(x, y), Size::new(w, h))
+    }
+
+    fn fill<D: DrawTarget<Color = Gray4, Error = Infallible>>(d: &mut D, r: Rectangle, c: Gray4) {
+        r.into_styled(PrimitiveStyle::with_fill(c)).draw(d).unwrap();
+    }
+
+    #[test]
+    fn runs_merge_across_rows() {
+        // Tiles (1..3, 0..2) changed: one 16x16 rectangle.
+        let dirty: DirtyRects<4> =
+            diff_tiles(Size::new(64, 64), |tx, ty| (1..3).contains(&tx) && ty < 2);
+        assert_eq!(dirty.rects(), [rect(8, 0, 16, 16)]);
+        assert_eq!(dirty.dirty_pixels(), 256);
+        assert!(!dirty.collapsed());
+    }
+
+    #[test]
+    fn separate_regions_stay_separate() {
+        let dirty: DirtyRects<4> = diff_tiles(Size::new(64, 64), |tx, ty| {
+            (tx, ty) == (0, 0) || (tx, ty) == (5, 6)
+        });
+        assert_eq!(dirty.rects(), [rect(0, 0, 8, 8), rect(40, 48, 8, 8)]);
+        assert_eq!(dirty.bounding_box(), Some(rect(0, 0, 48, 56)));
+    }
+
+    #[test]
+    fn too_many_regions_collapse_to_bounding_box() {
+        let dirty: DirtyRects<2> = diff_tiles(Size::new(64, 64), |tx, ty| tx == ty);
+        assert!(dirty.collapsed());
+        assert_eq!(dirty.rects(), [rect(0, 0, 64, 64)]);
+    }
+
+    #[test]
+    fn edge_tiles_are_clipped() {
+        let dirty: DirtyRects<4> = diff_tiles(Size::new(20, 10), |_, _| true);
+        assert_eq!(dirty.rects(), [rect(0, 0, 20, 10)]);
+        let update = dirty.update(ContentHint::Text);
+        assert_eq!(update.dirty_pixels, 200);
+    }
+
+    #[test]
+    fn double_buffer_finds_drawn_changes() {
+        let size = Size::new(33, 17);
+        let mut front = vec![0u8; frame_bytes(size)];
+        let mut back = vec![0u8; frame_bytes(size)];
+        let mut frame = DoubleBuffer::new(&mut front, &mut back, size).unwrap();
+        frame.reset(Gray4::WHITE);
+        assert!(frame.diff::<4>().is_empty());
+
+        fill(&mut frame, rect(32, 16, 1, 1), Gray4::BLACK);
+        fill(&mut frame, rect(9, 1, 2, 2), Gray4::new(0x6));
+        assert_eq!(frame.pixel(Point::new(32, 16)), Some(Gray4::BLACK));
+        assert_eq!(frame.pixel(Point::new(31, 16)), Some(Gray4::WHITE));
+        let dirty: DirtyRects<4> = frame.diff();
+        assert_eq!(dirty.rects(), [rect(8, 0, 8, 8), rect(32, 16, 1, 1)]);
+
+        frame.present();
+        assert!(frame.diff::<4>().is_empty());
+
+        // Redrawing the same content is not a change.
+        fill(&mut frame, rect(9, 1, 2, 2), Gray4::new(0x6));
+        assert!(frame.diff::<4>().is_empty());
+    }
+
+    #[test]
+    fn diff_within_ignores_changes_outside_the_regions() {
+        let size = Size::new(32, 16);
+        let mut front = vec![0u8; frame_bytes(size)];
+        let mut back = vec![0u8; frame_bytes(size)];
+        let mut frame = DoubleBuffer::new(&mut front, &mut back, size).unwrap();
+        frame.reset(Gray4::WHITE);
+        fill(&mut frame, rect(2, 2, 2, 2), Gray4::BLACK);
+        fill(&mut frame, rect(26, 10, 2, 2), Gray4::BLACK);
+
+        let dirty: DirtyRects<4> = frame.diff_within(&[rect(20, 9, 10, 4)]);
+        assert_eq!(dirty.rects(), [rect(24, 8, 8, 8)]);
+        assert!(frame.diff_within::<4>(&[]).is_empty());
+        assert_eq!(frame.diff::<4>().rects().len(), 2);
+    }
+
+    #[test]
+    fn blit_copies_only_the_area() {
+        use embedded_graphics::mock_display::MockDisplay;
+
+        let size = Size::new(16, 16);
+        let mut front = [0u8; 128];
+        let mut back = [0u8; 128];
+        let mut frame = DoubleBuffer::new(&mut front, &mut back, size).unwrap();
+        frame.reset(Gray4::WHITE);
+        fill(&mut frame, rect(0, 0, 16, 16), Gray4::BLACK);
+
+        let mut target = MockDisplay::<Gray4>::new();
+        frame.blit(&rect(4, 4, 2, 2), &mut target).unwrap();
+        assert_eq!(target.affected_area(), rect(4, 4, 2, 2));
+        assert_eq!(target.get_pixel(Point::new(5, 5)), Some(Gray4::BLACK));
+    }
+
+    #[test]
+    fn short_buffers_are_rejected() {
+        let mut front = [0u8; 10];
+        let mut back = [0u8; 200];
+        assert!(DoubleBuffer::new(&mut front, &mut back, Size::new(20, 10)).is_none());
+    }
+}
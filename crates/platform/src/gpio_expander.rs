@@ -0,0 +1,194 @@
+//! I2C GPIO expander abstraction
+//!
+//! Later board revisions may move the buttons off-chip onto an I2C port
+//! expander (PCA9555, TCA9535, MCP23017 and similar).  Those parts all look
+//! the same to the input layer: an open-drain interrupt line that goes low
+//! when an input changes, and an input-port register holding the pin levels.
+//! [`GpioExpander`] captures exactly that, so the input driver does not care
+//! which part is fitted; [`RegisterExpander`] implements it for any expander
+//! whose input port can be read with one register read.
+
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::I2c;
+
+/// A GPIO expander as seen by the input layer: an interrupt line plus a
+/// register read of all input levels.
+pub trait GpioExpander {
+    /// Bus or interrupt-pin error.
+    type Error: core::fmt::Debug;
+
+    /// Wait until the expander signals an input change.
+    fn wait_for_interrupt(&mut self)
+        -> impl core::future::Future<Output = Result<(), Self::Error>>;
+
+    /// Read all input levels, bit `n` = pin `n` (1 = high).
+    ///
+    /// On most parts this also clears the pending interrupt.
+    fn read_inputs(&mut self) -> impl core::future::Future<Output = Result<u16, Self::Error>>;
+}
+
+/// PCA9555 / TCA9535 input port 0 register (port 1 follows at `0x01`).
+pub const PCA9555_INPUT_PORT0: u8 = 0x00;
+
+/// MCP23017 GPIOA register with `IOCON.BANK = 0` (GPIOB follows at `0x13`).
+pub const MCP23017_GPIOA: u8 = 0x12;
+
+/// Error from a [`RegisterExpander`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ExpanderError<I, P> {
+    /// The I2C read failed.
+    I2c(I),
+    /// Waiting on the interrupt pin failed.
+    Interrupt(P),
+}
+
+/// [`GpioExpander`] for parts with an auto-incrementing input-port register.
+///
+/// Reads `ports` (1 or 2) bytes starting at `input_register`; port 0 is the
+/// low byte.  The interrupt line is active-low (open drain), so the wait
+/// returns as soon as it is low — including when a change is already
+/// pending.
+pub struct RegisterExpander<I, P> {
+    i2c: I,
+    int: P,
+    address: u8,
+    input_register: u8,
+    ports: usize,
+}
+
+impl<I: I2c, P: Wait> RegisterExpander<I, P> {
+    /// A 16-bit expander at `address` whose input ports start at
+    /// `input_register` (e.g. [`PCA9555_INPUT_PORT0`]).
+    pub fn new(i2c: I, int: P, address: u8, input_register: u8) -> Self {
+        Self {
+            i2c,
+            int,
+            address,
+            input_register,
+            ports: 2,
+        }
+    }
+
+    /// Read only the first 8-bit port (PCA9554, TCA9534, MCP23008).
+    #[must_use]
+    pub fn single_port(mut self) -> Self {
+        self.ports = 1;
+        self
+    }
+
+    /// Release the bus and interrupt pin.
+    pub fn release(self) -> (I, P) {
+        (self.i2c, self.int)
+    }
+}
+
+impl<I: I2c, P: Wait> GpioExpander for RegisterExpander<I, P> {
+    type Error = ExpanderError<I::Error, P::Error>;
+
+    async fn wait_for_interrupt(&mut self) -> Result<(), Self::Error> {
+        self.int
+            .wait_for_low()
+            .await
+            .map_err(ExpanderError::Interrupt)
+    }
+
+    async fn read_inputs(&mut self) -> Result<u16, Self::Error> {
+        let mut buf = [0u8; 2];
+        let port_bytes = buf.get_mut(..self.ports).unwrap_or(&mut []);
+        self.i2c
+            .write_read(self.address, &[self.input_register], port_bytes)
+            .await
+            .map_err(ExpanderError::I2c)?;
+        Ok(u16::from_le_bytes(buf))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use embedded_hal::i2c::{ErrorKind, ErrorType, Operation};
+
+    /// Expander bus that answers every read with `ports` and records the
+    /// register address written before it.
+    struct Bus {
+        ports: [u8; 2],
+        last: Option<(u8, u8, usize)>,
+    }
+
+    impl ErrorType for Bus {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for Bus {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            let mut register = 0;
+            for op in operations {
+                match op {
+                    Operation::Write(bytes) => register = bytes.first().copied().unwrap_or(0),
+                    Operation::Read(buf) => {
+                        for (b, p) in buf.iter_mut().zip(self.ports) {
+                            *b = p;
+                        }
+                        self.last = Some((address, register, buf.len()));
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    struct Int;
+
+    impl embedded_hal::digital::ErrorType for Int {
+        type Error = core::convert::Infallible;
+    }
+
+    impl Wait for Int {
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_both_ports_little_endian() {
+        let bus = Bus {
+            ports: [0x34, 0x12],
+            last: None,
+        };
+        let mut exp = RegisterExpander::new(bus, Int, 0x20, PCA9555_INPUT_PORT0);
+        exp.wait_for_interrupt().await.unwrap();
+        assert_eq!(exp.read_inputs().await.unwrap(), 0x1234);
+        let (bus, _) = exp.release();
+        assert_eq!(bus.last, Some((0x20, PCA9555_INPUT_PORT0, 2)));
+    }
+
+    #[tokio::test]
+    async fn single_port_reads_one_byte() {
+        let bus = Bus {
+            ports: [0x34, 0x12],
+            last: None,
+        };
+        let mut exp = RegisterExpander::new(bus, Int, 0x27, MCP23017_GPIOA).single_port();
+        assert_eq!(exp.read_inputs().await.unwrap(), 0x0034);
+        let (bus, _) = exp.release();
+        assert_eq!(bus.last, Some((0x27, MCP23017_GPIOA, 1)));
+    }
+}
@@ -0,0 +1,343 @@
+//! Hardware-in-the-loop test protocol.
+//!
+//! `cargo xtask hil-test` flashes the `hil_test` firmware example and drives
+//! it through a [`HilMailbox`]: a fixed block of words the firmware exports
+//! under the [`MAILBOX_SYMBOL`] name.  The host writes a command into it over
+//! SWD (the same debug-port memory access RTT is built on), bumps `seq`, and
+//! polls until the firmware echoes `seq` into `ack`.  The firmware also logs
+//! every command over defmt so a failing run can be read back from RTT.
+//!
+//! A mailbox instead of an RTT down channel keeps the host side to plain
+//! `probe-rs read` / `probe-rs write` calls, which need no long-lived session.
+//!
+//! # Layout
+//!
+//! ```text
+//! word  field     written by
+//! 0     magic     firmware, once ready (MAILBOX_MAGIC)
+//! 1     seq       host, last — publishes the command
+//! 2     command   host (HilCommand code)
+//! 3..5  args      host
+//! 5     ack       firmware, last — equals seq when the response is valid
+//! 6     status    firmware (HilStatus)
+//! 7..9  result    firmware, meaning depends on the command
+//! ```
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+/// Linker symbol of the mailbox in the HIL firmware.
+pub const MAILBOX_SYMBOL: &str = "HIL_MAILBOX";
+
+/// Written to word 0 once the firmware is ready for commands (`"HIL1"`).
+pub const MAILBOX_MAGIC: u32 = 0x4849_4C31;
+
+/// Mailbox size in 32-bit words.
+pub const MAILBOX_WORDS: u32 = 9;
+
+/// Byte offsets of the mailbox fields, for the host side.
+pub mod offset {
+    /// [`MAILBOX_MAGIC`](super::MAILBOX_MAGIC) once the firmware is ready.
+    pub const MAGIC: u32 = 0;
+    /// Host sequence number.
+    pub const SEQ: u32 = 4;
+    /// Command code followed by its two arguments.
+    pub const COMMAND: u32 = 8;
+    /// Firmware acknowledgement, followed by status and two result words.
+    pub const ACK: u32 = 20;
+}
+
+/// Screens the HIL firmware can render on request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HilScreen {
+    /// Boot splash.
+    Splash,
+    /// Panel test pattern (gradients and geometry).
+    TestPattern,
+}
+
+impl HilScreen {
+    /// Every screen, by id.
+    pub const ALL: [Self; 2] = [Self::Splash, Self::TestPattern];
+
+    /// Wire id.
+    pub const fn id(self) -> u32 {
+        match self {
+            Self::Splash => 0,
+            Self::TestPattern => 1,
+        }
+    }
+
+    /// Screen for a wire id.
+    pub fn from_id(id: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.id() == id)
+    }
+}
+
+/// A request from the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HilCommand {
+    /// Liveness check.  Result: `[PROTOCOL_VERSION, uptime_ms]`.
+    Ping,
+    /// Render a screen and full-refresh the panel.
+    /// Result: `[framebuffer checksum, refresh_ms]`.
+    RenderScreen(HilScreen),
+    /// Checksum the current framebuffer without refreshing.
+    /// Result: `[framebuffer checksum, 0]`.
+    FramebufferChecksum,
+    /// Play a full-scale sine through the DAC.
+    /// Result: `[frames written, DMA underruns]`.
+    PlaySine {
+        /// Tone frequency.
+        freq_hz: u32,
+        /// Playback length.
+        duration_ms: u32,
+    },
+    /// Run the DAC → ADC loopback self-test
+    /// ([`audio_loopback`](crate::audio_loopback)).
+    /// Result: `[fault mask, left reference level]`; a zero mask passed.
+    AudioLoopback,
+}
+
+/// Protocol revision reported by [`HilCommand::Ping`].
+pub const PROTOCOL_VERSION: u32 = 1;
+
+impl HilCommand {
+    /// `[code, arg0, arg1]` as written to the mailbox.
+    pub const fn encode(self) -> [u32; 3] {
+        match self {
+            Self::Ping => [1, 0, 0],
+            Self::RenderScreen(screen) => [2, screen.id(), 0],
+            Self::FramebufferChecksum => [3, 0, 0],
+            Self::PlaySine {
+                freq_hz,
+                duration_ms,
+            } => [4, freq_hz, duration_ms],
+            Self::AudioLoopback => [5, 0, 0],
+        }
+    }
+
+    /// Inverse of [`encode`](Self::encode); `None` for unknown codes or ids.
+    pub fn decode([code, arg0, arg1]: [u32; 3]) -> Option<Self> {
+        match code {
+            1 => Some(Self::Ping),
+            2 => HilScreen::from_id(arg0).map(Self::RenderScreen),
+            3 => Some(Self::FramebufferChecksum),
+            4 => Some(Self::PlaySine {
+                freq_hz: arg0,
+                duration_ms: arg1,
+            }),
+            5 => Some(Self::AudioLoopback),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of one command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HilStatus {
+    /// The command ran; the result words are valid.
+    Ok,
+    /// The command ran and failed (e.g. a display error).
+    Failed,
+    /// This firmware cannot run the command yet (e.g. audio not wired).
+    Unsupported,
+    /// The command code or argument was not recognised.
+    UnknownCommand,
+}
+
+impl HilStatus {
+    /// Wire value (0 is reserved for "no response").
+    pub const fn code(self) -> u32 {
+        match self {
+            Self::Ok => 1,
+            Self::Failed => 2,
+            Self::Unsupported => 3,
+            Self::UnknownCommand => 4,
+        }
+    }
+
+    /// Status for a wire value.
+    pub fn from_code(code: u32) -> Option<Self> {
+        [
+            Self::Ok,
+            Self::Failed,
+            Self::Unsupported,
+            Self::UnknownCommand,
+        ]
+        .into_iter()
+        .find(|s| s.code() == code)
+    }
+}
+
+/// The firmware's answer to one command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HilResponse {
+    /// Whether the command ran.
+    pub status: HilStatus,
+    /// Command-specific values (see [`HilCommand`]).
+    pub result: [u32; 2],
+}
+
+impl HilResponse {
+    /// Successful response.
+    pub const fn ok(result: [u32; 2]) -> Self {
+        Self {
+            status: HilStatus::Ok,
+            result,
+        }
+    }
+
+    /// Response without result values.
+    pub const fn status(status: HilStatus) -> Self {
+        Self {
+            status,
+            result: [0, 0],
+        }
+    }
+
+    /// Parse `[ack, status, result0, result1]` as read from [`offset::ACK`];
+    /// `None` until the firmware acknowledged `seq`.
+    pub fn from_words(seq: u32, [ack, status, r0, r1]: [u32; 4]) -> Option<Self> {
+        if ack != seq {
+            return None;
+        }
+        Some(Self {
+            status: HilStatus::from_code(status)?,
+            result: [r0, r1],
+        })
+    }
+}
+
+/// The command mailbox, exported by the HIL firmware as [`MAILBOX_SYMBOL`].
+///
+/// Every field is an `AtomicU32`, so the layout is nine plain words and the
+/// debugger's writes are never cached in a register.
+#[repr(C)]
+pub struct HilMailbox {
+    magic: AtomicU32,
+    seq: AtomicU32,
+    command: [AtomicU32; 3],
+    ack: AtomicU32,
+    status: AtomicU32,
+    result: [AtomicU32; 2],
+}
+
+impl HilMailbox {
+    /// An empty mailbox (not yet ready).
+    pub const fn new() -> Self {
+        Self {
+            magic: AtomicU32::new(0),
+            seq: AtomicU32::new(0),
+            command: [AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0)],
+            ack: AtomicU32::new(0),
+            status: AtomicU32::new(0),
+            result: [AtomicU32::new(0), AtomicU32::new(0)],
+        }
+    }
+
+    /// Tell the host the firmware accepts commands.
+    pub fn mark_ready(&self) {
+        self.magic.store(MAILBOX_MAGIC, Ordering::Release);
+    }
+
+    /// The newest command if the host posted one since `last_seq`.
+    ///
+    /// Returns the new sequence number and the decoded command (`None` for
+    /// an unknown code, which should be answered with
+    /// [`HilStatus::UnknownCommand`]).
+    pub fn take(&self, last_seq: u32) -> Option<(u32, Option<HilCommand>)> {
+        let seq = self.seq.load(Ordering::Acquire);
+        if seq == last_seq {
+            return None;
+        }
+        let words = [
+            self.command[0].load(Ordering::Relaxed),
+            self.command[1].load(Ordering::Relaxed),
+            self.command[2].load(Ordering::Relaxed),
+        ];
+        Some((seq, HilCommand::decode(words)))
+    }
+
+    /// Publish the response to command `seq`.
+    pub fn complete(&self, seq: u32, response: HilResponse) {
+        self.status.store(response.status.code(), Ordering::Relaxed);
+        self.result[0].store(response.result[0], Ordering::Relaxed);
+        self.result[1].store(response.result[1], Ordering::Relaxed);
+        self.ack.store(seq, Ordering::Release);
+    }
+}
+
+impl Default for HilMailbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// FNV-1a checksum of a framebuffer, as reported by the HIL firmware.
+pub fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash: u32, &b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_round_trip_through_the_wire_encoding() {
+        let commands = [
+            HilCommand::Ping,
+            HilCommand::RenderScreen(HilScreen::TestPattern),
+            HilCommand::FramebufferChecksum,
+            HilCommand::PlaySine {
+                freq_hz: 1_000,
+                duration_ms: 500,
+            },
+            HilCommand::AudioLoopback,
+        ];
+        for command in commands {
+            assert_eq!(HilCommand::decode(command.encode()), Some(command));
+        }
+        assert_eq!(HilCommand::decode([2, 99, 0]), None);
+        assert_eq!(HilCommand::decode([0, 0, 0]), None);
+    }
+
+    #[test]
+    fn mailbox_hands_each_command_over_once() {
+        let mailbox = HilMailbox::new();
+        assert!(mailbox.take(0).is_none());
+
+        // What the host does over SWD: command words first, then seq.
+        for (word, value) in mailbox.command.iter().zip(HilCommand::Ping.encode()) {
+            word.store(value, Ordering::Relaxed);
+        }
+        mailbox.seq.store(1, Ordering::Release);
+
+        assert_eq!(mailbox.take(0), Some((1, Some(HilCommand::Ping))));
+        assert!(mailbox.take(1).is_none());
+
+        mailbox.complete(1, HilResponse::ok([PROTOCOL_VERSION, 42]));
+        let words = [
+            mailbox.ack.load(Ordering::Acquire),
+            mailbox.status.load(Ordering::Relaxed),
+            mailbox.result[0].load(Ordering::Relaxed),
+            mailbox.result[1].load(Ordering::Relaxed),
+        ];
+        assert_eq!(HilResponse::from_words(2, words), None);
+        assert_eq!(
+            HilResponse::from_words(1, words),
+            Some(HilResponse::ok([PROTOCOL_VERSION, 42]))
+        );
+    }
+
+    #[test]
+    fn checksum_is_fnv1a() {
+        assert_eq!(checksum(b""), 0x811c_9dc5);
+        assert_eq!(checksum(b"foobar"), 0xbf9c_f968);
+    }
+}
@@ -9,6 +9,40 @@ pub trait InputDevice {
     fn poll_event(&mut self) -> Option<InputEvent>;
 }
 
+/// Input device that records when each event happened.
+///
+/// Timestamps come from the hardware tick (Embassy `Instant`) on target and
+/// from the emulator clock on desktop, in milliseconds on the same timeline
+/// the display uses, so they can be fed straight into a
+/// [`LatencyTracker`](crate::latency::LatencyTracker).
+pub trait TimestampedInput: InputDevice {
+    /// Wait for the next event together with its timestamp.
+    fn wait_for_timestamped_event(
+        &mut self,
+    ) -> impl core::future::Future<Output = TimestampedEvent>;
+
+    /// Poll for an event together with its timestamp (non-blocking).
+    fn poll_timestamped_event(&mut self) -> Option<TimestampedEvent>;
+}
+
+/// An [`InputEvent`] and the time it was generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimestampedEvent {
+    /// The event.
+    pub event: InputEvent,
+    /// When the event was generated, in milliseconds since boot (hardware)
+    /// or emulator start / virtual-clock time (emulator).
+    pub at_ms: u64,
+}
+
+impl TimestampedEvent {
+    /// Pair `event` with its timestamp.
+    pub const fn new(event: InputEvent, at_ms: u64) -> Self {
+        Self { event, at_ms }
+    }
+}
+
 /// Input events from buttons and encoders
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -0,0 +1,255 @@
+//! Interaction latency measurement — input → render → refresh complete.
+//!
+//! An interaction starts when an input event arrives, reaches *render* when
+//! the UI first draws in response, and ends when the display refresh showing
+//! the result completes.  [`LatencyTracker`] records those three timestamps
+//! and keeps last / worst / over-budget statistics against
+//! [`LATENCY_BUDGET_MS`].
+//!
+//! Timestamps are plain milliseconds on one timeline: the Embassy tick on
+//! hardware, the emulator clock (wall or virtual) on desktop.  Inputs that
+//! arrive while an interaction is still in flight are folded into it, so the
+//! latency is always measured from the oldest unanswered input.
+//!
+//! # Example
+//!
+//! ```
+//! use platform::latency::LatencyTracker;
+//!
+//! let mut tracker = LatencyTracker::new();
+//! tracker.on_input(1_000);
+//! tracker.on_render(1_012);
+//! let latency = tracker.on_refresh_complete(1_300).unwrap();
+//! assert_eq!(latency.input_to_render_ms(), 12);
+//! assert_eq!(latency.total_ms(), 300);
+//! assert!(latency.within(tracker.budget_ms()));
+//! ```
+
+/// Interaction latency budget: input to refresh complete (ms).
+pub const LATENCY_BUDGET_MS: u64 = 350;
+
+/// Timing of one completed interaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct InteractionLatency {
+    /// Input event timestamp.
+    pub input_ms: u64,
+    /// First draw after the input (equal to `complete_ms` if nothing was
+    /// drawn, e.g. a refresh without new content).
+    pub render_ms: u64,
+    /// Refresh completion timestamp.
+    pub complete_ms: u64,
+}
+
+impl InteractionLatency {
+    /// Input to first draw (ms).
+    pub const fn input_to_render_ms(&self) -> u64 {
+        self.render_ms.saturating_sub(self.input_ms)
+    }
+
+    /// First draw to refresh complete (ms).
+    pub const fn render_to_complete_ms(&self) -> u64 {
+        self.complete_ms.saturating_sub(self.render_ms)
+    }
+
+    /// Input to refresh complete (ms).
+    pub const fn total_ms(&self) -> u64 {
+        self.complete_ms.saturating_sub(self.input_ms)
+    }
+
+    /// Whether the interaction finished within `budget_ms`.
+    pub const fn within(&self, budget_ms: u64) -> bool {
+        self.total_ms() <= budget_ms
+    }
+}
+
+/// Tracks input → render → refresh-complete latency per interaction.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LatencyTracker {
+    budget_ms: u64,
+    pending_input_ms: Option<u64>,
+    pending_render_ms: Option<u64>,
+    last: Option<InteractionLatency>,
+    worst: Option<InteractionLatency>,
+    count: u32,
+    over_budget: u32,
+}
+
+impl LatencyTracker {
+    /// Tracker with the default [`LATENCY_BUDGET_MS`].
+    pub const fn new() -> Self {
+        Self::with_budget(LATENCY_BUDGET_MS)
+    }
+
+    /// Tracker with a custom budget.
+    pub const fn with_budget(budget_ms: u64) -> Self {
+        Self {
+            budget_ms,
+            pending_input_ms: None,
+            pending_render_ms: None,
+            last: None,
+            worst: None,
+            count: 0,
+            over_budget: 0,
+        }
+    }
+
+    /// Budget interactions are checked against (ms).
+    pub const fn budget_ms(&self) -> u64 {
+        self.budget_ms
+    }
+
+    /// An input event arrived at `at_ms`.
+    ///
+    /// Starts a new interaction unless one is already waiting for its
+    /// refresh, in which case the earlier timestamp is kept.
+    pub fn on_input(&mut self, at_ms: u64) {
+        if self.pending_input_ms.is_none() {
+            self.pending_input_ms = Some(at_ms);
+        }
+    }
+
+    /// The UI drew at `at_ms`.  Only the first draw after an input counts.
+    pub fn on_render(&mut self, at_ms: u64) {
+        if self.pending_input_ms.is_some() && self.pending_render_ms.is_none() {
+            self.pending_render_ms = Some(at_ms);
+        }
+    }
+
+    /// A display refresh completed at `at_ms`.
+    ///
+    /// Closes the pending interaction, if any, and returns its timing.
+    pub fn on_refresh_complete(&mut self, at_ms: u64) -> Option<InteractionLatency> {
+        let input_ms = self.pending_input_ms.take()?;
+        let render_ms = self.pending_render_ms.take().unwrap_or(at_ms);
+        let latency = InteractionLatency {
+            input_ms,
+            render_ms,
+            complete_ms: at_ms,
+        };
+        self.count = self.count.saturating_add(1);
+        if !latency.within(self.budget_ms) {
+            self.over_budget = self.over_budget.saturating_add(1);
+        }
+        match self.worst {
+            Some(w) if w.total_ms() >= latency.total_ms() => {}
+            _ => self.worst = Some(latency),
+        }
+        self.last = Some(latency);
+        Some(latency)
+    }
+
+    /// Whether an input is waiting for its refresh.
+    pub const fn is_pending(&self) -> bool {
+        self.pending_input_ms.is_some()
+    }
+
+    /// Most recent completed interaction.
+    pub const fn last(&self) -> Option<InteractionLatency> {
+        self.last
+    }
+
+    /// Slowest completed interaction.
+    pub const fn worst(&self) -> Option<InteractionLatency> {
+        self.worst
+    }
+
+    /// Number of completed interactions.
+    pub const fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Number of completed interactions that exceeded the budget.
+    pub const fn over_budget_count(&self) -> u32 {
+        self.over_budget
+    }
+
+    /// Forget all statistics and any pending interaction.
+    pub fn reset(&mut self) {
+        *self = Self::with_budget(self.budget_ms);
+    }
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#[allow(clippy::arithmetic_side_effects)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_each_phase() {
+        let mut t = LatencyTracker::new();
+        t.on_input(100);
+        assert!(t.is_pending());
+        t.on_render(130);
+        t.on_render(180); // later draws in the same frame are ignored
+        let l = t.on_refresh_complete(400).unwrap();
+        assert_eq!(l.input_to_render_ms(), 30);
+        assert_eq!(l.render_to_complete_ms(), 270);
+        assert_eq!(l.total_ms(), 300);
+        assert!(!t.is_pending());
+        assert_eq!(t.count(), 1);
+        assert_eq!(t.over_budget_count(), 0);
+    }
+
+    #[test]
+    fn refresh_without_input_is_not_an_interaction() {
+        let mut t = LatencyTracker::new();
+        t.on_render(10);
+        assert!(t.on_refresh_complete(300).is_none());
+        assert_eq!(t.count(), 0);
+    }
+
+    #[test]
+    fn coalesced_inputs_measure_from_the_first() {
+        let mut t = LatencyTracker::new();
+        t.on_input(0);
+        t.on_input(50);
+        t.on_input(90);
+        assert_eq!(t.on_refresh_complete(320).unwrap().total_ms(), 320);
+    }
+
+    #[test]
+    fn missing_render_counts_as_render_at_completion() {
+        let mut t = LatencyTracker::new();
+        t.on_input(0);
+        let l = t.on_refresh_complete(260).unwrap();
+        assert_eq!(l.input_to_render_ms(), 260);
+        assert_eq!(l.render_to_complete_ms(), 0);
+    }
+
+    #[test]
+    fn tracks_worst_and_over_budget() {
+        let mut t = LatencyTracker::new();
+        for (input, done) in [(0, 300), (1_000, 3_100), (5_000, 5_320)] {
+            t.on_input(input);
+            t.on_render(input + 5);
+            t.on_refresh_complete(done);
+        }
+        assert_eq!(t.count(), 3);
+        assert_eq!(t.over_budget_count(), 1);
+        assert_eq!(t.worst().unwrap().total_ms(), 2_100);
+        assert_eq!(t.last().unwrap().total_ms(), 320);
+
+        t.reset();
+        assert_eq!(t.count(), 0);
+        assert!(t.worst().is_none());
+        assert_eq!(t.budget_ms(), LATENCY_BUDGET_MS);
+    }
+
+    #[test]
+    fn custom_budget() {
+        let mut t = LatencyTracker::with_budget(100);
+        t.on_input(0);
+        let l = t.on_refresh_complete(150).unwrap();
+        assert!(!l.within(t.budget_ms()));
+        assert_eq!(t.over_budget_count(), 1);
+    }
+}
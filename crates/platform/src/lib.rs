@@ -19,16 +19,28 @@
 //!
 //! ## High-Level Peripherals
 //! - [`DisplayDriver`] - E-ink display control
+//! - [`DisplayMux`] - Route one `DisplayDriver` to either of two panels
 //! - [`InputDevice`] - Button and rotary encoder input
+//! - [`LatencyTracker`] - Input → refresh-complete interaction latency
+//! - [`boot_timing`] - Boot phase timestamps and deferred subsystem start-up
+//! - [`crash_loop`] - Boot-loop detection and safe mode
+//! - [`feedback`] - Click/haptic confirmation of input events
 //! - [`AudioCodec`] - Audio output
 //! - [`Storage`] - File system access
 //! - [`BluetoothAdapter`] - Wireless connectivity
+//! - [`Rtc`] - Wall-clock time, alarms and backup registers
 //!
 //! ## Mid-Level Peripherals
 //! - [`gpio`] - Pin control with typestate
+//! - [`gpio_expander`] - I2C port expanders (interrupt line + input register)
 //! - [`peripheral`] - SPI, I2C, UART abstractions
 //! - [`dma`] - DMA transfer management
 //! - [`power`] - Power management
+//! - [`refresh_policy`] - Per-update waveform (DU/DU4/GC16) selection
+//! - [`frame_diff`] - Double-buffered frames and changed-rectangle diffing
+//! - [`diagnostics`] - Display, SD card and battery self-test
+//! - [`dropout_journal`] - Audio underrun/overrun log with concurrent activity
+//! - [`soul_config`] - Power-user overrides from `soul.toml`
 //!
 //! # Features
 //!
@@ -76,30 +88,65 @@
 pub mod asset_store;
 pub mod audio;
 pub mod audio_config;
+pub mod audio_loopback;
 pub mod audio_sequencer;
 pub mod es9038q2m;
 pub mod bq25895;
 pub mod audio_types;
 pub mod bluetooth;
+pub mod boot_timing;
 pub mod clock_config;
 pub mod config;
+pub mod crash_loop;
+pub mod diagnostics;
 pub mod display;
+pub mod display_mux;
 pub mod dma;
 pub mod dma_safety;
+pub mod dropout_journal;
+pub mod feedback;
+pub mod frame_diff;
 pub mod gpio;
+pub mod gpio_expander;
+pub mod hil;
 pub mod input;
+pub mod latency;
+pub mod memory_budget;
 pub mod mpu;
+pub mod output_profile;
 pub mod peripheral;
+pub mod playback_speed;
 pub mod power;
 pub mod qspi_config;
+pub mod refresh_policy;
+pub mod rtc;
 pub mod sdram;
+pub mod silence_trim;
+pub mod smoke;
+pub mod soul_config;
 pub mod soul_library;
 pub mod storage;
 pub mod storage_config;
+pub mod toml_subset;
+
+#[cfg(feature = "std")]
+pub mod rtc_system;
 
 #[cfg(feature = "std")]
 pub mod storage_local;
 
+#[cfg(feature = "std")]
+pub mod storage_mem;
+
+#[cfg(feature = "std")]
+pub mod storage_fixture;
+
+#[cfg(feature = "std")]
+pub mod storage_fat;
+
+#[cfg(feature = "std")]
+pub mod storage_sim;
+
 #[cfg(not(feature = "std"))]
 pub mod storage_sdmmc;
 
@@ -107,17 +154,29 @@ pub mod storage_sdmmc;
 pub use asset_store::{AssetKey, AssetStore};
 pub use audio::{AudioCodec, AudioConfig, DsdMode, OversamplingFilter};
 pub use bluetooth::BluetoothAdapter;
-pub use display::{DisplayDriver, DisplayError, DisplayInfo, EinkDisplay, RefreshMode};
-pub use input::{Button, InputDevice, InputEvent};
+pub use display::{ColorMode, DisplayDriver, DisplayError, DisplayInfo, EinkDisplay, RefreshMode};
+pub use display_mux::{DisplayId, DisplayMux, MuxError};
+pub use input::{Button, InputDevice, InputEvent, TimestampedEvent, TimestampedInput};
+pub use latency::{InteractionLatency, LatencyTracker, LATENCY_BUDGET_MS};
+pub use output_profile::{Crossfeed, EqPreset, OutputProfile, OutputProfiles, ProfileError};
+pub use playback_speed::{ContentKind, PlaybackSpeed, SpeedScope};
+pub use silence_trim::SilenceTrimConfig;
+pub use refresh_policy::{ContentHint, RefreshPolicy};
+pub use rtc::{DateTime, Rtc, RtcError};
 pub use sdram::{ExternalRam, RamRegion};
 pub use soul_library::{
-    art_path, library_idx_path, library_meta_path, manifest_path, SOUL_ROOT,
+    art_path, library_browse_path, library_idx_path, library_meta_path, manifest_path,
+    output_profiles_path, queue_journal_path, tag_overrides_path, transfer_part_path,
+    transfer_state_path, LibraryIntegrity, LibrarySection, SOUL_ROOT,
 };
 pub use storage::{File, Storage};
 
 #[cfg(feature = "std")]
 pub use storage_local::LocalFileStorage;
 
+#[cfg(feature = "std")]
+pub use storage_mem::MemoryVolume;
+
 // Re-export GPIO types
 pub use gpio::{
     Analog, Input, InputPin, InterruptMode, InterruptPin, Output, OutputPin, Pin, PinGroup,
@@ -134,4 +193,6 @@ pub use peripheral::{
 pub use dma::{CircularBuffer, DmaBuffer, DmaBufferMut, DmaChannel, DmaTransfer};
 
 // Re-export power types
-pub use power::{Peripheral, PowerManager, PowerMonitor, SleepMode, VoltageScale, WakeSource};
+pub use power::{
+    BatteryLevel, Peripheral, PowerManager, PowerMonitor, SleepMode, VoltageScale, WakeSource,
+};
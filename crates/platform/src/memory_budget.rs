@@ -0,0 +1,320 @@
+//! Memory budget — intended size of every large static, per memory region.
+//!
+//! [`Region`] mirrors the `MEMORY` block of `memory.x` (a test parses the
+//! linker script and fails if the two drift apart).  [`ALLOCATIONS`] lists
+//! the big buffers the firmware reserves in each region; compile-time
+//! assertions below refuse to build when a region is over-committed, so a
+//! larger framebuffer or an extra task stack is caught before link time
+//! rather than as a flip-link stack fault on hardware.
+//!
+//! The budget covers intent, not the linked image: `cargo xtask
+//! memory-report` compares the actual section sizes of a firmware build
+//! against the same regions.
+
+use crate::dma_safety::{
+    AUDIO_DMA_BUFFER_BYTES, AXI_SRAM_BASE, AXI_SRAM_SIZE_BYTES, EXTSDRAM_BASE, EXTSDRAM_SIZE_BYTES,
+    FRAMEBUFFER_SIZE_BYTES, MIN_AXI_SRAM_HEADROOM_BYTES, SRAM4_BASE, SRAM4_SIZE_BYTES,
+    TOTAL_TASK_STACK_BYTES,
+};
+use crate::sdram::RamRegion;
+
+/// A memory region from `memory.x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    /// Internal flash, 2 MB.
+    Flash,
+    /// AXI SRAM (D1), 512 KB — `.data`, `.bss`, stack and `.axisram`.
+    AxiSram,
+    /// DTCM, 128 KB — CPU-only.
+    Dtcm,
+    /// SRAM1 (D2), 128 KB.
+    Sram1,
+    /// SRAM2 (D2), 128 KB.
+    Sram2,
+    /// SRAM3 (D2), 32 KB.
+    Sram3,
+    /// SRAM4 (D3), 64 KB — BDMA buffers.
+    Sram4,
+    /// External SDRAM via FMC, 32 MB.
+    ExtSdram,
+    /// External QSPI NOR flash, 16 MB.
+    Qspi,
+}
+
+impl Region {
+    /// Every region, in `memory.x` order.
+    pub const ALL: [Self; 9] = [
+        Self::Flash,
+        Self::AxiSram,
+        Self::Dtcm,
+        Self::Sram1,
+        Self::Sram2,
+        Self::Sram3,
+        Self::Sram4,
+        Self::ExtSdram,
+        Self::Qspi,
+    ];
+
+    /// Region name as written in `memory.x`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Flash => "FLASH",
+            Self::AxiSram => "RAM",
+            Self::Dtcm => "DTCM",
+            Self::Sram1 => "SRAM1",
+            Self::Sram2 => "SRAM2",
+            Self::Sram3 => "SRAM3",
+            Self::Sram4 => "SRAM4",
+            Self::ExtSdram => "EXTSDRAM",
+            Self::Qspi => "QSPI",
+        }
+    }
+
+    /// Start address.
+    pub const fn origin(self) -> u32 {
+        match self {
+            Self::Flash => 0x0800_0000,
+            Self::AxiSram => AXI_SRAM_BASE,
+            Self::Dtcm => 0x2000_0000,
+            Self::Sram1 => 0x3000_0000,
+            Self::Sram2 => 0x3002_0000,
+            Self::Sram3 => 0x3004_0000,
+            Self::Sram4 => SRAM4_BASE,
+            Self::ExtSdram => EXTSDRAM_BASE,
+            Self::Qspi => 0x9000_0000,
+        }
+    }
+
+    /// Length in bytes.
+    pub const fn length(self) -> usize {
+        match self {
+            Self::Flash => 2048 * 1024,
+            Self::AxiSram => AXI_SRAM_SIZE_BYTES,
+            Self::Dtcm | Self::Sram1 | Self::Sram2 => 128 * 1024,
+            Self::Sram3 => 32 * 1024,
+            Self::Sram4 => SRAM4_SIZE_BYTES,
+            Self::ExtSdram => EXTSDRAM_SIZE_BYTES,
+            Self::Qspi => 16 * 1024 * 1024,
+        }
+    }
+
+    /// The region containing `addr`, if any.
+    pub fn containing(addr: u32) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| {
+            let offset = addr.wrapping_sub(r.origin());
+            addr >= r.origin() && usize::try_from(offset).is_ok_and(|o| o < r.length())
+        })
+    }
+
+    /// Bytes reserved in this region by [`ALLOCATIONS`].
+    pub const fn budgeted(self) -> usize {
+        let mut total = 0usize;
+        let mut rest = ALLOCATIONS;
+        while let [first, tail @ ..] = rest {
+            if first.region as u8 == self as u8 {
+                total = total.saturating_add(first.bytes);
+            }
+            rest = tail;
+        }
+        total
+    }
+
+    /// Bytes left after the budgeted allocations (0 when over-committed).
+    pub const fn headroom(self) -> usize {
+        self.length().saturating_sub(self.budgeted())
+    }
+}
+
+/// One large static reservation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Allocation {
+    /// What the memory holds.
+    pub name: &'static str,
+    /// Where it lives.
+    pub region: Region,
+    /// Reserved size in bytes.
+    pub bytes: usize,
+}
+
+/// Every large static the firmware reserves.
+///
+/// Keep in step with the `#[link_section]` statics in `firmware/src/main.rs`
+/// and the SDRAM layout in [`RamRegion`].
+pub const ALLOCATIONS: &[Allocation] = &[
+    Allocation {
+        name: "display framebuffers (2 planes)",
+        region: Region::AxiSram,
+        bytes: FRAMEBUFFER_SIZE_BYTES * 2,
+    },
+    Allocation {
+        name: "audio SAI DMA ring",
+        region: Region::AxiSram,
+        bytes: AUDIO_DMA_BUFFER_BYTES * 2,
+    },
+    Allocation {
+        name: "Embassy task stacks",
+        region: Region::AxiSram,
+        bytes: TOTAL_TASK_STACK_BYTES,
+    },
+    Allocation {
+        name: "main stack (flip-link minimum)",
+        region: Region::AxiSram,
+        bytes: 32 * 1024,
+    },
+    Allocation {
+        name: ".data/.bss headroom",
+        region: Region::AxiSram,
+        bytes: MIN_AXI_SRAM_HEADROOM_BYTES,
+    },
+    Allocation {
+        // `library::TrackIndex` hot tables: 8 bytes per track plus 8 per
+        // interned name, for 8192 tracks.
+        name: "track index lookup tables",
+        region: Region::Sram1,
+        bytes: 8192 * 16,
+    },
+    Allocation {
+        name: "track index cache",
+        region: Region::ExtSdram,
+        bytes: RamRegion::LIBRARY_INDEX.len,
+    },
+    Allocation {
+        name: "album art cache",
+        region: Region::ExtSdram,
+        bytes: RamRegion::ALBUM_ART.len,
+    },
+    Allocation {
+        name: "audio decode scratch",
+        region: Region::ExtSdram,
+        bytes: RamRegion::AUDIO_SCRATCH.len,
+    },
+    Allocation {
+        name: "UI overflow",
+        region: Region::ExtSdram,
+        bytes: RamRegion::UI_OVERFLOW.len,
+    },
+];
+
+/// Whether every region's allocations fit in it.
+const fn all_regions_fit() -> bool {
+    let mut rest = Region::ALL.as_slice();
+    while let [region, tail @ ..] = rest {
+        if region.budgeted() > region.length() {
+            return false;
+        }
+        rest = tail;
+    }
+    true
+}
+
+const _: () = assert!(
+    Region::AxiSram.budgeted() <= Region::AxiSram.length(),
+    "AXI SRAM over-committed — move caches to SDRAM or shrink DMA buffers"
+);
+const _: () = assert!(
+    Region::ExtSdram.budgeted() <= Region::ExtSdram.length(),
+    "External SDRAM over-committed — shrink the RamRegion layout"
+);
+const _: () = assert!(
+    all_regions_fit(),
+    "A memory region is over-committed — see memory_budget::ALLOCATIONS"
+);
+
+/// Parse one region line of a `memory.x` `MEMORY` block, e.g.
+/// `RAM (xrw) : ORIGIN = 0x24000000, LENGTH = 512K`.
+///
+/// Returns `(name, origin, length)`; lengths take `K` and `M` suffixes.
+/// `None` for any other line.
+pub fn parse_region_line(line: &str) -> Option<(&str, u32, usize)> {
+    let line = line.split("/*").next()?.trim();
+    let (head, body) = line.split_once(':')?;
+    let name = head.split_whitespace().next()?;
+    let mut origin = None;
+    let mut length = None;
+    for field in body.split(',') {
+        let (key, value) = field.split_once('=')?;
+        let value = value.trim();
+        match key.trim() {
+            "ORIGIN" => {
+                let hex = value
+                    .strip_prefix("0x")
+                    .or_else(|| value.strip_prefix("0X"))?;
+                origin = u32::from_str_radix(hex, 16).ok();
+            }
+            "LENGTH" => length = parse_length(value),
+            _ => {}
+        }
+    }
+    Some((name, origin?, length?))
+}
+
+/// `512K`, `32M` or a plain byte count.
+fn parse_length(value: &str) -> Option<usize> {
+    let (digits, scale) = match value.as_bytes().last()? {
+        b'K' | b'k' => (value.get(..value.len().saturating_sub(1))?, 1024),
+        b'M' | b'm' => (value.get(..value.len().saturating_sub(1))?, 1024 * 1024),
+        _ => (value, 1),
+    };
+    digits.trim().parse::<usize>().ok()?.checked_mul(scale)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+mod tests {
+    use super::*;
+
+    const MEMORY_X: &str = include_str!("../../../memory.x");
+
+    #[test]
+    fn regions_match_memory_x() {
+        let parsed: Vec<_> = MEMORY_X.lines().filter_map(parse_region_line).collect();
+        assert_eq!(parsed.len(), Region::ALL.len());
+        for region in Region::ALL {
+            let (_, origin, length) = parsed
+                .iter()
+                .find(|(name, ..)| *name == region.name())
+                .unwrap_or_else(|| panic!("{} missing from memory.x", region.name()));
+            assert_eq!(*origin, region.origin(), "{} origin", region.name());
+            assert_eq!(*length, region.length(), "{} length", region.name());
+        }
+    }
+
+    #[test]
+    fn parse_region_line_handles_suffixes_and_comments() {
+        assert_eq!(
+            parse_region_line("  SRAM3  (xrw) : ORIGIN = 0x30040000, LENGTH = 32K"),
+            Some(("SRAM3", 0x3004_0000, 32 * 1024))
+        );
+        assert_eq!(
+            parse_region_line("X (rx) : ORIGIN = 0x90000000, LENGTH = 16M /* XiP */"),
+            Some(("X", 0x9000_0000, 16 * 1024 * 1024))
+        );
+        assert_eq!(parse_region_line("/* Flash bank 1 */"), None);
+        assert_eq!(parse_region_line("MEMORY"), None);
+    }
+
+    #[test]
+    fn budget_leaves_axi_headroom() {
+        let budgeted: usize = ALLOCATIONS
+            .iter()
+            .filter(|a| a.region == Region::AxiSram)
+            .map(|a| a.bytes)
+            .sum();
+        assert_eq!(Region::AxiSram.budgeted(), budgeted);
+        assert!(Region::AxiSram.headroom() > 0);
+        assert_eq!(
+            Region::ExtSdram.headroom(),
+            0,
+            "SDRAM layout is fully mapped"
+        );
+        assert_eq!(Region::Qspi.budgeted(), 0);
+    }
+
+    #[test]
+    fn containing_finds_region_by_address() {
+        assert_eq!(Region::containing(0x2400_1000), Some(Region::AxiSram));
+        assert_eq!(Region::containing(0x3002_0000), Some(Region::Sram2));
+        assert_eq!(Region::containing(0xC1FF_FFFF), Some(Region::ExtSdram));
+        assert_eq!(Region::containing(0x0000_0000), None);
+    }
+}
@@ -0,0 +1,551 @@
+//! Output device profiles — per-output volume ceiling, EQ, crossfeed and
+//! DAC filter.
+//!
+//! A profile bundles the settings that belong to what is plugged in rather
+//! than to the music: sensitive IEMs want a low volume ceiling, planar
+//! headphones want the full range and some crossfeed, a line out wants a
+//! fixed level.  [`OutputProfiles`] holds the user's list and the active
+//! entry, and persists them to `{root}/profiles.bin`
+//! ([`output_profiles_path`](crate::soul_library::output_profiles_path)).
+//!
+//! # File format
+//!
+//! ```text
+//! [0..4]   magic     b"OPRF"
+//! [4]      version   1
+//! [5]      count     u8
+//! [6]      active    u8
+//! [7..]    count × PROFILE_RECORD_LEN bytes:
+//!            [0]      name length
+//!            [1..25]  name, UTF-8, zero padded
+//!            [25]     max_volume (0–100)
+//!            [26]     EqPreset code
+//!            [27]     Crossfeed code
+//!            [28]     OversamplingFilter index
+//! [end-4..] FNV-1a of all preceding bytes, u32 le
+//! ```
+
+use heapless::{String, Vec};
+
+use crate::audio::OversamplingFilter;
+
+/// Maximum profile name length in bytes.
+pub const PROFILE_NAME_LEN: usize = 24;
+
+/// Profiles an [`OutputProfiles`] holds by default.
+pub const MAX_PROFILES: usize = 8;
+
+/// Encoded size of one profile.
+pub const PROFILE_RECORD_LEN: usize = 29;
+
+const MAGIC: &[u8; 4] = b"OPRF";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 7;
+const CHECKSUM_LEN: usize = 4;
+/// Header plus checksum.
+const FRAMING_LEN: usize = 11;
+/// End of the name field within a record.
+const NAME_END: usize = 25;
+
+/// Equaliser preset, as gains for the bands in [`EQ_BANDS_HZ`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EqPreset {
+    /// No equalisation.
+    #[default]
+    Flat,
+    /// Lifted sub-bass and bass.
+    BassBoost,
+    /// Gently tilted towards the low end.
+    Warm,
+    /// Lifted treble, for dark headphones.
+    Bright,
+    /// Presence lift around 1–4 kHz.
+    Vocal,
+}
+
+/// Centre frequencies of the equaliser bands.
+pub const EQ_BANDS_HZ: [u32; 5] = [60, 250, 1_000, 4_000, 12_000];
+
+impl EqPreset {
+    /// Every preset, in menu order.
+    pub const ALL: [Self; 5] = [
+        Self::Flat,
+        Self::BassBoost,
+        Self::Warm,
+        Self::Bright,
+        Self::Vocal,
+    ];
+
+    /// Band gains in dB, one per entry of [`EQ_BANDS_HZ`].
+    #[must_use]
+    pub const fn band_gains_db(self) -> [i8; 5] {
+        match self {
+            Self::Flat => [0, 0, 0, 0, 0],
+            Self::BassBoost => [6, 3, 0, 0, 0],
+            Self::Warm => [3, 2, 0, -1, -2],
+            Self::Bright => [0, 0, 0, 2, 4],
+            Self::Vocal => [-2, 0, 2, 3, 0],
+        }
+    }
+
+    /// Short name for menus.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Flat => "Flat",
+            Self::BassBoost => "Bass boost",
+            Self::Warm => "Warm",
+            Self::Bright => "Bright",
+            Self::Vocal => "Vocal",
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            Self::Flat => 0,
+            Self::BassBoost => 1,
+            Self::Warm => 2,
+            Self::Bright => 3,
+            Self::Vocal => 4,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(code)).copied()
+    }
+}
+
+/// Headphone crossfeed: a low-passed, attenuated copy of each channel fed
+/// into the other, so hard-panned stereo sounds less "inside the head".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Crossfeed {
+    /// No crossfeed (IEMs, line out).
+    #[default]
+    Off,
+    /// Subtle: 650 Hz cut-off, fed at −9.5 dB.
+    Light,
+    /// Speaker-like: 700 Hz cut-off, fed at −4.5 dB.
+    Strong,
+}
+
+impl Crossfeed {
+    /// Every level, in menu order.
+    pub const ALL: [Self; 3] = [Self::Off, Self::Light, Self::Strong];
+
+    /// Cut-off frequency (Hz) and feed attenuation (tenths of a dB), or
+    /// `None` when off.
+    #[must_use]
+    pub const fn params(self) -> Option<(u16, u8)> {
+        match self {
+            Self::Off => None,
+            Self::Light => Some((650, 95)),
+            Self::Strong => Some((700, 45)),
+        }
+    }
+
+    /// Short name for menus.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Off => "Off",
+            Self::Light => "Light",
+            Self::Strong => "Strong",
+        }
+    }
+
+    fn code(self) -> u8 {
+        match self {
+            Self::Off => 0,
+            Self::Light => 1,
+            Self::Strong => 2,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.get(usize::from(code)).copied()
+    }
+}
+
+/// Settings for one output device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputProfile {
+    /// Name shown in the quick menu.
+    pub name: String<PROFILE_NAME_LEN>,
+    /// DAC volume (0–100) at full user volume; the volume control is scaled
+    /// into `0..=max_volume`, so the whole encoder range stays usable.
+    pub max_volume: u8,
+    /// Equaliser preset.
+    pub eq: EqPreset,
+    /// Crossfeed level.
+    pub crossfeed: Crossfeed,
+    /// DAC oversampling filter.
+    pub filter: OversamplingFilter,
+}
+
+impl OutputProfile {
+    /// A profile named `name` (truncated to [`PROFILE_NAME_LEN`] bytes on a
+    /// character boundary) with flat settings and the full volume range.
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        let mut truncated = String::new();
+        for c in name.chars() {
+            if truncated.push(c).is_err() {
+                break;
+            }
+        }
+        Self {
+            name: truncated,
+            max_volume: 100,
+            eq: EqPreset::Flat,
+            crossfeed: Crossfeed::Off,
+            filter: OversamplingFilter::default(),
+        }
+    }
+
+    /// DAC volume for user volume `volume` (0–100).
+    #[must_use]
+    pub fn output_volume(&self, volume: u8) -> u8 {
+        let scaled =
+            u16::from(volume.min(100)).saturating_mul(u16::from(self.max_volume.min(100))) / 100;
+        u8::try_from(scaled).unwrap_or(100)
+    }
+
+    /// Write this profile's [`PROFILE_RECORD_LEN`]-byte record to `out`.
+    fn encode_into(&self, out: &mut [u8]) -> Option<()> {
+        let name = self.name.as_bytes();
+        *out.get_mut(0)? = u8::try_from(name.len()).ok()?;
+        let name_field = out.get_mut(1..NAME_END)?;
+        name_field.fill(0);
+        name_field.get_mut(..name.len())?.copy_from_slice(name);
+        out.get_mut(NAME_END..PROFILE_RECORD_LEN)?
+            .copy_from_slice(&[
+                self.max_volume,
+                self.eq.code(),
+                self.crossfeed.code(),
+                u8::try_from(self.filter.index()).ok()?,
+            ]);
+        Some(())
+    }
+
+    /// Parse one record; `None` when any field is out of range.
+    fn decode_from(record: &[u8]) -> Option<Self> {
+        let len = usize::from(*record.first()?);
+        if len > PROFILE_NAME_LEN {
+            return None;
+        }
+        let name = core::str::from_utf8(record.get(1..len.checked_add(1)?)?).ok()?;
+        let &[max_volume, eq, crossfeed, filter] = record.get(NAME_END..PROFILE_RECORD_LEN)? else {
+            return None;
+        };
+        if max_volume > 100 {
+            return None;
+        }
+        Some(Self {
+            max_volume,
+            eq: EqPreset::from_code(eq)?,
+            crossfeed: Crossfeed::from_code(crossfeed)?,
+            filter: OversamplingFilter::from_index(usize::from(filter))?,
+            ..Self::new(name)
+        })
+    }
+}
+
+/// Errors returned by [`OutputProfiles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProfileError {
+    /// The list already holds its maximum number of profiles.
+    Full,
+    /// The encode buffer is smaller than [`OutputProfiles::encoded_len`].
+    BufferTooSmall,
+    /// The stored file is truncated, fails its checksum, or holds
+    /// out-of-range values.
+    Corrupt,
+}
+
+/// The user's output profiles and which one is active.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputProfiles<const N: usize = MAX_PROFILES> {
+    profiles: Vec<OutputProfile, N>,
+    active: usize,
+}
+
+impl<const N: usize> OutputProfiles<N> {
+    /// An empty list.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            profiles: Vec::new(),
+            active: 0,
+        }
+    }
+
+    /// The factory profiles — "IEM", "Planar headphones" and "Line out" —
+    /// with "IEM" active, as the safest starting volume.  Profiles beyond
+    /// `N` are left out.
+    #[must_use]
+    pub fn defaults() -> Self {
+        let mut profiles = Self::new();
+        for profile in [
+            OutputProfile {
+                max_volume: 60,
+                ..OutputProfile::new("IEM")
+            },
+            OutputProfile {
+                crossfeed: Crossfeed::Light,
+                ..OutputProfile::new("Planar headphones")
+            },
+            OutputProfile {
+                filter: OversamplingFilter::SlowRollOffLinearPhase,
+                ..OutputProfile::new("Line out")
+            },
+        ] {
+            let _ = profiles.push(profile);
+        }
+        profiles
+    }
+
+    /// Number of profiles.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    /// `true` when there are no profiles.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    /// Profile `index`, if any.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&OutputProfile> {
+        self.profiles.get(index)
+    }
+
+    /// Mutable profile `index`, for editing its settings.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut OutputProfile> {
+        self.profiles.get_mut(index)
+    }
+
+    /// All profiles, in menu order.
+    pub fn iter(&self) -> impl Iterator<Item = &OutputProfile> {
+        self.profiles.iter()
+    }
+
+    /// Index of the active profile (0 when the list is empty).
+    #[must_use]
+    pub fn active_index(&self) -> usize {
+        self.active
+    }
+
+    /// The active profile, or `None` when the list is empty.
+    #[must_use]
+    pub fn active(&self) -> Option<&OutputProfile> {
+        self.profiles.get(self.active)
+    }
+
+    /// Make profile `index` active, returning it; `None` (and no change)
+    /// when out of range.
+    pub fn select(&mut self, index: usize) -> Option<&OutputProfile> {
+        let profile = self.profiles.get(index)?;
+        self.active = index;
+        Some(profile)
+    }
+
+    /// Append a profile.
+    ///
+    /// # Errors
+    ///
+    /// [`ProfileError::Full`] when `N` profiles are already stored.
+    pub fn push(&mut self, profile: OutputProfile) -> Result<(), ProfileError> {
+        self.profiles.push(profile).map_err(|_| ProfileError::Full)
+    }
+
+    /// Bytes [`encode`](Self::encode) writes.
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        self.profiles
+            .len()
+            .saturating_mul(PROFILE_RECORD_LEN)
+            .saturating_add(FRAMING_LEN)
+    }
+
+    /// Encode the list into `buf`, returning the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// [`ProfileError::BufferTooSmall`] when `buf` is shorter than
+    /// [`encoded_len`](Self::encoded_len).
+    pub fn encode(&self, buf: &mut [u8]) -> Result<usize, ProfileError> {
+        let len = self.encoded_len();
+        let out = buf.get_mut(..len).ok_or(ProfileError::BufferTooSmall)?;
+        let count = u8::try_from(self.profiles.len()).map_err(|_| ProfileError::Corrupt)?;
+        let active = u8::try_from(self.active).map_err(|_| ProfileError::Corrupt)?;
+        let body_end = len.saturating_sub(CHECKSUM_LEN);
+        let (body, checksum) = out.split_at_mut(body_end);
+        let (header, records) = body.split_at_mut(HEADER_LEN);
+        let [m0, m1, m2, m3] = *MAGIC;
+        header.copy_from_slice(&[m0, m1, m2, m3, VERSION, count, active]);
+        for (profile, record) in self
+            .profiles
+            .iter()
+            .zip(records.chunks_exact_mut(PROFILE_RECORD_LEN))
+        {
+            profile
+                .encode_into(record)
+                .ok_or(ProfileError::BufferTooSmall)?;
+        }
+        checksum.copy_from_slice(&fnv1a(body).to_le_bytes());
+        Ok(len)
+    }
+
+    /// Decode a list written by [`encode`](Self::encode).
+    ///
+    /// # Errors
+    ///
+    /// [`ProfileError::Corrupt`] when `bytes` is truncated, fails its
+    /// checksum, holds more than `N` profiles or has an out-of-range field.
+    pub fn decode(bytes: &[u8]) -> Result<Self, ProfileError> {
+        let body_end = bytes
+            .len()
+            .checked_sub(CHECKSUM_LEN)
+            .ok_or(ProfileError::Corrupt)?;
+        let (body, checksum) = bytes.split_at(body_end);
+        if checksum != fnv1a(body).to_le_bytes() {
+            return Err(ProfileError::Corrupt);
+        }
+        let Some(&[m0, m1, m2, m3, version, count, active]) = body.get(..HEADER_LEN) else {
+            return Err(ProfileError::Corrupt);
+        };
+        if [m0, m1, m2, m3] != *MAGIC || version != VERSION {
+            return Err(ProfileError::Corrupt);
+        }
+        let records = body.get(HEADER_LEN..).ok_or(ProfileError::Corrupt)?;
+        if records.len() != usize::from(count).saturating_mul(PROFILE_RECORD_LEN) {
+            return Err(ProfileError::Corrupt);
+        }
+        let mut profiles = Self::new();
+        for record in records.chunks_exact(PROFILE_RECORD_LEN) {
+            let profile = OutputProfile::decode_from(record).ok_or(ProfileError::Corrupt)?;
+            profiles.push(profile).map_err(|_| ProfileError::Corrupt)?;
+        }
+        let active = usize::from(active);
+        if active >= profiles.len() && !profiles.is_empty() {
+            return Err(ProfileError::Corrupt);
+        }
+        profiles.active = active;
+        Ok(profiles)
+    }
+}
+
+impl<const N: usize> Default for OutputProfiles<N> {
+    fn default() -> Self {
+        Self::defaults()
+    }
+}
+
+/// FNV-1a over `bytes` — enough to catch a torn write.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811C_9DC5u32, |hash, &b| {
+        (hash ^ u32::from(b)).wrapping_mul(0x0100_0193)
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)] // fixed-size test buffers
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_start_on_iem() {
+        let profiles: OutputProfiles = OutputProfiles::defaults();
+        assert_eq!(profiles.len(), 3);
+        let active = profiles.active().expect("active profile");
+        assert_eq!(active.name.as_str(), "IEM");
+        assert_eq!(active.max_volume, 60);
+    }
+
+    #[test]
+    fn output_volume_scales_into_ceiling() {
+        let iem = OutputProfile {
+            max_volume: 60,
+            ..OutputProfile::new("IEM")
+        };
+        assert_eq!(iem.output_volume(100), 60);
+        assert_eq!(iem.output_volume(50), 30);
+        assert_eq!(iem.output_volume(0), 0);
+        assert_eq!(iem.output_volume(255), 60, "user volume clamps to 100");
+        assert_eq!(OutputProfile::new("Full").output_volume(80), 80);
+    }
+
+    #[test]
+    fn select_ignores_out_of_range() {
+        let mut profiles: OutputProfiles = OutputProfiles::defaults();
+        assert_eq!(
+            profiles.select(2).map(|p| p.name.as_str()),
+            Some("Line out")
+        );
+        assert!(profiles.select(3).is_none());
+        assert_eq!(profiles.active_index(), 2);
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let mut profiles: OutputProfiles = OutputProfiles::defaults();
+        profiles
+            .push(OutputProfile {
+                eq: EqPreset::Vocal,
+                crossfeed: Crossfeed::Strong,
+                filter: OversamplingFilter::HybridFastRollOff,
+                max_volume: 75,
+                ..OutputProfile::new("Café speakers with a long name")
+            })
+            .unwrap();
+        profiles.select(3);
+
+        let mut buf = [0u8; 256];
+        let len = profiles.encode(&mut buf).unwrap();
+        assert_eq!(len, profiles.encoded_len());
+        let decoded: OutputProfiles = OutputProfiles::decode(&buf[..len]).unwrap();
+        assert_eq!(decoded, profiles);
+        assert_eq!(
+            decoded.get(3).unwrap().name.as_str(),
+            "Café speakers with a lo"
+        );
+    }
+
+    #[test]
+    fn decode_rejects_damage() {
+        let profiles: OutputProfiles = OutputProfiles::defaults();
+        let mut buf = [0u8; 128];
+        let len = profiles.encode(&mut buf).unwrap();
+
+        assert_eq!(
+            OutputProfiles::<8>::decode(&buf[..len - 1]),
+            Err(ProfileError::Corrupt)
+        );
+        buf[HEADER_LEN + 25] = 101;
+        assert_eq!(
+            OutputProfiles::<8>::decode(&buf[..len]),
+            Err(ProfileError::Corrupt),
+            "checksum catches the change"
+        );
+        assert_eq!(
+            OutputProfiles::<2>::decode(&{
+                let mut fresh = [0u8; 128];
+                let n = profiles.encode(&mut fresh).unwrap();
+                fresh[..n].to_vec()
+            }),
+            Err(ProfileError::Corrupt),
+            "more profiles than capacity"
+        );
+        assert_eq!(
+            profiles.encode(&mut [0u8; 16]),
+            Err(ProfileError::BufferTooSmall)
+        );
+    }
+}
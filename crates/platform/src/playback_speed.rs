@@ -0,0 +1,229 @@
+//! Playback speed for spoken-word content.
+//!
+//! Audiobooks and podcasts can play faster or slower than recorded, from
+//! 0.8× to 2.0×, with the pitch kept (the time-stretch stage lives in the
+//! `playback` crate).  Speeds are whole percent and the menu steps through
+//! [`PlaybackSpeed::STEPS`].  Which content follows the speed setting is a
+//! [`SpeedScope`]: spoken word only by default, so music never plays
+//! stretched unless asked for.
+
+use core::fmt;
+
+/// Playback speed in percent of the recorded speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PlaybackSpeed(u8);
+
+impl PlaybackSpeed {
+    /// Recorded speed.
+    pub const NORMAL: Self = Self(100);
+    /// Slowest speed.
+    pub const MIN: Self = Self(80);
+    /// Fastest speed.
+    pub const MAX: Self = Self(200);
+
+    /// Speeds offered in the quick menu, slowest first.
+    pub const STEPS: [Self; 9] = [
+        Self(80),
+        Self(90),
+        Self(100),
+        Self(110),
+        Self(125),
+        Self(150),
+        Self(175),
+        Self(190),
+        Self(200),
+    ];
+
+    /// A speed of `percent`, or `None` outside [`MIN`](Self::MIN)..=[`MAX`](Self::MAX).
+    #[must_use]
+    pub const fn new(percent: u8) -> Option<Self> {
+        if percent < Self::MIN.0 || percent > Self::MAX.0 {
+            return None;
+        }
+        Some(Self(percent))
+    }
+
+    /// Speed in percent.
+    #[must_use]
+    pub const fn percent(self) -> u8 {
+        self.0
+    }
+
+    /// Whether this is the recorded speed.
+    #[must_use]
+    pub const fn is_normal(self) -> bool {
+        self.0 == Self::NORMAL.0
+    }
+
+    /// Index in [`STEPS`](Self::STEPS) of the step at or just above this
+    /// speed.
+    #[must_use]
+    pub fn step(self) -> usize {
+        Self::STEPS
+            .iter()
+            .position(|s| s.0 >= self.0)
+            .unwrap_or(Self::STEPS.len().saturating_sub(1))
+    }
+
+    /// The step after this one, wrapping from the fastest to the slowest.
+    #[must_use]
+    pub fn next_step(self) -> Self {
+        let next = self.step().saturating_add(1);
+        Self::STEPS.get(next).copied().unwrap_or(Self::MIN)
+    }
+
+    /// Playing time of `source_ms` of recording at this speed.
+    #[must_use]
+    pub fn wall_ms(self, source_ms: u64) -> u64 {
+        source_ms
+            .saturating_mul(100)
+            .checked_div(u64::from(self.0))
+            .unwrap_or(source_ms)
+    }
+}
+
+impl Default for PlaybackSpeed {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+/// `1.25x` — the hundredths are dropped when zero (`2x`, `1.5x`).
+impl fmt::Display for PlaybackSpeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (whole, hundredths) = (self.0 / 100, self.0 % 100);
+        match hundredths {
+            0 => write!(f, "{whole}x"),
+            h if h % 10 == 0 => write!(f, "{whole}.{}x", h / 10),
+            h => write!(f, "{whole}.{h:02}x"),
+        }
+    }
+}
+
+/// What the playing track is, as far as the speed setting cares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ContentKind {
+    /// Songs.
+    #[default]
+    Music,
+    /// Audiobook chapters or files.
+    Audiobook,
+    /// Podcast episodes.
+    Podcast,
+}
+
+/// Content kinds that play at the chosen speed; the rest play at
+/// [`PlaybackSpeed::NORMAL`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SpeedScope {
+    /// Music follows the speed.
+    pub music: bool,
+    /// Audiobooks follow the speed.
+    pub audiobook: bool,
+    /// Podcasts follow the speed.
+    pub podcast: bool,
+}
+
+impl SpeedScope {
+    /// Spoken word only.
+    pub const SPOKEN_WORD: Self = Self {
+        music: false,
+        audiobook: true,
+        podcast: true,
+    };
+
+    /// Whether `kind` follows the speed setting.
+    #[must_use]
+    pub const fn allows(self, kind: ContentKind) -> bool {
+        match kind {
+            ContentKind::Music => self.music,
+            ContentKind::Audiobook => self.audiobook,
+            ContentKind::Podcast => self.podcast,
+        }
+    }
+
+    /// The speed `kind` plays at when `chosen` is selected.
+    #[must_use]
+    pub const fn speed_for(self, kind: ContentKind, chosen: PlaybackSpeed) -> PlaybackSpeed {
+        if self.allows(kind) {
+            chosen
+        } else {
+            PlaybackSpeed::NORMAL
+        }
+    }
+}
+
+impl Default for SpeedScope {
+    fn default() -> Self {
+        Self::SPOKEN_WORD
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContentKind, PlaybackSpeed, SpeedScope};
+
+    #[test]
+    fn speed_is_limited_to_the_supported_range() {
+        assert_eq!(PlaybackSpeed::new(79), None);
+        assert_eq!(PlaybackSpeed::new(201), None);
+        assert_eq!(
+            PlaybackSpeed::new(125).map(PlaybackSpeed::percent),
+            Some(125)
+        );
+        assert!(PlaybackSpeed::default().is_normal());
+        assert!(PlaybackSpeed::STEPS.is_sorted());
+        assert_eq!(PlaybackSpeed::STEPS.first(), Some(&PlaybackSpeed::MIN));
+        assert_eq!(PlaybackSpeed::STEPS.last(), Some(&PlaybackSpeed::MAX));
+    }
+
+    #[test]
+    fn next_step_walks_the_menu_and_wraps() {
+        let mut speed = PlaybackSpeed::NORMAL;
+        for expected in [110, 125, 150, 175, 190, 200, 80, 90, 100] {
+            speed = speed.next_step();
+            assert_eq!(speed.percent(), expected);
+        }
+        // Off-menu speeds snap to the next step up.
+        let odd = PlaybackSpeed::new(130).unwrap_or_default();
+        assert_eq!(odd.next_step().percent(), 175);
+    }
+
+    #[test]
+    fn speed_labels() {
+        let label = |p| PlaybackSpeed::new(p).unwrap_or_default().to_string();
+        assert_eq!(label(100), "1x");
+        assert_eq!(label(150), "1.5x");
+        assert_eq!(label(125), "1.25x");
+        assert_eq!(label(80), "0.8x");
+        assert_eq!(label(200), "2x");
+    }
+
+    #[test]
+    fn wall_time_shrinks_with_speed() {
+        let double = PlaybackSpeed::MAX;
+        assert_eq!(double.wall_ms(60_000), 30_000);
+        assert_eq!(PlaybackSpeed::MIN.wall_ms(60_000), 75_000);
+        assert_eq!(PlaybackSpeed::NORMAL.wall_ms(1_234), 1_234);
+    }
+
+    #[test]
+    fn only_spoken_word_follows_the_speed_by_default() {
+        let scope = SpeedScope::default();
+        let fast = PlaybackSpeed::MAX;
+        assert_eq!(
+            scope.speed_for(ContentKind::Music, fast),
+            PlaybackSpeed::NORMAL
+        );
+        assert_eq!(scope.speed_for(ContentKind::Audiobook, fast), fast);
+        assert_eq!(scope.speed_for(ContentKind::Podcast, fast), fast);
+        let all = SpeedScope {
+            music: true,
+            ..scope
+        };
+        assert_eq!(all.speed_for(ContentKind::Music, fast), fast);
+    }
+}
@@ -114,4 +114,158 @@ pub trait PowerMonitor {
 
     /// Check if USB power connected
     fn is_usb_connected(&self) -> bool;
+
+    /// Battery alert level (`None` if the voltage is unknown)
+    ///
+    /// External power suppresses the alerts: the system runs from VBUS.
+    fn battery_level(&self) -> Option<BatteryLevel> {
+        if self.is_usb_connected() {
+            return Some(BatteryLevel::Normal);
+        }
+        self.battery_voltage().map(BatteryLevel::from_mv)
+    }
+}
+
+/// Below this the UI warns about the battery (mV, loaded).
+pub const LOW_BATTERY_MV: u16 = 3_500;
+
+/// Below this the firmware must save state and power off (mV, loaded).
+///
+/// Leaves margin above the cell protection cut-off (~3.0 V) for the
+/// shutdown sequence and a final e-ink refresh.
+pub const CRITICAL_BATTERY_MV: u16 = 3_350;
+
+/// Battery alert level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BatteryLevel {
+    /// Below [`CRITICAL_BATTERY_MV`]: shut down now
+    Critical,
+    /// Below [`LOW_BATTERY_MV`]: warn the user
+    Low,
+    /// Enough charge
+    Normal,
+}
+
+impl BatteryLevel {
+    /// Classify a battery voltage
+    pub const fn from_mv(mv: u16) -> Self {
+        if mv < CRITICAL_BATTERY_MV {
+            Self::Critical
+        } else if mv < LOW_BATTERY_MV {
+            Self::Low
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+/// Open-circuit voltage (mV) to state of charge (%) for a single LiPo cell.
+///
+/// Typical curve for a 4.2 V cell at room temperature; good to a few
+/// percent, which is all a status-bar gauge needs.
+const LIPO_OCV_CURVE: [(u16, u8); 12] = [
+    (3_300, 0),
+    (3_400, 2),
+    (3_500, 5),
+    (3_600, 12),
+    (3_650, 20),
+    (3_700, 30),
+    (3_750, 40),
+    (3_800, 50),
+    (3_900, 65),
+    (4_000, 78),
+    (4_100, 90),
+    (4_200, 100),
+];
+
+/// State of charge (0-100) for a LiPo cell voltage, interpolated linearly
+pub fn lipo_percent_from_mv(mv: u16) -> u8 {
+    let mut lower = (0, 0);
+    for (v, pct) in LIPO_OCV_CURVE {
+        if mv < v {
+            let (v0, p0) = lower;
+            if v0 == 0 {
+                return 0;
+            }
+            // v > mv >= v0, so the span is non-zero and the result < pct.
+            let span = u32::from(v.saturating_sub(v0));
+            let along = u32::from(mv.saturating_sub(v0));
+            let rise = u32::from(pct.saturating_sub(p0));
+            let step = along.saturating_mul(rise).checked_div(span).unwrap_or(0);
+            return p0.saturating_add(u8::try_from(step).unwrap_or(0));
+        }
+        lower = (v, pct);
+    }
+    100
+}
+
+/// LiPo cell voltage (mV) for a state of charge (0-100); inverse of
+/// [`lipo_percent_from_mv`]
+pub fn lipo_mv_from_percent(percent: u8) -> u16 {
+    let mut lower = (3_300, 0);
+    for (v, pct) in LIPO_OCV_CURVE {
+        if percent <= pct {
+            let (v0, p0) = lower;
+            let span = u32::from(pct.saturating_sub(p0));
+            let along = u32::from(percent.saturating_sub(p0));
+            let rise = u32::from(v.saturating_sub(v0));
+            let step = along.saturating_mul(rise).checked_div(span).unwrap_or(0);
+            return v0.saturating_add(u16::try_from(step).unwrap_or(0));
+        }
+        lower = (v, pct);
+    }
+    4_200
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lipo_curve_end_points_and_interpolation() {
+        assert_eq!(lipo_percent_from_mv(3_000), 0);
+        assert_eq!(lipo_percent_from_mv(3_300), 0);
+        assert_eq!(lipo_percent_from_mv(3_725), 35);
+        assert_eq!(lipo_percent_from_mv(4_200), 100);
+        assert_eq!(lipo_percent_from_mv(4_350), 100);
+    }
+
+    #[test]
+    fn lipo_curve_round_trips() {
+        for pct in [0u8, 5, 20, 35, 50, 71, 90, 100] {
+            let mv = lipo_mv_from_percent(pct);
+            let back = lipo_percent_from_mv(mv);
+            assert!(back.abs_diff(pct) <= 1, "{pct}% -> {mv} mV -> {back}%");
+        }
+    }
+
+    struct Fixed {
+        mv: u16,
+        usb: bool,
+    }
+
+    impl PowerMonitor for Fixed {
+        fn battery_voltage(&self) -> Option<u16> {
+            Some(self.mv)
+        }
+        fn battery_percentage(&self) -> Option<u8> {
+            Some(lipo_percent_from_mv(self.mv))
+        }
+        fn is_charging(&self) -> bool {
+            self.usb
+        }
+        fn is_usb_connected(&self) -> bool {
+            self.usb
+        }
+    }
+
+    #[test]
+    fn battery_level_thresholds_and_usb_override() {
+        let level = |mv, usb| Fixed { mv, usb }.battery_level();
+        assert_eq!(level(3_900, false), Some(BatteryLevel::Normal));
+        assert_eq!(level(3_450, false), Some(BatteryLevel::Low));
+        assert_eq!(level(3_200, false), Some(BatteryLevel::Critical));
+        assert_eq!(level(3_200, true), Some(BatteryLevel::Normal));
+    }
 }
@@ -0,0 +1,520 @@
+//! Waveform auto-selection — pick DU / DU4 / GC16 per display update.
+//!
+//! Every screen update has to choose between speed and image quality:
+//!
+//! | [`RefreshMode`] | Waveform | Speed    | Trade-off                              |
+//! |-----------------|----------|----------|----------------------------------------|
+//! | `Fast`          | DU       | ~260 ms  | 1-bit, most ghosting                   |
+//! | `Partial`       | DU4      | ~300 ms  | 4 grey levels, accumulates ghosting    |
+//! | `Full`          | GC16     | ~2000 ms | 16 grey levels, flashes, clears ghosts |
+//!
+//! [`RefreshPolicy`] makes that choice from the size of the dirty area, a
+//! [`ContentHint`] from the screen, and the panel's condition — accumulated
+//! ghosting, DC balance and time since the last full refresh.  The same
+//! policy runs on hardware (timestamps from the Embassy tick) and in the
+//! emulator (virtual clock, measured ghosting via
+//! [`EinkDisplay::ghosting_level`](crate::EinkDisplay::ghosting_level)).
+//!
+//! Hardware cannot measure ghosting, so the policy keeps its own estimate
+//! from the refreshes it has chosen; a measured value, when supplied in
+//! [`PanelState`], takes precedence.
+//!
+//! All arithmetic is integer (permille) so the policy costs nothing on the
+//! MCU.
+//!
+//! # Example
+//!
+//! ```
+//! use platform::refresh_policy::{ContentHint, PanelState, RefreshPolicy, Update};
+//! use platform::RefreshMode;
+//!
+//! let mut policy = RefreshPolicy::new(800 * 480);
+//! // First frame after boot is always a full refresh.
+//! let first = policy.decide(Update::new(800 * 480, ContentHint::Image), PanelState::UNKNOWN, 0);
+//! assert_eq!(first.mode, RefreshMode::Full);
+//! // A small text change afterwards is a partial refresh.
+//! let next = policy.decide(Update::new(200 * 24, ContentHint::Text), PanelState::UNKNOWN, 1_000);
+//! assert_eq!(next.mode, RefreshMode::Partial);
+//! ```
+
+use crate::display::RefreshMode;
+
+/// What kind of content changed, as hinted by the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ContentHint {
+    /// Text (labels, lists, menus) — DU4 renders anti-aliasing acceptably.
+    Text,
+    /// Icons, lines and flat UI shapes.
+    Graphics,
+    /// Photographic content (album art) — needs 16 grey levels.
+    Image,
+    /// Rapidly changing content (progress bar, scrubbing, scrolling) —
+    /// latency matters more than grey levels.
+    Animation,
+}
+
+/// One pending screen update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Update {
+    /// Dirty area in pixels (sum of dirty rectangles).
+    pub dirty_pixels: u32,
+    /// Content type of the dirty area.
+    pub hint: ContentHint,
+}
+
+impl Update {
+    /// An update touching `dirty_pixels` pixels of `hint` content.
+    pub const fn new(dirty_pixels: u32, hint: ContentHint) -> Self {
+        Self { dirty_pixels, hint }
+    }
+}
+
+/// Measured panel condition, where the backend can provide it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PanelState {
+    /// Measured average ghosting in permille (emulator only).
+    pub ghosting_permille: Option<u16>,
+    /// Worst absolute DC balance of any pixel (emulator only; same scale as
+    /// the emulator's pixel model, warning at 30, critical at 50).
+    pub dc_balance: Option<u16>,
+}
+
+impl PanelState {
+    /// Nothing measured (hardware); the policy uses its own estimate.
+    pub const UNKNOWN: Self = Self {
+        ghosting_permille: None,
+        dc_balance: None,
+    };
+
+    /// Panel state from a measured ghosting level in `[0.0, 1.0]`, as
+    /// returned by [`EinkDisplay::ghosting_level`](crate::EinkDisplay::ghosting_level).
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::arithmetic_side_effects
+    )]
+    pub fn with_ghosting(level: f32) -> Self {
+        // SAFETY (cast): clamped to 0.0..=1000.0 before the cast.
+        let permille = (level.clamp(0.0, 1.0) * 1000.0) as u16;
+        Self {
+            ghosting_permille: Some(permille),
+            dc_balance: None,
+        }
+    }
+}
+
+/// Why a mode was chosen (for logging and tests).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RefreshReason {
+    /// No full refresh since boot; the panel state is unknown.
+    FirstFrame,
+    /// Every pixel changed behind the policy's back (night mode toggled).
+    Invalidated,
+    /// Accumulated ghosting reached the limit.
+    Ghosting,
+    /// DC balance reached the limit.
+    DcBalance,
+    /// Too long since the last full refresh.
+    Interval,
+    /// Too many partial/fast refreshes since the last full refresh.
+    PartialCount,
+    /// The update covers most of the screen (page transition).
+    LargeArea,
+    /// Photographic content needs 16 grey levels.
+    Image,
+    /// Animation: latency over quality.
+    Animation,
+    /// Ordinary incremental update.
+    Incremental,
+}
+
+/// A policy decision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RefreshChoice {
+    /// Mode to refresh with.
+    pub mode: RefreshMode,
+    /// Why.
+    pub reason: RefreshReason,
+}
+
+/// Thresholds for [`RefreshPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RefreshPolicyConfig {
+    /// Force GC16 at or above this ghosting (permille).
+    pub max_ghosting_permille: u16,
+    /// Force GC16 at or above this DC balance.
+    pub max_dc_balance: u16,
+    /// Force GC16 after this long without one (ms).
+    pub full_interval_ms: u64,
+    /// Force GC16 after this many partial/fast refreshes.
+    pub max_partials: u16,
+    /// Dirty area (permille of the screen) at which an update counts as a
+    /// page transition and gets GC16.
+    pub large_area_permille: u16,
+    /// Image updates smaller than this (permille) use DU4 instead of GC16
+    /// (thumbnails, small icons).
+    pub min_image_permille: u16,
+    /// Estimated ghosting added per DU4 refresh (permille).
+    pub partial_ghosting_permille: u16,
+    /// Estimated ghosting added per DU refresh (permille).
+    pub fast_ghosting_permille: u16,
+}
+
+impl RefreshPolicyConfig {
+    /// Defaults tuned for the 3.97" GDEM0397T81P.
+    pub const DEFAULT: Self = Self {
+        max_ghosting_permille: 300,
+        max_dc_balance: 30,
+        full_interval_ms: 10 * 60 * 1000,
+        max_partials: 30,
+        large_area_permille: 600,
+        min_image_permille: 50,
+        partial_ghosting_permille: 8,
+        fast_ghosting_permille: 25,
+    };
+}
+
+impl Default for RefreshPolicyConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Chooses a [`RefreshMode`] per update and tracks panel wear between full
+/// refreshes.
+#[derive(Debug, Clone)]
+pub struct RefreshPolicy {
+    config: RefreshPolicyConfig,
+    screen_pixels: u32,
+    partials_since_full: u16,
+    estimated_ghosting_permille: u16,
+    last_full_ms: Option<u64>,
+    /// The next refresh must be full (see [`invalidate`](Self::invalidate)).
+    invalidated: bool,
+}
+
+impl RefreshPolicy {
+    /// Policy for a screen of `screen_pixels` pixels with default thresholds.
+    pub const fn new(screen_pixels: u32) -> Self {
+        Self::with_config(screen_pixels, RefreshPolicyConfig::DEFAULT)
+    }
+
+    /// Policy with custom thresholds.
+    pub const fn with_config(screen_pixels: u32, config: RefreshPolicyConfig) -> Self {
+        Self {
+            config,
+            screen_pixels,
+            partials_since_full: 0,
+            estimated_ghosting_permille: 0,
+            last_full_ms: None,
+            invalidated: false,
+        }
+    }
+
+    /// Active thresholds.
+    pub const fn config(&self) -> &RefreshPolicyConfig {
+        &self.config
+    }
+
+    /// Partial/fast refreshes since the last full refresh.
+    pub const fn partials_since_full(&self) -> u16 {
+        self.partials_since_full
+    }
+
+    /// Ghosting estimate from the refreshes recorded so far (permille).
+    pub const fn estimated_ghosting_permille(&self) -> u16 {
+        self.estimated_ghosting_permille
+    }
+
+    /// Choose a mode for `update` without recording it.
+    pub fn choose(&self, update: Update, panel: PanelState, now_ms: u64) -> RefreshChoice {
+        let full = |reason| RefreshChoice {
+            mode: RefreshMode::Full,
+            reason,
+        };
+        let cfg = &self.config;
+
+        let Some(last_full) = self.last_full_ms else {
+            return full(RefreshReason::FirstFrame);
+        };
+        if self.invalidated {
+            return full(RefreshReason::Invalidated);
+        }
+        let ghosting = panel
+            .ghosting_permille
+            .unwrap_or(self.estimated_ghosting_permille);
+        if ghosting >= cfg.max_ghosting_permille {
+            return full(RefreshReason::Ghosting);
+        }
+        if panel.dc_balance.is_some_and(|dc| dc >= cfg.max_dc_balance) {
+            return full(RefreshReason::DcBalance);
+        }
+        if now_ms.saturating_sub(last_full) >= cfg.full_interval_ms {
+            return full(RefreshReason::Interval);
+        }
+        if self.partials_since_full >= cfg.max_partials {
+            return full(RefreshReason::PartialCount);
+        }
+
+        let area = self.area_permille(update.dirty_pixels);
+        if area >= cfg.large_area_permille {
+            return full(RefreshReason::LargeArea);
+        }
+        match update.hint {
+            ContentHint::Image if area >= cfg.min_image_permille => full(RefreshReason::Image),
+            ContentHint::Animation => RefreshChoice {
+                mode: RefreshMode::Fast,
+                reason: RefreshReason::Animation,
+            },
+            ContentHint::Text | ContentHint::Graphics | ContentHint::Image => RefreshChoice {
+                mode: RefreshMode::Partial,
+                reason: RefreshReason::Incremental,
+            },
+        }
+    }
+
+    /// Choose a mode for `update` and record it (see [`record`](Self::record)).
+    pub fn decide(&mut self, update: Update, panel: PanelState, now_ms: u64) -> RefreshChoice {
+        let choice = self.choose(update, panel, now_ms);
+        self.record(choice.mode, now_ms);
+        choice
+    }
+
+    /// Make the next refresh full whatever the update, e.g. after the panel
+    /// was switched to night mode and every pixel has to flip.
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+
+    /// Record a refresh performed with `mode` at `now_ms`.
+    ///
+    /// Call this for refreshes the application forces itself (e.g. a GC16
+    /// on wake) so the wear estimate stays accurate.
+    pub fn record(&mut self, mode: RefreshMode, now_ms: u64) {
+        let step = match mode {
+            RefreshMode::Full => {
+                self.invalidated = false;
+                self.partials_since_full = 0;
+                self.estimated_ghosting_permille = 0;
+                self.last_full_ms = Some(now_ms);
+                return;
+            }
+            RefreshMode::Partial => self.config.partial_ghosting_permille,
+            RefreshMode::Fast => self.config.fast_ghosting_permille,
+        };
+        self.partials_since_full = self.partials_since_full.saturating_add(1);
+        self.estimated_ghosting_permille = self
+            .estimated_ghosting_permille
+            .saturating_add(step)
+            .min(1000);
+    }
+
+    /// Dirty area as permille of the screen (saturating at 1000).
+    fn area_permille(&self, dirty_pixels: u32) -> u16 {
+        if self.screen_pixels == 0 {
+            return 1000;
+        }
+        let permille = u64::from(dirty_pixels)
+            .saturating_mul(1000)
+            .checked_div(u64::from(self.screen_pixels))
+            .unwrap_or(1000)
+            .min(1000);
+        u16::try_from(permille).unwrap_or(1000)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#[allow(clippy::arithmetic_side_effects)]
+mod tests {
+    use super::*;
+
+    const W: u32 = 800;
+    const H: u32 = 480;
+    const SCREEN: u32 = W * H;
+
+    /// Policy that has already done its boot-time full refresh at t = 0.
+    fn booted() -> RefreshPolicy {
+        let mut policy = RefreshPolicy::new(SCREEN);
+        policy.record(RefreshMode::Full, 0);
+        policy
+    }
+
+    fn text_line() -> Update {
+        Update::new(W * 24, ContentHint::Text)
+    }
+
+    #[test]
+    fn invalidate_forces_one_full_refresh() {
+        let mut policy = booted();
+        policy.invalidate();
+        let choice = policy.decide(text_line(), PanelState::UNKNOWN, 10);
+        assert_eq!(choice.mode, RefreshMode::Full);
+        assert_eq!(choice.reason, RefreshReason::Invalidated);
+        assert_eq!(
+            policy.decide(text_line(), PanelState::UNKNOWN, 20).mode,
+            RefreshMode::Partial
+        );
+    }
+
+    #[test]
+    fn first_frame_is_full() {
+        let mut policy = RefreshPolicy::new(SCREEN);
+        let choice = policy.decide(text_line(), PanelState::UNKNOWN, 0);
+        assert_eq!(choice.mode, RefreshMode::Full);
+        assert_eq!(choice.reason, RefreshReason::FirstFrame);
+        assert_eq!(
+            policy.decide(text_line(), PanelState::UNKNOWN, 10).mode,
+            RefreshMode::Partial
+        );
+    }
+
+    #[test]
+    fn menu_scrolling_uses_du4_until_partial_limit() {
+        // Scrolling a list: highlight moves between two rows each step.
+        let mut policy = booted();
+        let cfg = *policy.config();
+        let mut modes = Vec::new();
+        for step in 1..=u64::from(cfg.max_partials) + 1 {
+            let choice = policy.decide(
+                Update::new(2 * W * 40, ContentHint::Text),
+                PanelState::UNKNOWN,
+                step * 300,
+            );
+            modes.push(choice);
+        }
+        let (last, rest) = modes.split_last().unwrap();
+        assert!(rest.iter().all(|c| c.mode == RefreshMode::Partial));
+        assert_eq!(last.mode, RefreshMode::Full);
+        assert_eq!(last.reason, RefreshReason::PartialCount);
+        assert_eq!(policy.partials_since_full(), 0);
+    }
+
+    #[test]
+    fn progress_bar_uses_du() {
+        let mut policy = booted();
+        let bar = Update::new(W * 8, ContentHint::Animation);
+        for second in 1..=5 {
+            let choice = policy.decide(bar, PanelState::UNKNOWN, second * 1000);
+            assert_eq!(choice.mode, RefreshMode::Fast);
+            assert_eq!(choice.reason, RefreshReason::Animation);
+        }
+        assert_eq!(policy.estimated_ghosting_permille(), 5 * 25);
+    }
+
+    #[test]
+    fn album_art_gets_gc16_but_thumbnails_do_not() {
+        let policy = booted();
+        let art = Update::new(300 * 300, ContentHint::Image);
+        assert_eq!(
+            policy.choose(art, PanelState::UNKNOWN, 1000).reason,
+            RefreshReason::Image
+        );
+        let thumb = Update::new(48 * 48, ContentHint::Image);
+        assert_eq!(
+            policy.choose(thumb, PanelState::UNKNOWN, 1000).mode,
+            RefreshMode::Partial
+        );
+    }
+
+    #[test]
+    fn page_transition_is_full() {
+        let policy = booted();
+        let page = Update::new(SCREEN * 7 / 10, ContentHint::Text);
+        let choice = policy.choose(page, PanelState::UNKNOWN, 1000);
+        assert_eq!(choice.mode, RefreshMode::Full);
+        assert_eq!(choice.reason, RefreshReason::LargeArea);
+        // Larger than the screen (overlapping rects) saturates.
+        let huge = Update::new(u32::MAX, ContentHint::Text);
+        assert_eq!(
+            policy.choose(huge, PanelState::UNKNOWN, 1000).reason,
+            RefreshReason::LargeArea
+        );
+    }
+
+    #[test]
+    fn idle_reading_triggers_interval_full() {
+        let mut policy = booted();
+        policy.decide(text_line(), PanelState::UNKNOWN, 1000);
+        let later = policy.config().full_interval_ms;
+        let choice = policy.decide(text_line(), PanelState::UNKNOWN, later);
+        assert_eq!(choice.reason, RefreshReason::Interval);
+        // The interval restarts from the full refresh.
+        assert_eq!(
+            policy
+                .decide(text_line(), PanelState::UNKNOWN, later + 1000)
+                .mode,
+            RefreshMode::Partial
+        );
+    }
+
+    #[test]
+    fn estimated_ghosting_forces_full() {
+        let config = RefreshPolicyConfig {
+            max_partials: u16::MAX,
+            ..RefreshPolicyConfig::DEFAULT
+        };
+        let mut policy = RefreshPolicy::with_config(SCREEN, config);
+        policy.record(RefreshMode::Full, 0);
+        let bar = Update::new(W * 8, ContentHint::Animation);
+        let mut fulls = 0;
+        for tick in 1..=40 {
+            if policy.decide(bar, PanelState::UNKNOWN, tick * 100).reason == RefreshReason::Ghosting
+            {
+                fulls += 1;
+            }
+        }
+        // 300 / 25 = 12 DU refreshes, then GC16 (13th); 40 steps → 3 cleanups.
+        assert_eq!(fulls, 3);
+    }
+
+    #[test]
+    fn measured_panel_state_takes_precedence() {
+        let policy = booted();
+        let ghosted = PanelState::with_ghosting(0.5);
+        assert_eq!(ghosted.ghosting_permille, Some(500));
+        assert_eq!(
+            policy.choose(text_line(), ghosted, 1000).reason,
+            RefreshReason::Ghosting
+        );
+        // Measured clean panel overrides a pessimistic estimate.
+        let mut worn = booted();
+        for t in 1..=12 {
+            worn.record(RefreshMode::Fast, t);
+        }
+        assert_eq!(
+            worn.choose(text_line(), PanelState::UNKNOWN, 100).reason,
+            RefreshReason::Ghosting
+        );
+        assert_eq!(
+            worn.choose(text_line(), PanelState::with_ghosting(0.05), 100)
+                .mode,
+            RefreshMode::Partial
+        );
+
+        let dc = PanelState {
+            dc_balance: Some(35),
+            ..PanelState::UNKNOWN
+        };
+        assert_eq!(
+            policy.choose(text_line(), dc, 1000).reason,
+            RefreshReason::DcBalance
+        );
+    }
+
+    #[test]
+    fn zero_sized_screen_treats_every_update_as_large() {
+        let mut policy = RefreshPolicy::new(0);
+        policy.record(RefreshMode::Full, 0);
+        assert_eq!(
+            policy.choose(text_line(), PanelState::UNKNOWN, 1).reason,
+            RefreshReason::LargeArea
+        );
+    }
+}
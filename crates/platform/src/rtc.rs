@@ -0,0 +1,432 @@
+//! Real-time clock abstraction
+//!
+//! Wall-clock time for the status bar clock, the sleep timer, scrobble log
+//! timestamps and comparing file-modified times during a library rescan.
+//!
+//! On the STM32H7 the RTC runs from the 32.768 kHz LSE in the backup domain,
+//! so the time (and the backup registers) survive resets and, with a coin
+//! cell or supercap on VBAT, power-off.  The emulator reads the host clock
+//! (see `rtc_system::SystemRtc`).
+//!
+//! [`DateTime`] is a plain calendar time without a time zone; the device
+//! keeps local time, as the status bar shows it.
+
+use core::fmt;
+
+/// Earliest representable year (Unix epoch).
+pub const MIN_YEAR: u16 = 1970;
+
+/// Latest representable year.
+///
+/// The STM32 RTC stores a two-digit year, so 2099 is the last year the
+/// hardware can hold.  Within 1970–2099 every fourth year is a leap year
+/// (2000 is divisible by 400), which keeps the calendar arithmetic simple.
+pub const MAX_YEAR: u16 = 2099;
+
+/// General-purpose backup registers available through [`Rtc`].
+///
+/// The STM32H7 has 32 (`RTC_BKP0R`–`RTC_BKP31R`); the last one holds the
+/// "time was set" marker behind [`Rtc::is_time_valid`].
+pub const BACKUP_REGISTERS: usize = 31;
+
+/// Seconds per day.
+const SECS_PER_DAY: u64 = 86_400;
+
+/// Days before the first of each month in a non-leap year.
+const DAYS_BEFORE_MONTH: [u16; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+
+/// Real-time clock interface
+pub trait Rtc {
+    /// Error type
+    type Error: core::fmt::Debug;
+
+    /// Current local time.
+    fn now(&self) -> Result<DateTime, Self::Error>;
+
+    /// Set the clock.
+    fn set_time(&mut self, time: DateTime) -> Result<(), Self::Error>;
+
+    /// Whether the clock has been set since the backup domain last lost
+    /// power.  When `false`, [`now`](Self::now) counts from a reset value
+    /// and must not be shown to the user or written to logs.
+    fn is_time_valid(&self) -> bool;
+
+    /// Arm the alarm for `at`, replacing any previous alarm.
+    fn set_alarm(&mut self, at: DateTime) -> Result<(), Self::Error>;
+
+    /// Disarm the alarm.
+    fn cancel_alarm(&mut self);
+
+    /// The armed alarm, if any.
+    fn alarm(&self) -> Option<DateTime>;
+
+    /// Wait until the armed alarm time is reached, then disarm it.
+    ///
+    /// Returns at once if the alarm time has already passed.
+    async fn wait_alarm(&mut self) -> Result<(), Self::Error>;
+
+    /// Read backup register `index` (`0..BACKUP_REGISTERS`).
+    fn read_backup(&self, index: usize) -> Option<u32>;
+
+    /// Write backup register `index` (`0..BACKUP_REGISTERS`).
+    fn write_backup(&mut self, index: usize, value: u32) -> Result<(), Self::Error>;
+}
+
+/// Errors shared by the [`Rtc`] implementations in this workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RtcError {
+    /// A calendar field is out of range.
+    InvalidDateTime,
+    /// [`Rtc::wait_alarm`] was called with no alarm armed.
+    NoAlarm,
+    /// Backup register index out of range.
+    InvalidBackupRegister,
+    /// The RTC peripheral is not running or could not be read.
+    Hardware,
+}
+
+impl fmt::Display for RtcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidDateTime => write!(f, "date/time out of range"),
+            Self::NoAlarm => write!(f, "no alarm armed"),
+            Self::InvalidBackupRegister => write!(f, "backup register out of range"),
+            Self::Hardware => write!(f, "RTC not running"),
+        }
+    }
+}
+
+/// Day of the week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Weekday {
+    /// Monday
+    Monday,
+    /// Tuesday
+    Tuesday,
+    /// Wednesday
+    Wednesday,
+    /// Thursday
+    Thursday,
+    /// Friday
+    Friday,
+    /// Saturday
+    Saturday,
+    /// Sunday
+    Sunday,
+}
+
+/// A validated calendar date and time of day, 1970-01-01 to 2099-12-31.
+///
+/// Ordering is chronological.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DateTime {
+    // Field order matters: the derived `Ord` compares year first.
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl DateTime {
+    /// Midnight, 1 January 1970.
+    pub const UNIX_EPOCH: Self = Self {
+        year: MIN_YEAR,
+        month: 1,
+        day: 1,
+        hour: 0,
+        minute: 0,
+        second: 0,
+    };
+
+    /// Build a date/time, checking every field.
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> Result<Self, RtcError> {
+        let valid = (MIN_YEAR..=MAX_YEAR).contains(&year)
+            && (1..=12).contains(&month)
+            && day >= 1
+            && day <= days_in_month(year, month)
+            && hour < 24
+            && minute < 60
+            && second < 60;
+        if !valid {
+            return Err(RtcError::InvalidDateTime);
+        }
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// Year (1970–2099).
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Month (1–12).
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Day of the month (1–31).
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Hour (0–23).
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Minute (0–59).
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Second (0–59).
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Day of the week.
+    pub fn weekday(&self) -> Weekday {
+        // 1970-01-01 was a Thursday.
+        match self.days_since_epoch() % 7 {
+            0 => Weekday::Thursday,
+            1 => Weekday::Friday,
+            2 => Weekday::Saturday,
+            3 => Weekday::Sunday,
+            4 => Weekday::Monday,
+            5 => Weekday::Tuesday,
+            _ => Weekday::Wednesday,
+        }
+    }
+
+    /// Seconds since 1970-01-01 00:00:00, treating this time as UTC.
+    #[allow(clippy::arithmetic_side_effects)] // Bounded: < 2100-01-01 fits easily in u64
+    pub fn to_unix_secs(&self) -> u64 {
+        self.days_since_epoch() * SECS_PER_DAY
+            + u64::from(self.hour) * 3_600
+            + u64::from(self.minute) * 60
+            + u64::from(self.second)
+    }
+
+    /// Inverse of [`to_unix_secs`](Self::to_unix_secs).
+    #[allow(clippy::arithmetic_side_effects)] // Bounded: loops stop within 1970–2099
+    #[allow(clippy::cast_possible_truncation)] // Remainders are < 86_400, < 60, < 24
+    pub fn from_unix_secs(secs: u64) -> Result<Self, RtcError> {
+        let mut days = secs / SECS_PER_DAY;
+        let rem = secs % SECS_PER_DAY;
+
+        let mut year = MIN_YEAR;
+        while days >= u64::from(days_in_year(year)) {
+            days -= u64::from(days_in_year(year));
+            year += 1;
+            if year > MAX_YEAR {
+                return Err(RtcError::InvalidDateTime);
+            }
+        }
+        let mut month = 1;
+        while days >= u64::from(days_in_month(year, month)) {
+            days -= u64::from(days_in_month(year, month));
+            month += 1;
+        }
+
+        Self::new(
+            year,
+            month,
+            days as u8 + 1,
+            (rem / 3_600) as u8,
+            (rem / 60 % 60) as u8,
+            (rem % 60) as u8,
+        )
+    }
+
+    /// `secs` later, e.g. the sleep timer deadline.
+    pub fn checked_add_secs(&self, secs: u64) -> Option<Self> {
+        Self::from_unix_secs(self.to_unix_secs().checked_add(secs)?).ok()
+    }
+
+    /// Seconds from `earlier` to `self`; `None` if `earlier` is later.
+    pub fn secs_since(&self, earlier: &Self) -> Option<u64> {
+        self.to_unix_secs().checked_sub(earlier.to_unix_secs())
+    }
+
+    /// Decode a FAT directory-entry timestamp (`date`, `time` words).
+    ///
+    /// FAT stores local time with two-second resolution from 1980.  Returns
+    /// `None` for zeroed or corrupt fields.
+    #[allow(clippy::arithmetic_side_effects)] // Shifts/masks on u16 fields
+    #[allow(clippy::cast_possible_truncation)] // Masked to ≤ 7 bits before casting
+    pub fn from_fat(date: u16, time: u16) -> Option<Self> {
+        Self::new(
+            1980 + (date >> 9),
+            ((date >> 5) & 0x0F) as u8,
+            (date & 0x1F) as u8,
+            (time >> 11) as u8,
+            ((time >> 5) & 0x3F) as u8,
+            (time & 0x1F) as u8 * 2,
+        )
+        .ok()
+    }
+
+    /// Encode as a FAT `(date, time)` pair (seconds rounded down to even);
+    /// `None` before 1980.
+    #[allow(clippy::arithmetic_side_effects)] // Fields are range-checked by `new`
+    pub fn to_fat(&self) -> Option<(u16, u16)> {
+        let years = self.year.checked_sub(1980)?;
+        let date = (years << 9) | (u16::from(self.month) << 5) | u16::from(self.day);
+        let time = (u16::from(self.hour) << 11)
+            | (u16::from(self.minute) << 5)
+            | u16::from(self.second / 2);
+        Some((date, time))
+    }
+
+    #[allow(clippy::arithmetic_side_effects)] // Bounded by MIN_YEAR..=MAX_YEAR
+    fn days_since_epoch(self) -> u64 {
+        let years = u64::from(self.year - MIN_YEAR);
+        // Leap years in [1970, year): 1972, 1976, …
+        let leap_days = (years + 1) / 4;
+        let month_index = usize::from(self.month - 1);
+        let mut days_into_year =
+            u64::from(DAYS_BEFORE_MONTH.get(month_index).copied().unwrap_or(0))
+                + u64::from(self.day - 1);
+        if is_leap_year(self.year) && self.month > 2 {
+            days_into_year += 1;
+        }
+        years * 365 + leap_days + days_into_year
+    }
+}
+
+impl fmt::Display for DateTime {
+    /// ISO 8601, e.g. `2026-10-15T09:41:00`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+/// Leap year within [`MIN_YEAR`]..=[`MAX_YEAR`].
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4)
+}
+
+fn days_in_year(year: u16) -> u16 {
+    if is_leap_year(year) {
+        366
+    } else {
+        365
+    }
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+
+    fn dt(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> DateTime {
+        DateTime::new(year, month, day, hour, minute, second).unwrap()
+    }
+
+    #[test]
+    fn new_rejects_out_of_range_fields() {
+        assert!(DateTime::new(2024, 2, 29, 0, 0, 0).is_ok());
+        assert!(DateTime::new(2023, 2, 29, 0, 0, 0).is_err());
+        assert!(DateTime::new(2024, 4, 31, 0, 0, 0).is_err());
+        assert!(DateTime::new(2024, 13, 1, 0, 0, 0).is_err());
+        assert!(DateTime::new(2024, 1, 1, 24, 0, 0).is_err());
+        assert!(DateTime::new(1969, 12, 31, 0, 0, 0).is_err());
+        assert!(DateTime::new(2100, 1, 1, 0, 0, 0).is_err());
+    }
+
+    #[test]
+    fn unix_seconds_match_known_instants() {
+        assert_eq!(DateTime::UNIX_EPOCH.to_unix_secs(), 0);
+        assert_eq!(dt(2000, 3, 1, 0, 0, 0).to_unix_secs(), 951_868_800);
+        assert_eq!(dt(2026, 10, 15, 9, 41, 7).to_unix_secs(), 1_792_057_267);
+        assert_eq!(dt(2099, 12, 31, 23, 59, 59).to_unix_secs(), 4_102_444_799);
+        assert!(DateTime::from_unix_secs(4_102_444_800).is_err());
+    }
+
+    #[test]
+    fn unix_seconds_round_trip_across_every_month_boundary() {
+        for year in [1970, 1999, 2000, 2024, 2099] {
+            for month in 1..=12 {
+                let last = days_in_month(year, month);
+                for t in [
+                    dt(year, month, 1, 0, 0, 0),
+                    dt(year, month, last, 23, 59, 59),
+                ] {
+                    assert_eq!(DateTime::from_unix_secs(t.to_unix_secs()), Ok(t), "{t}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn weekday_and_ordering() {
+        assert_eq!(DateTime::UNIX_EPOCH.weekday(), Weekday::Thursday);
+        assert_eq!(dt(2026, 10, 15, 12, 0, 0).weekday(), Weekday::Thursday);
+        assert_eq!(dt(2024, 2, 29, 0, 0, 0).weekday(), Weekday::Thursday);
+        assert_eq!(dt(2000, 1, 2, 0, 0, 0).weekday(), Weekday::Sunday);
+        assert!(dt(2025, 12, 31, 23, 59, 59) < dt(2026, 1, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn sleep_timer_arithmetic() {
+        let start = dt(2024, 2, 28, 23, 30, 0);
+        let end = start.checked_add_secs(45 * 60).unwrap();
+        assert_eq!(end, dt(2024, 2, 29, 0, 15, 0));
+        assert_eq!(end.secs_since(&start), Some(45 * 60));
+        assert_eq!(start.secs_since(&end), None);
+        assert_eq!(dt(2099, 12, 31, 23, 59, 0).checked_add_secs(60), None);
+    }
+
+    #[test]
+    #[allow(clippy::decimal_bitwise_operands)] // Year, month and day fields
+    fn fat_timestamps_round_trip() {
+        let t = dt(2023, 7, 14, 18, 5, 42);
+        let (date, time) = t.to_fat().unwrap();
+        assert_eq!(date, (43 << 9) | (7 << 5) | 14);
+        assert_eq!(DateTime::from_fat(date, time), Some(t));
+        // Odd seconds round down to FAT's two-second resolution.
+        let odd = dt(2023, 7, 14, 18, 5, 43).to_fat().unwrap();
+        assert_eq!(DateTime::from_fat(odd.0, odd.1), Some(t));
+
+        assert_eq!(DateTime::from_fat(0, 0), None, "zeroed entry");
+        assert_eq!(dt(1979, 12, 31, 0, 0, 0).to_fat(), None);
+    }
+
+    #[test]
+    fn display_is_iso_8601() {
+        let t = dt(2026, 1, 5, 7, 3, 9);
+        assert_eq!(format!("{t}"), "2026-01-05T07:03:09");
+    }
+}
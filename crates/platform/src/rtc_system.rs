@@ -0,0 +1,165 @@
+//! Host-clock [`Rtc`] implementation for the desktop emulator.
+//!
+//! `SystemRtc` reads `std::time::SystemTime` (UTC).  [`Rtc::set_time`] does
+//! not touch the host clock; it stores an offset, so the emulator's "set
+//! clock" screen behaves like the device's.  Backup registers live in memory
+//! and the alarm is a timer on the host, waking once the alarm time is
+//! reached (host clock jumps are picked up on the next check).
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::rtc::{DateTime, Rtc, RtcError, BACKUP_REGISTERS};
+
+/// Longest single sleep in [`SystemRtc::wait_alarm`], so a host clock change
+/// delays the alarm by at most this long.
+const MAX_ALARM_SLEEP_SECS: u64 = 60;
+
+/// [`Rtc`] backed by the host's system clock.
+#[derive(Debug, Clone)]
+pub struct SystemRtc {
+    /// Seconds added to the host clock (set by [`Rtc::set_time`]).
+    offset_secs: i64,
+    alarm: Option<DateTime>,
+    backup: [u32; BACKUP_REGISTERS],
+}
+
+impl SystemRtc {
+    /// Follow the host clock, as UTC.
+    pub fn new() -> Self {
+        Self::with_utc_offset(0)
+    }
+
+    /// Follow the host clock shifted by `offset_secs` (e.g. `3_600` for
+    /// UTC+1), since the device shows local time.
+    pub fn with_utc_offset(offset_secs: i64) -> Self {
+        Self {
+            offset_secs,
+            alarm: None,
+            backup: [0; BACKUP_REGISTERS],
+        }
+    }
+
+    fn host_secs() -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| i64::try_from(d.as_secs()).unwrap_or(i64::MAX))
+    }
+}
+
+impl Default for SystemRtc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Rtc for SystemRtc {
+    type Error = RtcError;
+
+    fn now(&self) -> Result<DateTime, RtcError> {
+        let secs = Self::host_secs().saturating_add(self.offset_secs);
+        let secs = u64::try_from(secs).map_err(|_| RtcError::InvalidDateTime)?;
+        DateTime::from_unix_secs(secs)
+    }
+
+    fn set_time(&mut self, time: DateTime) -> Result<(), RtcError> {
+        let target = i64::try_from(time.to_unix_secs()).map_err(|_| RtcError::InvalidDateTime)?;
+        self.offset_secs = target.saturating_sub(Self::host_secs());
+        Ok(())
+    }
+
+    fn is_time_valid(&self) -> bool {
+        // The host clock is always set.
+        true
+    }
+
+    fn set_alarm(&mut self, at: DateTime) -> Result<(), RtcError> {
+        self.alarm = Some(at);
+        Ok(())
+    }
+
+    fn cancel_alarm(&mut self) {
+        self.alarm = None;
+    }
+
+    fn alarm(&self) -> Option<DateTime> {
+        self.alarm
+    }
+
+    async fn wait_alarm(&mut self) -> Result<(), RtcError> {
+        let at = self.alarm.ok_or(RtcError::NoAlarm)?;
+        loop {
+            let Some(remaining) = at.secs_since(&self.now()?).filter(|&s| s > 0) else {
+                self.alarm = None;
+                return Ok(());
+            };
+            let sleep = remaining.min(MAX_ALARM_SLEEP_SECS);
+            embassy_time::Timer::after(embassy_time::Duration::from_secs(sleep)).await;
+        }
+    }
+
+    fn read_backup(&self, index: usize) -> Option<u32> {
+        self.backup.get(index).copied()
+    }
+
+    fn write_backup(&mut self, index: usize, value: u32) -> Result<(), RtcError> {
+        let slot = self
+            .backup
+            .get_mut(index)
+            .ok_or(RtcError::InvalidBackupRegister)?;
+        *slot = value;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+
+    #[test]
+    fn now_tracks_host_clock_and_set_time_shifts_it() {
+        let mut rtc = SystemRtc::new();
+        let host = SystemRtc::host_secs().unsigned_abs();
+        assert!(rtc.now().unwrap().to_unix_secs().abs_diff(host) <= 1);
+        assert!(rtc.is_time_valid());
+
+        let set = DateTime::new(2031, 6, 1, 12, 0, 0).unwrap();
+        rtc.set_time(set).unwrap();
+        assert!(rtc.now().unwrap().secs_since(&set).unwrap() <= 1);
+
+        let local = SystemRtc::with_utc_offset(3_600);
+        let diff =
+            local.now().unwrap().to_unix_secs() - SystemRtc::new().now().unwrap().to_unix_secs();
+        assert!((3_599..=3_601).contains(&diff));
+    }
+
+    #[test]
+    fn backup_registers_are_bounded() {
+        let mut rtc = SystemRtc::new();
+        rtc.write_backup(0, 0xDEAD_BEEF).unwrap();
+        assert_eq!(rtc.read_backup(0), Some(0xDEAD_BEEF));
+        assert_eq!(rtc.read_backup(BACKUP_REGISTERS), None);
+        assert_eq!(
+            rtc.write_backup(BACKUP_REGISTERS, 1),
+            Err(RtcError::InvalidBackupRegister)
+        );
+    }
+
+    #[tokio::test]
+    async fn past_alarm_fires_immediately_and_disarms() {
+        let mut rtc = SystemRtc::new();
+        assert_eq!(rtc.wait_alarm().await, Err(RtcError::NoAlarm));
+
+        let past = rtc.now().unwrap();
+        rtc.set_alarm(past).unwrap();
+        assert_eq!(rtc.alarm(), Some(past));
+        rtc.wait_alarm().await.unwrap();
+        assert_eq!(rtc.alarm(), None);
+
+        rtc.set_alarm(past.checked_add_secs(3_600).unwrap())
+            .unwrap();
+        rtc.cancel_alarm();
+        assert_eq!(rtc.alarm(), None);
+    }
+}
@@ -0,0 +1,116 @@
+//! Silence trimming settings.
+//!
+//! Vinyl rips and some CD rips carry seconds of near-silence before the
+//! first note or after the last.  With trimming on, the decode path skips
+//! silent runs of at least [`SilenceTrimConfig::min_ms`] at either end of a
+//! track (the stage lives in the `playback` crate).  "Silent" means every
+//! sample of every channel at or below [`SilenceTrimConfig::threshold_db`].
+//! Off by default; `soul.toml` turns it on and tunes it (see
+//! [`soul_config`](crate::soul_config)).
+
+/// `10^(-r/20)` in Q16 for `r` in `0..20` dB.
+const DB_Q16: [u32; 20] = [
+    65536, 58409, 52057, 46396, 41350, 36854, 32846, 29274, 26090, 23253, 20724, 18471, 16462,
+    14672, 13076, 11654, 10387, 9257, 8250, 7353,
+];
+
+/// How silence at the ends of a track is found and skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SilenceTrimConfig {
+    /// Skip silence at all.
+    pub enabled: bool,
+    /// Loudest level that counts as silence, in dBFS
+    /// ([`MIN_THRESHOLD_DB`](Self::MIN_THRESHOLD_DB)..=[`MAX_THRESHOLD_DB`](Self::MAX_THRESHOLD_DB)).
+    pub threshold_db: i8,
+    /// Shortest silence skipped, in milliseconds; shorter gaps are part of
+    /// the music.
+    pub min_ms: u32,
+}
+
+impl SilenceTrimConfig {
+    /// Off; when turned on, −60 dBFS for at least 2 s.
+    pub const DEFAULT: Self = Self {
+        enabled: false,
+        threshold_db: -60,
+        min_ms: 2_000,
+    };
+
+    /// Quietest threshold accepted.
+    pub const MIN_THRESHOLD_DB: i8 = -90;
+    /// Loudest threshold accepted.
+    pub const MAX_THRESHOLD_DB: i8 = -30;
+
+    /// The threshold as a left-justified 32-bit sample magnitude.
+    /// Thresholds outside the accepted range are clamped to it.
+    #[must_use]
+    pub fn threshold(self) -> u32 {
+        let db = self
+            .threshold_db
+            .clamp(Self::MIN_THRESHOLD_DB, Self::MAX_THRESHOLD_DB)
+            .unsigned_abs();
+        let mut level = u64::from(i32::MAX.unsigned_abs());
+        for _ in 0..db / 20 {
+            level /= 10;
+        }
+        let fraction = DB_Q16
+            .get(usize::from(db % 20))
+            .copied()
+            .map_or(0, u64::from);
+        u32::try_from(level.saturating_mul(fraction) >> 16).unwrap_or(u32::MAX)
+    }
+
+    /// [`min_ms`](Self::min_ms) in frames at `sample_rate`.
+    #[must_use]
+    pub fn min_frames(self, sample_rate: u32) -> u32 {
+        let frames = u64::from(self.min_ms).saturating_mul(u64::from(sample_rate)) / 1_000;
+        u32::try_from(frames).unwrap_or(u32::MAX)
+    }
+}
+
+impl Default for SilenceTrimConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SilenceTrimConfig;
+
+    fn at(threshold_db: i8) -> u32 {
+        SilenceTrimConfig {
+            threshold_db,
+            ..SilenceTrimConfig::DEFAULT
+        }
+        .threshold()
+    }
+
+    #[test]
+    fn threshold_follows_the_decibels() {
+        let full = f64::from(i32::MAX);
+        for db in [-30i8, -47, -60, -73, -90] {
+            let expected = full * 10f64.powf(f64::from(db) / 20.0);
+            let got = f64::from(at(db));
+            assert!(
+                (got / expected - 1.0).abs() < 0.001,
+                "{db} dB: {got} vs {expected}"
+            );
+        }
+        assert_eq!(at(-120), at(-90), "clamped to the quietest");
+        assert_eq!(at(0), at(-30), "clamped to the loudest");
+    }
+
+    #[test]
+    fn min_duration_in_frames() {
+        let config = SilenceTrimConfig::DEFAULT;
+        assert!(!config.enabled);
+        assert_eq!(config.min_frames(44_100), 88_200);
+        assert_eq!(config.min_frames(192_000), 384_000);
+        let long = SilenceTrimConfig {
+            min_ms: u32::MAX,
+            ..config
+        };
+        assert_eq!(long.min_frames(384_000), u32::MAX);
+    }
+}
@@ -0,0 +1,98 @@
+//! Post-flash smoke-test markers.
+//!
+//! The firmware logs each marker over defmt/RTT as the matching subsystem
+//! comes up; `cargo xtask flash --smoke` watches the RTT stream after
+//! flashing and fails if a required marker does not arrive in time.  Both
+//! sides take the strings from here so they cannot drift apart.
+//!
+//! # Example
+//!
+//! ```
+//! use platform::smoke::SmokeMarker;
+//!
+//! let line = "0.021 INFO  SMOKE display-ok";
+//! assert_eq!(SmokeMarker::find(line), Some(SmokeMarker::DisplayInit));
+//! ```
+
+/// A boot milestone reported over RTT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SmokeMarker {
+    /// Clocks, MPU and watchdog are configured.
+    Boot,
+    /// The SSD1677 accepted its init sequence.
+    DisplayInit,
+    /// The SD card filesystem is mounted.
+    SdMounted,
+    /// The SAI audio task is running.
+    AudioAlive,
+}
+
+impl SmokeMarker {
+    /// Every marker, in the order the firmware emits them.
+    pub const ALL: [Self; 4] = [
+        Self::Boot,
+        Self::DisplayInit,
+        Self::SdMounted,
+        Self::AudioAlive,
+    ];
+
+    /// Text the firmware logs; the host matches it as a substring of the
+    /// decoded defmt line, so timestamps and level prefixes don't matter.
+    pub const fn tag(self) -> &'static str {
+        match self {
+            Self::Boot => "SMOKE boot-ok",
+            Self::DisplayInit => "SMOKE display-ok",
+            Self::SdMounted => "SMOKE sd-mounted",
+            Self::AudioAlive => "SMOKE audio-alive",
+        }
+    }
+
+    /// Human-readable name for the host-side summary.
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Boot => "Boot OK",
+            Self::DisplayInit => "Display init OK",
+            Self::SdMounted => "SD mounted",
+            Self::AudioAlive => "Audio task alive",
+        }
+    }
+
+    /// Whether a missing marker fails the smoke test.
+    ///
+    /// `SdMounted` is reported but not yet required: SDMMC1 bring-up is
+    /// still pending in the firmware entry point, so no build emits it.
+    pub const fn required(self) -> bool {
+        !matches!(self, Self::SdMounted)
+    }
+
+    /// The marker whose tag appears in `line`, if any.
+    pub fn find(line: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|m| line.contains(m.tag()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_are_distinct_and_none_contains_another() {
+        for a in SmokeMarker::ALL {
+            for b in SmokeMarker::ALL {
+                if a != b {
+                    assert!(!a.tag().contains(b.tag()), "{a:?} contains {b:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn find_ignores_unrelated_lines() {
+        assert_eq!(SmokeMarker::find("INFO  Entering main loop"), None);
+        assert_eq!(
+            SmokeMarker::find("1.002 INFO  SMOKE audio-alive"),
+            Some(SmokeMarker::AudioAlive)
+        );
+    }
+}
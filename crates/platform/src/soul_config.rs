@@ -0,0 +1,607 @@
+//! Power-user settings from `soul.toml` at the card root.
+//!
+//! The file is optional and only overrides: anything it leaves out keeps
+//! the device's own setting.  The firmware reads it once at boot with
+//! [`load`]; a problem never stops the boot.  Keys that parse are applied,
+//! and the rest come back as [`ConfigError`]s with a line number and a
+//! message, which the diagnostics screen lists.
+//!
+//! ```toml
+//! [audio]
+//! eq = "bass_boost"      # default for new output profiles
+//! crossfeed = "light"
+//! max_volume = 80        # 0-100
+//! trim_silence = true    # skip silence at the start and end of tracks
+//! silence_threshold_db = -60   # -90 to -30
+//! silence_min_ms = 2000        # 250-30000
+//!
+//! [library]
+//! exclude = ["Audiobooks/old", "*.tmp"]
+//!
+//! [ui]
+//! full_refresh_every = 20     # partial refreshes between full refreshes
+//! full_refresh_minutes = 5
+//! accessible = true           # large text, high contrast, fewer rows
+//! night_mode = true           # white on black, less glare in the dark
+//!
+//! [developer]
+//! cpu_usage = true
+//! verbose_log = false
+//! skip_library_scan = false
+//! ```
+//!
+//! Preset names are the menu labels in lower case with `_` for spaces.
+//! The syntax is the subset read by [`toml_subset`](crate::toml_subset).
+
+use core::fmt;
+
+use heapless::{String, Vec};
+
+use crate::output_profile::{Crossfeed, EqPreset, OutputProfile};
+use crate::refresh_policy::RefreshPolicyConfig;
+use crate::silence_trim::SilenceTrimConfig;
+use crate::storage::{File, Storage};
+use crate::toml_subset::{Parser, Value};
+
+/// Location of the file on the card.
+pub const CONFIG_PATH: &str = "/soul.toml";
+
+/// Suggested size of the buffer passed to [`load`] (bytes).
+pub const MAX_CONFIG_BYTES: usize = 4096;
+
+/// Scan exclusion patterns kept.
+pub const MAX_EXCLUDES: usize = 16;
+
+/// Longest exclusion pattern (bytes).
+pub const EXCLUDE_LEN: usize = 64;
+
+/// Errors kept per load; later ones are only counted.
+pub const MAX_ERRORS: usize = 8;
+
+/// Longest `table.key` name kept in a [`ConfigError`] (bytes).
+pub const KEY_NAME_LEN: usize = 32;
+
+/// `[audio]`: defaults for new output profiles, and silence trimming.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AudioOverrides {
+    /// Equaliser preset.
+    pub eq: Option<EqPreset>,
+    /// Crossfeed level.
+    pub crossfeed: Option<Crossfeed>,
+    /// Volume ceiling (0–100).
+    pub max_volume: Option<u8>,
+    /// Skip silence at the ends of tracks.
+    pub trim_silence: Option<bool>,
+    /// Silence threshold in dBFS.
+    pub silence_threshold_db: Option<i8>,
+    /// Shortest silence skipped (ms).
+    pub silence_min_ms: Option<u16>,
+}
+
+/// `[library]`: scanner settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LibraryOverrides {
+    /// Paths or globs the scanner skips.
+    pub exclude: Vec<String<EXCLUDE_LEN>, MAX_EXCLUDES>,
+}
+
+/// `[ui]`: display refresh tuning and presentation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UiOverrides {
+    /// Partial refreshes allowed between full refreshes.
+    pub full_refresh_every: Option<u16>,
+    /// Longest time between full refreshes (minutes).
+    pub full_refresh_minutes: Option<u16>,
+    /// Accessibility mode: large text, high contrast, fewer rows.
+    pub accessible: Option<bool>,
+    /// Night mode: the display inverted, white on black.
+    pub night_mode: Option<bool>,
+}
+
+/// `[developer]`: debugging aids, all off by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct DeveloperFlags {
+    /// Log the per-task CPU report.
+    pub cpu_usage: bool,
+    /// Log at debug level.
+    pub verbose_log: bool,
+    /// Boot without scanning the library.
+    pub skip_library_scan: bool,
+}
+
+/// Everything `soul.toml` can set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SoulConfig {
+    /// `[audio]`.
+    pub audio: AudioOverrides,
+    /// `[library]`.
+    pub library: LibraryOverrides,
+    /// `[ui]`.
+    pub ui: UiOverrides,
+    /// `[developer]`.
+    pub developer: DeveloperFlags,
+}
+
+/// What was wrong with one setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigErrorKind {
+    /// The line is not valid syntax.
+    Syntax(&'static str),
+    /// `[table]` is not one this file knows.
+    UnknownTable,
+    /// The key is not known in its table.
+    UnknownKey,
+    /// The value has the wrong type; names the expected one.
+    Expected(&'static str),
+    /// An integer outside `min..=max`.
+    OutOfRange {
+        /// Smallest allowed value.
+        min: i64,
+        /// Largest allowed value.
+        max: i64,
+    },
+    /// A name that is not one of the allowed values; lists them.
+    UnknownName(&'static str),
+    /// An array with more items than fit; the first `max` were kept.
+    TooMany {
+        /// Items kept.
+        max: usize,
+    },
+    /// A string longer than fits.
+    TooLong {
+        /// Longest allowed length (bytes).
+        max: usize,
+    },
+    /// The file does not fit the read buffer; nothing was applied.
+    FileTooLarge,
+    /// The file is not UTF-8; nothing was applied.
+    NotUtf8,
+}
+
+/// A setting that was not applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// 1-based line, or 0 for whole-file errors.
+    pub line: u32,
+    /// `table.key`, or empty when the line has no readable key.
+    pub key: String<KEY_NAME_LEN>,
+    /// What was wrong.
+    pub kind: ConfigErrorKind,
+}
+
+impl fmt::Display for ConfigErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(reason) => f.write_str(reason),
+            Self::UnknownTable => f.write_str("unknown table"),
+            Self::UnknownKey => f.write_str("unknown key"),
+            Self::Expected(what) => write!(f, "expected {what}"),
+            Self::OutOfRange { min, max } => write!(f, "must be {min}-{max}"),
+            Self::UnknownName(names) => write!(f, "must be one of {names}"),
+            Self::TooMany { max } => write!(f, "only the first {max} are used"),
+            Self::TooLong { max } => write!(f, "longer than {max} bytes"),
+            Self::FileTooLarge => f.write_str("file is too large"),
+            Self::NotUtf8 => f.write_str("file is not UTF-8"),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}: ", self.line)?;
+        }
+        if !self.key.is_empty() {
+            write!(f, "{}: ", self.key)?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+/// Result of reading `soul.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigLoad {
+    /// The settings that were valid.
+    pub config: SoulConfig,
+    /// The first [`MAX_ERRORS`] problems, in file order.
+    pub errors: Vec<ConfigError, MAX_ERRORS>,
+    /// Problems past [`MAX_ERRORS`].
+    pub dropped_errors: u16,
+}
+
+impl ConfigLoad {
+    fn push_error(&mut self, line: u32, table: &str, key: &str, kind: ConfigErrorKind) {
+        let mut name = String::new();
+        for part in [table, ".", key] {
+            if table.is_empty() && part == "." {
+                continue;
+            }
+            for c in part.chars() {
+                if name.push(c).is_err() {
+                    break;
+                }
+            }
+        }
+        let error = ConfigError {
+            line,
+            key: name,
+            kind,
+        };
+        if self.errors.push(error).is_err() {
+            self.dropped_errors = self.dropped_errors.saturating_add(1);
+        }
+    }
+
+    /// Total number of problems, kept or dropped.
+    pub fn error_count(&self) -> usize {
+        self.errors
+            .len()
+            .saturating_add(usize::from(self.dropped_errors))
+    }
+}
+
+/// `label` and `name` match ignoring ASCII case, with `_` standing for a
+/// space (`"Bass boost"` matches `"bass_boost"`).
+fn name_matches(label: &str, name: &str) -> bool {
+    label.len() == name.len()
+        && label.bytes().zip(name.bytes()).all(|(l, n)| {
+            let n = if n == b'_' { b' ' } else { n };
+            l.eq_ignore_ascii_case(&n)
+        })
+}
+
+fn int_in(value: Value<'_>, min: i64, max: i64) -> Result<i64, ConfigErrorKind> {
+    match value {
+        Value::Int(v) if (min..=max).contains(&v) => Ok(v),
+        Value::Int(_) => Err(ConfigErrorKind::OutOfRange { min, max }),
+        _ => Err(ConfigErrorKind::Expected("an integer")),
+    }
+}
+
+fn boolean(value: Value<'_>) -> Result<bool, ConfigErrorKind> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        _ => Err(ConfigErrorKind::Expected("true or false")),
+    }
+}
+
+fn named<T: Copy>(
+    value: Value<'_>,
+    all: &[T],
+    label: fn(T) -> &'static str,
+    names: &'static str,
+) -> Result<T, ConfigErrorKind> {
+    let Value::Str(name) = value else {
+        return Err(ConfigErrorKind::Expected("a quoted name"));
+    };
+    all.iter()
+        .copied()
+        .find(|&item| name_matches(label(item), name))
+        .ok_or(ConfigErrorKind::UnknownName(names))
+}
+
+impl SoulConfig {
+    /// Parse `text`.  Valid settings are applied even when others fail.
+    pub fn parse(text: &str) -> ConfigLoad {
+        let mut load = ConfigLoad::default();
+        for entry in Parser::new(text) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    load.push_error(e.line, "", "", ConfigErrorKind::Syntax(e.reason));
+                    continue;
+                }
+            };
+            if let Err(kind) = load.config.set(entry.table, entry.key, entry.value) {
+                load.push_error(entry.line, entry.table, entry.key, kind);
+            }
+        }
+        load
+    }
+
+    fn set(&mut self, table: &str, key: &str, value: Value<'_>) -> Result<(), ConfigErrorKind> {
+        match (table, key) {
+            ("audio", "eq") => {
+                let names = "flat, bass_boost, warm, bright, vocal";
+                self.audio.eq = Some(named(value, &EqPreset::ALL, EqPreset::label, names)?);
+            }
+            ("audio", "crossfeed") => {
+                let names = "off, light, strong";
+                self.audio.crossfeed =
+                    Some(named(value, &Crossfeed::ALL, Crossfeed::label, names)?);
+            }
+            ("audio", "max_volume") => {
+                let v = int_in(value, 0, 100)?;
+                self.audio.max_volume = u8::try_from(v).ok();
+            }
+            ("audio", "trim_silence") => self.audio.trim_silence = Some(boolean(value)?),
+            ("audio", "silence_threshold_db") => {
+                let v = int_in(
+                    value,
+                    SilenceTrimConfig::MIN_THRESHOLD_DB.into(),
+                    SilenceTrimConfig::MAX_THRESHOLD_DB.into(),
+                )?;
+                self.audio.silence_threshold_db = i8::try_from(v).ok();
+            }
+            ("audio", "silence_min_ms") => {
+                let v = int_in(value, 250, 30_000)?;
+                self.audio.silence_min_ms = u16::try_from(v).ok();
+            }
+            ("library", "exclude") => return self.set_excludes(value),
+            ("ui", "full_refresh_every") => {
+                let v = int_in(value, 1, 1_000)?;
+                self.ui.full_refresh_every = u16::try_from(v).ok();
+            }
+            ("ui", "full_refresh_minutes") => {
+                let v = int_in(value, 1, 24 * 60)?;
+                self.ui.full_refresh_minutes = u16::try_from(v).ok();
+            }
+            ("ui", "accessible") => self.ui.accessible = Some(boolean(value)?),
+            ("ui", "night_mode") => self.ui.night_mode = Some(boolean(value)?),
+            ("developer", "cpu_usage") => self.developer.cpu_usage = boolean(value)?,
+            ("developer", "verbose_log") => self.developer.verbose_log = boolean(value)?,
+            ("developer", "skip_library_scan") => {
+                self.developer.skip_library_scan = boolean(value)?;
+            }
+            ("audio" | "library" | "ui" | "developer", _) => {
+                return Err(ConfigErrorKind::UnknownKey)
+            }
+            _ => return Err(ConfigErrorKind::UnknownTable),
+        }
+        Ok(())
+    }
+
+    /// Keeps the patterns that fit and reports the first problem.
+    fn set_excludes(&mut self, value: Value<'_>) -> Result<(), ConfigErrorKind> {
+        let Value::Array(array) = value else {
+            return Err(ConfigErrorKind::Expected("an array of strings"));
+        };
+        let mut excludes = Vec::new();
+        let mut problem = None;
+        for item in array.items() {
+            let pattern = match item {
+                Ok(Value::Str(pattern)) => pattern,
+                Ok(_) => {
+                    problem.get_or_insert(ConfigErrorKind::Expected("an array of strings"));
+                    continue;
+                }
+                Err(reason) => {
+                    problem.get_or_insert(ConfigErrorKind::Syntax(reason));
+                    break;
+                }
+            };
+            let Ok(pattern) = String::try_from(pattern) else {
+                problem.get_or_insert(ConfigErrorKind::TooLong { max: EXCLUDE_LEN });
+                continue;
+            };
+            if excludes.push(pattern).is_err() {
+                problem.get_or_insert(ConfigErrorKind::TooMany { max: MAX_EXCLUDES });
+                break;
+            }
+        }
+        self.library.exclude = excludes;
+        problem.map_or(Ok(()), Err)
+    }
+
+    /// Apply the `[audio]` defaults to a new output profile.
+    pub fn apply_to_profile(&self, profile: &mut OutputProfile) {
+        if let Some(eq) = self.audio.eq {
+            profile.eq = eq;
+        }
+        if let Some(crossfeed) = self.audio.crossfeed {
+            profile.crossfeed = crossfeed;
+        }
+        if let Some(max_volume) = self.audio.max_volume {
+            profile.max_volume = max_volume;
+        }
+    }
+
+    /// Apply the `[audio]` silence trimming overrides.
+    pub fn apply_to_silence_trim(&self, trim: &mut SilenceTrimConfig) {
+        if let Some(enabled) = self.audio.trim_silence {
+            trim.enabled = enabled;
+        }
+        if let Some(threshold_db) = self.audio.silence_threshold_db {
+            trim.threshold_db = threshold_db;
+        }
+        if let Some(min_ms) = self.audio.silence_min_ms {
+            trim.min_ms = u32::from(min_ms);
+        }
+    }
+
+    /// Apply the `[ui]` refresh overrides.
+    pub fn apply_to_refresh_policy(&self, policy: &mut RefreshPolicyConfig) {
+        if let Some(every) = self.ui.full_refresh_every {
+            policy.max_partials = every;
+        }
+        if let Some(minutes) = self.ui.full_refresh_minutes {
+            policy.full_interval_ms = u64::from(minutes).saturating_mul(60_000);
+        }
+    }
+}
+
+/// Read `path` (normally [`CONFIG_PATH`]) into `buf` and parse it.
+///
+/// `buf` bounds the file size; [`MAX_CONFIG_BYTES`] is plenty for every
+/// key this module knows, with comments.
+///
+/// `Ok(None)` when the file does not exist.  Storage errors are returned;
+/// everything wrong with the contents is reported in
+/// [`ConfigLoad::errors`].
+pub async fn load<S: Storage>(
+    storage: &mut S,
+    path: &str,
+    buf: &mut [u8],
+) -> Result<Option<ConfigLoad>, S::Error> {
+    if !storage.exists(path).await? {
+        return Ok(None);
+    }
+    let mut file = storage.open_file(path).await?;
+    let mut whole = ConfigLoad::default();
+    if file.size() > buf.len() as u64 {
+        whole.push_error(0, "", "", ConfigErrorKind::FileTooLarge);
+        return Ok(Some(whole));
+    }
+    let mut len = 0;
+    while let Some(rest) = buf.get_mut(len..).filter(|r| !r.is_empty()) {
+        // A read error mid-file reads as a truncated file: report what
+        // the card returned rather than fail the boot.
+        match file.read(rest).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => len = len.saturating_add(n),
+        }
+    }
+    let Ok(text) = core::str::from_utf8(buf.get(..len).unwrap_or(&[])) else {
+        whole.push_error(0, "", "", ConfigErrorKind::NotUtf8);
+        return Ok(Some(whole));
+    };
+    Ok(Some(SoulConfig::parse(text)))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+[audio]
+eq = \"Bass_Boost\"
+crossfeed = \"light\"
+max_volume = 80
+trim_silence = true
+silence_threshold_db = -54
+
+[library]
+exclude = [
+  \"Audiobooks/old\",
+  \"*.tmp\",
+]
+
+[ui]
+full_refresh_every = 20
+full_refresh_minutes = 5
+accessible = true
+night_mode = true
+
+[developer]
+cpu_usage = true
+";
+
+    fn messages(load: &ConfigLoad) -> std::vec::Vec<std::string::String> {
+        load.errors.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn example_file_parses_without_errors() {
+        let load = SoulConfig::parse(EXAMPLE);
+        assert!(load.errors.is_empty(), "{:?}", messages(&load));
+        let config = load.config;
+        assert_eq!(config.audio.eq, Some(EqPreset::BassBoost));
+        assert_eq!(config.audio.crossfeed, Some(Crossfeed::Light));
+        assert_eq!(config.audio.max_volume, Some(80));
+        assert_eq!(config.library.exclude.len(), 2);
+        assert_eq!(config.library.exclude[1].as_str(), "*.tmp");
+        assert!(config.developer.cpu_usage);
+        assert!(!config.developer.verbose_log);
+        assert_eq!(config.ui.accessible, Some(true));
+        assert_eq!(config.ui.night_mode, Some(true));
+
+        let mut profile = OutputProfile::new("IEM");
+        config.apply_to_profile(&mut profile);
+        assert_eq!((profile.eq, profile.max_volume), (EqPreset::BassBoost, 80));
+        let mut trim = SilenceTrimConfig::DEFAULT;
+        config.apply_to_silence_trim(&mut trim);
+        assert!(trim.enabled);
+        assert_eq!((trim.threshold_db, trim.min_ms), (-54, 2_000));
+        let mut policy = RefreshPolicyConfig::DEFAULT;
+        config.apply_to_refresh_policy(&mut policy);
+        assert_eq!(
+            (policy.max_partials, policy.full_interval_ms),
+            (20, 300_000)
+        );
+    }
+
+    #[test]
+    fn problems_are_reported_and_valid_keys_still_apply() {
+        let text = "\
+[audio]
+eq = \"loud\"
+max_volume = 120
+crossfeed = \"strong\"
+volume = 3
+silence_min_ms = 100
+[video]
+fps = 60
+[developer]
+cpu_usage = 1
+broken line
+";
+        let load = SoulConfig::parse(text);
+        assert_eq!(load.config.audio.crossfeed, Some(Crossfeed::Strong));
+        assert_eq!(load.config.audio.eq, None);
+        assert_eq!(
+            messages(&load),
+            [
+                "line 2: audio.eq: must be one of flat, bass_boost, warm, bright, vocal",
+                "line 3: audio.max_volume: must be 0-100",
+                "line 5: audio.volume: unknown key",
+                "line 6: audio.silence_min_ms: must be 250-30000",
+                "line 8: video.fps: unknown table",
+                "line 10: developer.cpu_usage: expected true or false",
+                "line 11: expected `key = value`",
+            ]
+        );
+    }
+
+    #[test]
+    fn excludes_keep_what_fits() {
+        let long = "x".repeat(EXCLUDE_LEN + 1);
+        let text = format!("[library]\nexclude = [\"a\", 3, \"{long}\", \"b\"]\n");
+        let load = SoulConfig::parse(&text);
+        let kept: std::vec::Vec<_> = load
+            .config
+            .library
+            .exclude
+            .iter()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(kept, ["a", "b"]);
+        assert_eq!(
+            load.errors[0].kind,
+            ConfigErrorKind::Expected("an array of strings")
+        );
+    }
+
+    #[test]
+    fn errors_past_the_limit_are_counted() {
+        let text = "[audio]\nnope = 1\n".repeat(MAX_ERRORS + 3);
+        let load = SoulConfig::parse(&text);
+        assert_eq!(load.errors.len(), MAX_ERRORS);
+        assert_eq!(load.error_count(), MAX_ERRORS + 3);
+    }
+
+    #[cfg(feature = "std")]
+    #[tokio::test]
+    async fn load_reads_the_card_root_file() {
+        let mut volume = crate::MemoryVolume::new();
+        let mut buf = vec![0u8; MAX_CONFIG_BYTES];
+        let loaded = load(&mut volume, CONFIG_PATH, &mut buf).await.unwrap();
+        assert_eq!(loaded, None);
+
+        volume.write_file(CONFIG_PATH, EXAMPLE).unwrap();
+        let loaded = load(&mut volume, CONFIG_PATH, &mut buf).await.unwrap();
+        assert_eq!(loaded.unwrap().config.audio.max_volume, Some(80));
+        let loaded = load(&mut volume, CONFIG_PATH, &mut buf[..16])
+            .await
+            .unwrap();
+        assert_eq!(
+            loaded.unwrap().errors[0].kind,
+            ConfigErrorKind::FileTooLarge
+        );
+
+        volume.write_file(CONFIG_PATH, vec![0xFF, 0xFE]).unwrap();
+        let loaded = load(&mut volume, CONFIG_PATH, &mut buf).await.unwrap();
+        assert_eq!(loaded.unwrap().errors[0].kind, ConfigErrorKind::NotUtf8);
+    }
+}
@@ -7,6 +7,12 @@
 //! ├── manifest.bin    — 64 B fixed header (counts, checksums)
 //! ├── library.idx     — 24 B × N sorted index entries
 //! ├── library.meta    — postcard-encoded TrackMeta blobs
+//! ├── library.browse  — pre-sorted artist/album/title orders + group tables
+//! ├── queue.jnl       — append-only play-queue journal (device-written)
+//! ├── profiles.bin    — output device profiles (device-written)
+//! ├── tags.bin        — tag corrections made on the device (device-written)
+//! ├── transfer.part   — BLE file transfer being received (device-written)
+//! ├── transfer.bin    — resume state of that transfer (device-written)
 //! └── art/
 //!     └── {hi:02x}/   — first byte of album_id as hex (256 subdirs)
 //!         └── {album_id:08x}.raw  — 2bpp 240×240 pre-dithered album art
@@ -15,6 +21,13 @@
 //! `SOUL_ROOT` is the single source of truth for the SD card mount point.
 //! Override it per deployment (SD card, emulator path) by calling the
 //! path-builder functions with the actual root string.
+//!
+//! `library.idx`, `library.meta` and `library.browse` each carry a CRC32
+//! (the first two in `manifest.bin`, the browse one in its own header).
+//! The library reader checks them on open and records what it found in a
+//! [`LibraryIntegrity`], which the diagnostics screen shows.
+
+use core::fmt;
 
 use heapless::String;
 
@@ -45,6 +58,58 @@ pub fn library_meta_path(root: &str) -> String<64> {
     build_path(root, "/library.meta")
 }
 
+/// Absolute path to the browse index.
+///
+/// Always `{root}/library.browse`.
+#[must_use]
+pub fn library_browse_path(root: &str) -> String<64> {
+    build_path(root, "/library.browse")
+}
+
+/// Absolute path to the play-queue journal.
+///
+/// Always `{root}/queue.jnl`.  During compaction the next generation is
+/// written to `{root}/queue.jnl.new` and renamed over this file.
+#[must_use]
+pub fn queue_journal_path(root: &str) -> String<64> {
+    build_path(root, "/queue.jnl")
+}
+
+/// Absolute path to the output device profiles.
+///
+/// Always `{root}/profiles.bin` (see [`crate::output_profile`]).
+#[must_use]
+pub fn output_profiles_path(root: &str) -> String<64> {
+    build_path(root, "/profiles.bin")
+}
+
+/// Absolute path to the tag overrides edited on the device.
+///
+/// Always `{root}/tags.bin`.  Library scans apply them on top of the tags
+/// read from the files.
+#[must_use]
+pub fn tag_overrides_path(root: &str) -> String<64> {
+    build_path(root, "/tags.bin")
+}
+
+/// Absolute path to the staging file of a BLE file transfer.
+///
+/// Always `{root}/transfer.part`.  Chunks from the companion app land here
+/// until the transfer is committed and its CRC checks out.
+#[must_use]
+pub fn transfer_part_path(root: &str) -> String<64> {
+    build_path(root, "/transfer.part")
+}
+
+/// Absolute path to the resume state of the transfer in
+/// [`transfer_part_path`].
+///
+/// Always `{root}/transfer.bin`; absent when no transfer is in progress.
+#[must_use]
+pub fn transfer_state_path(root: &str) -> String<64> {
+    build_path(root, "/transfer.bin")
+}
+
 /// Absolute path to a pre-dithered album art file.
 ///
 /// Uses two-level sharding: `{root}/art/{hi:02x}/{album_id:08x}.raw`
@@ -71,6 +136,118 @@ pub fn art_path(root: &str, album_id: u32) -> String<80> {
     s
 }
 
+// ---------------------------------------------------------------------------
+// Integrity
+// ---------------------------------------------------------------------------
+
+/// A checksummed file of the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LibrarySection {
+    /// `library.idx` — the sorted track table.
+    Index,
+    /// `library.meta` — the per-track metadata blobs.
+    Meta,
+    /// `library.browse` — the artist/album/title orderings.
+    Browse,
+}
+
+impl LibrarySection {
+    /// Every section, in file-layout order.
+    pub const ALL: [Self; 3] = [Self::Index, Self::Meta, Self::Browse];
+
+    /// File name under the library root.
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::Index => "library.idx",
+            Self::Meta => "library.meta",
+            Self::Browse => "library.browse",
+        }
+    }
+
+    const fn bit(self) -> u8 {
+        match self {
+            Self::Index => 1,
+            Self::Meta => 2,
+            Self::Browse => 4,
+        }
+    }
+}
+
+/// What the library reader found when it checked the section checksums.
+///
+/// A corrupt section does not stop the library from opening: a bad browse
+/// index is dropped (browse falls back to the index order), and with a bad
+/// index or metadata file each track is checked as it is read, so only the
+/// damaged ones fail.  Those are counted in `bad_tracks`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LibraryIntegrity {
+    corrupt: u8,
+    /// Tracks that failed to read since the library was opened.
+    pub bad_tracks: u32,
+}
+
+impl LibraryIntegrity {
+    /// No corrupt sections.
+    pub const fn new() -> Self {
+        Self {
+            corrupt: 0,
+            bad_tracks: 0,
+        }
+    }
+
+    /// Record a checksum or size mismatch in `section`.
+    pub fn mark_corrupt(&mut self, section: LibrarySection) {
+        self.corrupt |= section.bit();
+    }
+
+    /// `section` failed its check.
+    pub const fn is_corrupt(&self, section: LibrarySection) -> bool {
+        self.corrupt & section.bit() != 0
+    }
+
+    /// The sections that failed, in file-layout order.
+    pub fn corrupt_sections(&self) -> impl Iterator<Item = LibrarySection> + '_ {
+        LibrarySection::ALL
+            .into_iter()
+            .filter(|&s| self.is_corrupt(s))
+    }
+
+    /// Every section passed and every track read so far decoded.
+    pub const fn is_intact(&self) -> bool {
+        self.corrupt == 0 && self.bad_tracks == 0
+    }
+}
+
+impl fmt::Display for LibraryIntegrity {
+    /// `"ok"`, or the corrupt files and bad track count, e.g.
+    /// `"library.meta corrupt, 3 bad tracks"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_intact() {
+            return f.write_str("ok");
+        }
+        let mut first = true;
+        for section in self.corrupt_sections() {
+            if !first {
+                f.write_str(", ")?;
+            }
+            write!(f, "{} corrupt", section.file_name())?;
+            first = false;
+        }
+        if self.bad_tracks > 0 {
+            if !first {
+                f.write_str(", ")?;
+            }
+            match self.bad_tracks {
+                1 => f.write_str("1 bad track")?,
+                n => write!(f, "{n} bad tracks")?,
+            }
+        }
+        Ok(())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Private helpers
 // ---------------------------------------------------------------------------
@@ -129,6 +306,44 @@ mod tests {
         assert_eq!(library_meta_path(SOUL_ROOT).as_str(), "/soul/library.meta");
     }
 
+    #[test]
+    fn library_browse_path_is_under_soul_root() {
+        assert_eq!(
+            library_browse_path(SOUL_ROOT).as_str(),
+            "/soul/library.browse"
+        );
+    }
+
+    #[test]
+    fn queue_journal_path_is_under_soul_root() {
+        assert_eq!(queue_journal_path(SOUL_ROOT).as_str(), "/soul/queue.jnl");
+    }
+
+    #[test]
+    fn output_profiles_path_is_under_soul_root() {
+        assert_eq!(
+            output_profiles_path(SOUL_ROOT).as_str(),
+            "/soul/profiles.bin"
+        );
+    }
+
+    #[test]
+    fn tag_overrides_path_is_under_soul_root() {
+        assert_eq!(tag_overrides_path(SOUL_ROOT).as_str(), "/soul/tags.bin");
+    }
+
+    #[test]
+    fn transfer_paths_are_under_soul_root() {
+        assert_eq!(
+            transfer_part_path(SOUL_ROOT).as_str(),
+            "/soul/transfer.part"
+        );
+        assert_eq!(
+            transfer_state_path(SOUL_ROOT).as_str(),
+            "/soul/transfer.bin"
+        );
+    }
+
     #[test]
     fn art_path_uses_two_level_sharding() {
         let path = art_path(SOUL_ROOT, 0xABCD_1234);
@@ -147,6 +362,27 @@ mod tests {
         assert_eq!(path.as_str(), "/soul/art/ff/ffffffff.raw");
     }
 
+    #[test]
+    fn integrity_lists_corrupt_sections_and_bad_tracks() {
+        let mut integrity = LibraryIntegrity::new();
+        assert!(integrity.is_intact());
+        assert_eq!(integrity.to_string(), "ok");
+
+        integrity.mark_corrupt(LibrarySection::Browse);
+        integrity.mark_corrupt(LibrarySection::Index);
+        assert!(integrity.is_corrupt(LibrarySection::Index));
+        assert!(!integrity.is_corrupt(LibrarySection::Meta));
+        assert_eq!(
+            integrity.to_string(),
+            "library.idx corrupt, library.browse corrupt"
+        );
+
+        integrity = LibraryIntegrity::new();
+        integrity.bad_tracks = 2;
+        assert!(!integrity.is_intact());
+        assert_eq!(integrity.to_string(), "2 bad tracks");
+    }
+
     #[test]
     fn custom_root_builds_correct_paths() {
         assert_eq!(manifest_path("/music").as_str(), "/music/manifest.bin");
@@ -0,0 +1,769 @@
+//! Read-only FAT32 disk-image `platform::Storage` for the desktop emulator.
+//!
+//! `FatImage` opens a raw image of an SD card — either a bare FAT32 volume
+//! (`mkfs.fat -F 32 music.img`) or a whole-card dump with an MBR partition
+//! table (`dd if=/dev/sdX of=card.img`) — and serves files from it without
+//! mounting anything on the host.  Unlike [`crate::storage_local`], paths are
+//! resolved exactly as the firmware's FAT driver resolves them:
+//!
+//! - long file names (VFAT LFN entries) with 8.3 short-name fallback,
+//! - case-insensitive lookup,
+//! - data read cluster by cluster along the FAT chain, so a fragmented card
+//!   behaves like a fragmented card.
+//!
+//! Each [`File::read`] returns at most the rest of the current cluster;
+//! callers must loop, exactly as with SDMMC block reads.
+//!
+//! Only FAT32 is supported.  FAT12/16 (cards ≤ 2 GB) and exFAT (SDXC cards
+//! as shipped) are rejected with [`FatImageError::Unsupported`]; reformat
+//! the image as FAT32, which is what the player expects on the card anyway.
+//!
+//! # Example
+//! ```no_run
+//! # async fn example() {
+//! use platform::storage_fat::FatImage;
+//! use platform::{File, Storage};
+//!
+//! let mut card = FatImage::open("card.img").unwrap();
+//! let mut file = card.open_file("/Music/Dummy/01 Mysterons.flac").await.unwrap();
+//! let mut buf = [0u8; 4];
+//! file.read(&mut buf).await.unwrap();
+//! # }
+//! ```
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::storage::{File, Storage};
+
+/// Size of one directory entry.
+const DIR_ENTRY_LEN: usize = 32;
+
+/// Attribute bits of a directory entry.
+const ATTR_DIRECTORY: u8 = 0x10;
+const ATTR_VOLUME_ID: u8 = 0x08;
+const ATTR_LFN: u8 = 0x0F;
+
+/// First byte of a deleted directory entry.
+const ENTRY_DELETED: u8 = 0xE5;
+
+/// FAT32 entries use the low 28 bits.
+const FAT32_MASK: u32 = 0x0FFF_FFFF;
+/// Entries at or above this value terminate a cluster chain.
+const FAT32_END_OF_CHAIN: u32 = 0x0FFF_FFF8;
+
+/// Error type for [`FatImage`] operations.
+#[derive(Debug)]
+pub enum FatImageError {
+    /// Reading the image file failed.
+    Io(std::io::Error),
+    /// The image holds no FAT32 volume (no boot sector, or FAT12/16/exFAT).
+    Unsupported,
+    /// A cluster chain or directory points outside the volume or loops.
+    Corrupt,
+    /// No entry exists at the path (or a parent is missing).
+    NotFound,
+    /// A file was expected but the path names a directory.
+    IsADirectory,
+    /// A directory was expected but the path (or a parent) names a file.
+    NotADirectory,
+}
+
+impl core::fmt::Display for FatImageError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "FAT image error: {e}"),
+            Self::Unsupported => write!(f, "FAT image error: no FAT32 volume found"),
+            Self::Corrupt => write!(f, "FAT image error: corrupt cluster chain"),
+            Self::NotFound => write!(f, "FAT image error: no such file or directory"),
+            Self::IsADirectory => write!(f, "FAT image error: is a directory"),
+            Self::NotADirectory => write!(f, "FAT image error: not a directory"),
+        }
+    }
+}
+
+impl std::error::Error for FatImageError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for FatImageError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Volume layout decoded from the FAT32 boot sector, in image byte offsets.
+#[derive(Debug, Clone, Copy)]
+struct Geometry {
+    /// Start of the first FAT.
+    fat_offset: u64,
+    /// Start of cluster 2.
+    data_offset: u64,
+    /// Bytes per cluster.
+    cluster_len: u64,
+    /// First cluster of the root directory.
+    root_cluster: u32,
+    /// Number of data clusters (valid cluster numbers are `2..cluster_count + 2`).
+    cluster_count: u32,
+}
+
+impl Geometry {
+    /// Decode the boot sector of a volume starting at `volume_offset`.
+    fn parse(sector: &[u8; 512], volume_offset: u64) -> Result<Self, FatImageError> {
+        let u16_at = |at: usize| {
+            sector
+                .get(at..at.saturating_add(2))
+                .and_then(|b| <[u8; 2]>::try_from(b).ok())
+                .map_or(0, u16::from_le_bytes)
+        };
+        let u32_at = |at: usize| {
+            sector
+                .get(at..at.saturating_add(4))
+                .and_then(|b| <[u8; 4]>::try_from(b).ok())
+                .map_or(0, u32::from_le_bytes)
+        };
+
+        let bytes_per_sector = u64::from(u16_at(11));
+        let sectors_per_cluster = u64::from(sector[13]);
+        let reserved = u64::from(u16_at(14));
+        let fats = u64::from(sector[16]);
+        let root_entries = u16_at(17);
+        let fat_size_16 = u16_at(22);
+        let total = match u16_at(19) {
+            0 => u64::from(u32_at(32)),
+            n => u64::from(n),
+        };
+        let fat_size = u64::from(u32_at(36));
+
+        // FAT32 has no fixed root directory and only a 32-bit FAT size.
+        let sane = matches!(bytes_per_sector, 512 | 1024 | 2048 | 4096)
+            && sectors_per_cluster.is_power_of_two()
+            && reserved > 0
+            && fats > 0
+            && root_entries == 0
+            && fat_size_16 == 0
+            && fat_size > 0;
+        if !sane {
+            return Err(FatImageError::Unsupported);
+        }
+
+        let data_sector = reserved.saturating_add(fats.saturating_mul(fat_size));
+        let data_sectors = total.saturating_sub(data_sector);
+        let cluster_count = data_sectors
+            .checked_div(sectors_per_cluster)
+            .and_then(|n| u32::try_from(n).ok())
+            .ok_or(FatImageError::Unsupported)?;
+        // Cluster count is what makes a volume FAT32 (Microsoft FAT spec §3.5).
+        if cluster_count < 65_525 {
+            return Err(FatImageError::Unsupported);
+        }
+
+        Ok(Self {
+            fat_offset: volume_offset.saturating_add(reserved.saturating_mul(bytes_per_sector)),
+            data_offset: volume_offset.saturating_add(data_sector.saturating_mul(bytes_per_sector)),
+            cluster_len: sectors_per_cluster.saturating_mul(bytes_per_sector),
+            root_cluster: u32_at(44),
+            cluster_count,
+        })
+    }
+
+    fn is_valid_cluster(&self, cluster: u32) -> bool {
+        cluster
+            .checked_sub(2)
+            .is_some_and(|index| index < self.cluster_count)
+    }
+
+    /// Image offset of the first byte of `cluster`.
+    fn cluster_offset(&self, cluster: u32) -> u64 {
+        let index = u64::from(cluster.saturating_sub(2));
+        self.data_offset
+            .saturating_add(index.saturating_mul(self.cluster_len))
+    }
+}
+
+/// The image file, shared by the volume and every open file.
+type SharedImage = Arc<Mutex<fs::File>>;
+
+/// Read exactly `buf.len()` bytes at `offset`.
+fn read_at(image: &SharedImage, offset: u64, buf: &mut [u8]) -> Result<(), FatImageError> {
+    let mut file = image.lock().unwrap_or_else(PoisonError::into_inner);
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(buf)?;
+    Ok(())
+}
+
+/// A directory entry after LFN assembly.
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    is_dir: bool,
+    first_cluster: u32,
+    size: u32,
+}
+
+/// A read-only FAT32 volume inside a disk image.  See the module docs.
+#[derive(Debug, Clone)]
+pub struct FatImage {
+    image: SharedImage,
+    geometry: Geometry,
+}
+
+impl FatImage {
+    /// Open an image file and locate its FAT32 volume.
+    ///
+    /// Sector 0 is tried as a boot sector first; failing that, the first
+    /// MBR partition is used.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FatImageError> {
+        let image: SharedImage = Arc::new(Mutex::new(fs::File::open(path)?));
+
+        let mut sector = [0u8; 512];
+        read_at(&image, 0, &mut sector)?;
+        if sector[510..] != [0x55, 0xAA] {
+            return Err(FatImageError::Unsupported);
+        }
+        let geometry = if let Ok(geometry) = Geometry::parse(&sector, 0) {
+            geometry
+        } else {
+            // Partition entry 1: starting LBA at 0x1C6, always 512-byte sectors.
+            let lba =
+                u32::from_le_bytes([sector[0x1C6], sector[0x1C7], sector[0x1C8], sector[0x1C9]]);
+            let offset = u64::from(lba).saturating_mul(512);
+            if lba == 0 {
+                return Err(FatImageError::Unsupported);
+            }
+            read_at(&image, offset, &mut sector)?;
+            Geometry::parse(&sector, offset)?
+        };
+        if !geometry.is_valid_cluster(geometry.root_cluster) {
+            return Err(FatImageError::Corrupt);
+        }
+        Ok(Self { image, geometry })
+    }
+
+    /// Follow the FAT from `first` to the end of the chain.
+    fn chain(&self, first: u32) -> Result<Vec<u32>, FatImageError> {
+        let mut clusters = Vec::new();
+        let mut cluster = first;
+        while cluster < FAT32_END_OF_CHAIN {
+            // A chain longer than the volume can only be a loop.
+            if !self.geometry.is_valid_cluster(cluster)
+                || clusters.len() >= self.geometry.cluster_count as usize
+            {
+                return Err(FatImageError::Corrupt);
+            }
+            clusters.push(cluster);
+            let mut entry = [0u8; 4];
+            let at = self
+                .geometry
+                .fat_offset
+                .saturating_add(u64::from(cluster).saturating_mul(4));
+            read_at(&self.image, at, &mut entry)?;
+            cluster = u32::from_le_bytes(entry) & FAT32_MASK;
+        }
+        Ok(clusters)
+    }
+
+    /// Every live entry of the directory starting at `first_cluster`.
+    fn read_dir(&self, first_cluster: u32) -> Result<Vec<Entry>, FatImageError> {
+        let cluster_len =
+            usize::try_from(self.geometry.cluster_len).map_err(|_| FatImageError::Corrupt)?;
+        let mut entries = Vec::new();
+        let mut lfn = LfnBuilder::default();
+        let mut buf = vec![0u8; cluster_len];
+        for cluster in self.chain(first_cluster)? {
+            read_at(&self.image, self.geometry.cluster_offset(cluster), &mut buf)?;
+            let raw_entries = buf
+                .chunks_exact(DIR_ENTRY_LEN)
+                .filter_map(|c| <&[u8; DIR_ENTRY_LEN]>::try_from(c).ok());
+            for raw in raw_entries {
+                match (raw[0], raw[11]) {
+                    (0, _) => return Ok(entries),
+                    (ENTRY_DELETED, _) => lfn.clear(),
+                    (_, ATTR_LFN) => lfn.push(raw),
+                    (_, attr) if attr & ATTR_VOLUME_ID != 0 => lfn.clear(),
+                    (_, attr) => {
+                        let short = short_name(raw);
+                        let name = lfn.take(checksum(raw)).unwrap_or(short);
+                        if name != "." && name != ".." {
+                            entries.push(Entry {
+                                name,
+                                is_dir: attr & ATTR_DIRECTORY != 0,
+                                // Low half at 26, high half at 20.
+                                first_cluster: u32::from_le_bytes([
+                                    raw[26], raw[27], raw[20], raw[21],
+                                ]),
+                                size: u32::from_le_bytes([raw[28], raw[29], raw[30], raw[31]]),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Resolve `path`; `None` for the root directory.
+    fn lookup(&self, path: &str) -> Result<Option<Entry>, FatImageError> {
+        let mut dir = self.geometry.root_cluster;
+        let mut found = None;
+        for component in path
+            .split(['/', '\\'])
+            .filter(|c| !c.is_empty() && *c != ".")
+        {
+            if let Some(Entry { is_dir: false, .. }) = found {
+                return Err(FatImageError::NotADirectory);
+            }
+            let entry = self
+                .read_dir(dir)?
+                .into_iter()
+                .find(|e| e.name.to_lowercase() == component.to_lowercase())
+                .ok_or(FatImageError::NotFound)?;
+            // An empty directory may have cluster 0 in its entry; ".." to root does too.
+            dir = if entry.first_cluster == 0 {
+                self.geometry.root_cluster
+            } else {
+                entry.first_cluster
+            };
+            found = Some(entry);
+        }
+        Ok(found)
+    }
+}
+
+impl Storage for FatImage {
+    type Error = FatImageError;
+    type File = FatFile;
+
+    async fn open_file(&mut self, path: &str) -> Result<Self::File, Self::Error> {
+        match self.lookup(path)? {
+            None | Some(Entry { is_dir: true, .. }) => Err(FatImageError::IsADirectory),
+            Some(entry) => {
+                let clusters = if entry.size == 0 {
+                    Vec::new()
+                } else {
+                    self.chain(entry.first_cluster)?
+                };
+                let size = u64::from(entry.size);
+                if (clusters.len() as u64).saturating_mul(self.geometry.cluster_len) < size {
+                    return Err(FatImageError::Corrupt);
+                }
+                Ok(FatFile {
+                    image: Arc::clone(&self.image),
+                    geometry: self.geometry,
+                    clusters,
+                    size,
+                    pos: 0,
+                })
+            }
+        }
+    }
+
+    async fn exists(&mut self, path: &str) -> Result<bool, Self::Error> {
+        match self.lookup(path) {
+            Ok(_) => Ok(true),
+            Err(FatImageError::NotFound | FatImageError::NotADirectory) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// An open file inside a [`FatImage`].
+///
+/// The cluster chain is resolved at open time, so reads are one positioned
+/// read of the image each.
+#[derive(Debug)]
+pub struct FatFile {
+    image: SharedImage,
+    geometry: Geometry,
+    clusters: Vec<u32>,
+    size: u64,
+    pos: u64,
+}
+
+impl File for FatFile {
+    type Error = FatImageError;
+
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let cluster_len = self.geometry.cluster_len;
+        let (Some(index), Some(within)) = (
+            self.pos.checked_div(cluster_len),
+            self.pos.checked_rem(cluster_len),
+        ) else {
+            return Err(FatImageError::Corrupt);
+        };
+        let Some(&cluster) = usize::try_from(index)
+            .ok()
+            .and_then(|i| self.clusters.get(i))
+        else {
+            return Ok(0);
+        };
+        // Stop at the end of the cluster and at the end of the file.
+        let available = cluster_len
+            .saturating_sub(within)
+            .min(self.size.saturating_sub(self.pos));
+        let n = buf
+            .len()
+            .min(usize::try_from(available).unwrap_or(usize::MAX));
+        let Some(dst) = buf.get_mut(..n) else {
+            return Ok(0);
+        };
+        read_at(
+            &self.image,
+            self.geometry.cluster_offset(cluster).saturating_add(within),
+            dst,
+        )?;
+        self.pos = self.pos.saturating_add(n as u64);
+        Ok(n)
+    }
+
+    async fn seek(&mut self, pos: u64) -> Result<u64, Self::Error> {
+        // Seeking past the end is allowed; subsequent reads return 0.
+        self.pos = pos;
+        Ok(pos)
+    }
+
+    fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Collects the LFN entries that precede a short-name entry.
+#[derive(Default)]
+struct LfnBuilder {
+    /// UTF-16 units, 13 per LFN entry, in name order.
+    units: Vec<u16>,
+    /// Short-name checksum every LFN entry in the run must carry.
+    checksum: Option<u8>,
+}
+
+impl LfnBuilder {
+    fn clear(&mut self) {
+        self.units.clear();
+        self.checksum = None;
+    }
+
+    fn push(&mut self, raw: &[u8; DIR_ENTRY_LEN]) {
+        let order = raw[0];
+        // 0x40 marks the last (highest) part, which comes first on disk.
+        if order & 0x40 != 0 {
+            self.clear();
+            self.checksum = Some(raw[13]);
+        } else if self.checksum != Some(raw[13]) {
+            self.clear();
+            return;
+        }
+        let Some(slot) = usize::from(order & 0x1F).checked_sub(1) else {
+            self.clear();
+            return;
+        };
+        let chars = raw[1..11]
+            .chunks_exact(2)
+            .chain(raw[14..26].chunks_exact(2))
+            .chain(raw[28..32].chunks_exact(2))
+            .filter_map(|b| {
+                let &[lo, hi] = b else { return None };
+                Some(u16::from_le_bytes([lo, hi]))
+            });
+        let start = slot.saturating_mul(13);
+        if self.units.len() < start.saturating_add(13) {
+            self.units.resize(start.saturating_add(13), 0xFFFF);
+        }
+        for (unit, c) in self.units.iter_mut().skip(start).zip(chars) {
+            *unit = c;
+        }
+    }
+
+    /// The assembled name, if the run belongs to the short entry with `checksum`.
+    fn take(&mut self, checksum: u8) -> Option<String> {
+        let matches = self.checksum == Some(checksum);
+        let units: Vec<u16> = self
+            .units
+            .iter()
+            .copied()
+            .take_while(|&u| u != 0 && u != 0xFFFF)
+            .collect();
+        self.clear();
+        if matches && !units.is_empty() {
+            String::from_utf16(&units).ok()
+        } else {
+            None
+        }
+    }
+}
+
+/// `NAME.EXT` from an 8.3 entry, honouring the NT lowercase flags.
+fn short_name(raw: &[u8; DIR_ENTRY_LEN]) -> String {
+    let case = raw[12];
+    let part = |bytes: &[u8], lower: bool| {
+        let s: String = bytes
+            .iter()
+            .map(|&b| char::from(b))
+            .collect::<String>()
+            .trim_end()
+            .to_owned();
+        if lower {
+            s.to_lowercase()
+        } else {
+            s
+        }
+    };
+    let mut base = part(&raw[0..8], case & 0x08 != 0);
+    // 0x05 stands in for a leading 0xE5 byte in a live entry.
+    if base.starts_with('\u{5}') {
+        base.replace_range(..1, "\u{e5}");
+    }
+    let ext = part(&raw[8..11], case & 0x10 != 0);
+    if ext.is_empty() {
+        base
+    } else {
+        format!("{base}.{ext}")
+    }
+}
+
+/// Short-name checksum stored in every LFN entry.
+fn checksum(raw: &[u8; DIR_ENTRY_LEN]) -> u8 {
+    raw[..11]
+        .iter()
+        .fold(0u8, |sum, &b| sum.rotate_right(1).wrapping_add(b))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#[allow(clippy::indexing_slicing)] // Tests index with known bounds
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const SECTOR: u64 = 512;
+    const RESERVED: u64 = 32;
+    const FAT_SECTORS: u64 = 560;
+    const TOTAL_SECTORS: u64 = 70_000;
+    const DATA: u64 = RESERVED + FAT_SECTORS;
+
+    /// Minimal FAT32 image writer: 512-byte clusters, one FAT, sparse file.
+    struct ImageWriter {
+        file: NamedTempFile,
+        base: u64,
+    }
+
+    impl ImageWriter {
+        /// `base` is the volume's byte offset (non-zero behind an MBR).
+        fn new(base: u64) -> Self {
+            let file = NamedTempFile::new().unwrap();
+            file.as_file()
+                .set_len(base + TOTAL_SECTORS * SECTOR)
+                .unwrap();
+            let mut w = Self { file, base };
+            let mut boot = [0u8; 512];
+            boot[0..3].copy_from_slice(&[0xEB, 0x58, 0x90]);
+            boot[11..13].copy_from_slice(&512u16.to_le_bytes());
+            boot[13] = 1;
+            boot[14..16].copy_from_slice(&u16::try_from(RESERVED).unwrap().to_le_bytes());
+            boot[16] = 1;
+            boot[32..36].copy_from_slice(&u32::try_from(TOTAL_SECTORS).unwrap().to_le_bytes());
+            boot[36..40].copy_from_slice(&u32::try_from(FAT_SECTORS).unwrap().to_le_bytes());
+            boot[44..48].copy_from_slice(&2u32.to_le_bytes());
+            boot[510..].copy_from_slice(&[0x55, 0xAA]);
+            w.write(0, &boot);
+            w.link(&[2]);
+            w
+        }
+
+        fn write(&mut self, at: u64, bytes: &[u8]) {
+            let f = self.file.as_file_mut();
+            f.seek(SeekFrom::Start(self.base + at)).unwrap();
+            f.write_all(bytes).unwrap();
+        }
+
+        /// Chain `clusters` together in the FAT.
+        fn link(&mut self, clusters: &[u32]) {
+            for (i, &c) in clusters.iter().enumerate() {
+                let next = clusters.get(i + 1).copied().unwrap_or(FAT32_MASK);
+                self.write(RESERVED * SECTOR + u64::from(c) * 4, &next.to_le_bytes());
+            }
+        }
+
+        fn cluster(&mut self, cluster: u32, bytes: &[u8]) {
+            self.write((DATA + u64::from(cluster) - 2) * SECTOR, bytes);
+        }
+
+        /// Directory entries (LFN run + short entry) for one child.
+        fn entry(
+            long: Option<&str>,
+            short: &[u8; 11],
+            dir: bool,
+            first: u32,
+            size: u32,
+        ) -> Vec<u8> {
+            let mut short_entry = [0u8; 32];
+            short_entry[..11].copy_from_slice(short);
+            short_entry[11] = if dir { ATTR_DIRECTORY } else { 0x20 };
+            short_entry[20..22].copy_from_slice(&first.to_le_bytes()[2..]);
+            short_entry[26..28].copy_from_slice(&first.to_le_bytes()[..2]);
+            short_entry[28..32].copy_from_slice(&size.to_le_bytes());
+            let sum = checksum(&short_entry);
+
+            let mut out = Vec::new();
+            if let Some(long) = long {
+                let mut units: Vec<u16> = long.encode_utf16().collect();
+                units.push(0);
+                while !units.len().is_multiple_of(13) {
+                    units.push(0xFFFF);
+                }
+                let parts = units.len() / 13;
+                for part in (0..parts).rev() {
+                    let mut e = [0u8; 32];
+                    e[0] = (u8::try_from(part).unwrap() + 1)
+                        | if part + 1 == parts { 0x40 } else { 0 };
+                    e[11] = ATTR_LFN;
+                    e[13] = sum;
+                    let chunk = &units[part * 13..part * 13 + 13];
+                    let offsets = (1..11)
+                        .step_by(2)
+                        .chain((14..26).step_by(2))
+                        .chain((28..32).step_by(2));
+                    for (off, unit) in offsets.zip(chunk) {
+                        e[off..off + 2].copy_from_slice(&unit.to_le_bytes());
+                    }
+                    out.extend_from_slice(&e);
+                }
+            }
+            out.extend_from_slice(&short_entry);
+            out
+        }
+
+        fn finish(self) -> NamedTempFile {
+            self.file
+        }
+    }
+
+    /// `/Music/Portishead - Mysterons.flac` spread over clusters 4, 5 and 7,
+    /// plus an 8.3 `/README.TXT`.
+    fn sample_image(base: u64) -> (NamedTempFile, Vec<u8>) {
+        let mut w = ImageWriter::new(base);
+        let mut root = ImageWriter::entry(Some("Music"), b"MUSIC      ", true, 3, 0);
+        root.extend(ImageWriter::entry(None, b"README  TXT", false, 8, 5));
+        w.cluster(2, &root);
+        w.cluster(8, b"hello");
+        w.link(&[8]);
+
+        let track: Vec<u8> = (0..1200u32).map(|i| i.to_le_bytes()[0]).collect();
+        let dir = ImageWriter::entry(
+            Some("Portishead - Mysterons.flac"),
+            b"PORTIS~1FLA",
+            false,
+            4,
+            u32::try_from(track.len()).unwrap(),
+        );
+        w.cluster(3, &dir);
+        w.link(&[3]);
+        w.cluster(4, &track[..512]);
+        w.cluster(5, &track[512..1024]);
+        w.cluster(7, &track[1024..]);
+        w.link(&[4, 5, 7]);
+        (w.finish(), track)
+    }
+
+    async fn read_all(file: &mut FatFile) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut buf = [0u8; 700];
+        loop {
+            let n = file.read(&mut buf).await.unwrap();
+            if n == 0 {
+                return out;
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reads_fragmented_file_by_long_name() {
+        let (img, track) = sample_image(0);
+        let mut card = FatImage::open(img.path()).unwrap();
+        let mut file = card
+            .open_file("/music/PORTISHEAD - mysterons.flac")
+            .await
+            .unwrap();
+        assert_eq!(file.size(), 1200);
+
+        // Reads stop at cluster boundaries, like SDMMC block reads.
+        let mut buf = [0u8; 700];
+        assert_eq!(file.read(&mut buf).await.unwrap(), 512);
+
+        file.seek(0).await.unwrap();
+        assert_eq!(read_all(&mut file).await, track);
+
+        file.seek(1100).await.unwrap();
+        let mut tail = [0u8; 200];
+        assert_eq!(file.read(&mut tail).await.unwrap(), 100);
+        assert_eq!(&tail[..100], &track[1100..]);
+    }
+
+    #[tokio::test]
+    async fn test_short_names_and_exists() {
+        let (img, _) = sample_image(0);
+        let mut card = FatImage::open(img.path()).unwrap();
+        let mut readme = card.open_file("readme.txt").await.unwrap();
+        assert_eq!(read_all(&mut readme).await, b"hello");
+
+        assert!(card.exists("/").await.unwrap());
+        assert!(card.exists("\\Music").await.unwrap());
+        assert!(!card.exists("/Music/missing.flac").await.unwrap());
+        assert!(!card.exists("/README.TXT/child").await.unwrap());
+        assert!(matches!(
+            card.open_file("/Music").await,
+            Err(FatImageError::IsADirectory)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_volume_behind_mbr_partition() {
+        let (img, track) = sample_image(2048 * SECTOR);
+        let mut mbr = [0u8; 512];
+        mbr[0x1C2] = 0x0C; // FAT32 LBA
+        mbr[0x1C6..0x1CA].copy_from_slice(&2048u32.to_le_bytes());
+        mbr[510..].copy_from_slice(&[0x55, 0xAA]);
+        let mut f = img.reopen().unwrap();
+        f.write_all(&mbr).unwrap();
+
+        let mut card = FatImage::open(img.path()).unwrap();
+        let mut file = card
+            .open_file("Music/Portishead - Mysterons.flac")
+            .await
+            .unwrap();
+        assert_eq!(read_all(&mut file).await, track);
+    }
+
+    #[test]
+    fn test_rejects_non_fat_images() {
+        let mut img = NamedTempFile::new().unwrap();
+        img.write_all(&[0u8; 4096]).unwrap();
+        assert!(matches!(
+            FatImage::open(img.path()),
+            Err(FatImageError::Unsupported)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cluster_loop_is_corrupt_not_a_hang() {
+        let (img, _) = sample_image(0);
+        let mut w = ImageWriter { file: img, base: 0 };
+        // 4 -> 5 -> 4 -> ...
+        w.write(RESERVED * SECTOR + 5 * 4, &4u32.to_le_bytes());
+        let img = w.finish();
+        let mut card = FatImage::open(img.path()).unwrap();
+        assert!(matches!(
+            card.open_file("/Music/Portishead - Mysterons.flac").await,
+            Err(FatImageError::Corrupt)
+        ));
+    }
+}
@@ -0,0 +1,418 @@
+//! Fixture builder for populating a [`MemoryVolume`] with tagged audio files.
+//!
+//! The generated files are *structurally* valid — correct magic, block and
+//! chunk framing, real tag encodings — but contain no audio frames beyond a
+//! single silent header, so they are a few hundred bytes each.  That is
+//! enough for format detection, tag parsing and directory scanning; decoder
+//! tests should keep using real test vectors.
+//!
+//! | Extension | Container written                                         |
+//! |-----------|-----------------------------------------------------------|
+//! | `.flac`   | `fLaC` + STREAMINFO + VORBIS_COMMENT                      |
+//! | `.mp3`    | ID3v2.4 (TIT2/TPE1/TALB/TRCK/TPOS/TDRC) + one MPEG-1 L3 frame |
+//! | `.wav`    | RIFF/WAVE `fmt ` + `LIST`/`INFO` + empty `data`           |
+//!
+//! # Example
+//! ```
+//! use platform::storage_fixture::{FixtureBuilder, TrackTags};
+//!
+//! let vol = FixtureBuilder::new()
+//!     .dir("/soul")
+//!     .track("/Music/Portishead/Dummy/01 Mysterons.flac",
+//!            &TrackTags::new("Mysterons").artist("Portishead").album("Dummy").track(1))
+//!     .file("/Music/Portishead/Dummy/cover.jpg", [0xFF, 0xD8])
+//!     .build()
+//!     .unwrap();
+//! assert_eq!(vol.file_count(), 2);
+//! ```
+
+use crate::storage_mem::{MemoryStorageError, MemoryVolume};
+
+/// Tags written into a generated audio file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackTags {
+    /// Track title.
+    pub title: String,
+    /// Track artist.
+    pub artist: Option<String>,
+    /// Album title.
+    pub album: Option<String>,
+    /// Track number within the disc.
+    pub track_number: Option<u16>,
+    /// Disc number.
+    pub disc_number: Option<u16>,
+    /// Release year.
+    pub year: Option<u16>,
+}
+
+impl TrackTags {
+    /// Tags with only a title set.
+    #[must_use]
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_owned(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the artist.
+    #[must_use]
+    pub fn artist(mut self, artist: &str) -> Self {
+        self.artist = Some(artist.to_owned());
+        self
+    }
+
+    /// Set the album.
+    #[must_use]
+    pub fn album(mut self, album: &str) -> Self {
+        self.album = Some(album.to_owned());
+        self
+    }
+
+    /// Set the track number.
+    #[must_use]
+    pub fn track(mut self, n: u16) -> Self {
+        self.track_number = Some(n);
+        self
+    }
+
+    /// Set the disc number.
+    #[must_use]
+    pub fn disc(mut self, n: u16) -> Self {
+        self.disc_number = Some(n);
+        self
+    }
+
+    /// Set the release year.
+    #[must_use]
+    pub fn year(mut self, year: u16) -> Self {
+        self.year = Some(year);
+        self
+    }
+
+    /// `(key, value)` pairs in Vorbis-comment naming.
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut out = vec![("TITLE", self.title.clone())];
+        if let Some(v) = &self.artist {
+            out.push(("ARTIST", v.clone()));
+        }
+        if let Some(v) = &self.album {
+            out.push(("ALBUM", v.clone()));
+        }
+        if let Some(n) = self.track_number {
+            out.push(("TRACKNUMBER", n.to_string()));
+        }
+        if let Some(n) = self.disc_number {
+            out.push(("DISCNUMBER", n.to_string()));
+        }
+        if let Some(y) = self.year {
+            out.push(("DATE", y.to_string()));
+        }
+        out
+    }
+}
+
+/// Builds a [`MemoryVolume`] from a list of directories and files.
+///
+/// Errors are deferred to [`build`](Self::build) so fixtures can be written
+/// as a single expression.
+#[derive(Debug, Default)]
+pub struct FixtureBuilder {
+    volume: MemoryVolume,
+    error: Option<MemoryStorageError>,
+}
+
+impl FixtureBuilder {
+    /// Start from an empty volume.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a directory (and its parents).
+    #[must_use]
+    pub fn dir(mut self, path: &str) -> Self {
+        let result = self.volume.mkdir_all(path);
+        self.record(result);
+        self
+    }
+
+    /// Add a file with raw contents.
+    #[must_use]
+    pub fn file(mut self, path: &str, data: impl Into<Vec<u8>>) -> Self {
+        let result = self.volume.write_file(path, data);
+        self.record(result);
+        self
+    }
+
+    /// Add an audio file carrying `tags`; the container follows the extension.
+    ///
+    /// An unsupported extension is recorded as
+    /// [`MemoryStorageError::InvalidName`].
+    #[must_use]
+    pub fn track(mut self, path: &str, tags: &TrackTags) -> Self {
+        let result = tagged_file_bytes(path, tags)
+            .ok_or(MemoryStorageError::InvalidName)
+            .and_then(|bytes| self.volume.write_file(path, bytes));
+        self.record(result);
+        self
+    }
+
+    /// Add `count` numbered tracks to `dir` for one album.
+    ///
+    /// Files are named `NN Track NN.<ext>` with titles `Track NN`.
+    #[must_use]
+    pub fn album(mut self, dir: &str, artist: &str, album: &str, ext: &str, count: u16) -> Self {
+        for n in 1..=count {
+            let path = format!("{}/{n:02} Track {n:02}.{ext}", dir.trim_end_matches('/'));
+            let tags = TrackTags::new(&format!("Track {n:02}"))
+                .artist(artist)
+                .album(album)
+                .track(n);
+            self = self.track(&path, &tags);
+        }
+        self
+    }
+
+    /// Finish, returning the first error encountered, if any.
+    pub fn build(self) -> Result<MemoryVolume, MemoryStorageError> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.volume),
+        }
+    }
+
+    fn record(&mut self, result: Result<(), MemoryStorageError>) {
+        if let (None, Err(e)) = (&self.error, result) {
+            self.error = Some(e);
+        }
+    }
+}
+
+/// Encode a tagged audio file for `path`'s extension (`flac`, `mp3`, `wav`).
+#[must_use]
+pub fn tagged_file_bytes(path: &str, tags: &TrackTags) -> Option<Vec<u8>> {
+    let (_, ext) = path.rsplit_once('.')?;
+    if ext.eq_ignore_ascii_case("flac") {
+        Some(flac_bytes(tags))
+    } else if ext.eq_ignore_ascii_case("mp3") {
+        Some(mp3_bytes(tags))
+    } else if ext.eq_ignore_ascii_case("wav") {
+        Some(wav_bytes(tags))
+    } else {
+        None
+    }
+}
+
+/// `fLaC` + STREAMINFO (44.1 kHz / 16-bit / stereo) + VORBIS_COMMENT.
+#[must_use]
+pub fn flac_bytes(tags: &TrackTags) -> Vec<u8> {
+    let mut out = b"fLaC".to_vec();
+
+    // STREAMINFO (type 0, not last, 34 bytes).
+    out.extend_from_slice(&[0x00, 0x00, 0x00, 34]);
+    out.extend_from_slice(&4096u16.to_be_bytes()); // min block size
+    out.extend_from_slice(&4096u16.to_be_bytes()); // max block size
+    out.extend_from_slice(&[0; 6]); // min/max frame size unknown
+
+    // 20-bit sample rate (44 100) | 3-bit channels-1 (1) | 5-bit bps-1 (15)
+    // | 36-bit total samples (0, unknown).
+    out.extend_from_slice(&0x0AC4_42F0_0000_0000u64.to_be_bytes());
+    out.extend_from_slice(&[0; 16]); // MD5 unknown
+
+    // VORBIS_COMMENT (type 4, last block).
+    let vendor = b"soul-fixture";
+    let mut body = Vec::new();
+    body.extend_from_slice(&len_u32(vendor.len()).to_le_bytes());
+    body.extend_from_slice(vendor);
+    let fields = tags.fields();
+    body.extend_from_slice(&len_u32(fields.len()).to_le_bytes());
+    for (key, value) in fields {
+        let comment = format!("{key}={value}");
+        body.extend_from_slice(&len_u32(comment.len()).to_le_bytes());
+        body.extend_from_slice(comment.as_bytes());
+    }
+    let len = len_u32(body.len()).min(0x00FF_FFFF).to_be_bytes();
+    out.extend_from_slice(&[0x80 | 0x04, len[1], len[2], len[3]]);
+    out.extend_from_slice(&body);
+    out
+}
+
+/// ID3v2.4 tag followed by one silent MPEG-1 Layer III frame (128 kbps, 44.1 kHz).
+#[must_use]
+pub fn mp3_bytes(tags: &TrackTags) -> Vec<u8> {
+    let mut frames = Vec::new();
+    let mut frame = |id: &[u8; 4], text: &str| {
+        // Encoding byte 0x03 = UTF-8.
+        let size = len_u32(text.len().saturating_add(1));
+        frames.extend_from_slice(id);
+        frames.extend_from_slice(&syncsafe(size));
+        frames.extend_from_slice(&[0, 0, 0x03]);
+        frames.extend_from_slice(text.as_bytes());
+    };
+    frame(b"TIT2", &tags.title);
+    if let Some(v) = &tags.artist {
+        frame(b"TPE1", v);
+    }
+    if let Some(v) = &tags.album {
+        frame(b"TALB", v);
+    }
+    if let Some(n) = tags.track_number {
+        frame(b"TRCK", &n.to_string());
+    }
+    if let Some(n) = tags.disc_number {
+        frame(b"TPOS", &n.to_string());
+    }
+    if let Some(y) = tags.year {
+        frame(b"TDRC", &y.to_string());
+    }
+
+    let mut out = b"ID3".to_vec();
+    out.extend_from_slice(&[4, 0, 0]); // v2.4.0, no flags
+    out.extend_from_slice(&syncsafe(len_u32(frames.len())));
+    out.extend_from_slice(&frames);
+
+    // MPEG-1 Layer III, 128 kbps, 44.1 kHz, no padding: 417-byte frame.
+    out.extend_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+    out.resize(out.len().saturating_add(413), 0);
+    out
+}
+
+/// RIFF/WAVE with a 44.1 kHz / 16-bit / stereo `fmt ` chunk and `LIST`/`INFO` tags.
+#[must_use]
+pub fn wav_bytes(tags: &TrackTags) -> Vec<u8> {
+    let mut fmt = Vec::with_capacity(16);
+    fmt.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    fmt.extend_from_slice(&2u16.to_le_bytes()); // channels
+    fmt.extend_from_slice(&44_100u32.to_le_bytes());
+    fmt.extend_from_slice(&176_400u32.to_le_bytes()); // byte rate
+    fmt.extend_from_slice(&4u16.to_le_bytes()); // block align
+    fmt.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+
+    let mut info = b"INFO".to_vec();
+    let mut sub = |id: [u8; 4], text: &str| {
+        let mut value = text.as_bytes().to_vec();
+        value.push(0);
+        push_chunk(&mut info, id, &value);
+    };
+    sub(*b"INAM", &tags.title);
+    if let Some(v) = &tags.artist {
+        sub(*b"IART", v);
+    }
+    if let Some(v) = &tags.album {
+        sub(*b"IPRD", v);
+    }
+    if let Some(n) = tags.track_number {
+        sub(*b"ITRK", &n.to_string());
+    }
+    if let Some(y) = tags.year {
+        sub(*b"ICRD", &y.to_string());
+    }
+
+    let mut body = b"WAVE".to_vec();
+    push_chunk(&mut body, *b"fmt ", &fmt);
+    push_chunk(&mut body, *b"LIST", &info);
+    push_chunk(&mut body, *b"data", &[]);
+
+    let mut out = b"RIFF".to_vec();
+    out.extend_from_slice(&len_u32(body.len()).to_le_bytes());
+    out.extend_from_slice(&body);
+    out
+}
+
+/// Append a RIFF chunk, padding odd-length payloads to an even size.
+fn push_chunk(out: &mut Vec<u8>, id: [u8; 4], payload: &[u8]) {
+    out.extend_from_slice(&id);
+    out.extend_from_slice(&len_u32(payload.len()).to_le_bytes());
+    out.extend_from_slice(payload);
+    if payload.len() & 1 == 1 {
+        out.push(0);
+    }
+}
+
+/// ID3v2 syncsafe integer: 7 bits per byte, 28 bits total.
+// SAFETY (cast): every byte is masked to 7 bits before the cast.
+#[allow(clippy::cast_possible_truncation)]
+fn syncsafe(n: u32) -> [u8; 4] {
+    let n = n.min(0x0FFF_FFFF);
+    [
+        (n.wrapping_shr(21) & 0x7F) as u8,
+        (n.wrapping_shr(14) & 0x7F) as u8,
+        (n.wrapping_shr(7) & 0x7F) as u8,
+        (n & 0x7F) as u8,
+    ]
+}
+
+/// Fixture payloads are tiny; saturate rather than truncate if one is not.
+fn len_u32(len: usize) -> u32 {
+    u32::try_from(len).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#[allow(clippy::indexing_slicing)] // Tests index with known bounds
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flac_layout() {
+        let bytes = flac_bytes(&TrackTags::new("T").artist("A"));
+        assert_eq!(&bytes[..4], b"fLaC");
+        // STREAMINFO header then VORBIS_COMMENT flagged as last block.
+        assert_eq!(bytes[4], 0x00);
+        assert_eq!(bytes[4 + 4 + 34], 0x84);
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("TITLE=T"));
+        assert!(text.contains("ARTIST=A"));
+    }
+
+    #[test]
+    fn test_mp3_id3_size_matches_frames() {
+        let bytes = mp3_bytes(&TrackTags::new("Title").album("Album").track(3));
+        assert_eq!(&bytes[..3], b"ID3");
+        let size = bytes[6..10]
+            .iter()
+            .fold(0usize, |acc, &b| (acc << 7) | usize::from(b));
+        // MPEG frame sync immediately follows the tag.
+        assert_eq!(bytes[10 + size], 0xFF);
+        assert_eq!(bytes.len(), 10 + size + 417);
+    }
+
+    #[test]
+    fn test_wav_riff_size_and_info() {
+        let bytes = wav_bytes(&TrackTags::new("Odd").artist("Even"));
+        assert_eq!(&bytes[..4], b"RIFF");
+        let riff_len = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        assert_eq!(riff_len, bytes.len() - 8);
+        assert!(bytes.windows(4).any(|w| w == b"INAM"));
+    }
+
+    #[test]
+    fn test_builder_reports_first_error() {
+        let err = FixtureBuilder::new()
+            .track("/a/notes.txt", &TrackTags::new("x"))
+            .file("/bad:name", [])
+            .build()
+            .unwrap_err();
+        assert_eq!(err, MemoryStorageError::InvalidName);
+    }
+
+    #[test]
+    fn test_album_helper_numbers_tracks() {
+        let vol = FixtureBuilder::new()
+            .album("/Music/A/B", "A", "B", "flac", 3)
+            .build()
+            .unwrap();
+        let names: Vec<_> = vol
+            .read_dir("/Music/A/B")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(
+            names,
+            ["01 Track 01.flac", "02 Track 02.flac", "03 Track 03.flac"]
+        );
+    }
+}
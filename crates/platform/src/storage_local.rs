@@ -27,6 +27,7 @@ impl std::error::Error for LocalStorageError {
 }
 
 /// An open file on the local filesystem.
+#[derive(Debug)]
 pub struct LocalFile {
     inner: fs::File,
     size: u64,
@@ -62,6 +63,7 @@ impl File for LocalFile {
 /// let file = storage.open_file("manifest.bin").await.unwrap();
 /// # }
 /// ```
+#[derive(Debug)]
 pub struct LocalFileStorage {
     root: PathBuf,
 }
@@ -125,7 +127,7 @@ mod tests {
     #[tokio::test]
     async fn local_storage_size_matches() {
         let tmp = TempDir::new().unwrap();
-        fs::write(tmp.path().join("size.bin"), &[0u8; 64]).unwrap();
+        fs::write(tmp.path().join("size.bin"), [0u8; 64]).unwrap();
         let mut storage = LocalFileStorage::new(tmp.path().to_str().unwrap());
         let file = storage.open_file("size.bin").await.unwrap();
         assert_eq!(file.size(), 64);
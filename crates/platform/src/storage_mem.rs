@@ -0,0 +1,587 @@
+//! In-memory FAT32-style volume implementing `platform::Storage`.
+//!
+//! `MemoryVolume` lets library scanner, playlist and persistence code run
+//! against a realistic file tree on the host without temp directories or SD
+//! card images.  It reproduces the FAT32 behaviour that code on the device
+//! actually observes:
+//!
+//! - **Case-insensitive, case-preserving names.**  `Music/ABBA` and
+//!   `music/abba` are the same directory; listings return the spelling used
+//!   when the entry was created.
+//! - **Directory order is creation order**, not alphabetical — callers that
+//!   need sorted output must sort themselves, exactly as on the card.
+//! - **Long-file-name rules.**  Components are at most 255 characters and
+//!   may not contain `" * : < > ? |`, control characters, or end in a space
+//!   or dot.  Whole paths are limited to [`MAX_PATH_LEN`].
+//! - **4 GiB − 1 maximum file size.**
+//! - Both `/` and `\` separate components; a leading `/` is optional.
+//!
+//! Two fault-injection knobs cover the error paths that are awkward to reach
+//! with real files: [`MemoryVolume::set_max_read`] makes every read return at
+//! most N bytes (SDMMC reads are block-sized, so callers must loop), and
+//! [`MemoryVolume::fail_open`] makes opening a given path fail.
+//!
+//! Use [`crate::storage_fixture::FixtureBuilder`] to populate a volume with
+//! tagged audio files.
+//!
+//! # Example
+//! ```
+//! # async fn example() {
+//! use platform::storage_mem::MemoryVolume;
+//! use platform::{File, Storage};
+//!
+//! let mut vol = MemoryVolume::new();
+//! vol.write_file("/soul/manifest.bin", [1u8, 2, 3]).unwrap();
+//! assert!(vol.exists("/SOUL/Manifest.BIN").await.unwrap());
+//!
+//! let mut file = vol.open_file("soul/manifest.bin").await.unwrap();
+//! let mut buf = [0u8; 3];
+//! assert_eq!(file.read(&mut buf).await.unwrap(), 3);
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::storage::{File, Storage};
+
+/// Longest permitted name component, in characters (FAT LFN limit).
+pub const MAX_NAME_LEN: usize = 255;
+
+/// Longest permitted normalised path, in characters.
+pub const MAX_PATH_LEN: usize = 260;
+
+/// Largest file FAT32 can store.
+pub const MAX_FILE_SIZE: u64 = 0xFFFF_FFFF;
+
+/// Characters FAT long file names may not contain (besides separators).
+const ILLEGAL_CHARS: &[char] = &['"', '*', ':', '<', '>', '?', '|'];
+
+/// Error type for [`MemoryVolume`] operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryStorageError {
+    /// No entry exists at the path (or a parent is missing).
+    NotFound,
+    /// A file was expected but the path names a directory.
+    IsADirectory,
+    /// A directory was expected but the path (or a parent) names a file.
+    NotADirectory,
+    /// The path violates FAT naming rules.
+    InvalidName,
+    /// The data exceeds [`MAX_FILE_SIZE`].
+    FileTooLarge,
+    /// The path was registered with [`MemoryVolume::fail_open`].
+    InjectedFault,
+}
+
+impl core::fmt::Display for MemoryStorageError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let msg = match self {
+            Self::NotFound => "no such file or directory",
+            Self::IsADirectory => "is a directory",
+            Self::NotADirectory => "not a directory",
+            Self::InvalidName => "invalid FAT file name",
+            Self::FileTooLarge => "file exceeds FAT32 size limit",
+            Self::InjectedFault => "injected I/O fault",
+        };
+        write!(f, "memory storage error: {msg}")
+    }
+}
+
+impl std::error::Error for MemoryStorageError {}
+
+/// One entry returned by [`MemoryVolume::read_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// Name as created (case preserved).
+    pub name: String,
+    /// Full path with `/` separators and a leading `/`.
+    pub path: String,
+    /// `true` for directories.
+    pub is_dir: bool,
+    /// File size in bytes (0 for directories).
+    pub size: u64,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    /// Display path (case preserved), e.g. `/Music/ABBA`.
+    path: String,
+    /// Creation sequence number, for FAT-style directory order.
+    seq: u64,
+    /// `None` for directories.
+    data: Option<Arc<[u8]>>,
+}
+
+/// A case-insensitive in-memory volume.  See the module docs.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryVolume {
+    /// Keyed by case-folded path without a leading `/`; the root is implicit.
+    nodes: BTreeMap<String, Node>,
+    next_seq: u64,
+    max_read: Option<usize>,
+    failing: BTreeSet<String>,
+}
+
+/// A normalised path: display components and the case-folded key.
+struct Normalised {
+    components: Vec<String>,
+    key: String,
+}
+
+impl MemoryVolume {
+    /// Create an empty volume.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create `path` and any missing parents.
+    pub fn mkdir_all(&mut self, path: &str) -> Result<(), MemoryStorageError> {
+        let norm = normalise(path)?;
+        self.ensure_dirs(&norm.components)
+    }
+
+    /// Create or replace a file, creating missing parent directories.
+    pub fn write_file(
+        &mut self,
+        path: &str,
+        data: impl Into<Vec<u8>>,
+    ) -> Result<(), MemoryStorageError> {
+        let data = data.into();
+        if data.len() as u64 > MAX_FILE_SIZE {
+            return Err(MemoryStorageError::FileTooLarge);
+        }
+        let norm = normalise(path)?;
+        let Some((_, parents)) = norm.components.split_last() else {
+            return Err(MemoryStorageError::IsADirectory);
+        };
+        self.ensure_dirs(parents)?;
+
+        let data: Arc<[u8]> = data.into();
+        match self.nodes.get_mut(&norm.key) {
+            Some(node) if node.data.is_none() => Err(MemoryStorageError::IsADirectory),
+            // Overwriting keeps the original name spelling and directory slot.
+            Some(node) => {
+                node.data = Some(data);
+                Ok(())
+            }
+            None => {
+                self.insert(&norm, Some(data));
+                Ok(())
+            }
+        }
+    }
+
+    /// Remove a file, or a directory and everything below it.
+    pub fn remove(&mut self, path: &str) -> Result<(), MemoryStorageError> {
+        let norm = normalise(path)?;
+        if self.nodes.remove(&norm.key).is_none() {
+            return Err(MemoryStorageError::NotFound);
+        }
+        let prefix = format!("{}/", norm.key);
+        self.nodes.retain(|key, _| !key.starts_with(&prefix));
+        Ok(())
+    }
+
+    /// List a directory in creation order.  `""` or `"/"` lists the root.
+    pub fn read_dir(&self, path: &str) -> Result<Vec<DirEntry>, MemoryStorageError> {
+        let norm = normalise(path)?;
+        if !norm.key.is_empty() {
+            match self.nodes.get(&norm.key) {
+                None => return Err(MemoryStorageError::NotFound),
+                Some(node) if node.data.is_some() => return Err(MemoryStorageError::NotADirectory),
+                Some(_) => {}
+            }
+        }
+        let mut children: Vec<&Node> = self
+            .nodes
+            .iter()
+            .filter(|(key, _)| parent_key(key) == norm.key)
+            .map(|(_, node)| node)
+            .collect();
+        children.sort_by_key(|node| node.seq);
+        Ok(children
+            .into_iter()
+            .map(|node| DirEntry {
+                name: node.path.rsplit('/').next().unwrap_or_default().to_owned(),
+                path: node.path.clone(),
+                is_dir: node.data.is_none(),
+                size: node.data.as_ref().map_or(0, |d| d.len() as u64),
+            })
+            .collect())
+    }
+
+    /// Every file below `path`, depth-first in directory order.
+    pub fn walk_files(&self, path: &str) -> Result<Vec<DirEntry>, MemoryStorageError> {
+        let mut out = Vec::new();
+        let mut stack = vec![self.read_dir(path)?.into_iter()];
+        while let Some(iter) = stack.last_mut() {
+            match iter.next() {
+                Some(entry) if entry.is_dir => {
+                    let below = self.read_dir(&entry.path)?;
+                    stack.push(below.into_iter());
+                }
+                Some(entry) => out.push(entry),
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Contents of a file, if `path` names one.
+    #[must_use]
+    pub fn file_bytes(&self, path: &str) -> Option<&[u8]> {
+        let norm = normalise(path).ok()?;
+        self.nodes.get(&norm.key)?.data.as_deref()
+    }
+
+    /// Number of files on the volume.
+    #[must_use]
+    pub fn file_count(&self) -> usize {
+        self.nodes.values().filter(|n| n.data.is_some()).count()
+    }
+
+    /// Limit every [`File::read`] to at most `max` bytes (`None` = unlimited).
+    ///
+    /// Applies to files opened after the call.  A limit of 0 is treated as 1.
+    pub fn set_max_read(&mut self, max: Option<usize>) {
+        self.max_read = max.map(|m| m.max(1));
+    }
+
+    /// Make [`Storage::open_file`] on `path` fail with
+    /// [`MemoryStorageError::InjectedFault`].
+    pub fn fail_open(&mut self, path: &str) -> Result<(), MemoryStorageError> {
+        let norm = normalise(path)?;
+        self.failing.insert(norm.key);
+        Ok(())
+    }
+
+    /// Copy a host directory tree into the volume under `at`.
+    ///
+    /// Useful for loading the output of `std::fs`-based writers (e.g.
+    /// `library::writer::LibraryWriter`) and then exercising the reader
+    /// against the in-memory copy.
+    pub fn import_dir(&mut self, host_dir: &Path, at: &str) -> std::io::Result<()> {
+        let to_io =
+            |e: MemoryStorageError| std::io::Error::new(std::io::ErrorKind::InvalidInput, e);
+        self.mkdir_all(at).map_err(to_io)?;
+        let mut entries: Vec<_> = std::fs::read_dir(host_dir)?.collect::<Result<_, _>>()?;
+        entries.sort_by_key(std::fs::DirEntry::file_name);
+        for entry in entries {
+            let name = entry.file_name();
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            let target = format!("{}/{name}", at.trim_end_matches(['/', '\\']));
+            if entry.file_type()?.is_dir() {
+                self.import_dir(&entry.path(), &target)?;
+            } else {
+                self.write_file(&target, std::fs::read(entry.path())?)
+                    .map_err(to_io)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn ensure_dirs(&mut self, components: &[String]) -> Result<(), MemoryStorageError> {
+        let mut display = Vec::with_capacity(components.len());
+        for component in components {
+            display.push(component.clone());
+            let norm = Normalised {
+                key: fold(&display.join("/")),
+                components: display.clone(),
+            };
+            match self.nodes.get(&norm.key) {
+                Some(node) if node.data.is_some() => return Err(MemoryStorageError::NotADirectory),
+                Some(node) => {
+                    // Keep the existing spelling for the rest of the path.
+                    if let (Some(last), Some(name)) =
+                        (display.last_mut(), node.path.rsplit('/').next())
+                    {
+                        name.clone_into(last);
+                    }
+                }
+                None => self.insert(&norm, None),
+            }
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, norm: &Normalised, data: Option<Arc<[u8]>>) {
+        // Parents are created first, so the parent's spelling is already fixed.
+        let name = norm.components.last().cloned().unwrap_or_default();
+        let parent = parent_key(&norm.key);
+        let parent_path = self
+            .nodes
+            .get(parent)
+            .map_or_else(String::new, |n| n.path.clone());
+        let node = Node {
+            path: format!("{parent_path}/{name}"),
+            seq: self.next_seq,
+            data,
+        };
+        self.next_seq = self.next_seq.wrapping_add(1);
+        self.nodes.insert(norm.key.clone(), node);
+    }
+}
+
+/// An open file on a [`MemoryVolume`].
+///
+/// Holds a snapshot of the contents taken at open time, like a FAT handle
+/// whose cluster chain is not affected by later directory changes.
+#[derive(Debug, Clone)]
+pub struct MemoryFile {
+    data: Arc<[u8]>,
+    pos: usize,
+    max_read: Option<usize>,
+}
+
+impl File for MemoryFile {
+    type Error = MemoryStorageError;
+
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let remaining = self.data.get(self.pos..).unwrap_or_default();
+        let n = buf
+            .len()
+            .min(remaining.len())
+            .min(self.max_read.unwrap_or(usize::MAX));
+        if let (Some(dst), Some(src)) = (buf.get_mut(..n), remaining.get(..n)) {
+            dst.copy_from_slice(src);
+        }
+        self.pos = self.pos.saturating_add(n);
+        Ok(n)
+    }
+
+    async fn seek(&mut self, pos: u64) -> Result<u64, Self::Error> {
+        // Seeking past the end is allowed; subsequent reads return 0.
+        self.pos = usize::try_from(pos).unwrap_or(usize::MAX);
+        Ok(pos)
+    }
+
+    fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+}
+
+impl Storage for MemoryVolume {
+    type Error = MemoryStorageError;
+    type File = MemoryFile;
+
+    async fn open_file(&mut self, path: &str) -> Result<Self::File, Self::Error> {
+        let norm = normalise(path)?;
+        if self.failing.contains(&norm.key) {
+            return Err(MemoryStorageError::InjectedFault);
+        }
+        match self.nodes.get(&norm.key) {
+            Some(Node {
+                data: Some(data), ..
+            }) => Ok(MemoryFile {
+                data: Arc::clone(data),
+                pos: 0,
+                max_read: self.max_read,
+            }),
+            Some(_) => Err(MemoryStorageError::IsADirectory),
+            None if norm.key.is_empty() => Err(MemoryStorageError::IsADirectory),
+            None => Err(MemoryStorageError::NotFound),
+        }
+    }
+
+    async fn exists(&mut self, path: &str) -> Result<bool, Self::Error> {
+        let norm = normalise(path)?;
+        Ok(norm.key.is_empty() || self.nodes.contains_key(&norm.key))
+    }
+}
+
+/// Split, validate and case-fold a path.
+fn normalise(path: &str) -> Result<Normalised, MemoryStorageError> {
+    let mut components = Vec::new();
+    for component in path.split(['/', '\\']) {
+        match component {
+            "" | "." => {}
+            ".." => return Err(MemoryStorageError::InvalidName),
+            name => {
+                validate_name(name)?;
+                components.push(name.to_owned());
+            }
+        }
+    }
+    let joined = components.join("/");
+    if joined.chars().count() >= MAX_PATH_LEN {
+        return Err(MemoryStorageError::InvalidName);
+    }
+    Ok(Normalised {
+        key: fold(&joined),
+        components,
+    })
+}
+
+fn validate_name(name: &str) -> Result<(), MemoryStorageError> {
+    let valid = name.chars().count() <= MAX_NAME_LEN
+        && !name.ends_with([' ', '.'])
+        && !name
+            .chars()
+            .any(|c| c.is_control() || ILLEGAL_CHARS.contains(&c));
+    if valid {
+        Ok(())
+    } else {
+        Err(MemoryStorageError::InvalidName)
+    }
+}
+
+/// FAT compares long names case-insensitively.
+fn fold(path: &str) -> String {
+    path.to_lowercase()
+}
+
+fn parent_key(key: &str) -> &str {
+    key.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#[allow(clippy::indexing_slicing)] // Tests index with known bounds
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_is_case_insensitive_and_preserving() {
+        let mut vol = MemoryVolume::new();
+        vol.write_file("Music/ABBA/Gold.flac", [0u8; 4]).unwrap();
+        vol.write_file("music/abba/Arrival.flac", [0u8; 2]).unwrap();
+
+        let root = vol.read_dir("/").unwrap();
+        assert_eq!(root.len(), 1);
+        assert_eq!(root[0].path, "/Music");
+
+        let names: Vec<_> = vol
+            .read_dir("MUSIC\\abba")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.path)
+            .collect();
+        assert_eq!(names, ["/Music/ABBA/Gold.flac", "/Music/ABBA/Arrival.flac"]);
+    }
+
+    #[test]
+    fn test_directory_order_is_creation_order() {
+        let mut vol = MemoryVolume::new();
+        for name in ["c.mp3", "a.mp3", "b.mp3"] {
+            vol.write_file(&format!("/d/{name}"), []).unwrap();
+        }
+        let names: Vec<_> = vol
+            .read_dir("/d")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, ["c.mp3", "a.mp3", "b.mp3"]);
+    }
+
+    #[test]
+    fn test_invalid_names_rejected() {
+        let mut vol = MemoryVolume::new();
+        for bad in [
+            "a:b",
+            "what?",
+            "trailing.",
+            "trailing ",
+            "../up",
+            "tab\there",
+        ] {
+            assert_eq!(
+                vol.write_file(bad, []),
+                Err(MemoryStorageError::InvalidName),
+                "{bad}"
+            );
+        }
+        let long = "x".repeat(MAX_NAME_LEN + 1);
+        assert_eq!(vol.mkdir_all(&long), Err(MemoryStorageError::InvalidName));
+    }
+
+    #[test]
+    fn test_file_and_directory_conflicts() {
+        let mut vol = MemoryVolume::new();
+        vol.write_file("/a/file", [1u8]).unwrap();
+        assert_eq!(
+            vol.write_file("/a/file/child", []),
+            Err(MemoryStorageError::NotADirectory)
+        );
+        assert_eq!(
+            vol.write_file("/a", []),
+            Err(MemoryStorageError::IsADirectory)
+        );
+        assert_eq!(
+            vol.read_dir("/a/file"),
+            Err(MemoryStorageError::NotADirectory)
+        );
+    }
+
+    #[test]
+    fn test_remove_directory_is_recursive() {
+        let mut vol = MemoryVolume::new();
+        vol.write_file("/a/b/c.wav", []).unwrap();
+        vol.write_file("/ab.wav", []).unwrap();
+        vol.remove("/A").unwrap();
+        assert_eq!(vol.file_count(), 1);
+        assert_eq!(vol.remove("/a"), Err(MemoryStorageError::NotFound));
+    }
+
+    #[test]
+    fn test_walk_files_depth_first() {
+        let mut vol = MemoryVolume::new();
+        vol.write_file("/m/x/1.flac", []).unwrap();
+        vol.write_file("/m/top.mp3", []).unwrap();
+        vol.write_file("/m/x/y/2.flac", []).unwrap();
+        let paths: Vec<_> = vol
+            .walk_files("/m")
+            .unwrap()
+            .into_iter()
+            .map(|e| e.path)
+            .collect();
+        assert_eq!(paths, ["/m/x/1.flac", "/m/x/y/2.flac", "/m/top.mp3"]);
+    }
+
+    #[tokio::test]
+    async fn test_max_read_forces_short_reads() {
+        let mut vol = MemoryVolume::new();
+        vol.write_file("/f", *b"abcdefgh").unwrap();
+        vol.set_max_read(Some(3));
+        let mut file = vol.open_file("/f").await.unwrap();
+        let mut buf = [0u8; 8];
+        assert_eq!(file.read(&mut buf).await.unwrap(), 3);
+        assert_eq!(&buf[..3], b"abc");
+    }
+
+    #[tokio::test]
+    async fn test_fail_open_and_directory_open() {
+        let mut vol = MemoryVolume::new();
+        vol.write_file("/soul/manifest.bin", [0u8; 64]).unwrap();
+        vol.fail_open("/SOUL/manifest.bin").unwrap();
+        assert_eq!(
+            vol.open_file("/soul/manifest.bin").await.unwrap_err(),
+            MemoryStorageError::InjectedFault
+        );
+        assert_eq!(
+            vol.open_file("/soul").await.unwrap_err(),
+            MemoryStorageError::IsADirectory
+        );
+    }
+
+    #[test]
+    fn test_import_dir_copies_tree() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(tmp.path().join("art")).unwrap();
+        std::fs::write(tmp.path().join("art/01.raw"), [7u8; 5]).unwrap();
+        std::fs::write(tmp.path().join("manifest.bin"), [1u8; 3]).unwrap();
+
+        let mut vol = MemoryVolume::new();
+        vol.import_dir(tmp.path(), "/soul").unwrap();
+        assert_eq!(vol.file_bytes("/soul/art/01.raw"), Some(&[7u8; 5][..]));
+        assert_eq!(vol.file_bytes("/soul/manifest.bin"), Some(&[1u8; 3][..]));
+    }
+}
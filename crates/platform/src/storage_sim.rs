@@ -0,0 +1,882 @@
+//! Emulated SD card for the desktop emulator.
+//!
+//! [`SimulatedSdCard`] wraps any [`Storage`] and charges every operation
+//! the time a real microSD card would take, so library scanning and audio
+//! streaming can be profiled on the desktop without hardware.  The backing
+//! store is usually a [`HostVolume`]: the directory or FAT32 image named by
+//! `MUSIC_PATH` (see `cargo xtask dev --music-path`).
+//!
+//! # Cost model
+//!
+//! The card is modelled the way the SDMMC driver uses it — whole 512-byte
+//! blocks, one read command per [`File::read`]:
+//!
+//! - every read pays [`SdCardProfile::command_latency_us`] plus the block
+//!   transfer time at [`SdCardProfile::read_bytes_per_sec`];
+//! - a read that does not continue where the previous one stopped (a seek,
+//!   or the first read of a file) also pays
+//!   [`SdCardProfile::random_access_us`];
+//! - `open_file` and `exists` pay [`SdCardProfile::lookup_latency_us`] for
+//!   the directory walk.
+//!
+//! With [`Pacing::RealTime`] the cost is slept through an
+//! `embassy_time::Timer`, so the UI sees the stalls it would see on the
+//! device.  With [`Pacing::Virtual`] it is only added to the
+//! [`SdStats`] counters, which keeps tests and benchmarks fast while still
+//! reporting how long the work would have taken.
+//!
+//! # Fault injection
+//!
+//! [`SdFaults`] adds the failures that retry, skip and graceful-stop paths
+//! exist for: random read errors, latency spikes, and pulling the card
+//! after a given number of bytes.  [`SimulatedSdCard::remove`] and
+//! [`SimulatedSdCard::insert`] do the same on demand.  In the emulator the
+//! faults come from `SD_FAULTS` (see [`SdFaults::parse`]).
+//!
+//! # Example
+//! ```
+//! # async fn example() {
+//! use platform::storage_mem::MemoryVolume;
+//! use platform::storage_sim::{Pacing, SdCardProfile, SimulatedSdCard};
+//! use platform::{File, Storage};
+//!
+//! let mut vol = MemoryVolume::new();
+//! vol.write_file("/Music/track.flac", vec![0u8; 4096]).unwrap();
+//!
+//! let mut card = SimulatedSdCard::new(vol, SdCardProfile::CLASS_10, Pacing::Virtual);
+//! let mut file = card.open_file("/Music/track.flac").await.unwrap();
+//! let mut buf = [0u8; 4096];
+//! file.read(&mut buf).await.unwrap();
+//! assert!(card.stats().busy_us > 0);
+//! # }
+//! ```
+
+use std::path::Path;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::storage::{File, Storage};
+use crate::storage_fat::{FatFile, FatImage, FatImageError};
+use crate::storage_local::{LocalFile, LocalFileStorage, LocalStorageError};
+
+/// SDMMC block size; transfers are rounded up to whole blocks.
+const BLOCK_LEN: u64 = 512;
+
+/// Timing characteristics of one kind of SD card.
+///
+/// The built-in profiles are ballpark figures for 4-bit SDR25 reads (the
+/// bus tops out at 25 MB/s, see [`crate::storage_config::SdmmcConfig`]),
+/// taken from the SD speed-class minima and typical card behaviour rather
+/// than from measurements on the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SdCardProfile {
+    /// Name accepted by [`SdCardProfile::by_name`].
+    pub name: &'static str,
+    /// Fixed cost of one read command.
+    pub command_latency_us: u32,
+    /// Extra cost of a read that is not sequential with the previous one.
+    pub random_access_us: u32,
+    /// Cost of resolving a path (`open_file`, `exists`).
+    pub lookup_latency_us: u32,
+    /// Sustained read throughput; 0 means transfers are free.
+    pub read_bytes_per_sec: u32,
+}
+
+impl SdCardProfile {
+    /// No simulated cost at all — host speed.
+    pub const UNTHROTTLED: Self = Self {
+        name: "unthrottled",
+        command_latency_us: 0,
+        random_access_us: 0,
+        lookup_latency_us: 0,
+        read_bytes_per_sec: 0,
+    };
+
+    /// Good UHS-I card with the A1 app-performance class, bus-limited.
+    pub const UHS_I_A1: Self = Self {
+        name: "uhs1-a1",
+        command_latency_us: 150,
+        random_access_us: 650,
+        lookup_latency_us: 1_500,
+        read_bytes_per_sec: 22_000_000,
+    };
+
+    /// Typical Class 10 card without an app-performance rating.
+    pub const CLASS_10: Self = Self {
+        name: "class10",
+        command_latency_us: 300,
+        random_access_us: 2_000,
+        lookup_latency_us: 4_000,
+        read_bytes_per_sec: 15_000_000,
+    };
+
+    /// Old Class 4 card — the worst case the player should still handle.
+    pub const CLASS_4: Self = Self {
+        name: "class4",
+        command_latency_us: 1_000,
+        random_access_us: 8_000,
+        lookup_latency_us: 12_000,
+        read_bytes_per_sec: 4_000_000,
+    };
+
+    /// Every built-in profile, fastest first.
+    pub const ALL: [Self; 4] = [
+        Self::UNTHROTTLED,
+        Self::UHS_I_A1,
+        Self::CLASS_10,
+        Self::CLASS_4,
+    ];
+
+    /// Look up a built-in profile by [`name`](Self::name), case-insensitively.
+    pub fn by_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The profile named by the `SD_PROFILE` environment variable.
+    ///
+    /// Returns [`UNTHROTTLED`](Self::UNTHROTTLED) when the variable is unset
+    /// and `None` when it names no profile.
+    pub fn from_env() -> Option<Self> {
+        match std::env::var("SD_PROFILE") {
+            Ok(name) => Self::by_name(&name),
+            Err(_) => Some(Self::UNTHROTTLED),
+        }
+    }
+
+    /// Simulated duration of one `len`-byte read, in microseconds.
+    pub fn read_cost_us(&self, len: usize, sequential: bool) -> u64 {
+        let blocks = (len as u64).div_ceil(BLOCK_LEN);
+        let transfer = blocks
+            .saturating_mul(BLOCK_LEN)
+            .saturating_mul(1_000_000)
+            .checked_div(u64::from(self.read_bytes_per_sec))
+            .unwrap_or(0);
+        let seek = if sequential {
+            0
+        } else {
+            u64::from(self.random_access_us)
+        };
+        u64::from(self.command_latency_us)
+            .saturating_add(seek)
+            .saturating_add(transfer)
+    }
+}
+
+/// How simulated time is spent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pacing {
+    /// Sleep for the simulated duration of every operation.
+    RealTime,
+    /// Only account for the time in [`SdStats`].
+    Virtual,
+}
+
+/// Counters accumulated by a [`SimulatedSdCard`] and its open files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SdStats {
+    /// Total simulated card time, in microseconds.
+    pub busy_us: u64,
+    /// Read commands issued.
+    pub reads: u64,
+    /// Reads that paid the random-access penalty.
+    pub random_reads: u64,
+    /// Bytes returned to callers.
+    pub bytes_read: u64,
+    /// Path lookups (`open_file` and `exists`).
+    pub lookups: u64,
+    /// Injected read errors.
+    pub read_errors: u64,
+    /// Injected latency spikes.
+    pub spikes: u64,
+}
+
+impl SdStats {
+    /// Effective read throughput in bytes per second of simulated time.
+    pub fn throughput_bytes_per_sec(&self) -> u64 {
+        self.bytes_read
+            .saturating_mul(1_000_000)
+            .checked_div(self.busy_us)
+            .unwrap_or(0)
+    }
+}
+
+/// Faults injected by a [`SimulatedSdCard`].
+///
+/// Random faults come from a seeded PRNG, so a run that exposed a bug can
+/// be replayed exactly with the same `seed` and the same sequence of calls.
+/// The default injects nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SdFaults {
+    /// On average one read in N fails with [`SdCardError::ReadFault`].
+    pub read_error_one_in: Option<u32>,
+    /// On average one read in N stalls for an extra [`spike_us`](Self::spike_us).
+    pub spike_one_in: Option<u32>,
+    /// Length of a latency spike.
+    pub spike_us: u32,
+    /// The card is pulled once this many bytes have been read from it in
+    /// total; the read that crosses the mark returns only the bytes before it.
+    pub remove_after_bytes: Option<u64>,
+    /// PRNG seed.
+    pub seed: u64,
+}
+
+impl SdFaults {
+    /// Parse a comma-separated spec such as
+    /// `read-error=500,spike=100:80000,remove-at=1048576,seed=7`.
+    ///
+    /// | Key         | Value                          |
+    /// |-------------|--------------------------------|
+    /// | `read-error`| N: one read in N fails         |
+    /// | `spike`     | `N:us`: one read in N stalls   |
+    /// | `remove-at` | total bytes read before removal|
+    /// | `seed`      | PRNG seed                      |
+    ///
+    /// Returns `None` on an unknown key or malformed value.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut faults = Self::default();
+        for item in spec.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = item.split_once('=')?;
+            match key.trim() {
+                "read-error" => faults.read_error_one_in = Some(value.trim().parse().ok()?),
+                "spike" => {
+                    let (one_in, us) = value.split_once(':')?;
+                    faults.spike_one_in = Some(one_in.trim().parse().ok()?);
+                    faults.spike_us = us.trim().parse().ok()?;
+                }
+                "remove-at" => faults.remove_after_bytes = Some(value.trim().parse().ok()?),
+                "seed" => faults.seed = value.trim().parse().ok()?,
+                _ => return None,
+            }
+        }
+        Some(faults)
+    }
+
+    /// Faults from the `SD_FAULTS` environment variable (see
+    /// [`parse`](Self::parse)); none when unset or malformed.
+    pub fn from_env() -> Self {
+        std::env::var("SD_FAULTS")
+            .ok()
+            .and_then(|spec| Self::parse(&spec))
+            .unwrap_or_default()
+    }
+}
+
+/// Error type for [`SimulatedSdCard`] operations.
+#[derive(Debug)]
+pub enum SdCardError<E> {
+    /// From the backing storage.
+    Inner(E),
+    /// An injected read error (the SDMMC driver's CRC / timeout case).
+    ReadFault,
+    /// The card has been removed; every operation fails until
+    /// [`SimulatedSdCard::insert`].
+    Removed,
+}
+
+impl<E: core::fmt::Display> core::fmt::Display for SdCardError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Inner(e) => write!(f, "{e}"),
+            Self::ReadFault => write!(f, "SD card error: injected read fault"),
+            Self::Removed => write!(f, "SD card error: card removed"),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for SdCardError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Inner(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Card state behind the [`Card`] handle.
+#[derive(Debug, Default)]
+struct CardState {
+    stats: SdStats,
+    faults: SdFaults,
+    rng: u64,
+    removed: bool,
+    /// Bumped on every [`SimulatedSdCard::insert`]; files from an older
+    /// insertion stay dead.
+    generation: u32,
+}
+
+impl CardState {
+    /// `true` on average once in `one_in` calls (xorshift64*).
+    fn roll(&mut self, one_in: Option<u32>) -> bool {
+        let Some(one_in) = one_in.filter(|&n| n > 0) else {
+            return false;
+        };
+        self.rng ^= self.rng.wrapping_shr(12);
+        self.rng ^= self.rng.wrapping_shl(25);
+        self.rng ^= self.rng.wrapping_shr(27);
+        let value = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        value.checked_rem(u64::from(one_in)) == Some(0)
+    }
+}
+
+/// Shared between the card and its files, which outlive `&mut` borrows of it.
+#[derive(Debug, Clone)]
+struct Card {
+    pacing: Pacing,
+    state: Arc<Mutex<CardState>>,
+}
+
+impl Card {
+    fn lock(&self) -> std::sync::MutexGuard<'_, CardState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// `Removed` unless the card is in and still the one from `generation`.
+    fn check_inserted<E>(&self, generation: u32) -> Result<(), SdCardError<E>> {
+        let state = self.lock();
+        if state.removed || state.generation != generation {
+            Err(SdCardError::Removed)
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn spend(&self, us: u64, update: impl FnOnce(&mut SdStats)) {
+        {
+            let mut state = self.lock();
+            state.stats.busy_us = state.stats.busy_us.saturating_add(us);
+            update(&mut state.stats);
+        }
+        if self.pacing == Pacing::RealTime && us > 0 {
+            embassy_time::Timer::after(embassy_time::Duration::from_micros(us)).await;
+        }
+    }
+}
+
+/// A [`Storage`] with SD card timing and fault injection.  See the module docs.
+#[derive(Debug)]
+pub struct SimulatedSdCard<S> {
+    inner: S,
+    profile: SdCardProfile,
+    card: Card,
+}
+
+impl<S: Storage> SimulatedSdCard<S> {
+    /// Wrap `inner` with the timing of `profile` and no faults.
+    pub fn new(inner: S, profile: SdCardProfile, pacing: Pacing) -> Self {
+        Self {
+            inner,
+            profile,
+            card: Card {
+                pacing,
+                state: Arc::default(),
+            },
+        }
+    }
+
+    /// The active profile.
+    pub fn profile(&self) -> SdCardProfile {
+        self.profile
+    }
+
+    /// Change the profile; files opened afterwards use the new one.
+    pub fn set_profile(&mut self, profile: SdCardProfile) {
+        self.profile = profile;
+    }
+
+    /// Replace the injected faults and reseed the PRNG.
+    ///
+    /// Applies to open files too.  The removal mark counts from the bytes
+    /// already read, so `remove_after_bytes: Some(0)` pulls the card on the
+    /// next read.
+    pub fn set_faults(&mut self, faults: SdFaults) {
+        let mut state = self.card.lock();
+        state.rng = faults.seed.max(1);
+        state.faults = SdFaults {
+            remove_after_bytes: faults
+                .remove_after_bytes
+                .map(|n| n.saturating_add(state.stats.bytes_read)),
+            ..faults
+        };
+    }
+
+    /// Pull the card now.
+    pub fn remove(&self) {
+        self.card.lock().removed = true;
+    }
+
+    /// Put the card back.  Files opened before the removal stay dead, as
+    /// on the device, where the FAT volume must be remounted.
+    pub fn insert(&self) {
+        let mut state = self.card.lock();
+        if state.removed {
+            state.removed = false;
+            state.faults.remove_after_bytes = None;
+            state.generation = state.generation.wrapping_add(1);
+        }
+    }
+
+    /// Whether the card is currently removed.
+    pub fn is_removed(&self) -> bool {
+        self.card.lock().removed
+    }
+
+    /// Counters since creation or the last [`reset_stats`](Self::reset_stats).
+    pub fn stats(&self) -> SdStats {
+        self.card.lock().stats
+    }
+
+    /// Zero the counters.
+    pub fn reset_stats(&self) {
+        self.card.lock().stats = SdStats::default();
+    }
+
+    /// The wrapped storage.
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    async fn lookup(&self) -> Result<(), SdCardError<S::Error>> {
+        let generation = self.card.lock().generation;
+        self.card.check_inserted(generation)?;
+        let cost = u64::from(self.profile.lookup_latency_us);
+        self.card
+            .spend(cost, |s| s.lookups = s.lookups.saturating_add(1))
+            .await;
+        Ok(())
+    }
+}
+
+impl<S: Storage> Storage for SimulatedSdCard<S> {
+    type Error = SdCardError<S::Error>;
+    type File = SimulatedFile<S::File>;
+
+    async fn open_file(&mut self, path: &str) -> Result<Self::File, Self::Error> {
+        self.lookup().await?;
+        let generation = self.card.lock().generation;
+        let inner = self
+            .inner
+            .open_file(path)
+            .await
+            .map_err(SdCardError::Inner)?;
+        Ok(SimulatedFile {
+            inner,
+            profile: self.profile,
+            card: self.card.clone(),
+            generation,
+            pos: 0,
+            next_sequential: None,
+        })
+    }
+
+    async fn exists(&mut self, path: &str) -> Result<bool, Self::Error> {
+        self.lookup().await?;
+        self.inner.exists(path).await.map_err(SdCardError::Inner)
+    }
+}
+
+/// An open file on a [`SimulatedSdCard`].
+#[derive(Debug)]
+pub struct SimulatedFile<F> {
+    inner: F,
+    profile: SdCardProfile,
+    card: Card,
+    generation: u32,
+    pos: u64,
+    /// Where a read must start to count as sequential (`None` before the first).
+    next_sequential: Option<u64>,
+}
+
+impl<F: File> File for SimulatedFile<F> {
+    type Error = SdCardError<F::Error>;
+
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let sequential = self.next_sequential == Some(self.pos);
+        self.card.check_inserted(self.generation)?;
+        let (fault, spike, left) = {
+            let mut state = self.card.lock();
+            let faults = state.faults;
+            let left = faults
+                .remove_after_bytes
+                .map(|mark| mark.saturating_sub(state.stats.bytes_read));
+            if left == Some(0) {
+                state.removed = true;
+                return Err(SdCardError::Removed);
+            }
+            let fault = state.roll(faults.read_error_one_in);
+            let spike = if state.roll(faults.spike_one_in) {
+                u64::from(faults.spike_us)
+            } else {
+                0
+            };
+            (fault, spike, left)
+        };
+
+        if fault {
+            // The command went out and timed out: pay for it, move nothing.
+            let cost = self
+                .profile
+                .read_cost_us(0, sequential)
+                .saturating_add(spike);
+            self.card
+                .spend(cost, |s| s.read_errors = s.read_errors.saturating_add(1))
+                .await;
+            self.next_sequential = None;
+            return Err(SdCardError::ReadFault);
+        }
+
+        let mut n = self.inner.read(buf).await.map_err(SdCardError::Inner)?;
+        if let Some(left) = left {
+            // Deliver what was read before the contacts lost touch.
+            n = n.min(usize::try_from(left).unwrap_or(usize::MAX));
+        }
+
+        let cost = self
+            .profile
+            .read_cost_us(n, sequential)
+            .saturating_add(spike);
+        self.card
+            .spend(cost, |s| {
+                s.reads = s.reads.saturating_add(1);
+                s.bytes_read = s.bytes_read.saturating_add(n as u64);
+                if !sequential {
+                    s.random_reads = s.random_reads.saturating_add(1);
+                }
+                if spike > 0 {
+                    s.spikes = s.spikes.saturating_add(1);
+                }
+            })
+            .await;
+        self.pos = self.pos.saturating_add(n as u64);
+        self.next_sequential = Some(self.pos);
+        Ok(n)
+    }
+
+    async fn seek(&mut self, pos: u64) -> Result<u64, Self::Error> {
+        self.card.check_inserted(self.generation)?;
+        // Free until the next read, which then pays for the random access.
+        self.pos = self.inner.seek(pos).await.map_err(SdCardError::Inner)?;
+        Ok(self.pos)
+    }
+
+    fn size(&self) -> u64 {
+        self.inner.size()
+    }
+}
+
+/// Error type for [`HostVolume`] operations.
+#[derive(Debug)]
+pub enum HostVolumeError {
+    /// From a host directory.
+    Local(LocalStorageError),
+    /// From a FAT32 image.
+    Image(FatImageError),
+}
+
+impl core::fmt::Display for HostVolumeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Local(e) => write!(f, "{e}"),
+            Self::Image(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for HostVolumeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Local(e) => Some(e),
+            Self::Image(e) => Some(e),
+        }
+    }
+}
+
+/// The emulator's card contents: a host directory or a FAT32 image.
+#[derive(Debug)]
+pub enum HostVolume {
+    /// Files under a directory, via [`LocalFileStorage`].
+    Directory(LocalFileStorage),
+    /// A raw card image, via [`FatImage`].
+    Image(FatImage),
+}
+
+impl HostVolume {
+    /// A directory is served as-is; any other path is opened as an image.
+    pub fn open(path: &Path) -> Result<Self, FatImageError> {
+        if path.is_dir() {
+            Ok(Self::Directory(LocalFileStorage::new(
+                &path.to_string_lossy(),
+            )))
+        } else {
+            FatImage::open(path).map(Self::Image)
+        }
+    }
+
+    /// Open the volume named by `MUSIC_PATH`; `Ok(None)` if it is unset.
+    pub fn from_env() -> Result<Option<Self>, FatImageError> {
+        std::env::var_os("MUSIC_PATH")
+            .map(|p| Self::open(Path::new(&p)))
+            .transpose()
+    }
+}
+
+impl Storage for HostVolume {
+    type Error = HostVolumeError;
+    type File = HostFile;
+
+    async fn open_file(&mut self, path: &str) -> Result<Self::File, Self::Error> {
+        match self {
+            Self::Directory(s) => s
+                .open_file(path)
+                .await
+                .map(HostFile::Local)
+                .map_err(HostVolumeError::Local),
+            Self::Image(s) => s
+                .open_file(path)
+                .await
+                .map(HostFile::Image)
+                .map_err(HostVolumeError::Image),
+        }
+    }
+
+    async fn exists(&mut self, path: &str) -> Result<bool, Self::Error> {
+        match self {
+            Self::Directory(s) => s.exists(path).await.map_err(HostVolumeError::Local),
+            Self::Image(s) => s.exists(path).await.map_err(HostVolumeError::Image),
+        }
+    }
+}
+
+/// An open file on a [`HostVolume`].
+#[derive(Debug)]
+pub enum HostFile {
+    /// From a host directory.
+    Local(LocalFile),
+    /// From a FAT32 image.
+    Image(FatFile),
+}
+
+impl File for HostFile {
+    type Error = HostVolumeError;
+
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        match self {
+            Self::Local(f) => f.read(buf).await.map_err(HostVolumeError::Local),
+            Self::Image(f) => f.read(buf).await.map_err(HostVolumeError::Image),
+        }
+    }
+
+    async fn seek(&mut self, pos: u64) -> Result<u64, Self::Error> {
+        match self {
+            Self::Local(f) => f.seek(pos).await.map_err(HostVolumeError::Local),
+            Self::Image(f) => f.seek(pos).await.map_err(HostVolumeError::Image),
+        }
+    }
+
+    fn size(&self) -> u64 {
+        match self {
+            Self::Local(f) => f.size(),
+            Self::Image(f) => f.size(),
+        }
+    }
+}
+
+impl SimulatedSdCard<HostVolume> {
+    /// The emulator's card: `MUSIC_PATH` with the `SD_PROFILE` timing and
+    /// `SD_FAULTS` faults, paced in real time.
+    ///
+    /// `Ok(None)` if `MUSIC_PATH` is unset.  An unknown `SD_PROFILE` falls
+    /// back to [`SdCardProfile::UNTHROTTLED`].
+    pub fn from_env() -> Result<Option<Self>, FatImageError> {
+        let profile = SdCardProfile::from_env().unwrap_or(SdCardProfile::UNTHROTTLED);
+        Ok(HostVolume::from_env()?.map(|vol| {
+            let mut card = Self::new(vol, profile, Pacing::RealTime);
+            card.set_faults(SdFaults::from_env());
+            card
+        }))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#[allow(clippy::indexing_slicing)] // Tests index with known bounds
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+    use crate::storage_mem::MemoryVolume;
+
+    fn card(profile: SdCardProfile) -> SimulatedSdCard<MemoryVolume> {
+        let mut vol = MemoryVolume::new();
+        vol.write_file("/Music/a.flac", vec![7u8; 8192]).unwrap();
+        SimulatedSdCard::new(vol, profile, Pacing::Virtual)
+    }
+
+    #[test]
+    fn test_read_cost_rounds_up_to_blocks() {
+        let p = SdCardProfile {
+            name: "test",
+            command_latency_us: 100,
+            random_access_us: 1_000,
+            lookup_latency_us: 0,
+            read_bytes_per_sec: 1_024_000,
+        };
+        // One byte still moves a whole 512-byte block: 500 us.
+        assert_eq!(p.read_cost_us(1, true), 600);
+        assert_eq!(p.read_cost_us(512, true), 600);
+        assert_eq!(p.read_cost_us(513, false), 100 + 1_000 + 1_000);
+        assert_eq!(SdCardProfile::UNTHROTTLED.read_cost_us(1 << 20, false), 0);
+    }
+
+    #[test]
+    fn test_profiles_by_name() {
+        assert_eq!(
+            SdCardProfile::by_name("Class10"),
+            Some(SdCardProfile::CLASS_10)
+        );
+        assert_eq!(
+            SdCardProfile::by_name(" uhs1-a1 "),
+            Some(SdCardProfile::UHS_I_A1)
+        );
+        assert_eq!(SdCardProfile::by_name("class2"), None);
+    }
+
+    #[tokio::test]
+    async fn test_sequential_reads_skip_random_access_penalty() {
+        let mut card = card(SdCardProfile::CLASS_10);
+        let mut file = card.open_file("music/A.FLAC").await.unwrap();
+        let mut buf = [0u8; 2048];
+        for _ in 0..4 {
+            assert_eq!(file.read(&mut buf).await.unwrap(), 2048);
+        }
+        let stats = card.stats();
+        assert_eq!(stats.lookups, 1);
+        assert_eq!(stats.reads, 4);
+        assert_eq!(stats.random_reads, 1);
+        assert_eq!(stats.bytes_read, 8192);
+
+        file.seek(0).await.unwrap();
+        file.read(&mut buf).await.unwrap();
+        assert_eq!(card.stats().random_reads, 2);
+    }
+
+    #[tokio::test]
+    async fn test_slower_profile_costs_more() {
+        let mut busy = Vec::new();
+        for profile in [SdCardProfile::UHS_I_A1, SdCardProfile::CLASS_4] {
+            let mut sd = card(profile);
+            let mut file = sd.open_file("/Music/a.flac").await.unwrap();
+            let mut buf = [0u8; 8192];
+            file.read(&mut buf).await.unwrap();
+            assert!(!sd.exists("/Music/b.flac").await.unwrap());
+            busy.push(sd.stats().busy_us);
+        }
+        assert!(busy[0] < busy[1], "{busy:?}");
+
+        let mut sd = card(SdCardProfile::UNTHROTTLED);
+        sd.open_file("/Music/a.flac").await.unwrap();
+        assert_eq!(sd.stats().busy_us, 0);
+    }
+
+    #[tokio::test]
+    async fn test_host_volume_serves_directories() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("track.wav"), b"RIFF").unwrap();
+        let vol = HostVolume::open(tmp.path()).unwrap();
+        let mut card = SimulatedSdCard::new(vol, SdCardProfile::UHS_I_A1, Pacing::Virtual);
+        let mut file = card.open_file("track.wav").await.unwrap();
+        let mut buf = [0u8; 4];
+        assert_eq!(file.read(&mut buf).await.unwrap(), 4);
+        assert_eq!(&buf, b"RIFF");
+        assert!(matches!(
+            card.open_file("missing.wav").await,
+            Err(SdCardError::Inner(HostVolumeError::Local(_)))
+        ));
+    }
+
+    #[test]
+    fn test_fault_spec_parsing() {
+        let faults =
+            SdFaults::parse("read-error=500, spike=100:80000,remove-at=1048576,seed=7").unwrap();
+        assert_eq!(
+            faults,
+            SdFaults {
+                read_error_one_in: Some(500),
+                spike_one_in: Some(100),
+                spike_us: 80_000,
+                remove_after_bytes: Some(1_048_576),
+                seed: 7,
+            }
+        );
+        assert_eq!(SdFaults::parse(""), Some(SdFaults::default()));
+        assert_eq!(SdFaults::parse("spike=100"), None);
+        assert_eq!(SdFaults::parse("eject=1"), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_errors_are_seeded_and_recoverable() {
+        let run = |seed| async move {
+            let mut sd = card(SdCardProfile::UNTHROTTLED);
+            sd.set_faults(SdFaults {
+                read_error_one_in: Some(3),
+                seed,
+                ..SdFaults::default()
+            });
+            let mut file = sd.open_file("/Music/a.flac").await.unwrap();
+            let mut buf = [0u8; 64];
+            let mut outcomes = Vec::new();
+            for _ in 0..30 {
+                outcomes.push(file.read(&mut buf).await.is_ok());
+            }
+            (outcomes, sd.stats())
+        };
+        let (first, stats) = run(42).await;
+        assert_eq!(run(42).await.0, first);
+        assert!(stats.read_errors > 0 && stats.reads > 0, "{stats:?}");
+        assert_eq!(stats.read_errors + stats.reads, 30);
+        // A failed read moves nothing: the successful ones tile the file.
+        assert_eq!(stats.bytes_read, stats.reads * 64);
+    }
+
+    #[tokio::test]
+    async fn test_latency_spikes_add_time() {
+        let mut sd = card(SdCardProfile::UNTHROTTLED);
+        sd.set_faults(SdFaults {
+            spike_one_in: Some(1),
+            spike_us: 50_000,
+            seed: 1,
+            ..SdFaults::default()
+        });
+        let mut file = sd.open_file("/Music/a.flac").await.unwrap();
+        file.read(&mut [0u8; 16]).await.unwrap();
+        assert_eq!(sd.stats().spikes, 1);
+        assert_eq!(sd.stats().busy_us, 50_000);
+    }
+
+    #[tokio::test]
+    async fn test_card_removed_at_byte_mark() {
+        let mut sd = card(SdCardProfile::UNTHROTTLED);
+        sd.set_faults(SdFaults {
+            remove_after_bytes: Some(5_000),
+            ..SdFaults::default()
+        });
+        let mut file = sd.open_file("/Music/a.flac").await.unwrap();
+        let mut buf = [0u8; 4096];
+        assert_eq!(file.read(&mut buf).await.unwrap(), 4096);
+        assert_eq!(file.read(&mut buf).await.unwrap(), 904);
+        assert!(matches!(
+            file.read(&mut buf).await,
+            Err(SdCardError::Removed)
+        ));
+        assert!(sd.is_removed());
+        assert!(matches!(
+            sd.exists("/Music").await,
+            Err(SdCardError::Removed)
+        ));
+
+        // Reinserting needs a fresh open, like a remount on the device.
+        sd.insert();
+        assert!(matches!(file.seek(0).await, Err(SdCardError::Removed)));
+        let mut reopened = sd.open_file("/Music/a.flac").await.unwrap();
+        assert_eq!(reopened.read(&mut buf).await.unwrap(), 4096);
+    }
+}
@@ -0,0 +1,426 @@
+//! Minimal TOML reader for the card-root `soul.toml`.
+//!
+//! Supports the part of TOML a settings file needs and nothing else:
+//!
+//! - `# comments`, blank lines
+//! - `[table]` headers (dotted names allowed, `[[arrays of tables]]` not)
+//! - `key = value` with bare keys
+//! - values: `"strings"` (no escape sequences), integers (sign and `_`
+//!   separators), `true`/`false`, and arrays of those, which may span lines
+//!
+//! Everything is borrowed from the input, so parsing allocates nothing.
+//! [`Parser`] yields one [`Entry`] per key, or a [`SyntaxError`] for a line
+//! it cannot read and then carries on with the next line, so one typo does
+//! not hide the rest of the file.
+//!
+//! # Example
+//!
+//! ```
+//! use platform::toml_subset::{Parser, Value};
+//!
+//! let mut entries = Parser::new("[audio]\nmax_volume = 80 # IEMs\n");
+//! let entry = entries.next().unwrap().unwrap();
+//! assert_eq!((entry.table, entry.key, entry.line), ("audio", "max_volume", 2));
+//! assert_eq!(entry.value, Value::Int(80));
+//! assert!(entries.next().is_none());
+//! ```
+
+/// A parsed value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value<'a> {
+    /// `true` or `false`.
+    Bool(bool),
+    /// Decimal integer.
+    Int(i64),
+    /// String contents without the quotes.
+    Str(&'a str),
+    /// Array; read the items with [`Array::items`].
+    Array(Array<'a>),
+}
+
+impl Value<'_> {
+    /// Name of the value's type, for error messages.
+    pub const fn type_name(&self) -> &'static str {
+        match self {
+            Self::Bool(_) => "boolean",
+            Self::Int(_) => "integer",
+            Self::Str(_) => "string",
+            Self::Array(_) => "array",
+        }
+    }
+}
+
+/// The text between an array's brackets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Array<'a> {
+    raw: &'a str,
+}
+
+impl<'a> Array<'a> {
+    /// Iterate over the items.  An item that fails to parse ends the
+    /// iteration after its error.
+    pub fn items(&self) -> ArrayItems<'a> {
+        ArrayItems { rest: self.raw }
+    }
+}
+
+/// Iterator returned by [`Array::items`].
+#[derive(Debug, Clone)]
+pub struct ArrayItems<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for ArrayItems<'a> {
+    type Item = Result<Value<'a>, &'static str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let text = skip_blank(self.rest);
+        if text.is_empty() {
+            self.rest = text;
+            return None;
+        }
+        self.rest = "";
+        let (value, rest) = match parse_value(text) {
+            Ok(parsed) => parsed,
+            Err(reason) => return Some(Err(reason)),
+        };
+        let rest = skip_blank(rest);
+        match rest.strip_prefix(',') {
+            Some(after) => self.rest = after,
+            None if rest.is_empty() => {}
+            None => return Some(Err("expected `,` between array items")),
+        }
+        Some(Ok(value))
+    }
+}
+
+/// One `key = value` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry<'a> {
+    /// 1-based line of the key.
+    pub line: u32,
+    /// Enclosing `[table]`, or `""` before the first header.
+    pub table: &'a str,
+    /// Key name.
+    pub key: &'a str,
+    /// Value.
+    pub value: Value<'a>,
+}
+
+/// A line the parser could not read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// 1-based line.
+    pub line: u32,
+    /// What was wrong, phrased for the user.
+    pub reason: &'static str,
+}
+
+/// Iterator over the entries of a document.
+#[derive(Debug, Clone)]
+pub struct Parser<'a> {
+    rest: &'a str,
+    line: u32,
+    table: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    /// Parse `text`.
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            rest: text,
+            line: 0,
+            table: "",
+        }
+    }
+
+    fn error(&self, reason: &'static str) -> Result<Entry<'a>, SyntaxError> {
+        Err(SyntaxError {
+            line: self.line,
+            reason,
+        })
+    }
+}
+
+impl<'a> Iterator for Parser<'a> {
+    type Item = Result<Entry<'a>, SyntaxError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+            let (line, after) = split_line(self.rest);
+            self.line = self.line.saturating_add(1);
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                self.rest = after;
+                continue;
+            }
+            if trimmed.starts_with('[') {
+                self.rest = after;
+                match parse_header(trimmed) {
+                    Ok(table) => {
+                        self.table = table;
+                        continue;
+                    }
+                    Err(reason) => return Some(self.error(reason)),
+                }
+            }
+
+            let Some((key, value_text)) = line.split_once('=') else {
+                self.rest = after;
+                return Some(self.error("expected `key = value`"));
+            };
+            let key = key.trim();
+            if key.is_empty() || !key.chars().all(is_bare_key_char) {
+                self.rest = after;
+                return Some(self.error("keys must be bare: letters, digits, `_` and `-`"));
+            }
+            // Re-slice from the document rather than the line, so an array
+            // can run on over the following lines.
+            let offset = line.len().saturating_sub(value_text.len());
+            let from = self.rest.get(offset..).unwrap_or("");
+            let (value, rest) = match parse_value(from) {
+                Ok(parsed) => parsed,
+                Err(reason) => {
+                    self.rest = after;
+                    return Some(self.error(reason));
+                }
+            };
+            let key_line = self.line;
+            let consumed = from
+                .get(..from.len().saturating_sub(rest.len()))
+                .unwrap_or("");
+            let extra_lines = u32::try_from(consumed.matches('\n').count()).unwrap_or(u32::MAX);
+            self.line = self.line.saturating_add(extra_lines);
+            let (tail, after) = split_line(rest);
+            self.rest = after;
+            let tail = tail.trim();
+            if !tail.is_empty() && !tail.starts_with('#') {
+                return Some(self.error("unexpected text after the value"));
+            }
+            return Some(Ok(Entry {
+                line: key_line,
+                table: self.table,
+                key,
+                value,
+            }));
+        }
+    }
+}
+
+fn is_bare_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// First line of `text` (without `\n` or `\r\n`) and the text after it.
+fn split_line(text: &str) -> (&str, &str) {
+    let (line, rest) = text.split_once('\n').unwrap_or((text, ""));
+    (line.strip_suffix('\r').unwrap_or(line), rest)
+}
+
+/// Skip whitespace, newlines and comments.
+fn skip_blank(mut text: &str) -> &str {
+    loop {
+        text = text.trim_start();
+        match text.strip_prefix('#') {
+            Some(comment) => text = comment.split_once('\n').map_or("", |(_, rest)| rest),
+            None => return text,
+        }
+    }
+}
+
+fn parse_header(line: &str) -> Result<&str, &'static str> {
+    if line.starts_with("[[") {
+        return Err("arrays of tables (`[[...]]`) are not supported");
+    }
+    let body = line.strip_prefix('[').unwrap_or(line);
+    let Some((name, tail)) = body.split_once(']') else {
+        return Err("missing `]` after the table name");
+    };
+    let tail = tail.trim();
+    if !tail.is_empty() && !tail.starts_with('#') {
+        return Err("unexpected text after the table name");
+    }
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| is_bare_key_char(c) || c == '.') {
+        return Err("table names must be bare: letters, digits, `_`, `-` and `.`");
+    }
+    Ok(name)
+}
+
+/// Parse the value at the start of `text`; returns it and the text after.
+fn parse_value(text: &str) -> Result<(Value<'_>, &str), &'static str> {
+    let text = text.trim_start_matches([' ', '\t']);
+    if let Some(body) = text.strip_prefix('"') {
+        let (string, rest) = body.split_once('"').ok_or("unterminated string")?;
+        if string.contains('\n') {
+            return Err("unterminated string");
+        }
+        if string.contains('\\') {
+            return Err("escape sequences in strings are not supported");
+        }
+        return Ok((Value::Str(string), rest));
+    }
+    if let Some(body) = text.strip_prefix('[') {
+        return parse_array(body);
+    }
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, '#' | ',' | ']'))
+        .unwrap_or(text.len());
+    let token = text.get(..end).unwrap_or("");
+    let rest = text.get(end..).unwrap_or("");
+    let value = match token {
+        "" => return Err("expected a value"),
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => Value::Int(parse_int(token).ok_or("expected a string, integer, boolean or array")?),
+    };
+    Ok((value, rest))
+}
+
+/// `body` starts just after the `[`.
+fn parse_array(body: &str) -> Result<(Value<'_>, &str), &'static str> {
+    let mut in_string = false;
+    let mut in_comment = false;
+    for (i, c) in body.char_indices() {
+        if in_comment {
+            in_comment = c != '\n';
+        } else if in_string {
+            match c {
+                '"' => in_string = false,
+                '\n' => return Err("unterminated string"),
+                _ => {}
+            }
+        } else {
+            match c {
+                '"' => in_string = true,
+                '#' => in_comment = true,
+                '[' => return Err("nested arrays are not supported"),
+                ']' => {
+                    let raw = body.get(..i).unwrap_or("");
+                    let rest = body.get(i.saturating_add(1)..).unwrap_or("");
+                    return Ok((Value::Array(Array { raw }), rest));
+                }
+                _ => {}
+            }
+        }
+    }
+    Err("missing `]` at the end of the array")
+}
+
+/// Decimal integer with optional sign and `_` between digits.
+fn parse_int(token: &str) -> Option<i64> {
+    let (negative, digits) = match token.as_bytes().first()? {
+        b'-' => (true, token.get(1..)?),
+        b'+' => (false, token.get(1..)?),
+        _ => (false, token),
+    };
+    if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_') {
+        return None;
+    }
+    let mut value: i64 = 0;
+    let mut previous_underscore = false;
+    for c in digits.chars() {
+        if c == '_' {
+            if previous_underscore {
+                return None;
+            }
+            previous_underscore = true;
+            continue;
+        }
+        previous_underscore = false;
+        let digit = i64::from(c.to_digit(10)?);
+        value = value.checked_mul(10)?;
+        value = if negative {
+            value.checked_sub(digit)?
+        } else {
+            value.checked_add(digit)?
+        };
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::panic, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    fn entries(text: &str) -> Vec<Result<Entry<'_>, SyntaxError>> {
+        Parser::new(text).collect()
+    }
+
+    #[test]
+    fn reads_tables_keys_and_scalars() {
+        let text = "top = true\n\n[audio]  # DAC\neq = \"warm\"\nmax_volume = -1_000\r\n";
+        let got: Vec<_> = entries(text).into_iter().map(Result::unwrap).collect();
+        let summary: Vec<_> = got
+            .iter()
+            .map(|e| (e.line, e.table, e.key, e.value))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (1, "", "top", Value::Bool(true)),
+                (4, "audio", "eq", Value::Str("warm")),
+                (5, "audio", "max_volume", Value::Int(-1_000)),
+            ]
+        );
+    }
+
+    #[test]
+    fn arrays_may_span_lines_and_hold_comments() {
+        let text = "exclude = [\n  \"a]b\",  # odd name\n  \"c\",\n]\nnext = 1\n";
+        let got = entries(text);
+        let Ok(Entry {
+            line: 1,
+            value: Value::Array(array),
+            ..
+        }) = got[0]
+        else {
+            panic!("{got:?}");
+        };
+        let items: Vec<_> = array.items().map(Result::unwrap).collect();
+        assert_eq!(items, [Value::Str("a]b"), Value::Str("c")]);
+        assert_eq!(got[1].unwrap().line, 5);
+    }
+
+    #[test]
+    fn errors_name_the_line_and_parsing_continues() {
+        let text =
+            "[[tables]]\nkey\n\"quoted\" = 1\nv = \"a\\n\"\nn = 12abc\nok = 3 extra\nfine = 4\n";
+        let got = entries(text);
+        let errors: Vec<_> = got.iter().filter_map(|r| r.err()).map(|e| e.line).collect();
+        assert_eq!(errors, [1, 2, 3, 4, 5, 6]);
+        assert_eq!(got.last().unwrap().unwrap().key, "fine");
+        assert_eq!(
+            got[3].unwrap_err().reason,
+            "escape sequences in strings are not supported"
+        );
+    }
+
+    #[test]
+    fn array_items_report_bad_separators_and_nesting() {
+        let Ok(Entry {
+            value: Value::Array(array),
+            ..
+        }) = Parser::new("a = [1 2]").next().unwrap()
+        else {
+            panic!();
+        };
+        let items: Vec<_> = array.items().collect();
+        assert_eq!(items, [Err("expected `,` between array items")]);
+        assert!(Parser::new("a = [[1]]").next().unwrap().is_err());
+    }
+
+    #[test]
+    fn integers_reject_malformed_separators_and_overflow() {
+        assert_eq!(parse_int("+42"), Some(42));
+        assert_eq!(parse_int("1_000_000"), Some(1_000_000));
+        assert_eq!(parse_int("-9223372036854775808"), Some(i64::MIN));
+        for bad in ["_1", "1_", "1__0", "-", "9223372036854775808", "0x10"] {
+            assert_eq!(parse_int(bad), None, "{bad}");
+        }
+    }
+}
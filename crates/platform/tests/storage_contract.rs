@@ -0,0 +1,112 @@
+//! Contract tests: every `platform::Storage` implementation must behave the same.
+//!
+//! Each check is written once against the trait and run against both
+//! `LocalFileStorage` (tempdir) and `MemoryVolume`, so the in-memory fixture
+//! cannot drift from the behaviour code sees on a real file system.
+//!
+//! Requires the `std` feature (enabled automatically under `cargo test --workspace`
+//! through the library crate's dev-dependency).
+#![cfg(feature = "std")]
+#![allow(clippy::unwrap_used, clippy::expect_used)]
+#![allow(clippy::indexing_slicing)]
+
+use platform::storage_local::LocalFileStorage;
+use platform::storage_mem::MemoryVolume;
+use platform::{File, Storage};
+use tempfile::TempDir;
+
+const FILES: &[(&str, &[u8])] = &[
+    ("manifest.bin", b"0123456789abcdef"),
+    ("art/00/00000001.raw", &[0xAA; 700]),
+    ("empty.bin", b""),
+];
+
+fn local() -> (TempDir, LocalFileStorage) {
+    let tmp = TempDir::new().unwrap();
+    for (path, data) in FILES {
+        let full = tmp.path().join(path);
+        std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+        std::fs::write(full, data).unwrap();
+    }
+    let storage = LocalFileStorage::new(tmp.path().to_str().unwrap());
+    (tmp, storage)
+}
+
+fn memory() -> MemoryVolume {
+    let mut vol = MemoryVolume::new();
+    for (path, data) in FILES {
+        vol.write_file(path, *data).unwrap();
+    }
+    vol
+}
+
+async fn read_to_end<F: File>(file: &mut F, chunk: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut buf = vec![0u8; chunk];
+    loop {
+        let n = file.read(&mut buf).await.unwrap();
+        if n == 0 {
+            return out;
+        }
+        out.extend_from_slice(&buf[..n]);
+    }
+}
+
+async fn check_full_read<S: Storage>(s: &mut S) {
+    for (path, data) in FILES {
+        let mut file = s.open_file(path).await.unwrap();
+        assert_eq!(file.size(), data.len() as u64, "{path}");
+        assert_eq!(read_to_end(&mut file, 64).await, *data, "{path}");
+    }
+}
+
+async fn check_seek<S: Storage>(s: &mut S) {
+    let mut file = s.open_file("manifest.bin").await.unwrap();
+    assert_eq!(file.seek(10).await.unwrap(), 10);
+    let mut buf = [0u8; 6];
+    let n = file.read(&mut buf).await.unwrap();
+    assert_eq!(&buf[..n], &b"abcdef"[..n]);
+
+    file.seek(0).await.unwrap();
+    assert_eq!(read_to_end(&mut file, 5).await, FILES[0].1);
+}
+
+async fn check_eof_reads_zero<S: Storage>(s: &mut S) {
+    let mut file = s.open_file("empty.bin").await.unwrap();
+    let mut buf = [0u8; 8];
+    assert_eq!(file.read(&mut buf).await.unwrap(), 0);
+}
+
+async fn check_exists<S: Storage>(s: &mut S) {
+    assert!(s.exists("manifest.bin").await.unwrap());
+    assert!(s.exists("art/00/00000001.raw").await.unwrap());
+    assert!(s.exists("art").await.unwrap());
+    assert!(!s.exists("missing.bin").await.unwrap());
+    assert!(s.open_file("missing.bin").await.is_err());
+}
+
+#[tokio::test]
+async fn local_storage_meets_contract() {
+    let (_tmp, mut s) = local();
+    check_full_read(&mut s).await;
+    check_seek(&mut s).await;
+    check_eof_reads_zero(&mut s).await;
+    check_exists(&mut s).await;
+}
+
+#[tokio::test]
+async fn memory_volume_meets_contract() {
+    let mut s = memory();
+    check_full_read(&mut s).await;
+    check_seek(&mut s).await;
+    check_eof_reads_zero(&mut s).await;
+    check_exists(&mut s).await;
+}
+
+#[tokio::test]
+async fn memory_volume_meets_contract_with_short_reads() {
+    let mut s = memory();
+    s.set_max_read(Some(7));
+    check_full_read(&mut s).await;
+    check_seek(&mut s).await;
+}
@@ -0,0 +1,230 @@
+//! Per-frame decode benchmarks for FLAC / MP3 / WAV.
+//!
+//! Run: cargo bench -p playback --features mp3 --bench decode_frame
+//! Collect / compare:  cargo run -p xtask -- bench run, then ... -- bench diff <old>
+//!
+//! Results show, per decoded frame:
+//!   flac/*  — LPC restoration + left-justification of one stereo block
+//!             (the prediction kernel `FlacDecoder` runs, without
+//!             bitstream parsing)
+//!   wav/*   — `WavDecoder` converting one frame of its `data` chunk into
+//!             a left-justified `PcmFrame`
+//!   mp3/*   — `NanoMp3Decoder` over real files (needs `--features mp3`)
+//!
+//! MP3 needs real encoded data, which is not checked in.  Point
+//! `SOUL_BENCH_VECTORS` at a directory of `.mp3` files to include them; each
+//! file is also run once through `frame_timing::time_frame` and its
+//! min / mean / max per-frame time printed, mirroring the on-target
+//! `cycle-count` output.
+
+#![allow(
+    clippy::expect_used,              // benchmark helpers use expect for brevity
+    clippy::cast_possible_wrap,       // bounded u32 → i32 test data
+    clippy::cast_possible_truncation, // nanosecond frame times fit in u32
+    clippy::arithmetic_side_effects,
+    clippy::indexing_slicing,
+    missing_docs,                     // criterion_group! macro generates undocumented items
+)]
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use playback::decoder::{FrameDecoder, PcmFrame};
+use playback::flac_lpc::restore_lpc;
+use playback::wav_decoder::WavDecoder;
+use util::Rng;
+
+/// Largest stereo block that fits one `PcmFrame` (4 096 interleaved samples).
+const FLAC_BLOCK: usize = 2048;
+/// Samples per channel in one MP3 frame; WAV frames are read in the same size.
+const WAV_FRAME: usize = 1152;
+
+fn signed(rng: &mut Rng, bits: u32) -> i32 {
+    let half = 1u32 << (bits - 1);
+    rng.below(2 * half) as i32 - half as i32
+}
+
+// ---------------------------------------------------------------------------
+// FLAC
+// ---------------------------------------------------------------------------
+
+struct FlacCase {
+    name: &'static str,
+    bps: u32,
+    coeffs: Vec<i32>,
+    shift: u32,
+    /// Warm-up samples then residuals, per channel.
+    residuals: [Vec<i32>; 2],
+}
+
+fn flac_case(rng: &mut Rng, name: &'static str, bps: u32, order: usize) -> FlacCase {
+    let coeffs = (0..order).map(|_| signed(rng, 12)).collect();
+    let channel = |rng: &mut Rng| -> Vec<i32> {
+        (0..FLAC_BLOCK)
+            .map(|i| signed(rng, if i < order { bps } else { 8 }))
+            .collect()
+    };
+    FlacCase {
+        name,
+        bps,
+        coeffs,
+        shift: 10,
+        residuals: [channel(rng), channel(rng)],
+    }
+}
+
+/// Restore both channels and interleave them left-justified into `out`.
+fn flac_frame(case: &FlacCase, work: &mut [Vec<i32>; 2], out: &mut PcmFrame) {
+    for (dst, src) in work.iter_mut().zip(&case.residuals) {
+        dst.copy_from_slice(src);
+        restore_lpc(dst, &case.coeffs, case.shift, case.bps).expect("restore");
+    }
+    let justify = 32 - case.bps;
+    for (i, pair) in out.samples.chunks_exact_mut(2).enumerate() {
+        pair[0] = work[0][i] << justify;
+        pair[1] = work[1][i] << justify;
+    }
+    out.len = FLAC_BLOCK;
+}
+
+fn bench_flac(c: &mut Criterion) {
+    let mut rng = Rng::seed_from_u64(0xF1AC);
+    let cases = [
+        flac_case(&mut rng, "stereo_16bit_order8", 16, 8),
+        flac_case(&mut rng, "stereo_24bit_order12", 24, 12),
+    ];
+    let mut group = c.benchmark_group("flac");
+    let mut out = PcmFrame::default();
+    for case in &cases {
+        let mut work = [vec![0; FLAC_BLOCK], vec![0; FLAC_BLOCK]];
+        group.bench_with_input(BenchmarkId::new("frame", case.name), case, |b, case| {
+            b.iter(|| flac_frame(case, &mut work, black_box(&mut out)));
+        });
+    }
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+// WAV
+// ---------------------------------------------------------------------------
+
+/// RIFF header, `fmt ` chunk and `data` chunk header for stereo PCM.
+fn wav_header(bytes_per_sample: u16, data_len: usize) -> Vec<u8> {
+    let block_align = 2 * bytes_per_sample;
+    let mut out = b"RIFF".to_vec();
+    out.extend_from_slice(&(36 + data_len as u32).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&44_100u32.to_le_bytes());
+    out.extend_from_slice(&(44_100 * u32::from(block_align)).to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&(8 * bytes_per_sample).to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&(data_len as u32).to_le_bytes());
+    out
+}
+
+/// A decoder that has consumed `header`, so its next call converts samples.
+fn wav_at_data(header: &[u8]) -> WavDecoder {
+    let mut decoder = WavDecoder::new();
+    let mut out = PcmFrame::default();
+    let mut pos = 0;
+    while pos < header.len() {
+        pos += decoder
+            .decode_frame(&header[pos..], &mut out)
+            .expect("header");
+    }
+    decoder
+}
+
+fn bench_wav(c: &mut Criterion) {
+    let mut rng = Rng::seed_from_u64(0x0AF);
+    let mut group = c.benchmark_group("wav");
+    let mut out = PcmFrame::default();
+    for (name, bytes) in [("stereo_16bit", 2u16), ("stereo_24bit", 3)] {
+        let mut payload = vec![0u8; WAV_FRAME * 2 * usize::from(bytes)];
+        rng.fill_bytes(&mut payload);
+        let header = wav_header(bytes, payload.len());
+        group.bench_with_input(BenchmarkId::new("frame", name), &payload, |b, payload| {
+            b.iter_batched_ref(
+                || wav_at_data(&header),
+                |decoder| decoder.decode_frame(black_box(payload), &mut out),
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+// MP3
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "mp3")]
+fn bench_mp3(c: &mut Criterion) {
+    use playback::frame_timing::{time_frame, FrameStats};
+    use playback::mp3_decoder::NanoMp3Decoder;
+    use std::time::Instant;
+
+    /// Decode a whole file, recording every frame into `stats`.
+    fn decode_all(data: &[u8], stats: &mut FrameStats, out: &mut PcmFrame) -> u32 {
+        let epoch = Instant::now();
+        let clock = || epoch.elapsed().as_nanos() as u32;
+        let mut decoder = NanoMp3Decoder::new();
+        let mut pos = 0;
+        while let Some(rest) = data.get(pos..) {
+            match time_frame(&mut decoder, rest, out, stats, clock) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => pos += n,
+            }
+        }
+        stats.frames()
+    }
+
+    let Ok(dir) = std::env::var("SOUL_BENCH_VECTORS") else {
+        eprintln!("mp3: SOUL_BENCH_VECTORS not set, skipping");
+        return;
+    };
+    let mut group = c.benchmark_group("mp3");
+    let mut out = PcmFrame::default();
+    let mut paths: Vec<_> = std::fs::read_dir(&dir)
+        .expect("SOUL_BENCH_VECTORS")
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("mp3")))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let data = std::fs::read(&path).expect("read vector");
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        let mut stats = FrameStats::new();
+        let frames = decode_all(&data, &mut stats, &mut out);
+        eprintln!(
+            "mp3/{name}: frames={frames} mean={}ns min={}ns max={}ns",
+            stats.mean(),
+            stats.min().unwrap_or(0),
+            stats.max()
+        );
+        if frames == 0 {
+            continue;
+        }
+
+        group.throughput(criterion::Throughput::Elements(u64::from(frames)));
+        group.bench_with_input(BenchmarkId::new("file", &name), &data, |b, data| {
+            b.iter(|| decode_all(black_box(data), &mut FrameStats::new(), &mut out));
+        });
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "mp3"))]
+fn bench_mp3(_c: &mut Criterion) {
+    eprintln!("mp3: built without the `mp3` feature, skipping");
+}
+
+criterion_group!(benches, bench_flac, bench_wav, bench_mp3);
+criterion_main!(benches);
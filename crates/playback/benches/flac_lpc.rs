@@ -0,0 +1,100 @@
+//! Criterion benchmarks for FLAC LPC restoration.
+//!
+//! Run: cargo bench -p playback --bench flac_lpc
+//!      cargo bench -p playback --features flac-dsp --bench flac_lpc
+//!
+//! Results show, per 4096-sample block (one libFLAC default frame):
+//!   reference/*  — straightforward i64 loop
+//!   fast/*       — order-specialised kernels with accumulator selection
+//!
+//! Cases: 16-bit order 8 (CD, `-5` preset), 24-bit order 12 (96 kHz hi-res,
+//! `-8` preset), 24-bit order 32 (format maximum, generic kernel).
+
+#![allow(
+    clippy::expect_used,              // benchmark helpers use expect for brevity
+    clippy::cast_possible_wrap,       // bounded u32 → i32 test data
+    clippy::arithmetic_side_effects,
+    missing_docs,                     // criterion_group! macro generates undocumented items
+)]
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use playback::flac_lpc::{restore_lpc, restore_lpc_reference};
+use util::Rng;
+
+const BLOCK: usize = 4096;
+
+struct Case {
+    name: &'static str,
+    bps: u32,
+    order: usize,
+    precision: u32,
+    shift: u32,
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "16bit_order8",
+        bps: 16,
+        order: 8,
+        precision: 12,
+        shift: 10,
+    },
+    Case {
+        name: "24bit_order12",
+        bps: 24,
+        order: 12,
+        precision: 15,
+        shift: 14,
+    },
+    Case {
+        name: "24bit_order32",
+        bps: 24,
+        order: 32,
+        precision: 15,
+        shift: 14,
+    },
+];
+
+fn signed(rng: &mut Rng, bits: u32) -> i32 {
+    let half = 1u32 << (bits - 1);
+    rng.below(2 * half) as i32 - half as i32
+}
+
+/// Residual-like input: small values after `order` full-scale warm-up samples.
+fn input(rng: &mut Rng, case: &Case) -> (Vec<i32>, Vec<i32>) {
+    let coeffs = (0..case.order)
+        .map(|_| signed(rng, case.precision))
+        .collect();
+    let samples = (0..BLOCK)
+        .map(|i| signed(rng, if i < case.order { case.bps } else { 8 }))
+        .collect();
+    (coeffs, samples)
+}
+
+fn bench_lpc(c: &mut Criterion) {
+    let mut rng = Rng::seed_from_u64(0xF1AC);
+    let mut group = c.benchmark_group("flac_lpc");
+    group.throughput(Throughput::Elements(BLOCK as u64));
+
+    for case in CASES {
+        let (coeffs, samples) = input(&mut rng, case);
+        let mut work = samples.clone();
+
+        group.bench_with_input(BenchmarkId::new("reference", case.name), case, |b, case| {
+            b.iter(|| {
+                work.copy_from_slice(&samples);
+                restore_lpc_reference(black_box(&mut work), &coeffs, case.shift).expect("restore");
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("fast", case.name), case, |b, case| {
+            b.iter(|| {
+                work.copy_from_slice(&samples);
+                restore_lpc(black_box(&mut work), &coeffs, case.shift, case.bps).expect("restore");
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_lpc);
+criterion_main!(benches);
@@ -0,0 +1,217 @@
+//! AAC-LC frame decoder for MP4/M4A tracks.
+//!
+//! Implements the `FrameDecoder` trait for raw AAC access units as stored in
+//! MP4 `mdat` (no ADTS headers); [`crate::mp4::Mp4Audio`] finds them and
+//! supplies the `AudioSpecificConfig` the decoder is built from.
+//!
+//! # Feature flag
+//!
+//! The real decode path is gated behind the `aac` feature and uses
+//! `symphonia-codec-aac` (pure Rust, MPL-2.0).  Symphonia needs `std`, so
+//! the feature implies `std` and currently serves the desktop emulator and
+//! host tests; without it every frame returns
+//! [`DecodeError::UnsupportedFormat`], as the MP3 decoder does without
+//! `mp3`.  A `no_std` AAC-LC backend for the target slots in behind the
+//! same type.
+
+use crate::decoder::{AudioFormat, Codec, DecodeError, FrameDecoder, PcmFrame};
+use crate::mp4::{find_box, AudioSpecificConfig, Mp4Audio, Mp4Error, AOT_AAC_LC};
+
+/// Samples per channel in one AAC-LC access unit.
+pub const AAC_FRAME_LEN: usize = 1024;
+
+// ─── Implementation ───────────────────────────────────────────────────────────
+
+/// AAC-LC decoder for one MP4 audio track.
+///
+/// Each call to [`FrameDecoder::decode_frame`] takes exactly one access unit
+/// (one `Mp4Audio::sample`) and consumes all of it.
+pub struct AacDecoder {
+    sample_rate: u32,
+    channels: u8,
+    #[cfg(feature = "aac")]
+    inner: symphonia_codec_aac::AacDecoder,
+    #[cfg(feature = "aac")]
+    scratch: Option<symphonia_core::audio::SampleBuffer<i32>>,
+}
+
+impl AacDecoder {
+    /// Create a decoder for the track described by `config`.
+    ///
+    /// # Errors
+    ///
+    /// [`DecodeError::UnsupportedFormat`] when the track is not AAC-LC (e.g.
+    /// HE-AAC or ALAC in an MP4 container) or the config is rejected.
+    pub fn new(config: &AudioSpecificConfig) -> Result<Self, DecodeError> {
+        if config.object_type != AOT_AAC_LC {
+            return Err(DecodeError::UnsupportedFormat);
+        }
+
+        #[cfg(feature = "aac")]
+        let inner = {
+            use symphonia_core::codecs::{
+                CodecParameters, Decoder, DecoderOptions, CODEC_TYPE_AAC,
+            };
+
+            let mut params = CodecParameters::new();
+            params
+                .for_codec(CODEC_TYPE_AAC)
+                .with_sample_rate(config.sample_rate)
+                .with_extra_data(config.raw.as_slice().into());
+            symphonia_codec_aac::AacDecoder::try_new(&params, &DecoderOptions::default())
+                .map_err(|_| DecodeError::UnsupportedFormat)?
+        };
+
+        Ok(Self {
+            sample_rate: config.sample_rate,
+            channels: config.channels,
+            #[cfg(feature = "aac")]
+            inner,
+            #[cfg(feature = "aac")]
+            scratch: None,
+        })
+    }
+}
+
+impl FrameDecoder for AacDecoder {
+    type Error = DecodeError;
+
+    /// Decode one raw AAC access unit from `input` into `output`.
+    ///
+    /// Returns `input.len()` on success: access units are delimited by the
+    /// MP4 sample table, not by the bitstream.  Samples are left-justified
+    /// in the 32-bit `PcmFrame.samples`, interleaved.
+    fn decode_frame(&mut self, input: &[u8], output: &mut PcmFrame) -> Result<usize, Self::Error> {
+        if input.is_empty() {
+            return Err(DecodeError::EndOfStream);
+        }
+
+        #[cfg(feature = "aac")]
+        {
+            use symphonia_core::audio::{AudioBufferRef, SampleBuffer};
+            use symphonia_core::codecs::Decoder;
+            use symphonia_core::errors::Error;
+            use symphonia_core::formats::Packet;
+
+            let packet = Packet::new_from_slice(0, 0, AAC_FRAME_LEN as u64, input);
+            let decoded: AudioBufferRef<'_> = self.inner.decode(&packet).map_err(|e| match e {
+                Error::Unsupported(_) => DecodeError::UnsupportedFormat,
+                _ => DecodeError::InvalidData,
+            })?;
+            let spec = *decoded.spec();
+            let frames = decoded.frames();
+            let channels = spec.channels.count();
+
+            let needed = decoded.capacity().saturating_mul(channels);
+            if !matches!(&self.scratch, Some(b) if b.capacity() >= needed) {
+                self.scratch = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+            }
+            let Some(scratch) = self.scratch.as_mut() else {
+                return Err(DecodeError::BufferTooSmall);
+            };
+            scratch.copy_interleaved_ref(decoded);
+
+            let samples = scratch.samples();
+            let n = samples.len().min(output.samples.len());
+            if let (Some(dst), Some(src)) = (output.samples.get_mut(..n), samples.get(..n)) {
+                dst.copy_from_slice(src);
+            }
+            self.sample_rate = spec.rate;
+            self.channels = u8::try_from(channels).unwrap_or(u8::MAX);
+            output.len = frames.min(n.checked_div(channels).unwrap_or(0));
+            output.sample_rate = self.sample_rate;
+            output.channels = self.channels;
+            Ok(input.len())
+        }
+
+        #[cfg(not(feature = "aac"))]
+        {
+            let _ = output;
+            Err(DecodeError::UnsupportedFormat)
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u8 {
+        self.channels
+    }
+}
+
+// ─── Registry entry ───────────────────────────────────────────────────────────
+
+/// AAC entry in [`crate::decoder::CODECS`]; decodes only with the `aac`
+/// feature.
+///
+/// The library claims any `ftyp` brand; [`AacDecoder::new`] rejects MP4
+/// files whose audio track is another codec (e.g. ALAC).
+pub const CODEC: Codec = Codec {
+    format: AudioFormat::Aac,
+    open: if cfg!(feature = "aac") {
+        Some(open)
+    } else {
+        None
+    },
+};
+
+/// Build the decoder from the `moov` box, which `head` must contain
+/// (fast-start files carry it before `mdat`).
+fn open(
+    head: &[u8],
+    run: &mut dyn FnMut(&mut dyn FrameDecoder<Error = DecodeError>),
+) -> Result<(), DecodeError> {
+    let moov = find_box(head, *b"moov").ok_or(DecodeError::InvalidData)?;
+    let track = Mp4Audio::parse(moov).map_err(|err| match err {
+        Mp4Error::UnsupportedCodec => DecodeError::UnsupportedFormat,
+        Mp4Error::NoAudioTrack | Mp4Error::Malformed => DecodeError::InvalidData,
+    })?;
+    run(&mut AacDecoder::new(track.config())?);
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)] // Tests use expect() for readable assertions
+mod tests {
+    use super::*;
+
+    /// AAC-LC, 44.1 kHz, stereo.
+    const ASC_LC_44K_STEREO: [u8; 2] = [0x12, 0x10];
+
+    #[test]
+    fn test_aac_decoder_implements_frame_decoder() {
+        fn assert_impl<T: crate::decoder::FrameDecoder>() {}
+        assert_impl::<AacDecoder>();
+    }
+
+    #[test]
+    fn test_aac_decoder_takes_stream_parameters_from_config() {
+        let config = AudioSpecificConfig::parse(&ASC_LC_44K_STEREO).expect("valid ASC");
+        let decoder = AacDecoder::new(&config).expect("AAC-LC is supported");
+        assert_eq!(decoder.sample_rate(), 44_100);
+        assert_eq!(decoder.channels(), 2);
+    }
+
+    #[test]
+    fn test_aac_decoder_rejects_he_aac() {
+        // Object type 5 (SBR), 44.1 kHz, stereo.
+        let config = AudioSpecificConfig::parse(&[0x2A, 0x10]).expect("valid ASC");
+        assert_eq!(config.object_type, 5);
+        assert!(matches!(
+            AacDecoder::new(&config),
+            Err(DecodeError::UnsupportedFormat)
+        ));
+    }
+
+    #[test]
+    fn test_aac_decode_empty_returns_error() {
+        let config = AudioSpecificConfig::parse(&ASC_LC_44K_STEREO).expect("valid ASC");
+        let mut decoder = AacDecoder::new(&config).expect("AAC-LC is supported");
+        let mut output = PcmFrame::default();
+        assert_eq!(
+            decoder.decode_frame(&[], &mut output),
+            Err(DecodeError::EndOfStream)
+        );
+    }
+}
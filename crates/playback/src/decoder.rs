@@ -1,10 +1,14 @@
-//! Audio decoder abstractions — format detection, PCM frame types, codec traits.
+//! Audio decoder abstractions — format registry, PCM frame types, codec traits.
 //!
-//! No concrete decoder crates are linked here yet; this module defines the
-//! data types and traits that future FLAC/MP3/WAV decoder integrations must
-//! implement.  The constraint of `no_std` + fixed-size stack arrays is
-//! intentional: DMA buffers on the STM32H743 live in AXI SRAM and must never
-//! touch the heap.
+//! Every decodable format has one [`Codec`] entry in [`CODECS`] pairing it
+//! with its decoder constructor.  Extensions and magic bytes live in the
+//! library's format table ([`library::metadata::FORMATS`]), which the
+//! scanner and [`detect_format`] read.  Codec modules define their own
+//! entries; a backend behind a cargo feature only contributes its
+//! constructor when the feature is on.
+//!
+//! The constraint of `no_std` + fixed-size stack arrays is intentional: DMA
+//! buffers on the STM32H743 live in AXI SRAM and must never touch the heap.
 //!
 //! # Decoder crate selection rationale (research 2025-02)
 //!
@@ -13,16 +17,38 @@
 //!   to multiple ARM-specific UB issues.  `symphonia` requires `std` and is too
 //!   large for internal flash.
 //!
-//! * **FLAC**: `libfoxenflac` via C FFI (tiny, heap-free, state-machine based,
-//!   GPL-2.0 C99 — build.rs integration pending) or `claxon` (pure-Rust but
-//!   requires `std`).  `libfoxenflac` wins for embedded: 8.8 KB WASM, no alloc.
+//! * **FLAC**: decoded in-crate by [`crate::flac_decoder`] on top of the
+//!   [`crate::flac_lpc`] kernels.  `libfoxenflac` (GPL-2.0 C via FFI) and
+//!   `claxon` (pure Rust but requires `std`) were the alternatives.
+//!
+//! * **WAV**: [`crate::wav_decoder`] parses PCM chunks directly — no
+//!   third-party crate needed.
+//!
+//! * **AAC**: `symphonia-codec-aac` (pure Rust, MPL-2.0) behind the `aac`
+//!   feature; it needs `std`, so it serves the emulator until a `no_std`
+//!   AAC-LC backend lands.  Without the feature neither the registry nor the
+//!   library's format table lists AAC, so the device never indexes `.m4a`
+//!   files it cannot open.  The MP4 container is demuxed in [`crate::mp4`].
 //!
-//! * **WAV**: Parse PCM chunks directly — no third-party crate needed.
+//! * **Opus**: libopus (BSD-3-Clause, fixed-point capable) through the `opus`
+//!   crate behind the `opus` feature, also `std`-only for now, and likewise
+//!   left out of the registry and format table without it.  Ogg pages
+//!   are parsed in [`crate::ogg`]; pre-skip and output gain are applied in
+//!   integer arithmetic by [`crate::opus_decoder`].
 
-/// A decoded PCM frame — up to 4 096 samples per channel on the stack.
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+/// A decoded PCM frame — up to 4 096 interleaved samples on the stack.
 ///
-/// MP3 decodes at most 1 152 samples/channel; FLAC block size ≤ 4 096.
-/// The array is always fully allocated; `len` indicates the valid suffix.
+/// MP3 decodes at most 1 152 samples/channel; FLAC blocks that do not fit
+/// (4 096-sample stereo) are emitted in windows, see [`FrameDecoder`].
+/// The array is always fully allocated; `len` indicates the valid prefix.
 /// Samples are left-justified 32-bit signed integers (MSBs carry the audio
 /// data regardless of the original bit depth).
 #[derive(Clone)]
@@ -83,32 +109,60 @@ pub enum DecodeError {
     BufferTooSmall,
 }
 
-/// Audio container / codec format.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum AudioFormat {
-    /// Free Lossless Audio Codec
-    Flac,
-    /// MPEG Layer 3
-    Mp3,
-    /// Waveform Audio File Format (PCM or IEEE-float payload)
-    Wav,
+pub use library::metadata::detect_format;
+pub use library::track::AudioFormat;
+
+// ─── Format registry ──────────────────────────────────────────────────────────
+
+/// Builds a codec's decoder from the start of the file and lends it to
+/// `run`, which drives the decode loop.
+///
+/// The decoder lives on the constructor's stack while `run` executes, so
+/// callers handle every codec through `dyn FrameDecoder` without an
+/// allocation or a decoder enum.
+///
+/// # Errors
+///
+/// [`DecodeError::InvalidData`] when `head` lacks the setup data the codec
+/// needs (MP4 `moov`, Ogg `OpusHead` page);
+/// [`DecodeError::UnsupportedFormat`] when the stream uses a variant the
+/// decoder does not handle.
+pub type OpenDecoder = fn(
+    head: &[u8],
+    run: &mut dyn FnMut(&mut dyn FrameDecoder<Error = DecodeError>),
+) -> Result<(), DecodeError>;
+
+/// One format's decoder in the registry.
+///
+/// Each codec module defines its own entry (e.g.
+/// [`mp3_decoder::CODEC`](crate::mp3_decoder::CODEC)); [`CODECS`] lists
+/// them.  Adding a format means adding an [`AudioFormat`] variant and
+/// [`FormatInfo`](library::metadata::FormatInfo) in the library, then one
+/// entry here.
+#[derive(Debug, Clone, Copy)]
+pub struct Codec {
+    /// Format this entry decodes.
+    pub format: AudioFormat,
+    /// Decoder constructor; `None` when this build has no backend for the
+    /// format (its cargo feature is off, or no decoder exists yet).
+    pub open: Option<OpenDecoder>,
 }
 
-impl AudioFormat {
-    /// Detect the audio format from a lowercase file extension.
-    ///
-    /// Returns `None` when the extension is not recognised.
-    ///
-    /// The match is case-sensitive; callers should lower-case the extension
-    /// before calling this function.
-    pub fn from_extension(ext: &str) -> Option<Self> {
-        match ext {
-            "flac" => Some(Self::Flac),
-            "mp3" => Some(Self::Mp3),
-            "wav" => Some(Self::Wav),
-            _ => None,
-        }
-    }
+/// Every decodable format, in the library's detection order.
+pub const CODECS: &[Codec] = &[
+    crate::flac_decoder::CODEC,
+    crate::mp3_decoder::CODEC,
+    crate::wav_decoder::CODEC,
+    #[cfg(feature = "aac")]
+    crate::aac_decoder::CODEC,
+    #[cfg(feature = "opus")]
+    crate::opus_decoder::CODEC,
+];
+
+/// The registry entry for `format`, or `None` when this build cannot
+/// decode it.
+pub fn codec_for(format: AudioFormat) -> Option<&'static Codec> {
+    CODECS.iter().find(|codec| codec.format == format)
 }
 
 /// Trait for stateful, frame-by-frame audio decoders.
@@ -117,6 +171,13 @@ impl AudioFormat {
 /// one decoded PCM frame to `output`, returning the number of input bytes
 /// consumed.  Implementations must be `no_std`-safe and must not allocate.
 ///
+/// Two results carry no complete frame:
+///
+/// - `Ok(n)` with `output.len == 0`: `n` bytes of headers or metadata were
+///   consumed.
+/// - `Ok(0)` with `output.len > 0`: the frame did not fit in `output` and
+///   continues; pass the same input again for the rest.
+///
 /// [`decode_frame`]: FrameDecoder::decode_frame
 pub trait FrameDecoder {
     /// Error type produced by this decoder.
@@ -8,6 +8,61 @@
 //! call decoders.  Those concerns are handled by higher-level tasks that read
 //! `engine.state()` and issue commands via Embassy channels.  This separation
 //! makes the state machine trivially testable on the host.
+//!
+//! # Events
+//!
+//! Every transition queues a [`PlaybackEvent`]: `StateChanged` from
+//! [`play`](PlaybackEngine::play) / [`pause`](PlaybackEngine::pause) /
+//! [`stop`](PlaybackEngine::stop), `TrackStarted` from
+//! [`load_track`](PlaybackEngine::load_track), `PositionTick` from seeks and
+//! every [`POSITION_TICK_MS`] of [`advance_ms`](PlaybackEngine::advance_ms),
+//! `BufferUnderrun` from [`underrun`](PlaybackEngine::underrun), `EqChanged`
+//! from [`set_eq_preset`](PlaybackEngine::set_eq_preset) and `Fault` from
+//! [`handle_fault`](PlaybackEngine::handle_fault).  The engine still does no
+//! I/O: the task driving it hands the queue to the bus with
+//! [`publish_events`](PlaybackEngine::publish_events) after each call, or
+//! drains it with [`next_event`](PlaybackEngine::next_event).  The queue
+//! holds [`EVENT_CAPACITY`] events and drops the oldest, like the bus.
+//!
+//! Faults raised while playing go through [`PlaybackEngine::handle_fault`],
+//! which applies the [`RecoveryPolicy`] and updates the state to match the
+//! decision (see [`crate::fault`]).
+//!
+//! # Chapters
+//!
+//! Audiobooks carry chapter marks (`library::Chapters`).  The chapter
+//! methods take the marks as a sorted slice of start times in milliseconds
+//! (`Chapters::starts_ms`), so the engine stays independent of the library
+//! crate.  Before the first mark counts as no chapter.
+//!
+//! # Playback speed
+//!
+//! The listener picks one speed ([`PlaybackEngine::set_speed`]); whether
+//! the playing track follows it depends on its [`ContentKind`] and the
+//! [`SpeedScope`] (spoken word only by default).  The playback task feeds
+//! [`PlaybackEngine::effective_speed`] to the
+//! [`TimeStretch`](crate::time_stretch::TimeStretch) stage.  Positions and
+//! durations stay in recording time; [`PlaybackEngine::remaining_wall_ms`]
+//! converts the rest of the track to listening time.
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use heapless::Deque;
+use platform::{ContentKind, PlaybackSpeed, SpeedScope};
+
+use crate::events::{PlaybackEvent, PlaybackEventPublisher, EVENT_CAPACITY};
+use crate::fault::{PlaybackFault, RecoveryAction, RecoveryPolicy, RecoveryTracker, SinkError};
+
+/// Within this long after a chapter starts, "previous chapter" goes to the
+/// chapter before; later, it restarts the current chapter (like a CD
+/// player's previous-track button).
+pub const PREVIOUS_CHAPTER_GRACE_MS: u64 = 3_000;
+
+/// How far [`PlaybackEngine::resume_at`] backs up from the saved position,
+/// so the listener catches the sentence they left off in.
+pub const RESUME_REWIND_MS: u64 = 5_000;
+
+/// Playback time between [`PlaybackEvent::PositionTick`]s (4 Hz).
+pub const POSITION_TICK_MS: u64 = 250;
 
 /// Current playback state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -41,6 +96,14 @@ pub struct PlaybackEngine {
     state: PlaybackState,
     position_ms: u64,
     duration_ms: u64,
+    recovery: RecoveryTracker,
+    speed: PlaybackSpeed,
+    speed_scope: SpeedScope,
+    content: ContentKind,
+    eq_preset: u8,
+    /// Position reported by the last `PositionTick`.
+    ticked_ms: u64,
+    events: Deque<PlaybackEvent, EVENT_CAPACITY>,
 }
 
 impl PlaybackEngine {
@@ -51,11 +114,7 @@ impl PlaybackEngine {
     ///
     /// [`with_duration`]: PlaybackEngine::with_duration
     pub fn new() -> Self {
-        Self {
-            state: PlaybackState::Stopped,
-            position_ms: 0,
-            duration_ms: u64::MAX,
-        }
+        Self::with_duration(u64::MAX)
     }
 
     /// Create a new engine with a known track duration in milliseconds.
@@ -66,9 +125,34 @@ impl PlaybackEngine {
             state: PlaybackState::Stopped,
             position_ms: 0,
             duration_ms,
+            recovery: RecoveryTracker::new(RecoveryPolicy::DEFAULT),
+            speed: PlaybackSpeed::NORMAL,
+            speed_scope: SpeedScope::SPOKEN_WORD,
+            content: ContentKind::Music,
+            eq_preset: 0,
+            ticked_ms: 0,
+            events: Deque::new(),
         }
     }
 
+    /// Start a new track from the beginning and queue `TrackStarted`.
+    ///
+    /// `duration_ms` is `u64::MAX` when unknown, as for [`new`]; the event
+    /// then carries 0.  The state is kept, so a playing engine plays the
+    /// new track.
+    ///
+    /// [`new`]: PlaybackEngine::new
+    pub fn load_track(&mut self, track_id: u32, duration_ms: u64) {
+        self.duration_ms = duration_ms;
+        self.position_ms = 0;
+        self.ticked_ms = 0;
+        self.recovery.on_track_change();
+        self.emit(PlaybackEvent::TrackStarted {
+            track_id,
+            duration_ms: u32::try_from(duration_ms).unwrap_or(0),
+        });
+    }
+
     /// Start or resume playback.
     ///
     /// Transitions:
@@ -83,7 +167,7 @@ impl PlaybackEngine {
         match self.state {
             PlaybackState::Playing => Err(PlaybackError::AlreadyPlaying),
             PlaybackState::Stopped | PlaybackState::Paused => {
-                self.state = PlaybackState::Playing;
+                self.set_state(PlaybackState::Playing);
                 Ok(())
             }
         }
@@ -103,7 +187,7 @@ impl PlaybackEngine {
         match self.state {
             PlaybackState::Stopped => Err(PlaybackError::NotPlaying),
             PlaybackState::Playing | PlaybackState::Paused => {
-                self.state = PlaybackState::Paused;
+                self.set_state(PlaybackState::Paused);
                 Ok(())
             }
         }
@@ -117,8 +201,9 @@ impl PlaybackEngine {
     ///
     /// Always returns `Ok(())`.
     pub fn stop(&mut self) -> Result<(), PlaybackError> {
-        self.state = PlaybackState::Stopped;
+        self.set_state(PlaybackState::Stopped);
         self.position_ms = 0;
+        self.ticked_ms = 0;
         Ok(())
     }
 
@@ -128,9 +213,29 @@ impl PlaybackEngine {
     /// known (constructed with [`new`]), clamping is effectively disabled
     /// because `duration_ms` is initialised to `u64::MAX`.
     ///
+    /// Queues a `PositionTick` for the new position.
+    ///
     /// [`new`]: PlaybackEngine::new
     pub fn seek_ms(&mut self, ms: u64) {
         self.position_ms = ms.min(self.duration_ms);
+        self.tick();
+    }
+
+    /// Account for `ms` of audio reaching the DAC while playing.
+    ///
+    /// The position stops at the track duration.  Queues a `PositionTick`
+    /// each time the position has moved [`POSITION_TICK_MS`] past the last
+    /// one, and at the end of the track.  Does nothing unless playing.
+    pub fn advance_ms(&mut self, ms: u64) {
+        if self.state != PlaybackState::Playing {
+            return;
+        }
+        self.position_ms = self.position_ms.saturating_add(ms).min(self.duration_ms);
+        if self.position_ms.saturating_sub(self.ticked_ms) >= POSITION_TICK_MS
+            || (self.position_ms == self.duration_ms && self.ticked_ms != self.duration_ms)
+        {
+            self.tick();
+        }
     }
 
     /// Return the current playback position in milliseconds.
@@ -138,17 +243,223 @@ impl PlaybackEngine {
         self.position_ms
     }
 
+    /// Index of the chapter containing the current position, or `None`
+    /// before the first chapter mark.
+    pub fn chapter_index(&self, chapter_starts: &[u32]) -> Option<usize> {
+        chapter_at(chapter_starts, self.position_ms)
+    }
+
+    /// Seek to the start of the next chapter and return its index.
+    ///
+    /// Returns `None` without seeking in the last chapter (or without
+    /// chapters); the caller then advances the queue as for a track end.
+    pub fn next_chapter(&mut self, chapter_starts: &[u32]) -> Option<usize> {
+        let next = self
+            .chapter_index(chapter_starts)
+            .map_or(0, |i| i.saturating_add(1));
+        let start = chapter_starts.get(next)?;
+        self.seek_ms(u64::from(*start));
+        Some(next)
+    }
+
+    /// Seek to the start of the current chapter, or of the previous one
+    /// within [`PREVIOUS_CHAPTER_GRACE_MS`] of the current chapter's start.
+    ///
+    /// Returns the chapter seeked to, or `None` (position unchanged) when
+    /// there are no chapters.  Before the first mark, seeks to the start.
+    pub fn previous_chapter(&mut self, chapter_starts: &[u32]) -> Option<usize> {
+        let Some(current) = self.chapter_index(chapter_starts) else {
+            chapter_starts.first()?;
+            self.seek_ms(0);
+            return None;
+        };
+        let start = chapter_starts.get(current).map_or(0, |&s| u64::from(s));
+        let target = if self.position_ms.saturating_sub(start) < PREVIOUS_CHAPTER_GRACE_MS {
+            current.saturating_sub(1)
+        } else {
+            current
+        };
+        self.seek_ms(chapter_starts.get(target).map_or(0, |&s| u64::from(s)));
+        Some(target)
+    }
+
+    /// Resume a saved position: back up [`RESUME_REWIND_MS`], but never
+    /// past the start of the chapter the position is in.
+    ///
+    /// Without chapters this is a plain rewind (clamped at zero).
+    pub fn resume_at(&mut self, saved_ms: u64, chapter_starts: &[u32]) {
+        let chapter_start = chapter_at(chapter_starts, saved_ms)
+            .and_then(|i| chapter_starts.get(i))
+            .map_or(0, |&s| u64::from(s));
+        self.seek_ms(saved_ms.saturating_sub(RESUME_REWIND_MS).max(chapter_start));
+    }
+
     /// Return the current [`PlaybackState`].
     pub fn state(&self) -> PlaybackState {
         self.state
     }
 
+    /// Apply the recovery policy to `fault` and return the decision.
+    ///
+    /// - `Stop` → the engine is stopped (position reset).
+    /// - `SkipTrack` → position resets to zero; the state is kept so the
+    ///   next track in the queue starts playing.
+    /// - `Continue` / `Retry` → no state change.
+    ///
+    /// Queues [`PlaybackEvent::Fault`] when the fault is
+    /// [reportable](PlaybackFault::is_reportable), then `StateChanged` if
+    /// the engine stopped.  For `SkipTrack` the caller advances the queue
+    /// and calls [`load_track`](Self::load_track).
+    pub fn handle_fault(&mut self, fault: PlaybackFault) -> RecoveryAction {
+        let action = self.recovery.on_fault(fault);
+        if fault.is_reportable(action) {
+            self.emit(PlaybackEvent::Fault { fault, action });
+        }
+        match action {
+            RecoveryAction::Stop => {
+                self.set_state(PlaybackState::Stopped);
+                self.position_ms = 0;
+            }
+            RecoveryAction::SkipTrack => self.position_ms = 0,
+            RecoveryAction::Continue | RecoveryAction::Retry { .. } => {}
+        }
+        action
+    }
+
+    /// The DMA feed ran dry and `missed_frames` of silence went out.
+    ///
+    /// Queues `BufferUnderrun` and applies the recovery policy to the
+    /// underrun as [`handle_fault`](Self::handle_fault) does.
+    pub fn underrun(&mut self, missed_frames: u32) -> RecoveryAction {
+        self.emit(PlaybackEvent::BufferUnderrun { missed_frames });
+        self.handle_fault(PlaybackFault::Sink(SinkError::Underrun))
+    }
+
+    /// Report a frame that decoded and reached the sink, clearing the
+    /// consecutive-failure counters.
+    pub fn frame_ok(&mut self) {
+        self.recovery.on_frame_ok();
+    }
+
+    /// Replace the recovery policy (counters are reset).
+    pub fn set_recovery_policy(&mut self, policy: RecoveryPolicy) {
+        self.recovery = RecoveryTracker::new(policy);
+    }
+
+    /// Fault bookkeeping for the current track.
+    pub fn recovery(&self) -> &RecoveryTracker {
+        &self.recovery
+    }
+
+    /// Set the listener's chosen speed.
+    pub fn set_speed(&mut self, speed: PlaybackSpeed) {
+        self.speed = speed;
+    }
+
+    /// The listener's chosen speed, whether or not the current track
+    /// follows it.
+    pub fn speed(&self) -> PlaybackSpeed {
+        self.speed
+    }
+
+    /// Set which content kinds follow the chosen speed.
+    pub fn set_speed_scope(&mut self, scope: SpeedScope) {
+        self.speed_scope = scope;
+    }
+
+    /// Content kinds that follow the chosen speed.
+    pub fn speed_scope(&self) -> SpeedScope {
+        self.speed_scope
+    }
+
+    /// Set the kind of the loaded track.
+    pub fn set_content(&mut self, content: ContentKind) {
+        self.content = content;
+    }
+
+    /// Kind of the loaded track.
+    pub fn content(&self) -> ContentKind {
+        self.content
+    }
+
+    /// Speed the loaded track actually plays at.
+    pub fn effective_speed(&self) -> PlaybackSpeed {
+        self.speed_scope.speed_for(self.content, self.speed)
+    }
+
+    /// Listening time left in the track at the effective speed, or
+    /// `u64::MAX` when the duration is unknown.
+    pub fn remaining_wall_ms(&self) -> u64 {
+        if self.duration_ms == u64::MAX {
+            return u64::MAX;
+        }
+        self.effective_speed()
+            .wall_ms(self.duration_ms.saturating_sub(self.position_ms))
+    }
+
     /// Return the track duration in milliseconds.
     ///
     /// Returns `u64::MAX` when no duration has been set.
     pub fn duration_ms(&self) -> u64 {
         self.duration_ms
     }
+
+    /// Select EQ preset `preset` (0 = flat), queueing `EqChanged` if it
+    /// differs from the current one.
+    pub fn set_eq_preset(&mut self, preset: u8) {
+        if preset != self.eq_preset {
+            self.eq_preset = preset;
+            self.emit(PlaybackEvent::EqChanged { preset });
+        }
+    }
+
+    /// The active EQ preset.
+    pub fn eq_preset(&self) -> u8 {
+        self.eq_preset
+    }
+
+    /// Take the oldest queued event.
+    pub fn next_event(&mut self) -> Option<PlaybackEvent> {
+        self.events.pop_front()
+    }
+
+    /// Publish every queued event on `publisher`'s bus, oldest first.
+    pub fn publish_events<M: RawMutex>(&mut self, publisher: &PlaybackEventPublisher<'_, M>) {
+        while let Some(event) = self.events.pop_front() {
+            publisher.publish(event);
+        }
+    }
+
+    fn set_state(&mut self, state: PlaybackState) {
+        if state != self.state {
+            self.state = state;
+            self.emit(PlaybackEvent::StateChanged(state));
+        }
+    }
+
+    /// Queue a `PositionTick` for the current position.
+    fn tick(&mut self) {
+        self.ticked_ms = self.position_ms;
+        self.emit(PlaybackEvent::PositionTick {
+            position_ms: u32::try_from(self.position_ms).unwrap_or(u32::MAX),
+        });
+    }
+
+    /// Queue `event`, dropping the oldest when the queue is full.
+    fn emit(&mut self, event: PlaybackEvent) {
+        if self.events.is_full() {
+            self.events.pop_front();
+        }
+        // Cannot fail: there is room after the pop above.
+        let _ = self.events.push_back(event);
+    }
+}
+
+/// Chapter containing `position_ms`.
+fn chapter_at(chapter_starts: &[u32], position_ms: u64) -> Option<usize> {
+    chapter_starts
+        .partition_point(|&s| u64::from(s) <= position_ms)
+        .checked_sub(1)
 }
 
 impl Default for PlaybackEngine {
@@ -0,0 +1,172 @@
+//! Playback event bus — bounded pub/sub between the engine and its observers.
+//!
+//! The bus is built for a single publisher, the task that drives
+//! `PlaybackEngine`, and a few subscribers (UI, Bluetooth AVRCP bridge,
+//! stats logger) that react to events instead of polling
+//! `PlaybackEngine::state()` / `position_ms()` on their own timers.  The
+//! engine queues an event for each transition and that task hands them over
+//! with `PlaybackEngine::publish_events`.  `firmware_ui::playback_events`
+//! drives the Now Playing screen from them and `firmware::companion` maps
+//! them for the companion link.
+//!
+//! # Design
+//!
+//! The bus is a thin wrapper around [`embassy_sync::pubsub::PubSubChannel`]:
+//! fixed capacity, no heap, usable from a `static`.  The raw mutex is a type
+//! parameter so firmware can pick `CriticalSectionRawMutex` while host tests
+//! use `NoopRawMutex`.
+//!
+//! Publishing never blocks: [`PlaybackEventPublisher::publish`] uses
+//! `publish_immediate`, which overwrites the oldest queued event when a
+//! subscriber falls behind.  The audio path must never wait on a slow e-ink
+//! refresh.  Lagging subscribers learn how many events they missed via
+//! [`PlaybackEventSubscriber::lagged`] and should resynchronise from the
+//! next `TrackStarted` / `StateChanged` event.
+//!
+//! # Example
+//!
+//! ```ignore
+//! static BUS: PlaybackEventBus<CriticalSectionRawMutex> = PlaybackEventBus::new();
+//!
+//! let publisher = PlaybackEventPublisher::new(&BUS);
+//! let mut ui_events = PlaybackEventSubscriber::new(&BUS).unwrap();
+//!
+//! publisher.publish(PlaybackEvent::TrackStarted { track_id: 7, duration_ms: 215_000 });
+//! let event = ui_events.next_event().await;
+//! ```
+
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use embassy_sync::pubsub::{ImmediatePublisher, PubSubChannel, Subscriber, WaitResult};
+
+use crate::engine::PlaybackState;
+use crate::fault::{PlaybackFault, RecoveryAction};
+
+/// Number of events buffered per bus before the oldest is overwritten.
+///
+/// Sized for one second of 4 Hz position ticks plus a burst of state events.
+pub const EVENT_CAPACITY: usize = 8;
+
+/// Maximum concurrent subscribers: UI, Bluetooth AVRCP, stats logger, and
+/// one spare for diagnostics.
+pub const MAX_SUBSCRIBERS: usize = 4;
+
+/// Maximum concurrent awaiting publishers.  The playback task uses an
+/// [`ImmediatePublisher`], which does not count against this limit.
+pub const MAX_PUBLISHERS: usize = 1;
+
+/// An event published by the playback task.
+///
+/// All variants are `Copy` and at most 12 bytes so that the bus stays small
+/// enough to live in DTCM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackEvent {
+    /// A new track began decoding.
+    TrackStarted {
+        /// Library track ID (`library.idx` key).
+        track_id: u32,
+        /// Track duration in milliseconds (0 when unknown).
+        duration_ms: u32,
+    },
+    /// The engine changed between stopped / playing / paused.
+    StateChanged(PlaybackState),
+    /// Periodic position update while playing.
+    PositionTick {
+        /// Position within the current track, in milliseconds.
+        position_ms: u32,
+    },
+    /// The DMA feed ran dry; the DAC output silence for this many frames.
+    BufferUnderrun {
+        /// Number of stereo frames replaced with silence.
+        missed_frames: u32,
+    },
+    /// The active EQ preset changed.
+    EqChanged {
+        /// Preset index (0 = flat).
+        preset: u8,
+    },
+    /// A fault occurred and the engine applied a recovery policy.
+    ///
+    /// Not published for `Continue` on transient faults (underruns already
+    /// have [`BufferUnderrun`](Self::BufferUnderrun)) or for the normal end
+    /// of a track — see [`PlaybackFault::is_reportable`].
+    Fault {
+        /// What went wrong.
+        fault: PlaybackFault,
+        /// What the engine did about it.
+        action: RecoveryAction,
+    },
+}
+
+/// Bounded pub/sub channel carrying [`PlaybackEvent`]s.
+pub type PlaybackEventBus<M> =
+    PubSubChannel<M, PlaybackEvent, EVENT_CAPACITY, MAX_SUBSCRIBERS, MAX_PUBLISHERS>;
+
+/// Error returned when the bus already has [`MAX_SUBSCRIBERS`] subscribers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManySubscribers;
+
+/// Non-blocking publisher handle for the playback task.
+pub struct PlaybackEventPublisher<'a, M: RawMutex> {
+    inner:
+        ImmediatePublisher<'a, M, PlaybackEvent, EVENT_CAPACITY, MAX_SUBSCRIBERS, MAX_PUBLISHERS>,
+}
+
+impl<'a, M: RawMutex> PlaybackEventPublisher<'a, M> {
+    /// Create a publisher for `bus`.
+    pub fn new(bus: &'a PlaybackEventBus<M>) -> Self {
+        Self {
+            inner: bus.immediate_publisher(),
+        }
+    }
+
+    /// Publish `event` to every subscriber without waiting.
+    ///
+    /// If the bus is full the oldest event is dropped.
+    pub fn publish(&self, event: PlaybackEvent) {
+        self.inner.publish_immediate(event);
+    }
+}
+
+/// Subscriber handle that tracks how many events were lost to lag.
+pub struct PlaybackEventSubscriber<'a, M: RawMutex> {
+    inner: Subscriber<'a, M, PlaybackEvent, EVENT_CAPACITY, MAX_SUBSCRIBERS, MAX_PUBLISHERS>,
+    lagged: u64,
+}
+
+impl<'a, M: RawMutex> PlaybackEventSubscriber<'a, M> {
+    /// Subscribe to `bus`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(TooManySubscribers)` when all subscriber slots are taken.
+    pub fn new(bus: &'a PlaybackEventBus<M>) -> Result<Self, TooManySubscribers> {
+        let inner = bus.subscriber().map_err(|_| TooManySubscribers)?;
+        Ok(Self { inner, lagged: 0 })
+    }
+
+    /// Wait for the next event, skipping over any lag notifications.
+    pub async fn next_event(&mut self) -> PlaybackEvent {
+        loop {
+            match self.inner.next_message().await {
+                WaitResult::Message(event) => return event,
+                WaitResult::Lagged(n) => self.lagged = self.lagged.saturating_add(n),
+            }
+        }
+    }
+
+    /// Return the next queued event, if any, without waiting.
+    pub fn try_next_event(&mut self) -> Option<PlaybackEvent> {
+        loop {
+            match self.inner.try_next_message()? {
+                WaitResult::Message(event) => return Some(event),
+                WaitResult::Lagged(n) => self.lagged = self.lagged.saturating_add(n),
+            }
+        }
+    }
+
+    /// Total number of events this subscriber has missed because it fell
+    /// more than [`EVENT_CAPACITY`] events behind.
+    pub fn lagged(&self) -> u64 {
+        self.lagged
+    }
+}
@@ -0,0 +1,262 @@
+//! Playback fault taxonomy and recovery policy.
+//!
+//! Everything that can go wrong while a track is playing is one of three
+//! things — the bitstream ([`DecodeError`]), the medium ([`StorageError`]) or
+//! the output ([`SinkError`]) — unified as [`PlaybackFault`].  Each fault
+//! knows its [`Recoverability`], and [`RecoveryTracker`] turns a stream of
+//! faults into a [`RecoveryAction`] for the playback task:
+//!
+//! | Fault                                   | Action                                  |
+//! |-----------------------------------------|-----------------------------------------|
+//! | corrupt frame (`InvalidData`)           | drop the frame; skip the track after [`RecoveryPolicy::max_decode_errors`] in a row |
+//! | unsupported format, file not found      | skip the track                          |
+//! | SD timeout / I/O error                  | retry with linear backoff; skip after [`RecoveryPolicy::max_storage_retries`] |
+//! | DMA underrun                            | continue (already reported as `BufferUnderrun`) |
+//! | card removed, DAC lost, buffer too small| stop                                    |
+//! | end of stream                           | skip the track (normal end; not reported) |
+//!
+//! Any successfully decoded frame resets the consecutive-failure counters, so
+//! a scratched rip with one bad frame per minute plays through.
+//!
+//! The playback task publishes every non-trivial decision as
+//! [`PlaybackEvent::Fault`](crate::events::PlaybackEvent::Fault) so the UI
+//! can say *why* a track was skipped or playback stopped.
+//!
+//! Platform storage and codec drivers keep their own error types; the task
+//! maps them into [`StorageError`] / [`SinkError`] at the boundary, since only
+//! it knows whether an SDMMC CRC error means "retry" or "card gone".
+
+use crate::decoder::DecodeError;
+
+/// Storage failures as seen by the playback task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageError {
+    /// The track file no longer exists (library out of date).
+    NotFound,
+    /// The card did not answer in time; usually succeeds on retry.
+    Timeout,
+    /// Read failed (CRC, FAT inconsistency); may succeed on retry.
+    Io,
+    /// The SD card was removed.
+    Removed,
+}
+
+/// Audio output failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkError {
+    /// The DMA feed ran dry; silence was played.
+    Underrun,
+    /// The DAC stopped acknowledging I²C or lost its master clock.
+    DeviceLost,
+    /// The DAC rejected the stream parameters (rate / bit depth).
+    Unsupported,
+}
+
+/// Any fault raised while a track is playing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackFault {
+    /// Bitstream error from the frame decoder.
+    Decode(DecodeError),
+    /// Error reading the track from storage.
+    Storage(StorageError),
+    /// Error writing to the audio output.
+    Sink(SinkError),
+}
+
+/// How bad a fault is, independent of how often it has happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Recoverability {
+    /// Likely to succeed if the operation is retried.
+    Transient,
+    /// This track cannot be played, but the next one may be fine.
+    Track,
+    /// Playback cannot continue until the user intervenes.
+    Fatal,
+}
+
+impl PlaybackFault {
+    /// Classify this fault.
+    pub const fn recoverability(self) -> Recoverability {
+        match self {
+            Self::Decode(DecodeError::InvalidData)
+            | Self::Storage(StorageError::Timeout | StorageError::Io)
+            | Self::Sink(SinkError::Underrun) => Recoverability::Transient,
+            Self::Decode(DecodeError::EndOfStream | DecodeError::UnsupportedFormat)
+            | Self::Storage(StorageError::NotFound)
+            | Self::Sink(SinkError::Unsupported) => Recoverability::Track,
+            // BufferTooSmall is a sizing bug: every track would fail the same way.
+            Self::Decode(DecodeError::BufferTooSmall)
+            | Self::Storage(StorageError::Removed)
+            | Self::Sink(SinkError::DeviceLost) => Recoverability::Fatal,
+        }
+    }
+
+    /// Whether the UI should hear about `action` taken for this fault.
+    ///
+    /// The normal end of a track and frames quietly dropped or underruns
+    /// (already reported as `BufferUnderrun`) are not worth an event.
+    pub const fn is_reportable(self, action: RecoveryAction) -> bool {
+        !matches!(self, Self::Decode(DecodeError::EndOfStream))
+            && !matches!(action, RecoveryAction::Continue)
+    }
+}
+
+impl From<DecodeError> for PlaybackFault {
+    fn from(e: DecodeError) -> Self {
+        Self::Decode(e)
+    }
+}
+
+impl From<StorageError> for PlaybackFault {
+    fn from(e: StorageError) -> Self {
+        Self::Storage(e)
+    }
+}
+
+impl From<SinkError> for PlaybackFault {
+    fn from(e: SinkError) -> Self {
+        Self::Sink(e)
+    }
+}
+
+/// What the playback task should do about a fault.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Carry on with the next frame (the failed one is dropped).
+    Continue,
+    /// Retry the failed storage operation after `delay_ms`.
+    Retry {
+        /// 1-based retry number.
+        attempt: u8,
+        /// Backoff before retrying, in milliseconds.
+        delay_ms: u16,
+    },
+    /// Abandon this track and advance the queue.
+    SkipTrack,
+    /// Stop playback.
+    Stop,
+}
+
+/// Thresholds for [`RecoveryTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecoveryPolicy {
+    /// Consecutive corrupt frames tolerated before the track is skipped.
+    pub max_decode_errors: u8,
+    /// Storage retries per failed read before the track is skipped.
+    pub max_storage_retries: u8,
+    /// Backoff added per retry attempt, in milliseconds.
+    pub retry_step_ms: u16,
+}
+
+impl RecoveryPolicy {
+    /// Defaults: 8 bad frames (≈ 0.2 s of MP3), 3 retries at 50/100/150 ms.
+    pub const DEFAULT: Self = Self {
+        max_decode_errors: 8,
+        max_storage_retries: 3,
+        retry_step_ms: 50,
+    };
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Applies a [`RecoveryPolicy`] to the faults of the current track.
+#[derive(Debug, Clone)]
+pub struct RecoveryTracker {
+    policy: RecoveryPolicy,
+    decode_errors: u8,
+    storage_retries: u8,
+    underruns: u32,
+}
+
+impl RecoveryTracker {
+    /// Create a tracker with `policy`.
+    pub const fn new(policy: RecoveryPolicy) -> Self {
+        Self {
+            policy,
+            decode_errors: 0,
+            storage_retries: 0,
+            underruns: 0,
+        }
+    }
+
+    /// The active policy.
+    pub const fn policy(&self) -> RecoveryPolicy {
+        self.policy
+    }
+
+    /// Decide what to do about `fault`.
+    pub fn on_fault(&mut self, fault: PlaybackFault) -> RecoveryAction {
+        let action = match fault.recoverability() {
+            Recoverability::Fatal => RecoveryAction::Stop,
+            Recoverability::Track => RecoveryAction::SkipTrack,
+            Recoverability::Transient => self.transient(fault),
+        };
+        if matches!(action, RecoveryAction::SkipTrack | RecoveryAction::Stop) {
+            self.on_track_change();
+        }
+        action
+    }
+
+    fn transient(&mut self, fault: PlaybackFault) -> RecoveryAction {
+        match fault {
+            PlaybackFault::Storage(_) => {
+                if self.storage_retries >= self.policy.max_storage_retries {
+                    return RecoveryAction::SkipTrack;
+                }
+                self.storage_retries = self.storage_retries.saturating_add(1);
+                RecoveryAction::Retry {
+                    attempt: self.storage_retries,
+                    delay_ms: self
+                        .policy
+                        .retry_step_ms
+                        .saturating_mul(u16::from(self.storage_retries)),
+                }
+            }
+            PlaybackFault::Sink(_) => {
+                self.underruns = self.underruns.saturating_add(1);
+                RecoveryAction::Continue
+            }
+            PlaybackFault::Decode(_) => {
+                self.decode_errors = self.decode_errors.saturating_add(1);
+                if self.decode_errors >= self.policy.max_decode_errors {
+                    RecoveryAction::SkipTrack
+                } else {
+                    RecoveryAction::Continue
+                }
+            }
+        }
+    }
+
+    /// A frame decoded and reached the sink: clear consecutive-failure counts.
+    pub fn on_frame_ok(&mut self) {
+        self.decode_errors = 0;
+        self.storage_retries = 0;
+    }
+
+    /// A new track started (or the current one was abandoned).
+    pub fn on_track_change(&mut self) {
+        self.decode_errors = 0;
+        self.storage_retries = 0;
+        self.underruns = 0;
+    }
+
+    /// Consecutive corrupt frames so far.
+    pub const fn decode_errors(&self) -> u8 {
+        self.decode_errors
+    }
+
+    /// Underruns during the current track.
+    pub const fn underruns(&self) -> u32 {
+        self.underruns
+    }
+}
+
+impl Default for RecoveryTracker {
+    fn default() -> Self {
+        Self::new(RecoveryPolicy::DEFAULT)
+    }
+}
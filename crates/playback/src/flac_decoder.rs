@@ -0,0 +1,949 @@
+//! FLAC frame decoder — frame headers, the four subframe types, Rice
+//! residuals and stereo decorrelation, with no allocation.
+//!
+//! [`FlacDecoder`] takes the stream from the `fLaC` marker: the marker and
+//! each metadata block are consumed by calls that produce no samples (a
+//! block longer than the input, such as embedded cover art, is skipped
+//! across calls), then each call decodes one frame.  Bytes that do not
+//! start a frame are skipped up to the next frame sync.  Prediction runs
+//! through the [`crate::flac_lpc`] kernels.
+//!
+//! # Large blocks
+//!
+//! A [`PcmFrame`] holds 4 096 interleaved samples: half of a 4 096-sample
+//! stereo block, the usual block size of CD rips.  Such a block is emitted
+//! in windows — every call but the last fills `output` and returns
+//! `Ok(0)`, and the caller passes the same input again.  Each channel keeps
+//! its read position and prediction history between windows, so nothing
+//! is decoded twice; only the first channel's residuals are skimmed once
+//! more to find where the second channel starts.  The frame CRC is checked
+//! when the last window completes.
+//!
+//! Supported: mono and stereo, 4–24 bits per sample (the streaming
+//! subset), every block size and sample rate.  Streams with more channels
+//! or deeper samples return [`DecodeError::UnsupportedFormat`].
+
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+use crate::decoder::{AudioFormat, Codec, DecodeError, FrameDecoder, PcmFrame};
+use crate::flac_lpc::{restore_fixed, restore_lpc, MAX_FIXED_ORDER, MAX_LPC_ORDER};
+
+/// Most channels the decoder outputs.
+pub const MAX_CHANNELS: usize = 2;
+
+/// Deepest supported sample.
+pub const MAX_BITS_PER_SAMPLE: u32 = 24;
+
+/// Length of the STREAMINFO block body.
+pub const STREAMINFO_LEN: usize = 34;
+
+/// Residuals restored per [`crate::flac_lpc`] call.  With the prediction
+/// history in front, the scratch buffer stays at 512 bytes of stack.
+const CHUNK: usize = 96;
+
+/// Prediction history plus one chunk of residuals.
+const SCRATCH: usize = MAX_LPC_ORDER + CHUNK;
+
+/// Stream parameters from the STREAMINFO metadata block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamInfo {
+    /// Largest block in the stream, in samples per channel.
+    pub max_block_size: u16,
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Channel count (1–8).
+    pub channels: u8,
+    /// Bits per sample (4–32).
+    pub bits_per_sample: u8,
+    /// Samples per channel in the stream; 0 when unknown.
+    pub total_samples: u64,
+}
+
+impl StreamInfo {
+    /// Parse the STREAMINFO block at the start of a FLAC file.
+    ///
+    /// Returns `None` unless `head` starts with `fLaC` followed by a
+    /// complete STREAMINFO block, which the format requires first.
+    pub fn parse(head: &[u8]) -> Option<Self> {
+        if head.get(..4) != Some(b"fLaC".as_slice()) {
+            return None;
+        }
+        // Block type 0 (bit 7 is the last-block flag), 34-byte body.
+        let &[kind, 0, 0, 34] = head.get(4..8)? else {
+            return None;
+        };
+        if kind & 0x7F != 0 {
+            return None;
+        }
+        Self::from_body(head.get(8..)?)
+    }
+
+    /// Parse a STREAMINFO block body.
+    fn from_body(body: &[u8]) -> Option<Self> {
+        let max_block_size = u16::from_be_bytes(body.get(2..4)?.try_into().ok()?);
+        // 20 bits rate, 3 bits channels - 1, 5 bits depth - 1, 36 bits total.
+        let packed = u64::from_be_bytes(body.get(10..18)?.try_into().ok()?);
+        Some(Self {
+            max_block_size,
+            sample_rate: u32::try_from(packed >> 44).ok()?,
+            channels: u8::try_from((packed >> 41) & 0x7).ok()?.wrapping_add(1),
+            bits_per_sample: u8::try_from((packed >> 36) & 0x1F).ok()?.wrapping_add(1),
+            total_samples: packed & 0xF_FFFF_FFFF,
+        })
+    }
+}
+
+// ─── Decoder ──────────────────────────────────────────────────────────────────
+
+/// Streaming FLAC decoder.
+pub struct FlacDecoder {
+    info: Option<StreamInfo>,
+    sample_rate: u32,
+    channels: u8,
+    /// Between the `fLaC` marker and the last metadata block.
+    in_metadata: bool,
+    /// Bytes left of a metadata block longer than the last input.
+    skip: usize,
+    /// Frame with windows still to emit.
+    frame: Option<Frame>,
+}
+
+impl FlacDecoder {
+    /// Create a decoder that learns the stream parameters from the
+    /// STREAMINFO block in the stream.
+    pub const fn new() -> Self {
+        Self {
+            info: None,
+            sample_rate: 0,
+            channels: 0,
+            in_metadata: false,
+            skip: 0,
+            frame: None,
+        }
+    }
+
+    /// Create a decoder for a stream whose STREAMINFO was already parsed,
+    /// so [`sample_rate`](FrameDecoder::sample_rate) and
+    /// [`channels`](FrameDecoder::channels) are known before the first
+    /// frame.
+    pub const fn with_stream_info(info: StreamInfo) -> Self {
+        Self {
+            info: Some(info),
+            sample_rate: info.sample_rate,
+            channels: info.channels,
+            in_metadata: false,
+            skip: 0,
+            frame: None,
+        }
+    }
+
+    /// Consume the `fLaC` marker or one metadata block.
+    ///
+    /// Returns `Some(consumed)` while in the metadata section.
+    fn metadata(&mut self, input: &[u8]) -> Result<Option<usize>, DecodeError> {
+        if self.skip > 0 {
+            let n = self.skip.min(input.len());
+            self.skip = self.skip.wrapping_sub(n);
+            return Ok(Some(n));
+        }
+        if input.starts_with(b"fLaC") {
+            self.in_metadata = true;
+            return Ok(Some(4));
+        }
+        if !self.in_metadata {
+            return Ok(None);
+        }
+        let [kind, len @ ..] = input
+            .get(..4)
+            .and_then(|h| <[u8; 4]>::try_from(h).ok())
+            .ok_or(DecodeError::EndOfStream)?;
+        let [high, mid, low] = len.map(usize::from);
+        let block = (high << 16 | mid << 8 | low).saturating_add(4);
+        if kind & 0x80 != 0 {
+            self.in_metadata = false;
+        }
+        if kind & 0x7F == 0 {
+            if let Some(info) = input.get(4..).and_then(StreamInfo::from_body) {
+                self.info = Some(info);
+                self.sample_rate = info.sample_rate;
+                self.channels = info.channels;
+            }
+        }
+        let n = block.min(input.len());
+        self.skip = block.wrapping_sub(n);
+        Ok(Some(n))
+    }
+
+    /// Parse the frame header at the start of `input`.
+    fn header(&self, input: &[u8]) -> Result<Header, DecodeError> {
+        let mut bits = Bits::at(input, 0);
+        // 14-bit sync code and a zero reserved bit.
+        if bits.read(15)? != 0x7FFC {
+            return Err(DecodeError::InvalidData);
+        }
+        let _variable_block_size = bits.read(1)?;
+        let block_code = bits.read(4)?;
+        let rate_code = bits.read(4)?;
+        let channel_code = bits.read(4)?;
+        let depth_code = bits.read(3)?;
+        if bits.read(1)? != 0 {
+            return Err(DecodeError::InvalidData);
+        }
+
+        // Frame or sample number, UTF-8 style: the lead byte's leading ones
+        // count the bytes, each continuation byte starts with `10`.
+        let lead = u8::try_from(bits.read(8)?).map_err(|_| DecodeError::InvalidData)?;
+        let continuation = match lead.leading_ones() {
+            0 => 0,
+            n @ 2..=7 => n.wrapping_sub(1),
+            _ => return Err(DecodeError::InvalidData),
+        };
+        for _ in 0..continuation {
+            if bits.read(2)? != 0b10 {
+                return Err(DecodeError::InvalidData);
+            }
+            bits.skip(6)?;
+        }
+
+        let block = match block_code {
+            1 => 192,
+            2..=5 => 576 << block_code.wrapping_sub(2),
+            6 => to_usize(bits.read(8)?).wrapping_add(1),
+            7 => to_usize(bits.read(16)?).wrapping_add(1),
+            8..=15 => 256 << block_code.wrapping_sub(8),
+            _ => return Err(DecodeError::InvalidData),
+        };
+        let sample_rate = match rate_code {
+            0 => self.info.map_or(0, |info| info.sample_rate),
+            1 => 88_200,
+            2 => 176_400,
+            3 => 192_000,
+            4 => 8_000,
+            5 => 16_000,
+            6 => 22_050,
+            7 => 24_000,
+            8 => 32_000,
+            9 => 44_100,
+            10 => 48_000,
+            11 => 96_000,
+            12 => bits.read(8)?.saturating_mul(1000),
+            13 => bits.read(16)?,
+            14 => bits.read(16)?.saturating_mul(10),
+            _ => return Err(DecodeError::InvalidData),
+        };
+        let bits_per_sample = match depth_code {
+            0 => self.info.map_or(0, |info| u32::from(info.bits_per_sample)),
+            1 => 8,
+            2 => 12,
+            4 => 16,
+            5 => 20,
+            6 => 24,
+            7 => 32,
+            _ => return Err(DecodeError::InvalidData),
+        };
+        let (stereo, channels) = match channel_code {
+            0..=7 => (Stereo::Independent, to_usize(channel_code).wrapping_add(1)),
+            8 => (Stereo::LeftSide, 2),
+            9 => (Stereo::RightSide, 2),
+            10 => (Stereo::MidSide, 2),
+            _ => return Err(DecodeError::InvalidData),
+        };
+        if sample_rate == 0 || bits_per_sample < 4 {
+            // Deferred to a STREAMINFO this decoder never saw.
+            return Err(DecodeError::InvalidData);
+        }
+        if channels > MAX_CHANNELS || bits_per_sample > MAX_BITS_PER_SAMPLE {
+            return Err(DecodeError::UnsupportedFormat);
+        }
+
+        let len = bits.pos / 8;
+        let crc = bits.read(8)?;
+        if u32::from(crc8(input.get(..len).unwrap_or_default())) != crc {
+            return Err(DecodeError::InvalidData);
+        }
+        Ok(Header {
+            block,
+            sample_rate,
+            bits_per_sample,
+            stereo,
+            channels,
+            len: len.wrapping_add(1),
+        })
+    }
+
+    /// Decode the next window of `frame` into `output`.
+    ///
+    /// Returns the frame length once its last window is out, else 0.
+    fn window(
+        frame: &mut Frame,
+        input: &[u8],
+        output: &mut PcmFrame,
+    ) -> Result<usize, DecodeError> {
+        let header = frame.header;
+        let capacity = output
+            .samples
+            .len()
+            .checked_div(header.channels)
+            .unwrap_or(0);
+        let window = header.block.saturating_sub(frame.emitted).min(capacity);
+        let out = output
+            .samples
+            .get_mut(..window.saturating_mul(header.channels))
+            .ok_or(DecodeError::BufferTooSmall)?;
+        let mut scratch = [0i32; SCRATCH];
+
+        for channel in 0..header.channels {
+            if frame.subframes.get(channel).is_some_and(Option::is_none) {
+                let start = match channel.checked_sub(1).and_then(|p| frame.subframes.get(p)) {
+                    Some(Some(previous)) => previous.end_bit(input)?,
+                    _ => header.len.saturating_mul(8),
+                };
+                let subframe =
+                    Subframe::parse(input, start, header.block, header.channel_bits(channel))?;
+                if let Some(slot) = frame.subframes.get_mut(channel) {
+                    *slot = Some(subframe);
+                }
+            }
+            let Some(Some(subframe)) = frame.subframes.get_mut(channel) else {
+                return Err(DecodeError::InvalidData);
+            };
+            let slots = out.get_mut(channel..).unwrap_or_default();
+            subframe.decode(input, window, slots, header.channels, &mut scratch)?;
+        }
+
+        decorrelate(out, header.stereo, header.bits_per_sample);
+        frame.emitted = frame.emitted.saturating_add(window);
+        output.len = window;
+        output.sample_rate = header.sample_rate;
+        output.channels = u8::try_from(header.channels).unwrap_or(0);
+        if frame.emitted < header.block {
+            return Ok(0);
+        }
+
+        // Every subframe is fully read: the last one ends the frame, which
+        // is zero-padded to a byte and followed by its CRC-16.
+        let end_bit = match header
+            .channels
+            .checked_sub(1)
+            .and_then(|c| frame.subframes.get(c))
+        {
+            Some(Some(last)) => last.bit,
+            _ => return Err(DecodeError::InvalidData),
+        };
+        let end = end_bit.div_ceil(8);
+        let footer = input
+            .get(end..end.saturating_add(2))
+            .and_then(|f| <[u8; 2]>::try_from(f).ok())
+            .ok_or(DecodeError::EndOfStream)?;
+        if crc16(input.get(..end).unwrap_or_default()) != u16::from_be_bytes(footer) {
+            return Err(DecodeError::InvalidData);
+        }
+        Ok(end.saturating_add(2))
+    }
+
+    fn decode(&mut self, input: &[u8], output: &mut PcmFrame) -> Result<usize, DecodeError> {
+        let mut frame = match self.frame.take() {
+            Some(frame) => frame,
+            None => {
+                if let Some(consumed) = self.metadata(input)? {
+                    output.len = 0;
+                    return Ok(consumed);
+                }
+                if !is_sync(input) {
+                    // Junk between frames: skip to the next sync code.
+                    let skip = input
+                        .windows(2)
+                        .position(is_sync)
+                        .unwrap_or(input.len().saturating_sub(1));
+                    if skip == 0 {
+                        return Err(DecodeError::EndOfStream);
+                    }
+                    output.len = 0;
+                    return Ok(skip);
+                }
+                let header = self.header(input)?;
+                self.sample_rate = header.sample_rate;
+                self.channels = u8::try_from(header.channels).unwrap_or(0);
+                Frame {
+                    header,
+                    emitted: 0,
+                    subframes: [None, None],
+                }
+            }
+        };
+        let consumed = Self::window(&mut frame, input, output)?;
+        if consumed == 0 {
+            self.frame = Some(frame);
+        }
+        Ok(consumed)
+    }
+}
+
+impl Default for FlacDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoder for FlacDecoder {
+    type Error = DecodeError;
+
+    /// Consume one metadata block or decode one frame (or window of a
+    /// frame; see the module docs) from `input` into `output`.
+    ///
+    /// `input` must hold the whole frame; a frame cut short returns
+    /// [`DecodeError::EndOfStream`].  Samples are interleaved and
+    /// left-justified.  After an error the next call starts a new frame.
+    fn decode_frame(&mut self, input: &[u8], output: &mut PcmFrame) -> Result<usize, Self::Error> {
+        if input.is_empty() {
+            return Err(DecodeError::EndOfStream);
+        }
+        let result = self.decode(input, output);
+        if result.is_err() {
+            self.frame = None;
+        }
+        result
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> u8 {
+        self.channels
+    }
+}
+
+/// Frames start with the sync code `0xFFF8` (fixed block size) or
+/// `0xFFF9` (variable).
+fn is_sync(bytes: &[u8]) -> bool {
+    matches!(bytes, [0xFF, 0xF8 | 0xF9, ..])
+}
+
+// ─── Frames ───────────────────────────────────────────────────────────────────
+
+/// Inter-channel decorrelation of a stereo frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stereo {
+    Independent,
+    /// Channel 0 is left, channel 1 is left − right.
+    LeftSide,
+    /// Channel 0 is left − right, channel 1 is right.
+    RightSide,
+    /// Channel 0 is (left + right) / 2, channel 1 is left − right.
+    MidSide,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Header {
+    /// Samples per channel.
+    block: usize,
+    sample_rate: u32,
+    bits_per_sample: u32,
+    stereo: Stereo,
+    channels: usize,
+    /// Header length in bytes, CRC-8 included.
+    len: usize,
+}
+
+impl Header {
+    /// Coded width of `channel`: one bit more for a side channel.
+    fn channel_bits(&self, channel: usize) -> u32 {
+        let side = match self.stereo {
+            Stereo::Independent => false,
+            Stereo::LeftSide | Stereo::MidSide => channel == 1,
+            Stereo::RightSide => channel == 0,
+        };
+        self.bits_per_sample.wrapping_add(u32::from(side))
+    }
+}
+
+struct Frame {
+    header: Header,
+    /// Samples per channel already output.
+    emitted: usize,
+    /// Parsed lazily: a subframe's start is known once the previous one
+    /// has been read to its end.
+    subframes: [Option<Subframe>; MAX_CHANNELS],
+}
+
+/// Undo stereo decorrelation and left-justify one window in place.
+fn decorrelate(samples: &mut [i32], stereo: Stereo, bits_per_sample: u32) {
+    let justify = 32u32.saturating_sub(bits_per_sample);
+    if stereo == Stereo::Independent {
+        for sample in samples {
+            *sample = sample.wrapping_shl(justify);
+        }
+        return;
+    }
+    for pair in samples.chunks_exact_mut(2) {
+        let [a, b] = pair else {
+            continue;
+        };
+        let (left, right) = match stereo {
+            Stereo::LeftSide => (*a, a.wrapping_sub(*b)),
+            Stereo::RightSide => (a.wrapping_add(*b), *b),
+            Stereo::MidSide => {
+                let mid = a.wrapping_shl(1) | (*b & 1);
+                (mid.wrapping_add(*b) >> 1, mid.wrapping_sub(*b) >> 1)
+            }
+            Stereo::Independent => (*a, *b),
+        };
+        *a = left.wrapping_shl(justify);
+        *b = right.wrapping_shl(justify);
+    }
+}
+
+// ─── Subframes ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Constant(i32),
+    Verbatim,
+    Fixed,
+    Lpc,
+}
+
+/// One channel of a frame, resumable between windows.
+#[derive(Clone)]
+struct Subframe {
+    kind: Kind,
+    /// Coded width, after removing wasted bits.
+    bits: u32,
+    /// Low zero bits removed by the encoder.
+    wasted: u32,
+    /// Samples per channel in the frame.
+    block: usize,
+    /// Predictor order (warm-up samples).
+    order: usize,
+    /// Bit position of the LPC coefficients, re-read for each window to
+    /// keep a two-channel frame under 512 bytes.
+    coeffs_at: usize,
+    /// LPC coefficient width.
+    precision: u32,
+    /// LPC quantisation shift.
+    shift: u32,
+    /// Last `order` samples, oldest first (the warm-up before any output).
+    history: [i32; MAX_LPC_ORDER],
+    residual: Residual,
+    /// Samples output so far.
+    decoded: usize,
+    /// Bit position of the next unread sample data.
+    bit: usize,
+}
+
+impl Subframe {
+    /// Parse the subframe header (and warm-up, coefficients and residual
+    /// header) starting at bit `start` of the frame.
+    fn parse(
+        data: &[u8],
+        start: usize,
+        block: usize,
+        bits_per_sample: u32,
+    ) -> Result<Self, DecodeError> {
+        let mut bits = Bits::at(data, start);
+        let header = bits.read(8)?;
+        if header & 0x80 != 0 {
+            return Err(DecodeError::InvalidData);
+        }
+        let wasted = if header & 1 == 0 {
+            0
+        } else {
+            bits.unary()?.wrapping_add(1)
+        };
+        let coded = bits_per_sample
+            .checked_sub(wasted)
+            .filter(|&b| b > 0)
+            .ok_or(DecodeError::InvalidData)?;
+        let (kind, order) = match (header >> 1) & 0x3F {
+            0 => (Kind::Constant(bits.signed(coded)?), 0),
+            1 => (Kind::Verbatim, 0),
+            code @ 8..=12 => (Kind::Fixed, to_usize(code.wrapping_sub(8))),
+            code @ 32..=63 => (Kind::Lpc, to_usize(code.wrapping_sub(31))),
+            _ => return Err(DecodeError::InvalidData),
+        };
+        if order > block || (kind == Kind::Fixed && order > MAX_FIXED_ORDER) {
+            return Err(DecodeError::InvalidData);
+        }
+
+        let mut subframe = Self {
+            kind,
+            bits: coded,
+            wasted,
+            block,
+            order,
+            coeffs_at: 0,
+            precision: 0,
+            shift: 0,
+            history: [0; MAX_LPC_ORDER],
+            residual: Residual::default(),
+            decoded: 0,
+            bit: 0,
+        };
+        for sample in subframe.history.iter_mut().take(order) {
+            *sample = bits.signed(coded)?;
+        }
+        if kind == Kind::Lpc {
+            let precision = bits.read(4)?;
+            if precision == 0xF {
+                return Err(DecodeError::InvalidData);
+            }
+            // Negative shifts are reserved by the format.
+            subframe.shift =
+                u32::try_from(bits.signed(5)?).map_err(|_| DecodeError::InvalidData)?;
+            subframe.precision = precision.wrapping_add(1);
+            subframe.coeffs_at = bits.pos;
+            bits.skip(order.saturating_mul(to_usize(subframe.precision)))?;
+        }
+        if matches!(kind, Kind::Fixed | Kind::Lpc) {
+            subframe.residual = Residual::parse(&mut bits, block, order)?;
+        }
+        subframe.bit = bits.pos;
+        Ok(subframe)
+    }
+
+    /// Decode the next `count` samples into every `stride`-th slot of
+    /// `out`, with the wasted bits restored.
+    fn decode(
+        &mut self,
+        data: &[u8],
+        count: usize,
+        out: &mut [i32],
+        stride: usize,
+        scratch: &mut [i32; SCRATCH],
+    ) -> Result<(), DecodeError> {
+        let mut slots = out.iter_mut().step_by(stride.max(1)).take(count);
+        let mut bits = Bits::at(data, self.bit);
+        match self.kind {
+            Kind::Constant(value) => {
+                for slot in slots {
+                    *slot = value.wrapping_shl(self.wasted);
+                }
+            }
+            Kind::Verbatim => {
+                for slot in slots {
+                    *slot = bits.signed(self.bits)?.wrapping_shl(self.wasted);
+                }
+            }
+            Kind::Fixed | Kind::Lpc => {
+                let order = self.order;
+                let mut coeffs = [0i32; MAX_LPC_ORDER];
+                if self.kind == Kind::Lpc {
+                    let mut at = Bits::at(data, self.coeffs_at);
+                    for coeff in coeffs.iter_mut().take(order) {
+                        *coeff = at.signed(self.precision)?;
+                    }
+                }
+                // Warm-up samples were read with the header.
+                let warm_up = self.history.get(self.decoded..order).unwrap_or_default();
+                let warm = count.min(warm_up.len());
+                for (slot, &sample) in slots.by_ref().take(warm).zip(warm_up) {
+                    *slot = sample.wrapping_shl(self.wasted);
+                }
+                let mut pending = count.saturating_sub(warm);
+                while pending > 0 {
+                    let n = pending.min(CHUNK);
+                    let samples = scratch
+                        .get_mut(..order.saturating_add(n))
+                        .ok_or(DecodeError::InvalidData)?;
+                    let (history, residuals) = samples.split_at_mut(order);
+                    history.copy_from_slice(self.history.get(..order).unwrap_or_default());
+                    for residual in residuals.iter_mut() {
+                        *residual = self.residual.next(&mut bits)?;
+                    }
+                    if self.kind == Kind::Lpc {
+                        let coeffs = coeffs.get(..order).unwrap_or_default();
+                        restore_lpc(samples, coeffs, self.shift, self.bits)?;
+                    } else {
+                        restore_fixed(samples, order, self.bits)?;
+                    }
+                    let restored = samples.get(order..).unwrap_or_default();
+                    for (slot, &sample) in slots.by_ref().take(n).zip(restored) {
+                        *slot = sample.wrapping_shl(self.wasted);
+                    }
+                    if let (Some(history), Some(tail)) = (
+                        self.history.get_mut(..order),
+                        samples.get(n..n.saturating_add(order)),
+                    ) {
+                        history.copy_from_slice(tail);
+                    }
+                    pending = pending.saturating_sub(n);
+                }
+            }
+        }
+        self.decoded = self.decoded.saturating_add(count);
+        self.bit = bits.pos;
+        Ok(())
+    }
+
+    /// Bit position just past this subframe, skimming any residuals not
+    /// yet decoded.
+    fn end_bit(&self, data: &[u8]) -> Result<usize, DecodeError> {
+        match self.kind {
+            Kind::Constant(_) => Ok(self.bit),
+            Kind::Verbatim => {
+                let left = self.block.saturating_sub(self.decoded);
+                left.checked_mul(to_usize(self.bits))
+                    .and_then(|n| n.checked_add(self.bit))
+                    .ok_or(DecodeError::InvalidData)
+            }
+            Kind::Fixed | Kind::Lpc => {
+                let mut residual = self.residual;
+                let mut bits = Bits::at(data, self.bit);
+                let left = self.block.saturating_sub(self.decoded.max(self.order));
+                for _ in 0..left {
+                    residual.next(&mut bits)?;
+                }
+                Ok(bits.pos)
+            }
+        }
+    }
+}
+
+/// Rice-coded residual reader, resumable mid-partition.
+#[derive(Debug, Clone, Copy, Default)]
+struct Residual {
+    /// Width of each partition's Rice parameter: 4 or 5 bits.
+    param_bits: u32,
+    /// Number of partitions.
+    partitions: usize,
+    /// Samples per partition; the first has `order` fewer.
+    partition_len: usize,
+    order: usize,
+    /// Partitions started so far.
+    started: usize,
+    /// Residuals left in the current partition.
+    left: usize,
+    /// Parameter value that marks an escaped partition.
+    escape_code: u32,
+    /// Rice parameter of the current partition.
+    param: u32,
+    /// Bit width of an escaped (unencoded) partition.
+    escape: Option<u32>,
+}
+
+impl Residual {
+    fn parse(bits: &mut Bits<'_>, block: usize, order: usize) -> Result<Self, DecodeError> {
+        let (param_bits, escape_code) = match bits.read(2)? {
+            0 => (4, 0xF),
+            1 => (5, 0x1F),
+            _ => return Err(DecodeError::InvalidData),
+        };
+        let partition_order = bits.read(4)?;
+        let partition_len = block >> partition_order;
+        if partition_len << partition_order != block || partition_len < order {
+            return Err(DecodeError::InvalidData);
+        }
+        Ok(Self {
+            param_bits,
+            escape_code,
+            partitions: 1 << partition_order,
+            partition_len,
+            order,
+            ..Self::default()
+        })
+    }
+
+    fn next(&mut self, bits: &mut Bits<'_>) -> Result<i32, DecodeError> {
+        while self.left == 0 {
+            if self.started >= self.partitions {
+                return Err(DecodeError::InvalidData);
+            }
+            let param = bits.read(self.param_bits)?;
+            self.escape = if param == self.escape_code {
+                Some(bits.read(5)?)
+            } else {
+                None
+            };
+            self.param = param;
+            self.left = if self.started == 0 {
+                self.partition_len.saturating_sub(self.order)
+            } else {
+                self.partition_len
+            };
+            self.started = self.started.saturating_add(1);
+        }
+        self.left = self.left.wrapping_sub(1);
+        match self.escape {
+            Some(width) => bits.signed(width),
+            None => {
+                let quotient = bits.unary()?;
+                let remainder = bits.read(self.param)?;
+                Ok(zigzag(quotient.wrapping_shl(self.param) | remainder))
+            }
+        }
+    }
+}
+
+// ─── Bit reading ──────────────────────────────────────────────────────────────
+
+/// MSB-first bit cursor.
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Bits<'a> {
+    fn at(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    /// Read `n` ≤ 32 bits as an unsigned value.
+    fn read(&mut self, n: u32) -> Result<u32, DecodeError> {
+        if n == 0 {
+            return Ok(0);
+        }
+        if n > 32 {
+            return Err(DecodeError::InvalidData);
+        }
+        let end = self.pos.saturating_add(to_usize(n));
+        if end > self.data.len().saturating_mul(8) {
+            return Err(DecodeError::EndOfStream);
+        }
+        // Up to 32 bits starting at any bit offset span at most 5 bytes.
+        let first = self.pos / 8;
+        let acc = (0..5).fold(0u64, |acc, i| {
+            let byte = self.data.get(first.wrapping_add(i)).copied().unwrap_or(0);
+            acc << 8 | u64::from(byte)
+        });
+        let offset = u32::try_from(self.pos % 8).unwrap_or(0);
+        let value = acc.wrapping_shl(24u32.wrapping_add(offset)) >> 64u32.wrapping_sub(n);
+        self.pos = end;
+        u32::try_from(value).map_err(|_| DecodeError::InvalidData)
+    }
+
+    /// Read `n` ≤ 32 bits as a two's complement value.
+    fn signed(&mut self, n: u32) -> Result<i32, DecodeError> {
+        let shift = 32u32.wrapping_sub(n);
+        let value = self.read(n)?.wrapping_shl(shift);
+        Ok(i32::from_ne_bytes(value.to_ne_bytes()).wrapping_shr(shift))
+    }
+
+    /// Count zero bits up to and including the next one bit.
+    fn unary(&mut self) -> Result<u32, DecodeError> {
+        let mut zeros = 0u32;
+        loop {
+            let byte = *self
+                .data
+                .get(self.pos / 8)
+                .ok_or(DecodeError::EndOfStream)?;
+            let offset = u32::try_from(self.pos % 8).unwrap_or(0);
+            let rest = byte.wrapping_shl(offset);
+            if rest != 0 {
+                let lead = rest.leading_zeros();
+                self.pos = self.pos.saturating_add(to_usize(lead.wrapping_add(1)));
+                return Ok(zeros.saturating_add(lead));
+            }
+            let skipped = 8u32.wrapping_sub(offset);
+            zeros = zeros.saturating_add(skipped);
+            self.pos = self.pos.saturating_add(to_usize(skipped));
+        }
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), DecodeError> {
+        let end = self.pos.saturating_add(n);
+        if end > self.data.len().saturating_mul(8) {
+            return Err(DecodeError::EndOfStream);
+        }
+        self.pos = end;
+        Ok(())
+    }
+}
+
+fn to_usize(n: u32) -> usize {
+    usize::try_from(n).unwrap_or(usize::MAX)
+}
+
+/// Fold a Rice-coded unsigned value back to signed: 0, −1, 1, −2, …
+fn zigzag(value: u32) -> i32 {
+    let magnitude = i32::try_from(value >> 1).unwrap_or(0);
+    if value & 1 == 0 {
+        magnitude
+    } else {
+        !magnitude
+    }
+}
+
+// ─── CRCs ─────────────────────────────────────────────────────────────────────
+
+/// CRC-8 (polynomial 0x07) of a frame header.
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |crc, &byte| {
+        (0..8).fold(crc ^ byte, |crc, _| {
+            if crc & 0x80 == 0 {
+                crc << 1
+            } else {
+                crc << 1 ^ 0x07
+            }
+        })
+    })
+}
+
+/// CRC-16 (polynomial 0x8005) of a whole frame.
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0u16, |crc, &byte| {
+        let [high, _] = crc.to_be_bytes();
+        let entry = CRC16_TABLE
+            .get(usize::from(high ^ byte))
+            .copied()
+            .unwrap_or(0);
+        crc << 8 ^ entry
+    })
+}
+
+/// Byte-at-a-time table for [`crc16`].
+const CRC16_TABLE: [u16; 256] = crc16_table();
+
+#[allow(
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation
+)] // Evaluated at compile time
+const fn crc16_table() -> [u16; 256] {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                crc << 1 ^ 0x8005
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+// ─── Registry entry ───────────────────────────────────────────────────────────
+
+/// FLAC entry in [`crate::decoder::CODECS`].
+pub const CODEC: Codec = Codec {
+    format: AudioFormat::Flac,
+    open: Some(open),
+};
+
+/// Build the decoder from the STREAMINFO block `head` must start with.
+fn open(
+    head: &[u8],
+    run: &mut dyn FnMut(&mut dyn FrameDecoder<Error = DecodeError>),
+) -> Result<(), DecodeError> {
+    let info = StreamInfo::parse(head).ok_or(DecodeError::InvalidData)?;
+    if usize::from(info.channels) > MAX_CHANNELS
+        || u32::from(info.bits_per_sample) > MAX_BITS_PER_SAMPLE
+    {
+        return Err(DecodeError::UnsupportedFormat);
+    }
+    run(&mut FlacDecoder::with_stream_info(info));
+    Ok(())
+}
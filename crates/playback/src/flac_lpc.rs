@@ -0,0 +1,280 @@
+//! FLAC linear-prediction restoration for FIXED and LPC subframes.
+//!
+//! This is the hot loop of FLAC decoding: every output sample is the
+//! residual plus a weighted sum of the previous `order` samples.  At
+//! 96 kHz / 24-bit stereo that is ~192 000 predictions per second of audio,
+//! each up to 32 taps, which has to fit comfortably inside the M7 budget
+//! alongside SD reads and the display.
+//!
+//! # Paths
+//!
+//! | Function                   | Accumulator | Notes                              |
+//! |----------------------------|-------------|------------------------------------|
+//! | [`restore_lpc_reference`]  | `i64`       | Straightforward loop; the baseline |
+//! | [`restore_lpc`]            | `i32`/`i64` | Order-specialised kernels          |
+//!
+//! [`restore_lpc`] monomorphises a kernel per order for orders
+//! 1..=[`MAX_SPECIALISED_ORDER`] (all that libFLAC's presets emit), so the
+//! tap loop is fully unrolled and the coefficients stay in registers.  It
+//! also picks a 32-bit accumulator when the stream's bit depth, coefficient
+//! precision and order guarantee the sum cannot overflow — the same rule
+//! libFLAC uses — which avoids `SMLAL` pairs for 16-bit material.
+//!
+//! With the `flac-dsp` feature the unrolled kernels split the sum across
+//! two independent accumulators (even and odd taps).  That halves the
+//! multiply-accumulate dependency chain, which lets the M7's dual-issue
+//! pipeline overlap MACs; on the host it makes little difference.
+//!
+//! # Buffer layout
+//!
+//! All functions restore **in place**: on entry `samples[..order]` holds the
+//! warm-up samples and `samples[order..]` the decoded residuals; on return
+//! the whole slice holds the signal.  Shifts and sums wrap exactly as
+//! libFLAC's do on corrupt input — the caller's frame CRC catches that.
+
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+use crate::decoder::DecodeError;
+
+/// Highest LPC order permitted by the FLAC format.
+pub const MAX_LPC_ORDER: usize = 32;
+
+/// Highest FIXED predictor order permitted by the FLAC format.
+pub const MAX_FIXED_ORDER: usize = 4;
+
+/// Orders above this use the generic (non-unrolled) fast kernel.
+pub const MAX_SPECIALISED_ORDER: usize = 12;
+
+/// FIXED predictor coefficients, expressed as LPC taps with shift 0.
+const FIXED_COEFFS: [&[i32]; MAX_FIXED_ORDER + 1] =
+    [&[], &[1], &[2, -1], &[3, -3, 1], &[4, -6, 4, -1]];
+
+/// Restore a FIXED subframe of order 0–4.
+///
+/// # Errors
+///
+/// [`DecodeError::InvalidData`] if `order > 4` or `samples` is shorter than
+/// `order`.
+pub fn restore_fixed(
+    samples: &mut [i32],
+    order: usize,
+    bits_per_sample: u32,
+) -> Result<(), DecodeError> {
+    let coeffs = FIXED_COEFFS.get(order).ok_or(DecodeError::InvalidData)?;
+    if coeffs.is_empty() {
+        // Order 0: the residual is the signal.
+        return Ok(());
+    }
+    restore_lpc(samples, coeffs, 0, bits_per_sample)
+}
+
+/// Restore an LPC subframe with the straightforward `i64` loop.
+///
+/// Kept as the correctness baseline for tests and benchmarks.
+///
+/// # Errors
+///
+/// [`DecodeError::InvalidData`] for an order outside 1..=32, a shift above
+/// 31, or fewer samples than the order.
+pub fn restore_lpc_reference(
+    samples: &mut [i32],
+    coeffs: &[i32],
+    shift: u32,
+) -> Result<(), DecodeError> {
+    check(samples, coeffs, shift)?;
+    let order = coeffs.len();
+    for i in order..samples.len() {
+        let (history, current) = samples.split_at_mut(i);
+        let window = history.get(i.wrapping_sub(order)..).unwrap_or_default();
+        let sum = coeffs
+            .iter()
+            .zip(window.iter().rev())
+            .fold(0i64, |acc, (&c, &s)| {
+                acc.wrapping_add(i64::from(c).wrapping_mul(i64::from(s)))
+            });
+        if let Some(out) = current.first_mut() {
+            *out = out.wrapping_add(narrow(sum.wrapping_shr(shift)));
+        }
+    }
+    Ok(())
+}
+
+/// Restore an LPC subframe using the fastest kernel that is exact for
+/// `bits_per_sample` (include the extra bit for a stereo side channel).
+///
+/// Produces bit-identical output to [`restore_lpc_reference`] whenever the
+/// samples fit in `bits_per_sample` bits, i.e. for every valid stream.
+///
+/// # Errors
+///
+/// As [`restore_lpc_reference`].
+pub fn restore_lpc(
+    samples: &mut [i32],
+    coeffs: &[i32],
+    shift: u32,
+    bits_per_sample: u32,
+) -> Result<(), DecodeError> {
+    check(samples, coeffs, shift)?;
+    let narrow_acc = lpc_fits_i32(coeffs, bits_per_sample);
+
+    macro_rules! dispatch {
+        ($($order:literal)*) => {
+            match coeffs.len() {
+                $(
+                    $order => {
+                        let taps: &[i32; $order] =
+                            coeffs.try_into().map_err(|_| DecodeError::InvalidData)?;
+                        if narrow_acc {
+                            kernel_i32::<$order>(samples, taps, shift);
+                        } else {
+                            kernel_i64::<$order>(samples, taps, shift);
+                        }
+                    }
+                )*
+                _ => generic_i64(samples, coeffs, shift),
+            }
+        };
+    }
+    dispatch!(1 2 3 4 5 6 7 8 9 10 11 12);
+    Ok(())
+}
+
+/// `true` when `order` taps of `coeffs` over `bits_per_sample`-bit samples
+/// cannot overflow a 32-bit accumulator.
+#[must_use]
+pub fn lpc_fits_i32(coeffs: &[i32], bits_per_sample: u32) -> bool {
+    let max_abs = coeffs.iter().map(|c| c.unsigned_abs()).max().unwrap_or(0);
+    // Signed bits needed for the largest coefficient.
+    let coeff_bits = 33u32.saturating_sub(max_abs.leading_zeros());
+    let order_bits = u32::try_from(coeffs.len())
+        .unwrap_or(u32::MAX)
+        .checked_next_power_of_two()
+        .map_or(u32::MAX, u32::trailing_zeros);
+    bits_per_sample
+        .saturating_add(coeff_bits)
+        .saturating_add(order_bits)
+        <= 32
+}
+
+fn check(samples: &[i32], coeffs: &[i32], shift: u32) -> Result<(), DecodeError> {
+    let order = coeffs.len();
+    if order == 0 || order > MAX_LPC_ORDER || shift > 31 || samples.len() < order {
+        return Err(DecodeError::InvalidData);
+    }
+    Ok(())
+}
+
+/// Truncate a prediction to the sample width.
+// SAFETY (cast): valid streams keep predictions within 32 bits; corrupt
+// streams wrap here exactly as libFLAC does and fail the frame CRC.
+#[allow(clippy::cast_possible_truncation)]
+fn narrow(v: i64) -> i32 {
+    v as i32
+}
+
+/// Unrolled kernel accumulating in `i64`.
+fn kernel_i64<const ORDER: usize>(samples: &mut [i32], coeffs: &[i32; ORDER], shift: u32) {
+    for i in ORDER..samples.len() {
+        let (history, current) = samples.split_at_mut(i);
+        let Some(Ok(window)) = history
+            .get(i.wrapping_sub(ORDER)..)
+            .map(<&[i32; ORDER]>::try_from)
+        else {
+            return;
+        };
+        let sum = mac_i64(coeffs, window);
+        if let Some(out) = current.first_mut() {
+            *out = out.wrapping_add(narrow(sum.wrapping_shr(shift)));
+        }
+    }
+}
+
+/// Unrolled kernel accumulating in `i32` — only called when
+/// [`lpc_fits_i32`] holds, so the wrapping ops never actually wrap.
+fn kernel_i32<const ORDER: usize>(samples: &mut [i32], coeffs: &[i32; ORDER], shift: u32) {
+    for i in ORDER..samples.len() {
+        let (history, current) = samples.split_at_mut(i);
+        let Some(Ok(window)) = history
+            .get(i.wrapping_sub(ORDER)..)
+            .map(<&[i32; ORDER]>::try_from)
+        else {
+            return;
+        };
+        let sum = mac_i32(coeffs, window);
+        if let Some(out) = current.first_mut() {
+            *out = out.wrapping_add(sum.wrapping_shr(shift));
+        }
+    }
+}
+
+/// Non-specialised kernel for orders above [`MAX_SPECIALISED_ORDER`].
+fn generic_i64(samples: &mut [i32], coeffs: &[i32], shift: u32) {
+    let order = coeffs.len();
+    for i in order..samples.len() {
+        let (history, current) = samples.split_at_mut(i);
+        let window = history.get(i.wrapping_sub(order)..).unwrap_or_default();
+        let sum = coeffs
+            .iter()
+            .zip(window.iter().rev())
+            .fold(0i64, |acc, (&c, &s)| {
+                acc.wrapping_add(i64::from(c).wrapping_mul(i64::from(s)))
+            });
+        if let Some(out) = current.first_mut() {
+            *out = out.wrapping_add(narrow(sum.wrapping_shr(shift)));
+        }
+    }
+}
+
+/// `Σ coeffs[j] · window[ORDER-1-j]` in `i64`.
+#[inline(always)]
+fn mac_i64<const ORDER: usize>(coeffs: &[i32; ORDER], window: &[i32; ORDER]) -> i64 {
+    let taps = coeffs.iter().zip(window.iter().rev());
+    #[cfg(feature = "flac-dsp")]
+    {
+        let (mut even, mut odd) = (0i64, 0i64);
+        for (k, (&c, &s)) in taps.enumerate() {
+            let p = i64::from(c).wrapping_mul(i64::from(s));
+            if k & 1 == 0 {
+                even = even.wrapping_add(p);
+            } else {
+                odd = odd.wrapping_add(p);
+            }
+        }
+        even.wrapping_add(odd)
+    }
+    #[cfg(not(feature = "flac-dsp"))]
+    {
+        taps.fold(0i64, |acc, (&c, &s)| {
+            acc.wrapping_add(i64::from(c).wrapping_mul(i64::from(s)))
+        })
+    }
+}
+
+/// `Σ coeffs[j] · window[ORDER-1-j]` in `i32`.
+#[inline(always)]
+fn mac_i32<const ORDER: usize>(coeffs: &[i32; ORDER], window: &[i32; ORDER]) -> i32 {
+    let taps = coeffs.iter().zip(window.iter().rev());
+    #[cfg(feature = "flac-dsp")]
+    {
+        let (mut even, mut odd) = (0i32, 0i32);
+        for (k, (&c, &s)) in taps.enumerate() {
+            let p = c.wrapping_mul(s);
+            if k & 1 == 0 {
+                even = even.wrapping_add(p);
+            } else {
+                odd = odd.wrapping_add(p);
+            }
+        }
+        even.wrapping_add(odd)
+    }
+    #[cfg(not(feature = "flac-dsp"))]
+    {
+        taps.fold(0i32, |acc, (&c, &s)| acc.wrapping_add(c.wrapping_mul(s)))
+    }
+}
@@ -0,0 +1,175 @@
+//! Per-frame decode timing, shared by the host benchmarks and the target.
+//!
+//! [`FrameStats`] accumulates min / max / mean ticks per decoded frame, and
+//! [`time_frame`] wraps one [`FrameDecoder::decode_frame`] call with a
+//! caller-supplied clock.  The clock is a plain `FnMut() -> u32` so the same
+//! code path runs against:
+//!
+//! - the Cortex-M7 cycle counter ([`dwt::cycles`], `cycle-count` feature), or
+//! - any host timer in tests and `benches/decode_frame.rs`.
+//!
+//! Tick differences use wrapping subtraction, so a 32-bit counter that rolls
+//! over mid-frame (every ~8.9 s at 480 MHz) still yields the right duration.
+//!
+//! # On target
+//!
+//! ```ignore
+//! playback::frame_timing::dwt::enable();
+//! let mut stats = FrameStats::new();
+//! loop {
+//!     time_frame(&mut decoder, input, &mut frame, &mut stats, dwt::cycles)?;
+//!     if stats.frames() == 256 {
+//!         dwt::log("flac", &stats);
+//!         stats.reset();
+//!     }
+//! }
+//! ```
+//!
+//! Capture the RTT output of two firmware builds to files and compare the
+//! `decode ...` lines with `cargo run -p xtask -- bench diff-log old.log new.log`.
+
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+use crate::decoder::{FrameDecoder, PcmFrame};
+
+/// Running per-frame timing statistics, in clock ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStats {
+    frames: u32,
+    total: u64,
+    min: u32,
+    max: u32,
+}
+
+impl FrameStats {
+    /// Empty statistics.
+    pub const fn new() -> Self {
+        Self {
+            frames: 0,
+            total: 0,
+            min: u32::MAX,
+            max: 0,
+        }
+    }
+
+    /// Record one frame that took `ticks`.
+    pub fn record(&mut self, ticks: u32) {
+        self.frames = self.frames.saturating_add(1);
+        self.total = self.total.saturating_add(u64::from(ticks));
+        self.min = self.min.min(ticks);
+        self.max = self.max.max(ticks);
+    }
+
+    /// Number of frames recorded.
+    pub const fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Fastest frame, or `None` before the first [`record`](Self::record).
+    pub const fn min(&self) -> Option<u32> {
+        if self.frames == 0 {
+            None
+        } else {
+            Some(self.min)
+        }
+    }
+
+    /// Slowest frame (0 before the first [`record`](Self::record)).
+    pub const fn max(&self) -> u32 {
+        self.max
+    }
+
+    /// Mean ticks per frame (0 before the first [`record`](Self::record)).
+    pub fn mean(&self) -> u32 {
+        self.total
+            .checked_div(u64::from(self.frames))
+            .and_then(|m| u32::try_from(m).ok())
+            .unwrap_or(0)
+    }
+
+    /// Clear all recorded frames.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decode one frame and record how many `clock` ticks it took.
+///
+/// Failed decodes are not recorded — an end-of-stream probe would otherwise
+/// drag the minimum down to a few cycles.
+///
+/// # Errors
+///
+/// Whatever `decoder.decode_frame` returns.
+pub fn time_frame<D: FrameDecoder>(
+    decoder: &mut D,
+    input: &[u8],
+    output: &mut PcmFrame,
+    stats: &mut FrameStats,
+    mut clock: impl FnMut() -> u32,
+) -> Result<usize, D::Error> {
+    let start = clock();
+    let consumed = decoder.decode_frame(input, output)?;
+    stats.record(clock().wrapping_sub(start));
+    Ok(consumed)
+}
+
+/// Cortex-M7 DWT cycle counter.
+#[cfg(feature = "cycle-count")]
+pub mod dwt {
+    use super::FrameStats;
+
+    const DEMCR: *mut u32 = 0xE000_EDFC as *mut u32;
+    const DWT_CTRL: *mut u32 = 0xE000_1000 as *mut u32;
+    const DWT_CYCCNT: *mut u32 = 0xE000_1004 as *mut u32;
+    const DWT_LAR: *mut u32 = 0xE000_1FB0 as *mut u32;
+
+    const DEMCR_TRCENA: u32 = 1 << 24;
+    const DWT_CTRL_CYCCNTENA: u32 = 1;
+    /// CoreSight lock-access key; the M7 ignores DWT writes until unlocked.
+    const LAR_KEY: u32 = 0xC5AC_CE55;
+
+    /// Enable and zero the cycle counter.  Call once at boot.
+    pub fn enable() {
+        // SAFETY: DEMCR and the DWT block are architecturally defined,
+        // always-mapped System Control Space registers on Armv7-M.  Only the
+        // trace-enable and cycle-counter-enable bits are set; no other code
+        // in the firmware touches these registers.
+        unsafe {
+            DEMCR.write_volatile(DEMCR.read_volatile() | DEMCR_TRCENA);
+            DWT_LAR.write_volatile(LAR_KEY);
+            DWT_CYCCNT.write_volatile(0);
+            DWT_CTRL.write_volatile(DWT_CTRL.read_volatile() | DWT_CTRL_CYCCNTENA);
+        }
+    }
+
+    /// Current cycle count (wraps every 2³² cycles).
+    pub fn cycles() -> u32 {
+        // SAFETY: read-only access to an always-mapped SCS register.
+        unsafe { DWT_CYCCNT.read_volatile() }
+    }
+
+    /// Emit `stats` as one defmt line.
+    pub fn log(label: &str, stats: &FrameStats) {
+        defmt::info!(
+            "decode {=str}: frames={=u32} mean={=u32} min={=u32} max={=u32} cycles",
+            label,
+            stats.frames(),
+            stats.mean(),
+            stats.min().unwrap_or(0),
+            stats.max()
+        );
+    }
+}
@@ -1,24 +1,57 @@
-//! Audio playback engine — FLAC/MP3/WAV decoding, DMA streaming to SAI I²S
-#![cfg_attr(not(test), no_std)]
+//! Audio playback engine — FLAC/MP3/WAV/AAC/Opus decoding, DMA streaming to SAI I²S
+//!
+//! # Panic-free hot paths
+//!
+//! [`decoder`], [`flac_decoder`], [`flac_lpc`], [`frame_timing`],
+//! [`mp3_decoder`], [`ogg`], [`ring_buffer`], [`silence`], [`time_stretch`],
+//! [`volume`] and [`wav_decoder`] run in the
+//! decode task and the SAI DMA interrupt, where a panic stops audio until
+//! reboot.  On top of the workspace denies each one denies `unreachable`, `panic_in_result_fn`,
+//! `unwrap_in_result` and `string_slice`; `tests/panic_free.rs` keeps
+//! panicking constructs and lint escapes out of their source, and
+//! `cargo run -p xtask -- panic-check` looks for calls into the panic
+//! machinery in the firmware disassembly, which also catches panics pulled
+//! in through dependencies such as nanomp3.
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 // unwrap_used, expect_used, panic enforced at workspace level (Cargo.toml)
 // TODO: Add rustdoc to all public items (tracked as tech debt)
 #![allow(missing_docs)]
 
+pub mod aac_decoder;
 pub mod decoder;
 pub mod engine;
+pub mod events;
+pub mod fault;
+pub mod flac_decoder;
+pub mod flac_lpc;
+pub mod frame_timing;
 pub mod mp3_decoder;
+pub mod mp4;
+pub mod ogg;
+pub mod opus_decoder;
+pub mod queue;
+pub mod queue_journal;
+pub mod resume;
 pub mod ring_buffer;
+pub mod silence;
+#[cfg(feature = "std")]
+pub mod test_vectors;
+pub mod time_stretch;
 pub mod volume;
+pub mod wav_decoder;
 
 // Tests come first — implementations below will make them pass
 #[cfg(test)]
 #[allow(clippy::expect_used)] // Tests use expect() for readable assertions
 #[allow(clippy::indexing_slicing)] // Tests may index safely with known bounds
 #[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+#[allow(clippy::cast_possible_truncation)] // Fixture lengths and bytes are small
 mod tests {
     /// Decoder abstraction tests
     mod decoder_tests {
-        use crate::decoder::{AudioFormat, DecodeError, PcmFrame};
+        use crate::decoder::{
+            codec_for, detect_format, AudioFormat, DecodeError, OpenDecoder, PcmFrame, CODECS,
+        };
 
         #[test]
         // LARGE_STACK_ARRAYS: inline 4 096-element array mirrors production PcmFrame
@@ -58,10 +91,326 @@ mod tests {
             assert_eq!(AudioFormat::from_extension("wav"), Some(AudioFormat::Wav));
         }
 
+        #[test]
+        fn test_audio_format_detection_aac() {
+            let aac = cfg!(feature = "aac").then_some(AudioFormat::Aac);
+            assert_eq!(AudioFormat::from_extension("m4a"), aac);
+            assert_eq!(AudioFormat::from_extension("m4b"), aac);
+        }
+
+        #[test]
+        fn test_audio_format_detection_opus() {
+            assert_eq!(
+                AudioFormat::from_extension("opus"),
+                cfg!(feature = "opus").then_some(AudioFormat::Opus)
+            );
+        }
+
         #[test]
         fn test_audio_format_unknown_returns_none() {
             assert_eq!(AudioFormat::from_extension("txt"), None);
         }
+
+        #[test]
+        fn test_audio_format_extension_any_case() {
+            assert_eq!(AudioFormat::from_extension("FLAC"), Some(AudioFormat::Flac));
+            assert_eq!(
+                AudioFormat::from_extension("M4b"),
+                cfg!(feature = "aac").then_some(AudioFormat::Aac)
+            );
+        }
+
+        #[test]
+        fn test_registry_covers_every_format_once() {
+            for (format, built) in [
+                (AudioFormat::Flac, true),
+                (AudioFormat::Mp3, true),
+                (AudioFormat::Wav, true),
+                (AudioFormat::Aac, cfg!(feature = "aac")),
+                (AudioFormat::Opus, cfg!(feature = "opus")),
+            ] {
+                let entries = CODECS.iter().filter(|c| c.format == format).count();
+                assert_eq!(entries, usize::from(built), "{format:?}");
+                assert_eq!(codec_for(format).is_some(), built, "{format:?}");
+                // The library recognises exactly the formats the registry lists.
+                assert_eq!(format.info().is_some(), built, "{format:?}");
+                for ext in format.info().map_or(&[][..], |info| info.extensions) {
+                    assert_eq!(AudioFormat::from_extension(ext), Some(format));
+                }
+            }
+        }
+
+        #[test]
+        fn test_detect_format_magic_bytes() {
+            assert_eq!(detect_format(b"fLaC\0\0\0\x22"), Some(AudioFormat::Flac));
+            assert_eq!(detect_format(b"ID3\x04"), Some(AudioFormat::Mp3));
+            assert_eq!(
+                detect_format(&[0xFF, 0xFB, 0x90, 0x00]),
+                Some(AudioFormat::Mp3)
+            );
+            assert_eq!(detect_format(b"RIFF\0\0\0\0WAVE"), Some(AudioFormat::Wav));
+            assert_eq!(
+                detect_format(b"\0\0\0\x18ftypM4A "),
+                cfg!(feature = "aac").then_some(AudioFormat::Aac)
+            );
+            let mut ogg = [0u8; 36];
+            ogg[..4].copy_from_slice(b"OggS");
+            ogg[28..].copy_from_slice(b"OpusHead");
+            assert_eq!(
+                detect_format(&ogg),
+                cfg!(feature = "opus").then_some(AudioFormat::Opus)
+            );
+            assert_eq!(detect_format(&ogg[..30]), None);
+            assert_eq!(detect_format(b""), None);
+            assert_eq!(detect_format(b"OggS\0\x02"), None);
+        }
+
+        #[test]
+        fn test_open_rejects_missing_setup_data() {
+            let mut ran = false;
+            for codec in [
+                crate::flac_decoder::CODEC,
+                crate::aac_decoder::CODEC,
+                crate::opus_decoder::CODEC,
+            ] {
+                let open: OpenDecoder = codec.open.unwrap_or(|_, _| Err(DecodeError::InvalidData));
+                assert_eq!(
+                    open(b"fLaC", &mut |_| ran = true),
+                    Err(DecodeError::InvalidData)
+                );
+            }
+            assert!(!ran);
+        }
+
+        #[test]
+        fn test_only_feature_gated_backends_lack_a_constructor() {
+            assert!(crate::flac_decoder::CODEC.open.is_some());
+            assert!(crate::wav_decoder::CODEC.open.is_some());
+            assert_eq!(
+                crate::mp3_decoder::CODEC.open.is_some(),
+                cfg!(feature = "mp3")
+            );
+        }
+    }
+
+    /// MP4 demuxer tests
+    mod mp4_tests {
+        use crate::mp4::{is_mp4, read_box_header, Mp4Audio, Mp4Error, Mp4Sample, AOT_AAC_LC};
+
+        fn bx(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+            out.extend_from_slice(kind);
+            out.extend_from_slice(body);
+            out
+        }
+
+        fn full(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            bx(kind, &[&[0u8; 4][..], body].concat())
+        }
+
+        fn words(values: &[u32]) -> Vec<u8> {
+            values.iter().flat_map(|v| v.to_be_bytes()).collect()
+        }
+
+        fn hdlr(handler: &[u8; 4]) -> Vec<u8> {
+            full(b"hdlr", &[&[0u8; 4][..], handler, &[0u8; 13]].concat())
+        }
+
+        /// `mp4a` entry carrying AAC-LC 44.1 kHz stereo.
+        fn mp4a() -> Vec<u8> {
+            let dsi = [0x05, 0x02, 0x12, 0x10];
+            let dcd_body = [&[0x40, 0x15][..], &[0u8; 11], &dsi].concat();
+            let dcd = [&[0x04, dcd_body.len() as u8][..], &dcd_body].concat();
+            let es_body = [&[0x00, 0x01, 0x00][..], &dcd].concat();
+            let es = [&[0x03, es_body.len() as u8][..], &es_body].concat();
+            let mut entry = vec![0u8; 6];
+            entry.extend_from_slice(&[0, 1]); // data_reference_index
+            entry.extend_from_slice(&[0u8; 8]); // version, revision, vendor
+            entry.extend_from_slice(&[0, 2, 0, 16, 0, 0, 0, 0]);
+            entry.extend_from_slice(&words(&[44_100 << 16]));
+            entry.extend_from_slice(&full(b"esds", &es));
+            bx(b"mp4a", &entry)
+        }
+
+        /// Five access units (10..50 bytes) in two chunks of 3 and 2, after
+        /// a video track.
+        fn moov(entry: Vec<u8>) -> Vec<u8> {
+            let stbl = [
+                full(b"stsd", &[words(&[1]), entry].concat()),
+                full(b"stts", &words(&[1, 5, 1024])),
+                full(b"stsc", &words(&[2, 1, 3, 1, 2, 2, 1])),
+                full(b"stsz", &words(&[0, 5, 10, 20, 30, 40, 50])),
+                full(b"stco", &words(&[2, 1000, 2000])),
+            ]
+            .concat();
+            let mdia = [
+                full(
+                    b"mdhd",
+                    &[words(&[0, 0, 44_100, 88_200]), vec![0; 4]].concat(),
+                ),
+                hdlr(b"soun"),
+                bx(b"minf", &bx(b"stbl", &stbl)),
+            ]
+            .concat();
+            let video = bx(b"trak", &bx(b"mdia", &hdlr(b"vide")));
+            [video, bx(b"trak", &bx(b"mdia", &mdia))].concat()
+        }
+
+        #[test]
+        fn test_mp4_parses_audio_track_after_video() {
+            let moov = moov(mp4a());
+            let audio = Mp4Audio::parse(&moov).expect("audio track");
+            assert_eq!(audio.config().object_type, AOT_AAC_LC);
+            assert_eq!(audio.config().sample_rate, 44_100);
+            assert_eq!(audio.config().channels, 2);
+            assert_eq!(audio.config().raw.as_slice(), &[0x12, 0x10]);
+            assert_eq!(audio.sample_count(), 5);
+            assert_eq!(audio.duration_ms(), 2_000);
+        }
+
+        #[test]
+        fn test_mp4_sample_offsets_follow_chunks() {
+            let moov = moov(mp4a());
+            let audio = Mp4Audio::parse(&moov).expect("audio track");
+            let at = |offset, size| Some(Mp4Sample { offset, size });
+            assert_eq!(audio.sample(0), at(1000, 10));
+            assert_eq!(audio.sample(2), at(1030, 30));
+            assert_eq!(audio.sample(3), at(2000, 40));
+            assert_eq!(audio.sample(4), at(2040, 50));
+            assert_eq!(audio.sample(5), None);
+        }
+
+        #[test]
+        fn test_mp4_sample_at_ms_uses_time_to_sample() {
+            let moov = moov(mp4a());
+            let audio = Mp4Audio::parse(&moov).expect("audio track");
+            assert_eq!(audio.sample_at_ms(0), Some(0));
+            // 50 ms = 2 205 ticks at 44.1 kHz; 1 024 ticks per access unit.
+            assert_eq!(audio.sample_at_ms(50), Some(2));
+            assert_eq!(audio.sample_at_ms(200), None);
+        }
+
+        #[test]
+        fn test_mp4_rejects_non_aac_and_missing_audio() {
+            let alac = moov(bx(b"alac", &[0u8; 28]));
+            assert_eq!(
+                Mp4Audio::parse(&alac).err(),
+                Some(Mp4Error::UnsupportedCodec)
+            );
+            let video_only = bx(b"trak", &bx(b"mdia", &hdlr(b"vide")));
+            assert_eq!(
+                Mp4Audio::parse(&video_only).err(),
+                Some(Mp4Error::NoAudioTrack)
+            );
+        }
+
+        #[test]
+        fn test_mp4_box_headers() {
+            assert!(is_mp4(b"\0\0\0\x18ftypM4A "));
+            assert!(!is_mp4(b"fLaC\0\0\0\0"));
+            let mut large = words(&[1]);
+            large.extend_from_slice(b"mdat");
+            large.extend_from_slice(&(1u64 << 32).to_be_bytes());
+            let header = read_box_header(&large).expect("64-bit box");
+            assert_eq!(header.kind, *b"mdat");
+            assert_eq!(header.size, Some(1 << 32));
+            assert_eq!(header.header_len, 16);
+            assert_eq!(read_box_header(&words(&[4, 0])), None);
+        }
+    }
+
+    /// Ogg container tests
+    mod ogg_tests {
+        use crate::ogg::{seal_page, OggError, OggPage, PacketAssembler};
+
+        /// Build a sealed page from its flags, lacing values and body.
+        fn page(flags: u8, granule: u64, lacing: &[u8], body: &[u8]) -> Vec<u8> {
+            let mut out = b"OggS".to_vec();
+            out.push(0);
+            out.push(flags);
+            out.extend_from_slice(&granule.to_le_bytes());
+            out.extend_from_slice(&0x1234u32.to_le_bytes());
+            out.extend_from_slice(&7u32.to_le_bytes());
+            out.extend_from_slice(&[0; 4]);
+            out.push(lacing.len() as u8);
+            out.extend_from_slice(lacing);
+            out.extend_from_slice(body);
+            seal_page(&mut out);
+            out
+        }
+
+        fn packets<const N: usize>(
+            assembler: &mut PacketAssembler<N>,
+            page: &OggPage<'_>,
+        ) -> (Vec<Vec<u8>>, Result<(), OggError>) {
+            let mut out = Vec::new();
+            let result = assembler.feed(page, |p| out.push(p.to_vec()));
+            (out, result)
+        }
+
+        #[test]
+        fn test_ogg_page_parses_header_and_packets() {
+            let body: Vec<u8> = (0..268u32).map(|i| i as u8).collect();
+            let bytes = page(0x02, 960, &[3, 255, 10], &body);
+            let parsed = OggPage::parse(&bytes).expect("valid page");
+            assert_eq!(parsed.size(), bytes.len());
+            assert_eq!(parsed.granule_position(), 960);
+            assert_eq!(parsed.serial(), 0x1234);
+            assert_eq!(parsed.sequence(), 7);
+            assert!(parsed.is_first() && !parsed.is_last() && !parsed.is_continued());
+
+            let mut assembler = PacketAssembler::<512>::new();
+            let (out, result) = packets(&mut assembler, &parsed);
+            assert_eq!(result, Ok(()));
+            assert_eq!(out.len(), 2);
+            assert_eq!(out[0], body[..3]);
+            assert_eq!(out[1], body[3..]);
+        }
+
+        #[test]
+        fn test_ogg_page_rejects_corruption() {
+            let mut bytes = page(0, 0, &[4], b"opus");
+            assert_eq!(
+                OggPage::parse(&bytes[..bytes.len() - 1]).err(),
+                Some(OggError::Truncated)
+            );
+            let last = bytes.len() - 1;
+            bytes[last] ^= 0xFF;
+            assert_eq!(OggPage::parse(&bytes).err(), Some(OggError::BadCrc));
+            assert_eq!(OggPage::parse(&[0u8; 32]).err(), Some(OggError::NotOgg));
+        }
+
+        #[test]
+        fn test_ogg_packet_spans_pages() {
+            let first = page(0, u64::MAX, &[255], &[1; 255]);
+            let second = page(0x01, 1920, &[5], &[2; 5]);
+            let mut assembler = PacketAssembler::<512>::new();
+
+            let (out, _) = packets(&mut assembler, &OggPage::parse(&first).expect("page 1"));
+            assert!(out.is_empty(), "packet continues on the next page");
+            let (out, _) = packets(&mut assembler, &OggPage::parse(&second).expect("page 2"));
+            assert_eq!(out.len(), 1);
+            assert_eq!(out[0].len(), 260);
+        }
+
+        #[test]
+        fn test_ogg_lost_continuation_discards_partial_packet() {
+            let first = page(0, u64::MAX, &[255], &[1; 255]);
+            let fresh = page(0, 960, &[3], &[3; 3]);
+            let mut assembler = PacketAssembler::<512>::new();
+            let _ = packets(&mut assembler, &OggPage::parse(&first).expect("page 1"));
+            let (out, _) = packets(&mut assembler, &OggPage::parse(&fresh).expect("page 2"));
+            assert_eq!(out, vec![vec![3u8; 3]]);
+        }
+
+        #[test]
+        fn test_ogg_oversized_packet_is_dropped() {
+            let bytes = page(0, 0, &[10, 2], &[9; 12]);
+            let mut assembler = PacketAssembler::<8>::new();
+            let (out, result) = packets(&mut assembler, &OggPage::parse(&bytes).expect("page"));
+            assert_eq!(result, Err(OggError::PacketTooLarge));
+            assert_eq!(out, vec![vec![9u8; 2]]);
+        }
     }
 
     /// Playback state machine tests
@@ -128,6 +477,541 @@ mod tests {
         }
     }
 
+    /// Chapter navigation tests
+    mod engine_event_tests {
+        use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+
+        use crate::engine::{PlaybackEngine, PlaybackState, POSITION_TICK_MS};
+        use crate::events::{
+            PlaybackEvent, PlaybackEventBus, PlaybackEventPublisher, PlaybackEventSubscriber,
+        };
+        use crate::fault::{PlaybackFault, RecoveryAction, SinkError};
+
+        fn drain(engine: &mut PlaybackEngine) -> Vec<PlaybackEvent> {
+            core::iter::from_fn(|| engine.next_event()).collect()
+        }
+
+        #[test]
+        fn test_transitions_queue_state_changes() {
+            let mut engine = PlaybackEngine::new();
+            engine.play().expect("play should succeed");
+            engine.pause().expect("pause should succeed");
+            engine.pause().expect("pause again should succeed");
+            engine.stop().expect("stop should succeed");
+            engine.stop().expect("stop again should succeed");
+            assert_eq!(
+                drain(&mut engine),
+                [
+                    PlaybackEvent::StateChanged(PlaybackState::Playing),
+                    PlaybackEvent::StateChanged(PlaybackState::Paused),
+                    PlaybackEvent::StateChanged(PlaybackState::Stopped),
+                ]
+            );
+        }
+
+        #[test]
+        fn test_load_track_queues_track_started() {
+            let mut engine = PlaybackEngine::new();
+            engine.load_track(7, 180_000);
+            engine.load_track(8, u64::MAX);
+            assert_eq!(engine.position_ms(), 0);
+            assert_eq!(
+                drain(&mut engine),
+                [
+                    PlaybackEvent::TrackStarted {
+                        track_id: 7,
+                        duration_ms: 180_000
+                    },
+                    PlaybackEvent::TrackStarted {
+                        track_id: 8,
+                        duration_ms: 0
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn test_advance_ticks_every_interval_while_playing() {
+            let mut engine = PlaybackEngine::with_duration(600);
+            engine.advance_ms(POSITION_TICK_MS);
+            assert_eq!(engine.position_ms(), 0, "stopped engines do not advance");
+            engine.play().expect("play should succeed");
+            drain(&mut engine);
+
+            engine.advance_ms(100);
+            assert_eq!(engine.next_event(), None);
+            engine.advance_ms(150);
+            engine.advance_ms(249);
+            engine.advance_ms(1_000);
+            engine.advance_ms(1_000);
+            assert_eq!(
+                drain(&mut engine),
+                [
+                    PlaybackEvent::PositionTick { position_ms: 250 },
+                    PlaybackEvent::PositionTick { position_ms: 600 },
+                ]
+            );
+        }
+
+        #[test]
+        fn test_seek_queues_position_tick() {
+            let mut engine = PlaybackEngine::with_duration(10_000);
+            engine.seek_ms(99_999);
+            assert_eq!(
+                engine.next_event(),
+                Some(PlaybackEvent::PositionTick {
+                    position_ms: 10_000
+                })
+            );
+        }
+
+        #[test]
+        fn test_eq_change_is_queued_once() {
+            let mut engine = PlaybackEngine::new();
+            engine.set_eq_preset(0);
+            engine.set_eq_preset(3);
+            engine.set_eq_preset(3);
+            assert_eq!(engine.eq_preset(), 3);
+            assert_eq!(
+                drain(&mut engine),
+                [PlaybackEvent::EqChanged { preset: 3 }]
+            );
+        }
+
+        #[test]
+        fn test_underrun_queues_buffer_underrun() {
+            let mut engine = PlaybackEngine::new();
+            engine.play().expect("play should succeed");
+            drain(&mut engine);
+
+            assert_eq!(engine.underrun(480), RecoveryAction::Continue);
+            assert_eq!(engine.state(), PlaybackState::Playing);
+            assert_eq!(
+                drain(&mut engine),
+                [PlaybackEvent::BufferUnderrun { missed_frames: 480 }]
+            );
+        }
+
+        #[test]
+        fn test_stop_decision_queues_fault_and_state_change() {
+            let mut engine = PlaybackEngine::new();
+            engine.play().expect("play should succeed");
+            drain(&mut engine);
+
+            let fault = PlaybackFault::Sink(SinkError::DeviceLost);
+            let action = engine.handle_fault(fault);
+            assert_eq!(action, RecoveryAction::Stop);
+            assert_eq!(
+                drain(&mut engine),
+                [
+                    PlaybackEvent::Fault { fault, action },
+                    PlaybackEvent::StateChanged(PlaybackState::Stopped),
+                ]
+            );
+        }
+
+        #[test]
+        fn test_publish_events_drains_into_the_bus() {
+            let bus = PlaybackEventBus::<NoopRawMutex>::new();
+            let publisher = PlaybackEventPublisher::new(&bus);
+            let mut subscriber = PlaybackEventSubscriber::new(&bus).expect("subscriber slot");
+
+            let mut engine = PlaybackEngine::new();
+            engine.load_track(1, 1_000);
+            engine.play().expect("play should succeed");
+            engine.publish_events(&publisher);
+            assert_eq!(engine.next_event(), None);
+
+            assert_eq!(
+                subscriber.try_next_event(),
+                Some(PlaybackEvent::TrackStarted {
+                    track_id: 1,
+                    duration_ms: 1_000
+                })
+            );
+            assert_eq!(
+                subscriber.try_next_event(),
+                Some(PlaybackEvent::StateChanged(PlaybackState::Playing))
+            );
+            assert_eq!(subscriber.try_next_event(), None);
+        }
+    }
+
+    mod chapter_tests {
+        use crate::engine::{PlaybackEngine, PREVIOUS_CHAPTER_GRACE_MS, RESUME_REWIND_MS};
+
+        const STARTS: [u32; 3] = [0, 60_000, 150_000];
+
+        #[test]
+        fn test_chapter_index_follows_position() {
+            let mut engine = PlaybackEngine::with_duration(200_000);
+            engine.seek_ms(59_999);
+            assert_eq!(engine.chapter_index(&STARTS), Some(0));
+            engine.seek_ms(60_000);
+            assert_eq!(engine.chapter_index(&STARTS), Some(1));
+            assert_eq!(engine.chapter_index(&[]), None);
+        }
+
+        #[test]
+        fn test_next_chapter_seeks_to_start() {
+            let mut engine = PlaybackEngine::with_duration(200_000);
+            engine.seek_ms(10_000);
+            assert_eq!(engine.next_chapter(&STARTS), Some(1));
+            assert_eq!(engine.position_ms(), 60_000);
+            assert_eq!(engine.next_chapter(&STARTS), Some(2));
+            assert_eq!(engine.next_chapter(&STARTS), None);
+            assert_eq!(engine.position_ms(), 150_000);
+        }
+
+        #[test]
+        fn test_previous_chapter_restarts_then_goes_back() {
+            let mut engine = PlaybackEngine::with_duration(200_000);
+            engine.seek_ms(100_000);
+            assert_eq!(engine.previous_chapter(&STARTS), Some(1));
+            assert_eq!(engine.position_ms(), 60_000);
+            // Pressed again right away: the chapter before.
+            engine.seek_ms(60_000 + PREVIOUS_CHAPTER_GRACE_MS - 1);
+            assert_eq!(engine.previous_chapter(&STARTS), Some(0));
+            assert_eq!(engine.position_ms(), 0);
+            assert_eq!(engine.previous_chapter(&STARTS), Some(0));
+        }
+
+        #[test]
+        fn test_resume_rewinds_within_chapter() {
+            let mut engine = PlaybackEngine::with_duration(200_000);
+            engine.resume_at(100_000, &STARTS);
+            assert_eq!(engine.position_ms(), 100_000 - RESUME_REWIND_MS);
+            // Just after a chapter mark: stop at the mark.
+            engine.resume_at(61_000, &STARTS);
+            assert_eq!(engine.position_ms(), 60_000);
+            engine.resume_at(2_000, &[]);
+            assert_eq!(engine.position_ms(), 0);
+        }
+    }
+
+    /// Playback speed tests
+    mod speed_tests {
+        use crate::engine::PlaybackEngine;
+        use platform::{ContentKind, PlaybackSpeed, SpeedScope};
+
+        #[test]
+        fn test_music_ignores_the_speed_by_default() {
+            let mut engine = PlaybackEngine::with_duration(60_000);
+            engine.set_speed(PlaybackSpeed::MAX);
+            assert_eq!(engine.speed(), PlaybackSpeed::MAX);
+            assert_eq!(engine.content(), ContentKind::Music);
+            assert_eq!(engine.effective_speed(), PlaybackSpeed::NORMAL);
+            assert_eq!(engine.remaining_wall_ms(), 60_000);
+        }
+
+        #[test]
+        fn test_spoken_word_follows_the_speed() {
+            let mut engine = PlaybackEngine::with_duration(60_000);
+            engine.set_speed(PlaybackSpeed::MAX);
+            engine.set_content(ContentKind::Podcast);
+            assert_eq!(engine.effective_speed(), PlaybackSpeed::MAX);
+            engine.seek_ms(20_000);
+            assert_eq!(engine.remaining_wall_ms(), 20_000);
+
+            engine.set_speed_scope(SpeedScope {
+                podcast: false,
+                ..SpeedScope::SPOKEN_WORD
+            });
+            assert_eq!(engine.effective_speed(), PlaybackSpeed::NORMAL);
+            // The choice is kept for the next audiobook.
+            engine.set_content(ContentKind::Audiobook);
+            assert_eq!(engine.effective_speed(), PlaybackSpeed::MAX);
+        }
+
+        #[test]
+        fn test_remaining_wall_time_unknown_without_duration() {
+            let mut engine = PlaybackEngine::new();
+            engine.set_content(ContentKind::Audiobook);
+            engine.set_speed(PlaybackSpeed::MIN);
+            assert_eq!(engine.remaining_wall_ms(), u64::MAX);
+        }
+    }
+
+    /// Silence trimming tests
+    mod silence_tests {
+        use crate::silence::SilenceTrim;
+        use platform::SilenceTrimConfig;
+
+        const RATE: u32 = 44_100;
+        /// 100 ms at 44.1 kHz.
+        const MIN_FRAMES: usize = 4_410;
+        /// Vinyl surface noise around −70 dBFS, under the −60 dB threshold.
+        const HISS: i32 = 600_000;
+        const TONE: i32 = 1 << 28;
+
+        fn trim(enabled: bool, channels: u8) -> SilenceTrim {
+            let mut trim = SilenceTrim::new(SilenceTrimConfig {
+                enabled,
+                threshold_db: -60,
+                min_ms: 100,
+            });
+            trim.start_track(RATE, channels);
+            trim
+        }
+
+        fn hiss(frames: usize) -> Vec<i32> {
+            (0..frames)
+                .map(|i| if i % 2 == 0 { HISS } else { -HISS / 2 })
+                .collect()
+        }
+
+        fn tone(frames: usize) -> Vec<i32> {
+            (0..frames)
+                .map(|i| if i % 20 < 10 { TONE } else { -TONE })
+                .collect()
+        }
+
+        /// Run `input` through `trim` in decoder-sized blocks, draining into
+        /// an `out_len`-sample buffer, then finish the track.
+        fn run(trim: &mut SilenceTrim, input: &[i32], out_len: usize) -> (Vec<i32>, u32) {
+            let mut out = Vec::new();
+            let mut buf = vec![0i32; out_len];
+            for block in input.chunks(1152) {
+                let mut at = 0;
+                while at < block.len() {
+                    let (consumed, produced) = trim.process(&block[at..], &mut buf);
+                    out.extend_from_slice(&buf[..produced]);
+                    at += consumed;
+                    assert!(consumed + produced > 0, "no progress");
+                }
+            }
+            (out, trim.finish())
+        }
+
+        #[test]
+        fn test_disabled_trim_passes_everything() {
+            let input = [hiss(MIN_FRAMES * 3), tone(500)].concat();
+            let (out, trailing) = run(&mut trim(false, 1), &input, 1024);
+            assert_eq!(out, input);
+            assert_eq!(trailing, 0);
+        }
+
+        #[test]
+        fn test_long_leading_silence_is_dropped() {
+            let input = [hiss(MIN_FRAMES * 5), tone(1_000)].concat();
+            let mut t = trim(true, 1);
+            let (out, _) = run(&mut t, &input, 1024);
+            assert_eq!(out, tone(1_000));
+            assert_eq!(t.leading_frames() as usize, MIN_FRAMES * 5);
+        }
+
+        #[test]
+        fn test_short_leading_silence_keeps_its_length() {
+            let input = [hiss(MIN_FRAMES / 2), tone(1_000)].concat();
+            let mut t = trim(true, 1);
+            let (out, _) = run(&mut t, &input, 1024);
+            assert_eq!(out.len(), input.len());
+            assert!(out[..MIN_FRAMES / 2].iter().all(|&s| s == 0));
+            assert_eq!(&out[MIN_FRAMES / 2..], &tone(1_000)[..]);
+            assert_eq!(t.leading_frames(), 0);
+        }
+
+        #[test]
+        fn test_pause_inside_a_track_keeps_its_length() {
+            let gap = MIN_FRAMES * 3;
+            let input = [tone(1_000), hiss(gap), tone(1_000)].concat();
+            let (out, trailing) = run(&mut trim(true, 1), &input, 1024);
+            assert_eq!(out.len(), input.len());
+            assert_eq!(trailing, 0);
+            // The first `min_ms` is the decoded noise, the rest is silence.
+            let pause = &out[1_000..1_000 + gap];
+            assert_eq!(&pause[..MIN_FRAMES], &hiss(gap)[..MIN_FRAMES]);
+            assert!(pause[MIN_FRAMES..].iter().all(|&s| s == 0));
+            assert_eq!(&out[1_000 + gap..], &tone(1_000)[..]);
+        }
+
+        #[test]
+        fn test_trailing_silence_is_dropped() {
+            let input = [tone(2_000), hiss(RATE as usize)].concat();
+            let (out, trailing) = run(&mut trim(true, 1), &input, 1024);
+            assert_eq!(out.len(), 2_000 + MIN_FRAMES);
+            assert_eq!(trailing as usize, RATE as usize - MIN_FRAMES);
+        }
+
+        #[test]
+        fn test_stereo_silence_needs_both_channels_quiet() {
+            // Left quiet, right playing: not silence.
+            let one_side: Vec<i32> = tone(MIN_FRAMES * 2)
+                .into_iter()
+                .flat_map(|s| [HISS, s])
+                .collect();
+            let (out, trailing) = run(&mut trim(true, 2), &one_side, 1024);
+            assert_eq!(out, one_side);
+            assert_eq!(trailing, 0);
+            let quiet: Vec<i32> = hiss(MIN_FRAMES * 2)
+                .into_iter()
+                .flat_map(|s| [s, -s])
+                .collect();
+            let (out, trailing) = run(&mut trim(true, 2), &quiet, 1024);
+            assert!(out.is_empty());
+            assert_eq!(
+                trailing as usize,
+                MIN_FRAMES * 2,
+                "silent track dropped whole"
+            );
+        }
+
+        #[test]
+        fn test_output_buffer_size_does_not_matter() {
+            let input = [
+                hiss(MIN_FRAMES / 3),
+                tone(3_000),
+                hiss(MIN_FRAMES * 2),
+                tone(700),
+                hiss(MIN_FRAMES * 2),
+            ]
+            .concat();
+            let (big, big_trailing) = run(&mut trim(true, 1), &input, 4096);
+            let (small, small_trailing) = run(&mut trim(true, 1), &input, 7);
+            assert_eq!(big, small);
+            assert_eq!(big_trailing, small_trailing);
+        }
+    }
+
+    /// Time-stretch tests
+    #[allow(clippy::cast_possible_truncation)] // test signals are well inside i32
+    mod time_stretch_tests {
+        use crate::time_stretch::{TimeStretch, HOP};
+        use platform::PlaybackSpeed;
+
+        const RATE: f64 = 44_100.0;
+        const AMPLITUDE: f64 = (1 << 30) as f64;
+
+        fn speed(percent: u8) -> PlaybackSpeed {
+            PlaybackSpeed::new(percent).expect("supported speed")
+        }
+
+        /// `frames` of a `hz` sine, interleaved over `channels`.
+        fn sine(hz: f64, frames: usize, channels: usize) -> Vec<i32> {
+            (0..frames)
+                .flat_map(|i| {
+                    let phase = 2.0 * core::f64::consts::PI * hz * i as f64 / RATE;
+                    let s = (AMPLITUDE * phase.sin()) as i32;
+                    core::iter::repeat_n(s, channels)
+                })
+                .collect()
+        }
+
+        /// Run `input` through `ts` in decoder-sized blocks and collect
+        /// everything it produces.
+        fn stretch(ts: &mut TimeStretch, input: &[i32]) -> Vec<i32> {
+            let mut out = Vec::new();
+            let mut buf = vec![0i32; 1024];
+            for block in input.chunks(1152 * ts.channels()) {
+                let mut at = 0;
+                loop {
+                    let (consumed, produced) = ts.process(&block[at..], &mut buf);
+                    out.extend_from_slice(&buf[..produced]);
+                    at += consumed;
+                    if consumed == 0 && produced == 0 {
+                        break;
+                    }
+                }
+                assert_eq!(at, block.len(), "every block is taken in full");
+            }
+            out
+        }
+
+        /// Rising zero crossings per second of channel 0.
+        fn frequency(samples: &[i32], channels: usize) -> f64 {
+            let mono: Vec<i32> = samples.iter().step_by(channels).copied().collect();
+            let rising = mono.windows(2).filter(|w| w[0] < 0 && w[1] >= 0).count();
+            rising as f64 * RATE / mono.len() as f64
+        }
+
+        #[test]
+        fn test_normal_speed_passes_samples_through() {
+            let input = sine(441.0, 10_000, 2);
+            let mut ts = TimeStretch::new(2);
+            assert!(ts.is_bypassed());
+            assert_eq!(stretch(&mut ts, &input), input);
+        }
+
+        #[test]
+        fn test_output_length_follows_the_speed() {
+            for (percent, channels) in [(150, 2), (200, 1), (80, 2)] {
+                let frames = 44_100;
+                let input = sine(220.0, frames, usize::from(channels));
+                let mut ts = TimeStretch::new(channels);
+                ts.set_speed(speed(percent));
+                let out = stretch(&mut ts, &input);
+                let expected = frames * 100 / usize::from(percent);
+                let produced = out.len() / usize::from(channels);
+                assert!(
+                    produced.abs_diff(expected) < 4 * HOP,
+                    "{percent}%: {produced} frames, expected about {expected}"
+                );
+            }
+        }
+
+        #[test]
+        fn test_pitch_is_preserved() {
+            for percent in [80, 125, 200] {
+                for channels in [1, 2] {
+                    let input = sine(441.0, 88_200, usize::from(channels));
+                    let mut ts = TimeStretch::new(channels);
+                    ts.set_speed(speed(percent));
+                    let out = stretch(&mut ts, &input);
+                    let hz = frequency(&out[HOP * ts.channels()..], ts.channels());
+                    assert!(
+                        (hz - 441.0).abs() < 441.0 * 0.02,
+                        "{percent}% x{channels}: {hz:.1} Hz"
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn test_stretched_output_has_no_clicks() {
+            let input = sine(441.0, 44_100, 2);
+            // A 441 Hz sine moves at most 2π/100 of its amplitude per sample.
+            let max_step = (AMPLITUDE * 0.1) as i64;
+            for percent in [90, 150, 190] {
+                let mut ts = TimeStretch::new(2);
+                ts.set_speed(speed(percent));
+                let out = stretch(&mut ts, &input);
+                let worst = out
+                    .iter()
+                    .step_by(2)
+                    .collect::<Vec<_>>()
+                    .windows(2)
+                    .map(|w| (i64::from(*w[1]) - i64::from(*w[0])).abs())
+                    .max()
+                    .unwrap_or(0);
+                assert!(worst < max_step, "{percent}%: step of {worst}");
+            }
+        }
+
+        #[test]
+        fn test_back_to_normal_speed_keeps_the_audio_flowing() {
+            let input = sine(441.0, 44_100, 2);
+            let (first, second) = input.split_at(22_050 * 2);
+            let mut ts = TimeStretch::new(2);
+            ts.set_speed(speed(150));
+            let fast = stretch(&mut ts, first);
+
+            ts.set_speed(PlaybackSpeed::NORMAL);
+            assert!(!ts.is_bypassed(), "buffered audio is not dropped");
+            let normal = stretch(&mut ts, second);
+            let frames = normal.len() / 2;
+            assert!(frames.abs_diff(22_050) < 4 * HOP, "{frames} frames");
+
+            // No jump where the two runs meet.
+            let joined: Vec<i32> = fast.iter().chain(&normal).step_by(2).copied().collect();
+            let at = fast.len() / 2;
+            let step = (i64::from(joined[at]) - i64::from(joined[at - 1])).abs();
+            assert!(step < (AMPLITUDE * 0.1) as i64);
+
+            ts.reset();
+            assert!(ts.is_bypassed());
+        }
+    }
+
     /// Ring buffer tests
     mod ring_buffer_tests {
         use crate::ring_buffer::RingBuffer;
@@ -185,6 +1069,830 @@ mod tests {
             assert_eq!(&rest[..4], &[1i32; 4]);
             assert_eq!(&rest[4..], &[2i32; 4]);
         }
+
+        #[test]
+        fn test_ring_buffer_linear_slices_split_at_wrap() {
+            let mut rb: RingBuffer<8> = RingBuffer::new();
+            let (mut tx, mut rx) = rb.split();
+            tx.write_slice(&[0; 6]).expect("fill");
+            rx.consume(6);
+            tx.write_slice(&[1, 2, 3, 4]).expect("wrapping write");
+
+            let (first, second) = rx.as_linear_slices();
+            assert_eq!(first, &[1, 2]);
+            assert_eq!(second, &[3, 4]);
+            assert_eq!(rx.linear_chunk(2), Some(&[1, 2][..]));
+            assert_eq!(rx.linear_chunk(3), None);
+        }
+
+        #[test]
+        fn test_ring_buffer_zero_copy_producer_commit() {
+            let mut rb: RingBuffer<8> = RingBuffer::new();
+            let (mut tx, mut rx) = rb.split();
+            let (first, second) = tx.as_linear_slices_mut();
+            assert_eq!((first.len(), second.len()), (8, 0));
+            first[..3].copy_from_slice(&[7, 8, 9]);
+            // Nothing is visible until committed.
+            assert_eq!(rx.available(), 0);
+            tx.commit(3);
+            assert_eq!(rx.linear_chunk(3), Some(&[7, 8, 9][..]));
+            rx.consume(3);
+            assert_eq!(tx.free(), 8);
+        }
+
+        #[test]
+        fn test_ring_buffer_commit_and_consume_clamp() {
+            let mut rb: RingBuffer<4> = RingBuffer::new();
+            let (mut tx, mut rx) = rb.split();
+            tx.commit(100);
+            assert_eq!(rx.available(), 4);
+            rx.consume(100);
+            assert_eq!(rx.available(), 0);
+        }
+
+        #[test]
+        fn test_ring_buffer_spsc_across_threads() {
+            const TOTAL: i32 = 100_000;
+            let mut rb: RingBuffer<64> = RingBuffer::new();
+            let (mut tx, mut rx) = rb.split();
+            std::thread::scope(|s| {
+                s.spawn(move || {
+                    let mut next = 0;
+                    while next < TOTAL {
+                        let (first, _) = tx.as_linear_slices_mut();
+                        let n = first.len().min(7);
+                        for slot in &mut first[..n] {
+                            *slot = next;
+                            next += 1;
+                        }
+                        tx.commit(n);
+                    }
+                });
+                let mut expected = 0;
+                let mut buf = [0i32; 5];
+                while expected < TOTAL {
+                    let n = rx.read_slice(&mut buf);
+                    for &v in &buf[..n] {
+                        assert_eq!(v, expected);
+                        expected += 1;
+                    }
+                }
+            });
+        }
+    }
+
+    /// FLAC LPC restoration tests
+    mod flac_lpc_tests {
+        use crate::decoder::DecodeError;
+        use crate::flac_lpc::{
+            lpc_fits_i32, restore_fixed, restore_lpc, restore_lpc_reference, MAX_LPC_ORDER,
+        };
+        use util::Rng;
+
+        /// Uniform value in the signed `bits`-bit range.
+        #[allow(clippy::cast_possible_wrap)] // bits <= 24, so both fit in i32
+        fn signed(rng: &mut Rng, bits: u32) -> i32 {
+            let half = 1u32 << (bits - 1);
+            rng.below(2 * half) as i32 - half as i32
+        }
+
+        /// Forward prediction, as a FLAC encoder would: returns the warm-up
+        /// samples followed by residuals.
+        #[allow(clippy::cast_possible_truncation)] // residuals wrap like the decoder
+        fn encode(signal: &[i32], coeffs: &[i32], shift: u32) -> Vec<i32> {
+            let order = coeffs.len();
+            let mut out = signal.to_vec();
+            for i in order..signal.len() {
+                let pred: i64 = coeffs
+                    .iter()
+                    .enumerate()
+                    .map(|(j, &c)| i64::from(c) * i64::from(signal[i - 1 - j]))
+                    .sum();
+                out[i] = signal[i].wrapping_sub((pred >> shift) as i32);
+            }
+            out
+        }
+
+        #[test]
+        fn test_fast_path_matches_reference_all_orders() {
+            let mut rng = Rng::seed_from_u64(0xF1AC);
+            for (bps, precision, shift) in [(16, 12, 10), (24, 15, 14), (8, 4, 2)] {
+                for order in 1..=MAX_LPC_ORDER {
+                    let taps: Vec<i32> = (0..order).map(|_| signed(&mut rng, precision)).collect();
+                    let signal: Vec<i32> = (0..1024).map(|_| signed(&mut rng, bps)).collect();
+                    let residual = encode(&signal, &taps, shift);
+
+                    let mut reference = residual.clone();
+                    restore_lpc_reference(&mut reference, &taps, shift).expect("reference");
+                    assert_eq!(reference, signal, "reference bps={bps} order={order}");
+
+                    let mut fast = residual;
+                    restore_lpc(&mut fast, &taps, shift, bps).expect("fast");
+                    assert_eq!(fast, signal, "fast bps={bps} order={order}");
+                }
+            }
+        }
+
+        #[test]
+        fn test_fixed_order_2_restores_ramp() {
+            // x[n] = 3n: second-order residual of a ramp is zero after warm-up.
+            let mut samples = vec![0, 3, 0, 0, 0, 0];
+            restore_fixed(&mut samples, 2, 16).expect("fixed");
+            assert_eq!(samples, [0, 3, 6, 9, 12, 15]);
+
+            let mut passthrough = vec![5, -5];
+            restore_fixed(&mut passthrough, 0, 16).expect("order 0");
+            assert_eq!(passthrough, [5, -5]);
+        }
+
+        #[test]
+        fn test_i32_accumulator_selection() {
+            assert!(lpc_fits_i32(&[2047; 8], 16));
+            assert!(!lpc_fits_i32(&[16_383; 12], 24));
+        }
+
+        #[test]
+        fn test_invalid_parameters_rejected() {
+            let mut samples = [0i32; 4];
+            assert_eq!(
+                restore_lpc(&mut samples, &[], 0, 16),
+                Err(DecodeError::InvalidData)
+            );
+            assert_eq!(
+                restore_lpc(&mut samples, &[1; 5], 0, 16),
+                Err(DecodeError::InvalidData)
+            );
+            assert_eq!(
+                restore_lpc(&mut samples, &[1], 32, 16),
+                Err(DecodeError::InvalidData)
+            );
+            assert_eq!(
+                restore_fixed(&mut samples, 5, 16),
+                Err(DecodeError::InvalidData)
+            );
+        }
+    }
+
+    /// FLAC frame decoder tests (full streams: tests/conformance.rs)
+    mod flac_decoder_tests {
+        use crate::decoder::{DecodeError, FrameDecoder, PcmFrame};
+        use crate::flac_decoder::{FlacDecoder, StreamInfo, CODEC};
+
+        /// `fLaC` + last-block STREAMINFO: 16-sample blocks, 44.1 kHz,
+        /// `channels`, `bits` per sample, 16 samples in total.
+        fn head(channels: u8, bits: u8) -> Vec<u8> {
+            let mut out = b"fLaC".to_vec();
+            out.extend_from_slice(&[0x80, 0, 0, 34]);
+            out.extend_from_slice(&[0, 16, 0, 16, 0, 0, 0, 0, 0, 0]);
+            // rate (20 bits) | channels - 1 (3) | bits - 1 (5) | total (36)
+            let packed = (44_100u64 << 44)
+                | (u64::from(channels - 1) << 41)
+                | (u64::from(bits - 1) << 36)
+                | 16;
+            out.extend_from_slice(&packed.to_be_bytes());
+            out.extend_from_slice(&[0; 16]); // MD5
+            out
+        }
+
+        /// One mono 16-bit frame: 16 samples (8-bit explicit block size),
+        /// a CONSTANT subframe of 0x1234, CRC-8 and CRC-16.
+        const CONSTANT_FRAME: [u8; 12] = [
+            0xFF, 0xF8, 0x69, 0x08, 0x00, 0x0F, 0x30, 0x00, 0x12, 0x34, 0xAA, 0x3B,
+        ];
+
+        #[test]
+        fn test_stream_info_parsed_from_head() {
+            let info = StreamInfo::parse(&head(2, 24)).expect("streaminfo");
+            assert_eq!(info.max_block_size, 16);
+            assert_eq!(info.sample_rate, 44_100);
+            assert_eq!((info.channels, info.bits_per_sample), (2, 24));
+            assert_eq!(info.total_samples, 16);
+            assert_eq!(StreamInfo::parse(b"RIFF"), None);
+        }
+
+        #[test]
+        fn test_metadata_then_constant_frame() {
+            let mut stream = head(1, 16);
+            stream.extend_from_slice(&CONSTANT_FRAME);
+            let mut decoder = FlacDecoder::new();
+            let mut frame = PcmFrame::default();
+
+            // Marker, then STREAMINFO: consumed without samples.
+            assert_eq!(decoder.decode_frame(&stream, &mut frame), Ok(4));
+            assert_eq!(frame.len, 0);
+            assert_eq!(decoder.decode_frame(&stream[4..], &mut frame), Ok(38));
+            assert_eq!(frame.len, 0);
+            assert_eq!(decoder.sample_rate(), 44_100);
+
+            let rest = &stream[42..];
+            assert_eq!(decoder.decode_frame(rest, &mut frame), Ok(12));
+            assert_eq!((frame.len, frame.channels), (16, 1));
+            assert!(frame.samples[..16].iter().all(|&s| s == 0x1234 << 16));
+        }
+
+        #[test]
+        fn test_corrupt_frame_rejected() {
+            let mut corrupt = CONSTANT_FRAME;
+            corrupt[9] ^= 1; // sample bits: CRC-16 mismatch
+            let mut frame = PcmFrame::default();
+            assert_eq!(
+                FlacDecoder::new().decode_frame(&corrupt, &mut frame),
+                Err(DecodeError::InvalidData)
+            );
+            let mut truncated = FlacDecoder::new();
+            assert_eq!(
+                truncated.decode_frame(&CONSTANT_FRAME[..10], &mut frame),
+                Err(DecodeError::EndOfStream)
+            );
+        }
+
+        #[test]
+        fn test_junk_before_frame_skipped() {
+            let mut stream = vec![0x00, 0x42, 0xFF];
+            stream.extend_from_slice(&CONSTANT_FRAME);
+            let mut decoder = FlacDecoder::new();
+            let mut frame = PcmFrame::default();
+            assert_eq!(decoder.decode_frame(&stream, &mut frame), Ok(3));
+            assert_eq!(frame.len, 0);
+            assert_eq!(decoder.decode_frame(&stream[3..], &mut frame), Ok(12));
+            assert_eq!(frame.len, 16);
+        }
+
+        #[test]
+        fn test_open_rejects_unsupported_streams() {
+            let open = CODEC.open.expect("flac constructor");
+            let mut ran = false;
+            for (channels, bits) in [(6, 16), (2, 32)] {
+                assert_eq!(
+                    open(&head(channels, bits), &mut |_| ran = true),
+                    Err(DecodeError::UnsupportedFormat)
+                );
+            }
+            assert!(!ran);
+            assert_eq!(open(&head(2, 16), &mut |_| ran = true), Ok(()));
+            assert!(ran);
+        }
+    }
+
+    /// Per-frame timing tests
+    mod frame_timing_tests {
+        use crate::decoder::{DecodeError, FrameDecoder, PcmFrame};
+        use crate::frame_timing::{time_frame, FrameStats};
+
+        /// Consumes 4 bytes per frame; fails on empty input.
+        struct FixedDecoder;
+
+        impl FrameDecoder for FixedDecoder {
+            type Error = DecodeError;
+
+            fn decode_frame(
+                &mut self,
+                input: &[u8],
+                output: &mut PcmFrame,
+            ) -> Result<usize, DecodeError> {
+                if input.is_empty() {
+                    return Err(DecodeError::EndOfStream);
+                }
+                output.len = 1;
+                Ok(input.len().min(4))
+            }
+
+            fn sample_rate(&self) -> u32 {
+                44_100
+            }
+
+            fn channels(&self) -> u8 {
+                2
+            }
+        }
+
+        #[test]
+        fn test_stats_empty() {
+            let stats = FrameStats::new();
+            assert_eq!(stats.frames(), 0);
+            assert_eq!(stats.min(), None);
+            assert_eq!(stats.max(), 0);
+            assert_eq!(stats.mean(), 0);
+        }
+
+        #[test]
+        fn test_stats_min_max_mean() {
+            let mut stats = FrameStats::new();
+            for ticks in [300, 100, 200] {
+                stats.record(ticks);
+            }
+            assert_eq!(stats.frames(), 3);
+            assert_eq!(stats.min(), Some(100));
+            assert_eq!(stats.max(), 300);
+            assert_eq!(stats.mean(), 200);
+            stats.reset();
+            assert_eq!(stats, FrameStats::default());
+        }
+
+        #[test]
+        fn test_time_frame_handles_counter_wrap() {
+            let mut ticks = [u32::MAX - 9, 40].into_iter();
+            let mut stats = FrameStats::new();
+            let mut frame = PcmFrame::default();
+            let consumed = time_frame(&mut FixedDecoder, &[0; 8], &mut frame, &mut stats, || {
+                ticks.next().unwrap_or(0)
+            })
+            .expect("decode");
+            assert_eq!(consumed, 4);
+            assert_eq!(stats.max(), 50);
+        }
+
+        #[test]
+        fn test_failed_decode_not_recorded() {
+            let mut stats = FrameStats::new();
+            let mut frame = PcmFrame::default();
+            let result = time_frame(&mut FixedDecoder, &[], &mut frame, &mut stats, || 0);
+            assert_eq!(result, Err(DecodeError::EndOfStream));
+            assert_eq!(stats.frames(), 0);
+        }
+    }
+
+    /// Fault taxonomy and recovery policy tests
+    mod fault_tests {
+        use crate::decoder::DecodeError;
+        use crate::engine::{PlaybackEngine, PlaybackState};
+        use crate::fault::{
+            PlaybackFault, Recoverability, RecoveryAction, RecoveryPolicy, RecoveryTracker,
+            SinkError, StorageError,
+        };
+
+        const CORRUPT: PlaybackFault = PlaybackFault::Decode(DecodeError::InvalidData);
+        const TIMEOUT: PlaybackFault = PlaybackFault::Storage(StorageError::Timeout);
+
+        #[test]
+        fn test_classification() {
+            assert_eq!(CORRUPT.recoverability(), Recoverability::Transient);
+            assert_eq!(
+                PlaybackFault::from(StorageError::NotFound).recoverability(),
+                Recoverability::Track
+            );
+            assert_eq!(
+                PlaybackFault::from(SinkError::DeviceLost).recoverability(),
+                Recoverability::Fatal
+            );
+            assert_eq!(
+                PlaybackFault::from(DecodeError::BufferTooSmall).recoverability(),
+                Recoverability::Fatal
+            );
+        }
+
+        #[test]
+        fn test_persistent_decode_failure_skips_track() {
+            let mut tracker = RecoveryTracker::default();
+            let max = RecoveryPolicy::DEFAULT.max_decode_errors;
+            for _ in 1..max {
+                assert_eq!(tracker.on_fault(CORRUPT), RecoveryAction::Continue);
+            }
+            assert_eq!(tracker.on_fault(CORRUPT), RecoveryAction::SkipTrack);
+            assert_eq!(tracker.decode_errors(), 0, "reset for the next track");
+        }
+
+        #[test]
+        fn test_good_frame_resets_decode_errors() {
+            let mut tracker = RecoveryTracker::default();
+            for _ in 0..100 {
+                assert_eq!(tracker.on_fault(CORRUPT), RecoveryAction::Continue);
+                tracker.on_frame_ok();
+            }
+        }
+
+        #[test]
+        fn test_storage_retry_backoff_then_skip() {
+            let mut tracker = RecoveryTracker::new(RecoveryPolicy {
+                max_storage_retries: 2,
+                retry_step_ms: 40,
+                ..RecoveryPolicy::DEFAULT
+            });
+            assert_eq!(
+                tracker.on_fault(TIMEOUT),
+                RecoveryAction::Retry {
+                    attempt: 1,
+                    delay_ms: 40
+                }
+            );
+            assert_eq!(
+                tracker.on_fault(PlaybackFault::Storage(StorageError::Io)),
+                RecoveryAction::Retry {
+                    attempt: 2,
+                    delay_ms: 80
+                }
+            );
+            assert_eq!(tracker.on_fault(TIMEOUT), RecoveryAction::SkipTrack);
+            // The next track gets a fresh retry budget.
+            assert!(matches!(
+                tracker.on_fault(TIMEOUT),
+                RecoveryAction::Retry { attempt: 1, .. }
+            ));
+        }
+
+        #[test]
+        fn test_underrun_continues_and_is_counted() {
+            let mut tracker = RecoveryTracker::default();
+            let underrun = PlaybackFault::Sink(SinkError::Underrun);
+            assert_eq!(tracker.on_fault(underrun), RecoveryAction::Continue);
+            assert_eq!(tracker.on_fault(underrun), RecoveryAction::Continue);
+            assert_eq!(tracker.underruns(), 2);
+            assert!(!underrun.is_reportable(RecoveryAction::Continue));
+        }
+
+        #[test]
+        fn test_end_of_stream_is_not_reported() {
+            let eos = PlaybackFault::Decode(DecodeError::EndOfStream);
+            let mut tracker = RecoveryTracker::default();
+            let action = tracker.on_fault(eos);
+            assert_eq!(action, RecoveryAction::SkipTrack);
+            assert!(!eos.is_reportable(action));
+            assert!(TIMEOUT.is_reportable(RecoveryAction::SkipTrack));
+        }
+
+        #[test]
+        fn test_engine_stops_on_sink_loss() {
+            let mut engine = PlaybackEngine::new();
+            engine.play().expect("play");
+            engine.seek_ms(5_000);
+            let action = engine.handle_fault(SinkError::DeviceLost.into());
+            assert_eq!(action, RecoveryAction::Stop);
+            assert_eq!(engine.state(), PlaybackState::Stopped);
+            assert_eq!(engine.position_ms(), 0);
+        }
+
+        #[test]
+        fn test_engine_skip_keeps_playing() {
+            let mut engine = PlaybackEngine::new();
+            engine.play().expect("play");
+            engine.seek_ms(5_000);
+            let action = engine.handle_fault(StorageError::NotFound.into());
+            assert_eq!(action, RecoveryAction::SkipTrack);
+            assert_eq!(engine.state(), PlaybackState::Playing);
+            assert_eq!(engine.position_ms(), 0);
+        }
+
+        #[test]
+        fn test_engine_policy_override() {
+            let mut engine = PlaybackEngine::new();
+            engine.set_recovery_policy(RecoveryPolicy {
+                max_decode_errors: 1,
+                ..RecoveryPolicy::DEFAULT
+            });
+            assert_eq!(engine.handle_fault(CORRUPT), RecoveryAction::SkipTrack);
+            engine.frame_ok();
+            assert_eq!(engine.recovery().decode_errors(), 0);
+        }
+    }
+
+    /// Event bus tests
+    mod events_tests {
+        use crate::engine::PlaybackState;
+        use crate::events::{
+            PlaybackEvent, PlaybackEventBus, PlaybackEventPublisher, PlaybackEventSubscriber,
+            EVENT_CAPACITY, MAX_SUBSCRIBERS,
+        };
+        use embassy_futures::block_on;
+        use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+
+        #[test]
+        fn test_every_subscriber_receives_each_event() {
+            let bus: PlaybackEventBus<NoopRawMutex> = PlaybackEventBus::new();
+            let publisher = PlaybackEventPublisher::new(&bus);
+            let mut ui = PlaybackEventSubscriber::new(&bus).expect("ui subscriber");
+            let mut avrcp = PlaybackEventSubscriber::new(&bus).expect("avrcp subscriber");
+
+            let event = PlaybackEvent::StateChanged(PlaybackState::Playing);
+            publisher.publish(event);
+
+            assert_eq!(block_on(ui.next_event()), event);
+            assert_eq!(avrcp.try_next_event(), Some(event));
+            assert_eq!(ui.try_next_event(), None);
+        }
+
+        #[test]
+        #[allow(clippy::cast_possible_truncation)] // EVENT_CAPACITY is a small constant
+        fn test_slow_subscriber_reports_lag_instead_of_blocking() {
+            let bus: PlaybackEventBus<NoopRawMutex> = PlaybackEventBus::new();
+            let publisher = PlaybackEventPublisher::new(&bus);
+            let mut slow = PlaybackEventSubscriber::new(&bus).expect("subscriber");
+
+            let extra = 3u32;
+            for ms in 0..(EVENT_CAPACITY as u32 + extra) {
+                publisher.publish(PlaybackEvent::PositionTick { position_ms: ms });
+            }
+
+            assert_eq!(
+                slow.try_next_event(),
+                Some(PlaybackEvent::PositionTick { position_ms: extra })
+            );
+            assert_eq!(slow.lagged(), u64::from(extra));
+        }
+
+        #[test]
+        fn test_subscriber_limit_enforced() {
+            let bus: PlaybackEventBus<NoopRawMutex> = PlaybackEventBus::new();
+            let subs: Vec<_> = (0..MAX_SUBSCRIBERS)
+                .map(|_| PlaybackEventSubscriber::new(&bus).expect("within limit"))
+                .collect();
+            assert!(PlaybackEventSubscriber::new(&bus).is_err());
+            drop(subs);
+        }
+    }
+
+    /// Play queue tests
+    mod queue_tests {
+        use crate::queue::{PlayQueue, QueueError};
+
+        fn queue_of(ids: &[u32]) -> PlayQueue<8> {
+            let mut q = PlayQueue::new();
+            for &id in ids {
+                q.enqueue(id).expect("enqueue within capacity");
+            }
+            q
+        }
+
+        #[test]
+        fn test_first_enqueue_becomes_current() {
+            let q = queue_of(&[10, 20]);
+            assert_eq!(q.current(), Some(10));
+            assert_eq!(q.current_index(), Some(0));
+        }
+
+        #[test]
+        fn test_enqueue_past_capacity_fails() {
+            let mut q: PlayQueue<2> = PlayQueue::new();
+            q.enqueue(1).expect("first");
+            q.enqueue(2).expect("second");
+            assert_eq!(q.enqueue(3), Err(QueueError::Full));
+        }
+
+        #[test]
+        fn test_remove_before_cursor_keeps_current_track() {
+            let mut q = queue_of(&[1, 2, 3]);
+            q.advance_to(2).expect("advance");
+            q.remove(0).expect("remove");
+            assert_eq!(q.current(), Some(3));
+        }
+
+        #[test]
+        fn test_remove_current_resets_position() {
+            let mut q = queue_of(&[1, 2, 3]);
+            q.set_position_ms(5_000);
+            q.remove(0).expect("remove");
+            assert_eq!(q.current(), Some(2));
+            assert_eq!(q.position_ms(), 0);
+        }
+
+        #[test]
+        fn test_reorder_cursor_follows_track() {
+            let mut q = queue_of(&[1, 2, 3, 4]);
+            q.advance_to(1).expect("advance");
+            q.reorder(1, 3).expect("reorder");
+            assert_eq!(q.tracks(), &[1, 3, 4, 2]);
+            assert_eq!(q.current(), Some(2));
+            q.reorder(3, 0).expect("reorder back");
+            assert_eq!(q.tracks(), &[2, 1, 3, 4]);
+            assert_eq!(q.current_index(), Some(0));
+        }
+
+        #[test]
+        fn test_shuffle_keeps_current_track_playing() {
+            let mut q = queue_of(&[1, 2, 3, 4, 5, 6, 7, 8]);
+            q.advance_to(5).expect("advance");
+            q.set_position_ms(9_000);
+            q.shuffle(&mut util::Rng::seed_from_u64(1));
+
+            assert_eq!(q.current_index(), Some(0));
+            assert_eq!(q.current(), Some(6));
+            assert_eq!(q.position_ms(), 9_000);
+            let mut sorted = q.tracks().to_vec();
+            sorted.sort_unstable();
+            assert_eq!(sorted, [1, 2, 3, 4, 5, 6, 7, 8]);
+        }
+
+        #[test]
+        fn test_shuffle_is_reproducible_from_seed() {
+            let mut a = queue_of(&[1, 2, 3, 4, 5, 6, 7, 8]);
+            let mut b = a.clone();
+            a.shuffle(&mut util::Rng::seed_from_u64(77));
+            b.shuffle(&mut util::Rng::seed_from_u64(77));
+            assert_eq!(a, b);
+            assert_ne!(a.tracks(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+        }
+
+        #[test]
+        fn test_out_of_range_index_rejected() {
+            let mut q = queue_of(&[1]);
+            assert_eq!(q.remove(1), Err(QueueError::OutOfRange));
+            assert_eq!(q.advance_to(5), Err(QueueError::OutOfRange));
+        }
+    }
+
+    /// Queue journal tests
+    mod queue_journal_tests {
+        use crate::queue_journal::{
+            JournalStore, MemoryJournalStore, QueueJournal, QueueRecord, RECORD_SIZE,
+        };
+        use embassy_futures::block_on;
+
+        type Store = MemoryJournalStore<4096>;
+
+        #[test]
+        fn test_record_roundtrip() {
+            let rec = QueueRecord::Reorder { from: 7, to: 2 };
+            assert_eq!(QueueRecord::decode(&rec.encode()), Some(rec));
+        }
+
+        #[test]
+        fn test_record_crc_mismatch_rejected() {
+            let mut bytes = QueueRecord::Enqueue(42).encode();
+            bytes[2] ^= 0xFF;
+            assert_eq!(QueueRecord::decode(&bytes), None);
+        }
+
+        #[test]
+        fn test_reopen_restores_queue_and_position() {
+            let (mut j, _) = block_on(QueueJournal::<_, 16>::open(Store::new())).expect("open");
+            block_on(async {
+                j.enqueue(100).await.expect("enqueue");
+                j.enqueue(200).await.expect("enqueue");
+                j.enqueue(300).await.expect("enqueue");
+                j.reorder(2, 0).await.expect("reorder");
+                j.advance_to(1).await.expect("advance");
+                j.checkpoint_position(12_345).await.expect("position");
+            });
+            let before = j.queue().clone();
+
+            let (restored, report) =
+                block_on(QueueJournal::<_, 16>::open(j.into_store())).expect("reopen");
+            assert_eq!(restored.queue(), &before);
+            assert_eq!(restored.queue().current(), Some(100));
+            assert_eq!(restored.queue().position_ms(), 12_345);
+            assert!(!report.torn_tail);
+        }
+
+        #[test]
+        fn test_shuffle_replays_to_same_order() {
+            let (mut j, _) = block_on(QueueJournal::<_, 16>::open(Store::new())).expect("open");
+            block_on(async {
+                for id in 1..=8 {
+                    j.enqueue(id).await.expect("enqueue");
+                }
+                j.advance_to(3).await.expect("advance");
+                j.shuffle(0xC0FF_EE00).await.expect("shuffle");
+            });
+            let before = j.queue().clone();
+            assert_eq!(before.current(), Some(4));
+
+            let (restored, _) =
+                block_on(QueueJournal::<_, 16>::open(j.into_store())).expect("reopen");
+            assert_eq!(restored.queue(), &before);
+        }
+
+        #[test]
+        fn test_torn_tail_is_discarded_and_compacted() {
+            let (mut j, _) = block_on(QueueJournal::<_, 16>::open(Store::new())).expect("open");
+            block_on(async {
+                j.enqueue(1).await.expect("enqueue");
+                j.enqueue(2).await.expect("enqueue");
+            });
+            let good = j.into_store();
+            let mut bytes = good.as_bytes().to_vec();
+            // Simulate a battery pull halfway through the third record.
+            let torn = QueueRecord::Enqueue(3).encode();
+            bytes.extend_from_slice(&torn[..RECORD_SIZE / 2]);
+
+            let (restored, report) =
+                block_on(QueueJournal::<_, 16>::open(Store::from_bytes(&bytes))).expect("open");
+            assert!(report.torn_tail);
+            assert_eq!(restored.queue().tracks(), &[1, 2]);
+            assert_eq!(restored.store().len() as usize % RECORD_SIZE, 0);
+        }
+
+        #[test]
+        fn test_compaction_shrinks_journal_without_changing_queue() {
+            let (j, _) = block_on(QueueJournal::<_, 16>::open(Store::new())).expect("open");
+            let mut j = j.with_compact_threshold(u32::MAX);
+            block_on(async {
+                for id in 0..8 {
+                    j.enqueue(id).await.expect("enqueue");
+                }
+                for _ in 0..4 {
+                    j.remove(0).await.expect("remove");
+                }
+            });
+            let before_len = j.store().len();
+            let before = j.queue().clone();
+            block_on(j.compact()).expect("compact");
+            assert!(j.store().len() < before_len);
+            assert_eq!(j.queue(), &before);
+
+            let (restored, _) =
+                block_on(QueueJournal::<_, 16>::open(j.into_store())).expect("reopen");
+            assert_eq!(restored.queue(), &before);
+        }
+
+        #[test]
+        fn test_failed_compaction_keeps_old_generation_writable() {
+            // Room for seven records: six enqueues fill the threshold, and
+            // their eight-record snapshot does not fit.
+            let store = MemoryJournalStore::<{ 7 * RECORD_SIZE }>::new();
+            let (j, _) = block_on(QueueJournal::<_, 16>::open(store)).expect("open");
+            let mut j = j.with_compact_threshold(6 * RECORD_SIZE as u32);
+            block_on(async {
+                for id in 1..=5 {
+                    j.enqueue(id).await.expect("enqueue");
+                }
+                assert!(j.enqueue(6).await.is_err());
+                // Appends go to the old generation again, and the smaller
+                // queue compacts.
+                j.remove(0).await.expect("remove after failed compaction");
+            });
+            assert_eq!(j.store().len() as usize, 7 * RECORD_SIZE);
+
+            let (restored, report) =
+                block_on(QueueJournal::<_, 16>::open(j.into_store())).expect("reopen");
+            assert_eq!(restored.queue().tracks(), &[2, 3, 4, 5, 6]);
+            assert!(!report.torn_tail);
+        }
+
+        #[test]
+        fn test_snapshot_over_threshold_is_not_rewritten_on_every_append() {
+            let (j, _) = block_on(QueueJournal::<_, 16>::open(Store::new())).expect("open");
+            let threshold = 4 * RECORD_SIZE;
+            let mut j = j.with_compact_threshold(threshold as u32);
+            block_on(async {
+                for id in 0..12 {
+                    j.enqueue(id).await.expect("enqueue");
+                }
+                j.compact().await.expect("compact");
+            });
+            // Clear, twelve Enqueues and Advance: already past the threshold.
+            let snapshot = j.store().len() as usize;
+            assert_eq!(snapshot, 14 * RECORD_SIZE);
+
+            block_on(async {
+                for (n, ms) in (1..4).zip([1_000, 2_000, 3_000]) {
+                    j.checkpoint_position(ms).await.expect("position");
+                    assert_eq!(j.store().len() as usize, snapshot + n * RECORD_SIZE);
+                }
+                // The threshold's worth of growth compacts again.
+                j.checkpoint_position(4_000).await.expect("position");
+            });
+            assert_eq!(j.store().len() as usize, 15 * RECORD_SIZE);
+        }
+
+        #[test]
+        fn test_rejected_mutation_is_not_journaled() {
+            let (mut j, _) = block_on(QueueJournal::<_, 4>::open(Store::new())).expect("open");
+            let result = block_on(j.remove(0));
+            assert!(result.is_err());
+            assert!(j.store().is_empty());
+        }
+    }
+
+    /// Resume record tests
+    mod resume_tests {
+        use crate::decoder::AudioFormat;
+        use crate::resume::{ResumeRecord, RESUME_PATH_MAX};
+
+        #[test]
+        fn test_record_roundtrip() {
+            let rec = ResumeRecord::new(
+                "Music/Portishead/Dummy/03 Sour Times.flac",
+                7,
+                Some(3),
+                93_000,
+            )
+            .expect("path fits");
+            assert_eq!(ResumeRecord::decode(&rec.encode()), Some(rec.clone()));
+            assert_eq!(rec.format(), Some(AudioFormat::Flac));
+
+            let no_album = ResumeRecord::new("a.mp3", 1, None, 0).expect("path fits");
+            assert_eq!(ResumeRecord::decode(&no_album.encode()), Some(no_album));
+        }
+
+        #[test]
+        fn test_corrupt_record_rejected() {
+            let rec = ResumeRecord::new("Music/a.flac", 1, None, 5_000).expect("path fits");
+            let mut bytes = rec.encode();
+            bytes[20] ^= 0x01;
+            assert_eq!(ResumeRecord::decode(&bytes), None);
+            assert_eq!(
+                ResumeRecord::decode(&[0; crate::resume::RESUME_RECORD_SIZE]),
+                None
+            );
+        }
+
+        #[test]
+        fn test_overlong_path_not_recorded() {
+            let long = "x".repeat(RESUME_PATH_MAX + 1);
+            assert_eq!(ResumeRecord::new(&long, 1, None, 0), None);
+            let max = "x".repeat(RESUME_PATH_MAX);
+            let rec = ResumeRecord::new(&max, 1, None, 0).expect("exactly fits");
+            assert_eq!(ResumeRecord::decode(&rec.encode()), Some(rec));
+        }
     }
 
     /// Volume/DSP tests
@@ -221,4 +1929,107 @@ mod tests {
             assert_eq!(att.get(), 127);
         }
     }
+
+    /// WAV decoder tests
+    mod wav_decoder_tests {
+        use crate::decoder::{DecodeError, FrameDecoder, PcmFrame};
+        use crate::wav_decoder::{WavDecoder, WavFormat};
+
+        /// `fmt ` body: format tag, channels, 44.1 kHz, sample size.
+        fn fmt(tag: u16, channels: u16, bits: u16) -> Vec<u8> {
+            let align = channels * (bits / 8);
+            let mut body = tag.to_le_bytes().to_vec();
+            body.extend_from_slice(&channels.to_le_bytes());
+            body.extend_from_slice(&44_100u32.to_le_bytes());
+            body.extend_from_slice(&(44_100 * u32::from(align)).to_le_bytes());
+            body.extend_from_slice(&align.to_le_bytes());
+            body.extend_from_slice(&bits.to_le_bytes());
+            body
+        }
+
+        fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut out = id.to_vec();
+            out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            out.extend_from_slice(body);
+            out
+        }
+
+        fn wav(fmt_body: &[u8], data: &[u8]) -> Vec<u8> {
+            let mut body = b"WAVE".to_vec();
+            body.extend(chunk(b"fmt ", fmt_body));
+            body.extend(chunk(b"data", data));
+            body.extend(chunk(b"id3 ", &[0; 4])); // trailing tag, never played
+            let mut out = b"RIFF".to_vec();
+            out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            out.extend(body);
+            out
+        }
+
+        /// Decode all of `bytes`, returning the samples.
+        fn decode(bytes: &[u8]) -> Result<Vec<i32>, DecodeError> {
+            let mut decoder = WavDecoder::new();
+            let mut frame = PcmFrame::default();
+            let mut samples = Vec::new();
+            let mut pos = 0;
+            loop {
+                match decoder.decode_frame(&bytes[pos..], &mut frame) {
+                    Ok(n) => pos += n,
+                    Err(DecodeError::EndOfStream) => return Ok(samples),
+                    Err(e) => return Err(e),
+                }
+                let n = frame.len * usize::from(frame.channels.max(1));
+                samples.extend_from_slice(&frame.samples[..n]);
+            }
+        }
+
+        #[test]
+        fn test_parse_fmt_chunk() {
+            let format = WavFormat::parse(&fmt(1, 2, 24)).expect("pcm");
+            assert_eq!((format.channels, format.bits_per_sample), (2, 24));
+            assert_eq!(format.block_align(), 6);
+            assert!(!format.float);
+
+            // WAVE_FORMAT_EXTENSIBLE carrying float: tag at offset 24.
+            let mut extensible = fmt(0xFFFE, 1, 32);
+            extensible.extend_from_slice(&[22, 0, 32, 0, 4, 0, 0, 0, 3, 0]);
+            extensible.resize(40, 0);
+            assert!(WavFormat::parse(&extensible).expect("extensible").float);
+        }
+
+        #[test]
+        fn test_unsupported_and_malformed_fmt_rejected() {
+            assert_eq!(WavFormat::parse(&fmt(2, 2, 16)), Err(DecodeError::UnsupportedFormat));
+            assert_eq!(WavFormat::parse(&fmt(1, 6, 16)), Err(DecodeError::UnsupportedFormat));
+            assert_eq!(WavFormat::parse(&fmt(1, 2, 12)), Err(DecodeError::UnsupportedFormat));
+            let mut misaligned = fmt(1, 2, 16);
+            misaligned[12] = 3;
+            assert_eq!(WavFormat::parse(&misaligned), Err(DecodeError::InvalidData));
+            assert_eq!(WavFormat::parse(&[1, 0]), Err(DecodeError::InvalidData));
+        }
+
+        #[test]
+        fn test_16_bit_stereo_left_justified() {
+            let data = [0x01, 0x00, 0xFF, 0xFF, 0xFF, 0x7F, 0x00, 0x80];
+            let samples = decode(&wav(&fmt(1, 2, 16), &data)).expect("decode");
+            assert_eq!(samples, [1 << 16, -1 << 16, i32::MAX & !0xFFFF, i32::MIN]);
+        }
+
+        #[test]
+        fn test_8_bit_and_float_scaled() {
+            let samples = decode(&wav(&fmt(1, 1, 8), &[0, 128, 255])).expect("8-bit");
+            assert_eq!(samples, [i32::MIN, 0, 127 << 24]);
+
+            let mut data = 0.5f32.to_le_bytes().to_vec();
+            data.extend_from_slice(&(-2.0f32).to_le_bytes());
+            let samples = decode(&wav(&fmt(3, 1, 32), &data)).expect("float");
+            assert_eq!(samples, [1 << 30, i32::MIN]); // -2.0 clamps to -1.0
+        }
+
+        #[test]
+        fn test_data_before_fmt_rejected() {
+            let mut bytes = b"RIFF\x10\x00\x00\x00WAVE".to_vec();
+            bytes.extend(chunk(b"data", &[0; 4]));
+            assert_eq!(decode(&bytes), Err(DecodeError::InvalidData));
+        }
+    }
 }
@@ -11,7 +11,15 @@
 //! `mp3` feature so the crate compiles on bare-metal targets that don't need
 //! MP3 support yet.
 
-use crate::decoder::{DecodeError, FrameDecoder, PcmFrame};
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+use crate::decoder::{AudioFormat, Codec, DecodeError, FrameDecoder, PcmFrame};
 
 // ─── Implementation ───────────────────────────────────────────────────────────
 
@@ -64,7 +72,7 @@ impl FrameDecoder for NanoMp3Decoder {
     /// - Returns `(bytes_consumed, None)` when no frame was decoded (garbage
     ///   at start, or true end-of-stream).
     /// - The `pcm` slice must be at least `MAX_SAMPLES_PER_FRAME` (= 2304)
-    ///   elements long or the call panics.
+    ///   elements long or the call panics; `pcm_buf` below is exactly that.
     ///
     /// Samples produced are left-justified into the 32-bit `PcmFrame.samples`
     /// field (f32 → i32 via bit-cast preserving the float range).
@@ -76,7 +84,10 @@ impl FrameDecoder for NanoMp3Decoder {
         #[cfg(feature = "mp3")]
         {
             // Scratch buffer on the stack — 2304 f32 samples = 9 216 bytes.
-            // MAX_SAMPLES_PER_FRAME = 1152 * 2 = 2304.
+            // MAX_SAMPLES_PER_FRAME = 1152 * 2 = 2304.  nanomp3 has no
+            // smaller decode granule, and the decode task's stack is sized
+            // for it.
+            #[allow(clippy::large_stack_arrays)]
             let mut pcm_buf = [0.0f32; nanomp3::MAX_SAMPLES_PER_FRAME];
 
             let (consumed, info_opt) = self.inner.decode(input, &mut pcm_buf);
@@ -84,19 +95,26 @@ impl FrameDecoder for NanoMp3Decoder {
             match info_opt {
                 Some(info) => {
                     self.sample_rate = info.sample_rate;
-                    self.channels = info.channels.num() as u8;
+                    self.channels = info.channels.num();
 
                     // Copy decoded f32 samples → left-justified i32.
                     // f32 range is [-1.0, 1.0]; we scale to full i32 range.
-                    let n_samples = info.samples_produced;
-                    let n = n_samples.min(output.samples.len());
-                    for (dst, &src) in output.samples[..n].iter_mut().zip(pcm_buf[..n].iter()) {
+                    let n = info
+                        .samples_produced
+                        .min(output.samples.len())
+                        .min(pcm_buf.len());
+                    for (dst, &src) in output.samples.iter_mut().zip(pcm_buf.iter()).take(n) {
                         // Scale f32 [-1.0, 1.0] to i32 range, clamping.
-                        *dst = (src.clamp(-1.0, 1.0) * i32::MAX as f32) as i32;
+                        // SAFETY (cast): the clamped product is within i32
+                        // range, and float-to-int `as` saturates anyway.
+                        #[allow(clippy::cast_possible_truncation)]
+                        {
+                            *dst = (src.clamp(-1.0, 1.0) * i32::MAX as f32) as i32;
+                        }
                     }
                     // `len` = number of samples per channel.
-                    let ch = self.channels.max(1) as usize;
-                    output.len = n / ch;
+                    let ch = usize::from(self.channels.max(1));
+                    output.len = n.checked_div(ch).unwrap_or(0);
                     output.sample_rate = self.sample_rate;
                     output.channels = self.channels;
                     Ok(consumed)
@@ -125,6 +143,28 @@ impl FrameDecoder for NanoMp3Decoder {
     }
 }
 
+// ─── Registry entry ───────────────────────────────────────────────────────────
+
+/// MP3 entry in [`crate::decoder::CODECS`]; decodes only with the `mp3`
+/// feature.
+pub const CODEC: Codec = Codec {
+    format: AudioFormat::Mp3,
+    open: if cfg!(feature = "mp3") {
+        Some(open)
+    } else {
+        None
+    },
+};
+
+/// nanomp3 needs no setup data; frame headers carry the stream parameters.
+fn open(
+    _head: &[u8],
+    run: &mut dyn FnMut(&mut dyn FrameDecoder<Error = DecodeError>),
+) -> Result<(), DecodeError> {
+    run(&mut NanoMp3Decoder::new());
+    Ok(())
+}
+
 #[cfg(test)]
 #[allow(clippy::indexing_slicing)] // Test indexing into known-length buffers is safe
 #[allow(clippy::expect_used)] // Tests use expect() for readable assertions
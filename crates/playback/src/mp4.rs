@@ -0,0 +1,461 @@
+//! MP4/M4A container demuxer — locates the AAC access units of the first
+//! audio track.
+//!
+//! The demuxer works on the `moov` box, which the playback task reads into
+//! memory once per track (a few tens of KB for an album track; the sample
+//! size table dominates).  Sample lookups walk the `stsc`/`stsz`/`stco`
+//! tables in place, so nothing is copied or allocated and the cost is
+//! proportional to the samples in one chunk.  The sample data itself stays
+//! in `mdat` and is read from the file at the offsets returned by
+//! [`Mp4Audio::sample`].
+//!
+//! `moov` may sit before or after `mdat` (iTunes writes it first, many
+//! encoders last); [`read_box_header`] lets the caller walk the top-level
+//! boxes with small reads to find it.
+
+/// Errors returned by [`Mp4Audio::parse`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mp4Error {
+    /// No `trak` with a sound handler (`soun`).
+    NoAudioTrack,
+    /// The audio track is not MPEG-4 audio (`mp4a` + `esds`).
+    UnsupportedCodec,
+    /// A box is truncated or a table is inconsistent.
+    Malformed,
+}
+
+/// A box header: four-character type and total size including the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoxHeader {
+    /// Four-character box type, e.g. `*b"moov"`.
+    pub kind: [u8; 4],
+    /// Size of the whole box in bytes; `None` when it runs to end of file.
+    pub size: Option<u64>,
+    /// Header length: 8, or 16 with a 64-bit size.
+    pub header_len: u8,
+}
+
+/// Parse the box header at the start of `bytes` (at least 16 bytes for
+/// 64-bit sizes).  Returns `None` when `bytes` is too short or the size is
+/// smaller than the header.
+pub fn read_box_header(bytes: &[u8]) -> Option<BoxHeader> {
+    let size32 = be_u32(bytes, 0)?;
+    let kind: [u8; 4] = bytes.get(4..8)?.try_into().ok()?;
+    let (size, header_len) = match size32 {
+        0 => (None, 8),
+        1 => (Some(be_u64(bytes, 8)?), 16),
+        n => (Some(u64::from(n)), 8),
+    };
+    if size.is_some_and(|s| s < u64::from(header_len)) {
+        return None;
+    }
+    Some(BoxHeader {
+        kind,
+        size,
+        header_len,
+    })
+}
+
+/// `true` when `header` starts with an `ftyp` box (MP4, M4A, M4B).
+pub fn is_mp4(header: &[u8]) -> bool {
+    header.get(4..8) == Some(b"ftyp".as_slice())
+}
+
+/// Payload of the first box of type `kind` among the boxes in `data`.
+pub fn find_box(data: &[u8], kind: [u8; 4]) -> Option<&[u8]> {
+    let mut rest = data;
+    while let Some(header) = read_box_header(rest) {
+        let size = match header.size {
+            Some(size) => usize::try_from(size).ok()?,
+            None => rest.len(),
+        };
+        let body = rest.get(usize::from(header.header_len)..size)?;
+        if header.kind == kind {
+            return Some(body);
+        }
+        rest = rest.get(size..)?;
+    }
+    None
+}
+
+/// MPEG-4 Audio object type for AAC Low Complexity.
+pub const AOT_AAC_LC: u8 = 2;
+
+/// Sampling rates indexed by `samplingFrequencyIndex` (ISO/IEC 14496-3).
+const SAMPLE_RATES: [u32; 13] = [
+    96_000, 88_200, 64_000, 48_000, 44_100, 32_000, 24_000, 22_050, 16_000, 12_000, 11_025, 8_000,
+    7_350,
+];
+
+/// The `AudioSpecificConfig` from the track's `esds` box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioSpecificConfig {
+    /// Audio object type ([`AOT_AAC_LC`] for AAC-LC).
+    pub object_type: u8,
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// `channelConfiguration` (1 = mono, 2 = stereo; 0 = defined in-band).
+    pub channels: u8,
+    /// The raw config bytes, as the decoder expects them.
+    pub raw: heapless::Vec<u8, 16>,
+}
+
+impl AudioSpecificConfig {
+    /// Parse an `AudioSpecificConfig` (the `DecoderSpecificInfo` payload).
+    pub fn parse(raw: &[u8]) -> Option<Self> {
+        let mut bits = BitReader::new(raw);
+        let mut object_type = bits.read(5)?;
+        if object_type == 31 {
+            object_type = bits.read(6)?.checked_add(32)?;
+        }
+        let sample_rate = match bits.read(4)? {
+            0xF => bits.read(24)?,
+            index => *SAMPLE_RATES.get(usize::try_from(index).ok()?)?,
+        };
+        let channels = bits.read(4)?;
+        Some(Self {
+            object_type: u8::try_from(object_type).ok()?,
+            sample_rate,
+            channels: u8::try_from(channels).ok()?,
+            raw: heapless::Vec::from_slice(raw).ok()?,
+        })
+    }
+}
+
+/// Location of one access unit in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mp4Sample {
+    /// Absolute file offset.
+    pub offset: u64,
+    /// Size in bytes.
+    pub size: u32,
+}
+
+/// The first audio track of an MP4 file, borrowed from its `moov` payload.
+#[derive(Debug, Clone)]
+pub struct Mp4Audio<'a> {
+    config: AudioSpecificConfig,
+    timescale: u32,
+    duration: u64,
+    sample_count: u32,
+    /// Fixed sample size from `stsz`, or 0 when sizes are in `sizes`.
+    fixed_size: u32,
+    sizes: &'a [u8],
+    stts: &'a [u8],
+    stsc: &'a [u8],
+    chunk_offsets: &'a [u8],
+    wide_offsets: bool,
+}
+
+impl<'a> Mp4Audio<'a> {
+    /// Parse the first sound track from the payload of the `moov` box.
+    ///
+    /// # Errors
+    ///
+    /// See [`Mp4Error`].
+    pub fn parse(moov: &'a [u8]) -> Result<Self, Mp4Error> {
+        let mut rest = moov;
+        while let Some(header) = read_box_header(rest) {
+            let size = match header.size {
+                Some(size) => usize::try_from(size).map_err(|_| Mp4Error::Malformed)?,
+                None => rest.len(),
+            };
+            let body = rest
+                .get(usize::from(header.header_len)..size)
+                .ok_or(Mp4Error::Malformed)?;
+            if header.kind == *b"trak" {
+                if let Some(audio) = Self::from_trak(body)? {
+                    return Ok(audio);
+                }
+            }
+            rest = rest.get(size..).ok_or(Mp4Error::Malformed)?;
+        }
+        Err(Mp4Error::NoAudioTrack)
+    }
+
+    /// `Ok(None)` when `trak` is not a sound track.
+    fn from_trak(trak: &'a [u8]) -> Result<Option<Self>, Mp4Error> {
+        let mdia = find_box(trak, *b"mdia").ok_or(Mp4Error::Malformed)?;
+        let hdlr = find_box(mdia, *b"hdlr").ok_or(Mp4Error::Malformed)?;
+        // version/flags (4), pre_defined (4), handler_type (4)
+        if hdlr.get(8..12) != Some(b"soun".as_slice()) {
+            return Ok(None);
+        }
+
+        let mdhd = find_box(mdia, *b"mdhd").ok_or(Mp4Error::Malformed)?;
+        let (timescale, duration) = match mdhd.first() {
+            Some(1) => (be_u32(mdhd, 20), be_u64(mdhd, 24)),
+            _ => (be_u32(mdhd, 12), be_u32(mdhd, 16).map(u64::from)),
+        };
+        let timescale = timescale.filter(|&t| t != 0).ok_or(Mp4Error::Malformed)?;
+        let duration = duration.ok_or(Mp4Error::Malformed)?;
+
+        let stbl = find_box(mdia, *b"minf")
+            .and_then(|minf| find_box(minf, *b"stbl"))
+            .ok_or(Mp4Error::Malformed)?;
+        let stsd = find_box(stbl, *b"stsd").ok_or(Mp4Error::Malformed)?;
+        let config = parse_stsd(stsd)?;
+
+        let stsz = find_box(stbl, *b"stsz").ok_or(Mp4Error::Malformed)?;
+        let fixed_size = be_u32(stsz, 4).ok_or(Mp4Error::Malformed)?;
+        let sample_count = be_u32(stsz, 8).ok_or(Mp4Error::Malformed)?;
+        // version/flags (4), sample_size (4), sample_count (4), then sizes
+        // when they vary.
+        let sizes = if fixed_size == 0 {
+            table(stsz, 12, 4)?
+        } else {
+            &[]
+        };
+
+        let (chunk_offsets, wide_offsets) = match find_box(stbl, *b"stco") {
+            Some(stco) => (table(stco, 8, 4)?, false),
+            None => {
+                let co64 = find_box(stbl, *b"co64").ok_or(Mp4Error::Malformed)?;
+                (table(co64, 8, 8)?, true)
+            }
+        };
+        let stsc = table(find_box(stbl, *b"stsc").ok_or(Mp4Error::Malformed)?, 8, 12)?;
+        let stts = table(find_box(stbl, *b"stts").ok_or(Mp4Error::Malformed)?, 8, 8)?;
+
+        Ok(Some(Self {
+            config,
+            timescale,
+            duration,
+            sample_count,
+            fixed_size,
+            sizes,
+            stts,
+            stsc,
+            chunk_offsets,
+            wide_offsets,
+        }))
+    }
+
+    /// Decoder configuration from the `esds` box.
+    pub fn config(&self) -> &AudioSpecificConfig {
+        &self.config
+    }
+
+    /// Number of access units in the track.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Track duration in milliseconds.
+    pub fn duration_ms(&self) -> u64 {
+        self.duration
+            .saturating_mul(1000)
+            .checked_div(u64::from(self.timescale))
+            .unwrap_or(0)
+    }
+
+    /// File location of access unit `index`, or `None` past the end.
+    pub fn sample(&self, index: u32) -> Option<Mp4Sample> {
+        if index >= self.sample_count {
+            return None;
+        }
+        let chunk_count = entries(self.chunk_offsets, if self.wide_offsets { 8 } else { 4 });
+        let runs = entries(self.stsc, 12);
+        let mut run_start = 0u32;
+        for run in 0..runs {
+            let at = usize::try_from(run).ok()?.checked_mul(12)?;
+            let first_chunk = be_u32(self.stsc, at)?;
+            let per_chunk = be_u32(self.stsc, at.checked_add(4)?)?;
+            let next_chunk = if run.checked_add(1)? < runs {
+                be_u32(self.stsc, at.checked_add(12)?)?
+            } else {
+                chunk_count.checked_add(1)?
+            };
+            let run_samples = next_chunk
+                .checked_sub(first_chunk)?
+                .checked_mul(per_chunk)?;
+            let in_run = index.checked_sub(run_start)?;
+            if in_run < run_samples {
+                let chunk_in_run = in_run.checked_div(per_chunk)?;
+                let chunk = first_chunk.checked_add(chunk_in_run)?.checked_sub(1)?;
+                let first_sample = run_start.checked_add(chunk_in_run.checked_mul(per_chunk)?)?;
+                let mut offset = self.chunk_offset(chunk)?;
+                for s in first_sample..index {
+                    offset = offset.checked_add(u64::from(self.sample_size(s)?))?;
+                }
+                return Some(Mp4Sample {
+                    offset,
+                    size: self.sample_size(index)?,
+                });
+            }
+            run_start = run_start.checked_add(run_samples)?;
+        }
+        None
+    }
+
+    /// Access unit playing at `position_ms`, or `None` past the end.
+    pub fn sample_at_ms(&self, position_ms: u64) -> Option<u32> {
+        let target = position_ms
+            .checked_mul(u64::from(self.timescale))?
+            .checked_div(1000)?;
+        let mut ticks = 0u64;
+        let mut first = 0u32;
+        for entry in 0..entries(self.stts, 8) {
+            let at = usize::try_from(entry).ok()?.checked_mul(8)?;
+            let count = be_u32(self.stts, at)?;
+            let delta = be_u32(self.stts, at.checked_add(4)?)?;
+            let span = u64::from(count).checked_mul(u64::from(delta))?;
+            if target < ticks.checked_add(span)? {
+                let within = target.checked_sub(ticks)?.checked_div(u64::from(delta))?;
+                return first.checked_add(u32::try_from(within).ok()?);
+            }
+            ticks = ticks.checked_add(span)?;
+            first = first.checked_add(count)?;
+        }
+        None
+    }
+
+    fn sample_size(&self, index: u32) -> Option<u32> {
+        if self.fixed_size != 0 {
+            return Some(self.fixed_size);
+        }
+        be_u32(self.sizes, usize::try_from(index).ok()?.checked_mul(4)?)
+    }
+
+    fn chunk_offset(&self, chunk: u32) -> Option<u64> {
+        let chunk = usize::try_from(chunk).ok()?;
+        if self.wide_offsets {
+            be_u64(self.chunk_offsets, chunk.checked_mul(8)?)
+        } else {
+            be_u32(self.chunk_offsets, chunk.checked_mul(4)?).map(u64::from)
+        }
+    }
+}
+
+/// Parse the `mp4a` sample entry in `stsd` down to its `AudioSpecificConfig`.
+fn parse_stsd(stsd: &[u8]) -> Result<AudioSpecificConfig, Mp4Error> {
+    // version/flags (4), entry_count (4), then the first sample entry box.
+    let entry = stsd.get(8..).ok_or(Mp4Error::Malformed)?;
+    let header = read_box_header(entry).ok_or(Mp4Error::Malformed)?;
+    if header.kind != *b"mp4a" {
+        return Err(Mp4Error::UnsupportedCodec);
+    }
+    let end = match header.size {
+        Some(size) => usize::try_from(size).map_err(|_| Mp4Error::Malformed)?,
+        None => entry.len(),
+    };
+    let body = entry
+        .get(usize::from(header.header_len)..end)
+        .ok_or(Mp4Error::Malformed)?;
+    // SampleEntry: reserved (6), data_reference_index (2).  AudioSampleEntry:
+    // version (2), revision (2), vendor (4), channels (2), sample size (2),
+    // compression id (2), packet size (2), sample rate (4).  QuickTime
+    // version 1 and 2 descriptions append 16 and 36 bytes.
+    let extra = match be_u16(body, 8).ok_or(Mp4Error::Malformed)? {
+        0 => 0,
+        1 => 16,
+        2 => 36,
+        _ => return Err(Mp4Error::UnsupportedCodec),
+    };
+    let children = body
+        .get(28usize.saturating_add(extra)..)
+        .ok_or(Mp4Error::Malformed)?;
+    let esds = find_box(children, *b"esds").ok_or(Mp4Error::UnsupportedCodec)?;
+    // version/flags (4), then the ES_Descriptor.
+    let es = descriptor(esds.get(4..).ok_or(Mp4Error::Malformed)?, 0x03)?;
+    let flags = *es.get(2).ok_or(Mp4Error::Malformed)?;
+    let mut skip = 3usize;
+    if flags & 0x80 != 0 {
+        skip = skip.saturating_add(2);
+    }
+    if flags & 0x40 != 0 {
+        let url_len = *es.get(skip).ok_or(Mp4Error::Malformed)?;
+        skip = skip.saturating_add(1).saturating_add(usize::from(url_len));
+    }
+    if flags & 0x20 != 0 {
+        skip = skip.saturating_add(2);
+    }
+    let dcd = descriptor(es.get(skip..).ok_or(Mp4Error::Malformed)?, 0x04)?;
+    // objectTypeIndication 0x40 = MPEG-4 Audio; 0x66–0x68 = MPEG-2 AAC.
+    if !matches!(dcd.first(), Some(0x40 | 0x66..=0x68)) {
+        return Err(Mp4Error::UnsupportedCodec);
+    }
+    // objectTypeIndication (1), streamType (1), bufferSizeDB (3),
+    // maxBitrate (4), avgBitrate (4), then DecoderSpecificInfo.
+    let dsi = descriptor(dcd.get(13..).ok_or(Mp4Error::Malformed)?, 0x05)?;
+    AudioSpecificConfig::parse(dsi).ok_or(Mp4Error::Malformed)
+}
+
+/// Body of the descriptor at the start of `data`, which must carry `tag`.
+fn descriptor(data: &[u8], tag: u8) -> Result<&[u8], Mp4Error> {
+    if data.first() != Some(&tag) {
+        return Err(Mp4Error::Malformed);
+    }
+    // Expandable length: up to four bytes of seven bits, high bit = more.
+    let mut len = 0usize;
+    let mut at = 1usize;
+    for _ in 0..4 {
+        let byte = *data.get(at).ok_or(Mp4Error::Malformed)?;
+        at = at.saturating_add(1);
+        len = len.checked_shl(7).ok_or(Mp4Error::Malformed)? | usize::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    data.get(at..at.saturating_add(len))
+        .ok_or(Mp4Error::Malformed)
+}
+
+/// The entry table of a full box: entries start after `header` bytes
+/// (version/flags plus fixed fields, ending with the entry count).
+fn table(body: &[u8], header: usize, entry_len: usize) -> Result<&[u8], Mp4Error> {
+    let count = be_u32(body, header.saturating_sub(4)).ok_or(Mp4Error::Malformed)?;
+    let len = usize::try_from(count)
+        .ok()
+        .and_then(|c| c.checked_mul(entry_len))
+        .ok_or(Mp4Error::Malformed)?;
+    body.get(header..header.saturating_add(len))
+        .ok_or(Mp4Error::Malformed)
+}
+
+/// Number of `entry_len`-byte entries in `table`.
+fn entries(table: &[u8], entry_len: usize) -> u32 {
+    u32::try_from(table.len().checked_div(entry_len).unwrap_or(0)).unwrap_or(u32::MAX)
+}
+
+fn be_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(at..at.checked_add(2)?)?.try_into().ok()?,
+    ))
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(at..at.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+fn be_u64(data: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(
+        data.get(at..at.checked_add(8)?)?.try_into().ok()?,
+    ))
+}
+
+/// MSB-first bit reader for `AudioSpecificConfig`.
+struct BitReader<'a> {
+    data: &'a [u8],
+    bit: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, bit: 0 }
+    }
+
+    /// Read `n` ≤ 32 bits.
+    fn read(&mut self, n: u32) -> Option<u32> {
+        let mut value = 0u32;
+        for _ in 0..n {
+            let byte = *self.data.get(self.bit / 8)?;
+            let shift = 7u32.checked_sub(u32::try_from(self.bit % 8).ok()?)?;
+            value = value.checked_shl(1)? | u32::from(byte.checked_shr(shift)? & 1);
+            self.bit = self.bit.checked_add(1)?;
+        }
+        Some(value)
+    }
+}
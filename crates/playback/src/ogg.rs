@@ -0,0 +1,272 @@
+//! Ogg container parsing — pages, CRC check and packet reassembly.
+//!
+//! [`OggPage::parse`] validates one page in place (capture pattern, version,
+//! lacing table, CRC) without copying.  [`PacketAssembler`] joins the
+//! lacing segments of consecutive pages into whole packets in a fixed-size
+//! buffer; a packet that spans pages is carried over until its last
+//! segment arrives.  Only single-stream files (one logical bitstream, as
+//! Opus and Vorbis music files are) are handled.
+
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+use heapless::Vec;
+
+/// Fixed part of a page header, before the lacing table.
+pub const PAGE_HEADER_LEN: usize = 27;
+
+/// Header type flag: the first packet continues one from the previous page.
+const FLAG_CONTINUED: u8 = 0x01;
+/// Header type flag: first page of the logical bitstream.
+const FLAG_FIRST: u8 = 0x02;
+/// Header type flag: last page of the logical bitstream.
+const FLAG_LAST: u8 = 0x04;
+
+/// Errors returned by [`OggPage::parse`] and [`PacketAssembler::feed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OggError {
+    /// The data does not start with the `OggS` capture pattern.
+    NotOgg,
+    /// The page is cut short; read more data and retry.
+    Truncated,
+    /// The page CRC does not match its contents.
+    BadCrc,
+    /// `stream_structure_version` is not 0.
+    UnsupportedVersion,
+    /// A packet did not fit in the assembler's buffer and was dropped.
+    PacketTooLarge,
+}
+
+/// One Ogg page, borrowed from the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OggPage<'a> {
+    header_type: u8,
+    granule_position: u64,
+    serial: u32,
+    sequence: u32,
+    lacing: &'a [u8],
+    body: &'a [u8],
+}
+
+impl<'a> OggPage<'a> {
+    /// Parse and CRC-check the page at the start of `data`.
+    ///
+    /// # Errors
+    ///
+    /// See [`OggError`]; [`OggError::Truncated`] means `data` ends inside
+    /// the page.
+    pub fn parse(data: &'a [u8]) -> Result<Self, OggError> {
+        let header = data.get(..PAGE_HEADER_LEN).ok_or(OggError::Truncated)?;
+        if header.get(..4) != Some(b"OggS".as_slice()) {
+            return Err(OggError::NotOgg);
+        }
+        if header.get(4) != Some(&0) {
+            return Err(OggError::UnsupportedVersion);
+        }
+        let field = |range: core::ops::Range<usize>| header.get(range).ok_or(OggError::Truncated);
+        let header_type = *header.get(5).ok_or(OggError::Truncated)?;
+        let granule_position =
+            u64::from_le_bytes(field(6..14)?.try_into().map_err(|_| OggError::Truncated)?);
+        let serial = le_u32(field(14..18)?)?;
+        let sequence = le_u32(field(18..22)?)?;
+        let crc = le_u32(field(22..26)?)?;
+        let segments = usize::from(*header.get(26).ok_or(OggError::Truncated)?);
+
+        let lacing_end = PAGE_HEADER_LEN.saturating_add(segments);
+        let lacing = data
+            .get(PAGE_HEADER_LEN..lacing_end)
+            .ok_or(OggError::Truncated)?;
+        let body_len = lacing
+            .iter()
+            .fold(0usize, |n, &l| n.saturating_add(usize::from(l)));
+        let page_end = lacing_end.saturating_add(body_len);
+        let page = data.get(..page_end).ok_or(OggError::Truncated)?;
+        let body = data.get(lacing_end..page_end).ok_or(OggError::Truncated)?;
+
+        if page_crc(page) != crc {
+            return Err(OggError::BadCrc);
+        }
+        Ok(Self {
+            header_type,
+            granule_position,
+            serial,
+            sequence,
+            lacing,
+            body,
+        })
+    }
+
+    /// Total size of the page in bytes (header, lacing table and body):
+    /// the offset of the next page.
+    pub fn size(&self) -> usize {
+        PAGE_HEADER_LEN
+            .saturating_add(self.lacing.len())
+            .saturating_add(self.body.len())
+    }
+
+    /// Codec-defined position after the last packet that ends on this page
+    /// (samples at 48 kHz for Opus); `u64::MAX` when no packet ends here.
+    pub fn granule_position(&self) -> u64 {
+        self.granule_position
+    }
+
+    /// Logical bitstream serial number.
+    pub fn serial(&self) -> u32 {
+        self.serial
+    }
+
+    /// Page sequence number; a gap means pages were lost.
+    pub fn sequence(&self) -> u32 {
+        self.sequence
+    }
+
+    /// The first packet continues one from the previous page.
+    pub fn is_continued(&self) -> bool {
+        self.header_type & FLAG_CONTINUED != 0
+    }
+
+    /// First page of the bitstream.
+    pub fn is_first(&self) -> bool {
+        self.header_type & FLAG_FIRST != 0
+    }
+
+    /// Last page of the bitstream.
+    pub fn is_last(&self) -> bool {
+        self.header_type & FLAG_LAST != 0
+    }
+
+    /// Page body (all segments, concatenated).
+    pub fn body(&self) -> &'a [u8] {
+        self.body
+    }
+}
+
+/// Joins page segments into packets, holding at most `N` bytes per packet.
+#[derive(Debug)]
+pub struct PacketAssembler<const N: usize> {
+    packet: Vec<u8, N>,
+    /// The packet being built overflowed; skip to its end.
+    dropping: bool,
+}
+
+impl<const N: usize> PacketAssembler<N> {
+    /// An empty assembler.
+    pub const fn new() -> Self {
+        Self {
+            packet: Vec::new(),
+            dropping: false,
+        }
+    }
+
+    /// Discard any partial packet (after a seek).
+    pub fn reset(&mut self) {
+        self.packet.clear();
+        self.dropping = false;
+    }
+
+    /// Feed one page, calling `on_packet` for every packet that completes
+    /// on it.  A trailing partial packet is kept for the next page.
+    ///
+    /// A page that does not continue a packet discards any partial one left
+    /// by a lost page.
+    ///
+    /// # Errors
+    ///
+    /// [`OggError::PacketTooLarge`] when a packet exceeded `N` bytes; it is
+    /// dropped, and packets after it on the page are still delivered.
+    pub fn feed(
+        &mut self,
+        page: &OggPage<'_>,
+        mut on_packet: impl FnMut(&[u8]),
+    ) -> Result<(), OggError> {
+        if !page.is_continued() {
+            self.reset();
+        }
+        let mut result = Ok(());
+        let mut at = 0usize;
+        for &lace in page.lacing {
+            let end = at.saturating_add(usize::from(lace));
+            let segment = page.body.get(at..end).unwrap_or(&[]);
+            at = end;
+            if !self.dropping && self.packet.extend_from_slice(segment).is_err() {
+                self.dropping = true;
+                result = Err(OggError::PacketTooLarge);
+            }
+            if lace < 255 {
+                if !self.dropping {
+                    on_packet(&self.packet);
+                }
+                self.reset();
+            }
+        }
+        result
+    }
+}
+
+impl<const N: usize> Default for PacketAssembler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn le_u32(bytes: &[u8]) -> Result<u32, OggError> {
+    Ok(u32::from_le_bytes(
+        bytes.try_into().map_err(|_| OggError::Truncated)?,
+    ))
+}
+
+/// CRC-32 as used by Ogg: polynomial 0x04C11DB7, MSB-first, zero initial
+/// value and no final XOR (not the reflected zlib CRC `crc32fast` computes).
+static CRC_TABLE: [u32; 256] = crc_table();
+
+// Const evaluation: `i < 256` bounds every index and `as u32` is lossless;
+// iterators and checked ops are not available in a const fn loop. The 1 KiB
+// table only lives on the stack during compile-time evaluation.
+#[allow(
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation,
+    clippy::large_stack_arrays
+)]
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0usize;
+    while i < 256 {
+        let mut r = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            r = if r & 0x8000_0000 != 0 {
+                (r << 1) ^ 0x04C1_1DB7
+            } else {
+                r << 1
+            };
+            bit += 1;
+        }
+        table[i] = r;
+        i += 1;
+    }
+    table
+}
+
+/// CRC of a whole page with its CRC field (bytes 22..26) taken as zero.
+fn page_crc(page: &[u8]) -> u32 {
+    page.iter().enumerate().fold(0u32, |crc, (i, &byte)| {
+        let byte = if (22..26).contains(&i) { 0 } else { byte };
+        let index = usize::from(crc.to_be_bytes()[0] ^ byte);
+        crc.wrapping_shl(8) ^ CRC_TABLE.get(index).copied().unwrap_or(0)
+    })
+}
+
+/// Compute the CRC for `page` and store it in the header, for tests and
+/// tools that build pages.
+pub fn seal_page(page: &mut [u8]) {
+    let crc = page_crc(page);
+    if let Some(field) = page.get_mut(22..26) {
+        field.copy_from_slice(&crc.to_le_bytes());
+    }
+}
@@ -0,0 +1,345 @@
+//! Ogg Opus decoder — `OpusHead` parsing, pre-skip and output gain.
+//!
+//! Implements the `FrameDecoder` trait for Opus packets reassembled from an
+//! Ogg stream by [`crate::ogg::PacketAssembler`].  The first packet of the
+//! stream is the `OpusHead` identification header ([`OpusHead::parse`]), the
+//! second is `OpusTags`; audio packets follow.
+//!
+//! Opus always decodes at 48 kHz.  The encoder's look-ahead is removed by
+//! dropping the first `pre_skip` samples, and the header's output gain
+//! (Q7.8 dB) is applied as an integer Q16 factor, so nothing here needs an
+//! FPU.
+//!
+//! # Feature flag
+//!
+//! The real decode path is gated behind the `opus` feature and binds
+//! libopus (BSD-3-Clause) through the `opus` crate, which needs `std`;
+//! libopus itself builds fixed-point for Cortex-M, so a target binding can
+//! replace it behind the same type.  Without the feature every frame
+//! returns [`DecodeError::UnsupportedFormat`], as the MP3 decoder does
+//! without `mp3`.
+
+use crate::decoder::{AudioFormat, Codec, DecodeError, FrameDecoder, PcmFrame};
+use crate::ogg::OggPage;
+
+/// Opus output sample rate.
+pub const OPUS_SAMPLE_RATE: u32 = 48_000;
+
+/// Longest Opus packet: 120 ms at 48 kHz, per channel.
+pub const MAX_OPUS_FRAME: usize = 5_760;
+
+/// Unity gain in Q16.
+const UNITY_Q16: u32 = 65_536;
+
+/// The `OpusHead` identification header (RFC 7845 §5.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpusHead {
+    /// Output channel count.
+    pub channels: u8,
+    /// Samples (at 48 kHz) to drop from the start of the decoded stream.
+    pub pre_skip: u16,
+    /// Sample rate of the original input, for display only.
+    pub input_sample_rate: u32,
+    /// Gain to apply to the output, in Q7.8 dB.
+    pub output_gain: i16,
+    /// Channel mapping family (0 = mono/stereo).
+    pub mapping_family: u8,
+}
+
+impl OpusHead {
+    /// Parse the identification header packet.
+    ///
+    /// Returns `None` when the packet is not an `OpusHead` or its major
+    /// version is not 0.
+    pub fn parse(packet: &[u8]) -> Option<Self> {
+        if packet.get(..8) != Some(b"OpusHead".as_slice()) {
+            return None;
+        }
+        // Versions 0–15 are compatible; the upper nibble is the major version.
+        if packet.get(8)? & 0xF0 != 0 {
+            return None;
+        }
+        Some(Self {
+            channels: *packet.get(9)?,
+            pre_skip: u16::from_le_bytes(packet.get(10..12)?.try_into().ok()?),
+            input_sample_rate: u32::from_le_bytes(packet.get(12..16)?.try_into().ok()?),
+            output_gain: i16::from_le_bytes(packet.get(16..18)?.try_into().ok()?),
+            mapping_family: *packet.get(18)?,
+        })
+    }
+
+    /// The output gain as a linear Q16 factor (65 536 = unity).
+    ///
+    /// Whole decibels multiply by 10^(1/20) each; the fractional part uses
+    /// a second-order expansion of e^x, which is within 0.05 % over 1 dB.
+    pub fn gain_q16(&self) -> u32 {
+        let magnitude = u64::from(self.output_gain.unsigned_abs());
+        let whole = magnitude / 256;
+        let frac = magnitude % 256;
+        // x = frac/256 dB · ln(10)/20, in Q16 (ln(10)/20 = 0.115129 ≈ 7545/65536).
+        let x = frac.saturating_mul(7_545) / 256;
+        let mut gain = u64::from(UNITY_Q16)
+            .saturating_add(x)
+            .saturating_add(x.saturating_mul(x) / 131_072);
+        // 10^(1/20) = 1.122018 ≈ 73533/65536.
+        for _ in 0..whole {
+            gain = gain.saturating_mul(73_533) / 65_536;
+        }
+        if self.output_gain < 0 {
+            gain = 0x1_0000_0000u64.checked_div(gain).unwrap_or(0);
+        }
+        u32::try_from(gain).unwrap_or(u32::MAX)
+    }
+}
+
+// ─── Implementation ───────────────────────────────────────────────────────────
+
+/// Opus decoder for one Ogg Opus stream (mono or stereo).
+pub struct OpusDecoder {
+    channels: u8,
+    /// Pre-skip samples (per channel) still to drop.
+    skip: usize,
+    gain_q16: u32,
+    #[cfg(feature = "opus")]
+    inner: opus::Decoder,
+}
+
+impl OpusDecoder {
+    /// Create a decoder for the stream described by `head`.
+    ///
+    /// # Errors
+    ///
+    /// [`DecodeError::UnsupportedFormat`] for multichannel streams (mapping
+    /// families other than 0) or when the decoder cannot be created.
+    pub fn new(head: &OpusHead) -> Result<Self, DecodeError> {
+        if head.mapping_family != 0 || !matches!(head.channels, 1 | 2) {
+            return Err(DecodeError::UnsupportedFormat);
+        }
+
+        #[cfg(feature = "opus")]
+        let inner = {
+            let channels = if head.channels == 1 {
+                opus::Channels::Mono
+            } else {
+                opus::Channels::Stereo
+            };
+            opus::Decoder::new(OPUS_SAMPLE_RATE, channels)
+                .map_err(|_| DecodeError::UnsupportedFormat)?
+        };
+
+        Ok(Self {
+            channels: head.channels,
+            skip: usize::from(head.pre_skip),
+            gain_q16: head.gain_q16(),
+            #[cfg(feature = "opus")]
+            inner,
+        })
+    }
+
+    /// Drop pending pre-skip samples from the front of `output` and apply
+    /// the output gain to what remains.
+    // Called from the `opus` decode path and the tests.
+    #[cfg_attr(not(feature = "opus"), allow(dead_code))]
+    fn apply_head(&mut self, output: &mut PcmFrame) {
+        let channels = usize::from(self.channels.max(1));
+        let skip = self.skip.min(output.len);
+        if skip > 0 {
+            let total = output
+                .len
+                .saturating_mul(channels)
+                .min(output.samples.len());
+            let start = skip.saturating_mul(channels).min(total);
+            output.samples.copy_within(start..total, 0);
+            output.len = output.len.saturating_sub(skip);
+            self.skip = self.skip.saturating_sub(skip);
+        }
+        if self.gain_q16 != UNITY_Q16 {
+            let total = output.len.saturating_mul(channels);
+            for sample in output.samples.iter_mut().take(total) {
+                let scaled = i64::from(*sample).saturating_mul(i64::from(self.gain_q16)) / 65_536;
+                *sample = i32::try_from(scaled.clamp(i64::from(i32::MIN), i64::from(i32::MAX)))
+                    .unwrap_or(0);
+            }
+        }
+    }
+}
+
+impl FrameDecoder for OpusDecoder {
+    type Error = DecodeError;
+
+    /// Decode one Opus packet from `input` into `output`.
+    ///
+    /// Returns `input.len()` on success: packets are delimited by the Ogg
+    /// lacing, not by the bitstream.  Samples are left-justified in the
+    /// 32-bit `PcmFrame.samples`, interleaved; packets longer than the
+    /// frame (over 40 ms stereo) return [`DecodeError::BufferTooSmall`].
+    fn decode_frame(&mut self, input: &[u8], output: &mut PcmFrame) -> Result<usize, Self::Error> {
+        if input.is_empty() {
+            return Err(DecodeError::EndOfStream);
+        }
+
+        #[cfg(feature = "opus")]
+        {
+            let channels = usize::from(self.channels);
+            let mut pcm = [0i16; MAX_OPUS_FRAME * 2];
+            let frames = self
+                .inner
+                .decode(input, &mut pcm, false)
+                .map_err(|_| DecodeError::InvalidData)?;
+            let total = frames.saturating_mul(channels);
+            let (Some(dst), Some(src)) = (output.samples.get_mut(..total), pcm.get(..total)) else {
+                return Err(DecodeError::BufferTooSmall);
+            };
+            for (d, &s) in dst.iter_mut().zip(src) {
+                *d = i32::from(s).wrapping_shl(16);
+            }
+            output.len = frames;
+            output.sample_rate = OPUS_SAMPLE_RATE;
+            output.channels = self.channels;
+            self.apply_head(output);
+            Ok(input.len())
+        }
+
+        #[cfg(not(feature = "opus"))]
+        {
+            let _ = output;
+            Err(DecodeError::UnsupportedFormat)
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        OPUS_SAMPLE_RATE
+    }
+
+    fn channels(&self) -> u8 {
+        self.channels
+    }
+}
+
+// ─── Registry entry ───────────────────────────────────────────────────────────
+
+/// Opus entry in [`crate::decoder::CODECS`]; decodes only with the `opus`
+/// feature.
+pub const CODEC: Codec = Codec {
+    format: AudioFormat::Opus,
+    open: if cfg!(feature = "opus") {
+        Some(open)
+    } else {
+        None
+    },
+};
+
+/// Build the decoder from the `OpusHead` packet on the first Ogg page.
+fn open(
+    head: &[u8],
+    run: &mut dyn FnMut(&mut dyn FrameDecoder<Error = DecodeError>),
+) -> Result<(), DecodeError> {
+    let page = OggPage::parse(head).map_err(|_| DecodeError::InvalidData)?;
+    let opus_head = OpusHead::parse(page.body()).ok_or(DecodeError::InvalidData)?;
+    run(&mut OpusDecoder::new(&opus_head)?);
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)] // Tests use expect() for readable assertions
+#[allow(clippy::indexing_slicing)] // Test indexing into known-length buffers is safe
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)] // Ramp samples are small
+mod tests {
+    use super::*;
+
+    fn head(pre_skip: u16, output_gain: i16) -> OpusHead {
+        let mut packet = b"OpusHead".to_vec();
+        packet.extend_from_slice(&[1, 2]);
+        packet.extend_from_slice(&pre_skip.to_le_bytes());
+        packet.extend_from_slice(&44_100u32.to_le_bytes());
+        packet.extend_from_slice(&output_gain.to_le_bytes());
+        packet.push(0);
+        OpusHead::parse(&packet).expect("valid OpusHead")
+    }
+
+    #[test]
+    fn test_opus_decoder_implements_frame_decoder() {
+        fn assert_impl<T: crate::decoder::FrameDecoder>() {}
+        assert_impl::<OpusDecoder>();
+    }
+
+    #[test]
+    fn test_opus_head_parses_fields() {
+        let h = head(312, -256);
+        assert_eq!(h.channels, 2);
+        assert_eq!(h.pre_skip, 312);
+        assert_eq!(h.input_sample_rate, 44_100);
+        assert_eq!(h.output_gain, -256);
+        assert_eq!(h.mapping_family, 0);
+        assert_eq!(OpusHead::parse(b"OpusTags\0\0\0\0"), None);
+    }
+
+    #[test]
+    fn test_opus_gain_q16() {
+        assert_eq!(head(0, 0).gain_q16(), 65_536);
+        // +6 dB ≈ ×1.9953, −6 dB ≈ ×0.5012, +0.5 dB ≈ ×1.0593.
+        let close = |gain: i16, expected: f64| {
+            let got = f64::from(head(0, gain).gain_q16()) / 65_536.0;
+            assert!(
+                (got - expected).abs() < 0.001,
+                "{gain}: {got} vs {expected}"
+            );
+        };
+        close(6 * 256, 1.9953);
+        close(-6 * 256, 0.5012);
+        close(128, 1.0593);
+    }
+
+    #[test]
+    fn test_opus_rejects_multichannel_mapping() {
+        let mut h = head(0, 0);
+        h.mapping_family = 1;
+        h.channels = 6;
+        assert!(matches!(
+            OpusDecoder::new(&h),
+            Err(DecodeError::UnsupportedFormat)
+        ));
+    }
+
+    #[test]
+    fn test_opus_pre_skip_spans_frames() {
+        let mut decoder = OpusDecoder::new(&head(3, 0)).expect("stereo");
+        let mut frame = PcmFrame::default();
+        for (i, s) in frame.samples[..8].iter_mut().enumerate() {
+            *s = i as i32;
+        }
+        frame.len = 2;
+        decoder.apply_head(&mut frame);
+        assert_eq!(frame.len, 0, "first frame entirely pre-skip");
+
+        for (i, s) in frame.samples[..8].iter_mut().enumerate() {
+            *s = i as i32;
+        }
+        frame.len = 4;
+        decoder.apply_head(&mut frame);
+        assert_eq!(frame.len, 3);
+        assert_eq!(&frame.samples[..6], &[2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_opus_gain_scales_and_saturates() {
+        let mut decoder = OpusDecoder::new(&head(0, 6 * 256)).expect("stereo");
+        let mut frame = PcmFrame::default();
+        frame.samples[0] = 1 << 20;
+        frame.samples[1] = i32::MIN;
+        frame.len = 1;
+        decoder.apply_head(&mut frame);
+        assert!((frame.samples[0] - (2 << 20)).abs() < (1 << 13));
+        assert_eq!(frame.samples[1], i32::MIN);
+    }
+
+    #[test]
+    fn test_opus_decode_empty_returns_error() {
+        let mut decoder = OpusDecoder::new(&head(0, 0)).expect("stereo");
+        let mut output = PcmFrame::default();
+        assert_eq!(
+            decoder.decode_frame(&[], &mut output),
+            Err(DecodeError::EndOfStream)
+        );
+    }
+}
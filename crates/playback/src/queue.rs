@@ -0,0 +1,223 @@
+//! Play queue — ordered list of track IDs plus the current cursor.
+//!
+//! `PlayQueue<N>` is a pure, `no_std`, allocation-free data structure in the
+//! same spirit as [`PlaybackEngine`](crate::engine::PlaybackEngine): it holds
+//! no I/O and knows nothing about storage.  Durability is layered on top by
+//! [`QueueJournal`](crate::queue_journal::QueueJournal), which records every
+//! mutation before it is acknowledged to the caller.
+//!
+//! Tracks are referenced by their library `track_id` (the `u32` key used in
+//! `library.idx`), so restoring a queue never requires a library rescan.
+
+use heapless::Vec;
+use util::Rng;
+
+/// Errors returned by [`PlayQueue`] mutations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueError {
+    /// The queue already holds `N` tracks.
+    Full,
+    /// An index argument is `>= len()`.
+    OutOfRange,
+}
+
+/// Fixed-capacity play queue.
+///
+/// The cursor (`current`) is `None` only when the queue is empty.  Removing
+/// or reordering entries keeps the cursor pointing at the same track where
+/// possible, mirroring what the user sees on the queue screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayQueue<const N: usize> {
+    tracks: Vec<u32, N>,
+    current: Option<u16>,
+    position_ms: u32,
+}
+
+impl<const N: usize> PlayQueue<N> {
+    /// Create an empty queue.
+    pub const fn new() -> Self {
+        Self {
+            tracks: Vec::new(),
+            current: None,
+            position_ms: 0,
+        }
+    }
+
+    /// Append a track to the end of the queue.
+    ///
+    /// The first track enqueued into an empty queue becomes current.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(QueueError::Full)` when the queue holds `N` tracks.
+    pub fn enqueue(&mut self, track_id: u32) -> Result<(), QueueError> {
+        self.tracks.push(track_id).map_err(|_| QueueError::Full)?;
+        if self.current.is_none() {
+            self.current = Some(0);
+            self.position_ms = 0;
+        }
+        Ok(())
+    }
+
+    /// Remove the entry at `index`, returning its track ID.
+    ///
+    /// Removing the current track moves the cursor to the track that slid
+    /// into its place (or the new last track) and resets the position.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(QueueError::OutOfRange)` when `index >= len()`.
+    pub fn remove(&mut self, index: u16) -> Result<u32, QueueError> {
+        let i = usize::from(index);
+        if i >= self.tracks.len() {
+            return Err(QueueError::OutOfRange);
+        }
+        let removed = self.tracks.remove(i);
+        self.current = match self.current {
+            _ if self.tracks.is_empty() => None,
+            Some(cur) if cur > index => Some(cur.saturating_sub(1)),
+            Some(cur) if cur == index => {
+                self.position_ms = 0;
+                Some(cur.min(self.last_index()))
+            }
+            other => other,
+        };
+        if self.current.is_none() {
+            self.position_ms = 0;
+        }
+        Ok(removed)
+    }
+
+    /// Move the entry at `from` so that it ends up at index `to`.
+    ///
+    /// The cursor follows the track it pointed at before the move.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(QueueError::OutOfRange)` when either index is `>= len()`.
+    pub fn reorder(&mut self, from: u16, to: u16) -> Result<(), QueueError> {
+        let (f, t) = (usize::from(from), usize::from(to));
+        let len = self.tracks.len();
+        if f >= len || t >= len {
+            return Err(QueueError::OutOfRange);
+        }
+        if f < t {
+            #[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+            // Safety: f < t < len checked above; t + 1 <= len.
+            self.tracks[f..=t].rotate_left(1);
+        } else {
+            #[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+            // Safety: t <= f < len checked above; f + 1 <= len.
+            self.tracks[t..=f].rotate_right(1);
+        }
+        self.current = self.current.map(|cur| {
+            if cur == from {
+                to
+            } else if from < cur && cur <= to {
+                cur.saturating_sub(1)
+            } else if to <= cur && cur < from {
+                cur.saturating_add(1)
+            } else {
+                cur
+            }
+        });
+        Ok(())
+    }
+
+    /// Shuffle the queue in place.
+    ///
+    /// The current track moves to the front and keeps its position, so
+    /// playback continues uninterrupted; everything after it is permuted
+    /// with `rng`.  The same generator state always yields the same order.
+    pub fn shuffle(&mut self, rng: &mut Rng) {
+        match self.current {
+            Some(cur) => {
+                self.tracks.swap(0, usize::from(cur));
+                if let Some(rest) = self.tracks.get_mut(1..) {
+                    rng.shuffle(rest);
+                }
+                self.current = Some(0);
+            }
+            None => rng.shuffle(&mut self.tracks),
+        }
+    }
+
+    /// Make the entry at `index` current and rewind to its start.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(QueueError::OutOfRange)` when `index >= len()`.
+    pub fn advance_to(&mut self, index: u16) -> Result<(), QueueError> {
+        if usize::from(index) >= self.tracks.len() {
+            return Err(QueueError::OutOfRange);
+        }
+        self.current = Some(index);
+        self.position_ms = 0;
+        Ok(())
+    }
+
+    /// Record the playback position within the current track.
+    ///
+    /// Ignored when the queue is empty.
+    pub fn set_position_ms(&mut self, ms: u32) {
+        if self.current.is_some() {
+            self.position_ms = ms;
+        }
+    }
+
+    /// Remove every entry and reset the cursor.
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+        self.current = None;
+        self.position_ms = 0;
+    }
+
+    /// Track IDs in play order.
+    pub fn tracks(&self) -> &[u32] {
+        &self.tracks
+    }
+
+    /// Number of queued tracks.
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// `true` when the queue holds no tracks.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Maximum number of tracks the queue can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Index of the current track, or `None` when empty.
+    pub fn current_index(&self) -> Option<u16> {
+        self.current
+    }
+
+    /// Track ID of the current track, or `None` when empty.
+    pub fn current(&self) -> Option<u32> {
+        self.current
+            .and_then(|i| self.tracks.get(usize::from(i)).copied())
+    }
+
+    /// Playback position within the current track, in milliseconds.
+    pub fn position_ms(&self) -> u32 {
+        self.position_ms
+    }
+
+    // SAFETY (cast): N is a small compile-time queue capacity, far below
+    // u16::MAX; len() - 1 therefore always fits.
+    #[allow(clippy::cast_possible_truncation)]
+    fn last_index(&self) -> u16 {
+        self.tracks.len().saturating_sub(1) as u16
+    }
+}
+
+impl<const N: usize> Default for PlayQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
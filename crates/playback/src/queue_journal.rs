@@ -0,0 +1,547 @@
+//! Crash-safe play-queue persistence via an append-only journal.
+//!
+//! Every [`PlayQueue`] mutation is encoded as a small fixed-size record and
+//! appended to a [`JournalStore`] before the call returns.  On boot,
+//! [`QueueJournal::open`] replays the records to rebuild the exact queue,
+//! cursor and in-track position — no library rescan required.
+//!
+//! # Record format
+//!
+//! ```text
+//! [0]      kind      u8   (see RecordKind)
+//! [1..5]   arg0      u32 le  (track_id / index / from / position_ms / seed)
+//! [5..7]   arg1      u16 le  (reorder target; 0 otherwise)
+//! [7..11]  crc32     u32 le  (CRC32 of bytes [0..7])
+//! ```
+//!
+//! All records are [`RECORD_SIZE`] bytes, so a torn write after a battery
+//! pull can only ever damage the final record.  Replay stops at the first
+//! record that is truncated or fails its CRC; `open` then compacts
+//! immediately so that subsequent appends do not land behind the garbage.
+//!
+//! `Shuffle` stores only the PRNG seed: [`PlayQueue::shuffle`] is
+//! deterministic, so replay reproduces the exact order.
+//!
+//! # Compaction
+//!
+//! Once the journal has grown by the configured threshold since the last
+//! snapshot it is rewritten as a minimal snapshot (`Clear`, one `Enqueue`
+//! per track, `Advance`, `Position`).  Measuring growth rather than total
+//! size keeps a queue whose snapshot alone exceeds the threshold from being
+//! rewritten on every mutation.
+//! The store swaps generations atomically in
+//! [`JournalStore::commit_compaction`], so a crash mid-compaction leaves the
+//! previous generation intact.  A compaction that fails is abandoned with
+//! [`JournalStore::abort_compaction`] and later records keep going to the
+//! previous generation.
+//!
+//! # Storage
+//!
+//! [`MemoryJournalStore`] is the only [`JournalStore`] so far.  An SD-card
+//! store needs file writes and renames, which [`platform::storage::Storage`]
+//! does not offer yet; [`platform::queue_journal_path`] names the file it is
+//! meant to use.
+//!
+//! # Flash wear
+//!
+//! `Position` records are cheap but not free.  Callers should checkpoint the
+//! position on pause, on track change and at a coarse interval (tens of
+//! seconds) — not on every UI position tick.
+
+use crate::queue::{PlayQueue, QueueError};
+use util::Rng;
+
+/// Size in bytes of one encoded journal record.
+pub const RECORD_SIZE: usize = 11;
+
+// SAFETY (cast): RECORD_SIZE is the constant 11.
+#[allow(clippy::cast_possible_truncation)]
+const RECORD_SIZE_U32: u32 = RECORD_SIZE as u32;
+
+/// Default journal growth (bytes) since the last snapshot that triggers
+/// automatic compaction.
+///
+/// 4 KiB ≈ 370 records — one FAT32 cluster on a typical SD card.
+pub const DEFAULT_COMPACT_THRESHOLD: u32 = 4096;
+
+/// Journal record discriminants.  Values are part of the on-disk format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum RecordKind {
+    Clear = 0x01,
+    Enqueue = 0x02,
+    Remove = 0x03,
+    Reorder = 0x04,
+    Advance = 0x05,
+    Position = 0x06,
+    Shuffle = 0x07,
+}
+
+impl RecordKind {
+    fn from_u8(b: u8) -> Option<Self> {
+        match b {
+            0x01 => Some(Self::Clear),
+            0x02 => Some(Self::Enqueue),
+            0x03 => Some(Self::Remove),
+            0x04 => Some(Self::Reorder),
+            0x05 => Some(Self::Advance),
+            0x06 => Some(Self::Position),
+            0x07 => Some(Self::Shuffle),
+            _ => None,
+        }
+    }
+}
+
+/// A single decoded queue mutation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueRecord {
+    /// Remove every entry.
+    Clear,
+    /// Append `track_id` to the end of the queue.
+    Enqueue(u32),
+    /// Remove the entry at the given index.
+    Remove(u16),
+    /// Move the entry at `from` to `to`.
+    Reorder {
+        /// Source index.
+        from: u16,
+        /// Destination index.
+        to: u16,
+    },
+    /// Make the entry at the given index current.
+    Advance(u16),
+    /// Playback position within the current track, in milliseconds.
+    Position(u32),
+    /// Shuffle with a generator seeded from the given value.
+    Shuffle(u32),
+}
+
+impl QueueRecord {
+    /// Encode this record into its fixed on-disk representation.
+    #[allow(clippy::indexing_slicing)] // Safety: constant ranges within [0, RECORD_SIZE)
+    pub fn encode(&self) -> [u8; RECORD_SIZE] {
+        let (kind, arg0, arg1) = match *self {
+            Self::Clear => (RecordKind::Clear, 0u32, 0u16),
+            Self::Enqueue(id) => (RecordKind::Enqueue, id, 0),
+            Self::Remove(i) => (RecordKind::Remove, u32::from(i), 0),
+            Self::Reorder { from, to } => (RecordKind::Reorder, u32::from(from), to),
+            Self::Advance(i) => (RecordKind::Advance, u32::from(i), 0),
+            Self::Position(ms) => (RecordKind::Position, ms, 0),
+            Self::Shuffle(seed) => (RecordKind::Shuffle, seed, 0),
+        };
+        let mut buf = [0u8; RECORD_SIZE];
+        buf[0] = kind as u8;
+        buf[1..5].copy_from_slice(&arg0.to_le_bytes());
+        buf[5..7].copy_from_slice(&arg1.to_le_bytes());
+        let crc = crc32fast::hash(&buf[0..7]);
+        buf[7..11].copy_from_slice(&crc.to_le_bytes());
+        buf
+    }
+
+    /// Decode a record, returning `None` on CRC mismatch or unknown kind.
+    #[allow(clippy::indexing_slicing)] // Safety: constant ranges within [0, RECORD_SIZE)
+    pub fn decode(buf: &[u8; RECORD_SIZE]) -> Option<Self> {
+        let stored = u32::from_le_bytes([buf[7], buf[8], buf[9], buf[10]]);
+        if crc32fast::hash(&buf[0..7]) != stored {
+            return None;
+        }
+        let arg0 = u32::from_le_bytes([buf[1], buf[2], buf[3], buf[4]]);
+        let arg1 = u16::from_le_bytes([buf[5], buf[6]]);
+        // Index-carrying records were written from a u16, so arg0 fits; a
+        // value that does not is corruption the CRC happened to miss.
+        let idx = || u16::try_from(arg0).ok();
+        match RecordKind::from_u8(buf[0])? {
+            RecordKind::Clear => Some(Self::Clear),
+            RecordKind::Enqueue => Some(Self::Enqueue(arg0)),
+            RecordKind::Remove => idx().map(Self::Remove),
+            RecordKind::Reorder => idx().map(|from| Self::Reorder { from, to: arg1 }),
+            RecordKind::Advance => idx().map(Self::Advance),
+            RecordKind::Position => Some(Self::Position(arg0)),
+            RecordKind::Shuffle => Some(Self::Shuffle(arg0)),
+        }
+    }
+
+    /// Apply this record to `queue`.
+    ///
+    /// # Errors
+    ///
+    /// Propagates the [`QueueError`] from the underlying queue operation.
+    pub fn apply<const N: usize>(&self, queue: &mut PlayQueue<N>) -> Result<(), QueueError> {
+        match *self {
+            Self::Clear => {
+                queue.clear();
+                Ok(())
+            }
+            Self::Enqueue(id) => queue.enqueue(id),
+            Self::Remove(i) => queue.remove(i).map(|_| ()),
+            Self::Reorder { from, to } => queue.reorder(from, to),
+            Self::Advance(i) => queue.advance_to(i),
+            Self::Position(ms) => {
+                queue.set_position_ms(ms);
+                Ok(())
+            }
+            Self::Shuffle(seed) => {
+                queue.shuffle(&mut Rng::seed_from_u64(u64::from(seed)));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Byte-level backing store for a [`QueueJournal`].
+///
+/// A generation is meant to map onto a file, with compaction writing
+/// `queue.jnl.new` and renaming it over `queue.jnl`; only
+/// [`MemoryJournalStore`] exists so far (see the module docs).
+///
+/// # Atomicity contract
+///
+/// - `append` may be torn by power loss, but must never corrupt bytes that
+///   were already durable before the call.
+/// - Between `begin_compaction` and `commit_compaction`, appends go to a new
+///   generation that is invisible to `read_at`/`len` until the commit.
+/// - `commit_compaction` must atomically replace the old generation (e.g.
+///   FAT rename over the old file).  A crash before the commit leaves the
+///   old generation in place.
+/// - `abort_compaction` drops the new generation; appends go to the old
+///   one again.
+pub trait JournalStore {
+    /// Storage error type.
+    type Error: core::fmt::Debug;
+
+    /// Current committed journal length in bytes.
+    fn len(&self) -> u32;
+
+    /// `true` when the committed journal is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Read committed journal bytes starting at `offset` into `buf`.
+    ///
+    /// Returns the number of bytes read (0 at end of journal).
+    fn read_at(
+        &mut self,
+        offset: u32,
+        buf: &mut [u8],
+    ) -> impl core::future::Future<Output = Result<usize, Self::Error>>;
+
+    /// Append bytes to the active generation and make them durable.
+    fn append(
+        &mut self,
+        bytes: &[u8],
+    ) -> impl core::future::Future<Output = Result<(), Self::Error>>;
+
+    /// Start writing a fresh generation.
+    fn begin_compaction(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>>;
+
+    /// Atomically replace the committed journal with the new generation.
+    fn commit_compaction(&mut self) -> impl core::future::Future<Output = Result<(), Self::Error>>;
+
+    /// Discard the new generation after a failed compaction.
+    fn abort_compaction(&mut self) -> impl core::future::Future<Output = ()>;
+}
+
+/// Errors returned by [`QueueJournal`] operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalError<E> {
+    /// The requested queue mutation was rejected; nothing was written.
+    Queue(QueueError),
+    /// The backing store failed.  The in-memory queue has already been
+    /// updated; the mutation may not survive a reboot.
+    Store(E),
+}
+
+/// Summary of what [`QueueJournal::open`] found on storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReplayReport {
+    /// Number of records successfully applied.
+    pub records_applied: u32,
+    /// `true` when replay stopped at a torn or corrupt record.
+    pub torn_tail: bool,
+    /// Records that decoded cleanly but could not be applied (e.g. an
+    /// `Enqueue` beyond capacity after `N` was reduced in a firmware update).
+    pub records_skipped: u32,
+}
+
+/// A [`PlayQueue`] whose every mutation is journaled to a [`JournalStore`].
+pub struct QueueJournal<S: JournalStore, const N: usize> {
+    store: S,
+    queue: PlayQueue<N>,
+    compact_threshold: u32,
+    /// Journal length right after the last compaction (0 until one runs).
+    snapshot_len: u32,
+}
+
+impl<S: JournalStore, const N: usize> QueueJournal<S, N> {
+    /// Restore the queue by replaying `store`.
+    ///
+    /// A torn tail triggers an immediate compaction so that the journal ends
+    /// on a record boundary before any new records are appended.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(JournalError::Store)` if reading or compacting fails.
+    pub async fn open(mut store: S) -> Result<(Self, ReplayReport), JournalError<S::Error>> {
+        let mut queue = PlayQueue::new();
+        let mut report = ReplayReport::default();
+        let mut offset = 0u32;
+        let len = store.len();
+        let mut buf = [0u8; RECORD_SIZE];
+
+        while offset < len {
+            let n = store.read_at(offset, &mut buf).await.map_err(JournalError::Store)?;
+            if n < RECORD_SIZE {
+                report.torn_tail = true;
+                break;
+            }
+            let Some(record) = QueueRecord::decode(&buf) else {
+                report.torn_tail = true;
+                break;
+            };
+            if record.apply(&mut queue).is_ok() {
+                report.records_applied = report.records_applied.saturating_add(1);
+            } else {
+                report.records_skipped = report.records_skipped.saturating_add(1);
+            }
+            offset = offset.saturating_add(RECORD_SIZE_U32);
+        }
+
+        let mut journal = Self {
+            store,
+            queue,
+            compact_threshold: DEFAULT_COMPACT_THRESHOLD,
+            snapshot_len: 0,
+        };
+        if report.torn_tail || report.records_skipped > 0 {
+            journal.compact().await?;
+        }
+        Ok((journal, report))
+    }
+
+    /// Override the journal growth (bytes) since the last snapshot that
+    /// triggers automatic compaction.
+    #[must_use]
+    pub fn with_compact_threshold(mut self, bytes: u32) -> Self {
+        self.compact_threshold = bytes;
+        self
+    }
+
+    /// The restored / current queue.
+    pub fn queue(&self) -> &PlayQueue<N> {
+        &self.queue
+    }
+
+    /// Borrow the backing store (diagnostics and tests).
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Consume the journal and return the backing store.
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// Append `track_id` to the queue.
+    ///
+    /// # Errors
+    ///
+    /// See [`JournalError`].
+    pub async fn enqueue(&mut self, track_id: u32) -> Result<(), JournalError<S::Error>> {
+        self.record(QueueRecord::Enqueue(track_id)).await
+    }
+
+    /// Remove the entry at `index`.
+    ///
+    /// # Errors
+    ///
+    /// See [`JournalError`].
+    pub async fn remove(&mut self, index: u16) -> Result<(), JournalError<S::Error>> {
+        self.record(QueueRecord::Remove(index)).await
+    }
+
+    /// Move the entry at `from` to `to`.
+    ///
+    /// # Errors
+    ///
+    /// See [`JournalError`].
+    pub async fn reorder(&mut self, from: u16, to: u16) -> Result<(), JournalError<S::Error>> {
+        self.record(QueueRecord::Reorder { from, to }).await
+    }
+
+    /// Make the entry at `index` current.
+    ///
+    /// # Errors
+    ///
+    /// See [`JournalError`].
+    pub async fn advance_to(&mut self, index: u16) -> Result<(), JournalError<S::Error>> {
+        self.record(QueueRecord::Advance(index)).await
+    }
+
+    /// Checkpoint the in-track playback position.
+    ///
+    /// # Errors
+    ///
+    /// See [`JournalError`].
+    pub async fn checkpoint_position(&mut self, ms: u32) -> Result<(), JournalError<S::Error>> {
+        self.record(QueueRecord::Position(ms)).await
+    }
+
+    /// Shuffle the queue, keeping the current track playing.
+    ///
+    /// Draw `seed` from the device PRNG; only the seed is journaled.
+    ///
+    /// # Errors
+    ///
+    /// See [`JournalError`].
+    pub async fn shuffle(&mut self, seed: u32) -> Result<(), JournalError<S::Error>> {
+        self.record(QueueRecord::Shuffle(seed)).await
+    }
+
+    /// Remove every entry.
+    ///
+    /// # Errors
+    ///
+    /// See [`JournalError`].
+    pub async fn clear(&mut self) -> Result<(), JournalError<S::Error>> {
+        self.record(QueueRecord::Clear).await
+    }
+
+    /// Rewrite the journal as a minimal snapshot of the current queue.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(JournalError::Store)` on storage failure; the previous
+    /// generation remains authoritative in that case and later records are
+    /// appended to it.
+    pub async fn compact(&mut self) -> Result<(), JournalError<S::Error>> {
+        self.store.begin_compaction().await.map_err(JournalError::Store)?;
+        let result = self.write_snapshot().await;
+        match result {
+            Ok(()) => self.snapshot_len = self.store.len(),
+            Err(_) => self.store.abort_compaction().await,
+        }
+        result
+    }
+
+    async fn write_snapshot(&mut self) -> Result<(), JournalError<S::Error>> {
+        self.append(QueueRecord::Clear).await?;
+        for &id in self.queue.tracks() {
+            self.store
+                .append(&QueueRecord::Enqueue(id).encode())
+                .await
+                .map_err(JournalError::Store)?;
+        }
+        if let Some(cur) = self.queue.current_index() {
+            self.append(QueueRecord::Advance(cur)).await?;
+            if self.queue.position_ms() != 0 {
+                self.append(QueueRecord::Position(self.queue.position_ms())).await?;
+            }
+        }
+        self.store.commit_compaction().await.map_err(JournalError::Store)
+    }
+
+    async fn record(&mut self, record: QueueRecord) -> Result<(), JournalError<S::Error>> {
+        record.apply(&mut self.queue).map_err(JournalError::Queue)?;
+        self.append(record).await?;
+        if self.store.len() >= self.snapshot_len.saturating_add(self.compact_threshold) {
+            self.compact().await?;
+        }
+        Ok(())
+    }
+
+    async fn append(&mut self, record: QueueRecord) -> Result<(), JournalError<S::Error>> {
+        self.store
+            .append(&record.encode())
+            .await
+            .map_err(JournalError::Store)
+    }
+}
+
+/// Error returned by [`MemoryJournalStore`] when its capacity is exhausted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryJournalFull;
+
+/// RAM-backed [`JournalStore`] for the emulator and host tests.
+///
+/// Holds two generations of up to `CAP` bytes each, mirroring the two-file
+/// layout described on [`JournalStore`].
+pub struct MemoryJournalStore<const CAP: usize> {
+    committed: heapless::Vec<u8, CAP>,
+    pending: Option<heapless::Vec<u8, CAP>>,
+}
+
+impl<const CAP: usize> MemoryJournalStore<CAP> {
+    /// Create an empty store.
+    pub const fn new() -> Self {
+        Self {
+            committed: heapless::Vec::new(),
+            pending: None,
+        }
+    }
+
+    /// Create a store pre-loaded with raw journal bytes (e.g. to simulate a
+    /// torn write).  Bytes beyond `CAP` are dropped.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let take = bytes.len().min(CAP);
+        let mut committed = heapless::Vec::new();
+        // Cannot fail: `take <= CAP`.
+        let _ = committed.extend_from_slice(bytes.get(..take).unwrap_or_default());
+        Self {
+            committed,
+            pending: None,
+        }
+    }
+
+    /// Raw committed journal bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.committed
+    }
+}
+
+impl<const CAP: usize> Default for MemoryJournalStore<CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const CAP: usize> JournalStore for MemoryJournalStore<CAP> {
+    type Error = MemoryJournalFull;
+
+    // SAFETY (cast): CAP is a RAM buffer size, always well below u32::MAX.
+    #[allow(clippy::cast_possible_truncation)]
+    fn len(&self) -> u32 {
+        self.committed.len() as u32
+    }
+
+    async fn read_at(&mut self, offset: u32, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let start = usize::try_from(offset).unwrap_or(usize::MAX);
+        let src = self.committed.get(start..).unwrap_or_default();
+        let n = src.len().min(buf.len());
+        if let (Some(dst), Some(src)) = (buf.get_mut(..n), src.get(..n)) {
+            dst.copy_from_slice(src);
+        }
+        Ok(n)
+    }
+
+    async fn append(&mut self, bytes: &[u8]) -> Result<(), Self::Error> {
+        let target = self.pending.as_mut().unwrap_or(&mut self.committed);
+        target.extend_from_slice(bytes).map_err(|_| MemoryJournalFull)
+    }
+
+    async fn begin_compaction(&mut self) -> Result<(), Self::Error> {
+        self.pending = Some(heapless::Vec::new());
+        Ok(())
+    }
+
+    async fn commit_compaction(&mut self) -> Result<(), Self::Error> {
+        if let Some(next) = self.pending.take() {
+            self.committed = next;
+        }
+        Ok(())
+    }
+
+    async fn abort_compaction(&mut self) {
+        self.pending = None;
+    }
+}
@@ -0,0 +1,112 @@
+//! Resume record — the track that was playing, by path, for the fast boot.
+//!
+//! The [queue journal](crate::queue_journal) stores track ids, which mean
+//! nothing until the library index is loaded.  To start sound straight
+//! after the SD mount, the playback task also keeps one [`ResumeRecord`]:
+//! the current track's card path and position.  At boot it is read, the
+//! file is opened by path and [`PlaybackEngine::resume_at`] seeks into it,
+//! while the index loads behind the first frame.
+//!
+//! Rewrite the record whenever the queue journal checkpoints the position
+//! (pause, track change, the coarse timer); it is one small file write.
+//!
+//! # Record format
+//!
+//! ```text
+//! [0]        version      u8   (RESUME_VERSION)
+//! [1..5]     track_id     u32 le
+//! [5..9]     album_id     u32 le  (u32::MAX when unknown)
+//! [9..13]    position_ms  u32 le
+//! [13..15]   path_len     u16 le
+//! [15..271]  path         UTF-8, zero padded
+//! [271..275] crc32        u32 le  (CRC32 of bytes [0..271])
+//! ```
+//!
+//! [`PlaybackEngine::resume_at`]: crate::engine::PlaybackEngine::resume_at
+
+use crate::decoder::AudioFormat;
+
+/// Longest card path a record holds, in bytes.
+pub const RESUME_PATH_MAX: usize = 256;
+
+/// Size in bytes of one encoded record.
+pub const RESUME_RECORD_SIZE: usize = 15 + RESUME_PATH_MAX + 4;
+
+/// Format version; a record with any other value is ignored.
+pub const RESUME_VERSION: u8 = 1;
+
+const PATH_START: usize = 15;
+const CRC_START: usize = PATH_START + RESUME_PATH_MAX;
+const NO_ALBUM: u32 = u32::MAX;
+
+/// Where playback was when the record was last written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumeRecord {
+    /// Card path of the track, as the scanner recorded it.
+    pub path: heapless::String<RESUME_PATH_MAX>,
+    /// Library id of the track, to find its metadata once the index loads.
+    pub track_id: u32,
+    /// Album of the track, keying its art (`None` when unknown).
+    pub album_id: Option<u32>,
+    /// Position in the track, in milliseconds.
+    pub position_ms: u32,
+}
+
+impl ResumeRecord {
+    /// A record for `path`, or `None` when the path is longer than
+    /// [`RESUME_PATH_MAX`] (such a track resumes after the index loads).
+    pub fn new(path: &str, track_id: u32, album_id: Option<u32>, position_ms: u32) -> Option<Self> {
+        let mut stored = heapless::String::new();
+        stored.push_str(path).ok()?;
+        Some(Self {
+            path: stored,
+            track_id,
+            album_id,
+            position_ms,
+        })
+    }
+
+    /// Codec for the track, from the path's extension.
+    pub fn format(&self) -> Option<AudioFormat> {
+        let (_, ext) = self.path.rsplit_once('.')?;
+        AudioFormat::from_extension(ext)
+    }
+
+    /// Encode this record into its fixed on-disk representation.
+    #[allow(clippy::indexing_slicing)] // Safety: constant ranges within [0, RESUME_RECORD_SIZE)
+    pub fn encode(&self) -> [u8; RESUME_RECORD_SIZE] {
+        let path = self.path.as_bytes();
+        // `path` fits in RESUME_PATH_MAX, which is far below u16::MAX.
+        let len = u16::try_from(path.len()).unwrap_or(0);
+        let mut buf = [0u8; RESUME_RECORD_SIZE];
+        buf[0] = RESUME_VERSION;
+        buf[1..5].copy_from_slice(&self.track_id.to_le_bytes());
+        buf[5..9].copy_from_slice(&self.album_id.unwrap_or(NO_ALBUM).to_le_bytes());
+        buf[9..13].copy_from_slice(&self.position_ms.to_le_bytes());
+        buf[13..15].copy_from_slice(&len.to_le_bytes());
+        if let Some(dst) = buf[PATH_START..CRC_START].get_mut(..path.len()) {
+            dst.copy_from_slice(path);
+        }
+        let crc = crc32fast::hash(&buf[..CRC_START]);
+        buf[CRC_START..].copy_from_slice(&crc.to_le_bytes());
+        buf
+    }
+
+    /// Decode a record, returning `None` on CRC mismatch, an unknown
+    /// version or a path that is not UTF-8.
+    #[allow(clippy::indexing_slicing)] // Safety: constant ranges within [0, RESUME_RECORD_SIZE)
+    pub fn decode(buf: &[u8; RESUME_RECORD_SIZE]) -> Option<Self> {
+        let stored = u32::from_le_bytes(buf[CRC_START..].try_into().ok()?);
+        if crc32fast::hash(&buf[..CRC_START]) != stored || buf[0] != RESUME_VERSION {
+            return None;
+        }
+        let track_id = u32::from_le_bytes([buf[1], buf[2], buf[3], buf[4]]);
+        let album_id = u32::from_le_bytes([buf[5], buf[6], buf[7], buf[8]]);
+        let position_ms = u32::from_le_bytes([buf[9], buf[10], buf[11], buf[12]]);
+        let len = usize::from(u16::from_le_bytes([buf[13], buf[14]]));
+        let path = buf[PATH_START..CRC_START].get(..len)?;
+        let path = core::str::from_utf8(path).ok()?;
+        let album_id = (album_id != NO_ALBUM).then_some(album_id);
+        Self::new(path, track_id, album_id, position_ms)
+    }
+}
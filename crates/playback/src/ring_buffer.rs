@@ -1,45 +1,97 @@
-//! Const-generic, stack-allocated ring buffer for PCM audio samples.
+//! Const-generic, lock-free SPSC ring buffer for PCM audio samples.
 //!
 //! `RingBuffer<N>` stores up to `N` `i32` samples without heap allocation.
-//! It is a single-producer / single-consumer (SPSC) structure intended for
-//! use between the decode task (writer) and the DMA-feed task (reader).
+//! It sits between the decode task (producer) and the SAI DMA feed
+//! (consumer).  [`RingBuffer::split`] hands out one [`Producer`] and one
+//! [`Consumer`], which may live in different Embassy tasks — or the consumer
+//! in the DMA half-transfer interrupt — without a mutex.
+//!
+//! # Zero-copy DMA
+//!
+//! [`Consumer::as_linear_slices`] returns the readable samples as at most
+//! two contiguous slices *inside the ring storage*.  When the ring is placed
+//! in DMA-reachable RAM (AXI SRAM, `0x2400_0000`), a slice can be handed
+//! straight to the SAI DMA and released with [`Consumer::consume`] once the
+//! half-transfer completes, instead of being copied into a separate DMA
+//! buffer first.  If `N` is a multiple of the DMA half-buffer length and the
+//! producer commits in whole half-buffers, [`Consumer::linear_chunk`] never
+//! has to straddle the wrap point.
+//!
+//! The producer side mirrors this: the decoder can write PCM directly into
+//! [`Producer::as_linear_slices_mut`] and publish it with
+//! [`Producer::commit`].
 //!
 //! # Constraints
 //!
-//! - `N` must be a power of two for efficient index wrapping (enforced at
-//!   runtime by a debug assertion; the API is otherwise correct for any `N`).
-//! - `no_std`, no `heapless` — the backing store lives entirely on the stack
-//!   or in a `static`.
-//! - This implementation is **not** interrupt-safe or `Send`.  Concurrent
-//!   access from different Embassy tasks must be protected with a `Mutex`.
-
-/// A fixed-capacity ring buffer for `i32` audio samples.
+//! - `N` must be a non-zero power of two.  This is checked at compile time
+//!   when [`RingBuffer::new`] is instantiated.
+//! - `no_std`, no `heapless` — the backing store lives entirely in the
+//!   struct, so the ring can be a `static` (e.g. in a `StaticCell`).
+//!
+//! # Memory ordering
+//!
+//! `write` and `read` are free-running wrapping counters, each stored by
+//! exactly one side.  Sample stores happen-before the `Release` store of
+//! `write`, which the consumer observes with `Acquire` (and symmetrically for
+//! `read`), so neither side ever sees a slot the other is still touching.
+
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// A fixed-capacity SPSC ring buffer for `i32` audio samples.
 ///
 /// Capacity is set at compile time via the const generic `N`.
 pub struct RingBuffer<const N: usize> {
-    buf: [i32; N],
-    /// Index of the next slot to read from.
-    read: usize,
-    /// Index of the next slot to write to.
-    write: usize,
-    /// Number of valid samples currently held.
-    count: usize,
+    buf: UnsafeCell<[i32; N]>,
+    /// Total samples ever consumed (wrapping).  Stored only by the consumer.
+    read: AtomicUsize,
+    /// Total samples ever produced (wrapping).  Stored only by the producer.
+    write: AtomicUsize,
 }
 
+// SAFETY: sample storage is only written through `&mut RingBuffer` or the
+// single `Producer`, and only in the free region; it is only read through
+// `&mut RingBuffer` or the single `Consumer`, and only in the filled region.
+// `split` takes `&mut self`, so at most one of each half exists.  The
+// regions are handed over with Release/Acquire stores of `write`/`read`.
+// `&self` methods only load the atomics.
+unsafe impl<const N: usize> Sync for RingBuffer<N> {}
+
 impl<const N: usize> RingBuffer<N> {
+    const CAPACITY_IS_POWER_OF_TWO: () =
+        assert!(N.is_power_of_two(), "RingBuffer capacity must be a power of two");
+
     /// Create a new, empty ring buffer.
     ///
     /// This function is `const` so that ring buffers may be stored in
     /// `static` variables without a runtime initialiser.
     pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)] // forces the compile-time capacity check
+        let () = Self::CAPACITY_IS_POWER_OF_TWO;
         Self {
-            buf: [0i32; N],
-            read: 0,
-            write: 0,
-            count: 0,
+            buf: UnsafeCell::new([0i32; N]),
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
         }
     }
 
+    /// Split into producer and consumer halves.
+    ///
+    /// The halves borrow the ring, so for cross-task use the ring must be
+    /// `'static` (e.g. `StaticCell<RingBuffer<N>>`).
+    pub fn split(&mut self) -> (Producer<'_, N>, Consumer<'_, N>) {
+        let ring: &Self = self;
+        (Producer { ring }, Consumer { ring })
+    }
+
     /// Write a slice of samples into the buffer.
     ///
     /// # Errors
@@ -47,39 +99,23 @@ impl<const N: usize> RingBuffer<N> {
     /// Returns `Err(())` if the slice would not fit in the remaining capacity.
     /// The buffer is left unchanged on error (the write is all-or-nothing).
     #[allow(clippy::result_unit_err)] // overflow is the only error; () is sufficient
-    #[allow(clippy::indexing_slicing)] // Safety: write < N invariant; data.len() <= N - count checked above
-    #[allow(clippy::arithmetic_side_effects)] // Safety: ring buffer wrap via % N; count += data.len() <= N
     pub fn write_slice(&mut self, data: &[i32]) -> Result<(), ()> {
-        if data.len() > N - self.count {
-            return Err(());
-        }
-        for &sample in data {
-            self.buf[self.write] = sample;
-            self.write = (self.write + 1) % N;
-        }
-        self.count += data.len();
-        Ok(())
+        Producer { ring: self }.write_slice(data)
     }
 
     /// Read up to `out.len()` samples from the buffer into `out`.
     ///
     /// Returns the number of samples actually read (may be less than
     /// `out.len()` if the buffer contains fewer samples than requested).
-    #[allow(clippy::indexing_slicing)] // Safety: read < N invariant; only reads up to self.count samples
-    #[allow(clippy::arithmetic_side_effects)] // Safety: ring buffer wrap via % N; count -= n where n <= count
     pub fn read_slice(&mut self, out: &mut [i32]) -> usize {
-        let n = out.len().min(self.count);
-        for slot in out.iter_mut().take(n) {
-            *slot = self.buf[self.read];
-            self.read = (self.read + 1) % N;
-        }
-        self.count -= n;
-        n
+        Consumer { ring: self }.read_slice(out)
     }
 
     /// Number of samples currently available to read.
     pub fn available(&self) -> usize {
-        self.count
+        self.write
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.read.load(Ordering::Acquire))
     }
 
     /// Maximum number of samples the buffer can hold.
@@ -89,12 +125,29 @@ impl<const N: usize> RingBuffer<N> {
 
     /// `true` when no samples are present.
     pub fn is_empty(&self) -> bool {
-        self.count == 0
+        self.available() == 0
     }
 
     /// `true` when the buffer is completely full.
     pub fn is_full(&self) -> bool {
-        self.count == N
+        self.available() == N
+    }
+
+    /// Ring slot for a free-running counter value.
+    const fn slot(counter: usize) -> usize {
+        counter & N.wrapping_sub(1)
+    }
+
+    /// Split the `len` slots starting at counter `from` into the part up to
+    /// the end of storage and the wrapped remainder: `(start, first, second)`.
+    fn regions(from: usize, len: usize) -> (usize, usize, usize) {
+        let start = Self::slot(from);
+        let first = len.min(N.saturating_sub(start));
+        (start, first, len.saturating_sub(first))
+    }
+
+    fn base(&self) -> *mut i32 {
+        self.buf.get().cast::<i32>()
     }
 }
 
@@ -103,3 +156,129 @@ impl<const N: usize> Default for RingBuffer<N> {
         Self::new()
     }
 }
+
+/// Writing half of a [`RingBuffer`].
+pub struct Producer<'a, const N: usize> {
+    ring: &'a RingBuffer<N>,
+}
+
+impl<const N: usize> Producer<'_, N> {
+    /// Number of samples that can be written without overwriting unread data.
+    pub fn free(&self) -> usize {
+        let write = self.ring.write.load(Ordering::Relaxed);
+        let read = self.ring.read.load(Ordering::Acquire);
+        N.saturating_sub(write.wrapping_sub(read))
+    }
+
+    /// Copy `data` into the ring.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err(())` if `data` does not fit; nothing is written.
+    #[allow(clippy::result_unit_err)] // overflow is the only error; () is sufficient
+    pub fn write_slice(&mut self, data: &[i32]) -> Result<(), ()> {
+        if data.len() > self.free() {
+            return Err(());
+        }
+        let (first, second) = self.as_linear_slices_mut();
+        let (head, tail) = data.split_at(data.len().min(first.len()));
+        if let Some(dst) = first.get_mut(..head.len()) {
+            dst.copy_from_slice(head);
+        }
+        if let Some(dst) = second.get_mut(..tail.len()) {
+            dst.copy_from_slice(tail);
+        }
+        self.commit(data.len());
+        Ok(())
+    }
+
+    /// The free region as up to two contiguous slices, in write order.
+    ///
+    /// Fill a prefix of these, then [`commit`](Self::commit) the count.
+    pub fn as_linear_slices_mut(&mut self) -> (&mut [i32], &mut [i32]) {
+        let write = self.ring.write.load(Ordering::Relaxed);
+        let (start, first, second) = RingBuffer::<N>::regions(write, self.free());
+        let base = self.ring.base();
+        // SAFETY: `start + first <= N` and `second <= start` (the free region
+        // never exceeds N slots), so both ranges are in bounds and disjoint.
+        // They lie in the free region, which the consumer does not read until
+        // `commit` publishes it, and `&mut self` prevents a second borrow.
+        unsafe {
+            (
+                core::slice::from_raw_parts_mut(base.add(start), first),
+                core::slice::from_raw_parts_mut(base, second),
+            )
+        }
+    }
+
+    /// Publish `n` samples written via [`as_linear_slices_mut`](Self::as_linear_slices_mut).
+    ///
+    /// `n` is clamped to [`free`](Self::free).
+    pub fn commit(&mut self, n: usize) {
+        let n = n.min(self.free());
+        let write = self.ring.write.load(Ordering::Relaxed);
+        self.ring.write.store(write.wrapping_add(n), Ordering::Release);
+    }
+}
+
+/// Reading half of a [`RingBuffer`].
+pub struct Consumer<'a, const N: usize> {
+    ring: &'a RingBuffer<N>,
+}
+
+impl<const N: usize> Consumer<'_, N> {
+    /// Number of samples ready to read.
+    pub fn available(&self) -> usize {
+        let read = self.ring.read.load(Ordering::Relaxed);
+        self.ring.write.load(Ordering::Acquire).wrapping_sub(read)
+    }
+
+    /// Copy up to `out.len()` samples out of the ring; returns the count.
+    pub fn read_slice(&mut self, out: &mut [i32]) -> usize {
+        let n = out.len().min(self.available());
+        let (first, second) = self.as_linear_slices();
+        let (head, tail) = out.split_at_mut(n.min(first.len()));
+        if let Some(src) = first.get(..head.len()) {
+            head.copy_from_slice(src);
+        }
+        let tail_len = n.saturating_sub(head.len());
+        if let (Some(dst), Some(src)) = (tail.get_mut(..tail_len), second.get(..tail_len)) {
+            dst.copy_from_slice(src);
+        }
+        self.consume(n);
+        n
+    }
+
+    /// The readable samples as up to two contiguous slices, oldest first.
+    ///
+    /// The slices point into the ring storage and stay valid until
+    /// [`consume`](Self::consume) releases them.
+    pub fn as_linear_slices(&self) -> (&[i32], &[i32]) {
+        let read = self.ring.read.load(Ordering::Relaxed);
+        let (start, first, second) = RingBuffer::<N>::regions(read, self.available());
+        let base = self.ring.base();
+        // SAFETY: bounds as in `Producer::as_linear_slices_mut`.  The filled
+        // region was published with a Release store the Acquire load in
+        // `available` synchronised with, and the producer does not write it
+        // again until `consume` hands it back.
+        unsafe {
+            (
+                core::slice::from_raw_parts(base.add(start), first),
+                core::slice::from_raw_parts(base, second),
+            )
+        }
+    }
+
+    /// The next `len` samples as one contiguous slice, if they are available
+    /// without crossing the end of storage — e.g. one DMA half-buffer.
+    pub fn linear_chunk(&self, len: usize) -> Option<&[i32]> {
+        self.as_linear_slices().0.get(..len)
+    }
+
+    /// Release `n` samples back to the producer (clamped to [`available`](Self::available)).
+    pub fn consume(&mut self, n: usize) {
+        let n = n.min(self.available());
+        let read = self.ring.read.load(Ordering::Relaxed);
+        self.ring.read.store(read.wrapping_add(n), Ordering::Release);
+    }
+}
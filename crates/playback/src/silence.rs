@@ -0,0 +1,178 @@
+//! Silence trimming — skip long silence at the ends of a track.
+//!
+//! [`SilenceTrim`] sits in the decode path and looks only at the PCM going
+//! through it, so finding the silence costs no extra reads of the file.  A
+//! frame is silent when every channel is at or below the configured
+//! threshold; what happens to a silent run depends on where it is:
+//!
+//! - **Leading**: held back from the start of the track.  If the run
+//!   reaches [`SilenceTrimConfig::min_ms`] it is dropped; a shorter one is
+//!   played as digital silence of the same length once the music starts.
+//! - **Inside the track**: the first `min_ms` play as decoded.  Beyond that
+//!   the stage holds the frames back and counts them; when the music
+//!   resumes the count is played out as digital silence, so a pause keeps
+//!   its length and only its noise floor is lost.
+//! - **Trailing**: whatever is held back when the decoder reaches the end
+//!   of the file is dropped by [`SilenceTrim::finish`].
+//!
+//! Held-back frames cost a counter, not memory.  Because they are not
+//! handed on, the decoder runs through a silent tail as fast as it can
+//! decode and the next track starts without the wait.
+//!
+//! Positions stay in track time: [`SilenceTrim::leading_frames`] tells the
+//! playback task how far the track had advanced when the first sound
+//! played.
+
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+use platform::SilenceTrimConfig;
+
+/// Streaming silence trimmer for interleaved PCM.
+#[derive(Debug, Clone)]
+pub struct SilenceTrim {
+    config: SilenceTrimConfig,
+    /// `config` as of the last [`start_track`](Self::start_track).
+    enabled: bool,
+    threshold: u32,
+    min_frames: u32,
+    channels: usize,
+    /// A frame above the threshold has been seen this track.
+    started: bool,
+    /// Frames in the current silent run.
+    run: u32,
+    /// Silent frames held back.
+    held: u32,
+    /// Frames of digital silence still to play before the next input.
+    replay: u32,
+    /// Leading frames dropped this track.
+    leading: u32,
+}
+
+impl SilenceTrim {
+    /// A trimmer for tracks at 44.1 kHz stereo until
+    /// [`start_track`](Self::start_track) says otherwise.
+    pub fn new(config: SilenceTrimConfig) -> Self {
+        let mut trim = Self {
+            config,
+            enabled: false,
+            threshold: 0,
+            min_frames: 0,
+            channels: 2,
+            started: false,
+            run: 0,
+            held: 0,
+            replay: 0,
+            leading: 0,
+        };
+        trim.start_track(44_100, 2);
+        trim
+    }
+
+    /// Settings in use.
+    pub fn config(&self) -> SilenceTrimConfig {
+        self.config
+    }
+
+    /// Change the settings; takes effect at the next track.
+    pub fn set_config(&mut self, config: SilenceTrimConfig) {
+        self.config = config;
+    }
+
+    /// Reset for a new track (or a seek) of `sample_rate` and `channels`.
+    pub fn start_track(&mut self, sample_rate: u32, channels: u8) {
+        self.enabled = self.config.enabled;
+        self.threshold = self.config.threshold();
+        self.min_frames = self.config.min_frames(sample_rate);
+        self.channels = usize::from(channels).max(1);
+        self.started = false;
+        self.run = 0;
+        self.held = 0;
+        self.replay = 0;
+        self.leading = 0;
+    }
+
+    /// Frames dropped from the start of the track.
+    pub fn leading_frames(&self) -> u32 {
+        self.leading
+    }
+
+    /// Pass interleaved `input` to `output`, holding back or dropping
+    /// silence.  Returns the number of samples consumed from `input` and
+    /// written to `output`, both whole frames.  Call again with the rest of
+    /// the input once `output` has been drained.  Disabled, it copies.
+    pub fn process(&mut self, input: &[i32], output: &mut [i32]) -> (usize, usize) {
+        let ch = self.channels;
+        if !self.enabled {
+            let frames = input.len().min(output.len()).checked_div(ch).unwrap_or(0);
+            let n = frames.saturating_mul(ch);
+            if let (Some(src), Some(dst)) = (input.get(..n), output.get_mut(..n)) {
+                dst.copy_from_slice(src);
+            }
+            return (n, n);
+        }
+
+        let mut frames = input.chunks_exact(ch);
+        let mut slots = output.chunks_exact_mut(ch);
+        let (mut consumed, mut produced) = (0usize, 0usize);
+        loop {
+            if self.replay > 0 {
+                let Some(slot) = slots.next() else { break };
+                slot.fill(0);
+                self.replay = self.replay.saturating_sub(1);
+                produced = produced.saturating_add(ch);
+                continue;
+            }
+            let Some(frame) = frames.clone().next() else {
+                break;
+            };
+            let silent = frame.iter().all(|s| s.unsigned_abs() <= self.threshold);
+            let run = if silent {
+                self.run.saturating_add(1)
+            } else {
+                0
+            };
+            if silent && (!self.started || run > self.min_frames) {
+                self.run = run;
+                self.held = self.held.saturating_add(1);
+                frames.next();
+                consumed = consumed.saturating_add(ch);
+                continue;
+            }
+            if !silent && self.held > 0 {
+                if !self.started && self.held >= self.min_frames {
+                    self.leading = self.held;
+                } else {
+                    self.replay = self.held;
+                }
+                self.held = 0;
+                self.started = true;
+                continue;
+            }
+            let Some(slot) = slots.next() else { break };
+            slot.copy_from_slice(frame);
+            self.run = run;
+            self.started |= !silent;
+            frames.next();
+            consumed = consumed.saturating_add(ch);
+            produced = produced.saturating_add(ch);
+        }
+        (consumed, produced)
+    }
+
+    /// End of the track: drop the silence held back and return how many
+    /// frames that was.  Call once the last [`process`](Self::process) has
+    /// taken all its input.  A track that never rose above the threshold is
+    /// dropped whole.
+    pub fn finish(&mut self) -> u32 {
+        let trailing = self.held;
+        self.held = 0;
+        self.replay = 0;
+        trailing
+    }
+}
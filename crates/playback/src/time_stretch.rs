@@ -0,0 +1,359 @@
+//! Time stretching — playback speed without a pitch change.
+//!
+//! [`TimeStretch`] is a WSOLA (waveform-similarity overlap-add) stage in
+//! integer arithmetic.  Output is built from segments of `2 × HOP` input
+//! frames, each cross-faded over `HOP` frames into the previous one.  The
+//! segments are taken `HOP × speed` frames apart in the input, so the audio
+//! runs faster or slower while every segment keeps its own waveform, and
+//! with it the pitch.  Before each cross-fade the segment start is moved by
+//! up to [`SEEK`] frames to where the input best matches the audio being
+//! faded out, so the overlap adds in phase instead of smearing.
+//!
+//! The match is scored on a mono mix at reduced resolution (a coarse pass
+//! over every fourth offset and frame, then a fine pass around the best),
+//! which keeps the search to a few percent of the Cortex-M7 at 44.1 kHz and
+//! makes stereo cost the same as mono.
+//!
+//! At [`PlaybackSpeed::NORMAL`] a fresh stage passes samples through
+//! untouched.  Once stretching has started, returning to 1× keeps the stage
+//! running at 1×, where the best match is the very next input frame and the
+//! output is the input unchanged, so no audio is dropped; [`reset`]
+//! (on a seek or a track change) returns it to pass-through.
+//!
+//! [`reset`]: TimeStretch::reset
+
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+use platform::PlaybackSpeed;
+
+/// Cross-fade length and output block size, in frames (11.6 ms at 44.1 kHz).
+pub const HOP: usize = 512;
+
+/// Furthest a segment start moves from its nominal position, in frames —
+/// one period of a voice down to 170 Hz at 44.1 kHz.
+pub const SEEK: usize = 256;
+
+/// Largest channel count handled.
+pub const MAX_CHANNELS: usize = 2;
+
+/// Input window: a full search range around a segment, plus room for the
+/// next decoded block.
+const INPUT_FRAMES: usize = 2 * SEEK + 3 * HOP;
+
+/// Divisor taking a left-justified sample to 14 bits for the match score.
+const SCORE_SCALE: i64 = 1 << 18;
+
+/// Offset stride and frame stride of the coarse search pass.
+const COARSE_STRIDE: usize = 4;
+
+/// Frame stride of the fine search pass.
+const FINE_STRIDE: usize = 2;
+
+/// Pitch-preserving speed change for interleaved PCM.
+///
+/// About 24 KB; keep it in a `static` or a task's state rather than on a
+/// short-lived stack frame.
+pub struct TimeStretch {
+    speed: PlaybackSpeed,
+    channels: usize,
+    /// Buffered input, `filled` frames from the start.
+    input: [i32; INPUT_FRAMES * MAX_CHANNELS],
+    filled: usize,
+    /// Nominal start of the next segment in hundredths of a frame,
+    /// relative to the start of `input`.
+    next_start: i64,
+    /// Second half of the last segment: faded out by the next one.
+    tail: [i32; HOP * MAX_CHANNELS],
+    primed: bool,
+    /// Finished block, `out_len` frames, `out_read` already handed out.
+    out: [i32; HOP * MAX_CHANNELS],
+    out_len: usize,
+    out_read: usize,
+}
+
+impl TimeStretch {
+    /// A pass-through stage for `channels` interleaved channels (clamped to
+    /// `1..=MAX_CHANNELS`).
+    // LARGE_STACK_ARRAYS: const so the stage can be built straight into a
+    // `static`; see the type docs.
+    #[allow(clippy::large_stack_arrays)]
+    pub const fn new(channels: u8) -> Self {
+        Self {
+            speed: PlaybackSpeed::NORMAL,
+            channels: if channels > 1 { MAX_CHANNELS } else { 1 },
+            input: [0; INPUT_FRAMES * MAX_CHANNELS],
+            filled: 0,
+            next_start: 0,
+            tail: [0; HOP * MAX_CHANNELS],
+            primed: false,
+            out: [0; HOP * MAX_CHANNELS],
+            out_len: 0,
+            out_read: 0,
+        }
+    }
+
+    /// Current speed.
+    pub fn speed(&self) -> PlaybackSpeed {
+        self.speed
+    }
+
+    /// Change speed; takes effect from the next output block.
+    pub fn set_speed(&mut self, speed: PlaybackSpeed) {
+        self.speed = speed;
+    }
+
+    /// Channel count the stage was set up for.
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Drop buffered audio (seek, track change) and return to pass-through
+    /// if the speed is 1×.  The channel count is kept.
+    pub fn reset(&mut self) {
+        self.filled = 0;
+        self.next_start = 0;
+        self.primed = false;
+        self.out_len = 0;
+        self.out_read = 0;
+    }
+
+    /// Whether samples currently pass straight through.
+    pub fn is_bypassed(&self) -> bool {
+        self.speed.is_normal() && !self.primed && self.filled == 0
+    }
+
+    /// Stretch interleaved `input` into `output`.  Returns the number of
+    /// samples consumed from `input` and written to `output`, both whole
+    /// frames.  Call again with the rest of the input once `output` has been
+    /// drained; a call consumes input only while it has room to produce.
+    pub fn process(&mut self, input: &[i32], output: &mut [i32]) -> (usize, usize) {
+        let ch = self.channels;
+        if self.is_bypassed() {
+            let frames = input.len().min(output.len()).checked_div(ch).unwrap_or(0);
+            let n = frames.saturating_mul(ch);
+            if let (Some(src), Some(dst)) = (input.get(..n), output.get_mut(..n)) {
+                dst.copy_from_slice(src);
+            }
+            return (n, n);
+        }
+
+        let (mut consumed, mut produced) = (0usize, 0usize);
+        loop {
+            produced =
+                produced.saturating_add(self.drain(output.get_mut(produced..).unwrap_or_default()));
+            if output.len().saturating_sub(produced) < ch {
+                break;
+            }
+            if self.step() {
+                continue;
+            }
+            let taken = self.fill(input.get(consumed..).unwrap_or_default());
+            if taken == 0 {
+                break;
+            }
+            consumed = consumed.saturating_add(taken);
+        }
+        (consumed, produced)
+    }
+
+    /// Copy finished frames into `output`; returns samples written.
+    fn drain(&mut self, output: &mut [i32]) -> usize {
+        let ch = self.channels;
+        let frames = self
+            .out_len
+            .saturating_sub(self.out_read)
+            .min(output.len().checked_div(ch).unwrap_or(0));
+        let from = self.out_read.saturating_mul(ch);
+        let n = frames.saturating_mul(ch);
+        if let (Some(src), Some(dst)) = (
+            self.out.get(from..from.saturating_add(n)),
+            output.get_mut(..n),
+        ) {
+            dst.copy_from_slice(src);
+        }
+        self.out_read = self.out_read.saturating_add(frames);
+        n
+    }
+
+    /// Buffer whole frames of `input`; returns samples taken.
+    fn fill(&mut self, input: &[i32]) -> usize {
+        let ch = self.channels;
+        let frames = INPUT_FRAMES
+            .saturating_sub(self.filled)
+            .min(input.len().checked_div(ch).unwrap_or(0));
+        let at = self.filled.saturating_mul(ch);
+        let n = frames.saturating_mul(ch);
+        if let (Some(src), Some(dst)) =
+            (input.get(..n), self.input.get_mut(at..at.saturating_add(n)))
+        {
+            dst.copy_from_slice(src);
+        }
+        self.filled = self.filled.saturating_add(frames);
+        n
+    }
+
+    /// Produce the next output block if the output is drained and enough
+    /// input is buffered.  Returns `true` when a block was made.
+    fn step(&mut self) -> bool {
+        if self.out_read < self.out_len {
+            return false;
+        }
+        let ch = self.channels;
+        let block = HOP.saturating_mul(ch);
+        let hop_100 = i64::try_from(HOP.saturating_mul(100)).unwrap_or(i64::MAX);
+        let advance =
+            i64::try_from(HOP.saturating_mul(usize::from(self.speed.percent()))).unwrap_or(hop_100);
+
+        if !self.primed {
+            // The first HOP frames become the tail of a segment that
+            // started HOP frames before the input.
+            if self.filled < HOP {
+                return false;
+            }
+            if let (Some(src), Some(dst)) = (self.input.get(..block), self.tail.get_mut(..block)) {
+                dst.copy_from_slice(src);
+            }
+            self.primed = true;
+            self.next_start = advance.saturating_sub(hop_100);
+        }
+
+        // Need the whole search range, and a full segment at its far end.
+        let nominal = self.next_start.div_euclid(100);
+        let seek = i64::try_from(SEEK).unwrap_or(0);
+        let span = i64::try_from(2 * HOP).unwrap_or(i64::MAX);
+        let filled = i64::try_from(self.filled).unwrap_or(0);
+        if nominal.saturating_add(seek).saturating_add(span) > filled {
+            return false;
+        }
+        let lo = usize::try_from(nominal.saturating_sub(seek)).unwrap_or(0);
+        let hi = usize::try_from(nominal.saturating_add(seek)).unwrap_or(0);
+        let start = self.best_start(lo, hi);
+
+        // Cross-fade the tail out and the new segment's first half in.
+        let seg = start.saturating_mul(ch);
+        let hop = i64::try_from(HOP).unwrap_or(1);
+        let new = self
+            .input
+            .get(seg..seg.saturating_add(block))
+            .unwrap_or_default();
+        for (i, ((out, &old), &new)) in self
+            .out
+            .iter_mut()
+            .zip(self.tail.iter())
+            .zip(new.iter())
+            .take(block)
+            .enumerate()
+        {
+            let t = i64::try_from(i.checked_div(ch).unwrap_or(0)).unwrap_or(0);
+            let mixed = i64::from(old)
+                .saturating_mul(hop.saturating_sub(t))
+                .saturating_add(i64::from(new).saturating_mul(t))
+                .checked_div(hop)
+                .unwrap_or(0);
+            *out = i32::try_from(mixed).unwrap_or(old);
+        }
+        self.out_len = HOP;
+        self.out_read = 0;
+
+        // The second half fades out under the next segment.
+        let second = seg.saturating_add(block);
+        if let (Some(src), Some(dst)) = (
+            self.input.get(second..second.saturating_add(block)),
+            self.tail.get_mut(..block),
+        ) {
+            dst.copy_from_slice(src);
+        }
+
+        // Advance, then drop input before the next search range.
+        self.next_start = self.next_start.saturating_add(advance);
+        let keep_from = self
+            .next_start
+            .div_euclid(100)
+            .saturating_sub(seek)
+            .clamp(0, filled);
+        let drop = usize::try_from(keep_from).unwrap_or(0);
+        if drop > 0 {
+            let from = drop.saturating_mul(ch);
+            let to = self.filled.saturating_mul(ch);
+            if from <= to && to <= self.input.len() {
+                self.input.copy_within(from..to, 0);
+            }
+            self.filled = self.filled.saturating_sub(drop);
+            self.next_start = self
+                .next_start
+                .saturating_sub(keep_from.saturating_mul(100));
+        }
+        true
+    }
+
+    /// Segment start in `lo..=hi` whose first half best matches the tail.
+    fn best_start(&self, lo: usize, hi: usize) -> usize {
+        let hi = hi.max(lo);
+        let mut best = (lo, self.score(lo, COARSE_STRIDE));
+        for start in (lo..=hi).step_by(COARSE_STRIDE).skip(1) {
+            let score = self.score(start, COARSE_STRIDE);
+            if score.beats(best.1) {
+                best = (start, score);
+            }
+        }
+        let around = best.0;
+        let mut best = (around, self.score(around, FINE_STRIDE));
+        let fine_lo = around.saturating_sub(COARSE_STRIDE - 1).max(lo);
+        let fine_hi = around.saturating_add(COARSE_STRIDE - 1).min(hi);
+        for start in fine_lo..=fine_hi {
+            let score = self.score(start, FINE_STRIDE);
+            if score.beats(best.1) {
+                best = (start, score);
+            }
+        }
+        best.0
+    }
+
+    /// Match between the tail and the input from frame `start`, on every
+    /// `stride`-th frame of the mono mix.
+    fn score(&self, start: usize, stride: usize) -> Score {
+        let ch = self.channels;
+        let mono = |buf: &[i32], frame: usize| -> i64 {
+            let at = frame.saturating_mul(ch);
+            buf.get(at..at.saturating_add(ch))
+                .unwrap_or_default()
+                .iter()
+                .fold(0i64, |sum, &s| {
+                    sum.saturating_add(i64::from(s) / SCORE_SCALE)
+                })
+        };
+        let mut score = Score { corr: 0, energy: 0 };
+        for frame in (0..HOP).step_by(stride) {
+            let x = mono(&self.input, start.saturating_add(frame));
+            let y = mono(&self.tail, frame);
+            score.corr = score.corr.saturating_add(x.saturating_mul(y));
+            score.energy = score.energy.saturating_add(x.saturating_mul(x));
+        }
+        score
+    }
+}
+
+/// Cross-correlation of a candidate with the tail and the candidate's
+/// energy; candidates compare by `corr × |corr| / energy`, the squared
+/// normalised correlation with its sign, so a loud passage does not win
+/// only by being loud.
+#[derive(Debug, Clone, Copy)]
+struct Score {
+    corr: i64,
+    energy: i64,
+}
+
+impl Score {
+    fn beats(self, other: Self) -> bool {
+        let signed_sq = |c: i64| i128::from(c).saturating_mul(i128::from(c.unsigned_abs()));
+        let lhs = signed_sq(self.corr).saturating_mul(i128::from(other.energy.max(1)));
+        let rhs = signed_sq(other.corr).saturating_mul(i128::from(self.energy.max(1)));
+        lhs > rhs
+    }
+}
@@ -8,6 +8,14 @@
 //! (0 – 100) to the hardware register value via the [`VolumePercent`] and
 //! [`AttenuationRegister`] newtypes, which enforce valid ranges at compile time.
 
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
 use platform::audio_types::{AttenuationRegister, VolumePercent};
 
 /// Map a [`VolumePercent`] to an ES9038Q2M [`AttenuationRegister`] value.
@@ -0,0 +1,282 @@
+//! WAV decoder — RIFF chunk walking and PCM sample conversion.
+//!
+//! [`WavDecoder`] takes the file from the `RIFF` header.  The header and
+//! every chunk before `data` (`fmt `, `LIST`, odd-sized `JUNK` with its pad
+//! byte, …) are consumed by calls that produce no samples; a chunk longer
+//! than the input is skipped across calls.  After that each call converts
+//! as many whole sample frames as fit in both the input and the
+//! [`PcmFrame`], and the end of the `data` chunk returns
+//! [`DecodeError::EndOfStream`], so trailing chunks are never played.
+//!
+//! Supported payloads: integer PCM at 8 (unsigned), 16, 24 and 32 bits and
+//! 32-bit IEEE float, plain or as `WAVE_FORMAT_EXTENSIBLE`, mono or stereo.
+//! Anything else returns [`DecodeError::UnsupportedFormat`].
+
+// Audio hot path: must not panic (see the crate docs and tests/panic_free.rs).
+#![deny(
+    clippy::unreachable,
+    clippy::panic_in_result_fn,
+    clippy::unwrap_in_result,
+    clippy::string_slice
+)]
+
+use crate::decoder::{AudioFormat, Codec, DecodeError, FrameDecoder, PcmFrame};
+
+/// Most channels the decoder outputs.
+pub const MAX_CHANNELS: u16 = 2;
+
+const FORMAT_PCM: u16 = 0x0001;
+const FORMAT_FLOAT: u16 = 0x0003;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Sample layout from the `fmt ` chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavFormat {
+    /// Channel count.
+    pub channels: u8,
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Bits per sample in the container.
+    pub bits_per_sample: u16,
+    /// IEEE float samples rather than integer PCM.
+    pub float: bool,
+}
+
+impl WavFormat {
+    /// Parse a `fmt ` chunk body.
+    ///
+    /// # Errors
+    ///
+    /// [`DecodeError::InvalidData`] for a truncated chunk or a block
+    /// alignment that does not match the sample size;
+    /// [`DecodeError::UnsupportedFormat`] for compressed payloads, unusual
+    /// sample sizes and more than [`MAX_CHANNELS`] channels.
+    pub fn parse(body: &[u8]) -> Result<Self, DecodeError> {
+        let field = |at: usize| {
+            body.get(at..at.saturating_add(2))
+                .and_then(|b| <[u8; 2]>::try_from(b).ok())
+                .map(u16::from_le_bytes)
+                .ok_or(DecodeError::InvalidData)
+        };
+        let sample_rate = body
+            .get(4..8)
+            .and_then(|b| <[u8; 4]>::try_from(b).ok())
+            .map(u32::from_le_bytes)
+            .ok_or(DecodeError::InvalidData)?;
+        let channels = field(2)?;
+        let block_align = field(12)?;
+        let bits_per_sample = field(14)?;
+        // The extensible header carries the real format tag as the first
+        // two bytes of its sub-format GUID.
+        let tag = match field(0)? {
+            FORMAT_EXTENSIBLE => field(24)?,
+            tag => tag,
+        };
+        let float = match (tag, bits_per_sample) {
+            (FORMAT_PCM, 8 | 16 | 24 | 32) => false,
+            (FORMAT_FLOAT, 32) => true,
+            _ => return Err(DecodeError::UnsupportedFormat),
+        };
+        if channels == 0 || channels > MAX_CHANNELS {
+            return Err(DecodeError::UnsupportedFormat);
+        }
+        if block_align != channels.saturating_mul(bits_per_sample / 8) {
+            return Err(DecodeError::InvalidData);
+        }
+        Ok(Self {
+            channels: u8::try_from(channels).map_err(|_| DecodeError::UnsupportedFormat)?,
+            sample_rate,
+            bits_per_sample,
+            float,
+        })
+    }
+
+    /// Bytes per sample of one channel.
+    pub fn bytes_per_sample(&self) -> usize {
+        usize::from(self.bits_per_sample / 8)
+    }
+
+    /// Bytes per sample frame (all channels).
+    pub fn block_align(&self) -> usize {
+        self.bytes_per_sample()
+            .saturating_mul(usize::from(self.channels))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Expecting the 12-byte `RIFF`/`WAVE` header.
+    Riff,
+    /// Expecting a chunk header.
+    Chunks,
+    /// Skipping the rest of a chunk before `data`.
+    Skip(usize),
+    /// Inside the `data` chunk, with this many bytes left.
+    Data(usize),
+}
+
+/// Streaming WAV decoder.
+pub struct WavDecoder {
+    format: Option<WavFormat>,
+    state: State,
+}
+
+impl WavDecoder {
+    /// Create a decoder; the format is read from the stream's `fmt ` chunk.
+    pub const fn new() -> Self {
+        Self {
+            format: None,
+            state: State::Riff,
+        }
+    }
+
+    /// Consume the RIFF header or one chunk before `data`.
+    fn header(&mut self, input: &[u8]) -> Result<usize, DecodeError> {
+        match self.state {
+            State::Riff => {
+                let header = input.get(..12).ok_or(DecodeError::EndOfStream)?;
+                if header.get(..4) != Some(b"RIFF".as_slice())
+                    || header.get(8..) != Some(b"WAVE".as_slice())
+                {
+                    return Err(DecodeError::InvalidData);
+                }
+                self.state = State::Chunks;
+                Ok(12)
+            }
+            State::Skip(left) => {
+                let n = left.min(input.len());
+                let left = left.wrapping_sub(n);
+                self.state = if left == 0 {
+                    State::Chunks
+                } else {
+                    State::Skip(left)
+                };
+                Ok(n)
+            }
+            State::Chunks => {
+                let header = input.get(..8).ok_or(DecodeError::EndOfStream)?;
+                let len = header
+                    .get(4..)
+                    .and_then(|b| <[u8; 4]>::try_from(b).ok())
+                    .map(u32::from_le_bytes)
+                    .and_then(|len| usize::try_from(len).ok())
+                    .ok_or(DecodeError::InvalidData)?;
+                // Odd-sized chunks are followed by a pad byte.
+                let padded = len.saturating_add(len % 2);
+                match header.get(..4) {
+                    Some(b"fmt ") => {
+                        let body = input
+                            .get(8..8usize.saturating_add(len))
+                            .ok_or(DecodeError::EndOfStream)?;
+                        self.format = Some(WavFormat::parse(body)?);
+                        self.state = State::Skip(padded.saturating_add(8));
+                    }
+                    Some(b"data") => {
+                        if self.format.is_none() {
+                            return Err(DecodeError::InvalidData);
+                        }
+                        self.state = State::Data(len);
+                        return Ok(8);
+                    }
+                    _ => self.state = State::Skip(padded.saturating_add(8)),
+                }
+                self.header(input)
+            }
+            State::Data(_) => Ok(0),
+        }
+    }
+}
+
+impl Default for WavDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoder for WavDecoder {
+    type Error = DecodeError;
+
+    /// Consume header data, or convert the next run of samples from
+    /// `input` into `output` (interleaved, left-justified).
+    ///
+    /// Returns [`DecodeError::EndOfStream`] once the `data` chunk is used
+    /// up, or when `input` holds less than one sample frame.
+    fn decode_frame(&mut self, input: &[u8], output: &mut PcmFrame) -> Result<usize, Self::Error> {
+        if input.is_empty() {
+            return Err(DecodeError::EndOfStream);
+        }
+        let (State::Data(left), Some(format)) = (self.state, self.format) else {
+            output.len = 0;
+            return self.header(input);
+        };
+
+        let block_align = format.block_align();
+        let channels = usize::from(format.channels);
+        let frames = left
+            .min(input.len())
+            .checked_div(block_align)
+            .unwrap_or(0)
+            .min(output.samples.len().checked_div(channels).unwrap_or(0));
+        if frames == 0 {
+            return Err(DecodeError::EndOfStream);
+        }
+        let bytes = frames.saturating_mul(block_align);
+        let payload = input.get(..bytes).ok_or(DecodeError::EndOfStream)?;
+        let width = format.bytes_per_sample();
+        let out = output.samples.iter_mut();
+        for (sample, raw) in out.zip(payload.chunks_exact(width)) {
+            *sample = convert(raw, format.float);
+        }
+        self.state = State::Data(left.saturating_sub(bytes));
+        output.len = frames;
+        output.sample_rate = format.sample_rate;
+        output.channels = format.channels;
+        Ok(bytes)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.format.map_or(0, |f| f.sample_rate)
+    }
+
+    fn channels(&self) -> u8 {
+        self.format.map_or(0, |f| f.channels)
+    }
+}
+
+/// Convert one little-endian sample to a left-justified `i32`.
+fn convert(raw: &[u8], float: bool) -> i32 {
+    match *raw {
+        // 8-bit WAV is unsigned, centred on 128.
+        [byte] => i32::from(byte).wrapping_sub(128).wrapping_shl(24),
+        [b0, b1] => i32::from_le_bytes([0, 0, b0, b1]),
+        [b0, b1, b2] => i32::from_le_bytes([0, b0, b1, b2]),
+        [b0, b1, b2, b3] if float => float_to_i32(f32::from_le_bytes([b0, b1, b2, b3])),
+        [b0, b1, b2, b3] => i32::from_le_bytes([b0, b1, b2, b3]),
+        _ => 0,
+    }
+}
+
+/// Scale a float sample in [-1.0, 1.0] to the full `i32` range, clamping.
+// SAFETY (cast): the clamped product is within i32 range, and float-to-int
+// `as` saturates anyway (NaN becomes 0).
+#[allow(clippy::cast_possible_truncation)]
+fn float_to_i32(sample: f32) -> i32 {
+    (sample.clamp(-1.0, 1.0) * i32::MAX as f32) as i32
+}
+
+// ─── Registry entry ───────────────────────────────────────────────────────────
+
+/// WAV entry in [`crate::decoder::CODECS`].
+pub const CODEC: Codec = Codec {
+    format: AudioFormat::Wav,
+    open: Some(open),
+};
+
+/// The `fmt ` chunk is read from the stream, so no setup data is needed.
+fn open(
+    _head: &[u8],
+    run: &mut dyn FnMut(&mut dyn FrameDecoder<Error = DecodeError>),
+) -> Result<(), DecodeError> {
+    run(&mut WavDecoder::new());
+    Ok(())
+}
@@ -0,0 +1,202 @@
+//! Decoder conformance against the crafted vectors in `playback::test_vectors`.
+//!
+//! Every lossless vector is opened through [`decoder::CODECS`] (the same
+//! path the player takes) and must decode to the PCM hash pinned in
+//! `tests/vectors/manifest.txt`.  The FLAC vectors cover every subframe
+//! type, stereo mode and Rice coding variant, and large stereo blocks that
+//! the decoder emits in windows.
+//!
+//! MP3 vectors are real files from `SOUL_TEST_VECTORS` (`x.mp3` plus an
+//! `x.mp3.crc32` sidecar with the expected hash); they run with
+//! `--features mp3` and are skipped when the variable is unset.
+//!
+//! Requires the `std` feature (enabled automatically under `cargo test --workspace`
+//! through xtask's dependency on playback).
+//!
+//! [`decoder::CODECS`]: playback::decoder::CODECS
+#![cfg(feature = "std")]
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+
+use playback::decoder::{codec_for, AudioFormat, DecodeError, FrameDecoder, PcmFrame};
+use playback::test_vectors::{self, decode_hash, parse_manifest, pcm_hash, Vector};
+
+const MANIFEST: &str = include_str!("vectors/manifest.txt");
+
+fn expected(name: &str) -> u32 {
+    parse_manifest(MANIFEST)
+        .into_iter()
+        .find(|(n, _)| *n == name)
+        .unwrap_or_else(|| panic!("{name} missing from manifest"))
+        .1
+}
+
+#[test]
+fn manifest_covers_every_vector() {
+    let vectors = test_vectors::all();
+    let manifest = parse_manifest(MANIFEST);
+    assert_eq!(manifest.len(), vectors.len());
+    for v in &vectors {
+        assert_eq!(pcm_hash(&v.pcm), expected(v.name), "{}", v.name);
+    }
+}
+
+#[test]
+fn vectors_are_detected_by_magic() {
+    for v in test_vectors::all() {
+        let magic = &v.bytes[..4];
+        match v.format {
+            AudioFormat::Flac => assert_eq!(magic, b"fLaC", "{}", v.name),
+            AudioFormat::Wav => assert_eq!(magic, b"RIFF", "{}", v.name),
+            AudioFormat::Mp3 | AudioFormat::Aac | AudioFormat::Opus => {
+                unreachable!("lossy vectors are external")
+            }
+        }
+    }
+}
+
+// ─── Lossless decoders ───────────────────────────────────────────────────────
+
+/// Open `v` through the codec registry and run `f` on the decoder.
+fn with_decoder<T>(
+    v: &Vector,
+    f: impl FnOnce(&mut dyn FrameDecoder<Error = DecodeError>) -> T,
+) -> T {
+    let open = codec_for(v.format)
+        .and_then(|codec| codec.open)
+        .unwrap_or_else(|| panic!("{}: no decoder", v.name));
+    let mut f = Some(f);
+    let mut out = None;
+    open(&v.bytes, &mut |decoder| out = f.take().map(|f| f(decoder))).unwrap();
+    out.unwrap_or_else(|| panic!("{}: decoder never ran", v.name))
+}
+
+#[test]
+fn lossless_vectors_conform() {
+    for v in test_vectors::all() {
+        let (hash, frames) = with_decoder(&v, |decoder| {
+            let result = decode_hash(decoder, &v.bytes);
+            assert_eq!(decoder.sample_rate(), v.sample_rate, "{}", v.name);
+            assert_eq!(decoder.channels(), v.channels, "{}", v.name);
+            result
+        })
+        .unwrap_or_else(|e| panic!("{}: {e:?}", v.name));
+        assert_eq!(frames, v.frames(), "{}", v.name);
+        assert_eq!(hash, expected(v.name), "{}", v.name);
+    }
+}
+
+#[test]
+fn truncated_vectors_end_cleanly() {
+    for v in test_vectors::all() {
+        let cut = &v.bytes[..v.bytes.len() - 3];
+        let (_, frames) = with_decoder(&v, |decoder| decode_hash(decoder, cut))
+            .unwrap_or_else(|e| panic!("{}: {e:?}", v.name));
+        assert!(frames < v.frames(), "{}", v.name);
+    }
+}
+
+/// Samples per channel of every FLAC frame, joining windowed output.
+fn flac_blocks(name: &str) -> Vec<usize> {
+    let v = test_vectors::by_name(name).unwrap();
+    with_decoder(&v, |decoder| {
+        let mut frame = PcmFrame::default();
+        let (mut blocks, mut block, mut pos) = (Vec::new(), 0, 0);
+        while pos < v.bytes.len() {
+            let consumed = decoder.decode_frame(&v.bytes[pos..], &mut frame).unwrap();
+            block += frame.len;
+            if consumed > 0 && block > 0 {
+                blocks.push(block);
+                block = 0;
+            }
+            pos += consumed;
+        }
+        blocks
+    })
+}
+
+#[test]
+fn flac_block_layouts() {
+    assert_eq!(
+        flac_blocks("flac_s16_stereo_short_tail"),
+        [4096, 4096, 1808]
+    );
+    assert_eq!(flac_blocks("flac_s24_mono_odd_block"), [1153, 1153, 694]);
+
+    let min = flac_blocks("flac_s16_stereo_min_block");
+    assert_eq!(min.len(), 32);
+    assert!(min.iter().take(31).all(|&b| b == 16));
+    assert_eq!(min.last(), Some(&4));
+
+    let mixed = flac_blocks("flac_s16_stereo_mixed");
+    assert_eq!(mixed.len(), 12);
+    assert!(mixed.iter().take(11).all(|&b| b == 1152));
+    assert_eq!(mixed.last(), Some(&652));
+}
+
+#[test]
+fn wav_extra_chunks_have_odd_size_and_pad() {
+    let v = test_vectors::by_name("wav_s16_stereo_list_chunk").unwrap();
+    let junk = v.bytes.windows(4).position(|w| w == b"JUNK").unwrap();
+    assert_eq!(v.bytes[junk + 4] % 2, 1);
+    assert_eq!(&v.bytes[junk + 8 + 5 + 1..junk + 8 + 5 + 5], b"data");
+}
+
+// ─── ID3v2 ───────────────────────────────────────────────────────────────────
+
+#[test]
+fn id3v2_padding_is_stripped() {
+    let stream = [0xFF, 0xFB, 0x90, 0x64, 1, 2, 3];
+    for padding in [0, 1, 127, 128, 4096] {
+        let tagged = test_vectors::with_id3v2_padding(&stream, "Roads", padding);
+        assert_eq!(
+            test_vectors::strip_id3v2(&tagged),
+            stream,
+            "padding={padding}"
+        );
+    }
+    assert_eq!(test_vectors::strip_id3v2(&stream), stream);
+}
+
+// ─── MP3 (external vectors) ──────────────────────────────────────────────────
+
+#[cfg(feature = "mp3")]
+#[test]
+fn mp3_vectors_conform() {
+    use playback::mp3_decoder::NanoMp3Decoder;
+    use playback::test_vectors::{decode_hash, strip_id3v2, with_id3v2_padding};
+
+    let Ok(dir) = std::env::var("SOUL_TEST_VECTORS") else {
+        eprintln!("SOUL_TEST_VECTORS not set; skipping MP3 conformance");
+        return;
+    };
+    let mut checked = 0;
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if !path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("mp3"))
+        {
+            continue;
+        }
+        let sidecar = path.with_extension("mp3.crc32");
+        let Ok(text) = std::fs::read_to_string(&sidecar) else {
+            eprintln!("{}: no .crc32 sidecar, skipping", path.display());
+            continue;
+        };
+        let want = u32::from_str_radix(text.trim(), 16).unwrap();
+        let data = std::fs::read(&path).unwrap();
+
+        let (hash, frames) = decode_hash(&mut NanoMp3Decoder::new(), strip_id3v2(&data)).unwrap();
+        assert!(frames > 0, "{}", path.display());
+        assert_eq!(hash, want, "{}", path.display());
+
+        // The same stream behind a heavily padded tag must decode identically.
+        let padded = with_id3v2_padding(strip_id3v2(&data), "padded", 8192);
+        let (padded_hash, _) =
+            decode_hash(&mut NanoMp3Decoder::new(), strip_id3v2(&padded)).unwrap();
+        assert_eq!(padded_hash, want, "{} (padded)", path.display());
+        checked += 1;
+    }
+    eprintln!("checked {checked} MP3 vectors");
+}
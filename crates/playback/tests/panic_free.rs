@@ -0,0 +1,173 @@
+//! Panic-free enforcement for the audio hot paths.
+//!
+//! Clippy's workspace denies can be switched off with an `#[allow]` at any
+//! item; these tests make sure nobody does that in a hot-path module, that
+//! each module keeps its extra `#![deny]` block, and that no panicking
+//! macro or method slipped in under a `cfg` clippy never saw (the `mp3`
+//! decode path, for one, only compiles with its feature on).
+//!
+//! Only compile-time code may opt out: a `const fn` or an associated
+//! `const` is evaluated by the compiler, so it cannot panic on device.
+//!
+//! The disassembly-level counterpart is `cargo run -p xtask -- panic-check`.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+
+/// Hot-path modules, listed in the crate docs.
+const HOT_PATHS: &[(&str, &str)] = &[
+    ("decoder.rs", include_str!("../src/decoder.rs")),
+    ("flac_decoder.rs", include_str!("../src/flac_decoder.rs")),
+    ("flac_lpc.rs", include_str!("../src/flac_lpc.rs")),
+    ("frame_timing.rs", include_str!("../src/frame_timing.rs")),
+    ("mp3_decoder.rs", include_str!("../src/mp3_decoder.rs")),
+    ("ogg.rs", include_str!("../src/ogg.rs")),
+    ("ring_buffer.rs", include_str!("../src/ring_buffer.rs")),
+    ("silence.rs", include_str!("../src/silence.rs")),
+    ("time_stretch.rs", include_str!("../src/time_stretch.rs")),
+    ("volume.rs", include_str!("../src/volume.rs")),
+    ("wav_decoder.rs", include_str!("../src/wav_decoder.rs")),
+];
+
+/// Lints whose `#[allow]` would let a panic back in.
+const PANIC_LINTS: &[&str] = &[
+    "indexing_slicing",
+    "arithmetic_side_effects",
+    "unwrap_used",
+    "expect_used",
+    "panic",
+    "unreachable",
+    "panic_in_result_fn",
+    "unwrap_in_result",
+    "string_slice",
+];
+
+/// Constructs that panic at run time.
+const PANICKING: &[&str] = &[
+    ".unwrap()",
+    ".expect(",
+    "panic!(",
+    "unreachable!(",
+    "todo!(",
+    "unimplemented!(",
+    "assert!(",
+    "assert_eq!(",
+    "assert_ne!(",
+];
+
+/// Source lines before any `#[cfg(test)]` module, without comments.
+fn production_lines(src: &str) -> Vec<(usize, &str)> {
+    src.lines()
+        .enumerate()
+        .take_while(|(_, line)| line.trim() != "#[cfg(test)]")
+        .map(|(i, line)| (i + 1, line.split("//").next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .collect()
+}
+
+/// Whether the item starting after line index `at` is evaluated at compile
+/// time: a `const fn`, or an associated/free `const`.
+fn is_const_item(lines: &[(usize, &str)], at: usize) -> bool {
+    lines[at..]
+        .iter()
+        .map(|(_, line)| *line)
+        .find(|line| !line.starts_with("#["))
+        .is_some_and(|line| {
+            let item = line
+                .trim_start_matches("pub ")
+                .trim_start_matches("pub(crate) ");
+            item.starts_with("const ")
+        })
+}
+
+#[test]
+fn hot_paths_keep_their_deny_block() {
+    for (file, src) in HOT_PATHS {
+        let deny = src
+            .find("#![deny(")
+            .unwrap_or_else(|| panic!("{file}: missing the hot-path #![deny] block"));
+        let block = &src[deny..src[deny..].find(')').map_or(src.len(), |e| deny + e)];
+        for lint in [
+            "unreachable",
+            "panic_in_result_fn",
+            "unwrap_in_result",
+            "string_slice",
+        ] {
+            assert!(
+                block.contains(&format!("clippy::{lint}")),
+                "{file}: #![deny] block lost clippy::{lint}"
+            );
+        }
+    }
+}
+
+#[test]
+fn hot_paths_do_not_allow_panic_lints() {
+    for (file, src) in HOT_PATHS {
+        let lines = production_lines(src);
+        for (at, (number, line)) in lines.iter().enumerate() {
+            if !line.starts_with("#[allow(") {
+                continue;
+            }
+            let lint = PANIC_LINTS
+                .iter()
+                .find(|lint| line.contains(&format!("clippy::{lint}")));
+            if let Some(lint) = lint {
+                assert!(
+                    is_const_item(&lines, at + 1),
+                    "{file}:{number}: `allow(clippy::{lint})` on run-time code in an audio hot path"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn hot_paths_have_no_panicking_constructs() {
+    for (file, src) in HOT_PATHS {
+        let lines = production_lines(src);
+        for (at, (number, line)) in lines.iter().enumerate() {
+            let Some(found) = PANICKING.iter().find(|p| line.contains(*p)) else {
+                continue;
+            };
+            // `const X: () = assert!(..)` is a compile-time check.
+            let in_const = line.contains("const ")
+                || at.checked_sub(1).is_some_and(|prev| {
+                    lines[prev].1.starts_with("const ") && lines[prev].1.ends_with('=')
+                });
+            assert!(
+                in_const,
+                "{file}:{number}: `{found}` can panic in an audio hot path: `{line}`"
+            );
+        }
+    }
+}
+
+#[test]
+fn scanner_flags_what_it_should() {
+    let src = "\
+fn ok(x: &[u8]) -> Option<u8> {
+    x.get(0).copied() // .unwrap() in a comment is fine
+}
+#[allow(clippy::indexing_slicing)]
+fn bad(x: &[u8]) -> u8 {
+    x[0]
+}
+#[allow(clippy::indexing_slicing)]
+const fn table() -> [u8; 1] {
+    [0]
+}
+#[cfg(test)]
+mod tests {
+    fn t() { None::<u8>.unwrap(); }
+}
+";
+    let lines = production_lines(src);
+    assert!(lines.iter().all(|(_, l)| !l.contains(".unwrap()")));
+    let allows: Vec<_> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, l))| l.starts_with("#[allow("))
+        .map(|(at, _)| is_const_item(&lines, at + 1))
+        .collect();
+    assert_eq!(allows, [false, true]);
+}
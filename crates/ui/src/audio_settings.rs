@@ -0,0 +1,102 @@
+//! Audio settings state — the DAC oversampling filter picker.
+//!
+//! Filters are identified by their index in `platform::OversamplingFilter::ALL`
+//! so the `ui` crate stays independent of the platform layer.  The encoder
+//! moves the selection; Select applies it, and the caller forwards the
+//! returned index to `AudioCodec::set_filter`.
+
+/// Selection within the audio settings screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioSettings {
+    filter_count: usize,
+    selected: usize,
+    applied: usize,
+}
+
+impl AudioSettings {
+    /// A picker over `filter_count` filters, opened on the `applied` one
+    /// (clamped to the list).
+    pub fn new(filter_count: usize, applied: usize) -> Self {
+        let applied = applied.min(filter_count.saturating_sub(1));
+        Self {
+            filter_count,
+            selected: applied,
+            applied,
+        }
+    }
+
+    /// Number of filters offered.
+    #[must_use]
+    pub fn filter_count(&self) -> usize {
+        self.filter_count
+    }
+
+    /// Highlighted filter.
+    #[must_use]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Filter currently programmed into the DAC.
+    #[must_use]
+    pub fn applied(&self) -> usize {
+        self.applied
+    }
+
+    /// Move the selection by `steps` (encoder detents), clamping at the ends.
+    pub fn scroll(&mut self, steps: i32) {
+        let delta = usize::try_from(steps.unsigned_abs()).unwrap_or(usize::MAX);
+        let target = if steps >= 0 {
+            self.selected.saturating_add(delta)
+        } else {
+            self.selected.saturating_sub(delta)
+        };
+        self.selected = target.min(self.filter_count.saturating_sub(1));
+    }
+
+    /// Apply the selected filter.  Returns its index when it differs from
+    /// the applied one (the DAC needs a register write), `None` otherwise.
+    pub fn confirm(&mut self) -> Option<usize> {
+        if self.filter_count == 0 || self.selected == self.applied {
+            return None;
+        }
+        self.applied = self.selected;
+        Some(self.applied)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AudioSettings;
+
+    #[test]
+    fn test_audio_settings_opens_on_applied_filter() {
+        let settings = AudioSettings::new(7, 4);
+        assert_eq!(settings.selected(), 4);
+        assert_eq!(settings.applied(), 4);
+        assert_eq!(AudioSettings::new(7, 20).applied(), 6);
+    }
+
+    #[test]
+    fn test_audio_settings_scroll_clamps() {
+        let mut settings = AudioSettings::new(7, 0);
+        settings.scroll(3);
+        assert_eq!(settings.selected(), 3);
+        settings.scroll(10);
+        assert_eq!(settings.selected(), 6);
+        settings.scroll(-10);
+        assert_eq!(settings.selected(), 0);
+    }
+
+    #[test]
+    fn test_audio_settings_confirm_reports_changes_only() {
+        let mut settings = AudioSettings::new(7, 0);
+        assert_eq!(settings.confirm(), None);
+        settings.scroll(2);
+        assert_eq!(settings.applied(), 0, "scrolling alone does not apply");
+        assert_eq!(settings.confirm(), Some(2));
+        assert_eq!(settings.applied(), 2);
+        assert_eq!(settings.confirm(), None);
+        assert_eq!(AudioSettings::new(0, 0).confirm(), None);
+    }
+}
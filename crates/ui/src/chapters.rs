@@ -0,0 +1,133 @@
+//! Chapter list state — selection and scroll position of the chapters screen.
+//!
+//! The list opens with the playing chapter selected; the encoder moves the
+//! selection and Select jumps playback to the selected chapter's start.
+//! Chapter titles and times live in `library::Chapters`; this state only
+//! tracks indices so the `ui` crate stays independent of the library.
+
+/// Selection within the chapters screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChapterList {
+    count: usize,
+    rows: usize,
+    selected: usize,
+    top: usize,
+    playing: Option<usize>,
+}
+
+impl ChapterList {
+    /// A list of `count` chapters showing `rows` at a time (at least one),
+    /// opened on `playing` (or the first chapter).
+    pub fn new(count: usize, rows: usize, playing: Option<usize>) -> Self {
+        let mut list = Self {
+            count,
+            rows: rows.max(1),
+            selected: 0,
+            top: 0,
+            playing: None,
+        };
+        list.set_playing(playing);
+        if let Some(i) = list.playing {
+            list.select(i);
+        }
+        list
+    }
+
+    /// Number of chapters.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Rows visible at once.
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Selected chapter (0 when the list is empty).
+    #[must_use]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// First visible chapter.
+    #[must_use]
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// Chapter currently playing, if any.
+    #[must_use]
+    pub fn playing(&self) -> Option<usize> {
+        self.playing
+    }
+
+    /// Update the playing chapter (ignored when out of range).
+    pub fn set_playing(&mut self, playing: Option<usize>) {
+        self.playing = playing.filter(|&i| i < self.count);
+    }
+
+    /// Select `index` (clamped to the list) and scroll it into view.
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.count.saturating_sub(1));
+        if self.selected < self.top {
+            self.top = self.selected;
+        } else if self.selected.saturating_sub(self.top) >= self.rows {
+            self.top = self.selected.saturating_sub(self.rows.saturating_sub(1));
+        }
+    }
+
+    /// Move the selection by `steps` (encoder detents), clamping at the ends.
+    pub fn scroll(&mut self, steps: i32) {
+        let delta = usize::try_from(steps.unsigned_abs()).unwrap_or(usize::MAX);
+        let target = if steps >= 0 {
+            self.selected.saturating_add(delta)
+        } else {
+            self.selected.saturating_sub(delta)
+        };
+        self.select(target);
+    }
+
+    /// `true` when chapter `index` is on screen.
+    #[must_use]
+    pub fn is_visible(&self, index: usize) -> bool {
+        index >= self.top && index.saturating_sub(self.top) < self.rows && index < self.count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChapterList;
+
+    #[test]
+    fn test_chapter_list_opens_on_playing_chapter() {
+        let list = ChapterList::new(20, 5, Some(12));
+        assert_eq!(list.selected(), 12);
+        assert!(list.is_visible(12));
+        assert_eq!(list.top(), 8);
+    }
+
+    #[test]
+    fn test_chapter_list_scroll_clamps_and_follows() {
+        let mut list = ChapterList::new(6, 3, None);
+        assert_eq!(list.selected(), 0);
+        list.scroll(4);
+        assert_eq!(list.selected(), 4);
+        assert_eq!(list.top(), 2);
+        list.scroll(10);
+        assert_eq!(list.selected(), 5);
+        list.scroll(-5);
+        assert_eq!(list.selected(), 0);
+        assert_eq!(list.top(), 0);
+    }
+
+    #[test]
+    fn test_chapter_list_ignores_out_of_range_playing() {
+        let mut list = ChapterList::new(3, 3, Some(7));
+        assert_eq!(list.playing(), None);
+        list.set_playing(Some(2));
+        assert_eq!(list.playing(), Some(2));
+        assert!(!ChapterList::new(0, 3, None).is_visible(0));
+    }
+}
@@ -0,0 +1,177 @@
+//! Diagnostics service screen state — the hidden self-test.
+//!
+//! The screen is not in any menu: [`ServiceUnlock`] opens it after
+//! [`UNLOCK_PRESSES`] Select presses on the Settings screen within
+//! [`UNLOCK_WINDOW_MS`].  It then shows each test pattern in turn (Select
+//! advances once the pattern's refresh has been timed) and ends on the
+//! results page.  Back leaves from any step.
+
+/// Select presses that open the service screen.
+pub const UNLOCK_PRESSES: u8 = 5;
+/// All unlock presses must land within this window (ms).
+pub const UNLOCK_WINDOW_MS: u64 = 3_000;
+
+/// What the service screen is showing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticsStep {
+    /// Black/white checkerboard.
+    Checkerboard,
+    /// Four grey bands.
+    Gradient,
+    /// Edge and inset frames.
+    Border,
+    /// Timing, ghosting, SD card and battery results.
+    Results,
+}
+
+impl DiagnosticsStep {
+    /// The step after this one, or `None` on the results page.
+    #[must_use]
+    pub const fn next(self) -> Option<Self> {
+        match self {
+            Self::Checkerboard => Some(Self::Gradient),
+            Self::Gradient => Some(Self::Border),
+            Self::Border => Some(Self::Results),
+            Self::Results => None,
+        }
+    }
+
+    /// `true` while a full-screen pattern is shown.
+    #[must_use]
+    pub const fn is_pattern(self) -> bool {
+        !matches!(self, Self::Results)
+    }
+}
+
+/// Diagnostics screen state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Diagnostics {
+    step: DiagnosticsStep,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Diagnostics {
+    /// A run starting at the first pattern.
+    pub const fn new() -> Self {
+        Self {
+            step: DiagnosticsStep::Checkerboard,
+        }
+    }
+
+    /// Current step.
+    #[must_use]
+    pub fn step(&self) -> DiagnosticsStep {
+        self.step
+    }
+
+    /// Select pressed: move to the next step.  Returns `false` on the
+    /// results page, where there is nothing to advance to.
+    pub fn advance(&mut self) -> bool {
+        match self.step.next() {
+            Some(next) => {
+                self.step = next;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start over from the first pattern (Select on the results page).
+    pub fn restart(&mut self) {
+        self.step = DiagnosticsStep::Checkerboard;
+    }
+}
+
+/// Counts the hidden Select presses that open the service screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ServiceUnlock {
+    presses: u8,
+    first_ms: u64,
+}
+
+impl ServiceUnlock {
+    /// No presses counted.
+    pub const fn new() -> Self {
+        Self {
+            presses: 0,
+            first_ms: 0,
+        }
+    }
+
+    /// Select pressed at `now_ms`.  Returns `true` on the press that
+    /// completes the sequence; the counter then starts over.
+    pub fn press(&mut self, now_ms: u64) -> bool {
+        if self.presses == 0 || now_ms.saturating_sub(self.first_ms) > UNLOCK_WINDOW_MS {
+            self.presses = 0;
+            self.first_ms = now_ms;
+        }
+        self.presses = self.presses.saturating_add(1);
+        if self.presses < UNLOCK_PRESSES {
+            return false;
+        }
+        self.reset();
+        true
+    }
+
+    /// Any other input breaks the sequence.
+    pub fn reset(&mut self) {
+        self.presses = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostics, DiagnosticsStep, ServiceUnlock, UNLOCK_PRESSES};
+
+    #[test]
+    fn test_diagnostics_steps_through_patterns_to_results() {
+        let mut diag = Diagnostics::new();
+        let mut seen = [diag.step(); 4];
+        for slot in seen.iter_mut().skip(1) {
+            assert!(diag.advance());
+            *slot = diag.step();
+        }
+        assert_eq!(
+            seen,
+            [
+                DiagnosticsStep::Checkerboard,
+                DiagnosticsStep::Gradient,
+                DiagnosticsStep::Border,
+                DiagnosticsStep::Results,
+            ]
+        );
+        assert!(!diag.step().is_pattern());
+        assert!(!diag.advance());
+        diag.restart();
+        assert_eq!(diag.step(), DiagnosticsStep::Checkerboard);
+    }
+
+    #[test]
+    fn test_service_unlock_needs_presses_within_window() {
+        let mut unlock = ServiceUnlock::new();
+        let opened = (0..u64::from(UNLOCK_PRESSES)).map(|i| unlock.press(i.saturating_mul(400)));
+        assert_eq!(opened.filter(|&o| o).count(), 1);
+
+        // Too slow: the window restarts at the fourth press.
+        let mut unlock = ServiceUnlock::new();
+        for t in [0, 1_000, 2_000, 3_500, 3_600, 3_700, 3_800] {
+            assert!(!unlock.press(t), "opened at {t}");
+        }
+        assert!(unlock.press(3_900));
+    }
+
+    #[test]
+    fn test_service_unlock_reset_breaks_sequence() {
+        let mut unlock = ServiceUnlock::new();
+        for t in 0..4 {
+            assert!(!unlock.press(t));
+        }
+        unlock.reset();
+        assert!(!unlock.press(5));
+    }
+}
@@ -7,6 +7,16 @@
 // TODO: Add rustdoc to all public items (tracked as tech debt)
 #![allow(missing_docs)]
 
+pub mod audio_settings;
+pub mod chapters;
+pub mod diagnostics;
+pub mod library_scan;
+pub mod lyrics;
 pub mod navigation;
 pub mod now_playing;
+pub mod queue;
+pub mod quick_menu;
 pub mod screen;
+pub mod selection;
+pub mod tag_editor;
+pub mod text_input;
@@ -0,0 +1,87 @@
+//! Library scan screen state — progress of the initial card scan.
+//!
+//! The screen is shown while the scanner walks the card.  The counts come
+//! from `library::ScanProgress`; this state only tracks where the scan is
+//! in its life cycle so Back can cancel it exactly once.  After a cancel
+//! the tracks indexed so far are playable, so Back on a finished scan
+//! leaves the screen.
+
+/// Where the scan is in its life cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanPhase {
+    /// Walking the card.
+    Scanning,
+    /// Cancel requested; waiting for the walker to reach its next step.
+    Cancelling,
+    /// Walk ended, either completed or cancelled.
+    Done {
+        /// `true` when the scan stopped early.
+        cancelled: bool,
+    },
+}
+
+/// Library scan screen state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LibraryScan {
+    phase: ScanPhase,
+}
+
+impl Default for LibraryScan {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LibraryScan {
+    /// A scan that has just started.
+    pub const fn new() -> Self {
+        Self {
+            phase: ScanPhase::Scanning,
+        }
+    }
+
+    /// Current phase.
+    #[must_use]
+    pub fn phase(&self) -> ScanPhase {
+        self.phase
+    }
+
+    /// Back pressed while scanning.  Returns `true` the first time, when the
+    /// caller should raise the scanner's cancel flag.
+    pub fn request_cancel(&mut self) -> bool {
+        if self.phase != ScanPhase::Scanning {
+            return false;
+        }
+        self.phase = ScanPhase::Cancelling;
+        true
+    }
+
+    /// The walker returned; `cancelled` reports whether it stopped early.
+    pub fn finish(&mut self, cancelled: bool) {
+        self.phase = ScanPhase::Done { cancelled };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LibraryScan, ScanPhase};
+
+    #[test]
+    fn test_library_scan_cancels_once() {
+        let mut scan = LibraryScan::new();
+        assert_eq!(scan.phase(), ScanPhase::Scanning);
+        assert!(scan.request_cancel());
+        assert!(!scan.request_cancel());
+        assert_eq!(scan.phase(), ScanPhase::Cancelling);
+        scan.finish(true);
+        assert_eq!(scan.phase(), ScanPhase::Done { cancelled: true });
+    }
+
+    #[test]
+    fn test_library_scan_cannot_cancel_after_finish() {
+        let mut scan = LibraryScan::new();
+        scan.finish(false);
+        assert!(!scan.request_cancel());
+        assert_eq!(scan.phase(), ScanPhase::Done { cancelled: false });
+    }
+}
@@ -0,0 +1,135 @@
+//! Lyrics screen state — which line is highlighted and which page is shown.
+//!
+//! The view shows `rows` lines at a time.  As playback moves on, only the
+//! highlight moves (two rows to redraw) until the current line runs off the
+//! page; then the page turns so the current line is second from the top,
+//! keeping the previous line as context.  Turning pages rarely, rather than
+//! scrolling every line, keeps full-area redraws — and e-paper flashing —
+//! to one per `rows - 1` lines.
+
+/// What [`LyricsView::follow`] changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricsRedraw {
+    /// Nothing visible changed.
+    None,
+    /// The highlight moved within the page: redraw these rows (absolute
+    /// line indices; `None` when there was or is no highlighted line).
+    Lines {
+        previous: Option<usize>,
+        current: Option<usize>,
+    },
+    /// The page turned: redraw the whole lyrics area.
+    Page,
+}
+
+/// Highlight and page position of the lyrics screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LyricsView {
+    rows: usize,
+    top: usize,
+    current: Option<usize>,
+}
+
+impl LyricsView {
+    /// A view showing `rows` lines (at least one), starting at the top.
+    pub fn new(rows: usize) -> Self {
+        Self {
+            rows: rows.max(1),
+            top: 0,
+            current: None,
+        }
+    }
+
+    /// Lines per page.
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Index of the first line on the page.
+    #[must_use]
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// Highlighted line, if playback has reached the first line.
+    #[must_use]
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Move the highlight to `current` (from `Lyrics::line_at`), turning the
+    /// page if it leaves the visible rows.
+    pub fn follow(&mut self, current: Option<usize>) -> LyricsRedraw {
+        if current == self.current {
+            return LyricsRedraw::None;
+        }
+        let previous = core::mem::replace(&mut self.current, current);
+        let top = match current {
+            Some(line) if !self.is_visible(line) => line.saturating_sub(1),
+            // Seeking back before the first line.
+            None if self.top != 0 => 0,
+            _ => self.top,
+        };
+        if top == self.top {
+            LyricsRedraw::Lines { previous, current }
+        } else {
+            self.top = top;
+            LyricsRedraw::Page
+        }
+    }
+
+    /// `true` when `line` is on the current page.
+    #[must_use]
+    pub fn is_visible(&self, line: usize) -> bool {
+        line >= self.top && line.saturating_sub(self.top) < self.rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LyricsRedraw, LyricsView};
+
+    #[test]
+    fn test_lyrics_view_moves_highlight_within_page() {
+        let mut view = LyricsView::new(4);
+        assert_eq!(
+            view.follow(Some(0)),
+            LyricsRedraw::Lines {
+                previous: None,
+                current: Some(0)
+            }
+        );
+        assert_eq!(
+            view.follow(Some(1)),
+            LyricsRedraw::Lines {
+                previous: Some(0),
+                current: Some(1)
+            }
+        );
+        assert_eq!(view.follow(Some(1)), LyricsRedraw::None);
+        assert_eq!(view.top(), 0);
+    }
+
+    #[test]
+    fn test_lyrics_view_turns_page_keeping_one_line_of_context() {
+        let mut view = LyricsView::new(4);
+        view.follow(Some(3));
+        assert_eq!(view.follow(Some(4)), LyricsRedraw::Page);
+        assert_eq!(view.top(), 3);
+        assert!(view.is_visible(6));
+        assert!(!view.is_visible(7));
+    }
+
+    #[test]
+    fn test_lyrics_view_seek_back_turns_page() {
+        let mut view = LyricsView::new(4);
+        view.follow(Some(10));
+        assert_eq!(view.top(), 9);
+        assert_eq!(view.follow(Some(2)), LyricsRedraw::Page);
+        assert_eq!(view.top(), 1);
+        assert_eq!(view.follow(None), LyricsRedraw::Page);
+        assert_eq!(view.top(), 0);
+        assert_eq!(view.current(), None);
+    }
+}
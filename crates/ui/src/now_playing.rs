@@ -14,6 +14,13 @@ pub struct NowPlayingState {
     pub title: heapless::String<128>,
     /// Artist name (up to 64 UTF-8 bytes).
     pub artist: heapless::String<64>,
+    /// Album of the current track, keying its art in the art cache (`None`
+    /// when unknown).
+    pub album_id: Option<u32>,
+    /// Title and artist are not known yet: the track was resumed at boot
+    /// from its path and the library index is still loading.  The screen
+    /// draws placeholder bars in their place.
+    pub loading: bool,
 }
 
 impl NowPlayingState {
@@ -37,6 +44,16 @@ impl NowPlayingState {
         self.duration_ms = ms;
     }
 
+    /// Set the album of the current track.
+    pub fn set_album_id(&mut self, album_id: Option<u32>) {
+        self.album_id = album_id;
+    }
+
+    /// Set whether the track's metadata is still loading.
+    pub fn set_loading(&mut self, loading: bool) {
+        self.loading = loading;
+    }
+
     /// Return a `0.0..=1.0` progress ratio.
     ///
     /// Returns `0.0` when `duration_ms` is zero.
@@ -59,6 +76,8 @@ impl Default for NowPlayingState {
             duration_ms: 0,
             title: heapless::String::new(),
             artist: heapless::String::new(),
+            album_id: None,
+            loading: false,
         }
     }
 }
@@ -106,6 +125,22 @@ mod tests {
         assert!((ratio - 0.5_f32).abs() < 1e-6, "expected ~0.5, got {ratio}");
     }
 
+    #[test]
+    fn test_now_playing_album_id() {
+        let mut state = NowPlayingState::default();
+        assert_eq!(state.album_id, None);
+        state.set_album_id(Some(0x00AB_CDEF));
+        assert_eq!(state.album_id, Some(0x00AB_CDEF));
+    }
+
+    #[test]
+    fn test_now_playing_loading() {
+        let mut state = NowPlayingState::default();
+        assert!(!state.loading);
+        state.set_loading(true);
+        assert!(state.loading);
+    }
+
     #[test]
     fn test_now_playing_progress_zero_duration() {
         let state = NowPlayingState::default();
@@ -0,0 +1,415 @@
+//! Queue screen state — cursor, scroll position and multi-select.
+//!
+//! The encoder moves the cursor and a click on Select plays the track under
+//! it.  Holding Select switches to select mode with that track marked;
+//! clicks then mark or unmark tracks, Menu cycles the batch action shown in
+//! the footer, and holding Select again applies it to every marked track.
+//! Back leaves select mode and drops the marks.
+//!
+//! The state only deals in queue indices, like
+//! [`ChapterList`](crate::chapters::ChapterList).  A [`QueueBatch`] tells
+//! the caller what to do; [`QueueBatch::removals`] and
+//! [`QueueBatch::play_next_moves`] spell it out as the single-index
+//! remove/reorder steps the play queue and its journal already support.
+
+use crate::selection::Selection;
+
+/// What the encoder and Select button do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueMode {
+    /// Click plays the track under the cursor.
+    Browse,
+    /// Click marks the track under the cursor.
+    Select,
+}
+
+/// An action applied to every marked track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchAction {
+    /// Take the tracks out of the queue.
+    Remove,
+    /// Add the tracks' albums to favourites.
+    FavoriteAlbums,
+    /// Move the tracks to play right after the current one.
+    PlayNext,
+}
+
+impl BatchAction {
+    /// Every action, in footer order.
+    pub const ALL: [Self; 3] = [Self::Remove, Self::FavoriteAlbums, Self::PlayNext];
+
+    /// Footer label.
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Remove => "Remove",
+            Self::FavoriteAlbums => "Favourite album",
+            Self::PlayNext => "Play next",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Remove => Self::FavoriteAlbums,
+            Self::FavoriteAlbums => Self::PlayNext,
+            Self::PlayNext => Self::Remove,
+        }
+    }
+}
+
+/// Result of a click.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueClick {
+    /// Play the track at this index.
+    Play(usize),
+    /// A mark changed; redraw the row.
+    Marked,
+    /// Nothing to do (empty queue).
+    None,
+}
+
+/// A batch action and the tracks it applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueBatch {
+    /// What to do.
+    pub action: BatchAction,
+    /// Marked queue indices.
+    pub tracks: Selection,
+}
+
+impl QueueBatch {
+    /// Indices to remove one at a time, last first, so each stays valid
+    /// after the ones before it are gone.
+    pub fn removals(&self) -> impl Iterator<Item = usize> + '_ {
+        self.tracks.marked().rev()
+    }
+
+    /// `(from, to)` reorder steps that move the marked tracks, in queue
+    /// order, to just after `current`.  Apply them one after another; a
+    /// marked current track stays where it is.  Moves that would not
+    /// change anything are left out.
+    pub fn play_next_moves(&self, current: usize) -> PlayNextMoves {
+        PlayNextMoves {
+            marked: self.tracks,
+            next: 0,
+            original_current: current,
+            current,
+            moved: 0,
+        }
+    }
+}
+
+/// Iterator returned by [`QueueBatch::play_next_moves`].
+#[derive(Debug, Clone)]
+pub struct PlayNextMoves {
+    marked: Selection,
+    next: usize,
+    original_current: usize,
+    /// Position of the current track after the moves so far.
+    current: usize,
+    /// Tracks placed after the current one so far.
+    moved: usize,
+}
+
+impl Iterator for PlayNextMoves {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        loop {
+            let index = self.marked.marked().find(|&i| i >= self.next)?;
+            self.next = index.saturating_add(1);
+            if index == self.original_current {
+                continue;
+            }
+            let step = if index < self.original_current {
+                // Every earlier move took a track from before this one,
+                // and removing it pulls the current track back by one.
+                let from = index.saturating_sub(self.moved);
+                let to = self.current.saturating_add(self.moved);
+                self.current = self.current.saturating_sub(1);
+                (from, to)
+            } else {
+                // Earlier moves only took tracks from before this one and
+                // put them before it too, so its index is unchanged.
+                let to = self.current.saturating_add(1).saturating_add(self.moved);
+                (index, to)
+            };
+            self.moved = self.moved.saturating_add(1);
+            if step.0 != step.1 {
+                return Some(step);
+            }
+        }
+    }
+}
+
+/// State of the queue screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueView {
+    count: usize,
+    rows: usize,
+    cursor: usize,
+    top: usize,
+    playing: Option<usize>,
+    mode: QueueMode,
+    action: BatchAction,
+    marks: Selection,
+}
+
+impl QueueView {
+    /// A queue of `count` tracks showing `rows` at a time (at least one),
+    /// opened on `playing` (or the first track).
+    pub fn new(count: usize, rows: usize, playing: Option<usize>) -> Self {
+        let mut view = Self {
+            count,
+            rows: rows.max(1),
+            cursor: 0,
+            top: 0,
+            playing: None,
+            mode: QueueMode::Browse,
+            action: BatchAction::Remove,
+            marks: Selection::new(count),
+        };
+        view.set_playing(playing);
+        if let Some(i) = view.playing {
+            view.select(i);
+        }
+        view
+    }
+
+    /// Number of tracks in the queue.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Rows visible at once.
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Track under the cursor (0 when the queue is empty).
+    #[must_use]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// First visible track.
+    #[must_use]
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// Track currently playing, if any.
+    #[must_use]
+    pub fn playing(&self) -> Option<usize> {
+        self.playing
+    }
+
+    /// Browse or select mode.
+    #[must_use]
+    pub fn mode(&self) -> QueueMode {
+        self.mode
+    }
+
+    /// Batch action shown in the footer.
+    #[must_use]
+    pub fn action(&self) -> BatchAction {
+        self.action
+    }
+
+    /// Marked tracks.
+    #[must_use]
+    pub fn marks(&self) -> &Selection {
+        &self.marks
+    }
+
+    /// Update the playing track (ignored when out of range).
+    pub fn set_playing(&mut self, playing: Option<usize>) {
+        self.playing = playing.filter(|&i| i < self.count);
+    }
+
+    /// Move the cursor to `index` (clamped to the queue) and scroll it into
+    /// view.
+    pub fn select(&mut self, index: usize) {
+        self.cursor = index.min(self.count.saturating_sub(1));
+        if self.cursor < self.top {
+            self.top = self.cursor;
+        } else if self.cursor.saturating_sub(self.top) >= self.rows {
+            self.top = self.cursor.saturating_sub(self.rows.saturating_sub(1));
+        }
+    }
+
+    /// Move the cursor by `steps` (encoder detents), clamping at the ends.
+    pub fn scroll(&mut self, steps: i32) {
+        let delta = usize::try_from(steps.unsigned_abs()).unwrap_or(usize::MAX);
+        let target = if steps >= 0 {
+            self.cursor.saturating_add(delta)
+        } else {
+            self.cursor.saturating_sub(delta)
+        };
+        self.select(target);
+    }
+
+    /// `true` when track `index` is on screen.
+    #[must_use]
+    pub fn is_visible(&self, index: usize) -> bool {
+        index >= self.top && index.saturating_sub(self.top) < self.rows && index < self.count
+    }
+
+    /// Select clicked.
+    pub fn click(&mut self) -> QueueClick {
+        if self.count == 0 {
+            return QueueClick::None;
+        }
+        match self.mode {
+            QueueMode::Browse => QueueClick::Play(self.cursor),
+            QueueMode::Select => {
+                self.marks.toggle(self.cursor);
+                QueueClick::Marked
+            }
+        }
+    }
+
+    /// Select held.  In browse mode, enters select mode with the track
+    /// under the cursor marked.  In select mode, applies the footer action
+    /// to the marked tracks and returns to browse mode; nothing happens
+    /// while no track is marked.
+    pub fn hold(&mut self) -> Option<QueueBatch> {
+        match self.mode {
+            QueueMode::Browse if self.count > 0 => {
+                self.mode = QueueMode::Select;
+                self.action = BatchAction::Remove;
+                self.marks.clear();
+                self.marks.set(self.cursor, true);
+                None
+            }
+            QueueMode::Browse => None,
+            QueueMode::Select if self.marks.is_none() => None,
+            QueueMode::Select => {
+                let batch = QueueBatch {
+                    action: self.action,
+                    tracks: self.marks,
+                };
+                self.cancel();
+                Some(batch)
+            }
+        }
+    }
+
+    /// Menu pressed: in select mode, show the next batch action.
+    pub fn cycle_action(&mut self) {
+        if self.mode == QueueMode::Select {
+            self.action = self.action.next();
+        }
+    }
+
+    /// Back pressed: leave select mode and drop the marks.  Returns `false`
+    /// in browse mode, where Back leaves the screen instead.
+    pub fn cancel(&mut self) -> bool {
+        let was_selecting = self.mode == QueueMode::Select;
+        self.mode = QueueMode::Browse;
+        self.marks.clear();
+        was_selecting
+    }
+
+    /// The queue changed underneath (after a batch, or from elsewhere):
+    /// resize, keep the cursor in range and drop any marks.
+    pub fn set_count(&mut self, count: usize, playing: Option<usize>) {
+        self.count = count;
+        self.marks = Selection::new(count);
+        self.mode = QueueMode::Browse;
+        self.set_playing(playing);
+        self.top = self.top.min(count.saturating_sub(self.rows));
+        self.select(self.cursor);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use super::{BatchAction, QueueBatch, QueueClick, QueueMode, QueueView};
+    use crate::selection::Selection;
+
+    /// Apply reorder steps the way `PlayQueue::reorder` does.
+    fn apply(queue: &mut Vec<u32>, moves: impl Iterator<Item = (usize, usize)>) {
+        for (from, to) in moves {
+            let track = queue.remove(from);
+            queue.insert(to, track);
+        }
+    }
+
+    fn batch(action: BatchAction, len: usize, marked: &[usize]) -> QueueBatch {
+        let mut tracks = Selection::new(len);
+        for &i in marked {
+            tracks.set(i, true);
+        }
+        QueueBatch { action, tracks }
+    }
+
+    #[test]
+    fn test_queue_hold_enters_select_mode_and_click_marks() {
+        let mut view = QueueView::new(10, 4, Some(2));
+        assert_eq!(view.click(), QueueClick::Play(2));
+        assert_eq!(view.hold(), None);
+        assert_eq!(view.mode(), QueueMode::Select);
+        view.scroll(3);
+        assert_eq!(view.click(), QueueClick::Marked);
+        view.scroll(1);
+        view.click();
+        view.click();
+        assert_eq!(view.marks().marked().collect::<Vec<_>>(), [2, 5]);
+
+        view.cycle_action();
+        view.cycle_action();
+        assert_eq!(view.hold(), Some(batch(BatchAction::PlayNext, 10, &[2, 5])));
+        assert_eq!(view.mode(), QueueMode::Browse);
+        assert!(view.marks().is_none());
+    }
+
+    #[test]
+    fn test_queue_back_cancels_and_empty_selection_applies_nothing() {
+        let mut view = QueueView::new(3, 4, None);
+        view.hold();
+        view.click();
+        assert_eq!(view.hold(), None, "nothing marked");
+        assert!(view.cancel());
+        assert!(!view.cancel());
+        assert_eq!(QueueView::new(0, 4, None).hold(), None);
+    }
+
+    #[test]
+    fn test_queue_removals_run_last_first() {
+        let b = batch(BatchAction::Remove, 8, &[1, 4, 6]);
+        let mut queue: Vec<u32> = (0..8).collect();
+        for i in b.removals() {
+            queue.remove(i);
+        }
+        assert_eq!(queue, [0, 2, 3, 5, 7]);
+    }
+
+    #[test]
+    fn test_queue_play_next_moves_marked_tracks_after_current() {
+        let b = batch(BatchAction::PlayNext, 8, &[0, 2, 3, 5, 7]);
+        let mut queue: Vec<u32> = (0..8).collect();
+        apply(&mut queue, b.play_next_moves(3));
+        assert_eq!(queue, [1, 3, 0, 2, 5, 7, 4, 6]);
+
+        let b = batch(BatchAction::PlayNext, 5, &[1, 2]);
+        let mut queue: Vec<u32> = (0..5).collect();
+        assert_eq!(b.play_next_moves(0).count(), 0, "already next");
+        apply(&mut queue, b.play_next_moves(4));
+        assert_eq!(queue, [0, 3, 4, 1, 2]);
+    }
+
+    #[test]
+    fn test_queue_set_count_clamps_cursor() {
+        let mut view = QueueView::new(10, 4, Some(9));
+        view.hold();
+        view.set_count(5, Some(1));
+        assert_eq!(view.cursor(), 4);
+        assert_eq!(view.top(), 1);
+        assert_eq!(view.mode(), QueueMode::Browse);
+        assert_eq!(view.playing(), Some(1));
+    }
+}
@@ -0,0 +1,244 @@
+//! Quick menu state — the output profile switcher, playback speed and
+//! night mode.
+//!
+//! The quick menu is pushed over any screen (long-press Menu) and lists the
+//! output profiles by index into `platform::OutputProfiles`.  The encoder
+//! moves the selection; Select switches profile, and the caller hands the
+//! returned index to the audio output manager and persists the list.
+//!
+//! While an audiobook or podcast plays, the caller adds a speed row under
+//! the profiles ([`QuickMenu::with_speed`]) holding an index into
+//! `platform::PlaybackSpeed::STEPS`.  Select on it steps to the next speed,
+//! wrapping from the fastest back to the slowest, and the menu stays open
+//! so a few clicks reach any speed.
+//!
+//! The last row ([`QuickMenu::with_night_mode`]) toggles night mode, the
+//! black-background UI that makes refreshes far less glaring in the dark.
+//! The caller passes the returned state to the display and persists it.
+
+/// What Select changed in the quick menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuickMenuAction {
+    /// Switch to the output profile at this index.
+    Profile(usize),
+    /// Play at the speed step at this index.
+    Speed(usize),
+    /// Turn night mode on (`true`) or off.
+    NightMode(bool),
+}
+
+/// Selection within the quick menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuickMenu {
+    count: usize,
+    selected: usize,
+    active: usize,
+    /// Current speed step and the number of steps, when the row is shown.
+    speed: Option<(usize, usize)>,
+    /// Night mode state, when the row is shown.
+    night: Option<bool>,
+}
+
+impl QuickMenu {
+    /// A menu over `count` profiles, opened on the `active` one (clamped to
+    /// the list).
+    pub fn new(count: usize, active: usize) -> Self {
+        let active = active.min(count.saturating_sub(1));
+        Self {
+            count,
+            selected: active,
+            active,
+            speed: None,
+            night: None,
+        }
+    }
+
+    /// Add the speed row, on `step` of `steps` (clamped to the steps).
+    /// Without steps there is no row.
+    #[must_use]
+    pub fn with_speed(mut self, step: usize, steps: usize) -> Self {
+        self.speed = (steps > 0).then(|| (step.min(steps.saturating_sub(1)), steps));
+        self
+    }
+
+    /// Add the night mode row, showing `on`.  It follows the speed row.
+    #[must_use]
+    pub fn with_night_mode(mut self, on: bool) -> Self {
+        self.night = Some(on);
+        self
+    }
+
+    /// Number of profiles listed.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Number of rows: the profiles, plus the speed and night mode rows
+    /// when shown.
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.night_row()
+            .saturating_add(usize::from(self.night.is_some()))
+    }
+
+    /// Index the night mode row has (or would have): after the profiles
+    /// and the speed row.
+    fn night_row(&self) -> usize {
+        self.count.saturating_add(usize::from(self.speed.is_some()))
+    }
+
+    /// Highlighted row; [`count`](Self::count) is the speed row.
+    #[must_use]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Speed step shown in the speed row, or `None` without the row.
+    #[must_use]
+    pub fn speed_step(&self) -> Option<usize> {
+        self.speed.map(|(step, _)| step)
+    }
+
+    /// Whether the speed row is highlighted.
+    #[must_use]
+    pub fn speed_selected(&self) -> bool {
+        self.speed.is_some() && self.selected == self.count
+    }
+
+    /// Night mode state shown in its row, or `None` without the row.
+    #[must_use]
+    pub fn night_mode(&self) -> Option<bool> {
+        self.night
+    }
+
+    /// Whether the night mode row is highlighted.
+    #[must_use]
+    pub fn night_selected(&self) -> bool {
+        self.night.is_some() && self.selected == self.night_row()
+    }
+
+    /// Profile in use.
+    #[must_use]
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Move the selection by `steps`, wrapping around the list so any
+    /// row is at most a few detents away.
+    pub fn scroll(&mut self, steps: i32) {
+        let Some(count) = i64::try_from(self.rows()).ok().filter(|&c| c > 0) else {
+            return;
+        };
+        let selected = i64::try_from(self.selected).unwrap_or(0);
+        let target = selected.saturating_add(i64::from(steps)).rem_euclid(count);
+        self.selected = usize::try_from(target).unwrap_or(0);
+    }
+
+    /// Act on the selected row: switch to the selected profile when it
+    /// differs from the active one, step the speed or toggle night mode.
+    /// Returns what changed, `None` when nothing did.
+    pub fn confirm(&mut self) -> Option<QuickMenuAction> {
+        if self.night_selected() {
+            let on = !self.night?;
+            self.night = Some(on);
+            return Some(QuickMenuAction::NightMode(on));
+        }
+        if self.speed_selected() {
+            let (step, steps) = self.speed?;
+            let next = step.saturating_add(1).checked_rem(steps).unwrap_or(0);
+            self.speed = Some((next, steps));
+            return Some(QuickMenuAction::Speed(next));
+        }
+        if self.count == 0 || self.selected == self.active {
+            return None;
+        }
+        self.active = self.selected;
+        Some(QuickMenuAction::Profile(self.active))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{QuickMenu, QuickMenuAction};
+
+    #[test]
+    fn test_quick_menu_opens_on_active_profile() {
+        let menu = QuickMenu::new(3, 1);
+        assert_eq!(menu.selected(), 1);
+        assert_eq!(QuickMenu::new(3, 9).active(), 2);
+    }
+
+    #[test]
+    fn test_quick_menu_scroll_wraps() {
+        let mut menu = QuickMenu::new(3, 0);
+        menu.scroll(-1);
+        assert_eq!(menu.selected(), 2);
+        menu.scroll(2);
+        assert_eq!(menu.selected(), 1);
+        let mut empty = QuickMenu::new(0, 0);
+        empty.scroll(1);
+        assert_eq!(empty.selected(), 0);
+    }
+
+    #[test]
+    fn test_quick_menu_confirm_reports_changes_only() {
+        let mut menu = QuickMenu::new(3, 0);
+        assert_eq!(menu.confirm(), None);
+        menu.scroll(1);
+        assert_eq!(menu.confirm(), Some(QuickMenuAction::Profile(1)));
+        assert_eq!(menu.active(), 1);
+        assert_eq!(menu.confirm(), None);
+    }
+
+    #[test]
+    fn test_quick_menu_speed_row_follows_the_profiles() {
+        let mut menu = QuickMenu::new(3, 0).with_speed(2, 9);
+        assert_eq!(menu.rows(), 4);
+        assert_eq!(menu.speed_step(), Some(2));
+        assert!(!menu.speed_selected());
+        menu.scroll(-1);
+        assert_eq!(menu.selected(), 3);
+        assert!(menu.speed_selected());
+        menu.scroll(1);
+        assert_eq!(menu.selected(), 0, "wraps past the speed row");
+
+        let plain = QuickMenu::new(3, 0);
+        assert_eq!(plain.rows(), 3);
+        assert_eq!(plain.speed_step(), None);
+        assert_eq!(QuickMenu::new(3, 0).with_speed(1, 0).speed_step(), None);
+        assert_eq!(QuickMenu::new(3, 0).with_speed(20, 9).speed_step(), Some(8));
+    }
+
+    #[test]
+    fn test_quick_menu_speed_row_steps_and_wraps() {
+        let mut menu = QuickMenu::new(2, 1).with_speed(7, 9);
+        menu.scroll(1);
+        assert_eq!(menu.confirm(), Some(QuickMenuAction::Speed(8)));
+        assert_eq!(menu.confirm(), Some(QuickMenuAction::Speed(0)));
+        assert_eq!(menu.speed_step(), Some(0));
+        assert!(menu.speed_selected(), "the menu stays on the row");
+        assert_eq!(menu.active(), 1, "the profile is untouched");
+    }
+
+    #[test]
+    fn test_quick_menu_night_mode_row_is_last_and_toggles() {
+        let mut menu = QuickMenu::new(2, 0).with_speed(3, 9).with_night_mode(false);
+        assert_eq!(menu.rows(), 4);
+        assert_eq!(menu.night_mode(), Some(false));
+        menu.scroll(-1);
+        assert!(menu.night_selected());
+        assert!(!menu.speed_selected());
+        assert_eq!(menu.confirm(), Some(QuickMenuAction::NightMode(true)));
+        assert_eq!(menu.confirm(), Some(QuickMenuAction::NightMode(false)));
+        assert!(menu.night_selected(), "the menu stays on the row");
+        assert_eq!(menu.speed_step(), Some(3));
+
+        let mut no_speed = QuickMenu::new(2, 0).with_night_mode(true);
+        no_speed.scroll(2);
+        assert!(no_speed.night_selected());
+        assert_eq!(no_speed.confirm(), Some(QuickMenuAction::NightMode(false)));
+        assert_eq!(QuickMenu::new(2, 0).night_mode(), None);
+        assert!(!QuickMenu::new(2, 0).night_selected());
+    }
+}
@@ -7,10 +7,26 @@ pub enum Screen {
     NowPlaying,
     /// Music library browser.
     LibraryBrowse,
+    /// Progress of the library scan, with Back to cancel.
+    LibraryScan,
+    /// Synchronised lyrics for the current track.
+    Lyrics,
+    /// Chapter list of the current audiobook.
+    Chapters,
+    /// Play queue, with multi-select for batch actions.
+    Queue,
     /// Application settings.
     Settings,
+    /// DAC oversampling filter selection.
+    AudioSettings,
     /// Transient volume-adjustment overlay (pushed on top of any screen).
     VolumeOverlay,
+    /// Output profile switcher (pushed on top of any screen).
+    QuickMenu,
+    /// Title/artist/album corrections for the selected track.
+    TagEditor,
+    /// Hidden service screen: display patterns and self-test results.
+    Diagnostics,
 }
 
 #[cfg(test)]
@@ -29,18 +45,66 @@ mod tests {
         assert_eq!(s, Screen::LibraryBrowse);
     }
 
+    #[test]
+    fn test_screen_enum_has_library_scan() {
+        let s = Screen::LibraryScan;
+        assert_eq!(s, Screen::LibraryScan);
+    }
+
+    #[test]
+    fn test_screen_enum_has_lyrics() {
+        let s = Screen::Lyrics;
+        assert_eq!(s, Screen::Lyrics);
+    }
+
+    #[test]
+    fn test_screen_enum_has_chapters() {
+        let s = Screen::Chapters;
+        assert_eq!(s, Screen::Chapters);
+    }
+
+    #[test]
+    fn test_screen_enum_has_queue() {
+        let s = Screen::Queue;
+        assert_eq!(s, Screen::Queue);
+    }
+
     #[test]
     fn test_screen_enum_has_settings() {
         let s = Screen::Settings;
         assert_eq!(s, Screen::Settings);
     }
 
+    #[test]
+    fn test_screen_enum_has_audio_settings() {
+        let s = Screen::AudioSettings;
+        assert_eq!(s, Screen::AudioSettings);
+    }
+
     #[test]
     fn test_screen_enum_has_volume_overlay() {
         let s = Screen::VolumeOverlay;
         assert_eq!(s, Screen::VolumeOverlay);
     }
 
+    #[test]
+    fn test_screen_enum_has_quick_menu() {
+        let s = Screen::QuickMenu;
+        assert_eq!(s, Screen::QuickMenu);
+    }
+
+    #[test]
+    fn test_screen_enum_has_tag_editor() {
+        let s = Screen::TagEditor;
+        assert_eq!(s, Screen::TagEditor);
+    }
+
+    #[test]
+    fn test_screen_enum_has_diagnostics() {
+        let s = Screen::Diagnostics;
+        assert_eq!(s, Screen::Diagnostics);
+    }
+
     #[test]
     fn test_screen_is_copy() {
         let a = Screen::NowPlaying;
@@ -0,0 +1,131 @@
+//! Multi-selection over a list — which rows are marked for a batch action.
+//!
+//! A fixed bitset, so marking is allocation-free and a copy of the marks can
+//! be handed to the caller with the action.  Lists longer than
+//! [`MAX_SELECTABLE`] rows can only mark the first [`MAX_SELECTABLE`].
+
+/// Rows a [`Selection`] can track.
+pub const MAX_SELECTABLE: usize = 256;
+
+const WORD_BITS: usize = 32;
+const WORDS: usize = MAX_SELECTABLE / WORD_BITS;
+
+/// Marked rows of a list of `len` rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    words: [u32; WORDS],
+    len: usize,
+}
+
+impl Selection {
+    /// Nothing marked in a list of `len` rows.
+    pub fn new(len: usize) -> Self {
+        Self {
+            words: [0; WORDS],
+            len: len.min(MAX_SELECTABLE),
+        }
+    }
+
+    /// Rows that can be marked.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// `true` when the list has no markable rows.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn slot(&self, index: usize) -> Option<(usize, u32)> {
+        if index >= self.len {
+            return None;
+        }
+        let bit = 1u32.checked_shl(u32::try_from(index % WORD_BITS).ok()?)?;
+        Some((index / WORD_BITS, bit))
+    }
+
+    /// Whether row `index` is marked.
+    #[must_use]
+    pub fn is_marked(&self, index: usize) -> bool {
+        self.slot(index)
+            .and_then(|(word, bit)| self.words.get(word).map(|w| w & bit != 0))
+            .unwrap_or(false)
+    }
+
+    /// Mark or unmark row `index` (ignored out of range).
+    pub fn set(&mut self, index: usize, marked: bool) {
+        let Some((word, bit)) = self.slot(index) else {
+            return;
+        };
+        if let Some(w) = self.words.get_mut(word) {
+            if marked {
+                *w |= bit;
+            } else {
+                *w &= !bit;
+            }
+        }
+    }
+
+    /// Flip row `index`; returns whether it is now marked.
+    pub fn toggle(&mut self, index: usize) -> bool {
+        let marked = !self.is_marked(index);
+        self.set(index, marked);
+        self.is_marked(index)
+    }
+
+    /// Number of marked rows.
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.words
+            .iter()
+            .map(|w| usize::try_from(w.count_ones()).unwrap_or(0))
+            .fold(0, usize::saturating_add)
+    }
+
+    /// `true` when no row is marked.
+    #[must_use]
+    pub fn is_none(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Unmark every row.
+    pub fn clear(&mut self) {
+        self.words = [0; WORDS];
+    }
+
+    /// Marked rows, first to last.
+    pub fn marked(&self) -> impl DoubleEndedIterator<Item = usize> + '_ {
+        (0..self.len).filter(|&i| self.is_marked(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Selection, MAX_SELECTABLE};
+
+    #[test]
+    fn test_selection_marks_and_counts() {
+        let mut sel = Selection::new(40);
+        assert!(sel.is_none());
+        assert!(sel.toggle(3));
+        sel.set(35, true);
+        sel.set(39, true);
+        assert!(!sel.toggle(39));
+        assert_eq!(sel.count(), 2);
+        assert_eq!(sel.marked().collect::<Vec<_>>(), [3, 35]);
+        assert_eq!(sel.marked().rev().collect::<Vec<_>>(), [35, 3]);
+        sel.clear();
+        assert!(sel.is_none());
+    }
+
+    #[test]
+    fn test_selection_ignores_rows_past_the_end() {
+        let mut sel = Selection::new(4);
+        sel.set(4, true);
+        assert!(!sel.toggle(9));
+        assert!(sel.is_none());
+        assert_eq!(Selection::new(MAX_SELECTABLE + 10).len(), MAX_SELECTABLE);
+    }
+}
@@ -0,0 +1,211 @@
+//! Tag editor state — fixing a track's title, artist and album.
+//!
+//! Fields are identified by their index in `library::TagField::ALL` so the
+//! `ui` crate stays independent of the library.  The encoder moves between
+//! fields; Select opens the highlighted one in a [`TextInput`] wheel, and
+//! Done on the wheel stores the new value.  Holding Select while editing
+//! deletes the last character.  [`click`](TagEditor::click)
+//! then returns the field index, and the caller records it with
+//! `TagOverrides::set` and persists the table.  Back while editing discards
+//! the text; Back on the field list leaves the screen.
+
+use heapless::String;
+
+use crate::text_input::TextInput;
+
+/// Number of editable fields (title, artist, album).
+pub const TAG_FIELDS: usize = 3;
+
+/// Byte capacity of one field value (the longest, the title).
+pub const TAG_VALUE_LEN: usize = 128;
+
+/// Field list and, while a field is open, its text input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagEditor {
+    values: [String<TAG_VALUE_LEN>; TAG_FIELDS],
+    limits: [usize; TAG_FIELDS],
+    selected: usize,
+    editing: Option<TextInput<TAG_VALUE_LEN>>,
+}
+
+impl TagEditor {
+    /// An editor over the current `values`, each at most `limits` bytes
+    /// long (clamped to [`TAG_VALUE_LEN`]).
+    pub fn new(values: [&str; TAG_FIELDS], limits: [usize; TAG_FIELDS]) -> Self {
+        let limits = limits.map(|limit| limit.min(TAG_VALUE_LEN));
+        let mut owned: [String<TAG_VALUE_LEN>; TAG_FIELDS] = Default::default();
+        for ((slot, value), limit) in owned.iter_mut().zip(values).zip(limits) {
+            *slot = String::try_from(TextInput::<TAG_VALUE_LEN>::new(value, limit).text())
+                .unwrap_or_default();
+        }
+        Self {
+            values: owned,
+            limits,
+            selected: 0,
+            editing: None,
+        }
+    }
+
+    /// Highlighted field.
+    #[must_use]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Current value of `field` (empty when out of range).
+    #[must_use]
+    pub fn value(&self, field: usize) -> &str {
+        self.values.get(field).map_or("", String::as_str)
+    }
+
+    /// The open text input, while a field is being edited.
+    #[must_use]
+    pub fn editing(&self) -> Option<&TextInput<TAG_VALUE_LEN>> {
+        self.editing.as_ref()
+    }
+
+    /// Turn the encoder by `steps`: the wheel while editing, otherwise the
+    /// field selection (clamped at the ends).
+    pub fn scroll(&mut self, steps: i32) {
+        if let Some(input) = self.editing.as_mut() {
+            input.scroll(steps);
+            return;
+        }
+        let delta = usize::try_from(steps.unsigned_abs()).unwrap_or(usize::MAX);
+        let target = if steps >= 0 {
+            self.selected.saturating_add(delta)
+        } else {
+            self.selected.saturating_sub(delta)
+        };
+        self.selected = target.min(TAG_FIELDS.saturating_sub(1));
+    }
+
+    /// Release Select.  Opens the highlighted field, or presses the wheel
+    /// key (see [`TextInput::release`]).  Returns the field index when Done
+    /// stored a changed value.
+    pub fn click(&mut self) -> Option<usize> {
+        let Some(input) = self.editing.as_mut() else {
+            let limit = self.limits.get(self.selected).copied().unwrap_or(0);
+            self.editing = Some(TextInput::new(self.value(self.selected), limit));
+            return None;
+        };
+        if !input.release() {
+            return None;
+        }
+        let text = String::try_from(input.text()).unwrap_or_default();
+        self.editing = None;
+        let slot = self.values.get_mut(self.selected)?;
+        if *slot == text {
+            return None;
+        }
+        *slot = text;
+        Some(self.selected)
+    }
+
+    /// Hold Select: delete the last character of the open field.
+    pub fn long_press(&mut self) {
+        if let Some(input) = self.editing.as_mut() {
+            input.long_press();
+        }
+    }
+
+    /// Press Back.  Discards an open edit and returns `false`; on the
+    /// field list returns `true` (leave the screen).
+    pub fn back(&mut self) -> bool {
+        self.editing.take().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TagEditor, TAG_VALUE_LEN};
+    use crate::text_input::Key;
+
+    fn editor() -> TagEditor {
+        TagEditor::new(["Track 01", "Unknown", "Dummy"], [128, 64, 64])
+    }
+
+    /// Turn the wheel forward to `key`.
+    fn turn_to(editor: &mut TagEditor, key: Key) {
+        for _ in 0..crate::text_input::WHEEL_LEN {
+            if editor.editing().map(|i| i.key()) == Some(key) {
+                return;
+            }
+            editor.scroll(1);
+        }
+    }
+
+    /// Turn the wheel to `key` and press it.
+    fn press(editor: &mut TagEditor, key: Key) {
+        turn_to(editor, key);
+        editor.click();
+    }
+
+    #[test]
+    fn test_tag_editor_selection_clamps() {
+        let mut e = editor();
+        e.scroll(5);
+        assert_eq!(e.selected(), 2);
+        e.scroll(-9);
+        assert_eq!(e.selected(), 0);
+        assert_eq!(e.value(1), "Unknown");
+        assert_eq!(e.value(3), "");
+    }
+
+    #[test]
+    fn test_tag_editor_done_reports_changed_field() {
+        let mut e = editor();
+        e.scroll(1);
+        assert_eq!(e.click(), None, "opens the artist field");
+        assert_eq!(e.editing().map(|i| i.text()), Some("Unknown"));
+        for _ in 0.."Unknown".len() {
+            press(&mut e, Key::Delete);
+        }
+        press(&mut e, Key::Char('Y'));
+        press(&mut e, Key::Char('o'));
+        turn_to(&mut e, Key::Done);
+        assert_eq!(e.click(), Some(1));
+        assert!(e.editing().is_none());
+        assert_eq!(e.value(1), "Yo");
+
+        // Done without changes reports nothing.
+        e.click();
+        e.scroll(-1);
+        assert_eq!(e.click(), None);
+    }
+
+    #[test]
+    fn test_tag_editor_back_discards_then_leaves() {
+        let mut e = editor();
+        e.click();
+        press(&mut e, Key::Char('X'));
+        assert!(!e.back());
+        assert_eq!(e.value(0), "Track 01");
+        assert!(e.back());
+    }
+
+    #[test]
+    fn test_tag_editor_hold_deletes_in_the_open_field() {
+        let mut e = editor();
+        e.long_press();
+        assert_eq!(e.editing(), None, "a hold on the list does nothing");
+        e.click();
+        e.long_press();
+        assert_eq!(e.click(), None, "release after the hold");
+        turn_to(&mut e, Key::Done);
+        assert_eq!(e.click(), Some(0));
+        assert_eq!(e.value(0), "Track 0");
+    }
+
+    #[test]
+    fn test_tag_editor_clamps_limits() {
+        let long = "x".repeat(200);
+        let e = TagEditor::new([&long, "", ""], [500, 4, 4]);
+        assert_eq!(e.value(0).len(), TAG_VALUE_LEN);
+        let mut e = TagEditor::new(["", "abcdef", ""], [128, 4, 4]);
+        assert_eq!(e.value(1), "abcd");
+        e.scroll(1);
+        e.click();
+        assert_eq!(e.editing().map(|i| i.limit()), Some(4));
+    }
+}
@@ -0,0 +1,219 @@
+//! Text input state — spelling out text with the rotary encoder.
+//!
+//! There is no keyboard, so text is entered one character at a time from a
+//! wheel: the encoder turns the wheel, Select types the highlighted key.
+//! The wheel holds [`CHARSET`] followed by two actions, [`Key::Delete`]
+//! (remove the last character) and [`Key::Done`].  It opens on the first
+//! character, so Done and Delete are one and two detents back.  Holding
+//! Select deletes too, wherever the wheel is: the event loop forwards the
+//! button's long press to [`long_press`](TextInput::long_press) and its
+//! release to [`release`](TextInput::release), which then types nothing.
+//! Back is left to the caller (it usually discards the edit).
+
+use heapless::String;
+
+/// Characters on the wheel, in order.
+pub const CHARSET: &str =
+    "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789 .,'&-()!?/:";
+
+/// Number of positions on the wheel: every character plus the two actions.
+pub const WHEEL_LEN: usize = CHARSET.len().saturating_add(2);
+
+/// One position on the wheel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// Type this character.
+    Char(char),
+    /// Remove the last character.
+    Delete,
+    /// Finish editing.
+    Done,
+}
+
+impl Key {
+    /// The key at wheel position `index`, wrapping around the wheel.
+    pub fn at(index: usize) -> Self {
+        let index = index.checked_rem(WHEEL_LEN).unwrap_or(0);
+        match CHARSET.as_bytes().get(index) {
+            Some(&b) => Self::Char(char::from(b)),
+            None if index == CHARSET.len() => Self::Delete,
+            None => Self::Done,
+        }
+    }
+}
+
+/// Text being entered, up to `limit` bytes (at most `N`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextInput<const N: usize> {
+    text: String<N>,
+    limit: usize,
+    position: usize,
+    held: bool,
+}
+
+impl<const N: usize> TextInput<N> {
+    /// Start from `initial` (truncated to `limit` bytes on a character
+    /// boundary), with room for `limit` bytes (clamped to `N`).
+    pub fn new(initial: &str, limit: usize) -> Self {
+        let limit = limit.min(N);
+        let mut text = String::new();
+        for c in initial.chars() {
+            if text.len().saturating_add(c.len_utf8()) > limit || text.push(c).is_err() {
+                break;
+            }
+        }
+        Self {
+            text,
+            limit,
+            position: 0,
+            held: false,
+        }
+    }
+
+    /// Text entered so far.
+    #[must_use]
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+
+    /// Byte capacity of the text.
+    #[must_use]
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Whether another character would exceed the limit.
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        self.text.len() >= self.limit
+    }
+
+    /// Highlighted wheel position.
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Highlighted key.
+    #[must_use]
+    pub fn key(&self) -> Key {
+        Key::at(self.position)
+    }
+
+    /// The key `offset` positions from the highlighted one, for drawing
+    /// the wheel around it.
+    #[must_use]
+    pub fn key_near(&self, offset: i32) -> Key {
+        Key::at(wheel_step(self.position, offset))
+    }
+
+    /// Turn the wheel by `steps`, wrapping around.
+    pub fn scroll(&mut self, steps: i32) {
+        self.position = wheel_step(self.position, steps);
+    }
+
+    /// Press the highlighted key.  Returns `true` on [`Key::Done`], when
+    /// [`text`](Self::text) holds the finished value.  Characters past the
+    /// limit are ignored.
+    pub fn click(&mut self) -> bool {
+        match self.key() {
+            Key::Char(c) => {
+                if !self.is_full() {
+                    let _ = self.text.push(c);
+                }
+                false
+            }
+            Key::Delete => {
+                self.text.pop();
+                false
+            }
+            Key::Done => true,
+        }
+    }
+
+    /// Select was held: remove the last character.  Returns `true` if there
+    /// was one.  The release that ends the hold is swallowed.
+    pub fn long_press(&mut self) -> bool {
+        self.held = true;
+        self.text.pop().is_some()
+    }
+
+    /// Select was released: [`click`](Self::click), unless the press was a
+    /// [`long_press`](Self::long_press).
+    pub fn release(&mut self) -> bool {
+        if core::mem::take(&mut self.held) {
+            return false;
+        }
+        self.click()
+    }
+}
+
+/// `position` moved by `steps` around the wheel.
+fn wheel_step(position: usize, steps: i32) -> usize {
+    let (Ok(len), Ok(position)) = (i64::try_from(WHEEL_LEN), i64::try_from(position)) else {
+        return 0;
+    };
+    let target = position.saturating_add(i64::from(steps)).rem_euclid(len);
+    usize::try_from(target).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Key, TextInput, CHARSET, WHEEL_LEN};
+
+    #[test]
+    fn test_text_input_types_deletes_and_finishes() {
+        let mut input = TextInput::<16>::new("Roa", 16);
+        assert_eq!(input.key(), Key::Char('A'));
+        input.scroll(29); // 'd'
+        assert!(!input.click());
+        input.scroll(-28); // 'B'
+        assert!(!input.click());
+        assert_eq!(input.text(), "RoadB");
+        input.scroll(-3);
+        assert_eq!(input.key(), Key::Delete);
+        assert!(!input.click());
+        input.scroll(1);
+        assert_eq!(input.key(), Key::Done);
+        assert!(input.click());
+        assert_eq!(input.text(), "Road");
+    }
+
+    #[test]
+    fn test_text_input_wheel_wraps_both_ways() {
+        let mut input = TextInput::<8>::new("", 8);
+        input.scroll(-1);
+        assert_eq!(input.key(), Key::Done);
+        assert_eq!(input.key_near(-1), Key::Delete);
+        assert_eq!(input.key_near(1), Key::Char('A'));
+        input.scroll(i32::try_from(WHEEL_LEN).unwrap_or(0));
+        assert_eq!(input.key(), Key::Done);
+        assert_eq!(Key::at(CHARSET.len().saturating_sub(1)), Key::Char(':'));
+    }
+
+    #[test]
+    fn test_text_input_respects_the_limit() {
+        let mut input = TextInput::<8>::new("Überlong", 4);
+        assert_eq!(input.text(), "Übe", "truncated on a char boundary");
+        assert_eq!(input.limit(), 4);
+        assert!(input.is_full());
+        input.click();
+        assert_eq!(input.text(), "Übe");
+        assert_eq!(TextInput::<2>::new("", 10).limit(), 2);
+    }
+
+    #[test]
+    fn test_text_input_long_press_deletes_without_typing() {
+        let mut input = TextInput::<8>::new("ab", 8);
+        assert!(input.long_press());
+        assert!(!input.release());
+        assert_eq!(input.text(), "a", "the release after a hold types nothing");
+        assert!(!input.release());
+        assert_eq!(input.text(), "aA", "a plain release types the key");
+        input.long_press();
+        input.long_press();
+        assert!(!input.long_press(), "nothing left to delete");
+        input.release();
+        assert_eq!(input.text(), "");
+    }
+}
@@ -0,0 +1,9 @@
+//! Shared `no_std` utilities for the SoulAudio DAP crates.
+//!
+//! Only dependency-free building blocks belong here — anything touching
+//! hardware traits lives in `platform`.
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+
+pub mod rng;
+
+pub use rng::Rng;
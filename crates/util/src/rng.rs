@@ -0,0 +1,303 @@
+//! Deterministic pseudo-random number generator.
+//!
+//! [`Rng`] is xoshiro128++ — 16 bytes of state, one add/rotate/xor round
+//! per `u32`, no multiplies, which suits the Cortex-M7.  It is **not**
+//! cryptographically secure; it exists so that shuffle order, dither noise
+//! and screensaver motion are varied on the device yet exactly reproducible
+//! in tests.
+//!
+//! # Seeding
+//!
+//! | Context  | Seed source                                               |
+//! |----------|-----------------------------------------------------------|
+//! | Device   | STM32H7 RNG peripheral words → [`Rng::from_entropy`]      |
+//! | Emulator | wall-clock nanoseconds → [`Rng::seed_from_u64`]           |
+//! | Tests    | a fixed literal → [`Rng::seed_from_u64`]                  |
+//!
+//! Each subsystem should own its own stream, obtained with [`Rng::fork`],
+//! so that adding a draw in one place does not perturb every other
+//! consumer's sequence.
+//!
+//! # Example
+//!
+//! ```
+//! use util::Rng;
+//!
+//! let mut rng = Rng::seed_from_u64(42);
+//! let mut shuffle_rng = rng.fork();
+//!
+//! let mut order = [1, 2, 3, 4, 5];
+//! shuffle_rng.shuffle(&mut order);
+//!
+//! // Same seed, same order.
+//! let mut again = [1, 2, 3, 4, 5];
+//! Rng::seed_from_u64(42).fork().shuffle(&mut again);
+//! assert_eq!(order, again);
+//! ```
+
+/// Jump polynomial advancing the state by 2^64 steps.
+const JUMP: [u32; 4] = [0x8764_000b, 0xf542_d2d3, 0x6fa0_35c3, 0x77f2_db5b];
+
+/// xoshiro128++ generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    s: [u32; 4],
+}
+
+impl Rng {
+    /// Expand a 64-bit seed into a full state with SplitMix64.
+    ///
+    /// Every seed (including 0) yields a valid, non-zero state.
+    #[must_use]
+    pub const fn seed_from_u64(seed: u64) -> Self {
+        let (sm, a) = splitmix64(seed);
+        let (_, b) = splitmix64(sm);
+        Self::from_state(split(a), split(b))
+    }
+
+    /// Seed from raw hardware entropy words.
+    ///
+    /// The words are folded through SplitMix64, so a weak or partially stuck
+    /// entropy source still produces a well-mixed state.  An empty slice
+    /// behaves like `seed_from_u64(0)`.
+    #[must_use]
+    pub fn from_entropy(words: &[u32]) -> Self {
+        let mut acc = 0u64;
+        for &w in words {
+            acc = splitmix64(acc ^ u64::from(w)).1;
+        }
+        Self::seed_from_u64(acc)
+    }
+
+    /// Raw 128-bit state, for persisting a stream across reboots.
+    #[must_use]
+    pub const fn state(&self) -> [u32; 4] {
+        self.s
+    }
+
+    /// Restore a generator from [`state`](Self::state).
+    ///
+    /// The all-zero state is a fixed point of xoshiro and is replaced by
+    /// `seed_from_u64(0)`.
+    #[must_use]
+    pub const fn from_raw_state(s: [u32; 4]) -> Self {
+        if s[0] == 0 && s[1] == 0 && s[2] == 0 && s[3] == 0 {
+            Self::seed_from_u64(0)
+        } else {
+            Self { s }
+        }
+    }
+
+    const fn from_state(lo: (u32, u32), hi: (u32, u32)) -> Self {
+        Self::from_raw_state([lo.0, lo.1, hi.0, hi.1])
+    }
+
+    /// Next 32 random bits.
+    pub fn next_u32(&mut self) -> u32 {
+        let [s0, s1, s2, s3] = &mut self.s;
+        let result = s0.wrapping_add(*s3).rotate_left(7).wrapping_add(*s0);
+        let t = s1.wrapping_shl(9);
+        *s2 ^= *s0;
+        *s3 ^= *s1;
+        *s1 ^= *s2;
+        *s0 ^= *s3;
+        *s2 ^= t;
+        *s3 = s3.rotate_left(11);
+        result
+    }
+
+    /// Next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        let lo = u64::from(self.next_u32());
+        let hi = u64::from(self.next_u32());
+        hi.wrapping_shl(32) | lo
+    }
+
+    /// Uniform value in `0..bound`, without modulo bias.
+    ///
+    /// Returns 0 when `bound` is 0.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        if bound == 0 {
+            return 0;
+        }
+        // Lemire's multiply-shift with rejection of the biased low zone.
+        let threshold = bound.wrapping_neg().checked_rem(bound).unwrap_or(0);
+        loop {
+            let m = u64::from(self.next_u32()).wrapping_mul(u64::from(bound));
+            // SAFETY (cast): low half of a u64 product; truncation intended.
+            #[allow(clippy::cast_possible_truncation)]
+            let low = m as u32;
+            if low >= threshold {
+                // SAFETY (cast): high half of a 32×32 product fits in u32.
+                #[allow(clippy::cast_possible_truncation)]
+                return m.wrapping_shr(32) as u32;
+            }
+        }
+    }
+
+    /// Uniform value in `lo..hi`; returns `lo` when the range is empty.
+    pub fn range(&mut self, lo: u32, hi: u32) -> u32 {
+        lo.saturating_add(self.below(hi.saturating_sub(lo)))
+    }
+
+    /// `true` with probability `numerator / denominator`.
+    pub fn chance(&mut self, numerator: u32, denominator: u32) -> bool {
+        self.below(denominator) < numerator
+    }
+
+    /// Fill `dest` with random bytes.
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            for (d, b) in chunk.iter_mut().zip(bytes) {
+                *d = b;
+            }
+        }
+    }
+
+    /// Fisher–Yates shuffle of `items` in place.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        let mut i = items.len();
+        while i > 1 {
+            // SAFETY (cast): slices longer than u32::MAX are not shuffled on
+            // this target; saturate rather than truncate on the host.
+            #[allow(clippy::cast_possible_truncation)]
+            let bound = i.min(u32::MAX as usize) as u32;
+            let j = self.below(bound) as usize;
+            i = i.saturating_sub(1);
+            items.swap(i, j);
+        }
+    }
+
+    /// Split off an independent stream.
+    ///
+    /// Returns a copy of the current generator, then jumps `self` ahead by
+    /// 2^64 steps so the two sequences never overlap in practice.
+    #[must_use]
+    pub fn fork(&mut self) -> Self {
+        let child = self.clone();
+        self.jump();
+        child
+    }
+
+    /// Advance the state by 2^64 steps.
+    pub fn jump(&mut self) {
+        let mut acc = [0u32; 4];
+        for word in JUMP {
+            for bit in 0..32 {
+                if word.wrapping_shr(bit) & 1 != 0 {
+                    for (a, s) in acc.iter_mut().zip(self.s) {
+                        *a ^= s;
+                    }
+                }
+                self.next_u32();
+            }
+        }
+        self.s = acc;
+    }
+}
+
+/// One SplitMix64 step: returns `(next_state, output)`.
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ z.wrapping_shr(30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ z.wrapping_shr(27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ z.wrapping_shr(31))
+}
+
+/// Split a `u64` into `(low, high)` halves.
+// SAFETY (cast): each half is masked/shifted to 32 bits before the cast.
+#[allow(clippy::cast_possible_truncation)]
+const fn split(v: u64) -> (u32, u32) {
+    (v as u32, v.wrapping_shr(32) as u32)
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)] // Tests index with known bounds
+#[allow(clippy::arithmetic_side_effects)] // Tests use arithmetic safely
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference_vector() {
+        // Reference output of xoshiro128++ from state [1, 2, 3, 4].
+        let mut rng = Rng::from_raw_state([1, 2, 3, 4]);
+        let out = [rng.next_u32(), rng.next_u32(), rng.next_u32(), rng.next_u32()];
+        assert_eq!(out, [641, 1_573_767, 3_222_811_527, 3_517_856_514]);
+    }
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Rng::seed_from_u64(7);
+        let mut b = Rng::seed_from_u64(7);
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+        assert_ne!(Rng::seed_from_u64(7), Rng::seed_from_u64(8));
+    }
+
+    #[test]
+    fn test_zero_state_is_replaced() {
+        let mut rng = Rng::from_raw_state([0; 4]);
+        assert_ne!(rng.state(), [0; 4]);
+        assert_ne!(rng.next_u32(), rng.next_u32());
+    }
+
+    #[test]
+    fn test_from_entropy_mixes_every_word() {
+        let a = Rng::from_entropy(&[1, 2, 3]);
+        let b = Rng::from_entropy(&[1, 2, 4]);
+        assert_ne!(a, b);
+        assert_eq!(Rng::from_entropy(&[]), Rng::seed_from_u64(0));
+    }
+
+    #[test]
+    fn test_below_stays_in_bounds_and_covers_range() {
+        let mut rng = Rng::seed_from_u64(1);
+        let mut seen = [false; 6];
+        for _ in 0..600 {
+            let v = rng.below(6);
+            assert!(v < 6);
+            seen[v as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+        assert_eq!(rng.below(0), 0);
+        assert_eq!(rng.range(10, 10), 10);
+        assert!((10..20).contains(&rng.range(10, 20)));
+    }
+
+    #[test]
+    fn test_shuffle_is_a_permutation() {
+        let mut rng = Rng::seed_from_u64(99);
+        let mut items = [0u8, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        rng.shuffle(&mut items);
+        assert_ne!(items, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        let mut sorted = items;
+        sorted.sort_unstable();
+        assert_eq!(sorted, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        let mut empty: [u8; 0] = [];
+        rng.shuffle(&mut empty);
+    }
+
+    #[test]
+    fn test_fork_streams_diverge() {
+        let mut parent = Rng::seed_from_u64(3);
+        let mut a = parent.fork();
+        let mut b = parent.fork();
+        assert_ne!(a.next_u32(), b.next_u32());
+    }
+
+    #[test]
+    fn test_fill_bytes_partial_chunk() {
+        let mut a = Rng::seed_from_u64(5);
+        let mut b = Rng::seed_from_u64(5);
+        let mut buf = [0u8; 7];
+        a.fill_bytes(&mut buf);
+        let w0 = b.next_u32().to_le_bytes();
+        let w1 = b.next_u32().to_le_bytes();
+        assert_eq!(buf[..4], w0);
+        assert_eq!(buf[4..], w1[..3]);
+    }
+}
@@ -0,0 +1,14 @@
+REM GENRE Audiobook
+PERFORMER "Narrator"
+TITLE "The Book"
+FILE "book.m4b" MP4
+  TRACK 01 AUDIO
+    TITLE "Chapter 1"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Chapter 2"
+    INDEX 00 12:30:00
+    INDEX 01 12:34:56
+FILE "appendix.m4b" MP4
+  TRACK 03 AUDIO
+    INDEX 01 00:00:00
@@ -0,0 +1,3 @@
+﻿FILE "a.mp3" MP3
+  TRACK 01 AUDIO
+    INDEX 01 01:02:03
@@ -0,0 +1,18 @@
+//! CUE sheet chapter parsing.
+//!
+//! CUE files sit next to audiobooks on the card and are read as text.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use library::Chapters;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(src) = core::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(chapters) = Chapters::from_cue(src) {
+        assert!(!chapters.is_empty());
+        assert_eq!(chapters.starts_ms().len(), chapters.len());
+        let _ = chapters.index_at(u64::MAX);
+    }
+});
@@ -0,0 +1,15 @@
+//! ID3v2 tag skipping and magic-byte format detection.
+//!
+//! The player strips a leading tag before decoding and sniffs the format
+//! from the first bytes of every scanned file; both see arbitrary bytes.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let audio = playback::test_vectors::strip_id3v2(data);
+    // The stripped stream is always a suffix of the input.
+    assert!(data.ends_with(audio));
+    let _ = library::detect_format(data);
+    let _ = library::detect_format(audio);
+});
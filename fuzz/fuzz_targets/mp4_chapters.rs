@@ -0,0 +1,15 @@
+//! MP4 box walking and `chpl` chapter parsing for `.m4b` audiobooks.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use library::Chapters;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = playback::mp4::read_box_header(data);
+    if let Ok(chapters) = Chapters::from_mp4(data) {
+        assert!(!chapters.is_empty());
+        for i in 0..chapters.len() {
+            assert!(chapters.title(i).is_some());
+        }
+    }
+});
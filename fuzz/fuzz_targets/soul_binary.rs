@@ -0,0 +1,39 @@
+//! Soul library binary decoding: `manifest.bin`, `library.idx` entries,
+//! the `library.browse` header and postcard `TrackMeta` records.
+//!
+//! Input layout: a 64-byte manifest, a 32-byte browse header, then
+//! alternating 24-byte index entries and the bytes each one points at.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use library::{BrowseHeader, BrowseOrder, IndexEntry, ManifestBin, TrackMeta};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((manifest, rest)) = data.split_first_chunk::<{ ManifestBin::SIZE }>() else {
+        return;
+    };
+    if let Ok(m) = ManifestBin::decode(manifest) {
+        assert_eq!(ManifestBin::decode(&m.encode()), Ok(m));
+    }
+
+    let Some((browse, mut rest)) = rest.split_first_chunk::<{ BrowseHeader::SIZE }>() else {
+        return;
+    };
+    if let Ok(h) = BrowseHeader::decode(browse) {
+        for order in [BrowseOrder::Artist, BrowseOrder::Album, BrowseOrder::Title] {
+            let _ = h.order_offset(order, u32::MAX);
+            let _ = h.group_offset(order, h.group_count(order));
+        }
+        let _ = h.file_len();
+    }
+
+    while let Some((entry, tail)) = rest.split_first_chunk::<{ IndexEntry::SIZE }>() {
+        let Ok(entry) = IndexEntry::decode(entry) else {
+            return;
+        };
+        let len = usize::try_from(entry.meta_size).unwrap_or(usize::MAX);
+        let meta = tail.get(..len).unwrap_or(tail);
+        let _ = postcard::from_bytes::<TrackMeta>(meta);
+        rest = tail.get(len..).unwrap_or_default();
+    }
+});
@@ -0,0 +1,280 @@
+//! `cargo run -p xtask -- bench` — collect and compare per-frame decode timings.
+//!
+//! Host numbers come from the playback crate's `decode_frame` criterion
+//! bench, saved as a criterion baseline named after the current commit.
+//! Target numbers are the `decode <label>: ... mean=N ...` defmt lines the
+//! `cycle-count` feature prints; capture RTT output to a file per build and
+//! compare the files.
+
+use anyhow::{Context, Result};
+use clap::Subcommand;
+use colored::Colorize;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const BENCH_PACKAGE: &str = "playback";
+const BENCH_NAME: &str = "decode_frame";
+
+#[derive(Subcommand)]
+pub enum BenchCommand {
+    /// Run the host decode benchmarks and save them as a baseline
+    Run {
+        /// Baseline name (default: short hash of HEAD)
+        #[arg(long)]
+        name: Option<String>,
+        /// Include MP3 vectors (also set SOUL_BENCH_VECTORS)
+        #[arg(long)]
+        mp3: bool,
+    },
+    /// Compare two saved host baselines (e.g. two commit hashes)
+    Diff {
+        /// Older baseline
+        base: String,
+        /// Newer baseline (default: short hash of HEAD)
+        new: Option<String>,
+    },
+    /// Compare two captured on-target logs (`cycle-count` defmt output)
+    DiffLog {
+        /// RTT capture from the older firmware build
+        base: PathBuf,
+        /// RTT capture from the newer firmware build
+        new: PathBuf,
+    },
+}
+
+pub fn run(command: BenchCommand) -> Result<()> {
+    match command {
+        BenchCommand::Run { name, mp3 } => collect(name, mp3),
+        BenchCommand::Diff { base, new } => {
+            let new = match new {
+                Some(n) => n,
+                None => head_hash()?,
+            };
+            let root = workspace_root()?.join("target").join("criterion");
+            let old = read_baseline(&root, &base)?;
+            let cur = read_baseline(&root, &new)?;
+            print_diff(&base, &new, &old, &cur, "ns");
+            Ok(())
+        }
+        BenchCommand::DiffLog { base, new } => {
+            let old = read_target_log(&base)?;
+            let cur = read_target_log(&new)?;
+            print_diff(
+                &base.display().to_string(),
+                &new.display().to_string(),
+                &old,
+                &cur,
+                "cycles",
+            );
+            Ok(())
+        }
+    }
+}
+
+fn collect(name: Option<String>, mp3: bool) -> Result<()> {
+    let name = match name {
+        Some(n) => n,
+        None => head_hash()?,
+    };
+    println!();
+    println!(
+        "{}",
+        format!("⏱  Running decode benchmarks (baseline '{name}')...")
+            .cyan()
+            .bold()
+    );
+    println!();
+
+    let mut cmd = Command::new("cargo");
+    cmd.args(["bench", "-p", BENCH_PACKAGE, "--bench", BENCH_NAME]);
+    if mp3 {
+        cmd.args(["--features", "mp3"]);
+    }
+    cmd.args(["--", "--save-baseline", &name]);
+
+    let status = cmd.status().context("Failed to run cargo bench")?;
+    if !status.success() {
+        anyhow::bail!("Benchmarks failed");
+    }
+
+    println!();
+    println!("{}", format!("✓ Saved baseline '{name}'").green());
+    println!(
+        "   {}",
+        format!("Compare with: cargo run -p xtask -- bench diff <older> {name}").dimmed()
+    );
+    println!();
+    Ok(())
+}
+
+fn head_hash() -> Result<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        anyhow::bail!("git rev-parse failed");
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn workspace_root() -> Result<PathBuf> {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .context("xtask has no parent directory")
+}
+
+/// Mean time per benchmark id (`group/function/param`) for one baseline.
+///
+/// Criterion lays results out as `<root>/<group>/<function>/<param>/<baseline>/estimates.json`.
+fn read_baseline(root: &Path, baseline: &str) -> Result<BTreeMap<String, f64>> {
+    let mut out = BTreeMap::new();
+    for entry in walkdir::WalkDir::new(root) {
+        let entry = entry?;
+        let path = entry.path();
+        if path.file_name() != Some(OsStr::new("estimates.json")) {
+            continue;
+        }
+        let Some(dir) = path.parent() else { continue };
+        if dir.file_name() != Some(OsStr::new(baseline)) {
+            continue;
+        }
+        let Some(id) = dir
+            .parent()
+            .and_then(|p| p.strip_prefix(root).ok())
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+        else {
+            continue;
+        };
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let json: serde_json::Value = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if let Some(mean) = json
+            .get("mean")
+            .and_then(|m| m.get("point_estimate"))
+            .and_then(serde_json::Value::as_f64)
+        {
+            out.insert(id, mean);
+        }
+    }
+    if out.is_empty() {
+        anyhow::bail!(
+            "No results for baseline '{baseline}' under {} — run `cargo run -p xtask -- bench run --name {baseline}` first",
+            root.display()
+        );
+    }
+    Ok(out)
+}
+
+/// Mean cycles per label from defmt lines like
+/// `decode flac: frames=256 mean=81234 min=80011 max=90400 cycles`.
+///
+/// A label that appears more than once keeps its last value.
+fn read_target_log(path: &Path) -> Result<BTreeMap<String, f64>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let out: BTreeMap<String, f64> = text.lines().filter_map(parse_target_line).collect();
+    if out.is_empty() {
+        anyhow::bail!("No `decode ...` lines in {}", path.display());
+    }
+    Ok(out)
+}
+
+fn parse_target_line(line: &str) -> Option<(String, f64)> {
+    let (_, rest) = line.split_once("decode ")?;
+    let (label, fields) = rest.split_once(':')?;
+    let mean = fields
+        .split_whitespace()
+        .find_map(|f| f.strip_prefix("mean="))?
+        .parse()
+        .ok()?;
+    Some((label.trim().to_string(), mean))
+}
+
+fn print_diff(
+    base_name: &str,
+    new_name: &str,
+    base: &BTreeMap<String, f64>,
+    new: &BTreeMap<String, f64>,
+    unit: &str,
+) {
+    println!();
+    println!(
+        "{}",
+        format!("Decode time per frame: {base_name} → {new_name} ({unit})")
+            .cyan()
+            .bold()
+    );
+    println!();
+    let width = base
+        .keys()
+        .chain(new.keys())
+        .map(String::len)
+        .max()
+        .unwrap_or(0);
+    for (id, old) in base {
+        let Some(cur) = new.get(id) else {
+            println!("  {id:<width$}  {old:>12.0}  {:>12}", "(removed)".dimmed());
+            continue;
+        };
+        let change = percent_change(*old, *cur);
+        let text = format!("{change:+.1}%");
+        let text = if change > 5.0 {
+            text.red()
+        } else if change < -5.0 {
+            text.green()
+        } else {
+            text.normal()
+        };
+        println!("  {id:<width$}  {old:>12.0}  {cur:>12.0}  {text}");
+    }
+    for (id, cur) in new.iter().filter(|(id, _)| !base.contains_key(*id)) {
+        println!("  {id:<width$}  {:>12}  {cur:>12.0}", "(new)".dimmed());
+    }
+    println!();
+}
+
+fn percent_change(old: f64, new: f64) -> f64 {
+    if old == 0.0 {
+        0.0
+    } else {
+        (new - old) / old * 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_defmt_line_with_prefix() {
+        let line = "0.123456 INFO  decode flac: frames=256 mean=81234 min=80011 max=90400 cycles";
+        assert_eq!(parse_target_line(line), Some(("flac".to_string(), 81234.0)));
+        assert_eq!(parse_target_line("INFO  boot complete"), None);
+    }
+
+    #[test]
+    fn reads_criterion_layout() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("flac/frame/stereo_16bit_order8/abc123");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("estimates.json"),
+            r#"{"mean":{"point_estimate":1500.5}}"#,
+        )
+        .unwrap();
+        let results = read_baseline(tmp.path(), "abc123").unwrap();
+        assert_eq!(results.get("flac/frame/stereo_16bit_order8"), Some(&1500.5));
+        assert!(read_baseline(tmp.path(), "missing").is_err());
+    }
+
+    #[test]
+    fn percent_change_handles_zero_base() {
+        assert_eq!(percent_change(0.0, 10.0), 0.0);
+        assert_eq!(percent_change(200.0, 100.0), -50.0);
+    }
+}
@@ -0,0 +1,75 @@
+//! `cargo xtask demo` — record the firmware-ui demo mode as an animated GIF.
+//!
+//! Steps one loop of `firmware_ui::demo::Demo` on a headless emulator, one
+//! frame per `FRAME_MS` of virtual time, and writes the frames as a GIF that
+//! loops forever — the same attract loop the `demo_mode` example shows in the
+//! emulator window, for booth screens, slides and the README.
+
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use eink_emulator::recording::GifRecorder;
+use firmware_ui::demo::{Demo, FRAME_MS};
+use firmware_ui::gallery;
+
+pub fn run(out: &Path) -> Result<()> {
+    if let Some(dir) = out.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+
+    println!();
+    println!("{}", "🎬 Recording demo loop...".cyan().bold());
+    println!();
+
+    let recorder = record(Demo::new());
+    recorder
+        .save(out)
+        .map_err(|e| anyhow!("Failed to write {}: {e}", out.display()))?;
+
+    println!(
+        "  {} {} ({} frames, {} s)",
+        "✓".green(),
+        out.display(),
+        recorder.len(),
+        recorder.duration_ms() / 1_000,
+    );
+    println!();
+    Ok(())
+}
+
+/// Render `demo` frame by frame until it wraps around once.
+fn record(mut demo: Demo) -> GifRecorder {
+    let mut display = gallery::emulator();
+    let mut recorder = GifRecorder::new();
+    for _ in 0..demo.loop_ms().div_ceil(FRAME_MS) {
+        let Ok(()) = demo.render(&mut display, |_, _, _, _| {});
+        recorder.capture(&display, FRAME_MS);
+        demo.advance(FRAME_MS);
+    }
+    recorder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_one_full_loop() {
+        let demo = Demo::new();
+        let loop_ms = demo.loop_ms();
+        let recorder = record(demo);
+        assert_eq!(recorder.duration_ms(), u64::from(loop_ms));
+        // Screens change and the progress bar moves between frames.
+        assert!(recorder.len() > 10);
+    }
+
+    #[test]
+    fn writes_a_gif() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let out = tmp.path().join("nested/demo.gif");
+        run(&out).unwrap();
+        assert!(std::fs::read(&out).unwrap().starts_with(b"GIF89a"));
+    }
+}
@@ -34,25 +34,52 @@
 //!    invokes cargo to rebuild the dylib. We still need a file watcher here
 //!    for the xtask level to watch for non-UI changes (firmware src, etc).
 //!
-//! 2. The render_ui() unsafe function in firmware-ui/src/lib.rs takes a
-//!    raw pointer. Adding versioned shared state via shared memory or
-//!    message passing would make the hot-reload boundary type-safe.
+//! 2. The hot-reload exports are declared with hot_reload_abi! in
+//!    firmware-ui/src/lib.rs; the binary checks their signature hash when it
+//!    connects. Sharing UI state across reloads still needs a versioned
+//!    state type on the same boundary.
 //!
 //! 3. On Windows, the DLL is locked while loaded. hot-lib-reloader works
 //!    around this by loading a copy with a unique filename. This is handled
 //!    automatically by hot-lib-reloader 0.8.
+//!
+//! ## Test Watch (--watch-tests flag)
+//!
+//! Alongside either mode, --watch-tests re-runs the golden/UI tests affected
+//! by each change on a background thread (see `affected_test_packages`).
+//! Tests build into target/dev-tests so they never wait on the emulator
+//! build's cargo lock.  Results are written to target/dev/test-status and
+//! the emulator is pointed at that file through EINK_TEST_STATUS; with the
+//! debug feature it shows the failing tests in the debug panel's Display
+//! tab.  In --hot-reload mode (no debug feature) the results are printed here
+//! only.
 
 use anyhow::{Context, Result};
 use colored::Colorize;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use platform::config;
+use std::collections::BTreeSet;
 use std::io::{self, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command};
-use std::sync::mpsc::channel;
+use std::sync::mpsc::{channel, Receiver, Sender};
 use std::time::{Duration, Instant};
 
-pub fn run(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Result<()> {
+/// Packages whose tests render UI and compare against golden images.
+const UI_TEST_PACKAGES: &[&str] = &["eink-testing", "firmware-ui"];
+
+/// Status file read by the emulator (via `EINK_TEST_STATUS`).
+const TEST_STATUS_PATH: &str = "target/dev/test-status";
+
+/// Separate target dir so test builds don't block on the emulator's cargo lock.
+const TEST_TARGET_DIR: &str = "target/dev-tests";
+
+pub fn run(
+    headless: bool,
+    hot_reload: bool,
+    watch_tests: bool,
+    music_path: Option<&Path>,
+) -> Result<()> {
     clear_screen();
     print_banner();
 
@@ -73,18 +100,35 @@ pub fn run(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Resul
             "Watching: firmware, platform, eink-components, eink-system".dimmed()
         );
     }
+    if watch_tests {
+        println!(
+            "{}",
+            "Test watch: affected golden/UI tests re-run on change".dimmed()
+        );
+    }
     println!();
 
-    // Initial build and run
-    let mut emulator_process = match start_emulator(headless, hot_reload, music_path) {
-        Ok(process) => process,
-        Err(e) => {
-            eprintln!("{}", format!("Build failed: {}", e).red().bold());
-            eprintln!("{}", "Fix errors and save to trigger rebuild".dimmed());
-            println!();
-            None
-        }
+    let test_status = if watch_tests {
+        Some(std::env::current_dir()?.join(TEST_STATUS_PATH))
+    } else {
+        None
     };
+    let test_runner = test_status.clone().map(spawn_test_runner).transpose()?;
+    if let Some(runner) = &test_runner {
+        let _ = runner.send(UI_TEST_PACKAGES.iter().copied().collect());
+    }
+
+    // Initial build and run
+    let mut emulator_process =
+        match start_emulator(headless, hot_reload, music_path, test_status.as_deref()) {
+            Ok(process) => process,
+            Err(e) => {
+                eprintln!("{}", format!("Build failed: {}", e).red().bold());
+                eprintln!("{}", "Fix errors and save to trigger rebuild".dimmed());
+                println!();
+                None
+            }
+        };
 
     // Set up file watcher
     let (tx, rx) = channel();
@@ -92,14 +136,19 @@ pub fn run(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Resul
         move |res: Result<Event, notify::Error>| {
             if let Ok(event) = res {
                 // Only trigger on modify and create events for Rust files
-                if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_))
-                    && event.paths.iter().any(|p| {
-                        p.extension()
-                            .map(|ext| ext == "rs" || ext == "toml")
-                            .unwrap_or(false)
-                    })
-                {
-                    let _ = tx.send(());
+                if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                    let changed: Vec<PathBuf> = event
+                        .paths
+                        .into_iter()
+                        .filter(|p| {
+                            p.extension()
+                                .map(|ext| ext == "rs" || ext == "toml")
+                                .unwrap_or(false)
+                        })
+                        .collect();
+                    if !changed.is_empty() {
+                        let _ = tx.send(changed);
+                    }
                 }
             }
         },
@@ -107,7 +156,7 @@ pub fn run(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Resul
     )?;
 
     // Watch firmware source directory, examples, and Cargo.toml
-    let watch_paths = vec![
+    let mut watch_paths = vec![
         Path::new("crates/firmware/src"),
         Path::new("crates/firmware/examples"), // Watch examples for hot-reload
         Path::new("crates/firmware/Cargo.toml"),
@@ -116,6 +165,14 @@ pub fn run(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Resul
         Path::new("crates/eink/eink-system/src"),     // Watch layout system
         Path::new("crates/eink/eink-emulator/src"),   // Watch emulator rendering
     ];
+    if watch_tests {
+        watch_paths.extend([
+            Path::new("crates/firmware-ui/src"),
+            Path::new("crates/firmware-ui/tests"),
+            Path::new("crates/eink/eink-testing/src"),
+            Path::new("crates/eink/eink-testing/tests"),
+        ]);
+    }
 
     for path in &watch_paths {
         if path.exists() {
@@ -143,7 +200,7 @@ pub fn run(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Resul
 
     loop {
         match rx.recv_timeout(Duration::from_millis(100)) {
-            Ok(_) => {
+            Ok(mut changed) => {
                 // Debounce - wait a bit for multiple file events
                 let elapsed = last_rebuild.elapsed();
                 if elapsed < Duration::from_millis(500) {
@@ -153,10 +210,23 @@ pub fn run(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Resul
                 std::thread::sleep(Duration::from_millis(200));
 
                 // Drain any pending events
-                while rx.try_recv().is_ok() {}
+                while let Ok(more) = rx.try_recv() {
+                    changed.extend(more);
+                }
 
                 last_rebuild = Instant::now();
 
+                // Tests run in parallel with the emulator rebuild below.
+                if let Some(runner) = &test_runner {
+                    let packages: BTreeSet<&'static str> = changed
+                        .iter()
+                        .flat_map(|p| affected_test_packages(p).iter().copied())
+                        .collect();
+                    if !packages.is_empty() {
+                        let _ = runner.send(packages);
+                    }
+                }
+
                 println!();
                 println!("{}", "Changes detected - rebuilding...".yellow().bold());
                 println!();
@@ -172,7 +242,7 @@ pub fn run(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Resul
                 print_banner();
 
                 // Rebuild and restart
-                match start_emulator(headless, hot_reload, music_path) {
+                match start_emulator(headless, hot_reload, music_path, test_status.as_deref()) {
                     Ok(new_process) => {
                         emulator_process = new_process;
                         println!();
@@ -233,7 +303,12 @@ pub fn run(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Resul
     Ok(())
 }
 
-fn start_emulator(headless: bool, hot_reload: bool, music_path: Option<&Path>) -> Result<Option<Child>> {
+fn start_emulator(
+    headless: bool,
+    hot_reload: bool,
+    music_path: Option<&Path>,
+    test_status: Option<&Path>,
+) -> Result<Option<Child>> {
     let start = Instant::now();
 
     println!();
@@ -290,6 +365,10 @@ fn start_emulator(headless: bool, hot_reload: bool, music_path: Option<&Path>) -
         cmd.env("MUSIC_PATH", mp);
     }
 
+    if let Some(path) = test_status {
+        cmd.env("EINK_TEST_STATUS", path);
+    }
+
     if headless {
         println!("{}", "Running in headless mode (no window)".dimmed());
         let status = cmd.status().context("Failed to run cargo")?;
@@ -321,6 +400,135 @@ fn start_emulator(headless: bool, hot_reload: bool, music_path: Option<&Path>) -
     Ok(Some(child))
 }
 
+/// Packages whose tests cover a changed file.
+///
+/// Component and layout changes can move pixels in any golden image, so they
+/// re-run every UI test package as well as their own crate's tests.  Files
+/// outside the UI stack (firmware, platform) trigger no tests.
+fn affected_test_packages(path: &Path) -> &'static [&'static str] {
+    let in_crate = |name: &str| path.components().any(|c| c.as_os_str() == name);
+    if in_crate("firmware-ui") {
+        &["firmware-ui"]
+    } else if in_crate("eink-testing") {
+        &["eink-testing"]
+    } else if in_crate("eink-components") {
+        &["eink-components", "eink-testing", "firmware-ui"]
+    } else if in_crate("eink-system") {
+        &["eink-system", "eink-testing", "firmware-ui"]
+    } else if in_crate("eink-emulator") {
+        UI_TEST_PACKAGES
+    } else {
+        &[]
+    }
+}
+
+/// Start the background test thread; send it the packages to test.
+///
+/// Requests that queue up while a run is in progress are merged into one run.
+fn spawn_test_runner(status_path: PathBuf) -> Result<Sender<BTreeSet<&'static str>>> {
+    if let Some(dir) = status_path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let (tx, rx) = channel();
+    std::thread::Builder::new()
+        .name("dev-tests".into())
+        .spawn(move || run_tests_forever(&rx, &status_path))
+        .context("Failed to spawn test runner thread")?;
+    Ok(tx)
+}
+
+fn run_tests_forever(rx: &Receiver<BTreeSet<&'static str>>, status_path: &Path) {
+    let mut passed = 0;
+    while let Ok(mut packages) = rx.recv() {
+        while let Ok(more) = rx.try_recv() {
+            packages.extend(more);
+        }
+        let scope = packages.iter().copied().collect::<Vec<_>>().join(" ");
+
+        // Keep the previous count visible while the new run builds.
+        write_test_status(status_path, true, &scope, passed, &[]);
+
+        let mut cmd = Command::new("cargo");
+        cmd.arg("test").env("CARGO_TARGET_DIR", TEST_TARGET_DIR);
+        for package in &packages {
+            cmd.arg("-p").arg(package);
+        }
+        let (run_passed, failed) = match cmd.output() {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let (run_passed, mut failed) = parse_test_output(&stdout);
+                if !output.status.success() && failed.is_empty() {
+                    // Compile error: no test ran, so no names to report.
+                    failed.push("(build failed - see cargo test output)".to_string());
+                }
+                (run_passed, failed)
+            }
+            Err(e) => (0, vec![format!("(cargo test did not start: {e})")]),
+        };
+        passed = run_passed;
+        write_test_status(status_path, false, &scope, passed, &failed);
+
+        if failed.is_empty() {
+            println!("{}", format!("Tests passed: {passed} ({scope})").green());
+        } else {
+            println!(
+                "{}",
+                format!("Tests failed: {} ({scope})", failed.len())
+                    .red()
+                    .bold()
+            );
+            for name in &failed {
+                println!("{}", format!("  {name}").red());
+            }
+        }
+    }
+}
+
+/// Total passed count and failing test names from libtest's output.
+fn parse_test_output(stdout: &str) -> (u32, Vec<String>) {
+    let mut passed = 0u32;
+    let mut failed = Vec::new();
+    for line in stdout.lines() {
+        if let Some(name) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.strip_suffix(" ... FAILED"))
+        {
+            failed.push(name.to_string());
+        } else if let Some(summary) = line.strip_prefix("test result: ") {
+            // "ok. 3 passed; 0 failed; ..." -- drop the status word first.
+            let counts = summary.split_once(". ").map_or(summary, |(_, c)| c);
+            let count = counts
+                .split(';')
+                .filter_map(|part| part.trim().split_once(' '))
+                .find(|(_, label)| *label == "passed")
+                .and_then(|(n, _)| n.parse::<u32>().ok());
+            passed = passed.saturating_add(count.unwrap_or(0));
+        }
+    }
+    (passed, failed)
+}
+
+/// Write the status file in the `key value` format the emulator parses.
+///
+/// Written to a temporary file and renamed so the emulator never reads a
+/// half-written report.
+fn write_test_status(path: &Path, running: bool, scope: &str, passed: u32, failed: &[String]) {
+    let mut text = format!(
+        "state {}\nscope {scope}\npassed {passed}\n",
+        if running { "running" } else { "done" }
+    );
+    for name in failed {
+        text.push_str("failed ");
+        text.push_str(name);
+        text.push('\n');
+    }
+    let tmp = path.with_extension("tmp");
+    if let Err(e) = std::fs::write(&tmp, text).and_then(|()| std::fs::rename(&tmp, path)) {
+        eprintln!("Failed to write test status: {}", e);
+    }
+}
+
 fn clear_screen() {
     // ANSI escape code to clear screen and move cursor to top-left
     print!("\x1B[2J\x1B[1;1H");
@@ -337,6 +545,8 @@ fn print_banner() {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     /// Smoke test: verifies the PathBuf → &Path conversion used at the call site
     /// in main.rs (`music_path.as_deref()`). Does NOT test cmd.env() forwarding
     /// (that logic runs in start_emulator which requires a live cargo process).
@@ -347,4 +557,40 @@ mod tests {
         let as_path_ref: Option<&std::path::Path> = path.as_deref();
         assert_eq!(as_path_ref.unwrap().to_str().unwrap(), "/tmp/music");
     }
+
+    #[test]
+    fn affected_packages_follow_the_ui_stack() {
+        let root = Path::new("/work/soul-listener/crates");
+        assert_eq!(
+            affected_test_packages(&root.join("firmware-ui/src/render.rs")),
+            ["firmware-ui"]
+        );
+        assert_eq!(
+            affected_test_packages(&root.join("eink/eink-components/src/button.rs")),
+            ["eink-components", "eink-testing", "firmware-ui"]
+        );
+        assert_eq!(
+            affected_test_packages(&root.join("eink/eink-emulator/src/lib.rs")),
+            UI_TEST_PACKAGES
+        );
+        assert!(affected_test_packages(&root.join("platform/src/lib.rs")).is_empty());
+    }
+
+    #[test]
+    fn parse_test_output_sums_passes_and_collects_failures() {
+        let stdout = "\
+running 2 tests
+test golden::now_playing ... FAILED
+test golden::menu ... ok
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out
+
+running 3 tests
+test layout::stack ... ok
+test result: ok. 3 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
+";
+        let (passed, failed) = parse_test_output(stdout);
+        assert_eq!(passed, 4);
+        assert_eq!(failed, ["golden::now_playing"]);
+    }
 }
@@ -1,9 +1,12 @@
 use anyhow::{Context, Result};
 use colored::Colorize;
-use std::process::Command;
-use std::time::Instant;
+use platform::smoke::SmokeMarker;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::time::{Duration, Instant};
 
-pub fn run(release: bool) -> Result<()> {
+pub fn run(release: bool, smoke: bool, smoke_timeout_secs: u64) -> Result<()> {
     let mode = if release { "release" } else { "debug" };
 
     println!();
@@ -71,6 +74,12 @@ pub fn run(release: bool) -> Result<()> {
         .arg("--probe-index")
         .arg("0");
 
+    if smoke {
+        // `probe-rs run` flashes, resets and then streams decoded defmt
+        // output, so the markers from this boot cannot be missed.
+        return run_smoke_test(flash_cmd, Duration::from_secs(smoke_timeout_secs));
+    }
+
     let flash_output = flash_cmd
         .output()
         .context("Failed to run probe-rs. Is probe-rs installed? (cargo install probe-rs-tools)")?;
@@ -98,6 +107,129 @@ pub fn run(release: bool) -> Result<()> {
     Ok(())
 }
 
+/// Flash via `flash_cmd` and wait for the boot markers on its RTT output.
+fn run_smoke_test(mut flash_cmd: Command, timeout: Duration) -> Result<()> {
+    let start = Instant::now();
+    let mut child = flash_cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit()) // flashing progress
+        .spawn()
+        .context("Failed to run probe-rs. Is probe-rs installed? (cargo install probe-rs-tools)")?;
+    let stdout = child
+        .stdout
+        .take()
+        .context("probe-rs stdout was not captured")?;
+
+    let (tx, rx) = channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    println!(
+        "{}",
+        format!(
+            "🔎 Smoke test: waiting up to {}s for boot markers...",
+            timeout.as_secs()
+        )
+        .cyan()
+        .bold()
+    );
+    let mut progress = SmokeProgress::default();
+    while !progress.is_finished() {
+        let Some(remaining) = timeout.checked_sub(start.elapsed()) else {
+            break;
+        };
+        match rx.recv_timeout(remaining) {
+            Ok(line) => {
+                if let Some(marker) = progress.observe(&line) {
+                    println!(
+                        "   {} {} ({:.1}s)",
+                        "✓".green(),
+                        marker.label(),
+                        start.elapsed().as_secs_f64()
+                    );
+                }
+            }
+            // Timeout, or probe-rs exited (flash failure, probe unplugged).
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+
+    println!();
+    println!("{}", "📋 Smoke test summary:".cyan().bold());
+    for (marker, seen) in progress.results() {
+        let status = match (seen, marker.required()) {
+            (true, _) => "PASS".green(),
+            (false, true) => "FAIL".red().bold(),
+            (false, false) => "SKIP (not required yet)".dimmed(),
+        };
+        println!("   {:<18} {}", marker.label(), status);
+    }
+    if let Some(panic) = &progress.panic {
+        println!("   {}", format!("Panic: {panic}").red());
+    }
+    println!();
+
+    if progress.passed() {
+        println!("{}", "✓ Smoke test passed".green().bold());
+        Ok(())
+    } else {
+        eprintln!("{}", "✗ Smoke test failed".red().bold());
+        anyhow::bail!("Smoke test failed - the flashed firmware did not reach all boot milestones")
+    }
+}
+
+/// Boot markers seen so far on the RTT stream.
+#[derive(Debug, Default)]
+struct SmokeProgress {
+    seen: [bool; SmokeMarker::ALL.len()],
+    /// First panic line, if the firmware panicked.
+    panic: Option<String>,
+}
+
+impl SmokeProgress {
+    /// Record one decoded RTT line; returns a marker seen for the first time.
+    fn observe(&mut self, line: &str) -> Option<SmokeMarker> {
+        if self.panic.is_none() && line.contains("panicked") {
+            self.panic = Some(line.trim().to_string());
+        }
+        let marker = SmokeMarker::find(line)?;
+        let index = SmokeMarker::ALL.iter().position(|m| *m == marker)?;
+        let seen = self.seen.get_mut(index)?;
+        if *seen {
+            return None;
+        }
+        *seen = true;
+        Some(marker)
+    }
+
+    fn results(&self) -> impl Iterator<Item = (SmokeMarker, bool)> + '_ {
+        SmokeMarker::ALL.into_iter().zip(self.seen.iter().copied())
+    }
+
+    /// All required markers arrived and nothing panicked.
+    fn passed(&self) -> bool {
+        self.panic.is_none()
+            && self
+                .results()
+                .all(|(marker, seen)| seen || !marker.required())
+    }
+
+    /// No reason to keep waiting: passed outright, or a panic was seen.
+    ///
+    /// Optional markers are not waited for once the required ones arrived.
+    fn is_finished(&self) -> bool {
+        self.passed() || self.panic.is_some()
+    }
+}
+
 fn show_binary_size(release: bool) -> Result<()> {
     let binary_path = if release {
         "target/thumbv7em-none-eabihf/release/firmware"
@@ -145,3 +277,35 @@ fn show_binary_size(release: bool) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smoke_progress_passes_once_required_markers_arrive() {
+        let mut progress = SmokeProgress::default();
+        assert_eq!(
+            progress.observe("0.000 INFO  SMOKE boot-ok"),
+            Some(SmokeMarker::Boot)
+        );
+        // Repeats are not reported twice.
+        assert_eq!(progress.observe("0.001 INFO  SMOKE boot-ok"), None);
+        progress.observe("0.020 INFO  SMOKE display-ok");
+        assert!(!progress.passed());
+        progress.observe("3.010 INFO  SMOKE audio-alive");
+        assert!(progress.passed());
+        assert!(progress.is_finished());
+    }
+
+    #[test]
+    fn smoke_progress_fails_on_panic() {
+        let mut progress = SmokeProgress::default();
+        for marker in SmokeMarker::ALL {
+            progress.observe(marker.tag());
+        }
+        progress.observe("ERROR panicked at 'FRAMEBUFFER not in AXI SRAM'");
+        assert!(!progress.passed());
+        assert!(progress.is_finished());
+    }
+}
@@ -0,0 +1,88 @@
+//! `cargo run -p xtask -- fuzz` — short smoke run of the cargo-fuzz targets.
+//!
+//! Each target runs from its committed seeds in `fuzz/corpus/<target>` for a
+//! few seconds.  New inputs go to `target/fuzz-corpus/<target>` so the seed
+//! set only changes when someone copies a finding in deliberately.  Crashes
+//! land in `fuzz/artifacts/<target>` as usual.
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Every target in `fuzz/Cargo.toml`.
+pub(crate) const TARGETS: &[&str] = &["id3", "cue", "mp4_chapters", "soul_binary"];
+
+pub fn run(target: Option<&str>, seconds: u64) -> Result<()> {
+    let root = workspace_root()?;
+    let targets: Vec<&str> = match target {
+        Some(t) if TARGETS.contains(&t) => vec![t],
+        Some(t) => bail!("unknown fuzz target `{t}` (expected one of {TARGETS:?})"),
+        None => TARGETS.to_vec(),
+    };
+
+    for name in targets {
+        println!("{} {name} for {seconds}s", "Fuzzing".green().bold());
+        let scratch = root.join("target").join("fuzz-corpus").join(name);
+        std::fs::create_dir_all(&scratch)
+            .with_context(|| format!("creating {}", scratch.display()))?;
+        let status = Command::new("cargo")
+            .current_dir(&root)
+            .args(["+nightly", "fuzz", "run", name])
+            .arg(&scratch)
+            .arg(seed_dir(&root, name))
+            .arg("--")
+            .arg(format!("-max_total_time={seconds}"))
+            .status()
+            .context("running cargo fuzz (install with `cargo install cargo-fuzz`)")?;
+        if !status.success() {
+            bail!("fuzz target `{name}` failed; see fuzz/artifacts/{name}");
+        }
+    }
+    println!("{}", "All fuzz targets ran clean".green().bold());
+    Ok(())
+}
+
+fn seed_dir(root: &Path, target: &str) -> PathBuf {
+    root.join("fuzz").join("corpus").join(target)
+}
+
+fn workspace_root() -> Result<PathBuf> {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .map(Path::to_path_buf)
+        .context("xtask has no parent directory")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_target_has_a_source_and_seeds() {
+        let root = workspace_root().unwrap();
+        let manifest = std::fs::read_to_string(root.join("fuzz/Cargo.toml")).unwrap();
+        for target in TARGETS {
+            assert!(
+                manifest.contains(&format!("name = \"{target}\"")),
+                "{target} missing from fuzz/Cargo.toml"
+            );
+            let source = root.join("fuzz/fuzz_targets").join(format!("{target}.rs"));
+            assert!(source.is_file(), "{} missing", source.display());
+            let seeds = std::fs::read_dir(seed_dir(&root, target)).unwrap().count();
+            assert!(seeds > 0, "{target} has no seed inputs");
+        }
+    }
+
+    #[test]
+    fn manifest_lists_no_unknown_targets() {
+        let root = workspace_root().unwrap();
+        let manifest = std::fs::read_to_string(root.join("fuzz/Cargo.toml")).unwrap();
+        let bins = manifest.matches("[[bin]]").count();
+        assert_eq!(
+            bins,
+            TARGETS.len(),
+            "TARGETS out of step with fuzz/Cargo.toml"
+        );
+    }
+}
@@ -0,0 +1,500 @@
+//! xtask hil-test - Hardware-in-the-loop test runner
+//!
+//! Builds and flashes the `hil_test` firmware example, then drives it
+//! through its command mailbox (protocol in `platform::hil`) and asserts the
+//! responses on the host:
+//!
+//! 1. Build `examples/hil_test.rs` for thumbv7em-none-eabihf
+//! 2. `probe-rs download` + `probe-rs reset`
+//! 3. Look up the `HIL_MAILBOX` address in the ELF (`rust-nm`)
+//! 4. For each scenario: `probe-rs write` the command, poll `probe-rs read`
+//!    until the firmware acknowledges, check the result
+//!
+//! Rendered-screen checksums are compared against
+//! crates/firmware/tests/hil/checksums.txt (`<name> <hex>` per line).
+//! Run with --bless to record the current panel output as the baseline.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use platform::audio_loopback::LoopbackFault;
+use platform::hil::{
+    offset, HilCommand, HilResponse, HilScreen, HilStatus, MAILBOX_MAGIC, MAILBOX_SYMBOL,
+};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const CHIP: &str = "STM32H743ZITx";
+const TARGET: &str = "thumbv7em-none-eabihf";
+const BASELINE_PATH: &str = "crates/firmware/tests/hil/checksums.txt";
+
+/// How long the firmware may take to boot and mark the mailbox ready.
+const READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Upper bound for one command; a GC16 full refresh takes ~1.5 s.
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One host-side assertion against the firmware.
+struct Scenario {
+    name: &'static str,
+    command: HilCommand,
+    expect: Expect,
+}
+
+enum Expect {
+    /// Status OK; result words are informational.
+    Ok,
+    /// Status OK and `result[0]` matches the baseline checksum named `name`.
+    Checksum,
+    /// Status OK and `result[0]`, a fault bitmask, is zero.
+    NoFaults,
+}
+
+const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        name: "ping",
+        command: HilCommand::Ping,
+        expect: Expect::Ok,
+    },
+    Scenario {
+        name: "render:splash",
+        command: HilCommand::RenderScreen(HilScreen::Splash),
+        expect: Expect::Checksum,
+    },
+    Scenario {
+        name: "render:test-pattern",
+        command: HilCommand::RenderScreen(HilScreen::TestPattern),
+        expect: Expect::Checksum,
+    },
+    Scenario {
+        name: "audio:sine-1khz",
+        command: HilCommand::PlaySine {
+            freq_hz: 1_000,
+            duration_ms: 500,
+        },
+        expect: Expect::Ok,
+    },
+    Scenario {
+        name: "audio:loopback",
+        command: HilCommand::AudioLoopback,
+        expect: Expect::NoFaults,
+    },
+];
+
+/// Word-level access to target memory.
+trait Link {
+    fn read(&mut self, addr: u32, words: u32) -> Result<Vec<u32>>;
+    fn write(&mut self, addr: u32, words: &[u32]) -> Result<()>;
+}
+
+/// Memory access through the probe-rs CLI; each call attaches without reset.
+struct ProbeRs;
+
+impl Link for ProbeRs {
+    fn read(&mut self, addr: u32, words: u32) -> Result<Vec<u32>> {
+        let output = Command::new("probe-rs")
+            .args(["read", "--chip", CHIP, "b32"])
+            .arg(format!("{addr:#010x}"))
+            .arg(words.to_string())
+            .output()
+            .context("Failed to run probe-rs read")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "probe-rs read failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        parse_read_output(&String::from_utf8_lossy(&output.stdout), words)
+    }
+
+    fn write(&mut self, addr: u32, words: &[u32]) -> Result<()> {
+        let status = Command::new("probe-rs")
+            .args(["write", "--chip", CHIP, "b32"])
+            .arg(format!("{addr:#010x}"))
+            .args(words.iter().map(|w| format!("{w:#x}")))
+            .status()
+            .context("Failed to run probe-rs write")?;
+        if !status.success() {
+            anyhow::bail!("probe-rs write failed");
+        }
+        Ok(())
+    }
+}
+
+pub fn run(release: bool, bless: bool) -> Result<()> {
+    let mode = if release { "release" } else { "debug" };
+    let elf = format!("target/{TARGET}/{mode}/examples/hil_test");
+
+    println!();
+    println!(
+        "{}",
+        format!("🔨 Building HIL firmware ({mode} mode)...")
+            .cyan()
+            .bold()
+    );
+    let mut build = Command::new("cargo");
+    build.args([
+        "build",
+        "-p",
+        "firmware",
+        "--example",
+        "hil_test",
+        "--target",
+        TARGET,
+        "--features",
+        "hardware",
+    ]);
+    if release {
+        build.arg("--release");
+    }
+    if !build
+        .status()
+        .context("Failed to run cargo build")?
+        .success()
+    {
+        anyhow::bail!("HIL firmware build failed");
+    }
+
+    let mailbox = find_symbol(Path::new(&elf), MAILBOX_SYMBOL)?;
+    println!(
+        "   {}",
+        format!("{MAILBOX_SYMBOL} at {mailbox:#010x}").dimmed()
+    );
+
+    println!("{}", "📡 Flashing HIL firmware...".cyan().bold());
+    for args in [
+        vec!["download", "--chip", CHIP, elf.as_str()],
+        vec!["reset", "--chip", CHIP],
+    ] {
+        let status = Command::new("probe-rs").args(&args).status().context(
+            "Failed to run probe-rs. Is probe-rs installed? (cargo install probe-rs-tools)",
+        )?;
+        if !status.success() {
+            anyhow::bail!("probe-rs {} failed", args.first().unwrap_or(&""));
+        }
+    }
+
+    let baseline_path = Path::new(BASELINE_PATH);
+    let baseline_text = std::fs::read_to_string(baseline_path).unwrap_or_default();
+    let baseline: BTreeMap<&str, u32> = playback::test_vectors::parse_manifest(&baseline_text)
+        .into_iter()
+        .collect();
+
+    let mut link = ProbeRs;
+    wait_ready(&mut link, mailbox)?;
+    println!("{}", "✓ Firmware ready".green());
+    println!();
+
+    let report = run_scenarios(&mut link, mailbox, &baseline)?;
+    report.print();
+
+    if bless {
+        write_baseline(baseline_path, &report)?;
+    }
+
+    if report.failed() > 0 && !bless {
+        anyhow::bail!("{} HIL scenario(s) failed", report.failed());
+    }
+    Ok(())
+}
+
+/// Address of `symbol` from `rust-nm` (cargo-binutils) or `arm-none-eabi-nm`.
+fn find_symbol(elf: &Path, symbol: &str) -> Result<u32> {
+    for tool in ["rust-nm", "arm-none-eabi-nm"] {
+        let Ok(output) = Command::new(tool).arg(elf).output() else {
+            continue;
+        };
+        if output.status.success() {
+            return parse_nm_symbol(&String::from_utf8_lossy(&output.stdout), symbol)
+                .with_context(|| format!("{symbol} not found in {}", elf.display()));
+        }
+    }
+    anyhow::bail!(
+        "Neither rust-nm nor arm-none-eabi-nm is available (cargo install cargo-binutils)"
+    )
+}
+
+fn parse_nm_symbol(nm_output: &str, symbol: &str) -> Option<u32> {
+    nm_output.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let (addr, _kind, name) = (parts.next()?, parts.next()?, parts.next()?);
+        if name != symbol {
+            return None;
+        }
+        u32::from_str_radix(addr, 16).ok()
+    })
+}
+
+/// Parse `probe-rs read` output: hex words separated by whitespace.
+fn parse_read_output(stdout: &str, words: u32) -> Result<Vec<u32>> {
+    let values = stdout
+        .split_whitespace()
+        .map(|w| u32::from_str_radix(w.trim_start_matches("0x"), 16))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Unexpected probe-rs read output: {stdout:?}"))?;
+    if values.len() != words as usize {
+        anyhow::bail!(
+            "probe-rs read returned {} words, expected {words}",
+            values.len()
+        );
+    }
+    Ok(values)
+}
+
+/// Address of the mailbox field at byte `offset`.
+fn field(mailbox: u32, offset: u32) -> u32 {
+    mailbox.saturating_add(offset)
+}
+
+fn wait_ready(link: &mut impl Link, mailbox: u32) -> Result<()> {
+    let start = Instant::now();
+    loop {
+        // The probe may not attach while the target is still resetting.
+        if let Ok(words) = link.read(field(mailbox, offset::MAGIC), 1) {
+            if words.first() == Some(&MAILBOX_MAGIC) {
+                return Ok(());
+            }
+        }
+        if start.elapsed() > READY_TIMEOUT {
+            anyhow::bail!("HIL firmware did not become ready within {READY_TIMEOUT:?}");
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+}
+
+/// Post `command` and wait for the firmware's response.
+fn send(link: &mut impl Link, mailbox: u32, seq: u32, command: HilCommand) -> Result<HilResponse> {
+    // Command words first, seq last: the firmware treats a new seq as "go".
+    link.write(field(mailbox, offset::COMMAND), &command.encode())?;
+    link.write(field(mailbox, offset::SEQ), &[seq])?;
+
+    let start = Instant::now();
+    loop {
+        let words = link.read(field(mailbox, offset::ACK), 4)?;
+        let words: [u32; 4] = words
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("short mailbox read"))?;
+        if let Some(response) = HilResponse::from_words(seq, words) {
+            return Ok(response);
+        }
+        if start.elapsed() > COMMAND_TIMEOUT {
+            anyhow::bail!("No response to {command:?} within {COMMAND_TIMEOUT:?}");
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    Fail(String),
+    Skip(String),
+}
+
+struct ScenarioResult {
+    name: &'static str,
+    response: HilResponse,
+    outcome: Outcome,
+}
+
+struct Report {
+    results: Vec<ScenarioResult>,
+}
+
+impl Report {
+    fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| matches!(r.outcome, Outcome::Fail(_)))
+            .count()
+    }
+
+    fn print(&self) {
+        println!("{}", "📋 HIL results:".cyan().bold());
+        for r in &self.results {
+            let outcome = match &r.outcome {
+                Outcome::Pass => "PASS".green(),
+                Outcome::Fail(why) => format!("FAIL  {why}").red().bold(),
+                Outcome::Skip(why) => format!("SKIP  {why}").dimmed(),
+            };
+            println!(
+                "   {:<22} {outcome}  {}",
+                r.name,
+                format!("[{:#010x}, {}]", r.response.result[0], r.response.result[1]).dimmed()
+            );
+        }
+        println!();
+    }
+}
+
+fn run_scenarios(
+    link: &mut impl Link,
+    mailbox: u32,
+    baseline: &BTreeMap<&str, u32>,
+) -> Result<Report> {
+    // Continue after whatever sequence number the firmware last acknowledged.
+    let mut seq = link
+        .read(field(mailbox, offset::ACK), 1)?
+        .first()
+        .copied()
+        .unwrap_or(0);
+    let mut results = Vec::new();
+    for scenario in SCENARIOS {
+        seq = seq.wrapping_add(1).max(1);
+        let response = send(link, mailbox, seq, scenario.command)?;
+        let outcome = judge(scenario, &response, baseline);
+        results.push(ScenarioResult {
+            name: scenario.name,
+            response,
+            outcome,
+        });
+    }
+    Ok(Report { results })
+}
+
+fn judge(scenario: &Scenario, response: &HilResponse, baseline: &BTreeMap<&str, u32>) -> Outcome {
+    match response.status {
+        HilStatus::Ok => {}
+        HilStatus::Unsupported => return Outcome::Skip("not supported by this firmware".into()),
+        status => return Outcome::Fail(format!("status {status:?}")),
+    }
+    match scenario.expect {
+        Expect::Ok => Outcome::Pass,
+        Expect::Checksum => match baseline.get(scenario.name) {
+            Some(&expected) if expected == response.result[0] => Outcome::Pass,
+            Some(&expected) => Outcome::Fail(format!(
+                "checksum {:08x}, baseline {expected:08x}",
+                response.result[0]
+            )),
+            None => Outcome::Skip("no baseline (run with --bless)".into()),
+        },
+        Expect::NoFaults => match response.result[0] {
+            0 => Outcome::Pass,
+            mask => {
+                let faults: Vec<_> = LoopbackFault::ALL
+                    .into_iter()
+                    .filter(|f| mask & f.bit() != 0)
+                    .map(LoopbackFault::name)
+                    .collect();
+                Outcome::Fail(faults.join(", "))
+            }
+        },
+    }
+}
+
+fn write_baseline(path: &Path, report: &Report) -> Result<()> {
+    let mut text =
+        String::from("# <scenario> <framebuffer FNV-1a, hex> (cargo xtask hil-test --bless)\n");
+    for r in &report.results {
+        let is_checksum = SCENARIOS
+            .iter()
+            .any(|s| s.name == r.name && matches!(s.expect, Expect::Checksum));
+        if is_checksum && r.response.status == HilStatus::Ok {
+            text.push_str(&format!("{:<22} {:08x}\n", r.name, r.response.result[0]));
+        }
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    println!(
+        "{}",
+        format!("✓ Baseline written to {}", path.display()).green()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+
+    use super::*;
+    use platform::hil::{self, HilMailbox};
+
+    /// In-memory target: answers each command the moment seq is written.
+    struct FakeTarget {
+        words: [u32; hil::MAILBOX_WORDS as usize],
+        checksum: u32,
+        loopback_faults: u32,
+    }
+
+    impl Link for FakeTarget {
+        fn read(&mut self, addr: u32, words: u32) -> Result<Vec<u32>> {
+            let start = (addr / 4) as usize;
+            Ok(self.words[start..start + words as usize].to_vec())
+        }
+
+        fn write(&mut self, addr: u32, words: &[u32]) -> Result<()> {
+            let start = (addr / 4) as usize;
+            self.words[start..start + words.len()].copy_from_slice(words);
+            if addr == offset::SEQ {
+                let command = HilCommand::decode([self.words[2], self.words[3], self.words[4]]);
+                let response = match command {
+                    Some(HilCommand::RenderScreen(_)) => HilResponse::ok([self.checksum, 1_400]),
+                    Some(HilCommand::PlaySine { .. }) => {
+                        HilResponse::status(HilStatus::Unsupported)
+                    }
+                    Some(HilCommand::AudioLoopback) => HilResponse::ok([self.loopback_faults, 0]),
+                    Some(_) => HilResponse::ok([hil::PROTOCOL_VERSION, 0]),
+                    None => HilResponse::status(HilStatus::UnknownCommand),
+                };
+                // Same word order the firmware's HilMailbox::complete uses.
+                self.words[5] = words[0];
+                self.words[6] = response.status.code();
+                self.words[7] = response.result[0];
+                self.words[8] = response.result[1];
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn mailbox_struct_matches_host_offsets() {
+        assert_eq!(
+            std::mem::size_of::<HilMailbox>(),
+            hil::MAILBOX_WORDS as usize * 4
+        );
+        assert_eq!(offset::ACK, 5 * 4);
+    }
+
+    #[test]
+    fn scenarios_compare_checksums_against_the_baseline() {
+        let mut target = FakeTarget {
+            words: [0; hil::MAILBOX_WORDS as usize],
+            checksum: 0xdead_beef,
+            loopback_faults: LoopbackFault::MuteLeak.bit(),
+        };
+        let baseline = BTreeMap::from([("render:splash", 0xdead_beef), ("render:test-pattern", 1)]);
+        let report = run_scenarios(&mut target, 0, &baseline).unwrap();
+
+        let outcomes: Vec<_> = report
+            .results
+            .iter()
+            .map(|r| (r.name, &r.outcome))
+            .collect();
+        assert_eq!(outcomes[0], ("ping", &Outcome::Pass));
+        assert_eq!(outcomes[1], ("render:splash", &Outcome::Pass));
+        assert!(matches!(outcomes[2].1, Outcome::Fail(_)));
+        assert!(matches!(outcomes[3].1, Outcome::Skip(_)));
+        assert_eq!(
+            outcomes[4],
+            ("audio:loopback", &Outcome::Fail("mute leak".into()))
+        );
+        assert_eq!(report.failed(), 2);
+    }
+
+    #[test]
+    fn parses_nm_and_probe_rs_output() {
+        let nm = "24000000 B FRAMEBUFFER\n24010040 D HIL_MAILBOX\n08000400 T main\n";
+        assert_eq!(parse_nm_symbol(nm, "HIL_MAILBOX"), Some(0x2401_0040));
+        assert_eq!(parse_nm_symbol(nm, "MISSING"), None);
+
+        assert_eq!(
+            parse_read_output("48494c31 00000002\n", 2).unwrap(),
+            vec![MAILBOX_MAGIC, 2]
+        );
+        assert!(parse_read_output("48494c31\n", 2).is_err());
+    }
+}
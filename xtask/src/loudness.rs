@@ -0,0 +1,477 @@
+//! EBU R128 loudness measurement for `xtask scan-library`.
+//!
+//! Implements ITU-R BS.1770-4 integrated loudness: K-weighting (a high
+//! shelf and a high pass, recomputed for each sample rate), 400 ms blocks
+//! with 75 % overlap, an absolute gate at -70 LUFS and a relative gate
+//! 10 LU below the ungated mean.  Album loudness gates the blocks of every
+//! track of the album together, as ReplayGain 2.0 specifies.
+//!
+//! Only formats the host can decode without extra tooling are measured:
+//! WAV (integer and float PCM) and MP3 through `playback`'s decoder.  Other
+//! files are left without loudness and play at their own level.
+
+use std::f64::consts::PI;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use library::binary::{Loudness, REPLAYGAIN_REFERENCE_LUFS};
+use playback::decoder::{FrameDecoder, PcmFrame};
+use playback::mp3_decoder::NanoMp3Decoder;
+
+/// Blocks are built from 100 ms steps, four to a block.
+const STEPS_PER_BLOCK: usize = 4;
+
+/// Absolute gate (LUFS).
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// Relative gate below the absolute-gated mean (LU).
+const RELATIVE_GATE_LU: f64 = 10.0;
+
+/// Loudness of a block of mean-square `energy` (BS.1770 eq. 2).
+fn lufs(energy: f64) -> f64 {
+    -0.691 + 10.0 * energy.log10()
+}
+
+/// A biquad in direct form I.
+#[derive(Debug, Clone, Copy)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// Stage 1 of the K-weighting: +4 dB shelf above ~1.7 kHz (head effects).
+    fn shelf(sample_rate: f64) -> Self {
+        let f0 = 1_681.974_450_955_533;
+        let gain_db = 3.999_843_853_973_347;
+        let q = 0.707_175_236_955_419_6;
+        let k = (PI * f0 / sample_rate).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.499_666_774_154_541_6);
+        let a0 = 1.0 + k / q + k * k;
+        Self::new(
+            [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        )
+    }
+
+    /// Stage 2 of the K-weighting: the RLB high pass at ~38 Hz.
+    fn high_pass(sample_rate: f64) -> Self {
+        let f0 = 38.135_470_876_024_44;
+        let q = 0.500_327_037_323_877_3;
+        let k = (PI * f0 / sample_rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        Self::new(
+            [1.0, -2.0, 1.0],
+            [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        )
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// Streams interleaved samples into 400 ms block energies.
+pub(crate) struct Meter {
+    filters: Vec<(Biquad, Biquad)>,
+    step_len: usize,
+    step_fill: usize,
+    step_sum: f64,
+    steps: Vec<f64>,
+    peak: f64,
+}
+
+impl Meter {
+    pub fn new(sample_rate: u32, channels: usize) -> Self {
+        let rate = f64::from(sample_rate);
+        Self {
+            filters: vec![(Biquad::shelf(rate), Biquad::high_pass(rate)); channels.max(1)],
+            step_len: (sample_rate as usize / 10).max(1),
+            step_fill: 0,
+            step_sum: 0.0,
+            steps: Vec::new(),
+            peak: 0.0,
+        }
+    }
+
+    /// Add one frame: a sample per channel, full scale ±1.0.  Extra
+    /// samples beyond the channel count are ignored.
+    pub fn push_frame(&mut self, frame: &[f64]) {
+        for (filters, &x) in self.filters.iter_mut().zip(frame) {
+            self.peak = self.peak.max(x.abs());
+            let y = filters.1.process(filters.0.process(x));
+            self.step_sum += y * y;
+        }
+        self.step_fill = self.step_fill.saturating_add(1);
+        if self.step_fill == self.step_len {
+            self.steps.push(self.step_sum);
+            self.step_fill = 0;
+            self.step_sum = 0.0;
+        }
+    }
+
+    /// Block energies and peak.  A trailing partial 100 ms step is dropped.
+    pub fn finish(self) -> Measurement {
+        // A 100 ms step is at most a few hundred thousand samples.
+        #[allow(clippy::cast_precision_loss)]
+        let block_len = self.step_len.saturating_mul(STEPS_PER_BLOCK) as f64;
+        let blocks = self
+            .steps
+            .windows(STEPS_PER_BLOCK)
+            .map(|w| w.iter().sum::<f64>() / block_len)
+            .collect();
+        Measurement {
+            blocks,
+            peak: self.peak,
+        }
+    }
+}
+
+/// Gating-block energies and sample peak of one track.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Measurement {
+    blocks: Vec<f64>,
+    /// Largest absolute sample, full scale 1.0.
+    pub peak: f64,
+}
+
+impl Measurement {
+    /// Integrated loudness (LUFS), or `None` for silence or a track
+    /// shorter than one block.
+    pub fn integrated_lufs(&self) -> Option<f64> {
+        gated_lufs(self.blocks.iter().copied())
+    }
+}
+
+/// Integrated loudness of the tracks of one album, gated together.
+pub(crate) fn album_lufs(tracks: &[&Measurement]) -> Option<f64> {
+    gated_lufs(tracks.iter().flat_map(|m| m.blocks.iter().copied()))
+}
+
+fn gated_lufs(blocks: impl Iterator<Item = f64> + Clone) -> Option<f64> {
+    let mean = |threshold: f64| {
+        let (sum, count) = blocks
+            .clone()
+            .filter(|&e| e > 0.0 && lufs(e) > threshold)
+            .fold((0.0, 0u32), |(s, n): (f64, u32), e| {
+                (s + e, n.saturating_add(1))
+            });
+        (count > 0).then(|| sum / f64::from(count))
+    };
+    let relative = lufs(mean(ABSOLUTE_GATE_LUFS)?) - RELATIVE_GATE_LU;
+    mean(relative.max(ABSOLUTE_GATE_LUFS)).map(lufs)
+}
+
+/// ReplayGain values for a track measured at `track_lufs`, on an album
+/// measured at `album_lufs`.
+pub(crate) fn replay_gain(track_lufs: f64, album_lufs: f64, peak: f64) -> Loudness {
+    // Both values are clamped to the target range before the cast.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    {
+        let cdb = |lufs: f64| {
+            let gain = (f64::from(REPLAYGAIN_REFERENCE_LUFS) - lufs) * 100.0;
+            gain.round().clamp(f64::from(i16::MIN), f64::from(i16::MAX)) as i16
+        };
+        let peak = (peak * f64::from(Loudness::FULL_SCALE_PEAK)).round();
+        Loudness {
+            track_gain_cdb: cdb(track_lufs),
+            album_gain_cdb: cdb(album_lufs),
+            track_peak: peak.clamp(0.0, f64::from(u16::MAX)) as u16,
+        }
+    }
+}
+
+/// Decode and measure the file at `path`; `None` for a format this host
+/// cannot decode.
+pub(crate) fn measure_file(path: &Path) -> Result<Option<Measurement>> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if ext != "wav" && ext != "mp3" {
+        return Ok(None);
+    }
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(match ext.as_str() {
+        "wav" => measure_wav(&bytes),
+        _ => measure_mp3(&bytes),
+    })
+}
+
+/// Measure a RIFF/WAVE file: 8/16/24/32-bit integer or 32/64-bit float
+/// PCM, plain or `WAVE_FORMAT_EXTENSIBLE`.
+pub(crate) fn measure_wav(bytes: &[u8]) -> Option<Measurement> {
+    if bytes.get(0..4)? != b"RIFF" || bytes.get(8..12)? != b"WAVE" {
+        return None;
+    }
+    let mut fmt = None;
+    let mut pos = 12usize;
+    while let Some(header) = bytes.get(pos..pos.checked_add(8)?) {
+        let len = u32::from_le_bytes(header.get(4..8)?.try_into().ok()?) as usize;
+        let body = pos.checked_add(8)?;
+        let chunk = bytes.get(body..body.checked_add(len)?.min(bytes.len()))?;
+        match header.get(0..4)? {
+            b"fmt " => fmt = Some(WavFormat::parse(chunk)?),
+            b"data" => return fmt.and_then(|f| f.measure(chunk)),
+            _ => {}
+        }
+        // Chunks are padded to an even length.
+        pos = body.checked_add(len)?.checked_add(len & 1)?;
+    }
+    None
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WavFormat {
+    float: bool,
+    channels: usize,
+    sample_rate: u32,
+    bytes_per_sample: usize,
+}
+
+impl WavFormat {
+    const PCM: u16 = 1;
+    const IEEE_FLOAT: u16 = 3;
+    const EXTENSIBLE: u16 = 0xFFFE;
+
+    fn parse(chunk: &[u8]) -> Option<Self> {
+        let word = |at: usize| {
+            chunk
+                .get(at..at.saturating_add(2))
+                .and_then(|b| b.try_into().ok())
+                .map(u16::from_le_bytes)
+        };
+        let mut tag = word(0)?;
+        if tag == Self::EXTENSIBLE {
+            // The sub-format GUID starts with the plain format tag.
+            tag = word(24)?;
+        }
+        let channels = usize::from(word(2)?);
+        let sample_rate = u32::from_le_bytes(chunk.get(4..8)?.try_into().ok()?);
+        let bits = word(14)?;
+        let format = Self {
+            float: tag == Self::IEEE_FLOAT,
+            channels,
+            sample_rate,
+            bytes_per_sample: usize::from(bits.div_ceil(8)),
+        };
+        let supported = match tag {
+            Self::PCM => (1..=4).contains(&format.bytes_per_sample),
+            Self::IEEE_FLOAT => matches!(format.bytes_per_sample, 4 | 8),
+            _ => false,
+        };
+        (supported && channels > 0 && sample_rate > 0).then_some(format)
+    }
+
+    fn sample(&self, b: &[u8]) -> f64 {
+        match (self.float, b) {
+            (true, &[b0, b1, b2, b3]) => f64::from(f32::from_le_bytes([b0, b1, b2, b3])),
+            (true, _) => f64::from_le_bytes(b.try_into().unwrap_or_default()),
+            // 8-bit WAV is unsigned.
+            (false, &[b0]) => (f64::from(b0) - 128.0) / 128.0,
+            (false, _) => {
+                // Left-justify into an i32, then scale.  `parse` limits
+                // integer samples to 4 bytes, so the lengths match.
+                let mut word = [0u8; 4];
+                if let Some(msb) = word.get_mut(4usize.saturating_sub(b.len())..) {
+                    msb.copy_from_slice(b);
+                }
+                f64::from(i32::from_le_bytes(word)) / 2_147_483_648.0
+            }
+        }
+    }
+
+    fn measure(&self, data: &[u8]) -> Option<Measurement> {
+        let mut meter = Meter::new(self.sample_rate, self.channels);
+        let mut frame = vec![0.0; self.channels];
+        for raw in data.chunks_exact(self.bytes_per_sample.saturating_mul(self.channels)) {
+            for (slot, b) in frame
+                .iter_mut()
+                .zip(raw.chunks_exact(self.bytes_per_sample))
+            {
+                *slot = self.sample(b);
+            }
+            meter.push_frame(&frame);
+        }
+        Some(meter.finish())
+    }
+}
+
+/// Measure an MP3 stream, skipping anything the decoder cannot sync to.
+pub(crate) fn measure_mp3(bytes: &[u8]) -> Option<Measurement> {
+    let mut decoder = NanoMp3Decoder::new();
+    let mut pcm = Box::new(PcmFrame::zeroed());
+    let mut meter: Option<Meter> = None;
+    let mut pos = 0usize;
+    while let Some(input) = bytes.get(pos..).filter(|rest| !rest.is_empty()) {
+        let Ok(consumed) = decoder.decode_frame(input, &mut pcm) else {
+            break;
+        };
+        if consumed == 0 {
+            break;
+        }
+        pos = pos.saturating_add(consumed);
+        let channels = usize::from(pcm.channels.max(1));
+        let meter = meter.get_or_insert_with(|| Meter::new(pcm.sample_rate, channels));
+        let samples = pcm
+            .samples
+            .get(..pcm.len.saturating_mul(channels))
+            .unwrap_or_default();
+        for frame in samples.chunks_exact(channels) {
+            let frame: Vec<f64> = frame
+                .iter()
+                .map(|&s| f64::from(s) / 2_147_483_648.0)
+                .collect();
+            meter.push_frame(&frame);
+        }
+    }
+    meter.map(Meter::finish)
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::unwrap_used,
+    clippy::expect_used,
+    clippy::panic,
+    clippy::indexing_slicing,
+    clippy::arithmetic_side_effects,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+mod tests {
+    use super::*;
+
+    /// `seconds` of a 997 Hz sine at `dbfs` peak, on every channel.
+    fn sine(meter: &mut Meter, rate: u32, channels: usize, dbfs: f64, seconds: f64) {
+        let amplitude = 10f64.powf(dbfs / 20.0);
+        let n = (f64::from(rate) * seconds) as usize;
+        for i in 0..n {
+            let x = amplitude * (2.0 * PI * 997.0 * i as f64 / f64::from(rate)).sin();
+            meter.push_frame(&vec![x; channels]);
+        }
+    }
+
+    #[test]
+    fn stereo_sine_at_minus_23_dbfs_reads_minus_23_lufs() {
+        // EBU Tech 3341, test case 1.
+        for rate in [44_100, 48_000, 96_000] {
+            let mut meter = Meter::new(rate, 2);
+            sine(&mut meter, rate, 2, -23.0, 20.0);
+            let lufs = meter.finish().integrated_lufs().unwrap();
+            assert!((lufs + 23.0).abs() < 0.1, "{rate} Hz: {lufs}");
+        }
+    }
+
+    #[test]
+    fn gating_ignores_silence_and_quiet_passages() {
+        let mut meter = Meter::new(48_000, 2);
+        sine(&mut meter, 48_000, 2, -20.0, 10.0);
+        sine(&mut meter, 48_000, 2, -200.0, 10.0);
+        sine(&mut meter, 48_000, 2, -40.0, 10.0);
+        let lufs = meter.finish().integrated_lufs().unwrap();
+        assert!((lufs + 20.0).abs() < 0.2, "{lufs}");
+
+        let mut silent = Meter::new(48_000, 1);
+        sine(&mut silent, 48_000, 1, -200.0, 2.0);
+        assert_eq!(silent.finish().integrated_lufs(), None);
+    }
+
+    #[test]
+    fn album_loudness_gates_tracks_together() {
+        let measure = |dbfs| {
+            let mut meter = Meter::new(48_000, 2);
+            sine(&mut meter, 48_000, 2, dbfs, 10.0);
+            meter.finish()
+        };
+        let loud = measure(-14.0);
+        let quiet = measure(-30.0);
+        let album = album_lufs(&[&loud, &quiet]).unwrap();
+        // The quiet track is more than 10 LU down, so the relative gate
+        // drops it and the album reads as the loud track.
+        assert!((album - loud.integrated_lufs().unwrap()).abs() < 0.01);
+
+        let gain = replay_gain(quiet.integrated_lufs().unwrap(), album, quiet.peak);
+        assert!((gain.track_gain_cdb - 1_200).abs() <= 10, "{gain:?}");
+        assert!((gain.album_gain_cdb + 400).abs() <= 10, "{gain:?}");
+        // -30 dBFS is 0.0316 of full scale.
+        assert!((i32::from(gain.track_peak) - 1_036).abs() <= 1, "{gain:?}");
+    }
+
+    fn wav(format: u16, bits: u16, channels: u16, rate: u32, data: &[u8]) -> Vec<u8> {
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&format.to_le_bytes());
+        fmt.extend_from_slice(&channels.to_le_bytes());
+        fmt.extend_from_slice(&rate.to_le_bytes());
+        let align = channels * bits / 8;
+        fmt.extend_from_slice(&(rate * u32::from(align)).to_le_bytes());
+        fmt.extend_from_slice(&align.to_le_bytes());
+        fmt.extend_from_slice(&bits.to_le_bytes());
+        let mut body = b"WAVE".to_vec();
+        for (id, chunk) in [
+            (b"fmt ", fmt.as_slice()),
+            (b"LIST", b"odd".as_slice()),
+            (b"data", data),
+        ] {
+            body.extend_from_slice(id);
+            body.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            body.extend_from_slice(chunk);
+            if chunk.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+        let mut out = b"RIFF".to_vec();
+        out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        out.extend_from_slice(&body);
+        out
+    }
+
+    #[test]
+    fn wav_pcm_and_float_measure_alike() {
+        let rate = 48_000u32;
+        let amplitude = 10f64.powf(-23.0 / 20.0);
+        let samples: Vec<f64> = (0..rate * 5)
+            .map(|i| amplitude * (2.0 * PI * 997.0 * f64::from(i) / f64::from(rate)).sin())
+            .collect();
+        let pcm16: Vec<u8> = samples
+            .iter()
+            .flat_map(|&x| ((x * 32_767.0).round() as i16).to_le_bytes())
+            .collect();
+        let float: Vec<u8> = samples
+            .iter()
+            .flat_map(|&x| (x as f32).to_le_bytes())
+            .collect();
+
+        let a = measure_wav(&wav(1, 16, 1, rate, &pcm16)).unwrap();
+        let b = measure_wav(&wav(3, 32, 1, rate, &float)).unwrap();
+        let (a, b) = (a.integrated_lufs().unwrap(), b.integrated_lufs().unwrap());
+        // Mono: one channel of the stereo test case, 3 dB down.
+        assert!((a + 26.0).abs() < 0.1, "{a}");
+        assert!((a - b).abs() < 0.01);
+
+        assert!(measure_wav(b"RIFF\0\0\0\0AVI ").is_none());
+        assert!(measure_wav(&wav(2, 4, 1, rate, &pcm16)).is_none(), "ADPCM");
+    }
+}
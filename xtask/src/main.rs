@@ -3,13 +3,23 @@
 // TODO: Add rustdoc to all public items (tracked as tech debt)
 #![allow(missing_docs)]
 
+mod bench;
 mod check;
+mod demo;
 mod dev;
 mod doc;
 mod flash;
+mod fuzz;
 mod hardware;
+mod hil;
+mod loudness;
+mod memory;
+mod panic_check;
+mod podcasts;
 mod scan_library;
+mod screens;
 mod test;
+mod vectors;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
@@ -30,6 +40,13 @@ enum Commands {
         /// Build and flash release version
         #[arg(short, long)]
         release: bool,
+        /// After flashing, watch RTT for the boot markers (boot, display,
+        /// SD, audio) and fail if any required one is missing.
+        #[arg(long)]
+        smoke: bool,
+        /// Seconds to wait for the smoke-test markers, including flash time
+        #[arg(long, default_value_t = 30)]
+        smoke_timeout: u64,
     },
     /// Run emulator with hot-reload development mode
     Dev {
@@ -40,8 +57,14 @@ enum Commands {
         /// Requires: cargo build --package firmware-ui --features hot-reload
         #[arg(long)]
         hot_reload: bool,
-        /// Local music directory — passed as MUSIC_PATH env var to the emulator.
-        /// The emulator's LocalFileStorage reads this to locate Soul library files.
+        /// Re-run the affected golden/UI tests in the background on each change.
+        /// Failures appear in the emulator's debug panel (Display tab).
+        #[arg(long)]
+        watch_tests: bool,
+        /// Local music directory or FAT32 card image — passed as MUSIC_PATH env
+        /// var to the emulator (see platform::storage_sim::HostVolume). Export
+        /// SD_PROFILE=uhs1-a1|class10|class4 to emulate the card's timing and
+        /// SD_FAULTS (e.g. read-error=500,remove-at=1048576) to inject faults.
         #[arg(long)]
         music_path: Option<std::path::PathBuf>,
     },
@@ -68,6 +91,64 @@ enum Commands {
         #[command(subcommand)]
         command: hardware::HwCommand,
     },
+    /// Per-frame decode benchmarks: collect baselines and diff between commits
+    Bench {
+        #[command(subcommand)]
+        command: bench::BenchCommand,
+    },
+    /// Flash the HIL test firmware and run the hardware-in-the-loop scenarios
+    HilTest {
+        /// Build the test firmware in release mode
+        #[arg(short, long)]
+        release: bool,
+        /// Record the rendered-screen checksums as the new baseline
+        #[arg(long)]
+        bless: bool,
+    },
+    /// Report per-region memory use of a firmware build against memory.x
+    MemoryReport {
+        /// Build (or read) the release firmware
+        #[arg(long)]
+        release: bool,
+        /// Report on this ELF instead of building the firmware
+        #[arg(long)]
+        elf: Option<std::path::PathBuf>,
+    },
+    /// Smoke-run the cargo-fuzz parser targets from their seed corpora (nightly)
+    Fuzz {
+        /// Run only this target (default: all)
+        target: Option<String>,
+        /// Seconds per target
+        #[arg(long, default_value_t = 10)]
+        seconds: u64,
+    },
+    /// Fail if an audio hot-path function in the firmware can call a panic
+    PanicCheck {
+        /// Build (or read) the release firmware
+        #[arg(long)]
+        release: bool,
+        /// Check this ELF instead of building the firmware
+        #[arg(long)]
+        elf: Option<std::path::PathBuf>,
+    },
+    /// Write the audio conformance test vectors to a directory
+    Vectors {
+        /// Output directory
+        #[arg(long, default_value = "target/test-vectors")]
+        out: std::path::PathBuf,
+    },
+    /// Render every gallery screen headless and export PNGs plus an HTML index
+    Screens {
+        /// Output directory
+        #[arg(long, default_value = "target/screens")]
+        out: std::path::PathBuf,
+    },
+    /// Record one loop of the UI demo mode headless as an animated GIF
+    Demo {
+        /// Output file
+        #[arg(long, default_value = "target/demo.gif")]
+        out: std::path::PathBuf,
+    },
     /// Scan a local music folder and write Soul binary library files
     ScanLibrary {
         /// Directory containing music files (Artist/Album/track structure)
@@ -77,24 +158,51 @@ enum Commands {
         #[arg(long)]
         soul_root: std::path::PathBuf,
     },
+    /// Download the latest episodes of podcast RSS feeds into a Podcasts/ folder
+    PodcastSync {
+        /// Feeds file: one `Name = URL` or bare URL per line
+        #[arg(long, default_value = "podcasts.txt")]
+        feeds: std::path::PathBuf,
+        /// Card root to write Podcasts/{Show}/ under
+        #[arg(long)]
+        out: std::path::PathBuf,
+        /// Episodes to keep per feed (newest first)
+        #[arg(long, default_value_t = 5)]
+        keep: usize,
+    },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Flash { release } => flash::run(release),
+        Commands::Flash {
+            release,
+            smoke,
+            smoke_timeout,
+        } => flash::run(release, smoke, smoke_timeout),
         Commands::Dev {
             headless,
             hot_reload,
+            watch_tests,
             music_path,
-        } => dev::run(headless, hot_reload, music_path.as_deref()),
+        } => dev::run(headless, hot_reload, watch_tests, music_path.as_deref()),
         Commands::Check => check::run(),
         Commands::Test { unit, integration } => test::run(unit, integration),
         Commands::Doc { open } => doc::run(open),
         Commands::Hardware { command } => hardware::run(command),
-        Commands::ScanLibrary { music_dir, soul_root } => {
-            scan_library::run(&music_dir, &soul_root)
-        }
+        Commands::Bench { command } => bench::run(command),
+        Commands::HilTest { release, bless } => hil::run(release, bless),
+        Commands::MemoryReport { release, elf } => memory::run(release, elf.as_deref()),
+        Commands::PanicCheck { release, elf } => panic_check::run(release, elf.as_deref()),
+        Commands::Fuzz { target, seconds } => fuzz::run(target.as_deref(), seconds),
+        Commands::Vectors { out } => vectors::run(&out),
+        Commands::Screens { out } => screens::run(&out),
+        Commands::Demo { out } => demo::run(&out),
+        Commands::ScanLibrary {
+            music_dir,
+            soul_root,
+        } => scan_library::run(&music_dir, &soul_root),
+        Commands::PodcastSync { feeds, out, keep } => podcasts::run(&feeds, &out, keep),
     }
 }
@@ -0,0 +1,194 @@
+//! xtask memory-report — per-region memory use of a firmware build.
+//!
+//! Runs `rust-size -A` (or `arm-none-eabi-size -A`) on the firmware ELF and
+//! adds up the allocated sections by the region their address falls in,
+//! using the region table in `platform::memory_budget` (which a platform
+//! test keeps in step with `memory.x`).  `.data` is counted twice: at its
+//! RAM address and again in FLASH for its load image.
+//!
+//! The budgeted column is the intended reservation from
+//! `memory_budget::ALLOCATIONS`; the used column is what the linker placed.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use platform::memory_budget::Region;
+
+const TARGET: &str = "thumbv7em-none-eabihf";
+
+/// One line of `size -A` output.
+#[derive(Debug, PartialEq, Eq)]
+struct Section {
+    name: String,
+    size: u64,
+    addr: u64,
+}
+
+pub fn run(release: bool, elf: Option<&Path>) -> Result<()> {
+    let elf = match elf {
+        Some(path) => path.to_path_buf(),
+        None => build(release)?,
+    };
+    let sections = read_sections(&elf)?;
+    let usage = usage_by_region(&sections);
+
+    println!();
+    println!(
+        "{}",
+        format!("📊 Memory use: {}", elf.display()).cyan().bold()
+    );
+    println!(
+        "   {:<9} {:>10} {:>10} {:>10} {:>10} {:>6}",
+        "region", "size", "used", "free", "budgeted", "used%"
+    );
+    for (region, used) in usage {
+        let size = u64::try_from(region.length()).unwrap_or(u64::MAX);
+        let free = size.saturating_sub(used);
+        let percent = used.saturating_mul(100).checked_div(size).unwrap_or(0);
+        let line = format!(
+            "   {:<9} {:>10} {:>10} {:>10} {:>10} {:>5}%",
+            region.name(),
+            size,
+            used,
+            free,
+            region.budgeted(),
+            percent
+        );
+        if percent >= 90 {
+            println!("{}", line.red());
+        } else if used == 0 {
+            println!("{}", line.dimmed());
+        } else {
+            println!("{line}");
+        }
+    }
+    Ok(())
+}
+
+/// Build the hardware firmware and return the ELF path.
+pub(crate) fn build(release: bool) -> Result<PathBuf> {
+    let mut cmd = Command::new("cargo");
+    cmd.args([
+        "build",
+        "-p",
+        "firmware",
+        "--target",
+        TARGET,
+        "--features",
+        "hardware",
+    ]);
+    if release {
+        cmd.arg("--release");
+    }
+    let status = cmd.status().context("Failed to run cargo build")?;
+    if !status.success() {
+        bail!("Firmware build failed");
+    }
+    let profile = if release { "release" } else { "debug" };
+    Ok(PathBuf::from(format!("target/{TARGET}/{profile}/firmware")))
+}
+
+fn read_sections(elf: &Path) -> Result<Vec<Section>> {
+    for tool in ["rust-size", "arm-none-eabi-size"] {
+        let Ok(out) = Command::new(tool).arg("-A").arg(elf).output() else {
+            continue;
+        };
+        if out.status.success() {
+            return Ok(parse_size_output(&String::from_utf8_lossy(&out.stdout)));
+        }
+    }
+    bail!(
+        "Neither rust-size nor arm-none-eabi-size could read {} (cargo install cargo-binutils)",
+        elf.display()
+    )
+}
+
+/// Parse `size -A` output: `name size addr` rows between the header and
+/// the `Total` line.
+fn parse_size_output(text: &str) -> Vec<Section> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?;
+            let size = fields.next()?.parse().ok()?;
+            let addr = fields.next()?.parse().ok()?;
+            name.starts_with('.').then(|| Section {
+                name: name.to_owned(),
+                size,
+                addr,
+            })
+        })
+        .collect()
+}
+
+/// Bytes placed in each region, in `memory.x` order.  Sections at address
+/// 0 (debug info) or outside every region are ignored.
+fn usage_by_region(sections: &[Section]) -> Vec<(Region, u64)> {
+    let mut usage: Vec<(Region, u64)> = Region::ALL.iter().map(|&r| (r, 0)).collect();
+    let mut add = |region: Region, size: u64| {
+        if let Some((_, used)) = usage.iter_mut().find(|(r, _)| *r == region) {
+            *used = used.saturating_add(size);
+        }
+    };
+    for section in sections.iter().filter(|s| s.size > 0 && s.addr > 0) {
+        let Some(region) = u32::try_from(section.addr)
+            .ok()
+            .and_then(Region::containing)
+        else {
+            continue;
+        };
+        add(region, section.size);
+        if section.name == ".data" {
+            add(Region::Flash, section.size);
+        }
+    }
+    usage
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    const SIZE_OUTPUT: &str = "\
+firmware  :
+section               size         addr
+.vector_table          664    134217728
+.text                86212    134218392
+.rodata              12000    134304608
+.data                  512    603979776
+.bss                  4096    603980288
+.axisram            212768    603984384
+.sram4                 256    939524096
+.defmt                  12            0
+.debug_info         123456            0
+Total               439976
+";
+
+    #[test]
+    fn parses_section_rows_only() {
+        let sections = parse_size_output(SIZE_OUTPUT);
+        assert_eq!(sections.len(), 9);
+        assert_eq!(
+            sections[1],
+            Section {
+                name: ".text".to_owned(),
+                size: 86212,
+                addr: 0x0800_0298,
+            }
+        );
+    }
+
+    #[test]
+    fn sums_sections_per_region() {
+        let usage = usage_by_region(&parse_size_output(SIZE_OUTPUT));
+        let used = |region| usage.iter().find(|(r, _)| *r == region).unwrap().1;
+        assert_eq!(used(Region::Flash), 664 + 86212 + 12000 + 512);
+        assert_eq!(used(Region::AxiSram), 512 + 4096 + 212768);
+        assert_eq!(used(Region::Sram4), 256);
+        assert_eq!(used(Region::Dtcm), 0);
+        assert_eq!(usage.len(), Region::ALL.len());
+    }
+}
@@ -0,0 +1,194 @@
+//! xtask panic-check — no audio hot-path function may call into a panic.
+//!
+//! Disassembles the firmware ELF with `llvm-objdump` (cargo-binutils'
+//! `rust-objdump`, or `arm-none-eabi-objdump`) and, for every function
+//! whose demangled name lies in a hot-path module of the playback crate,
+//! looks for branches to the panic entry points of `core`.  Clippy and
+//! `playback/tests/panic_free.rs` cover our own source; this catches what
+//! they cannot see — bounds checks the optimiser kept, and panics pulled in
+//! from dependencies inlined into the hot path.
+//!
+//! Code inlined into a caller outside the hot-path modules is attributed to
+//! that caller, so the check is a floor, not a proof.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+/// Symbol prefixes of the hot-path modules listed in the playback crate docs.
+const HOT_PATHS: &[&str] = &[
+    "playback::decoder::",
+    "playback::flac_lpc::",
+    "playback::frame_timing::",
+    "playback::mp3_decoder::",
+    "playback::ogg::",
+    "playback::ring_buffer::",
+    "playback::volume::",
+];
+
+/// Functions `core` calls to start a panic.  Bounds, slice-range, unwrap and
+/// arithmetic-overflow failures all end in one of these.
+const PANIC_SYMBOLS: &[&str] = &[
+    "core::panicking::",
+    "core::slice::index::slice_",
+    "core::option::unwrap_failed",
+    "core::option::expect_failed",
+    "core::result::unwrap_failed",
+    "rust_begin_unwind",
+];
+
+/// A branch from a hot-path function to a panic entry point.
+#[derive(Debug, PartialEq, Eq)]
+struct PanicCall {
+    function: String,
+    target: String,
+}
+
+pub fn run(release: bool, elf: Option<&Path>) -> Result<()> {
+    let elf = match elf {
+        Some(path) => path.to_path_buf(),
+        None => crate::memory::build(release)?,
+    };
+    let calls = find_panic_calls(&disassemble(&elf)?);
+
+    println!();
+    if calls.is_empty() {
+        println!(
+            "{}",
+            "✓ No audio hot-path function calls into a panic"
+                .green()
+                .bold()
+        );
+        return Ok(());
+    }
+    println!(
+        "{}",
+        "✗ Audio hot-path functions that can panic:".red().bold()
+    );
+    for call in &calls {
+        println!("   {} → {}", call.function, call.target.red());
+    }
+    bail!("{} panic call(s) in audio hot paths", calls.len())
+}
+
+fn disassemble(elf: &Path) -> Result<String> {
+    for tool in ["rust-objdump", "llvm-objdump", "arm-none-eabi-objdump"] {
+        let Ok(out) = Command::new(tool)
+            .args(["-d", "--demangle", "--no-show-raw-insn"])
+            .arg(elf)
+            .output()
+        else {
+            continue;
+        };
+        if out.status.success() {
+            return Ok(String::from_utf8_lossy(&out.stdout).into_owned());
+        }
+    }
+    bail!(
+        "No objdump could disassemble {} (cargo install cargo-binutils)",
+        elf.display()
+    )
+}
+
+/// Scan objdump output: `<addr> <name>:` opens a function, and any
+/// instruction operand `<target>` or `<target+0x..>` naming a panic symbol
+/// inside a hot-path function is reported once per (function, target).
+fn find_panic_calls(disassembly: &str) -> Vec<PanicCall> {
+    let mut calls: Vec<PanicCall> = Vec::new();
+    let mut current: Option<&str> = None;
+    for line in disassembly.lines() {
+        if let Some(name) = function_header(line) {
+            current = HOT_PATHS
+                .iter()
+                .any(|prefix| name.contains(prefix))
+                .then_some(name);
+            continue;
+        }
+        let Some(function) = current else {
+            continue;
+        };
+        let Some(target) = branch_target(line) else {
+            continue;
+        };
+        if !PANIC_SYMBOLS.iter().any(|p| target.contains(p)) {
+            continue;
+        }
+        let call = PanicCall {
+            function: function.to_owned(),
+            target: target.to_owned(),
+        };
+        if !calls.contains(&call) {
+            calls.push(call);
+        }
+    }
+    calls
+}
+
+/// `08001234 <name>:` → `name`.
+fn function_header(line: &str) -> Option<&str> {
+    let (addr, rest) = line.split_once(' ')?;
+    if addr.is_empty() || !addr.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    rest.strip_prefix('<')?.strip_suffix(">:")
+}
+
+/// The symbol in a `bl 0x8001384 <target+0x4>` operand, without offset.
+fn branch_target(line: &str) -> Option<&str> {
+    let start = line.rfind('<')?;
+    let symbol = line.get(start.saturating_add(1)..)?.strip_suffix('>')?;
+    Some(symbol.split_once('+').map_or(symbol, |(name, _)| name))
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    const DISASSEMBLY: &str = "\
+firmware:\tfile format elf32-littlearm
+
+Disassembly of section .text:
+
+08001200 <playback::flac_lpc::restore_lpc>:
+ 8001200:      \tpush\t{r4, r5, r7, lr}
+ 8001204:      \tbl\t0x8009000 <core::panicking::panic_bounds_check>
+ 8001208:      \tbl\t0x8009000 <core::panicking::panic_bounds_check>
+ 800120c:      \tb.w\t0x8009100 <core::slice::index::slice_end_index_len_fail+0x2>
+
+08001300 <playback::ring_buffer::Producer<_>::commit>:
+ 8001300:      \tldr\tr0, [r0]
+ 8001302:      \tbx\tlr
+
+08001400 <firmware::ui_task>:
+ 8001400:      \tbl\t0x8009000 <core::panicking::panic_bounds_check>
+";
+
+    #[test]
+    fn reports_panics_only_in_hot_paths() {
+        let calls = find_panic_calls(DISASSEMBLY);
+        assert_eq!(calls.len(), 2, "{calls:?}");
+        assert_eq!(calls[0].function, "playback::flac_lpc::restore_lpc");
+        assert_eq!(calls[0].target, "core::panicking::panic_bounds_check");
+        assert_eq!(
+            calls[1].target,
+            "core::slice::index::slice_end_index_len_fail"
+        );
+    }
+
+    #[test]
+    fn parses_headers_and_operands() {
+        assert_eq!(
+            function_header("08001300 <playback::volume::volume_to_attenuation>:"),
+            Some("playback::volume::volume_to_attenuation")
+        );
+        assert_eq!(function_header(" 8001300:  \tbx\tlr"), None);
+        assert_eq!(
+            branch_target(" 800120c:  \tb.w\t0x8009100 <f+0x2>"),
+            Some("f")
+        );
+        assert_eq!(branch_target(" 8001300:  \tbx\tlr"), None);
+    }
+}
@@ -0,0 +1,474 @@
+//! `cargo xtask podcast-sync` — download podcast episodes onto a card image.
+//!
+//! Feeds are listed one per line in a text file, optionally named:
+//!
+//! ```text
+//! # comments and blank lines are ignored
+//! Hardcore History = https://example.com/feed.xml
+//! https://example.com/other.rss
+//! ```
+//!
+//! The latest `--keep` episodes of each feed are downloaded to
+//! `{out}/Podcasts/{Show}/{YYYY-MM-DD} - {Title}.{ext}` with a `.episode`
+//! sidecar next to each (parsed on device by `library::podcast::Episode`).
+//! Files already present are skipped, so re-running only fetches new
+//! episodes.  Downloads go through `curl`, which must be on `PATH`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use library::podcast::PODCASTS_DIR;
+
+/// One configured feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Feed {
+    /// Show name override; the feed's channel title is used when `None`.
+    pub name: Option<String>,
+    pub url: String,
+}
+
+/// One `<item>` of an RSS feed that has an audio enclosure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FeedItem {
+    pub title: String,
+    pub guid: String,
+    /// `pubDate` as Unix seconds (0 when missing or unparseable).
+    pub published: u32,
+    pub url: String,
+    /// `itunes:duration` in seconds (0 when missing).
+    pub duration_secs: u32,
+}
+
+/// Entry point called from main.rs
+pub fn run(feeds_file: &Path, out: &Path, keep: usize) -> Result<()> {
+    let src = std::fs::read_to_string(feeds_file)
+        .with_context(|| format!("Failed to read {}", feeds_file.display()))?;
+    let feeds = parse_feeds(&src);
+    if feeds.is_empty() {
+        bail!("No feeds listed in {}", feeds_file.display());
+    }
+
+    println!();
+    println!("{}", "🎙  Syncing podcasts...".cyan().bold());
+
+    let mut downloaded = 0usize;
+    for feed in &feeds {
+        let xml = fetch(&feed.url)?;
+        let (channel, mut items) = parse_rss(&xml);
+        let show = sanitize(feed.name.as_deref().unwrap_or(&channel));
+        if show.is_empty() {
+            bail!("{}: feed has no title; name it in the feeds file", feed.url);
+        }
+        items.sort_by_key(|item| std::cmp::Reverse(item.published));
+        items.truncate(keep);
+
+        println!();
+        println!("  {}", show.bold());
+        let dir = out.join(PODCASTS_DIR).join(&show);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        for item in &items {
+            let path = dir.join(episode_file_name(item));
+            if path.is_file() {
+                println!("    {} {}", "·".dimmed(), item.title.dimmed());
+                continue;
+            }
+            download(&item.url, &path)?;
+            let sidecar = path.with_extension("episode");
+            std::fs::write(&sidecar, episode_sidecar(&show, item))
+                .with_context(|| format!("Failed to write {}", sidecar.display()))?;
+            println!("    {} {}", "✓".green(), item.title);
+            downloaded = downloaded.saturating_add(1);
+        }
+    }
+
+    println!();
+    println!("   {}", format!("{downloaded} new episode(s)").dimmed());
+    println!();
+    Ok(())
+}
+
+/// Parse the feeds file: `Name = URL` or a bare URL per line.
+pub(crate) fn parse_feeds(src: &str) -> Vec<Feed> {
+    src.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.split_once(" = ") {
+            Some((name, url)) => Feed {
+                name: Some(name.trim().to_owned()),
+                url: url.trim().to_owned(),
+            },
+            None => Feed {
+                name: None,
+                url: line.to_owned(),
+            },
+        })
+        .collect()
+}
+
+fn fetch(url: &str) -> Result<String> {
+    let output = Command::new("curl")
+        .args(["-fsSL", url])
+        .output()
+        .context("Failed to run curl")?;
+    if !output.status.success() {
+        bail!(
+            "Fetching {url} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Download `url` to `path` via a `.part` file, so an interrupted download
+/// is not mistaken for a finished episode on the next run.
+fn download(url: &str, path: &Path) -> Result<()> {
+    let part: PathBuf = path.with_extension("part");
+    let status = Command::new("curl")
+        .args(["-fL", "--progress-bar", "-o"])
+        .arg(&part)
+        .arg(url)
+        .status()
+        .context("Failed to run curl")?;
+    if !status.success() {
+        let _ = std::fs::remove_file(&part);
+        bail!("Downloading {url} failed");
+    }
+    std::fs::rename(&part, path).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Extract the channel title and the audio items from an RSS 2.0 document.
+///
+/// A deliberately small scanner rather than a full XML parser: it reads
+/// `<title>`, `<guid>`, `<pubDate>`, `<itunes:duration>` and the
+/// `<enclosure url="…">` of each `<item>`, handling CDATA and the
+/// predefined entities.  Items without an enclosure are skipped.
+pub(crate) fn parse_rss(xml: &str) -> (String, Vec<FeedItem>) {
+    let head = xml.split("<item").next().unwrap_or_default();
+    let channel = tag_text(head, "title").unwrap_or_default();
+
+    let items = xml
+        .split("<item")
+        .skip(1)
+        .filter_map(|rest| {
+            let block = rest.split("</item>").next()?;
+            let url = attr(block, "enclosure", "url")?;
+            let title = tag_text(block, "title").unwrap_or_else(|| url.clone());
+            Some(FeedItem {
+                guid: tag_text(block, "guid").unwrap_or_else(|| url.clone()),
+                published: tag_text(block, "pubDate")
+                    .and_then(|d| parse_rfc822(&d))
+                    .unwrap_or(0),
+                duration_secs: tag_text(block, "itunes:duration")
+                    .and_then(|d| parse_duration(&d))
+                    .unwrap_or(0),
+                title,
+                url,
+            })
+        })
+        .collect();
+    (channel, items)
+}
+
+/// Text content of the first `<tag>` element in `xml`.
+fn tag_text(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{tag}");
+    let mut rest = xml;
+    loop {
+        let start = rest.find(&open)?;
+        rest = rest.get(start.saturating_add(open.len())..)?;
+        // `<title` must not match `<titleFoo`.
+        if rest.starts_with(['>', ' ', '\t', '\n', '\r']) {
+            break;
+        }
+    }
+    let body = rest.get(rest.find('>')?.saturating_add(1)..)?;
+    let text = body.get(..body.find(&format!("</{tag}>"))?)?;
+    let text = text.trim();
+    let text = match text
+        .strip_prefix("<![CDATA[")
+        .and_then(|t| t.strip_suffix("]]>"))
+    {
+        Some(cdata) => cdata.to_owned(),
+        None => decode_entities(text),
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_owned())
+}
+
+/// Value of attribute `name` on the first `<tag …>` element in `xml`.
+fn attr(xml: &str, tag: &str, name: &str) -> Option<String> {
+    let start = xml.find(&format!("<{tag}"))?;
+    let element = xml.get(start..)?;
+    let element = element.get(..element.find('>')?)?;
+    for quote in ['"', '\''] {
+        let key = format!("{name}={quote}");
+        if let Some(at) = element.find(&key) {
+            let value = element.get(at.saturating_add(key.len())..)?;
+            let value = value.get(..value.find(quote)?)?;
+            return Some(decode_entities(value));
+        }
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Parse an RFC 822 date (`Tue, 02 Jan 2024 10:30:00 +0000`) to Unix
+/// seconds.  Named zones other than GMT/UT/Z are taken as UTC.
+pub(crate) fn parse_rfc822(date: &str) -> Option<u32> {
+    let date = date.split_once(',').map_or(date, |(_, rest)| rest);
+    let mut parts = date.split_whitespace();
+    let day: u32 = parts.next()?.parse().ok()?;
+    let month = parts.next()?.get(..3)?;
+    let month = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ]
+    .iter()
+    .position(|m| m.eq_ignore_ascii_case(month))?;
+    let year: i64 = parts.next()?.parse().ok()?;
+    let mut hms = parts.next()?.split(':').map(str::parse::<i64>);
+    let hour = hms.next()?.ok()?;
+    let minute = hms.next()?.ok()?;
+    let second = hms.next().transpose().ok()?.unwrap_or(0);
+    let offset = parts.next().and_then(zone_offset_secs).unwrap_or(0);
+
+    let days = days_from_civil(year, u32::try_from(month).ok()?.checked_add(1)?, day)?;
+    let secs = days
+        .checked_mul(86_400)?
+        .checked_add(hour.checked_mul(3_600)?)?
+        .checked_add(minute.checked_mul(60)?)?
+        .checked_add(second)?
+        .checked_sub(offset)?;
+    u32::try_from(secs).ok()
+}
+
+/// `+hhmm` / `-hhmm` as seconds east of UTC.
+fn zone_offset_secs(zone: &str) -> Option<i64> {
+    let (sign, digits) = match zone.as_bytes().first()? {
+        b'+' => (1, zone.get(1..)?),
+        b'-' => (-1, zone.get(1..)?),
+        _ => return None,
+    };
+    if digits.len() != 4 {
+        return None;
+    }
+    let hours: i64 = digits.get(..2)?.parse().ok()?;
+    let minutes: i64 = digits.get(2..)?.parse().ok()?;
+    hours
+        .checked_mul(3_600)?
+        .checked_add(minutes.checked_mul(60)?)?
+        .checked_mul(sign)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date
+/// (Howard Hinnant's `days_from_civil`).
+// Inputs are range-checked below, so none of the arithmetic can overflow.
+#[allow(clippy::arithmetic_side_effects)]
+fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || !(1..=9999).contains(&year) {
+        return None;
+    }
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = i64::from(month);
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe - 719_468)
+}
+
+/// `YYYY-MM-DD` of Unix seconds `secs` (inverse of [`days_from_civil`]).
+// Day counts of a u32 timestamp are far from any overflow.
+#[allow(clippy::arithmetic_side_effects)]
+fn civil_date(secs: u32) -> String {
+    let z = i64::from(secs) / 86_400 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// `itunes:duration` as seconds: `SS`, `MM:SS` or `HH:MM:SS`.
+pub(crate) fn parse_duration(text: &str) -> Option<u32> {
+    text.trim().split(':').try_fold(0u32, |total, part| {
+        total
+            .checked_mul(60)?
+            .checked_add(part.trim().parse::<u32>().ok()?)
+    })
+}
+
+/// Make `name` safe as a FAT32 file name component.
+pub(crate) fn sanitize(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let words: Vec<&str> = cleaned.split_whitespace().collect();
+    let joined = words.join(" ");
+    // FAT32 rejects trailing dots; keep well under the 255-char limit.
+    joined
+        .trim_end_matches('.')
+        .chars()
+        .take(120)
+        .collect::<String>()
+        .trim_end()
+        .to_owned()
+}
+
+/// `{YYYY-MM-DD} - {Title}.{ext}`, with the extension taken from the
+/// enclosure URL (`mp3` when it has none).
+pub(crate) fn episode_file_name(item: &FeedItem) -> String {
+    let path = item.url.split(['?', '#']).next().unwrap_or_default();
+    let file = path.rsplit('/').next().unwrap_or_default();
+    let ext = file
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .filter(|ext| {
+            !ext.is_empty() && ext.len() <= 4 && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+        .unwrap_or_else(|| "mp3".to_owned());
+    format!(
+        "{} - {}.{ext}",
+        civil_date(item.published),
+        sanitize(&item.title)
+    )
+}
+
+/// The `.episode` sidecar for `item` (see `library::podcast`).
+pub(crate) fn episode_sidecar(show: &str, item: &FeedItem) -> String {
+    let line = |value: &str| value.replace(['\n', '\r'], " ");
+    format!(
+        "podcast={}\ntitle={}\nguid={}\npublished={}\nduration={}\n",
+        line(show),
+        line(&item.title),
+        line(&item.guid),
+        item.published,
+        item.duration_secs,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use library::podcast::Episode;
+
+    const FEED: &str = r#"<?xml version="1.0"?>
+<rss xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd" version="2.0">
+<channel>
+  <title>Tom &amp; Jerry's Show</title>
+  <item>
+    <title><![CDATA[Ep 2: Cats & Dogs]]></title>
+    <guid isPermaLink="false">tj-2</guid>
+    <pubDate>Tue, 02 Jan 2024 10:30:00 +0000</pubDate>
+    <itunes:duration>1:02:03</itunes:duration>
+    <enclosure url="https://cdn.example.com/ep2.m4a?x=1&amp;y=2" length="1" type="audio/mp4"/>
+  </item>
+  <item>
+    <title>Blog post</title>
+  </item>
+  <item>
+    <title>Ep 1</title>
+    <pubDate>Sun, 31 Dec 2023 23:00:00 -0500</pubDate>
+    <enclosure type="audio/mpeg" url='https://cdn.example.com/ep1'/>
+  </item>
+</channel>
+</rss>"#;
+
+    #[test]
+    fn parses_feeds_file() {
+        let feeds =
+            parse_feeds("# mine\n\nNews = https://a.example/rss\n https://b.example/feed \n");
+        assert_eq!(
+            feeds,
+            vec![
+                Feed {
+                    name: Some("News".to_owned()),
+                    url: "https://a.example/rss".to_owned(),
+                },
+                Feed {
+                    name: None,
+                    url: "https://b.example/feed".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_rss_items_with_enclosures() {
+        let (channel, items) = parse_rss(FEED);
+        assert_eq!(channel, "Tom & Jerry's Show");
+        assert_eq!(items.len(), 2, "items without an enclosure are skipped");
+
+        let ep2 = items.first().unwrap();
+        assert_eq!(ep2.title, "Ep 2: Cats & Dogs");
+        assert_eq!(ep2.guid, "tj-2");
+        assert_eq!(ep2.published, 1_704_191_400);
+        assert_eq!(ep2.duration_secs, 3_723);
+        assert_eq!(ep2.url, "https://cdn.example.com/ep2.m4a?x=1&y=2");
+
+        let ep1 = items.get(1).unwrap();
+        assert_eq!(
+            ep1.guid, "https://cdn.example.com/ep1",
+            "guid falls back to URL"
+        );
+        assert_eq!(ep1.published, 1_704_081_600, "offset applied");
+        assert_eq!(ep1.duration_secs, 0);
+    }
+
+    #[test]
+    fn parses_dates_and_durations() {
+        assert_eq!(parse_rfc822("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+        assert_eq!(parse_rfc822("29 Feb 2024 12:00 +0100"), Some(1_709_204_400));
+        assert_eq!(parse_rfc822("yesterday"), None);
+        assert_eq!(civil_date(1_709_204_400), "2024-02-29");
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(parse_duration("95"), Some(95));
+        assert_eq!(parse_duration("01:35"), Some(95));
+        assert_eq!(parse_duration("1h"), None);
+    }
+
+    #[test]
+    fn builds_safe_file_names() {
+        let (_, items) = parse_rss(FEED);
+        assert_eq!(
+            episode_file_name(items.first().unwrap()),
+            "2024-01-02 - Ep 2- Cats & Dogs.m4a"
+        );
+        assert_eq!(
+            episode_file_name(items.get(1).unwrap()),
+            "2024-01-01 - Ep 1.mp3"
+        );
+        assert_eq!(sanitize("  A/B:\tC...  "), "A-B- C");
+    }
+
+    #[test]
+    fn sidecar_round_trips_through_library() {
+        let (channel, items) = parse_rss(FEED);
+        let item = items.first().unwrap();
+        let episode = Episode::parse(&episode_sidecar(&channel, item)).unwrap();
+        assert_eq!(episode.podcast.as_str(), "Tom & Jerry's Show");
+        assert_eq!(episode.title.as_str(), item.title);
+        assert_eq!(episode.guid.as_str(), "tj-2");
+        assert_eq!(episode.published, item.published);
+        assert_eq!(episode.duration_secs, 3_723);
+    }
+}
@@ -2,15 +2,34 @@
 //!
 //! Metadata is inferred from folder structure: `{Artist}/{Album}/{NN} - {Title}.{ext}`
 //! No tag parsing — if you need accurate metadata, use Soul Player export instead.
+//!
+//! Lyrics are associated by name (`01 - Song.flac` → `01 - Song.lrc`); the
+//! firmware finds them from `TrackMeta::file_path` with
+//! `library::Scanner::lyrics_path_for`, so the scan only reports them.
+//!
+//! Each track the host can decode is measured (see [`crate::loudness`]) and
+//! its ReplayGain values stored in `TrackMeta::loudness`, so the device
+//! normalises playback without analysing audio itself.  Album gain covers
+//! the measured tracks of each `{Artist}/{Album}` folder.
+//!
+//! Tag corrections made on the device (`tags.bin` in `soul_root`, see
+//! [`library::TagOverrides`]) are applied on top of the inferred metadata,
+//! matched by [`FileHash`], so they survive a rescan and follow renamed
+//! files.
 
+use std::collections::HashMap;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use library::binary::{sort_key_for, TrackMeta};
+use library::tag_overrides::{FileHash, TagOverrides, HASH_SPAN};
 use library::writer::LibraryWriter;
 use walkdir::WalkDir;
 
-const AUDIO_EXTENSIONS: &[&str] = &["flac", "mp3", "wav", "aiff", "ogg", "opus", "m4a"];
+use crate::loudness::{self, Measurement};
+
+const AUDIO_EXTENSIONS: &[&str] = &["flac", "mp3", "wav", "aiff", "ogg", "opus", "m4a", "m4b"];
 
 /// Entry point called from main.rs
 pub fn run(music_dir: &Path, soul_root: &Path) -> Result<()> {
@@ -21,9 +40,15 @@ pub fn run(music_dir: &Path, soul_root: &Path) -> Result<()> {
 /// Scan `music_dir` and write binary library to `soul_root`.
 pub(crate) fn run_scan(music_dir: &Path, soul_root: &Path) -> Result<()> {
     let entries = scan_audio_files(music_dir)?;
-    println!("Found {} audio files", entries.len());
+    let with_lyrics = entries.iter().filter(|p| find_lyrics(p).is_some()).count();
+    println!(
+        "Found {} audio files ({with_lyrics} with lyrics)",
+        entries.len()
+    );
 
-    let mut metas: Vec<([u8; 16], TrackMeta)> = entries
+    let overrides = load_tag_overrides(soul_root)?;
+    let mut corrected = 0usize;
+    let mut metas: Vec<([u8; 16], TrackMeta, Option<Measurement>)> = entries
         .iter()
         .enumerate()
         .filter_map(|(i, path)| {
@@ -32,25 +57,46 @@ pub(crate) fn run_scan(music_dir: &Path, soul_root: &Path) -> Result<()> {
             let soul_id = (i as u32).saturating_add(1);
             // TODO: derive album_id from artist+album hash once multi-album grouping is needed.
             // Currently all tracks share album_id=1, making album_count in the manifest always 1.
-            let meta = infer_meta_from_path(path, soul_id, 1).ok()?;
+            let mut meta = infer_meta_from_path(path, soul_id, 1).ok()?;
+            if !overrides.is_empty() {
+                match file_hash(path) {
+                    Ok(hash) if overrides.apply(hash, &mut meta) => {
+                        corrected = corrected.saturating_add(1);
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Tag overrides: {e:#}"),
+                }
+            }
             let key = sort_key_for(
                 meta.artist.as_str(),
                 meta.album.as_str(),
                 meta.track_number,
                 meta.disc_number,
             );
-            Some((key, meta))
+            let measurement = match loudness::measure_file(path) {
+                Ok(m) => m,
+                Err(e) => {
+                    eprintln!("Loudness: {e:#}");
+                    None
+                }
+            };
+            Some((key, meta, measurement))
         })
         .collect();
 
-    metas.sort_by_key(|(k, _)| *k);
+    if !overrides.is_empty() {
+        println!("Applied tag corrections to {corrected} tracks");
+    }
+    metas.sort_by_key(|(k, _, _)| *k);
+    let measured = apply_loudness(&mut metas);
+    println!("Measured loudness of {measured} of {} tracks", metas.len());
 
     let root_str = soul_root
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("invalid soul_root path"))?;
     let mut writer = LibraryWriter::new(root_str)?;
     let mut album_ids = std::collections::HashSet::new();
-    for (key, meta) in metas {
+    for (key, meta, _) in metas {
         album_ids.insert(meta.album_id);
         writer.add_track(key, meta)?;
     }
@@ -63,6 +109,65 @@ pub(crate) fn run_scan(music_dir: &Path, soul_root: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Fill `TrackMeta::loudness` from each track's measurement, with album
+/// gain over the measured tracks sharing its artist and album.  Returns the
+/// number of tracks given loudness (silent tracks measure as `None`).
+pub(crate) fn apply_loudness(metas: &mut [([u8; 16], TrackMeta, Option<Measurement>)]) -> usize {
+    let mut albums: HashMap<(&str, &str), Vec<&Measurement>> = HashMap::new();
+    for (_, meta, measurement) in metas.iter() {
+        if let Some(m) = measurement {
+            albums
+                .entry((meta.artist.as_str(), meta.album.as_str()))
+                .or_default()
+                .push(m);
+        }
+    }
+    let album_lufs: HashMap<(String, String), f64> = albums
+        .into_iter()
+        .filter_map(|((artist, album), tracks)| {
+            let lufs = loudness::album_lufs(&tracks)?;
+            Some(((artist.to_owned(), album.to_owned()), lufs))
+        })
+        .collect();
+
+    let mut measured = 0usize;
+    for (_, meta, measurement) in metas.iter_mut() {
+        let Some(m) = measurement else {
+            continue;
+        };
+        let Some(track_lufs) = m.integrated_lufs() else {
+            continue;
+        };
+        let key = (meta.artist.to_string(), meta.album.to_string());
+        let album = album_lufs.get(&key).copied().unwrap_or(track_lufs);
+        meta.loudness = Some(loudness::replay_gain(track_lufs, album, m.peak));
+        measured = measured.saturating_add(1);
+    }
+    measured
+}
+
+/// Read the device's tag corrections from `soul_root/tags.bin`; an empty
+/// table when there is none yet.
+pub(crate) fn load_tag_overrides(soul_root: &Path) -> Result<TagOverrides> {
+    let path = soul_root.join("tags.bin");
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            TagOverrides::decode(&bytes).map_err(|e| anyhow::anyhow!("{}: {e:?}", path.display()))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(TagOverrides::new()),
+        Err(e) => Err(anyhow::anyhow!("{}: {e}", path.display())),
+    }
+}
+
+/// [`FileHash`] of the file at `path`, as the device computes it.
+pub(crate) fn file_hash(path: &Path) -> Result<FileHash> {
+    let file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut head = Vec::new();
+    file.take(HASH_SPAN).read_to_end(&mut head)?;
+    Ok(FileHash::from_head(size, &head))
+}
+
 /// Recursively collect all audio file paths under `dir`.
 pub(crate) fn scan_audio_files(dir: &Path) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
@@ -83,6 +188,14 @@ pub(crate) fn scan_audio_files(dir: &Path) -> Result<Vec<PathBuf>> {
     Ok(files)
 }
 
+/// The `.lrc` file next to `track`, matching the extension in any case.
+pub(crate) fn find_lyrics(track: &Path) -> Option<PathBuf> {
+    ["lrc", "LRC"]
+        .iter()
+        .map(|ext| track.with_extension(ext))
+        .find(|p| p.is_file())
+}
+
 /// Infer `TrackMeta` from file path components.
 ///
 /// Expected structure: `{Artist}/{Album}/{NN} - {Title}.{ext}`
@@ -111,6 +224,8 @@ pub(crate) fn infer_meta_from_path(path: &Path, soul_id: u32, album_id: u32) ->
         "flac" | "aiff" => 0,
         "mp3" => 1,
         "wav" => 2,
+        "m4a" | "m4b" => 3,
+        "opus" => 4,
         _ => 0,
     };
 
@@ -128,6 +243,7 @@ pub(crate) fn infer_meta_from_path(path: &Path, soul_id: u32, album_id: u32) ->
         artist: heapless::String::try_from(artist).unwrap_or_default(),
         album: heapless::String::try_from(album).unwrap_or_default(),
         file_path: heapless::String::try_from(path.to_str().unwrap_or("")).unwrap_or_default(),
+        loudness: None,
     })
 }
 
@@ -199,6 +315,19 @@ mod tests {
         assert_eq!(entries.len(), 3);
     }
 
+    #[test]
+    fn scan_associates_lyrics_by_track_name() {
+        let tmp = TempDir::new().unwrap();
+        create_fake_library(&tmp);
+        let album = tmp.path().join("Amon Tobin").join("Foley Room");
+        fs::write(album.join("02 - Kitchen Sink.lrc"), b"[00:01.00]x").unwrap();
+
+        let entries = scan_audio_files(tmp.path()).unwrap();
+        assert_eq!(entries.len(), 3, ".lrc files are not tracks");
+        let lyrics: Vec<_> = entries.iter().filter_map(|p| find_lyrics(p)).collect();
+        assert_eq!(lyrics, [album.join("02 - Kitchen Sink.lrc")]);
+    }
+
     #[test]
     fn infer_meta_parses_track_num_from_filename() {
         let path = std::path::Path::new("/soul/Amon Tobin/Foley Room/02 - Kitchen Sink.flac");
@@ -226,6 +355,61 @@ mod tests {
         assert!(dst.path().join("manifest.bin").exists());
         assert!(dst.path().join("library.idx").exists());
         assert!(dst.path().join("library.meta").exists());
+        assert!(dst.path().join("library.browse").exists());
+    }
+
+    #[test]
+    fn apply_loudness_shares_album_gain_across_measured_tracks() {
+        let tone = |dbfs: f64| {
+            let amplitude = 10f64.powf(dbfs / 20.0);
+            let mut meter = loudness::Meter::new(48_000, 2);
+            for i in 0..240_000u32 {
+                let x = amplitude * (f64::from(i) * 0.13).sin();
+                meter.push_frame(&[x, x]);
+            }
+            Some(meter.finish())
+        };
+        let track = |name: &str, measurement| {
+            let path = format!("/m/Portishead/Dummy/{name}");
+            let meta = infer_meta_from_path(Path::new(&path), 1, 1).unwrap();
+            (sort_key_for("Portishead", "Dummy", 1, 1), meta, measurement)
+        };
+        let mut metas = vec![
+            track("01 - Mysterons.wav", tone(-20.0)),
+            track("02 - Sour Times.mp3", tone(-24.0)),
+            track("03 - Strangers.flac", None),
+        ];
+        assert_eq!(apply_loudness(&mut metas), 2);
+
+        let [a, b] = [0, 1].map(|i| metas[i].1.loudness.unwrap());
+        assert_eq!(a.album_gain_cdb, b.album_gain_cdb);
+        assert!((i32::from(b.track_gain_cdb) - i32::from(a.track_gain_cdb) - 400).abs() <= 5);
+        assert!(a.track_peak > b.track_peak);
+        assert_eq!(metas[2].1.loudness, None);
+    }
+
+    #[test]
+    fn scan_applies_tag_corrections_from_the_device() {
+        use library::tag_overrides::TagField;
+        let src = TempDir::new().unwrap();
+        let dst = TempDir::new().unwrap();
+        create_fake_library(&src);
+        let track = src.path().join("Portishead/Dummy/01 - Mysterons.flac");
+        fs::write(&track, b"fLaC mysterons").unwrap();
+
+        let mut table: TagOverrides = TagOverrides::new();
+        table
+            .set(file_hash(&track).unwrap(), TagField::Title, "Mysterons (Live)")
+            .unwrap();
+        let mut buf = [0u8; 256];
+        let bytes = table.encode(&mut buf).unwrap();
+        fs::write(dst.path().join("tags.bin"), bytes).unwrap();
+        run_scan(src.path(), dst.path()).unwrap();
+
+        let meta = fs::read(dst.path().join("library.meta")).unwrap();
+        let contains = |s: &str| meta.windows(s.len()).any(|w| w == s.as_bytes());
+        assert!(contains("Mysterons (Live)"));
+        assert!(contains("Kitchen Sink"), "uncorrected tracks keep their tags");
     }
 
     #[test]
@@ -0,0 +1,151 @@
+//! `cargo xtask screens` — render every screen in the firmware-ui gallery on
+//! a headless emulator and export a PNG set for design review.
+//!
+//! Each entry of `firmware_ui::gallery::SCREENS` becomes `<id>.png` plus its
+//! JSON sidecar (display info and component bounds), and `index.html` lays
+//! them out side by side with their component ids.  Nothing written here is
+//! compared against anything; the golden tests own regressions.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use colored::Colorize;
+use eink_emulator::sidecar::ComponentEntry;
+use firmware_ui::gallery::{self, GalleryScreen};
+
+pub fn run(out_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+
+    println!();
+    println!("{}", "🖼  Rendering screen gallery...".cyan().bold());
+    println!();
+
+    let mut exported = Vec::new();
+    for screen in gallery::SCREENS {
+        let components = export(screen, out_dir)?;
+        println!(
+            "  {} {:<28} {:>2} components",
+            "✓".green(),
+            format!("{}.png", screen.id),
+            components.len(),
+        );
+        exported.push((screen, components));
+    }
+
+    let index = out_dir.join("index.html");
+    std::fs::write(&index, index_html(&exported))
+        .with_context(|| format!("Failed to write {}", index.display()))?;
+
+    println!();
+    println!(
+        "   {}",
+        format!("Open {} to review", index.display()).dimmed()
+    );
+    println!();
+    Ok(())
+}
+
+/// Render one screen and write its PNG and sidecar; returns the components
+/// it registered.
+fn export(screen: &GalleryScreen, out_dir: &Path) -> Result<Vec<ComponentEntry>> {
+    let mut display = gallery::emulator();
+    let mut components = Vec::new();
+    let Ok(()) = (screen.render)(&mut display, &mut |id, ty, position, size| {
+        components.push(ComponentEntry {
+            test_id: Some(id.to_owned()),
+            component_type: ty.to_owned(),
+            position,
+            size,
+        });
+    });
+
+    let png = png_path(out_dir, screen);
+    display
+        .screenshot(&png)
+        .map_err(|e| anyhow!("Failed to write {}: {e}", png.display()))?;
+    let mut meta = display.screenshot_metadata();
+    meta.components.clone_from(&components);
+    let sidecar = eink_emulator::sidecar::sidecar_path(&png);
+    meta.save(&sidecar)
+        .map_err(|e| anyhow!("Failed to write {}: {e}", sidecar.display()))?;
+    Ok(components)
+}
+
+fn png_path(out_dir: &Path, screen: &GalleryScreen) -> PathBuf {
+    out_dir.join(format!("{}.png", screen.id))
+}
+
+fn index_html(screens: &[(&GalleryScreen, Vec<ComponentEntry>)]) -> String {
+    let spec = &gallery::PANEL;
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>SoulAudio screens</title>\n<style>\n\
+         body { font-family: sans-serif; background: #eee; margin: 2em; }\n\
+         main { display: flex; flex-wrap: wrap; gap: 2em; }\n\
+         figure { margin: 0; background: #fff; padding: 1em; }\n\
+         img { border: 1px solid #999; image-rendering: pixelated; }\n\
+         ul { font: 12px monospace; color: #555; padding-left: 1.2em; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    let _ = writeln!(
+        html,
+        "<h1>SoulAudio screens</h1>\n<p>{} &middot; {}&times;{}</p>\n<main>",
+        escape(spec.name),
+        spec.width,
+        spec.height,
+    );
+    for (screen, components) in screens {
+        let _ = writeln!(
+            html,
+            "<figure id=\"{id}\">\n<figcaption><a href=\"#{id}\">{title}</a></figcaption>\n\
+             <img src=\"{id}.png\" width=\"{w}\" height=\"{h}\" alt=\"{title}\">\n<ul>",
+            id = screen.id,
+            title = escape(screen.title),
+            w = spec.width,
+            h = spec.height,
+        );
+        for component in components {
+            let _ = writeln!(
+                html,
+                "<li>{} <em>{}</em></li>",
+                escape(component.test_id.as_deref().unwrap_or("")),
+                escape(&component.component_type),
+            );
+        }
+        html.push_str("</ul>\n</figure>\n");
+    }
+    html.push_str("</main>\n</body>\n</html>\n");
+    html
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_a_png_per_screen_and_an_index() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        run(tmp.path()).unwrap();
+        let index = std::fs::read_to_string(tmp.path().join("index.html")).unwrap();
+        for screen in gallery::SCREENS {
+            let png = png_path(tmp.path(), screen);
+            assert!(png.is_file());
+            assert!(eink_emulator::sidecar::sidecar_path(&png).is_file());
+            assert!(index.contains(&format!("src=\"{}.png\"", screen.id)));
+        }
+    }
+
+    #[test]
+    fn escapes_markup_in_captions() {
+        assert_eq!(escape("<a & \"b\">"), "&lt;a &amp; &quot;b&quot;&gt;");
+    }
+}
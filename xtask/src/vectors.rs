@@ -0,0 +1,71 @@
+//! `cargo run -p xtask -- vectors` — write the playback conformance vectors
+//! to disk for inspection with external tools (`flac -t`, `sox --i`, a
+//! player) and print their expected PCM hashes.
+//!
+//! The vectors themselves live in `playback::test_vectors`; the tests build
+//! them in memory, so nothing written here needs to be committed.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use playback::test_vectors::{self, pcm_hash};
+
+pub fn run(out_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+
+    println!();
+    println!("{}", "🎵 Writing conformance vectors...".cyan().bold());
+    println!();
+
+    let mut manifest = String::from("# <name> <CRC-32 of decoded PCM, hex>\n");
+    for vector in test_vectors::all() {
+        let path = out_dir.join(vector.file_name());
+        std::fs::write(&path, &vector.bytes)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        let hash = pcm_hash(&vector.pcm);
+        manifest.push_str(&format!("{:<31} {hash:08x}\n", vector.name));
+        println!(
+            "  {} {:<32} {:>2} ch {:>2}-bit {:>6} Hz {:>6} frames  {hash:08x}",
+            "✓".green(),
+            vector.file_name(),
+            vector.channels,
+            vector.bits_per_sample,
+            vector.sample_rate,
+            vector.frames(),
+        );
+    }
+
+    let manifest_path = out_dir.join("manifest.txt");
+    std::fs::write(&manifest_path, manifest)
+        .with_context(|| format!("Failed to write {}", manifest_path.display()))?;
+
+    println!();
+    println!(
+        "   {}",
+        format!(
+            "Compare {} with crates/playback/tests/vectors/manifest.txt",
+            manifest_path.display()
+        )
+        .dimmed()
+    );
+    println!();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_every_vector_and_manifest() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        run(tmp.path()).unwrap();
+        let manifest = std::fs::read_to_string(tmp.path().join("manifest.txt")).unwrap();
+        for vector in test_vectors::all() {
+            assert!(tmp.path().join(vector.file_name()).is_file());
+            assert!(manifest.contains(vector.name));
+        }
+    }
+}